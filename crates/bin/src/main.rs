@@ -23,9 +23,31 @@
 //! ```bash
 //! rust-daq daemon --port 50051
 //! ```
+//!
+//! Serve synthetic data for GUI development:
+//! ```bash
+//! rust-daq daemon --simulate-data --sim-frame-rate 20
+//! ```
+//!
+//! Verify a signed run file:
+//! ```bash
+//! rust-daq verify data/<run_uid>_<time>.h5 --public-key run_signing.key.pub
+//! ```
 
 // Global allocator (Microsoft Rust Guidelines: M-MIMALLOC-APPS)
 // Use mimalloc for improved allocation performance in multi-threaded DAQ scenarios
+// The minimal profile must build without vendor SDKs or native storage libraries
+#[cfg(all(
+    feature = "minimal",
+    any(
+        feature = "storage_hdf5",
+        feature = "storage_arrow",
+        feature = "pvcam_sdk",
+        feature = "comedi_hardware"
+    )
+))]
+compile_error!("the `minimal` profile excludes HDF5, Arrow, PVCAM SDK and Comedi hardware support");
+
 #[cfg(not(test))]
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
@@ -40,7 +62,7 @@ use tokio::signal;
 
 use protocol::daq::*;
 #[cfg(feature = "networking")]
-use server::grpc::start_server_with_hardware;
+use server::grpc::ServerOptions;
 #[cfg(feature = "networking")]
 use std::collections::HashMap;
 
@@ -79,6 +101,69 @@ enum Commands {
         /// Mutually exclusive with --hardware-config
         #[arg(long, conflicts_with = "hardware_config")]
         lab_hardware: bool,
+
+        /// Register every configured device with its simulated backend,
+        /// as if each had `simulated = true`
+        #[arg(long, requires = "hardware_config")]
+        simulate_all: bool,
+
+        /// Do not recreate the module instances saved by the previous run
+        #[arg(long)]
+        no_restore: bool,
+
+        /// Hours of device parameter and setpoint history to keep
+        #[arg(long, default_value = "24")]
+        history_retention_hours: u64,
+
+        /// Hours of on-disk scalar channel history to keep (0 disables it)
+        #[arg(long, default_value = "24")]
+        channel_history_hours: u64,
+
+        /// Sign completed run files with the Ed25519 key in this file
+        /// (created on first use, public key written to <file>.pub)
+        #[arg(long, value_name = "KEY_FILE")]
+        sign_runs: Option<PathBuf>,
+
+        /// Directory of the script, plan and device config library
+        /// (default: <data dir>/rust-daq/library)
+        #[arg(long, value_name = "DIR")]
+        library_dir: Option<PathBuf>,
+
+        /// Offer the WASM component modules in this directory as module
+        /// types (requires the wasm_plugins feature)
+        #[arg(long, value_name = "DIR")]
+        wasm_plugins: Option<PathBuf>,
+
+        #[command(flatten)]
+        simulate: SimulateDataArgs,
+    },
+
+    /// Verify the provenance signature of a run file
+    Verify {
+        /// HDF5 run file
+        file: PathBuf,
+
+        /// Trusted signer: hex public key or a .pub file (repeatable).
+        /// Without one, the file is only checked against its own embedded key
+        #[arg(long = "public-key")]
+        public_keys: Vec<String>,
+    },
+
+    /// Sign an access token with the daemon's grpc.auth_token
+    ///
+    /// Clients present it through RUSTDAQ_TOKEN. Give unattended jobs only
+    /// the scopes they need, e.g. `--scope scripts:run:nightly_calibration`
+    /// for a scheduled script.
+    #[cfg(feature = "networking")]
+    Token {
+        /// User the token identifies (recorded in the audit log)
+        user: String,
+        /// Granted scope (repeatable); none grants full access
+        #[arg(long = "scope")]
+        scopes: Vec<String>,
+        /// Hours until the token expires
+        #[arg(long, default_value = "24")]
+        hours: u64,
     },
 
     /// Remote control commands (connect to daemon)
@@ -87,6 +172,44 @@ enum Commands {
     Client(ClientCommands),
 }
 
+/// Synthetic data served instead of devices
+#[derive(clap::Args)]
+struct SimulateDataArgs {
+    /// Serve synthetic data (noisy sine channels, a drifting beam camera and
+    /// periodic scans) instead of devices, for developing clients
+    #[arg(long, conflicts_with_all = ["hardware_config", "lab_hardware"])]
+    simulate_data: bool,
+
+    /// Number of simulated sine channels
+    #[arg(long, default_value = "4", requires = "simulate_data")]
+    sim_signals: usize,
+
+    /// Frequency of the first sine channel in Hz
+    #[arg(long, default_value = "0.5", requires = "simulate_data")]
+    sim_signal_frequency: f64,
+
+    /// Simulated camera frame rate in Hz (at most 30)
+    #[arg(long, default_value = "10", requires = "simulate_data")]
+    sim_frame_rate: f64,
+
+    /// Seconds between simulated scans (0 disables them)
+    #[arg(long, default_value = "30", requires = "simulate_data")]
+    sim_scan_interval: f64,
+}
+
+impl SimulateDataArgs {
+    fn config(&self) -> Option<server::simulator::SimulatorConfig> {
+        self.simulate_data
+            .then(|| server::simulator::SimulatorConfig {
+                signals: self.sim_signals,
+                signal_frequency_hz: self.sim_signal_frequency,
+                frame_rate_hz: self.sim_frame_rate,
+                scan_interval_s: self.sim_scan_interval,
+                ..Default::default()
+            })
+    }
+}
+
 #[cfg(feature = "networking")]
 #[derive(Subcommand)]
 enum ClientCommands {
@@ -152,6 +275,21 @@ enum ClientCommands {
         #[arg(long, default_value = "http://localhost:50051")]
         addr: String,
     },
+
+    /// Apply a daemon configuration (devices, modules, storage) as a diff
+    ///
+    /// Only the differences to the running configuration are applied. If any
+    /// change fails, the ones already applied are rolled back.
+    ApplyConfig {
+        /// Configuration file (TOML); omit to print the current configuration
+        file: Option<PathBuf>,
+        /// Show the planned changes without applying them
+        #[arg(long)]
+        dry_run: bool,
+        /// Daemon address
+        #[arg(long, default_value = "http://localhost:50051")]
+        addr: String,
+    },
 }
 
 #[tokio::main]
@@ -163,9 +301,19 @@ async fn main() -> Result<()> {
     #[cfg(not(feature = "networking"))]
     println!("DEBUG: Feature networking DISABLED");
     // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .init();
+    use tracing_subscriber::prelude::*;
+    let registry = tracing_subscriber::registry().with(
+        tracing_subscriber::fmt::layer()
+            .with_filter(tracing_subscriber::EnvFilter::from_default_env()),
+    );
+    // Remote log streaming (LogService) gets DEBUG and above regardless of RUST_LOG
+    #[cfg(feature = "networking")]
+    let registry = registry.with(
+        server::grpc::LogBroadcaster::global()
+            .layer()
+            .with_filter(tracing_subscriber::filter::LevelFilter::DEBUG),
+    );
+    registry.init();
 
     println!();
 
@@ -177,7 +325,43 @@ async fn main() -> Result<()> {
             port,
             hardware_config,
             lab_hardware,
-        } => start_daemon(port, hardware_config, lab_hardware).await,
+            simulate_all,
+            no_restore,
+            history_retention_hours,
+            channel_history_hours,
+            sign_runs,
+            library_dir,
+            wasm_plugins,
+            simulate,
+        } => {
+            start_daemon(
+                port,
+                hardware_config,
+                lab_hardware,
+                simulate_all,
+                no_restore,
+                history_retention_hours,
+                channel_history_hours,
+                sign_runs,
+                library_dir,
+                wasm_plugins,
+                simulate.config(),
+            )
+            .await
+        }
+        Commands::Verify { file, public_keys } => verify_run_file(&file, &public_keys),
+        #[cfg(feature = "networking")]
+        Commands::Token {
+            user,
+            scopes,
+            hours,
+        } => {
+            let ttl = std::time::Duration::from_secs(hours * 3600);
+            let token = server::grpc::issue_auth_token(&user, &scopes, ttl)
+                .map_err(|e| anyhow::anyhow!("{}", e))?;
+            println!("{}", token);
+            Ok(())
+        }
         #[cfg(feature = "networking")]
         Commands::Client(cmd) => handle_client_command(cmd).await,
     }
@@ -239,29 +423,25 @@ async fn run_script_once(script_path: PathBuf, config: Option<PathBuf>) -> Resul
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn start_daemon(
     port: u16,
     hardware_config: Option<PathBuf>,
     lab_hardware: bool,
+    simulate_all: bool,
+    no_restore: bool,
+    history_retention_hours: u64,
+    channel_history_hours: u64,
+    sign_runs: Option<PathBuf>,
+    library_dir: Option<PathBuf>,
+    wasm_plugins: Option<PathBuf>,
+    simulate_data: Option<server::simulator::SimulatorConfig>,
 ) -> Result<()> {
-    use server::health::sys_monitor::SystemMetricsCollector;
-    use server::health::{HealthMonitorConfig, SystemHealthMonitor};
-
     println!("🌐 Starting Headless DAQ Daemon");
     println!("   Architecture: V5 (Headless-First + Scriptable)");
     println!("   gRPC Port: {}", port);
     println!();
 
-    // Phase 5: Health Monitoring (bd-3ti1)
-    println!("❤️  Initializing health monitoring...");
-    let health_monitor = Arc::new(SystemHealthMonitor::new(HealthMonitorConfig::default()));
-
-    // Start system metrics collection
-    let metrics_collector = SystemMetricsCollector::new(health_monitor.clone());
-    tokio::spawn(async move {
-        metrics_collector.run().await;
-    });
-
     // Phase 4: Data Plane - Ring Buffer + HDF5 Writer (optional)
     #[cfg(all(feature = "storage_hdf5", feature = "storage_arrow"))]
     let (ring_buffer, writer_handle) = {
@@ -284,7 +464,9 @@ async fn start_daemon(
         let writer_clone = writer_arc.clone();
 
         let handle = tokio::spawn(async move {
-            writer_clone.run().await;
+            if let Err(e) = writer_clone.run_shared().await {
+                eprintln!("HDF5 writer stopped: {}", e);
+            }
         });
 
         println!("✅ Data plane ready");
@@ -299,34 +481,51 @@ async fn start_daemon(
     // Phase 3: Start gRPC server
     #[cfg(feature = "networking")]
     {
-        // use server::grpc::start_server_with_hardware; // Imported at top level
-        use rust_daq::hardware::registry::{
-            create_lab_registry, create_mock_registry, create_registry_from_file,
-            register_all_factories,
-        };
-        use std::sync::Arc;
+        // Registry, RunEngine, health monitoring and gRPC server (bd-3ti1)
+        use rust_daq::hardware::registry::HardwareConfig;
+        use server::runtime::DaqRuntimeBuilder;
 
         let addr = format!("0.0.0.0:{}", port).parse()?;
+        let server_options = ServerOptions {
+            restore_modules: !no_restore,
+            history_retention: std::time::Duration::from_secs(history_retention_hours * 3600),
+            channel_history_retention: std::time::Duration::from_secs(channel_history_hours * 3600),
+            run_signing_key: sign_runs,
+            library_dir: library_dir.unwrap_or_else(server::grpc::default_library_path),
+            plugin_module_types: wasm_module_types(wasm_plugins)?,
+            ..ServerOptions::default()
+        };
+        let builder = DaqRuntimeBuilder::new()
+            .bind(addr)
+            .server_options(server_options);
 
         // Create device registry based on configuration
         println!("🔧 Initializing hardware registry...");
-        let registry = if let Some(config_path) = hardware_config {
+        let builder = if let Some(config_path) = hardware_config {
             println!("   Loading from config: {}", config_path.display());
-            create_registry_from_file(&config_path).await?
+            let mut config = HardwareConfig::from_file(&config_path)?;
+            if simulate_all {
+                println!("   Simulating all devices (--simulate-all)");
+                config.simulate_all = true;
+            }
+            builder.hardware_config(config)
         } else if lab_hardware {
             println!("   Using lab hardware configuration (maitai@100.117.5.12)");
-            create_lab_registry().await?
+            builder.lab_hardware()
+        } else if let Some(simulator) = simulate_data {
+            println!(
+                "   Serving synthetic data (--simulate-data): {} signal(s), camera at {} Hz",
+                simulator.signals, simulator.frame_rate_hz
+            );
+            builder.simulated_data(simulator)
         } else {
             println!("   Using mock devices (no hardware config specified)");
-            create_mock_registry().await?
+            builder.mock_hardware()
         };
 
-        // Register driver factories for plugin-based device creation
-        // This enables using register_from_toml() for dynamic device registration
-        let config_dir = std::path::Path::new("config/devices");
-        if let Err(e) = register_all_factories(&registry, Some(config_dir)).await {
-            tracing::warn!("Failed to register some factories: {}", e);
-        }
+        let mut daemon = builder.start().await?;
+
+        let registry = daemon.registry();
         let factory_count = registry.list_factories().len();
         if factory_count > 0 {
             println!("   Registered {} driver factories", factory_count);
@@ -342,25 +541,6 @@ async fn start_daemon(
         }
         println!();
 
-        let registry = Arc::new(registry);
-
-        // Start registry monitoring
-        let mon_registry = registry.clone();
-        let mon_health = health_monitor.clone();
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(std::time::Duration::from_secs(10));
-            loop {
-                interval.tick().await;
-                let count = mon_registry.len();
-                mon_health
-                    .heartbeat_with_message(
-                        "hardware_registry",
-                        Some(format!("Managing {} devices", count)),
-                    )
-                    .await;
-            }
-        });
-
         println!("✅ gRPC server ready");
         println!("   Listening on: {}", addr);
         println!("   Features:");
@@ -383,11 +563,10 @@ async fn start_daemon(
         };
 
         // Race server against shutdown signal
-        let registry_for_server = registry.clone();
         tokio::select! {
-            result = start_server_with_hardware(addr, registry_for_server, health_monitor) => {
+            result = daemon.wait() => {
                 if let Err(e) = result {
-                    eprintln!("❌ gRPC server error: {}", e);
+                    eprintln!("❌ {}", e);
                 }
             }
             _ = shutdown_signal => {
@@ -395,8 +574,8 @@ async fn start_daemon(
             }
         }
 
-        if let Err(err) = registry.shutdown_all().await {
-            eprintln!("   Warning: device shutdown encountered errors: {}", err);
+        if let Err(err) = daemon.shutdown().await {
+            eprintln!("   Warning: {}", err);
         }
 
         // Perform cleanup
@@ -420,7 +599,18 @@ async fn start_daemon(
     #[cfg(not(feature = "networking"))]
     {
         // Silence unused variable warnings
-        let _ = (hardware_config, lab_hardware);
+        let _ = (
+            hardware_config,
+            lab_hardware,
+            simulate_all,
+            no_restore,
+            history_retention_hours,
+            channel_history_hours,
+            sign_runs,
+            library_dir,
+            wasm_plugins,
+            simulate_data,
+        );
 
         println!("⚠️  Networking feature not enabled - daemon mode requires 'networking' feature");
         println!("   Rebuild with: cargo build --features networking");
@@ -452,14 +642,91 @@ async fn start_daemon(
     }
 }
 
+/// Module types of the WASM components in `dir`, if one was given
+#[cfg(all(feature = "networking", feature = "wasm_plugins"))]
+fn wasm_module_types(dir: Option<PathBuf>) -> Result<Vec<server::modules::PluginModuleType>> {
+    let Some(dir) = dir else {
+        return Ok(Vec::new());
+    };
+    let mut loader = rust_daq::plugins::WasmPluginLoader::new()?;
+    loader.add_search_path(&dir);
+    let loaded = loader.discover()?;
+    println!(
+        "🧩 Loaded {} WASM module type(s) from {}",
+        loaded.len(),
+        dir.display()
+    );
+    Ok(loader.plugin_module_types())
+}
+
+#[cfg(all(feature = "networking", not(feature = "wasm_plugins")))]
+fn wasm_module_types(dir: Option<PathBuf>) -> Result<Vec<server::modules::PluginModuleType>> {
+    if dir.is_some() {
+        anyhow::bail!("--wasm-plugins needs a daemon built with the wasm_plugins feature");
+    }
+    Ok(Vec::new())
+}
+
+fn verify_run_file(file: &std::path::Path, public_keys: &[String]) -> Result<()> {
+    use common::provenance::parse_public_key;
+
+    let trusted = public_keys
+        .iter()
+        .map(|key| match std::fs::read_to_string(key) {
+            Ok(contents) => parse_public_key(&contents),
+            Err(_) => parse_public_key(key),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if trusted.is_empty() {
+        eprintln!("⚠️  No --public-key given: checking the file against its embedded key only");
+    }
+
+    let verification = storage::verify_run_file(file, &trusted)?;
+    println!("Run:       {}", verification.run_uid);
+    println!("Signed by: {}", verification.key_id);
+    println!(
+        "Signed at: {}",
+        chrono::DateTime::from_timestamp_nanos(verification.signed_at_ns as i64)
+    );
+    let problems = verification.problems();
+    if problems.is_empty() {
+        println!("✅ Signature valid, all datasets unchanged");
+        return Ok(());
+    }
+    for problem in &problems {
+        eprintln!("❌ {}", problem);
+    }
+    anyhow::bail!("{} failed verification", file.display())
+}
+
 #[cfg(feature = "networking")]
-async fn handle_client_command(cmd: ClientCommands) -> Result<()> {
-    use protocol::daq::control_service_client::ControlServiceClient;
+type ControlClient = protocol::daq::control_service_client::ControlServiceClient<
+    tonic::service::interceptor::InterceptedService<
+        tonic::transport::Channel,
+        server::auth::BearerToken,
+    >,
+>;
+
+/// Connect to the daemon's ControlService, presenting `RUSTDAQ_TOKEN` if set
+#[cfg(feature = "networking")]
+async fn control_client(addr: String) -> Result<ControlClient> {
+    let channel = tonic::transport::Endpoint::from_shared(addr)?
+        .connect()
+        .await?;
+    Ok(
+        protocol::daq::control_service_client::ControlServiceClient::with_interceptor(
+            channel,
+            server::auth::BearerToken::from_env()?,
+        ),
+    )
+}
 
+#[cfg(feature = "networking")]
+async fn handle_client_command(cmd: ClientCommands) -> Result<()> {
     match cmd {
         ClientCommands::Upload { script, name, addr } => {
             println!("📤 Uploading script to daemon at {}", addr);
-            let mut client = ControlServiceClient::connect(addr).await?;
+            let mut client = control_client(addr).await?;
             let content = tokio::fs::read_to_string(&script).await?;
 
             let response = client
@@ -485,7 +752,7 @@ async fn handle_client_command(cmd: ClientCommands) -> Result<()> {
 
         ClientCommands::Start { script_id, addr } => {
             println!("▶️  Starting script {} on daemon at {}", script_id, addr);
-            let mut client = ControlServiceClient::connect(addr).await?;
+            let mut client = control_client(addr).await?;
             let response = client
                 .start_script(StartRequest {
                     script_id,
@@ -511,7 +778,7 @@ async fn handle_client_command(cmd: ClientCommands) -> Result<()> {
                 "⏹️  Stopping execution {} on daemon at {}",
                 execution_id, addr
             );
-            let mut client = ControlServiceClient::connect(addr).await?;
+            let mut client = control_client(addr).await?;
             let response = client
                 .stop_script(StopRequest {
                     execution_id,
@@ -533,7 +800,7 @@ async fn handle_client_command(cmd: ClientCommands) -> Result<()> {
                 "📊 Checking status of execution {} on daemon at {}",
                 execution_id, addr
             );
-            let mut client = ControlServiceClient::connect(addr).await?;
+            let mut client = control_client(addr).await?;
             let response = client
                 .get_script_status(StatusRequest { execution_id })
                 .await?;
@@ -559,11 +826,12 @@ async fn handle_client_command(cmd: ClientCommands) -> Result<()> {
             println!("   Press Ctrl+C to stop");
             println!();
 
-            let mut client = ControlServiceClient::connect(addr).await?;
+            let mut client = control_client(addr).await?;
             let mut stream = client
                 .stream_measurements(MeasurementRequest {
                     channels,
                     max_rate_hz: 100,
+                    decimation: None,
                 })
                 .await?
                 .into_inner();
@@ -599,5 +867,57 @@ async fn handle_client_command(cmd: ClientCommands) -> Result<()> {
             println!("✅ Move command accepted");
             Ok(())
         }
+
+        ClientCommands::ApplyConfig {
+            file,
+            dry_run,
+            addr,
+        } => {
+            use protocol::daq::config_service_client::ConfigServiceClient;
+
+            let mut client = ConfigServiceClient::connect(addr).await?;
+            let Some(file) = file else {
+                let response = client.get_daemon_config(GetDaemonConfigRequest {}).await?;
+                print!("{}", response.into_inner().config_toml);
+                return Ok(());
+            };
+
+            let config_toml = tokio::fs::read_to_string(&file).await?;
+            let report = client
+                .apply_config(ApplyConfigRequest {
+                    config_toml,
+                    dry_run,
+                })
+                .await?
+                .into_inner();
+
+            if report.steps.is_empty() {
+                println!("✅ Configuration already up to date");
+                return Ok(());
+            }
+            for step in &report.steps {
+                let status = ConfigStepStatus::try_from(step.status)
+                    .map(|s| s.as_str_name())
+                    .unwrap_or("UNKNOWN");
+                println!(
+                    "   {:<28} {:<6} {:<7} {}",
+                    status, step.action, step.component, step.target
+                );
+                if !step.message.is_empty() {
+                    println!("      {}", step.message);
+                }
+            }
+            if report.dry_run {
+                println!("ℹ️  Dry run: {} change(s) planned", report.steps.len());
+            } else if report.success {
+                println!("✅ Applied {} change(s)", report.steps.len());
+            } else {
+                eprintln!("❌ Apply failed: {}", report.error_message);
+                if report.rolled_back {
+                    eprintln!("   Earlier changes were rolled back");
+                }
+            }
+            Ok(())
+        }
     }
 }
@@ -6,6 +6,7 @@ use anyhow::Result;
 use tonic::transport::Channel;
 
 use crate::connection::DaemonAddress;
+use crate::reconnect::ResyncSnapshot;
 
 /// gRPC channel configuration for connection reliability.
 ///
@@ -51,63 +52,154 @@ impl ChannelConfig {
     }
 }
 use protocol::daq::{
+    config_service_client::ConfigServiceClient,
     control_service_client::ControlServiceClient,
     hardware_service_client::HardwareServiceClient,
+    health_service_client::HealthServiceClient,
+    instrument_console_service_client::InstrumentConsoleServiceClient,
+    library_file_chunk::Payload as LibraryPayload,
+    library_service_client::LibraryServiceClient,
+    log_service_client::LogServiceClient,
     module_service_client::ModuleServiceClient,
+    restore_config_snapshot_request,
     run_engine_service_client::RunEngineServiceClient,
     scan_service_client::ScanServiceClient,
+    session_service_client::SessionServiceClient,
     storage_service_client::StorageServiceClient,
     // RunEngine control types
     AbortPlanRequest,
     AbortPlanResponse,
+    AbortRampRequest,
+    AbortWarmupRequest,
+    // Run marker types
+    AddRunMarkerRequest,
+    // Config apply types
+    ApplyConfigRequest,
     AssignDeviceRequest,
+    // Batch command types
+    BatchCommand,
+    BatchMode,
+    // Channel recording types
+    ChannelRecordingStatus,
+    // Session/presence types
+    CloseSessionRequest,
+    // Run comparison types
+    CompareRunsRequest,
+    ConfigApplyReport,
+    ConfigureModuleRequest,
     CreateModuleRequest,
     // Scan types
     CreateScanRequest,
     // Request/Response types
     DaemonInfoRequest,
+    // Script/plan/config library types
+    DeleteLibraryFileRequest,
+    // Client preference types
+    DeletePreferencesRequest,
+    DescribeCapabilitiesRequest,
     DeviceCommandRequest,
+    DeviceEvent,
+    DeviceEventSeverity,
+    DeviceLockRequest,
+    DeviceLockResponse,
     DeviceStateRequest,
+    DownloadLibraryFileRequest,
+    DryRunPlanRequest,
+    DryRunPlanResponse,
     EngineStatus,
+    ExecuteBatchRequest,
+    ExecuteBatchResponse,
     FrameData,
+    GetChannelRecordingRequest,
     // Laser control types (bd-pwjo)
+    GetDaemonConfigRequest,
     GetEmissionRequest,
     GetEngineStatusRequest,
+    GetInventoryRequest,
+    GetInventoryResponse,
+    GetModuleConfigRequest,
+    GetModuleTypeInfoRequest,
     GetParameterRequest,
+    GetPreferencesRequest,
     GetRecordingStatusRequest,
+    GetRunProgressRequest,
+    GetSelfTestReportRequest,
     GetShutterRequest,
     // Storage types
     GetStorageConfigRequest,
     GetWavelengthRequest,
+    LibraryFileChunk,
+    LibraryFileHeader,
+    LibraryFileInfo,
+    LibraryFileKind,
     ListAcquisitionsRequest,
     ListDevicesRequest,
     ListExecutionsRequest,
+    // Init recipe types
+    ListInitRecipesRequest,
+    ListLibraryFilesRequest,
     // Module types
     ListModuleTypesRequest,
     ListModulesRequest,
     // Parameter types (bd-cdh5.1)
     ListParametersRequest,
+    ListRampsRequest,
+    ListRunsRequest,
     ListScansRequest,
     ListScriptsRequest,
+    ListSerialPortsRequest,
+    ListSessionsRequest,
+    ListWarmupsRequest,
+    // Log streaming types
+    LogRecord,
     MoveRequest,
     ObservableValue,
+    OpenSessionRequest,
     PauseEngineRequest,
     PauseEngineResponse,
     PauseScanRequest,
+    Preference,
+    PreferenceScope,
+    PreferenceUpdate,
+    PresenceUpdate,
     QueuePlanRequest,
     QueuePlanResponse,
+    QueueTemplateRequest,
+    QueueTemplateResponse,
+    RampProfile,
+    RampStatus,
+    // Instrument console types
+    RawCommandRequest,
+    RawCommandResponse,
     ReadValueRequest,
+    RestoreConfigSnapshotRequest,
+    RestoreConfigSnapshotResponse,
     ResumeEngineRequest,
     ResumeEngineResponse,
     ResumeScanRequest,
+    RevokeSessionRequest,
+    RunComparison,
+    RunInitRecipeRequest,
+    RunMarker,
+    RunProgress,
+    RunSelfTestRequest,
     ScanConfig,
+    SelfTestReport,
+    SerialPortInfo,
+    SessionHeartbeatRequest,
+    SessionInfo,
+    SessionRole,
+    SetChannelRecordingRequest,
     SetEmissionRequest,
     SetParameterRequest,
+    SetPreferencesRequest,
+    SetRecordingDecimationRequest,
     SetShutterRequest,
     SetWavelengthRequest,
     StartEngineRequest,
     StartEngineResponse,
     StartModuleRequest,
+    StartRampRequest,
     StartRecordingRequest,
     // Script execution types (Phase 6: bd-uu9t)
     StartRequest as ScriptStartRequest,
@@ -116,19 +208,34 @@ use protocol::daq::{
     // Camera streaming with quality control
     StartStreamRequest,
     StopModuleRequest,
+    StopMotionRequest,
     StopRecordingRequest,
     StopRequest as ScriptStopRequest,
     StopResponse as ScriptStopResponse,
     StopScanRequest,
     StopStreamRequest,
+    StreamDeviceEventsRequest,
     StreamDocumentsRequest,
     StreamFramesRequest,
+    StreamLogsRequest,
+    StreamModuleEventsRequest,
     // Observable streaming (bd-qqjq stub for bd-r5vb)
     StreamObservablesRequest,
+    StreamPreferencesRequest,
+    StreamPresenceRequest,
     StreamQuality,
+    StreamRampEventsRequest,
+    SweepItem,
+    TimeSyncReport,
+    UploadLibraryFileResponse,
     UploadRequest as ScriptUploadRequest,
     UploadResponse as ScriptUploadResponse,
+    // Run provenance types
+    VerifyRunRequest,
+    VerifyRunResponse,
+    VerifyTimeSyncRequest,
 };
+use protocol::frame_tiles::TileSettings;
 
 /// gRPC client wrapper for the DAQ daemon
 #[derive(Clone)]
@@ -141,8 +248,22 @@ pub struct DaqClient {
     storage: StorageServiceClient<Channel>,
     module: ModuleServiceClient<Channel>,
     run_engine: RunEngineServiceClient<Channel>,
+    session: SessionServiceClient<Channel>,
+    /// Session client for the long-lived presence stream (no request timeout)
+    session_streaming: SessionServiceClient<Channel>,
+    config: ConfigServiceClient<Channel>,
+    /// Log client for the long-lived daemon log stream (no request timeout)
+    log_streaming: LogServiceClient<Channel>,
+    console: InstrumentConsoleServiceClient<Channel>,
+    library: LibraryServiceClient<Channel>,
+    health: HealthServiceClient<Channel>,
+    /// Health client for the long-lived state transition stream (no request timeout)
+    health_streaming: HealthServiceClient<Channel>,
 }
 
+/// Content bytes per message when uploading library files
+const LIBRARY_CHUNK_SIZE: usize = 64 * 1024;
+
 /// Maximum message size for gRPC (64 MB for high-resolution camera frames)
 /// Must match server's max_encoding_message_size in server.rs
 const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
@@ -164,8 +285,16 @@ impl DaqClient {
         address: &DaemonAddress,
         config: ChannelConfig,
     ) -> Result<Self> {
+        // unix:// and pipe:// addresses bypass TCP entirely
+        let local_socket = address.local_socket();
+        let base_endpoint = match &local_socket {
+            Some(_) => protocol::local_socket::endpoint(),
+            None => Channel::from_shared(address.as_str().to_string())?,
+        };
+
         // Channel with request timeout for regular RPCs
-        let endpoint = Channel::from_shared(address.as_str().to_string())?
+        let endpoint = base_endpoint
+            .clone()
             .connect_timeout(config.connect_timeout)
             .timeout(config.request_timeout)
             .http2_keep_alive_interval(config.keepalive_interval)
@@ -178,7 +307,7 @@ impl DaqClient {
         // - tcp_nodelay: Disables Nagle's algorithm to reduce latency
         // - buffer_size: Larger buffer to absorb network jitter (1MB)
         // - initial_stream_window_size: Larger window for high-bandwidth frame streams
-        let streaming_endpoint = Channel::from_shared(address.as_str().to_string())?
+        let streaming_endpoint = base_endpoint
             .connect_timeout(config.connect_timeout)
             // No .timeout() call - streaming RPCs should not have a request timeout
             .http2_keep_alive_interval(config.keepalive_interval)
@@ -194,8 +323,16 @@ impl DaqClient {
         //     endpoint = endpoint.tls_config(tls_config)?;
         // }
 
-        let channel = endpoint.connect().await?;
-        let streaming_channel = streaming_endpoint.connect().await?;
+        let (channel, streaming_channel) = match &local_socket {
+            Some(socket) => (
+                protocol::local_socket::connect(endpoint, socket).await?,
+                protocol::local_socket::connect(streaming_endpoint, socket).await?,
+            ),
+            None => (
+                endpoint.connect().await?,
+                streaming_endpoint.connect().await?,
+            ),
+        };
 
         Ok(Self {
             control: ControlServiceClient::new(channel.clone()),
@@ -203,11 +340,19 @@ impl DaqClient {
             hardware: HardwareServiceClient::new(channel.clone())
                 .max_decoding_message_size(MAX_MESSAGE_SIZE),
             // Dedicated streaming client without request timeout
-            hardware_streaming: HardwareServiceClient::new(streaming_channel)
+            hardware_streaming: HardwareServiceClient::new(streaming_channel.clone())
                 .max_decoding_message_size(MAX_MESSAGE_SIZE),
+            session_streaming: SessionServiceClient::new(streaming_channel.clone()),
+            health_streaming: HealthServiceClient::new(streaming_channel.clone()),
+            log_streaming: LogServiceClient::new(streaming_channel),
             scan: ScanServiceClient::new(channel.clone()),
             storage: StorageServiceClient::new(channel.clone()),
             module: ModuleServiceClient::new(channel.clone()),
+            session: SessionServiceClient::new(channel.clone()),
+            config: ConfigServiceClient::new(channel.clone()),
+            console: InstrumentConsoleServiceClient::new(channel.clone()),
+            library: LibraryServiceClient::new(channel.clone()),
+            health: HealthServiceClient::new(channel.clone()),
             run_engine: RunEngineServiceClient::new(channel),
         })
     }
@@ -223,12 +368,83 @@ impl DaqClient {
         Ok(())
     }
 
+    /// Re-read the daemon state a client caches, after a lost connection was restored.
+    ///
+    /// Engine status and run progress are best effort: a daemon without a
+    /// RunEngine still reports its device list.
+    pub async fn resync(&mut self) -> Result<ResyncSnapshot> {
+        let devices = self.list_devices().await?;
+        let engine = self.get_engine_status().await.ok();
+        let run_progress = self
+            .get_run_progress()
+            .await
+            .ok()
+            .filter(|progress| progress.active)
+            .and_then(|progress| progress.progress);
+        Ok(ResyncSnapshot {
+            devices,
+            engine,
+            run_progress,
+        })
+    }
+
     /// Get daemon information (version, capabilities, etc.)
     pub async fn get_daemon_info(&mut self) -> Result<protocol::daq::DaemonInfoResponse> {
         let response = self.control.get_daemon_info(DaemonInfoRequest {}).await?;
         Ok(response.into_inner())
     }
 
+    /// Statistics of every open server-streaming RPC, busiest first
+    pub async fn list_stream_stats(&mut self) -> Result<protocol::daq::ListStreamStatsResponse> {
+        let response = self
+            .control
+            .list_stream_stats(protocol::daq::ListStreamStatsRequest {})
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    // =========================================================================
+    // Health Service (state machines)
+    // =========================================================================
+
+    /// State graphs of modules, the run engine and devices, with the current
+    /// state of every instance
+    ///
+    /// * `kinds` - `"module"`, `"run_engine"` or `"device"` (empty = all)
+    pub async fn get_state_machines(
+        &mut self,
+        kinds: Vec<String>,
+    ) -> Result<protocol::daq::GetStateMachinesResponse> {
+        let response = self
+            .health
+            .get_state_machines(protocol::daq::GetStateMachinesRequest { kinds })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Stream state transitions as they happen
+    pub async fn stream_state_transitions(
+        &mut self,
+        request: protocol::daq::StreamStateTransitionsRequest,
+    ) -> Result<
+        impl futures::Stream<Item = Result<protocol::daq::StateTransitionEvent, tonic::Status>>,
+    > {
+        let response = self
+            .health_streaming
+            .stream_state_transitions(request)
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Memory held per subsystem against the daemon's configured budgets
+    pub async fn get_memory_usage(&mut self) -> Result<protocol::daq::GetMemoryUsageResponse> {
+        let response = self
+            .health
+            .get_memory_usage(protocol::daq::GetMemoryUsageRequest {})
+            .await?;
+        Ok(response.into_inner())
+    }
+
     // =========================================================================
     // Hardware Service
     // =========================================================================
@@ -240,6 +456,7 @@ impl DaqClient {
             .hardware
             .list_devices(ListDevicesRequest {
                 capability_filter: None,
+                ..Default::default()
             })
             .await
             .map_err(|e| {
@@ -265,12 +482,31 @@ impl DaqClient {
             .hardware
             .list_devices(ListDevicesRequest {
                 capability_filter: None,
+                ..Default::default()
             })
             .await?;
         let inner = response.into_inner();
         Ok((inner.devices, inner.registration_failures))
     }
 
+    /// Describe the capability traits a device implements
+    ///
+    /// Includes trait versions, method metadata and device-specific details
+    /// (units, ranges, parameter names), so callers can adapt to a device
+    /// without matching on its driver type.
+    pub async fn describe_capabilities(
+        &mut self,
+        device_id: &str,
+    ) -> Result<Vec<protocol::daq::CapabilityDescriptor>> {
+        let response = self
+            .hardware
+            .describe_capabilities(DescribeCapabilitiesRequest {
+                device_id: device_id.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner().capabilities)
+    }
+
     /// Get device state
     pub async fn get_device_state(
         &mut self,
@@ -321,6 +557,22 @@ impl DaqClient {
         Ok(response.into_inner())
     }
 
+    /// Stop motion of a device, returning the position it stopped at
+    pub async fn stop_motion(&mut self, device_id: &str) -> Result<f64> {
+        let response = self
+            .hardware
+            .stop_motion(StopMotionRequest {
+                device_id: device_id.to_string(),
+            })
+            .await?
+            .into_inner();
+        if response.success {
+            Ok(response.stopped_position)
+        } else {
+            anyhow::bail!("Stop motion failed for {}", device_id)
+        }
+    }
+
     /// Read value from device
     pub async fn read_value(
         &mut self,
@@ -330,6 +582,7 @@ impl DaqClient {
             .hardware
             .read_value(ReadValueRequest {
                 device_id: device_id.to_string(),
+                mode: None,
             })
             .await?;
         Ok(response.into_inner())
@@ -554,6 +807,29 @@ impl DaqClient {
         Ok(response.into_inner().acquisitions)
     }
 
+    /// Scalar channel samples from the daemon's rolling on-disk history
+    ///
+    /// `channels` may be empty for all channels; `end_ns` of 0 means now and
+    /// `max_points` of 0 returns every sample.
+    pub async fn query_channel_history(
+        &mut self,
+        channels: Vec<String>,
+        start_ns: u64,
+        end_ns: u64,
+        max_points: u32,
+    ) -> Result<protocol::daq::QueryChannelHistoryResponse> {
+        let response = self
+            .storage
+            .query_channel_history(protocol::daq::QueryChannelHistoryRequest {
+                channels,
+                start_ns,
+                end_ns,
+                max_points,
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
     // =========================================================================
     // Module Service
     // =========================================================================
@@ -598,6 +874,51 @@ impl DaqClient {
         Ok(response.into_inner())
     }
 
+    /// Get detailed info (parameters, roles, config schema) for a module type
+    pub async fn get_module_type_info(
+        &mut self,
+        type_id: &str,
+    ) -> Result<protocol::daq::ModuleTypeInfo> {
+        let response = self
+            .module
+            .get_module_type_info(GetModuleTypeInfoRequest {
+                type_id: type_id.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Get the current configuration of a module instance
+    pub async fn get_module_config(
+        &mut self,
+        module_id: &str,
+    ) -> Result<protocol::daq::ModuleConfig> {
+        let response = self
+            .module
+            .get_module_config(GetModuleConfigRequest {
+                module_id: module_id.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Replace a module's configuration
+    pub async fn configure_module(
+        &mut self,
+        module_id: &str,
+        parameters: std::collections::HashMap<String, String>,
+    ) -> Result<protocol::daq::ConfigureModuleResponse> {
+        let response = self
+            .module
+            .configure_module(ConfigureModuleRequest {
+                module_id: module_id.to_string(),
+                parameters,
+                partial: false,
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
     /// Start a module
     pub async fn start_module(
         &mut self,
@@ -627,6 +948,25 @@ impl DaqClient {
         Ok(response.into_inner())
     }
 
+    /// Follow events emitted by a module (alarms, state changes, errors)
+    ///
+    /// An empty `event_types` streams every event type.
+    pub async fn stream_module_events(
+        &mut self,
+        module_id: &str,
+        event_types: Vec<String>,
+    ) -> Result<impl futures::Stream<Item = Result<protocol::daq::ModuleEvent, tonic::Status>>>
+    {
+        let response = self
+            .module
+            .stream_module_events(StreamModuleEventsRequest {
+                module_id: module_id.to_string(),
+                event_types,
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
     /// Assign device to module role
     #[allow(dead_code)]
     pub async fn assign_device(
@@ -699,12 +1039,38 @@ impl DaqClient {
             device_id: device_id.to_string(),
             max_fps,
             quality: quality.into(),
+            ..Default::default()
         };
         // Use hardware_streaming client (no request timeout) for long-lived streams
         let response = self.hardware_streaming.stream_frames(request).await?;
         Ok(response.into_inner())
     }
 
+    /// Stream frames as periodic keyframes plus changed tiles
+    ///
+    /// Like [`stream_frames`](Self::stream_frames), but between keyframes the
+    /// server only sends tiles that changed (within `tiles.regions`, if any).
+    /// Decompress each frame, then pass it to a
+    /// [`TileAssembler`](protocol::frame_tiles::TileAssembler) to get whole
+    /// images back.
+    pub async fn stream_frames_tiled(
+        &mut self,
+        device_id: &str,
+        max_fps: u32,
+        quality: StreamQuality,
+        tiles: &TileSettings,
+    ) -> Result<impl futures::Stream<Item = Result<FrameData, tonic::Status>>> {
+        let mut request = StreamFramesRequest {
+            device_id: device_id.to_string(),
+            max_fps,
+            quality: quality.into(),
+            ..Default::default()
+        };
+        tiles.apply_to(&mut request);
+        let response = self.hardware_streaming.stream_frames(request).await?;
+        Ok(response.into_inner())
+    }
+
     // =========================================================================
     // Parameter Service (bd-cdh5.1)
     // =========================================================================
@@ -752,150 +1118,1156 @@ impl DaqClient {
                 device_id: device_id.to_string(),
                 parameter_name: name.to_string(),
                 value: value.to_string(),
+                typed_value: None,
             })
             .await?;
         Ok(response.into_inner())
     }
 
-    /// Execute a specialized device command
-    pub async fn execute_device_command(
+    /// Set a parameter from a typed value
+    ///
+    /// The daemon checks the value against the parameter's dtype, limits and
+    /// enum values before applying it; a mismatch fails with `InvalidArgument`.
+    pub async fn set_parameter_typed(
         &mut self,
         device_id: &str,
-        command: &str,
-        args: &str,
-    ) -> Result<protocol::daq::DeviceCommandResponse> {
+        name: &str,
+        value: protocol::daq::TypedValue,
+    ) -> Result<protocol::daq::SetParameterResponse> {
         let response = self
             .hardware
-            .execute_device_command(DeviceCommandRequest {
+            .set_parameter(SetParameterRequest {
                 device_id: device_id.to_string(),
-                command: command.to_string(),
-                args: args.to_string(),
+                parameter_name: name.to_string(),
+                value: String::new(),
+                typed_value: Some(value),
             })
             .await?;
         Ok(response.into_inner())
     }
 
-    // =========================================================================
-    // Observable Streaming (bd-qqjq stub for bd-r5vb)
-    // =========================================================================
-
-    /// Stream observable values from devices
-    ///
-    /// Returns a stream of ObservableValue messages from the specified devices
-    /// and observables. The server may downsample to the requested rate.
-    ///
-    /// Uses a dedicated streaming channel without request timeout to prevent
-    /// long-lived streams from being cancelled by the default 30-second timeout.
+    /// Confirm a proposed change to a dangerous parameter
     ///
-    /// # Arguments
-    ///
-    /// * `device_ids` - Device IDs to stream from
-    /// * `observable_names` - Observable names to stream (e.g., "power_mw")
-    /// * `sample_rate_hz` - Desired sample rate (server may downsample)
-    pub async fn stream_observables(
+    /// `proposal_token` comes from a [`set_parameter`](Self::set_parameter)
+    /// response with `confirmation_required` set.
+    pub async fn confirm_parameter_change(
         &mut self,
-        device_ids: Vec<String>,
-        observable_names: Vec<String>,
-        sample_rate_hz: u32,
-    ) -> Result<impl futures::Stream<Item = Result<ObservableValue, tonic::Status>>> {
-        let request = StreamObservablesRequest {
-            device_ids,
-            observable_names,
-            sample_rate_hz,
-            deadband: 0.001, // Default minimum change threshold
-        };
-        // Use hardware_streaming client (no request timeout) for long-lived streams
-        let response = self.hardware_streaming.stream_observables(request).await?;
+        proposal_token: &str,
+    ) -> Result<protocol::daq::SetParameterResponse> {
+        let response = self
+            .hardware
+            .confirm_parameter_change(protocol::daq::ConfirmParameterChangeRequest {
+                proposal_token: proposal_token.to_string(),
+            })
+            .await?;
         Ok(response.into_inner())
     }
 
-    // =========================================================================
-    // RunEngine Service
-    // =========================================================================
+    /// Discard a proposed change to a dangerous parameter
+    ///
+    /// Returns false if the proposal was unknown or had already expired.
+    pub async fn cancel_parameter_change(&mut self, proposal_token: &str) -> Result<bool> {
+        let response = self
+            .hardware
+            .cancel_parameter_change(protocol::daq::CancelParameterChangeRequest {
+                proposal_token: proposal_token.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner().cancelled)
+    }
 
-    /// Queue a plan for execution
-    pub async fn queue_plan(
+    /// Recorded parameter and setpoint changes within a time range
+    ///
+    /// `device_id` may be empty for all devices; `end_ns` of 0 means now and
+    /// `limit` of 0 returns every matching event.
+    pub async fn query_device_history(
         &mut self,
-        plan_type: &str,
-        parameters: std::collections::HashMap<String, String>,
-        device_mapping: std::collections::HashMap<String, String>,
-        metadata: std::collections::HashMap<String, String>,
-    ) -> Result<QueuePlanResponse> {
+        device_id: &str,
+        parameter_names: Vec<String>,
+        start_ns: u64,
+        end_ns: u64,
+        limit: u32,
+    ) -> Result<protocol::daq::DeviceHistoryResponse> {
         let response = self
-            .run_engine
-            .queue_plan(QueuePlanRequest {
-                plan_type: plan_type.to_string(),
-                parameters,
-                device_mapping,
-                metadata,
+            .hardware
+            .query_device_history(protocol::daq::DeviceHistoryRequest {
+                device_id: device_id.to_string(),
+                parameter_names,
+                start_ns,
+                end_ns,
+                limit,
             })
             .await?;
         Ok(response.into_inner())
     }
 
-    /// Stream documents from plan execution
-    pub async fn stream_documents(
+    /// A device's parameter values as they were at `timestamp_ns`
+    pub async fn get_device_state_at(
         &mut self,
-        run_uid: Option<String>,
-        doc_types: Vec<i32>,
-    ) -> Result<impl futures::Stream<Item = Result<protocol::daq::Document, tonic::Status>>> {
+        device_id: &str,
+        timestamp_ns: u64,
+    ) -> Result<protocol::daq::DeviceStateAtResponse> {
         let response = self
-            .run_engine
-            .stream_documents(StreamDocumentsRequest { run_uid, doc_types })
+            .hardware
+            .get_device_state_at(protocol::daq::DeviceStateAtRequest {
+                device_id: device_id.to_string(),
+                timestamp_ns,
+            })
             .await?;
         Ok(response.into_inner())
     }
 
-    /// Start the RunEngine to execute queued plans
-    pub async fn start_engine(&mut self) -> Result<StartEngineResponse> {
-        let response = self.run_engine.start_engine(StartEngineRequest {}).await?;
+    /// Dropped frames and samples since daemon start (empty `device_id` = all sources)
+    pub async fn get_data_integrity(
+        &mut self,
+        device_id: &str,
+    ) -> Result<protocol::daq::GetDataIntegrityResponse> {
+        let response = self
+            .hardware
+            .get_data_integrity(protocol::daq::GetDataIntegrityRequest {
+                device_id: device_id.to_string(),
+            })
+            .await?;
         Ok(response.into_inner())
     }
 
-    /// Abort the current running plan
-    ///
-    /// # Arguments
-    /// * `run_uid` - Optional run UID to abort. If empty, aborts the current plan.
-    pub async fn abort_plan(&mut self, run_uid: Option<&str>) -> Result<AbortPlanResponse> {
+    /// Display calibration of every channel that has one
+    pub async fn list_display_transforms(
+        &mut self,
+    ) -> Result<Vec<protocol::daq::ChannelDisplayTransform>> {
         let response = self
-            .run_engine
-            .abort_plan(AbortPlanRequest {
-                run_uid: run_uid.unwrap_or("").to_string(),
-            })
+            .hardware
+            .list_display_transforms(protocol::daq::ListDisplayTransformsRequest {})
             .await?;
-        Ok(response.into_inner())
+        Ok(response.into_inner().transforms)
     }
 
-    /// Pause the RunEngine
+    /// Set a channel's display calibration, or remove it with `clear`
     ///
-    /// # Arguments
-    /// * `defer` - If true, pause at next checkpoint. If false, pause immediately.
-    pub async fn pause_engine(&mut self, defer: bool) -> Result<PauseEngineResponse> {
+    /// Only changes how clients display the channel; stored data stays raw.
+    pub async fn set_display_transform(
+        &mut self,
+        transform: protocol::daq::ChannelDisplayTransform,
+        clear: bool,
+    ) -> Result<()> {
         let response = self
-            .run_engine
-            .pause_engine(PauseEngineRequest { defer })
+            .hardware
+            .set_display_transform(protocol::daq::SetDisplayTransformRequest {
+                transform: Some(transform),
+                clear,
+            })
             .await?;
-        Ok(response.into_inner())
+        let inner = response.into_inner();
+        if inner.success {
+            Ok(())
+        } else {
+            anyhow::bail!("Set display transform failed: {}", inner.error_message)
+        }
     }
 
-    /// Resume the paused RunEngine
-    pub async fn resume_engine(&mut self) -> Result<ResumeEngineResponse> {
+    /// Overlay annotations of a camera
+    pub async fn get_frame_annotations(
+        &mut self,
+        device_id: &str,
+    ) -> Result<Vec<protocol::daq::FrameAnnotation>> {
         let response = self
-            .run_engine
-            .resume_engine(ResumeEngineRequest {})
+            .hardware
+            .get_frame_annotations(protocol::daq::GetFrameAnnotationsRequest {
+                device_id: device_id.to_string(),
+            })
             .await?;
-        Ok(response.into_inner())
+        Ok(response.into_inner().annotations)
     }
 
-    /// Get the current RunEngine status
-    pub async fn get_engine_status(&mut self) -> Result<EngineStatus> {
+    /// Replace a camera's overlay annotations (empty clears them)
+    ///
+    /// The annotations are sent with streamed frames and stored with
+    /// recorded ones.
+    pub async fn set_frame_annotations(
+        &mut self,
+        device_id: &str,
+        annotations: Vec<protocol::daq::FrameAnnotation>,
+    ) -> Result<()> {
         let response = self
-            .run_engine
+            .hardware
+            .set_frame_annotations(protocol::daq::SetFrameAnnotationsRequest {
+                device_id: device_id.to_string(),
+                annotations,
+            })
+            .await?;
+        let inner = response.into_inner();
+        if inner.success {
+            Ok(())
+        } else {
+            anyhow::bail!("Set frame annotations failed: {}", inner.error_message)
+        }
+    }
+
+    /// Execute a specialized device command
+    pub async fn execute_device_command(
+        &mut self,
+        device_id: &str,
+        command: &str,
+        args: &str,
+    ) -> Result<protocol::daq::DeviceCommandResponse> {
+        let response = self
+            .hardware
+            .execute_device_command(DeviceCommandRequest {
+                device_id: device_id.to_string(),
+                command: command.to_string(),
+                args: args.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Run device commands in order with all their devices locked
+    ///
+    /// Check `success` and the per-command results: a failed batch is not
+    /// an error.
+    pub async fn execute_batch(
+        &mut self,
+        commands: Vec<BatchCommand>,
+        mode: BatchMode,
+    ) -> Result<ExecuteBatchResponse> {
+        let response = self
+            .hardware
+            .execute_batch(ExecuteBatchRequest {
+                commands,
+                mode: mode.into(),
+                lock_timeout_ms: 0,
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// List device initialization recipes (optionally for one device)
+    pub async fn list_init_recipes(
+        &mut self,
+        device_id: Option<&str>,
+    ) -> Result<Vec<protocol::daq::InitRecipeInfo>> {
+        let response = self
+            .hardware
+            .list_init_recipes(ListInitRecipesRequest {
+                device_id: device_id.map(str::to_string),
+            })
+            .await?;
+        Ok(response.into_inner().recipes)
+    }
+
+    /// Run a device initialization recipe and return its report
+    pub async fn run_init_recipe(&mut self, name: &str) -> Result<protocol::daq::InitRecipeReport> {
+        let response = self
+            .hardware
+            .run_init_recipe(RunInitRecipeRequest {
+                name: name.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// List warm-up schedules and their current state
+    pub async fn list_warmups(&mut self) -> Result<Vec<protocol::daq::WarmupInfo>> {
+        let response = self.hardware.list_warmups(ListWarmupsRequest {}).await?;
+        Ok(response.into_inner().warmups)
+    }
+
+    /// Abort a warm-up in progress, or skip the next one
+    ///
+    /// Returns false if the daemon has no schedule of that name.
+    pub async fn abort_warmup(&mut self, name: &str) -> Result<bool> {
+        let response = self
+            .hardware
+            .abort_warmup(AbortWarmupRequest {
+                name: name.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner().found)
+    }
+
+    /// Run the daemon's self-test now
+    ///
+    /// A report that is not `ready` leaves the daemon `NOT_SERVING`.
+    pub async fn run_self_test(&mut self) -> Result<SelfTestReport> {
+        let response = self.hardware.run_self_test(RunSelfTestRequest {}).await?;
+        Ok(response.into_inner())
+    }
+
+    /// Report of the last self-test, if one has run
+    pub async fn get_self_test_report(&mut self) -> Result<Option<SelfTestReport>> {
+        let response = self
+            .hardware
+            .get_self_test_report(GetSelfTestReportRequest {})
+            .await?;
+        Ok(response.into_inner().report)
+    }
+
+    /// Measure timestamp skew between devices against a common stimulus
+    ///
+    /// With `apply_corrections`, the measured offsets become the daemon's
+    /// timestamp corrections.
+    pub async fn verify_time_sync(
+        &mut self,
+        request: VerifyTimeSyncRequest,
+    ) -> Result<TimeSyncReport> {
+        let response = self.hardware.verify_time_sync(request).await?;
+        Ok(response.into_inner())
+    }
+
+    /// Instrument inventory, with identity changes since the last refresh
+    ///
+    /// With `refresh`, the daemon re-reads every identity now; otherwise it
+    /// returns the last refresh (usually from the start of the latest run).
+    pub async fn get_inventory(&mut self, refresh: bool) -> Result<GetInventoryResponse> {
+        let response = self
+            .hardware
+            .get_inventory(GetInventoryRequest { refresh })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Serial ports on the daemon host, with USB IDs and stable port specs
+    pub async fn list_serial_ports(&mut self) -> Result<Vec<SerialPortInfo>> {
+        let response = self
+            .hardware
+            .list_serial_ports(ListSerialPortsRequest {})
+            .await?;
+        Ok(response.into_inner().ports)
+    }
+
+    // =========================================================================
+    // Observable Streaming (bd-qqjq stub for bd-r5vb)
+    // =========================================================================
+
+    /// Stream observable values from devices
+    ///
+    /// Returns a stream of ObservableValue messages from the specified devices
+    /// and observables. The server may downsample to the requested rate.
+    ///
+    /// Uses a dedicated streaming channel without request timeout to prevent
+    /// long-lived streams from being cancelled by the default 30-second timeout.
+    ///
+    /// # Arguments
+    ///
+    /// * `device_ids` - Device IDs to stream from
+    /// * `observable_names` - Observable names to stream (e.g., "power_mw")
+    /// * `sample_rate_hz` - Desired sample rate (server may downsample)
+    pub async fn stream_observables(
+        &mut self,
+        device_ids: Vec<String>,
+        observable_names: Vec<String>,
+        sample_rate_hz: u32,
+    ) -> Result<impl futures::Stream<Item = Result<ObservableValue, tonic::Status>>> {
+        let request = StreamObservablesRequest {
+            device_ids,
+            observable_names,
+            sample_rate_hz,
+            deadband: 0.001, // Default minimum change threshold
+            channel_deadbands: std::collections::HashMap::new(),
+            keyframe_interval_ms: 0,
+        };
+        // Use hardware_streaming client (no request timeout) for long-lived streams
+        let response = self.hardware_streaming.stream_observables(request).await?;
+        Ok(response.into_inner())
+    }
+
+    /// Stream observable values only when they change, with periodic keyframes
+    ///
+    /// Meant for many slowly-changing channels: a value is sent when it moves
+    /// by more than its deadband, and every channel's current value is sent
+    /// when the stream starts and every `keyframe_interval_ms` (messages with
+    /// `keyframe` set), so the caller always converges on the full state.
+    ///
+    /// # Arguments
+    ///
+    /// * `device_ids` - Device IDs to stream from (empty = all devices)
+    /// * `observable_names` - Observable names to stream (empty = all)
+    /// * `sample_rate_hz` - Maximum update rate per channel
+    /// * `deadband` - Deadband for channels not in `channel_deadbands`
+    /// * `channel_deadbands` - Deadbands keyed `"device:observable"` or `"observable"`
+    /// * `keyframe_interval_ms` - Keyframe period (0 = no keyframes, changes only)
+    pub async fn stream_observables_on_change(
+        &mut self,
+        device_ids: Vec<String>,
+        observable_names: Vec<String>,
+        sample_rate_hz: u32,
+        deadband: f64,
+        channel_deadbands: std::collections::HashMap<String, f64>,
+        keyframe_interval_ms: u32,
+    ) -> Result<impl futures::Stream<Item = Result<ObservableValue, tonic::Status>>> {
+        let request = StreamObservablesRequest {
+            device_ids,
+            observable_names,
+            sample_rate_hz,
+            deadband,
+            channel_deadbands,
+            keyframe_interval_ms,
+        };
+        let response = self.hardware_streaming.stream_observables(request).await?;
+        Ok(response.into_inner())
+    }
+
+    /// Stream device events: state changes, errors, limit-switch hits and
+    /// auto-range changes, separate from measured data
+    ///
+    /// # Arguments
+    ///
+    /// * `device_ids` - Devices to follow (empty = all devices)
+    /// * `kinds` - Event kinds, e.g. `"limit_switch"` (empty = all kinds)
+    /// * `min_severity` - Least severe events to receive
+    pub async fn stream_device_events(
+        &mut self,
+        device_ids: Vec<String>,
+        kinds: Vec<String>,
+        min_severity: DeviceEventSeverity,
+    ) -> Result<impl futures::Stream<Item = Result<DeviceEvent, tonic::Status>>> {
+        let request = StreamDeviceEventsRequest {
+            device_ids,
+            kinds,
+            min_severity: min_severity as i32,
+        };
+        let response = self
+            .hardware_streaming
+            .stream_device_events(request)
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Ramp a parameter (`"position"` for a movable) gradually to `target`
+    ///
+    /// Give exactly one of `duration_s` and `rate` (units per second).
+    /// Returns the ramp's initial status; follow it with
+    /// [`stream_ramp_events`](Self::stream_ramp_events).
+    pub async fn start_ramp(
+        &mut self,
+        device_id: &str,
+        parameter: &str,
+        target: f64,
+        duration_s: Option<f64>,
+        rate: Option<f64>,
+        profile: RampProfile,
+    ) -> Result<RampStatus> {
+        let response = self
+            .hardware
+            .start_ramp(StartRampRequest {
+                device_id: device_id.to_string(),
+                parameter: parameter.to_string(),
+                target,
+                duration_s,
+                rate,
+                profile: profile as i32,
+                step_interval_ms: 0,
+            })
+            .await?;
+        let inner = response.into_inner();
+        if !inner.success {
+            anyhow::bail!("Start ramp failed: {}", inner.error_message);
+        }
+        inner
+            .status
+            .ok_or_else(|| anyhow::anyhow!("Start ramp returned no status"))
+    }
+
+    /// Stop a ramp at its last setpoint; returns whether it was running
+    pub async fn abort_ramp(&mut self, ramp_id: u64) -> Result<bool> {
+        let response = self
+            .hardware
+            .abort_ramp(AbortRampRequest {
+                ramp_id,
+                device_id: String::new(),
+            })
+            .await?;
+        Ok(response.into_inner().aborted > 0)
+    }
+
+    /// Stop every ramp of a device; returns how many were running
+    pub async fn abort_device_ramps(&mut self, device_id: &str) -> Result<u32> {
+        let response = self
+            .hardware
+            .abort_ramp(AbortRampRequest {
+                ramp_id: 0,
+                device_id: device_id.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner().aborted)
+    }
+
+    /// Ramps in progress, oldest first
+    pub async fn list_ramps(&mut self) -> Result<Vec<RampStatus>> {
+        let response = self.hardware.list_ramps(ListRampsRequest {}).await?;
+        Ok(response.into_inner().ramps)
+    }
+
+    /// Stream ramp progress of the given devices (empty = all devices)
+    pub async fn stream_ramp_events(
+        &mut self,
+        device_ids: Vec<String>,
+    ) -> Result<impl futures::Stream<Item = Result<RampStatus, tonic::Status>>> {
+        let response = self
+            .hardware_streaming
+            .stream_ramp_events(StreamRampEventsRequest { device_ids })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    // =========================================================================
+    // RunEngine Service
+    // =========================================================================
+
+    /// Queue a plan for execution
+    pub async fn queue_plan(
+        &mut self,
+        plan_type: &str,
+        parameters: std::collections::HashMap<String, String>,
+        device_mapping: std::collections::HashMap<String, String>,
+        metadata: std::collections::HashMap<String, String>,
+    ) -> Result<QueuePlanResponse> {
+        let response = self
+            .run_engine
+            .queue_plan(QueuePlanRequest {
+                plan_type: plan_type.to_string(),
+                parameters,
+                device_mapping,
+                metadata,
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Queue a plan template once per sweep item
+    ///
+    /// `{variable}` placeholders in the parameters, device mapping and
+    /// metadata are filled from each item. Returns the batch UID and the
+    /// child run UIDs in item order.
+    pub async fn queue_template(
+        &mut self,
+        template_name: &str,
+        plan_type: &str,
+        parameters: std::collections::HashMap<String, String>,
+        device_mapping: std::collections::HashMap<String, String>,
+        metadata: std::collections::HashMap<String, String>,
+        items: Vec<std::collections::HashMap<String, String>>,
+    ) -> Result<QueueTemplateResponse> {
+        let response = self
+            .run_engine
+            .queue_template(QueueTemplateRequest {
+                template_name: template_name.to_string(),
+                plan_type: plan_type.to_string(),
+                parameters,
+                device_mapping,
+                metadata,
+                items: items
+                    .into_iter()
+                    .map(|variables| SweepItem { variables })
+                    .collect(),
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Simulate a plan without touching hardware
+    ///
+    /// Returns the command sequence, estimated duration, and device conflicts.
+    /// Timing overrides (move speeds, exposures) can be set on the request.
+    pub async fn dry_run_plan(&mut self, request: DryRunPlanRequest) -> Result<DryRunPlanResponse> {
+        let response = self.run_engine.dry_run_plan(request).await?;
+        Ok(response.into_inner())
+    }
+
+    /// Stream documents from plan execution
+    pub async fn stream_documents(
+        &mut self,
+        run_uid: Option<String>,
+        doc_types: Vec<i32>,
+    ) -> Result<impl futures::Stream<Item = Result<protocol::daq::Document, tonic::Status>>> {
+        let response = self
+            .run_engine
+            .stream_documents(StreamDocumentsRequest { run_uid, doc_types })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Start the RunEngine to execute queued plans
+    pub async fn start_engine(&mut self) -> Result<StartEngineResponse> {
+        let response = self.run_engine.start_engine(StartEngineRequest {}).await?;
+        Ok(response.into_inner())
+    }
+
+    /// Abort the current running plan
+    ///
+    /// # Arguments
+    /// * `run_uid` - Optional run UID to abort. If empty, aborts the current plan.
+    pub async fn abort_plan(&mut self, run_uid: Option<&str>) -> Result<AbortPlanResponse> {
+        let response = self
+            .run_engine
+            .abort_plan(AbortPlanRequest {
+                run_uid: run_uid.unwrap_or("").to_string(),
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Pause the RunEngine
+    ///
+    /// # Arguments
+    /// * `defer` - If true, pause at next checkpoint. If false, pause immediately.
+    pub async fn pause_engine(&mut self, defer: bool) -> Result<PauseEngineResponse> {
+        let response = self
+            .run_engine
+            .pause_engine(PauseEngineRequest { defer })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Resume the paused RunEngine
+    pub async fn resume_engine(&mut self) -> Result<ResumeEngineResponse> {
+        let response = self
+            .run_engine
+            .resume_engine(ResumeEngineRequest {})
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Get the current RunEngine status
+    pub async fn get_engine_status(&mut self) -> Result<EngineStatus> {
+        let response = self
+            .run_engine
             .get_engine_status(GetEngineStatusRequest {})
             .await?;
         Ok(response.into_inner())
     }
 
+    /// Get structured progress (points completed/total, positions, ETA) for the active run
+    pub async fn get_run_progress(&mut self) -> Result<RunProgress> {
+        let response = self
+            .run_engine
+            .get_run_progress(GetRunProgressRequest {})
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// List recently completed runs, newest first (`limit` 0 = all retained)
+    pub async fn list_runs(&mut self, limit: u32) -> Result<Vec<protocol::daq::RunSummary>> {
+        let response = self
+            .run_engine
+            .list_runs(ListRunsRequest {
+                limit,
+                ..Default::default()
+            })
+            .await?;
+        Ok(response.into_inner().runs)
+    }
+
+    /// Compare a baseline ("good") run against a candidate run
+    pub async fn compare_runs(
+        &mut self,
+        baseline_run_uid: &str,
+        candidate_run_uid: &str,
+    ) -> Result<RunComparison> {
+        let response = self
+            .run_engine
+            .compare_runs(CompareRunsRequest {
+                baseline_run_uid: baseline_run_uid.to_string(),
+                candidate_run_uid: candidate_run_uid.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Check a run file against its signed provenance manifest
+    ///
+    /// `trusted_public_keys` (hex) are accepted as signers in addition to the
+    /// daemon's own key.
+    pub async fn verify_run(
+        &mut self,
+        run_uid: &str,
+        trusted_public_keys: Vec<String>,
+    ) -> Result<VerifyRunResponse> {
+        let response = self
+            .run_engine
+            .verify_run(VerifyRunRequest {
+                run_uid: run_uid.to_string(),
+                trusted_public_keys,
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Attach a marker to a run (`run_uid` empty = the active run)
+    ///
+    /// `time_ns = 0` stamps the marker with the daemon's clock. Returns the
+    /// UID of the run the marker was attached to.
+    pub async fn add_run_marker(
+        &mut self,
+        run_uid: &str,
+        time_ns: u64,
+        text: &str,
+        author: &str,
+    ) -> Result<String> {
+        let response = self
+            .run_engine
+            .add_run_marker(AddRunMarkerRequest {
+                run_uid: run_uid.to_string(),
+                marker: Some(RunMarker {
+                    time_ns,
+                    text: text.to_string(),
+                    author: author.to_string(),
+                }),
+            })
+            .await?;
+        Ok(response.into_inner().run_uid)
+    }
+
+    /// Enable or disable writing `channel` to storage
+    ///
+    /// Live document streams are unaffected. Returns the updated recording
+    /// state, including the changes logged against the active run.
+    pub async fn set_channel_recording(
+        &mut self,
+        channel: &str,
+        enabled: bool,
+        author: &str,
+    ) -> Result<ChannelRecordingStatus> {
+        let response = self
+            .run_engine
+            .set_channel_recording(SetChannelRecordingRequest {
+                channel: channel.to_string(),
+                enabled,
+                author: author.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Switch storage to commissioning mode
+    ///
+    /// Persists every `every_nth` event, or at most `max_rate_hz` events per
+    /// second, of each stream; pass 0 for both to record every event again.
+    /// Live document streams are unaffected.
+    pub async fn set_recording_decimation(
+        &mut self,
+        every_nth: u64,
+        max_rate_hz: f64,
+        author: &str,
+    ) -> Result<ChannelRecordingStatus> {
+        let response = self
+            .run_engine
+            .set_recording_decimation(SetRecordingDecimationRequest {
+                every_nth,
+                max_rate_hz,
+                author: author.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Channels excluded from storage and the channels seen in runs so far
+    pub async fn get_channel_recording(&mut self) -> Result<ChannelRecordingStatus> {
+        let response = self
+            .run_engine
+            .get_channel_recording(GetChannelRecordingRequest {})
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    // =========================================================================
+    // Session Service (multi-user presence)
+    // =========================================================================
+
+    /// Open a session announcing this client to other users
+    pub async fn open_session(
+        &mut self,
+        client_name: &str,
+        host: &str,
+        role: SessionRole,
+    ) -> Result<SessionInfo> {
+        let response = self
+            .session
+            .open_session(OpenSessionRequest {
+                client_name: client_name.to_string(),
+                host: host.to_string(),
+                role: role as i32,
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Keep a session alive (fails once it has expired or been revoked)
+    pub async fn session_heartbeat(&mut self, session_id: &str) -> Result<SessionInfo> {
+        let response = self
+            .session
+            .heartbeat(SessionHeartbeatRequest {
+                session_id: session_id.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Close a session, releasing its device locks
+    pub async fn close_session(&mut self, session_id: &str) -> Result<()> {
+        self.session
+            .close_session(CloseSessionRequest {
+                session_id: session_id.to_string(),
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// List all connected sessions
+    pub async fn list_sessions(&mut self) -> Result<Vec<SessionInfo>> {
+        let response = self.session.list_sessions(ListSessionsRequest {}).await?;
+        Ok(response.into_inner().sessions)
+    }
+
+    /// Take the advisory lock on a device
+    ///
+    /// `success` is false (with the current holder) if another session has it.
+    pub async fn acquire_device_lock(
+        &mut self,
+        session_id: &str,
+        device_id: &str,
+    ) -> Result<DeviceLockResponse> {
+        let response = self
+            .session
+            .acquire_device_lock(DeviceLockRequest {
+                session_id: session_id.to_string(),
+                device_id: device_id.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Release the advisory lock on a device
+    pub async fn release_device_lock(&mut self, session_id: &str, device_id: &str) -> Result<()> {
+        self.session
+            .release_device_lock(DeviceLockRequest {
+                session_id: session_id.to_string(),
+                device_id: device_id.to_string(),
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Revoke another session (requires an admin session)
+    pub async fn revoke_session(
+        &mut self,
+        admin_session_id: &str,
+        target_session_id: &str,
+        reason: &str,
+    ) -> Result<()> {
+        let response = self
+            .session
+            .revoke_session(RevokeSessionRequest {
+                admin_session_id: admin_session_id.to_string(),
+                target_session_id: target_session_id.to_string(),
+                reason: reason.to_string(),
+            })
+            .await?
+            .into_inner();
+        if response.success {
+            Ok(())
+        } else {
+            anyhow::bail!("Revoke session failed: {}", response.error_message)
+        }
+    }
+
+    /// Stream presence changes (first message is a snapshot of all sessions)
+    ///
+    /// Uses the streaming channel so the stream isn't cut off by the request
+    /// timeout.
+    pub async fn stream_presence(
+        &mut self,
+    ) -> Result<impl futures::Stream<Item = Result<PresenceUpdate, tonic::Status>>> {
+        let response = self
+            .session_streaming
+            .stream_presence(StreamPresenceRequest {})
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Preferences visible to a session (user scope, then session scope)
+    pub async fn get_preferences(
+        &mut self,
+        session_id: &str,
+        key_prefix: &str,
+    ) -> Result<Vec<Preference>> {
+        let response = self
+            .session
+            .get_preferences(GetPreferencesRequest {
+                session_id: session_id.to_string(),
+                key_prefix: key_prefix.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner().preferences)
+    }
+
+    /// Store preferences for the session's user or the session itself
+    pub async fn set_preferences(
+        &mut self,
+        session_id: &str,
+        scope: PreferenceScope,
+        values: std::collections::HashMap<String, String>,
+    ) -> Result<Vec<Preference>> {
+        let response = self
+            .session
+            .set_preferences(SetPreferencesRequest {
+                session_id: session_id.to_string(),
+                scope: scope as i32,
+                values,
+            })
+            .await?;
+        Ok(response.into_inner().preferences)
+    }
+
+    /// Delete preferences; returns how many existed
+    pub async fn delete_preferences(
+        &mut self,
+        session_id: &str,
+        scope: PreferenceScope,
+        keys: Vec<String>,
+    ) -> Result<u32> {
+        let response = self
+            .session
+            .delete_preferences(DeletePreferencesRequest {
+                session_id: session_id.to_string(),
+                scope: scope as i32,
+                keys,
+            })
+            .await?;
+        Ok(response.into_inner().deleted)
+    }
+
+    /// Stream preference changes visible to a session (first message is a snapshot)
+    pub async fn stream_preferences(
+        &mut self,
+        session_id: &str,
+    ) -> Result<impl futures::Stream<Item = Result<PreferenceUpdate, tonic::Status>>> {
+        let response = self
+            .session_streaming
+            .stream_preferences(StreamPreferencesRequest {
+                session_id: session_id.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    // =========================================================================
+    // Config Service (differential apply with rollback)
+    // =========================================================================
+
+    /// Get the daemon's current configuration as a TOML document
+    pub async fn get_daemon_config(&mut self) -> Result<String> {
+        let response = self
+            .config
+            .get_daemon_config(GetDaemonConfigRequest {})
+            .await?;
+        Ok(response.into_inner().config_toml)
+    }
+
+    /// Apply a desired configuration; the daemon rolls back on failure
+    ///
+    /// With `dry_run` the report lists the planned changes without applying them.
+    pub async fn apply_config(
+        &mut self,
+        config_toml: &str,
+        dry_run: bool,
+    ) -> Result<ConfigApplyReport> {
+        let response = self
+            .config
+            .apply_config(ApplyConfigRequest {
+                config_toml: config_toml.to_string(),
+                dry_run,
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Restore the configuration snapshot recorded with a run
+    ///
+    /// With `restore_parameters` the run's device parameter values are set
+    /// after the configuration is applied. With `dry_run` only the
+    /// configuration diff is reported.
+    pub async fn restore_run_config(
+        &mut self,
+        run_uid: &str,
+        dry_run: bool,
+        restore_parameters: bool,
+    ) -> Result<RestoreConfigSnapshotResponse> {
+        let response = self
+            .config
+            .restore_config_snapshot(RestoreConfigSnapshotRequest {
+                source: Some(restore_config_snapshot_request::Source::RunUid(
+                    run_uid.to_string(),
+                )),
+                dry_run,
+                restore_parameters,
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    // =========================================================================
+    // Log Service (remote daemon logs)
+    // =========================================================================
+
+    /// Stream daemon log records matching the request's filters
+    ///
+    /// Buffered history is replayed first, then new records follow. Uses the
+    /// streaming channel so the stream isn't cut off by the request timeout.
+    pub async fn stream_logs(
+        &mut self,
+        request: StreamLogsRequest,
+    ) -> Result<impl futures::Stream<Item = Result<LogRecord, tonic::Status>>> {
+        let response = self.log_streaming.stream_logs(request).await?;
+        Ok(response.into_inner())
+    }
+
+    // =========================================================================
+    // Instrument Console (raw command passthrough)
+    // =========================================================================
+
+    /// Send a raw command string to a device and return its unparsed reply
+    ///
+    /// The daemon refuses devices in use by a run and records every attempt
+    /// in its audit log under `session_id`. A device-side failure comes back
+    /// as a response with `success == false`, not as an error.
+    pub async fn send_raw_command(
+        &mut self,
+        device_id: &str,
+        command: &str,
+        expect_response: bool,
+        session_id: &str,
+    ) -> Result<RawCommandResponse> {
+        let response = self
+            .console
+            .send_raw_command(RawCommandRequest {
+                device_id: device_id.to_string(),
+                command: command.to_string(),
+                expect_response,
+                session_id: session_id.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    // =========================================================================
+    // Library (versioned scripts, plans and device configs)
+    // =========================================================================
+
+    /// List library files, optionally only those of one kind
+    pub async fn list_library_files(
+        &mut self,
+        kind: Option<LibraryFileKind>,
+    ) -> Result<Vec<LibraryFileInfo>> {
+        let response = self
+            .library
+            .list_library_files(ListLibraryFilesRequest {
+                kind: kind.map(|k| k as i32),
+            })
+            .await?;
+        Ok(response.into_inner().files)
+    }
+
+    /// Upload a file to the daemon's library as its next version
+    ///
+    /// `base_version` is the version the content was edited from; the daemon
+    /// refuses the upload if someone else saved a newer one (0 = overwrite
+    /// regardless). Rejected content or a conflict comes back as a response
+    /// with `success == false`, not as an error.
+    pub async fn upload_library_file(
+        &mut self,
+        kind: LibraryFileKind,
+        name: &str,
+        content: Vec<u8>,
+        base_version: u32,
+        comment: &str,
+    ) -> Result<UploadLibraryFileResponse> {
+        use sha2::{Digest, Sha256};
+
+        let header = LibraryFileHeader {
+            kind: kind as i32,
+            name: name.to_string(),
+            total_size: content.len() as u64,
+            sha256: format!("{:x}", Sha256::digest(&content)),
+            version: base_version,
+            comment: comment.to_string(),
+        };
+        let chunks: Vec<LibraryFileChunk> = std::iter::once(LibraryPayload::Header(header))
+            .chain(
+                content
+                    .chunks(LIBRARY_CHUNK_SIZE)
+                    .map(|chunk| LibraryPayload::Data(chunk.to_vec())),
+            )
+            .map(|payload| LibraryFileChunk {
+                payload: Some(payload),
+            })
+            .collect();
+
+        let response = self
+            .library
+            .upload_library_file(futures::stream::iter(chunks))
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Download a version of a library file (0 = latest)
+    ///
+    /// Returns the header of the version received and its verified content.
+    pub async fn download_library_file(
+        &mut self,
+        kind: LibraryFileKind,
+        name: &str,
+        version: u32,
+    ) -> Result<(LibraryFileHeader, Vec<u8>)> {
+        use futures::StreamExt;
+        use sha2::{Digest, Sha256};
+
+        let mut stream = self
+            .library
+            .download_library_file(DownloadLibraryFileRequest {
+                kind: kind as i32,
+                name: name.to_string(),
+                version,
+            })
+            .await?
+            .into_inner();
+
+        let mut header = None;
+        let mut content = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            match chunk?.payload {
+                Some(LibraryPayload::Header(h)) => {
+                    content.reserve(h.total_size as usize);
+                    header = Some(h);
+                }
+                Some(LibraryPayload::Data(data)) => content.extend_from_slice(&data),
+                None => {}
+            }
+        }
+
+        let header = header.ok_or_else(|| anyhow::anyhow!("Download of {} had no header", name))?;
+        if content.len() as u64 != header.total_size
+            || format!("{:x}", Sha256::digest(&content)) != header.sha256
+        {
+            anyhow::bail!("Download of {} was corrupted in transfer", name);
+        }
+        Ok((header, content))
+    }
+
+    /// Delete a library file and all its versions
+    ///
+    /// Returns false if the file did not exist.
+    pub async fn delete_library_file(&mut self, kind: LibraryFileKind, name: &str) -> Result<bool> {
+        let response = self
+            .library
+            .delete_library_file(DeleteLibraryFileRequest {
+                kind: kind as i32,
+                name: name.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner().deleted)
+    }
+
     // =========================================================================
     // Laser Control (bd-pwjo)
     // =========================================================================
@@ -20,6 +20,10 @@
 //! - Missing port (e.g., `http://localhost` → `http://localhost:50051`)
 //! - IPv6 addresses (e.g., `[::1]:50051` → `http://[::1]:50051`)
 //!
+//! A daemon on the same machine can also be reached over a local socket:
+//! `unix:///run/rust-daq/daq.sock` or, on Windows, `pipe://rust-daq`. These
+//! are kept as written (see [`protocol::local_socket`]).
+//!
 //! # Example
 //!
 //! ```
@@ -32,6 +36,7 @@
 //! # Ok::<(), daq_client::connection::AddressError>(())
 //! ```
 
+use protocol::local_socket::LocalSocket;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use url::Url;
@@ -134,9 +139,12 @@ impl DaemonAddress {
     /// # Ok::<(), daq_client::connection::AddressError>(())
     /// ```
     pub fn parse(input: &str, source: AddressSource) -> Result<Self, AddressError> {
-        let normalized = normalize_url(input)?;
+        let url = match LocalSocket::parse(input) {
+            Some(socket) => socket.to_string(),
+            None => normalize_url(input)?.to_string(),
+        };
         Ok(Self {
-            url: normalized.to_string(),
+            url,
             source,
             original: input.to_string(),
         })
@@ -160,6 +168,12 @@ impl DaemonAddress {
         &self.original
     }
 
+    /// Returns the local socket for `unix://` and `pipe://` addresses.
+    #[must_use]
+    pub fn local_socket(&self) -> Option<LocalSocket> {
+        LocalSocket::parse(&self.url)
+    }
+
     /// Returns `true` if this address uses TLS (https scheme).
     #[must_use]
     #[allow(dead_code)]
@@ -202,7 +216,7 @@ pub enum AddressError {
     MissingHost,
     /// Port could not be set (should not happen with valid hosts)
     InvalidPort(String),
-    /// Unsupported URL scheme (only http/https allowed)
+    /// Unsupported URL scheme (only http/https and local sockets allowed)
     UnsupportedScheme(String),
 }
 
@@ -215,7 +229,12 @@ impl fmt::Display for AddressError {
             Self::InvalidUrl(e) => write!(f, "Invalid URL: {e}"),
             Self::MissingHost => write!(f, "URL must include a host"),
             Self::InvalidPort(e) => write!(f, "Invalid port: {e}"),
-            Self::UnsupportedScheme(s) => write!(f, "Unsupported scheme '{s}' (use http or https)"),
+            Self::UnsupportedScheme(s) => {
+                write!(
+                    f,
+                    "Unsupported scheme '{s}' (use http, https, unix or pipe)"
+                )
+            }
         }
     }
 }
@@ -422,6 +441,25 @@ mod tests {
         assert!(addr.is_tls());
     }
 
+    #[test]
+    fn test_daemon_address_local_socket() {
+        let addr =
+            DaemonAddress::parse(" unix:///run/rust-daq/daq.sock ", AddressSource::UserInput)
+                .unwrap();
+        assert_eq!(addr.as_str(), "unix:///run/rust-daq/daq.sock");
+        assert_eq!(
+            addr.local_socket(),
+            Some(LocalSocket::Unix("/run/rust-daq/daq.sock".into()))
+        );
+
+        let addr = DaemonAddress::parse("pipe://rust-daq", AddressSource::UserInput).unwrap();
+        assert_eq!(
+            addr.local_socket(),
+            Some(LocalSocket::Pipe("rust-daq".to_string()))
+        );
+        assert!(DaemonAddress::default().local_socket().is_none());
+    }
+
     #[test]
     fn test_daemon_address_default() {
         let addr = DaemonAddress::default();
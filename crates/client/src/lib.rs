@@ -15,4 +15,4 @@ pub use connection::{
     DEFAULT_GRPC_PORT, STORAGE_KEY_DAEMON_ADDR,
 };
 pub use error::{ClientError, Result};
-pub use reconnect::{ConnectionManager, ConnectionState, ReconnectConfig};
+pub use reconnect::{ConnectionManager, ConnectionState, ReconnectConfig, ResyncSnapshot};
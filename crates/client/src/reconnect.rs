@@ -5,6 +5,7 @@
 //! - Auto-reconnect with exponential backoff and jitter
 //! - Cancellation support for pending connections
 //! - Periodic health checks to detect stale connections
+//! - Resynchronization of daemon state after a lost connection is restored
 //!
 //! # State Machine
 //!
@@ -260,6 +261,48 @@ fn rand_jitter() -> f64 {
     (nanos % 1000) as f64 / 1000.0
 }
 
+/// Daemon state re-read after a lost connection was restored.
+///
+/// Produced by [`DaqClient::resync`].
+#[derive(Debug, Clone, Default)]
+pub struct ResyncSnapshot {
+    /// Devices the daemon has now
+    pub devices: Vec<protocol::daq::DeviceInfo>,
+    /// RunEngine status, if the daemon runs an engine
+    pub engine: Option<protocol::daq::EngineStatus>,
+    /// Progress of the active run, if one is running
+    pub run_progress: Option<protocol::daq::ProgressDocument>,
+}
+
+impl ResyncSnapshot {
+    /// IDs among `known` that the daemon no longer has.
+    #[must_use]
+    pub fn missing_devices<'a>(&self, known: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        known
+            .into_iter()
+            .filter(|id| !self.devices.iter().any(|d| d.id == *id))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// One-line summary for the log.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let run = match &self.run_progress {
+            Some(progress) if progress.points_total > 0 => format!(
+                "run {} at {}/{}",
+                progress.run_uid, progress.points_completed, progress.points_total
+            ),
+            Some(progress) => format!(
+                "run {} at point {}",
+                progress.run_uid, progress.points_completed
+            ),
+            None => "no active run".to_string(),
+        };
+        format!("{} devices, {}", self.devices.len(), run)
+    }
+}
+
 /// Result of a connection attempt sent through the channel.
 pub enum ConnectResult {
     /// Connection succeeded.
@@ -298,6 +341,10 @@ pub struct ConnectionManager {
     cancel_handle: Option<tokio::sync::oneshot::Sender<()>>,
     /// Current reconnect attempt (0 if not reconnecting)
     reconnect_attempt: u32,
+    /// An established connection was lost and is being restored
+    lost: bool,
+    /// The last connection restored a lost one; state must be resynchronized
+    resync_pending: bool,
 }
 
 impl ConnectionManager {
@@ -322,6 +369,8 @@ impl ConnectionManager {
             rx,
             cancel_handle: None,
             reconnect_attempt: 0,
+            lost: false,
+            resync_pending: false,
         }
     }
 
@@ -438,26 +487,56 @@ impl ConnectionManager {
         self.health_status.check_in_progress = true;
     }
 
+    /// Run the next health check right away instead of waiting for the interval.
+    ///
+    /// Call this when a stream fails with a transport error, so a daemon restart
+    /// is noticed without waiting for the periodic check.
+    pub fn check_health_now(&mut self) {
+        self.health_status.last_check = None;
+    }
+
     /// Trigger reconnection due to health check failure.
     pub fn trigger_health_reconnect(
         &mut self,
         address: DaemonAddress,
         runtime: &tokio::runtime::Runtime,
     ) {
+        tracing::warn!("Health check threshold exceeded, triggering reconnect");
+        self.connection_lost(
+            address,
+            runtime,
+            "Connection lost (health check failed)".into(),
+        );
+    }
+
+    /// Handle the loss of an established connection.
+    ///
+    /// Starts reconnecting with backoff when auto-reconnect is enabled. Once the
+    /// connection is restored, [`take_resync`](Self::take_resync) reports that
+    /// daemon state has to be resynchronized.
+    pub fn connection_lost(
+        &mut self,
+        address: DaemonAddress,
+        runtime: &tokio::runtime::Runtime,
+        error: String,
+    ) {
+        self.lost = true;
         if !self.config.enabled {
             self.state = ConnectionState::Error {
-                message: "Connection lost (health check failed)".into(),
+                message: error,
                 retriable: true,
             };
             return;
         }
+        self.start_reconnect(address, runtime, error);
+    }
 
-        tracing::warn!("Health check threshold exceeded, triggering reconnect");
-        self.start_reconnect(
-            address,
-            runtime,
-            "Connection lost (health check failed)".into(),
-        );
+    /// Whether the last connection restored a lost one, clearing the flag.
+    ///
+    /// Streams that were open and the device list, run progress and other
+    /// cached daemon state are stale after such a reconnect.
+    pub fn take_resync(&mut self) -> bool {
+        std::mem::take(&mut self.resync_pending)
     }
 
     /// Reset health status (called on new connection).
@@ -606,6 +685,8 @@ impl ConnectionManager {
     /// Disconnect from the daemon.
     pub fn disconnect(&mut self) {
         self.cancel();
+        self.lost = false;
+        self.resync_pending = false;
         self.state = ConnectionState::Disconnected;
         tracing::info!("Disconnected from daemon");
     }
@@ -642,6 +723,7 @@ impl ConnectionManager {
                     connected_at: Instant::now(),
                 };
                 self.reconnect_attempt = 0;
+                self.resync_pending = std::mem::take(&mut self.lost);
                 self.reset_health_status(); // Reset health tracking for new connection
                 tracing::info!("Connected to {}", connected_addr.as_str());
                 Some((*client, daemon_version))
@@ -713,7 +795,11 @@ impl Default for ConnectionManager {
 }
 
 /// Determine if an error is retriable.
-fn is_retriable_error(error: &str) -> bool {
+///
+/// Also used to tell transport failures of open streams from errors the
+/// daemon reported.
+#[must_use]
+pub fn is_retriable_error(error: &str) -> bool {
     let error_lower = error.to_lowercase();
 
     // Non-retriable errors
@@ -735,7 +821,8 @@ fn is_retriable_error(error: &str) -> bool {
         || error_lower.contains("dns")
         || error_lower.contains("resolve")
         || error_lower.contains("unreachable")
-        || error_lower.contains("temporarily unavailable")
+        || error_lower.contains("unavailable")
+        || error_lower.contains("broken pipe")
 }
 
 /// Convert a raw error message to a user-friendly description.
@@ -849,6 +936,59 @@ mod tests {
         assert!(!disabled.should_retry(1));
     }
 
+    #[test]
+    fn test_connection_lost_without_auto_reconnect() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let address =
+            DaemonAddress::parse("localhost:50051", crate::AddressSource::Default).unwrap();
+        let mut manager = ConnectionManager::with_config(ReconnectConfig {
+            enabled: false,
+            ..Default::default()
+        });
+
+        manager.connection_lost(address, &runtime, "Connection lost".into());
+        assert_eq!(
+            manager.state(),
+            &ConnectionState::Error {
+                message: "Connection lost".into(),
+                retriable: true
+            }
+        );
+        assert!(!manager.is_busy());
+        // Nothing to resynchronize until the connection is restored
+        assert!(!manager.take_resync());
+        manager.disconnect();
+        assert_eq!(manager.state(), &ConnectionState::Disconnected);
+    }
+
+    #[test]
+    fn test_resync_snapshot() {
+        let device = |id: &str| protocol::daq::DeviceInfo {
+            id: id.to_string(),
+            ..Default::default()
+        };
+        let mut snapshot = ResyncSnapshot {
+            devices: vec![device("stage"), device("camera")],
+            ..Default::default()
+        };
+        assert_eq!(
+            snapshot.missing_devices(["stage", "power_meter"]),
+            vec!["power_meter".to_string()]
+        );
+        assert_eq!(snapshot.summary(), "2 devices, no active run");
+
+        snapshot.run_progress = Some(protocol::daq::ProgressDocument {
+            run_uid: "abc".to_string(),
+            points_completed: 12,
+            points_total: 50,
+            ..Default::default()
+        });
+        assert_eq!(snapshot.summary(), "2 devices, run abc at 12/50");
+    }
+
     #[test]
     fn test_is_retriable_error() {
         // Retriable
@@ -856,6 +996,9 @@ mod tests {
         assert!(is_retriable_error("connection refused"));
         assert!(is_retriable_error("request timed out"));
         assert!(is_retriable_error("DNS resolution failed"));
+        assert!(is_retriable_error(
+            "status: Unavailable, message: \"error reading a body from connection\""
+        ));
 
         // Not retriable
         assert!(!is_retriable_error("invalid URL"));
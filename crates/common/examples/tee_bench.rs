@@ -16,7 +16,7 @@
 //! TEE_BENCH_MESSAGES=500000 cargo run -p common --example tee_bench --release
 //! ```
 
-use common::core::Measurement;
+use common::core::{DataQuality, Measurement};
 use common::pipeline::{MeasurementSink, Tee};
 use std::time::Instant;
 use tokio::sync::{broadcast, mpsc, oneshot};
@@ -80,6 +80,7 @@ async fn main() {
             value: 1.0,
             unit: "arb".into(),
             timestamp: chrono::Utc::now(),
+            quality: DataQuality::Good,
         };
         if src_tx.send(meas).await.is_err() {
             break;
@@ -0,0 +1,309 @@
+//! Acquisition modes for scalar reads.
+//!
+//! A read can ask for more than one instantaneous value: the mean of N
+//! samples, the peak over a time window, or the integral over a window.
+//! Drivers whose hardware does this natively (meter-side averaging, a
+//! hardware integrator) implement it in [`Readable::read_native`]; for the
+//! rest, [`acquire_generic`] builds the value from repeated
+//! [`Readable::read_qualified`] calls.
+//!
+//! Modes are written as `single`, `average:16`, `peak:250ms` or
+//! `integrate:1000ms`. Run engine metadata, descriptors and gRPC responses
+//! record the mode of every value in that form.
+//!
+//! [`Readable::read_native`]: crate::capabilities::Readable::read_native
+//! [`Readable::read_qualified`]: crate::capabilities::Readable::read_qualified
+
+use crate::capabilities::Readable;
+use crate::core::DataQuality;
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Metadata key prefix selecting a device's mode in queued run metadata,
+/// e.g. `acquisition_mode.power_meter = "average:16"`
+pub const ACQUISITION_MODE_METADATA_PREFIX: &str = "acquisition_mode.";
+
+/// Spacing between reads when the core builds a windowed value
+pub const GENERIC_SAMPLE_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Upper bound on samples per averaged read
+pub const MAX_AVERAGE_SAMPLES: u32 = 100_000;
+
+/// How a single reported value is acquired
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum AcquisitionMode {
+    /// One instantaneous reading
+    #[default]
+    Single,
+    /// Mean of `samples` consecutive readings
+    Average { samples: u32 },
+    /// Largest reading over the window
+    Peak { window_ms: u64 },
+    /// Time integral of the readings over the window (value × seconds)
+    Integrate { window_ms: u64 },
+}
+
+impl AcquisitionMode {
+    pub fn is_single(&self) -> bool {
+        matches!(self, Self::Single)
+    }
+
+    /// Check the sample count or window is usable
+    pub fn validate(&self) -> Result<()> {
+        match *self {
+            Self::Single => Ok(()),
+            Self::Average { samples } if samples == 0 || samples > MAX_AVERAGE_SAMPLES => bail!(
+                "Average needs 1..={} samples, got {}",
+                MAX_AVERAGE_SAMPLES,
+                samples
+            ),
+            Self::Peak { window_ms: 0 } | Self::Integrate { window_ms: 0 } => {
+                bail!("{} needs a window of at least 1 ms", self.name())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Mode name without its argument
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Single => "single",
+            Self::Average { .. } => "average",
+            Self::Peak { .. } => "peak",
+            Self::Integrate { .. } => "integrate",
+        }
+    }
+}
+
+impl fmt::Display for AcquisitionMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Single => f.write_str("single"),
+            Self::Average { samples } => write!(f, "average:{}", samples),
+            Self::Peak { window_ms } => write!(f, "peak:{}ms", window_ms),
+            Self::Integrate { window_ms } => write!(f, "integrate:{}ms", window_ms),
+        }
+    }
+}
+
+impl FromStr for AcquisitionMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, arg) = match s.trim().split_once(':') {
+            Some((name, arg)) => (name.trim(), Some(arg.trim())),
+            None => (s.trim(), None),
+        };
+        let window_ms = || -> Result<u64> {
+            let arg = arg.ok_or_else(|| anyhow!("'{}' needs a window, e.g. {}:100ms", s, name))?;
+            let (number, scale) = if let Some(ms) = arg.strip_suffix("ms") {
+                (ms, 1)
+            } else if let Some(secs) = arg.strip_suffix('s') {
+                (secs, 1000)
+            } else {
+                (arg, 1)
+            };
+            let value: u64 = number
+                .trim()
+                .parse()
+                .map_err(|_| anyhow!("Invalid window '{}'", arg))?;
+            Ok(value * scale)
+        };
+        let mode = match name {
+            "single" => Self::Single,
+            "average" => Self::Average {
+                samples: arg
+                    .ok_or_else(|| anyhow!("'{}' needs a sample count, e.g. average:16", s))?
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid sample count in '{}'", s))?,
+            },
+            "peak" => Self::Peak {
+                window_ms: window_ms()?,
+            },
+            "integrate" => Self::Integrate {
+                window_ms: window_ms()?,
+            },
+            other => bail!("Unknown acquisition mode '{}'", other),
+        };
+        mode.validate()?;
+        Ok(mode)
+    }
+}
+
+/// A value acquired in some mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AcquiredValue {
+    pub value: f64,
+    /// Worst quality of the readings that went into the value
+    pub quality: DataQuality,
+    pub mode: AcquisitionMode,
+    /// Readings combined (1 for single reads; 0 if the driver doesn't say)
+    pub samples: u32,
+    /// Acquired by the driver rather than by [`acquire_generic`]
+    pub native: bool,
+}
+
+impl AcquiredValue {
+    pub fn single(value: f64, quality: DataQuality) -> Self {
+        Self {
+            value,
+            quality,
+            mode: AcquisitionMode::Single,
+            samples: 1,
+            native: false,
+        }
+    }
+}
+
+/// Acquire `mode` from repeated reads of `device`
+pub async fn acquire_generic<R: Readable + ?Sized>(
+    device: &R,
+    mode: AcquisitionMode,
+) -> Result<AcquiredValue> {
+    mode.validate()?;
+    let mut quality = DataQuality::Good;
+    let mut keep_worst = |q: DataQuality| {
+        if quality.is_good() {
+            quality = q;
+        }
+    };
+
+    let (value, samples) = match mode {
+        AcquisitionMode::Single => {
+            let (value, q) = device.read_qualified().await?;
+            keep_worst(q);
+            (value, 1)
+        }
+        AcquisitionMode::Average { samples } => {
+            let mut sum = 0.0;
+            for i in 0..samples {
+                if i > 0 {
+                    tokio::time::sleep(GENERIC_SAMPLE_INTERVAL).await;
+                }
+                let (value, q) = device.read_qualified().await?;
+                keep_worst(q);
+                sum += value;
+            }
+            (sum / f64::from(samples), samples)
+        }
+        AcquisitionMode::Peak { window_ms } | AcquisitionMode::Integrate { window_ms } => {
+            let window = Duration::from_millis(window_ms);
+            let start = Instant::now();
+            let mut readings = Vec::new();
+            loop {
+                let (value, q) = device.read_qualified().await?;
+                keep_worst(q);
+                let at = start.elapsed();
+                readings.push((at.as_secs_f64(), value));
+                if at >= window {
+                    break;
+                }
+                tokio::time::sleep(GENERIC_SAMPLE_INTERVAL).await;
+            }
+            let value = if matches!(mode, AcquisitionMode::Peak { .. }) {
+                readings
+                    .iter()
+                    .map(|&(_, value)| value)
+                    .fold(f64::NEG_INFINITY, f64::max)
+            } else {
+                integrate(&readings)
+            };
+            (value, u32::try_from(readings.len()).unwrap_or(u32::MAX))
+        }
+    };
+
+    Ok(AcquiredValue {
+        value,
+        quality,
+        mode,
+        samples,
+        native: false,
+    })
+}
+
+/// Trapezoidal integral of `(seconds, value)` readings
+fn integrate(readings: &[(f64, f64)]) -> f64 {
+    match readings {
+        [] => 0.0,
+        [(t, value)] => value * t,
+        _ => readings
+            .windows(2)
+            .map(|pair| (pair[1].0 - pair[0].0) * (pair[0].1 + pair[1].1) / 2.0)
+            .sum(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Reads 1, 2, 3, ... with the third reading flagged
+    struct Counter(AtomicU32);
+
+    #[async_trait]
+    impl Readable for Counter {
+        async fn read(&self) -> Result<f64> {
+            Ok(f64::from(self.0.fetch_add(1, Ordering::SeqCst) + 1))
+        }
+
+        async fn read_qualified(&self) -> Result<(f64, DataQuality)> {
+            let value = self.read().await?;
+            let quality = if value == 3.0 {
+                DataQuality::Suspect
+            } else {
+                DataQuality::Good
+            };
+            Ok((value, quality))
+        }
+    }
+
+    #[test]
+    fn test_mode_parse_and_display() {
+        for text in ["single", "average:16", "peak:250ms", "integrate:1000ms"] {
+            let mode: AcquisitionMode = text.parse().unwrap();
+            assert_eq!(mode.to_string(), text);
+        }
+        assert_eq!(
+            "integrate:2s".parse::<AcquisitionMode>().unwrap(),
+            AcquisitionMode::Integrate { window_ms: 2000 }
+        );
+        assert!("average".parse::<AcquisitionMode>().is_err());
+        assert!("average:0".parse::<AcquisitionMode>().is_err());
+        assert!("peak:0ms".parse::<AcquisitionMode>().is_err());
+        assert!("median:5".parse::<AcquisitionMode>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_generic_average_and_peak() {
+        let device = Counter(AtomicU32::new(0));
+        let avg = device
+            .read_with_mode(AcquisitionMode::Average { samples: 4 })
+            .await
+            .unwrap();
+        assert_eq!(avg.value, 2.5);
+        assert_eq!(avg.samples, 4);
+        assert_eq!(avg.quality, DataQuality::Suspect);
+        assert!(!avg.native);
+
+        let peak = device
+            .read_with_mode(AcquisitionMode::Peak { window_ms: 5 })
+            .await
+            .unwrap();
+        assert!(peak.samples >= 2);
+        assert_eq!(peak.value, f64::from(4 + peak.samples));
+        assert_eq!(peak.quality, DataQuality::Good);
+    }
+
+    #[test]
+    fn test_integrate_trapezoid() {
+        assert_eq!(integrate(&[(0.0, 2.0), (0.5, 2.0), (1.0, 4.0)]), 2.5);
+        assert_eq!(integrate(&[(0.25, 4.0)]), 1.0);
+    }
+}
@@ -0,0 +1,98 @@
+//! Cancellation of In-Flight Hardware Operations
+//!
+//! Aborting a plan or losing the client that asked for a reading must end
+//! the hardware await it is in, not leave it running until the driver's own
+//! timeout with the serial port held. Whoever owns the operation passes a
+//! [`CancellationToken`] down with it:
+//!
+//! - the `*_cancellable` capability methods
+//!   ([`Movable::move_abs_cancellable`], [`Readable::read_with_mode_cancellable`],
+//!   ...) race the operation against the token and drop the in-flight
+//!   future, releasing any port lock it holds, the moment it fires;
+//! - the motion methods also stop the axis, so a cancelled move does not
+//!   finish on its own;
+//! - drivers that can abort a transaction on the wire override them.
+//!
+//! A cancelled operation fails with [`Cancelled`]; callers that cancelled on
+//! purpose recognise it with [`is_cancelled`] and do not report a failure.
+//!
+//! ```rust,ignore
+//! let cancel = CancellationToken::new();
+//! let move_task = stage.move_abs_cancellable(10.0, &cancel);
+//! // elsewhere, on abort:
+//! cancel.cancel();
+//! ```
+//!
+//! [`Movable::move_abs_cancellable`]: crate::capabilities::Movable::move_abs_cancellable
+//! [`Readable::read_with_mode_cancellable`]: crate::capabilities::Readable::read_with_mode_cancellable
+
+use anyhow::Result;
+use std::future::Future;
+
+pub use tokio_util::sync::CancellationToken;
+
+/// Error of an operation ended by its cancellation token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Operation cancelled")]
+pub struct Cancelled;
+
+/// Run `operation` until it completes or `cancel` fires
+///
+/// A token that is already cancelled wins over an operation that is ready,
+/// so nothing new is started on hardware after an abort.
+pub async fn run_cancellable<T, F>(cancel: &CancellationToken, operation: F) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    tokio::select! {
+        biased;
+        () = cancel.cancelled() => Err(Cancelled.into()),
+        result = operation => result,
+    }
+}
+
+/// Whether an error (or any error in its context chain) is a cancellation
+pub fn is_cancelled(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| cause.is::<Cancelled>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancel_interrupts_operation() {
+        let cancel = CancellationToken::new();
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            trigger.cancel();
+        });
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(2),
+            run_cancellable(&cancel, async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                Ok(())
+            }),
+        )
+        .await
+        .expect("cancellation should end the operation");
+        let error = result.unwrap_err().context("Move of stage_x failed");
+        assert!(is_cancelled(&error));
+    }
+
+    #[tokio::test]
+    async fn test_completed_operation_and_cancelled_token() {
+        let cancel = CancellationToken::new();
+        assert_eq!(run_cancellable(&cancel, async { Ok(7) }).await.unwrap(), 7);
+
+        cancel.cancel();
+        let error = run_cancellable(&cancel, async { Ok(7) }).await.unwrap_err();
+        assert!(is_cancelled(&error));
+        assert!(!is_cancelled(
+            &anyhow::anyhow!("Timed out").context("Read failed")
+        ));
+    }
+}
@@ -79,6 +79,9 @@
 //! }
 //! ```
 
+use crate::acquisition::{acquire_generic, AcquiredValue, AcquisitionMode};
+use crate::cancellation::{is_cancelled, run_cancellable, CancellationToken};
+use crate::core::DataQuality;
 use crate::observable::ParameterSet;
 use anyhow::Result;
 use async_trait::async_trait;
@@ -217,6 +220,36 @@ pub trait Movable: Send + Sync {
     async fn stop(&self) -> Result<()> {
         anyhow::bail!("Stop not supported by this device")
     }
+
+    /// Move to absolute position, giving up when `cancel` fires
+    ///
+    /// On cancellation the in-flight command is dropped and the axis is
+    /// stopped (if the device supports [`Self::stop`]); the call then fails
+    /// with [`Cancelled`](crate::cancellation::Cancelled).
+    async fn move_abs_cancellable(&self, position: f64, cancel: &CancellationToken) -> Result<()> {
+        let result = run_cancellable(cancel, self.move_abs(position)).await;
+        stop_if_cancelled(self, &result).await;
+        result
+    }
+
+    /// Wait for motion to settle, stopping the axis when `cancel` fires
+    async fn wait_settled_cancellable(&self, cancel: &CancellationToken) -> Result<()> {
+        let result = run_cancellable(cancel, self.wait_settled()).await;
+        stop_if_cancelled(self, &result).await;
+        result
+    }
+}
+
+/// Halt an axis whose move was cancelled; a device without stop support
+/// keeps moving, which is only worth a debug line
+async fn stop_if_cancelled<M: Movable + ?Sized>(device: &M, result: &Result<()>) {
+    if let Err(e) = result {
+        if is_cancelled(e) {
+            if let Err(e) = device.stop().await {
+                tracing::debug!(error = %e, "Cancelled move not stopped");
+            }
+        }
+    }
 }
 
 /// Capability: External Triggering
@@ -261,6 +294,11 @@ pub trait Triggerable: Send + Sync {
     async fn is_armed(&self) -> Result<bool> {
         anyhow::bail!("Armed state query not supported by this device")
     }
+
+    /// Send a software trigger, giving up when `cancel` fires
+    async fn trigger_cancellable(&self, cancel: &CancellationToken) -> Result<()> {
+        run_cancellable(cancel, self.trigger()).await
+    }
 }
 
 /// Capability: Exposure Time Control
@@ -594,6 +632,17 @@ pub trait FrameProducer: Send + Sync {
         0
     }
 
+    /// Frames this driver has lost, per pipeline stage
+    ///
+    /// Counts are cumulative over the driver's lifetime (not reset per
+    /// stream), so the run engine can difference them across a run.
+    ///
+    /// # Default Implementation
+    /// Returns no counts (loss tracking not supported)
+    async fn drop_counts(&self) -> crate::integrity::DropCounts {
+        crate::integrity::DropCounts::new()
+    }
+
     // ========================================================================
     // Primary Output Registration (bd-0dax.5)
     // ========================================================================
@@ -715,6 +764,56 @@ pub trait Readable: Send + Sync {
     /// - Ok(value) on successful read
     /// - Err on hardware error or timeout
     async fn read(&self) -> Result<f64>;
+
+    /// Read current value with its quality flag
+    ///
+    /// Drivers that can tell a reading is unreliable (it only succeeded after
+    /// a timeout and retry, the meter changed range mid-sample) override this.
+    /// The default reports every successful `read()` as [`DataQuality::Good`].
+    async fn read_qualified(&self) -> Result<(f64, DataQuality)> {
+        Ok((self.read().await?, DataQuality::Good))
+    }
+
+    /// Acquire a value in `mode` on the device itself
+    ///
+    /// Drivers with hardware averaging, peak-hold or integration override
+    /// this and return `Ok(None)` for modes they don't support. The default
+    /// supports none.
+    async fn read_native(&self, mode: AcquisitionMode) -> Result<Option<AcquiredValue>> {
+        let _ = mode;
+        Ok(None)
+    }
+
+    /// Read a value acquired in `mode`
+    ///
+    /// Uses [`Self::read_native`] when the driver supports the mode, and
+    /// otherwise builds it from repeated reads with [`acquire_generic`].
+    async fn read_with_mode(&self, mode: AcquisitionMode) -> Result<AcquiredValue> {
+        if mode.is_single() {
+            let (value, quality) = self.read_qualified().await?;
+            return Ok(AcquiredValue::single(value, quality));
+        }
+        mode.validate()?;
+        match self.read_native(mode).await? {
+            Some(value) => Ok(AcquiredValue {
+                native: true,
+                ..value
+            }),
+            None => acquire_generic(self, mode).await,
+        }
+    }
+
+    /// Read a value acquired in `mode`, giving up when `cancel` fires
+    ///
+    /// Averaged and integrated acquisitions can run for seconds; cancelling
+    /// drops the acquisition between (or during) the reads it is made of.
+    async fn read_with_mode_cancellable(
+        &self,
+        mode: AcquisitionMode,
+        cancel: &CancellationToken,
+    ) -> Result<AcquiredValue> {
+        run_cancellable(cancel, self.read_with_mode(mode)).await
+    }
 }
 
 /// Capability: Wavelength Tuning
@@ -1151,6 +1250,65 @@ pub trait Commandable: Send + Sync {
     ) -> Result<serde_json::Value>;
 }
 
+/// Capability: Raw Command Passthrough
+///
+/// Devices that can send an arbitrary command string through their transport
+/// (serial port, TCP socket) and return the unparsed reply. Used by the
+/// instrument console for protocol debugging.
+///
+/// # Contract
+/// - The command is sent as typed; the driver appends its usual terminator.
+/// - With `expect_response` false the command is written and an empty string
+///   is returned without waiting for a reply.
+/// - Implementations must hold the same port lock as normal driver I/O so
+///   raw traffic never interleaves with driver commands.
+#[async_trait]
+pub trait RawTerminal: Send + Sync {
+    /// Send a raw command and return the raw (trimmed) response
+    async fn send_raw(&self, command: &str, expect_response: bool) -> Result<String>;
+}
+
+/// Capability: Named Discrete Positions
+///
+/// Devices with a fixed set of named positions (slots): filter wheels,
+/// sample changers, flip mirrors.
+///
+/// # Contract
+/// - `position_names()` lists every slot in slot order; names are unique.
+/// - `move_to_position()` returns once the device is verified to be in the
+///   slot, and errors if it ends up anywhere else.
+/// - `current_position()` is `None` while between slots or if the state
+///   matches no slot.
+#[async_trait]
+pub trait DiscretePositioner: Send + Sync {
+    /// Names of all slots, in slot order
+    fn position_names(&self) -> Vec<String>;
+
+    /// Move to a named slot and verify it was reached
+    ///
+    /// # Returns
+    /// - Ok(()) once the device is in the slot
+    /// - Err if the name is unknown or the slot was not reached
+    async fn move_to_position(&self, name: &str) -> Result<()>;
+
+    /// Move to a named slot, giving up when `cancel` fires
+    async fn move_to_position_cancellable(
+        &self,
+        name: &str,
+        cancel: &CancellationToken,
+    ) -> Result<()> {
+        run_cancellable(cancel, self.move_to_position(name)).await
+    }
+
+    /// Name of the slot the device is in
+    async fn current_position(&self) -> Result<Option<String>>;
+
+    /// Index of a slot in [`position_names`](Self::position_names)
+    fn position_index(&self, name: &str) -> Option<usize> {
+        self.position_names().iter().position(|n| n == name)
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::float_cmp)]
 mod tests {
@@ -1203,6 +1361,59 @@ mod tests {
         stage.wait_settled().await.unwrap();
     }
 
+    struct StuckStage {
+        stopped: std::sync::atomic::AtomicBool,
+    }
+
+    #[async_trait]
+    impl Movable for StuckStage {
+        async fn move_abs(&self, _position: f64) -> Result<()> {
+            // A move that only ends at the driver's (long) timeout
+            tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+            Ok(())
+        }
+
+        async fn move_rel(&self, distance: f64) -> Result<()> {
+            self.move_abs(distance).await
+        }
+
+        async fn position(&self) -> Result<f64> {
+            Ok(0.0)
+        }
+
+        async fn wait_settled(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn stop(&self) -> Result<()> {
+            self.stopped
+                .store(true, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_move_stops_axis() {
+        let stage = StuckStage {
+            stopped: std::sync::atomic::AtomicBool::new(false),
+        };
+        let cancel = CancellationToken::new();
+        let abort = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            abort.cancel();
+        });
+
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(2),
+            stage.move_abs_cancellable(5.0, &cancel),
+        )
+        .await
+        .expect("cancellation should end the move");
+        assert!(is_cancelled(&result.unwrap_err()));
+        assert!(stage.stopped.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
     struct MockPowerMeter;
 
     #[async_trait]
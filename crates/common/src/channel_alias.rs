@@ -0,0 +1,266 @@
+//! Channel aliasing for experiment-meaningful names.
+//!
+//! Aliases let a channel be published under a stable, experiment-level name
+//! (`sample_temp`) that maps to a concrete hardware channel
+//! (`lakeshore1:inputA`). Plans, scripts and analysis code refer to the alias,
+//! so replacing or renaming hardware only requires a config change.
+//!
+//! Targets are either a device ID (`stage_x`) or a device ID plus parameter
+//! name separated by `:` (`lakeshore1:inputA`).
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [aliases]
+//! sample_temp = "lakeshore1:inputA"
+//! sample_x = "esp300_axis1"
+//! ```
+
+use crate::error::DaqError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Separator between device ID and parameter name in a channel target
+pub const CHANNEL_SEPARATOR: char = ':';
+
+/// A concrete hardware channel: a device and optionally one of its parameters
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChannelRef {
+    /// Registered device ID
+    pub device_id: String,
+    /// Parameter name for parameter-level channels
+    pub parameter: Option<String>,
+}
+
+impl ChannelRef {
+    /// Parse `device` or `device:parameter`
+    pub fn parse(target: &str) -> Self {
+        match target.split_once(CHANNEL_SEPARATOR) {
+            Some((device, parameter)) if !parameter.is_empty() => Self {
+                device_id: device.to_string(),
+                parameter: Some(parameter.to_string()),
+            },
+            Some((device, _)) => Self {
+                device_id: device.to_string(),
+                parameter: None,
+            },
+            None => Self {
+                device_id: target.to_string(),
+                parameter: None,
+            },
+        }
+    }
+}
+
+impl fmt::Display for ChannelRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.parameter {
+            Some(parameter) => write!(f, "{}{}{}", self.device_id, CHANNEL_SEPARATOR, parameter),
+            None => write!(f, "{}", self.device_id),
+        }
+    }
+}
+
+/// Alias table mapping experiment names to hardware channels
+///
+/// Serializes as a flat `alias = "target"` table. Deserialization applies
+/// the same checks as [`insert`](Self::insert).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    into = "BTreeMap<String, String>",
+    try_from = "BTreeMap<String, String>"
+)]
+pub struct ChannelAliases {
+    aliases: BTreeMap<String, String>,
+}
+
+impl ChannelAliases {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an alias, rejecting empty names, self-references and chained aliases
+    pub fn insert(&mut self, alias: &str, target: &str) -> Result<(), DaqError> {
+        if alias.is_empty() || alias.contains(CHANNEL_SEPARATOR) {
+            return Err(DaqError::Configuration(format!(
+                "Invalid channel alias '{}': must be non-empty and must not contain '{}'",
+                alias, CHANNEL_SEPARATOR
+            )));
+        }
+        let target_ref = ChannelRef::parse(target);
+        if target_ref.device_id.is_empty() {
+            return Err(DaqError::Configuration(format!(
+                "Channel alias '{}' has an empty target",
+                alias
+            )));
+        }
+        if target_ref.device_id == alias {
+            return Err(DaqError::Configuration(format!(
+                "Channel alias '{}' refers to itself",
+                alias
+            )));
+        }
+        if self.aliases.contains_key(&target_ref.device_id) {
+            return Err(DaqError::Configuration(format!(
+                "Channel alias '{}' targets another alias '{}'; aliases cannot be chained",
+                alias, target_ref.device_id
+            )));
+        }
+        if let Some(chained) = self
+            .aliases
+            .iter()
+            .find(|(_, t)| ChannelRef::parse(t).device_id == alias)
+        {
+            return Err(DaqError::Configuration(format!(
+                "Channel alias '{}' is already used as the target of alias '{}'",
+                alias, chained.0
+            )));
+        }
+        self.aliases.insert(alias.to_string(), target.to_string());
+        Ok(())
+    }
+
+    /// Builder-style [`insert`](Self::insert)
+    pub fn with_alias(mut self, alias: &str, target: &str) -> Result<Self, DaqError> {
+        self.insert(alias, target)?;
+        Ok(self)
+    }
+
+    /// Remove an alias, returning its target
+    pub fn remove(&mut self, alias: &str) -> Option<String> {
+        self.aliases.remove(alias)
+    }
+
+    /// Target for an alias, if `name` is one
+    pub fn target(&self, name: &str) -> Option<&str> {
+        self.aliases.get(name).map(String::as_str)
+    }
+
+    /// Resolve a name to its hardware channel; non-aliases pass through unchanged
+    pub fn resolve(&self, name: &str) -> ChannelRef {
+        ChannelRef::parse(self.target(name).unwrap_or(name))
+    }
+
+    /// All aliases whose target is `target` (exact match, e.g. `lakeshore1:inputA`)
+    pub fn aliases_for<'a>(&'a self, target: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.aliases
+            .iter()
+            .filter(move |(_, t)| t.as_str() == target)
+            .map(|(alias, _)| alias.as_str())
+    }
+
+    /// All aliases whose target is on `device_id` (including parameter-level targets)
+    pub fn aliases_for_device<'a>(
+        &'a self,
+        device_id: &'a str,
+    ) -> impl Iterator<Item = &'a str> + 'a {
+        self.aliases
+            .iter()
+            .filter(move |(_, t)| ChannelRef::parse(t).device_id == device_id)
+            .map(|(alias, _)| alias.as_str())
+    }
+
+    /// Check that no alias shadows a device ID and report aliases whose target
+    /// device is not in `device_ids`
+    ///
+    /// Shadowing is an error; dangling targets are returned so callers can warn
+    /// (the device may simply have failed to register).
+    pub fn validate<'a>(
+        &self,
+        device_ids: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Vec<String>, DaqError> {
+        let devices: std::collections::HashSet<&str> = device_ids.into_iter().collect();
+        if let Some(alias) = self.aliases.keys().find(|a| devices.contains(a.as_str())) {
+            return Err(DaqError::Configuration(format!(
+                "Channel alias '{}' shadows a registered device ID",
+                alias
+            )));
+        }
+        Ok(self
+            .aliases
+            .iter()
+            .filter(|(_, t)| !devices.contains(ChannelRef::parse(t).device_id.as_str()))
+            .map(|(alias, _)| alias.clone())
+            .collect())
+    }
+
+    /// Iterate `(alias, target)` pairs in alias order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.aliases.iter().map(|(a, t)| (a.as_str(), t.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.aliases.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty()
+    }
+}
+
+impl TryFrom<BTreeMap<String, String>> for ChannelAliases {
+    type Error = DaqError;
+
+    fn try_from(map: BTreeMap<String, String>) -> Result<Self, Self::Error> {
+        let mut aliases = Self::new();
+        for (alias, target) in &map {
+            aliases.insert(alias, target)?;
+        }
+        Ok(aliases)
+    }
+}
+
+impl From<ChannelAliases> for BTreeMap<String, String> {
+    fn from(aliases: ChannelAliases) -> Self {
+        aliases.aliases
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_alias_and_passthrough() {
+        let aliases = ChannelAliases::new()
+            .with_alias("sample_temp", "lakeshore1:inputA")
+            .unwrap()
+            .with_alias("sample_x", "stage_x")
+            .unwrap();
+
+        let temp = aliases.resolve("sample_temp");
+        assert_eq!(temp.device_id, "lakeshore1");
+        assert_eq!(temp.parameter.as_deref(), Some("inputA"));
+        assert_eq!(temp.to_string(), "lakeshore1:inputA");
+
+        assert_eq!(aliases.resolve("sample_x").device_id, "stage_x");
+        assert_eq!(aliases.resolve("stage_y").device_id, "stage_y");
+        assert_eq!(
+            aliases.aliases_for_device("lakeshore1").collect::<Vec<_>>(),
+            vec!["sample_temp"]
+        );
+    }
+
+    #[test]
+    fn test_reject_chained_and_shadowing_aliases() {
+        let mut aliases = ChannelAliases::new();
+        aliases.insert("sample_x", "stage_x").unwrap();
+        assert!(aliases.insert("x", "sample_x").is_err());
+        assert!(aliases.insert("stage_x", "stage_y").is_err());
+        assert!(aliases.insert("bad:name", "stage_y").is_err());
+
+        assert!(aliases.validate(["sample_x"]).is_err());
+        assert_eq!(
+            aliases.validate(["stage_y"]).unwrap(),
+            vec!["sample_x".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_toml_table() {
+        let aliases: ChannelAliases =
+            toml::from_str("sample_temp = \"lakeshore1:inputA\"").unwrap();
+        assert_eq!(aliases.target("sample_temp"), Some("lakeshore1:inputA"));
+    }
+}
@@ -0,0 +1,280 @@
+//! Cleanup sequences run when a run is aborted or fails.
+//!
+//! An aborted scan should not leave a shutter open, a stage out at the end
+//! of its travel or a camera armed. A plan declares a [`CleanupPlan`]: an
+//! ordered list of device actions that the RunEngine runs after the run
+//! stops for any reason other than success. The sequence has its own
+//! timeout, so a hung device cannot keep the engine from finishing the run.
+//!
+//! Every step is attempted, even after an earlier one fails. Steps not
+//! started when the timeout expires are reported as not run. The outcome is
+//! stored as a [`CleanupReport`] in JSON in the stop document metadata under
+//! [`CLEANUP_METADATA_KEY`].
+//!
+//! Cleanup runs after parking (see [`crate::parking`]), so its actions have
+//! the last word on the state the hardware is left in.
+//!
+//! # Declaring cleanup for a queued run
+//!
+//! Plans built in code override `Plan::cleanup` in the experiment crate. Any
+//! queued run can also carry a cleanup plan as JSON in its metadata under
+//! [`CLEANUP_PLAN_METADATA_KEY`], which replaces the plan's own:
+//!
+//! ```json
+//! {
+//!   "timeout_s": 20,
+//!   "steps": [
+//!     { "device": "shutter", "action": "close_shutter" },
+//!     { "device": "stage_x", "action": "move_to", "position": 0.0 },
+//!     { "device": "camera", "action": "unstage" }
+//!   ]
+//! }
+//! ```
+
+use crate::parking::ParkAction;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Stop document metadata key holding the run's [`CleanupReport`]
+pub const CLEANUP_METADATA_KEY: &str = "cleanup";
+
+/// Run metadata key holding a [`CleanupPlan`] as JSON
+pub const CLEANUP_PLAN_METADATA_KEY: &str = "cleanup_plan";
+
+/// Time allowed for a whole cleanup sequence unless the plan sets one
+pub const DEFAULT_CLEANUP_TIMEOUT_S: f64 = 30.0;
+
+/// Longest timeout a cleanup sequence may ask for
+pub const MAX_CLEANUP_TIMEOUT_S: f64 = 3600.0;
+
+fn default_timeout_s() -> f64 {
+    DEFAULT_CLEANUP_TIMEOUT_S
+}
+
+/// One action applied to one device during cleanup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CleanupStep {
+    /// Device ID (or channel alias)
+    pub device: String,
+    #[serde(flatten)]
+    pub action: ParkAction,
+}
+
+impl fmt::Display for CleanupStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.device, self.action)
+    }
+}
+
+/// Ordered device actions that leave the hardware safe after an abort or failure
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CleanupPlan {
+    /// Applied in order
+    #[serde(default)]
+    pub steps: Vec<CleanupStep>,
+    /// Time allowed for the whole sequence
+    #[serde(default = "default_timeout_s")]
+    pub timeout_s: f64,
+}
+
+impl Default for CleanupPlan {
+    fn default() -> Self {
+        Self {
+            steps: Vec::new(),
+            timeout_s: DEFAULT_CLEANUP_TIMEOUT_S,
+        }
+    }
+}
+
+impl CleanupPlan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a step
+    pub fn with_step(mut self, device: &str, action: ParkAction) -> Self {
+        self.steps.push(CleanupStep {
+            device: device.to_string(),
+            action,
+        });
+        self
+    }
+
+    /// Set the time allowed for the whole sequence
+    pub fn with_timeout(mut self, timeout_s: f64) -> Self {
+        self.timeout_s = timeout_s;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if !(self.timeout_s > 0.0 && self.timeout_s <= MAX_CLEANUP_TIMEOUT_S) {
+            bail!(
+                "Cleanup timeout must be between 0 and {} s, got {}",
+                MAX_CLEANUP_TIMEOUT_S,
+                self.timeout_s
+            );
+        }
+        if let Some(step) = self.steps.iter().find(|step| step.device.is_empty()) {
+            bail!("Cleanup step '{}' has no device", step.action);
+        }
+        Ok(())
+    }
+
+    /// The cleanup plan in queued run metadata, if there is one
+    pub fn from_metadata(metadata: &HashMap<String, String>) -> Result<Option<Self>> {
+        let Some(json) = metadata.get(CLEANUP_PLAN_METADATA_KEY) else {
+            return Ok(None);
+        };
+        let plan: Self = serde_json::from_str(json)
+            .with_context(|| format!("Invalid metadata '{}'", CLEANUP_PLAN_METADATA_KEY))?;
+        plan.validate()?;
+        Ok(Some(plan))
+    }
+
+    /// Encode for [`CLEANUP_PLAN_METADATA_KEY`]
+    pub fn to_metadata_value(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// What happened to one cleanup step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum CleanupStepStatus {
+    Done,
+    Failed {
+        error: String,
+    },
+    /// The timeout expired before the step started
+    NotRun,
+}
+
+/// Outcome of one cleanup step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CleanupStepResult {
+    pub device: String,
+    /// The action, as text
+    pub action: String,
+    #[serde(flatten)]
+    pub status: CleanupStepStatus,
+}
+
+/// Outcome of a cleanup sequence, recorded in the stop document
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CleanupReport {
+    /// Exit status of the run that triggered the cleanup ("abort" or "fail")
+    pub trigger: String,
+    pub steps: Vec<CleanupStepResult>,
+    /// The sequence did not finish within its timeout
+    pub timed_out: bool,
+    pub elapsed_s: f64,
+}
+
+impl CleanupReport {
+    pub fn new(trigger: &str) -> Self {
+        Self {
+            trigger: trigger.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn record(&mut self, step: &CleanupStep, status: CleanupStepStatus) {
+        self.steps.push(CleanupStepResult {
+            device: step.device.clone(),
+            action: step.action.to_string(),
+            status,
+        });
+    }
+
+    /// Every step ran and succeeded
+    pub fn is_clean(&self) -> bool {
+        !self.timed_out
+            && self
+                .steps
+                .iter()
+                .all(|step| step.status == CleanupStepStatus::Done)
+    }
+
+    /// Steps that failed or did not run
+    pub fn problems(&self) -> impl Iterator<Item = &CleanupStepResult> {
+        self.steps
+            .iter()
+            .filter(|step| step.status != CleanupStepStatus::Done)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_from_metadata() {
+        let metadata = HashMap::from([(
+            CLEANUP_PLAN_METADATA_KEY.to_string(),
+            r#"{"timeout_s": 5, "steps": [
+                {"device": "shutter", "action": "close_shutter"},
+                {"device": "stage_x", "action": "move_to", "position": 1.5}
+            ]}"#
+            .to_string(),
+        )]);
+        let plan = CleanupPlan::from_metadata(&metadata).unwrap().unwrap();
+        assert_eq!(
+            plan,
+            CleanupPlan::new()
+                .with_step("shutter", ParkAction::CloseShutter)
+                .with_step("stage_x", ParkAction::MoveTo { position: 1.5 })
+                .with_timeout(5.0)
+        );
+
+        let round_trip = HashMap::from([(
+            CLEANUP_PLAN_METADATA_KEY.to_string(),
+            plan.to_metadata_value(),
+        )]);
+        assert_eq!(CleanupPlan::from_metadata(&round_trip).unwrap(), Some(plan));
+        assert_eq!(CleanupPlan::from_metadata(&HashMap::new()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_plan_rejects_bad_metadata() {
+        for json in [
+            r#"{"steps": [{"device": "shutter", "action": "explode"}]}"#,
+            r#"{"steps": [], "timeout_s": 0}"#,
+            r#"{"steps": [{"device": "", "action": "unstage"}]}"#,
+        ] {
+            let metadata =
+                HashMap::from([(CLEANUP_PLAN_METADATA_KEY.to_string(), json.to_string())]);
+            assert!(CleanupPlan::from_metadata(&metadata).is_err(), "{}", json);
+        }
+    }
+
+    #[test]
+    fn test_report_serializes_step_status() {
+        let step = CleanupStep {
+            device: "stage_x".to_string(),
+            action: ParkAction::MoveTo { position: 0.0 },
+        };
+        let mut report = CleanupReport::new("abort");
+        report.record(&step, CleanupStepStatus::Done);
+        report.record(
+            &step,
+            CleanupStepStatus::Failed {
+                error: "limit switch".to_string(),
+            },
+        );
+        assert!(!report.is_clean());
+        assert_eq!(report.problems().count(), 1);
+
+        let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["steps"][0]["status"], "done");
+        assert_eq!(json["steps"][1]["status"], "failed");
+        assert_eq!(json["steps"][1]["error"], "limit switch");
+        assert_eq!(json["steps"][1]["action"], "move to 0");
+    }
+}
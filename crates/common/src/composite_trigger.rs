@@ -0,0 +1,773 @@
+//! Composite triggers: acquisitions gated on combined conditions.
+//!
+//! A [`CompositeTrigger`] is a [`Triggerable`] device whose `trigger()` waits
+//! until a [`TriggerCondition`] holds and then triggers its target device
+//! (a camera, a digitizer). Conditions combine, with `all` (AND) and `any`
+//! (OR):
+//!
+//! - `digital_edge`: a digital input changed state;
+//! - `threshold`: a channel is above and/or below a value;
+//! - `software`: a software signal, raised through the trigger's `signal.*`
+//!   parameters, was pulsed or is held;
+//! - `time_window`: the wait has lasted between `start_s` and `end_s`,
+//!   optionally repeating every `period_s`.
+//!
+//! ```toml
+//! [[composite_triggers]]
+//! id = "gated_camera"
+//! target = "camera"
+//! timeout_s = 5.0
+//! condition = { type = "all", conditions = [
+//!     { type = "threshold", channel = "shutter:open", above = 0.5 },
+//!     { type = "digital_edge", channel = "chopper_ref", edge = "rising" },
+//! ] }
+//! ```
+//!
+//! Channels are device IDs, channel aliases or `device:parameter` channels,
+//! read as numbers (booleans read as 0 or 1). All channels are sampled once
+//! per `poll_interval_ms` and the condition is evaluated on each sample.
+//! Edges are therefore seen at the polling resolution: an input pulse
+//! shorter than the interval can be missed, so hardware edges that must
+//! not be missed belong in the target's own trigger input.
+//!
+//! The condition tree is compiled into a flat [`TriggerEvaluator`]: channels
+//! and signals are resolved to slots once, and each sample is evaluated
+//! without allocating.
+
+use crate::capabilities::{Parameterized, Readable, Settable, Triggerable};
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Prefix of the Settable parameters that drive software signals
+pub const SIGNAL_PARAMETER_PREFIX: &str = "signal.";
+
+fn default_level() -> f64 {
+    0.5
+}
+
+fn default_poll_interval_ms() -> u64 {
+    5
+}
+
+fn default_timeout_s() -> f64 {
+    10.0
+}
+
+/// Direction of a digital edge
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Edge {
+    #[default]
+    Rising,
+    Falling,
+    Either,
+}
+
+impl Edge {
+    fn matches(self, was_high: bool, is_high: bool) -> bool {
+        match self {
+            Self::Rising => !was_high && is_high,
+            Self::Falling => was_high && !is_high,
+            Self::Either => was_high != is_high,
+        }
+    }
+}
+
+/// A condition a composite trigger waits for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum TriggerCondition {
+    /// A digital input changed state since the previous sample
+    DigitalEdge {
+        channel: String,
+        #[serde(default)]
+        edge: Edge,
+        /// Values at or above this level are high
+        #[serde(default = "default_level")]
+        level: f64,
+        /// How long the edge keeps the condition true, for combining with
+        /// conditions that become true later (0: only the sample it is seen)
+        #[serde(default)]
+        hold_s: f64,
+    },
+    /// A channel value is within the given bounds
+    Threshold {
+        channel: String,
+        #[serde(default)]
+        above: Option<f64>,
+        #[serde(default)]
+        below: Option<f64>,
+    },
+    /// A software signal is held, or was pulsed within `hold_s`
+    Software {
+        signal: String,
+        #[serde(default)]
+        hold_s: f64,
+    },
+    /// Time since the wait started, optionally repeating every `period_s`
+    TimeWindow {
+        start_s: f64,
+        end_s: f64,
+        #[serde(default)]
+        period_s: Option<f64>,
+    },
+    /// Every condition holds
+    All { conditions: Vec<TriggerCondition> },
+    /// At least one condition holds
+    Any { conditions: Vec<TriggerCondition> },
+}
+
+/// Configuration of a composite trigger (`[[composite_triggers]]` in the hardware config)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CompositeTriggerConfig {
+    /// Device ID the trigger is registered under
+    pub id: String,
+    /// Display name
+    #[serde(default)]
+    pub name: String,
+    /// Triggerable device fired when the condition holds; without one the
+    /// trigger only gates (its `trigger()` returns when the condition holds)
+    #[serde(default)]
+    pub target: Option<String>,
+    pub condition: TriggerCondition,
+    /// Sampling interval of the condition's channels
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    /// Longest a trigger waits for the condition
+    #[serde(default = "default_timeout_s")]
+    pub timeout_s: f64,
+}
+
+impl CompositeTriggerConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.poll_interval_ms == 0 {
+            bail!(
+                "Composite trigger '{}': poll_interval_ms must be positive",
+                self.id
+            );
+        }
+        if !(self.timeout_s > 0.0 && self.timeout_s.is_finite()) {
+            bail!(
+                "Composite trigger '{}': invalid timeout {}",
+                self.id,
+                self.timeout_s
+            );
+        }
+        TriggerEvaluator::compile(&self.condition)
+            .with_context(|| format!("Composite trigger '{}'", self.id))?;
+        Ok(())
+    }
+}
+
+/// State of a software signal
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SignalState {
+    /// Number of times the signal was pulsed
+    pub pulses: u64,
+    /// The signal is held on
+    pub held: bool,
+}
+
+/// One step of a compiled condition, in postfix order
+#[derive(Debug, Clone)]
+enum Op {
+    Edge {
+        slot: usize,
+        edge: Edge,
+        level: f64,
+        hold_s: f64,
+        memory: usize,
+    },
+    Threshold {
+        slot: usize,
+        above: Option<f64>,
+        below: Option<f64>,
+    },
+    Signal {
+        slot: usize,
+        hold_s: f64,
+        memory: usize,
+    },
+    Window {
+        start_s: f64,
+        end_s: f64,
+        period_s: Option<f64>,
+    },
+    /// Pop `n` results, push whether all are true
+    All(usize),
+    /// Pop `n` results, push whether any is true
+    Any(usize),
+}
+
+/// Per-op memory of edge and signal conditions
+#[derive(Debug, Clone, Copy, Default)]
+struct Memory {
+    /// Previous level, or pulse count, once a sample has been seen
+    previous: Option<f64>,
+    /// When the condition last fired
+    fired_at: Option<f64>,
+}
+
+/// A [`TriggerCondition`] compiled for repeated evaluation
+#[derive(Debug, Clone)]
+pub struct TriggerEvaluator {
+    ops: Vec<Op>,
+    channels: Vec<String>,
+    signals: Vec<String>,
+    memory: Vec<Memory>,
+    stack: Vec<bool>,
+}
+
+impl TriggerEvaluator {
+    /// Compile a condition, checking its parameters
+    pub fn compile(condition: &TriggerCondition) -> Result<Self> {
+        let mut evaluator = Self {
+            ops: Vec::new(),
+            channels: Vec::new(),
+            signals: Vec::new(),
+            memory: Vec::new(),
+            stack: Vec::new(),
+        };
+        evaluator.push(condition)?;
+        evaluator.stack = Vec::with_capacity(evaluator.ops.len());
+        Ok(evaluator)
+    }
+
+    fn push(&mut self, condition: &TriggerCondition) -> Result<()> {
+        let op = match condition {
+            TriggerCondition::DigitalEdge {
+                channel,
+                edge,
+                level,
+                hold_s,
+            } => {
+                if !level.is_finite() {
+                    bail!("Edge on '{}' has invalid level {}", channel, level);
+                }
+                Op::Edge {
+                    slot: slot(&mut self.channels, channel)?,
+                    edge: *edge,
+                    level: *level,
+                    hold_s: hold(*hold_s)?,
+                    memory: self.new_memory(),
+                }
+            }
+            TriggerCondition::Threshold {
+                channel,
+                above,
+                below,
+            } => {
+                if above.is_none() && below.is_none() {
+                    bail!("Threshold on '{}' needs `above` or `below`", channel);
+                }
+                if above.is_some_and(|v| !v.is_finite()) || below.is_some_and(|v| !v.is_finite()) {
+                    bail!("Threshold on '{}' has an invalid bound", channel);
+                }
+                Op::Threshold {
+                    slot: slot(&mut self.channels, channel)?,
+                    above: *above,
+                    below: *below,
+                }
+            }
+            TriggerCondition::Software { signal, hold_s } => Op::Signal {
+                slot: slot(&mut self.signals, signal)?,
+                hold_s: hold(*hold_s)?,
+                memory: self.new_memory(),
+            },
+            TriggerCondition::TimeWindow {
+                start_s,
+                end_s,
+                period_s,
+            } => {
+                if !(start_s.is_finite() && end_s.is_finite() && *start_s >= 0.0 && end_s > start_s)
+                {
+                    bail!("Time window needs 0 <= start_s < end_s");
+                }
+                if period_s.is_some_and(|p| !(p.is_finite() && p >= *end_s)) {
+                    bail!("Time window period must be at least end_s");
+                }
+                Op::Window {
+                    start_s: *start_s,
+                    end_s: *end_s,
+                    period_s: *period_s,
+                }
+            }
+            TriggerCondition::All { conditions } | TriggerCondition::Any { conditions } => {
+                if conditions.is_empty() {
+                    bail!("`all` and `any` need at least one condition");
+                }
+                for child in conditions {
+                    self.push(child)?;
+                }
+                if matches!(condition, TriggerCondition::All { .. }) {
+                    Op::All(conditions.len())
+                } else {
+                    Op::Any(conditions.len())
+                }
+            }
+        };
+        self.ops.push(op);
+        Ok(())
+    }
+
+    fn new_memory(&mut self) -> usize {
+        self.memory.push(Memory::default());
+        self.memory.len() - 1
+    }
+
+    /// Channels to sample, in slot order
+    pub fn channels(&self) -> &[String] {
+        &self.channels
+    }
+
+    /// Software signals the condition uses, in slot order
+    pub fn signals(&self) -> &[String] {
+        &self.signals
+    }
+
+    /// Forget edges and pulses seen so far; the next sample is a baseline
+    pub fn reset(&mut self) {
+        self.memory.fill(Memory::default());
+    }
+
+    /// Evaluate one sample
+    ///
+    /// `channels` and `signals` are in slot order; `elapsed_s` is the time
+    /// since the wait started. Every op is evaluated (no short-circuit) so
+    /// edge and signal memories follow every sample.
+    pub fn evaluate(&mut self, elapsed_s: f64, channels: &[f64], signals: &[SignalState]) -> bool {
+        self.stack.clear();
+        for op in &self.ops {
+            let result = match *op {
+                Op::Edge {
+                    slot,
+                    edge,
+                    level,
+                    hold_s,
+                    memory,
+                } => {
+                    let value = channels[slot];
+                    let memory = &mut self.memory[memory];
+                    let is_high = value >= level;
+                    if let Some(previous) = memory.previous {
+                        if edge.matches(previous >= level, is_high) {
+                            memory.fired_at = Some(elapsed_s);
+                        }
+                    }
+                    memory.previous = Some(value);
+                    held(memory.fired_at, elapsed_s, hold_s)
+                }
+                Op::Threshold { slot, above, below } => {
+                    let value = channels[slot];
+                    above.is_none_or(|a| value > a) && below.is_none_or(|b| value < b)
+                }
+                Op::Signal {
+                    slot,
+                    hold_s,
+                    memory,
+                } => {
+                    let state = signals[slot];
+                    let memory = &mut self.memory[memory];
+                    let pulses = state.pulses as f64;
+                    if memory.previous.is_some_and(|previous| pulses > previous) {
+                        memory.fired_at = Some(elapsed_s);
+                    }
+                    memory.previous = Some(pulses);
+                    state.held || held(memory.fired_at, elapsed_s, hold_s)
+                }
+                Op::Window {
+                    start_s,
+                    end_s,
+                    period_s,
+                } => {
+                    let t = match period_s {
+                        Some(period) => elapsed_s % period,
+                        None => elapsed_s,
+                    };
+                    t >= start_s && t < end_s
+                }
+                Op::All(n) => {
+                    let start = self.stack.len() - n;
+                    let all = self.stack[start..].iter().all(|&v| v);
+                    self.stack.truncate(start);
+                    all
+                }
+                Op::Any(n) => {
+                    let start = self.stack.len() - n;
+                    let any = self.stack[start..].iter().any(|&v| v);
+                    self.stack.truncate(start);
+                    any
+                }
+            };
+            self.stack.push(result);
+        }
+        self.stack.pop().unwrap_or(false)
+    }
+}
+
+fn slot(names: &mut Vec<String>, name: &str) -> Result<usize> {
+    if name.is_empty() {
+        bail!("Condition has an empty channel or signal name");
+    }
+    Ok(match names.iter().position(|n| n == name) {
+        Some(slot) => slot,
+        None => {
+            names.push(name.to_string());
+            names.len() - 1
+        }
+    })
+}
+
+fn hold(hold_s: f64) -> Result<f64> {
+    if !(hold_s.is_finite() && hold_s >= 0.0) {
+        bail!("Invalid hold_s {}", hold_s);
+    }
+    Ok(hold_s)
+}
+
+/// Whether an event at `fired_at` still holds at `now`
+fn held(fired_at: Option<f64>, now: f64, hold_s: f64) -> bool {
+    fired_at.is_some_and(|t| now - t <= hold_s)
+}
+
+/// Where a condition channel is read from
+#[derive(Clone)]
+pub enum TriggerInput {
+    Readable(Arc<dyn Readable>),
+    /// A numeric or boolean parameter of a device
+    Parameter(Arc<dyn Parameterized>, String),
+}
+
+impl TriggerInput {
+    async fn read(&self) -> Result<f64> {
+        match self {
+            Self::Readable(readable) => readable.read().await,
+            Self::Parameter(device, name) => {
+                let value = device
+                    .parameters()
+                    .get(name)
+                    .ok_or_else(|| anyhow!("Unknown parameter '{}'", name))?
+                    .get_json()?;
+                match value {
+                    Value::Bool(b) => Ok(if b { 1.0 } else { 0.0 }),
+                    other => other
+                        .as_f64()
+                        .ok_or_else(|| anyhow!("Parameter '{}' is not numeric", name)),
+                }
+            }
+        }
+    }
+}
+
+/// [`Triggerable`] that fires its target once a condition holds
+pub struct CompositeTrigger {
+    config: CompositeTriggerConfig,
+    evaluator: Mutex<TriggerEvaluator>,
+    inputs: Vec<TriggerInput>,
+    signals: Mutex<HashMap<String, SignalState>>,
+    target: Option<Arc<dyn Triggerable>>,
+    armed: AtomicBool,
+}
+
+impl CompositeTrigger {
+    /// `inputs` maps every channel of the condition to its source
+    pub fn new(
+        config: CompositeTriggerConfig,
+        mut inputs: HashMap<String, TriggerInput>,
+        target: Option<Arc<dyn Triggerable>>,
+    ) -> Result<Self> {
+        config.validate()?;
+        let evaluator = TriggerEvaluator::compile(&config.condition)?;
+        let inputs = evaluator
+            .channels()
+            .iter()
+            .map(|channel| {
+                inputs
+                    .remove(channel)
+                    .ok_or_else(|| anyhow!("No input for channel '{}'", channel))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if config.target.is_some() != target.is_some() {
+            bail!("Composite trigger '{}': target device missing", config.id);
+        }
+        let signals = evaluator
+            .signals()
+            .iter()
+            .map(|name| (name.clone(), SignalState::default()))
+            .collect();
+        Ok(Self {
+            config,
+            evaluator: Mutex::new(evaluator),
+            inputs,
+            signals: Mutex::new(signals),
+            target,
+            armed: AtomicBool::new(false),
+        })
+    }
+
+    pub fn config(&self) -> &CompositeTriggerConfig {
+        &self.config
+    }
+
+    /// Pulse a software signal
+    pub fn pulse(&self, signal: &str) -> Result<()> {
+        self.update_signal(signal, |state| state.pulses += 1)
+    }
+
+    /// Hold a software signal on or release it
+    pub fn hold(&self, signal: &str, on: bool) -> Result<()> {
+        self.update_signal(signal, |state| state.held = on)
+    }
+
+    fn update_signal(&self, signal: &str, update: impl FnOnce(&mut SignalState)) -> Result<()> {
+        let mut signals = self.signals.lock().unwrap_or_else(|p| p.into_inner());
+        let state = signals.get_mut(signal).ok_or_else(|| {
+            anyhow!(
+                "Composite trigger '{}' has no signal '{}'",
+                self.config.id,
+                signal
+            )
+        })?;
+        update(state);
+        Ok(())
+    }
+
+    /// Wait until the condition holds, returning how long that took
+    pub async fn wait(&self) -> Result<Duration> {
+        let interval = Duration::from_millis(self.config.poll_interval_ms);
+        let timeout = Duration::from_secs_f64(self.config.timeout_s);
+        self.evaluator
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .reset();
+
+        let started = Instant::now();
+        let mut values = vec![0.0; self.inputs.len()];
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            for (value, input) in values.iter_mut().zip(&self.inputs) {
+                *value = input.read().await?;
+            }
+            let signals: Vec<SignalState> = {
+                let states = self.signals.lock().unwrap_or_else(|p| p.into_inner());
+                let evaluator = self.evaluator.lock().unwrap_or_else(|p| p.into_inner());
+                evaluator
+                    .signals()
+                    .iter()
+                    .map(|name| states.get(name).copied().unwrap_or_default())
+                    .collect()
+            };
+            let elapsed = started.elapsed();
+            let open = self
+                .evaluator
+                .lock()
+                .unwrap_or_else(|p| p.into_inner())
+                .evaluate(elapsed.as_secs_f64(), &values, &signals);
+            if open {
+                return Ok(elapsed);
+            }
+            if elapsed >= timeout {
+                bail!(
+                    "Composite trigger '{}': condition not met within {} s",
+                    self.config.id,
+                    self.config.timeout_s
+                );
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Triggerable for CompositeTrigger {
+    async fn arm(&self) -> Result<()> {
+        if let Some(target) = &self.target {
+            target.arm().await?;
+        }
+        self.armed.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn trigger(&self) -> Result<()> {
+        let waited = self.wait().await?;
+        tracing::debug!(
+            trigger = %self.config.id,
+            waited_ms = waited.as_millis() as u64,
+            "Composite trigger condition met"
+        );
+        if let Some(target) = &self.target {
+            target.trigger().await?;
+        }
+        Ok(())
+    }
+
+    async fn is_armed(&self) -> Result<bool> {
+        Ok(self.armed.load(Ordering::SeqCst))
+    }
+}
+
+/// Software signals as parameters: `signal.<name>` set to `true` holds the
+/// signal, `false` releases it, and `"pulse"` pulses it
+#[async_trait]
+impl Settable for CompositeTrigger {
+    async fn set_value(&self, name: &str, value: Value) -> Result<()> {
+        let signal = name
+            .strip_prefix(SIGNAL_PARAMETER_PREFIX)
+            .ok_or_else(|| anyhow!("Unknown parameter '{}'", name))?;
+        match value {
+            Value::Bool(on) => self.hold(signal, on),
+            Value::String(s) if s == "pulse" => self.pulse(signal),
+            other => bail!(
+                "Signal '{}' takes true, false or \"pulse\", got {}",
+                signal,
+                other
+            ),
+        }
+    }
+
+    async fn get_value(&self, name: &str) -> Result<Value> {
+        let signal = name
+            .strip_prefix(SIGNAL_PARAMETER_PREFIX)
+            .ok_or_else(|| anyhow!("Unknown parameter '{}'", name))?;
+        let signals = self.signals.lock().unwrap_or_else(|p| p.into_inner());
+        let state = signals
+            .get(signal)
+            .ok_or_else(|| anyhow!("Unknown signal '{}'", signal))?;
+        Ok(Value::Bool(state.held))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(toml: &str) -> TriggerEvaluator {
+        let condition: TriggerCondition = toml::from_str(toml).unwrap();
+        TriggerEvaluator::compile(&condition).unwrap()
+    }
+
+    #[test]
+    fn test_and_of_level_and_edge() {
+        let mut evaluator = compile(
+            r#"
+            type = "all"
+            conditions = [
+                { type = "threshold", channel = "shutter", above = 0.5 },
+                { type = "digital_edge", channel = "chopper", edge = "rising" },
+            ]
+            "#,
+        );
+        assert_eq!(evaluator.channels(), ["shutter", "chopper"]);
+
+        // The first sample is a baseline: a high input is not an edge
+        assert!(!evaluator.evaluate(0.0, &[1.0, 1.0], &[]));
+        assert!(!evaluator.evaluate(0.1, &[1.0, 0.0], &[]));
+        // Edge with the shutter closed
+        assert!(!evaluator.evaluate(0.2, &[0.0, 1.0], &[]));
+        assert!(!evaluator.evaluate(0.3, &[1.0, 0.0], &[]));
+        assert!(evaluator.evaluate(0.4, &[1.0, 1.0], &[]));
+        // Without a hold the edge only counts on the sample it is seen
+        assert!(!evaluator.evaluate(0.5, &[1.0, 1.0], &[]));
+    }
+
+    #[test]
+    fn test_or_of_signal_and_window() {
+        let mut evaluator = compile(
+            r#"
+            type = "any"
+            conditions = [
+                { type = "software", signal = "go", hold_s = 0.1 },
+                { type = "time_window", start_s = 0.5, end_s = 0.6, period_s = 1.0 },
+            ]
+            "#,
+        );
+        let idle = [SignalState::default()];
+        let pulsed = [SignalState {
+            pulses: 1,
+            held: false,
+        }];
+        assert!(!evaluator.evaluate(0.0, &[], &idle));
+        assert!(evaluator.evaluate(0.1, &[], &pulsed));
+        assert!(evaluator.evaluate(0.15, &[], &pulsed));
+        assert!(!evaluator.evaluate(0.3, &[], &pulsed));
+        assert!(evaluator.evaluate(1.55, &[], &pulsed));
+
+        let held = [SignalState {
+            pulses: 1,
+            held: true,
+        }];
+        assert!(evaluator.evaluate(2.0, &[], &held));
+    }
+
+    #[test]
+    fn test_compile_rejects_invalid_conditions() {
+        for toml in [
+            r#"type = "threshold"
+               channel = "diode""#,
+            r#"type = "all"
+               conditions = []"#,
+            r#"type = "time_window"
+               start_s = 1.0
+               end_s = 0.5"#,
+            r#"type = "digital_edge"
+               channel = "in0"
+               hold_s = -1.0"#,
+        ] {
+            let condition: TriggerCondition = toml::from_str(toml).unwrap();
+            assert!(TriggerEvaluator::compile(&condition).is_err(), "{}", toml);
+        }
+    }
+
+    struct Counter(Mutex<u32>);
+
+    #[async_trait]
+    impl Triggerable for Counter {
+        async fn arm(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn trigger(&self) -> Result<()> {
+            *self.0.lock().unwrap() += 1;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trigger_waits_for_software_signal() {
+        let config = CompositeTriggerConfig {
+            id: "gate".to_string(),
+            name: String::new(),
+            target: Some("camera".to_string()),
+            condition: TriggerCondition::Software {
+                signal: "go".to_string(),
+                hold_s: 0.0,
+            },
+            poll_interval_ms: 1,
+            timeout_s: 2.0,
+        };
+        let camera = Arc::new(Counter(Mutex::new(0)));
+        let trigger =
+            Arc::new(CompositeTrigger::new(config, HashMap::new(), Some(camera.clone())).unwrap());
+
+        let waiting = tokio::spawn({
+            let trigger = trigger.clone();
+            async move { trigger.trigger().await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(*camera.0.lock().unwrap(), 0);
+        trigger
+            .set_value("signal.go", Value::String("pulse".to_string()))
+            .await
+            .unwrap();
+        waiting.await.unwrap().unwrap();
+        assert_eq!(*camera.0.lock().unwrap(), 1);
+    }
+}
@@ -0,0 +1,397 @@
+//! Sample coordinate systems registered from fiducials.
+//!
+//! A [`SampleRegistration`] maps sample (user) coordinates to stage
+//! coordinates with an affine transform fitted by least squares to N
+//! reference points. Each fiducial pairs a stage position, read when the
+//! point is centred in view, with its known position on the sample:
+//!
+//! ```toml
+//! [[sample_registrations]]
+//! sample_id = "wafer_07"
+//! stage_axes = ["stage_x", "stage_y"]
+//! axes = ["u", "v"]
+//! fiducials = [
+//!     { name = "A", stage = [12.10, 4.95], sample = [0.0, 0.0] },
+//!     { name = "B", stage = [22.08, 5.31], sample = [10.0, 0.0] },
+//!     { name = "C", stage = [11.75, 14.93], sample = [0.0, 10.0] },
+//! ]
+//! ```
+//!
+//! The registry exposes a registration as a motion group
+//! ([`Kinematics::Affine`]), so `wafer_07_u` and `wafer_07_v` can be scanned
+//! like any stage. The registration of the active sample is stored in run
+//! metadata under [`SAMPLE_REGISTRATION_METADATA_KEY`] and can be restored
+//! from there with [`SampleRegistration::from_metadata`].
+
+use crate::motion_group::{Kinematics, MotionGroupConfig};
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Run metadata key holding the JSON of the active sample registration
+pub const SAMPLE_REGISTRATION_METADATA_KEY: &str = "sample_registration";
+
+/// Default sample axis names, by dimension
+const DEFAULT_AXES: [&str; 3] = ["u", "v", "w"];
+
+/// `y = matrix * x + offset`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AffineTransform {
+    /// Row-major square matrix
+    pub matrix: Vec<Vec<f64>>,
+    pub offset: Vec<f64>,
+}
+
+impl AffineTransform {
+    pub fn identity(dims: usize) -> Self {
+        let matrix = (0..dims)
+            .map(|row| (0..dims).map(|col| f64::from(row == col)).collect())
+            .collect();
+        Self {
+            matrix,
+            offset: vec![0.0; dims],
+        }
+    }
+
+    pub fn dims(&self) -> usize {
+        self.offset.len()
+    }
+
+    /// Check the matrix is square and matches the offset
+    pub fn validate(&self) -> Result<()> {
+        let dims = self.dims();
+        if dims == 0 || self.matrix.len() != dims || self.matrix.iter().any(|r| r.len() != dims) {
+            bail!("Affine transform matrix must be {0}x{0}", dims.max(1));
+        }
+        Ok(())
+    }
+
+    /// Transform a point
+    pub fn apply(&self, point: &[f64]) -> Vec<f64> {
+        self.matrix
+            .iter()
+            .zip(&self.offset)
+            .map(|(row, offset)| row.iter().zip(point).map(|(a, x)| a * x).sum::<f64>() + offset)
+            .collect()
+    }
+
+    /// The reverse mapping; fails if the matrix is singular
+    pub fn inverse(&self) -> Result<Self> {
+        self.validate()?;
+        let inverse = solve(self.matrix.clone(), Self::identity(self.dims()).matrix)
+            .ok_or_else(|| anyhow!("Affine transform is not invertible"))?;
+        let offset = inverse
+            .iter()
+            .map(|row| {
+                -row.iter()
+                    .zip(&self.offset)
+                    .map(|(a, b)| a * b)
+                    .sum::<f64>()
+            })
+            .collect();
+        Ok(Self {
+            matrix: inverse,
+            offset,
+        })
+    }
+
+    /// Least-squares fit mapping each `from` point to its `to` point
+    ///
+    /// Needs at least `dims + 1` point pairs that are not all on one line
+    /// (or plane, in 3D).
+    pub fn fit(from: &[Vec<f64>], to: &[Vec<f64>]) -> Result<Self> {
+        let dims = from.first().map_or(0, Vec::len);
+        if dims == 0 || from.len() != to.len() {
+            bail!("Fit needs matching, non-empty point lists");
+        }
+        if from.iter().chain(to).any(|p| p.len() != dims) {
+            bail!("Every point must have {} coordinates", dims);
+        }
+        if from.len() < dims + 1 {
+            bail!(
+                "A {}D fit needs at least {} points, got {}",
+                dims,
+                dims + 1,
+                from.len()
+            );
+        }
+
+        // Normal equations (A^T A) X = A^T B, with rows of A = [from, 1]
+        let n = dims + 1;
+        let mut ata = vec![vec![0.0; n]; n];
+        let mut atb = vec![vec![0.0; dims]; n];
+        for (x, y) in from.iter().zip(to) {
+            let row: Vec<f64> = x.iter().copied().chain([1.0]).collect();
+            for i in 0..n {
+                for j in 0..n {
+                    ata[i][j] += row[i] * row[j];
+                }
+                for j in 0..dims {
+                    atb[i][j] += row[i] * y[j];
+                }
+            }
+        }
+        let solution =
+            solve(ata, atb).ok_or_else(|| anyhow!("Fit points are degenerate (collinear)"))?;
+
+        // solution[i][j] is the coefficient of input i in output j
+        Ok(Self {
+            matrix: (0..dims)
+                .map(|j| (0..dims).map(|i| solution[i][j]).collect())
+                .collect(),
+            offset: solution[dims].clone(),
+        })
+    }
+}
+
+/// Solve `a * x = b` for `x` by Gauss-Jordan elimination with partial
+/// pivoting; `None` if `a` is singular
+fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<Vec<f64>>) -> Option<Vec<Vec<f64>>> {
+    let n = a.len();
+    let scale = a
+        .iter()
+        .flatten()
+        .fold(0.0_f64, |m, v| m.max(v.abs()))
+        .max(f64::MIN_POSITIVE);
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() <= scale * 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let p = a[col][col];
+        a[col].iter_mut().for_each(|v| *v /= p);
+        b[col].iter_mut().for_each(|v| *v /= p);
+        let (pivot_a, pivot_b) = (a[col].clone(), b[col].clone());
+        for (row, (a_row, b_row)) in a.iter_mut().zip(b.iter_mut()).enumerate() {
+            let factor = a_row[col];
+            if row == col || factor == 0.0 {
+                continue;
+            }
+            for (v, p) in a_row.iter_mut().zip(&pivot_a) {
+                *v -= factor * p;
+            }
+            for (v, p) in b_row.iter_mut().zip(&pivot_b) {
+                *v -= factor * p;
+            }
+        }
+    }
+    Some(b)
+}
+
+/// A reference point with known stage and sample coordinates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Fiducial {
+    #[serde(default)]
+    pub name: String,
+    /// Stage positions, in `stage_axes` order
+    pub stage: Vec<f64>,
+    /// Sample coordinates, in sample axis order
+    pub sample: Vec<f64>,
+}
+
+/// User-supplied part of a registration (`[[sample_registrations]]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SampleRegistrationConfig {
+    /// Sample ID; sample axes are registered as `<sample_id>_<axis>`
+    pub sample_id: String,
+    /// Stage device IDs the sample coordinates map onto
+    pub stage_axes: Vec<String>,
+    /// Sample axis names (default `u`, `v`, `w`)
+    #[serde(default)]
+    pub axes: Vec<String>,
+    pub fiducials: Vec<Fiducial>,
+}
+
+impl SampleRegistrationConfig {
+    /// Sample axis names, from `axes` or the defaults
+    pub fn axis_names(&self) -> Vec<String> {
+        if self.axes.is_empty() {
+            DEFAULT_AXES
+                .iter()
+                .take(self.stage_axes.len())
+                .map(ToString::to_string)
+                .collect()
+        } else {
+            self.axes.clone()
+        }
+    }
+}
+
+/// Fitted mapping between a sample's coordinates and stage coordinates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SampleRegistration {
+    #[serde(flatten)]
+    pub config: SampleRegistrationConfig,
+    /// Sample to stage transform
+    pub transform: AffineTransform,
+    /// RMS distance between measured and fitted fiducials, in stage units
+    pub rms_residual: f64,
+}
+
+impl SampleRegistration {
+    /// Fit the transform to the configured fiducials
+    pub fn fit(config: SampleRegistrationConfig) -> Result<Self> {
+        let dims = config.stage_axes.len();
+        if !(1..=DEFAULT_AXES.len()).contains(&dims) {
+            bail!(
+                "Sample '{}' needs 1 to {} stage axes, got {}",
+                config.sample_id,
+                DEFAULT_AXES.len(),
+                dims
+            );
+        }
+        if config.axis_names().len() != dims {
+            bail!(
+                "Sample '{}' needs {} axis names, got {}",
+                config.sample_id,
+                dims,
+                config.axes.len()
+            );
+        }
+        let (sample, stage): (Vec<Vec<f64>>, Vec<Vec<f64>>) = config
+            .fiducials
+            .iter()
+            .map(|f| (f.sample.clone(), f.stage.clone()))
+            .unzip();
+        let transform = AffineTransform::fit(&sample, &stage)
+            .map_err(|e| anyhow!("Sample '{}': {}", config.sample_id, e))?;
+        // Reject fits that cannot be inverted for position readback
+        transform
+            .inverse()
+            .map_err(|e| anyhow!("Sample '{}': {}", config.sample_id, e))?;
+
+        let squared: f64 = sample
+            .iter()
+            .zip(&stage)
+            .map(|(s, measured)| {
+                transform
+                    .apply(s)
+                    .iter()
+                    .zip(measured)
+                    .map(|(fit, m)| (fit - m).powi(2))
+                    .sum::<f64>()
+            })
+            .sum();
+        let rms_residual = (squared / sample.len() as f64).sqrt();
+
+        Ok(Self {
+            config,
+            transform,
+            rms_residual,
+        })
+    }
+
+    pub fn sample_id(&self) -> &str {
+        &self.config.sample_id
+    }
+
+    /// Stage positions for sample coordinates
+    pub fn to_stage(&self, sample: &[f64]) -> Vec<f64> {
+        self.transform.apply(sample)
+    }
+
+    /// Sample coordinates for stage positions
+    pub fn to_sample(&self, stage: &[f64]) -> Result<Vec<f64>> {
+        Ok(self.transform.inverse()?.apply(stage))
+    }
+
+    /// Motion group that moves the stages in sample coordinates
+    pub fn motion_group_config(&self) -> MotionGroupConfig {
+        MotionGroupConfig {
+            id: self.config.sample_id.clone(),
+            name: format!("Sample {}", self.config.sample_id),
+            members: self.config.stage_axes.clone(),
+            kinematics: Kinematics::Affine(self.transform.clone()),
+            axes: self.config.axis_names(),
+            limits: BTreeMap::new(),
+            keep_out: Vec::new(),
+        }
+    }
+
+    /// Value stored under [`SAMPLE_REGISTRATION_METADATA_KEY`]
+    pub fn to_metadata_value(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Registration stored in run metadata, if any
+    pub fn from_metadata(metadata: &HashMap<String, String>) -> Result<Option<Self>> {
+        metadata
+            .get(SAMPLE_REGISTRATION_METADATA_KEY)
+            .map(|json| {
+                serde_json::from_str(json)
+                    .map_err(|e| anyhow!("Invalid sample registration in metadata: {}", e))
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_fit_rotated_scaled_sample() {
+        // Sample rotated 90 degrees, 2x scale, origin at stage (5, 3)
+        let config: SampleRegistrationConfig = toml::from_str(
+            r#"
+            sample_id = "wafer"
+            stage_axes = ["stage_x", "stage_y"]
+            fiducials = [
+                { name = "A", stage = [5.0, 3.0], sample = [0.0, 0.0] },
+                { name = "B", stage = [5.0, 5.0], sample = [1.0, 0.0] },
+                { name = "C", stage = [3.0, 3.0], sample = [0.0, 1.0] },
+                { name = "D", stage = [1.0, 7.0], sample = [2.0, 2.0] },
+            ]
+            "#,
+        )
+        .unwrap();
+        let registration = SampleRegistration::fit(config).unwrap();
+        assert!(registration.rms_residual < 1e-9);
+        assert_close(&registration.to_stage(&[3.0, 1.0]), &[3.0, 9.0]);
+        assert_close(&registration.to_sample(&[3.0, 9.0]).unwrap(), &[3.0, 1.0]);
+
+        let group = registration.motion_group_config();
+        assert_eq!(group.axis_names(), ["u", "v"]);
+        assert_close(&group.kinematics.forward(&[5.0, 5.0]), &[1.0, 0.0]);
+
+        // Round trip through run metadata
+        let metadata = HashMap::from([(
+            SAMPLE_REGISTRATION_METADATA_KEY.to_string(),
+            registration.to_metadata_value(),
+        )]);
+        let restored = SampleRegistration::from_metadata(&metadata).unwrap();
+        assert_eq!(restored, Some(registration));
+    }
+
+    #[test]
+    fn test_degenerate_fiducials_are_rejected() {
+        let collinear = SampleRegistrationConfig {
+            sample_id: "slide".to_string(),
+            stage_axes: vec!["x".to_string(), "y".to_string()],
+            axes: Vec::new(),
+            fiducials: (0..3)
+                .map(|i| Fiducial {
+                    name: String::new(),
+                    stage: vec![f64::from(i), f64::from(i)],
+                    sample: vec![f64::from(i), f64::from(i)],
+                })
+                .collect(),
+        };
+        assert!(SampleRegistration::fit(collinear.clone()).is_err());
+
+        let mut too_few = collinear;
+        too_few.fiducials.truncate(2);
+        assert!(SampleRegistration::fit(too_few).is_err());
+    }
+}
@@ -40,6 +40,73 @@ use tokio::task::JoinHandle;
 // Basic Data Types
 // =============================================================================
 
+/// Reliability of a single value.
+///
+/// Set by drivers (a reading that needed a retry after a timeout, an
+/// auto-range change during the integration window) and by the processing
+/// pipeline, then carried alongside the value through gRPC and storage so
+/// analysis can filter on it. The discriminants match the proto
+/// `DataQuality` enum and the codes in HDF5 quality datasets; the proto
+/// names (`DATA_QUALITY_SUSPECT`) are accepted when deserializing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[repr(u8)]
+pub enum DataQuality {
+    /// Measured normally
+    #[default]
+    #[serde(alias = "DATA_QUALITY_GOOD")]
+    Good = 0,
+    /// Measured, but something during acquisition makes it unreliable
+    #[serde(alias = "DATA_QUALITY_SUSPECT")]
+    Suspect = 1,
+    /// Last known value repeated because no fresh reading was available
+    #[serde(alias = "DATA_QUALITY_STALE")]
+    Stale = 2,
+    /// Outside the instrument's measurable range
+    #[serde(alias = "DATA_QUALITY_OUT_OF_RANGE")]
+    OutOfRange = 3,
+    /// Computed from neighbouring values rather than measured
+    #[serde(alias = "DATA_QUALITY_INTERPOLATED")]
+    Interpolated = 4,
+}
+
+impl DataQuality {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DataQuality::Good => "good",
+            DataQuality::Suspect => "suspect",
+            DataQuality::Stale => "stale",
+            DataQuality::OutOfRange => "out_of_range",
+            DataQuality::Interpolated => "interpolated",
+        }
+    }
+
+    pub fn is_good(&self) -> bool {
+        *self == DataQuality::Good
+    }
+}
+
+impl fmt::Display for DataQuality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for DataQuality {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "good" => Ok(DataQuality::Good),
+            "suspect" => Ok(DataQuality::Suspect),
+            "stale" => Ok(DataQuality::Stale),
+            "out_of_range" => Ok(DataQuality::OutOfRange),
+            "interpolated" => Ok(DataQuality::Interpolated),
+            other => Err(anyhow::anyhow!("unknown data quality '{}'", other)),
+        }
+    }
+}
+
 /// A single data point captured from an instrument (legacy V1 structure).
 ///
 /// `DataPoint` is maintained for backwards compatibility but new code should
@@ -53,6 +120,7 @@ use tokio::task::JoinHandle;
 /// * `value` - Measured value (all measurements normalized to f64)
 /// * `unit` - Physical unit (SI notation recommended)
 /// * `metadata` - Optional instrument-specific metadata (JSON)
+/// * `quality` - Reliability flag (see [`DataQuality`])
 #[deprecated(since = "0.5.0", note = "Use Measurement enum instead")]
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct DataPoint {
@@ -69,6 +137,9 @@ pub struct DataPoint {
     /// Optional instrument-specific metadata (JSON)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<serde_json::Value>,
+    /// Reliability flag (omitted when good)
+    #[serde(default, skip_serializing_if = "DataQuality::is_good")]
+    pub quality: DataQuality,
 }
 
 /// Represents a frequency bin in a spectrum measurement.
@@ -211,42 +282,40 @@ impl Roi {
 }
 
 /// Image metadata (exposure, gain, etc.)
+///
+/// Unset fields are serialized as `None` rather than skipped: ring buffer
+/// records are bincode, which can't decode a struct with omitted fields.
+/// Missing fields still deserialize as `None` from self-describing formats.
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct ImageMetadata {
     /// Exposure time in milliseconds.
     ///
     /// `None` if exposure is unknown or not applicable (e.g., pre-captured images).
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub exposure_ms: Option<f64>,
     /// Camera gain multiplier (unitless).
     ///
     /// `None` if gain is not applicable or not set. Common range: 1.0-16.0.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub gain: Option<f64>,
     /// Binning factors (horizontal, vertical).
     ///
     /// `None` if no binning is applied. (1, 1) represents no binning, (2, 2) bins 2×2 pixels.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub binning: Option<(u32, u32)>,
     /// Sensor temperature in degrees Celsius.
     ///
     /// `None` if temperature reading is unavailable. Negative values indicate cooling below ambient.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature_c: Option<f64>,
     /// Hardware timestamp from camera in microseconds.
     ///
     /// `None` if camera does not provide hardware timestamps. Used for precise inter-frame timing.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub hardware_timestamp_us: Option<i64>,
     /// Frame readout duration in milliseconds.
     ///
     /// `None` if readout time is unknown. Represents time from exposure end to data availability.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub readout_ms: Option<f64>,
     /// ROI origin (x, y) in full sensor coordinates.
     ///
     /// `None` if ROI matches full sensor area. Useful for reconstructing position in full frame.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub roi_origin: Option<(u32, u32)>,
 }
 
@@ -328,6 +397,9 @@ pub enum Measurement {
         unit: String,
         /// UTC timestamp when measurement was captured
         timestamp: DateTime<Utc>,
+        /// Reliability flag set by the driver or processing pipeline
+        #[serde(default)]
+        quality: DataQuality,
     },
 
     /// Vector of values (e.g., spectrum, time series)
@@ -405,6 +477,14 @@ impl Measurement {
             Measurement::Spectrum { name, .. } => name,
         }
     }
+
+    /// Reliability flag; only scalars carry one, everything else is good
+    pub fn quality(&self) -> DataQuality {
+        match self {
+            Measurement::Scalar { quality, .. } => *quality,
+            _ => DataQuality::Good,
+        }
+    }
 }
 
 /// Arrow RecordBatch conversion for zero-copy batch processing.
@@ -889,6 +969,46 @@ impl ParameterValue {
             _ => None,
         }
     }
+
+    /// Convert from JSON (integral numbers become `Int`, others `Float`)
+    pub fn from_json(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => ParameterValue::Null,
+            serde_json::Value::Bool(b) => ParameterValue::Bool(*b),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => ParameterValue::Int(i),
+                None => ParameterValue::Float(n.as_f64().unwrap_or(f64::NAN)),
+            },
+            serde_json::Value::String(s) => ParameterValue::String(s.clone()),
+            serde_json::Value::Array(items) => {
+                ParameterValue::Array(items.iter().map(Self::from_json).collect())
+            }
+            serde_json::Value::Object(map) => ParameterValue::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), Self::from_json(v)))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Convert to JSON, as accepted by `set_json` on parameters
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            ParameterValue::Bool(b) => serde_json::Value::Bool(*b),
+            ParameterValue::Int(i) => serde_json::Value::from(*i),
+            ParameterValue::Float(f) => serde_json::Value::from(*f),
+            ParameterValue::String(s) => serde_json::Value::String(s.clone()),
+            ParameterValue::FloatArray(arr) => serde_json::Value::from(arr.clone()),
+            ParameterValue::IntArray(arr) => serde_json::Value::from(arr.clone()),
+            ParameterValue::Array(arr) => {
+                serde_json::Value::Array(arr.iter().map(Self::to_json).collect())
+            }
+            ParameterValue::Object(obj) => serde_json::Value::Object(
+                obj.iter().map(|(k, v)| (k.clone(), v.to_json())).collect(),
+            ),
+            ParameterValue::Null => serde_json::Value::Null,
+        }
+    }
 }
 
 impl From<bool> for ParameterValue {
@@ -1377,6 +1497,7 @@ mod tests {
             value: 42.0,
             unit: "mW".to_string(),
             timestamp: Utc::now(),
+            quality: DataQuality::Good,
         };
 
         assert_eq!(m.name(), "test");
@@ -1424,12 +1545,14 @@ mod tests {
                 value: 100.0,
                 unit: "mW".to_string(),
                 timestamp: Utc::now(),
+                quality: DataQuality::Good,
             },
             Measurement::Scalar {
                 name: "temperature".to_string(),
                 value: 25.5,
                 unit: "C".to_string(),
                 timestamp: Utc::now(),
+                quality: DataQuality::Good,
             },
         ];
 
@@ -1453,6 +1576,7 @@ mod tests {
                 value: 100.0,
                 unit: "mW".to_string(),
                 timestamp: Utc::now(),
+                quality: DataQuality::Good,
             },
             Measurement::Vector {
                 name: "spectrum".to_string(),
@@ -1574,6 +1698,7 @@ mod tests {
                 value: 100.0,
                 unit: "mW".to_string(),
                 timestamp: Utc::now(),
+                quality: DataQuality::Good,
             },
             Measurement::Image {
                 name: "camera_frame".to_string(),
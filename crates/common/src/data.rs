@@ -1,5 +1,5 @@
 use bytes::Bytes;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
 /// Extended metadata for frames (bd-183h).
@@ -25,6 +25,16 @@ pub struct FrameMetadata {
 
     /// Extensible key-value metadata for driver-specific properties
     pub extra: HashMap<String, String>,
+
+    /// Channel values sampled at capture time (channel name -> value)
+    ///
+    /// Filled by frame enrichment (see [`crate::frame_enrichment`]).
+    pub channels: BTreeMap<String, f64>,
+
+    /// Overlay annotations current at capture time
+    ///
+    /// Filled by [`crate::frame_annotations::AnnotationEnricher`].
+    pub annotations: Vec<crate::frame_annotations::FrameAnnotation>,
 }
 
 /// Represents a single image frame.
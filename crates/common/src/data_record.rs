@@ -0,0 +1,267 @@
+//! Canonical data point shared by every layer.
+//!
+//! Drivers emit [`Measurement`]s, modules and plugins emit
+//! [`ModuleDataPoint`]s, and the legacy V1 [`DataPoint`] is still around.
+//! Each of them used to be converted to gRPC messages, storage rows and GUI
+//! plots by its own code, so a new field meant editing every converter.
+//!
+//! [`DataRecord`] is the one model they all convert into: a timestamped set
+//! of named values from one source, with units, a quality flag and free-form
+//! metadata. Its field names match the `DataRecord` message in `daq.proto`,
+//! so the protocol crate maps between the two by field name; a field added
+//! to both is carried through gRPC and JSON without touching a serializer.
+//!
+//! # Scope
+//!
+//! Only the gRPC layer has moved onto it so far: the hardware and
+//! measurement streams build their messages from a `DataRecord`. Plugins
+//! reach it through [`ModuleDataPoint`], whose metadata carries the quality
+//! and units (see [`QUALITY_METADATA_KEY`]), but the plugin loaders still
+//! produce `ModuleDataPoint`s. The storage writers and the GUI plots keep
+//! their own types and converters; moving them over is separate work, so
+//! until then a new field still needs adding there by hand.
+//!
+//! [`DataPoint`]: crate::core::DataPoint
+
+use crate::core::{DataQuality, Measurement};
+use crate::modules::ModuleDataPoint;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Metadata key under which [`ModuleDataPoint`] carries a record's quality
+pub const QUALITY_METADATA_KEY: &str = "quality";
+
+/// Metadata key prefix under which [`ModuleDataPoint`] carries units
+pub const UNIT_METADATA_PREFIX: &str = "unit:";
+
+/// One timestamped set of named values from a device, module or plugin
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DataRecord {
+    /// Device, module or plugin that produced the values
+    pub source_id: String,
+    /// What the values describe (e.g. "power", "statistics")
+    pub data_type: String,
+    /// When the values were captured (UNIX nanoseconds)
+    pub timestamp_ns: u64,
+    /// Values by channel name
+    pub values: HashMap<String, f64>,
+    /// Physical unit by channel name, where known
+    pub units: HashMap<String, String>,
+    /// Reliability of the values
+    pub quality: DataQuality,
+    /// Additional metadata
+    pub metadata: HashMap<String, String>,
+}
+
+impl DataRecord {
+    /// Record of a single value
+    pub fn scalar(
+        source_id: impl Into<String>,
+        channel: impl Into<String>,
+        value: f64,
+        unit: impl Into<String>,
+        timestamp_ns: u64,
+    ) -> Self {
+        let channel = channel.into();
+        let unit = unit.into();
+        let mut record = Self {
+            source_id: source_id.into(),
+            data_type: channel.clone(),
+            timestamp_ns,
+            ..Default::default()
+        };
+        if !unit.is_empty() {
+            record.units.insert(channel.clone(), unit);
+        }
+        record.values.insert(channel, value);
+        record
+    }
+
+    /// Scalar view of a driver measurement
+    ///
+    /// Arrays are summarised the way the scalar data streams have always
+    /// shown them: vectors and spectra by their length (`{name}_len`,
+    /// `{name}_spectrum`), images by their pixel count.
+    pub fn from_measurement(source_id: impl Into<String>, measurement: &Measurement) -> Self {
+        let timestamp_ns = measurement.timestamp().timestamp_nanos_opt().unwrap_or(0) as u64;
+        let mut record = match measurement {
+            Measurement::Scalar {
+                name, value, unit, ..
+            } => Self::scalar(
+                source_id,
+                name.as_str(),
+                *value,
+                unit.as_str(),
+                timestamp_ns,
+            ),
+            Measurement::Vector { name, values, .. } => Self::scalar(
+                source_id,
+                format!("{}_len", name),
+                values.len() as f64,
+                "",
+                timestamp_ns,
+            ),
+            Measurement::Image {
+                name,
+                width,
+                height,
+                ..
+            } => Self::scalar(
+                source_id,
+                name.as_str(),
+                f64::from(*width) * f64::from(*height),
+                "",
+                timestamp_ns,
+            ),
+            Measurement::Spectrum {
+                name, amplitudes, ..
+            } => Self::scalar(
+                source_id,
+                format!("{}_spectrum", name),
+                amplitudes.len() as f64,
+                "",
+                timestamp_ns,
+            ),
+        };
+        record.data_type = measurement.name().to_string();
+        record.quality = measurement.quality();
+        record
+    }
+
+    pub fn with_quality(mut self, quality: DataQuality) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Value of one channel
+    pub fn value(&self, channel: &str) -> Option<f64> {
+        self.values.get(channel).copied()
+    }
+
+    /// Unit of one channel, empty when unknown
+    pub fn unit(&self, channel: &str) -> &str {
+        self.units.get(channel).map(String::as_str).unwrap_or("")
+    }
+}
+
+// Modules have no quality or unit fields; they travel in the metadata
+impl From<ModuleDataPoint> for DataRecord {
+    fn from(point: ModuleDataPoint) -> Self {
+        let mut record = Self {
+            source_id: point.module_id,
+            data_type: point.data_type,
+            timestamp_ns: point.timestamp_ns,
+            values: point.values,
+            ..Default::default()
+        };
+        for (key, value) in point.metadata {
+            if key == QUALITY_METADATA_KEY {
+                if let Ok(quality) = value.parse() {
+                    record.quality = quality;
+                    continue;
+                }
+            }
+            match key.strip_prefix(UNIT_METADATA_PREFIX) {
+                Some(channel) => {
+                    record.units.insert(channel.to_string(), value);
+                }
+                None => {
+                    record.metadata.insert(key, value);
+                }
+            }
+        }
+        record
+    }
+}
+
+impl From<DataRecord> for ModuleDataPoint {
+    fn from(record: DataRecord) -> Self {
+        let mut metadata = record.metadata;
+        if !record.quality.is_good() {
+            metadata.insert(QUALITY_METADATA_KEY.to_string(), record.quality.to_string());
+        }
+        for (channel, unit) in record.units {
+            metadata.insert(format!("{}{}", UNIT_METADATA_PREFIX, channel), unit);
+        }
+        ModuleDataPoint {
+            module_id: record.source_id,
+            data_type: record.data_type,
+            timestamp_ns: record.timestamp_ns,
+            values: record.values,
+            metadata,
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl From<crate::core::DataPoint> for DataRecord {
+    fn from(point: crate::core::DataPoint) -> Self {
+        let timestamp_ns = point.timestamp.timestamp_nanos_opt().unwrap_or(0) as u64;
+        let mut record = Self::scalar(
+            point.instrument_id,
+            point.channel,
+            point.value,
+            point.unit,
+            timestamp_ns,
+        )
+        .with_quality(point.quality);
+        if let Some(metadata) = point.metadata {
+            record
+                .metadata
+                .insert("metadata".to_string(), metadata.to_string());
+        }
+        record
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_measurement_becomes_record() {
+        let measurement = Measurement::Scalar {
+            name: "power".to_string(),
+            value: 0.25,
+            unit: "W".to_string(),
+            timestamp: Utc.timestamp_nanos(1_000),
+            quality: DataQuality::Suspect,
+        };
+        let record = DataRecord::from_measurement("power_meter", &measurement);
+        assert_eq!(record.source_id, "power_meter");
+        assert_eq!(record.value("power"), Some(0.25));
+        assert_eq!(record.unit("power"), "W");
+        assert_eq!(record.timestamp_ns, 1_000);
+        assert_eq!(record.quality, DataQuality::Suspect);
+
+        let spectrum = Measurement::Vector {
+            name: "trace".to_string(),
+            values: vec![1.0, 2.0, 3.0],
+            unit: "V".to_string(),
+            timestamp: Utc.timestamp_nanos(0),
+        };
+        let record = DataRecord::from_measurement("scope", &spectrum);
+        assert_eq!(record.value("trace_len"), Some(3.0));
+    }
+
+    #[test]
+    fn test_module_point_round_trip_keeps_quality_and_units() {
+        let record = DataRecord::scalar("power_monitor", "mean", 1.5, "mW", 42)
+            .with_quality(DataQuality::Stale)
+            .with_metadata("window", "10");
+
+        let point = ModuleDataPoint::from(record.clone());
+        assert_eq!(point.module_id, "power_monitor");
+        assert_eq!(point.metadata["quality"], "stale");
+        assert_eq!(point.metadata["unit:mean"], "mW");
+
+        assert_eq!(DataRecord::from(point), record);
+    }
+}
@@ -0,0 +1,198 @@
+//! Per-consumer decimation and time alignment.
+//!
+//! Data is distributed at full rate, but most consumers don't want all of it:
+//! the HDF5 writer needs every sample while a GUI plot needs ~10 Hz. Each
+//! subscriber carries its own [`Decimation`] and the distribution layer drops
+//! samples for it before they are queued, instead of every consumer receiving
+//! everything and discarding it on its side.
+//!
+//! Rate-based decimation works in a [`ClockDomain`]. With
+//! [`ClockDomain::WallClock`] the periods are aligned to wall-clock
+//! boundaries: at 10 Hz a subscriber gets the first sample after each
+//! `.0`, `.1`, `.2` s boundary, so independent subscribers (and machines with
+//! synchronized clocks) see samples from the same periods.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! decimation = { mode = "rate", hz = 10.0, clock = "wall_clock" }
+//! # or
+//! decimation = { mode = "every_nth", n = 10 }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Clock used to divide time into decimation periods
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClockDomain {
+    /// Periods aligned to wall-clock (UNIX time) boundaries
+    #[default]
+    WallClock,
+    /// Periods counted from when the subscriber was created
+    Monotonic,
+}
+
+/// Which samples a consumer receives
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Decimation {
+    /// Every sample (full rate)
+    #[default]
+    All,
+    /// Every nth sample (1 = every sample)
+    EveryNth { n: u64 },
+    /// At most one sample per `1 / hz` period
+    Rate {
+        hz: f64,
+        #[serde(default)]
+        clock: ClockDomain,
+    },
+}
+
+impl Decimation {
+    /// At most `hz` samples per second, aligned to wall-clock boundaries
+    pub fn wall_clock_rate(hz: f64) -> Self {
+        Self::Rate {
+            hz,
+            clock: ClockDomain::WallClock,
+        }
+    }
+
+    /// Period length for rate-based decimation (`None` otherwise or if `hz`
+    /// is not positive)
+    pub fn period(&self) -> Option<Duration> {
+        match *self {
+            Self::Rate { hz, .. } if hz.is_finite() && hz > 0.0 => {
+                Some(Duration::from_nanos(((1e9 / hz) as u64).max(1)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Decimation state for one consumer
+///
+/// [`admit`](Self::admit) takes `&self` so a decimator can sit in a shared
+/// registry and be called from the producer's thread.
+#[derive(Debug)]
+pub struct Decimator {
+    decimation: Decimation,
+    period_ns: Option<u64>,
+    count: AtomicU64,
+    /// Index + 1 of the last period a sample was admitted in (0 = none yet)
+    last_period: AtomicU64,
+    origin: Instant,
+}
+
+impl Decimator {
+    pub fn new(decimation: Decimation) -> Self {
+        Self {
+            decimation,
+            period_ns: decimation.period().map(|p| p.as_nanos() as u64),
+            count: AtomicU64::new(0),
+            last_period: AtomicU64::new(0),
+            origin: Instant::now(),
+        }
+    }
+
+    pub fn decimation(&self) -> Decimation {
+        self.decimation
+    }
+
+    /// Decide whether the next sample goes to this consumer
+    pub fn admit(&self) -> bool {
+        let now_ns = match self.decimation {
+            Decimation::Rate {
+                clock: ClockDomain::WallClock,
+                ..
+            } => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos() as u64,
+            Decimation::Rate {
+                clock: ClockDomain::Monotonic,
+                ..
+            } => self.origin.elapsed().as_nanos() as u64,
+            _ => 0,
+        };
+        self.admit_at(now_ns)
+    }
+
+    /// Like [`admit`](Self::admit), but wall-clock decimation buckets the
+    /// sample by its own acquisition timestamp (UNIX nanoseconds, 0 = unknown)
+    /// rather than its arrival time
+    pub fn admit_sample(&self, timestamp_ns: u64) -> bool {
+        match self.decimation {
+            Decimation::Rate {
+                clock: ClockDomain::WallClock,
+                ..
+            } if timestamp_ns != 0 => self.admit_at(timestamp_ns),
+            _ => self.admit(),
+        }
+    }
+
+    /// [`admit`](Self::admit) with an explicit time in the decimator's clock
+    /// domain (nanoseconds)
+    pub fn admit_at(&self, now_ns: u64) -> bool {
+        match self.decimation {
+            Decimation::All => true,
+            Decimation::EveryNth { n } => self
+                .count
+                .fetch_add(1, Ordering::Relaxed)
+                .is_multiple_of(n.max(1)),
+            Decimation::Rate { .. } => {
+                let Some(period_ns) = self.period_ns else {
+                    return true;
+                };
+                let period = now_ns / period_ns + 1;
+                self.last_period.fetch_max(period, Ordering::AcqRel) < period
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_nth() {
+        let decimator = Decimator::new(Decimation::EveryNth { n: 3 });
+        let admitted: Vec<bool> = (0..7).map(|_| decimator.admit()).collect();
+        assert_eq!(admitted, vec![true, false, false, true, false, false, true]);
+        assert!(Decimator::new(Decimation::All).admit());
+    }
+
+    #[test]
+    fn test_rate_aligns_to_period_boundaries() {
+        // 10 Hz: one sample per 100 ms period, boundaries at multiples of 100 ms
+        let decimator = Decimator::new(Decimation::wall_clock_rate(10.0));
+        let ms = |t: u64| t * 1_000_000;
+        assert!(decimator.admit_at(ms(1_050)));
+        assert!(!decimator.admit_at(ms(1_090)));
+        // Next period starts at 1100 ms, not 1150 ms
+        assert!(decimator.admit_at(ms(1_100)));
+        assert!(!decimator.admit_at(ms(1_199)));
+        // Late samples from an earlier period are dropped
+        assert!(!decimator.admit_at(ms(1_000)));
+        assert!(decimator.admit_at(ms(1_500)));
+    }
+
+    #[test]
+    fn test_config_from_toml() {
+        #[derive(Deserialize)]
+        struct Subscriber {
+            decimation: Decimation,
+        }
+        let sub: Subscriber =
+            toml::from_str("decimation = { mode = \"rate\", hz = 10.0 }").unwrap();
+        assert_eq!(sub.decimation, Decimation::wall_clock_rate(10.0));
+        assert_eq!(sub.decimation.period(), Some(Duration::from_millis(100)));
+
+        let sub: Subscriber = toml::from_str("decimation = { mode = \"all\" }").unwrap();
+        assert_eq!(sub.decimation, Decimation::All);
+    }
+}
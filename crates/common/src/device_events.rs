@@ -0,0 +1,297 @@
+//! Structured device events, separate from measured data.
+//!
+//! State transitions, errors, limit-switch hits and auto-range changes are
+//! things that *happen to* a device rather than values it measures. They are
+//! published as [`DeviceEvent`]s with a kind, a severity and a structured
+//! payload:
+//!
+//! - Drivers emit through the [`DeviceEventSink`] they pass in
+//!   `DeviceComponents::events`; the registry attaches it to its event
+//!   channel when the device is registered.
+//! - Status changes reported through a `DeviceStatusHandle` become
+//!   [`DeviceEventKind::StateChange`] events, except the Ready/Busy toggles
+//!   of ordinary operations.
+//! - The daemon streams events over gRPC independently of data points, and
+//!   the RunEngine records those raised during a run as `DeviceEventDoc`s,
+//!   which storage keeps in a per-run `device_events` table.
+
+use crate::driver::DeviceStatus;
+use crate::experiment::document::now_ns;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use tokio::sync::broadcast;
+
+/// What happened to the device
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceEventKind {
+    /// Operational status changed (payload: `from`, `to`)
+    StateChange,
+    /// The device reported or caused an error
+    Error,
+    /// A travel limit switch was hit (payload: `position`, `limit`)
+    LimitSwitch,
+    /// The measurement range changed (payload: `from`, `to`)
+    AutoRange,
+    /// Driver-specific event
+    Custom(String),
+}
+
+impl DeviceEventKind {
+    /// Snake_case name for proto/API transport and filtering
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::StateChange => "state_change",
+            Self::Error => "error",
+            Self::LimitSwitch => "limit_switch",
+            Self::AutoRange => "auto_range",
+            Self::Custom(name) => name,
+        }
+    }
+
+    /// Kind from its name; unknown names are driver-specific kinds
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "state_change" => Self::StateChange,
+            "error" => Self::Error,
+            "limit_switch" => Self::LimitSwitch,
+            "auto_range" => Self::AutoRange,
+            other => Self::Custom(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for DeviceEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How much attention an event needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceEventSeverity {
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl DeviceEventSeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+impl fmt::Display for DeviceEventSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Something that happened to a device
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceEvent {
+    pub device_id: String,
+    pub kind: DeviceEventKind,
+    pub severity: DeviceEventSeverity,
+    pub message: String,
+    /// Structured details, keyed by name
+    #[serde(default)]
+    pub payload: BTreeMap<String, serde_json::Value>,
+    /// When it happened (UNIX nanoseconds)
+    pub timestamp_ns: u64,
+}
+
+impl DeviceEvent {
+    /// Event stamped now; the device ID is filled in by the sink
+    pub fn new(
+        kind: DeviceEventKind,
+        severity: DeviceEventSeverity,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            device_id: String::new(),
+            kind,
+            severity,
+            message: message.into(),
+            payload: BTreeMap::new(),
+            timestamp_ns: now_ns(),
+        }
+    }
+
+    /// Add a payload entry
+    pub fn with(mut self, key: &str, value: impl Into<serde_json::Value>) -> Self {
+        self.payload.insert(key.to_string(), value.into());
+        self
+    }
+
+    /// Event of a status change worth reporting, if it is one
+    ///
+    /// Moving between Ready and Busy is what every operation does, so those
+    /// transitions are not events.
+    pub fn status_change(device_id: &str, from: &DeviceStatus, to: &DeviceStatus) -> Option<Self> {
+        let routine =
+            |status: &DeviceStatus| matches!(status, DeviceStatus::Ready | DeviceStatus::Busy);
+        if from == to || (routine(from) && routine(to)) {
+            return None;
+        }
+        let severity = match to {
+            DeviceStatus::Fault { .. } => DeviceEventSeverity::Error,
+            DeviceStatus::Degraded { .. } => DeviceEventSeverity::Warning,
+            _ => DeviceEventSeverity::Info,
+        };
+        let mut event = Self::new(
+            DeviceEventKind::StateChange,
+            severity,
+            format!("{} -> {}", from, to),
+        )
+        .with("from", from.as_str())
+        .with("to", to.as_str());
+        match to {
+            DeviceStatus::Degraded { reason } => event = event.with("reason", reason.as_str()),
+            DeviceStatus::Fault { code, message } => {
+                event = event.with("code", *code).with("reason", message.as_str());
+            }
+            _ => {}
+        }
+        event.device_id = device_id.to_string();
+        Some(event)
+    }
+
+    /// Payload as JSON strings, for transports with string maps
+    pub fn payload_json(&self) -> BTreeMap<String, String> {
+        self.payload
+            .iter()
+            .map(|(key, value)| (key.clone(), value.to_string()))
+            .collect()
+    }
+}
+
+impl fmt::Display for DeviceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {} {}: {}",
+            self.severity, self.device_id, self.kind, self.message
+        )
+    }
+}
+
+/// Where a driver emits its events
+///
+/// Clones share the same channel. Events emitted before the registry has
+/// attached the sink are dropped.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceEventSink(Arc<RwLock<Option<AttachedSink>>>);
+
+#[derive(Debug)]
+struct AttachedSink {
+    device_id: String,
+    sender: broadcast::Sender<DeviceEvent>,
+}
+
+impl DeviceEventSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Publish this device's events on `sender` under `device_id`
+    pub fn attach(&self, device_id: &str, sender: broadcast::Sender<DeviceEvent>) {
+        *self.0.write().unwrap_or_else(|p| p.into_inner()) = Some(AttachedSink {
+            device_id: device_id.to_string(),
+            sender,
+        });
+    }
+
+    pub fn is_attached(&self) -> bool {
+        self.0.read().unwrap_or_else(|p| p.into_inner()).is_some()
+    }
+
+    /// ID the events are published under, once attached
+    pub fn device_id(&self) -> Option<String> {
+        self.0
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .as_ref()
+            .map(|sink| sink.device_id.clone())
+    }
+
+    /// Publish an event, stamped with the device ID
+    pub fn emit(&self, mut event: DeviceEvent) {
+        let guard = self.0.read().unwrap_or_else(|p| p.into_inner());
+        let Some(sink) = guard.as_ref() else {
+            tracing::debug!(kind = %event.kind, "Device event before registration dropped");
+            return;
+        };
+        event.device_id.clone_from(&sink.device_id);
+        tracing::debug!("Device event: {}", event);
+        // No subscribers is fine
+        let _ = sink.sender.send(event);
+    }
+
+    /// Publish a status change, if it is worth an event
+    pub fn emit_status_change(&self, from: &DeviceStatus, to: &DeviceStatus) {
+        if let Some(event) = DeviceEvent::status_change("", from, to) {
+            self.emit(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_changes_skip_routine_transitions() {
+        assert!(
+            DeviceEvent::status_change("stage", &DeviceStatus::Ready, &DeviceStatus::Busy)
+                .is_none()
+        );
+
+        let fault = DeviceStatus::Fault {
+            code: 7,
+            message: "encoder lost".to_string(),
+        };
+        let event = DeviceEvent::status_change("stage", &DeviceStatus::Busy, &fault).unwrap();
+        assert_eq!(event.kind, DeviceEventKind::StateChange);
+        assert_eq!(event.severity, DeviceEventSeverity::Error);
+        assert_eq!(event.payload["to"], "fault");
+        assert_eq!(event.payload["code"], 7);
+        assert_eq!(event.payload_json()["reason"], "\"encoder lost\"");
+    }
+
+    #[tokio::test]
+    async fn test_sink_publishes_after_attach() {
+        let (tx, mut rx) = broadcast::channel(8);
+        let sink = DeviceEventSink::new();
+        let limit = || {
+            DeviceEvent::new(
+                DeviceEventKind::LimitSwitch,
+                DeviceEventSeverity::Warning,
+                "hit max",
+            )
+            .with("position", 25.0)
+        };
+
+        sink.emit(limit());
+        sink.clone().attach("stage", tx);
+        sink.emit(limit());
+
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.device_id, "stage");
+        assert_eq!(event.payload["position"], 25.0);
+        assert!(rx.try_recv().is_err());
+        assert_eq!(
+            DeviceEventKind::from_name("door_open").as_str(),
+            "door_open"
+        );
+    }
+}
@@ -0,0 +1,486 @@
+//! Named-slot positioners: filter wheels, sample changers, flip mirrors.
+//!
+//! A [`MappedPositioner`] implements [`DiscretePositioner`] on top of another
+//! device, mapping each named slot either to a position of a [`Movable`]
+//! (a filter wheel on a rotation stage) or to values of [`Settable`]
+//! parameters (a flip mirror on a digital output line):
+//!
+//! ```toml
+//! [[discrete_positioners]]
+//! id = "nd_wheel"
+//! drive = { type = "movable", device = "wheel_rotator", tolerance = 0.5 }
+//! positions = [
+//!     { name = "open", position = 0.0 },
+//!     { name = "nd1", position = 60.0 },
+//!     { name = "nd2", position = 120.0 },
+//! ]
+//!
+//! [[discrete_positioners]]
+//! id = "flip_mirror"
+//! drive = { type = "outputs", device = "dio" }
+//! positions = [
+//!     { name = "up", outputs = { line0 = true } },
+//!     { name = "down", outputs = { line0 = false } },
+//! ]
+//! ```
+//!
+//! Every move is verified: a movable slot must be reached within
+//! `tolerance`, and output slots are read back from the device, which must
+//! therefore support `get_value`.
+
+use crate::capabilities::{DiscretePositioner, Movable, Settable};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+/// How slots are reached (`drive` in the config)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum PositionerDrive {
+    /// Each slot is a position of a Movable device
+    Movable {
+        device: String,
+        /// Largest distance from a slot's position still counted as in the slot
+        #[serde(default = "default_tolerance")]
+        tolerance: f64,
+    },
+    /// Each slot is a set of parameter values of a Settable device
+    Outputs { device: String },
+}
+
+fn default_tolerance() -> f64 {
+    0.01
+}
+
+impl PositionerDrive {
+    /// The device driven
+    pub fn device(&self) -> &str {
+        match self {
+            Self::Movable { device, .. } | Self::Outputs { device } => device,
+        }
+    }
+}
+
+/// One named slot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NamedPosition {
+    pub name: String,
+    /// Position of the Movable (movable drive)
+    #[serde(default)]
+    pub position: Option<f64>,
+    /// Parameter values (outputs drive)
+    #[serde(default)]
+    pub outputs: BTreeMap<String, Value>,
+}
+
+/// Configuration of a positioner (`[[discrete_positioners]]` in the hardware config)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DiscretePositionerConfig {
+    /// Device ID the positioner is registered under
+    pub id: String,
+    /// Display name
+    #[serde(default)]
+    pub name: String,
+    pub drive: PositionerDrive,
+    /// Slots, in slot order
+    pub positions: Vec<NamedPosition>,
+}
+
+impl DiscretePositionerConfig {
+    /// Check that every slot fits the drive and can be told apart
+    pub fn validate(&self) -> Result<()> {
+        let id = &self.id;
+        if self.positions.is_empty() {
+            bail!("Positioner '{}' has no positions", id);
+        }
+        let mut names = HashSet::new();
+        for slot in &self.positions {
+            if slot.name.is_empty() {
+                bail!("Positioner '{}' has a position without a name", id);
+            }
+            if !names.insert(slot.name.as_str()) {
+                bail!("Positioner '{}' has position '{}' twice", id, slot.name);
+            }
+        }
+
+        match &self.drive {
+            PositionerDrive::Movable { tolerance, .. } => {
+                if !tolerance.is_finite() || *tolerance < 0.0 {
+                    bail!("Positioner '{}': invalid tolerance {}", id, tolerance);
+                }
+                let mut targets = Vec::new();
+                for slot in &self.positions {
+                    let target = slot.position.filter(|p| p.is_finite()).ok_or_else(|| {
+                        anyhow!(
+                            "Positioner '{}': position '{}' needs a finite `position`",
+                            id,
+                            slot.name
+                        )
+                    })?;
+                    if !slot.outputs.is_empty() {
+                        bail!(
+                            "Positioner '{}': position '{}' sets outputs on a movable drive",
+                            id,
+                            slot.name
+                        );
+                    }
+                    targets.push((slot.name.as_str(), target));
+                }
+                for (i, (name, target)) in targets.iter().enumerate() {
+                    if let Some((other, _)) = targets[i + 1..]
+                        .iter()
+                        .find(|(_, t)| (t - target).abs() <= 2.0 * tolerance)
+                    {
+                        bail!(
+                            "Positioner '{}': positions '{}' and '{}' overlap within the tolerance",
+                            id,
+                            name,
+                            other
+                        );
+                    }
+                }
+            }
+            PositionerDrive::Outputs { .. } => {
+                for (i, slot) in self.positions.iter().enumerate() {
+                    if slot.outputs.is_empty() || slot.position.is_some() {
+                        bail!(
+                            "Positioner '{}': position '{}' needs `outputs` and no `position`",
+                            id,
+                            slot.name
+                        );
+                    }
+                    if let Some(other) = self.positions[i + 1..]
+                        .iter()
+                        .find(|other| outputs_match(&other.outputs, &slot.outputs))
+                    {
+                        bail!(
+                            "Positioner '{}': positions '{}' and '{}' set the same outputs",
+                            id,
+                            slot.name,
+                            other.name
+                        );
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Slot names, in slot order
+    pub fn position_names(&self) -> Vec<String> {
+        self.positions.iter().map(|p| p.name.clone()).collect()
+    }
+}
+
+/// The device a [`MappedPositioner`] drives
+#[derive(Clone)]
+pub enum PositionerOutput {
+    Movable(Arc<dyn Movable>),
+    Outputs(Arc<dyn Settable>),
+}
+
+/// [`DiscretePositioner`] mapping named slots onto another device
+pub struct MappedPositioner {
+    config: DiscretePositionerConfig,
+    output: PositionerOutput,
+}
+
+impl MappedPositioner {
+    /// Fails if the config is invalid or `output` does not match its drive
+    pub fn new(config: DiscretePositionerConfig, output: PositionerOutput) -> Result<Self> {
+        config.validate()?;
+        match (&config.drive, &output) {
+            (PositionerDrive::Movable { .. }, PositionerOutput::Movable(_))
+            | (PositionerDrive::Outputs { .. }, PositionerOutput::Outputs(_)) => {}
+            _ => bail!(
+                "Positioner '{}': device '{}' does not fit its drive",
+                config.id,
+                config.drive.device()
+            ),
+        }
+        Ok(Self { config, output })
+    }
+
+    pub fn config(&self) -> &DiscretePositionerConfig {
+        &self.config
+    }
+
+    fn slot(&self, name: &str) -> Result<&NamedPosition> {
+        self.config
+            .positions
+            .iter()
+            .find(|p| p.name == name)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Positioner '{}' has no position '{}' (expected one of: {})",
+                    self.config.id,
+                    name,
+                    self.config.position_names().join(", ")
+                )
+            })
+    }
+
+    /// Current values of every output any slot sets
+    async fn read_outputs(&self, device: &dyn Settable) -> Result<BTreeMap<String, Value>> {
+        let mut values = BTreeMap::new();
+        for slot in &self.config.positions {
+            for name in slot.outputs.keys() {
+                if !values.contains_key(name) {
+                    values.insert(name.clone(), device.get_value(name).await?);
+                }
+            }
+        }
+        Ok(values)
+    }
+}
+
+#[async_trait]
+impl DiscretePositioner for MappedPositioner {
+    fn position_names(&self) -> Vec<String> {
+        self.config.position_names()
+    }
+
+    async fn move_to_position(&self, name: &str) -> Result<()> {
+        let slot = self.slot(name)?;
+        match (&self.output, &self.config.drive) {
+            (PositionerOutput::Movable(movable), PositionerDrive::Movable { tolerance, .. }) => {
+                let target = slot.position.unwrap_or_default();
+                movable.move_abs(target).await?;
+                movable.wait_settled().await?;
+                let actual = movable.position().await?;
+                if (actual - target).abs() > *tolerance {
+                    bail!(
+                        "Positioner '{}' did not reach '{}': at {} instead of {}",
+                        self.config.id,
+                        name,
+                        actual,
+                        target
+                    );
+                }
+            }
+            (PositionerOutput::Outputs(device), _) => {
+                for (output, value) in &slot.outputs {
+                    device.set_value(output, value.clone()).await?;
+                }
+                for (output, value) in &slot.outputs {
+                    let actual = device.get_value(output).await?;
+                    if !values_match(&actual, value) {
+                        bail!(
+                            "Positioner '{}' did not reach '{}': {} reads {} instead of {}",
+                            self.config.id,
+                            name,
+                            output,
+                            actual,
+                            value
+                        );
+                    }
+                }
+            }
+            // Ruled out by `new`
+            (PositionerOutput::Movable(_), PositionerDrive::Outputs { .. }) => unreachable!(),
+        }
+        tracing::debug!(positioner = %self.config.id, position = name, "Positioner moved");
+        Ok(())
+    }
+
+    async fn current_position(&self) -> Result<Option<String>> {
+        let slot = match (&self.output, &self.config.drive) {
+            (PositionerOutput::Movable(movable), PositionerDrive::Movable { tolerance, .. }) => {
+                let actual = movable.position().await?;
+                self.config.positions.iter().find(|slot| {
+                    slot.position
+                        .is_some_and(|target| (actual - target).abs() <= *tolerance)
+                })
+            }
+            (PositionerOutput::Outputs(device), _) => {
+                let values = self.read_outputs(device.as_ref()).await?;
+                self.config.positions.iter().find(|slot| {
+                    slot.outputs.iter().all(|(name, value)| {
+                        values.get(name).is_some_and(|v| values_match(v, value))
+                    })
+                })
+            }
+            (PositionerOutput::Movable(_), PositionerDrive::Outputs { .. }) => unreachable!(),
+        };
+        Ok(slot.map(|slot| slot.name.clone()))
+    }
+}
+
+/// Whether two output values are the same, so `1`, `1.0` and `true` all match
+fn values_match(a: &Value, b: &Value) -> bool {
+    fn as_number(value: &Value) -> Option<f64> {
+        match value {
+            Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+            other => other.as_f64(),
+        }
+    }
+    match (as_number(a), as_number(b)) {
+        (Some(a), Some(b)) => (a - b).abs() < 1e-9,
+        _ => a == b,
+    }
+}
+
+fn outputs_match(a: &BTreeMap<String, Value>, b: &BTreeMap<String, Value>) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .all(|(name, value)| b.get(name).is_some_and(|v| values_match(v, value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Rotation stage that stops `error` short of every target
+    struct Wheel {
+        position: Mutex<f64>,
+        error: f64,
+    }
+
+    #[async_trait]
+    impl Movable for Wheel {
+        async fn move_abs(&self, position: f64) -> Result<()> {
+            *self.position.lock().unwrap() = position - self.error;
+            Ok(())
+        }
+        async fn move_rel(&self, distance: f64) -> Result<()> {
+            *self.position.lock().unwrap() += distance;
+            Ok(())
+        }
+        async fn position(&self) -> Result<f64> {
+            Ok(*self.position.lock().unwrap())
+        }
+        async fn wait_settled(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct Dio {
+        lines: Mutex<BTreeMap<String, Value>>,
+    }
+
+    #[async_trait]
+    impl Settable for Dio {
+        async fn set_value(&self, name: &str, value: Value) -> Result<()> {
+            self.lines.lock().unwrap().insert(name.to_string(), value);
+            Ok(())
+        }
+        async fn get_value(&self, name: &str) -> Result<Value> {
+            Ok(self
+                .lines
+                .lock()
+                .unwrap()
+                .get(name)
+                .cloned()
+                .unwrap_or(Value::Bool(false)))
+        }
+    }
+
+    fn wheel_config() -> DiscretePositionerConfig {
+        toml::from_str(
+            r#"
+            id = "nd_wheel"
+            drive = { type = "movable", device = "rotator", tolerance = 0.5 }
+            positions = [
+                { name = "open", position = 0.0 },
+                { name = "nd1", position = 60.0 },
+            ]
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_movable_slots_are_verified() {
+        let wheel = Arc::new(Wheel {
+            position: Mutex::new(30.0),
+            error: 0.2,
+        });
+        let positioner =
+            MappedPositioner::new(wheel_config(), PositionerOutput::Movable(wheel.clone()))
+                .unwrap();
+        assert_eq!(positioner.current_position().await.unwrap(), None);
+
+        positioner.move_to_position("nd1").await.unwrap();
+        assert_eq!(
+            positioner.current_position().await.unwrap().as_deref(),
+            Some("nd1")
+        );
+        assert_eq!(positioner.position_index("nd1"), Some(1));
+        assert!(positioner.move_to_position("nd3").await.is_err());
+
+        // Stalls outside the tolerance
+        let stalled = Arc::new(Wheel {
+            position: Mutex::new(0.0),
+            error: 2.0,
+        });
+        let positioner =
+            MappedPositioner::new(wheel_config(), PositionerOutput::Movable(stalled)).unwrap();
+        let error = positioner.move_to_position("nd1").await.unwrap_err();
+        assert!(error.to_string().contains("did not reach"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn test_output_slots_read_back() {
+        let config: DiscretePositionerConfig = toml::from_str(
+            r#"
+            id = "flip_mirror"
+            drive = { type = "outputs", device = "dio" }
+            positions = [
+                { name = "up", outputs = { line0 = true } },
+                { name = "down", outputs = { line0 = false } },
+            ]
+            "#,
+        )
+        .unwrap();
+        let dio = Arc::new(Dio::default());
+        let positioner =
+            MappedPositioner::new(config.clone(), PositionerOutput::Outputs(dio.clone())).unwrap();
+
+        assert_eq!(
+            positioner.current_position().await.unwrap().as_deref(),
+            Some("down")
+        );
+        positioner.move_to_position("up").await.unwrap();
+        assert_eq!(dio.lines.lock().unwrap()["line0"], Value::Bool(true));
+        // A driver reporting the line as 1 still counts as up
+        dio.lines
+            .lock()
+            .unwrap()
+            .insert("line0".to_string(), Value::from(1));
+        assert_eq!(
+            positioner.current_position().await.unwrap().as_deref(),
+            Some("up")
+        );
+
+        // Wrong device kind for the drive
+        let wheel = Arc::new(Wheel {
+            position: Mutex::new(0.0),
+            error: 0.0,
+        });
+        assert!(MappedPositioner::new(config, PositionerOutput::Movable(wheel)).is_err());
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(wheel_config().validate().is_ok());
+
+        let mut overlapping = wheel_config();
+        overlapping.positions[1].position = Some(0.8);
+        assert!(overlapping.validate().is_err());
+
+        let mut duplicate = wheel_config();
+        duplicate.positions[1].name = "open".to_string();
+        assert!(duplicate.validate().is_err());
+
+        let mut missing = wheel_config();
+        missing.positions[0].position = None;
+        assert!(missing.validate().is_err());
+    }
+}
@@ -0,0 +1,442 @@
+//! Document stream transforms.
+//!
+//! Each consumer of the run engine's document stream (storage, the gRPC
+//! document stream, ...) can have documents reshaped on their way to it: large
+//! arrays stripped for network clients, computed fields added, operator names
+//! redacted for data leaving the lab. Transforms are configured per consumer
+//! in the hardware config or registered by plugins on [`DocumentTransforms`],
+//! and every consumer runs its own chain over its own copy of the document.
+//!
+//! Transforms reshape documents but never drop them, so stream sequence
+//! numbers stay gap-free.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [[document_transforms]]
+//! type = "strip_arrays"
+//! consumers = ["stream"]
+//! max_bytes = 65536
+//!
+//! [[document_transforms]]
+//! type = "redact"
+//! consumers = ["storage", "stream"]
+//! keys = ["operator", "operator_email"]
+//!
+//! [[document_transforms]]
+//! type = "computed_field"
+//! field = "transmission"
+//! terms = { power_out = 1.0 }
+//! divide_by = "power_in"
+//! ```
+
+use crate::experiment::document::Document;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Consumer name of the storage writer
+pub const STORAGE_CONSUMER: &str = "storage";
+
+/// Consumer name of the gRPC document stream
+pub const STREAM_CONSUMER: &str = "stream";
+
+/// Consumer name matching every consumer
+pub const ALL_CONSUMERS: &str = "*";
+
+/// Event metadata key listing the arrays a transform removed
+pub const STRIPPED_ARRAYS_KEY: &str = "transform.stripped_arrays";
+
+/// Default text put in place of redacted values
+pub const DEFAULT_REDACTION: &str = "[redacted]";
+
+/// Hook that reshapes documents for one or more consumers
+///
+/// Called on the consumer's task for every document: implementations must
+/// be fast and must not block or perform I/O.
+pub trait DocumentTransform: Send + Sync {
+    fn transform(&self, doc: Document) -> Document;
+
+    /// Name for logging
+    fn name(&self) -> &'static str;
+}
+
+fn all_consumers() -> Vec<String> {
+    vec![ALL_CONSUMERS.to_string()]
+}
+
+fn default_redaction() -> String {
+    DEFAULT_REDACTION.to_string()
+}
+
+/// A transform from configuration, with the consumers it applies to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentTransformConfig {
+    /// Consumer names; `"*"` (the default) applies to every consumer
+    #[serde(default = "all_consumers")]
+    pub consumers: Vec<String>,
+    #[serde(flatten)]
+    pub kind: TransformKind,
+}
+
+/// Built-in transforms
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TransformKind {
+    /// Remove event arrays larger than `max_bytes` (0 removes all arrays)
+    StripArrays {
+        #[serde(default)]
+        max_bytes: usize,
+    },
+    /// Replace metadata and plan argument values under `keys`
+    Redact {
+        keys: Vec<String>,
+        #[serde(default = "default_redaction")]
+        replacement: String,
+    },
+    /// Add `field` to events: `(offset + Σ coefficient × data[key]) / data[divide_by]`
+    ///
+    /// Events missing any input are left unchanged.
+    ComputedField {
+        field: String,
+        terms: HashMap<String, f64>,
+        #[serde(default)]
+        offset: f64,
+        #[serde(default)]
+        divide_by: Option<String>,
+    },
+}
+
+impl DocumentTransformConfig {
+    /// Whether the transform applies to `consumer`
+    pub fn applies_to(&self, consumer: &str) -> bool {
+        consumer_matches(&self.consumers, consumer)
+    }
+
+    pub fn build(&self) -> Arc<dyn DocumentTransform> {
+        match &self.kind {
+            TransformKind::StripArrays { max_bytes } => Arc::new(StripArrays {
+                max_bytes: *max_bytes,
+            }),
+            TransformKind::Redact { keys, replacement } => Arc::new(Redact {
+                keys: keys.clone(),
+                replacement: replacement.clone(),
+            }),
+            TransformKind::ComputedField {
+                field,
+                terms,
+                offset,
+                divide_by,
+            } => Arc::new(ComputedField {
+                field: field.clone(),
+                terms: terms.clone(),
+                offset: *offset,
+                divide_by: divide_by.clone(),
+            }),
+        }
+    }
+}
+
+fn consumer_matches(consumers: &[String], consumer: &str) -> bool {
+    consumers
+        .iter()
+        .any(|c| c == ALL_CONSUMERS || c == consumer)
+}
+
+/// Removes event arrays above a size limit
+#[derive(Debug, Clone)]
+pub struct StripArrays {
+    pub max_bytes: usize,
+}
+
+impl DocumentTransform for StripArrays {
+    fn transform(&self, doc: Document) -> Document {
+        let Document::Event(mut event) = doc else {
+            return doc;
+        };
+        let mut stripped: Vec<String> = event
+            .arrays
+            .iter()
+            .filter(|(_, bytes)| self.max_bytes == 0 || bytes.len() > self.max_bytes)
+            .map(|(key, _)| key.clone())
+            .collect();
+        if !stripped.is_empty() {
+            stripped.sort_unstable();
+            for key in &stripped {
+                event.arrays.remove(key);
+            }
+            event
+                .metadata
+                .insert(STRIPPED_ARRAYS_KEY.to_string(), stripped.join(","));
+        }
+        Document::Event(event)
+    }
+
+    fn name(&self) -> &'static str {
+        "strip_arrays"
+    }
+}
+
+/// Replaces sensitive metadata values, e.g. operator names
+#[derive(Debug, Clone)]
+pub struct Redact {
+    pub keys: Vec<String>,
+    pub replacement: String,
+}
+
+impl Redact {
+    fn redact(&self, map: &mut HashMap<String, String>) {
+        for key in &self.keys {
+            if let Some(value) = map.get_mut(key) {
+                value.clone_from(&self.replacement);
+            }
+        }
+    }
+}
+
+impl DocumentTransform for Redact {
+    fn transform(&self, mut doc: Document) -> Document {
+        match &mut doc {
+            Document::Start(start) => {
+                self.redact(&mut start.metadata);
+                self.redact(&mut start.plan_args);
+            }
+            Document::Event(event) => self.redact(&mut event.metadata),
+            Document::Stop(stop) => self.redact(&mut stop.metadata),
+            Document::Manifest(manifest) => self.redact(&mut manifest.metadata),
+            Document::Descriptor(_)
+            | Document::Progress(_)
+            | Document::BatchSummary(_)
+            | Document::DeviceEvent(_) => {}
+        }
+        doc
+    }
+
+    fn name(&self) -> &'static str {
+        "redact"
+    }
+}
+
+/// Adds a field computed from an event's scalar data
+#[derive(Debug, Clone)]
+pub struct ComputedField {
+    pub field: String,
+    pub terms: HashMap<String, f64>,
+    pub offset: f64,
+    pub divide_by: Option<String>,
+}
+
+impl ComputedField {
+    fn compute(&self, data: &HashMap<String, f64>) -> Option<f64> {
+        let mut value = self.offset;
+        for (key, coefficient) in &self.terms {
+            value += coefficient * data.get(key)?;
+        }
+        match &self.divide_by {
+            Some(key) => {
+                let divisor = *data.get(key)?;
+                (divisor != 0.0).then(|| value / divisor)
+            }
+            None => Some(value),
+        }
+    }
+}
+
+impl DocumentTransform for ComputedField {
+    fn transform(&self, doc: Document) -> Document {
+        let Document::Event(mut event) = doc else {
+            return doc;
+        };
+        if let Some(value) = self.compute(&event.data) {
+            event.data.insert(self.field.clone(), value);
+        }
+        Document::Event(event)
+    }
+
+    fn name(&self) -> &'static str {
+        "computed_field"
+    }
+}
+
+struct Entry {
+    consumers: Vec<String>,
+    transform: Arc<dyn DocumentTransform>,
+}
+
+/// Transform chains of every document consumer
+///
+/// Configured transforms run first, in config order, followed by those
+/// registered by plugins in registration order.
+#[derive(Default)]
+pub struct DocumentTransforms {
+    configured: RwLock<Vec<Entry>>,
+    registered: RwLock<Vec<Entry>>,
+}
+
+impl DocumentTransforms {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the configured transforms (plugin registrations are kept)
+    pub fn set_config(&self, configs: &[DocumentTransformConfig]) {
+        let entries = configs
+            .iter()
+            .map(|config| Entry {
+                consumers: config.consumers.clone(),
+                transform: config.build(),
+            })
+            .collect();
+        *self.configured.write().unwrap_or_else(|p| p.into_inner()) = entries;
+    }
+
+    /// Add a transform for `consumers` (`"*"` for all)
+    pub fn register(&self, consumers: &[&str], transform: Arc<dyn DocumentTransform>) {
+        self.registered
+            .write()
+            .unwrap_or_else(|p| p.into_inner())
+            .push(Entry {
+                consumers: consumers.iter().map(ToString::to_string).collect(),
+                transform,
+            });
+    }
+
+    /// Transforms applied to `consumer`, in order
+    pub fn chain(&self, consumer: &str) -> Vec<Arc<dyn DocumentTransform>> {
+        let configured = self.configured.read().unwrap_or_else(|p| p.into_inner());
+        let registered = self.registered.read().unwrap_or_else(|p| p.into_inner());
+        configured
+            .iter()
+            .chain(registered.iter())
+            .filter(|entry| consumer_matches(&entry.consumers, consumer))
+            .map(|entry| entry.transform.clone())
+            .collect()
+    }
+
+    /// Run `consumer`'s chain over `doc`
+    pub fn apply(&self, consumer: &str, doc: Document) -> Document {
+        self.chain(consumer)
+            .iter()
+            .fold(doc, |doc, transform| transform.transform(doc))
+    }
+}
+
+impl std::fmt::Debug for DocumentTransforms {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = |entries: &RwLock<Vec<Entry>>| -> Vec<&'static str> {
+            entries
+                .read()
+                .unwrap_or_else(|p| p.into_inner())
+                .iter()
+                .map(|entry| entry.transform.name())
+                .collect()
+        };
+        f.debug_struct("DocumentTransforms")
+            .field("configured", &names(&self.configured))
+            .field("registered", &names(&self.registered))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use crate::experiment::document::{EventDoc, StartDoc};
+
+    fn event() -> Document {
+        let mut event = EventDoc::new("run", "desc", 0);
+        event.data.insert("power_in".to_string(), 2.0);
+        event.data.insert("power_out".to_string(), 1.5);
+        event.arrays.insert("camera".to_string(), vec![0; 1024]);
+        event.arrays.insert("trace".to_string(), vec![0; 16]);
+        Document::Event(event)
+    }
+
+    #[test]
+    fn test_config_parses() {
+        #[derive(Deserialize)]
+        struct File {
+            document_transforms: Vec<DocumentTransformConfig>,
+        }
+        let file: File = toml::from_str(
+            r#"
+            [[document_transforms]]
+            type = "strip_arrays"
+            consumers = ["stream"]
+            max_bytes = 64
+
+            [[document_transforms]]
+            type = "redact"
+            keys = ["operator"]
+            "#,
+        )
+        .unwrap();
+        let [strip, redact] = file.document_transforms.as_slice() else {
+            panic!("expected two transforms");
+        };
+        assert!(strip.applies_to(STREAM_CONSUMER));
+        assert!(!strip.applies_to(STORAGE_CONSUMER));
+        assert!(redact.applies_to(STORAGE_CONSUMER));
+        assert_eq!(
+            redact.kind,
+            TransformKind::Redact {
+                keys: vec!["operator".to_string()],
+                replacement: DEFAULT_REDACTION.to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_chains_are_per_consumer() {
+        let transforms = DocumentTransforms::new();
+        transforms.set_config(&[
+            DocumentTransformConfig {
+                consumers: vec![STREAM_CONSUMER.to_string()],
+                kind: TransformKind::StripArrays { max_bytes: 64 },
+            },
+            DocumentTransformConfig {
+                consumers: all_consumers(),
+                kind: TransformKind::ComputedField {
+                    field: "transmission".to_string(),
+                    terms: HashMap::from([("power_out".to_string(), 1.0)]),
+                    offset: 0.0,
+                    divide_by: Some("power_in".to_string()),
+                },
+            },
+        ]);
+
+        let Document::Event(streamed) = transforms.apply(STREAM_CONSUMER, event()) else {
+            panic!("expected event");
+        };
+        assert_eq!(streamed.arrays.len(), 1);
+        assert_eq!(streamed.metadata[STRIPPED_ARRAYS_KEY], "camera");
+        assert_eq!(streamed.data["transmission"], 0.75);
+
+        let Document::Event(stored) = transforms.apply(STORAGE_CONSUMER, event()) else {
+            panic!("expected event");
+        };
+        assert_eq!(stored.arrays.len(), 2);
+        assert_eq!(stored.data["transmission"], 0.75);
+
+        // Plugin transforms follow the configured ones and survive reconfiguration
+        transforms.register(
+            &["export"],
+            Arc::new(Redact {
+                keys: vec!["operator".to_string()],
+                replacement: "anon".to_string(),
+            }),
+        );
+        transforms.set_config(&[]);
+        assert!(transforms.chain(STREAM_CONSUMER).is_empty());
+
+        let mut start = StartDoc::new("count", "Count");
+        start
+            .metadata
+            .insert("operator".to_string(), "alice".to_string());
+        let Document::Start(exported) = transforms.apply("export", Document::Start(start)) else {
+            panic!("expected start");
+        };
+        assert_eq!(exported.metadata["operator"], "anon");
+    }
+}
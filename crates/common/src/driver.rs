@@ -67,11 +67,14 @@
 //! ```
 
 use crate::capabilities::{
-    Commandable, DeviceCategory, EmissionControl, ExposureControl, FrameProducer, Movable,
-    Parameterized, Readable, Settable, ShutterControl, Stageable, Triggerable, WavelengthTunable,
+    Commandable, DeviceCategory, DiscretePositioner, EmissionControl, ExposureControl,
+    FrameProducer, Movable, Parameterized, RawTerminal, Readable, Settable, ShutterControl,
+    Stageable, Triggerable, WavelengthTunable,
 };
 use crate::data::Frame;
+use crate::device_events::DeviceEventSink;
 use crate::pipeline::MeasurementSource;
+use crate::state_machine::{MachineKind, StateTracker};
 use anyhow::Result;
 use futures::future::BoxFuture;
 use serde::{Deserialize, Serialize};
@@ -147,6 +150,14 @@ pub enum Capability {
     /// Has observable parameters with subscriptions
     /// Corresponds to [`crate::capabilities::Parameterized`]
     Parameterized,
+
+    /// Accepts raw command strings (instrument console)
+    /// Corresponds to [`crate::capabilities::RawTerminal`]
+    RawTerminal,
+
+    /// Has named slots (filter wheels, sample changers, flip mirrors)
+    /// Corresponds to [`crate::capabilities::DiscretePositioner`]
+    DiscretePositioner,
 }
 
 impl Capability {
@@ -165,6 +176,8 @@ impl Capability {
             Self::Commandable => "Commandable",
             Self::Stageable => "Stageable",
             Self::Parameterized => "Parameterized",
+            Self::RawTerminal => "Raw Terminal",
+            Self::DiscretePositioner => "Discrete Positioner",
         }
     }
 
@@ -183,6 +196,8 @@ impl Capability {
             Self::Commandable => "commandable",
             Self::Stageable => "stageable",
             Self::Parameterized => "parameterized",
+            Self::RawTerminal => "raw_terminal",
+            Self::DiscretePositioner => "discrete_positioner",
         }
     }
 }
@@ -262,9 +277,22 @@ pub struct DeviceComponents {
     /// WavelengthTunable implementation (tunable wavelength)
     pub wavelength_tunable: Option<Arc<dyn WavelengthTunable>>,
 
+    /// RawTerminal implementation (raw command passthrough)
+    pub raw_terminal: Option<Arc<dyn RawTerminal>>,
+
+    /// DiscretePositioner implementation (named slots)
+    pub discrete_positioner: Option<Arc<dyn DiscretePositioner>>,
+
     /// Optional lifecycle hooks for device registration/shutdown
     pub lifecycle: Option<Arc<dyn DeviceLifecycle>>,
 
+    /// Status the driver updates (the registry creates one if unset)
+    pub status: Option<DeviceStatusHandle>,
+
+    /// Sink for events the driver emits (limit hits, range changes, ...);
+    /// status changes are reported through `status`
+    pub events: Option<DeviceEventSink>,
+
     /// Capability-specific metadata (units, ranges, etc.)
     pub metadata: DeviceMetadata,
 }
@@ -315,6 +343,12 @@ impl DeviceComponents {
         if self.parameterized.is_some() {
             caps.push(Capability::Parameterized);
         }
+        if self.raw_terminal.is_some() {
+            caps.push(Capability::RawTerminal);
+        }
+        if self.discrete_positioner.is_some() {
+            caps.push(Capability::DiscretePositioner);
+        }
 
         caps
     }
@@ -408,12 +442,36 @@ impl DeviceComponents {
         self
     }
 
+    /// Set RawTerminal implementation
+    pub fn with_raw_terminal(mut self, r: Arc<dyn RawTerminal>) -> Self {
+        self.raw_terminal = Some(r);
+        self
+    }
+
+    /// Set DiscretePositioner implementation
+    pub fn with_discrete_positioner(mut self, d: Arc<dyn DiscretePositioner>) -> Self {
+        self.discrete_positioner = Some(d);
+        self
+    }
+
     /// Set device lifecycle hooks
     pub fn with_lifecycle(mut self, lifecycle: Arc<dyn DeviceLifecycle>) -> Self {
         self.lifecycle = Some(lifecycle);
         self
     }
 
+    /// Report device status through `status` (keep a clone in the driver)
+    pub fn with_status(mut self, status: DeviceStatusHandle) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Emit device events through `events` (keep a clone in the driver)
+    pub fn with_events(mut self, events: DeviceEventSink) -> Self {
+        self.events = Some(events);
+        self
+    }
+
     /// Set device metadata
     pub fn with_metadata(mut self, metadata: DeviceMetadata) -> Self {
         self.metadata = metadata;
@@ -466,6 +524,174 @@ pub struct DeviceMetadata {
 
     /// For WavelengthTunable devices: maximum wavelength in nm
     pub max_wavelength_nm: Option<f64>,
+
+    /// Identity reported by the vendor SDK (model, serial, firmware).
+    /// Devices without one are asked `*IDN?` for the inventory.
+    pub identity: Option<DeviceIdentity>,
+}
+
+// =============================================================================
+// Device Identity
+// =============================================================================
+
+/// Instrument identity, as recorded in the inventory and run manifests
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceIdentity {
+    #[serde(default)]
+    pub manufacturer: String,
+    #[serde(default)]
+    pub model: String,
+    #[serde(default)]
+    pub serial_number: String,
+    #[serde(default)]
+    pub firmware_version: String,
+}
+
+impl DeviceIdentity {
+    /// Parse an IEEE 488.2 `*IDN?` response
+    /// (`<manufacturer>,<model>,<serial>,<firmware>`).
+    ///
+    /// Missing trailing fields are left empty; returns `None` for an empty
+    /// response.
+    pub fn from_idn(response: &str) -> Option<Self> {
+        let response = response.trim();
+        if response.is_empty() {
+            return None;
+        }
+        let mut fields = response.splitn(4, ',').map(|f| f.trim().to_string());
+        Some(Self {
+            manufacturer: fields.next().unwrap_or_default(),
+            model: fields.next().unwrap_or_default(),
+            serial_number: fields.next().unwrap_or_default(),
+            firmware_version: fields.next().unwrap_or_default(),
+        })
+    }
+}
+
+impl std::fmt::Display for DeviceIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} (serial {}, firmware {})",
+            self.manufacturer, self.model, self.serial_number, self.firmware_version
+        )
+    }
+}
+
+// =============================================================================
+// Device Status
+// =============================================================================
+
+/// Operational status of a registered device
+///
+/// Set by the driver through its [`DeviceStatusHandle`], and by the registry
+/// and health monitor when operations fail, so clients can react to degraded
+/// hardware before it times out.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum DeviceStatus {
+    /// Registered but not ready for use yet (e.g. homing, warming up)
+    Uninitialized,
+    /// Operational and idle
+    Ready,
+    /// Operational, executing an operation (moving, acquiring)
+    Busy,
+    /// Working, with reduced performance or reliability
+    Degraded { reason: String },
+    /// Unusable until the fault is cleared
+    Fault { code: i32, message: String },
+}
+
+impl DeviceStatus {
+    /// Snake_case state name for proto/API transport and filtering
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Uninitialized => "uninitialized",
+            Self::Ready => "ready",
+            Self::Busy => "busy",
+            Self::Degraded { .. } => "degraded",
+            Self::Fault { .. } => "fault",
+        }
+    }
+
+    /// Whether operations can be sent to the device
+    pub fn is_operational(&self) -> bool {
+        matches!(self, Self::Ready | Self::Busy | Self::Degraded { .. })
+    }
+}
+
+impl std::fmt::Display for DeviceStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Degraded { reason } => write!(f, "degraded: {}", reason),
+            Self::Fault { code, message } => write!(f, "fault {}: {}", code, message),
+            other => write!(f, "{}", other.as_str()),
+        }
+    }
+}
+
+/// Shared, updatable status of one device
+///
+/// Clones share the same status; the driver keeps one and the registry
+/// reads (and may override) the other. Notable changes are published as
+/// state-change [`DeviceEvent`](crate::device_events::DeviceEvent)s once
+/// the registry has attached the handle's event sink.
+#[derive(Debug, Clone)]
+pub struct DeviceStatusHandle {
+    status: Arc<std::sync::RwLock<DeviceStatus>>,
+    events: DeviceEventSink,
+}
+
+impl DeviceStatusHandle {
+    /// Create a handle starting at `status`
+    pub fn new(status: DeviceStatus) -> Self {
+        Self {
+            status: Arc::new(std::sync::RwLock::new(status)),
+            events: DeviceEventSink::new(),
+        }
+    }
+
+    /// Current status
+    pub fn get(&self) -> DeviceStatus {
+        self.status
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+
+    /// Replace the status, returning the previous one
+    pub fn set(&self, status: DeviceStatus) -> DeviceStatus {
+        let previous = std::mem::replace(
+            &mut *self.status.write().unwrap_or_else(|p| p.into_inner()),
+            status.clone(),
+        );
+        self.events.emit_status_change(&previous, &status);
+        if let Some(device_id) = self.events.device_id() {
+            let trigger = match &status {
+                DeviceStatus::Degraded { reason } => reason.as_str(),
+                DeviceStatus::Fault { message, .. } => message.as_str(),
+                _ => "status_update",
+            };
+            StateTracker::global().record(
+                MachineKind::Device,
+                &device_id,
+                status.as_str(),
+                trigger,
+            );
+        }
+        previous
+    }
+
+    /// Sink the status changes are published to
+    pub fn events(&self) -> &DeviceEventSink {
+        &self.events
+    }
+}
+
+impl Default for DeviceStatusHandle {
+    fn default() -> Self {
+        Self::new(DeviceStatus::Ready)
+    }
 }
 
 // =============================================================================
@@ -605,6 +831,41 @@ mod tests {
         assert!(empty.capabilities().is_empty());
     }
 
+    #[test]
+    fn test_device_identity_from_idn() {
+        let identity = DeviceIdentity::from_idn("Spectra-Physics,MaiTai,1234, 2.15.3\n").unwrap();
+        assert_eq!(identity.model, "MaiTai");
+        assert_eq!(identity.serial_number, "1234");
+        assert_eq!(identity.firmware_version, "2.15.3");
+
+        let partial = DeviceIdentity::from_idn("NEWPORT ESP300").unwrap();
+        assert_eq!(partial.manufacturer, "NEWPORT ESP300");
+        assert!(partial.firmware_version.is_empty());
+        assert!(DeviceIdentity::from_idn("  ").is_none());
+    }
+
+    #[test]
+    fn test_device_status_handle_is_shared() {
+        let driver = DeviceStatusHandle::new(DeviceStatus::Uninitialized);
+        let registry = driver.clone();
+        assert!(!registry.get().is_operational());
+
+        let previous = driver.set(DeviceStatus::Degraded {
+            reason: "TEC not at setpoint".to_string(),
+        });
+        assert_eq!(previous, DeviceStatus::Uninitialized);
+        assert!(registry.get().is_operational());
+        assert_eq!(registry.get().to_string(), "degraded: TEC not at setpoint");
+
+        let json = serde_json::to_value(DeviceStatus::Fault {
+            code: -113,
+            message: "Undefined header".to_string(),
+        })
+        .unwrap();
+        assert_eq!(json["state"], "fault");
+        assert_eq!(json["code"], -113);
+    }
+
     #[test]
     fn test_capability_serde() {
         // Test serialization
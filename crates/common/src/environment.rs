@@ -0,0 +1,190 @@
+//! Ambient environment log.
+//!
+//! Slow environment channels (lab temperature, humidity, optical table
+//! vibration) are recorded here by the environment monitor module. The run
+//! engine attaches an [`EnvironmentSummary`] covering each run to its
+//! StopDoc under [`ENVIRONMENT_METADATA_KEY`], so drift in the lab can be
+//! correlated with data quality afterwards.
+//!
+//! Samples older than the retention window are dropped, as are the oldest
+//! samples of a channel once [`MAX_SAMPLES_PER_CHANNEL`] is reached.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// StopDoc metadata key holding the JSON [`EnvironmentSummary`] of the run
+pub const ENVIRONMENT_METADATA_KEY: &str = "environment";
+
+/// Default time samples are kept for
+pub const DEFAULT_ENVIRONMENT_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Upper bound on stored samples per channel, whatever the retention window
+pub const MAX_SAMPLES_PER_CHANNEL: usize = 100_000;
+
+/// Statistics of one channel over a time span
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelSummary {
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Samples inside the span (0 = only an earlier sample was available)
+    pub count: usize,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub units: String,
+}
+
+/// Per-channel statistics, keyed by channel name
+pub type EnvironmentSummary = BTreeMap<String, ChannelSummary>;
+
+#[derive(Debug, Default)]
+struct ChannelLog {
+    units: String,
+    /// `(timestamp_ns, value)`, ordered by timestamp
+    samples: VecDeque<(u64, f64)>,
+}
+
+/// In-memory, time-ordered log of environment channels
+#[derive(Debug)]
+pub struct EnvironmentLog {
+    retention: Duration,
+    channels: Mutex<HashMap<String, ChannelLog>>,
+}
+
+impl Default for EnvironmentLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_ENVIRONMENT_RETENTION)
+    }
+}
+
+impl EnvironmentLog {
+    /// Empty log keeping samples for `retention`
+    pub fn new(retention: Duration) -> Self {
+        Self {
+            retention,
+            channels: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, ChannelLog>> {
+        self.channels.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Append a sample, dropping samples that fell out of the retention window
+    pub fn record(&self, channel: &str, units: &str, value: f64, timestamp_ns: u64) {
+        let mut channels = self.lock();
+        let log = channels.entry(channel.to_string()).or_default();
+        if log.units != units {
+            log.units = units.to_string();
+        }
+
+        // Samples normally arrive in order; a late one is inserted in place
+        let index = log.samples.partition_point(|&(t, _)| t <= timestamp_ns);
+        log.samples.insert(index, (timestamp_ns, value));
+
+        let newest = log.samples.back().map_or(timestamp_ns, |&(t, _)| t);
+        let cutoff = newest.saturating_sub(self.retention.as_nanos() as u64);
+        while log.samples.len() > MAX_SAMPLES_PER_CHANNEL
+            || log.samples.front().is_some_and(|&(t, _)| t < cutoff)
+        {
+            log.samples.pop_front();
+        }
+    }
+
+    /// Names of all recorded channels, sorted
+    pub fn channels(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.lock().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Most recent `(timestamp_ns, value)` of a channel
+    pub fn latest(&self, channel: &str) -> Option<(u64, f64)> {
+        self.lock().get(channel)?.samples.back().copied()
+    }
+
+    /// Statistics of every channel between `start_ns` and `end_ns`, inclusive
+    ///
+    /// A channel polled less often than the span is long reports its last
+    /// earlier sample with a `count` of 0, so short runs still get a value.
+    pub fn summary(&self, start_ns: u64, end_ns: u64) -> EnvironmentSummary {
+        let channels = self.lock();
+        let mut summary = EnvironmentSummary::new();
+        for (name, log) in channels.iter() {
+            let first = log.samples.partition_point(|&(t, _)| t < start_ns);
+            let last = log.samples.partition_point(|&(t, _)| t <= end_ns);
+            let channel = if first < last {
+                let values = log.samples.range(first..last).map(|&(_, v)| v);
+                let (mut sum, mut min, mut max) = (0.0, f64::INFINITY, f64::NEG_INFINITY);
+                for value in values {
+                    sum += value;
+                    min = min.min(value);
+                    max = max.max(value);
+                }
+                ChannelSummary {
+                    mean: sum / (last - first) as f64,
+                    min,
+                    max,
+                    count: last - first,
+                    units: log.units.clone(),
+                }
+            } else if let Some(&(_, value)) = first.checked_sub(1).and_then(|i| log.samples.get(i))
+            {
+                ChannelSummary {
+                    mean: value,
+                    min: value,
+                    max: value,
+                    count: 0,
+                    units: log.units.clone(),
+                }
+            } else {
+                continue;
+            };
+            summary.insert(name.clone(), channel);
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const S: u64 = 1_000_000_000;
+
+    #[test]
+    fn test_summary_over_span() {
+        let log = EnvironmentLog::default();
+        for (i, value) in [21.0, 21.5, 22.0, 23.0].into_iter().enumerate() {
+            log.record("lab_temperature", "degC", value, (i as u64 + 1) * 10 * S);
+        }
+        log.record("humidity", "%RH", 40.0, 5 * S);
+
+        let summary = log.summary(15 * S, 30 * S);
+        let temperature = &summary["lab_temperature"];
+        assert_eq!(temperature.count, 2);
+        assert!((temperature.mean - 21.75).abs() < 1e-9);
+        assert_eq!((temperature.min, temperature.max), (21.5, 22.0));
+        assert_eq!(temperature.units, "degC");
+
+        // Humidity was last polled before the span
+        assert_eq!(summary["humidity"].count, 0);
+        assert!((summary["humidity"].mean - 40.0).abs() < 1e-9);
+
+        assert!(log.summary(0, S).is_empty());
+        assert_eq!(log.latest("lab_temperature"), Some((40 * S, 23.0)));
+    }
+
+    #[test]
+    fn test_samples_outside_retention_are_dropped() {
+        let log = EnvironmentLog::new(Duration::from_secs(60));
+        log.record("vibration", "mg", 1.0, 0);
+        log.record("vibration", "mg", 2.0, 120 * S);
+
+        let summary = log.summary(0, 120 * S);
+        assert_eq!(summary["vibration"].count, 1);
+        assert!((summary["vibration"].mean - 2.0).abs() < 1e-9);
+        assert_eq!(log.channels(), ["vibration"]);
+    }
+}
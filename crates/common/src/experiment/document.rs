@@ -7,6 +7,10 @@
 //! - **DescriptorDoc**: Schema for data streams
 //! - **EventDoc**: Actual measurements at each point
 //! - **StopDoc**: Completion status and summary
+//! - **ProgressDoc**: Points completed/total and ETA for progress displays
+//! - **BatchSummaryDoc**: Outcomes of the child runs of a template sweep
+//! - **DeviceEventDoc**: State change, error, limit hit or range change of a
+//!   device during the run
 //! - **ExperimentManifest**: Hardware parameter snapshot for reproducibility (bd-ej44)
 //!
 //! # Provenance Tracking
@@ -28,13 +32,21 @@
 //!    │
 //!    ├── DescriptorDoc (1+, one per data stream)
 //!    │       │
-//!    │       └── EventDoc (N, measurements)
+//!    │       ├── EventDoc (N, measurements)
+//!    │       └── ProgressDoc (N, one after each EventDoc)
+//!    │
+//!    ├── DeviceEventDoc (N, as devices report them)
 //!    │
 //! StopDoc (1)
+//!
+//! BatchSummaryDoc (1 per batch, after the StopDoc of its last child run)
 //! ```
 
+use crate::core::DataQuality;
+use crate::device_events::DeviceEvent;
+use crate::driver::DeviceIdentity;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
@@ -67,6 +79,12 @@ pub enum Document {
     Stop(StopDoc),
     /// Experiment manifest - hardware parameter snapshot (bd-ib06)
     Manifest(ExperimentManifest),
+    /// Run progress - points completed and estimated time remaining
+    Progress(ProgressDoc),
+    /// Outcome of every child run of a template sweep
+    BatchSummary(BatchSummaryDoc),
+    /// Something that happened to a device during the run
+    DeviceEvent(DeviceEventDoc),
 }
 
 impl Document {
@@ -78,6 +96,9 @@ impl Document {
             Document::Event(d) => &d.uid,
             Document::Stop(d) => &d.uid,
             Document::Manifest(d) => &d.run_uid,
+            Document::Progress(d) => &d.uid,
+            Document::BatchSummary(d) => &d.uid,
+            Document::DeviceEvent(d) => &d.uid,
         }
     }
 
@@ -89,6 +110,10 @@ impl Document {
             Document::Event(d) => &d.run_uid,
             Document::Stop(d) => &d.run_uid,
             Document::Manifest(d) => &d.run_uid,
+            Document::Progress(d) => &d.run_uid,
+            // A batch has no run of its own
+            Document::BatchSummary(d) => &d.batch_uid,
+            Document::DeviceEvent(d) => &d.run_uid,
         }
     }
 
@@ -100,6 +125,9 @@ impl Document {
             Document::Event(d) => d.time_ns,
             Document::Stop(d) => d.time_ns,
             Document::Manifest(d) => d.timestamp_ns,
+            Document::Progress(d) => d.time_ns,
+            Document::BatchSummary(d) => d.time_ns,
+            Document::DeviceEvent(d) => d.event.timestamp_ns,
         }
     }
 }
@@ -337,6 +365,31 @@ impl EventDoc {
         self.arrays.insert(key.to_string(), bytes);
         self
     }
+
+    /// Flag the quality of data field `field`
+    ///
+    /// Stored as `<field>.quality` metadata; good values leave no entry.
+    pub fn set_quality(&mut self, field: &str, quality: DataQuality) {
+        let key = quality_key(field);
+        if quality.is_good() {
+            self.metadata.remove(&key);
+        } else {
+            self.metadata.insert(key, quality.as_str().to_string());
+        }
+    }
+
+    /// Quality of data field `field` (good unless flagged)
+    pub fn quality(&self, field: &str) -> DataQuality {
+        self.metadata
+            .get(&quality_key(field))
+            .and_then(|quality| quality.parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Event metadata key holding the quality flag of data field `field`
+pub fn quality_key(field: &str) -> String {
+    format!("{}.quality", field)
 }
 
 /// Stop document - emitted at the end of a run
@@ -354,6 +407,9 @@ pub struct StopDoc {
     pub time_ns: u64,
     /// Total events emitted
     pub num_events: u32,
+    /// Run summaries added at stop time (e.g. the environment summary)
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
 }
 
 impl StopDoc {
@@ -365,6 +421,7 @@ impl StopDoc {
             reason: String::new(),
             time_ns: now_ns(),
             num_events,
+            metadata: HashMap::new(),
         }
     }
 
@@ -376,6 +433,7 @@ impl StopDoc {
             reason: reason.to_string(),
             time_ns: now_ns(),
             num_events,
+            metadata: HashMap::new(),
         }
     }
 
@@ -387,8 +445,193 @@ impl StopDoc {
             reason: reason.to_string(),
             time_ns: now_ns(),
             num_events,
+            metadata: HashMap::new(),
+        }
+    }
+
+    /// Add a metadata entry
+    pub fn with_metadata(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.to_string(), value.into());
+        self
+    }
+}
+
+/// Progress document - emitted after each event while a run is active
+///
+/// Lets clients draw progress bars without counting Event documents.
+/// `eta_s` is derived from a rolling average of recent per-point durations
+/// and is `None` until at least one point has completed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProgressDoc {
+    /// Unique progress doc ID
+    pub uid: String,
+    /// Links to StartDoc
+    pub run_uid: String,
+    /// Points (events) completed so far
+    pub points_completed: u32,
+    /// Total points expected for the plan
+    pub points_total: u32,
+    /// Current axis positions (device_id -> position)
+    pub positions: HashMap<String, f64>,
+    /// Rolling average time per point in seconds
+    pub mean_point_duration_s: Option<f64>,
+    /// Estimated time remaining in seconds
+    pub eta_s: Option<f64>,
+    /// Seconds since the run started
+    pub elapsed_s: f64,
+    /// Timestamp when this progress was computed
+    pub time_ns: u64,
+}
+
+impl ProgressDoc {
+    pub fn new(run_uid: &str, points_completed: u32, points_total: u32) -> Self {
+        Self {
+            uid: new_uid(),
+            run_uid: run_uid.to_string(),
+            points_completed,
+            points_total,
+            positions: HashMap::new(),
+            mean_point_duration_s: None,
+            eta_s: None,
+            elapsed_s: 0.0,
+            time_ns: now_ns(),
         }
     }
+
+    /// Fraction of points completed in `[0, 1]`, or `None` if the total is unknown
+    pub fn fraction(&self) -> Option<f64> {
+        (self.points_total > 0)
+            .then(|| (f64::from(self.points_completed) / f64::from(self.points_total)).min(1.0))
+    }
+}
+
+/// Device event recorded during a run
+///
+/// Stored apart from the measurement events, in the run's `device_events`
+/// table.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeviceEventDoc {
+    /// Unique document ID
+    pub uid: String,
+    /// Links to StartDoc
+    pub run_uid: String,
+    pub event: DeviceEvent,
+}
+
+impl DeviceEventDoc {
+    pub fn new(run_uid: &str, event: DeviceEvent) -> Self {
+        Self {
+            uid: new_uid(),
+            run_uid: run_uid.to_string(),
+            event,
+        }
+    }
+}
+
+/// Rolling per-point timing used to estimate time remaining in a run
+///
+/// Keeps the last `window` point durations so the estimate adapts when
+/// point cost changes (e.g. longer moves at the end of a scan).
+#[derive(Debug, Clone)]
+pub struct ProgressTracker {
+    window: usize,
+    durations_ns: VecDeque<u64>,
+    run_start_ns: u64,
+    last_point_ns: u64,
+}
+
+impl ProgressTracker {
+    /// Default number of recent points averaged for the ETA
+    pub const DEFAULT_WINDOW: usize = 20;
+
+    pub fn new(run_start_ns: u64) -> Self {
+        Self::with_window(run_start_ns, Self::DEFAULT_WINDOW)
+    }
+
+    pub fn with_window(run_start_ns: u64, window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            durations_ns: VecDeque::new(),
+            run_start_ns,
+            last_point_ns: run_start_ns,
+        }
+    }
+
+    /// Record that a point completed at `time_ns`
+    pub fn record_point(&mut self, time_ns: u64) {
+        let duration = time_ns.saturating_sub(self.last_point_ns);
+        self.last_point_ns = time_ns;
+        if self.durations_ns.len() == self.window {
+            self.durations_ns.pop_front();
+        }
+        self.durations_ns.push_back(duration);
+    }
+
+    /// Rolling average point duration in seconds
+    pub fn mean_point_duration_s(&self) -> Option<f64> {
+        if self.durations_ns.is_empty() {
+            return None;
+        }
+        let sum: u64 = self.durations_ns.iter().sum();
+        Some(sum as f64 / self.durations_ns.len() as f64 / 1e9)
+    }
+
+    /// Build a progress document for the current state of the run
+    pub fn snapshot(
+        &self,
+        run_uid: &str,
+        points_completed: u32,
+        points_total: u32,
+        positions: &HashMap<String, f64>,
+    ) -> ProgressDoc {
+        let mut doc = ProgressDoc::new(run_uid, points_completed, points_total);
+        doc.positions.clone_from(positions);
+        doc.mean_point_duration_s = self.mean_point_duration_s();
+        doc.eta_s = doc
+            .mean_point_duration_s
+            .map(|mean| f64::from(points_total.saturating_sub(points_completed)) * mean);
+        doc.elapsed_s = doc.time_ns.saturating_sub(self.run_start_ns) as f64 / 1e9;
+        doc
+    }
+}
+
+/// Outcome of one child run of a batch
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BatchRunOutcome {
+    pub run_uid: String,
+    /// Position of the run in the batch
+    pub index: u32,
+    /// Sweep variables the template was expanded with
+    pub variables: BTreeMap<String, String>,
+    /// "success", "abort" or "fail"
+    pub exit_status: String,
+    pub reason: String,
+    pub num_events: u32,
+}
+
+/// Batch summary document - emitted once every child run of a template
+/// sweep has stopped or been removed from the queue
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BatchSummaryDoc {
+    /// Unique summary doc ID
+    pub uid: String,
+    /// Shared by the StartDocs of the child runs
+    pub batch_uid: String,
+    /// Name of the template the batch was expanded from
+    pub template_name: String,
+    /// Child runs in batch order
+    pub runs: Vec<BatchRunOutcome>,
+    pub time_ns: u64,
+}
+
+impl BatchSummaryDoc {
+    /// Number of child runs that ended with `exit_status`
+    pub fn count(&self, exit_status: &str) -> usize {
+        self.runs
+            .iter()
+            .filter(|run| run.exit_status == exit_status)
+            .count()
+    }
 }
 
 // =============================================================================
@@ -458,6 +701,15 @@ pub struct ExperimentManifest {
     /// Path to source .expgraph file (if from saved graph)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub graph_file: Option<String>,
+
+    // === Instrument inventory ===
+    /// Identity of each device at run start: device_id -> identity
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub inventory: HashMap<String, DeviceIdentity>,
+
+    /// Identity changes since the previous run (e.g. firmware updates)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub identity_changes: Vec<String>,
 }
 
 impl ExperimentManifest {
@@ -505,6 +757,8 @@ impl ExperimentManifest {
             git_dirty,
             graph_hash: None,
             graph_file: None,
+            inventory: HashMap::new(),
+            identity_changes: Vec::new(),
         }
     }
 
@@ -526,6 +780,17 @@ impl ExperimentManifest {
         self
     }
 
+    /// Add the instrument inventory and any identity changes since the last run
+    pub fn with_inventory(
+        mut self,
+        inventory: HashMap<String, DeviceIdentity>,
+        identity_changes: Vec<String>,
+    ) -> Self {
+        self.inventory = inventory;
+        self.identity_changes = identity_changes;
+        self
+    }
+
     /// Add graph file provenance (call when executing from saved .expgraph)
     ///
     /// This captures the graph file path and computes a SHA256 hash of the
@@ -578,6 +843,26 @@ mod tests {
         assert!(doc.hints.contains(&"x_motor".to_string()));
     }
 
+    #[test]
+    fn test_progress_tracker_rolling_eta() {
+        let mut tracker = ProgressTracker::with_window(0, 2);
+        let positions = HashMap::from([("stage_x".to_string(), 1.5)]);
+
+        let doc = tracker.snapshot("run", 0, 10, &positions);
+        assert_eq!(doc.eta_s, None);
+
+        // Points at 1s, 2s, then a slow 5s point; window of 2 drops the first
+        tracker.record_point(1_000_000_000);
+        tracker.record_point(2_000_000_000);
+        tracker.record_point(7_000_000_000);
+
+        let doc = tracker.snapshot("run", 3, 10, &positions);
+        assert_eq!(doc.mean_point_duration_s, Some(3.0));
+        assert_eq!(doc.eta_s, Some(21.0));
+        assert_eq!(doc.fraction(), Some(0.3));
+        assert_eq!(doc.positions.get("stage_x"), Some(&1.5));
+    }
+
     #[test]
     fn test_descriptor_doc() {
         let run_uid = new_uid();
@@ -590,6 +875,19 @@ mod tests {
         assert!(desc.data_keys.contains_key("position"));
     }
 
+    #[test]
+    fn test_event_quality_flags() {
+        let mut event = EventDoc::new("run", "desc", 0).with_datum("power", 1.0);
+        assert_eq!(event.quality("power"), DataQuality::Good);
+
+        event.set_quality("power", DataQuality::OutOfRange);
+        assert_eq!(event.metadata["power.quality"], "out_of_range");
+        assert_eq!(event.quality("power"), DataQuality::OutOfRange);
+
+        event.set_quality("power", DataQuality::Good);
+        assert!(event.metadata.is_empty());
+    }
+
     #[test]
     fn test_event_doc() {
         let run_uid = new_uid();
@@ -0,0 +1,202 @@
+//! Fault injection hooks for chaos and recovery testing.
+//!
+//! Daemon subsystems check a named fault point at a spot where a real
+//! failure could happen (the storage writer before each flush, the gRPC
+//! server while serving). Nothing happens until a test arms the point on the
+//! process-wide [`FaultInjector`]; the next check then fails the way the
+//! armed [`Fault`] says, once per arming. Supervision, checkpointing and
+//! safe-state logic can so be exercised against the real code paths.
+//!
+//! Devices are not covered here: mock drivers have their own error
+//! injection.
+//!
+//! An unarmed check is a single atomic load.
+//!
+//! Points can only be armed with the `fault-injection` feature (or in this
+//! crate's own tests), which the chaos suite enables as a dev-dependency
+//! feature. Release builds keep the checks but have no way to arm them.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tokio::sync::Notify;
+
+/// Fault points of daemon subsystems
+pub mod points {
+    /// Ring buffer to HDF5 writer, checked before each flush
+    pub const STORAGE_WRITER: &str = "storage.writer";
+    /// gRPC server, which stops as if it crashed
+    pub const GRPC_SERVER: &str = "grpc.server";
+}
+
+/// How an armed fault point fails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// Return an error with this message
+    Error(String),
+    /// Panic with this message
+    Panic(String),
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error(message) => write!(f, "injected error: {}", message),
+            Self::Panic(message) => write!(f, "injected panic: {}", message),
+        }
+    }
+}
+
+/// Armed faults by point, plus how often each point fired
+#[derive(Debug, Default)]
+pub struct FaultInjector {
+    /// Fast path: whether any point is armed
+    armed: AtomicBool,
+    state: Mutex<InjectorState>,
+    notify: Notify,
+}
+
+#[derive(Debug, Default)]
+struct InjectorState {
+    pending: HashMap<String, Vec<Fault>>,
+    fired: HashMap<String, u64>,
+}
+
+impl FaultInjector {
+    /// Process-wide injector checked by the daemon's subsystems
+    pub fn global() -> &'static FaultInjector {
+        static GLOBAL: OnceLock<FaultInjector> = OnceLock::new();
+        GLOBAL.get_or_init(FaultInjector::default)
+    }
+
+    /// Fail the next check of `point` with `fault`
+    ///
+    /// Arming a point several times queues the faults in order.
+    #[cfg(any(test, feature = "fault-injection"))]
+    pub fn arm(&self, point: &str, fault: Fault) {
+        tracing::warn!(point, %fault, "Fault armed");
+        self.lock()
+            .pending
+            .entry(point.to_string())
+            .or_default()
+            .push(fault);
+        self.armed.store(true, Ordering::Release);
+        self.notify.notify_waiters();
+    }
+
+    /// Drop every fault not fired yet
+    pub fn disarm_all(&self) {
+        self.lock().pending.clear();
+        self.armed.store(false, Ordering::Release);
+    }
+
+    /// Whether `point` has a fault waiting
+    pub fn is_armed(&self, point: &str) -> bool {
+        self.armed.load(Ordering::Acquire) && self.lock().pending.contains_key(point)
+    }
+
+    /// How many faults `point` has fired
+    pub fn fired(&self, point: &str) -> u64 {
+        self.lock().fired.get(point).copied().unwrap_or(0)
+    }
+
+    /// Take the next fault of `point`, if one is armed
+    pub fn take(&self, point: &str) -> Option<Fault> {
+        if !self.armed.load(Ordering::Acquire) {
+            return None;
+        }
+        let mut state = self.lock();
+        let queue = state.pending.get_mut(point)?;
+        let fault = queue.remove(0);
+        if queue.is_empty() {
+            state.pending.remove(point);
+        }
+        if state.pending.is_empty() {
+            self.armed.store(false, Ordering::Release);
+        }
+        *state.fired.entry(point.to_string()).or_default() += 1;
+        drop(state);
+        tracing::warn!(point, %fault, "Fault fired");
+        Some(fault)
+    }
+
+    /// Fail here if `point` is armed: an `Error` fault is returned, a
+    /// `Panic` fault panics
+    pub fn check(&self, point: &str) -> anyhow::Result<()> {
+        match self.take(point) {
+            None => Ok(()),
+            Some(Fault::Error(message)) => {
+                Err(anyhow::anyhow!("Injected fault at {}: {}", point, message))
+            }
+            Some(Fault::Panic(message)) => panic!("Injected fault at {}: {}", point, message),
+        }
+    }
+
+    /// Wait until `point` is armed and take its fault
+    ///
+    /// For subsystems without a natural check point, which race this
+    /// against their normal work.
+    pub async fn triggered(&self, point: &str) -> Fault {
+        loop {
+            let notified = self.notify.notified();
+            if let Some(fault) = self.take(point) {
+                return fault;
+            }
+            notified.await;
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, InjectorState> {
+        self.state.lock().unwrap_or_else(|p| p.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_faults_fire_once_in_order() {
+        let injector = FaultInjector::default();
+        assert!(injector.check("writer").is_ok());
+
+        injector.arm("writer", Fault::Error("disk gone".to_string()));
+        injector.arm("writer", Fault::Panic("bug".to_string()));
+        assert!(injector.is_armed("writer"));
+        assert!(injector.check("server").is_ok());
+
+        let error = injector.check("writer").unwrap_err().to_string();
+        assert!(error.contains("disk gone"), "{error}");
+        assert_eq!(
+            injector.take("writer"),
+            Some(Fault::Panic("bug".to_string()))
+        );
+        assert!(!injector.is_armed("writer"));
+        assert!(injector.check("writer").is_ok());
+        assert_eq!(injector.fired("writer"), 2);
+
+        injector.arm("writer", Fault::Error("late".to_string()));
+        injector.disarm_all();
+        assert!(injector.check("writer").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_triggered_waits_for_arming() {
+        let injector = std::sync::Arc::new(FaultInjector::default());
+        let waiter = tokio::spawn({
+            let injector = injector.clone();
+            async move { injector.triggered("server").await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+
+        injector.arm("server", Fault::Error("crash".to_string()));
+        let fault = tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fault, Fault::Error("crash".to_string()));
+    }
+}
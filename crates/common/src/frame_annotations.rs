@@ -0,0 +1,318 @@
+//! Overlay annotations attached to camera frames.
+//!
+//! Alignment references marked during acquisition (a beam ROI, a fiducial
+//! point, the crosshair an operator aligned to, the scale bar of the
+//! objective) are kept per camera in a [`FrameAnnotationStore`]. Viewers
+//! receive the current set with every streamed frame and draw it over the
+//! image; [`AnnotationEnricher`] copies it into [`FrameMetadata::annotations`]
+//! so storage backends record it next to the pixels and the references
+//! survive into analysis.
+//!
+//! Coordinates are frame pixels (origin top-left, x to the right, y down),
+//! matching the frame the camera delivers including any hardware ROI.
+//!
+//! # Configuration
+//!
+//! Annotations present from startup, keyed by camera device ID:
+//!
+//! ```toml
+//! [frame_annotations]
+//! camera = [
+//!     { type = "roi", label = "beam", x = 200, y = 150, width = 64, height = 48 },
+//!     { type = "crosshair", x = 320, y = 240 },
+//!     { type = "scale_bar", pixel_size = 0.65, unit = "µm", length = 50 },
+//! ]
+//! ```
+//!
+//! In run documents, a frame's annotations are the JSON event metadata
+//! field [`frame_annotations_key`] (`<detector>.annotations`).
+
+use crate::data::{FrameMetadata, FrameView};
+use crate::frame_enrichment::FrameEnricher;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Suffix of the event metadata field holding a detector's annotations
+pub const ANNOTATIONS_FIELD_SUFFIX: &str = ".annotations";
+
+/// Most annotations kept per camera
+pub const MAX_ANNOTATIONS: usize = 256;
+
+/// One overlay element, in frame pixel coordinates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum FrameAnnotation {
+    /// Rectangular region of interest
+    Roi {
+        #[serde(default)]
+        label: String,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+    /// Point of interest
+    Point {
+        #[serde(default)]
+        label: String,
+        x: f64,
+        y: f64,
+    },
+    /// Full-frame crosshair through a point
+    Crosshair {
+        #[serde(default)]
+        label: String,
+        x: f64,
+        y: f64,
+    },
+    /// Scale bar `length` `unit`s long, drawn `length / pixel_size` pixels
+    /// wide
+    ScaleBar {
+        /// Size of one pixel in `unit`
+        pixel_size: f64,
+        unit: String,
+        length: f64,
+    },
+}
+
+impl FrameAnnotation {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            FrameAnnotation::Roi {
+                x,
+                y,
+                width,
+                height,
+                ..
+            } => {
+                if ![x, y, width, height].iter().all(|v| v.is_finite()) {
+                    bail!("ROI annotation coordinates must be finite");
+                }
+                if *width <= 0.0 || *height <= 0.0 {
+                    bail!("ROI annotation needs a positive width and height");
+                }
+            }
+            FrameAnnotation::Point { x, y, .. } | FrameAnnotation::Crosshair { x, y, .. } => {
+                if !(x.is_finite() && y.is_finite()) {
+                    bail!("Annotation coordinates must be finite");
+                }
+            }
+            FrameAnnotation::ScaleBar {
+                pixel_size, length, ..
+            } => {
+                if !(*pixel_size > 0.0 && pixel_size.is_finite()) {
+                    bail!("Scale bar pixel size must be positive, got {}", pixel_size);
+                }
+                if !(*length > 0.0 && length.is_finite()) {
+                    bail!("Scale bar length must be positive, got {}", length);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Display label (empty for scale bars and unlabelled elements)
+    pub fn label(&self) -> &str {
+        match self {
+            FrameAnnotation::Roi { label, .. }
+            | FrameAnnotation::Point { label, .. }
+            | FrameAnnotation::Crosshair { label, .. } => label,
+            FrameAnnotation::ScaleBar { .. } => "",
+        }
+    }
+}
+
+/// Validate a camera's annotation set
+pub fn validate_annotations(annotations: &[FrameAnnotation]) -> Result<()> {
+    if annotations.len() > MAX_ANNOTATIONS {
+        bail!(
+            "At most {} annotations per camera, got {}",
+            MAX_ANNOTATIONS,
+            annotations.len()
+        );
+    }
+    for (i, annotation) in annotations.iter().enumerate() {
+        annotation
+            .validate()
+            .map_err(|e| anyhow::anyhow!("Annotation {}: {}", i, e))?;
+    }
+    Ok(())
+}
+
+/// Event metadata field holding `detector`'s annotations
+pub fn frame_annotations_key(detector: &str) -> String {
+    format!("{}{}", detector, ANNOTATIONS_FIELD_SUFFIX)
+}
+
+/// Detector of an annotations event metadata field
+pub fn annotations_detector(key: &str) -> Option<&str> {
+    key.strip_suffix(ANNOTATIONS_FIELD_SUFFIX)
+        .filter(|detector| !detector.is_empty())
+}
+
+/// JSON form stored in run documents
+pub fn annotations_to_json(annotations: &[FrameAnnotation]) -> String {
+    serde_json::to_string(annotations).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn annotations_from_json(json: &str) -> Result<Vec<FrameAnnotation>> {
+    Ok(serde_json::from_str(json)?)
+}
+
+/// Current annotations per camera, shared between the daemon's services,
+/// frame streams and enrichers
+#[derive(Debug, Clone, Default)]
+pub struct FrameAnnotationStore {
+    annotations: Arc<RwLock<HashMap<String, Vec<FrameAnnotation>>>>,
+}
+
+impl FrameAnnotationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the annotations of `device_id` (an empty set clears them)
+    pub fn set(&self, device_id: &str, annotations: Vec<FrameAnnotation>) -> Result<()> {
+        validate_annotations(&annotations)?;
+        let mut guard = self.annotations.write().unwrap_or_else(|p| p.into_inner());
+        if annotations.is_empty() {
+            guard.remove(device_id);
+        } else {
+            guard.insert(device_id.to_string(), annotations);
+        }
+        Ok(())
+    }
+
+    /// Current annotations of `device_id`
+    pub fn get(&self, device_id: &str) -> Vec<FrameAnnotation> {
+        self.annotations
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(device_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Cameras with annotations
+    pub fn devices(&self) -> Vec<String> {
+        let mut devices: Vec<String> = self
+            .annotations
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .keys()
+            .cloned()
+            .collect();
+        devices.sort();
+        devices
+    }
+}
+
+/// Enricher that attaches a camera's current annotations to
+/// [`FrameMetadata::annotations`]
+#[derive(Debug, Clone)]
+pub struct AnnotationEnricher {
+    device_id: String,
+    store: FrameAnnotationStore,
+}
+
+impl AnnotationEnricher {
+    pub fn new(device_id: impl Into<String>, store: FrameAnnotationStore) -> Self {
+        Self {
+            device_id: device_id.into(),
+            store,
+        }
+    }
+}
+
+impl FrameEnricher for AnnotationEnricher {
+    fn enrich(&self, _frame: &FrameView<'_>, metadata: &mut FrameMetadata) {
+        metadata.annotations = self.store.get(&self.device_id);
+    }
+
+    fn name(&self) -> &'static str {
+        "annotation_enricher"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_enrichment::FrameEnrichment;
+
+    #[test]
+    fn test_annotations_from_toml_and_json_roundtrip() {
+        let config: HashMap<String, Vec<FrameAnnotation>> = toml::from_str(
+            r#"
+camera = [
+    { type = "roi", label = "beam", x = 200.0, y = 150.0, width = 64.0, height = 48.0 },
+    { type = "crosshair", x = 320.0, y = 240.0 },
+    { type = "scale_bar", pixel_size = 0.65, unit = "um", length = 50.0 },
+]
+"#,
+        )
+        .unwrap();
+        let annotations = &config["camera"];
+        validate_annotations(annotations).unwrap();
+        assert_eq!(annotations[0].label(), "beam");
+        assert_eq!(
+            annotations[1],
+            FrameAnnotation::Crosshair {
+                label: String::new(),
+                x: 320.0,
+                y: 240.0
+            }
+        );
+
+        let json = annotations_to_json(annotations);
+        assert_eq!(&annotations_from_json(&json).unwrap(), annotations);
+
+        assert_eq!(frame_annotations_key("camera"), "camera.annotations");
+        assert_eq!(annotations_detector("camera.annotations"), Some("camera"));
+        assert_eq!(annotations_detector("camera.sample_x"), None);
+    }
+
+    #[test]
+    fn test_invalid_annotations_rejected() {
+        let store = FrameAnnotationStore::new();
+        let empty_roi = FrameAnnotation::Roi {
+            label: "beam".to_string(),
+            x: 0.0,
+            y: 0.0,
+            width: 0.0,
+            height: 10.0,
+        };
+        assert!(store.set("camera", vec![empty_roi]).is_err());
+        let bad_scale = FrameAnnotation::ScaleBar {
+            pixel_size: 0.0,
+            unit: "um".to_string(),
+            length: 10.0,
+        };
+        assert!(store.set("camera", vec![bad_scale]).is_err());
+        assert!(store.devices().is_empty());
+    }
+
+    #[test]
+    fn test_enricher_attaches_current_annotations() {
+        let store = FrameAnnotationStore::new();
+        let enrichment = FrameEnrichment::new()
+            .with_enricher(Arc::new(AnnotationEnricher::new("camera", store.clone())));
+        let pixels = [0u8; 4];
+        let view = FrameView::new(2, 2, 8, &pixels, 1, 1_000);
+        assert!(enrichment.enrich(&view).annotations.is_empty());
+
+        let point = FrameAnnotation::Point {
+            label: "fiducial".to_string(),
+            x: 1.0,
+            y: 0.5,
+        };
+        store.set("camera", vec![point.clone()]).unwrap();
+        assert_eq!(enrichment.enrich(&view).annotations, vec![point]);
+        assert_eq!(store.devices(), vec!["camera".to_string()]);
+
+        store.set("camera", Vec::new()).unwrap();
+        assert!(enrichment.enrich(&view).annotations.is_empty());
+        assert!(store.devices().is_empty());
+    }
+}
@@ -0,0 +1,360 @@
+//! Frame metadata enrichment.
+//!
+//! Enrichers attach acquisition conditions to each frame's [`FrameMetadata`]
+//! before it reaches storage: the current values of selected channels such as
+//! stage positions, laser wavelength or sample temperature. Storage backends
+//! then record them per frame, so correlating frames with conditions no longer
+//! needs an offline timestamp join.
+//!
+//! Enrichers run inside frame observer callbacks, which must not block. Channel
+//! values are therefore not read from hardware at frame time; a sampler keeps a
+//! [`ChannelSampleCache`] current and [`ChannelEnricher`] copies the latest
+//! sample of each channel into the frame metadata.
+//!
+//! Owned [`Frame`]s written outside a run (e.g. with the storage crate's
+//! `TiffWriter`, which puts the values in a JSON sidecar) are enriched with
+//! [`FrameEnrichment::enrich_frame`].
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [frame_enrichment]
+//! channels = ["sample_x", "sample_temp", "laser:wavelength"]
+//! sample_interval_ms = 100
+//! max_sample_age_ms = 1000
+//! ```
+
+use crate::data::{Frame, FrameMetadata, FrameView};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Default interval between channel samples
+pub const DEFAULT_SAMPLE_INTERVAL_MS: u64 = 100;
+
+fn default_sample_interval_ms() -> u64 {
+    DEFAULT_SAMPLE_INTERVAL_MS
+}
+
+/// Which channels to attach to frames, and how often to sample them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrameEnrichmentConfig {
+    /// Channel names (device IDs, channel aliases or `device:parameter`)
+    #[serde(default)]
+    pub channels: Vec<String>,
+    /// Interval between channel samples in milliseconds
+    #[serde(default = "default_sample_interval_ms")]
+    pub sample_interval_ms: u64,
+    /// Samples older than this (relative to the frame timestamp) are not
+    /// attached. `None` attaches the latest sample regardless of age.
+    #[serde(default)]
+    pub max_sample_age_ms: Option<u64>,
+}
+
+impl Default for FrameEnrichmentConfig {
+    fn default() -> Self {
+        Self {
+            channels: Vec::new(),
+            sample_interval_ms: DEFAULT_SAMPLE_INTERVAL_MS,
+            max_sample_age_ms: None,
+        }
+    }
+}
+
+impl FrameEnrichmentConfig {
+    pub fn with_channels(channels: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            channels: channels.into_iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Whether any channels are selected
+    pub fn is_enabled(&self) -> bool {
+        !self.channels.is_empty()
+    }
+
+    /// Sampling interval (never zero)
+    pub fn sample_interval(&self) -> Duration {
+        Duration::from_millis(self.sample_interval_ms.max(1))
+    }
+}
+
+/// Hook that adds metadata to a frame before it is stored.
+///
+/// Called from frame observer context: implementations must be fast and
+/// must not block or perform I/O.
+pub trait FrameEnricher: Send + Sync {
+    /// Add metadata for `frame`
+    fn enrich(&self, frame: &FrameView<'_>, metadata: &mut FrameMetadata);
+
+    /// Name for logging
+    fn name(&self) -> &'static str;
+}
+
+/// A sampled channel value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelSample {
+    pub value: f64,
+    /// Sample time (nanoseconds since UNIX epoch)
+    pub timestamp_ns: u64,
+}
+
+/// Latest sample per channel, shared between a sampler and enrichers
+#[derive(Debug, Clone, Default)]
+pub struct ChannelSampleCache {
+    samples: Arc<RwLock<HashMap<String, ChannelSample>>>,
+}
+
+impl ChannelSampleCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a sample, replacing the previous one for `channel`
+    pub fn record(&self, channel: &str, value: f64, timestamp_ns: u64) {
+        self.samples
+            .write()
+            .unwrap_or_else(|p| p.into_inner())
+            .insert(
+                channel.to_string(),
+                ChannelSample {
+                    value,
+                    timestamp_ns,
+                },
+            );
+    }
+
+    /// Latest sample for `channel`
+    pub fn latest(&self, channel: &str) -> Option<ChannelSample> {
+        self.samples
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(channel)
+            .copied()
+    }
+
+    pub fn clear(&self) {
+        self.samples
+            .write()
+            .unwrap_or_else(|p| p.into_inner())
+            .clear();
+    }
+}
+
+/// Enricher that attaches the latest sampled value of each selected channel
+/// to [`FrameMetadata::channels`]
+#[derive(Debug, Clone)]
+pub struct ChannelEnricher {
+    channels: Vec<String>,
+    cache: ChannelSampleCache,
+    max_age_ns: Option<u64>,
+}
+
+impl ChannelEnricher {
+    pub fn new(channels: Vec<String>, cache: ChannelSampleCache) -> Self {
+        Self {
+            channels,
+            cache,
+            max_age_ns: None,
+        }
+    }
+
+    /// Build from configuration
+    pub fn from_config(config: &FrameEnrichmentConfig, cache: ChannelSampleCache) -> Self {
+        let mut enricher = Self::new(config.channels.clone(), cache);
+        if let Some(ms) = config.max_sample_age_ms {
+            enricher = enricher.with_max_age(Duration::from_millis(ms));
+        }
+        enricher
+    }
+
+    /// Skip samples older than `max_age` at frame time
+    #[must_use]
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age_ns = Some(u64::try_from(max_age.as_nanos()).unwrap_or(u64::MAX));
+        self
+    }
+
+    pub fn channels(&self) -> &[String] {
+        &self.channels
+    }
+}
+
+impl FrameEnricher for ChannelEnricher {
+    fn enrich(&self, frame: &FrameView<'_>, metadata: &mut FrameMetadata) {
+        for channel in &self.channels {
+            let Some(sample) = self.cache.latest(channel) else {
+                continue;
+            };
+            // Frames without a timestamp can't be aged; attach the latest sample
+            if let Some(max_age) = self.max_age_ns {
+                if frame.timestamp_ns != 0
+                    && frame.timestamp_ns.saturating_sub(sample.timestamp_ns) > max_age
+                {
+                    continue;
+                }
+            }
+            metadata.channels.insert(channel.clone(), sample.value);
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "channel_enricher"
+    }
+}
+
+/// Ordered set of enrichers applied to each frame
+#[derive(Clone, Default)]
+pub struct FrameEnrichment {
+    enrichers: Vec<Arc<dyn FrameEnricher>>,
+}
+
+impl FrameEnrichment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder-style [`push`](Self::push)
+    #[must_use]
+    pub fn with_enricher(mut self, enricher: Arc<dyn FrameEnricher>) -> Self {
+        self.push(enricher);
+        self
+    }
+
+    pub fn push(&mut self, enricher: Arc<dyn FrameEnricher>) {
+        self.enrichers.push(enricher);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.enrichers.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.enrichers.len()
+    }
+
+    /// Build metadata for `frame`, starting from the fields the view carries
+    /// and applying each enricher in order
+    pub fn enrich(&self, frame: &FrameView<'_>) -> FrameMetadata {
+        let mut metadata = FrameMetadata {
+            temperature_c: frame.temperature_c,
+            binning: frame.binning,
+            ..Default::default()
+        };
+        for enricher in &self.enrichers {
+            enricher.enrich(frame, &mut metadata);
+        }
+        metadata
+    }
+
+    /// Apply each enricher to an owned frame, keeping the metadata it
+    /// already carries
+    #[must_use]
+    pub fn enrich_frame(&self, frame: Frame) -> Frame {
+        if self.is_empty() {
+            return frame;
+        }
+        let mut metadata = frame.metadata.as_deref().cloned().unwrap_or_default();
+        let view = FrameView::from_frame(&frame);
+        for enricher in &self.enrichers {
+            enricher.enrich(&view, &mut metadata);
+        }
+        frame.with_metadata(metadata)
+    }
+}
+
+impl std::fmt::Debug for FrameEnrichment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.enrichers.iter().map(|e| e.name()))
+            .finish()
+    }
+}
+
+/// Event data key for a channel value attached to a detector's frames
+pub fn frame_channel_key(detector: &str, channel: &str) -> String {
+    format!("{}.{}", detector, channel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_enricher_attaches_latest_samples() {
+        let cache = ChannelSampleCache::new();
+        cache.record("sample_x", 1.0, 1_000);
+        cache.record("sample_x", 2.5, 2_000);
+        cache.record("laser:wavelength", 800.0, 2_000);
+
+        let enrichment = FrameEnrichment::new().with_enricher(Arc::new(ChannelEnricher::new(
+            vec![
+                "sample_x".to_string(),
+                "laser:wavelength".to_string(),
+                "sample_temp".to_string(),
+            ],
+            cache,
+        )));
+
+        let pixels = [0u8; 4];
+        let view = FrameView::new(2, 2, 8, &pixels, 7, 2_500);
+        let metadata = enrichment.enrich(&view);
+        assert_eq!(metadata.channels.get("sample_x"), Some(&2.5));
+        assert_eq!(metadata.channels.get("laser:wavelength"), Some(&800.0));
+        assert!(!metadata.channels.contains_key("sample_temp"));
+        assert_eq!(frame_channel_key("cam", "sample_x"), "cam.sample_x");
+    }
+
+    #[test]
+    fn test_channel_enricher_skips_stale_samples() {
+        let cache = ChannelSampleCache::new();
+        cache.record("sample_temp", 4.2, 1_000_000);
+        cache.record("sample_x", 1.0, 9_000_000);
+
+        let config = FrameEnrichmentConfig {
+            max_sample_age_ms: Some(5),
+            ..FrameEnrichmentConfig::with_channels(["sample_temp", "sample_x"])
+        };
+        let enricher = ChannelEnricher::from_config(&config, cache);
+
+        let pixels = [0u8; 1];
+        let view = FrameView::new(1, 1, 8, &pixels, 0, 10_000_000);
+        let mut metadata = FrameMetadata::default();
+        enricher.enrich(&view, &mut metadata);
+        assert_eq!(metadata.channels.len(), 1);
+        assert_eq!(metadata.channels.get("sample_x"), Some(&1.0));
+    }
+
+    #[test]
+    fn test_enrich_frame_keeps_existing_metadata() {
+        let cache = ChannelSampleCache::new();
+        cache.record("sample_x", 1.25, 1_000);
+        let enrichment = FrameEnrichment::new().with_enricher(Arc::new(ChannelEnricher::new(
+            vec!["sample_x".to_string()],
+            cache,
+        )));
+
+        let metadata = FrameMetadata {
+            temperature_c: Some(-70.0),
+            ..Default::default()
+        };
+        let frame = Frame::from_u8(2, 2, vec![0; 4]).with_metadata(metadata);
+        let frame = enrichment.enrich_frame(frame);
+        let metadata = frame.metadata.as_deref().unwrap();
+        assert_eq!(metadata.channels.get("sample_x"), Some(&1.25));
+        assert_eq!(metadata.temperature_c, Some(-70.0));
+
+        let plain = FrameEnrichment::new().enrich_frame(Frame::from_u8(1, 1, vec![0]));
+        assert!(plain.metadata.is_none());
+    }
+
+    #[test]
+    fn test_config_defaults_from_toml() {
+        let config: FrameEnrichmentConfig = toml::from_str("channels = [\"sample_x\"]").unwrap();
+        assert!(config.is_enabled());
+        assert_eq!(config.sample_interval_ms, DEFAULT_SAMPLE_INTERVAL_MS);
+        assert_eq!(config.max_sample_age_ms, None);
+    }
+}
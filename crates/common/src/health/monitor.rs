@@ -5,13 +5,15 @@
 //! - Error collection from background tasks
 //! - Overall system health status for remote monitoring
 
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
 /// Severity level for health errors
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ErrorSeverity {
     /// Informational message
     Info = 0,
@@ -132,6 +134,8 @@ struct HealthMonitorState {
 /// ```
 pub struct SystemHealthMonitor {
     state: Arc<RwLock<HealthMonitorState>>,
+    /// Reported errors, for subscribers such as device status tracking
+    error_tx: tokio::sync::broadcast::Sender<HealthError>,
 }
 
 impl SystemHealthMonitor {
@@ -143,11 +147,19 @@ impl SystemHealthMonitor {
             config,
         };
 
+        let (error_tx, _) = tokio::sync::broadcast::channel(64);
+
         Self {
             state: Arc::new(RwLock::new(state)),
+            error_tx,
         }
     }
 
+    /// Receive errors as they are reported
+    pub fn subscribe_errors(&self) -> tokio::sync::broadcast::Receiver<HealthError> {
+        self.error_tx.subscribe()
+    }
+
     /// Record a heartbeat from a module
     ///
     /// Modules should call this periodically (e.g., every 5-10 seconds)
@@ -201,6 +213,9 @@ impl SystemHealthMonitor {
             context,
         };
 
+        // No subscribers is fine
+        let _ = self.error_tx.send(error.clone());
+
         let mut state = self.state.write().await;
 
         // Add to circular buffer
@@ -452,4 +467,23 @@ mod tests {
         monitor.unregister_module("test_module").await;
         assert_eq!(monitor.module_count().await, 0);
     }
+
+    #[tokio::test]
+    async fn test_subscribe_errors() {
+        let monitor = SystemHealthMonitor::new(Default::default());
+        let mut errors = monitor.subscribe_errors();
+
+        monitor
+            .report_error(
+                "camera",
+                ErrorSeverity::Warning,
+                "Sensor temperature drifting",
+                vec![("device_id", "cam0")],
+            )
+            .await;
+
+        let error = errors.recv().await.unwrap();
+        assert_eq!(error.severity, ErrorSeverity::Warning);
+        assert_eq!(error.context["device_id"], "cam0");
+    }
 }
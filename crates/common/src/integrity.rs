@@ -0,0 +1,191 @@
+//! Data-integrity accounting.
+//!
+//! Frames and samples can be lost at several points between the sensor and
+//! the data file. Every such point counts its losses by [`DropStage`]:
+//!
+//! - drivers report cumulative counts through
+//!   [`FrameProducer::drop_counts`](crate::capabilities::FrameProducer::drop_counts);
+//! - consumers that don't belong to a driver (taps, storage queues) record
+//!   into the process-wide [`DropLedger`], keyed by source.
+//!
+//! The device registry merges both into a [`DropReport`]. The run engine
+//! takes one when a run opens and attaches the difference at close to the
+//! StopDoc under [`INTEGRITY_METADATA_KEY`], answering "did we lose frames in
+//! this run, and where?".
+//!
+//! GUI preview streams drop frames by design and report them through their
+//! own streaming metrics instead.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+
+/// StopDoc metadata key holding the JSON [`DropReport`] of the run
+pub const INTEGRITY_METADATA_KEY: &str = "data_integrity";
+
+/// Where in the pipeline data was lost
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DropStage {
+    /// Between the hardware and the driver (gaps in hardware frame numbers)
+    Sdk,
+    /// No free buffer in the driver's frame pool
+    Pool,
+    /// The primary consumer's channel was full
+    Delivery,
+    /// A tap or frame observer fell behind
+    Tap,
+    /// A storage queue overflowed before the data was written
+    Storage,
+}
+
+impl DropStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DropStage::Sdk => "sdk",
+            DropStage::Pool => "pool",
+            DropStage::Delivery => "delivery",
+            DropStage::Tap => "tap",
+            DropStage::Storage => "storage",
+        }
+    }
+}
+
+impl fmt::Display for DropStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Lost frames or samples per stage
+pub type DropCounts = BTreeMap<DropStage, u64>;
+
+/// Drop counts keyed by source (usually a device ID)
+pub type DropReport = BTreeMap<String, DropCounts>;
+
+/// Total losses in a report
+pub fn total_dropped(report: &DropReport) -> u64 {
+    report.values().flat_map(BTreeMap::values).sum()
+}
+
+/// Losses in `current` that happened after `baseline` was taken
+///
+/// Sources and stages without new losses are left out. A counter that went
+/// backwards (e.g. a driver was re-registered) counts from zero.
+pub fn dropped_since(current: &DropReport, baseline: &DropReport) -> DropReport {
+    let mut report = DropReport::new();
+    for (source, counts) in current {
+        for (&stage, &count) in counts {
+            let before = baseline
+                .get(source)
+                .and_then(|b| b.get(&stage))
+                .copied()
+                .unwrap_or(0);
+            let new = count.checked_sub(before).unwrap_or(count);
+            if new > 0 {
+                report.entry(source.clone()).or_default().insert(stage, new);
+            }
+        }
+    }
+    report
+}
+
+/// Add `counts` of `source` into `report`
+pub fn merge_counts(report: &mut DropReport, source: &str, counts: &DropCounts) {
+    for (&stage, &count) in counts.iter().filter(|(_, &count)| count > 0) {
+        *report
+            .entry(source.to_string())
+            .or_default()
+            .entry(stage)
+            .or_default() += count;
+    }
+}
+
+/// Cumulative loss counters of consumers outside the drivers
+#[derive(Debug, Default)]
+pub struct DropLedger {
+    counts: Mutex<DropReport>,
+}
+
+impl DropLedger {
+    /// Process-wide ledger shared by taps, storage and the registry
+    pub fn global() -> &'static DropLedger {
+        static GLOBAL: OnceLock<DropLedger> = OnceLock::new();
+        GLOBAL.get_or_init(DropLedger::default)
+    }
+
+    /// Count `count` items lost by `source` at `stage`
+    pub fn record(&self, source: &str, stage: DropStage, count: u64) {
+        if count == 0 {
+            return;
+        }
+        let mut counts = self.counts.lock().unwrap_or_else(|p| p.into_inner());
+        match counts.get_mut(source) {
+            Some(stages) => *stages.entry(stage).or_default() += count,
+            None => {
+                counts.insert(source.to_string(), DropCounts::from([(stage, count)]));
+            }
+        }
+    }
+
+    /// Current cumulative counts
+    pub fn snapshot(&self) -> DropReport {
+        self.counts
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ledger_and_run_difference() {
+        let ledger = DropLedger::default();
+        ledger.record("camera", DropStage::Tap, 2);
+        ledger.record("documents", DropStage::Storage, 0);
+        let baseline = ledger.snapshot();
+        assert!(!baseline.contains_key("documents"));
+
+        ledger.record("camera", DropStage::Tap, 3);
+        ledger.record("documents", DropStage::Storage, 7);
+        let mut current = ledger.snapshot();
+        merge_counts(
+            &mut current,
+            "camera",
+            &DropCounts::from([(DropStage::Sdk, 4), (DropStage::Pool, 0)]),
+        );
+
+        let run = dropped_since(&current, &baseline);
+        assert_eq!(run["camera"][&DropStage::Tap], 3);
+        assert_eq!(run["camera"][&DropStage::Sdk], 4);
+        assert!(!run["camera"].contains_key(&DropStage::Pool));
+        assert_eq!(run["documents"][&DropStage::Storage], 7);
+        assert_eq!(total_dropped(&run), 14);
+
+        // Keys serialize as stage names
+        let json = serde_json::to_string(&run).unwrap();
+        assert!(json.contains(r#""sdk":4"#));
+        assert_eq!(serde_json::from_str::<DropReport>(&json).unwrap(), run);
+    }
+
+    #[test]
+    fn test_reset_counter_counts_from_zero() {
+        let baseline = DropReport::from([(
+            "camera".to_string(),
+            DropCounts::from([(DropStage::Sdk, 10)]),
+        )]);
+        let current = DropReport::from([(
+            "camera".to_string(),
+            DropCounts::from([(DropStage::Sdk, 3)]),
+        )]);
+        assert_eq!(
+            dropped_since(&current, &baseline)["camera"][&DropStage::Sdk],
+            3
+        );
+        assert!(dropped_since(&baseline, &baseline).is_empty());
+    }
+}
@@ -0,0 +1,449 @@
+//! Capability introspection metadata.
+//!
+//! Static descriptions of the capability traits in [`crate::capabilities`]:
+//! trait name, version and the methods each trait offers, with parameter
+//! types and units. Clients query these through the device registry to
+//! build controls or validate plans generically, without hard-coding
+//! driver types.
+//!
+//! A trait's `version` is bumped whenever its method set changes, so
+//! clients can detect a daemon that is newer or older than they expect.
+//!
+//! # Example
+//!
+//! ```
+//! use common::driver::Capability;
+//!
+//! let movable = Capability::Movable.descriptor();
+//! assert_eq!(movable.trait_name, "Movable");
+//! assert!(movable.method("move_abs").is_some());
+//! ```
+
+use serde::Serialize;
+
+use crate::driver::Capability;
+
+/// A method parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ParamDescriptor {
+    pub name: &'static str,
+    /// Rust type of the argument (e.g. `f64`, `&str`)
+    pub type_name: &'static str,
+    /// Physical units; `device` means device-native units (see DeviceMetadata)
+    pub units: &'static str,
+}
+
+/// A method of a capability trait
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct MethodDescriptor {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub params: &'static [ParamDescriptor],
+    /// Success type of the returned `Result` (`()` for none)
+    pub returns: &'static str,
+    /// Has a default implementation, so drivers may not really support it
+    pub provided: bool,
+}
+
+/// A capability trait
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CapabilityDescriptor {
+    pub capability: Capability,
+    /// Trait name in `common::capabilities`
+    pub trait_name: &'static str,
+    pub version: u32,
+    pub description: &'static str,
+    pub methods: &'static [MethodDescriptor],
+}
+
+impl CapabilityDescriptor {
+    /// Look up a method by name
+    pub fn method(&self, name: &str) -> Option<&'static MethodDescriptor> {
+        self.methods.iter().find(|m| m.name == name)
+    }
+}
+
+const fn param(
+    name: &'static str,
+    type_name: &'static str,
+    units: &'static str,
+) -> ParamDescriptor {
+    ParamDescriptor {
+        name,
+        type_name,
+        units,
+    }
+}
+
+const fn method(
+    name: &'static str,
+    description: &'static str,
+    params: &'static [ParamDescriptor],
+    returns: &'static str,
+) -> MethodDescriptor {
+    MethodDescriptor {
+        name,
+        description,
+        params,
+        returns,
+        provided: false,
+    }
+}
+
+const fn provided(
+    name: &'static str,
+    description: &'static str,
+    params: &'static [ParamDescriptor],
+    returns: &'static str,
+) -> MethodDescriptor {
+    MethodDescriptor {
+        provided: true,
+        ..method(name, description, params, returns)
+    }
+}
+
+const MOVABLE: CapabilityDescriptor = CapabilityDescriptor {
+    capability: Capability::Movable,
+    trait_name: "Movable",
+    version: 1,
+    description: "Moves to positions (stages, rotation mounts)",
+    methods: &[
+        method(
+            "move_abs",
+            "Start a move to an absolute position",
+            &[param("position", "f64", "device")],
+            "()",
+        ),
+        method(
+            "move_rel",
+            "Start a move by a relative distance",
+            &[param("distance", "f64", "device")],
+            "()",
+        ),
+        method("position", "Current position", &[], "f64"),
+        method("wait_settled", "Wait until motion completes", &[], "()"),
+        provided("stop", "Stop motion immediately", &[], "()"),
+    ],
+};
+
+const READABLE: CapabilityDescriptor = CapabilityDescriptor {
+    capability: Capability::Readable,
+    trait_name: "Readable",
+    version: 1,
+    description: "Reads a scalar value (power meters, sensors)",
+    methods: &[method("read", "Read the current value", &[], "f64")],
+};
+
+const TRIGGERABLE: CapabilityDescriptor = CapabilityDescriptor {
+    capability: Capability::Triggerable,
+    trait_name: "Triggerable",
+    version: 1,
+    description: "Can be armed and triggered (cameras, pulse generators)",
+    methods: &[
+        method("arm", "Prepare for a trigger", &[], "()"),
+        method("trigger", "Start acquisition or output", &[], "()"),
+        provided("is_armed", "Whether the device is armed", &[], "bool"),
+    ],
+};
+
+const FRAME_PRODUCER: CapabilityDescriptor = CapabilityDescriptor {
+    capability: Capability::FrameProducer,
+    trait_name: "FrameProducer",
+    version: 1,
+    description: "Produces image frames (cameras)",
+    methods: &[
+        method("start_stream", "Start continuous acquisition", &[], "()"),
+        provided(
+            "start_stream_finite",
+            "Start acquisition of a fixed number of frames",
+            &[param("frame_limit", "Option<u32>", "frames")],
+            "()",
+        ),
+        method("stop_stream", "Stop acquisition", &[], "()"),
+        method(
+            "resolution",
+            "Frame size (width, height)",
+            &[],
+            "(u32, u32)",
+        ),
+        provided(
+            "subscribe_frames",
+            "Receive frames as they are acquired",
+            &[],
+            "Receiver<Frame>",
+        ),
+        provided(
+            "is_streaming",
+            "Whether acquisition is running",
+            &[],
+            "bool",
+        ),
+        provided("frame_count", "Frames acquired so far", &[], "u64"),
+        provided(
+            "register_observer",
+            "Inspect frames synchronously without copying",
+            &[param("observer", "Box<dyn FrameObserver>", "")],
+            "ObserverHandle",
+        ),
+    ],
+};
+
+const EXPOSURE_CONTROL: CapabilityDescriptor = CapabilityDescriptor {
+    capability: Capability::ExposureControl,
+    trait_name: "ExposureControl",
+    version: 1,
+    description: "Exposure or integration time control",
+    methods: &[
+        method(
+            "set_exposure",
+            "Set the exposure time",
+            &[param("seconds", "f64", "s")],
+            "()",
+        ),
+        method("get_exposure", "Current exposure time (s)", &[], "f64"),
+    ],
+};
+
+const SETTABLE: CapabilityDescriptor = CapabilityDescriptor {
+    capability: Capability::Settable,
+    trait_name: "Settable",
+    version: 1,
+    description: "Named values set and read as JSON",
+    methods: &[
+        method(
+            "set_value",
+            "Set a named value",
+            &[
+                param("name", "&str", ""),
+                param("value", "serde_json::Value", ""),
+            ],
+            "()",
+        ),
+        provided(
+            "get_value",
+            "Read a named value",
+            &[param("name", "&str", "")],
+            "serde_json::Value",
+        ),
+    ],
+};
+
+const SHUTTER_CONTROL: CapabilityDescriptor = CapabilityDescriptor {
+    capability: Capability::ShutterControl,
+    trait_name: "ShutterControl",
+    version: 1,
+    description: "Laser shutter",
+    methods: &[
+        method("open_shutter", "Open the shutter", &[], "()"),
+        method("close_shutter", "Close the shutter", &[], "()"),
+        method(
+            "is_shutter_open",
+            "Whether the shutter is open",
+            &[],
+            "bool",
+        ),
+    ],
+};
+
+const WAVELENGTH_TUNABLE: CapabilityDescriptor = CapabilityDescriptor {
+    capability: Capability::WavelengthTunable,
+    trait_name: "WavelengthTunable",
+    version: 1,
+    description: "Tunable wavelength (lasers, power meter calibration)",
+    methods: &[
+        method(
+            "set_wavelength",
+            "Set the wavelength",
+            &[param("wavelength_nm", "f64", "nm")],
+            "()",
+        ),
+        method("get_wavelength", "Current wavelength (nm)", &[], "f64"),
+        provided(
+            "wavelength_range",
+            "Supported range (min, max) in nm",
+            &[],
+            "(f64, f64)",
+        ),
+    ],
+};
+
+const EMISSION_CONTROL: CapabilityDescriptor = CapabilityDescriptor {
+    capability: Capability::EmissionControl,
+    trait_name: "EmissionControl",
+    version: 1,
+    description: "Laser emission on/off",
+    methods: &[
+        method("enable_emission", "Turn emission on", &[], "()"),
+        method("disable_emission", "Turn emission off", &[], "()"),
+        provided("is_emission_enabled", "Whether emission is on", &[], "bool"),
+    ],
+};
+
+const COMMANDABLE: CapabilityDescriptor = CapabilityDescriptor {
+    capability: Capability::Commandable,
+    trait_name: "Commandable",
+    version: 1,
+    description: "Device-specific structured commands",
+    methods: &[method(
+        "execute_command",
+        "Run a named command with JSON arguments",
+        &[
+            param("command", "&str", ""),
+            param("args", "serde_json::Value", ""),
+        ],
+        "serde_json::Value",
+    )],
+};
+
+const STAGEABLE: CapabilityDescriptor = CapabilityDescriptor {
+    capability: Capability::Stageable,
+    trait_name: "Stageable",
+    version: 1,
+    description: "Prepared before and released after an acquisition",
+    methods: &[
+        method("stage", "Prepare for acquisition", &[], "()"),
+        method("unstage", "Release after acquisition", &[], "()"),
+        provided("is_staged", "Whether the device is staged", &[], "bool"),
+    ],
+};
+
+const PARAMETERIZED: CapabilityDescriptor = CapabilityDescriptor {
+    capability: Capability::Parameterized,
+    trait_name: "Parameterized",
+    version: 1,
+    description: "Observable parameters with change subscriptions",
+    methods: &[method(
+        "parameters",
+        "The device's parameter set",
+        &[],
+        "&ParameterSet",
+    )],
+};
+
+const RAW_TERMINAL: CapabilityDescriptor = CapabilityDescriptor {
+    capability: Capability::RawTerminal,
+    trait_name: "RawTerminal",
+    version: 1,
+    description: "Raw command passthrough (instrument console)",
+    methods: &[method(
+        "send_raw",
+        "Send a command string, optionally reading the reply",
+        &[
+            param("command", "&str", ""),
+            param("expect_response", "bool", ""),
+        ],
+        "String",
+    )],
+};
+
+const DISCRETE_POSITIONER: CapabilityDescriptor = CapabilityDescriptor {
+    capability: Capability::DiscretePositioner,
+    trait_name: "DiscretePositioner",
+    version: 1,
+    description: "Named slots (filter wheels, sample changers, flip mirrors)",
+    methods: &[
+        method(
+            "position_names",
+            "Slot names in slot order",
+            &[],
+            "Vec<String>",
+        ),
+        method(
+            "move_to_position",
+            "Move to a slot and verify it was reached",
+            &[param("name", "&str", "")],
+            "()",
+        ),
+        method(
+            "current_position",
+            "Slot the device is in, if any",
+            &[],
+            "Option<String>",
+        ),
+        provided(
+            "position_index",
+            "Index of a slot",
+            &[param("name", "&str", "")],
+            "Option<usize>",
+        ),
+    ],
+};
+
+impl Capability {
+    /// Every capability, in declaration order
+    pub const ALL: [Capability; 14] = [
+        Capability::Movable,
+        Capability::Readable,
+        Capability::Triggerable,
+        Capability::FrameProducer,
+        Capability::ExposureControl,
+        Capability::Settable,
+        Capability::ShutterControl,
+        Capability::WavelengthTunable,
+        Capability::EmissionControl,
+        Capability::Commandable,
+        Capability::Stageable,
+        Capability::Parameterized,
+        Capability::RawTerminal,
+        Capability::DiscretePositioner,
+    ];
+
+    /// Static description of the capability's trait
+    pub fn descriptor(&self) -> &'static CapabilityDescriptor {
+        match self {
+            Self::Movable => &MOVABLE,
+            Self::Readable => &READABLE,
+            Self::Triggerable => &TRIGGERABLE,
+            Self::FrameProducer => &FRAME_PRODUCER,
+            Self::ExposureControl => &EXPOSURE_CONTROL,
+            Self::Settable => &SETTABLE,
+            Self::ShutterControl => &SHUTTER_CONTROL,
+            Self::WavelengthTunable => &WAVELENGTH_TUNABLE,
+            Self::EmissionControl => &EMISSION_CONTROL,
+            Self::Commandable => &COMMANDABLE,
+            Self::Stageable => &STAGEABLE,
+            Self::Parameterized => &PARAMETERIZED,
+            Self::RawTerminal => &RAW_TERMINAL,
+            Self::DiscretePositioner => &DISCRETE_POSITIONER,
+        }
+    }
+
+    /// Parse the transport string from [`Capability::as_str`]
+    pub fn from_str_name(name: &str) -> Option<Capability> {
+        Self::ALL.into_iter().find(|c| c.as_str() == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_capability_has_matching_descriptor() {
+        for capability in Capability::ALL {
+            let descriptor = capability.descriptor();
+            assert_eq!(descriptor.capability, capability);
+            assert!(!descriptor.methods.is_empty(), "{:?}", capability);
+            assert_eq!(
+                Capability::from_str_name(capability.as_str()),
+                Some(capability)
+            );
+        }
+        assert_eq!(Capability::from_str_name("teleport"), None);
+    }
+
+    #[test]
+    fn test_method_metadata() {
+        let exposure = Capability::ExposureControl.descriptor();
+        let set = exposure.method("set_exposure").unwrap();
+        assert_eq!(set.params[0].units, "s");
+        assert!(!set.provided);
+        assert!(
+            Capability::Movable
+                .descriptor()
+                .method("stop")
+                .unwrap()
+                .provided
+        );
+    }
+}
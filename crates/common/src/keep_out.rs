@@ -0,0 +1,361 @@
+//! Keep-out zones for motion.
+//!
+//! A keep-out zone is a region a stage must never enter: the range of a Z
+//! stage where the objective meets the sample holder, or the footprint of a
+//! clamp in the plane of an XY stage. Moves that end inside a zone are
+//! rejected, and so are moves whose path crosses one. Rejections are logged
+//! and returned as errors, so the caller (gRPC, script, plan) fails instead
+//! of the hardware.
+//!
+//! Single Movable devices take `range` zones; the registry hands them out
+//! wrapped in [`KeepOutMovable`] and checks writes of their `position`
+//! parameter against the same zones. Motion groups also take `polygon` zones
+//! over two of their axes (see [`crate::motion_group`]), checked against the
+//! straight line from the current group position to the target. Dry runs
+//! check plan moves against the same zones before anything moves.
+//!
+//! A stage already inside a zone (after a config change or a manual move)
+//! may move out of it, as long as it does not cross another zone.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [[keep_out.stage_z]]
+//! name = "sample holder"
+//! range = [12.0, 15.5]      # forbidden between 12.0 and 15.5 (exclusive)
+//!
+//! [[motion_groups]]
+//! id = "sample_xy"
+//! members = ["stage_x", "stage_y"]
+//! keep_out = [
+//!     { name = "clamp", polygon = [[0.0, 0.0], [4.0, 0.0], [4.0, 3.0], [0.0, 3.0]] },
+//! ]
+//! ```
+
+use crate::capabilities::Movable;
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// A region a stage must not enter
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeepOutZone {
+    /// Name used in rejections and logs
+    #[serde(default)]
+    pub name: String,
+    /// Forbidden open range `[min, max]` of one axis
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<[f64; 2]>,
+    /// Forbidden polygon over two axes, as `[x, y]` vertices
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub polygon: Vec<[f64; 2]>,
+    /// Group axes the zone spans (motion groups only; defaults to the
+    /// first axis for ranges and the first two for polygons)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub axes: Vec<String>,
+}
+
+impl KeepOutZone {
+    /// Zone forbidding `(min, max)` on one axis
+    pub fn range(name: impl Into<String>, min: f64, max: f64) -> Self {
+        Self {
+            name: name.into(),
+            range: Some([min, max]),
+            ..Default::default()
+        }
+    }
+
+    /// Zone forbidding the inside of a polygon over two axes
+    pub fn polygon(name: impl Into<String>, vertices: Vec<[f64; 2]>) -> Self {
+        Self {
+            name: name.into(),
+            polygon: vertices,
+            ..Default::default()
+        }
+    }
+
+    /// Number of axes the zone spans
+    pub fn dims(&self) -> usize {
+        if self.range.is_some() {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Check that the zone is either a range or a polygon, and well formed
+    pub fn validate(&self) -> Result<()> {
+        match (self.range, self.polygon.is_empty()) {
+            (Some(_), false) => bail!("Keep-out zone {} has both a range and a polygon", self),
+            (None, true) => bail!("Keep-out zone {} has neither a range nor a polygon", self),
+            (Some([min, max]), true) if min >= max => {
+                bail!(
+                    "Keep-out zone {} has min {} not below max {}",
+                    self,
+                    min,
+                    max
+                )
+            }
+            (None, false) if self.polygon.len() < 3 => {
+                bail!("Keep-out zone {} needs at least 3 polygon vertices", self)
+            }
+            _ => {}
+        }
+        if !self.axes.is_empty() && self.axes.len() != self.dims() {
+            bail!(
+                "Keep-out zone {} spans {} axes, got {} axis names",
+                self,
+                self.dims(),
+                self.axes.len()
+            );
+        }
+        Ok(())
+    }
+
+    /// Whether a point (one coordinate per axis of the zone) is inside
+    pub fn contains(&self, point: &[f64]) -> bool {
+        match self.range {
+            Some([min, max]) => point.first().is_some_and(|&x| min < x && x < max),
+            None => point.len() >= 2 && point_in_polygon([point[0], point[1]], &self.polygon),
+        }
+    }
+
+    /// Whether the straight path between two points touches the zone
+    pub fn crosses(&self, from: &[f64], to: &[f64]) -> bool {
+        match self.range {
+            Some([min, max]) => match (from.first(), to.first()) {
+                (Some(&a), Some(&b)) => a.max(b) > min && a.min(b) < max,
+                _ => false,
+            },
+            None => {
+                if from.len() < 2 || to.len() < 2 {
+                    return false;
+                }
+                let (a, b) = ([from[0], from[1]], [to[0], to[1]]);
+                self.contains(from)
+                    || self.contains(to)
+                    || self
+                        .polygon
+                        .iter()
+                        .zip(self.polygon.iter().cycle().skip(1))
+                        .any(|(&p, &q)| segments_intersect(a, b, p, q))
+            }
+        }
+    }
+
+    /// Whether a move to `to` is forbidden
+    ///
+    /// Without a known start only the target is checked. A start inside the
+    /// zone lets the stage leave it.
+    pub fn blocks(&self, from: Option<&[f64]>, to: &[f64]) -> bool {
+        if self.contains(to) {
+            return true;
+        }
+        match from {
+            Some(from) if !self.contains(from) => self.crosses(from, to),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for KeepOutZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.name.is_empty() {
+            match self.range {
+                Some([min, max]) => write!(f, "({}, {})", min, max),
+                None => write!(f, "polygon of {} vertices", self.polygon.len()),
+            }
+        } else {
+            write!(f, "'{}'", self.name)
+        }
+    }
+}
+
+/// Fail, logging the rejection, if any zone forbids the move
+pub fn check_move(
+    device_id: &str,
+    zones: &[KeepOutZone],
+    from: Option<&[f64]>,
+    to: &[f64],
+) -> Result<()> {
+    if let Some(zone) = zones.iter().find(|zone| zone.blocks(from, to)) {
+        tracing::warn!(
+            device_id,
+            zone = %zone,
+            from = ?from,
+            to = ?to,
+            "Move rejected by keep-out zone"
+        );
+        bail!(
+            "Move of '{}' to {:?} rejected: it would enter keep-out zone {}",
+            device_id,
+            to,
+            zone
+        );
+    }
+    Ok(())
+}
+
+/// Ray casting; points on the boundary may fall either way
+fn point_in_polygon(point: [f64; 2], polygon: &[[f64; 2]]) -> bool {
+    let [x, y] = point;
+    let mut inside = false;
+    for (i, &[xi, yi]) in polygon.iter().enumerate() {
+        let [xj, yj] = polygon[(i + polygon.len() - 1) % polygon.len()];
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Whether segments `a-b` and `p-q` share a point
+fn segments_intersect(a: [f64; 2], b: [f64; 2], p: [f64; 2], q: [f64; 2]) -> bool {
+    fn orientation(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> f64 {
+        (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+    }
+    fn on_segment(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> bool {
+        c[0] >= a[0].min(b[0])
+            && c[0] <= a[0].max(b[0])
+            && c[1] >= a[1].min(b[1])
+            && c[1] <= a[1].max(b[1])
+    }
+
+    let (d1, d2) = (orientation(p, q, a), orientation(p, q, b));
+    let (d3, d4) = (orientation(a, b, p), orientation(a, b, q));
+    if d1 * d2 < 0.0 && d3 * d4 < 0.0 {
+        return true;
+    }
+    (d1 == 0.0 && on_segment(p, q, a))
+        || (d2 == 0.0 && on_segment(p, q, b))
+        || (d3 == 0.0 && on_segment(a, b, p))
+        || (d4 == 0.0 && on_segment(a, b, q))
+}
+
+/// A Movable whose moves are checked against keep-out ranges
+pub struct KeepOutMovable {
+    inner: Arc<dyn Movable>,
+    device_id: String,
+    zones: Arc<Vec<KeepOutZone>>,
+}
+
+impl KeepOutMovable {
+    pub fn new(
+        inner: Arc<dyn Movable>,
+        device_id: impl Into<String>,
+        zones: Arc<Vec<KeepOutZone>>,
+    ) -> Self {
+        Self {
+            inner,
+            device_id: device_id.into(),
+            zones,
+        }
+    }
+
+    /// The wrapped device
+    pub fn inner(&self) -> &Arc<dyn Movable> {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl Movable for KeepOutMovable {
+    async fn move_abs(&self, position: f64) -> Result<()> {
+        // If the position can't be read, at least the target is checked
+        let current = self.inner.position().await.ok();
+        check_move(
+            &self.device_id,
+            &self.zones,
+            current.as_ref().map(std::slice::from_ref),
+            &[position],
+        )?;
+        self.inner.move_abs(position).await
+    }
+
+    async fn move_rel(&self, distance: f64) -> Result<()> {
+        let current = self.inner.position().await?;
+        self.move_abs(current + distance).await
+    }
+
+    async fn position(&self) -> Result<f64> {
+        self.inner.position().await
+    }
+
+    async fn wait_settled(&self) -> Result<()> {
+        self.inner.wait_settled().await
+    }
+
+    async fn stop(&self) -> Result<()> {
+        self.inner.stop().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    struct Stage(Mutex<f64>);
+
+    #[async_trait]
+    impl Movable for Stage {
+        async fn move_abs(&self, position: f64) -> Result<()> {
+            *self.0.lock().unwrap() = position;
+            Ok(())
+        }
+        async fn move_rel(&self, distance: f64) -> Result<()> {
+            *self.0.lock().unwrap() += distance;
+            Ok(())
+        }
+        async fn position(&self) -> Result<f64> {
+            Ok(*self.0.lock().unwrap())
+        }
+        async fn wait_settled(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_polygon_blocks_targets_and_crossings() {
+        let zone = KeepOutZone::polygon(
+            "clamp",
+            vec![[0.0, 0.0], [4.0, 0.0], [4.0, 3.0], [0.0, 3.0]],
+        );
+        zone.validate().unwrap();
+
+        assert!(zone.blocks(None, &[2.0, 1.0]));
+        assert!(!zone.blocks(None, &[5.0, 1.0]));
+        // Both ends outside, straight path through the clamp
+        assert!(zone.blocks(Some(&[-1.0, 1.0]), &[5.0, 1.0]));
+        assert!(!zone.blocks(Some(&[-1.0, 4.0]), &[5.0, 4.0]));
+        // Leaving the zone is allowed
+        assert!(!zone.blocks(Some(&[2.0, 1.0]), &[2.0, 5.0]));
+
+        assert!(KeepOutZone::polygon("bad", vec![[0.0, 0.0], [1.0, 1.0]])
+            .validate()
+            .is_err());
+        assert!(KeepOutZone::range("bad", 2.0, 1.0).validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_movable_rejects_moves_through_a_range() {
+        let stage = Arc::new(Stage(Mutex::new(0.0)));
+        let guarded = KeepOutMovable::new(
+            stage.clone(),
+            "stage_z",
+            Arc::new(vec![KeepOutZone::range("sample holder", 12.0, 15.5)]),
+        );
+
+        guarded.move_abs(10.0).await.unwrap();
+        let err = guarded.move_abs(20.0).await.unwrap_err();
+        assert!(err.to_string().contains("sample holder"));
+        assert!(guarded.move_rel(3.0).await.is_err());
+        assert!((stage.position().await.unwrap() - 10.0).abs() < 1e-9);
+
+        // Touching the boundary is fine
+        guarded.move_abs(12.0).await.unwrap();
+    }
+}
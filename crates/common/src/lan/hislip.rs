@@ -0,0 +1,315 @@
+//! HiSLIP client (IVI-6.1), synchronized mode
+//!
+//! A session opens the synchronous channel (`Initialize` with the
+//! sub-address), then the asynchronous channel (`AsyncInitialize` with the
+//! session ID the server assigned) and agrees the maximum message size.
+//! Messages go out as `Data` / `DataEnd` on the synchronous channel, and a
+//! response is complete at the server's `DataEnd`.
+
+use super::LanResource;
+use anyhow::{anyhow, bail, Context, Result};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// IANA port of HiSLIP servers
+pub const HISLIP_PORT: u16 = 4880;
+
+const INITIALIZE: u8 = 0;
+const INITIALIZE_RESPONSE: u8 = 1;
+const FATAL_ERROR: u8 = 2;
+const ERROR: u8 = 3;
+const DATA: u8 = 6;
+const DATA_END: u8 = 7;
+const ASYNC_MAXIMUM_MESSAGE_SIZE: u8 = 15;
+const ASYNC_MAXIMUM_MESSAGE_SIZE_RESPONSE: u8 = 16;
+const ASYNC_INITIALIZE: u8 = 17;
+const ASYNC_INITIALIZE_RESPONSE: u8 = 18;
+
+const HEADER_LEN: usize = 16;
+/// Protocol version 1.0
+const PROTOCOL_VERSION: u32 = 0x0100;
+const VENDOR_ID: [u8; 2] = *b"RD";
+/// Message ID of the first message a client sends
+const FIRST_MESSAGE_ID: u32 = 0xffff_ff00;
+
+/// Largest message payload this client accepts
+const MAX_PAYLOAD: u64 = 256 * 1024 * 1024;
+
+/// Session with one HiSLIP server
+pub struct HislipClient {
+    sync: TcpStream,
+    /// Held open for the session; closing it ends the session
+    _async_channel: TcpStream,
+    message_id: u32,
+    /// Set once a complete response has arrived, reported with the next message
+    rmt_delivered: bool,
+    max_message_size: u64,
+    timeout: Duration,
+}
+
+impl HislipClient {
+    pub async fn connect(resource: &LanResource, timeout: Duration) -> Result<Self> {
+        let port = resource.port.unwrap_or(HISLIP_PORT);
+        let mut sync = connect(&resource.host, port, timeout).await?;
+        let vendor = u32::from(u16::from_be_bytes(VENDOR_ID));
+        send_message(
+            &mut sync,
+            INITIALIZE,
+            0,
+            (PROTOCOL_VERSION << 16) | vendor,
+            resource.device.as_bytes(),
+        )
+        .await?;
+        let response = expect(&mut sync, INITIALIZE_RESPONSE, timeout)
+            .await
+            .context("HiSLIP Initialize failed")?;
+        let session_id = response.parameter & 0xffff;
+
+        let mut async_channel = connect(&resource.host, port, timeout).await?;
+        send_message(&mut async_channel, ASYNC_INITIALIZE, 0, session_id, &[]).await?;
+        expect(&mut async_channel, ASYNC_INITIALIZE_RESPONSE, timeout)
+            .await
+            .context("HiSLIP AsyncInitialize failed")?;
+
+        send_message(
+            &mut async_channel,
+            ASYNC_MAXIMUM_MESSAGE_SIZE,
+            0,
+            0,
+            &MAX_PAYLOAD.to_be_bytes(),
+        )
+        .await?;
+        let response = expect(
+            &mut async_channel,
+            ASYNC_MAXIMUM_MESSAGE_SIZE_RESPONSE,
+            timeout,
+        )
+        .await
+        .context("HiSLIP AsyncMaximumMessageSize failed")?;
+        let max_message_size = response
+            .payload
+            .get(..8)
+            .map(|bytes| u64::from_be_bytes(bytes.try_into().expect("8 bytes")))
+            .ok_or_else(|| anyhow!("Malformed HiSLIP maximum message size"))?;
+
+        Ok(Self {
+            sync,
+            _async_channel: async_channel,
+            message_id: FIRST_MESSAGE_ID,
+            rmt_delivered: false,
+            // Leave room for the header, which the limit includes
+            max_message_size: max_message_size.saturating_sub(HEADER_LEN as u64).max(256),
+            timeout,
+        })
+    }
+
+    /// Send a message, split into `Data` messages ending with `DataEnd`
+    pub async fn write(&mut self, message: &[u8]) -> Result<()> {
+        let chunk_size = self.max_message_size.min(usize::MAX as u64) as usize;
+        let mut chunks = message.chunks(chunk_size).peekable();
+        while let Some(chunk) = chunks.next() {
+            let kind = if chunks.peek().is_none() {
+                DATA_END
+            } else {
+                DATA
+            };
+            let control = u8::from(std::mem::take(&mut self.rmt_delivered));
+            send_message(&mut self.sync, kind, control, self.message_id, chunk).await?;
+            self.message_id = self.message_id.wrapping_add(2);
+        }
+        Ok(())
+    }
+
+    /// Read a complete response message
+    pub async fn read(&mut self) -> Result<Vec<u8>> {
+        let mut response = Vec::new();
+        loop {
+            let message = recv_message(&mut self.sync, self.timeout).await?;
+            match message.kind {
+                DATA => response.extend_from_slice(&message.payload),
+                DATA_END => {
+                    response.extend_from_slice(&message.payload);
+                    self.rmt_delivered = true;
+                    return Ok(response);
+                }
+                kind => return Err(unexpected(kind, &message)),
+            }
+            if response.len() as u64 > MAX_PAYLOAD {
+                bail!("Response larger than {} bytes", MAX_PAYLOAD);
+            }
+        }
+    }
+}
+
+struct Message {
+    kind: u8,
+    parameter: u32,
+    payload: Vec<u8>,
+}
+
+async fn connect(host: &str, port: u16, timeout: Duration) -> Result<TcpStream> {
+    let stream = tokio::time::timeout(timeout, TcpStream::connect((host, port)))
+        .await
+        .map_err(|_| anyhow!("Timed out connecting to {}:{}", host, port))?
+        .with_context(|| format!("Cannot connect to {}:{}", host, port))?;
+    stream.set_nodelay(true)?;
+    Ok(stream)
+}
+
+async fn send_message(
+    stream: &mut TcpStream,
+    kind: u8,
+    control: u8,
+    parameter: u32,
+    payload: &[u8],
+) -> Result<()> {
+    let mut message = Vec::with_capacity(HEADER_LEN + payload.len());
+    message.extend_from_slice(b"HS");
+    message.push(kind);
+    message.push(control);
+    message.extend_from_slice(&parameter.to_be_bytes());
+    message.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+    message.extend_from_slice(payload);
+    stream.write_all(&message).await?;
+    Ok(())
+}
+
+async fn recv_message(stream: &mut TcpStream, timeout: Duration) -> Result<Message> {
+    tokio::time::timeout(timeout, async {
+        let mut header = [0u8; HEADER_LEN];
+        stream.read_exact(&mut header).await?;
+        if &header[..2] != b"HS" {
+            bail!("Not a HiSLIP message");
+        }
+        let len = u64::from_be_bytes(header[8..].try_into().expect("8 bytes"));
+        if len > MAX_PAYLOAD {
+            bail!("HiSLIP message larger than {} bytes", MAX_PAYLOAD);
+        }
+        let mut payload = vec![0; len as usize];
+        stream.read_exact(&mut payload).await?;
+        Ok(Message {
+            kind: header[2],
+            parameter: u32::from_be_bytes(header[4..8].try_into().expect("4 bytes")),
+            payload,
+        })
+    })
+    .await
+    .map_err(|_| anyhow!("Timed out waiting for the instrument"))?
+}
+
+async fn expect(stream: &mut TcpStream, kind: u8, timeout: Duration) -> Result<Message> {
+    let message = recv_message(stream, timeout).await?;
+    if message.kind != kind {
+        return Err(unexpected(message.kind, &message));
+    }
+    Ok(message)
+}
+
+fn unexpected(kind: u8, message: &Message) -> anyhow::Error {
+    let text = String::from_utf8_lossy(&message.payload);
+    match kind {
+        FATAL_ERROR => anyhow!("HiSLIP fatal error {}: {}", message.parameter, text),
+        ERROR => anyhow!("HiSLIP error {}: {}", message.parameter, text),
+        kind => anyhow!("Unexpected HiSLIP message type {}", kind),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lan::{connect_stream, LanTransport};
+    use tokio::net::TcpListener;
+
+    /// Minimal synchronized-mode server answering `*IDN?`
+    async fn serve_instrument(listener: TcpListener) {
+        let timeout = Duration::from_secs(2);
+        let (mut sync, _) = listener.accept().await.unwrap();
+        let init = expect(&mut sync, INITIALIZE, timeout).await.unwrap();
+        assert_eq!(init.payload, b"hislip0");
+        send_message(
+            &mut sync,
+            INITIALIZE_RESPONSE,
+            0,
+            (PROTOCOL_VERSION << 16) | 0x2a,
+            &[],
+        )
+        .await
+        .unwrap();
+
+        let (mut async_channel, _) = listener.accept().await.unwrap();
+        let init = expect(&mut async_channel, ASYNC_INITIALIZE, timeout)
+            .await
+            .unwrap();
+        assert_eq!(init.parameter, 42);
+        send_message(&mut async_channel, ASYNC_INITIALIZE_RESPONSE, 0, 0, &[])
+            .await
+            .unwrap();
+        expect(&mut async_channel, ASYNC_MAXIMUM_MESSAGE_SIZE, timeout)
+            .await
+            .unwrap();
+        // Small enough that the query arrives in two messages
+        send_message(
+            &mut async_channel,
+            ASYNC_MAXIMUM_MESSAGE_SIZE_RESPONSE,
+            0,
+            0,
+            &(HEADER_LEN as u64 + 256).to_be_bytes(),
+        )
+        .await
+        .unwrap();
+
+        let mut query = Vec::new();
+        loop {
+            let message = recv_message(&mut sync, timeout).await.unwrap();
+            query.extend_from_slice(&message.payload);
+            if message.kind == DATA_END {
+                assert_eq!(message.parameter, FIRST_MESSAGE_ID + 2);
+                break;
+            }
+            assert_eq!(message.parameter, FIRST_MESSAGE_ID);
+        }
+        assert!(query.starts_with(b"SYST:TEXT "));
+        assert!(query.ends_with(b";*IDN?\n"));
+        send_message(&mut sync, DATA, 0, FIRST_MESSAGE_ID + 2, b"RUDAQ,")
+            .await
+            .unwrap();
+        send_message(
+            &mut sync,
+            DATA_END,
+            0,
+            FIRST_MESSAGE_ID + 2,
+            b"SIM-HISLIP,0,1.0\n",
+        )
+        .await
+        .unwrap();
+        // Keep the session open until the client is done
+        let _ = recv_message(&mut sync, timeout).await;
+    }
+
+    #[tokio::test]
+    async fn test_query_through_stream() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(serve_instrument(listener));
+
+        let mut stream = connect_stream(
+            LanTransport::Hislip,
+            &format!("TCPIP0::127.0.0.1::hislip0,{}::INSTR", port),
+            Duration::from_secs(2),
+        )
+        .await
+        .unwrap();
+        let text = "x".repeat(300);
+        stream
+            .write_all(format!("SYST:TEXT \"{}\";*IDN?\n", text).as_bytes())
+            .await
+            .unwrap();
+        let mut response = [0u8; 23];
+        tokio::time::timeout(Duration::from_secs(2), stream.read_exact(&mut response))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(&response, b"RUDAQ,SIM-HISLIP,0,1.0\n");
+    }
+}
@@ -0,0 +1,323 @@
+//! LAN Instrument Transports - VXI-11 and HiSLIP without a VISA runtime
+//!
+//! LAN instruments speak SCPI over one of two message-based protocols:
+//! VXI-11 (ONC RPC, the older LXI standard) or HiSLIP (IVI-6.1, its faster
+//! successor). Both are implemented here directly on tokio sockets, so no
+//! vendor VISA installation is needed.
+//!
+//! Drivers written against a byte stream (the config-driven serial driver,
+//! anything taking a `DynSerial`) use [`connect_stream`], which bridges a
+//! duplex stream onto the instrument:
+//!
+//! - bytes written are sent as one message when a `\n` is written, or once
+//!   the writer pauses;
+//! - after a message containing a query (a header ending in `?`), the
+//!   response is read from the instrument and becomes readable on the stream.
+//!
+//! Drivers that know the message structure use [`LanClient`] directly.
+//!
+//! # Addresses
+//!
+//! - `192.168.1.20` - default device (`inst0` / `hislip0`) and port
+//! - `scope.lab:4880` - explicit port (VXI-11 otherwise asks the portmapper)
+//! - `192.168.1.20/hislip1` - explicit device name
+//! - VISA resource strings, e.g. `TCPIP0::192.168.1.20::hislip0::INSTR`
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [devices.config]
+//! transport = "hislip"
+//! host = "192.168.1.20"
+//! ```
+
+pub mod hislip;
+pub mod vxi11;
+
+pub use hislip::HislipClient;
+pub use vxi11::Vxi11Client;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+
+/// Bytes buffered in each direction of a [`connect_stream`] bridge
+const BRIDGE_BUFFER: usize = 64 * 1024;
+
+/// Pause after which unterminated written bytes are sent as a message
+const MESSAGE_IDLE: Duration = Duration::from_millis(5);
+
+/// LAN instrument protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LanTransport {
+    Vxi11,
+    Hislip,
+}
+
+impl LanTransport {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LanTransport::Vxi11 => "vxi11",
+            LanTransport::Hislip => "hislip",
+        }
+    }
+
+    /// Device name used when the address doesn't give one
+    pub fn default_device(&self) -> &'static str {
+        match self {
+            LanTransport::Vxi11 => "inst0",
+            LanTransport::Hislip => "hislip0",
+        }
+    }
+}
+
+/// Where a LAN instrument is reached
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanResource {
+    pub host: String,
+    /// `None` for HiSLIP's well-known port, or to ask the VXI-11 portmapper
+    pub port: Option<u16>,
+    /// Device (VXI-11) or sub-address (HiSLIP) name
+    pub device: String,
+}
+
+impl LanResource {
+    /// Parse a plain `host[:port][/device]` address or a VISA `TCPIP` resource
+    pub fn parse(address: &str, transport: LanTransport) -> Result<Self> {
+        let address = address.trim();
+        if address.to_ascii_uppercase().starts_with("TCPIP") && address.contains("::") {
+            return Self::parse_visa(address, transport);
+        }
+
+        let (endpoint, device) = match address.split_once('/') {
+            Some((endpoint, device)) => (endpoint, device.to_string()),
+            None => (address, transport.default_device().to_string()),
+        };
+        let (host, port) = match endpoint.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port
+                    .parse::<u16>()
+                    .map_err(|_| anyhow!("Invalid port '{}' in address '{}'", port, address))?;
+                (host, Some(port))
+            }
+            None => (endpoint, None),
+        };
+        Self::new(host, port, device, address)
+    }
+
+    /// `TCPIP[board]::host[::device[,port]]::INSTR`
+    fn parse_visa(resource: &str, transport: LanTransport) -> Result<Self> {
+        let parts: Vec<&str> = resource.split("::").collect();
+        if parts.len() < 3 || !parts[parts.len() - 1].eq_ignore_ascii_case("INSTR") {
+            bail!(
+                "Unsupported VISA resource '{}' (expected TCPIP::host::device::INSTR)",
+                resource
+            );
+        }
+        let host = parts[1];
+        let (device, port) = match parts.len() {
+            3 => (transport.default_device().to_string(), None),
+            4 => match parts[2].split_once(',') {
+                Some((device, port)) => {
+                    let port = port.parse::<u16>().map_err(|_| {
+                        anyhow!("Invalid port '{}' in VISA resource '{}'", port, resource)
+                    })?;
+                    (device.to_string(), Some(port))
+                }
+                None => (parts[2].to_string(), None),
+            },
+            _ => bail!("Unsupported VISA resource '{}'", resource),
+        };
+        let is_hislip = device.to_ascii_lowercase().starts_with("hislip");
+        if is_hislip != (transport == LanTransport::Hislip) {
+            bail!(
+                "VISA resource '{}' does not name a {} device",
+                resource,
+                transport.as_str()
+            );
+        }
+        Self::new(host, port, device, resource)
+    }
+
+    fn new(host: &str, port: Option<u16>, device: String, address: &str) -> Result<Self> {
+        if host.is_empty() || device.is_empty() {
+            bail!("Invalid LAN instrument address '{}'", address);
+        }
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            device,
+        })
+    }
+}
+
+/// Message-based session with a LAN instrument
+pub enum LanClient {
+    Vxi11(Vxi11Client),
+    Hislip(HislipClient),
+}
+
+impl LanClient {
+    /// Open a session; `timeout` bounds connecting and every read
+    pub async fn connect(
+        transport: LanTransport,
+        resource: &LanResource,
+        timeout: Duration,
+    ) -> Result<Self> {
+        Ok(match transport {
+            LanTransport::Vxi11 => LanClient::Vxi11(Vxi11Client::connect(resource, timeout).await?),
+            LanTransport::Hislip => {
+                LanClient::Hislip(HislipClient::connect(resource, timeout).await?)
+            }
+        })
+    }
+
+    /// Send one complete message
+    pub async fn write(&mut self, message: &[u8]) -> Result<()> {
+        match self {
+            LanClient::Vxi11(client) => client.write(message).await,
+            LanClient::Hislip(client) => client.write(message).await,
+        }
+    }
+
+    /// Read one complete response message
+    pub async fn read(&mut self) -> Result<Vec<u8>> {
+        match self {
+            LanClient::Vxi11(client) => client.read().await,
+            LanClient::Hislip(client) => client.read().await,
+        }
+    }
+
+    pub async fn query(&mut self, message: &[u8]) -> Result<Vec<u8>> {
+        self.write(message).await?;
+        self.read().await
+    }
+
+    /// End the session (best effort; the instrument also cleans up when the
+    /// connection drops)
+    pub async fn close(self) {
+        if let LanClient::Vxi11(client) = self {
+            client.close().await;
+        }
+    }
+}
+
+/// Whether an SCPI message expects a response: one of its `;`-separated
+/// commands has a header ending in `?`
+pub fn is_query(message: &[u8]) -> bool {
+    message.split(|&b| b == b';').any(|command| {
+        command
+            .split(|b| b.is_ascii_whitespace())
+            .find(|token| !token.is_empty())
+            .is_some_and(|header| header.ends_with(b"?"))
+    })
+}
+
+/// Connect to a LAN instrument and expose it as a byte stream
+///
+/// The stream stays usable until it is dropped or an exchange with the
+/// instrument fails. A failure is logged and shows up as end-of-stream, so a
+/// response that arrives late can never be read as the answer to a later query.
+pub async fn connect_stream(
+    transport: LanTransport,
+    address: &str,
+    timeout: Duration,
+) -> Result<DuplexStream> {
+    let resource = LanResource::parse(address, transport)?;
+    let mut client = LanClient::connect(transport, &resource, timeout).await?;
+    tracing::info!(
+        transport = transport.as_str(),
+        host = %resource.host,
+        device = %resource.device,
+        "Connected to LAN instrument"
+    );
+    let (stream, bridge_end) = tokio::io::duplex(BRIDGE_BUFFER);
+    let address = address.to_string();
+    tokio::spawn(async move {
+        if let Err(e) = run_bridge(&mut client, bridge_end).await {
+            tracing::error!(address = %address, error = %format!("{:#}", e), "LAN instrument bridge closed");
+        }
+        client.close().await;
+    });
+    Ok(stream)
+}
+
+/// Relay messages until the driver drops its end (`Ok`) or an exchange with
+/// the instrument fails
+async fn run_bridge(client: &mut LanClient, mut io: DuplexStream) -> Result<()> {
+    let mut pending = Vec::new();
+    let mut buf = vec![0u8; 4096];
+    loop {
+        let read = if pending.is_empty() {
+            Ok(io.read(&mut buf).await)
+        } else {
+            tokio::time::timeout(MESSAGE_IDLE, io.read(&mut buf)).await
+        };
+        match read {
+            Ok(Ok(0)) | Ok(Err(_)) => return Ok(()),
+            Ok(Ok(n)) => {
+                pending.extend_from_slice(&buf[..n]);
+                if !pending.ends_with(b"\n") {
+                    continue;
+                }
+            }
+            // The writer paused: what it wrote is one message
+            Err(_) => {}
+        }
+
+        let message = std::mem::take(&mut pending);
+        client
+            .write(&message)
+            .await
+            .context("LAN instrument write failed")?;
+        if is_query(&message) {
+            let response = client.read().await.context("LAN instrument read failed")?;
+            if io.write_all(&response).await.is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_addresses() {
+        let hislip = LanResource::parse("192.168.1.20", LanTransport::Hislip).unwrap();
+        assert_eq!(hislip.host, "192.168.1.20");
+        assert_eq!(hislip.port, None);
+        assert_eq!(hislip.device, "hislip0");
+
+        let vxi11 = LanResource::parse("scope.lab:1024/gpib0,7", LanTransport::Vxi11).unwrap();
+        assert_eq!(vxi11.host, "scope.lab");
+        assert_eq!(vxi11.port, Some(1024));
+        assert_eq!(vxi11.device, "gpib0,7");
+
+        let visa = LanResource::parse(
+            "TCPIP0::10.0.0.5::hislip1,4881::INSTR",
+            LanTransport::Hislip,
+        )
+        .unwrap();
+        assert_eq!(visa.host, "10.0.0.5");
+        assert_eq!(visa.port, Some(4881));
+        assert_eq!(visa.device, "hislip1");
+
+        let visa = LanResource::parse("TCPIP::10.0.0.5::INSTR", LanTransport::Vxi11).unwrap();
+        assert_eq!(visa.device, "inst0");
+        assert!(LanResource::parse("TCPIP::10.0.0.5::inst0::INSTR", LanTransport::Hislip).is_err());
+        assert!(LanResource::parse("10.0.0.5:http", LanTransport::Vxi11).is_err());
+    }
+
+    #[test]
+    fn test_query_detection() {
+        assert!(is_query(b"*IDN?\n"));
+        assert!(is_query(b"VOLT 1.5;:MEAS:VOLT?\n"));
+        assert!(is_query(b"  :SENS:FREQ? MAX\n"));
+        assert!(!is_query(b"*RST\n"));
+        assert!(!is_query(b"SYST:TEXT \"ready?\"\n"));
+    }
+}
@@ -0,0 +1,513 @@
+//! VXI-11 client: the DEVICE_CORE channel over ONC RPC on TCP
+//!
+//! A session asks the instrument's portmapper (port 111) for the core
+//! channel port unless one is given, opens a link to the device with
+//! `create_link`, then exchanges messages with `device_write` and
+//! `device_read`. The abort and interrupt channels are not used.
+
+use super::LanResource;
+use anyhow::{anyhow, bail, Context, Result};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const PORTMAPPER_PORT: u16 = 111;
+const PORTMAPPER_PROGRAM: u32 = 100_000;
+const PORTMAPPER_VERSION: u32 = 2;
+const PMAPPROC_GETPORT: u32 = 3;
+const IPPROTO_TCP: u32 = 6;
+
+const DEVICE_CORE: u32 = 0x0607AF;
+const DEVICE_CORE_VERSION: u32 = 1;
+const CREATE_LINK: u32 = 10;
+const DEVICE_WRITE: u32 = 11;
+const DEVICE_READ: u32 = 12;
+const DESTROY_LINK: u32 = 23;
+
+/// `device_write` flag: last chunk of the message
+const FLAG_END: u32 = 0x08;
+/// `device_read` reasons the read stopped: the message is complete
+const REASON_CHR: u32 = 0x02;
+const REASON_END: u32 = 0x04;
+
+/// Largest response accepted, so a misbehaving instrument can't exhaust memory
+const MAX_RESPONSE: usize = 256 * 1024 * 1024;
+
+/// Largest RPC record accepted
+const MAX_RECORD: usize = MAX_RESPONSE + 4096;
+
+/// Bytes requested per `device_read`
+const READ_CHUNK: u32 = 1024 * 1024;
+
+/// Session with one VXI-11 device link
+pub struct Vxi11Client {
+    rpc: RpcStream,
+    link_id: u32,
+    max_recv_size: usize,
+    timeout: Duration,
+}
+
+impl Vxi11Client {
+    pub async fn connect(resource: &LanResource, timeout: Duration) -> Result<Self> {
+        let port = match resource.port {
+            Some(port) => port,
+            None => core_port(&resource.host, timeout).await?,
+        };
+        let mut rpc = RpcStream::connect(&resource.host, port, timeout).await?;
+
+        let mut args = Vec::new();
+        put_u32(&mut args, std::process::id() & 0x7fff_ffff); // client id
+        put_u32(&mut args, 0); // lockDevice
+        put_u32(&mut args, 0); // lock_timeout
+        put_opaque(&mut args, resource.device.as_bytes());
+        let reply = rpc
+            .call(DEVICE_CORE, DEVICE_CORE_VERSION, CREATE_LINK, &args)
+            .await?;
+        let mut reply = XdrReader::new(&reply);
+        check_error(reply.u32()?).context("create_link failed")?;
+        let link_id = reply.u32()?;
+        let _abort_port = reply.u32()?;
+        let max_recv_size = reply.u32()? as usize;
+
+        Ok(Self {
+            rpc,
+            link_id,
+            // The standard requires at least 1024
+            max_recv_size: max_recv_size.max(1024),
+            timeout,
+        })
+    }
+
+    /// Send a message, split into `device_write` calls of the instrument's
+    /// maximum receive size
+    pub async fn write(&mut self, message: &[u8]) -> Result<()> {
+        let mut chunks = message.chunks(self.max_recv_size).peekable();
+        while let Some(chunk) = chunks.next() {
+            let mut args = Vec::with_capacity(chunk.len() + 24);
+            put_u32(&mut args, self.link_id);
+            put_u32(&mut args, self.timeout_ms()); // io_timeout
+            put_u32(&mut args, 0); // lock_timeout
+            put_u32(
+                &mut args,
+                if chunks.peek().is_none() { FLAG_END } else { 0 },
+            );
+            put_opaque(&mut args, chunk);
+            let reply = self
+                .rpc
+                .call(DEVICE_CORE, DEVICE_CORE_VERSION, DEVICE_WRITE, &args)
+                .await?;
+            let mut reply = XdrReader::new(&reply);
+            check_error(reply.u32()?).context("device_write failed")?;
+            let written = reply.u32()? as usize;
+            if written != chunk.len() {
+                bail!("device_write accepted {} of {} bytes", written, chunk.len());
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a complete response message
+    pub async fn read(&mut self) -> Result<Vec<u8>> {
+        let mut response = Vec::new();
+        loop {
+            let mut args = Vec::with_capacity(24);
+            put_u32(&mut args, self.link_id);
+            put_u32(&mut args, READ_CHUNK); // requestSize
+            put_u32(&mut args, self.timeout_ms()); // io_timeout
+            put_u32(&mut args, 0); // lock_timeout
+            put_u32(&mut args, 0); // flags
+            put_u32(&mut args, 0); // termChar
+            let reply = self
+                .rpc
+                .call(DEVICE_CORE, DEVICE_CORE_VERSION, DEVICE_READ, &args)
+                .await?;
+            let mut reply = XdrReader::new(&reply);
+            check_error(reply.u32()?).context("device_read failed")?;
+            let reason = reply.u32()?;
+            response.extend_from_slice(reply.opaque()?);
+            if response.len() > MAX_RESPONSE {
+                bail!("Response larger than {} bytes", MAX_RESPONSE);
+            }
+            if reason & (REASON_END | REASON_CHR) != 0 {
+                return Ok(response);
+            }
+        }
+    }
+
+    /// Destroy the device link
+    pub async fn close(mut self) {
+        let mut args = Vec::new();
+        put_u32(&mut args, self.link_id);
+        if let Err(e) = self
+            .rpc
+            .call(DEVICE_CORE, DEVICE_CORE_VERSION, DESTROY_LINK, &args)
+            .await
+        {
+            tracing::debug!(error = %e, "VXI-11 destroy_link failed");
+        }
+    }
+
+    fn timeout_ms(&self) -> u32 {
+        self.timeout.as_millis().min(u32::MAX as u128) as u32
+    }
+}
+
+/// Ask the portmapper for the DEVICE_CORE port
+async fn core_port(host: &str, timeout: Duration) -> Result<u16> {
+    let mut rpc = RpcStream::connect(host, PORTMAPPER_PORT, timeout)
+        .await
+        .context("Cannot reach the VXI-11 portmapper")?;
+    let mut args = Vec::new();
+    put_u32(&mut args, DEVICE_CORE);
+    put_u32(&mut args, DEVICE_CORE_VERSION);
+    put_u32(&mut args, IPPROTO_TCP);
+    put_u32(&mut args, 0);
+    let reply = rpc
+        .call(
+            PORTMAPPER_PROGRAM,
+            PORTMAPPER_VERSION,
+            PMAPPROC_GETPORT,
+            &args,
+        )
+        .await?;
+    let port = XdrReader::new(&reply).u32()?;
+    if port == 0 || port > u16::MAX as u32 {
+        bail!("{} does not offer a VXI-11 core channel", host);
+    }
+    Ok(port as u16)
+}
+
+fn check_error(code: u32) -> Result<()> {
+    let message = match code {
+        0 => return Ok(()),
+        1 => "syntax error",
+        3 => "device not accessible",
+        4 => "invalid link identifier",
+        5 => "parameter error",
+        6 => "channel not established",
+        8 => "operation not supported",
+        9 => "out of resources",
+        11 => "device locked by another link",
+        12 => "no lock held by this link",
+        15 => "I/O timeout",
+        17 => "I/O error",
+        21 => "invalid address",
+        23 => "abort",
+        29 => "channel already established",
+        _ => return Err(anyhow!("VXI-11 error {}", code)),
+    };
+    Err(anyhow!("VXI-11 error {}: {}", code, message))
+}
+
+/// ONC RPC client over one TCP connection (record marking, AUTH_NONE)
+struct RpcStream {
+    stream: TcpStream,
+    xid: u32,
+    timeout: Duration,
+    /// Set when a reply was cut off mid-record; the framing can't be recovered
+    desynced: bool,
+}
+
+impl RpcStream {
+    async fn connect(host: &str, port: u16, timeout: Duration) -> Result<Self> {
+        let stream = tokio::time::timeout(timeout, TcpStream::connect((host, port)))
+            .await
+            .map_err(|_| anyhow!("Timed out connecting to {}:{}", host, port))?
+            .with_context(|| format!("Cannot connect to {}:{}", host, port))?;
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            stream,
+            xid: std::process::id().rotate_left(16),
+            timeout,
+            desynced: false,
+        })
+    }
+
+    /// Call a procedure and return the encoded results
+    ///
+    /// Replies to earlier calls that timed out may still arrive; they are read
+    /// and discarded until the reply carrying this call's xid.
+    async fn call(
+        &mut self,
+        program: u32,
+        version: u32,
+        procedure: u32,
+        args: &[u8],
+    ) -> Result<Vec<u8>> {
+        if self.desynced {
+            bail!("RPC connection lost record framing");
+        }
+        self.xid = self.xid.wrapping_add(1);
+        let mut message = Vec::with_capacity(44 + args.len());
+        put_u32(&mut message, 0); // record mark, filled in below
+        put_u32(&mut message, self.xid);
+        put_u32(&mut message, 0); // CALL
+        put_u32(&mut message, 2); // RPC version
+        put_u32(&mut message, program);
+        put_u32(&mut message, version);
+        put_u32(&mut message, procedure);
+        message.extend_from_slice(&[0; 16]); // AUTH_NONE credential and verifier
+        message.extend_from_slice(args);
+        let mark = 0x8000_0000 | (message.len() - 4) as u32;
+        message[..4].copy_from_slice(&mark.to_be_bytes());
+        self.stream.write_all(&message).await?;
+
+        // The instrument may wait up to its own I/O timeout before replying
+        let deadline = tokio::time::Instant::now() + self.timeout * 2;
+        let reply = loop {
+            // Peeking consumes nothing, so giving up here leaves the stream intact
+            tokio::time::timeout_at(deadline, self.stream.peek(&mut [0u8; 1]))
+                .await
+                .map_err(|_| anyhow!("Timed out waiting for RPC reply"))??;
+            let reply = match tokio::time::timeout(self.timeout, self.read_record()).await {
+                Ok(Ok(reply)) => reply,
+                Ok(Err(e)) => {
+                    self.desynced = true;
+                    return Err(e);
+                }
+                Err(_) => {
+                    self.desynced = true;
+                    bail!("Timed out in the middle of an RPC reply");
+                }
+            };
+            let xid = XdrReader::new(&reply).u32()?;
+            if xid == self.xid {
+                break reply;
+            }
+            // Only calls already abandoned can have older xids
+            if self.xid.wrapping_sub(xid) >= 0x8000_0000 {
+                bail!("RPC reply does not match the call");
+            }
+            tracing::debug!(
+                xid,
+                expected = self.xid,
+                "Discarding reply to an abandoned RPC call"
+            );
+        };
+        let mut reader = XdrReader::new(&reply);
+        reader.u32()?;
+        if reader.u32()? != 1 {
+            bail!("Expected an RPC reply");
+        }
+        if reader.u32()? != 0 {
+            bail!("RPC call rejected");
+        }
+        let _verifier_flavor = reader.u32()?;
+        reader.opaque()?;
+        match reader.u32()? {
+            0 => Ok(reply[reader.pos..].to_vec()),
+            1 => bail!("RPC program unavailable"),
+            2 => bail!("RPC program version mismatch"),
+            3 => bail!("RPC procedure unavailable"),
+            4 => bail!("RPC arguments rejected"),
+            status => bail!("RPC call failed (status {})", status),
+        }
+    }
+
+    async fn read_record(&mut self) -> Result<Vec<u8>> {
+        let mut record = Vec::new();
+        loop {
+            let mark = self.stream.read_u32().await?;
+            let len = (mark & 0x7fff_ffff) as usize;
+            if record.len() + len > MAX_RECORD {
+                bail!("RPC reply larger than {} bytes", MAX_RECORD);
+            }
+            let start = record.len();
+            record.resize(start + len, 0);
+            self.stream.read_exact(&mut record[start..]).await?;
+            if mark & 0x8000_0000 != 0 {
+                return Ok(record);
+            }
+        }
+    }
+}
+
+fn put_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+/// XDR variable-length opaque (also strings): length, data, zero padding
+fn put_opaque(buf: &mut Vec<u8>, data: &[u8]) {
+    put_u32(buf, data.len() as u32);
+    buf.extend_from_slice(data);
+    buf.resize(buf.len() + (4 - data.len() % 4) % 4, 0);
+}
+
+struct XdrReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> XdrReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self
+            .buf
+            .get(self.pos..self.pos + 4)
+            .ok_or_else(|| anyhow!("Truncated RPC reply"))?;
+        self.pos += 4;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn opaque(&mut self) -> Result<&'a [u8]> {
+        let len = self.u32()? as usize;
+        let data = self
+            .buf
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| anyhow!("Truncated RPC reply"))?;
+        self.pos += len + (4 - len % 4) % 4;
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lan::{connect_stream, LanTransport};
+    use tokio::net::TcpListener;
+
+    /// Minimal DEVICE_CORE server answering `*IDN?`
+    async fn serve_instrument(listener: TcpListener) {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut last_write = Vec::new();
+        loop {
+            let Ok(mark) = stream.read_u32().await else {
+                return;
+            };
+            let mut call = vec![0; (mark & 0x7fff_ffff) as usize];
+            stream.read_exact(&mut call).await.unwrap();
+            let mut reader = XdrReader::new(&call);
+            let xid = reader.u32().unwrap();
+            for _ in 0..4 {
+                reader.u32().unwrap();
+            }
+            let procedure = reader.u32().unwrap();
+            reader.pos += 16;
+
+            let mut results = Vec::new();
+            match procedure {
+                CREATE_LINK => {
+                    reader.u32().unwrap();
+                    reader.u32().unwrap();
+                    reader.u32().unwrap();
+                    assert_eq!(reader.opaque().unwrap(), b"inst0");
+                    for value in [0, 7, 0, 1024] {
+                        put_u32(&mut results, value);
+                    }
+                }
+                DEVICE_WRITE => {
+                    assert_eq!(reader.u32().unwrap(), 7);
+                    reader.u32().unwrap();
+                    reader.u32().unwrap();
+                    assert_eq!(reader.u32().unwrap(), FLAG_END);
+                    last_write = reader.opaque().unwrap().to_vec();
+                    put_u32(&mut results, 0);
+                    put_u32(&mut results, last_write.len() as u32);
+                }
+                DEVICE_READ if last_write == b"*IDN?\n" => {
+                    put_u32(&mut results, 0);
+                    put_u32(&mut results, REASON_END);
+                    put_opaque(&mut results, b"RUDAQ,SIM-VXI,0,1.0\n");
+                }
+                // Any other query times out inside the instrument
+                DEVICE_READ => {
+                    put_u32(&mut results, 15);
+                    put_u32(&mut results, 0);
+                    put_opaque(&mut results, b"");
+                }
+                DESTROY_LINK => put_u32(&mut results, 0),
+                other => panic!("unexpected procedure {}", other),
+            }
+
+            stream.write_all(&rpc_reply(xid, &results)).await.unwrap();
+        }
+    }
+
+    /// Accepted, successful RPC reply record
+    fn rpc_reply(xid: u32, results: &[u8]) -> Vec<u8> {
+        let mut reply = Vec::new();
+        for value in [0, xid, 1, 0, 0, 0, 0] {
+            put_u32(&mut reply, value);
+        }
+        reply.extend_from_slice(results);
+        let len = reply.len() as u32 - 4;
+        reply[..4].copy_from_slice(&(0x8000_0000 | len).to_be_bytes());
+        reply
+    }
+
+    async fn read_call_xid(stream: &mut TcpStream) -> u32 {
+        let mark = stream.read_u32().await.unwrap();
+        let mut call = vec![0; (mark & 0x7fff_ffff) as usize];
+        stream.read_exact(&mut call).await.unwrap();
+        XdrReader::new(&call).u32().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_late_reply_is_discarded() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            // Answer the first call only after the client gave up on it
+            let first = read_call_xid(&mut stream).await;
+            let second = read_call_xid(&mut stream).await;
+            stream.write_all(&rpc_reply(first, b"late")).await.unwrap();
+            stream.write_all(&rpc_reply(second, b"mine")).await.unwrap();
+        });
+
+        let mut rpc = RpcStream::connect("127.0.0.1", port, Duration::from_millis(50))
+            .await
+            .unwrap();
+        assert!(rpc.call(DEVICE_CORE, 1, DEVICE_READ, &[]).await.is_err());
+        let reply = rpc.call(DEVICE_CORE, 1, DEVICE_READ, &[]).await.unwrap();
+        assert_eq!(reply, b"mine");
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_query_through_stream() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(serve_instrument(listener));
+
+        let mut stream = connect_stream(
+            LanTransport::Vxi11,
+            &format!("127.0.0.1:{}", port),
+            Duration::from_secs(2),
+        )
+        .await
+        .unwrap();
+        stream.write_all(b"*IDN?\n").await.unwrap();
+        let mut response = [0u8; 20];
+        tokio::time::timeout(Duration::from_secs(2), stream.read_exact(&mut response))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(&response, b"RUDAQ,SIM-VXI,0,1.0\n");
+    }
+
+    #[tokio::test]
+    async fn test_failed_read_ends_stream() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(serve_instrument(listener));
+
+        let mut stream = connect_stream(
+            LanTransport::Vxi11,
+            &format!("127.0.0.1:{}", port),
+            Duration::from_secs(2),
+        )
+        .await
+        .unwrap();
+        stream.write_all(b"MEAS:VOLT?\n").await.unwrap();
+        let mut response = Vec::new();
+        let n = tokio::time::timeout(Duration::from_secs(2), stream.read_to_end(&mut response))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(n, 0);
+    }
+}
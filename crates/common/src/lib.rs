@@ -30,7 +30,9 @@
 //! ## Feature Flags
 //!
 //! - `serial` - Enable serial port support for hardware drivers
+//! - `lan` - Enable VXI-11 and HiSLIP transports for LAN instruments
 //! - `storage_arrow` - Enable Arrow IPC format support
+//! - `gpu_preprocessing` - Enable the wgpu frame preprocessing backend
 //!
 //! [`Movable`]: capabilities::Movable
 //! [`Readable`]: capabilities::Readable
@@ -48,28 +50,107 @@
 #![allow(rustdoc::invalid_html_tags)]
 #![allow(rustdoc::broken_intra_doc_links)]
 
+// Minimal headless profile: heavy backends must stay out of the dependency graph
+#[cfg(all(
+    feature = "minimal",
+    any(feature = "storage_arrow", feature = "gpu_preprocessing")
+))]
+compile_error!(
+    "the `minimal` profile excludes Arrow and GPU preprocessing; drop `minimal` or the conflicting feature"
+);
+
 pub mod core;
+// Averaged, peak-hold and integrated scalar reads
+pub mod acquisition;
 // Data types (Frame, etc.)
 pub mod data;
+// Canonical data point shared by gRPC, storage, GUI and plugins
+pub mod data_record;
+// Per-consumer decimation and wall-clock alignment
+pub mod decimation;
+// State transitions, errors and limit hits reported apart from data
+pub mod device_events;
 // Document model (Bluesky-style)
 pub mod capabilities;
+// Cancellation tokens for in-flight hardware operations
+pub mod cancellation;
+// Experiment-level channel names mapped to hardware channels
+pub mod channel_alias;
 pub mod error;
 pub mod error_recovery;
 pub mod experiment;
+// Named fault points armed by chaos and recovery tests
+pub mod fault_injection;
+// Per-consumer reshaping of the run document stream
+pub mod document_transform;
+// Per-frame channel snapshots attached before storage
+pub mod frame_enrichment;
+// ROI, point, crosshair and scale bar overlays stored with frames
+pub mod frame_annotations;
 pub mod health;
+// Forbidden position ranges and polygons for stages and motion groups
+pub mod keep_out;
 pub mod limits;
 pub mod log_scrubbing;
 pub mod modules;
+// Multi-axis motion groups with kinematic transforms
+pub mod motion_group;
+// Backlash, scale and offset correction for Movable devices
+pub mod motion_correction;
+// Named-slot positioners over a Movable or digital outputs
+pub mod discrete_positioner;
+// Absolute and slew-rate limits on analog outputs and Movables
+pub mod output_limits;
+// Sample coordinate registration from fiducials
+pub mod coordinates;
+// Lifecycle state graphs and live states of modules, run engine and devices
+pub mod state_machine;
+// Ambient environment channels summarised per run
+pub mod environment;
+// Dropped frame and sample accounting per pipeline stage
+pub mod integrity;
+// Memory accounting and budgets per subsystem
+pub mod memory;
+// Filter expressions and pagination for listing RPCs
+pub mod listing;
 pub mod observable;
+// Deadband filtering and keyframes for on-change streams
+pub mod on_change;
 pub mod parameter;
+// Actions that park hardware while a run is paused or after it fails
+pub mod parking;
+// Cleanup sequences guaranteed to run when a run aborts or fails
+pub mod cleanup;
 pub mod pipeline;
+// Camera frame preprocessing (dark/flat, binning, histogram)
+pub mod preprocessing;
+// Gradual setpoint changes with easing profiles
+pub mod ramp;
+// Signed content digests of completed run files
+pub mod provenance;
+// Per-point deadlines for detector reads and motion
+pub mod time_budget;
+// Triggers gated on combined hardware and software conditions
+pub mod composite_trigger;
+// Timestamp skew between devices and per-device corrections
+pub mod time_sync;
+// Restart supervision of daemon subsystems
+pub mod supervisor;
+// Per-point validation rules and run quality summaries
+pub mod validation;
 
 // Driver factory and capability types for plugin architecture
 pub mod driver;
+// Capability trait metadata for runtime introspection
+pub mod introspection;
 
 // Serial port abstractions for driver crates (requires "serial" feature)
 #[cfg(feature = "serial")]
 pub mod serial;
 
+// VXI-11 and HiSLIP LAN instrument transports (requires "lan" feature)
+#[cfg(feature = "lan")]
+pub mod lan;
+
 // Arrow extension metadata helpers for Python interop
 pub mod arrow_metadata;
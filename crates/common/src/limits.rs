@@ -58,6 +58,10 @@ pub const MAX_FRAME_BYTES: usize = 100 * 1024 * 1024;
 pub const MAX_RESPONSE_SIZE: usize = 1024 * 1024;
 /// Maximum allowed script upload size in bytes (default: 1MB).
 pub const MAX_SCRIPT_SIZE: usize = 1024 * 1024;
+/// Maximum allowed library file (script, plan, device config) size in bytes (default: 16MB).
+pub const MAX_LIBRARY_FILE_SIZE: usize = 16 * 1024 * 1024;
+/// Content bytes per message when transferring library files (default: 64KB).
+pub const LIBRARY_CHUNK_SIZE: usize = 64 * 1024;
 /// Maximum supported width/height for frames.
 pub const MAX_FRAME_DIMENSION: u32 = 65_536;
 
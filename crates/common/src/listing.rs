@@ -0,0 +1,174 @@
+//! Filter expressions and pagination for listing RPCs.
+//!
+//! A filter is a list of `key=value` clauses that must all match:
+//!
+//! ```text
+//! capability=movable driver_type=ell14|esp300 tag=table_1 online=true
+//! plan_type=grid_scan exit_status!=success meta.sample=wafer_*
+//! ```
+//!
+//! - `key!=value` negates a clause;
+//! - `a|b` matches either value;
+//! - `*` in a value matches any run of characters;
+//! - comparisons ignore ASCII case;
+//! - a field may have several values (e.g. capabilities, tags), and a
+//!   clause matches if any of them does.
+//!
+//! Clauses may also be joined with `AND` for readability. Each listing
+//! declares the keys it understands; a key ending in `.` is a prefix, so
+//! `meta.` accepts `meta.sample`, `meta.operator`, ...
+
+use anyhow::{bail, Result};
+
+/// One `key=value` or `key!=value` clause
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Clause {
+    key: String,
+    negate: bool,
+    /// Alternatives from `a|b`, lowercased
+    patterns: Vec<String>,
+}
+
+/// Parsed filter expression; the empty filter matches everything
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ListFilter {
+    clauses: Vec<Clause>,
+}
+
+impl ListFilter {
+    /// Parse `expr`, accepting only the given keys (or key prefixes ending in `.`)
+    pub fn parse(expr: &str, keys: &[&str]) -> Result<Self> {
+        let mut clauses = Vec::new();
+        for token in expr.split_whitespace() {
+            if token.eq_ignore_ascii_case("and") {
+                continue;
+            }
+            let (key, negate, value) = match token.split_once("!=") {
+                Some((key, value)) => (key, true, value),
+                None => match token.split_once('=') {
+                    Some((key, value)) => (key, false, value),
+                    None => bail!("Filter clause '{}' is not key=value", token),
+                },
+            };
+            let key = key.to_ascii_lowercase();
+            let known = keys.iter().any(|k| match k.strip_suffix('.') {
+                Some(_) => key.starts_with(k) && key.len() > k.len(),
+                None => key == *k,
+            });
+            if !known {
+                bail!(
+                    "Unknown filter key '{}' (expected one of: {})",
+                    key,
+                    keys.join(", ")
+                );
+            }
+            if value.is_empty() {
+                bail!("Filter clause '{}' has no value", token);
+            }
+            clauses.push(Clause {
+                key,
+                negate,
+                patterns: value.split('|').map(str::to_ascii_lowercase).collect(),
+            });
+        }
+        Ok(Self { clauses })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+
+    /// Whether an item matches; `fields` returns the item's values for a key
+    pub fn matches<F>(&self, fields: F) -> bool
+    where
+        F: Fn(&str) -> Vec<String>,
+    {
+        self.clauses.iter().all(|clause| {
+            let values = fields(&clause.key);
+            let hit = values.iter().any(|value| {
+                let value = value.to_ascii_lowercase();
+                clause.patterns.iter().any(|p| glob_match(p, &value))
+            });
+            hit != clause.negate
+        })
+    }
+}
+
+/// Match `text` against a pattern where `*` matches any run of characters
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No `*` at all
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// The `offset..offset + limit` window of `items` (`limit` 0 = no limit)
+pub fn paginate<T>(items: Vec<T>, offset: usize, limit: usize) -> Vec<T> {
+    let limit = if limit == 0 { usize::MAX } else { limit };
+    items.into_iter().skip(offset).take(limit).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEVICE_KEYS: &[&str] = &["capability", "driver_type", "tag", "meta."];
+
+    fn device(key: &str) -> Vec<String> {
+        let values: &[&str] = match key {
+            "capability" => &["movable", "parameterized"],
+            "driver_type" => &["ell14"],
+            "tag" => &["Table_1", "optics"],
+            "meta.room" => &["b12"],
+            _ => &[],
+        };
+        values.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_filter_clauses() {
+        let matches = |expr: &str| {
+            ListFilter::parse(expr, DEVICE_KEYS)
+                .unwrap()
+                .matches(device)
+        };
+
+        assert!(matches(""));
+        assert!(matches("capability=movable AND tag=table_1"));
+        assert!(matches("driver_type=esp300|ell14"));
+        assert!(matches("driver_type=ell* meta.room=a12|b12"));
+        assert!(matches("capability!=readable"));
+        assert!(!matches("capability=movable tag!=optics"));
+        assert!(!matches("meta.operator=*"));
+
+        assert!(ListFilter::parse("online=true", DEVICE_KEYS).is_err());
+        assert!(ListFilter::parse("capability", DEVICE_KEYS).is_err());
+        assert!(ListFilter::parse("meta.=x", DEVICE_KEYS).is_err());
+    }
+
+    #[test]
+    fn test_glob_and_paginate() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("grid_*_scan", "grid_xy_scan"));
+        assert!(!glob_match("grid_*_scan", "grid_scan"));
+        assert!(glob_match("a*b*a", "abba"));
+        assert!(!glob_match("ab", "abc"));
+
+        assert_eq!(paginate(vec![1, 2, 3, 4, 5], 1, 2), [2, 3]);
+        assert_eq!(paginate(vec![1, 2, 3], 1, 0), [2, 3]);
+        assert!(paginate(vec![1, 2, 3], 5, 2).is_empty());
+    }
+}
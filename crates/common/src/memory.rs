@@ -0,0 +1,422 @@
+//! Per-subsystem memory accounting and budgets.
+//!
+//! Daemon RSS alone doesn't say which component is growing. Components that
+//! hold large or unbounded allocations (frame pools, ring buffers, queues,
+//! caches, histories) open a [`MemoryAccount`] in the process-wide
+//! [`MemoryLedger`] and keep it current as their footprint changes. An
+//! account is released when its owner drops it.
+//!
+//! [`MemoryLedger::report`] sums the accounts per [`MemorySubsystem`] and
+//! checks each total against its budget. The daemon's memory watchdog turns
+//! the report into health warnings and alarms, and the health service
+//! exposes it to clients.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [memory]
+//! check_interval_secs = 10
+//! warn_fraction = 0.8
+//!
+//! [memory.budgets_mb]
+//! pool = 2048
+//! ring_buffer = 1024
+//! history = 256
+//! ```
+//!
+//! Subsystems without a budget are accounted but never alarm.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+
+const MB: u64 = 1024 * 1024;
+
+/// Kind of component an allocation belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemorySubsystem {
+    /// Frame and buffer pools of drivers
+    Pool,
+    /// Memory-mapped ring buffers
+    RingBuffer,
+    /// Queues holding data between pipeline stages
+    Queue,
+    /// Caches of recent values (camera preview frames)
+    Cache,
+    /// Retained histories (device state logs, plot histories)
+    History,
+}
+
+impl MemorySubsystem {
+    pub const ALL: [MemorySubsystem; 5] = [
+        MemorySubsystem::Pool,
+        MemorySubsystem::RingBuffer,
+        MemorySubsystem::Queue,
+        MemorySubsystem::Cache,
+        MemorySubsystem::History,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MemorySubsystem::Pool => "pool",
+            MemorySubsystem::RingBuffer => "ring_buffer",
+            MemorySubsystem::Queue => "queue",
+            MemorySubsystem::Cache => "cache",
+            MemorySubsystem::History => "history",
+        }
+    }
+}
+
+impl fmt::Display for MemorySubsystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Budgets per subsystem, in MiB (`None` = unlimited)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubsystemBudgets {
+    pub pool: Option<u64>,
+    pub ring_buffer: Option<u64>,
+    pub queue: Option<u64>,
+    pub cache: Option<u64>,
+    pub history: Option<u64>,
+}
+
+impl SubsystemBudgets {
+    pub fn get(&self, subsystem: MemorySubsystem) -> Option<u64> {
+        match subsystem {
+            MemorySubsystem::Pool => self.pool,
+            MemorySubsystem::RingBuffer => self.ring_buffer,
+            MemorySubsystem::Queue => self.queue,
+            MemorySubsystem::Cache => self.cache,
+            MemorySubsystem::History => self.history,
+        }
+    }
+}
+
+/// Memory budgets and how often they are checked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemoryBudgetConfig {
+    /// Seconds between budget checks by the daemon's watchdog
+    pub check_interval_secs: u64,
+    /// Fraction of a budget at which a subsystem is reported as a warning
+    pub warn_fraction: f64,
+    pub budgets_mb: SubsystemBudgets,
+}
+
+impl Default for MemoryBudgetConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_secs: 10,
+            warn_fraction: 0.8,
+            budgets_mb: SubsystemBudgets::default(),
+        }
+    }
+}
+
+impl MemoryBudgetConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.check_interval_secs == 0 {
+            bail!("memory.check_interval_secs must be at least 1");
+        }
+        if !(self.warn_fraction > 0.0 && self.warn_fraction <= 1.0) {
+            bail!(
+                "memory.warn_fraction must be in (0, 1], got {}",
+                self.warn_fraction
+            );
+        }
+        Ok(())
+    }
+
+    /// Budget of a subsystem in bytes
+    pub fn budget_bytes(&self, subsystem: MemorySubsystem) -> Option<u64> {
+        self.budgets_mb.get(subsystem).map(|mb| mb * MB)
+    }
+
+    /// Where `bytes` stands against the subsystem's budget
+    pub fn status(&self, subsystem: MemorySubsystem, bytes: u64) -> BudgetStatus {
+        match self.budget_bytes(subsystem) {
+            Some(budget) if bytes > budget => BudgetStatus::Exceeded,
+            Some(budget) if bytes as f64 >= budget as f64 * self.warn_fraction => {
+                BudgetStatus::Warning
+            }
+            _ => BudgetStatus::Ok,
+        }
+    }
+}
+
+/// Usage of a subsystem relative to its budget, in increasing severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetStatus {
+    Ok,
+    /// At or above the warning fraction of the budget
+    Warning,
+    /// Over budget
+    Exceeded,
+}
+
+impl BudgetStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BudgetStatus::Ok => "ok",
+            BudgetStatus::Warning => "warning",
+            BudgetStatus::Exceeded => "exceeded",
+        }
+    }
+}
+
+impl fmt::Display for BudgetStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Bytes held by one component, kept current by its owner
+///
+/// Clones share the count; the ledger forgets the account once every clone
+/// is dropped.
+#[derive(Debug, Clone)]
+pub struct MemoryAccount {
+    bytes: Arc<AtomicU64>,
+}
+
+impl MemoryAccount {
+    /// Account outside any ledger (components created without one, tests)
+    pub fn detached() -> Self {
+        Self {
+            bytes: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn set(&self, bytes: u64) {
+        self.bytes.store(bytes, Ordering::Relaxed);
+    }
+
+    pub fn add(&self, bytes: u64) {
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Release `bytes`, stopping at zero
+    pub fn sub(&self, bytes: u64) {
+        let _ = self
+            .bytes
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |held| {
+                Some(held.saturating_sub(bytes))
+            });
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+}
+
+/// Usage of one subsystem
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubsystemUsage {
+    pub subsystem: MemorySubsystem,
+    pub bytes: u64,
+    pub budget_bytes: Option<u64>,
+    pub status: BudgetStatus,
+    /// Bytes per account name (accounts sharing a name are summed)
+    pub accounts: BTreeMap<String, u64>,
+}
+
+/// Accounted memory of every subsystem, checked against the budgets
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MemoryReport {
+    /// One entry per subsystem, in [`MemorySubsystem::ALL`] order
+    pub subsystems: Vec<SubsystemUsage>,
+    pub total_bytes: u64,
+}
+
+impl MemoryReport {
+    /// Most severe status of any subsystem
+    pub fn worst_status(&self) -> BudgetStatus {
+        self.subsystems
+            .iter()
+            .map(|usage| usage.status)
+            .max()
+            .unwrap_or(BudgetStatus::Ok)
+    }
+
+    pub fn get(&self, subsystem: MemorySubsystem) -> Option<&SubsystemUsage> {
+        self.subsystems.iter().find(|u| u.subsystem == subsystem)
+    }
+}
+
+struct LedgerEntry {
+    name: String,
+    subsystem: MemorySubsystem,
+    bytes: Weak<AtomicU64>,
+}
+
+/// Open memory accounts and the budgets they are checked against
+#[derive(Default)]
+pub struct MemoryLedger {
+    entries: Mutex<Vec<LedgerEntry>>,
+    budgets: Mutex<MemoryBudgetConfig>,
+}
+
+impl MemoryLedger {
+    /// Process-wide ledger shared by drivers, storage and the daemon
+    pub fn global() -> &'static MemoryLedger {
+        static GLOBAL: OnceLock<MemoryLedger> = OnceLock::new();
+        GLOBAL.get_or_init(MemoryLedger::default)
+    }
+
+    /// Open an account for `name` (e.g. `frame_pool:camera1`) in `subsystem`
+    pub fn account(&self, name: impl Into<String>, subsystem: MemorySubsystem) -> MemoryAccount {
+        let account = MemoryAccount::detached();
+        let mut entries = self.entries.lock().unwrap_or_else(|p| p.into_inner());
+        // Opening accounts is rare; forget released ones here
+        entries.retain(|entry| entry.bytes.strong_count() > 0);
+        entries.push(LedgerEntry {
+            name: name.into(),
+            subsystem,
+            bytes: Arc::downgrade(&account.bytes),
+        });
+        account
+    }
+
+    pub fn set_budgets(&self, budgets: MemoryBudgetConfig) {
+        *self.budgets.lock().unwrap_or_else(|p| p.into_inner()) = budgets;
+    }
+
+    pub fn budgets(&self) -> MemoryBudgetConfig {
+        self.budgets
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+
+    /// Current usage of every subsystem against the budgets
+    pub fn report(&self) -> MemoryReport {
+        let budgets = self.budgets();
+        let mut accounts: BTreeMap<MemorySubsystem, BTreeMap<String, u64>> = BTreeMap::new();
+        {
+            let mut entries = self.entries.lock().unwrap_or_else(|p| p.into_inner());
+            entries.retain(|entry| match entry.bytes.upgrade() {
+                Some(bytes) => {
+                    *accounts
+                        .entry(entry.subsystem)
+                        .or_default()
+                        .entry(entry.name.clone())
+                        .or_default() += bytes.load(Ordering::Relaxed);
+                    true
+                }
+                None => false,
+            });
+        }
+
+        let subsystems: Vec<SubsystemUsage> = MemorySubsystem::ALL
+            .iter()
+            .map(|&subsystem| {
+                let accounts = accounts.remove(&subsystem).unwrap_or_default();
+                let bytes = accounts.values().sum();
+                SubsystemUsage {
+                    subsystem,
+                    bytes,
+                    budget_bytes: budgets.budget_bytes(subsystem),
+                    status: budgets.status(subsystem, bytes),
+                    accounts,
+                }
+            })
+            .collect();
+        MemoryReport {
+            total_bytes: subsystems.iter().map(|u| u.bytes).sum(),
+            subsystems,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_sums_accounts_and_checks_budgets() {
+        let ledger = MemoryLedger::default();
+        ledger.set_budgets(MemoryBudgetConfig {
+            budgets_mb: SubsystemBudgets {
+                pool: Some(10),
+                history: Some(1),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        let camera = ledger.account("frame_pool:camera", MemorySubsystem::Pool);
+        camera.set(6 * MB);
+        let second = ledger.account("frame_pool:camera", MemorySubsystem::Pool);
+        second.set(2 * MB);
+        let history = ledger.account("device_history", MemorySubsystem::History);
+        history.add(2 * MB);
+        history.sub(MB / 2);
+        let queue = ledger.account("spill", MemorySubsystem::Queue);
+        queue.set(100 * MB);
+
+        let report = ledger.report();
+        let pool = report.get(MemorySubsystem::Pool).unwrap();
+        assert_eq!(pool.bytes, 8 * MB);
+        assert_eq!(pool.accounts["frame_pool:camera"], 8 * MB);
+        assert_eq!(pool.status, BudgetStatus::Warning);
+        let history_usage = report.get(MemorySubsystem::History).unwrap();
+        assert_eq!(history_usage.bytes, 3 * MB / 2);
+        assert_eq!(history_usage.status, BudgetStatus::Exceeded);
+        // No budget: accounted, never alarms
+        assert_eq!(
+            report.get(MemorySubsystem::Queue).unwrap().status,
+            BudgetStatus::Ok
+        );
+        assert_eq!(report.worst_status(), BudgetStatus::Exceeded);
+        assert_eq!(report.total_bytes, 8 * MB + 3 * MB / 2 + 100 * MB);
+
+        // Dropped accounts are released
+        drop((camera, queue));
+        let report = ledger.report();
+        assert_eq!(report.get(MemorySubsystem::Pool).unwrap().bytes, 2 * MB);
+        assert_eq!(report.get(MemorySubsystem::Queue).unwrap().bytes, 0);
+        assert_eq!(
+            report.get(MemorySubsystem::Pool).unwrap().status,
+            BudgetStatus::Ok
+        );
+    }
+
+    #[test]
+    fn test_config_parsing_and_validation() {
+        let config: MemoryBudgetConfig = toml::from_str(
+            r"
+            warn_fraction = 0.9
+
+            [budgets_mb]
+            ring_buffer = 512
+            ",
+        )
+        .unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.check_interval_secs, 10);
+        assert_eq!(
+            config.budget_bytes(MemorySubsystem::RingBuffer),
+            Some(512 * MB)
+        );
+        assert_eq!(config.budget_bytes(MemorySubsystem::Cache), None);
+        assert_eq!(
+            config.status(MemorySubsystem::RingBuffer, 470 * MB),
+            BudgetStatus::Warning
+        );
+
+        let invalid = MemoryBudgetConfig {
+            warn_fraction: 1.5,
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
@@ -14,6 +14,22 @@ pub enum ModuleState {
     Error = 7,
 }
 
+impl ModuleState {
+    /// Snake_case state name, as used by the module state graph
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unknown => "unknown",
+            Self::Created => "created",
+            Self::Configured => "configured",
+            Self::Staged => "staged",
+            Self::Running => "running",
+            Self::Paused => "paused",
+            Self::Stopped => "stopped",
+            Self::Error => "error",
+        }
+    }
+}
+
 /// Severity level for module events
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ModuleEventSeverity {
@@ -94,4 +110,9 @@ pub struct ModuleTypeInfo {
     pub data_types: Vec<String>,
     pub required_roles: Vec<ModuleRole>,
     pub optional_roles: Vec<ModuleRole>,
+    /// JSON Schema for the configuration, when `parameters` is too flat to
+    /// describe it. Top-level properties map to config keys; non-string
+    /// values are JSON-encoded.
+    #[serde(default)]
+    pub config_schema: Option<String>,
 }
@@ -0,0 +1,276 @@
+//! Backlash, scale and offset correction for Movable devices.
+//!
+//! [`CorrectedMovable`] wraps any [`Movable`] and converts between the
+//! driver's raw positions and corrected positions:
+//!
+//! ```text
+//! corrected = raw * scale + offset
+//! ```
+//!
+//! Backlash is compensated by unidirectional approach: a move that would
+//! end travelling against the configured approach direction first
+//! overshoots by the backlash distance, settles, and then makes the final
+//! move in the approach direction. Every target is therefore reached from
+//! the same side, which also removes most hysteresis.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [motion_corrections.rotator_2]
+//! backlash = 0.2          # raw units
+//! approach = "positive"
+//! scale = 1.0
+//! offset = -12.5          # corrected units
+//! ```
+//!
+//! The registry applies corrections when devices register and reports them
+//! in device metadata, so positions read through the registry (and stored
+//! with scans and frames) are corrected values. That covers the Movable
+//! handle and the driver's `position` parameter when it is written or read
+//! through the registry (`set_parameter_json`, `get_parameter_json`, which
+//! gRPC, presets and recipes use). The parameter itself, and the change
+//! notifications it sends, stay in raw units.
+
+use crate::capabilities::Movable;
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// Direction from which every target is finally approached
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApproachDirection {
+    /// Final move is towards increasing raw position
+    #[default]
+    Positive,
+    /// Final move is towards decreasing raw position
+    Negative,
+}
+
+/// Correction applied to a Movable device
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MotionCorrection {
+    /// Overshoot distance for backlash compensation, in raw units (0 = off)
+    #[serde(default)]
+    pub backlash: f64,
+    #[serde(default)]
+    pub approach: ApproachDirection,
+    /// Corrected units per raw unit
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+    /// Corrected position of raw zero
+    #[serde(default)]
+    pub offset: f64,
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+impl Default for MotionCorrection {
+    fn default() -> Self {
+        Self {
+            backlash: 0.0,
+            approach: ApproachDirection::default(),
+            scale: default_scale(),
+            offset: 0.0,
+        }
+    }
+}
+
+impl MotionCorrection {
+    /// Check that the correction can be inverted
+    pub fn validate(&self) -> Result<()> {
+        if !self.scale.is_finite() || self.scale == 0.0 {
+            bail!("scale must be a non-zero number (got {})", self.scale);
+        }
+        if !self.offset.is_finite() {
+            bail!("offset must be finite");
+        }
+        if !self.backlash.is_finite() || self.backlash < 0.0 {
+            bail!("backlash must not be negative (got {})", self.backlash);
+        }
+        Ok(())
+    }
+
+    /// Corrected position for a raw position
+    pub fn from_raw(&self, raw: f64) -> f64 {
+        raw * self.scale + self.offset
+    }
+
+    /// Raw position for a corrected position
+    pub fn to_raw(&self, corrected: f64) -> f64 {
+        (corrected - self.offset) / self.scale
+    }
+
+    /// Corrected travel range for a raw range
+    pub fn range_from_raw(&self, min: Option<f64>, max: Option<f64>) -> (Option<f64>, Option<f64>) {
+        let (min, max) = (min.map(|v| self.from_raw(v)), max.map(|v| self.from_raw(v)));
+        if self.scale < 0.0 {
+            (max, min)
+        } else {
+            (min, max)
+        }
+    }
+
+    /// Intermediate raw target needed before moving from `current` to
+    /// `target`, if the final move would go against the approach direction
+    pub fn overshoot(&self, current: f64, target: f64) -> Option<f64> {
+        if self.backlash == 0.0 {
+            return None;
+        }
+        match self.approach {
+            ApproachDirection::Positive if target < current => Some(target - self.backlash),
+            ApproachDirection::Negative if target > current => Some(target + self.backlash),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MotionCorrection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "scale {}, offset {}", self.scale, self.offset)?;
+        if self.backlash > 0.0 {
+            let side = match self.approach {
+                ApproachDirection::Positive => '+',
+                ApproachDirection::Negative => '-',
+            };
+            write!(f, ", backlash {} approached from {}", self.backlash, side)?;
+        }
+        Ok(())
+    }
+}
+
+/// A Movable whose positions are corrected and whose moves take up backlash
+pub struct CorrectedMovable {
+    inner: Arc<dyn Movable>,
+    correction: MotionCorrection,
+}
+
+impl CorrectedMovable {
+    pub fn new(inner: Arc<dyn Movable>, correction: MotionCorrection) -> Result<Self> {
+        correction.validate()?;
+        Ok(Self { inner, correction })
+    }
+
+    pub fn correction(&self) -> &MotionCorrection {
+        &self.correction
+    }
+
+    /// The wrapped device
+    pub fn inner(&self) -> &Arc<dyn Movable> {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl Movable for CorrectedMovable {
+    async fn move_abs(&self, position: f64) -> Result<()> {
+        let target = self.correction.to_raw(position);
+        if self.correction.backlash > 0.0 {
+            let current = self.inner.position().await?;
+            if let Some(overshoot) = self.correction.overshoot(current, target) {
+                self.inner.move_abs(overshoot).await?;
+                self.inner.wait_settled().await?;
+            }
+        }
+        self.inner.move_abs(target).await
+    }
+
+    async fn move_rel(&self, distance: f64) -> Result<()> {
+        let current = self.position().await?;
+        self.move_abs(current + distance).await
+    }
+
+    async fn position(&self) -> Result<f64> {
+        Ok(self.correction.from_raw(self.inner.position().await?))
+    }
+
+    async fn wait_settled(&self) -> Result<()> {
+        self.inner.wait_settled().await
+    }
+
+    async fn stop(&self) -> Result<()> {
+        self.inner.stop().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Stage that records every raw target it is sent
+    #[derive(Default)]
+    struct Stage {
+        position: Mutex<f64>,
+        moves: Mutex<Vec<f64>>,
+    }
+
+    #[async_trait]
+    impl Movable for Stage {
+        async fn move_abs(&self, position: f64) -> Result<()> {
+            *self.position.lock().unwrap() = position;
+            self.moves.lock().unwrap().push(position);
+            Ok(())
+        }
+        async fn move_rel(&self, distance: f64) -> Result<()> {
+            let target = *self.position.lock().unwrap() + distance;
+            self.move_abs(target).await
+        }
+        async fn position(&self) -> Result<f64> {
+            Ok(*self.position.lock().unwrap())
+        }
+        async fn wait_settled(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backlash_and_scale() {
+        let stage = Arc::new(Stage::default());
+        let correction: MotionCorrection = toml::from_str(
+            r"
+            backlash = 0.5
+            scale = 2.0
+            offset = 10.0
+            ",
+        )
+        .unwrap();
+        let corrected = CorrectedMovable::new(stage.clone(), correction).unwrap();
+
+        // Increasing moves go straight there
+        corrected.move_abs(20.0).await.unwrap();
+        assert_eq!(*stage.moves.lock().unwrap(), [5.0]);
+        assert!((corrected.position().await.unwrap() - 20.0).abs() < 1e-9);
+
+        // Decreasing moves overshoot, then approach from below
+        corrected.move_rel(-4.0).await.unwrap();
+        assert_eq!(*stage.moves.lock().unwrap(), [5.0, 2.5, 3.0]);
+        assert!((corrected.position().await.unwrap() - 16.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_correction_validation_and_range() {
+        let correction = MotionCorrection {
+            scale: -1.0,
+            offset: 5.0,
+            ..Default::default()
+        };
+        assert!(correction.validate().is_ok());
+        assert_eq!(
+            correction.range_from_raw(Some(0.0), Some(10.0)),
+            (Some(-5.0), Some(5.0))
+        );
+        assert_eq!(correction.overshoot(1.0, 0.0), None);
+
+        let zero_scale = MotionCorrection {
+            scale: 0.0,
+            ..Default::default()
+        };
+        assert!(zero_scale.validate().is_err());
+    }
+}
@@ -0,0 +1,616 @@
+//! Coordinated multi-axis motion groups.
+//!
+//! A motion group drives several [`Movable`] member devices as one unit,
+//! through a kinematic transform between the group's own axes and the
+//! members' positions. For example, a sample rotated in polar coordinates
+//! (`r`, `theta`) on top of two linear stages (`x`, `y`):
+//!
+//! ```toml
+//! [[motion_groups]]
+//! id = "sample_polar"
+//! members = ["stage_x", "stage_y"]
+//! kinematics = { type = "polar", center_x = 12.5, center_y = 8.0 }
+//! limits = { r = [0.0, 5.0], theta = [-180.0, 180.0] }
+//! keep_out = [{ name = "clamp", range = [4.0, 5.0], axes = ["r"] }]
+//! ```
+//!
+//! A group move checks the group limits and keep-out zones (see
+//! [`crate::keep_out`]) in group coordinates, transforms the target to member
+//! positions and starts every member move together; group settle waits for
+//! all members. Each group axis is also exposed as a [`Movable`]
+//! ([`GroupAxis`]) that holds the other axes at their last target, so a plan
+//! can scan `theta` like any single stage.
+
+use crate::capabilities::{Commandable, Movable};
+use crate::coordinates::AffineTransform;
+use crate::keep_out::{self, KeepOutZone};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use futures::future::{join_all, try_join_all};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// Mapping between group axes and member positions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Kinematics {
+    /// One group axis per member, positions passed through unchanged
+    #[default]
+    Direct,
+    /// `r` and `theta` (degrees) about a centre, driving an x and a y stage
+    Polar {
+        #[serde(default)]
+        center_x: f64,
+        #[serde(default)]
+        center_y: f64,
+    },
+    /// Affine map from group axes to members (see [`crate::coordinates`])
+    Affine(AffineTransform),
+}
+
+impl Kinematics {
+    /// Number of members the transform needs (`None` = any)
+    fn member_count(&self) -> Option<usize> {
+        match self {
+            Self::Direct => None,
+            Self::Polar { .. } => Some(2),
+            Self::Affine(transform) => Some(transform.dims()),
+        }
+    }
+
+    /// Default group axis names
+    fn default_axes(&self, members: &[String]) -> Vec<String> {
+        match self {
+            Self::Direct => members.to_vec(),
+            Self::Polar { .. } => vec!["r".to_string(), "theta".to_string()],
+            Self::Affine(_) => members.iter().map(|m| format!("{}_sample", m)).collect(),
+        }
+    }
+
+    /// Member positions for a group position
+    pub fn inverse(&self, axes: &[f64]) -> Result<Vec<f64>> {
+        match self {
+            Self::Direct => Ok(axes.to_vec()),
+            Self::Polar { center_x, center_y } => {
+                let (r, theta) = (axes[0], axes[1]);
+                if r < 0.0 {
+                    bail!("Polar radius must not be negative (got {})", r);
+                }
+                let theta = theta.to_radians();
+                Ok(vec![center_x + r * theta.cos(), center_y + r * theta.sin()])
+            }
+            Self::Affine(transform) => Ok(transform.apply(axes)),
+        }
+    }
+
+    /// Group position for member positions
+    pub fn forward(&self, members: &[f64]) -> Vec<f64> {
+        match self {
+            Self::Direct => members.to_vec(),
+            Self::Polar { center_x, center_y } => {
+                let (dx, dy) = (members[0] - center_x, members[1] - center_y);
+                vec![dx.hypot(dy), dy.atan2(dx).to_degrees()]
+            }
+            // Validated as invertible, so NaN only for a hand-built singular map
+            Self::Affine(transform) => match transform.inverse() {
+                Ok(inverse) => inverse.apply(members),
+                Err(_) => vec![f64::NAN; members.len()],
+            },
+        }
+    }
+}
+
+/// Configuration of a motion group (`[[motion_groups]]` in the hardware config)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MotionGroupConfig {
+    /// Group ID; each axis is registered as `<id>_<axis>`
+    pub id: String,
+    /// Display name
+    #[serde(default)]
+    pub name: String,
+    /// Member device IDs, in the order the kinematics expects
+    pub members: Vec<String>,
+    #[serde(default)]
+    pub kinematics: Kinematics,
+    /// Group axis names (defaults depend on the kinematics)
+    #[serde(default)]
+    pub axes: Vec<String>,
+    /// Travel limits per group axis: `axis = [min, max]`
+    #[serde(default)]
+    pub limits: BTreeMap<String, [f64; 2]>,
+    /// Regions of group coordinates the group must not enter or cross
+    #[serde(default)]
+    pub keep_out: Vec<KeepOutZone>,
+}
+
+impl MotionGroupConfig {
+    /// Group axis names, from `axes` or the kinematics defaults
+    pub fn axis_names(&self) -> Vec<String> {
+        if self.axes.is_empty() {
+            self.kinematics.default_axes(&self.members)
+        } else {
+            self.axes.clone()
+        }
+    }
+
+    /// Device ID under which a group axis is registered
+    pub fn axis_device_id(&self, axis: &str) -> String {
+        format!("{}_{}", self.id, axis)
+    }
+
+    /// Indices of the group axes a keep-out zone spans
+    fn zone_axes(&self, zone: &KeepOutZone) -> Result<Vec<usize>> {
+        let axes = self.axis_names();
+        if zone.axes.is_empty() {
+            if axes.len() < zone.dims() {
+                bail!(
+                    "Motion group '{}' keep-out zone {} spans {} axes, the group has {}",
+                    self.id,
+                    zone,
+                    zone.dims(),
+                    axes.len()
+                );
+            }
+            return Ok((0..zone.dims()).collect());
+        }
+        zone.axes
+            .iter()
+            .map(|name| {
+                axes.iter().position(|axis| axis == name).ok_or_else(|| {
+                    anyhow!(
+                        "Motion group '{}' keep-out zone {} uses unknown axis '{}'",
+                        self.id,
+                        zone,
+                        name
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Check member count, axis names and limits
+    pub fn validate(&self) -> Result<()> {
+        if self.members.is_empty() {
+            bail!("Motion group '{}' has no members", self.id);
+        }
+        match self.kinematics.member_count() {
+            Some(count) if self.members.len() != count => bail!(
+                "Motion group '{}' needs {} members for its kinematics, got {}",
+                self.id,
+                count,
+                self.members.len()
+            ),
+            _ => {}
+        }
+        if let Kinematics::Affine(transform) = &self.kinematics {
+            transform
+                .inverse()
+                .map_err(|e| anyhow!("Motion group '{}': {}", self.id, e))?;
+        }
+        let axes = self.axis_names();
+        let expected = self.kinematics.member_count().unwrap_or(self.members.len());
+        if axes.len() != expected {
+            bail!(
+                "Motion group '{}' needs {} axis names, got {}",
+                self.id,
+                expected,
+                axes.len()
+            );
+        }
+        for (axis, [min, max]) in &self.limits {
+            if !axes.contains(axis) {
+                bail!(
+                    "Motion group '{}' has limits for unknown axis '{}'",
+                    self.id,
+                    axis
+                );
+            }
+            if min > max {
+                bail!(
+                    "Motion group '{}' axis '{}' has min {} above max {}",
+                    self.id,
+                    axis,
+                    min,
+                    max
+                );
+            }
+        }
+        for zone in &self.keep_out {
+            zone.validate()
+                .map_err(|e| anyhow!("Motion group '{}': {}", self.id, e))?;
+            self.zone_axes(zone)?;
+        }
+        Ok(())
+    }
+}
+
+/// Several Movable devices moved together through a kinematic transform
+pub struct MotionGroup {
+    config: MotionGroupConfig,
+    axes: Vec<String>,
+    members: Vec<Arc<dyn Movable>>,
+    /// Last commanded group position, used to hold axes during single-axis moves
+    target: Mutex<Option<Vec<f64>>>,
+}
+
+impl std::fmt::Debug for MotionGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MotionGroup")
+            .field("config", &self.config)
+            .field("axes", &self.axes)
+            .field("target", &self.target)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MotionGroup {
+    /// Build a group from its config and the member devices, in config order
+    pub fn new(config: MotionGroupConfig, members: Vec<Arc<dyn Movable>>) -> Result<Self> {
+        config.validate()?;
+        if members.len() != config.members.len() {
+            bail!(
+                "Motion group '{}' expects {} members, got {}",
+                config.id,
+                config.members.len(),
+                members.len()
+            );
+        }
+        Ok(Self {
+            axes: config.axis_names(),
+            config,
+            members,
+            target: Mutex::new(None),
+        })
+    }
+
+    pub fn config(&self) -> &MotionGroupConfig {
+        &self.config
+    }
+
+    /// Group axis names, in position order
+    pub fn axis_names(&self) -> &[String] {
+        &self.axes
+    }
+
+    /// Index of a group axis by name
+    pub fn axis_index(&self, axis: &str) -> Option<usize> {
+        self.axes.iter().position(|a| a == axis)
+    }
+
+    /// Limits of a group axis, if configured
+    pub fn axis_limits(&self, axis: &str) -> Option<(f64, f64)> {
+        self.config.limits.get(axis).map(|&[min, max]| (min, max))
+    }
+
+    /// Fail if a group position is outside the group limits
+    pub fn check_limits(&self, position: &[f64]) -> Result<()> {
+        if position.len() != self.axes.len() {
+            bail!(
+                "Motion group '{}' has {} axes, got {} values",
+                self.config.id,
+                self.axes.len(),
+                position.len()
+            );
+        }
+        for (axis, &value) in self.axes.iter().zip(position) {
+            let Some((min, max)) = self.axis_limits(axis) else {
+                continue;
+            };
+            if !(min..=max).contains(&value) {
+                bail!(
+                    "Motion group '{}' axis '{}' target {} is outside [{}, {}]",
+                    self.config.id,
+                    axis,
+                    value,
+                    min,
+                    max
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Fail if the straight path to a group position enters a keep-out zone
+    ///
+    /// `from` is the current group position, if known.
+    pub fn check_keep_out(&self, from: Option<&[f64]>, to: &[f64]) -> Result<()> {
+        for zone in &self.config.keep_out {
+            let axes = self.config.zone_axes(zone)?;
+            let project =
+                |position: &[f64]| -> Vec<f64> { axes.iter().map(|&i| position[i]).collect() };
+            let from = from.map(project);
+            keep_out::check_move(
+                &self.config.id,
+                std::slice::from_ref(zone),
+                from.as_deref(),
+                &project(to),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Start a move of all members to a group position
+    pub async fn move_to(&self, position: &[f64]) -> Result<()> {
+        self.check_limits(position)?;
+        if !self.config.keep_out.is_empty() {
+            let current = self.position().await.ok();
+            self.check_keep_out(current.as_deref(), position)?;
+        }
+        let targets = self.config.kinematics.inverse(position)?;
+        try_join_all(
+            self.members
+                .iter()
+                .zip(targets)
+                .map(|(member, target)| member.move_abs(target)),
+        )
+        .await?;
+        *self.target.lock().unwrap_or_else(|p| p.into_inner()) = Some(position.to_vec());
+        Ok(())
+    }
+
+    /// Move one group axis, holding the others at their last target
+    pub async fn move_axis(&self, index: usize, value: f64) -> Result<()> {
+        let held = self
+            .target
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone();
+        let mut position = match held {
+            Some(position) => position,
+            None => self.position().await?,
+        };
+        let slot = position
+            .get_mut(index)
+            .ok_or_else(|| anyhow!("Motion group '{}' has no axis {}", self.config.id, index))?;
+        *slot = value;
+        self.move_to(&position).await
+    }
+
+    /// Current group position, from the members' positions
+    pub async fn position(&self) -> Result<Vec<f64>> {
+        let members = try_join_all(self.members.iter().map(|m| m.position())).await?;
+        Ok(self.config.kinematics.forward(&members))
+    }
+
+    /// Wait until every member has settled
+    pub async fn wait_settled(&self) -> Result<()> {
+        try_join_all(self.members.iter().map(|m| m.wait_settled())).await?;
+        Ok(())
+    }
+
+    /// Stop every member, even if some fail to stop
+    pub async fn stop(&self) -> Result<()> {
+        let results = join_all(self.members.iter().map(|m| m.stop())).await;
+        results.into_iter().collect::<Result<Vec<()>>>()?;
+        Ok(())
+    }
+
+    /// A single group axis as a Movable device
+    pub fn axis(self: &Arc<Self>, index: usize) -> GroupAxis {
+        GroupAxis {
+            group: Arc::clone(self),
+            index,
+        }
+    }
+
+    fn position_json(&self, position: &[f64]) -> serde_json::Value {
+        self.axes
+            .iter()
+            .zip(position)
+            .map(|(axis, value)| (axis.clone(), json!(value)))
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+}
+
+/// Group commands: `move` (`{"r": 2.0, "theta": 45.0}`, missing axes held),
+/// `position`, `wait_settled` and `stop`
+#[async_trait]
+impl Commandable for MotionGroup {
+    async fn execute_command(
+        &self,
+        command: &str,
+        args: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        match command {
+            "move" => {
+                let held = self
+                    .target
+                    .lock()
+                    .unwrap_or_else(|p| p.into_inner())
+                    .clone();
+                let mut position = match held {
+                    Some(position) => position,
+                    None => self.position().await?,
+                };
+                let targets = args
+                    .as_object()
+                    .ok_or_else(|| anyhow!("move expects an object of axis positions"))?;
+                for (axis, value) in targets {
+                    let index = self
+                        .axis_index(axis)
+                        .ok_or_else(|| anyhow!("Unknown group axis '{}'", axis))?;
+                    position[index] = value
+                        .as_f64()
+                        .ok_or_else(|| anyhow!("Position for '{}' must be a number", axis))?;
+                }
+                self.move_to(&position).await?;
+                Ok(self.position_json(&position))
+            }
+            "position" => Ok(self.position_json(&self.position().await?)),
+            "wait_settled" => {
+                self.wait_settled().await?;
+                Ok(serde_json::Value::Null)
+            }
+            "stop" => {
+                self.stop().await?;
+                Ok(serde_json::Value::Null)
+            }
+            _ => bail!(
+                "Unknown motion group command '{}' (move, position, wait_settled, stop)",
+                command
+            ),
+        }
+    }
+}
+
+/// One axis of a motion group, usable wherever a Movable is expected
+pub struct GroupAxis {
+    group: Arc<MotionGroup>,
+    index: usize,
+}
+
+#[async_trait]
+impl Movable for GroupAxis {
+    async fn move_abs(&self, position: f64) -> Result<()> {
+        self.group.move_axis(self.index, position).await
+    }
+
+    async fn move_rel(&self, distance: f64) -> Result<()> {
+        let current = self.position().await?;
+        self.group.move_axis(self.index, current + distance).await
+    }
+
+    async fn position(&self) -> Result<f64> {
+        Ok(self.group.position().await?[self.index])
+    }
+
+    async fn wait_settled(&self) -> Result<()> {
+        self.group.wait_settled().await
+    }
+
+    async fn stop(&self) -> Result<()> {
+        self.group.stop().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Member that jumps straight to its target
+    struct Stage(Mutex<f64>);
+
+    #[async_trait]
+    impl Movable for Stage {
+        async fn move_abs(&self, position: f64) -> Result<()> {
+            *self.0.lock().unwrap() = position;
+            Ok(())
+        }
+        async fn move_rel(&self, distance: f64) -> Result<()> {
+            *self.0.lock().unwrap() += distance;
+            Ok(())
+        }
+        async fn position(&self) -> Result<f64> {
+            Ok(*self.0.lock().unwrap())
+        }
+        async fn wait_settled(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn polar_group() -> (Arc<MotionGroup>, Arc<Stage>, Arc<Stage>) {
+        let config: MotionGroupConfig = toml::from_str(
+            r#"
+            id = "sample_polar"
+            members = ["stage_x", "stage_y"]
+            kinematics = { type = "polar", center_x = 10.0, center_y = 5.0 }
+            limits = { r = [0.0, 3.0] }
+            "#,
+        )
+        .unwrap();
+        let x = Arc::new(Stage(Mutex::new(10.0)));
+        let y = Arc::new(Stage(Mutex::new(5.0)));
+        let group = MotionGroup::new(config, vec![x.clone(), y.clone()]).unwrap();
+        (Arc::new(group), x, y)
+    }
+
+    #[tokio::test]
+    async fn test_polar_group_moves() {
+        let (group, x, y) = polar_group();
+        assert_eq!(group.axis_names(), ["r", "theta"]);
+
+        group.move_to(&[2.0, 90.0]).await.unwrap();
+        assert!((*x.0.lock().unwrap() - 10.0).abs() < 1e-9);
+        assert!((*y.0.lock().unwrap() - 7.0).abs() < 1e-9);
+
+        // Scanning theta alone keeps r at its target
+        let theta = group.axis(1);
+        theta.move_abs(180.0).await.unwrap();
+        assert!((*x.0.lock().unwrap() - 8.0).abs() < 1e-9);
+        assert!((theta.position().await.unwrap() - 180.0).abs() < 1e-9);
+
+        let r = group.axis(0);
+        assert!(r.move_abs(4.0).await.is_err());
+        assert!((r.position().await.unwrap() - 2.0).abs() < 1e-9);
+
+        let reply = group
+            .execute_command("move", json!({ "r": 1.0 }))
+            .await
+            .unwrap();
+        assert_eq!(reply["theta"], json!(180.0));
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let mut config = MotionGroupConfig {
+            id: "xy".to_string(),
+            name: String::new(),
+            members: vec!["stage_x".to_string()],
+            kinematics: Kinematics::Polar {
+                center_x: 0.0,
+                center_y: 0.0,
+            },
+            axes: Vec::new(),
+            limits: BTreeMap::new(),
+            keep_out: Vec::new(),
+        };
+        assert!(config.validate().is_err());
+
+        config.kinematics = Kinematics::Direct;
+        config.limits.insert("stage_x".to_string(), [0.0, 25.0]);
+        assert!(config.validate().is_ok());
+        assert_eq!(config.axis_device_id("stage_x"), "xy_stage_x");
+
+        config.limits.insert("z".to_string(), [0.0, 1.0]);
+        assert!(config.validate().is_err());
+
+        // A polygon needs two group axes
+        config.limits.clear();
+        config.keep_out = vec![KeepOutZone::polygon(
+            "clamp",
+            vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]],
+        )];
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_keep_out_polygon_blocks_group_paths() {
+        let config: MotionGroupConfig = toml::from_str(
+            r#"
+            id = "sample_xy"
+            members = ["stage_x", "stage_y"]
+            keep_out = [
+                { name = "clamp", polygon = [[0.0, 0.0], [4.0, 0.0], [4.0, 3.0], [0.0, 3.0]] },
+            ]
+            "#,
+        )
+        .unwrap();
+        let x = Arc::new(Stage(Mutex::new(-1.0)));
+        let y = Arc::new(Stage(Mutex::new(1.0)));
+        let group = MotionGroup::new(config, vec![x.clone(), y.clone()]).unwrap();
+
+        // Straight through the clamp, and into it
+        assert!(group.move_to(&[5.0, 1.0]).await.is_err());
+        assert!(group.move_to(&[2.0, 2.0]).await.is_err());
+        assert!((*x.0.lock().unwrap() + 1.0).abs() < 1e-9);
+
+        // Around it
+        group.move_to(&[-1.0, 4.0]).await.unwrap();
+        group.move_to(&[5.0, 4.0]).await.unwrap();
+        assert!((*x.0.lock().unwrap() - 5.0).abs() < 1e-9);
+    }
+}
@@ -82,6 +82,7 @@
 //! - **dtype="enum"**: Choice parameters use `dtype="enum"` per the proto contract
 //!   (daq.proto:610), not "string".
 
+use crate::core::ParameterValue;
 use anyhow::{anyhow, Result};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
@@ -152,6 +153,28 @@ pub trait ParameterBase: Send + Sync {
 
     /// Get the number of active subscribers
     fn subscriber_count(&self) -> usize;
+
+    /// Get the current value as a [`ParameterValue`] matching the metadata dtype.
+    fn get_typed(&self) -> Result<ParameterValue> {
+        self.metadata()
+            .conform_value(ParameterValue::from_json(&self.get_json()?))
+    }
+
+    /// Set the value from a [`ParameterValue`].
+    ///
+    /// The value is converted to the parameter's dtype and checked against
+    /// its limits and choices before it reaches the driver, so callers get a
+    /// descriptive error instead of a JSON deserialization failure. Returns
+    /// the value after the set.
+    fn set_typed(&self, value: ParameterValue) -> Result<ParameterValue> {
+        let metadata = self.metadata();
+        if metadata.read_only {
+            return Err(anyhow!("Parameter '{}' is read-only", metadata.name));
+        }
+        let value = metadata.validate_value(value)?;
+        self.set_json(value.to_json())?;
+        self.get_typed()
+    }
 }
 
 /// Combines ParameterBase with Any for downcasting when concrete type is needed.
@@ -317,6 +340,82 @@ pub struct ObservableMetadata {
     /// per the proto contract (daq.proto:610).
     #[serde(default)]
     pub enum_values: Vec<String>,
+
+    /// Whether changing this parameter needs explicit confirmation.
+    ///
+    /// Set for settings that can damage hardware or samples (laser power,
+    /// high-voltage setpoints). The daemon turns remote sets into proposals
+    /// that must be confirmed, and the GUI renders a confirmation dialog.
+    #[serde(default)]
+    pub dangerous: bool,
+}
+
+impl ObservableMetadata {
+    /// Convert `value` to this parameter's dtype.
+    ///
+    /// Integers are accepted for `float` parameters, and integral floats for
+    /// `int` parameters. Values of parameters with an empty or non-scalar
+    /// dtype pass unchanged.
+    pub fn conform_value(&self, value: ParameterValue) -> Result<ParameterValue> {
+        let dtype = if self.enum_values.is_empty() {
+            self.dtype.as_str()
+        } else {
+            "enum"
+        };
+        if !matches!(dtype, "float" | "int" | "bool" | "string" | "enum") {
+            return Ok(value);
+        }
+        let converted = match (dtype, value) {
+            ("float", ParameterValue::Float(f)) => Some(ParameterValue::Float(f)),
+            ("float", ParameterValue::Int(i)) => Some(ParameterValue::Float(i as f64)),
+            ("int", ParameterValue::Int(i)) => Some(ParameterValue::Int(i)),
+            ("int", ParameterValue::Float(f)) if f.fract() == 0.0 && f.is_finite() => {
+                Some(ParameterValue::Int(f as i64))
+            }
+            ("bool", ParameterValue::Bool(b)) => Some(ParameterValue::Bool(b)),
+            ("string" | "enum", ParameterValue::String(s)) => Some(ParameterValue::String(s)),
+            _ => None,
+        };
+        converted.ok_or_else(|| anyhow!("Parameter '{}' expects a {} value", self.name, dtype))
+    }
+
+    /// Convert `value` to this parameter's dtype and check it against the
+    /// introspectable limits (`min_value`, `max_value`, `enum_values`).
+    pub fn validate_value(&self, value: ParameterValue) -> Result<ParameterValue> {
+        let value = self.conform_value(value)?;
+        if let Some(number) = match &value {
+            ParameterValue::Float(f) => Some(*f),
+            ParameterValue::Int(i) => Some(*i as f64),
+            _ => None,
+        } {
+            if !number.is_finite() {
+                return Err(anyhow!("Value must be finite, got {}", number));
+            }
+            if self.min_value.is_some_and(|min| number < min)
+                || self.max_value.is_some_and(|max| number > max)
+            {
+                return Err(anyhow!(
+                    "Value {} out of range [{}, {}] for parameter '{}'",
+                    number,
+                    self.min_value.map_or("-inf".to_string(), |v| v.to_string()),
+                    self.max_value.map_or("inf".to_string(), |v| v.to_string()),
+                    self.name
+                ));
+            }
+        }
+        match &value {
+            ParameterValue::String(choice)
+                if !self.enum_values.is_empty() && !self.enum_values.contains(choice) =>
+            {
+                Err(anyhow!(
+                    "Value {:?} not in choices {:?}",
+                    choice,
+                    self.enum_values
+                ))
+            }
+            _ => Ok(value),
+        }
+    }
 }
 
 impl<T> Observable<T>
@@ -338,6 +437,7 @@ where
                     min_value: None,
                     max_value: None,
                     enum_values: Vec::new(),
+                    dangerous: false,
                 },
                 validator: None,
             })),
@@ -362,6 +462,12 @@ where
         self
     }
 
+    /// Mark this observable as dangerous (changes need confirmation).
+    pub fn dangerous(self) -> Self {
+        self.shared.write().metadata.dangerous = true;
+        self
+    }
+
     /// Add a custom validator function.
     pub fn with_validator<F>(self, validator: F) -> Self
     where
@@ -1157,6 +1263,43 @@ mod tests {
         assert_eq!(wavelength_param.get(), 850.0);
     }
 
+    #[test]
+    fn test_typed_get_set() {
+        let mut params = ParameterSet::new();
+        params.register(Observable::new("power", 50.0).with_range_introspectable(0.0, 100.0));
+        params.register(Observable::new("gain", 2_i64).with_range_introspectable(1, 4));
+        params.register(
+            Observable::new("mode", "auto".to_string())
+                .with_choices_introspectable(vec!["auto".into(), "manual".into()]),
+        );
+
+        let power = params.get("power").unwrap();
+        assert_eq!(power.get_typed().unwrap(), ParameterValue::Float(50.0));
+        // Integers are widened for float parameters
+        assert_eq!(
+            power.set_typed(ParameterValue::Int(75)).unwrap(),
+            ParameterValue::Float(75.0)
+        );
+        let err = power.set_typed(ParameterValue::Float(150.0)).unwrap_err();
+        assert!(err.to_string().contains("out of range"));
+        let err = power.set_typed(ParameterValue::Bool(true)).unwrap_err();
+        assert!(err.to_string().contains("expects a float"));
+
+        let gain = params.get("gain").unwrap();
+        assert_eq!(
+            gain.set_typed(ParameterValue::Float(3.0)).unwrap(),
+            ParameterValue::Int(3)
+        );
+        assert!(gain.set_typed(ParameterValue::Float(3.5)).is_err());
+
+        let mode = params.get("mode").unwrap();
+        assert!(mode.set_typed(ParameterValue::from("turbo")).is_err());
+        assert_eq!(
+            mode.set_typed(ParameterValue::from("manual")).unwrap(),
+            ParameterValue::from("manual")
+        );
+    }
+
     #[tokio::test]
     async fn test_parameter_set_with_parameter() {
         use crate::parameter::Parameter;
@@ -0,0 +1,165 @@
+//! On-change filtering for high-channel-count streams.
+//!
+//! Systems with hundreds of slowly-changing channels (temperatures,
+//! setpoints) would otherwise send the same values over and over. An
+//! [`OnChangeFilter`] passes a channel's value only when it moved by more
+//! than that channel's deadband since the value last sent, and periodically
+//! asks for a keyframe (every channel's current value) so subscribers that
+//! join late, or missed an update, converge on the full state.
+//!
+//! Channels are keyed `device:name`, like channel aliases. A deadband is
+//! looked up for `device:name`, then `name` (every device), then the default.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Deadbands per channel
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Deadbands {
+    /// Deadband for channels without their own entry
+    #[serde(default)]
+    pub default: f64,
+    /// `device:name` or `name` -> deadband
+    #[serde(default)]
+    pub channels: HashMap<String, f64>,
+}
+
+impl Deadbands {
+    /// The same deadband for every channel
+    pub fn uniform(deadband: f64) -> Self {
+        Self {
+            default: deadband,
+            channels: HashMap::new(),
+        }
+    }
+
+    /// Deadband for one channel
+    pub fn get(&self, device: &str, name: &str) -> f64 {
+        self.channels
+            .get(&channel_key(device, name))
+            .or_else(|| self.channels.get(name))
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+/// `device:name` channel key
+pub fn channel_key(device: &str, name: &str) -> String {
+    format!("{}:{}", device, name)
+}
+
+/// Deadband and keyframe state for one subscriber
+#[derive(Debug)]
+pub struct OnChangeFilter {
+    deadbands: Deadbands,
+    keyframe_interval: Option<Duration>,
+    /// Last value sent per channel key
+    last_sent: HashMap<String, f64>,
+    last_keyframe: Option<Instant>,
+}
+
+impl OnChangeFilter {
+    /// `keyframe_interval` of `None` sends only the initial keyframe
+    pub fn new(deadbands: Deadbands, keyframe_interval: Option<Duration>) -> Self {
+        Self {
+            deadbands,
+            keyframe_interval,
+            last_sent: HashMap::new(),
+            last_keyframe: None,
+        }
+    }
+
+    /// Whether a keyframe is due: at the start, then every interval
+    pub fn keyframe_due(&self, now: Instant) -> bool {
+        match (self.last_keyframe, self.keyframe_interval) {
+            (None, _) => true,
+            (Some(last), Some(interval)) => now.duration_since(last) >= interval,
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Record that a keyframe with these values was sent
+    pub fn keyframe_sent<'a>(
+        &mut self,
+        now: Instant,
+        values: impl IntoIterator<Item = (&'a str, &'a str, f64)>,
+    ) {
+        for (device, name, value) in values {
+            self.last_sent.insert(channel_key(device, name), value);
+        }
+        self.last_keyframe = Some(now);
+    }
+
+    /// Whether `value` moved beyond the channel's deadband since it was last
+    /// sent (always true for a channel not sent yet). Does not record it.
+    pub fn changed(&self, device: &str, name: &str, value: f64) -> bool {
+        let Some(&last) = self.last_sent.get(&channel_key(device, name)) else {
+            return true;
+        };
+        if value.is_nan() || last.is_nan() {
+            // NaN -> NaN is no change; entering or leaving NaN is
+            return value.is_nan() != last.is_nan();
+        }
+        (value - last).abs() > self.deadbands.get(device, name)
+    }
+
+    /// Record that `value` was sent for a channel
+    pub fn sent(&mut self, device: &str, name: &str, value: f64) {
+        self.last_sent.insert(channel_key(device, name), value);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deadband_lookup_order() {
+        let deadbands = Deadbands {
+            default: 0.1,
+            channels: HashMap::from([
+                ("temperature".to_string(), 0.5),
+                ("lakeshore:temperature".to_string(), 0.01),
+            ]),
+        };
+        assert_eq!(deadbands.get("lakeshore", "temperature"), 0.01);
+        assert_eq!(deadbands.get("chiller", "temperature"), 0.5);
+        assert_eq!(deadbands.get("chiller", "flow"), 0.1);
+    }
+
+    #[test]
+    fn test_changes_within_deadband_are_dropped() {
+        let mut filter = OnChangeFilter::new(Deadbands::uniform(0.5), None);
+        assert!(filter.changed("tc", "temp", 20.0));
+        filter.sent("tc", "temp", 20.0);
+
+        assert!(!filter.changed("tc", "temp", 20.4));
+        assert!(filter.changed("tc", "temp", 20.6));
+        // Compared with the last value sent, so slow drift still gets through
+        filter.sent("tc", "temp", 20.6);
+        assert!(!filter.changed("tc", "temp", 21.0));
+        assert!(filter.changed("tc", "temp", 21.2));
+
+        assert!(filter.changed("tc", "temp", f64::NAN));
+        filter.sent("tc", "temp", f64::NAN);
+        assert!(!filter.changed("tc", "temp", f64::NAN));
+    }
+
+    #[test]
+    fn test_keyframes() {
+        let start = Instant::now();
+        let mut filter =
+            OnChangeFilter::new(Deadbands::uniform(0.5), Some(Duration::from_secs(10)));
+        assert!(filter.keyframe_due(start));
+        filter.keyframe_sent(start, [("tc", "temp", 20.0)]);
+        assert!(!filter.changed("tc", "temp", 20.1));
+        assert!(!filter.keyframe_due(start + Duration::from_secs(9)));
+        assert!(filter.keyframe_due(start + Duration::from_secs(10)));
+
+        let mut once = OnChangeFilter::new(Deadbands::default(), None);
+        once.keyframe_sent(start, []);
+        assert!(!once.keyframe_due(start + Duration::from_secs(3600)));
+    }
+}
@@ -0,0 +1,542 @@
+//! Saturation and over-range protection for analog outputs and Movables.
+//!
+//! An [`OutputLimit`] bounds the setpoints a device accepts: absolute
+//! clamps and a maximum slew rate. The registry hands out devices wrapped in
+//! [`LimitedMovable`] and [`LimitedSettable`], and parameters written by
+//! name go through [`OutputLimiter::set_parameter`], so the limits hold for
+//! every client (gRPC, scripts, plans, modules), not only for the ones that
+//! check.
+//!
+//! Setpoints outside `[min, max]` are rejected, or clamped into range with
+//! `on_violation = "clamp"`. Steps larger than the slew rate allows are
+//! ramped in increments every [`SLEW_STEP_INTERVAL`]. Violations are logged
+//! and published as [`LimitViolation`]s; the daemon reports those of limits
+//! with `alarm = true` to the health monitor, which marks the device
+//! degraded.
+//!
+//! # Configuration
+//!
+//! Keyed by device ID, then by Settable or device parameter (`voltage`,
+//! `voltage_1`, ...) or [`POSITION_PARAMETER`] for Movable devices:
+//!
+//! ```toml
+//! [output_limits.piezo_amp.voltage]
+//! min = 0.0
+//! max = 1.0             # amplifier input is 1 V max
+//! max_slew_rate = 0.5   # units per second
+//! alarm = true
+//!
+//! [output_limits.stage_x.position]
+//! min = -5.0
+//! max = 20.0
+//! on_violation = "clamp"
+//! ```
+//!
+//! Non-numeric parameter values are passed through unchanged.
+
+use crate::capabilities::{Movable, Settable};
+use crate::experiment::document::now_ns;
+use crate::observable::ParameterBase;
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Parameter name under which a Movable's position limit is configured
+pub const POSITION_PARAMETER: &str = "position";
+
+/// Time between the intermediate setpoints of a slew-limited step
+pub const SLEW_STEP_INTERVAL: Duration = Duration::from_millis(50);
+
+/// What happens to a setpoint outside the limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViolationAction {
+    /// Refuse the command
+    #[default]
+    Reject,
+    /// Apply the nearest allowed setpoint instead
+    Clamp,
+}
+
+/// Limits on the setpoints of one output
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OutputLimit {
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+    /// Largest change per second; larger steps are ramped
+    #[serde(default)]
+    pub max_slew_rate: Option<f64>,
+    #[serde(default)]
+    pub on_violation: ViolationAction,
+    /// Report violations as alarms
+    #[serde(default)]
+    pub alarm: bool,
+}
+
+impl OutputLimit {
+    /// Check that the limits can be satisfied
+    pub fn validate(&self) -> Result<()> {
+        for (name, bound) in [("min", self.min), ("max", self.max)] {
+            if bound.is_some_and(|v| !v.is_finite()) {
+                bail!("{} must be finite", name);
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min, self.max) {
+            if min > max {
+                bail!("min ({}) is above max ({})", min, max);
+            }
+        }
+        if let Some(rate) = self.max_slew_rate {
+            if !rate.is_finite() || rate <= 0.0 {
+                bail!("max_slew_rate must be positive (got {})", rate);
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a setpoint is within `[min, max]`
+    pub fn contains(&self, value: f64) -> bool {
+        value.is_finite()
+            && self.min.is_none_or(|min| value >= min)
+            && self.max.is_none_or(|max| value <= max)
+    }
+
+    /// Nearest allowed setpoint
+    pub fn clamp(&self, value: f64) -> f64 {
+        let value = self.min.map_or(value, |min| value.max(min));
+        self.max.map_or(value, |max| value.min(max))
+    }
+
+    /// Setpoints to pass through on the way from `from` to `to`, one per
+    /// [`SLEW_STEP_INTERVAL`] (excluding `to` itself)
+    pub fn ramp(&self, from: f64, to: f64) -> Vec<f64> {
+        let Some(rate) = self.max_slew_rate else {
+            return Vec::new();
+        };
+        let max_step = rate * SLEW_STEP_INTERVAL.as_secs_f64();
+        let distance = to - from;
+        if !distance.is_finite() || distance.abs() <= max_step {
+            return Vec::new();
+        }
+        let steps = (distance.abs() / max_step).ceil() as usize;
+        (1..steps)
+            .map(|i| from + distance * i as f64 / steps as f64)
+            .collect()
+    }
+}
+
+impl fmt::Display for OutputLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bound = |v: Option<f64>| v.map_or_else(|| "-".to_string(), |v| v.to_string());
+        write!(f, "[{}, {}]", bound(self.min), bound(self.max))?;
+        if let Some(rate) = self.max_slew_rate {
+            write!(f, ", max {}/s", rate)?;
+        }
+        Ok(())
+    }
+}
+
+/// A setpoint that was refused or clamped by an output limit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LimitViolation {
+    pub device_id: String,
+    pub parameter: String,
+    pub requested: f64,
+    /// Setpoint applied instead (None if the command was rejected)
+    pub applied: Option<f64>,
+    pub limit: OutputLimit,
+    pub timestamp_ns: u64,
+}
+
+impl fmt::Display for LimitViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} setpoint {} outside limits {}",
+            self.device_id, self.parameter, self.requested, self.limit
+        )?;
+        match self.applied {
+            Some(applied) => write!(f, ", clamped to {}", applied),
+            None => write!(f, ", rejected"),
+        }
+    }
+}
+
+/// Output limits of one device and the setpoints last applied through them
+pub struct OutputLimiter {
+    device_id: String,
+    limits: HashMap<String, OutputLimit>,
+    violations: broadcast::Sender<LimitViolation>,
+    last_setpoints: Mutex<HashMap<String, f64>>,
+}
+
+impl OutputLimiter {
+    /// Limiter publishing violations to `violations`
+    pub fn new(
+        device_id: impl Into<String>,
+        limits: HashMap<String, OutputLimit>,
+        violations: broadcast::Sender<LimitViolation>,
+    ) -> Result<Self> {
+        let device_id = device_id.into();
+        for (parameter, limit) in &limits {
+            limit
+                .validate()
+                .map_err(|e| anyhow!("{} {}: {}", device_id, parameter, e))?;
+        }
+        Ok(Self {
+            device_id,
+            limits,
+            violations,
+            last_setpoints: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn device_id(&self) -> &str {
+        &self.device_id
+    }
+
+    /// Limit configured for a parameter
+    pub fn limit(&self, parameter: &str) -> Option<&OutputLimit> {
+        self.limits.get(parameter)
+    }
+
+    /// Setpoint to apply for a requested one, or an error if it is refused
+    ///
+    /// Violations are logged and published whether or not they are clamped.
+    pub fn enforce(&self, parameter: &str, requested: f64) -> Result<f64> {
+        let Some(limit) = self.limits.get(parameter) else {
+            return Ok(requested);
+        };
+        if limit.contains(requested) {
+            return Ok(requested);
+        }
+
+        let applied = match limit.on_violation {
+            ViolationAction::Clamp if !requested.is_nan() => Some(limit.clamp(requested)),
+            _ => None,
+        };
+        let violation = LimitViolation {
+            device_id: self.device_id.clone(),
+            parameter: parameter.to_string(),
+            requested,
+            applied,
+            limit: limit.clone(),
+            timestamp_ns: now_ns(),
+        };
+        tracing::warn!(
+            device_id = %self.device_id,
+            parameter,
+            alarm = limit.alarm,
+            "Output limit violated: {}",
+            violation
+        );
+        let message = violation.to_string();
+        // No subscribers is fine
+        let _ = self.violations.send(violation);
+        applied.ok_or_else(|| anyhow!(message))
+    }
+
+    /// Setpoint last applied through the limiter
+    pub fn last_setpoint(&self, parameter: &str) -> Option<f64> {
+        self.last_setpoints
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(parameter)
+            .copied()
+    }
+
+    fn record_setpoint(&self, parameter: &str, value: f64) {
+        self.last_setpoints
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .insert(parameter.to_string(), value);
+    }
+
+    fn slews(&self, parameter: &str) -> bool {
+        self.limits
+            .get(parameter)
+            .is_some_and(|limit| limit.max_slew_rate.is_some())
+    }
+
+    fn ramp(&self, parameter: &str, from: f64, to: f64) -> Vec<f64> {
+        self.limits
+            .get(parameter)
+            .map(|limit| limit.ramp(from, to))
+            .unwrap_or_default()
+    }
+}
+
+/// A Movable whose targets are checked against its position limit
+pub struct LimitedMovable {
+    inner: Arc<dyn Movable>,
+    limiter: Arc<OutputLimiter>,
+}
+
+impl LimitedMovable {
+    pub fn new(inner: Arc<dyn Movable>, limiter: Arc<OutputLimiter>) -> Self {
+        Self { inner, limiter }
+    }
+
+    /// The wrapped device
+    pub fn inner(&self) -> &Arc<dyn Movable> {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl Movable for LimitedMovable {
+    async fn move_abs(&self, position: f64) -> Result<()> {
+        let target = self.limiter.enforce(POSITION_PARAMETER, position)?;
+        if self.limiter.slews(POSITION_PARAMETER) {
+            let current = self.inner.position().await?;
+            for step in self.limiter.ramp(POSITION_PARAMETER, current, target) {
+                self.inner.move_abs(step).await?;
+                tokio::time::sleep(SLEW_STEP_INTERVAL).await;
+            }
+        }
+        self.inner.move_abs(target).await?;
+        self.limiter.record_setpoint(POSITION_PARAMETER, target);
+        Ok(())
+    }
+
+    async fn move_rel(&self, distance: f64) -> Result<()> {
+        // Relative moves are checked at their absolute target
+        let current = self.inner.position().await?;
+        self.move_abs(current + distance).await
+    }
+
+    async fn position(&self) -> Result<f64> {
+        self.inner.position().await
+    }
+
+    async fn wait_settled(&self) -> Result<()> {
+        self.inner.wait_settled().await
+    }
+
+    async fn stop(&self) -> Result<()> {
+        self.inner.stop().await
+    }
+}
+
+/// A Settable whose numeric parameters are checked against their limits
+pub struct LimitedSettable {
+    inner: Arc<dyn Settable>,
+    limiter: Arc<OutputLimiter>,
+}
+
+impl LimitedSettable {
+    pub fn new(inner: Arc<dyn Settable>, limiter: Arc<OutputLimiter>) -> Self {
+        Self { inner, limiter }
+    }
+
+    /// The wrapped device
+    pub fn inner(&self) -> &Arc<dyn Settable> {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl Settable for LimitedSettable {
+    async fn set_value(&self, name: &str, value: serde_json::Value) -> Result<()> {
+        self.limiter
+            .set_limited(self.inner.as_ref(), name, value)
+            .await
+    }
+
+    async fn get_value(&self, name: &str) -> Result<serde_json::Value> {
+        self.inner.get_value(name).await
+    }
+}
+
+/// A [`Parameterized`](crate::capabilities::Parameterized) device parameter
+/// seen as a one-value Settable
+struct ParameterOutput<'a>(&'a dyn ParameterBase);
+
+#[async_trait]
+impl Settable for ParameterOutput<'_> {
+    async fn set_value(&self, _name: &str, value: serde_json::Value) -> Result<()> {
+        self.0.set_json(value)
+    }
+
+    async fn get_value(&self, _name: &str) -> Result<serde_json::Value> {
+        self.0.get_json()
+    }
+}
+
+impl OutputLimiter {
+    /// Set a parameter by name, within its limit
+    ///
+    /// Parameters written by name bypass the registry's Settable and Movable
+    /// handles, so their writers go through here.
+    pub async fn set_parameter(
+        &self,
+        parameter: &dyn ParameterBase,
+        value: serde_json::Value,
+    ) -> Result<()> {
+        let name = parameter.name();
+        self.set_limited(&ParameterOutput(parameter), &name, value)
+            .await
+    }
+
+    /// Where a slew-limited ramp starts: the last limited setpoint, or the
+    /// output's current value
+    async fn current_value(&self, output: &dyn Settable, name: &str) -> Option<f64> {
+        match self.last_setpoint(name) {
+            Some(value) => Some(value),
+            None => output
+                .get_value(name)
+                .await
+                .ok()
+                .and_then(|value| value.as_f64()),
+        }
+    }
+
+    async fn set_limited(
+        &self,
+        output: &dyn Settable,
+        name: &str,
+        value: serde_json::Value,
+    ) -> Result<()> {
+        let requested = match value.as_f64() {
+            Some(requested) if self.limit(name).is_some() => requested,
+            _ => return output.set_value(name, value).await,
+        };
+        let target = self.enforce(name, requested)?;
+
+        if self.slews(name) {
+            match self.current_value(output, name).await {
+                Some(current) => {
+                    for step in self.ramp(name, current, target) {
+                        output.set_value(name, serde_json::json!(step)).await?;
+                        self.record_setpoint(name, step);
+                        tokio::time::sleep(SLEW_STEP_INTERVAL).await;
+                    }
+                }
+                None => tracing::warn!(
+                    device_id = %self.device_id,
+                    parameter = name,
+                    "Current value unknown, first setpoint is not slew limited"
+                ),
+            }
+        }
+
+        // Unchanged setpoints keep their original JSON type (e.g. integers)
+        let in_range = self
+            .limit(name)
+            .is_some_and(|limit| limit.contains(requested));
+        let value = if in_range {
+            value
+        } else {
+            serde_json::json!(target)
+        };
+        output.set_value(name, value).await?;
+        self.record_setpoint(name, target);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    /// Output that records every value it is set to
+    #[derive(Default)]
+    struct Output {
+        values: Mutex<Vec<f64>>,
+    }
+
+    #[async_trait]
+    impl Settable for Output {
+        async fn set_value(&self, _name: &str, value: serde_json::Value) -> Result<()> {
+            self.values.lock().unwrap().push(value.as_f64().unwrap());
+            Ok(())
+        }
+        async fn get_value(&self, _name: &str) -> Result<serde_json::Value> {
+            Ok(serde_json::json!(self
+                .values
+                .lock()
+                .unwrap()
+                .last()
+                .copied()
+                .unwrap_or(0.0)))
+        }
+    }
+
+    fn limiter(limit: &str) -> (Arc<OutputLimiter>, broadcast::Receiver<LimitViolation>) {
+        let (tx, rx) = broadcast::channel(8);
+        let limits = HashMap::from([("voltage".to_string(), toml::from_str(limit).unwrap())]);
+        (
+            Arc::new(OutputLimiter::new("piezo_amp", limits, tx).unwrap()),
+            rx,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_rejects_and_clamps_out_of_range_setpoints() {
+        let output = Arc::new(Output::default());
+        let (rejecting, mut violations) = limiter("min = 0.0\nmax = 1.0\nalarm = true");
+        let limited = LimitedSettable::new(output.clone(), rejecting);
+
+        limited
+            .set_value("voltage", serde_json::json!(0.5))
+            .await
+            .unwrap();
+        let err = limited
+            .set_value("voltage", serde_json::json!(10.0))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("rejected"));
+        assert_eq!(*output.values.lock().unwrap(), [0.5]);
+
+        let violation = violations.try_recv().unwrap();
+        assert_eq!(violation.requested, 10.0);
+        assert_eq!(violation.applied, None);
+        assert!(violation.limit.alarm);
+
+        let (clamping, _) = limiter("max = 1.0\non_violation = \"clamp\"");
+        let limited = LimitedSettable::new(output.clone(), clamping);
+        limited
+            .set_value("voltage", serde_json::json!(10.0))
+            .await
+            .unwrap();
+        assert_eq!(output.values.lock().unwrap().last(), Some(&1.0));
+
+        // Other parameters and non-numeric values pass through
+        limited
+            .set_value("offset", serde_json::json!(10.0))
+            .await
+            .unwrap();
+        assert_eq!(output.values.lock().unwrap().last(), Some(&10.0));
+    }
+
+    #[tokio::test]
+    async fn test_slew_rate_ramps_large_steps() {
+        let output = Arc::new(Output::default());
+        let (slewing, _) = limiter("max_slew_rate = 2.0");
+        let limited = LimitedSettable::new(output.clone(), slewing);
+
+        // 2 V/s allows 0.1 V per 50 ms step
+        limited
+            .set_value("voltage", serde_json::json!(0.5))
+            .await
+            .unwrap();
+        let values = output.values.lock().unwrap().clone();
+        assert_eq!(values.len(), 5);
+        assert!(values.windows(2).all(|w| w[1] - w[0] <= 0.1 + 1e-9));
+        assert_eq!(values.last(), Some(&0.5));
+
+        assert!(toml::from_str::<OutputLimit>("min = 2.0\nmax = 1.0")
+            .unwrap()
+            .validate()
+            .is_err());
+    }
+}
@@ -270,6 +270,12 @@ where
         self
     }
 
+    /// Mark parameter as dangerous so remote changes require confirmation
+    pub fn dangerous(mut self) -> Self {
+        self.inner = self.inner.dangerous();
+        self
+    }
+
     /// Connect hardware write function
     ///
     /// After calling this, `set()` will write to hardware before updating
@@ -593,6 +599,7 @@ where
     max: Option<T>,
     choices: Option<Vec<T>>,
     read_only: bool,
+    dangerous: bool,
 }
 
 impl<T> ParameterBuilder<T>
@@ -621,6 +628,7 @@ where
             max: None,
             choices: None,
             read_only: false,
+            dangerous: false,
         }
     }
 
@@ -682,6 +690,16 @@ where
         self.read_only = true;
         self
     }
+
+    /// Mark parameter as dangerous.
+    ///
+    /// Remote changes to dangerous parameters (laser power, high voltage)
+    /// become proposals that must be confirmed before they are applied.
+    /// Returns `self` for method chaining.
+    pub fn dangerous(mut self) -> Self {
+        self.dangerous = true;
+        self
+    }
 }
 
 impl<T> ParameterBuilder<T>
@@ -719,6 +737,10 @@ where
             param = param.read_only();
         }
 
+        if self.dangerous {
+            param = param.dangerous();
+        }
+
         param
     }
 }
@@ -0,0 +1,347 @@
+//! Hardware parking actions run when a run pauses and resumes.
+//!
+//! A run paused overnight should not leave a shutter open, a stage creeping
+//! or a camera streaming. Devices (in the hardware config) and plans declare
+//! [`ParkingActions`]: what to do to each device when the RunEngine pauses at
+//! a checkpoint, and what to do when the run resumes.
+//!
+//! Without explicit `on_resume` actions, resuming undoes the pause actions
+//! from the state captured before them: a shutter that was open is reopened,
+//! a camera that was streaming restarts, a stage moved away goes back. A
+//! device that was already parked is left alone.
+//!
+//! A run that fails (a device error stops the plan) applies the pause actions
+//! too and stays parked, as does a run aborted while paused.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [parking.shutter]
+//! on_pause = [{ action = "close_shutter" }]
+//!
+//! [parking.camera]
+//! on_pause = [{ action = "stop_stream" }, { action = "unstage" }]
+//!
+//! [parking.stage_x]
+//! on_pause = [{ action = "stop_motion" }, { action = "move_to", position = 0.0 }]
+//! on_resume = []   # empty: return to where the stage was
+//! ```
+
+use crate::capabilities::{
+    FrameProducer, Movable, Settable, ShutterControl, Stageable, Triggerable,
+};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// One action applied to a device when parking or unparking it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ParkAction {
+    CloseShutter,
+    OpenShutter,
+    /// Stop any motion in progress
+    StopMotion,
+    MoveTo {
+        position: f64,
+    },
+    StopStream,
+    StartStream,
+    /// Unstage the device, which disarms cameras
+    Unstage,
+    Stage,
+    Arm,
+    /// Set a Settable parameter
+    Set {
+        parameter: String,
+        value: serde_json::Value,
+    },
+}
+
+impl fmt::Display for ParkAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CloseShutter => write!(f, "close shutter"),
+            Self::OpenShutter => write!(f, "open shutter"),
+            Self::StopMotion => write!(f, "stop motion"),
+            Self::MoveTo { position } => write!(f, "move to {}", position),
+            Self::StopStream => write!(f, "stop stream"),
+            Self::StartStream => write!(f, "start stream"),
+            Self::Unstage => write!(f, "unstage"),
+            Self::Stage => write!(f, "stage"),
+            Self::Arm => write!(f, "arm"),
+            Self::Set { parameter, value } => write!(f, "set {} = {}", parameter, value),
+        }
+    }
+}
+
+/// Actions declared for one device
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParkingActions {
+    /// Applied in order when the run pauses or fails
+    #[serde(default)]
+    pub on_pause: Vec<ParkAction>,
+    /// Applied in order when the run resumes; if empty, the pause actions
+    /// are undone from the state captured before them
+    #[serde(default)]
+    pub on_resume: Vec<ParkAction>,
+}
+
+impl ParkingActions {
+    pub fn is_empty(&self) -> bool {
+        self.on_pause.is_empty() && self.on_resume.is_empty()
+    }
+}
+
+/// The capabilities of a device that parking actions can use
+#[derive(Clone, Default)]
+pub struct ParkingTarget {
+    pub shutter: Option<Arc<dyn ShutterControl>>,
+    pub movable: Option<Arc<dyn Movable>>,
+    pub frame_producer: Option<Arc<dyn FrameProducer>>,
+    pub stageable: Option<Arc<dyn Stageable>>,
+    pub triggerable: Option<Arc<dyn Triggerable>>,
+    pub settable: Option<Arc<dyn Settable>>,
+}
+
+/// Result of parking one device
+#[derive(Debug, Clone, Default)]
+pub struct ParkedDevice {
+    pub device_id: String,
+    /// Actions to run on resume
+    pub on_resume: Vec<ParkAction>,
+    /// Pause actions that failed, with their errors
+    pub failures: Vec<(ParkAction, String)>,
+}
+
+/// Run the pause actions of a device, capturing what resuming has to undo
+///
+/// Every action is attempted even if an earlier one fails.
+pub async fn park(
+    device_id: &str,
+    target: &ParkingTarget,
+    actions: &ParkingActions,
+) -> ParkedDevice {
+    let mut undo = Vec::new();
+    let mut failures = Vec::new();
+    for action in &actions.on_pause {
+        let restore = capture_restore(target, action).await;
+        match apply(target, action).await {
+            Ok(()) => undo.extend(restore.into_iter().rev()),
+            Err(e) => failures.push((action.clone(), e.to_string())),
+        }
+    }
+
+    let on_resume = if actions.on_resume.is_empty() {
+        undo.reverse();
+        undo
+    } else {
+        actions.on_resume.clone()
+    };
+    ParkedDevice {
+        device_id: device_id.to_string(),
+        on_resume,
+        failures,
+    }
+}
+
+/// Run the resume actions of a parked device
+///
+/// Returns the actions that failed, with their errors.
+pub async fn unpark(target: &ParkingTarget, parked: &ParkedDevice) -> Vec<(ParkAction, String)> {
+    let mut failures = Vec::new();
+    for action in &parked.on_resume {
+        if let Err(e) = apply(target, action).await {
+            failures.push((action.clone(), e.to_string()));
+        }
+    }
+    failures
+}
+
+/// Apply a single action
+pub async fn apply(target: &ParkingTarget, action: &ParkAction) -> Result<()> {
+    match action {
+        ParkAction::CloseShutter => shutter(target)?.close_shutter().await,
+        ParkAction::OpenShutter => shutter(target)?.open_shutter().await,
+        ParkAction::StopMotion => movable(target)?.stop().await,
+        ParkAction::MoveTo { position } => {
+            let movable = movable(target)?;
+            movable.move_abs(*position).await?;
+            movable.wait_settled().await
+        }
+        ParkAction::StopStream => frame_producer(target)?.stop_stream().await,
+        ParkAction::StartStream => frame_producer(target)?.start_stream().await,
+        ParkAction::Unstage => stageable(target)?.unstage().await,
+        ParkAction::Stage => stageable(target)?.stage().await,
+        ParkAction::Arm => triggerable(target)?.arm().await,
+        ParkAction::Set { parameter, value } => {
+            settable(target)?.set_value(parameter, value.clone()).await
+        }
+    }
+}
+
+/// Actions that restore the state an action is about to change
+///
+/// Nothing is returned when the device is already in the parked state, or
+/// its state cannot be read.
+async fn capture_restore(target: &ParkingTarget, action: &ParkAction) -> Vec<ParkAction> {
+    match action {
+        ParkAction::CloseShutter => match &target.shutter {
+            Some(s) if s.is_shutter_open().await.unwrap_or(false) => vec![ParkAction::OpenShutter],
+            _ => vec![],
+        },
+        ParkAction::OpenShutter => match &target.shutter {
+            Some(s) if !s.is_shutter_open().await.unwrap_or(true) => vec![ParkAction::CloseShutter],
+            _ => vec![],
+        },
+        ParkAction::StopMotion => vec![],
+        ParkAction::MoveTo { .. } => match &target.movable {
+            Some(m) => match m.position().await {
+                Ok(position) => vec![ParkAction::MoveTo { position }],
+                Err(_) => vec![],
+            },
+            None => vec![],
+        },
+        ParkAction::StopStream => match &target.frame_producer {
+            Some(p) if p.is_streaming().await.unwrap_or(false) => vec![ParkAction::StartStream],
+            _ => vec![],
+        },
+        ParkAction::StartStream => match &target.frame_producer {
+            Some(p) if !p.is_streaming().await.unwrap_or(true) => vec![ParkAction::StopStream],
+            _ => vec![],
+        },
+        ParkAction::Unstage => {
+            let Some(stageable) = &target.stageable else {
+                return vec![];
+            };
+            // Devices that cannot report their state are assumed staged
+            if !stageable.is_staged().await.unwrap_or(true) {
+                return vec![];
+            }
+            let mut restore = vec![ParkAction::Stage];
+            if let Some(triggerable) = &target.triggerable {
+                if triggerable.is_armed().await.unwrap_or(false) {
+                    restore.push(ParkAction::Arm);
+                }
+            }
+            restore
+        }
+        ParkAction::Stage => match &target.stageable {
+            Some(s) if !s.is_staged().await.unwrap_or(true) => vec![ParkAction::Unstage],
+            _ => vec![],
+        },
+        ParkAction::Arm => vec![],
+        ParkAction::Set { parameter, .. } => match &target.settable {
+            Some(s) => match s.get_value(parameter).await {
+                Ok(value) => vec![ParkAction::Set {
+                    parameter: parameter.clone(),
+                    value,
+                }],
+                Err(_) => vec![],
+            },
+            None => vec![],
+        },
+    }
+}
+
+fn shutter(target: &ParkingTarget) -> Result<&Arc<dyn ShutterControl>> {
+    target
+        .shutter
+        .as_ref()
+        .ok_or_else(|| anyhow!("Device has no shutter control"))
+}
+
+fn movable(target: &ParkingTarget) -> Result<&Arc<dyn Movable>> {
+    target
+        .movable
+        .as_ref()
+        .ok_or_else(|| anyhow!("Device is not movable"))
+}
+
+fn frame_producer(target: &ParkingTarget) -> Result<&Arc<dyn FrameProducer>> {
+    target
+        .frame_producer
+        .as_ref()
+        .ok_or_else(|| anyhow!("Device does not produce frames"))
+}
+
+fn stageable(target: &ParkingTarget) -> Result<&Arc<dyn Stageable>> {
+    target
+        .stageable
+        .as_ref()
+        .ok_or_else(|| anyhow!("Device is not stageable"))
+}
+
+fn triggerable(target: &ParkingTarget) -> Result<&Arc<dyn Triggerable>> {
+    target
+        .triggerable
+        .as_ref()
+        .ok_or_else(|| anyhow!("Device is not triggerable"))
+}
+
+fn settable(target: &ParkingTarget) -> Result<&Arc<dyn Settable>> {
+    target
+        .settable
+        .as_ref()
+        .ok_or_else(|| anyhow!("Device has no settable parameters"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[derive(Default)]
+    struct TestShutter {
+        open: AtomicBool,
+    }
+
+    #[async_trait]
+    impl ShutterControl for TestShutter {
+        async fn open_shutter(&self) -> Result<()> {
+            self.open.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn close_shutter(&self) -> Result<()> {
+            self.open.store(false, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn is_shutter_open(&self) -> Result<bool> {
+            Ok(self.open.load(Ordering::SeqCst))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_park_and_restore_shutter() {
+        let shutter = Arc::new(TestShutter::default());
+        shutter.open_shutter().await.unwrap();
+        let target = ParkingTarget {
+            shutter: Some(shutter.clone()),
+            ..Default::default()
+        };
+        let actions: ParkingActions = toml::from_str(
+            r#"on_pause = [{ action = "close_shutter" }, { action = "stop_motion" }]"#,
+        )
+        .unwrap();
+
+        let parked = park("shutter", &target, &actions).await;
+        assert!(!shutter.is_shutter_open().await.unwrap());
+        // The shutter has no Movable, so stopping motion fails but doesn't
+        // keep the shutter from closing
+        assert_eq!(parked.failures.len(), 1);
+        assert_eq!(parked.on_resume, vec![ParkAction::OpenShutter]);
+
+        assert!(unpark(&target, &parked).await.is_empty());
+        assert!(shutter.is_shutter_open().await.unwrap());
+
+        // An already closed shutter stays closed on resume
+        shutter.close_shutter().await.unwrap();
+        let parked = park("shutter", &target, &actions).await;
+        assert!(parked.on_resume.is_empty());
+    }
+}
@@ -0,0 +1,161 @@
+//! CPU preprocessing backend.
+
+use super::{
+    histogram_bin, max_pixel_value, Calibration, FramePreprocessor, PreprocessingConfig,
+    ProcessedFrame,
+};
+use crate::data::Frame;
+use crate::error::DaqError;
+
+/// Reference implementation of the preprocessing arithmetic
+#[derive(Debug, Clone)]
+pub struct CpuPreprocessor {
+    calibration: Calibration,
+    binning: u32,
+    histogram_bins: Option<u32>,
+}
+
+impl CpuPreprocessor {
+    pub fn new(config: &PreprocessingConfig, calibration: Calibration) -> Self {
+        Self {
+            calibration,
+            binning: config.binning.max(1),
+            histogram_bins: config.histogram_bins.filter(|&bins| bins > 0),
+        }
+    }
+}
+
+impl FramePreprocessor for CpuPreprocessor {
+    fn name(&self) -> &'static str {
+        "cpu"
+    }
+
+    fn process(&mut self, frame: &Frame) -> Result<ProcessedFrame, DaqError> {
+        let width = frame.width as usize;
+        let height = frame.height as usize;
+        let pixels = width * height;
+        self.calibration.check_len(pixels)?;
+
+        let raw: Vec<u16> = if frame.bit_depth > 8 {
+            if frame.data.len() < pixels * 2 {
+                return Err(DaqError::Processing(format!(
+                    "Frame has {} bytes, expected {} for {}x{} 16-bit",
+                    frame.data.len(),
+                    pixels * 2,
+                    width,
+                    height
+                )));
+            }
+            frame.data[..pixels * 2]
+                .chunks_exact(2)
+                .map(|b| u16::from_le_bytes([b[0], b[1]]))
+                .collect()
+        } else {
+            if frame.data.len() < pixels {
+                return Err(DaqError::Processing(format!(
+                    "Frame has {} bytes, expected {} for {}x{} 8-bit",
+                    frame.data.len(),
+                    pixels,
+                    width,
+                    height
+                )));
+            }
+            frame.data[..pixels].iter().map(|&v| u16::from(v)).collect()
+        };
+
+        let max_value = max_pixel_value(frame.bit_depth);
+        let bin = self.binning as usize;
+        let out_width = width / bin;
+        let out_height = height / bin;
+        let block = (bin * bin) as f32;
+        let dark = &self.calibration.dark;
+        let gain = &self.calibration.gain;
+
+        let mut output = Vec::with_capacity(out_width * out_height);
+        let mut histogram = self.histogram_bins.map(|bins| vec![0u64; bins as usize]);
+        for oy in 0..out_height {
+            for ox in 0..out_width {
+                let mut sum = 0.0f32;
+                for y in oy * bin..(oy + 1) * bin {
+                    for x in ox * bin..(ox + 1) * bin {
+                        let i = y * width + x;
+                        let mut value = f32::from(raw[i]);
+                        if !dark.is_empty() {
+                            value = (value - dark[i]).max(0.0);
+                        }
+                        if !gain.is_empty() {
+                            value *= gain[i];
+                        }
+                        sum += value;
+                    }
+                }
+                let value = (sum / block).round().clamp(0.0, max_value as f32) as u32;
+                if let (Some(histogram), Some(bins)) = (histogram.as_mut(), self.histogram_bins) {
+                    histogram[histogram_bin(value, bins, max_value)] += 1;
+                }
+                output.push(value as u16);
+            }
+        }
+
+        let mut processed = Frame::from_u16(out_width as u32, out_height as u32, &output)
+            .with_frame_number(frame.frame_number)
+            .with_timestamp(frame.timestamp_ns)
+            .with_roi_offset(frame.roi_x, frame.roi_y);
+        // 8-bit input is widened to the 16-bit container
+        if frame.bit_depth > 8 {
+            processed.bit_depth = frame.bit_depth;
+        }
+        processed.exposure_ms = frame.exposure_ms;
+        processed.metadata.clone_from(&frame.metadata);
+
+        Ok(ProcessedFrame {
+            frame: processed,
+            histogram,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dark_flat_correction_binning_and_histogram() {
+        let config = PreprocessingConfig {
+            binning: 2,
+            histogram_bins: Some(4),
+            ..Default::default()
+        };
+        let calibration = Calibration::from_frames(
+            Some(&[10; 8]),
+            Some(&[110, 210, 110, 210, 110, 210, 110, 210]),
+        )
+        .unwrap();
+        let mut cpu = CpuPreprocessor::new(&config, calibration);
+
+        // 4x2 frame -> 2x1 after 2x2 binning
+        let frame = Frame::from_u16(4, 2, &[110, 210, 110, 210, 60000, 60000, 60000, 60000])
+            .with_frame_number(3);
+        let processed = cpu.process(&frame).unwrap();
+
+        assert_eq!((processed.frame.width, processed.frame.height), (2, 1));
+        assert_eq!(processed.frame.frame_number, 3);
+        let out: Vec<u16> = processed
+            .frame
+            .data
+            .chunks_exact(2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .collect();
+        // Top row corrects to the flat mean (150); bottom row is
+        // (60000 - 10) * (1.5 + 0.75), so the block mean is 33819.375
+        assert_eq!(out, vec![33819, 33819]);
+        assert_eq!(processed.histogram, Some(vec![0, 0, 2, 0]));
+    }
+
+    #[test]
+    fn test_calibration_size_mismatch_is_error() {
+        let calibration = Calibration::from_frames(Some(&[0; 3]), None).unwrap();
+        let mut cpu = CpuPreprocessor::new(&PreprocessingConfig::default(), calibration);
+        assert!(cpu.process(&Frame::from_u16(2, 2, &[0; 4])).is_err());
+    }
+}
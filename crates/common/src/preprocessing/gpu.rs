@@ -0,0 +1,378 @@
+//! wgpu compute backend.
+//!
+//! Runs the same arithmetic as [`CpuPreprocessor`](super::CpuPreprocessor) in
+//! a compute shader (`preprocess.wgsl`). Calibration is uploaded once; frame
+//! buffers are reused while the frame size stays the same. Each call uploads
+//! the frame, dispatches one invocation per output pixel and reads back the
+//! binned frame and histogram.
+
+use super::{max_pixel_value, Calibration, FramePreprocessor, PreprocessingConfig, ProcessedFrame};
+use crate::data::Frame;
+use crate::error::DaqError;
+use wgpu::util::DeviceExt;
+
+const WORKGROUP_SIZE: u32 = 16;
+/// Keeps `value * bins` within u32 in the shader
+const MAX_HISTOGRAM_BINS: u32 = 65_536;
+
+const FLAG_DARK: u32 = 1;
+const FLAG_FLAT: u32 = 2;
+
+/// Buffers sized for one frame geometry
+struct FrameBuffers {
+    width: u32,
+    height: u32,
+    raw: wgpu::Buffer,
+    output: wgpu::Buffer,
+    histogram: wgpu::Buffer,
+    readback: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+/// GPU preprocessing backend
+pub struct GpuPreprocessor {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+    params: wgpu::Buffer,
+    dark: wgpu::Buffer,
+    gain: wgpu::Buffer,
+    calibration: Calibration,
+    binning: u32,
+    histogram_bins: Option<u32>,
+    adapter_name: String,
+    buffers: Option<FrameBuffers>,
+}
+
+impl GpuPreprocessor {
+    /// Open the default high-performance adapter and upload calibration
+    ///
+    /// Fails if no adapter or device is available.
+    pub fn new(config: &PreprocessingConfig, calibration: Calibration) -> Result<Self, DaqError> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            force_fallback_adapter: false,
+            compatible_surface: None,
+        }))
+        .map_err(|e| DaqError::Processing(format!("No GPU adapter: {}", e)))?;
+        let adapter_name = adapter.get_info().name;
+
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("preprocessing"),
+            required_limits: adapter.limits(),
+            ..Default::default()
+        }))
+        .map_err(|e| DaqError::Processing(format!("Failed to open GPU device: {}", e)))?;
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("preprocess.wgsl"));
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("preprocessing"),
+            layout: None,
+            module: &shader,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("preprocessing params"),
+            size: std::mem::size_of::<[u32; 8]>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let dark = calibration_buffer(&device, "dark", &calibration.dark);
+        let gain = calibration_buffer(&device, "gain", &calibration.gain);
+
+        Ok(Self {
+            device,
+            queue,
+            pipeline,
+            params,
+            dark,
+            gain,
+            calibration,
+            binning: config.binning.max(1),
+            histogram_bins: config
+                .histogram_bins
+                .filter(|&bins| bins > 0)
+                .map(|bins| bins.min(MAX_HISTOGRAM_BINS)),
+            adapter_name,
+            buffers: None,
+        })
+    }
+
+    /// Name of the GPU adapter in use
+    pub fn adapter_name(&self) -> &str {
+        &self.adapter_name
+    }
+
+    /// Allocate buffers for a `width`×`height` frame unless already sized for it
+    fn ensure_buffers(&mut self, width: u32, height: u32) {
+        let stale = self
+            .buffers
+            .as_ref()
+            .is_none_or(|b| b.width != width || b.height != height);
+        if stale {
+            self.buffers = Some(self.create_buffers(width, height));
+        }
+    }
+
+    fn create_buffers(&self, width: u32, height: u32) -> FrameBuffers {
+        let pixels = u64::from(width) * u64::from(height);
+        let out_pixels = u64::from(width / self.binning) * u64::from(height / self.binning);
+        let bins = u64::from(self.histogram_bins.unwrap_or(0));
+        let storage = wgpu::BufferUsages::STORAGE;
+
+        let buffer = |label: &str, size: u64, usage: wgpu::BufferUsages| {
+            self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                // Zero-sized bindings are invalid
+                size: size.max(4),
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+        // Two u16 pixels per u32 word
+        let raw = buffer(
+            "preprocessing raw",
+            pixels.div_ceil(2) * 4,
+            storage | wgpu::BufferUsages::COPY_DST,
+        );
+        let output = buffer(
+            "preprocessing output",
+            out_pixels * 4,
+            storage | wgpu::BufferUsages::COPY_SRC,
+        );
+        let histogram = buffer(
+            "preprocessing histogram",
+            bins * 4,
+            storage | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+        );
+        let readback = buffer(
+            "preprocessing readback",
+            output.size() + histogram.size(),
+            wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        );
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("preprocessing"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                binding(0, &self.params),
+                binding(1, &raw),
+                binding(2, &self.dark),
+                binding(3, &self.gain),
+                binding(4, &output),
+                binding(5, &histogram),
+            ],
+        });
+
+        FrameBuffers {
+            width,
+            height,
+            raw,
+            output,
+            histogram,
+            readback,
+            bind_group,
+        }
+    }
+}
+
+impl FramePreprocessor for GpuPreprocessor {
+    fn name(&self) -> &'static str {
+        "gpu"
+    }
+
+    fn process(&mut self, frame: &Frame) -> Result<ProcessedFrame, DaqError> {
+        let pixels = frame.width as usize * frame.height as usize;
+        self.calibration.check_len(pixels)?;
+        let packed = pack_pixels(frame, pixels)?;
+
+        let out_width = frame.width / self.binning;
+        let out_height = frame.height / self.binning;
+        let max_value = max_pixel_value(frame.bit_depth);
+        let histogram_bins = self.histogram_bins;
+        let mut flags = 0;
+        if !self.calibration.dark.is_empty() {
+            flags |= FLAG_DARK;
+        }
+        if !self.calibration.gain.is_empty() {
+            flags |= FLAG_FLAT;
+        }
+        let params = [
+            frame.width,
+            frame.height,
+            out_width,
+            out_height,
+            self.binning,
+            max_value,
+            histogram_bins.unwrap_or(0),
+            flags,
+        ];
+        self.queue
+            .write_buffer(&self.params, 0, bytemuck::cast_slice(&params));
+
+        self.ensure_buffers(frame.width, frame.height);
+        let buffers = self.buffers.as_ref().expect("buffers allocated above");
+        self.queue
+            .write_buffer(&buffers.raw, 0, bytemuck::cast_slice(&packed));
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("preprocessing"),
+            });
+        encoder.clear_buffer(&buffers.histogram, 0, None);
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("preprocessing"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &buffers.bind_group, &[]);
+            pass.dispatch_workgroups(
+                out_width.div_ceil(WORKGROUP_SIZE),
+                out_height.div_ceil(WORKGROUP_SIZE),
+                1,
+            );
+        }
+        let output_size = buffers.output.size();
+        encoder.copy_buffer_to_buffer(&buffers.output, 0, &buffers.readback, 0, output_size);
+        encoder.copy_buffer_to_buffer(
+            &buffers.histogram,
+            0,
+            &buffers.readback,
+            output_size,
+            buffers.histogram.size(),
+        );
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = buffers.readback.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        self.device
+            .poll(wgpu::PollType::wait_indefinitely())
+            .map_err(|e| DaqError::Processing(format!("GPU poll failed: {}", e)))?;
+        rx.recv()
+            .map_err(|_| DaqError::Processing("GPU readback was cancelled".to_string()))?
+            .map_err(|e| DaqError::Processing(format!("GPU readback failed: {}", e)))?;
+
+        let (output, histogram) = {
+            let mapped = slice.get_mapped_range();
+            let words: &[u32] = bytemuck::cast_slice(&mapped);
+            let out_pixels = out_width as usize * out_height as usize;
+            let output: Vec<u16> = words[..out_pixels].iter().map(|&v| v as u16).collect();
+            let offset = output_size as usize / 4;
+            let histogram = histogram_bins.map(|bins| {
+                words[offset..offset + bins as usize]
+                    .iter()
+                    .map(|&count| u64::from(count))
+                    .collect::<Vec<_>>()
+            });
+            (output, histogram)
+        };
+        buffers.readback.unmap();
+
+        let mut processed = Frame::from_u16(out_width, out_height, &output)
+            .with_frame_number(frame.frame_number)
+            .with_timestamp(frame.timestamp_ns)
+            .with_roi_offset(frame.roi_x, frame.roi_y);
+        if frame.bit_depth > 8 {
+            processed.bit_depth = frame.bit_depth;
+        }
+        processed.exposure_ms = frame.exposure_ms;
+        processed.metadata.clone_from(&frame.metadata);
+
+        Ok(ProcessedFrame {
+            frame: processed,
+            histogram,
+        })
+    }
+}
+
+/// Pack frame pixels two per u32 word (low half first), widening 8-bit input
+fn pack_pixels(frame: &Frame, pixels: usize) -> Result<Vec<u32>, DaqError> {
+    let bytes_per_pixel = if frame.bit_depth > 8 { 2 } else { 1 };
+    if frame.data.len() < pixels * bytes_per_pixel {
+        return Err(DaqError::Processing(format!(
+            "Frame has {} bytes, expected {} for {}x{} {}-bit",
+            frame.data.len(),
+            pixels * bytes_per_pixel,
+            frame.width,
+            frame.height,
+            if bytes_per_pixel == 2 { 16 } else { 8 }
+        )));
+    }
+    let pixel = |i: usize| -> u32 {
+        if i >= pixels {
+            0
+        } else if bytes_per_pixel == 2 {
+            u32::from(u16::from_le_bytes([
+                frame.data[2 * i],
+                frame.data[2 * i + 1],
+            ]))
+        } else {
+            u32::from(frame.data[i])
+        }
+    };
+    Ok((0..pixels.div_ceil(2))
+        .map(|word| pixel(2 * word) | (pixel(2 * word + 1) << 16))
+        .collect())
+}
+
+/// Upload a calibration array (a placeholder word when empty)
+fn calibration_buffer(device: &wgpu::Device, label: &str, values: &[f32]) -> wgpu::Buffer {
+    let placeholder = [0.0f32];
+    let contents = if values.is_empty() {
+        &placeholder[..]
+    } else {
+        values
+    };
+    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some(label),
+        contents: bytemuck::cast_slice(contents),
+        usage: wgpu::BufferUsages::STORAGE,
+    })
+}
+
+fn binding(index: u32, buffer: &wgpu::Buffer) -> wgpu::BindGroupEntry<'_> {
+    wgpu::BindGroupEntry {
+        binding: index,
+        resource: buffer.as_entire_binding(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preprocessing::CpuPreprocessor;
+
+    #[test]
+    fn test_matches_cpu_backend() {
+        let config = PreprocessingConfig {
+            binning: 2,
+            histogram_bins: Some(16),
+            ..Default::default()
+        };
+        let dark: Vec<u16> = (0..64).map(|i| 100 + i % 7).collect();
+        let flat: Vec<u16> = (0..64).map(|i| 2000 + (i * 37) % 500).collect();
+        let calibration = Calibration::from_frames(Some(&dark), Some(&flat)).unwrap();
+
+        // Skip on machines without a usable adapter
+        let Ok(mut gpu) = GpuPreprocessor::new(&config, calibration.clone()) else {
+            return;
+        };
+        let mut cpu = CpuPreprocessor::new(&config, calibration);
+
+        let pixels: Vec<u16> = (0..64).map(|i| (i * 997 % 65_000) as u16).collect();
+        let frame = Frame::from_u16(8, 8, &pixels);
+        let expected = cpu.process(&frame).unwrap();
+        let actual = gpu.process(&frame).unwrap();
+        assert_eq!(actual.frame.data, expected.frame.data);
+        assert_eq!(actual.histogram, expected.histogram);
+    }
+}
@@ -0,0 +1,354 @@
+//! Camera frame preprocessing (dark/flat correction, binning, histogram).
+//!
+//! Corrections run between the camera and the rest of the pipeline, so
+//! storage and streaming see corrected frames. Two backends implement the
+//! same arithmetic:
+//!
+//! - [`CpuPreprocessor`] - always available
+//! - `GpuPreprocessor` - wgpu compute shader, behind the `gpu_preprocessing`
+//!   feature, for 16-bit 2k×2k frames at frame rates the CPU path can't sustain
+//!
+//! [`Preprocessor`] picks a backend from [`PreprocessingConfig::backend`] and
+//! falls back to the CPU when no GPU is available or the GPU fails at runtime.
+//!
+//! # Arithmetic
+//!
+//! For each input pixel: `max(raw - dark, 0) * gain`, where
+//! `gain = mean(flat - dark) / (flat - dark)`. Corrected pixels are averaged
+//! over `binning × binning` blocks (edge remainders are dropped) and clamped
+//! to the input bit depth. The histogram counts output pixels in equal-width
+//! bins over the full bit-depth range.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [preprocessing.prime_bsi]
+//! backend = "auto"          # "auto", "cpu" or "gpu"
+//! dark_frame = "calibration/dark.raw"
+//! flat_frame = "calibration/flat.raw"
+//! binning = 2
+//! histogram_bins = 256
+//! ```
+//!
+//! Calibration frames are raw little-endian `u16` files with the same
+//! dimensions as the camera frames.
+
+mod cpu;
+#[cfg(feature = "gpu_preprocessing")]
+mod gpu;
+
+pub use cpu::CpuPreprocessor;
+#[cfg(feature = "gpu_preprocessing")]
+pub use gpu::GpuPreprocessor;
+
+use crate::data::Frame;
+use crate::error::DaqError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Backend selection for frame preprocessing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreprocessingBackend {
+    /// GPU when compiled in and an adapter is found, otherwise CPU
+    #[default]
+    Auto,
+    Cpu,
+    /// GPU requested; still falls back to CPU (with a warning) if unavailable
+    Gpu,
+}
+
+/// Per-camera preprocessing settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreprocessingConfig {
+    #[serde(default)]
+    pub backend: PreprocessingBackend,
+    /// Raw little-endian u16 dark frame
+    #[serde(default)]
+    pub dark_frame: Option<PathBuf>,
+    /// Raw little-endian u16 flat frame
+    #[serde(default)]
+    pub flat_frame: Option<PathBuf>,
+    /// Software binning factor (1 = none)
+    #[serde(default = "default_binning")]
+    pub binning: u32,
+    /// Histogram bin count; `None` disables the histogram
+    #[serde(default)]
+    pub histogram_bins: Option<u32>,
+}
+
+fn default_binning() -> u32 {
+    1
+}
+
+impl Default for PreprocessingConfig {
+    fn default() -> Self {
+        Self {
+            backend: PreprocessingBackend::Auto,
+            dark_frame: None,
+            flat_frame: None,
+            binning: 1,
+            histogram_bins: None,
+        }
+    }
+}
+
+impl PreprocessingConfig {
+    /// Whether any stage does work (otherwise frames can pass through untouched)
+    pub fn is_active(&self) -> bool {
+        self.dark_frame.is_some()
+            || self.flat_frame.is_some()
+            || self.binning > 1
+            || self.histogram_bins.is_some()
+    }
+}
+
+/// Dark offset and flat gain per pixel, derived from calibration frames
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Calibration {
+    /// Dark offset per pixel (empty = no dark correction)
+    pub dark: Vec<f32>,
+    /// Flat-field gain per pixel (empty = no flat correction)
+    pub gain: Vec<f32>,
+}
+
+impl Calibration {
+    /// Build from dark and flat frames (either may be absent)
+    ///
+    /// Fails if both are present with different lengths.
+    pub fn from_frames(dark: Option<&[u16]>, flat: Option<&[u16]>) -> Result<Self, DaqError> {
+        if let (Some(dark), Some(flat)) = (dark, flat) {
+            if dark.len() != flat.len() {
+                return Err(DaqError::Configuration(format!(
+                    "Dark frame has {} pixels but flat frame has {}",
+                    dark.len(),
+                    flat.len()
+                )));
+            }
+        }
+        let dark: Vec<f32> = dark
+            .map(|d| d.iter().map(|&v| f32::from(v)).collect())
+            .unwrap_or_default();
+        let gain = match flat {
+            Some(flat) => {
+                let signal: Vec<f32> = flat
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &v)| f32::from(v) - dark.get(i).copied().unwrap_or(0.0))
+                    .collect();
+                let mean =
+                    signal.iter().map(|&v| f64::from(v)).sum::<f64>() / signal.len().max(1) as f64;
+                signal
+                    .iter()
+                    .map(|&v| {
+                        if v > 0.0 {
+                            (mean / f64::from(v)) as f32
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+        Ok(Self { dark, gain })
+    }
+
+    /// Load the calibration frames named in `config`
+    pub fn load(config: &PreprocessingConfig) -> Result<Self, DaqError> {
+        let dark = config.dark_frame.as_deref().map(read_raw_u16).transpose()?;
+        let flat = config.flat_frame.as_deref().map(read_raw_u16).transpose()?;
+        Self::from_frames(dark.as_deref(), flat.as_deref())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dark.is_empty() && self.gain.is_empty()
+    }
+
+    /// Check the calibration covers a frame of `pixels` pixels
+    pub fn check_len(&self, pixels: usize) -> Result<(), DaqError> {
+        for (name, len) in [("dark", self.dark.len()), ("flat", self.gain.len())] {
+            if len != 0 && len != pixels {
+                return Err(DaqError::Configuration(format!(
+                    "{} calibration has {} pixels but frame has {}",
+                    name, len, pixels
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn read_raw_u16(path: &Path) -> Result<Vec<u16>, DaqError> {
+    let bytes = std::fs::read(path).map_err(|e| {
+        DaqError::Configuration(format!(
+            "Failed to read calibration frame {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    if bytes.len() % 2 != 0 {
+        return Err(DaqError::Configuration(format!(
+            "Calibration frame {} has an odd byte count",
+            path.display()
+        )));
+    }
+    Ok(bytes
+        .chunks_exact(2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .collect())
+}
+
+/// Output of a preprocessing stage
+#[derive(Debug, Clone)]
+pub struct ProcessedFrame {
+    /// Corrected (and binned) frame in 16-bit pixel storage
+    pub frame: Frame,
+    /// Histogram of output pixel values, when enabled
+    pub histogram: Option<Vec<u64>>,
+}
+
+/// A frame preprocessing backend
+pub trait FramePreprocessor: Send {
+    /// Backend name for logging ("cpu", "gpu")
+    fn name(&self) -> &'static str;
+
+    /// Correct, bin and histogram one frame
+    fn process(&mut self, frame: &Frame) -> Result<ProcessedFrame, DaqError>;
+}
+
+/// Preprocessor with automatic CPU fallback
+///
+/// Uses the GPU backend when configured and available. If the GPU fails on a
+/// frame, that frame and all later ones are processed on the CPU.
+pub struct Preprocessor {
+    gpu: Option<Box<dyn FramePreprocessor>>,
+    cpu: CpuPreprocessor,
+}
+
+impl Preprocessor {
+    /// Create a preprocessor, loading calibration frames from `config`
+    pub fn new(config: &PreprocessingConfig) -> Result<Self, DaqError> {
+        let calibration = Calibration::load(config)?;
+        Ok(Self::with_calibration(config, calibration))
+    }
+
+    /// Create a preprocessor with already-loaded calibration
+    pub fn with_calibration(config: &PreprocessingConfig, calibration: Calibration) -> Self {
+        let gpu = match config.backend {
+            PreprocessingBackend::Cpu => None,
+            PreprocessingBackend::Auto | PreprocessingBackend::Gpu => {
+                Self::create_gpu(config, &calibration)
+            }
+        };
+        Self {
+            gpu,
+            cpu: CpuPreprocessor::new(config, calibration),
+        }
+    }
+
+    #[cfg(feature = "gpu_preprocessing")]
+    fn create_gpu(
+        config: &PreprocessingConfig,
+        calibration: &Calibration,
+    ) -> Option<Box<dyn FramePreprocessor>> {
+        match GpuPreprocessor::new(config, calibration.clone()) {
+            Ok(gpu) => {
+                tracing::info!(adapter = %gpu.adapter_name(), "Using GPU frame preprocessing");
+                Some(Box::new(gpu))
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "GPU preprocessing unavailable, using CPU");
+                None
+            }
+        }
+    }
+
+    #[cfg(not(feature = "gpu_preprocessing"))]
+    fn create_gpu(
+        config: &PreprocessingConfig,
+        _calibration: &Calibration,
+    ) -> Option<Box<dyn FramePreprocessor>> {
+        if config.backend == PreprocessingBackend::Gpu {
+            tracing::warn!(
+                "GPU preprocessing requested but the gpu_preprocessing feature is not enabled, using CPU"
+            );
+        }
+        None
+    }
+
+    /// Name of the backend currently in use
+    pub fn backend_name(&self) -> &'static str {
+        self.gpu.as_ref().map_or(self.cpu.name(), |gpu| gpu.name())
+    }
+
+    /// Process a frame on the active backend
+    pub fn process(&mut self, frame: &Frame) -> Result<ProcessedFrame, DaqError> {
+        if let Some(gpu) = self.gpu.as_mut() {
+            match gpu.process(frame) {
+                Ok(processed) => return Ok(processed),
+                Err(e) => {
+                    tracing::warn!(error = %e, "GPU preprocessing failed, falling back to CPU");
+                    self.gpu = None;
+                }
+            }
+        }
+        self.cpu.process(frame)
+    }
+}
+
+/// Maximum pixel value for a bit depth (16-bit for anything above 8)
+pub(crate) fn max_pixel_value(bit_depth: u32) -> u32 {
+    match bit_depth {
+        1..=16 => (1u32 << bit_depth) - 1,
+        _ => u32::from(u16::MAX),
+    }
+}
+
+/// Histogram bin for `value`
+pub(crate) fn histogram_bin(value: u32, bins: u32, max_value: u32) -> usize {
+    ((u64::from(value) * u64::from(bins)) / (u64::from(max_value) + 1)) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calibration_flat_gain_normalizes_to_mean() {
+        let calibration =
+            Calibration::from_frames(Some(&[100, 100, 100, 100]), Some(&[300, 500, 300, 500]))
+                .unwrap();
+        // flat - dark = [200, 400, 200, 400], mean 300
+        assert_eq!(calibration.gain, vec![1.5, 0.75, 1.5, 0.75]);
+        assert!(calibration.check_len(4).is_ok());
+        assert!(calibration.check_len(8).is_err());
+
+        assert!(Calibration::from_frames(Some(&[1, 2]), Some(&[1])).is_err());
+    }
+
+    #[test]
+    fn test_cpu_backend_when_requested() {
+        let config = PreprocessingConfig {
+            backend: PreprocessingBackend::Cpu,
+            binning: 2,
+            ..Default::default()
+        };
+        let mut preprocessor = Preprocessor::with_calibration(&config, Calibration::default());
+        assert_eq!(preprocessor.backend_name(), "cpu");
+
+        let frame = Frame::from_u16(2, 2, &[10, 20, 30, 40]);
+        let processed = preprocessor.process(&frame).unwrap();
+        assert_eq!((processed.frame.width, processed.frame.height), (1, 1));
+        assert_eq!(processed.frame.as_u16_slice().unwrap(), &[25]);
+    }
+
+    #[test]
+    fn test_config_from_toml() {
+        let config: PreprocessingConfig =
+            toml::from_str("backend = \"gpu\"\nbinning = 2\nhistogram_bins = 64").unwrap();
+        assert_eq!(config.backend, PreprocessingBackend::Gpu);
+        assert!(config.is_active());
+        assert!(!PreprocessingConfig::default().is_active());
+    }
+}
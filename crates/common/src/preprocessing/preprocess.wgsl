@@ -0,0 +1,62 @@
+// Dark/flat correction, binning and histogram for 16-bit frames.
+//
+// Input pixels are packed two per u32 (little-endian u16 pairs). One
+// invocation computes one output (binned) pixel. Arithmetic matches the CPU
+// backend in cpu.rs.
+
+struct Params {
+    width: u32,
+    height: u32,
+    out_width: u32,
+    out_height: u32,
+    binning: u32,
+    max_value: u32,
+    histogram_bins: u32,
+    // Bit 0: dark correction, bit 1: flat correction
+    flags: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> raw: array<u32>;
+@group(0) @binding(2) var<storage, read> dark: array<f32>;
+@group(0) @binding(3) var<storage, read> gain: array<f32>;
+@group(0) @binding(4) var<storage, read_write> output: array<u32>;
+@group(0) @binding(5) var<storage, read_write> histogram: array<atomic<u32>>;
+
+fn raw_pixel(i: u32) -> f32 {
+    let word = raw[i / 2u];
+    let value = select(word & 0xffffu, word >> 16u, (i & 1u) == 1u);
+    return f32(value);
+}
+
+@compute @workgroup_size(16, 16, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.out_width || id.y >= params.out_height) {
+        return;
+    }
+
+    var sum = 0.0;
+    for (var dy = 0u; dy < params.binning; dy = dy + 1u) {
+        for (var dx = 0u; dx < params.binning; dx = dx + 1u) {
+            let i = (id.y * params.binning + dy) * params.width + id.x * params.binning + dx;
+            var value = raw_pixel(i);
+            if ((params.flags & 1u) != 0u) {
+                value = max(value - dark[i], 0.0);
+            }
+            if ((params.flags & 2u) != 0u) {
+                value = value * gain[i];
+            }
+            sum = sum + value;
+        }
+    }
+
+    let block = f32(params.binning * params.binning);
+    // floor(x + 0.5) rather than round(): matches f32::round for x >= 0
+    let value = u32(clamp(floor(sum / block + 0.5), 0.0, f32(params.max_value)));
+    output[id.y * params.out_width + id.x] = value;
+
+    if (params.histogram_bins > 0u) {
+        let bin = (value * params.histogram_bins) / (params.max_value + 1u);
+        atomicAdd(&histogram[bin], 1u);
+    }
+}
@@ -0,0 +1,414 @@
+//! Signed data provenance.
+//!
+//! When a run file is complete, the storage layer hashes every dataset (and
+//! the attributes of the metadata groups) into a [`ProvenanceManifest`]. The
+//! daemon signs the manifest's exact JSON bytes with its Ed25519 key, much
+//! like DKIM signs mail headers, and the resulting [`SignedManifest`] is
+//! embedded in the file and copied to the run catalog.
+//!
+//! Verifying a file recomputes the digests and checks them and the signature
+//! against the embedded manifest. Anyone holding the daemon's public key can
+//! tell whether a file was modified after acquisition, or re-signed with a
+//! different key.
+
+use crate::experiment::document::now_ns;
+use anyhow::{anyhow, bail, Context, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+
+pub use ed25519_dalek::VerifyingKey;
+
+/// HDF5 group holding the signed manifest of a run file
+pub const PROVENANCE_GROUP: &str = "provenance";
+
+/// Digest algorithm used for dataset hashes
+pub const DIGEST_ALGORITHM: &str = "sha256";
+
+/// Signature algorithm of [`SignedManifest`]
+pub const SIGNATURE_ALGORITHM: &str = "ed25519";
+
+/// Content digests of one run file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProvenanceManifest {
+    pub run_uid: String,
+    /// File name (without directory) the digests were taken from
+    pub file_name: String,
+    /// Unix timestamp of signing in nanoseconds
+    pub signed_at_ns: u64,
+    pub digest_algorithm: String,
+    /// Hex digest per dataset path (see [`dataset_digest`], [`attributes_digest`])
+    pub datasets: BTreeMap<String, String>,
+}
+
+impl ProvenanceManifest {
+    pub fn new(run_uid: &str, file_name: &str, datasets: BTreeMap<String, String>) -> Self {
+        Self {
+            run_uid: run_uid.to_string(),
+            file_name: file_name.to_string(),
+            signed_at_ns: now_ns(),
+            digest_algorithm: DIGEST_ALGORITHM.to_string(),
+            datasets,
+        }
+    }
+}
+
+/// A manifest with the daemon's signature over its exact JSON bytes
+///
+/// The manifest is kept as the signed string rather than re-serialized, so
+/// verification never depends on serializer output staying byte-identical.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedManifest {
+    /// JSON of the [`ProvenanceManifest`], exactly as signed
+    pub manifest: String,
+    pub algorithm: String,
+    /// Hex-encoded public key of the signer
+    pub public_key: String,
+    /// Short fingerprint of `public_key` (see [`key_id`])
+    pub key_id: String,
+    /// Hex-encoded signature
+    pub signature: String,
+}
+
+impl SignedManifest {
+    /// Parse the signed manifest JSON
+    pub fn manifest(&self) -> Result<ProvenanceManifest> {
+        serde_json::from_str(&self.manifest).context("Malformed provenance manifest")
+    }
+
+    /// Whether the signature matches the embedded public key
+    pub fn signature_valid(&self) -> bool {
+        self.algorithm == SIGNATURE_ALGORITHM
+            && self.check_signature().is_ok()
+            && parse_public_key(&self.public_key).is_ok_and(|key| key_id(&key) == self.key_id)
+    }
+
+    fn check_signature(&self) -> Result<()> {
+        let key = parse_public_key(&self.public_key)?;
+        let bytes: [u8; 64] = hex::decode(&self.signature)?
+            .try_into()
+            .map_err(|_| anyhow!("Signature must be 64 bytes"))?;
+        key.verify(self.manifest.as_bytes(), &Signature::from_bytes(&bytes))?;
+        Ok(())
+    }
+}
+
+/// Signs run manifests with the daemon's key
+pub struct RunSigner {
+    key: SigningKey,
+}
+
+impl fmt::Debug for RunSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the secret key
+        f.debug_struct("RunSigner")
+            .field("key_id", &self.key_id())
+            .finish()
+    }
+}
+
+impl RunSigner {
+    /// Signer with a fresh random key
+    pub fn generate() -> Result<Self> {
+        let mut seed = [0u8; 32];
+        getrandom::getrandom(&mut seed).map_err(|e| anyhow!("No system randomness: {}", e))?;
+        Ok(Self::from_seed(&seed))
+    }
+
+    pub fn from_seed(seed: &[u8; 32]) -> Self {
+        Self {
+            key: SigningKey::from_bytes(seed),
+        }
+    }
+
+    /// Load the key stored at `path`, creating it on first use
+    ///
+    /// The file holds the hex-encoded secret seed and is created readable
+    /// only by the owner. The public key is written next to it as
+    /// `<path>.pub` for distribution to verifiers.
+    pub fn from_key_file(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => {
+                let seed: [u8; 32] = hex::decode(text.trim())
+                    .ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(|| {
+                        anyhow!("{} is not a 32-byte hex signing key", path.display())
+                    })?;
+                Ok(Self::from_seed(&seed))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let signer = Self::generate()?;
+                signer
+                    .save(path)
+                    .with_context(|| format!("Failed to save signing key {}", path.display()))?;
+                tracing::info!(path = %path.display(), key_id = %signer.key_id(), "Created run signing key");
+                Ok(signer)
+            }
+            Err(e) => {
+                Err(e).with_context(|| format!("Failed to read signing key {}", path.display()))
+            }
+        }
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(path)?;
+        writeln!(file, "{}", hex::encode(self.key.to_bytes()))?;
+
+        let mut public = path.as_os_str().to_owned();
+        public.push(".pub");
+        std::fs::write(public, format!("{}\n", self.public_key_hex()))?;
+        Ok(())
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.key.verifying_key()
+    }
+
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(self.verifying_key().as_bytes())
+    }
+
+    pub fn key_id(&self) -> String {
+        key_id(&self.verifying_key())
+    }
+
+    /// Sign a manifest
+    pub fn sign(&self, manifest: &ProvenanceManifest) -> Result<SignedManifest> {
+        let manifest = serde_json::to_string(manifest)?;
+        let signature = self.key.sign(manifest.as_bytes());
+        Ok(SignedManifest {
+            manifest,
+            algorithm: SIGNATURE_ALGORITHM.to_string(),
+            public_key: self.public_key_hex(),
+            key_id: self.key_id(),
+            signature: hex::encode(signature.to_bytes()),
+        })
+    }
+}
+
+/// Short fingerprint of a public key: the first 8 bytes of its SHA-256, hex
+pub fn key_id(key: &VerifyingKey) -> String {
+    hex::encode(&Sha256::digest(key.as_bytes())[..8])
+}
+
+/// Parse a hex-encoded Ed25519 public key
+pub fn parse_public_key(text: &str) -> Result<VerifyingKey> {
+    let bytes: [u8; 32] = hex::decode(text.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| anyhow!("Public key must be 32 hex-encoded bytes"))?;
+    Ok(VerifyingKey::from_bytes(&bytes)?)
+}
+
+/// Digest of a dataset's element type, shape and raw little-endian data
+pub fn dataset_digest(dtype: &str, shape: &[usize], data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(dtype.as_bytes());
+    for dim in shape {
+        hasher.update((*dim as u64).to_le_bytes());
+    }
+    hasher.update((data.len() as u64).to_le_bytes());
+    hasher.update(data);
+    hex::encode(hasher.finalize())
+}
+
+/// Digest of a group's attributes, independent of their storage order
+pub fn attributes_digest(attributes: &BTreeMap<String, String>) -> String {
+    let mut hasher = Sha256::new();
+    for (name, value) in attributes {
+        // Length prefixes keep ("ab", "c") and ("a", "bc") apart
+        hasher.update((name.len() as u64).to_le_bytes());
+        hasher.update(name.as_bytes());
+        hasher.update((value.len() as u64).to_le_bytes());
+        hasher.update(value.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Outcome of checking a file against its signed manifest
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Verification {
+    pub run_uid: String,
+    pub key_id: String,
+    /// Unix timestamp of signing in nanoseconds
+    pub signed_at_ns: u64,
+    /// The signature matches the manifest and the embedded key
+    pub signature_valid: bool,
+    /// The embedded key is one of the trusted keys
+    pub key_trusted: bool,
+    /// Datasets whose content changed
+    pub modified: Vec<String>,
+    /// Signed datasets no longer in the file
+    pub missing: Vec<String>,
+    /// Datasets added after signing
+    pub unexpected: Vec<String>,
+}
+
+impl Verification {
+    /// Signature is valid and every dataset is unchanged
+    ///
+    /// Says nothing about *who* signed; check [`Self::key_trusted`] as well.
+    pub fn is_intact(&self) -> bool {
+        self.signature_valid
+            && self.modified.is_empty()
+            && self.missing.is_empty()
+            && self.unexpected.is_empty()
+    }
+
+    /// Human-readable list of everything that failed
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if !self.signature_valid {
+            problems.push("signature does not match the manifest".to_string());
+        }
+        if !self.key_trusted {
+            problems.push(format!("signing key {} is not trusted", self.key_id));
+        }
+        let mut list = |label: &str, paths: &[String]| {
+            if !paths.is_empty() {
+                problems.push(format!("{}: {}", label, paths.join(", ")));
+            }
+        };
+        list("modified", &self.modified);
+        list("missing", &self.missing);
+        list("added after signing", &self.unexpected);
+        problems
+    }
+}
+
+/// Check the current digests of a file against its signed manifest
+///
+/// With no `trusted_keys`, any valid signature counts as trusted: the file
+/// is then only checked for consistency with its own manifest.
+pub fn verify(
+    signed: &SignedManifest,
+    current: &BTreeMap<String, String>,
+    trusted_keys: &[VerifyingKey],
+) -> Result<Verification> {
+    let manifest = signed.manifest()?;
+    if manifest.digest_algorithm != DIGEST_ALGORITHM {
+        bail!(
+            "Unsupported digest algorithm '{}'",
+            manifest.digest_algorithm
+        );
+    }
+    let signature_valid = signed.signature_valid();
+    let key_trusted = signature_valid
+        && (trusted_keys.is_empty()
+            || trusted_keys
+                .iter()
+                .any(|key| hex::encode(key.as_bytes()) == signed.public_key.trim()));
+
+    let mut verification = Verification {
+        run_uid: manifest.run_uid,
+        key_id: signed.key_id.clone(),
+        signed_at_ns: manifest.signed_at_ns,
+        signature_valid,
+        key_trusted,
+        ..Default::default()
+    };
+    for (path, digest) in &manifest.datasets {
+        match current.get(path) {
+            Some(actual) if actual == digest => {}
+            Some(_) => verification.modified.push(path.clone()),
+            None => verification.missing.push(path.clone()),
+        }
+    }
+    verification.unexpected = current
+        .keys()
+        .filter(|path| !manifest.datasets.contains_key(*path))
+        .cloned()
+        .collect();
+    Ok(verification)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digests() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            (
+                "/primary/det1".to_string(),
+                dataset_digest("float64", &[2], &[0u8; 16]),
+            ),
+            (
+                "/start".to_string(),
+                attributes_digest(&BTreeMap::from([("uid".to_string(), "run1".to_string())])),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_sign_and_detect_tampering() {
+        let signer = RunSigner::from_seed(&[7; 32]);
+        let signed = signer
+            .sign(&ProvenanceManifest::new("run1", "run1_1000.h5", digests()))
+            .unwrap();
+
+        let ok = verify(&signed, &digests(), &[signer.verifying_key()]).unwrap();
+        assert!(ok.is_intact() && ok.key_trusted, "{:?}", ok.problems());
+        assert_eq!(ok.run_uid, "run1");
+
+        // Changed data, a removed and an added dataset
+        let mut current = digests();
+        current.insert(
+            "/primary/det1".to_string(),
+            dataset_digest("float64", &[2], &[1u8; 16]),
+        );
+        current.remove("/start");
+        current.insert("/primary/extra".to_string(), String::new());
+        let bad = verify(&signed, &current, &[]).unwrap();
+        assert!(!bad.is_intact());
+        assert_eq!(bad.modified, ["/primary/det1"]);
+        assert_eq!(bad.missing, ["/start"]);
+        assert_eq!(bad.unexpected, ["/primary/extra"]);
+
+        // An edited manifest no longer matches its signature
+        let mut forged = signed.clone();
+        forged.manifest = forged.manifest.replace("run1_1000", "run2_1000");
+        assert!(!verify(&forged, &digests(), &[]).unwrap().signature_valid);
+
+        // Re-signing with another key is valid but not trusted
+        let other = RunSigner::from_seed(&[8; 32]);
+        let resigned = other.sign(&signed.manifest().unwrap()).unwrap();
+        let check = verify(&resigned, &digests(), &[signer.verifying_key()]).unwrap();
+        assert!(check.is_intact());
+        assert!(!check.key_trusted);
+    }
+
+    #[test]
+    fn test_key_file_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys").join("run_signing.key");
+
+        let created = RunSigner::from_key_file(&path).unwrap();
+        let loaded = RunSigner::from_key_file(&path).unwrap();
+        assert_eq!(created.public_key_hex(), loaded.public_key_hex());
+
+        let public = std::fs::read_to_string(dir.path().join("keys/run_signing.key.pub")).unwrap();
+        assert_eq!(
+            key_id(&parse_public_key(&public).unwrap()),
+            created.key_id()
+        );
+        assert!(!format!("{:?}", created).contains(&hex::encode(created.key.to_bytes())));
+
+        std::fs::write(&path, "not a key").unwrap();
+        assert!(RunSigner::from_key_file(&path).is_err());
+    }
+}
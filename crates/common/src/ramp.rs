@@ -0,0 +1,684 @@
+//! Gradual setpoint changes (ramps) for settable parameters and Movables.
+//!
+//! Stepping a laser's power or a temperature controller's setpoint in one go
+//! trips interlocks and stresses hardware. A ramp instead walks the output
+//! from its current value to a target over a duration (or at a rate), along
+//! an easing [`RampProfile`], writing an intermediate setpoint every
+//! `step_interval_ms`.
+//!
+//! The [`RampManager`] runs ramps in the background, one per output at a
+//! time, publishes a [`RampStatus`] after every step and can abort a ramp,
+//! which leaves the output at the last setpoint written. Plans ramp through
+//! the RunEngine and clients through the hardware gRPC service; both end up
+//! in the device registry's manager.
+//!
+//! Output limits still apply to every setpoint written.
+//!
+//! ```toml
+//! # e.g. a plan step or a gRPC request
+//! target = 1.5
+//! duration_s = 30.0     # or: rate = 0.05 (units per second)
+//! profile = "ease_in_out"
+//! step_interval_ms = 100
+//! ```
+
+use crate::capabilities::{Movable, Settable};
+use crate::experiment::document::now_ns;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+
+/// Parameter name under which a Movable's position is ramped
+pub const POSITION_PARAMETER: &str = crate::output_limits::POSITION_PARAMETER;
+
+/// Default time between intermediate setpoints
+pub const DEFAULT_STEP_INTERVAL_MS: u64 = 100;
+
+/// Shortest time between intermediate setpoints
+pub const MIN_STEP_INTERVAL_MS: u64 = 10;
+
+/// How the setpoint moves between start and target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RampProfile {
+    /// Constant rate
+    #[default]
+    Linear,
+    /// Starts slowly, ends fast
+    EaseIn,
+    /// Starts fast, ends slowly
+    EaseOut,
+    /// Slow at both ends
+    EaseInOut,
+}
+
+impl RampProfile {
+    pub const ALL: [RampProfile; 4] = [Self::Linear, Self::EaseIn, Self::EaseOut, Self::EaseInOut];
+
+    /// Fraction of the distance covered at time fraction `t` (both 0-1)
+    pub fn shape(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t,
+            Self::EaseOut => t * (2.0 - t),
+            Self::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::EaseIn => "ease_in",
+            Self::EaseOut => "ease_out",
+            Self::EaseInOut => "ease_in_out",
+        }
+    }
+}
+
+impl fmt::Display for RampProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RampProfile {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|profile| profile.as_str() == s)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unknown ramp profile '{}' (expected linear, ease_in, ease_out or ease_in_out)",
+                    s
+                )
+            })
+    }
+}
+
+/// Where a ramp goes and how fast
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RampSpec {
+    pub target: f64,
+    /// Total ramp time; exclusive with `rate`
+    #[serde(default)]
+    pub duration_s: Option<f64>,
+    /// Average change per second; exclusive with `duration_s`
+    #[serde(default)]
+    pub rate: Option<f64>,
+    #[serde(default)]
+    pub profile: RampProfile,
+    #[serde(default = "default_step_interval_ms")]
+    pub step_interval_ms: u64,
+}
+
+fn default_step_interval_ms() -> u64 {
+    DEFAULT_STEP_INTERVAL_MS
+}
+
+impl RampSpec {
+    /// Ramp to `target` over `duration`
+    pub fn over(target: f64, duration: Duration) -> Self {
+        Self {
+            target,
+            duration_s: Some(duration.as_secs_f64()),
+            rate: None,
+            profile: RampProfile::Linear,
+            step_interval_ms: DEFAULT_STEP_INTERVAL_MS,
+        }
+    }
+
+    /// Ramp to `target` at `rate` units per second
+    pub fn at_rate(target: f64, rate: f64) -> Self {
+        Self {
+            target,
+            duration_s: None,
+            rate: Some(rate),
+            profile: RampProfile::Linear,
+            step_interval_ms: DEFAULT_STEP_INTERVAL_MS,
+        }
+    }
+
+    pub fn with_profile(mut self, profile: RampProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    pub fn with_step_interval(mut self, interval: Duration) -> Self {
+        self.step_interval_ms = interval.as_millis() as u64;
+        self
+    }
+
+    /// Check that the ramp can be run
+    pub fn validate(&self) -> Result<()> {
+        if !self.target.is_finite() {
+            bail!("Ramp target must be finite, got {}", self.target);
+        }
+        match (self.duration_s, self.rate) {
+            (Some(_), Some(_)) => bail!("Give either a ramp duration or a rate, not both"),
+            (None, None) => bail!("A ramp needs a duration or a rate"),
+            (Some(duration), None) if !duration.is_finite() || duration < 0.0 => {
+                bail!("Ramp duration must be zero or positive, got {}", duration)
+            }
+            (None, Some(rate)) if !rate.is_finite() || rate <= 0.0 => {
+                bail!("Ramp rate must be positive, got {}", rate)
+            }
+            _ => {}
+        }
+        if self.step_interval_ms < MIN_STEP_INTERVAL_MS {
+            bail!(
+                "Ramp step interval must be at least {} ms, got {}",
+                MIN_STEP_INTERVAL_MS,
+                self.step_interval_ms
+            );
+        }
+        Ok(())
+    }
+
+    /// How long the ramp from `from` takes
+    pub fn duration_from(&self, from: f64) -> Duration {
+        let seconds = match (self.duration_s, self.rate) {
+            (Some(duration), _) => duration,
+            (None, Some(rate)) if rate > 0.0 => (self.target - from).abs() / rate,
+            _ => 0.0,
+        };
+        Duration::try_from_secs_f64(seconds).unwrap_or_default()
+    }
+
+    /// Setpoints of the ramp from `from`, with their offsets from the start
+    ///
+    /// The last setpoint is always the target itself.
+    pub fn setpoints(&self, from: f64) -> Vec<(Duration, f64)> {
+        let total = self.duration_from(from);
+        let interval = Duration::from_millis(self.step_interval_ms.max(MIN_STEP_INTERVAL_MS));
+        let steps = (total.as_secs_f64() / interval.as_secs_f64())
+            .ceil()
+            .max(1.0) as u32;
+        (1..=steps)
+            .map(|i| {
+                let t = f64::from(i) / f64::from(steps);
+                let value = if i == steps {
+                    self.target
+                } else {
+                    from + (self.target - from) * self.profile.shape(t)
+                };
+                (total.mul_f64(t), value)
+            })
+            .collect()
+    }
+}
+
+/// Where a ramp is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RampState {
+    Running,
+    /// Reached the target
+    Completed,
+    /// Stopped on request at the last setpoint written
+    Aborted,
+    /// A setpoint could not be written
+    Failed,
+}
+
+impl RampState {
+    pub fn is_finished(&self) -> bool {
+        *self != Self::Running
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Completed => "completed",
+            Self::Aborted => "aborted",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// Progress of one ramp, published after every step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RampStatus {
+    pub id: u64,
+    pub device_id: String,
+    pub parameter: String,
+    pub start: f64,
+    pub target: f64,
+    /// Last setpoint written
+    pub value: f64,
+    /// Fraction of the ramp time elapsed (0-1)
+    pub progress: f64,
+    pub state: RampState,
+    /// Why the ramp failed
+    pub error: Option<String>,
+    /// Start time (UNIX nanoseconds)
+    pub started_ns: u64,
+    pub duration: Duration,
+}
+
+/// The output a ramp writes to
+#[derive(Clone)]
+pub enum RampOutput {
+    /// A numeric parameter of a Settable device
+    Parameter {
+        device: Arc<dyn Settable>,
+        parameter: String,
+    },
+    /// A Movable's position
+    Position(Arc<dyn Movable>),
+}
+
+impl RampOutput {
+    /// Current value of the output
+    pub async fn read(&self) -> Result<f64> {
+        match self {
+            Self::Parameter { device, parameter } => {
+                let value = device.get_value(parameter).await?;
+                value.as_f64().ok_or_else(|| {
+                    anyhow::anyhow!("Parameter '{}' is not numeric ({})", parameter, value)
+                })
+            }
+            Self::Position(movable) => movable.position().await,
+        }
+    }
+
+    /// Write one setpoint
+    pub async fn write(&self, value: f64) -> Result<()> {
+        match self {
+            Self::Parameter { device, parameter } => {
+                device.set_value(parameter, serde_json::json!(value)).await
+            }
+            Self::Position(movable) => movable.move_abs(value).await,
+        }
+    }
+}
+
+/// A started ramp
+pub struct RampHandle {
+    pub id: u64,
+    status: watch::Receiver<RampStatus>,
+}
+
+impl RampHandle {
+    /// Latest status
+    pub fn status(&self) -> RampStatus {
+        self.status.borrow().clone()
+    }
+
+    /// Wait until the ramp has finished and return its final status
+    pub async fn wait(&mut self) -> RampStatus {
+        let _ = self
+            .status
+            .wait_for(|status| status.state.is_finished())
+            .await;
+        self.status()
+    }
+}
+
+struct ActiveRamp {
+    status: watch::Receiver<RampStatus>,
+    abort: watch::Sender<bool>,
+}
+
+/// Runs ramps in the background and tracks the ones in progress
+pub struct RampManager {
+    next_id: AtomicU64,
+    active: Arc<Mutex<HashMap<u64, ActiveRamp>>>,
+    events: broadcast::Sender<RampStatus>,
+}
+
+impl Default for RampManager {
+    fn default() -> Self {
+        Self {
+            next_id: AtomicU64::new(1),
+            active: Arc::default(),
+            events: broadcast::channel(256).0,
+        }
+    }
+}
+
+impl RampManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start ramping `parameter` of `device_id` through `output`
+    ///
+    /// Fails if the spec is invalid, the start value cannot be read or the
+    /// output is already ramping. Must be called from within a Tokio runtime.
+    pub async fn start(
+        &self,
+        device_id: &str,
+        parameter: &str,
+        output: RampOutput,
+        spec: RampSpec,
+    ) -> Result<RampHandle> {
+        spec.validate()?;
+        if let Some(running) = self
+            .list()
+            .into_iter()
+            .find(|s| s.device_id == device_id && s.parameter == parameter)
+        {
+            bail!(
+                "{}.{} is already ramping (ramp {})",
+                device_id,
+                parameter,
+                running.id
+            );
+        }
+        let start = output.read().await?;
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let setpoints = spec.setpoints(start);
+        let initial = RampStatus {
+            id,
+            device_id: device_id.to_string(),
+            parameter: parameter.to_string(),
+            start,
+            target: spec.target,
+            value: start,
+            progress: 0.0,
+            state: RampState::Running,
+            error: None,
+            started_ns: now_ns(),
+            duration: spec.duration_from(start),
+        };
+        let (status_tx, status_rx) = watch::channel(initial.clone());
+        let (abort_tx, abort_rx) = watch::channel(false);
+        self.active
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .insert(
+                id,
+                ActiveRamp {
+                    status: status_rx.clone(),
+                    abort: abort_tx,
+                },
+            );
+        let _ = self.events.send(initial.clone());
+        tracing::info!(
+            ramp = id,
+            device_id,
+            parameter,
+            start,
+            target = spec.target,
+            profile = %spec.profile,
+            "Ramp started"
+        );
+
+        tokio::spawn(run_ramp(
+            initial,
+            output,
+            setpoints,
+            abort_rx,
+            status_tx,
+            self.events.clone(),
+            self.active.clone(),
+        ));
+
+        Ok(RampHandle {
+            id,
+            status: status_rx,
+        })
+    }
+
+    /// Stop a running ramp at its last setpoint
+    pub fn abort(&self, id: u64) -> Result<()> {
+        let active = self.active.lock().unwrap_or_else(|p| p.into_inner());
+        match active.get(&id) {
+            Some(ramp) => {
+                let _ = ramp.abort.send(true);
+                Ok(())
+            }
+            None => bail!("No running ramp {}", id),
+        }
+    }
+
+    /// Stop every running ramp of `device_id`; returns how many were stopped
+    pub fn abort_device(&self, device_id: &str) -> usize {
+        let active = self.active.lock().unwrap_or_else(|p| p.into_inner());
+        let mut stopped = 0;
+        for ramp in active.values() {
+            if ramp.status.borrow().device_id == device_id {
+                let _ = ramp.abort.send(true);
+                stopped += 1;
+            }
+        }
+        stopped
+    }
+
+    /// Ramps in progress, oldest first
+    pub fn list(&self) -> Vec<RampStatus> {
+        let active = self.active.lock().unwrap_or_else(|p| p.into_inner());
+        let mut ramps: Vec<_> = active
+            .values()
+            .map(|ramp| ramp.status.borrow().clone())
+            .collect();
+        ramps.sort_by_key(|status| status.id);
+        ramps
+    }
+
+    /// Status of every ramp when it starts, after each step and when it finishes
+    pub fn subscribe(&self) -> broadcast::Receiver<RampStatus> {
+        self.events.subscribe()
+    }
+}
+
+async fn run_ramp(
+    mut status: RampStatus,
+    output: RampOutput,
+    setpoints: Vec<(Duration, f64)>,
+    mut abort: watch::Receiver<bool>,
+    status_tx: watch::Sender<RampStatus>,
+    events: broadcast::Sender<RampStatus>,
+    active: Arc<Mutex<HashMap<u64, ActiveRamp>>>,
+) {
+    let started = tokio::time::Instant::now();
+    let total = status.duration.as_secs_f64();
+
+    for (offset, value) in setpoints {
+        let aborted = tokio::select! {
+            () = tokio::time::sleep_until(started + offset) => false,
+            aborted = abort_requested(&mut abort) => aborted,
+        };
+        if aborted {
+            status.state = RampState::Aborted;
+            break;
+        }
+        if let Err(e) = output.write(value).await {
+            status.state = RampState::Failed;
+            status.error = Some(format!("{:#}", e));
+            break;
+        }
+        status.value = value;
+        status.progress = if total > 0.0 {
+            (offset.as_secs_f64() / total).min(1.0)
+        } else {
+            1.0
+        };
+        let _ = status_tx.send(status.clone());
+        let _ = events.send(status.clone());
+    }
+    if status.state == RampState::Running {
+        status.state = RampState::Completed;
+    }
+
+    match status.state {
+        RampState::Failed => tracing::warn!(
+            ramp = status.id,
+            device_id = %status.device_id,
+            parameter = %status.parameter,
+            value = status.value,
+            error = status.error.as_deref().unwrap_or_default(),
+            "Ramp failed"
+        ),
+        state => tracing::info!(
+            ramp = status.id,
+            device_id = %status.device_id,
+            parameter = %status.parameter,
+            value = status.value,
+            state = state.as_str(),
+            "Ramp finished"
+        ),
+    }
+    active
+        .lock()
+        .unwrap_or_else(|p| p.into_inner())
+        .remove(&status.id);
+    let _ = status_tx.send(status.clone());
+    let _ = events.send(status);
+}
+
+/// Resolves once an abort is requested
+async fn abort_requested(abort: &mut watch::Receiver<bool>) -> bool {
+    if abort.wait_for(|abort| *abort).await.is_ok() {
+        return true;
+    }
+    // The manager is gone; nobody can abort any more
+    std::future::pending().await
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    /// Records every setpoint written
+    #[derive(Default)]
+    struct Recorder {
+        values: Mutex<Vec<f64>>,
+    }
+
+    #[async_trait]
+    impl Settable for Recorder {
+        async fn set_value(&self, _name: &str, value: serde_json::Value) -> Result<()> {
+            self.values.lock().unwrap().push(value.as_f64().unwrap());
+            Ok(())
+        }
+
+        async fn get_value(&self, _name: &str) -> Result<serde_json::Value> {
+            let last = self.values.lock().unwrap().last().copied();
+            Ok(serde_json::json!(last.unwrap_or(0.0)))
+        }
+    }
+
+    fn output(recorder: &Arc<Recorder>) -> RampOutput {
+        RampOutput::Parameter {
+            device: recorder.clone(),
+            parameter: "power".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_setpoints_follow_profile() {
+        let spec = RampSpec::over(10.0, Duration::from_secs(1))
+            .with_step_interval(Duration::from_millis(250));
+        let linear: Vec<_> = spec.setpoints(2.0).into_iter().map(|(_, v)| v).collect();
+        assert_eq!(linear, [4.0, 6.0, 8.0, 10.0]);
+
+        let eased = spec
+            .clone()
+            .with_profile(RampProfile::EaseInOut)
+            .setpoints(2.0);
+        assert_eq!(eased.last(), Some(&(Duration::from_secs(1), 10.0)));
+        // Slower than linear at the start, faster in the middle
+        assert!(eased[0].1 < 4.0);
+        assert_eq!(eased[1].1, 6.0);
+
+        // A rate sets the duration from the distance
+        let by_rate = RampSpec::at_rate(0.0, 4.0).setpoints(2.0);
+        assert_eq!(by_rate.len(), 5);
+        assert_eq!(by_rate.last().unwrap().0, Duration::from_millis(500));
+
+        assert_eq!(RampSpec::over(1.0, Duration::ZERO).setpoints(0.0).len(), 1);
+    }
+
+    #[test]
+    fn test_spec_validation() {
+        assert!(RampSpec::at_rate(1.0, 0.5).validate().is_ok());
+        assert!(RampSpec::at_rate(1.0, 0.0).validate().is_err());
+        assert!(RampSpec::over(f64::NAN, Duration::ZERO).validate().is_err());
+        let both = RampSpec {
+            rate: Some(1.0),
+            ..RampSpec::over(1.0, Duration::from_secs(1))
+        };
+        assert!(both.validate().is_err());
+        let too_fast = RampSpec::over(1.0, Duration::from_secs(1))
+            .with_step_interval(Duration::from_millis(1));
+        assert!(too_fast.validate().is_err());
+
+        let spec: RampSpec =
+            toml::from_str("target = 1.5\nrate = 0.05\nprofile = \"ease_out\"").unwrap();
+        assert_eq!(spec.profile, RampProfile::EaseOut);
+        assert_eq!(spec.step_interval_ms, DEFAULT_STEP_INTERVAL_MS);
+        assert_eq!(
+            "ease_in".parse::<RampProfile>().unwrap(),
+            RampProfile::EaseIn
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ramp_runs_to_target_with_progress() {
+        let recorder = Arc::new(Recorder::default());
+        let manager = RampManager::new();
+        let mut events = manager.subscribe();
+        let spec = RampSpec::over(1.0, Duration::from_millis(400));
+
+        let mut handle = manager
+            .start("laser", "power", output(&recorder), spec.clone())
+            .await
+            .unwrap();
+        assert!(manager
+            .start("laser", "power", output(&recorder), spec)
+            .await
+            .is_err());
+
+        let done = handle.wait().await;
+        assert_eq!(done.state, RampState::Completed);
+        assert_eq!(done.value, 1.0);
+        assert_eq!(recorder.values.lock().unwrap().len(), 4);
+        assert!(manager.list().is_empty());
+
+        let mut progress = Vec::new();
+        while let Ok(status) = events.try_recv() {
+            progress.push(status.progress);
+        }
+        // Start, four steps, finish
+        assert_eq!(progress.len(), 6);
+        assert!(progress.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(progress.last(), Some(&1.0));
+    }
+
+    #[tokio::test]
+    async fn test_abort_stops_at_last_setpoint() {
+        let recorder = Arc::new(Recorder::default());
+        let manager = RampManager::new();
+        let mut handle = manager
+            .start(
+                "laser",
+                "power",
+                output(&recorder),
+                RampSpec::over(10.0, Duration::from_secs(10)),
+            )
+            .await
+            .unwrap();
+
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        assert_eq!(manager.abort_device("laser"), 1);
+        let done = handle.wait().await;
+        assert_eq!(done.state, RampState::Aborted);
+        assert_eq!(recorder.values.lock().unwrap().last(), Some(&done.value));
+        assert!(done.value < 1.0);
+        assert!(manager.abort(done.id).is_err());
+    }
+}
@@ -18,6 +18,8 @@
 //! - [`DynSerial`]: Type-erased boxed serial port
 //! - [`SharedPort`]: Thread-safe shared serial port with buffered reading
 //! - [`SharedPortUnbuffered`]: Thread-safe shared serial port without buffering
+//! - [`SerialBus`]: Shared multidrop bus with transaction scopes and per-device delays
+//! - [`PortResync`]: Drains the reply an interrupted transaction left on the port
 //!
 //! # Utilities
 //!
@@ -38,10 +40,15 @@
 //! let discarded = drain_serial_buffer(guard.get_mut(), 50).await;
 //! ```
 
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, BufReader};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, MutexGuard};
+use tokio::time::Instant;
 
 // =============================================================================
 // Serial Port Trait
@@ -162,6 +169,276 @@ pub fn wrap_shared_unbuffered(port: DynSerial) -> SharedPortUnbuffered {
     Arc::new(Mutex::new(port))
 }
 
+// =============================================================================
+// Serial Bus with Transaction Scopes
+// =============================================================================
+
+/// Port state guarded by the bus mutex.
+struct BusPort {
+    io: DynSerial,
+    /// When the previous transaction released the bus.
+    last_release: Option<Instant>,
+}
+
+struct SerialBusInner {
+    port: Mutex<BusPort>,
+    /// Set while a device transaction was left unfinished.
+    resync: PortResync,
+    /// Minimum quiet time each device needs after prior bus traffic.
+    device_delays: parking_lot::RwLock<HashMap<String, Duration>>,
+}
+
+/// Shared serial bus with explicit transaction scopes.
+///
+/// Multidrop buses (RS-485, Elliptec) carry several devices on one port. Locking
+/// the port for a single write and again for the read lets a second driver slip a
+/// command in between, which garbles both responses. `SerialBus` makes the
+/// command/response pair one scope:
+///
+/// - **Pairing**: the port stays locked for the whole transaction scope.
+/// - **Inter-command delay**: each device can register the quiet time it needs
+///   after the previous transaction on the bus; the scope waits it out while
+///   holding the lock so nobody else can jump in.
+/// - **Fairness**: waiters are served in FIFO order (tokio's `Mutex` is fair), so
+///   a chatty poller cannot starve other actors sharing the bus.
+/// - **Resync**: a transaction dropped before [`BusTransaction::finish`]
+///   (cancelled or failed mid-reply) makes the next one drain the port first,
+///   so no device reads another's late reply (see [`PortResync`]).
+///
+/// Cloning is cheap and yields a handle to the same bus.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use common::serial::SerialBus;
+/// use tokio::io::{AsyncReadExt, AsyncWriteExt};
+///
+/// let bus = SerialBus::new(Box::new(port));
+/// bus.set_inter_command_delay("2", Duration::from_millis(20));
+///
+/// let reply = bus
+///     .with_transaction("2", |port| {
+///         Box::pin(async move {
+///             port.write_all(b"2gp").await?;
+///             let mut buf = [0u8; 32];
+///             let n = port.read(&mut buf).await?;
+///             Ok::<_, std::io::Error>(buf[..n].to_vec())
+///         })
+///     })
+///     .await?;
+/// ```
+#[derive(Clone)]
+pub struct SerialBus {
+    inner: Arc<SerialBusInner>,
+}
+
+impl SerialBus {
+    /// Wrap a type-erased serial port in a shared bus.
+    pub fn new(port: DynSerial) -> Self {
+        Self {
+            inner: Arc::new(SerialBusInner {
+                port: Mutex::new(BusPort {
+                    io: port,
+                    last_release: None,
+                }),
+                resync: PortResync::new(),
+                device_delays: parking_lot::RwLock::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// Set the quiet time `device` needs after prior bus traffic before it
+    /// accepts a new command. A zero delay removes the entry.
+    pub fn set_inter_command_delay(&self, device: &str, delay: Duration) {
+        let mut delays = self.inner.device_delays.write();
+        if delay.is_zero() {
+            delays.remove(device);
+        } else {
+            delays.insert(device.to_string(), delay);
+        }
+    }
+
+    /// Get the configured inter-command delay for `device` (zero if unset).
+    pub fn inter_command_delay(&self, device: &str) -> Duration {
+        self.inner
+            .device_delays
+            .read()
+            .get(device)
+            .copied()
+            .unwrap_or(Duration::ZERO)
+    }
+
+    /// Lock the bus without applying any device delay.
+    ///
+    /// Useful for bus-level housekeeping (flush, health checks). Device I/O
+    /// should go through [`SerialBus::transaction`] instead.
+    pub async fn lock(&self) -> BusTransaction<'_> {
+        BusTransaction {
+            guard: self.inner.port.lock().await,
+            in_flight: None,
+        }
+    }
+
+    /// Begin a transaction scope for `device`.
+    ///
+    /// Waits for exclusive access (FIFO), drains whatever an interrupted
+    /// transaction left on the bus, then waits for the device's inter-command
+    /// delay to elapse since the previous transaction ended. The bus stays
+    /// locked until the returned guard is dropped; call
+    /// [`BusTransaction::finish`] once the reply has been read.
+    pub async fn transaction(&self, device: &str) -> BusTransaction<'_> {
+        let delay = self.inter_command_delay(device);
+        let mut guard = self.inner.port.lock().await;
+        let in_flight = self.inner.resync.begin(&mut guard.io).await;
+
+        if let Some(last_release) = guard.last_release {
+            let ready_at = last_release + delay;
+            if ready_at > Instant::now() {
+                tokio::time::sleep_until(ready_at).await;
+            }
+        }
+
+        BusTransaction {
+            guard,
+            in_flight: Some(in_flight),
+        }
+    }
+
+    /// Run `f` inside a transaction scope for `device`.
+    ///
+    /// The closure receives exclusive access to the port for the whole
+    /// command/response exchange; the transaction finishes when it returns.
+    pub async fn with_transaction<F, T>(&self, device: &str, f: F) -> T
+    where
+        F: for<'a> FnOnce(&'a mut DynSerial) -> BoxFuture<'a, T>,
+    {
+        let mut transaction = self.transaction(device).await;
+        let result = f(&mut transaction).await;
+        transaction.finish();
+        result
+    }
+}
+
+/// Exclusive access to a [`SerialBus`] for one transaction scope.
+///
+/// Dereferences to the underlying port. Dropping the guard releases the bus and
+/// records the release time used for inter-command delays; dropping it before
+/// [`BusTransaction::finish`] marks the bus for a resync.
+pub struct BusTransaction<'a> {
+    guard: MutexGuard<'a, BusPort>,
+    in_flight: Option<InFlight>,
+}
+
+impl BusTransaction<'_> {
+    /// Mark the exchange complete and release the bus.
+    pub fn finish(mut self) {
+        if let Some(in_flight) = self.in_flight.take() {
+            in_flight.finish();
+        }
+    }
+}
+
+impl Deref for BusTransaction<'_> {
+    type Target = DynSerial;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard.io
+    }
+}
+
+impl DerefMut for BusTransaction<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard.io
+    }
+}
+
+impl Drop for BusTransaction<'_> {
+    fn drop(&mut self) {
+        self.guard.last_release = Some(Instant::now());
+    }
+}
+
+// =============================================================================
+// Resync After Interrupted Transactions
+// =============================================================================
+
+/// How long [`PortResync`] drains a port after an interrupted transaction.
+pub const RESYNC_DRAIN_MS: u64 = 100;
+
+/// Tracks whether the last transaction on a port was left unfinished.
+///
+/// Dropping a command/response future part-way (a cancelled move, a timed-out
+/// read, an error between write and read) leaves the device's reply on the
+/// wire, where the next transaction would read it as its own answer. Drivers
+/// open each transaction with [`PortResync::begin`], which first drains the
+/// port if the previous one never called [`InFlight::finish`].
+///
+/// Cloning is cheap and yields a handle to the same state, so it can be shared
+/// by every driver on one port.
+#[derive(Clone, Debug, Default)]
+pub struct PortResync {
+    interrupted: Arc<AtomicBool>,
+}
+
+impl PortResync {
+    /// Create the state for a port with no transaction in flight.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the last transaction was left unfinished.
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::Acquire)
+    }
+
+    /// Start a transaction on `port`, draining it first if the last one was
+    /// interrupted.
+    ///
+    /// For a [`SharedPort`] pass the `BufReader` itself, so bytes it already
+    /// buffered are discarded too.
+    pub async fn begin<R: AsyncRead + Unpin>(&self, port: &mut R) -> InFlight {
+        if self.is_interrupted() {
+            let discarded = drain_serial_buffer(port, RESYNC_DRAIN_MS).await;
+            tracing::debug!(
+                discarded,
+                "Drained serial port after an interrupted transaction"
+            );
+            // Only clear the flag once the drain completed, in case this
+            // transaction is cancelled too
+            self.interrupted.store(false, Ordering::Release);
+        }
+        InFlight {
+            interrupted: self.interrupted.clone(),
+            finished: false,
+        }
+    }
+}
+
+/// A transaction started with [`PortResync::begin`].
+///
+/// Dropping it without calling [`InFlight::finish`] marks the port for a
+/// drain before the next transaction.
+#[derive(Debug)]
+pub struct InFlight {
+    interrupted: Arc<AtomicBool>,
+    finished: bool,
+}
+
+impl InFlight {
+    /// Mark the exchange complete: the whole reply has been read.
+    pub fn finish(mut self) {
+        self.finished = true;
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.interrupted.store(true, Ordering::Release);
+        }
+    }
+}
+
 // =============================================================================
 // Serial Port Utilities
 // =============================================================================
@@ -371,4 +648,136 @@ mod tests {
             Ok(Err(e)) => panic!("Unexpected error: {}", e),
         }
     }
+
+    #[tokio::test]
+    async fn test_serial_bus_transaction_pairs_command_and_response() {
+        let (mut host, device) = tokio::io::duplex(64);
+        let bus = SerialBus::new(Box::new(device));
+
+        // Echo device: answer each 3-byte command with "<cmd>!"
+        let responder = tokio::spawn(async move {
+            for _ in 0..2 {
+                let mut cmd = [0u8; 3];
+                host.read_exact(&mut cmd).await.unwrap();
+                host.write_all(&cmd).await.unwrap();
+                host.write_all(b"!").await.unwrap();
+            }
+        });
+
+        let mut handles = Vec::new();
+        for cmd in [&b"2gp"[..], &b"3gp"[..]] {
+            let bus = bus.clone();
+            handles.push(tokio::spawn(async move {
+                bus.with_transaction("any", |port| {
+                    Box::pin(async move {
+                        port.write_all(cmd).await.unwrap();
+                        let mut reply = [0u8; 4];
+                        port.read_exact(&mut reply).await.unwrap();
+                        (cmd.to_vec(), reply.to_vec())
+                    })
+                })
+                .await
+            }));
+        }
+
+        for handle in handles {
+            let (cmd, reply) = handle.await.unwrap();
+            assert_eq!(&reply[..3], &cmd[..]);
+            assert_eq!(reply[3], b'!');
+        }
+        responder.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_serial_bus_inter_command_delay() {
+        let (_host, device) = tokio::io::duplex(64);
+        let bus = SerialBus::new(Box::new(device));
+        bus.set_inter_command_delay("slow", Duration::from_millis(30));
+        assert_eq!(bus.inter_command_delay("slow"), Duration::from_millis(30));
+        assert_eq!(bus.inter_command_delay("fast"), Duration::ZERO);
+
+        drop(bus.transaction("fast").await);
+        let released = Instant::now();
+
+        drop(bus.transaction("slow").await);
+        assert!(released.elapsed() >= Duration::from_millis(30));
+
+        bus.set_inter_command_delay("slow", Duration::ZERO);
+        assert_eq!(bus.inter_command_delay("slow"), Duration::ZERO);
+    }
+
+    /// Send `command` and read one reply line, as a line-based driver does
+    async fn query(port: &SharedPort, resync: &PortResync, command: &[u8]) -> String {
+        let mut guard = port.lock().await;
+        let in_flight = resync.begin(&mut *guard).await;
+        guard.get_mut().write_all(command).await.unwrap();
+        let mut line = String::new();
+        guard.read_line(&mut line).await.unwrap();
+        in_flight.finish();
+        line
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_query_does_not_leak_reply_into_next() {
+        let (mut host, device) = tokio::io::duplex(64);
+        let port: SharedPort = wrap_shared(Box::new(device));
+        let resync = PortResync::new();
+
+        // Cancel the first query before the device answers
+        let cancelled =
+            tokio::time::timeout(Duration::from_millis(20), query(&port, &resync, b"A?\n")).await;
+        assert!(cancelled.is_err());
+        assert!(resync.is_interrupted());
+
+        // The late answer arrives, followed later by the answer to the next query
+        let mut command = [0u8; 3];
+        host.read_exact(&mut command).await.unwrap();
+        assert_eq!(&command, b"A?\n");
+        host.write_all(b"late A\n").await.unwrap();
+        let responder = tokio::spawn(async move {
+            host.read_exact(&mut command).await.unwrap();
+            assert_eq!(&command, b"B?\n");
+            host.write_all(b"B\n").await.unwrap();
+            host
+        });
+
+        assert_eq!(query(&port, &resync, b"B?\n").await.trim(), "B");
+        assert!(!resync.is_interrupted());
+        responder.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_serial_bus_resyncs_after_unfinished_transaction() {
+        let (mut host, device) = tokio::io::duplex(64);
+        let bus = SerialBus::new(Box::new(device));
+
+        // Device "2" is asked, but the transaction is dropped before reading
+        {
+            let mut transaction = bus.transaction("2").await;
+            transaction.write_all(b"2gp").await.unwrap();
+        }
+        let mut command = [0u8; 3];
+        host.read_exact(&mut command).await.unwrap();
+        host.write_all(b"2PO").await.unwrap();
+
+        // Device "3" must get its own reply, not the one left by "2"
+        let responder = tokio::spawn(async move {
+            host.read_exact(&mut command).await.unwrap();
+            assert_eq!(&command, b"3gp");
+            host.write_all(b"3PO").await.unwrap();
+            host
+        });
+        let reply = bus
+            .with_transaction("3", |port| {
+                Box::pin(async move {
+                    port.write_all(b"3gp").await.unwrap();
+                    let mut reply = [0u8; 3];
+                    port.read_exact(&mut reply).await.unwrap();
+                    reply
+                })
+            })
+            .await;
+        assert_eq!(&reply, b"3PO");
+        responder.await.unwrap();
+    }
 }
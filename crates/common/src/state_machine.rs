@@ -0,0 +1,370 @@
+//! Lifecycle state machines and their live states.
+//!
+//! Modules, the run engine and devices each move through a fixed set of
+//! states. [`StateGraph`] describes which transitions are allowed for each
+//! [`MachineKind`]; the process-wide [`StateTracker`] records where every
+//! instance currently is, how long it has been there and how it got there.
+//!
+//! Transitions are published as they happen, so a client can draw the graph
+//! with the current state highlighted and see a module stuck in `staged` or
+//! a run engine that never leaves `aborting`. A transition that is not an
+//! edge of the graph is still recorded, but marked unexpected.
+
+use crate::experiment::document::now_ns;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+use tokio::sync::broadcast;
+
+/// Transitions kept per instance
+const HISTORY_PER_INSTANCE: usize = 32;
+
+/// Transitions buffered for slow subscribers
+const TRANSITION_CHANNEL_CAPACITY: usize = 256;
+
+/// Kind of state machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MachineKind {
+    /// Experiment module lifecycle ([`ModuleState`](crate::modules::ModuleState))
+    Module,
+    /// Plan execution state of the run engine
+    RunEngine,
+    /// Operational status of a device
+    /// ([`DeviceStatus`](crate::driver::DeviceStatus))
+    Device,
+}
+
+impl MachineKind {
+    pub const ALL: [MachineKind; 3] = [Self::Module, Self::RunEngine, Self::Device];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Module => "module",
+            Self::RunEngine => "run_engine",
+            Self::Device => "device",
+        }
+    }
+
+    /// Parse a name as returned by [`as_str`](Self::as_str)
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == name)
+    }
+
+    /// Allowed states and transitions of this kind
+    pub fn graph(&self) -> &'static StateGraph {
+        match self {
+            Self::Module => &MODULE_GRAPH,
+            Self::RunEngine => &RUN_ENGINE_GRAPH,
+            Self::Device => &DEVICE_GRAPH,
+        }
+    }
+}
+
+impl fmt::Display for MachineKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An allowed transition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub from: &'static str,
+    pub to: &'static str,
+    /// What usually causes it (a command, or an event such as `fault`)
+    pub trigger: &'static str,
+}
+
+const fn edge(from: &'static str, to: &'static str, trigger: &'static str) -> Edge {
+    Edge { from, to, trigger }
+}
+
+/// States and allowed transitions of one kind of state machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateGraph {
+    pub kind: MachineKind,
+    /// State a new instance starts in
+    pub initial: &'static str,
+    pub states: &'static [&'static str],
+    pub edges: &'static [Edge],
+}
+
+impl StateGraph {
+    /// Whether `from -> to` is an edge of the graph
+    pub fn allows(&self, from: &str, to: &str) -> bool {
+        self.edges.iter().any(|e| e.from == from && e.to == to)
+    }
+}
+
+static MODULE_GRAPH: StateGraph = StateGraph {
+    kind: MachineKind::Module,
+    initial: "created",
+    states: &[
+        "created",
+        "configured",
+        "staged",
+        "running",
+        "paused",
+        "stopped",
+        "error",
+    ],
+    edges: &[
+        edge("created", "configured", "configure"),
+        edge("stopped", "configured", "configure"),
+        edge("error", "configured", "configure"),
+        edge("created", "staged", "stage"),
+        edge("configured", "staged", "stage"),
+        edge("stopped", "staged", "stage"),
+        edge("created", "running", "start"),
+        edge("configured", "running", "start"),
+        edge("staged", "running", "start"),
+        edge("stopped", "running", "start"),
+        edge("running", "paused", "pause"),
+        edge("paused", "running", "resume"),
+        edge("running", "stopped", "stop"),
+        edge("paused", "stopped", "stop"),
+        edge("running", "error", "failure"),
+        edge("paused", "error", "failure"),
+        edge("staged", "error", "failure"),
+    ],
+};
+
+static RUN_ENGINE_GRAPH: StateGraph = StateGraph {
+    kind: MachineKind::RunEngine,
+    initial: "idle",
+    states: &["idle", "running", "paused", "aborting"],
+    edges: &[
+        edge("idle", "running", "start"),
+        edge("running", "paused", "checkpoint"),
+        edge("paused", "running", "resume"),
+        edge("running", "aborting", "abort"),
+        edge("paused", "aborting", "abort"),
+        edge("running", "idle", "finished"),
+        edge("aborting", "idle", "finished"),
+    ],
+};
+
+static DEVICE_GRAPH: StateGraph = StateGraph {
+    kind: MachineKind::Device,
+    initial: "uninitialized",
+    states: &["uninitialized", "ready", "busy", "degraded", "fault"],
+    edges: &[
+        edge("uninitialized", "ready", "initialized"),
+        edge("uninitialized", "fault", "fault"),
+        edge("ready", "busy", "operation"),
+        edge("busy", "ready", "done"),
+        edge("ready", "degraded", "degraded"),
+        edge("busy", "degraded", "degraded"),
+        edge("degraded", "ready", "recovered"),
+        edge("degraded", "busy", "operation"),
+        edge("ready", "fault", "fault"),
+        edge("busy", "fault", "fault"),
+        edge("degraded", "fault", "fault"),
+        edge("fault", "ready", "cleared"),
+        edge("fault", "uninitialized", "reset"),
+        edge("ready", "uninitialized", "reset"),
+    ],
+};
+
+/// One observed state change
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateTransition {
+    pub kind: MachineKind,
+    pub instance_id: String,
+    /// Previous state; empty when the instance first appeared
+    pub from: String,
+    pub to: String,
+    /// Command or event that caused the change
+    pub trigger: String,
+    pub timestamp_ns: u64,
+    /// Whether the change is an edge of the kind's graph
+    pub expected: bool,
+}
+
+/// Current state of one instance, with its recent transitions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackedMachine {
+    pub kind: MachineKind,
+    pub instance_id: String,
+    pub state: String,
+    /// When the current state was entered
+    pub entered_ns: u64,
+    /// Oldest first
+    pub recent: VecDeque<StateTransition>,
+}
+
+/// Live states of every module, the run engine and every device
+#[derive(Debug)]
+pub struct StateTracker {
+    machines: Mutex<BTreeMap<(MachineKind, String), TrackedMachine>>,
+    sender: broadcast::Sender<StateTransition>,
+}
+
+impl Default for StateTracker {
+    fn default() -> Self {
+        Self {
+            machines: Mutex::default(),
+            sender: broadcast::channel(TRANSITION_CHANNEL_CAPACITY).0,
+        }
+    }
+}
+
+impl StateTracker {
+    /// Process-wide tracker fed by the module registry, run engine and
+    /// device registry
+    pub fn global() -> &'static StateTracker {
+        static GLOBAL: OnceLock<StateTracker> = OnceLock::new();
+        GLOBAL.get_or_init(StateTracker::default)
+    }
+
+    /// Record that `instance_id` is now in `state`
+    ///
+    /// Returns the transition, or `None` if the instance was already in
+    /// `state`.
+    pub fn record(
+        &self,
+        kind: MachineKind,
+        instance_id: &str,
+        state: &str,
+        trigger: &str,
+    ) -> Option<StateTransition> {
+        let now = now_ns();
+        let mut machines = self.machines.lock().unwrap_or_else(|p| p.into_inner());
+        let machine = machines
+            .entry((kind, instance_id.to_string()))
+            .or_insert_with(|| TrackedMachine {
+                kind,
+                instance_id: instance_id.to_string(),
+                state: String::new(),
+                entered_ns: now,
+                recent: VecDeque::new(),
+            });
+        if machine.state == state {
+            return None;
+        }
+
+        let from = std::mem::replace(&mut machine.state, state.to_string());
+        let transition = StateTransition {
+            kind,
+            instance_id: instance_id.to_string(),
+            expected: from.is_empty() || kind.graph().allows(&from, state),
+            from,
+            to: state.to_string(),
+            trigger: trigger.to_string(),
+            timestamp_ns: now,
+        };
+        if !transition.expected {
+            tracing::warn!(
+                kind = %kind,
+                instance = instance_id,
+                from = %transition.from,
+                to = state,
+                trigger,
+                "Unexpected state transition"
+            );
+        }
+        machine.entered_ns = now;
+        if machine.recent.len() == HISTORY_PER_INSTANCE {
+            machine.recent.pop_front();
+        }
+        machine.recent.push_back(transition.clone());
+        drop(machines);
+
+        // No subscribers is fine
+        let _ = self.sender.send(transition.clone());
+        Some(transition)
+    }
+
+    /// Forget an instance that no longer exists
+    pub fn remove(&self, kind: MachineKind, instance_id: &str) {
+        self.machines
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .remove(&(kind, instance_id.to_string()));
+    }
+
+    /// Current state of one instance
+    pub fn get(&self, kind: MachineKind, instance_id: &str) -> Option<TrackedMachine> {
+        self.machines
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(&(kind, instance_id.to_string()))
+            .cloned()
+    }
+
+    /// All tracked instances, ordered by kind and ID
+    pub fn snapshot(&self) -> Vec<TrackedMachine> {
+        self.machines
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Receive transitions recorded from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<StateTransition> {
+        self.sender.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph_edges_use_declared_states() {
+        for kind in MachineKind::ALL {
+            let graph = kind.graph();
+            assert_eq!(graph.kind, kind);
+            assert!(graph.states.contains(&graph.initial));
+            for edge in graph.edges {
+                assert!(graph.states.contains(&edge.from), "{kind}: {}", edge.from);
+                assert!(graph.states.contains(&edge.to), "{kind}: {}", edge.to);
+            }
+            assert_eq!(MachineKind::parse(kind.as_str()), Some(kind));
+        }
+    }
+
+    #[test]
+    fn test_tracker_records_and_flags_transitions() {
+        let tracker = StateTracker::default();
+        let mut rx = tracker.subscribe();
+
+        let first = tracker
+            .record(MachineKind::RunEngine, "run_engine", "idle", "created")
+            .unwrap();
+        assert!(first.from.is_empty() && first.expected);
+        assert!(tracker
+            .record(MachineKind::RunEngine, "run_engine", "idle", "created")
+            .is_none());
+
+        let start = tracker
+            .record(MachineKind::RunEngine, "run_engine", "running", "start")
+            .unwrap();
+        assert_eq!(start.from, "idle");
+        assert!(start.expected);
+
+        assert!(
+            tracker
+                .record(MachineKind::RunEngine, "run_engine", "aborting", "halt")
+                .unwrap()
+                .expected
+        );
+        // Not an edge of the graph, but still recorded
+        let odd = tracker
+            .record(MachineKind::RunEngine, "run_engine", "paused", "bug")
+            .unwrap();
+        assert!(!odd.expected);
+
+        let machine = tracker.get(MachineKind::RunEngine, "run_engine").unwrap();
+        assert_eq!(machine.state, "paused");
+        assert_eq!(machine.recent.len(), 4);
+        assert_eq!(rx.try_recv().unwrap(), first);
+
+        tracker.remove(MachineKind::RunEngine, "run_engine");
+        assert!(tracker.snapshot().is_empty());
+    }
+}
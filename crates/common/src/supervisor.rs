@@ -0,0 +1,247 @@
+//! Restart supervision of long-running daemon subsystems.
+//!
+//! [`supervise`] runs a subsystem (the storage writer, the gRPC server) and
+//! starts it again when it returns an error or panics, with exponential
+//! backoff. A subsystem failing more than `max_restarts` times within
+//! `window` is given up on, so a persistent fault surfaces as an error
+//! instead of a restart loop. Returning `Ok` is a clean stop.
+//!
+//! Restarts are recorded in the process-wide [`SupervisorMonitor`] for
+//! health reporting and for tests that kill subsystems on purpose.
+
+use futures::FutureExt;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// When and how often a failed subsystem is restarted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartPolicy {
+    /// Restarts allowed within `window` before giving up
+    pub max_restarts: u32,
+    pub window: Duration,
+    /// Wait before the first restart; doubled for each quick failure
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            window: Duration::from_secs(60),
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Restart at once, for tests
+    pub fn immediate(max_restarts: u32) -> Self {
+        Self {
+            max_restarts,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+            ..Self::default()
+        }
+    }
+
+    fn backoff(&self, recent_failures: usize) -> Duration {
+        let factor = 1u32 << recent_failures.saturating_sub(1).min(16);
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// Where a supervised subsystem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SupervisedState {
+    Running,
+    /// Failed, waiting out the backoff
+    Restarting,
+    /// Returned `Ok`
+    Stopped,
+    /// Failed too often; not restarted again
+    GaveUp,
+}
+
+/// Restart history of one subsystem
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SupervisedStats {
+    pub state: SupervisedState,
+    /// Restarts since the daemon started
+    pub restarts: u64,
+    pub last_error: Option<String>,
+}
+
+/// Restart history of every supervised subsystem
+#[derive(Debug, Default)]
+pub struct SupervisorMonitor {
+    subsystems: Mutex<BTreeMap<String, SupervisedStats>>,
+}
+
+impl SupervisorMonitor {
+    /// Process-wide monitor fed by [`supervise`]
+    pub fn global() -> &'static SupervisorMonitor {
+        static GLOBAL: OnceLock<SupervisorMonitor> = OnceLock::new();
+        GLOBAL.get_or_init(SupervisorMonitor::default)
+    }
+
+    /// Current stats of one subsystem
+    pub fn get(&self, name: &str) -> Option<SupervisedStats> {
+        self.lock().get(name).cloned()
+    }
+
+    /// Current stats by subsystem name
+    pub fn snapshot(&self) -> BTreeMap<String, SupervisedStats> {
+        self.lock().clone()
+    }
+
+    fn update(&self, name: &str, update: impl FnOnce(&mut SupervisedStats)) {
+        let mut subsystems = self.lock();
+        let stats = subsystems
+            .entry(name.to_string())
+            .or_insert(SupervisedStats {
+                state: SupervisedState::Running,
+                restarts: 0,
+                last_error: None,
+            });
+        update(stats);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, SupervisedStats>> {
+        self.subsystems.lock().unwrap_or_else(|p| p.into_inner())
+    }
+}
+
+/// Run the subsystem `name` made by `start`, restarting it on failure
+///
+/// `start` is called once per run. Returns `Ok` once a run returns `Ok`, and
+/// the last failure once the restart budget of `policy` is used up.
+pub async fn supervise<F, Fut>(
+    name: &str,
+    policy: &RestartPolicy,
+    mut start: F,
+) -> anyhow::Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<()>>,
+{
+    let monitor = SupervisorMonitor::global();
+    let mut failures: VecDeque<Instant> = VecDeque::new();
+
+    loop {
+        monitor.update(name, |stats| stats.state = SupervisedState::Running);
+        let error = match AssertUnwindSafe(start()).catch_unwind().await {
+            Ok(Ok(())) => {
+                monitor.update(name, |stats| stats.state = SupervisedState::Stopped);
+                return Ok(());
+            }
+            Ok(Err(e)) => e,
+            Err(panic) => anyhow::anyhow!("panicked: {}", panic_message(panic.as_ref())),
+        };
+
+        let now = Instant::now();
+        failures.push_back(now);
+        while failures
+            .front()
+            .is_some_and(|first| now.duration_since(*first) > policy.window)
+        {
+            failures.pop_front();
+        }
+
+        let message = format!("{:#}", error);
+        if failures.len() > policy.max_restarts as usize {
+            tracing::error!(
+                subsystem = name,
+                error = %message,
+                "Subsystem failed {} times within {:?}; giving up",
+                failures.len(),
+                policy.window
+            );
+            monitor.update(name, |stats| {
+                stats.state = SupervisedState::GaveUp;
+                stats.last_error = Some(message);
+            });
+            return Err(error.context(format!("{} failed too often", name)));
+        }
+
+        let backoff = policy.backoff(failures.len());
+        tracing::warn!(
+            subsystem = name,
+            error = %message,
+            "Subsystem failed; restarting in {:?}",
+            backoff
+        );
+        monitor.update(name, |stats| {
+            stats.state = SupervisedState::Restarting;
+            stats.restarts += 1;
+            stats.last_error = Some(message);
+        });
+        tokio::time::sleep(backoff).await;
+    }
+}
+
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn test_restarts_until_clean_stop() {
+        let runs = AtomicU32::new(0);
+        let result = supervise("test.recovers", &RestartPolicy::immediate(3), || async {
+            match runs.fetch_add(1, Ordering::SeqCst) {
+                0 => anyhow::bail!("lost connection"),
+                1 => panic!("bug"),
+                _ => Ok(()),
+            }
+        })
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        let stats = SupervisorMonitor::global().get("test.recovers").unwrap();
+        assert_eq!(stats.state, SupervisedState::Stopped);
+        assert_eq!(stats.restarts, 2);
+        assert_eq!(stats.last_error.as_deref(), Some("panicked: bug"));
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_budget() {
+        let runs = AtomicU32::new(0);
+        let result = supervise("test.gives_up", &RestartPolicy::immediate(2), || async {
+            runs.fetch_add(1, Ordering::SeqCst);
+            anyhow::bail!("disk full")
+        })
+        .await;
+
+        assert!(result.unwrap_err().to_string().contains("failed too often"));
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        let stats = SupervisorMonitor::global().get("test.gives_up").unwrap();
+        assert_eq!(stats.state, SupervisedState::GaveUp);
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let policy = RestartPolicy::default();
+        let waits: Vec<_> = (1..=8).map(|n| policy.backoff(n).as_millis()).collect();
+        assert_eq!(waits, [100, 200, 400, 800, 1600, 3200, 5000, 5000]);
+    }
+}
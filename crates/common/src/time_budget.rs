@@ -0,0 +1,250 @@
+//! Per-point time budgets for detector reads and motion.
+//!
+//! One stuck serial transaction should not hang a scan overnight. A plan
+//! declares a [`TimeBudget`]: how long a detector read (waiting for a frame
+//! included) and a move (settling included) may take. When an operation
+//! overruns its budget the RunEngine cancels it and applies the budget's
+//! [`OverrunPolicy`]:
+//!
+//! - `retry`: run the operation again, up to `attempts` more times, then
+//!   fail the run;
+//! - `skip_and_flag`: carry on without it. A skipped read is recorded as NaN
+//!   with [`DataQuality::Suspect`]; either way the point's event lists the
+//!   overruns under [`TIME_BUDGET_OVERRUN_KEY`];
+//! - `pause`: pause the run (parking devices as for any pause) and run the
+//!   operation again when the run resumes.
+//!
+//! A [`TimeBudgetSummary`] of the run is stored as JSON in the stop document
+//! metadata under [`TIME_BUDGET_METADATA_KEY`] when any operation overran.
+//!
+//! Plans built in code override `Plan::time_budget` in the experiment crate.
+//! A budget queued as JSON in the run metadata under [`TIME_BUDGET_METADATA_KEY`]
+//! replaces the plan's own:
+//!
+//! ```json
+//! { "read_s": 2.0, "move_s": 30.0, "on_overrun": { "policy": "retry", "attempts": 2 } }
+//! ```
+//!
+//! [`DataQuality::Suspect`]: crate::core::DataQuality::Suspect
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+/// Run metadata key holding a [`TimeBudget`], and stop document metadata key
+/// holding the run's [`TimeBudgetSummary`]
+pub const TIME_BUDGET_METADATA_KEY: &str = "time_budget";
+
+/// Event metadata key listing the operations that overran during the point
+/// (comma-separated)
+pub const TIME_BUDGET_OVERRUN_KEY: &str = "time_budget.overrun";
+
+/// Longest budget an operation may be given
+pub const MAX_BUDGET_S: f64 = 86_400.0;
+
+/// What to do when an operation overruns its budget
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "policy", rename_all = "snake_case", deny_unknown_fields)]
+pub enum OverrunPolicy {
+    /// Run the operation again, then fail the run
+    Retry { attempts: u32 },
+    /// Carry on without the operation and flag the point
+    #[default]
+    SkipAndFlag,
+    /// Pause the run; the operation runs again on resume
+    Pause,
+}
+
+impl fmt::Display for OverrunPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Retry { attempts } => write!(f, "retry ({} attempts)", attempts),
+            Self::SkipAndFlag => write!(f, "skip and flag"),
+            Self::Pause => write!(f, "pause"),
+        }
+    }
+}
+
+/// Kind of operation a budget applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetedOperation {
+    Read,
+    Move,
+}
+
+impl fmt::Display for BudgetedOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read => write!(f, "read"),
+            Self::Move => write!(f, "move"),
+        }
+    }
+}
+
+/// Time allowed per operation of each point
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TimeBudget {
+    /// Seconds allowed for a detector read; unlimited if absent
+    #[serde(default)]
+    pub read_s: Option<f64>,
+    /// Seconds allowed for a move, settling included; unlimited if absent
+    #[serde(default)]
+    pub move_s: Option<f64>,
+    #[serde(default)]
+    pub on_overrun: OverrunPolicy,
+}
+
+impl TimeBudget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_read_budget(mut self, seconds: f64) -> Self {
+        self.read_s = Some(seconds);
+        self
+    }
+
+    pub fn with_move_budget(mut self, seconds: f64) -> Self {
+        self.move_s = Some(seconds);
+        self
+    }
+
+    pub fn with_policy(mut self, policy: OverrunPolicy) -> Self {
+        self.on_overrun = policy;
+        self
+    }
+
+    /// The budget of an operation, if it has one
+    pub fn limit(&self, operation: BudgetedOperation) -> Option<Duration> {
+        let seconds = match operation {
+            BudgetedOperation::Read => self.read_s,
+            BudgetedOperation::Move => self.move_s,
+        };
+        seconds.map(Duration::from_secs_f64)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read_s.is_none() && self.move_s.is_none()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        for (name, seconds) in [("read_s", self.read_s), ("move_s", self.move_s)] {
+            if let Some(seconds) = seconds {
+                if !(seconds > 0.0 && seconds <= MAX_BUDGET_S) {
+                    bail!(
+                        "Time budget '{}' must be between 0 and {} s, got {}",
+                        name,
+                        MAX_BUDGET_S,
+                        seconds
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The time budget in queued run metadata, if there is one
+    pub fn from_metadata(metadata: &HashMap<String, String>) -> Result<Option<Self>> {
+        let Some(json) = metadata.get(TIME_BUDGET_METADATA_KEY) else {
+            return Ok(None);
+        };
+        let budget: Self = serde_json::from_str(json)
+            .with_context(|| format!("Invalid metadata '{}'", TIME_BUDGET_METADATA_KEY))?;
+        budget.validate()?;
+        Ok(Some(budget))
+    }
+
+    /// Encode for [`TIME_BUDGET_METADATA_KEY`]
+    pub fn to_metadata_value(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Overruns during a run, recorded in the stop document
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimeBudgetSummary {
+    pub policy: OverrunPolicy,
+    /// Operations that overran their budget, retries included
+    pub overruns: u32,
+    /// Overruns that were retried
+    pub retried: u32,
+    /// Operations skipped
+    pub skipped: u32,
+    /// Times the run was paused
+    pub paused: u32,
+    /// Overruns per device
+    pub devices: HashMap<String, u32>,
+}
+
+impl TimeBudgetSummary {
+    pub fn new(policy: OverrunPolicy) -> Self {
+        Self {
+            policy,
+            ..Default::default()
+        }
+    }
+
+    pub fn record_overrun(&mut self, device_id: &str) {
+        self.overruns += 1;
+        *self.devices.entry(device_id.to_string()).or_default() += 1;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.overruns == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_from_metadata() {
+        let metadata = HashMap::from([(
+            TIME_BUDGET_METADATA_KEY.to_string(),
+            r#"{"read_s": 2, "on_overrun": {"policy": "retry", "attempts": 3}}"#.to_string(),
+        )]);
+        let budget = TimeBudget::from_metadata(&metadata).unwrap().unwrap();
+        assert_eq!(
+            budget.limit(BudgetedOperation::Read),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(budget.limit(BudgetedOperation::Move), None);
+        assert_eq!(budget.on_overrun, OverrunPolicy::Retry { attempts: 3 });
+
+        let round_trip = HashMap::from([(
+            TIME_BUDGET_METADATA_KEY.to_string(),
+            budget.to_metadata_value(),
+        )]);
+        assert_eq!(
+            TimeBudget::from_metadata(&round_trip).unwrap(),
+            Some(budget)
+        );
+    }
+
+    #[test]
+    fn test_budget_defaults_to_skip_and_flag() {
+        let budget: TimeBudget = serde_json::from_str(r#"{"move_s": 10}"#).unwrap();
+        assert_eq!(budget.on_overrun, OverrunPolicy::SkipAndFlag);
+        assert!(!budget.is_empty());
+        assert!(TimeBudget::new().is_empty());
+    }
+
+    #[test]
+    fn test_budget_rejects_bad_limits() {
+        for json in [
+            r#"{"read_s": 0}"#,
+            r#"{"move_s": -1}"#,
+            r#"{"read_s": 1e9}"#,
+            r#"{"read_s": 1, "on_overrun": {"policy": "ignore"}}"#,
+        ] {
+            let metadata =
+                HashMap::from([(TIME_BUDGET_METADATA_KEY.to_string(), json.to_string())]);
+            assert!(TimeBudget::from_metadata(&metadata).is_err(), "{}", json);
+        }
+    }
+}
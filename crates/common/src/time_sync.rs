@@ -0,0 +1,251 @@
+//! Timestamp skew between devices, and per-device corrections.
+//!
+//! Devices stamp their data with different clocks and latencies: a camera's
+//! frame timestamp, a DAQ card's sample time and the host time of a power
+//! meter read can disagree by milliseconds for the same physical instant.
+//! The time-sync check fires a common stimulus (a trigger or an output step)
+//! several times and records when each device reports seeing it; [`analyze`]
+//! turns those observations into a per-device offset and jitter.
+//!
+//! Offsets can be kept as [`TimestampCorrections`], which are subtracted from
+//! the timestamps the RunEngine records with each reading (`EventDoc`
+//! per-field timestamps), so channels from different devices line up.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! # Nanoseconds subtracted from each device's timestamps
+//! [timestamp_offsets]
+//! camera = 4_200_000
+//! power_meter = -350_000
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// When one device reported one stimulus
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkewSample {
+    /// Repetition the observation belongs to
+    pub repetition: u32,
+    /// Host time the stimulus was fired (UNIX nanoseconds)
+    pub stimulus_ns: u64,
+    /// Device timestamp of the response (UNIX nanoseconds)
+    pub observed_ns: u64,
+}
+
+impl SkewSample {
+    /// Delay from stimulus to the device's timestamp
+    pub fn delay_ns(&self) -> i64 {
+        self.observed_ns as i64 - self.stimulus_ns as i64
+    }
+}
+
+/// Skew statistics of one device
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceSkew {
+    pub device_id: String,
+    /// Repetitions the device responded to
+    pub samples: u32,
+    /// Repetitions without a response before the timeout
+    pub missed: u32,
+    /// Mean delay from the stimulus, in nanoseconds
+    pub mean_delay_ns: f64,
+    /// Mean offset from the reference device, in nanoseconds
+    pub offset_ns: f64,
+    /// Standard deviation of the offset from the reference, in nanoseconds
+    pub jitter_ns: f64,
+    pub min_offset_ns: i64,
+    pub max_offset_ns: i64,
+}
+
+/// Result of a time-sync check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncReport {
+    /// Device the offsets are relative to (`None`: the stimulus time)
+    pub reference: Option<String>,
+    pub repetitions: u32,
+    pub devices: Vec<DeviceSkew>,
+}
+
+impl SyncReport {
+    /// Largest difference between the mean offsets of any two devices
+    pub fn max_skew_ns(&self) -> f64 {
+        let offsets = self.devices.iter().filter(|d| d.samples > 0);
+        let max = offsets
+            .clone()
+            .map(|d| d.offset_ns)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let min = offsets.map(|d| d.offset_ns).fold(f64::INFINITY, f64::min);
+        if max.is_finite() && min.is_finite() {
+            max - min
+        } else {
+            0.0
+        }
+    }
+
+    /// Corrections that would cancel each device's mean offset
+    pub fn corrections(&self) -> TimestampCorrections {
+        TimestampCorrections::from(
+            self.devices
+                .iter()
+                .filter(|d| d.samples > 0)
+                .map(|d| (d.device_id.clone(), d.offset_ns.round() as i64))
+                .collect::<HashMap<_, _>>(),
+        )
+    }
+}
+
+/// Offset and jitter of each device from observations of repeated stimuli
+///
+/// With a `reference` device, each device's offset in a repetition is its
+/// delay minus the reference's delay in the same repetition (repetitions the
+/// reference missed are skipped); otherwise offsets are delays from the
+/// stimulus.
+pub fn analyze(
+    observations: &BTreeMap<String, Vec<SkewSample>>,
+    repetitions: u32,
+    reference: Option<&str>,
+) -> SyncReport {
+    let reference_delays: Option<HashMap<u32, i64>> = reference.map(|reference| {
+        observations
+            .get(reference)
+            .map(|samples| {
+                samples
+                    .iter()
+                    .map(|s| (s.repetition, s.delay_ns()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    });
+
+    let devices = observations
+        .iter()
+        .map(|(device_id, samples)| {
+            let delays: Vec<i64> = samples.iter().map(SkewSample::delay_ns).collect();
+            let offsets: Vec<i64> = match &reference_delays {
+                Some(reference) => samples
+                    .iter()
+                    .filter_map(|s| reference.get(&s.repetition).map(|r| s.delay_ns() - r))
+                    .collect(),
+                None => delays.clone(),
+            };
+            let (offset_ns, jitter_ns) = mean_and_std(&offsets);
+            DeviceSkew {
+                device_id: device_id.clone(),
+                samples: samples.len() as u32,
+                missed: repetitions.saturating_sub(samples.len() as u32),
+                mean_delay_ns: mean_and_std(&delays).0,
+                offset_ns,
+                jitter_ns,
+                min_offset_ns: offsets.iter().copied().min().unwrap_or(0),
+                max_offset_ns: offsets.iter().copied().max().unwrap_or(0),
+            }
+        })
+        .collect();
+
+    SyncReport {
+        reference: reference.map(str::to_string),
+        repetitions,
+        devices,
+    }
+}
+
+fn mean_and_std(values: &[i64]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().map(|&v| v as f64).sum::<f64>() / n;
+    let variance = values
+        .iter()
+        .map(|&v| (v as f64 - mean).powi(2))
+        .sum::<f64>()
+        / n;
+    (mean, variance.sqrt())
+}
+
+/// Per-device timestamp offsets, in nanoseconds, subtracted from the
+/// device's timestamps
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TimestampCorrections {
+    offsets: HashMap<String, i64>,
+}
+
+impl From<HashMap<String, i64>> for TimestampCorrections {
+    fn from(offsets: HashMap<String, i64>) -> Self {
+        Self { offsets }
+    }
+}
+
+impl TimestampCorrections {
+    pub fn offset_ns(&self, device_id: &str) -> i64 {
+        self.offsets.get(device_id).copied().unwrap_or(0)
+    }
+
+    pub fn set(&mut self, device_id: &str, offset_ns: i64) {
+        if offset_ns == 0 {
+            self.offsets.remove(device_id);
+        } else {
+            self.offsets.insert(device_id.to_string(), offset_ns);
+        }
+    }
+
+    pub fn offsets(&self) -> &HashMap<String, i64> {
+        &self.offsets
+    }
+
+    /// A device timestamp on the common time base
+    pub fn correct(&self, device_id: &str, timestamp_ns: u64) -> u64 {
+        timestamp_ns.saturating_add_signed(-self.offset_ns(device_id))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    fn samples(delays: &[(u32, i64)]) -> Vec<SkewSample> {
+        delays
+            .iter()
+            .map(|&(repetition, delay)| SkewSample {
+                repetition,
+                stimulus_ns: 1_000_000_000,
+                observed_ns: (1_000_000_000 + delay) as u64,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_offsets_relative_to_reference() {
+        let observations = BTreeMap::from([
+            (
+                "camera".to_string(),
+                samples(&[(0, 5_000), (1, 7_000), (2, 6_000)]),
+            ),
+            ("daq".to_string(), samples(&[(0, 1_000), (1, 3_000)])),
+        ]);
+
+        let report = analyze(&observations, 3, Some("daq"));
+        let camera = &report.devices[0];
+        // Repetition 2 has no reference observation
+        assert_eq!(camera.offset_ns, 4_000.0);
+        assert_eq!(camera.jitter_ns, 0.0);
+        assert_eq!(camera.mean_delay_ns, 6_000.0);
+        let daq = &report.devices[1];
+        assert_eq!(daq.missed, 1);
+        assert_eq!(daq.offset_ns, 0.0);
+        assert_eq!(report.max_skew_ns(), 4_000.0);
+
+        let corrections = report.corrections();
+        assert_eq!(corrections.offset_ns("camera"), 4_000);
+        assert_eq!(corrections.correct("camera", 10_000), 6_000);
+        assert_eq!(corrections.correct("daq", 10_000), 10_000);
+
+        let absolute = analyze(&observations, 3, None);
+        assert_eq!(absolute.devices[1].offset_ns, 2_000.0);
+        assert_eq!(absolute.devices[1].jitter_ns, 1_000.0);
+    }
+}
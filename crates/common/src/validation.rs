@@ -0,0 +1,382 @@
+//! Per-point data validation rules.
+//!
+//! Rules catch points that were acquired but should not be trusted: a
+//! detector reading at the dark level because the beam was blocked, a
+//! reference channel drifting away from its nominal value, a camera frame
+//! clipped at full scale. The RunEngine checks every event against the
+//! configured rules before emitting it:
+//!
+//! - the checked field is given a quality flag (see [`EventDoc::quality`]),
+//!   unless it is already flagged;
+//! - the names of the failed rules are listed in the event metadata under
+//!   [`VALIDATION_FAILED_KEY`];
+//! - a [`ValidationSummary`] of the run is stored as JSON in the stop
+//!   document metadata under [`VALIDATION_METADATA_KEY`].
+//!
+//! Points are flagged, never dropped. A rule whose field is missing from an
+//! event does not apply to it.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [[validation_rules]]
+//! type = "noise_floor"
+//! field = "diode"
+//! min = 0.002
+//!
+//! [[validation_rules]]
+//! name = "reference power"
+//! type = "tolerance"
+//! field = "ref_power"
+//! nominal = 1.0
+//! tolerance = 0.05
+//!
+//! [[validation_rules]]
+//! type = "saturation"
+//! field = "camera"
+//! saturation = 4095          # 12-bit sensor
+//! max_fraction = 0.001       # allow a few hot pixels
+//! ```
+
+use crate::core::DataQuality;
+use crate::experiment::document::EventDoc;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// Stop document metadata key holding the run's [`ValidationSummary`]
+pub const VALIDATION_METADATA_KEY: &str = "validation";
+
+/// Event metadata key listing the rules a point failed (comma-separated)
+pub const VALIDATION_FAILED_KEY: &str = "validation.failed";
+
+/// Default full-scale pixel value of frames (16-bit)
+pub const DEFAULT_SATURATION: u16 = u16::MAX;
+
+fn default_saturation() -> u16 {
+    DEFAULT_SATURATION
+}
+
+/// A check applied to one field of every event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationRule {
+    /// Name used in event flags and the run summary
+    /// (defaults to `<type>:<field>`)
+    #[serde(default)]
+    pub name: String,
+    /// Event field checked (detector or device ID, or channel alias)
+    pub field: String,
+    #[serde(flatten)]
+    pub check: ValidationCheck,
+}
+
+/// Built-in checks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ValidationCheck {
+    /// The value must reach `min`; lower values are flagged suspect
+    NoiseFloor { min: f64 },
+    /// The value must be within `tolerance` of `nominal`; others are
+    /// flagged suspect
+    Tolerance { nominal: f64, tolerance: f64 },
+    /// At most `max_fraction` of the frame's pixels (uint16) may be at
+    /// `saturation` or above; saturated frames are flagged out of range.
+    /// Scalar fields are checked against `saturation` directly.
+    Saturation {
+        #[serde(default = "default_saturation")]
+        saturation: u16,
+        #[serde(default)]
+        max_fraction: f64,
+    },
+}
+
+impl ValidationCheck {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Self::NoiseFloor { .. } => "noise_floor",
+            Self::Tolerance { .. } => "tolerance",
+            Self::Saturation { .. } => "saturation",
+        }
+    }
+}
+
+impl ValidationRule {
+    pub fn new(field: impl Into<String>, check: ValidationCheck) -> Self {
+        Self {
+            name: String::new(),
+            field: field.into(),
+            check,
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Name in flags and summaries
+    pub fn display_name(&self) -> String {
+        if self.name.is_empty() {
+            format!("{}:{}", self.check.type_name(), self.field)
+        } else {
+            self.name.clone()
+        }
+    }
+
+    /// Check that the rule is well formed
+    pub fn validate(&self) -> Result<()> {
+        if self.field.is_empty() {
+            bail!("Validation rule '{}' has no field", self.display_name());
+        }
+        match self.check {
+            ValidationCheck::NoiseFloor { min } if !min.is_finite() => {
+                bail!(
+                    "Validation rule '{}' has a non-finite min",
+                    self.display_name()
+                )
+            }
+            ValidationCheck::Tolerance { nominal, tolerance }
+                if !(nominal.is_finite() && tolerance.is_finite() && tolerance >= 0.0) =>
+            {
+                bail!(
+                    "Validation rule '{}' needs a finite nominal and a non-negative tolerance",
+                    self.display_name()
+                )
+            }
+            ValidationCheck::Saturation { max_fraction, .. }
+                if !(0.0..1.0).contains(&max_fraction) =>
+            {
+                bail!(
+                    "Validation rule '{}' has max_fraction {} outside [0, 1)",
+                    self.display_name(),
+                    max_fraction
+                )
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Quality the field gets when the rule fails, or `None` if the event
+    /// passes or lacks the field
+    pub fn check(&self, event: &EventDoc) -> Option<DataQuality> {
+        match self.check {
+            ValidationCheck::NoiseFloor { min } => {
+                let value = event.data.get(&self.field)?;
+                (*value < min).then_some(DataQuality::Suspect)
+            }
+            ValidationCheck::Tolerance { nominal, tolerance } => {
+                let value = event.data.get(&self.field)?;
+                ((value - nominal).abs() > tolerance).then_some(DataQuality::Suspect)
+            }
+            ValidationCheck::Saturation {
+                saturation,
+                max_fraction,
+            } => {
+                let saturated = match event.arrays.get(&self.field) {
+                    Some(frame) => saturated_fraction(frame, saturation) > max_fraction,
+                    None => *event.data.get(&self.field)? >= f64::from(saturation),
+                };
+                saturated.then_some(DataQuality::OutOfRange)
+            }
+        }
+    }
+}
+
+/// Check rule names are unique and every rule is well formed
+pub fn validate_rules(rules: &[ValidationRule]) -> Result<()> {
+    let mut names = HashSet::new();
+    for rule in rules {
+        rule.validate()?;
+        if !names.insert(rule.display_name()) {
+            bail!("Duplicate validation rule '{}'", rule.display_name());
+        }
+    }
+    Ok(())
+}
+
+/// Fraction of little-endian uint16 pixels at or above `saturation`
+fn saturated_fraction(frame: &[u8], saturation: u16) -> f64 {
+    let pixels = frame.len() / 2;
+    if pixels == 0 {
+        return 0.0;
+    }
+    let saturated = frame
+        .chunks_exact(2)
+        .filter(|pixel| u16::from_le_bytes([pixel[0], pixel[1]]) >= saturation)
+        .count();
+    saturated as f64 / pixels as f64
+}
+
+/// Outcome of the validation rules over a run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationSummary {
+    /// Events checked
+    pub points: u32,
+    /// Events failing at least one rule
+    pub flagged_points: u32,
+    /// Failures per rule name (rules that never failed are listed with 0)
+    pub failures: BTreeMap<String, u32>,
+}
+
+impl ValidationSummary {
+    /// Whether every point passed
+    pub fn is_clean(&self) -> bool {
+        self.flagged_points == 0
+    }
+}
+
+/// The rules of one run, with their running summary
+#[derive(Debug, Clone, Default)]
+pub struct RunValidator {
+    rules: Vec<ValidationRule>,
+    summary: ValidationSummary,
+}
+
+impl RunValidator {
+    pub fn new(rules: Vec<ValidationRule>) -> Self {
+        let failures = rules.iter().map(|rule| (rule.display_name(), 0)).collect();
+        Self {
+            rules,
+            summary: ValidationSummary {
+                failures,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Check an event, flagging failed fields and recording the failed rules
+    ///
+    /// Returns the names of the failed rules.
+    pub fn apply(&mut self, event: &mut EventDoc) -> Vec<String> {
+        let mut failed = Vec::new();
+        for rule in &self.rules {
+            let Some(quality) = rule.check(event) else {
+                continue;
+            };
+            // A flag from acquisition says more than the rule's
+            if event.quality(&rule.field).is_good() {
+                event.set_quality(&rule.field, quality);
+            }
+            let name = rule.display_name();
+            *self.summary.failures.entry(name.clone()).or_default() += 1;
+            failed.push(name);
+        }
+        self.summary.points += 1;
+        if !failed.is_empty() {
+            self.summary.flagged_points += 1;
+            event
+                .metadata
+                .insert(VALIDATION_FAILED_KEY.to_string(), failed.join(","));
+        }
+        failed
+    }
+
+    pub fn summary(&self) -> &ValidationSummary {
+        &self.summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(data: &[(&str, f64)]) -> EventDoc {
+        let mut event = EventDoc::new("run", "primary", 0);
+        event.data = data.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+        event
+    }
+
+    #[test]
+    fn test_rules_flag_failing_points() {
+        #[derive(Deserialize)]
+        struct Config {
+            validation_rules: Vec<ValidationRule>,
+        }
+        let rules = toml::from_str::<Config>(
+            r#"
+            [[validation_rules]]
+            type = "noise_floor"
+            field = "diode"
+            min = 0.01
+
+            [[validation_rules]]
+            name = "reference power"
+            type = "tolerance"
+            field = "ref"
+            nominal = 1.0
+            tolerance = 0.1
+
+            [[validation_rules]]
+            type = "saturation"
+            field = "camera"
+            saturation = 4095
+            max_fraction = 0.3
+            "#,
+        )
+        .unwrap()
+        .validation_rules;
+        validate_rules(&rules).unwrap();
+        let mut validator = RunValidator::new(rules);
+
+        let mut good = event(&[("diode", 0.5), ("ref", 1.05)]);
+        assert!(validator.apply(&mut good).is_empty());
+        assert!(!good.metadata.contains_key(VALIDATION_FAILED_KEY));
+
+        let mut bad = event(&[("diode", 0.001), ("ref", 1.2)]);
+        // Two of four pixels at full scale
+        let pixels: [u16; 4] = [100, 4095, 4095, 200];
+        bad.arrays.insert(
+            "camera".to_string(),
+            pixels.iter().flat_map(|p| p.to_le_bytes()).collect(),
+        );
+        let failed = validator.apply(&mut bad);
+        assert_eq!(
+            failed,
+            ["noise_floor:diode", "reference power", "saturation:camera"]
+        );
+        assert_eq!(bad.quality("diode"), DataQuality::Suspect);
+        assert_eq!(bad.quality("camera"), DataQuality::OutOfRange);
+        assert_eq!(
+            bad.metadata[VALIDATION_FAILED_KEY],
+            "noise_floor:diode,reference power,saturation:camera"
+        );
+
+        // Fields the event lacks are not checked
+        let mut partial = event(&[("ref", 1.0)]);
+        assert!(validator.apply(&mut partial).is_empty());
+
+        let summary = validator.summary();
+        assert_eq!(summary.points, 3);
+        assert_eq!(summary.flagged_points, 1);
+        assert_eq!(summary.failures["reference power"], 1);
+        assert!(!summary.is_clean());
+    }
+
+    #[test]
+    fn test_acquisition_flags_are_kept_and_bad_rules_rejected() {
+        let mut validator = RunValidator::new(vec![ValidationRule::new(
+            "diode",
+            ValidationCheck::NoiseFloor { min: 1.0 },
+        )]);
+        let mut stale = event(&[("diode", 0.0)]);
+        stale.set_quality("diode", DataQuality::Stale);
+        validator.apply(&mut stale);
+        assert_eq!(stale.quality("diode"), DataQuality::Stale);
+        assert_eq!(validator.summary().failures["noise_floor:diode"], 1);
+
+        let negative = ValidationRule::new(
+            "ref",
+            ValidationCheck::Tolerance {
+                nominal: 1.0,
+                tolerance: -0.1,
+            },
+        );
+        assert!(negative.validate().is_err());
+        let twice = ValidationRule::new("diode", ValidationCheck::NoiseFloor { min: 0.0 });
+        assert!(validate_rules(&[twice.clone(), twice]).is_err());
+    }
+}
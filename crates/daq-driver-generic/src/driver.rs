@@ -5,7 +5,8 @@
 
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
-use common::capabilities::{Movable, Readable, ShutterControl, WavelengthTunable};
+use common::capabilities::{Movable, RawTerminal, Readable, ShutterControl, WavelengthTunable};
+use common::serial::PortResync;
 use daq_plugin_api::config::{ErrorSeverity, InstrumentConfig, ResponseFieldType};
 use evalexpr::{eval_number_with_context, ContextWithMutableVariables, HashMapContext, Value};
 use regex::Regex;
@@ -128,6 +129,8 @@ pub struct GenericSerialDriver {
     config: Arc<InstrumentConfig>,
     /// Shared serial port
     port: SharedPort,
+    /// Drains replies left by interrupted transactions
+    resync: PortResync,
     /// Device address (for RS-485 multidrop)
     address: String,
     /// Cached parameter values (for conversions)
@@ -173,6 +176,7 @@ impl GenericSerialDriver {
         Ok(Self {
             config: Arc::new(config),
             port,
+            resync: PortResync::new(),
             address: address.to_string(),
             parameters: Arc::new(Mutex::new(parameters)),
             response_patterns: Arc::new(response_patterns),
@@ -403,6 +407,7 @@ impl GenericSerialDriver {
     pub async fn transaction(&self, command: &str) -> Result<String> {
         let timeout = Duration::from_millis(self.config.connection.timeout_ms as u64);
         let mut port = self.port.lock().await;
+        let in_flight = self.resync.begin(&mut *port).await;
         port.write_all(command.as_bytes())
             .await
             .context("Failed to write command")?;
@@ -437,6 +442,10 @@ impl GenericSerialDriver {
                 }
             }
         }
+        // A device that has not answered yet may still do so
+        if !response_buf.is_empty() {
+            in_flight.finish();
+        }
         Ok(String::from_utf8(response_buf)
             .context("Invalid UTF-8")?
             .trim()
@@ -612,6 +621,18 @@ impl WavelengthTunable for GenericSerialDriver {
     }
 }
 
+#[async_trait]
+impl RawTerminal for GenericSerialDriver {
+    async fn send_raw(&self, command: &str, expect_response: bool) -> Result<String> {
+        if expect_response {
+            self.transaction(command).await
+        } else {
+            self.send_command(command).await?;
+            Ok(String::new())
+        }
+    }
+}
+
 #[async_trait]
 impl ShutterControl for GenericSerialDriver {
     async fn open_shutter(&self) -> Result<()> {
@@ -33,7 +33,7 @@ impl GenericSerialDriverFactory {
     pub fn new(config: InstrumentConfig) -> Self {
         let driver_type = config.device.protocol.to_lowercase();
         let name = config.device.name.clone();
-        let capabilities = config
+        let mut capabilities: Vec<CoreCapability> = config
             .device
             .capabilities
             .iter()
@@ -45,6 +45,8 @@ impl GenericSerialDriverFactory {
                 _ => None,
             })
             .collect();
+        // Every serial instrument accepts raw commands through the console
+        capabilities.push(CoreCapability::RawTerminal);
 
         Self {
             config,
@@ -114,7 +116,10 @@ impl DriverFactoryTrait for GenericSerialDriverFactory {
                 wavelength_tunable: Some(
                     driver_arc.clone() as Arc<dyn common::capabilities::WavelengthTunable>
                 ),
-                shutter_control: Some(driver_arc as Arc<dyn common::capabilities::ShutterControl>),
+                shutter_control: Some(
+                    driver_arc.clone() as Arc<dyn common::capabilities::ShutterControl>
+                ),
+                raw_terminal: Some(driver_arc as Arc<dyn common::capabilities::RawTerminal>),
                 ..Default::default()
             })
         })
@@ -573,6 +573,32 @@ mod driver_tests {
         harness_task.await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_cancelled_transaction_does_not_answer_the_next() {
+        let config = load_config(MINIMAL_CONFIG);
+        let (port, mut harness) = new_mock_serial();
+        let shared_port = create_shared_port(port);
+
+        let driver = GenericSerialDriver::new(config, shared_port, "2").unwrap();
+
+        // Cancel a status query before the device answers it
+        let cancelled = timeout(Duration::from_millis(20), driver.transaction("2gs")).await;
+        assert!(cancelled.is_err());
+        harness.expect_write(b"2gs").await;
+        harness.send_response(b"2GS00").unwrap();
+
+        let harness_task = tokio::spawn(async move {
+            harness.expect_and_respond(b"2gp", b"2PO00004600").await;
+            harness
+        });
+
+        // The late status reply is drained, not read as the position
+        let response = driver.transaction("2gp").await.unwrap();
+        assert_eq!(response, "2PO00004600");
+
+        harness_task.await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_send_command_no_response() {
         let config = load_config(MINIMAL_CONFIG);
@@ -11,6 +11,7 @@
 //! - [`MockLaser`] - Simulated tunable laser (MaiTai-like) with wavelength tuning and safety interlocks
 //! - [`MockRotator`] - Simulated rotary stage (ELL14-like) with velocity control
 //! - [`MockDAQOutput`] - Simulated analog output with voltage range validation
+//! - [`MockSignal`] - Noisy sine wave scalar channel
 //!
 //! # Performance Characteristics
 //!
@@ -20,6 +21,7 @@
 //! - MockLaser: 30s warmup, 690-1040nm tuning range, shutter-emission interlock
 //! - MockRotator: 0-360° range, velocity-dependent motion timing
 //! - MockDAQOutput: ±10V / ±5V / 0-10V / 0-5V ranges
+//! - MockSignal: no delay; value follows the wall clock
 //!
 //! # Driver Factory Pattern
 //!
@@ -45,6 +47,7 @@ mod mock_daq_output;
 mod mock_laser;
 mod mock_power_meter;
 mod mock_rotator;
+mod mock_signal;
 mod mock_stage;
 mod pattern;
 
@@ -57,10 +60,11 @@ pub use mock_daq_output::{MockDAQOutput, MockDAQOutputFactory, VoltageRange};
 pub use mock_laser::{MockLaser, MockLaserFactory};
 pub use mock_power_meter::{MockPowerMeter, MockPowerMeterFactory};
 pub use mock_rotator::{MockRotator, MockRotatorFactory};
+pub use mock_signal::{MockSignal, MockSignalConfig, MockSignalFactory};
 pub use mock_stage::{MockStage, MockStageFactory};
 
 // Re-export for convenience
-pub use pattern::generate_test_pattern;
+pub use pattern::{FramePattern, generate_beam_pattern, generate_test_pattern};
 
 /// Force the linker to include this crate's driver factory registrations.
 ///
@@ -82,6 +86,7 @@ pub fn link() {
     std::hint::black_box(std::any::TypeId::of::<MockLaser>());
     std::hint::black_box(std::any::TypeId::of::<MockRotator>());
     std::hint::black_box(std::any::TypeId::of::<MockDAQOutput>());
+    std::hint::black_box(std::any::TypeId::of::<MockSignal>());
 }
 
 /// Register all mock driver factories with a device registry.
@@ -104,6 +109,7 @@ pub fn register_all(registry: &impl FactoryRegistry) {
     registry.register_factory(Box::new(MockLaserFactory));
     registry.register_factory(Box::new(MockRotatorFactory));
     registry.register_factory(Box::new(MockDAQOutputFactory));
+    registry.register_factory(Box::new(MockSignalFactory));
 }
 
 /// Trait for registries that can accept driver factories.
@@ -1,6 +1,6 @@
 //! Mock camera implementation with trigger and streaming support.
 
-use crate::pattern::generate_test_pattern;
+use crate::pattern::FramePattern;
 // Import common infrastructure (bd-1gdn.2)
 use crate::common::{ErrorConfig, MockMode, MockRng, TimingConfig};
 use anyhow::{Result, anyhow};
@@ -11,6 +11,8 @@ use common::capabilities::{
 };
 use common::data::{Frame, FrameView};
 use common::driver::{Capability, DeviceComponents, DriverFactory};
+use common::integrity::{DropCounts, DropStage};
+use common::memory::{MemoryAccount, MemoryLedger, MemorySubsystem};
 use common::observable::ParameterSet;
 use common::parameter::Parameter;
 use futures::future::BoxFuture;
@@ -46,6 +48,15 @@ pub struct MockCameraConfig {
     /// Initial exposure in seconds (default: 0.033)
     #[serde(default = "default_exposure")]
     pub exposure_s: f64,
+
+    /// Frame content (default: test pattern)
+    #[serde(default)]
+    pub pattern: FramePattern,
+
+    /// Pace streamed frames at this rate, capped by exposure and the 33 ms
+    /// readout (default: no pacing, frames as fast as they are generated)
+    #[serde(default)]
+    pub frame_rate_hz: Option<f64>,
 }
 
 fn default_width() -> u32 {
@@ -64,6 +75,8 @@ impl Default for MockCameraConfig {
             width: 1920,
             height: 1080,
             exposure_s: 0.033,
+            pattern: FramePattern::default(),
+            frame_rate_hz: None,
         }
     }
 }
@@ -101,6 +114,9 @@ impl DriverFactory for MockCameraFactory {
         if cfg.exposure_s <= 0.0 {
             anyhow::bail!("Exposure must be positive");
         }
+        if cfg.frame_rate_hz.is_some_and(|fps| fps <= 0.0) {
+            anyhow::bail!("Frame rate must be positive");
+        }
         Ok(())
     }
 
@@ -137,6 +153,8 @@ pub struct FrameStatistics {
     pub discontinuity_events: u32,
     /// Frames dropped by consumer
     pub dropped_frames: u64,
+    /// Frames skipped because the frame pool was exhausted
+    pub pool_exhausted: u64,
     /// Last hardware frame number seen
     last_frame_nr: u64,
 }
@@ -219,6 +237,7 @@ pub struct MockCameraBuilder {
     shutter_close_delay_ms: u64,
     initial_temperature: f64,
     max_fps: f64,
+    pattern: FramePattern,
 }
 
 impl MockCameraBuilder {
@@ -234,6 +253,7 @@ impl MockCameraBuilder {
             shutter_close_delay_ms: 0,
             initial_temperature: 20.0,
             max_fps: 30.0,
+            pattern: FramePattern::default(),
         }
     }
 
@@ -273,6 +293,11 @@ impl MockCameraBuilder {
         self
     }
 
+    pub fn pattern(mut self, pattern: FramePattern) -> Self {
+        self.pattern = pattern;
+        self
+    }
+
     pub fn build(self) -> MockCamera {
         MockCamera::from_builder(self)
     }
@@ -317,6 +342,8 @@ pub struct MockCamera {
     staged_flag: Arc<AtomicBool>,
     primary_tx: Arc<Mutex<Option<tokio::sync::mpsc::Sender<LoanedFrame>>>>,
     frame_pool: Arc<Mutex<Option<Arc<Pool<FrameData>>>>>,
+    /// Buffers preallocated by `frame_pool`
+    frame_pool_memory: MemoryAccount,
     observers: ObserverRegistry,
     next_observer_id: AtomicU64,
     // New fields (bd-1gdn.2)
@@ -330,6 +357,7 @@ pub struct MockCamera {
     statistics: Arc<Mutex<FrameStatistics>>,
     rng: Arc<MockRng>,
     max_fps: f64,
+    pattern: FramePattern,
 }
 
 impl MockCamera {
@@ -360,6 +388,8 @@ impl MockCamera {
             width: builder.width,
             height: builder.height,
             exposure_s: 0.033,
+            pattern: builder.pattern,
+            frame_rate_hz: None,
         };
 
         Self::with_full_config(
@@ -446,6 +476,7 @@ impl MockCamera {
             let frame_pool_write = frame_pool.clone();
             let observers_write = observers_for_streaming.clone();
             let resolution = (config.width, config.height);
+            let pattern = config.pattern;
 
             // Clone these Arcs for the closure (originals kept for struct)
             let mode_for_streaming = mode;
@@ -526,7 +557,7 @@ impl MockCamera {
                                 }
 
                                 let (w, h) = res;
-                                let buffer = generate_test_pattern(w, h, frame_num);
+                                let buffer = pattern.render(w, h, frame_num);
 
                                 // Calculate actual frame delay based on exposure and max_fps
                                 let exposure_s = exposure_param.get();
@@ -573,19 +604,23 @@ impl MockCamera {
                                             frame_data.actual_len = byte_len;
                                         }
 
-                                        if p_tx.try_send(loaned_frame).is_err()
-                                            && frame_num.is_multiple_of(100)
-                                        {
+                                        if p_tx.try_send(loaned_frame).is_err() {
+                                            stats.lock().await.dropped_frames += 1;
+                                            if frame_num.is_multiple_of(100) {
+                                                tracing::warn!(
+                                                    "MockCamera: primary channel full at frame {}",
+                                                    frame_num
+                                                );
+                                            }
+                                        }
+                                    } else {
+                                        stats.lock().await.pool_exhausted += 1;
+                                        if frame_num.is_multiple_of(100) {
                                             tracing::warn!(
-                                                "MockCamera: primary channel full at frame {}",
+                                                "MockCamera: frame pool exhausted at frame {}",
                                                 frame_num
                                             );
                                         }
-                                    } else if frame_num.is_multiple_of(100) {
-                                        tracing::warn!(
-                                            "MockCamera: frame pool exhausted at frame {}",
-                                            frame_num
-                                        );
                                     }
                                 }
 
@@ -716,6 +751,8 @@ impl MockCamera {
             staged_flag,
             primary_tx,
             frame_pool,
+            frame_pool_memory: MemoryLedger::global()
+                .account("frame_pool:mock_camera", MemorySubsystem::Pool),
             observers,
             next_observer_id: AtomicU64::new(0),
             mode,
@@ -728,21 +765,27 @@ impl MockCamera {
             statistics,
             rng,
             max_fps,
+            pattern: config.pattern,
         }
     }
 
     /// Create mock camera with basic configuration (backward compatible).
     pub fn with_config(config: MockCameraConfig) -> Self {
+        // A frame rate switches to realistic timing, which paces the stream
+        let (mode, max_fps) = match config.frame_rate_hz {
+            Some(fps) => (MockMode::Realistic, fps),
+            None => (MockMode::Instant, 30.0),
+        };
         Self::with_full_config(
             config,
-            MockMode::Instant,
+            mode,
             0.0,
             ErrorConfig::none(),
             TimingConfig::camera(),
             0,
             0,
             20.0,
-            30.0,
+            max_fps,
         )
     }
 
@@ -813,7 +856,7 @@ impl Triggerable for MockCamera {
         sleep(Duration::from_millis(33)).await;
 
         let (w, h) = self.resolution;
-        let buffer = generate_test_pattern(w, h, count);
+        let buffer = self.pattern.render(w, h, count);
         let frame = Arc::new(Frame::from_u16(w, h, &buffer));
 
         let _ = self.frame_tx.send(frame);
@@ -876,6 +919,15 @@ impl FrameProducer for MockCamera {
         self.frame_count.load(Ordering::SeqCst)
     }
 
+    async fn drop_counts(&self) -> DropCounts {
+        let stats = self.statistics.lock().await;
+        DropCounts::from([
+            (DropStage::Sdk, stats.lost_frames),
+            (DropStage::Pool, stats.pool_exhausted),
+            (DropStage::Delivery, stats.dropped_frames),
+        ])
+    }
+
     #[allow(deprecated)]
     async fn subscribe_frames(&self) -> Option<tokio::sync::broadcast::Receiver<Arc<Frame>>> {
         tracing::warn!(
@@ -905,6 +957,8 @@ impl FrameProducer for MockCamera {
         );
 
         *self.frame_pool.lock().await = Some(pool);
+        self.frame_pool_memory
+            .set((MOCK_FRAME_POOL_SIZE * frame_bytes) as u64);
         *self.primary_tx.lock().await = Some(tx);
         Ok(())
     }
@@ -1269,6 +1323,11 @@ mod tests {
 
         let stats = camera.statistics().await;
         assert!(stats.total_frames > 0, "Should have captured some frames");
+        assert_eq!(
+            camera.drop_counts().await[&DropStage::Sdk],
+            stats.lost_frames,
+            "Simulated losses are reported as SDK drops"
+        );
 
         // With 50% loss rate, we expect some discontinuities
         // (but allow for random chance with small sample)
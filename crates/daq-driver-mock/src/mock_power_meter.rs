@@ -6,6 +6,10 @@
 //! - Noise model (shot + thermal components)
 //! - Filter/integration time simulation
 //! - Attenuator simulation (10/20/30 dB)
+//! - Auto-range: a power change that crosses a decade during the integration
+//!   window flags the reading [`DataQuality::Suspect`]; every range change
+//!   is reported as a [`DeviceEventKind::AutoRange`] event
+//! - Native `average:N` acquisition (N samples in one integration window)
 //!
 //! # Example
 //!
@@ -29,13 +33,16 @@
 use crate::common::{ErrorConfig, MockMode, MockRng};
 use anyhow::Result;
 use async_trait::async_trait;
+use common::acquisition::{AcquiredValue, AcquisitionMode};
 use common::capabilities::{Parameterized, Readable};
+use common::core::DataQuality;
+use common::device_events::{DeviceEvent, DeviceEventKind, DeviceEventSeverity, DeviceEventSink};
 use common::driver::{Capability, DeviceComponents, DriverFactory};
 use common::observable::ParameterSet;
 use common::parameter::Parameter;
 use futures::future::BoxFuture;
 use serde::Deserialize;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 // =============================================================================
 // MockPowerMeterFactory - DriverFactory implementation
@@ -92,6 +99,7 @@ impl DriverFactory for MockPowerMeterFactory {
 
             Ok(DeviceComponents {
                 readable: Some(meter.clone()),
+                events: Some(meter.events()),
                 parameterized: Some(meter),
                 ..Default::default()
             })
@@ -357,6 +365,10 @@ pub struct MockPowerMeter {
     rng: Arc<MockRng>,
     mode: MockMode,
     error_config: ErrorConfig,
+    /// Decade of the last reading
+    range: Arc<Mutex<Option<i32>>>,
+    /// Auto-range changes
+    events: DeviceEventSink,
 }
 
 impl MockPowerMeter {
@@ -390,6 +402,8 @@ impl MockPowerMeter {
             rng: Arc::new(MockRng::new(None)),
             mode: MockMode::default(),
             error_config: ErrorConfig::default(),
+            range: Arc::new(Mutex::new(None)),
+            events: DeviceEventSink::new(),
         }
     }
 
@@ -398,6 +412,11 @@ impl MockPowerMeter {
         MockPowerMeterBuilder::default()
     }
 
+    /// Where auto-range changes are reported
+    pub fn events(&self) -> DeviceEventSink {
+        self.events.clone()
+    }
+
     /// Set the base power reading.
     pub async fn set_base_power(&self, power: f64) -> Result<()> {
         self.base_power.set(power).await
@@ -492,10 +511,39 @@ impl Default for MockPowerMeter {
 #[async_trait]
 impl Readable for MockPowerMeter {
     async fn read(&self) -> Result<f64> {
+        self.read_qualified().await.map(|(reading, _)| reading)
+    }
+
+    async fn read_qualified(&self) -> Result<(f64, DataQuality)> {
+        self.integrate(1).await
+    }
+
+    async fn read_native(&self, mode: AcquisitionMode) -> Result<Option<AcquiredValue>> {
+        // Like the 1830-C's internal averaging: N samples in one integration
+        let AcquisitionMode::Average { samples } = mode else {
+            return Ok(None);
+        };
+        let (value, quality) = self.integrate(samples).await?;
+        Ok(Some(AcquiredValue {
+            value,
+            quality,
+            mode,
+            samples,
+            native: true,
+        }))
+    }
+}
+
+impl MockPowerMeter {
+    /// One integration window, averaging `samples` noisy samples
+    async fn integrate(&self, samples: u32) -> Result<(f64, DataQuality)> {
         // Check for injected errors
         self.error_config
             .check_operation("mock_power_meter", "read")?;
 
+        // The real meter auto-ranges by decade
+        let range_at_start = power_decade(self.base_power.get());
+
         // Simulate integration time delay
         if self.filter.integration_time_ms() > 0 && self.mode == MockMode::Realistic {
             tokio::time::sleep(tokio::time::Duration::from_millis(
@@ -506,13 +554,24 @@ impl Readable for MockPowerMeter {
 
         // Get base power
         let base = self.base_power.get();
+        let range = power_decade(base);
+        let quality = if range == range_at_start {
+            DataQuality::Good
+        } else {
+            DataQuality::Suspect
+        };
+        self.update_range(range);
 
         // Apply wavelength-dependent correction
         let correction = self.spectral_response.correction_factor(self.wavelength_nm);
         let corrected = base * correction;
 
         // Apply noise
-        let noisy = self.noise_model.apply_noise(corrected, &self.rng);
+        let samples = samples.max(1);
+        let noisy = (0..samples)
+            .map(|_| self.noise_model.apply_noise(corrected, &self.rng))
+            .sum::<f64>()
+            / f64::from(samples);
 
         // Apply attenuation
         let attenuated = noisy * self.attenuator.factor();
@@ -520,10 +579,36 @@ impl Readable for MockPowerMeter {
         // Convert to selected unit
         let reading = self.convert_to_unit(attenuated);
 
-        Ok(reading)
+        Ok((reading, quality))
     }
 }
 
+impl MockPowerMeter {
+    /// Track the range in use, reporting changes
+    fn update_range(&self, range: Option<i32>) {
+        let previous = {
+            let mut current = self.range.lock().unwrap_or_else(|p| p.into_inner());
+            std::mem::replace(&mut *current, range)
+        };
+        if let Some((from, to)) = previous.zip(range).filter(|(from, to)| from != to) {
+            self.events.emit(
+                DeviceEvent::new(
+                    DeviceEventKind::AutoRange,
+                    DeviceEventSeverity::Info,
+                    format!("Range 1e{} W -> 1e{} W", from, to),
+                )
+                .with("from", from)
+                .with("to", to),
+            );
+        }
+    }
+}
+
+/// Auto-range decade of a power in Watts (`None` for zero/negative power)
+fn power_decade(watts: f64) -> Option<i32> {
+    (watts > 0.0).then(|| watts.log10().floor() as i32)
+}
+
 // =============================================================================
 // Builder Pattern
 // =============================================================================
@@ -643,6 +728,8 @@ impl MockPowerMeterBuilder {
             rng: Arc::new(MockRng::new(self.rng_seed)),
             mode: self.mode,
             error_config: self.error_config,
+            range: Arc::new(Mutex::new(None)),
+            events: DeviceEventSink::new(),
         }
     }
 }
@@ -893,6 +980,73 @@ mod tests {
         assert!((meter_dbm.convert_to_unit(1e-3) - 0.0).abs() < 0.001);
     }
 
+    #[tokio::test]
+    async fn test_range_change_mid_sample_is_suspect() {
+        let meter = MockPowerMeter::builder()
+            .base_power(1.0)
+            .noise_model(NoiseModel::none())
+            .filter(FilterSetting::Fast)
+            .mode(MockMode::Realistic)
+            .build();
+
+        let (reading, quality) = meter.read_qualified().await.unwrap();
+        assert!((reading - 1.0).abs() < 1e-9);
+        assert_eq!(quality, DataQuality::Good);
+
+        // Drop two decades while the meter is integrating
+        let (result, set) = tokio::join!(meter.read_qualified(), meter.set_base_power(0.05));
+        set.unwrap();
+        let (reading, quality) = result.unwrap();
+        assert!((reading - 0.05).abs() < 1e-9);
+        assert_eq!(quality, DataQuality::Suspect);
+    }
+
+    #[tokio::test]
+    async fn test_range_changes_emit_events() {
+        let meter = MockPowerMeter::builder()
+            .base_power(1.0)
+            .noise_model(NoiseModel::none())
+            .build();
+        let (tx, mut rx) = tokio::sync::broadcast::channel(8);
+        meter.events().attach("power_meter", tx);
+
+        meter.read().await.unwrap();
+        meter.set_base_power(2.0).await.unwrap();
+        meter.read().await.unwrap();
+        assert!(rx.try_recv().is_err());
+
+        meter.set_base_power(0.05).await.unwrap();
+        meter.read().await.unwrap();
+        let event = rx.try_recv().unwrap();
+        assert_eq!(event.kind, DeviceEventKind::AutoRange);
+        assert_eq!(event.payload["from"], 0);
+        assert_eq!(event.payload["to"], -2);
+    }
+
+    #[tokio::test]
+    async fn test_native_average() {
+        let meter = MockPowerMeter::builder()
+            .base_power(1.0e-3)
+            .noise_model(NoiseModel::none())
+            .build();
+
+        let avg = meter
+            .read_with_mode(AcquisitionMode::Average { samples: 8 })
+            .await
+            .unwrap();
+        assert!(avg.native);
+        assert_eq!(avg.samples, 8);
+        assert!((avg.value - 1.0e-3).abs() < 1e-12);
+
+        // Peak-hold isn't native; the core builds it from reads
+        let peak = meter
+            .read_with_mode(AcquisitionMode::Peak { window_ms: 2 })
+            .await
+            .unwrap();
+        assert!(!peak.native);
+        assert!((peak.value - 1.0e-3).abs() < 1e-12);
+    }
+
     impl Clone for MockPowerMeter {
         fn clone(&self) -> Self {
             MockPowerMeter::builder()
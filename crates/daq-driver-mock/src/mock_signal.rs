@@ -0,0 +1,241 @@
+//! Mock signal source: a noisy sine wave read as a scalar channel.
+//!
+//! Stands in for a photodiode, lock-in output or any other analog channel
+//! when developing displays. The value follows the wall clock, so plots
+//! show the same waveform whatever rate the channel is sampled at:
+//!
+//! ```text
+//! value = offset + amplitude * sin(2π * frequency_hz * t + phase) + noise
+//! ```
+//!
+//! The noise is uniform in `±noise`. Amplitude, frequency, offset and noise
+//! are parameters and can be changed while the signal is being read.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use daq_driver_mock::{MockSignal, MockSignalConfig};
+//!
+//! let signal = MockSignal::new(MockSignalConfig {
+//!     frequency_hz: 2.0,
+//!     ..Default::default()
+//! });
+//! let value = signal.read().await?;
+//! ```
+
+use crate::common::MockRng;
+use anyhow::Result;
+use async_trait::async_trait;
+use common::capabilities::{Parameterized, Readable};
+use common::driver::{Capability, DeviceComponents, DriverFactory};
+use common::observable::ParameterSet;
+use common::parameter::Parameter;
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Instant;
+
+// =============================================================================
+// MockSignalFactory - DriverFactory implementation
+// =============================================================================
+
+/// Configuration for MockSignal driver
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockSignalConfig {
+    /// Peak amplitude (default: 1.0)
+    #[serde(default = "default_amplitude")]
+    pub amplitude: f64,
+
+    /// Sine frequency in Hz (default: 0.5)
+    #[serde(default = "default_frequency")]
+    pub frequency_hz: f64,
+
+    /// Constant offset (default: 0.0)
+    #[serde(default)]
+    pub offset: f64,
+
+    /// Phase in degrees (default: 0.0)
+    #[serde(default)]
+    pub phase_deg: f64,
+
+    /// Uniform noise half-width (default: 0.05)
+    #[serde(default = "default_noise")]
+    pub noise: f64,
+
+    /// Unit reported for the channel (default: "V")
+    #[serde(default = "default_unit")]
+    pub unit: String,
+}
+
+fn default_amplitude() -> f64 {
+    1.0
+}
+fn default_frequency() -> f64 {
+    0.5
+}
+fn default_noise() -> f64 {
+    0.05
+}
+fn default_unit() -> String {
+    "V".to_string()
+}
+
+impl Default for MockSignalConfig {
+    fn default() -> Self {
+        Self {
+            amplitude: 1.0,
+            frequency_hz: 0.5,
+            offset: 0.0,
+            phase_deg: 0.0,
+            noise: 0.05,
+            unit: default_unit(),
+        }
+    }
+}
+
+/// Factory for creating MockSignal instances.
+pub struct MockSignalFactory;
+
+/// Static capabilities for MockSignal
+static MOCK_SIGNAL_CAPABILITIES: &[Capability] = &[Capability::Readable, Capability::Parameterized];
+
+impl DriverFactory for MockSignalFactory {
+    fn driver_type(&self) -> &'static str {
+        "mock_signal"
+    }
+
+    fn name(&self) -> &'static str {
+        "Mock Signal"
+    }
+
+    fn capabilities(&self) -> &'static [Capability] {
+        MOCK_SIGNAL_CAPABILITIES
+    }
+
+    fn validate(&self, config: &toml::Value) -> Result<()> {
+        let cfg: MockSignalConfig = config.clone().try_into()?;
+        if cfg.frequency_hz < 0.0 || cfg.noise < 0.0 {
+            anyhow::bail!("Frequency and noise must not be negative");
+        }
+        Ok(())
+    }
+
+    fn build(&self, config: toml::Value) -> BoxFuture<'static, Result<DeviceComponents>> {
+        Box::pin(async move {
+            let cfg: MockSignalConfig = config.try_into().unwrap_or_default();
+
+            let signal = Arc::new(MockSignal::new(cfg));
+
+            Ok(DeviceComponents {
+                readable: Some(signal.clone()),
+                parameterized: Some(signal),
+                ..Default::default()
+            })
+        })
+    }
+}
+
+// =============================================================================
+// MockSignal - Simulated analog channel
+// =============================================================================
+
+/// Noisy sine wave source
+pub struct MockSignal {
+    amplitude: Parameter<f64>,
+    frequency_hz: Parameter<f64>,
+    offset: Parameter<f64>,
+    noise: Parameter<f64>,
+    phase_rad: f64,
+    params: ParameterSet,
+    started: Instant,
+    rng: Arc<MockRng>,
+}
+
+impl MockSignal {
+    pub fn new(config: MockSignalConfig) -> Self {
+        let unit = config.unit.as_str();
+        let amplitude = Parameter::new("amplitude", config.amplitude)
+            .with_description("Peak amplitude of the sine")
+            .with_unit(unit);
+        let frequency_hz = Parameter::new("frequency_hz", config.frequency_hz)
+            .with_description("Sine frequency")
+            .with_unit("Hz")
+            .with_range(0.0, 1000.0);
+        let offset = Parameter::new("offset", config.offset)
+            .with_description("Constant offset")
+            .with_unit(unit);
+        let noise = Parameter::new("noise", config.noise)
+            .with_description("Uniform noise half-width")
+            .with_unit(unit);
+
+        let mut params = ParameterSet::new();
+        params.register(amplitude.clone());
+        params.register(frequency_hz.clone());
+        params.register(offset.clone());
+        params.register(noise.clone());
+
+        Self {
+            amplitude,
+            frequency_hz,
+            offset,
+            noise,
+            phase_rad: config.phase_deg.to_radians(),
+            params,
+            started: Instant::now(),
+            rng: Arc::new(MockRng::new(None)),
+        }
+    }
+
+    /// Noise-free value `t` seconds after the signal was created
+    pub fn value_at(&self, t: f64) -> f64 {
+        let phase = 2.0 * std::f64::consts::PI * self.frequency_hz.get() * t + self.phase_rad;
+        self.offset.get() + self.amplitude.get() * phase.sin()
+    }
+}
+
+impl Parameterized for MockSignal {
+    fn parameters(&self) -> &ParameterSet {
+        &self.params
+    }
+}
+
+#[async_trait]
+impl Readable for MockSignal {
+    async fn read(&self) -> Result<f64> {
+        let value = self.value_at(self.started.elapsed().as_secs_f64());
+        let noise = self.noise.get();
+        if noise > 0.0 {
+            Ok(value + self.rng.gen_range(-noise..noise))
+        } else {
+            Ok(value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_signal_follows_configured_sine() {
+        let signal = MockSignal::new(MockSignalConfig {
+            amplitude: 2.0,
+            frequency_hz: 1.0,
+            offset: 0.5,
+            phase_deg: 90.0,
+            noise: 0.1,
+            ..Default::default()
+        });
+        assert!((signal.value_at(0.0) - 2.5).abs() < 1e-9);
+        assert!((signal.value_at(0.5) + 1.5).abs() < 1e-9);
+
+        for _ in 0..100 {
+            let value = signal.read().await.unwrap();
+            assert!((-1.6..=2.6).contains(&value), "{}", value);
+        }
+
+        signal.amplitude.set(0.0).await.unwrap();
+        signal.noise.set(0.0).await.unwrap();
+        assert!((signal.read().await.unwrap() - 0.5).abs() < 1e-9);
+    }
+}
@@ -3,7 +3,10 @@
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use common::capabilities::{Movable, Parameterized};
-use common::driver::{Capability, DeviceComponents, DriverFactory};
+use common::device_events::{DeviceEvent, DeviceEventKind, DeviceEventSeverity};
+use common::driver::{
+    Capability, DeviceComponents, DeviceStatus, DeviceStatusHandle, DriverFactory,
+};
 use common::observable::ParameterSet;
 use common::parameter::Parameter;
 use futures::future::BoxFuture;
@@ -76,6 +79,7 @@ impl DriverFactory for MockStageFactory {
 
             Ok(DeviceComponents {
                 movable: Some(stage.clone()),
+                status: Some(stage.status()),
                 parameterized: Some(stage),
                 ..Default::default()
             })
@@ -125,6 +129,16 @@ impl StageLimits {
         }
     }
 
+    /// Limit switch (`"min"` or `"max"`) a move to `target` would hit
+    fn switch_hit(&self, target: f64) -> Option<&'static str> {
+        match self.behavior {
+            LimitBehavior::Ignore => None,
+            _ if target < self.min_position => Some("min"),
+            _ if target > self.max_position => Some("max"),
+            _ => None,
+        }
+    }
+
     /// Check and enforce limits on a target position
     fn enforce(&self, target: f64) -> Result<f64> {
         match self.behavior {
@@ -274,6 +288,8 @@ pub struct MockStage {
     error_config: ErrorConfig,
     /// Parameter set
     params: ParameterSet,
+    /// Reported status (busy while moving)
+    status: DeviceStatusHandle,
 }
 
 impl Clone for MockStage {
@@ -293,6 +309,7 @@ impl Clone for MockStage {
                 params.register(self.position.clone());
                 params
             },
+            status: self.status.clone(),
         }
     }
 }
@@ -337,6 +354,11 @@ impl MockStage {
             .build()
     }
 
+    /// Status handle shared with the device registry
+    pub fn status(&self) -> DeviceStatusHandle {
+        self.status.clone()
+    }
+
     /// Create a builder for configuring MockStage
     pub fn builder() -> MockStageBuilder {
         MockStageBuilder::new()
@@ -385,6 +407,7 @@ impl MockStage {
     pub async fn stop(&self) -> Result<()> {
         let mut state = self.state.write().await;
         state.is_moving = false;
+        self.status.set(DeviceStatus::Ready);
         tracing::debug!("MockStage: Emergency stop");
         Ok(())
     }
@@ -438,6 +461,26 @@ impl Movable for MockStage {
 
         // Enforce limits
         let target = if let Some(limits) = &self.limits {
+            if let Some(limit) = limits.switch_hit(target) {
+                self.status.events().emit(
+                    DeviceEvent::new(
+                        DeviceEventKind::LimitSwitch,
+                        DeviceEventSeverity::Warning,
+                        format!("{} limit hit moving to {:.2}mm", limit, target),
+                    )
+                    .with("limit", limit)
+                    .with("target", target)
+                    .with(
+                        "position",
+                        if limit == "min" {
+                            limits.min_position
+                        } else {
+                            limits.max_position
+                        },
+                    )
+                    .with("stopped", limits.behavior == LimitBehavior::HardStop),
+                );
+            }
             limits.enforce(target)?
         } else {
             target
@@ -458,6 +501,7 @@ impl Movable for MockStage {
             let mut state = self.state.write().await;
             state.is_moving = true;
         }
+        self.status.set(DeviceStatus::Busy);
 
         // Simulate motion time
         let motion_duration = self.calculate_motion_duration(distance);
@@ -471,6 +515,7 @@ impl Movable for MockStage {
             state.position = target;
             state.is_moving = false;
         }
+        self.status.set(DeviceStatus::Ready);
 
         // Update parameter (triggers callbacks)
         self.position.set(target).await?;
@@ -501,6 +546,10 @@ impl Movable for MockStage {
 
         Ok(())
     }
+
+    async fn stop(&self) -> Result<()> {
+        MockStage::stop(self).await
+    }
 }
 
 // =============================================================================
@@ -608,6 +657,7 @@ impl MockStageBuilder {
             mode: self.mode,
             error_config: self.error_config,
             params,
+            status: DeviceStatusHandle::default(),
         }
     }
 }
@@ -672,6 +722,21 @@ mod tests {
         assert_eq!(stage.position().await.unwrap(), 20.0);
     }
 
+    #[tokio::test]
+    async fn test_mock_stage_reports_busy_while_moving() {
+        let stage = MockStage::builder().mode(MockMode::Realistic).build();
+        let status = stage.status();
+        assert_eq!(status.get(), DeviceStatus::Ready);
+
+        let moving = stage.clone();
+        let motion = tokio::spawn(async move { moving.move_abs(5.0).await });
+        sleep(Duration::from_millis(20)).await;
+        assert_eq!(status.get(), DeviceStatus::Busy);
+
+        motion.await.unwrap().unwrap();
+        assert_eq!(status.get(), DeviceStatus::Ready);
+    }
+
     #[tokio::test]
     async fn test_mock_stage_parameter_set_moves_stage() {
         let stage = MockStage::new();
@@ -724,6 +789,26 @@ mod tests {
         assert_eq!(stage.position().await.unwrap(), 50.0);
     }
 
+    #[tokio::test]
+    async fn test_limit_hits_emit_events() {
+        let stage = MockStage::builder()
+            .limits(StageLimits::clamp(0.0, 100.0))
+            .build();
+        let (tx, mut rx) = tokio::sync::broadcast::channel(8);
+        stage.status().events().attach("stage", tx);
+
+        stage.move_abs(50.0).await.unwrap();
+        stage.move_abs(150.0).await.unwrap();
+
+        let event = rx.try_recv().unwrap();
+        assert_eq!(event.kind, DeviceEventKind::LimitSwitch);
+        assert_eq!(event.device_id, "stage");
+        assert_eq!(event.payload["limit"], "max");
+        assert_eq!(event.payload["position"], 100.0);
+        assert_eq!(event.payload["stopped"], false);
+        assert!(rx.try_recv().is_err());
+    }
+
     #[tokio::test]
     async fn test_homing() {
         let stage = MockStage::builder().build();
@@ -1,5 +1,7 @@
 //! Test pattern generation for mock camera frames.
 
+use serde::Deserialize;
+
 /// Simple pseudo-random number generator (LCG) for reproducible noise.
 /// Uses the same algorithm as glibc for predictable cross-platform behavior.
 #[inline]
@@ -218,6 +220,73 @@ pub fn generate_test_pattern(width: u32, height: u32, frame_num: u64) -> Vec<u16
     buffer
 }
 
+/// Image content of mock camera frames
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FramePattern {
+    /// Diagnostic pattern ([`generate_test_pattern`])
+    #[default]
+    TestPattern,
+    /// Drifting laser spot ([`generate_beam_pattern`])
+    GaussianBeam,
+}
+
+impl FramePattern {
+    /// Render frame `frame_num`
+    pub fn render(self, width: u32, height: u32, frame_num: u64) -> Vec<u16> {
+        match self {
+            Self::TestPattern => generate_test_pattern(width, height, frame_num),
+            Self::GaussianBeam => generate_beam_pattern(width, height, frame_num),
+        }
+    }
+}
+
+/// Generates a laser beam profile for beam-analysis and drift displays.
+///
+/// The frame holds an elliptical Gaussian spot on a dark, noisy background:
+/// - The centroid wanders slowly (two incommensurate sinusoids, a few
+///   percent of the frame over minutes at 30 fps) with ~1 pixel pointing jitter
+/// - The spot size breathes by ±5% and the peak intensity flickers by ±3%
+///
+/// Deterministic in `frame_num`, like [`generate_test_pattern`].
+pub fn generate_beam_pattern(width: u32, height: u32, frame_num: u64) -> Vec<u16> {
+    let w = width as usize;
+    let h = height as usize;
+    let mut buffer = vec![0u16; w * h];
+    let t = frame_num as f64;
+    let size = width.min(height) as f64;
+
+    // Slow drift plus frame-to-frame pointing jitter
+    let jitter_seed = prng(frame_num.wrapping_mul(2654435761));
+    let jitter_x = ((jitter_seed & 0xFF) as f64 / 255.0 - 0.5) * 2.0;
+    let jitter_y = (((jitter_seed >> 8) & 0xFF) as f64 / 255.0 - 0.5) * 2.0;
+    let center_x = width as f64 / 2.0
+        + size * (0.08 * (t * 0.0031).sin() + 0.03 * (t * 0.017).sin())
+        + jitter_x;
+    let center_y = height as f64 / 2.0
+        + size * (0.06 * (t * 0.0023).cos() + 0.02 * (t * 0.013).sin())
+        + jitter_y;
+
+    let sigma = (size / 12.0).max(1.0) * (1.0 + 0.05 * (t * 0.021).sin());
+    let (sigma_x, sigma_y) = (sigma * 1.2, sigma);
+    let peak = 40000.0 * (1.0 + 0.03 * (t * 0.11).sin());
+    let background = 1000.0;
+
+    let frame_seed = frame_num.wrapping_mul(2654435761);
+    for y in 0..h {
+        let dy = (y as f64 - center_y) / sigma_y;
+        for x in 0..w {
+            let idx = y * w + x;
+            let dx = (x as f64 - center_x) / sigma_x;
+            let spot = peak * (-0.5 * (dx * dx + dy * dy)).exp();
+            let noise = (prng(frame_seed ^ (idx as u64)) & 0x3FF) as f64 - 512.0;
+            buffer[idx] = (background + spot + noise).clamp(0.0, 65535.0) as u16;
+        }
+    }
+
+    buffer
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -241,4 +310,22 @@ mod tests {
         // Patterns should differ due to noise and dynamic elements
         assert_ne!(buffer1, buffer2);
     }
+
+    #[test]
+    fn test_beam_centroid_drifts() {
+        let centroid = |buffer: &[u16]| {
+            let (mut sum, mut sum_x) = (0.0, 0.0);
+            for (idx, &pixel) in buffer.iter().enumerate() {
+                let value = f64::from(pixel.saturating_sub(2000));
+                sum += value;
+                sum_x += value * (idx % 128) as f64;
+            }
+            sum_x / sum
+        };
+        let start = generate_beam_pattern(128, 128, 0);
+        let later = generate_beam_pattern(128, 128, 300);
+        assert_eq!(start.len(), 128 * 128);
+        assert!((centroid(&start) - 64.0).abs() < 2.0);
+        assert!((centroid(&later) - centroid(&start)).abs() > 2.0);
+    }
 }
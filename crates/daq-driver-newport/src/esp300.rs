@@ -27,12 +27,12 @@
 
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
-use common::capabilities::{Movable, Parameterized};
+use common::capabilities::{Movable, Parameterized, RawTerminal};
 use common::driver::{Capability, DeviceComponents, DriverFactory};
 use common::error::DaqError;
 use common::observable::ParameterSet;
 use common::parameter::Parameter;
-use common::serial::{open_serial_async, wrap_shared, SharedPort};
+use common::serial::{open_serial_async, wrap_shared, PortResync, SharedPort};
 use futures::future::BoxFuture;
 use serde::Deserialize;
 use std::sync::Arc;
@@ -60,7 +60,11 @@ pub struct Esp300Config {
 pub struct Esp300Factory;
 
 /// Static capabilities for ESP300
-static ESP300_CAPABILITIES: &[Capability] = &[Capability::Movable, Capability::Parameterized];
+static ESP300_CAPABILITIES: &[Capability] = &[
+    Capability::Movable,
+    Capability::Parameterized,
+    Capability::RawTerminal,
+];
 
 impl DriverFactory for Esp300Factory {
     fn driver_type(&self) -> &'static str {
@@ -94,7 +98,8 @@ impl DriverFactory for Esp300Factory {
 
             Ok(DeviceComponents {
                 movable: Some(driver.clone()),
-                parameterized: Some(driver),
+                parameterized: Some(driver.clone()),
+                raw_terminal: Some(driver),
                 ..Default::default()
             })
         })
@@ -112,6 +117,8 @@ impl DriverFactory for Esp300Factory {
 pub struct Esp300Driver {
     /// Serial port protected by Mutex for exclusive access
     port: SharedPort,
+    /// Drains replies left by interrupted queries
+    resync: PortResync,
     /// Axis number (1-3)
     axis: u8,
     /// Command timeout duration
@@ -205,6 +212,7 @@ impl Esp300Driver {
 
         Self {
             port,
+            resync: PortResync::new(),
             axis,
             timeout,
             position_mm: position,
@@ -271,6 +279,7 @@ impl Esp300Driver {
     /// Send query and read response
     async fn query(&self, command: &str) -> Result<String> {
         let mut port = self.port.lock().await;
+        let in_flight = self.resync.begin(&mut *port).await;
 
         let cmd = format!("{}\r\n", command);
         let writer = port.get_mut();
@@ -285,6 +294,7 @@ impl Esp300Driver {
             .await
             .context("ESP300 read timeout")?
             .context("ESP300 read error")?;
+        in_flight.finish();
 
         Ok(response.trim().to_string())
     }
@@ -318,6 +328,18 @@ impl Parameterized for Esp300Driver {
     }
 }
 
+#[async_trait]
+impl RawTerminal for Esp300Driver {
+    async fn send_raw(&self, command: &str, expect_response: bool) -> Result<String> {
+        if expect_response {
+            self.query(command).await
+        } else {
+            self.send_command(command).await?;
+            Ok(String::new())
+        }
+    }
+}
+
 #[async_trait]
 impl Movable for Esp300Driver {
     #[instrument(skip(self), fields(axis = self.axis, position), err)]
@@ -430,4 +452,29 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn cancelled_query_does_not_answer_the_next() -> Result<()> {
+        let (mut host, device) = tokio::io::duplex(64);
+        let port: SharedPort = Arc::new(Mutex::new(BufReader::new(Box::new(device))));
+        let driver = Esp300Driver::with_test_port(port, 1);
+
+        // Cancel a position read before the controller answers it
+        let cancelled = tokio::time::timeout(Duration::from_millis(20), driver.position()).await;
+        assert!(cancelled.is_err());
+        let mut buf = vec![0u8; 64];
+        let _n = host.read(&mut buf).await?;
+        host.write_all(b"5.000000\r\n").await?;
+
+        let responder = tokio::spawn(async move {
+            let n = host.read(&mut buf).await.unwrap();
+            assert_eq!(&buf[..n], b"1TP?\r\n");
+            host.write_all(b"7.000000\r\n").await.unwrap();
+            host
+        });
+        assert_eq!(driver.position().await?, 7.0);
+        responder.await?;
+
+        Ok(())
+    }
 }
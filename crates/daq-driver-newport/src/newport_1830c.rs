@@ -36,7 +36,8 @@
 
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
-use common::capabilities::{Parameterized, Readable, WavelengthTunable};
+use common::capabilities::{Parameterized, RawTerminal, Readable, WavelengthTunable};
+use common::core::DataQuality;
 use common::driver::{Capability, DeviceComponents, DriverFactory};
 use common::error::DaqError;
 use common::observable::ParameterSet;
@@ -71,6 +72,7 @@ static NEWPORT_1830C_CAPABILITIES: &[Capability] = &[
     Capability::Readable,
     Capability::WavelengthTunable,
     Capability::Parameterized,
+    Capability::RawTerminal,
 ];
 
 impl DriverFactory for Newport1830CFactory {
@@ -112,7 +114,8 @@ impl DriverFactory for Newport1830CFactory {
             Ok(DeviceComponents {
                 readable: Some(driver.clone()),
                 wavelength_tunable: Some(driver.clone()),
-                parameterized: Some(driver),
+                parameterized: Some(driver.clone()),
+                raw_terminal: Some(driver),
                 ..Default::default()
             })
         })
@@ -337,12 +340,16 @@ impl Newport1830CDriver {
     ///
     /// Re-asserts Watts mode (U1) before each read to handle potential
     /// front-panel changes that could switch units unexpectedly.
-    async fn query_power(&self) -> Result<f64> {
+    ///
+    /// A reading that only arrived after a timed-out or garbled attempt is
+    /// flagged [`DataQuality::Suspect`]: the meter may have integrated across
+    /// the retry backoff.
+    async fn query_power(&self) -> Result<(f64, DataQuality)> {
         // Re-assert Watts mode before reading to handle front-panel changes.
         // This adds ~100ms overhead but ensures consistent scientific notation format.
         self.send_config_command("U1").await?;
 
-        let response = self.query("D?").await?;
+        let (response, attempts) = self.query_with_attempts("D?").await?;
         let power = self.parse_power_response(&response)?;
 
         // Log with magnitude classification for debugging wild swings
@@ -366,7 +373,13 @@ impl Newport1830CDriver {
             response
         );
 
-        Ok(power)
+        let quality = if attempts > 1 {
+            tracing::debug!(attempts, "Newport 1830-C: power read needed retries");
+            DataQuality::Suspect
+        } else {
+            DataQuality::Good
+        };
+        Ok((power, quality))
     }
 
     /// Parse wavelength response (4-digit nm format)
@@ -426,6 +439,13 @@ impl Newport1830CDriver {
     ///
     /// Wraps `query_once` with up to 3 retries and linear backoff.
     async fn query(&self, command: &str) -> Result<String> {
+        self.query_with_attempts(command)
+            .await
+            .map(|(response, _)| response)
+    }
+
+    /// [`Self::query`], also returning how many attempts it took
+    async fn query_with_attempts(&self, command: &str) -> Result<(String, u32)> {
         const MAX_RETRIES: u32 = 3;
         const BASE_BACKOFF_MS: u64 = 100;
 
@@ -444,7 +464,7 @@ impl Newport1830CDriver {
             }
 
             match self.query_once(command).await {
-                Ok(resp) => return Ok(resp),
+                Ok(resp) => return Ok((resp, attempt + 1)),
                 Err(e) => {
                     tracing::debug!(
                         cmd = %command,
@@ -628,10 +648,28 @@ impl Parameterized for Newport1830CDriver {
     }
 }
 
+#[async_trait]
+impl RawTerminal for Newport1830CDriver {
+    async fn send_raw(&self, command: &str, expect_response: bool) -> Result<String> {
+        // Single attempt: a raw command may not be safe to repeat
+        if expect_response {
+            self.query_once(command).await
+        } else {
+            self.send_config_command(command).await?;
+            Ok(String::new())
+        }
+    }
+}
+
 #[async_trait]
 impl Readable for Newport1830CDriver {
     #[instrument(skip(self), err)]
     async fn read(&self) -> Result<f64> {
+        self.query_power().await.map(|(power, _)| power)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn read_qualified(&self) -> Result<(f64, DataQuality)> {
         self.query_power().await
     }
 }
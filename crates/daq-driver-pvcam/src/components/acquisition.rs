@@ -59,6 +59,8 @@ use anyhow::{anyhow, bail, Result};
 use bytes::Bytes;
 use common::core::Roi;
 use common::data::Frame;
+#[cfg(feature = "pvcam_sdk")]
+use common::memory::{MemoryAccount, MemoryLedger, MemorySubsystem};
 use common::parameter::Parameter;
 #[cfg(feature = "pvcam_sdk")]
 use pool::buffer_pool::BufferPool;
@@ -66,6 +68,7 @@ use pool::buffer_pool::BufferPool;
 use pool::{FrameData, Pool};
 #[cfg(feature = "pvcam_sdk")]
 use std::alloc::{alloc_zeroed, dealloc, Layout};
+use std::collections::HashMap;
 #[cfg(feature = "pvcam_sdk")]
 use std::sync::atomic::AtomicBool;
 #[cfg(feature = "pvcam_sdk")]
@@ -1120,6 +1123,10 @@ pub struct PvcamAcquisition {
     /// Taps are called with borrowed frame references before broadcast.
     pub tap_registry: Arc<TapRegistry>,
 
+    /// Driver-specific key/values copied into every frame's metadata
+    /// (e.g. post-processing settings). Snapshotted when a stream starts.
+    frame_extra: std::sync::Mutex<Arc<HashMap<String, String>>>,
+
     /// Optional metadata channel for hardware timestamps (Gemini SDK review).
     /// When enabled, each frame's decoded metadata is sent here alongside the frame data.
     #[cfg(feature = "pvcam_sdk")]
@@ -1137,6 +1144,10 @@ pub struct PvcamAcquisition {
     /// When the buffer pool is exhausted, frames are dropped with a warning
     /// rather than falling back to heap allocation.
     pub dropped_frames: Arc<AtomicU64>,
+    /// `lost_frames` and `dropped_frames` of earlier acquisitions, so the
+    /// driver can report lifetime totals for data-integrity accounting.
+    lost_frames_before: AtomicU64,
+    dropped_frames_before: AtomicU64,
     /// Last hardware frame number for gap detection (-1 = uninitialized).
     #[cfg(feature = "pvcam_sdk")]
     last_hardware_frame_nr: Arc<AtomicI32>,
@@ -1185,6 +1196,10 @@ pub struct PvcamAcquisition {
     /// Pool is cleared in stop_stream() to release memory.
     #[cfg(feature = "pvcam_sdk")]
     frame_pool: Arc<Mutex<Option<BufferPool>>>,
+
+    /// Bytes preallocated by the frame pool, in the memory ledger
+    #[cfg(feature = "pvcam_sdk")]
+    frame_pool_memory: MemoryAccount,
 }
 
 impl PvcamAcquisition {
@@ -1204,6 +1219,7 @@ impl PvcamAcquisition {
 
             // Tap registry for synchronous frame observers (bd-0dax.4)
             tap_registry: Arc::new(TapRegistry::new()),
+            frame_extra: std::sync::Mutex::new(Arc::new(HashMap::new())),
 
             // Metadata channel and state (Gemini SDK review)
             #[cfg(feature = "pvcam_sdk")]
@@ -1216,6 +1232,8 @@ impl PvcamAcquisition {
             discontinuity_events: Arc::new(AtomicU64::new(0)),
             // Pool exhaustion counter (bd-dmbl)
             dropped_frames: Arc::new(AtomicU64::new(0)),
+            lost_frames_before: AtomicU64::new(0),
+            dropped_frames_before: AtomicU64::new(0),
             #[cfg(feature = "pvcam_sdk")]
             last_hardware_frame_nr: Arc::new(AtomicI32::new(-1)), // -1 = uninitialized
 
@@ -1254,14 +1272,21 @@ impl PvcamAcquisition {
             // Created in start_stream() when frame size is known
             #[cfg(feature = "pvcam_sdk")]
             frame_pool: Arc::new(Mutex::new(None)),
+            #[cfg(feature = "pvcam_sdk")]
+            frame_pool_memory: MemoryLedger::global()
+                .account("frame_pool:pvcam", MemorySubsystem::Pool),
         }
     }
 
     /// Reset frame loss metrics at the start of a new acquisition.
     pub fn reset_frame_loss_metrics(&self) {
-        self.lost_frames.store(0, Ordering::SeqCst);
+        self.lost_frames_before
+            .fetch_add(self.lost_frames.swap(0, Ordering::SeqCst), Ordering::SeqCst);
         self.discontinuity_events.store(0, Ordering::SeqCst);
-        self.dropped_frames.store(0, Ordering::SeqCst);
+        self.dropped_frames_before.fetch_add(
+            self.dropped_frames.swap(0, Ordering::SeqCst),
+            Ordering::SeqCst,
+        );
         #[cfg(feature = "pvcam_sdk")]
         {
             self.last_hardware_frame_nr.store(-1, Ordering::SeqCst);
@@ -1281,6 +1306,19 @@ impl PvcamAcquisition {
         )
     }
 
+    /// Get (lost_frames, dropped_frames) summed over all acquisitions.
+    ///
+    /// Unlike [`Self::frame_loss_stats`], these are not reset when a new
+    /// acquisition starts.
+    pub fn lifetime_frame_loss(&self) -> (u64, u64) {
+        (
+            self.lost_frames_before.load(Ordering::Relaxed)
+                + self.lost_frames.load(Ordering::Relaxed),
+            self.dropped_frames_before.load(Ordering::Relaxed)
+                + self.dropped_frames.load(Ordering::Relaxed),
+        )
+    }
+
     /// Get the number of frames dropped due to pool exhaustion (bd-dmbl).
     ///
     /// This counter is incremented when the buffer pool is exhausted and
@@ -1513,6 +1551,19 @@ impl PvcamAcquisition {
     ///
     /// Resets frame loss metrics at the start of each acquisition. During streaming,
     /// the poll loop tracks hardware frame numbers to detect and count dropped frames.
+    /// Set the key/values recorded in the metadata of frames from the next
+    /// stream on
+    pub fn set_frame_extra(&self, extra: HashMap<String, String>) {
+        *self.frame_extra.lock().unwrap_or_else(|p| p.into_inner()) = Arc::new(extra);
+    }
+
+    fn frame_extra(&self) -> Arc<HashMap<String, String>> {
+        self.frame_extra
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+
     pub async fn start_stream(
         &self,
         conn: &PvcamConnection,
@@ -1903,6 +1954,8 @@ impl PvcamAcquisition {
             let pool_size = (buffer_count as f64 * 1.5).ceil() as usize;
             let buffer_pool = BufferPool::new(pool_size, actual_frame_bytes);
             *self.frame_pool.lock().await = Some(buffer_pool.clone());
+            self.frame_pool_memory
+                .set((pool_size * actual_frame_bytes) as u64);
             tracing::info!(
                 pool_size,
                 frame_capacity_mb = actual_frame_bytes as f64 / (1024.0 * 1024.0),
@@ -2130,6 +2183,7 @@ impl PvcamAcquisition {
 
             // bd-0dax.4: Clone tap registry for frame observers
             let tap_registry = self.tap_registry.clone();
+            let frame_extra = self.frame_extra();
 
             // bd-g6pr: Create completion channel for poll thread synchronization.
             // Drop will wait on this receiver before calling FFI cleanup functions,
@@ -2180,6 +2234,7 @@ impl PvcamAcquisition {
                     circ_overwrite,
                     buffer_pool,  // bd-0dax.4: Buffer pool for true zero-allocation
                     tap_registry, // bd-0dax.4: For synchronous tap observers
+                    frame_extra,
                 );
             });
 
@@ -2279,6 +2334,7 @@ impl PvcamAcquisition {
         let frame_tx = self.frame_tx.clone();
         let frame_count = self.frame_count.clone();
         let tap_registry = self.tap_registry.clone(); // bd-0dax.4: For tap observers
+        let frame_extra = self.frame_extra();
         let (x_bin, y_bin) = binning;
 
         // bd-5oss: Capture primary_tx for LoanedFrame delivery
@@ -2378,6 +2434,7 @@ impl PvcamAcquisition {
                 // Populate frame metadata using builder pattern (bd-183h)
                 let ext_metadata = common::data::FrameMetadata {
                     binning: Some(binning),
+                    extra: (*frame_extra).clone(),
                     ..Default::default()
                 };
                 let frame = Arc::new(
@@ -2537,6 +2594,7 @@ impl PvcamAcquisition {
         let frame_count = self.frame_count.clone();
         let lost_frames = self.lost_frames.clone();
         let tap_registry = self.tap_registry.clone(); // bd-0dax.4: For tap observers
+        let frame_extra = self.frame_extra();
         let width = binned_width;
         let height = binned_height;
         let roi_x = roi.x;
@@ -2573,6 +2631,7 @@ impl PvcamAcquisition {
                 binning,
                 done_tx,
                 tap_registry, // bd-0dax.4: For tap observers
+                frame_extra,
             );
         });
 
@@ -2604,6 +2663,7 @@ impl PvcamAcquisition {
         binning: (u16, u16),
         done_tx: std::sync::mpsc::Sender<()>,
         tap_registry: Arc<TapRegistry>, // bd-0dax.4: For synchronous tap observers
+        frame_extra: Arc<HashMap<String, String>>,
     ) {
         // Main sequence loop
         let mut total_frames: u64 = 0;
@@ -2683,6 +2743,7 @@ impl PvcamAcquisition {
                         // Build frame (matching mock and hardware path patterns)
                         let ext_metadata = common::data::FrameMetadata {
                             binning: Some(binning),
+                            extra: (*frame_extra).clone(),
                             ..Default::default()
                         };
                         let frame = Arc::new(
@@ -2826,6 +2887,7 @@ impl PvcamAcquisition {
         circ_overwrite: bool,
         buffer_pool: BufferPool, // bd-0dax.4: Buffer pool for true zero-allocation
         tap_registry: Arc<TapRegistry>, // bd-0dax.4: For synchronous tap observers
+        frame_extra: Arc<HashMap<String, String>>,
     ) {
         let loop_span = tracing::debug_span!(
             "pvcam_frame_loop",
@@ -3565,6 +3627,7 @@ impl PvcamAcquisition {
                 // Add extended metadata (bd-183h)
                 let ext_metadata = common::data::FrameMetadata {
                     binning: Some(binning),
+                    extra: (*frame_extra).clone(),
                     ..Default::default()
                 };
                 frame = frame.with_metadata(ext_metadata);
@@ -2,5 +2,6 @@ pub mod acquisition;
 pub mod connection;
 pub mod features;
 pub mod frame_pool;
+pub mod post_processing;
 pub mod speed_table;
 pub mod taps;
@@ -0,0 +1,167 @@
+//! Post-Processing Feature Control
+//!
+//! Exposes the camera's post-processing (PP) features (PrimeEnhance,
+//! PrimeLocate, despeckle, ...) as regular driver parameters named
+//! `processing.<Feature>.<Param>`, enumerated from the camera at startup so
+//! each model shows only what it supports.
+//!
+//! PVCAM only accepts PP changes while no acquisition is running, so setting
+//! a parameter just records the value. [`PostProcessing::apply`] writes all
+//! of them to the camera when it is staged or armed, and returns the settings
+//! that go into the metadata of every frame acquired with them.
+
+use crate::components::connection::PvcamConnection;
+use crate::components::features::PvcamFeatures;
+use anyhow::{anyhow, Result};
+use common::observable::ParameterSet;
+use common::parameter::Parameter;
+use std::collections::HashMap;
+
+/// Prefix of the frame metadata keys recording PP settings
+pub const PP_METADATA_PREFIX: &str = "pp.";
+
+/// One parameter of one PP feature
+#[derive(Clone)]
+pub struct PostProcessingParam {
+    pub feature_index: u16,
+    pub param_index: u16,
+    /// Feature name as reported by the camera (e.g. "PrimeEnhance")
+    pub feature: String,
+    /// Parameter name as reported by the camera (e.g. "Enabled")
+    pub name: String,
+    pub parameter: Parameter<u32>,
+}
+
+/// The PP features of one camera
+#[derive(Clone, Default)]
+pub struct PostProcessing {
+    params: Vec<PostProcessingParam>,
+}
+
+impl PostProcessing {
+    /// Enumerate the camera's PP features and their current values
+    ///
+    /// Cameras without PP support get an empty set.
+    pub fn discover(conn: &PvcamConnection) -> Self {
+        let features = match PvcamFeatures::list_pp_features(conn) {
+            Ok(features) => features,
+            Err(e) => {
+                tracing::debug!("No post-processing features: {}", e);
+                return Self::default();
+            }
+        };
+
+        let mut params = Vec::new();
+        for feature in features {
+            let feature_params = match PvcamFeatures::list_pp_params(conn, feature.index) {
+                Ok(feature_params) => feature_params,
+                Err(e) => {
+                    tracing::warn!("Failed to list PP parameters of {}: {}", feature.name, e);
+                    continue;
+                }
+            };
+            for param in feature_params {
+                let parameter =
+                    Parameter::new(parameter_name(&feature.name, &param.name), param.value)
+                        .with_description(format!(
+                            "Post-processing: {} {} (applied at stage/arm)",
+                            feature.name, param.name
+                        ));
+                params.push(PostProcessingParam {
+                    feature_index: feature.index,
+                    param_index: param.index,
+                    feature: feature.name.clone(),
+                    name: param.name,
+                    parameter,
+                });
+            }
+        }
+        tracing::info!("Discovered {} post-processing parameters", params.len());
+        Self { params }
+    }
+
+    pub fn params(&self) -> &[PostProcessingParam] {
+        &self.params
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// Add the PP parameters to the driver's parameter set
+    pub fn register(&self, set: &mut ParameterSet) {
+        for param in &self.params {
+            set.register(param.parameter.clone());
+        }
+    }
+
+    /// Write every PP setting to the camera
+    ///
+    /// Must not be called while acquiring. Returns the frame metadata
+    /// describing the settings (`pp.<Feature>.<Param>` -> value).
+    pub fn apply(&self, conn: &PvcamConnection) -> Result<HashMap<String, String>> {
+        let mut metadata = HashMap::with_capacity(self.params.len());
+        for param in &self.params {
+            let value = param.parameter.get();
+            PvcamFeatures::set_pp_param(conn, param.feature_index, param.param_index, value)
+                .map_err(|e| anyhow!("Failed to apply {}: {}", param.parameter.name(), e))?;
+            metadata.insert(metadata_key(&param.feature, &param.name), value.to_string());
+        }
+        Ok(metadata)
+    }
+
+    /// Read the camera's PP values back into the parameters (e.g. after a
+    /// reset to defaults)
+    pub async fn reload(&self, conn: &PvcamConnection) -> Result<()> {
+        for param in &self.params {
+            let value = PvcamFeatures::get_pp_param(conn, param.feature_index, param.param_index)?;
+            param.parameter.set(value).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Driver parameter name of a PP feature parameter
+pub fn parameter_name(feature: &str, param: &str) -> String {
+    format!("processing.{}.{}", sanitize(feature), sanitize(param))
+}
+
+/// Frame metadata key recording a PP feature parameter
+pub fn metadata_key(feature: &str, param: &str) -> String {
+    format!(
+        "{}{}.{}",
+        PP_METADATA_PREFIX,
+        sanitize(feature),
+        sanitize(param)
+    )
+}
+
+/// Camera-reported names may contain spaces and punctuation
+fn sanitize(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(all(test, not(feature = "pvcam_sdk")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discovered_features_become_parameters() {
+        let conn = PvcamConnection::new();
+        let pp = PostProcessing::discover(&conn);
+        assert!(!pp.is_empty());
+
+        let names: Vec<_> = pp.params().iter().map(|p| p.parameter.name()).collect();
+        assert!(names.contains(&"processing.PrimeEnhance.Enabled".to_string()));
+
+        let metadata = pp.apply(&conn).unwrap();
+        assert_eq!(metadata["pp.PrimeEnhance.Enabled"], "1");
+        assert_eq!(
+            parameter_name("DESPECKLE BRIGHT HIGH", "Min Adu Affected"),
+            "processing.DESPECKLE_BRIGHT_HIGH.Min_Adu_Affected"
+        );
+    }
+}
@@ -33,14 +33,15 @@ use anyhow::Result;
 use async_trait::async_trait;
 use common::capabilities::{
     Commandable, ExposureControl, Frame, FrameObserver, FrameProducer, LoanedFrame, ObserverHandle,
-    Parameterized, Triggerable,
+    Parameterized, Stageable, Triggerable,
 };
 use common::core::Roi;
 use common::error::DaqError;
+use common::integrity::{DropCounts, DropStage};
 use common::observable::ParameterSet;
 use common::parameter::Parameter;
 use common::pipeline::MeasurementSource;
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Mutex;
@@ -56,6 +57,7 @@ pub use crate::components::features::PvcamFeatures;
 
 use crate::components::acquisition::PvcamAcquisition;
 use crate::components::connection::PvcamConnection;
+pub use crate::components::post_processing::{PostProcessing, PostProcessingParam};
 use crate::components::speed_table::SpeedTable;
 use crate::components::taps::ObserverAdapter;
 #[cfg(feature = "pvcam_sdk")]
@@ -129,6 +131,10 @@ pub struct PvcamDriver {
     host_summing_enabled: Parameter<bool>,
     host_summing_count: Parameter<u32>,
 
+    // Post-Processing Features (processing.<Feature>.<Param>)
+    post_processing: PostProcessing,
+    staged: AtomicBool,
+
     // Metadata (Info)
     serial_number: Parameter<String>,
     firmware_version: Parameter<String>,
@@ -323,6 +329,12 @@ impl PvcamDriver {
                 )
             };
 
+        // Enumerate the camera's post-processing features
+        let post_processing = {
+            let conn = connection.lock().await;
+            PostProcessing::discover(&conn)
+        };
+
         let mut params = ParameterSet::new();
 
         // Acquisition Group
@@ -564,6 +576,7 @@ impl PvcamDriver {
         params.register(host_flip.clone());
         params.register(host_summing_enabled.clone());
         params.register(host_summing_count.clone());
+        post_processing.register(&mut params);
         params.register(serial_number.clone());
         params.register(firmware_version.clone());
         params.register(model_name.clone());
@@ -617,6 +630,8 @@ impl PvcamDriver {
             host_flip,
             host_summing_enabled,
             host_summing_count,
+            post_processing,
+            staged: AtomicBool::new(false),
             serial_number,
             firmware_version,
             model_name,
@@ -1298,8 +1313,31 @@ impl PvcamDriver {
         }
     }
 
+    /// Write the post-processing settings to the camera and record them in
+    /// the metadata of the frames that follow
+    ///
+    /// Skipped while streaming, since PVCAM rejects PP changes then.
+    fn apply_post_processing(&self, conn: &PvcamConnection) -> Result<()> {
+        if self.post_processing.is_empty() {
+            return Ok(());
+        }
+        if self.streaming.get() {
+            tracing::debug!("Post-processing not applied while streaming");
+            return Ok(());
+        }
+        let metadata = self.post_processing.apply(conn)?;
+        self.acquisition.set_frame_extra(metadata);
+        Ok(())
+    }
+
+    /// Post-processing features of this camera
+    pub fn post_processing(&self) -> &PostProcessing {
+        &self.post_processing
+    }
+
     pub async fn acquire_frame(&self) -> Result<Frame> {
         let conn = self.connection.lock().await;
+        self.apply_post_processing(&conn)?;
         self.acquisition
             .acquire_single_frame(
                 &conn,
@@ -1512,6 +1550,10 @@ impl ExposureControl for PvcamDriver {
 #[async_trait]
 impl Triggerable for PvcamDriver {
     async fn arm(&self) -> Result<()> {
+        {
+            let conn = self.connection.lock().await;
+            self.apply_post_processing(&conn)?;
+        }
         self.armed.set(true).await
     }
     async fn trigger(&self) -> Result<()> {
@@ -1545,6 +1587,7 @@ impl Triggerable for PvcamDriver {
 impl FrameProducer for PvcamDriver {
     async fn start_stream(&self) -> Result<()> {
         let conn = self.connection.lock().await;
+        self.apply_post_processing(&conn)?;
         self.acquisition
             .start_stream(
                 &conn,
@@ -1593,6 +1636,12 @@ impl FrameProducer for PvcamDriver {
         self.acquisition.frame_count.load(Ordering::SeqCst)
     }
 
+    async fn drop_counts(&self) -> DropCounts {
+        // dropped_frames counts pool exhaustion (bd-dmbl)
+        let (lost, dropped) = self.acquisition.lifetime_frame_loss();
+        DropCounts::from([(DropStage::Sdk, lost), (DropStage::Pool, dropped)])
+    }
+
     // bd-0dax.4: Observer registration for gRPC/external consumers
     async fn register_observer(&self, observer: Box<dyn FrameObserver>) -> Result<ObserverHandle> {
         // Wrap the generic observer in our adapter to convert to internal FrameTap
@@ -1634,6 +1683,25 @@ impl MeasurementSource for PvcamDriver {
     }
 }
 
+#[async_trait]
+impl Stageable for PvcamDriver {
+    async fn stage(&self) -> Result<()> {
+        let conn = self.connection.lock().await;
+        self.apply_post_processing(&conn)?;
+        self.staged.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn unstage(&self) -> Result<()> {
+        self.staged.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn is_staged(&self) -> Result<bool> {
+        Ok(self.staged.load(Ordering::SeqCst))
+    }
+}
+
 impl Parameterized for PvcamDriver {
     fn parameters(&self) -> &ParameterSet {
         &self.params
@@ -1652,8 +1720,26 @@ impl Commandable for PvcamDriver {
         match command {
             "reset_pp" => {
                 PvcamFeatures::reset_pp_features(&conn)?;
+                // Show the defaults in the parameters so the next arm keeps them
+                self.post_processing.reload(&conn).await?;
                 Ok(serde_json::json!({ "success": true }))
             }
+            "list_pp_features" => {
+                let features: Vec<_> = self
+                    .post_processing
+                    .params()
+                    .iter()
+                    .map(|p| {
+                        serde_json::json!({
+                            "feature": p.feature,
+                            "param": p.name,
+                            "parameter": p.parameter.name(),
+                            "value": p.parameter.get(),
+                        })
+                    })
+                    .collect();
+                Ok(serde_json::json!({ "features": features }))
+            }
             "upload_smart_stream" => {
                 let exposures = args
                     .get("exposures")
@@ -36,7 +36,7 @@
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use common::capabilities::{
-    EmissionControl, Parameterized, Readable, ShutterControl, WavelengthTunable,
+    EmissionControl, Parameterized, RawTerminal, Readable, ShutterControl, WavelengthTunable,
 };
 use common::driver::{Capability, DeviceComponents, DriverFactory};
 use common::error::DaqError;
@@ -81,6 +81,7 @@ static MAITAI_CAPABILITIES: &[Capability] = &[
     Capability::ShutterControl,
     Capability::EmissionControl,
     Capability::Parameterized,
+    Capability::RawTerminal,
 ];
 
 impl DriverFactory for MaiTaiFactory {
@@ -126,7 +127,8 @@ impl DriverFactory for MaiTaiFactory {
                 wavelength_tunable: Some(driver.clone()),
                 shutter_control: Some(driver.clone()),
                 emission_control: Some(driver.clone()),
-                parameterized: Some(driver),
+                parameterized: Some(driver.clone()),
+                raw_terminal: Some(driver),
                 ..Default::default()
             })
         })
@@ -541,6 +543,24 @@ impl Parameterized for MaiTaiDriver {
     }
 }
 
+#[async_trait]
+impl RawTerminal for MaiTaiDriver {
+    async fn send_raw(&self, command: &str, expect_response: bool) -> Result<String> {
+        // Emission must go through set_emission() so the shutter interlock applies
+        if command.trim().eq_ignore_ascii_case("ON") {
+            return Err(anyhow!(
+                "Refusing raw emission enable; use SetEmission so the shutter interlock is checked"
+            ));
+        }
+        if expect_response {
+            self.query(command).await
+        } else {
+            self.send_command(command).await?;
+            Ok(String::new())
+        }
+    }
+}
+
 #[async_trait]
 impl Readable for MaiTaiDriver {
     #[instrument(skip(self), err)]
@@ -731,6 +751,22 @@ mod tests {
         // New format: "wav 800.000\n" (lowercase, space separator, LF terminator)
         assert!(sent.contains("wav 800"));
 
+        Ok(())
+    }
+    #[tokio::test]
+    async fn raw_terminal_refuses_emission_enable() -> Result<()> {
+        let (mut host, device) = tokio::io::duplex(64);
+        let port: SharedPort = Arc::new(Mutex::new(BufReader::new(Box::new(device))));
+
+        let driver = MaiTaiDriver::with_test_port(port);
+
+        assert!(driver.send_raw(" on ", false).await.is_err());
+
+        driver.send_raw("SHUT 0", false).await?;
+        let mut buf = vec![0u8; 64];
+        let n = host.read(&mut buf).await?;
+        assert_eq!(String::from_utf8_lossy(&buf[..n]), "SHUT 0\n");
+
         Ok(())
     }
 }
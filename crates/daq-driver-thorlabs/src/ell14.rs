@@ -52,7 +52,7 @@
 use crate::shared_ports::{get_or_open_port, get_or_open_port_with_timeout, SharedPort};
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
-use common::capabilities::{Movable, Parameterized};
+use common::capabilities::{Movable, Parameterized, RawTerminal};
 use common::driver::{Capability, DeviceComponents, DriverFactory};
 use common::error::DaqError;
 use common::observable::ParameterSet;
@@ -81,13 +81,21 @@ pub struct Ell14Config {
     /// Optional port timeout in milliseconds (default: 500)
     #[serde(default)]
     pub timeout_ms: Option<u64>,
+    /// Optional quiet time in milliseconds this device needs after other
+    /// traffic on the shared bus before accepting a command (default: none)
+    #[serde(default)]
+    pub inter_command_delay_ms: Option<u64>,
 }
 
 /// Factory for creating ELL14 driver instances.
 pub struct Ell14Factory;
 
 /// Static capabilities for ELL14
-static ELL14_CAPABILITIES: &[Capability] = &[Capability::Movable, Capability::Parameterized];
+static ELL14_CAPABILITIES: &[Capability] = &[
+    Capability::Movable,
+    Capability::Parameterized,
+    Capability::RawTerminal,
+];
 
 impl DriverFactory for Ell14Factory {
     fn driver_type(&self) -> &'static str {
@@ -118,17 +126,24 @@ impl DriverFactory for Ell14Factory {
                 get_or_open_port(&cfg.port).await?
             };
 
+            // Bus delays are keyed by the same address the driver transacts with
+            let address = Ell14Driver::normalize_address(&cfg.address);
+            if let Some(delay_ms) = cfg.inter_command_delay_ms {
+                port.set_inter_command_delay(&address, Duration::from_millis(delay_ms));
+            }
+
             // Create driver with calibration
             let driver = if let Some(ppd) = cfg.pulses_per_degree {
-                Arc::new(Ell14Driver::with_calibration(port, &cfg.address, ppd))
+                Arc::new(Ell14Driver::with_calibration(port, &address, ppd))
             } else {
                 // Query device for calibration
-                Arc::new(Ell14Driver::with_shared_port_calibrated(port, &cfg.address).await?)
+                Arc::new(Ell14Driver::with_shared_port_calibrated(port, &address).await?)
             };
 
             Ok(DeviceComponents {
                 movable: Some(driver.clone()),
-                parameterized: Some(driver),
+                parameterized: Some(driver.clone()),
+                raw_terminal: Some(driver),
                 ..Default::default()
             })
         })
@@ -258,8 +273,15 @@ impl Ell14Driver {
         Self::with_calibration(port, address, Self::DEFAULT_PULSES_PER_DEGREE)
     }
 
+    /// Normalise a bus address to the uppercase form the device echoes in its
+    /// replies ("a" and "A" name the same device).
+    fn normalize_address(address: &str) -> String {
+        address.trim().to_ascii_uppercase()
+    }
+
     /// Create driver with custom calibration.
     pub fn with_calibration(port: SharedPort, address: &str, pulses_per_degree: f64) -> Self {
+        let address = Self::normalize_address(address);
         let mut params = ParameterSet::new();
 
         let mut position_deg = Parameter::new("position", 0.0)
@@ -271,7 +293,7 @@ impl Ell14Driver {
         Self::attach_position_callbacks(
             &mut position_deg,
             port.clone(),
-            address.to_string(),
+            address.clone(),
             pulses_per_degree,
         );
 
@@ -279,7 +301,7 @@ impl Ell14Driver {
 
         Self {
             port,
-            address: address.to_string(),
+            address,
             pulses_per_degree,
             position_deg,
             params: Arc::new(params),
@@ -309,7 +331,7 @@ impl Ell14Driver {
                 let cmd = format!("{}ma{:08X}", addr, pulses);
                 let expected_prefix = format!("{}PO", addr);
 
-                let mut guard = port.lock().await;
+                let mut guard = port.transaction(&addr).await;
 
                 // Aggressive buffer draining (same as transaction_once)
                 let mut discard = [0u8; 256];
@@ -365,6 +387,7 @@ impl Ell14Driver {
                 let mut response = Vec::with_capacity(64);
                 let mut buf = [0u8; 64];
                 let deadline = tokio::time::Instant::now() + Duration::from_millis(500);
+                let mut consumed = false;
 
                 // Initial wait for device to start responding
                 tokio::time::sleep(Duration::from_millis(15)).await;
@@ -388,6 +411,7 @@ impl Ell14Driver {
                                 if after_prefix.len() >= 11
                                     && (after_prefix.contains('\r') || after_prefix.contains('\n'))
                                 {
+                                    consumed = true;
                                     break;
                                 }
                             }
@@ -416,6 +440,10 @@ impl Ell14Driver {
                     response = %String::from_utf8_lossy(&response),
                     "ELL14 move_abs response consumed"
                 );
+                // Without the full PO reply the bus is drained before its next use
+                if consumed {
+                    guard.finish();
+                }
 
                 Ok(())
             })
@@ -434,7 +462,7 @@ impl Ell14Driver {
                 let cmd = format!("{}gp", addr);
                 let expected_prefix = format!("{}PO", addr);
 
-                let mut guard = port.lock().await;
+                let mut guard = port.transaction(&addr).await;
 
                 // Aggressive buffer draining
                 let mut discard = [0u8; 256];
@@ -505,6 +533,7 @@ impl Ell14Driver {
                                     // Extract and parse position
                                     if let Some(hex) = after_prefix.get(3..11) {
                                         if let Ok(pulses) = u32::from_str_radix(hex, 16) {
+                                            guard.finish();
                                             return Ok((pulses as i32) as f64 / ppd);
                                         }
                                     }
@@ -549,12 +578,13 @@ impl Ell14Driver {
 
     /// Create driver with calibration queried from device.
     pub async fn with_shared_port_calibrated(port: SharedPort, address: &str) -> Result<Self> {
+        let address = Self::normalize_address(address);
         // Query device info to get calibration
         let cmd = format!("{}in", address);
-        let expected_prefix = format!("{}IN", address.to_uppercase());
+        let expected_prefix = format!("{}IN", address);
 
         let pulses_per_degree = {
-            let mut guard = port.lock().await;
+            let mut guard = port.transaction(&address).await;
 
             // Aggressive buffer draining (same pattern as transaction_once)
             let mut discard = [0u8; 256];
@@ -612,6 +642,7 @@ impl Ell14Driver {
             let mut response = Vec::with_capacity(128);
             let mut buf = [0u8; 64];
             let deadline = tokio::time::Instant::now() + Duration::from_millis(500);
+            let mut complete = false;
 
             loop {
                 if tokio::time::Instant::now() > deadline {
@@ -630,6 +661,7 @@ impl Ell14Driver {
                             if after_prefix.len() >= 33
                                 && (after_prefix.contains('\r') || after_prefix.contains('\n'))
                             {
+                                complete = true;
                                 break;
                             }
                         }
@@ -650,6 +682,9 @@ impl Ell14Driver {
                 }
                 tokio::time::sleep(Duration::from_millis(5)).await;
             }
+            if complete {
+                guard.finish();
+            }
 
             let resp = String::from_utf8_lossy(&response);
             tracing::debug!(address = %address, response = %resp, "Device info response");
@@ -722,7 +757,7 @@ impl Ell14Driver {
             "Calibrated ELL14 driver"
         );
 
-        let driver = Self::with_calibration(port, address, pulses_per_degree);
+        let driver = Self::with_calibration(port, &address, pulses_per_degree);
 
         // Set maximum velocity for fastest operation
         if let Err(e) = driver.set_max_velocity().await {
@@ -812,7 +847,7 @@ impl Ell14Driver {
         let full_cmd = format!("{}{}", self.address, cmd);
         let expected_prefix = &self.address;
 
-        let mut guard = self.port.lock().await;
+        let mut guard = self.port.transaction(&self.address).await;
 
         // Aggressive buffer draining: read until we get 0 bytes or hit safety limit
         // This is critical for RS-485 buses where other devices may have sent data
@@ -898,6 +933,7 @@ impl Ell14Driver {
                             // Extract valid message, discarding leading garbage
                             let valid_msg = after_prefix[..end_offset].to_string();
                             tracing::debug!(cmd = %full_cmd, response = %valid_msg, "ELL14 transaction");
+                            guard.finish();
                             return Ok(valid_msg);
                         }
                     }
@@ -1099,6 +1135,20 @@ impl Parameterized for Ell14Driver {
     }
 }
 
+/// Raw commands are prefixed with this device's bus address, so the console
+/// only ever talks to its own rotator on a shared RS-485 bus.
+#[async_trait]
+impl RawTerminal for Ell14Driver {
+    async fn send_raw(&self, command: &str, expect_response: bool) -> Result<String> {
+        // Single attempt: retrying e.g. "ho" would home the device again
+        match self.transaction_once(command).await {
+            Ok(resp) => Ok(resp),
+            Err(e) if !expect_response && e.to_string().contains("timeout") => Ok(String::new()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
 #[async_trait]
 impl Movable for Ell14Driver {
     #[instrument(skip(self), fields(address = %self.address))]
@@ -1395,6 +1445,29 @@ mod tests {
         assert_eq!(hex, "00000F8E"); // ~3982 pulses
     }
 
+    #[tokio::test]
+    async fn test_lowercase_address_matches_uppercase_replies() -> Result<()> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let (mut host, device) = tokio::io::duplex(64);
+        let port = SharedPort::new(Box::new(device));
+        port.set_inter_command_delay("A", Duration::from_millis(1));
+        let driver = Ell14Driver::with_shared_port(port, "a");
+        assert_eq!(driver.address(), "A");
+
+        let responder = tokio::spawn(async move {
+            let mut buf = [0u8; 16];
+            let n = host.read(&mut buf).await.unwrap();
+            assert_eq!(&buf[..n], b"Ags");
+            host.write_all(b"AGS00\r\n").await.unwrap();
+            host
+        });
+        assert_eq!(driver.transaction_once("gs").await?, "AGS00");
+        responder.await?;
+
+        Ok(())
+    }
+
     /// This test documents the critical no-retry behavior for motion commands.
     ///
     /// Motion commands MUST use transaction_once() (no retry) because:
@@ -1,7 +1,9 @@
 //! Shared port management for RS-485 multidrop bus devices.
 //!
 //! Multiple ELL14 devices can share a single serial port. This module
-//! provides a static registry to track and reuse open ports.
+//! provides a static registry to track and reuse open ports. Ports are
+//! [`SerialBus`](common::serial::SerialBus) handles, so every driver on the
+//! same path shares one transaction queue.
 
 use common::serial::open_serial_async;
 use parking_lot::RwLock;
@@ -9,7 +11,7 @@ use std::collections::HashMap;
 use std::sync::OnceLock;
 
 // Re-export SharedPort type for backward compatibility
-pub use common::serial::SharedPortUnbuffered as SharedPort;
+pub use common::serial::SerialBus as SharedPort;
 
 /// Module-local registry for shared serial ports.
 static SHARED_PORTS: OnceLock<RwLock<HashMap<String, SharedPort>>> = OnceLock::new();
@@ -100,7 +102,7 @@ pub async fn get_or_open_port_with_baud(
 
     // Open new port using shared utility
     let stream = open_serial_async(port_path, baud_rate, "ELL14").await?;
-    let shared = common::serial::wrap_shared_unbuffered(Box::new(stream));
+    let shared = SharedPort::new(Box::new(stream));
 
     // Store in registry
     register_port(port_path, shared.clone());
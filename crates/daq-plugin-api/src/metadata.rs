@@ -109,9 +109,11 @@ pub struct AbiVersion {
 
 impl AbiVersion {
     /// Current ABI version
+    ///
+    /// 0.2: `FfiModuleTypeInfo::config_schema`
     pub const CURRENT: Self = Self {
         major: 0,
-        minor: 1,
+        minor: 2,
         patch: 0,
     };
 
@@ -68,6 +68,13 @@ pub struct FfiModuleTypeInfo {
     pub data_types: RVec<RString>,
     pub required_roles: RVec<FfiModuleRole>,
     pub optional_roles: RVec<FfiModuleRole>,
+    /// Optional JSON Schema (draft 2020-12 subset) for the module configuration.
+    ///
+    /// Use this when the flat `parameters` list can't express the config
+    /// (nested objects, arrays, `oneOf`/`if`-`then`-`else` conditionals).
+    /// Each top-level property becomes one entry in the [`FfiModuleConfig`]
+    /// map: strings are passed as-is, other values as compact JSON.
+    pub config_schema: ROption<RString>,
 }
 
 /// FFI-safe event data
@@ -81,6 +88,11 @@ pub struct FfiModuleEvent {
 }
 
 /// FFI-safe data point
+///
+/// Quality and units go in `metadata`, so they need no ABI change: a
+/// `"quality"` entry (`"suspect"`, `"stale"`, ...) and `"unit:<name>"`
+/// entries for the values, which `common::data_record::DataRecord` reads
+/// back out of the daemon's module data points.
 #[repr(C)]
 #[derive(Debug, Clone, StableAbi)]
 pub struct FfiModuleDataPoint {
@@ -6,6 +6,27 @@
 use daq_plugin_api::prelude::*;
 use std::collections::VecDeque;
 
+/// JSON Schema for the echo module configuration
+const ECHO_CONFIG_SCHEMA: &str = r#"{
+  "type": "object",
+  "properties": {
+    "message": {
+      "type": "string",
+      "title": "Message",
+      "description": "The message to echo",
+      "default": "Hello from plugin!"
+    },
+    "echo_count": {
+      "type": "integer",
+      "title": "Echo Count",
+      "description": "Number of times to echo the message",
+      "minimum": 1,
+      "maximum": 100,
+      "default": 3
+    }
+  }
+}"#;
+
 // =============================================================================
 // Plugin Entry Point
 // =============================================================================
@@ -133,6 +154,9 @@ impl EchoModule {
             },
             required_roles: RVec::new(),
             optional_roles: RVec::new(),
+            // Optional: a JSON Schema lets the GUI render a richer form than
+            // the flat parameter list (nested objects, arrays, conditionals)
+            config_schema: ROption::RSome(RString::from(ECHO_CONFIG_SCHEMA)),
         }
     }
 
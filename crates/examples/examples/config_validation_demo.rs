@@ -23,6 +23,7 @@ async fn main() -> anyhow::Result<()> {
             driver: DriverType::MockStage {
                 initial_position: 0.0,
             },
+            simulated: false,
         })
         .await
     {
@@ -39,6 +40,7 @@ async fn main() -> anyhow::Result<()> {
             driver: DriverType::Newport1830C {
                 port: "/dev/nonexistent_serial".into(),
             },
+            simulated: false,
         })
         .await
     {
@@ -59,6 +61,7 @@ async fn main() -> anyhow::Result<()> {
                 port: "/dev/ttyUSB0".into(),
                 address: "ZZZ".into(), // Invalid - must be single hex digit
             },
+            simulated: false,
         })
         .await
     {
@@ -79,6 +82,7 @@ async fn main() -> anyhow::Result<()> {
                 port: "/dev/ttyUSB1".into(),
                 axis: 5, // Invalid - must be 1-3
             },
+            simulated: false,
         })
         .await
     {
@@ -98,6 +102,7 @@ async fn main() -> anyhow::Result<()> {
             driver: DriverType::Pvcam {
                 camera_name: "".into(), // Invalid - cannot be empty
             },
+            simulated: false,
         })
         .await
     {
@@ -28,6 +28,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Find camera
     let devices = client.list_devices(ListDevicesRequest {
         capability_filter: None,
+        ..Default::default()
     }).await?;
 
     let camera_id = devices.get_ref().devices.iter()
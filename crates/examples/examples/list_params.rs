@@ -19,6 +19,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let devices = client.list_devices(ListDevicesRequest {
         capability_filter: None,
+        ..Default::default()
     }).await?;
 
     let camera_id = devices.get_ref().devices.iter()
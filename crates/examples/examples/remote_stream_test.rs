@@ -38,6 +38,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let devices = client
         .list_devices(ListDevicesRequest {
             capability_filter: None,
+            ..Default::default()
         })
         .await?;
     println!("\nAvailable devices:");
@@ -90,6 +91,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         device_id: cam.clone(),
         max_fps: 30, // Rate limit for GUI rendering
         quality: StreamQuality::Full.into(), // Full resolution for test
+        ..Default::default()
     };
     let mut stream = client.stream_frames(request).await?.into_inner();
 
@@ -20,6 +20,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let devices = client.list_devices(ListDevicesRequest {
         capability_filter: None,
+        ..Default::default()
     }).await?;
 
     let camera_id = devices.get_ref().devices.iter()
@@ -60,6 +61,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             device_id: cam.clone(),
             parameter_name: "acquisition.roi".to_string(),
             value: roi.to_string(),
+            typed_value: None,
         }).await {
             Ok(resp) => {
                 if resp.get_ref().success {
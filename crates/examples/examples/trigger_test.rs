@@ -29,6 +29,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Find camera
     let devices = client.list_devices(ListDevicesRequest {
         capability_filter: None,
+        ..Default::default()
     }).await?;
 
     let camera_id = devices.get_ref().devices.iter()
@@ -77,6 +78,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             device_id: cam.clone(),
             parameter_name: "acquisition.trigger_mode".to_string(),
             value: mode.to_string(),
+            typed_value: None,
         }).await {
             Ok(resp) => {
                 if resp.get_ref().success {
@@ -0,0 +1,176 @@
+//! Per-run configuration snapshots
+//!
+//! Every run records the configuration it ran under: the effective daemon
+//! configuration (devices, modules, storage) as the TOML `ApplyConfig`
+//! accepts, every device parameter value, and the software versions. The
+//! snapshot goes into the StartDoc metadata under
+//! [`CONFIG_SNAPSHOT_METADATA_KEY`] and is saved as JSON next to the run's
+//! data file, so a run can be reproduced by applying it to a daemon again.
+
+use anyhow::{Context, Result};
+use common::experiment::document::{now_ns, StartDoc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+
+/// StartDoc metadata key holding the run's [`ConfigSnapshot`] as JSON
+pub const CONFIG_SNAPSHOT_METADATA_KEY: &str = "config_snapshot";
+
+/// Extension of snapshot files saved beside run data files
+pub const CONFIG_SNAPSHOT_EXTENSION: &str = "config.json";
+
+/// Future returned by [`ConfigSnapshotSource::daemon_config`]
+pub type DaemonConfigFuture<'a> = Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>>;
+
+/// Provides the effective daemon configuration at run start
+///
+/// Implemented by the server, which owns the device, module and storage
+/// configuration the run engine cannot see.
+pub trait ConfigSnapshotSource: Send + Sync {
+    /// Configuration currently in effect as a TOML document
+    fn daemon_config(&self) -> DaemonConfigFuture<'_>;
+}
+
+/// Everything needed to put a daemon back into the state a run started in
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConfigSnapshot {
+    pub run_uid: String,
+    pub captured_ns: u64,
+    /// Effective daemon configuration as TOML (empty if no source is set)
+    #[serde(default)]
+    pub daemon_config: String,
+    /// Parameter values: device_id -> parameter -> value
+    #[serde(default)]
+    pub parameters: BTreeMap<String, BTreeMap<String, serde_json::Value>>,
+    /// Software versions and build provenance, e.g. `rust_daq`, `git_commit`
+    #[serde(default)]
+    pub software: BTreeMap<String, String>,
+}
+
+impl ConfigSnapshot {
+    pub fn new(
+        run_uid: &str,
+        daemon_config: String,
+        parameters: &HashMap<String, HashMap<String, serde_json::Value>>,
+    ) -> Self {
+        Self {
+            run_uid: run_uid.to_string(),
+            captured_ns: now_ns(),
+            daemon_config,
+            parameters: parameters
+                .iter()
+                .map(|(device, params)| {
+                    let params = params.iter().map(|(k, v)| (k.clone(), v.clone()));
+                    (device.clone(), params.collect())
+                })
+                .collect(),
+            software: software_versions(),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize config snapshot")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("Invalid config snapshot")
+    }
+
+    /// Snapshot recorded in a StartDoc, if any
+    pub fn from_start(start: &StartDoc) -> Option<Result<Self>> {
+        start
+            .metadata
+            .get(CONFIG_SNAPSHOT_METADATA_KEY)
+            .map(|json| Self::from_json(json))
+    }
+
+    /// Snapshot file for a run data file (`run_123.h5` -> `run_123.config.json`)
+    pub fn path_for(data_file: &Path) -> PathBuf {
+        data_file.with_extension(CONFIG_SNAPSHOT_EXTENSION)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_json()?)
+            .with_context(|| format!("Failed to write config snapshot {}", path.display()))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config snapshot {}", path.display()))?;
+        Self::from_json(&json)
+    }
+
+    /// Find and load the snapshot saved for `run_uid` in `directory`
+    ///
+    /// Data files are named `<run_uid>_<start_ns>.<ext>`, so the snapshot is
+    /// the `.config.json` file starting with the run UID.
+    pub fn find(directory: &Path, run_uid: &str) -> Result<Self> {
+        let prefix = format!("{}_", run_uid);
+        let suffix = format!(".{}", CONFIG_SNAPSHOT_EXTENSION);
+        for entry in std::fs::read_dir(directory)
+            .with_context(|| format!("Failed to read {}", directory.display()))?
+        {
+            let path = entry?.path();
+            let matches = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(&suffix));
+            if matches {
+                return Self::load(&path);
+            }
+        }
+        anyhow::bail!("No config snapshot for run '{}'", run_uid)
+    }
+}
+
+fn software_versions() -> BTreeMap<String, String> {
+    let mut software = BTreeMap::new();
+    software.insert(
+        "rust_daq".to_string(),
+        env!("CARGO_PKG_VERSION").to_string(),
+    );
+    if let Some(commit) = option_env!("VERGEN_GIT_SHA") {
+        software.insert("git_commit".to_string(), commit.to_string());
+    }
+    software.insert("os".to_string(), std::env::consts::OS.to_string());
+    software.insert("arch".to_string(), std::env::consts::ARCH.to_string());
+    software
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_snapshot_roundtrip_through_start_and_file() {
+        let mut parameters = HashMap::new();
+        parameters.insert(
+            "stage".to_string(),
+            HashMap::from([("velocity".to_string(), json!(2.5))]),
+        );
+        let snapshot = ConfigSnapshot::new("run-1", "[storage]\n".to_string(), &parameters);
+        assert_eq!(snapshot.parameters["stage"]["velocity"], json!(2.5));
+        assert!(snapshot.software.contains_key("rust_daq"));
+
+        let mut start = StartDoc::new("count", "Count");
+        assert!(ConfigSnapshot::from_start(&start).is_none());
+        start.metadata.insert(
+            CONFIG_SNAPSHOT_METADATA_KEY.to_string(),
+            snapshot.to_json().unwrap(),
+        );
+        assert_eq!(
+            ConfigSnapshot::from_start(&start).unwrap().unwrap(),
+            snapshot
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = ConfigSnapshot::path_for(&dir.path().join("run-1_42.h5"));
+        assert_eq!(path.file_name().unwrap(), "run-1_42.config.json");
+        snapshot.save(&path).unwrap();
+        assert_eq!(ConfigSnapshot::find(dir.path(), "run-1").unwrap(), snapshot);
+        assert!(ConfigSnapshot::find(dir.path(), "run").is_err());
+    }
+}
@@ -0,0 +1,288 @@
+//! Sequence-numbered document fan-out.
+//!
+//! The RunEngine publishes every document exactly once to a [`DocumentBus`].
+//! Each document gets the next value of a monotonically increasing sequence
+//! number, then is pushed into one bounded queue per subscriber.
+//!
+//! Pushing never waits. A subscriber whose queue is full misses the document
+//! instead of stalling the RunEngine, and the gap shows up on its side as a
+//! jump in sequence numbers ([`DocumentRecvError::Gap`]). Storage writers and
+//! gRPC streamers can therefore tell "nothing happened" apart from "I fell
+//! behind", and one slow consumer can't hold up acquisition or other
+//! consumers.
+//!
+//! Subscriber queues are Tokio MPSC channels (lock-free on the send side).
+//! The subscriber list is only write-locked to add subscribers and to prune
+//! closed ones; consumers never touch it.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let mut docs = engine.subscribe();
+//! loop {
+//!     match docs.recv_sequenced().await {
+//!         Ok(sequenced) => store(sequenced.seq, sequenced.doc),
+//!         Err(DocumentRecvError::Gap { missed }) => warn!(missed, "Fell behind"),
+//!         Err(DocumentRecvError::Closed) => break,
+//!     }
+//! }
+//! ```
+
+use common::experiment::document::Document;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+use tokio::sync::mpsc;
+
+/// Default queue depth per subscriber
+pub const DEFAULT_SUBSCRIBER_CAPACITY: usize = 1024;
+
+/// A document and its position in the engine's emission order
+///
+/// Sequence numbers start at 1 and increase by one per published document.
+#[derive(Debug, Clone)]
+pub struct SequencedDocument {
+    pub seq: u64,
+    pub doc: Document,
+}
+
+/// Error returned by [`DocumentReceiver`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentRecvError {
+    /// Documents were dropped because this subscriber fell behind. The next
+    /// receive returns the document that followed the gap.
+    Gap { missed: u64 },
+    /// The bus was dropped
+    Closed,
+}
+
+impl fmt::Display for DocumentRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Gap { missed } => write!(f, "missed {} documents", missed),
+            Self::Closed => write!(f, "document bus closed"),
+        }
+    }
+}
+
+impl std::error::Error for DocumentRecvError {}
+
+/// Fan-out of sequence-numbered documents to any number of subscribers
+#[derive(Debug)]
+pub struct DocumentBus {
+    next_seq: AtomicU64,
+    capacity: usize,
+    subscribers: RwLock<Vec<mpsc::Sender<SequencedDocument>>>,
+    dropped: AtomicU64,
+}
+
+impl Default for DocumentBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_SUBSCRIBER_CAPACITY)
+    }
+}
+
+impl DocumentBus {
+    /// Create a bus whose subscribers queue up to `capacity` documents each
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            next_seq: AtomicU64::new(1),
+            capacity: capacity.max(1),
+            subscribers: RwLock::new(Vec::new()),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Subscribe with the bus's default queue depth
+    pub fn subscribe(&self) -> DocumentReceiver {
+        self.subscribe_with_capacity(self.capacity)
+    }
+
+    /// Subscribe with a custom queue depth (e.g. deeper for storage writers)
+    pub fn subscribe_with_capacity(&self, capacity: usize) -> DocumentReceiver {
+        let (tx, rx) = mpsc::channel(capacity.max(1));
+        self.subscribers
+            .write()
+            .unwrap_or_else(|p| p.into_inner())
+            .push(tx);
+        DocumentReceiver {
+            rx,
+            next_seq: None,
+            pending: None,
+        }
+    }
+
+    /// Assign the next sequence number to `doc` and push it to every
+    /// subscriber without waiting
+    ///
+    /// Returns the sequence number. Subscribers with full queues miss the
+    /// document; closed subscribers are removed.
+    pub fn publish(&self, doc: Document) -> u64 {
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let mut closed = false;
+        {
+            let subscribers = self.subscribers.read().unwrap_or_else(|p| p.into_inner());
+            for tx in subscribers.iter() {
+                let sequenced = SequencedDocument {
+                    seq,
+                    doc: doc.clone(),
+                };
+                match tx.try_send(sequenced) {
+                    Ok(()) => {}
+                    Err(mpsc::error::TrySendError::Full(_)) => {
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(mpsc::error::TrySendError::Closed(_)) => closed = true,
+                }
+            }
+        }
+        if closed {
+            self.subscribers
+                .write()
+                .unwrap_or_else(|p| p.into_inner())
+                .retain(|tx| !tx.is_closed());
+        }
+        seq
+    }
+
+    /// Sequence number of the most recently published document (0 if none)
+    pub fn last_seq(&self) -> u64 {
+        self.next_seq.load(Ordering::Relaxed) - 1
+    }
+
+    /// Number of live subscribers
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .iter()
+            .filter(|tx| !tx.is_closed())
+            .count()
+    }
+
+    /// Total deliveries skipped because a subscriber queue was full
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// Receiving side of a [`DocumentBus`] subscription
+///
+/// Tracks the sequence number it expects next and reports jumps as
+/// [`DocumentRecvError::Gap`] before returning the document after the gap.
+#[derive(Debug)]
+pub struct DocumentReceiver {
+    rx: mpsc::Receiver<SequencedDocument>,
+    next_seq: Option<u64>,
+    pending: Option<SequencedDocument>,
+}
+
+impl DocumentReceiver {
+    /// Receive the next document with its sequence number
+    pub async fn recv_sequenced(&mut self) -> Result<SequencedDocument, DocumentRecvError> {
+        if let Some(sequenced) = self.pending.take() {
+            return Ok(sequenced);
+        }
+        let sequenced = self.rx.recv().await.ok_or(DocumentRecvError::Closed)?;
+        self.check_gap(sequenced)
+    }
+
+    /// Receive the next document
+    pub async fn recv(&mut self) -> Result<Document, DocumentRecvError> {
+        self.recv_sequenced().await.map(|sequenced| sequenced.doc)
+    }
+
+    /// Receive without waiting; `Ok(None)` if no document is queued
+    pub fn try_recv(&mut self) -> Result<Option<SequencedDocument>, DocumentRecvError> {
+        if let Some(sequenced) = self.pending.take() {
+            return Ok(Some(sequenced));
+        }
+        match self.rx.try_recv() {
+            Ok(sequenced) => self.check_gap(sequenced).map(Some),
+            Err(mpsc::error::TryRecvError::Empty) => Ok(None),
+            Err(mpsc::error::TryRecvError::Disconnected) => Err(DocumentRecvError::Closed),
+        }
+    }
+
+    fn check_gap(
+        &mut self,
+        sequenced: SequencedDocument,
+    ) -> Result<SequencedDocument, DocumentRecvError> {
+        let expected = self.next_seq;
+        // Never move the expectation backwards, so a late document from a
+        // concurrent publisher doesn't re-trigger gap reports
+        self.next_seq = Some(sequenced.seq.max(expected.unwrap_or(0)) + 1);
+        match expected {
+            Some(expected) if sequenced.seq > expected => {
+                let missed = sequenced.seq - expected;
+                self.pending = Some(sequenced);
+                Err(DocumentRecvError::Gap { missed })
+            }
+            _ => Ok(sequenced),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::experiment::document::StartDoc;
+
+    fn start(n: usize) -> Document {
+        Document::Start(StartDoc::new(&format!("plan_{}", n), "test"))
+    }
+
+    #[tokio::test]
+    async fn test_sequence_numbers_are_monotonic() {
+        let bus = DocumentBus::default();
+        let mut rx = bus.subscribe();
+        for n in 0..3 {
+            bus.publish(start(n));
+        }
+        assert_eq!(bus.last_seq(), 3);
+
+        for expected in 1..=3 {
+            assert_eq!(rx.recv_sequenced().await.unwrap().seq, expected);
+        }
+        assert!(rx.try_recv().unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_slow_subscriber_sees_gap_without_blocking_publisher() {
+        let bus = DocumentBus::default();
+        let mut slow = bus.subscribe_with_capacity(2);
+        let mut fast = bus.subscribe();
+
+        // Publishing never waits on the full queue
+        for n in 0..5 {
+            bus.publish(start(n));
+        }
+        assert_eq!(bus.dropped_count(), 3);
+
+        assert_eq!(slow.recv_sequenced().await.unwrap().seq, 1);
+        assert_eq!(slow.recv_sequenced().await.unwrap().seq, 2);
+        bus.publish(start(5));
+        assert_eq!(
+            slow.recv_sequenced().await.unwrap_err(),
+            DocumentRecvError::Gap { missed: 3 }
+        );
+        assert_eq!(slow.recv_sequenced().await.unwrap().seq, 6);
+
+        for expected in 1..=6 {
+            assert_eq!(fast.recv_sequenced().await.unwrap().seq, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dropped_subscribers_are_pruned_and_closed_is_reported() {
+        let bus = DocumentBus::default();
+        let rx = bus.subscribe();
+        drop(rx);
+        bus.publish(start(0));
+        assert_eq!(bus.subscriber_count(), 0);
+
+        let mut rx = bus.subscribe();
+        drop(bus);
+        assert_eq!(rx.recv().await.unwrap_err(), DocumentRecvError::Closed);
+    }
+}
@@ -0,0 +1,596 @@
+//! Dry-run simulation of plans
+//!
+//! Walks a plan's command stream against simulated devices without touching
+//! hardware, producing the full command sequence, an estimated duration, and a
+//! list of resource/device conflicts (including moves into or through
+//! keep-out zones). Lets users sanity-check a multi-hour scan before
+//! committing the instrument to it.
+//!
+//! # Timing Model
+//!
+//! - `MoveTo` - distance from the simulated position divided by the axis speed
+//!   (zero for the first move of an axis with no known starting position)
+//! - `Read` - the detector's exposure/integration time
+//! - `Wait` - the requested duration
+//! - `Trigger`, `Set`, `Checkpoint`, `EmitEvent` - fixed per-command overheads
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let mut plan = LineScan::new("stage_x", 0.0, 10.0, 11).with_detector("power_meter");
+//! let report = engine.dry_run(&mut plan, DryRunOptions::default()).await;
+//! println!("ETA: {:.1} s, {} issues", report.estimated_duration_s, report.issues.len());
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use common::driver::Capability;
+use common::keep_out::KeepOutZone;
+use common::ramp::POSITION_PARAMETER;
+
+use crate::plans::{Plan, PlanCommand};
+
+/// Upper bound on simulated commands, guarding against plans that never terminate.
+const MAX_SIMULATED_COMMANDS: usize = 10_000_000;
+
+/// Tunables for the dry-run timing model.
+#[derive(Debug, Clone)]
+pub struct DryRunOptions {
+    /// Per-device move speed (position units per second)
+    pub move_speeds: HashMap<String, f64>,
+    /// Move speed for axes without an explicit entry
+    pub default_move_speed: f64,
+    /// Per-device exposure/integration time in seconds
+    pub exposure_times: HashMap<String, f64>,
+    /// Read time for detectors without a known exposure
+    pub default_read_time_s: f64,
+    /// Fixed cost of a trigger command in seconds
+    pub trigger_overhead_s: f64,
+    /// Fixed cost of a parameter set command in seconds
+    pub set_overhead_s: f64,
+    /// Starting positions of axes (unknown axes start at their first target)
+    pub initial_positions: HashMap<String, f64>,
+    /// Maximum number of commands recorded in the report (timing covers all)
+    pub max_recorded_commands: usize,
+}
+
+impl Default for DryRunOptions {
+    fn default() -> Self {
+        Self {
+            move_speeds: HashMap::new(),
+            default_move_speed: 1.0,
+            exposure_times: HashMap::new(),
+            default_read_time_s: 0.01,
+            trigger_overhead_s: 0.0,
+            set_overhead_s: 0.0,
+            initial_positions: HashMap::new(),
+            max_recorded_commands: 10_000,
+        }
+    }
+}
+
+/// What the simulator knows about a device.
+#[derive(Debug, Clone, Default)]
+pub struct SimulatedDevice {
+    /// Capabilities the device advertises
+    pub capabilities: Vec<Capability>,
+    /// Lower travel limit (Movable devices)
+    pub min_position: Option<f64>,
+    /// Upper travel limit (Movable devices)
+    pub max_position: Option<f64>,
+    /// Ranges the device must not enter or cross (Movable devices)
+    pub keep_out: Vec<KeepOutZone>,
+}
+
+impl SimulatedDevice {
+    fn has(&self, capability: Capability) -> bool {
+        self.capabilities.contains(&capability)
+    }
+}
+
+/// Severity of a dry-run finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DryRunSeverity {
+    /// Plan will run but probably not as intended
+    Warning,
+    /// Plan would fail or damage the run if executed
+    Error,
+}
+
+/// A conflict or problem detected during the dry run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DryRunIssue {
+    /// How serious the finding is
+    pub severity: DryRunSeverity,
+    /// Device involved, if any
+    pub device_id: Option<String>,
+    /// Human-readable description
+    pub message: String,
+}
+
+/// One simulated command with its place on the estimated timeline.
+#[derive(Debug, Clone)]
+pub struct DryRunStep {
+    /// The command the plan yielded
+    pub command: PlanCommand,
+    /// Estimated offset from run start when the command begins (seconds)
+    pub start_offset_s: f64,
+    /// Estimated duration of the command (seconds)
+    pub duration_s: f64,
+}
+
+/// Result of simulating a plan.
+#[derive(Debug, Clone)]
+pub struct DryRunReport {
+    /// Plan type identifier
+    pub plan_type: String,
+    /// Recorded command sequence (capped at `max_recorded_commands`)
+    pub steps: Vec<DryRunStep>,
+    /// Total commands the plan yielded
+    pub total_commands: usize,
+    /// True if `steps` was capped
+    pub truncated: bool,
+    /// Number of events the plan would emit
+    pub num_events: u32,
+    /// Estimated wall-clock duration in seconds
+    pub estimated_duration_s: f64,
+    /// Conflicts and problems found
+    pub issues: Vec<DryRunIssue>,
+}
+
+impl DryRunReport {
+    /// True if any finding would make the real run fail.
+    pub fn has_errors(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|i| i.severity == DryRunSeverity::Error)
+    }
+}
+
+/// Simulate `plan` against `devices` without touching hardware.
+///
+/// The plan is reset before and after the simulation so it can be queued
+/// afterwards.
+pub fn simulate(
+    plan: &mut dyn Plan,
+    devices: &HashMap<String, SimulatedDevice>,
+    options: &DryRunOptions,
+) -> DryRunReport {
+    let mut sim = Simulator {
+        devices,
+        options,
+        positions: options.initial_positions.clone(),
+        issues: Vec::new(),
+        reported: HashSet::new(),
+    };
+
+    // Devices acting as both axis and detector usually indicate a mapping mistake
+    let detectors = plan.detectors();
+    for mover in plan.movers() {
+        if detectors.contains(&mover) {
+            sim.report(
+                DryRunSeverity::Warning,
+                &mover,
+                "role",
+                format!("Device '{}' is used as both mover and detector", mover),
+            );
+        }
+    }
+
+    plan.reset();
+
+    let mut steps = Vec::new();
+    let mut total_commands = 0usize;
+    let mut num_events = 0u32;
+    let mut elapsed = 0.0f64;
+
+    while let Some(cmd) = plan.next_command() {
+        total_commands += 1;
+        if total_commands > MAX_SIMULATED_COMMANDS {
+            sim.issues.push(DryRunIssue {
+                severity: DryRunSeverity::Error,
+                device_id: None,
+                message: format!(
+                    "Plan did not terminate within {} commands",
+                    MAX_SIMULATED_COMMANDS
+                ),
+            });
+            break;
+        }
+
+        if matches!(cmd, PlanCommand::EmitEvent { .. }) {
+            num_events += 1;
+        }
+
+        let duration_s = sim.step(&cmd);
+        if steps.len() < options.max_recorded_commands {
+            steps.push(DryRunStep {
+                command: cmd,
+                start_offset_s: elapsed,
+                duration_s,
+            });
+        }
+        elapsed += duration_s;
+    }
+
+    plan.reset();
+
+    DryRunReport {
+        plan_type: plan.plan_type().to_string(),
+        truncated: steps.len() < total_commands,
+        steps,
+        total_commands,
+        num_events,
+        estimated_duration_s: elapsed,
+        issues: sim.issues,
+    }
+}
+
+struct Simulator<'a> {
+    devices: &'a HashMap<String, SimulatedDevice>,
+    options: &'a DryRunOptions,
+    positions: HashMap<String, f64>,
+    issues: Vec<DryRunIssue>,
+    /// (device, check) pairs already reported, so a 10k-point scan yields one issue
+    reported: HashSet<(String, &'static str)>,
+}
+
+impl<'a> Simulator<'a> {
+    fn report(
+        &mut self,
+        severity: DryRunSeverity,
+        device_id: &str,
+        check: &'static str,
+        message: String,
+    ) {
+        if self.reported.insert((device_id.to_string(), check)) {
+            self.issues.push(DryRunIssue {
+                severity,
+                device_id: Some(device_id.to_string()),
+                message,
+            });
+        }
+    }
+
+    /// Look up a device, reporting it once if unknown.
+    fn device(&mut self, device_id: &str) -> Option<&'a SimulatedDevice> {
+        let device = self.devices.get(device_id);
+        if device.is_none() {
+            self.report(
+                DryRunSeverity::Error,
+                device_id,
+                "unknown",
+                format!("Device '{}' is not registered", device_id),
+            );
+        }
+        device
+    }
+
+    /// Simulate one command and return its estimated duration in seconds.
+    fn step(&mut self, cmd: &PlanCommand) -> f64 {
+        match cmd {
+            PlanCommand::MoveTo {
+                device_id,
+                position,
+            } => {
+                if let Some(device) = self.device(device_id) {
+                    if !device.has(Capability::Movable) {
+                        self.report(
+                            DryRunSeverity::Error,
+                            device_id,
+                            "movable",
+                            format!("Device '{}' is not movable", device_id),
+                        );
+                    }
+                    let below = device.min_position.is_some_and(|min| *position < min);
+                    let above = device.max_position.is_some_and(|max| *position > max);
+                    if below || above {
+                        self.report(
+                            DryRunSeverity::Error,
+                            device_id,
+                            "limits",
+                            format!(
+                                "Target {} is outside the travel limits of '{}' ({:?}..{:?})",
+                                position, device_id, device.min_position, device.max_position
+                            ),
+                        );
+                    }
+                    let from = self.positions.get(device_id).copied();
+                    if let Some(zone) = device.keep_out.iter().find(|zone| {
+                        zone.blocks(from.as_ref().map(std::slice::from_ref), &[*position])
+                    }) {
+                        self.report(
+                            DryRunSeverity::Error,
+                            device_id,
+                            "keep_out",
+                            format!(
+                                "Move of '{}' to {} enters keep-out zone {}",
+                                device_id, position, zone
+                            ),
+                        );
+                    }
+                }
+
+                let speed = self
+                    .options
+                    .move_speeds
+                    .get(device_id)
+                    .copied()
+                    .unwrap_or(self.options.default_move_speed);
+                let previous = self.positions.insert(device_id.clone(), *position);
+                match previous {
+                    Some(from) if speed > 0.0 => (position - from).abs() / speed,
+                    _ => 0.0,
+                }
+            }
+            PlanCommand::Read { device_id } => {
+                if let Some(device) = self.device(device_id) {
+                    if !device.has(Capability::Readable) && !device.has(Capability::FrameProducer) {
+                        self.report(
+                            DryRunSeverity::Warning,
+                            device_id,
+                            "readable",
+                            format!(
+                                "Device '{}' is not readable; reads will return 0.0",
+                                device_id
+                            ),
+                        );
+                    }
+                }
+                self.options
+                    .exposure_times
+                    .get(device_id)
+                    .copied()
+                    .unwrap_or(self.options.default_read_time_s)
+            }
+            PlanCommand::Trigger { device_id } => {
+                if let Some(device) = self.device(device_id) {
+                    if !device.has(Capability::Triggerable) {
+                        self.report(
+                            DryRunSeverity::Warning,
+                            device_id,
+                            "triggerable",
+                            format!(
+                                "Device '{}' is not triggerable; trigger will be skipped",
+                                device_id
+                            ),
+                        );
+                    }
+                }
+                self.options.trigger_overhead_s
+            }
+            PlanCommand::Set {
+                device_id,
+                parameter,
+                ..
+            } => {
+                if let Some(device) = self.device(device_id) {
+                    if !device.has(Capability::Settable) && !device.has(Capability::Parameterized) {
+                        self.report(
+                            DryRunSeverity::Error,
+                            device_id,
+                            "settable",
+                            format!(
+                                "Device '{}' does not support setting '{}'",
+                                device_id, parameter
+                            ),
+                        );
+                    }
+                }
+                self.options.set_overhead_s
+            }
+            PlanCommand::MoveToPosition {
+                device_id,
+                position_name,
+            } => {
+                if let Some(device) = self.device(device_id) {
+                    if !device.has(Capability::DiscretePositioner) {
+                        self.report(
+                            DryRunSeverity::Error,
+                            device_id,
+                            "positioner",
+                            format!(
+                                "Device '{}' cannot move to position '{}'",
+                                device_id, position_name
+                            ),
+                        );
+                    }
+                }
+                self.options.set_overhead_s
+            }
+            PlanCommand::Ramp {
+                device_id,
+                parameter,
+                ramp,
+            } => {
+                let is_position = parameter == POSITION_PARAMETER;
+                if let Some(device) = self.device(device_id) {
+                    let capability = if is_position {
+                        Capability::Movable
+                    } else {
+                        Capability::Settable
+                    };
+                    if !device.has(capability) {
+                        self.report(
+                            DryRunSeverity::Error,
+                            device_id,
+                            "rampable",
+                            format!("Device '{}' cannot ramp '{}'", device_id, parameter),
+                        );
+                    }
+                }
+                if let Err(e) = ramp.validate() {
+                    self.issues.push(DryRunIssue {
+                        severity: DryRunSeverity::Error,
+                        device_id: Some(device_id.clone()),
+                        message: format!("Invalid ramp of '{}.{}': {}", device_id, parameter, e),
+                    });
+                    return 0.0;
+                }
+
+                // A rate needs the start value, known only for positions
+                let from = is_position
+                    .then(|| self.positions.get(device_id).copied())
+                    .flatten();
+                if is_position {
+                    self.positions.insert(device_id.clone(), ramp.target);
+                }
+                match (ramp.duration_s, from) {
+                    (Some(duration), _) => duration,
+                    (None, Some(from)) => ramp.duration_from(from).as_secs_f64(),
+                    (None, None) => 0.0,
+                }
+            }
+            PlanCommand::Wait { seconds } => {
+                if seconds.is_finite() && *seconds >= 0.0 {
+                    *seconds
+                } else {
+                    self.issues.push(DryRunIssue {
+                        severity: DryRunSeverity::Error,
+                        device_id: None,
+                        message: format!("Invalid wait duration: {}", seconds),
+                    });
+                    0.0
+                }
+            }
+            PlanCommand::Checkpoint { .. } | PlanCommand::EmitEvent { .. } => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plans::{Count, LineScan};
+    use crate::plans_imperative::ImperativePlan;
+    use common::ramp::RampSpec;
+
+    fn devices() -> HashMap<String, SimulatedDevice> {
+        let mut devices = HashMap::new();
+        devices.insert(
+            "stage_x".to_string(),
+            SimulatedDevice {
+                capabilities: vec![Capability::Movable],
+                min_position: Some(0.0),
+                max_position: Some(25.0),
+                ..Default::default()
+            },
+        );
+        devices.insert(
+            "power_meter".to_string(),
+            SimulatedDevice {
+                capabilities: vec![Capability::Readable],
+                ..Default::default()
+            },
+        );
+        devices
+    }
+
+    #[test]
+    fn test_line_scan_duration_estimate() {
+        let mut plan = LineScan::new("stage_x", 0.0, 10.0, 11)
+            .with_detector("power_meter")
+            .with_settle_time(0.5);
+
+        let mut options = DryRunOptions::default();
+        options.move_speeds.insert("stage_x".to_string(), 2.0);
+        options
+            .exposure_times
+            .insert("power_meter".to_string(), 0.1);
+        options.initial_positions.insert("stage_x".to_string(), 0.0);
+
+        let report = simulate(&mut plan, &devices(), &options);
+
+        // 10 mm travel at 2 mm/s + 11 settles + 11 reads
+        let expected = 10.0 / 2.0 + 11.0 * 0.5 + 11.0 * 0.1;
+        assert!((report.estimated_duration_s - expected).abs() < 1e-9);
+        assert_eq!(report.num_events, 11);
+        assert_eq!(report.steps.len(), report.total_commands);
+        assert!(!report.truncated);
+        // power_meter is not triggerable, reported exactly once
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].severity, DryRunSeverity::Warning);
+        assert!(!report.has_errors());
+    }
+
+    #[test]
+    fn test_detects_limit_and_unknown_device_conflicts() {
+        let mut plan = LineScan::new("stage_x", 0.0, 50.0, 6).with_detector("camera");
+
+        let report = simulate(&mut plan, &devices(), &DryRunOptions::default());
+
+        assert!(report.has_errors());
+        let devices_with_issues: Vec<_> = report
+            .issues
+            .iter()
+            .filter_map(|i| i.device_id.as_deref())
+            .collect();
+        assert!(devices_with_issues.contains(&"stage_x"));
+        assert!(devices_with_issues.contains(&"camera"));
+        // One limits issue and one unknown-device issue, despite many commands
+        assert_eq!(report.issues.len(), 2);
+    }
+
+    #[test]
+    fn test_detects_moves_through_keep_out_zones() {
+        let mut devices = devices();
+        devices.get_mut("stage_x").unwrap().keep_out =
+            vec![KeepOutZone::range("sample holder", 12.0, 13.0)];
+
+        // 0, 5, ..., 25: no point inside, but 10 -> 15 crosses the zone
+        let mut plan = LineScan::new("stage_x", 0.0, 25.0, 6);
+        let report = simulate(&mut plan, &devices, &DryRunOptions::default());
+        assert!(report.has_errors());
+        assert!(report.issues[0].message.contains("sample holder"));
+
+        let mut plan = LineScan::new("stage_x", 0.0, 10.0, 6);
+        let report = simulate(&mut plan, &devices, &DryRunOptions::default());
+        assert!(!report.has_errors());
+    }
+
+    #[test]
+    fn test_ramp_duration_and_capability() {
+        let ramp = |device_id: &str, parameter: &str, spec: RampSpec| PlanCommand::Ramp {
+            device_id: device_id.to_string(),
+            parameter: parameter.to_string(),
+            ramp: spec,
+        };
+        let mut plan = ImperativePlan::new(vec![
+            ramp("stage_x", POSITION_PARAMETER, RampSpec::at_rate(10.0, 2.0)),
+            ramp(
+                "power_meter",
+                "gain",
+                RampSpec::over(2.0, std::time::Duration::from_secs(3)),
+            ),
+        ]);
+        let mut options = DryRunOptions::default();
+        options.initial_positions.insert("stage_x".to_string(), 0.0);
+
+        let report = simulate(&mut plan, &devices(), &options);
+
+        // 10 mm at 2 mm/s, then 3 s
+        assert!((report.estimated_duration_s - 8.0).abs() < 1e-9);
+        assert_eq!(report.issues.len(), 1);
+        assert!(report.issues[0].message.contains("cannot ramp 'gain'"));
+    }
+
+    #[test]
+    fn test_truncates_recorded_commands_and_resets_plan() {
+        let mut plan = Count::new(100).with_detector("power_meter");
+        let options = DryRunOptions {
+            max_recorded_commands: 10,
+            ..Default::default()
+        };
+
+        let report = simulate(&mut plan, &devices(), &options);
+
+        assert!(report.truncated);
+        assert_eq!(report.steps.len(), 10);
+        assert_eq!(report.num_events, 100);
+        // Plan is reset and can still be executed afterwards
+        assert!(matches!(
+            plan.next_command(),
+            Some(PlanCommand::Checkpoint { .. })
+        ));
+    }
+}
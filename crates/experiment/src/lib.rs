@@ -29,19 +29,35 @@
 //! engine.resume().await?;
 //! ```
 
+pub mod config_snapshot;
+pub mod document_bus;
+pub mod dry_run;
 pub mod plans;
 pub mod plans_daq;
 pub mod plans_imperative;
+pub mod recording;
+pub mod run_comparison;
 pub mod run_engine;
+pub mod templates;
 
 // Re-export document types from common
 pub use common::experiment::document::{
-    DataKey, DescriptorDoc, Document, EventDoc, ExperimentManifest, StartDoc, StopDoc,
+    BatchRunOutcome, BatchSummaryDoc, DataKey, DescriptorDoc, DeviceEventDoc, Document, EventDoc,
+    ExperimentManifest, ProgressDoc, StartDoc, StopDoc,
 };
-pub use plans::{Plan, PlanCommand, PlanRegistry};
+pub use config_snapshot::{ConfigSnapshot, ConfigSnapshotSource, CONFIG_SNAPSHOT_METADATA_KEY};
+pub use document_bus::{DocumentBus, DocumentReceiver, DocumentRecvError, SequencedDocument};
+pub use dry_run::{DryRunIssue, DryRunOptions, DryRunReport, DryRunSeverity};
+pub use plans::{DeviceRole, Plan, PlanCommand, PlanRegistry};
 pub use plans_daq::{
     TimeSeries, TimeSeriesBuilder, TriggeredAcquisition, TriggeredAcquisitionBuilder, VoltageScan,
     VoltageScanBuilder,
 };
 pub use plans_imperative::ImperativePlan;
+pub use recording::{ChannelRecording, RecordingChange};
+pub use run_comparison::{
+    ChannelComparison, ChannelStats, FieldDiff, HistoryWrite, HistoryWriter, RunDiff, RunHistory,
+    RunMarker, RunSummary,
+};
 pub use run_engine::{EngineState, RunEngine, RunResult};
+pub use templates::{BatchTracker, PlanTemplate, TemplateRun};
@@ -8,6 +8,7 @@
 //!
 //! Plans yield a sequence of `PlanCommand` values:
 //! - `MoveTo` - Move a device to a position
+//! - `MoveToPosition` - Move a discrete positioner to a named slot
 //! - `Read` - Read a value from a device
 //! - `Trigger` - Trigger a device (e.g., start acquisition)
 //! - `Wait` - Wait for a duration
@@ -31,6 +32,11 @@
 //! // ...
 //! ```
 
+use common::cleanup::CleanupPlan;
+use common::driver::Capability;
+use common::parking::ParkingActions;
+use common::ramp::RampSpec;
+use common::time_budget::TimeBudget;
 use std::collections::HashMap;
 
 /// Commands that plans yield for the RunEngine to execute
@@ -43,6 +49,13 @@ pub enum PlanCommand {
         /// Target position
         position: f64,
     },
+    /// Move a discrete positioner to a named slot
+    MoveToPosition {
+        /// Positioner device ID
+        device_id: String,
+        /// Slot name (e.g. "nd1")
+        position_name: String,
+    },
     /// Read a value from a device
     Read {
         /// Device to read
@@ -81,6 +94,15 @@ pub enum PlanCommand {
         /// Value to set
         value: String,
     },
+    /// Ramp a parameter gradually to a new setpoint and wait until it is there
+    Ramp {
+        /// Device to ramp
+        device_id: String,
+        /// Parameter name (`position` for Movables)
+        parameter: String,
+        /// Target, speed and profile
+        ramp: RampSpec,
+    },
 }
 
 /// Plan trait - all plans implement this to generate commands
@@ -108,6 +130,31 @@ pub trait Plan: Send + Sync {
 
     /// Reset the plan to start from the beginning
     fn reset(&mut self);
+
+    /// Hardware parking while the run is paused, keyed by device ID
+    ///
+    /// Replaces the parking actions configured for those devices for the
+    /// duration of this run (see [`common::parking`]).
+    fn parking_actions(&self) -> HashMap<String, ParkingActions> {
+        HashMap::new()
+    }
+
+    /// Device actions run when the run is aborted or fails
+    ///
+    /// The RunEngine runs the sequence, within its timeout, before emitting
+    /// the StopDoc, and records the outcome there (see [`common::cleanup`]).
+    /// A cleanup plan in the run metadata replaces this one.
+    fn cleanup(&self) -> Option<CleanupPlan> {
+        None
+    }
+
+    /// Time allowed for each detector read and move, and what to do when
+    /// one overruns (see [`common::time_budget`])
+    ///
+    /// A time budget in the run metadata replaces this one.
+    fn time_budget(&self) -> Option<TimeBudget> {
+        None
+    }
 }
 
 /// Line scan - scan a single axis with one or more detectors
@@ -688,6 +735,153 @@ impl Plan for Count {
     }
 }
 
+/// Position scan - step a discrete positioner through named slots
+///
+/// At each slot the detectors are read once. Events carry the slot index
+/// under the positioner's ID.
+#[derive(Debug, Clone)]
+pub struct PositionScan {
+    positioner: String,
+    position_names: Vec<String>,
+    detectors: Vec<String>,
+    settle_time: f64,
+    current_point: usize,
+    current_step: LineScanStep,
+}
+
+impl PositionScan {
+    /// Create a PositionScan visiting `position_names` in order
+    pub fn new(positioner: &str, position_names: &[&str]) -> Self {
+        Self {
+            positioner: positioner.to_string(),
+            position_names: position_names.iter().map(|s| s.to_string()).collect(),
+            detectors: Vec::new(),
+            settle_time: 0.0,
+            current_point: 0,
+            current_step: LineScanStep::Move,
+        }
+    }
+
+    /// Add a detector to the scan
+    pub fn with_detector(mut self, detector: &str) -> Self {
+        self.detectors.push(detector.to_string());
+        self
+    }
+
+    /// Set settle time in seconds
+    pub fn with_settle_time(mut self, seconds: f64) -> Self {
+        self.settle_time = seconds;
+        self
+    }
+}
+
+impl Plan for PositionScan {
+    fn plan_type(&self) -> &'static str {
+        "position_scan"
+    }
+
+    fn plan_name(&self) -> &'static str {
+        "Position Scan"
+    }
+
+    fn plan_args(&self) -> HashMap<String, String> {
+        let mut args = HashMap::new();
+        args.insert("positioner".to_string(), self.positioner.clone());
+        args.insert("positions".to_string(), self.position_names.join(","));
+        args.insert("detectors".to_string(), self.detectors.join(","));
+        args
+    }
+
+    fn movers(&self) -> Vec<String> {
+        vec![self.positioner.clone()]
+    }
+
+    fn detectors(&self) -> Vec<String> {
+        self.detectors.clone()
+    }
+
+    fn num_points(&self) -> usize {
+        self.position_names.len()
+    }
+
+    fn next_command(&mut self) -> Option<PlanCommand> {
+        let position_name = self.position_names.get(self.current_point)?.clone();
+
+        let cmd = match self.current_step {
+            LineScanStep::Move => {
+                self.current_step = if self.settle_time > 0.0 {
+                    LineScanStep::Settle
+                } else {
+                    LineScanStep::Checkpoint
+                };
+                PlanCommand::MoveToPosition {
+                    device_id: self.positioner.clone(),
+                    position_name,
+                }
+            }
+            LineScanStep::Settle => {
+                self.current_step = LineScanStep::Checkpoint;
+                PlanCommand::Wait {
+                    seconds: self.settle_time,
+                }
+            }
+            LineScanStep::Checkpoint => {
+                self.current_step = LineScanStep::TriggerDetectors;
+                PlanCommand::Checkpoint {
+                    label: format!("position_{}", position_name),
+                }
+            }
+            LineScanStep::TriggerDetectors => {
+                self.current_step = LineScanStep::ReadDetectors { detector_idx: 0 };
+                match self.detectors.first() {
+                    Some(det) => PlanCommand::Trigger {
+                        device_id: det.clone(),
+                    },
+                    None => {
+                        self.current_step = LineScanStep::EmitEvent;
+                        return self.next_command();
+                    }
+                }
+            }
+            LineScanStep::ReadDetectors { detector_idx } => {
+                match self.detectors.get(detector_idx) {
+                    Some(det) => {
+                        self.current_step = LineScanStep::ReadDetectors {
+                            detector_idx: detector_idx + 1,
+                        };
+                        PlanCommand::Read {
+                            device_id: det.clone(),
+                        }
+                    }
+                    None => {
+                        self.current_step = LineScanStep::EmitEvent;
+                        return self.next_command();
+                    }
+                }
+            }
+            LineScanStep::EmitEvent => {
+                self.current_point += 1;
+                self.current_step = LineScanStep::Move;
+
+                // The RunEngine records the slot index on MoveToPosition
+                PlanCommand::EmitEvent {
+                    stream: "primary".to_string(),
+                    data: HashMap::new(),
+                    positions: HashMap::new(),
+                }
+            }
+            LineScanStep::Done => return None,
+        };
+
+        Some(cmd)
+    }
+
+    fn reset(&mut self) {
+        self.current_point = 0;
+        self.current_step = LineScanStep::Move;
+    }
+}
+
 /// Builder trait for creating plans from string parameters
 pub trait PlanBuilder: Send + Sync {
     /// Build a plan instance from parameters and device mappings
@@ -702,6 +896,148 @@ pub trait PlanBuilder: Send + Sync {
 
     /// Get category tags for this plan type (e.g., "scanning", "0d", "1d", "2d")
     fn categories(&self) -> Vec<String>;
+
+    /// Device roles this plan binds via `device_mapping`
+    ///
+    /// Builders that declare roles get their mapping validated against device
+    /// capabilities at queue time (see [`bind_device_roles`]). An empty list
+    /// skips validation.
+    fn device_roles(&self) -> Vec<DeviceRole> {
+        Vec::new()
+    }
+}
+
+/// A logical device role declared by a plan type (e.g. "motor", "detector")
+///
+/// Roles are bound to concrete device IDs at queue time. When a request leaves
+/// a role unbound, `default_binding` (e.g. "scan_axis_1") is tried instead;
+/// rigs define those names as channel aliases so the same plan definition runs
+/// on different hardware without edits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceRole {
+    /// Key in `device_mapping` (e.g. "x_motor")
+    pub role_id: String,
+    /// The bound device must have at least one of these capabilities
+    pub capabilities: Vec<Capability>,
+    /// Human-readable description for GUIs
+    pub description: String,
+    /// Whether the plan can run without this role bound
+    pub optional: bool,
+    /// Logical device name used when the role is not bound explicitly
+    pub default_binding: Option<String>,
+}
+
+impl DeviceRole {
+    /// A role that must be bound to a device with `capability`
+    pub fn required(role_id: &str, capability: Capability, description: &str) -> Self {
+        Self {
+            role_id: role_id.to_string(),
+            capabilities: vec![capability],
+            description: description.to_string(),
+            optional: false,
+            default_binding: None,
+        }
+    }
+
+    /// A role the plan can run without
+    pub fn optional(role_id: &str, capability: Capability, description: &str) -> Self {
+        Self {
+            optional: true,
+            ..Self::required(role_id, capability, description)
+        }
+    }
+
+    /// Also accept devices with `capability`
+    pub fn or_capability(mut self, capability: Capability) -> Self {
+        self.capabilities.push(capability);
+        self
+    }
+
+    /// Fall back to `name` (a device ID or channel alias) when unbound
+    pub fn with_default_binding(mut self, name: &str) -> Self {
+        self.default_binding = Some(name.to_string());
+        self
+    }
+}
+
+/// Standard detector role shared by the built-in scan plans
+fn detector_role() -> DeviceRole {
+    DeviceRole::optional(
+        "detector",
+        Capability::Readable,
+        "Detector read at each point",
+    )
+    .or_capability(Capability::FrameProducer)
+    .with_default_binding("primary_detector")
+}
+
+/// Resolve a plan's device roles to concrete devices
+///
+/// `capabilities` returns the capabilities of a device ID (or channel alias),
+/// or `None` if no such device exists. Explicit bindings win; unbound roles
+/// fall back to their `default_binding` when that device exists. Bound devices
+/// must have one of the role's capabilities, required roles must be bound, and
+/// mapping keys that are not declared roles are rejected to catch typos.
+///
+/// Returns the mapping to pass to [`PlanBuilder::build`].
+pub fn bind_device_roles(
+    roles: &[DeviceRole],
+    device_mapping: &HashMap<String, String>,
+    capabilities: impl Fn(&str) -> Option<Vec<Capability>>,
+) -> Result<HashMap<String, String>, String> {
+    if roles.is_empty() {
+        return Ok(device_mapping.clone());
+    }
+
+    if let Some(unknown) = device_mapping
+        .keys()
+        .find(|key| !roles.iter().any(|r| &r.role_id == *key))
+    {
+        let expected: Vec<&str> = roles.iter().map(|r| r.role_id.as_str()).collect();
+        return Err(format!(
+            "Unknown device role '{}' (expected one of: {})",
+            unknown,
+            expected.join(", ")
+        ));
+    }
+
+    let mut bound = HashMap::new();
+    for role in roles {
+        let explicit = device_mapping
+            .get(&role.role_id)
+            .filter(|device| !device.is_empty());
+        let device = match explicit {
+            Some(device) => device.clone(),
+            None => match role
+                .default_binding
+                .as_ref()
+                .filter(|name| capabilities(name).is_some())
+            {
+                Some(default) => default.clone(),
+                None if role.optional => continue,
+                None => {
+                    return Err(format!(
+                        "Missing device binding for role '{}' ({})",
+                        role.role_id, role.description
+                    ));
+                }
+            },
+        };
+
+        let device_caps = capabilities(&device)
+            .ok_or_else(|| format!("Role '{}': unknown device '{}'", role.role_id, device))?;
+        if !role.capabilities.iter().any(|c| device_caps.contains(c)) {
+            let wanted: Vec<&str> = role.capabilities.iter().map(Capability::as_str).collect();
+            return Err(format!(
+                "Role '{}': device '{}' is not {}",
+                role.role_id,
+                device,
+                wanted.join(" or ")
+            ));
+        }
+        bound.insert(role.role_id.clone(), device);
+    }
+    Ok(bound)
 }
 
 /// Builder for Count plans
@@ -764,6 +1100,10 @@ impl PlanBuilder for CountBuilder {
     fn categories(&self) -> Vec<String> {
         vec!["0d".to_string()]
     }
+
+    fn device_roles(&self) -> Vec<DeviceRole> {
+        vec![detector_role()]
+    }
 }
 
 /// Builder for LineScan plans
@@ -854,6 +1194,14 @@ impl PlanBuilder for LineScanBuilder {
     fn categories(&self) -> Vec<String> {
         vec!["scanning".to_string(), "1d".to_string()]
     }
+
+    fn device_roles(&self) -> Vec<DeviceRole> {
+        vec![
+            DeviceRole::required("motor", Capability::Movable, "Scanned axis")
+                .with_default_binding("scan_axis_1"),
+            detector_role(),
+        ]
+    }
 }
 
 /// Builder for GridScan plans
@@ -983,6 +1331,89 @@ impl PlanBuilder for GridScanBuilder {
     fn categories(&self) -> Vec<String> {
         vec!["scanning".to_string(), "2d".to_string()]
     }
+
+    fn device_roles(&self) -> Vec<DeviceRole> {
+        vec![
+            DeviceRole::required("x_motor", Capability::Movable, "Inner (fast) axis")
+                .with_default_binding("scan_axis_1"),
+            DeviceRole::required("y_motor", Capability::Movable, "Outer (slow) axis")
+                .with_default_binding("scan_axis_2"),
+            detector_role(),
+        ]
+    }
+}
+
+/// Builder for PositionScan plans
+pub struct PositionScanBuilder;
+
+impl PlanBuilder for PositionScanBuilder {
+    fn build(
+        &self,
+        parameters: &HashMap<String, String>,
+        device_mapping: &HashMap<String, String>,
+    ) -> Result<Box<dyn Plan>, String> {
+        let positions: Vec<&str> = parameters
+            .get("positions")
+            .ok_or("Missing parameter: positions")?
+            .split(',')
+            .map(str::trim)
+            .collect();
+        if positions.iter().any(|p| p.is_empty()) {
+            return Err("positions must be a comma-separated list of names".to_string());
+        }
+
+        let positioner = device_mapping
+            .get("positioner")
+            .ok_or("Missing device mapping: positioner")?;
+        if positioner.is_empty() {
+            return Err("positioner device name cannot be empty".to_string());
+        }
+
+        let mut plan = PositionScan::new(positioner, &positions);
+
+        // Optional detector
+        if let Some(detector) = device_mapping.get("detector") {
+            if detector.is_empty() {
+                return Err("detector device name cannot be empty".to_string());
+            }
+            plan = plan.with_detector(detector);
+        }
+
+        // Optional settle time
+        if let Some(settle_str) = parameters.get("settle_time") {
+            let settle = settle_str
+                .parse::<f64>()
+                .map_err(|e| format!("Invalid settle_time: {}", e))?;
+            if !settle.is_finite() {
+                return Err("settle_time must be a finite number (not NaN or infinity)".to_string());
+            }
+            if settle < 0.0 {
+                return Err("settle_time must be >= 0".to_string());
+            }
+            plan = plan.with_settle_time(settle);
+        }
+
+        Ok(Box::new(plan))
+    }
+
+    fn description(&self) -> String {
+        "Measure at each named slot of a filter wheel, sample changer or flip mirror".to_string()
+    }
+
+    fn categories(&self) -> Vec<String> {
+        vec!["scanning".to_string(), "discrete".to_string()]
+    }
+
+    fn device_roles(&self) -> Vec<DeviceRole> {
+        vec![
+            DeviceRole::required(
+                "positioner",
+                Capability::DiscretePositioner,
+                "Positioner stepped through its slots",
+            ),
+            detector_role(),
+        ]
+    }
 }
 
 /// Plan registry for looking up and creating plans by type
@@ -1026,6 +1457,30 @@ impl PlanRegistry {
         self.builders.contains_key(plan_type)
     }
 
+    /// Device roles declared by a plan type
+    pub fn device_roles(&self, plan_type: &str) -> Option<Vec<DeviceRole>> {
+        self.builders.get(plan_type).map(|b| b.device_roles())
+    }
+
+    /// Bind a plan type's roles to devices, then create the plan
+    ///
+    /// See [`bind_device_roles`] for how `device_mapping` is resolved.
+    pub fn create_plan_with_roles(
+        &self,
+        plan_type: &str,
+        parameters: &HashMap<String, String>,
+        device_mapping: &HashMap<String, String>,
+        capabilities: impl Fn(&str) -> Option<Vec<Capability>>,
+    ) -> Result<Box<dyn Plan>, String> {
+        let builder = self
+            .builders
+            .get(plan_type)
+            .ok_or_else(|| format!("Unknown plan type: {}", plan_type))?;
+
+        let bound = bind_device_roles(&builder.device_roles(), device_mapping, capabilities)?;
+        builder.build(parameters, &bound)
+    }
+
     /// Create a plan instance
     pub fn create_plan(
         &self,
@@ -1107,6 +1562,38 @@ mod tests {
         assert_eq!(event_count, 5);
     }
 
+    #[test]
+    fn test_position_scan_visits_slots() {
+        let mut parameters = HashMap::new();
+        parameters.insert("positions".to_string(), "open, nd1,nd2".to_string());
+        let mut mapping = HashMap::new();
+        mapping.insert("positioner".to_string(), "nd_wheel".to_string());
+        mapping.insert("detector".to_string(), "power_meter".to_string());
+        let mut plan = PositionScanBuilder.build(&parameters, &mapping).unwrap();
+        assert_eq!(plan.num_points(), 3);
+
+        let mut slots = Vec::new();
+        let mut events = 0;
+        while let Some(cmd) = plan.next_command() {
+            match cmd {
+                PlanCommand::MoveToPosition {
+                    device_id,
+                    position_name,
+                } => {
+                    assert_eq!(device_id, "nd_wheel");
+                    slots.push(position_name);
+                }
+                PlanCommand::EmitEvent { .. } => events += 1,
+                _ => {}
+            }
+        }
+        assert_eq!(slots, ["open", "nd1", "nd2"]);
+        assert_eq!(events, 3);
+
+        parameters.insert("positions".to_string(), "open,,nd2".to_string());
+        assert!(PositionScanBuilder.build(&parameters, &mapping).is_err());
+    }
+
     #[test]
     fn test_plan_reset() {
         let mut plan = Count::new(3);
@@ -1125,4 +1612,47 @@ mod tests {
 
         assert_eq!(count, 3);
     }
+
+    fn rig_capabilities(id: &str) -> Option<Vec<Capability>> {
+        match id {
+            "stage_x" | "stage_y" | "scan_axis_1" => Some(vec![Capability::Movable]),
+            "power_meter" | "primary_detector" => Some(vec![Capability::Readable]),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_bind_roles_uses_default_bindings() {
+        let roles = LineScanBuilder.device_roles();
+        let bound = bind_device_roles(&roles, &HashMap::new(), rig_capabilities).unwrap();
+        assert_eq!(bound.get("motor").map(String::as_str), Some("scan_axis_1"));
+        assert_eq!(
+            bound.get("detector").map(String::as_str),
+            Some("primary_detector")
+        );
+
+        let mapping = HashMap::from([("motor".to_string(), "stage_y".to_string())]);
+        let bound = bind_device_roles(&roles, &mapping, rig_capabilities).unwrap();
+        assert_eq!(bound.get("motor").map(String::as_str), Some("stage_y"));
+    }
+
+    #[test]
+    fn test_bind_roles_rejects_bad_bindings() {
+        let roles = GridScanBuilder.device_roles();
+
+        // y_motor has no default binding on this rig
+        let err = bind_device_roles(&roles, &HashMap::new(), rig_capabilities).unwrap_err();
+        assert!(err.contains("y_motor"), "{err}");
+
+        let wrong_capability = HashMap::from([
+            ("x_motor".to_string(), "stage_x".to_string()),
+            ("y_motor".to_string(), "power_meter".to_string()),
+        ]);
+        let err = bind_device_roles(&roles, &wrong_capability, rig_capabilities).unwrap_err();
+        assert!(err.contains("not movable"), "{err}");
+
+        let typo = HashMap::from([("x_motr".to_string(), "stage_x".to_string())]);
+        let err = bind_device_roles(&roles, &typo, rig_capabilities).unwrap_err();
+        assert!(err.contains("Unknown device role 'x_motr'"), "{err}");
+    }
 }
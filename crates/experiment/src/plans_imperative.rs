@@ -40,6 +40,7 @@
 //! ]).with_emit_event(true);
 //! ```
 
+use common::ramp::RampSpec;
 use std::collections::HashMap;
 
 use super::plans::{Plan, PlanCommand};
@@ -69,9 +70,11 @@ impl ImperativePlan {
         // Try to infer primary device from first command
         let primary_device = commands.first().and_then(|cmd| match cmd {
             PlanCommand::MoveTo { device_id, .. } => Some(device_id.clone()),
+            PlanCommand::MoveToPosition { device_id, .. } => Some(device_id.clone()),
             PlanCommand::Read { device_id } => Some(device_id.clone()),
             PlanCommand::Trigger { device_id } => Some(device_id.clone()),
             PlanCommand::Set { device_id, .. } => Some(device_id.clone()),
+            PlanCommand::Ramp { device_id, .. } => Some(device_id.clone()),
             _ => None,
         });
 
@@ -133,6 +136,21 @@ impl ImperativePlan {
         .with_primary_device(device)
     }
 
+    /// Create an ImperativePlan that ramps a parameter to a new setpoint
+    pub fn ramp(
+        device_id: impl Into<String>,
+        parameter: impl Into<String>,
+        ramp: RampSpec,
+    ) -> Self {
+        let device = device_id.into();
+        Self::new(vec![PlanCommand::Ramp {
+            device_id: device.clone(),
+            parameter: parameter.into(),
+            ramp,
+        }])
+        .with_primary_device(device)
+    }
+
     /// Set whether to emit an event after commands complete
     pub fn with_emit_event(mut self, emit: bool) -> Self {
         self.emit_event = emit;
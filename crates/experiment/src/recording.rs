@@ -0,0 +1,402 @@
+//! Per-channel recording control
+//!
+//! Operators can keep individual channels (e.g. high-rate debug signals) out
+//! of storage while a run is live. [`ChannelRecording`] sits between the
+//! document stream and the storage writer: live subscribers still receive
+//! every channel, while the persisted copy of each event leaves out the
+//! disabled ones.
+//!
+//! Descriptors are persisted unchanged, so a channel can be re-enabled mid-run.
+//! The channels disabled at start go into the StartDoc metadata, and every
+//! toggle during the run goes into the StopDoc metadata. Together they record
+//! what the file is missing and from when.
+//!
+//! In commissioning mode a storage [`Decimation`] also thins out whole
+//! events (every Nth, or at most N per second), separately for each
+//! descriptor stream, so alignment sessions don't fill disks with full-rate
+//! data. [`ChannelRecording::admit`] decides which events are persisted; the
+//! decimation, its changes and the number of events left out are recorded in
+//! the run metadata the same way.
+
+use common::decimation::{Decimation, Decimator};
+use common::experiment::document::Document;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+/// Start/stop metadata key listing the channels not being recorded
+pub const DISABLED_CHANNELS_KEY: &str = "recording.disabled_channels";
+
+/// Stop metadata key holding the run's recording changes as a JSON array
+pub const CHANNEL_CHANGES_KEY: &str = "recording.channel_changes";
+
+/// Start/stop metadata key holding the storage decimation as JSON
+pub const DECIMATION_KEY: &str = "recording.decimation";
+
+/// Stop metadata key holding the run's decimation changes as a JSON array
+pub const DECIMATION_CHANGES_KEY: &str = "recording.decimation_changes";
+
+/// Stop metadata key counting the events decimation left out of the run
+pub const EVENTS_DECIMATED_KEY: &str = "recording.events_decimated";
+
+/// A channel switched on or off for recording
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordingChange {
+    pub time_ns: u64,
+    pub channel: String,
+    pub enabled: bool,
+    pub author: String,
+}
+
+/// Storage decimation changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecimationChange {
+    pub time_ns: u64,
+    pub decimation: Decimation,
+    pub author: String,
+}
+
+/// Which channels and events are written to storage
+///
+/// A channel names a data key, or a prefix ending before a `.`: disabling
+/// `camera` also disables `camera.mean` and `camera.max`.
+#[derive(Debug, Default)]
+pub struct ChannelRecording {
+    disabled: BTreeSet<String>,
+    /// Data keys seen in descriptors, for channel pickers
+    known: BTreeSet<String>,
+    /// Run the change log belongs to
+    run_uid: Option<String>,
+    /// Changes since the active run started
+    run_changes: Vec<RecordingChange>,
+    /// Events persisted in commissioning mode
+    decimation: Decimation,
+    /// Decimation state per descriptor UID
+    decimators: HashMap<String, Decimator>,
+    /// Decimation changes since the active run started
+    decimation_changes: Vec<DecimationChange>,
+    /// Events left out of the active run by decimation
+    events_decimated: u64,
+}
+
+impl ChannelRecording {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable or disable recording of `channel`
+    ///
+    /// Returns `false` if the channel was already in that state. Changes made
+    /// while a run is active are logged into its stop metadata.
+    pub fn set_enabled(
+        &mut self,
+        channel: &str,
+        enabled: bool,
+        author: &str,
+        time_ns: u64,
+    ) -> bool {
+        let changed = if enabled {
+            self.disabled.remove(channel)
+        } else {
+            self.disabled.insert(channel.to_string())
+        };
+        if changed && self.run_uid.is_some() {
+            self.run_changes.push(RecordingChange {
+                time_ns,
+                channel: channel.to_string(),
+                enabled,
+                author: author.to_string(),
+            });
+        }
+        changed
+    }
+
+    /// Set the storage decimation ([`Decimation::All`] leaves commissioning
+    /// mode)
+    ///
+    /// Returns `false` if it was already set. Changes made while a run is
+    /// active are logged into its stop metadata.
+    pub fn set_decimation(&mut self, decimation: Decimation, author: &str, time_ns: u64) -> bool {
+        if decimation == self.decimation {
+            return false;
+        }
+        self.decimation = decimation;
+        self.decimators.clear();
+        if self.run_uid.is_some() {
+            self.decimation_changes.push(DecimationChange {
+                time_ns,
+                decimation,
+                author: author.to_string(),
+            });
+        }
+        true
+    }
+
+    /// Events persisted (every event unless in commissioning mode)
+    pub fn decimation(&self) -> Decimation {
+        self.decimation
+    }
+
+    /// Decimation changes made since the active run started
+    pub fn decimation_changes(&self) -> &[DecimationChange] {
+        &self.decimation_changes
+    }
+
+    /// Events left out of the active run by decimation
+    pub fn events_decimated(&self) -> u64 {
+        self.events_decimated
+    }
+
+    /// Whether `doc` is persisted at all
+    ///
+    /// Only events are ever left out. Call before [`apply`](Self::apply).
+    pub fn admit(&mut self, doc: &Document) -> bool {
+        let Document::Event(event) = doc else {
+            return true;
+        };
+        if self.decimation == Decimation::All {
+            return true;
+        }
+        let decimation = self.decimation;
+        let admitted = self
+            .decimators
+            .entry(event.descriptor_uid.clone())
+            .or_insert_with(|| Decimator::new(decimation))
+            .admit_sample(event.time_ns);
+        if !admitted {
+            self.events_decimated += 1;
+        }
+        admitted
+    }
+
+    /// Whether a data key is written to storage
+    pub fn is_recorded(&self, key: &str) -> bool {
+        !self
+            .disabled
+            .iter()
+            .any(|channel| channel_covers(channel, key))
+    }
+
+    /// Channels not being recorded, sorted
+    pub fn disabled_channels(&self) -> impl Iterator<Item = &str> {
+        self.disabled.iter().map(String::as_str)
+    }
+
+    /// Data keys seen in run descriptors, sorted
+    pub fn known_channels(&self) -> impl Iterator<Item = &str> {
+        self.known.iter().map(String::as_str)
+    }
+
+    /// UID of the run in progress, if any
+    pub fn active_run(&self) -> Option<&str> {
+        self.run_uid.as_deref()
+    }
+
+    /// Changes made since the active run started
+    pub fn run_changes(&self) -> &[RecordingChange] {
+        &self.run_changes
+    }
+
+    /// The copy of `doc` to persist
+    pub fn apply(&mut self, doc: Document) -> Document {
+        match doc {
+            Document::Start(mut start) => {
+                self.run_uid = Some(start.uid.clone());
+                self.run_changes.clear();
+                self.decimation_changes.clear();
+                self.decimators.clear();
+                self.events_decimated = 0;
+                if !self.disabled.is_empty() {
+                    start
+                        .metadata
+                        .insert(DISABLED_CHANNELS_KEY.to_string(), self.disabled_list());
+                }
+                if self.decimation != Decimation::All {
+                    start
+                        .metadata
+                        .insert(DECIMATION_KEY.to_string(), self.decimation_json());
+                }
+                Document::Start(start)
+            }
+            Document::Descriptor(desc) => {
+                self.known.extend(desc.data_keys.keys().cloned());
+                Document::Descriptor(desc)
+            }
+            Document::Event(mut event) if !self.disabled.is_empty() => {
+                event.data.retain(|key, _| self.is_recorded(key));
+                event.timestamps.retain(|key, _| self.is_recorded(key));
+                event.arrays.retain(|key, _| self.is_recorded(key));
+                event.metadata.retain(|key, _| self.is_recorded(key));
+                Document::Event(event)
+            }
+            Document::Stop(mut stop) if self.active_run() == Some(stop.run_uid.as_str()) => {
+                if !self.run_changes.is_empty() {
+                    let changes = serde_json::to_string(&self.run_changes).unwrap_or_default();
+                    stop.metadata
+                        .insert(CHANNEL_CHANGES_KEY.to_string(), changes);
+                }
+                if !self.disabled.is_empty() {
+                    stop.metadata
+                        .insert(DISABLED_CHANNELS_KEY.to_string(), self.disabled_list());
+                }
+                if !self.decimation_changes.is_empty() {
+                    let changes =
+                        serde_json::to_string(&self.decimation_changes).unwrap_or_default();
+                    stop.metadata
+                        .insert(DECIMATION_CHANGES_KEY.to_string(), changes);
+                }
+                if self.decimation != Decimation::All || self.events_decimated > 0 {
+                    stop.metadata
+                        .insert(DECIMATION_KEY.to_string(), self.decimation_json());
+                    stop.metadata.insert(
+                        EVENTS_DECIMATED_KEY.to_string(),
+                        self.events_decimated.to_string(),
+                    );
+                }
+                self.run_uid = None;
+                self.run_changes.clear();
+                self.decimation_changes.clear();
+                Document::Stop(stop)
+            }
+            doc => doc,
+        }
+    }
+
+    fn disabled_list(&self) -> String {
+        self.disabled_channels().collect::<Vec<_>>().join(",")
+    }
+
+    fn decimation_json(&self) -> String {
+        serde_json::to_string(&self.decimation).unwrap_or_default()
+    }
+}
+
+/// Whether `channel` names `key` or a `.`-separated prefix of it
+fn channel_covers(channel: &str, key: &str) -> bool {
+    key.strip_prefix(channel)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::experiment::document::{DataKey, DescriptorDoc, EventDoc, StartDoc, StopDoc};
+
+    fn event(run_uid: &str) -> EventDoc {
+        let mut event = EventDoc::new(run_uid, "desc", 0);
+        event.data.insert("power".to_string(), 1.0);
+        event.data.insert("debug".to_string(), 2.0);
+        event.data.insert("debug.raw".to_string(), 3.0);
+        event.data.insert("debugger".to_string(), 4.0);
+        event.arrays.insert("debug.trace".to_string(), vec![0; 64]);
+        event
+    }
+
+    fn persisted_event(recording: &mut ChannelRecording, run_uid: &str) -> EventDoc {
+        match recording.apply(Document::Event(event(run_uid))) {
+            Document::Event(event) => event,
+            other => panic!("unexpected document {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_disabled_channels_are_left_out() {
+        let mut recording = ChannelRecording::new();
+        assert!(recording.set_enabled("debug", false, "op", 1));
+        assert!(!recording.set_enabled("debug", false, "op", 2));
+
+        let event = persisted_event(&mut recording, "run");
+        let mut keys: Vec<_> = event.data.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(keys, ["debugger", "power"]);
+        assert!(event.arrays.is_empty());
+
+        assert!(recording.set_enabled("debug", true, "op", 3));
+        assert_eq!(persisted_event(&mut recording, "run").data.len(), 4);
+    }
+
+    #[test]
+    fn test_changes_logged_in_run_metadata() {
+        let mut recording = ChannelRecording::new();
+        recording.set_enabled("debug", false, "op", 1);
+
+        let start = StartDoc::new("count", "Count");
+        let run_uid = start.uid.clone();
+        let Document::Start(start) = recording.apply(Document::Start(start)) else {
+            panic!("expected start");
+        };
+        assert_eq!(start.metadata[DISABLED_CHANNELS_KEY], "debug");
+
+        let mut desc = DescriptorDoc::new(&run_uid, "primary");
+        desc.data_keys
+            .insert("power".to_string(), DataKey::scalar("power_meter", "W"));
+        recording.apply(Document::Descriptor(desc));
+        assert_eq!(recording.known_channels().collect::<Vec<_>>(), ["power"]);
+
+        recording.set_enabled("debug", true, "alice", 10);
+        recording.set_enabled("power", false, "bob", 20);
+        assert_eq!(recording.run_changes().len(), 2);
+
+        let stop = StopDoc::success(&run_uid, 0);
+        let Document::Stop(stop) = recording.apply(Document::Stop(stop)) else {
+            panic!("expected stop");
+        };
+        let changes: Vec<RecordingChange> =
+            serde_json::from_str(&stop.metadata[CHANNEL_CHANGES_KEY]).unwrap();
+        assert_eq!(changes[0].channel, "debug");
+        assert!(changes[0].enabled);
+        assert_eq!(changes[1].author, "bob");
+        assert_eq!(stop.metadata[DISABLED_CHANNELS_KEY], "power");
+
+        // Idle changes are not logged against the finished run
+        assert_eq!(recording.active_run(), None);
+        recording.set_enabled("power", true, "bob", 30);
+        assert!(recording.run_changes().is_empty());
+    }
+
+    #[test]
+    fn test_decimation_thins_events_per_stream() {
+        let mut recording = ChannelRecording::new();
+        let every_third = Decimation::EveryNth { n: 3 };
+        assert!(recording.set_decimation(every_third, "op", 1));
+        assert!(!recording.set_decimation(every_third, "op", 2));
+
+        let start = StartDoc::new("count", "Count");
+        let run_uid = start.uid.clone();
+        let Document::Start(start) = recording.apply(Document::Start(start)) else {
+            panic!("expected start");
+        };
+        assert_eq!(
+            serde_json::from_str::<Decimation>(&start.metadata[DECIMATION_KEY]).unwrap(),
+            every_third
+        );
+
+        // Streams are decimated independently; other documents always pass
+        let admitted = |recording: &mut ChannelRecording, descriptor: &str| {
+            (0..6)
+                .filter(|_| {
+                    let event = EventDoc::new(&run_uid, descriptor, 0);
+                    recording.admit(&Document::Event(event))
+                })
+                .count()
+        };
+        assert_eq!(admitted(&mut recording, "primary"), 2);
+        assert_eq!(admitted(&mut recording, "baseline"), 2);
+        assert!(recording.admit(&Document::Descriptor(DescriptorDoc::new(
+            &run_uid, "primary"
+        ))));
+
+        // Back to full rate mid-run
+        assert!(recording.set_decimation(Decimation::All, "alice", 10));
+        assert_eq!(admitted(&mut recording, "primary"), 6);
+
+        let Document::Stop(stop) = recording.apply(Document::Stop(StopDoc::success(&run_uid, 0)))
+        else {
+            panic!("expected stop");
+        };
+        assert_eq!(stop.metadata[EVENTS_DECIMATED_KEY], "8");
+        let changes: Vec<DecimationChange> =
+            serde_json::from_str(&stop.metadata[DECIMATION_CHANGES_KEY]).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].author, "alice");
+    }
+}
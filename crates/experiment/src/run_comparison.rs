@@ -0,0 +1,811 @@
+//! Run summaries and run-to-run comparison.
+//!
+//! The most common post-mortem question in the lab is "what changed between
+//! the good run and the bad run?". [`RunHistory`] follows the document stream
+//! and keeps a compact [`RunSummary`] per run:
+//!
+//! - plan type, name and arguments (StartDoc)
+//! - device parameter snapshot, system info and git provenance (Manifest)
+//! - running statistics for every scalar event field (EventDoc)
+//! - exit status, event count and dropped data (StopDoc)
+//! - the signed provenance manifest of the run file, once the storage layer
+//!   has signed it
+//! - timestamped markers dropped by users while the run was live or afterwards
+//!
+//! [`RunDiff::compare`] diffs two summaries. Scalar sections only list the
+//! keys that differ; channels are listed for both runs with their statistics
+//! so shifts in shared channels can be ranked.
+//!
+//! Summaries are small, so completed runs are optionally written as JSON to a
+//! directory and reloaded on startup, keeping comparisons available across
+//! daemon restarts. The directory holds the same `capacity` newest runs as
+//! memory. [`RunHistory`] only queues its file changes as [`HistoryWrite`]s;
+//! callers holding it behind an async lock hand them to a [`HistoryWriter`]
+//! so no file I/O happens under the lock.
+
+use anyhow::{anyhow, Context, Result};
+use common::experiment::document::{Document, ExperimentManifest, StartDoc};
+use common::integrity::{total_dropped, DropReport, INTEGRITY_METADATA_KEY};
+use common::provenance::SignedManifest;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+
+/// Default number of completed runs kept in memory (and on disk)
+pub const DEFAULT_HISTORY_CAPACITY: usize = 500;
+
+/// Running statistics for one scalar channel (Welford's algorithm)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChannelStats {
+    pub count: u64,
+    pub mean: f64,
+    /// Sum of squared deviations from the mean
+    m2: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Default for ChannelStats {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl ChannelStats {
+    /// Add one sample (non-finite values are ignored)
+    pub fn push(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Sample standard deviation (0 for fewer than two samples)
+    pub fn std_dev(&self) -> f64 {
+        if self.count < 2 {
+            0.0
+        } else {
+            (self.m2 / (self.count - 1) as f64).sqrt()
+        }
+    }
+}
+
+/// User annotation at a point in time of a run
+///
+/// Markers live in the run catalog rather than the run file: the file's
+/// attributes are covered by its provenance signature.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunMarker {
+    /// Wall-clock time the marker refers to (ns since UNIX epoch)
+    pub time_ns: u64,
+    pub text: String,
+    /// Who dropped the marker (free-form, may be empty)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub author: String,
+}
+
+/// Compact record of one run, built from its documents
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunSummary {
+    pub run_uid: String,
+    pub plan_type: String,
+    pub plan_name: String,
+    pub plan_args: BTreeMap<String, String>,
+    /// User metadata from the StartDoc
+    pub metadata: BTreeMap<String, String>,
+    /// Device parameter snapshot: device_id -> parameter -> value
+    pub parameters: BTreeMap<String, BTreeMap<String, serde_json::Value>>,
+    /// Software version, hostname, git provenance, ...
+    pub configuration: BTreeMap<String, String>,
+    /// "success", "abort" or "fail"; `None` while the run is active
+    pub exit_status: Option<String>,
+    pub reason: String,
+    pub num_events: u32,
+    pub start_ns: u64,
+    pub stop_ns: u64,
+    /// Statistics per scalar event field
+    pub channels: BTreeMap<String, ChannelStats>,
+    /// Frames and samples lost during the run, per source and stage
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dropped: DropReport,
+    /// Catalog copy of the run file's signature (`None` = unsigned)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<SignedManifest>,
+    /// User markers, ordered by time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub markers: Vec<RunMarker>,
+}
+
+impl RunSummary {
+    /// Start a summary from a run's StartDoc
+    pub fn from_start(start: &StartDoc) -> Self {
+        Self {
+            run_uid: start.uid.clone(),
+            plan_type: start.plan_type.clone(),
+            plan_name: start.plan_name.clone(),
+            plan_args: sorted(&start.plan_args),
+            metadata: sorted(&start.metadata),
+            start_ns: start.time_ns,
+            ..Default::default()
+        }
+    }
+
+    /// Fold a document of this run into the summary
+    pub fn observe(&mut self, doc: &Document) {
+        match doc {
+            Document::Manifest(manifest) => self.apply_manifest(manifest),
+            Document::Event(event) => {
+                self.num_events += 1;
+                // Skip non-finite values up front so every stored channel has
+                // finite min/max (JSON has no infinities)
+                for (field, &value) in event.data.iter().filter(|(_, v)| v.is_finite()) {
+                    self.channels.entry(field.clone()).or_default().push(value);
+                }
+            }
+            Document::Stop(stop) => {
+                self.exit_status = Some(stop.exit_status.clone());
+                self.reason.clone_from(&stop.reason);
+                self.num_events = stop.num_events;
+                self.stop_ns = stop.time_ns;
+                if let Some(json) = stop.metadata.get(INTEGRITY_METADATA_KEY) {
+                    match serde_json::from_str(json) {
+                        Ok(dropped) => self.dropped = dropped,
+                        Err(e) => tracing::warn!(error = %e, "Invalid drop counts in StopDoc"),
+                    }
+                }
+            }
+            Document::Start(_)
+            | Document::Descriptor(_)
+            | Document::Progress(_)
+            | Document::BatchSummary(_)
+            | Document::DeviceEvent(_) => {}
+        }
+    }
+
+    fn apply_manifest(&mut self, manifest: &ExperimentManifest) {
+        self.parameters = manifest
+            .parameters
+            .iter()
+            .map(|(device, params)| {
+                let params = params.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+                (device.clone(), params)
+            })
+            .collect();
+        self.configuration = sorted(&manifest.system_info);
+        if let Some(commit) = &manifest.git_commit {
+            self.configuration
+                .insert("git_commit".to_string(), commit.clone());
+        }
+        if let Some(dirty) = manifest.git_dirty {
+            self.configuration
+                .insert("git_dirty".to_string(), dirty.to_string());
+        }
+        if let Some(hash) = &manifest.graph_hash {
+            self.configuration
+                .insert("graph_hash".to_string(), hash.clone());
+        }
+        if let Some(file) = &manifest.graph_file {
+            self.configuration
+                .insert("graph_file".to_string(), file.clone());
+        }
+    }
+
+    /// Whether the StopDoc has been seen
+    pub fn is_complete(&self) -> bool {
+        self.exit_status.is_some()
+    }
+
+    /// Run duration in nanoseconds (0 while active)
+    pub fn duration_ns(&self) -> u64 {
+        self.stop_ns.saturating_sub(self.start_ns)
+    }
+
+    /// Device parameters flattened to `device.parameter` keys
+    fn flat_parameters(&self) -> BTreeMap<String, String> {
+        self.parameters
+            .iter()
+            .flat_map(|(device, params)| {
+                params
+                    .iter()
+                    .map(move |(name, value)| (format!("{}.{}", device, name), value.to_string()))
+            })
+            .collect()
+    }
+
+    fn run_info(&self) -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("plan_type".to_string(), self.plan_type.clone()),
+            ("plan_name".to_string(), self.plan_name.clone()),
+            (
+                "exit_status".to_string(),
+                self.exit_status.clone().unwrap_or_default(),
+            ),
+            ("num_events".to_string(), self.num_events.to_string()),
+            (
+                "dropped".to_string(),
+                total_dropped(&self.dropped).to_string(),
+            ),
+        ])
+    }
+}
+
+fn sorted(map: &HashMap<String, String>) -> BTreeMap<String, String> {
+    map.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+}
+
+/// A key whose value differs between two runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldDiff {
+    pub key: String,
+    /// Value in the baseline run (`None` = absent)
+    pub baseline: Option<String>,
+    /// Value in the candidate run (`None` = absent)
+    pub candidate: Option<String>,
+}
+
+/// Statistics of one channel in both runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelComparison {
+    pub channel: String,
+    pub baseline: Option<ChannelStats>,
+    pub candidate: Option<ChannelStats>,
+}
+
+impl ChannelComparison {
+    /// Whether the channel was recorded in both runs
+    pub fn is_shared(&self) -> bool {
+        self.baseline.is_some() && self.candidate.is_some()
+    }
+
+    /// Change in mean, in units of the pooled standard deviation
+    ///
+    /// `None` unless the channel is shared and has non-zero spread.
+    pub fn mean_shift_sigma(&self) -> Option<f64> {
+        let (baseline, candidate) = (self.baseline?, self.candidate?);
+        let pooled = f64::midpoint(baseline.std_dev().powi(2), candidate.std_dev().powi(2)).sqrt();
+        (pooled > 0.0).then(|| (candidate.mean - baseline.mean) / pooled)
+    }
+}
+
+/// Differences between a baseline ("good") and a candidate ("bad") run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunDiff {
+    pub baseline: RunSummary,
+    pub candidate: RunSummary,
+    /// Plan type/name, exit status, event count
+    pub run_info: Vec<FieldDiff>,
+    pub plan_args: Vec<FieldDiff>,
+    /// Keyed `device.parameter`
+    pub device_parameters: Vec<FieldDiff>,
+    pub configuration: Vec<FieldDiff>,
+    pub metadata: Vec<FieldDiff>,
+    /// Every channel recorded in either run, largest mean shift first
+    pub channels: Vec<ChannelComparison>,
+}
+
+impl RunDiff {
+    /// Compare two run summaries
+    pub fn compare(baseline: &RunSummary, candidate: &RunSummary) -> Self {
+        let names: BTreeSet<&String> = baseline
+            .channels
+            .keys()
+            .chain(candidate.channels.keys())
+            .collect();
+        let mut channels: Vec<ChannelComparison> = names
+            .into_iter()
+            .map(|name| ChannelComparison {
+                channel: name.clone(),
+                baseline: baseline.channels.get(name).copied(),
+                candidate: candidate.channels.get(name).copied(),
+            })
+            .collect();
+        // Shared channels with the largest shift first, then one-sided ones
+        channels.sort_by(|a, b| {
+            let shift = |c: &ChannelComparison| c.mean_shift_sigma().map(f64::abs);
+            b.is_shared().cmp(&a.is_shared()).then_with(|| {
+                shift(b)
+                    .partial_cmp(&shift(a))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+        });
+
+        Self {
+            run_info: diff_maps(&baseline.run_info(), &candidate.run_info()),
+            plan_args: diff_maps(&baseline.plan_args, &candidate.plan_args),
+            device_parameters: diff_maps(&baseline.flat_parameters(), &candidate.flat_parameters()),
+            configuration: diff_maps(&baseline.configuration, &candidate.configuration),
+            metadata: diff_maps(&baseline.metadata, &candidate.metadata),
+            channels,
+            baseline: baseline.clone(),
+            candidate: candidate.clone(),
+        }
+    }
+
+    /// Whether nothing but channel statistics differ
+    pub fn is_identical_setup(&self) -> bool {
+        self.plan_args.is_empty()
+            && self.device_parameters.is_empty()
+            && self.configuration.is_empty()
+            && self.metadata.is_empty()
+    }
+}
+
+/// Keys whose values differ between two maps, in key order
+fn diff_maps(
+    baseline: &BTreeMap<String, String>,
+    candidate: &BTreeMap<String, String>,
+) -> Vec<FieldDiff> {
+    let keys: BTreeSet<&String> = baseline.keys().chain(candidate.keys()).collect();
+    keys.into_iter()
+        .filter_map(|key| {
+            let (b, c) = (baseline.get(key), candidate.get(key));
+            (b != c).then(|| FieldDiff {
+                key: key.clone(),
+                baseline: b.cloned(),
+                candidate: c.cloned(),
+            })
+        })
+        .collect()
+}
+
+/// Summaries of recent runs, fed from the document stream
+#[derive(Debug)]
+pub struct RunHistory {
+    /// Where completed summaries are persisted (`None` = memory only)
+    directory: Option<PathBuf>,
+    capacity: usize,
+    /// Completed runs, oldest first
+    completed: VecDeque<RunSummary>,
+    /// Runs that have started but not stopped
+    active: HashMap<String, RunSummary>,
+    /// File changes not yet handed out by [`RunHistory::take_writes`]
+    writes: Vec<HistoryWrite>,
+}
+
+impl Default for RunHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_CAPACITY)
+    }
+}
+
+impl RunHistory {
+    /// In-memory history of up to `capacity` completed runs
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            directory: None,
+            capacity: capacity.max(1),
+            completed: VecDeque::new(),
+            active: HashMap::new(),
+            writes: Vec::new(),
+        }
+    }
+
+    /// History persisted as `<run_uid>.json` files in `directory`
+    ///
+    /// Existing summaries in the directory are loaded (newest `capacity`
+    /// runs) and older ones are deleted. Unreadable files are skipped with a
+    /// warning.
+    pub fn with_directory(directory: impl Into<PathBuf>, capacity: usize) -> Result<Self> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory)
+            .with_context(|| format!("Failed to create {}", directory.display()))?;
+
+        let mut history = Self::new(capacity);
+        let mut loaded = Vec::new();
+        for entry in std::fs::read_dir(&directory)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                match load_summary(&path) {
+                    Ok(summary) => loaded.push((path, summary)),
+                    Err(e) => {
+                        tracing::warn!(path = %path.display(), error = %e, "Skipping run summary");
+                    }
+                }
+            }
+        }
+        loaded.sort_by_key(|(_, summary)| summary.start_ns);
+        let excess = loaded.len().saturating_sub(history.capacity);
+        for (path, _) in loaded.drain(..excess) {
+            HistoryWrite::Remove(path).apply()?;
+        }
+        for (_, summary) in loaded {
+            history.push_completed(summary);
+        }
+        history.directory = Some(directory);
+        Ok(history)
+    }
+
+    /// File changes queued since the last call, oldest first
+    ///
+    /// Apply them in order, e.g. with [`HistoryWriter::submit`] while still
+    /// holding the lock that guards this history.
+    pub fn take_writes(&mut self) -> Vec<HistoryWrite> {
+        std::mem::take(&mut self.writes)
+    }
+
+    /// Apply queued file changes on the calling thread
+    pub fn flush(&mut self) -> Result<()> {
+        for write in self.take_writes() {
+            write.apply()?;
+        }
+        Ok(())
+    }
+
+    fn queue_save(&mut self, summary: &RunSummary) {
+        let Some(directory) = &self.directory else {
+            return;
+        };
+        match serde_json::to_string_pretty(summary) {
+            Ok(json) => self.writes.push(HistoryWrite::Save {
+                path: summary_path(directory, &summary.run_uid),
+                json,
+            }),
+            Err(e) => {
+                tracing::error!(run_uid = %summary.run_uid, error = %e, "Failed to serialize run summary");
+            }
+        }
+    }
+
+    /// Fold a document into the matching run
+    ///
+    /// Returns the run's summary once its StopDoc arrives.
+    pub fn observe(&mut self, doc: &Document) -> Option<&RunSummary> {
+        if let Document::Start(start) = doc {
+            self.active
+                .insert(start.uid.clone(), RunSummary::from_start(start));
+            return None;
+        }
+
+        let summary = self.active.get_mut(doc.run_uid())?;
+        summary.observe(doc);
+        if !summary.is_complete() {
+            return None;
+        }
+
+        let summary = self.active.remove(doc.run_uid())?;
+        self.queue_save(&summary);
+        self.push_completed(summary);
+        self.completed.back()
+    }
+
+    fn push_completed(&mut self, summary: RunSummary) {
+        self.completed.retain(|s| s.run_uid != summary.run_uid);
+        self.completed.push_back(summary);
+        while self.completed.len() > self.capacity {
+            let Some(evicted) = self.completed.pop_front() else {
+                break;
+            };
+            if let Some(directory) = &self.directory {
+                let path = summary_path(directory, &evicted.run_uid);
+                self.writes.push(HistoryWrite::Remove(path));
+            }
+        }
+    }
+
+    /// Record the signed manifest of a completed run's file
+    ///
+    /// Returns false if the run is unknown.
+    pub fn attach_provenance(&mut self, run_uid: &str, signed: SignedManifest) -> bool {
+        let Some(index) = self.completed.iter().position(|s| s.run_uid == run_uid) else {
+            return false;
+        };
+        self.completed[index].provenance = Some(signed);
+        let summary = self.completed[index].clone();
+        self.queue_save(&summary);
+        true
+    }
+
+    /// Attach a marker to a run
+    ///
+    /// `run_uid = None` targets the active run, which must be unambiguous.
+    /// Returns the UID of the run the marker was added to.
+    pub fn add_marker(&mut self, run_uid: Option<&str>, marker: RunMarker) -> Result<String> {
+        if marker.text.trim().is_empty() {
+            return Err(anyhow!("Marker text must not be empty"));
+        }
+        let summary = match run_uid {
+            Some(uid) => match self.active.get_mut(uid) {
+                Some(summary) => summary,
+                None => self
+                    .completed
+                    .iter_mut()
+                    .find(|s| s.run_uid == uid)
+                    .ok_or_else(|| anyhow!("Unknown run '{}'", uid))?,
+            },
+            None => {
+                if self.active.len() > 1 {
+                    return Err(anyhow!(
+                        "{} runs are active; specify a run UID",
+                        self.active.len()
+                    ));
+                }
+                self.active
+                    .values_mut()
+                    .next()
+                    .ok_or_else(|| anyhow!("No active run"))?
+            }
+        };
+
+        let index = summary
+            .markers
+            .partition_point(|m| m.time_ns <= marker.time_ns);
+        summary.markers.insert(index, marker);
+
+        // Active runs are saved with the rest of the summary at StopDoc
+        let run_uid = summary.run_uid.clone();
+        if summary.is_complete() {
+            let summary = summary.clone();
+            self.queue_save(&summary);
+        }
+        Ok(run_uid)
+    }
+
+    /// Completed runs, newest first
+    pub fn list(&self) -> impl Iterator<Item = &RunSummary> {
+        self.completed.iter().rev()
+    }
+
+    /// Summary of a completed or active run
+    pub fn get(&self, run_uid: &str) -> Option<&RunSummary> {
+        self.completed
+            .iter()
+            .find(|s| s.run_uid == run_uid)
+            .or_else(|| self.active.get(run_uid))
+    }
+
+    /// Compare two runs by UID
+    pub fn compare(&self, baseline_uid: &str, candidate_uid: &str) -> Result<RunDiff> {
+        let lookup = |uid: &str| {
+            self.get(uid)
+                .ok_or_else(|| anyhow!("Unknown run '{}'", uid))
+        };
+        Ok(RunDiff::compare(
+            lookup(baseline_uid)?,
+            lookup(candidate_uid)?,
+        ))
+    }
+}
+
+fn load_summary(path: &Path) -> Result<RunSummary> {
+    let json = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+fn summary_path(directory: &Path, run_uid: &str) -> PathBuf {
+    directory.join(format!("{}.json", run_uid))
+}
+
+/// Change to a [`RunHistory`] directory, snapshotted when it was queued
+#[derive(Debug, Clone, PartialEq)]
+pub enum HistoryWrite {
+    /// Write a serialized summary
+    Save { path: PathBuf, json: String },
+    /// Delete the summary of a run evicted from the history
+    Remove(PathBuf),
+}
+
+impl HistoryWrite {
+    /// Perform the change (blocking file I/O)
+    pub fn apply(&self) -> Result<()> {
+        match self {
+            HistoryWrite::Save { path, json } => std::fs::write(path, json)
+                .with_context(|| format!("Failed to write {}", path.display())),
+            HistoryWrite::Remove(path) => match std::fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    Err(e).with_context(|| format!("Failed to remove {}", path.display()))
+                }
+                _ => Ok(()),
+            },
+        }
+    }
+}
+
+/// Applies [`HistoryWrite`]s on the blocking thread pool, in submission order
+///
+/// Cloning yields a handle to the same writer task, which ends once every
+/// handle is dropped.
+#[derive(Debug, Clone)]
+pub struct HistoryWriter {
+    tx: mpsc::UnboundedSender<Vec<HistoryWrite>>,
+}
+
+impl HistoryWriter {
+    /// Start the writer task on the current tokio runtime
+    pub fn spawn() -> Self {
+        let (tx, mut rx) = mpsc::unbounded_channel::<Vec<HistoryWrite>>();
+        tokio::spawn(async move {
+            while let Some(writes) = rx.recv().await {
+                let applied = tokio::task::spawn_blocking(move || {
+                    for write in writes {
+                        if let Err(e) = write.apply() {
+                            tracing::error!(error = %format!("{:#}", e), "Failed to update run history");
+                        }
+                    }
+                })
+                .await;
+                if let Err(e) = applied {
+                    tracing::error!(error = %e, "Run history writer panicked");
+                }
+            }
+        });
+        Self { tx }
+    }
+
+    /// Queue writes without blocking
+    ///
+    /// Submitting while still holding the history's lock keeps writes from
+    /// concurrent callers in the order the history produced them.
+    pub fn submit(&self, writes: Vec<HistoryWrite>) {
+        if !writes.is_empty() && self.tx.send(writes).is_err() {
+            tracing::error!("Run history writer stopped; summary changes not persisted");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::experiment::document::{EventDoc, StopDoc};
+
+    fn run(history: &mut RunHistory, exposure: f64, samples: &[f64]) -> String {
+        let start = StartDoc::new("count", "dark_count").with_arg("num_points", "3");
+        let uid = start.uid.clone();
+        history.observe(&Document::Start(start));
+
+        let mut parameters = HashMap::new();
+        parameters.insert(
+            "camera".to_string(),
+            HashMap::from([("exposure_ms".to_string(), serde_json::json!(exposure))]),
+        );
+        let manifest = ExperimentManifest::new(&uid, "count", "dark_count", parameters);
+        history.observe(&Document::Manifest(manifest));
+
+        for (i, &value) in samples.iter().enumerate() {
+            let event = EventDoc::new(&uid, "desc", i as u32).with_datum("power", value);
+            history.observe(&Document::Event(event));
+        }
+        let mut stop = StopDoc::success(&uid, samples.len() as u32);
+        if exposure > 15.0 {
+            stop.metadata.insert(
+                INTEGRITY_METADATA_KEY.to_string(),
+                r#"{"camera":{"pool":4,"tap":1}}"#.to_string(),
+            );
+        }
+        assert!(history.observe(&Document::Stop(stop)).is_some());
+        uid
+    }
+
+    #[test]
+    fn test_channel_stats() {
+        let mut stats = ChannelStats::default();
+        for value in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0, f64::NAN] {
+            stats.push(value);
+        }
+        assert_eq!(stats.count, 8);
+        assert!((stats.mean - 5.0).abs() < 1e-12);
+        assert_eq!((stats.min as i64, stats.max as i64), (2, 9));
+        assert!((stats.std_dev() - 2.138).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_compare_highlights_changes() {
+        let mut history = RunHistory::default();
+        let good = run(&mut history, 10.0, &[1.0, 1.1, 0.9]);
+        let bad = run(&mut history, 20.0, &[2.0, 2.1, 1.9]);
+
+        let diff = history.compare(&good, &bad).unwrap();
+        assert!(diff.plan_args.is_empty());
+        assert!(diff.configuration.is_empty());
+        assert_eq!(
+            diff.device_parameters,
+            vec![FieldDiff {
+                key: "camera.exposure_ms".to_string(),
+                baseline: Some("10.0".to_string()),
+                candidate: Some("20.0".to_string()),
+            }]
+        );
+        assert!(!diff.is_identical_setup());
+        assert!(diff.run_info.iter().any(|field| field.key == "dropped"
+            && field.baseline.as_deref() == Some("0")
+            && field.candidate.as_deref() == Some("5")));
+
+        let power = &diff.channels[0];
+        assert_eq!(power.channel, "power");
+        assert!((power.mean_shift_sigma().unwrap() - 10.0).abs() < 1e-9);
+
+        assert!(history.compare(&good, "missing").is_err());
+        assert_eq!(history.list().next().unwrap().run_uid, bad);
+    }
+
+    #[test]
+    fn test_history_persists_completed_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let signer = common::provenance::RunSigner::from_seed(&[1; 32]);
+        let uid = {
+            let mut history = RunHistory::with_directory(dir.path(), 10).unwrap();
+            let uid = run(&mut history, 10.0, &[1.0]);
+            let manifest =
+                common::provenance::ProvenanceManifest::new(&uid, "run.h5", BTreeMap::new());
+            assert!(history.attach_provenance(&uid, signer.sign(&manifest).unwrap()));
+            assert!(!history.attach_provenance("unknown", signer.sign(&manifest).unwrap()));
+            history.flush().unwrap();
+            uid
+        };
+
+        let history = RunHistory::with_directory(dir.path(), 10).unwrap();
+        let summary = history.get(&uid).unwrap();
+        assert_eq!(summary.exit_status.as_deref(), Some("success"));
+        assert_eq!(summary.channels["power"].count, 1);
+        assert_eq!(summary.plan_args["num_points"], "3");
+        assert_eq!(summary.provenance.as_ref().unwrap().key_id, signer.key_id());
+    }
+
+    #[test]
+    fn test_markers_follow_run_into_catalog() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = |time_ns, text: &str| RunMarker {
+            time_ns,
+            text: text.to_string(),
+            author: String::new(),
+        };
+
+        let mut history = RunHistory::with_directory(dir.path(), 10).unwrap();
+        assert!(history.add_marker(None, marker(1, "no run")).is_err());
+
+        let start = StartDoc::new("count", "dark_count");
+        let uid = start.uid.clone();
+        history.observe(&Document::Start(start));
+        assert_eq!(history.add_marker(None, marker(20, "spike")).unwrap(), uid);
+        assert!(history.add_marker(None, marker(30, "  ")).is_err());
+        history.observe(&Document::Stop(StopDoc::success(&uid, 0)));
+
+        // Markers can be added after the fact and are kept in time order
+        history
+            .add_marker(Some(&uid), marker(10, "door opened"))
+            .unwrap();
+        assert!(history.add_marker(Some("missing"), marker(1, "x")).is_err());
+        history.flush().unwrap();
+
+        let history = RunHistory::with_directory(dir.path(), 10).unwrap();
+        let texts: Vec<_> = history
+            .get(&uid)
+            .unwrap()
+            .markers
+            .iter()
+            .map(|m| m.text.as_str())
+            .collect();
+        assert_eq!(texts, ["door opened", "spike"]);
+    }
+
+    #[test]
+    fn test_directory_keeps_capacity_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = || std::fs::read_dir(dir.path()).unwrap().count();
+
+        let mut history = RunHistory::with_directory(dir.path(), 3).unwrap();
+        let uids: Vec<_> = (0..4)
+            .map(|i| run(&mut history, 10.0, &[i as f64]))
+            .collect();
+        history.flush().unwrap();
+        assert_eq!(files(), 3);
+        assert!(!dir.path().join(format!("{}.json", uids[0])).exists());
+
+        // A smaller capacity trims the directory on the next start
+        let history = RunHistory::with_directory(dir.path(), 2).unwrap();
+        assert_eq!(files(), 2);
+        let kept: Vec<_> = history.list().map(|s| s.run_uid.clone()).collect();
+        assert_eq!(kept, [uids[3].clone(), uids[2].clone()]);
+    }
+}
@@ -34,7 +34,7 @@
 //! engine.start().await?;
 //!
 //! // Process documents as they arrive
-//! while let Some(doc) = docs.recv().await {
+//! while let Ok(doc) = docs.recv().await {
 //!     match doc {
 //!         Document::Event(e) => {
 //!             println!("Data: {:?}", e.data);
@@ -46,19 +46,53 @@
 //! }
 //! ```
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use tokio::sync::{broadcast, mpsc, Mutex, RwLock};
 use tokio::time::{sleep, Duration};
 use tracing::{debug, error, info, instrument, warn};
 
+use super::config_snapshot::{ConfigSnapshot, ConfigSnapshotSource, CONFIG_SNAPSHOT_METADATA_KEY};
+use super::document_bus::{DocumentBus, DocumentReceiver};
+use super::dry_run::{self, DryRunOptions, DryRunReport, SimulatedDevice};
 use super::plans::{Plan, PlanCommand};
+use super::templates::{batch_metadata, BatchTracker, TemplateRun};
+use common::acquisition::{AcquiredValue, AcquisitionMode, ACQUISITION_MODE_METADATA_PREFIX};
+use common::cancellation::{is_cancelled, CancellationToken, Cancelled};
 use common::capabilities::{FrameObserver, ObserverHandle};
+use common::cleanup::{CleanupPlan, CleanupReport, CleanupStepStatus, CLEANUP_METADATA_KEY};
+use common::coordinates::SAMPLE_REGISTRATION_METADATA_KEY;
+use common::core::DataQuality;
 use common::data::FrameView;
+use common::device_events::DeviceEvent;
+use common::document_transform::DocumentTransforms;
+use common::driver::Capability;
+use common::environment::ENVIRONMENT_METADATA_KEY;
 use common::experiment::document::{
-    new_uid, DataKey, DescriptorDoc, Document, EventDoc, ExperimentManifest, StartDoc, StopDoc,
+    new_uid, now_ns, DataKey, DescriptorDoc, DeviceEventDoc, Document, EventDoc,
+    ExperimentManifest, ProgressDoc, ProgressTracker, StartDoc, StopDoc,
 };
-use hardware::registry::DeviceRegistry;
+use common::frame_annotations::{
+    annotations_to_json, frame_annotations_key, AnnotationEnricher, FrameAnnotation,
+};
+use common::frame_enrichment::{
+    frame_channel_key, ChannelEnricher, ChannelSampleCache, FrameEnrichment, FrameEnrichmentConfig,
+};
+use common::integrity::{
+    dropped_since, total_dropped, DropLedger, DropReport, DropStage, INTEGRITY_METADATA_KEY,
+};
+use common::parking::{self, ParkedDevice, ParkingActions};
+use common::ramp::{RampSpec, RampState, POSITION_PARAMETER};
+use common::state_machine::{MachineKind, StateTracker};
+use common::time_budget::{
+    BudgetedOperation, OverrunPolicy, TimeBudget, TimeBudgetSummary, TIME_BUDGET_METADATA_KEY,
+    TIME_BUDGET_OVERRUN_KEY,
+};
+use common::validation::{RunValidator, VALIDATION_METADATA_KEY};
+use hardware::registry::{DeviceLocks, DeviceRegistry};
+
+/// Instance ID of the run engine in the [`StateTracker`]
+const STATE_MACHINE_ID: &str = "run_engine";
 
 /// Engine state
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -98,25 +132,43 @@ struct FrameCapture {
     width: u32,
     height: u32,
     frame_number: u64,
+    /// Capture timestamp from the camera (nanoseconds since UNIX epoch)
+    timestamp_ns: u64,
+    /// Channel values sampled at frame time (channel -> value)
+    channels: BTreeMap<String, f64>,
+    /// Overlay annotations current at frame time
+    annotations: Vec<FrameAnnotation>,
 }
 
 /// Observer that captures frames for experiment persistence
 struct ExperimentFrameObserver {
     tx: mpsc::Sender<FrameCapture>,
     device_id: String,
+    enrichment: FrameEnrichment,
 }
 
 impl FrameObserver for ExperimentFrameObserver {
     fn on_frame(&self, frame: &FrameView<'_>) {
+        let (channels, annotations) = if self.enrichment.is_empty() {
+            (BTreeMap::new(), Vec::new())
+        } else {
+            let metadata = self.enrichment.enrich(frame);
+            (metadata.channels, metadata.annotations)
+        };
         let capture = FrameCapture {
             device_id: self.device_id.clone(),
             data: frame.pixels().to_vec(),
             width: frame.width,
             height: frame.height,
             frame_number: frame.frame_number,
+            timestamp_ns: frame.timestamp_ns,
+            channels,
+            annotations,
         };
         // Non-blocking send - drop frames if channel is full
-        let _ = self.tx.try_send(capture);
+        if let Err(mpsc::error::TrySendError::Full(_)) = self.tx.try_send(capture) {
+            DropLedger::global().record(&self.device_id, DropStage::Tap, 1);
+        }
     }
 
     fn name(&self) -> &'static str {
@@ -130,12 +182,64 @@ struct RunContext {
     descriptor_uid: String,
     seq_num: u32,
     collected_data: HashMap<String, f64>,
+    /// Quality flags of `collected_data` values that are not good
+    collected_quality: HashMap<String, DataQuality>,
     collected_frames: HashMap<String, Vec<u8>>,
+    /// Channel values attached to collected frames, keyed by `frame_channel_key`
+    collected_frame_channels: HashMap<String, f64>,
+    /// JSON annotations of collected frames, keyed by `frame_annotations_key`
+    collected_frame_annotations: HashMap<String, String>,
+    /// Corrected timestamps of the values in `collected_data` and
+    /// `collected_frames`, recorded as the event's per-field timestamps
+    collected_timestamps: HashMap<String, u64>,
     current_positions: HashMap<String, f64>,
     frame_observers: HashMap<String, ObserverHandle>,
     frame_channels: HashMap<String, mpsc::Receiver<FrameCapture>>,
     /// Unix timestamp in nanoseconds when the run started
     run_start_ns: u64,
+    /// Plan type of the active run
+    plan_type: String,
+    /// Devices the plan moves or reads (aliases resolved to device IDs)
+    devices: Vec<String>,
+    /// Total points expected (from `Plan::num_points`)
+    points_total: u32,
+    /// Rolling per-point timing for ETA estimation
+    progress: ProgressTracker,
+    /// Most recently emitted progress document
+    latest_progress: Option<ProgressDoc>,
+    /// Frame enrichment sampling, when channels are configured
+    enrichment: Option<EnrichmentSampler>,
+    /// Cumulative drop counts when the run opened
+    drop_baseline: DropReport,
+    /// Acquisition mode per detector (single reads if absent)
+    acquisition_modes: HashMap<String, AcquisitionMode>,
+    /// Device events not yet recorded as documents
+    device_events: broadcast::Receiver<DeviceEvent>,
+    /// Validation rules of the run and their results so far
+    validator: RunValidator,
+    /// Operations of the current point that overran their time budget
+    overruns: Vec<String>,
+}
+
+/// Time budget of the active run and the overruns so far
+struct RunBudget {
+    budget: TimeBudget,
+    summary: TimeBudgetSummary,
+}
+
+/// Result of a command processed within a time budget
+enum BudgetOutcome {
+    /// The command completed (or was skipped); true if an event was emitted
+    Processed(bool),
+    /// The run paused on an overrun; the command runs again on resume
+    Paused(PlanCommand),
+}
+
+/// Background sampling of frame enrichment channels for the active run
+struct EnrichmentSampler {
+    channels: Vec<String>,
+    cache: ChannelSampleCache,
+    task: tokio::task::JoinHandle<()>,
 }
 
 /// The RunEngine orchestrates experiment execution
@@ -149,8 +253,8 @@ pub struct RunEngine {
     /// Queue of plans to execute
     plan_queue: Mutex<Vec<QueuedPlan>>,
 
-    /// Document broadcast channel
-    doc_sender: broadcast::Sender<Document>,
+    /// Sequence-numbered document fan-out
+    documents: DocumentBus,
 
     /// Pause request flag
     pause_requested: RwLock<bool>,
@@ -158,33 +262,79 @@ pub struct RunEngine {
     /// Abort request flag
     abort_requested: RwLock<bool>,
 
+    /// Cancelled on abort to end the current run's in-flight hardware awaits
+    run_cancel: RwLock<CancellationToken>,
+
     /// Current run context (when running)
     run_context: Mutex<Option<RunContext>>,
 
     /// Last checkpoint label (for resume)
     last_checkpoint: RwLock<Option<String>>,
+
+    /// Effective daemon configuration recorded with each run
+    config_source: std::sync::RwLock<Option<Arc<dyn ConfigSnapshotSource>>>,
+
+    /// Template batches with child runs still queued or running
+    batches: Mutex<Vec<BatchTracker>>,
 }
 
 impl RunEngine {
     /// Create a new RunEngine
     pub fn new(device_registry: Arc<DeviceRegistry>) -> Self {
-        let (doc_sender, _) = broadcast::channel(1024);
-
+        StateTracker::global().record(
+            MachineKind::RunEngine,
+            STATE_MACHINE_ID,
+            &EngineState::Idle.to_string(),
+            "create",
+        );
         Self {
             state: RwLock::new(EngineState::Idle),
             device_registry,
             plan_queue: Mutex::new(Vec::new()),
-            doc_sender,
+            documents: DocumentBus::default(),
             pause_requested: RwLock::new(false),
             abort_requested: RwLock::new(false),
+            run_cancel: RwLock::new(CancellationToken::new()),
             run_context: Mutex::new(None),
             last_checkpoint: RwLock::new(None),
+            config_source: std::sync::RwLock::new(None),
+            batches: Mutex::new(Vec::new()),
         }
     }
 
+    /// Per-consumer transforms for subscribers of the document stream
+    ///
+    /// Consumers apply their chain to each document they receive; see
+    /// [`common::document_transform`].
+    pub fn document_transforms(&self) -> Arc<DocumentTransforms> {
+        self.device_registry.document_transforms()
+    }
+
+    /// Set where the daemon configuration in each run's config snapshot comes from
+    ///
+    /// Without a source, snapshots still hold parameter values and software
+    /// versions but no daemon configuration.
+    pub fn set_config_snapshot_source(&self, source: Arc<dyn ConfigSnapshotSource>) {
+        *self
+            .config_source
+            .write()
+            .unwrap_or_else(|p| p.into_inner()) = Some(source);
+    }
+
     /// Subscribe to document stream
-    pub fn subscribe(&self) -> broadcast::Receiver<Document> {
-        self.doc_sender.subscribe()
+    pub fn subscribe(&self) -> DocumentReceiver {
+        self.documents.subscribe()
+    }
+
+    /// Subscribe with a custom queue depth (e.g. for storage writers that
+    /// must ride out slow disks without missing documents)
+    pub fn subscribe_with_capacity(&self, capacity: usize) -> DocumentReceiver {
+        self.documents.subscribe_with_capacity(capacity)
+    }
+
+    /// Sequence number of the last emitted document (0 if none)
+    pub fn last_document_seq(&self) -> u64 {
+        self.documents.last_seq()
     }
 
     /// Get current engine state
@@ -192,6 +342,17 @@ impl RunEngine {
         *self.state.read().await
     }
 
+    /// Move to `state`, recording the transition with the state tracker
+    async fn set_state(&self, state: EngineState, trigger: &str) {
+        *self.state.write().await = state;
+        StateTracker::global().record(
+            MachineKind::RunEngine,
+            STATE_MACHINE_ID,
+            &state.to_string(),
+            trigger,
+        );
+    }
+
     /// Get the start time (Unix nanoseconds) of the current run, if any
     pub async fn current_run_start_ns(&self) -> Option<u64> {
         self.run_context
@@ -235,6 +396,78 @@ impl RunEngine {
         run_uid
     }
 
+    /// Queue the child runs of an expanded template as one batch
+    ///
+    /// Returns the batch UID and the child run UIDs in batch order. A
+    /// [`Document::BatchSummary`] follows the StopDoc of the last child to end.
+    pub async fn queue_batch(
+        &self,
+        template_name: &str,
+        runs: Vec<(Box<dyn Plan>, TemplateRun)>,
+    ) -> (String, Vec<String>) {
+        let run_uids: Vec<String> = runs.iter().map(|_| new_uid()).collect();
+        let tracker = BatchTracker::new(
+            template_name,
+            run_uids
+                .iter()
+                .cloned()
+                .zip(runs.iter().map(|(_, run)| run.variables.clone())),
+        );
+        let batch_uid = tracker.batch_uid().to_string();
+        info!(batch_uid = %batch_uid, template = %template_name, runs = run_uids.len(), "Queueing batch");
+
+        // Track the batch before any child can start
+        self.batches.lock().await.push(tracker);
+        let mut queue = self.plan_queue.lock().await;
+        for ((plan, run), run_uid) in runs.into_iter().zip(&run_uids) {
+            let mut metadata = run.metadata;
+            metadata.extend(batch_metadata(
+                &batch_uid,
+                template_name,
+                run.index,
+                &run_uids,
+            ));
+            queue.push(QueuedPlan {
+                plan,
+                metadata,
+                run_uid: run_uid.clone(),
+            });
+        }
+
+        (batch_uid, run_uids)
+    }
+
+    /// Record how a run ended; emits the batch summary when it was the last
+    /// child of a batch to end
+    async fn record_batch_outcome(
+        &self,
+        run_uid: &str,
+        exit_status: &str,
+        reason: &str,
+        num_events: u32,
+    ) {
+        let summary = {
+            let mut batches = self.batches.lock().await;
+            let Some(pos) = batches
+                .iter_mut()
+                .position(|batch| batch.record(run_uid, exit_status, reason, num_events))
+            else {
+                return;
+            };
+            if !batches[pos].is_complete() {
+                return;
+            }
+            batches.remove(pos).summary()
+        };
+        info!(
+            batch_uid = %summary.batch_uid,
+            succeeded = summary.count("success"),
+            runs = summary.runs.len(),
+            "Batch complete"
+        );
+        self.emit_document(Document::BatchSummary(summary)).await;
+    }
+
     /// Start executing queued plans
     #[instrument(skip(self), err)]
     pub async fn start(&self) -> anyhow::Result<()> {
@@ -246,6 +479,7 @@ impl RunEngine {
         // Reset flags
         *self.pause_requested.write().await = false;
         *self.abort_requested.write().await = false;
+        *self.run_cancel.write().await = CancellationToken::new();
 
         // Get next plan from queue
         let queued = {
@@ -256,7 +490,7 @@ impl RunEngine {
             queue.remove(0)
         };
 
-        *self.state.write().await = EngineState::Running;
+        self.set_state(EngineState::Running, "start").await;
         info!("Engine started");
 
         // Execute the plan
@@ -286,7 +520,7 @@ impl RunEngine {
 
         info!("Resuming from pause");
         *self.pause_requested.write().await = false;
-        *self.state.write().await = EngineState::Running;
+        self.set_state(EngineState::Running, "resume").await;
         Ok(())
     }
 
@@ -313,8 +547,8 @@ impl RunEngine {
                 match current_state {
                     EngineState::Running | EngineState::Paused => {
                         info!(reason = %reason, "Abort requested for current run");
-                        *self.abort_requested.write().await = true;
-                        *self.state.write().await = EngineState::Aborting;
+                        self.request_abort().await;
+                        self.set_state(EngineState::Aborting, "abort").await;
                         Ok(())
                     }
                     _ => anyhow::bail!("Cannot abort: engine is {}", current_state),
@@ -325,8 +559,8 @@ impl RunEngine {
                 let current_run_uid = self.current_run_uid().await;
                 if current_run_uid.as_deref() == Some(uid) {
                     info!(run_uid = %uid, reason = %reason, "Abort requested for current run");
-                    *self.abort_requested.write().await = true;
-                    *self.state.write().await = EngineState::Aborting;
+                    self.request_abort().await;
+                    self.set_state(EngineState::Aborting, "abort").await;
                     return Ok(());
                 }
 
@@ -334,12 +568,14 @@ impl RunEngine {
                 let mut queue = self.plan_queue.lock().await;
                 if let Some(pos) = queue.iter().position(|q| q.run_uid == uid) {
                     let removed = queue.remove(pos);
+                    drop(queue);
                     info!(
                         run_uid = %uid,
                         plan_type = %removed.plan.plan_type(),
                         reason = %reason,
                         "Removed queued plan"
                     );
+                    self.record_batch_outcome(uid, "abort", reason, 0).await;
                     return Ok(());
                 }
 
@@ -352,12 +588,66 @@ impl RunEngine {
     /// Halt immediately (emergency stop)
     pub async fn halt(&self) -> anyhow::Result<()> {
         warn!("HALT requested - emergency stop");
-        *self.abort_requested.write().await = true;
-        *self.state.write().await = EngineState::Aborting;
-        // In a real implementation, this would also send stop commands to all hardware
+        self.request_abort().await;
+        self.set_state(EngineState::Aborting, "halt").await;
         Ok(())
     }
 
+    /// Flag the current run for abort and cancel the hardware operation it
+    /// is waiting on; a cancelled move also stops its axis
+    async fn request_abort(&self) {
+        *self.abort_requested.write().await = true;
+        self.run_cancel.read().await.cancel();
+    }
+
+    /// Token of the current run, passed to every hardware await
+    async fn cancel_token(&self) -> CancellationToken {
+        self.run_cancel.read().await.clone()
+    }
+
+    /// Snapshot of the configuration in effect; a failing source only leaves
+    /// the daemon configuration out
+    async fn capture_config_snapshot(
+        &self,
+        run_uid: &str,
+        parameters: &HashMap<String, HashMap<String, serde_json::Value>>,
+    ) -> ConfigSnapshot {
+        let source = self
+            .config_source
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone();
+        let daemon_config = match source {
+            Some(source) => source.daemon_config().await.unwrap_or_else(|e| {
+                warn!(run_uid = %run_uid, error = %e, "Failed to capture daemon configuration");
+                String::new()
+            }),
+            None => String::new(),
+        };
+        ConfigSnapshot::new(run_uid, daemon_config, parameters)
+    }
+
+    /// End a run whose settings are rejected after the engine started it
+    ///
+    /// The run still gets a StartDoc and a failed StopDoc, and its batch an
+    /// outcome, and the engine returns to Idle so later plans can run.
+    async fn fail_run_setup(
+        &self,
+        start_doc: StartDoc,
+        error: anyhow::Error,
+    ) -> anyhow::Result<()> {
+        let run_uid = start_doc.uid.clone();
+        let reason = format!("{:#}", error);
+        error!(run_uid = %run_uid, error = %reason, "Run settings rejected");
+        self.emit_document(Document::Start(start_doc)).await;
+        self.emit_document(Document::Stop(StopDoc::fail(&run_uid, &reason, 0)))
+            .await;
+        self.record_batch_outcome(&run_uid, "fail", &reason, 0)
+            .await;
+        self.set_state(EngineState::Idle, "failed").await;
+        Err(error)
+    }
+
     /// Execute a single plan
     #[instrument(skip(self, queued), fields(run_uid = %queued.run_uid, plan_type = %queued.plan.plan_type()), err)]
     async fn execute_plan(&self, mut queued: QueuedPlan) -> anyhow::Result<()> {
@@ -370,11 +660,76 @@ impl RunEngine {
         start_doc.metadata = queued.metadata;
         start_doc.hints = plan.movers();
 
+        // Per-detector acquisition modes requested in the run metadata
+        let acquisition_modes = match parse_acquisition_modes(&start_doc.metadata) {
+            Ok(modes) => modes,
+            Err(e) => return self.fail_run_setup(start_doc, e).await,
+        };
+
+        // What to do to the hardware if the run is aborted or fails; a cleanup
+        // plan queued with the run replaces the plan's own
+        let cleanup = match CleanupPlan::from_metadata(&start_doc.metadata) {
+            Ok(Some(cleanup)) => Some(cleanup),
+            Ok(None) => plan.cleanup(),
+            Err(e) => return self.fail_run_setup(start_doc, e).await,
+        }
+        .filter(|cleanup| !cleanup.is_empty());
+        if let Some(Err(e)) = cleanup.as_ref().map(CleanupPlan::validate) {
+            return self.fail_run_setup(start_doc, e).await;
+        }
+
+        // Deadlines for the reads and moves of each point
+        let time_budget = match TimeBudget::from_metadata(&start_doc.metadata) {
+            Ok(Some(budget)) => Some(budget),
+            Ok(None) => plan.time_budget(),
+            Err(e) => return self.fail_run_setup(start_doc, e).await,
+        }
+        .filter(|budget| !budget.is_empty());
+        let mut budget = None;
+        if let Some(time_budget) = time_budget {
+            if let Err(e) = time_budget.validate() {
+                return self.fail_run_setup(start_doc, e).await;
+            }
+            budget = Some(RunBudget {
+                summary: TimeBudgetSummary::new(time_budget.on_overrun),
+                budget: time_budget,
+            });
+        }
+
+        // Keep the sample coordinate system with the run so sample-space
+        // positions can be mapped back to the stage later
+        if let Some(registration) = self.device_registry.active_sample_registration() {
+            start_doc
+                .metadata
+                .entry(SAMPLE_REGISTRATION_METADATA_KEY.to_string())
+                .or_insert_with(|| registration.to_metadata_value());
+        }
+
         let run_uid = start_doc.uid.clone();
-        self.emit_document(Document::Start(start_doc.clone())).await;
+
+        // What to do to the hardware if the run pauses; the plan's own
+        // actions replace the configured ones for its devices
+        let mut parking_actions = self.device_registry.parking_actions();
+        parking_actions.extend(plan.parking_actions());
 
         // Capture experiment manifest - snapshot all hardware parameters (bd-ej44)
         let parameter_snapshot = self.device_registry.snapshot_all_parameters();
+
+        // Record the configuration the run starts under so it can be reproduced
+        let snapshot = self
+            .capture_config_snapshot(&run_uid, &parameter_snapshot)
+            .await;
+        match snapshot.to_json() {
+            Ok(json) => {
+                start_doc
+                    .metadata
+                    .insert(CONFIG_SNAPSHOT_METADATA_KEY.to_string(), json);
+            }
+            Err(e) => warn!(run_uid = %run_uid, error = %e, "Failed to record config snapshot"),
+        }
+
+        self.emit_document(Document::Start(start_doc.clone())).await;
+
         let manifest = ExperimentManifest::new(
             &run_uid,
             &start_doc.plan_type,
@@ -383,6 +738,19 @@ impl RunEngine {
         )
         .with_metadata(start_doc.metadata.clone());
 
+        // Record instrument identities and flag firmware changes since the
+        // last run; a failed inventory never blocks the run
+        let manifest = match self.device_registry.refresh_inventory().await {
+            Ok(inventory) => manifest.with_inventory(
+                inventory.identities(),
+                inventory.changes.iter().map(ToString::to_string).collect(),
+            ),
+            Err(e) => {
+                warn!(run_uid = %run_uid, error = %e, "Failed to refresh instrument inventory");
+                manifest
+            }
+        };
+
         // Log manifest creation
         info!(
             run_uid = %run_uid,
@@ -400,6 +768,19 @@ impl RunEngine {
         let mut frame_observers = HashMap::new();
         let mut frame_channels = HashMap::new();
 
+        // Frame enrichment: attach sampled channel values to each frame
+        let enrichment_config = self.device_registry.frame_enrichment();
+        let enrichment_cache = ChannelSampleCache::new();
+        let enrichment = if enrichment_config.is_enabled() {
+            FrameEnrichment::new().with_enricher(Arc::new(ChannelEnricher::from_config(
+                &enrichment_config,
+                enrichment_cache.clone(),
+            )))
+        } else {
+            FrameEnrichment::new()
+        };
+        let annotations = self.device_registry.frame_annotations();
+
         for det_id in plan.detectors() {
             if let Some(producer) = self.device_registry.get_frame_producer(&det_id) {
                 if producer.supports_observers() {
@@ -410,6 +791,9 @@ impl RunEngine {
                     let observer = Box::new(ExperimentFrameObserver {
                         tx,
                         device_id: det_id.to_string(),
+                        enrichment: enrichment.clone().with_enricher(Arc::new(
+                            AnnotationEnricher::new(det_id.clone(), annotations.clone()),
+                        )),
                     });
 
                     // Register observer
@@ -430,46 +814,99 @@ impl RunEngine {
         // Create and emit DescriptorDoc for the primary stream
         let mut descriptor = DescriptorDoc::new(&run_uid, "primary");
 
-        // Populate descriptor data keys
+        // Populate descriptor data keys. Keys use the name the plan refers to
+        // (possibly a channel alias); `source` is the resolved hardware channel
+        // so both names are recorded.
+        let aliases = self.device_registry.aliases();
         for det in plan.detectors() {
+            let source = aliases.resolve(&det).to_string();
             if let Some(producer) = self.device_registry.get_frame_producer(&det) {
                 let (w, h) = producer.resolution();
                 // Assume uint16 for now, or check metadata if available
-                // Assume uint16 for now, or check metadata if available
-                let mut key = DataKey::array(&det, vec![h as i32, w as i32]);
+                let mut key = DataKey::array(&source, vec![h as i32, w as i32]);
                 key.dtype = "uint16".to_string();
                 descriptor.data_keys.insert(det.clone(), key);
             } else {
                 descriptor
                     .data_keys
-                    .insert(det.clone(), DataKey::scalar(&det, ""));
+                    .insert(det.clone(), DataKey::scalar(&source, ""));
             }
         }
         for mover in plan.movers() {
+            let source = aliases.resolve(&mover).to_string();
             descriptor
                 .data_keys
-                .insert(mover.clone(), DataKey::scalar(&mover, ""));
+                .insert(mover.clone(), DataKey::scalar(&source, ""));
+        }
+        for (det, mode) in &acquisition_modes {
+            descriptor.configuration.insert(
+                format!("{}{}", ACQUISITION_MODE_METADATA_PREFIX, det),
+                mode.to_string(),
+            );
+        }
+        for (alias, target) in aliases.iter() {
+            descriptor
+                .configuration
+                .insert(format!("alias.{}", alias), target.to_string());
+        }
+        if enrichment_config.is_enabled() {
+            for det in frame_observers.keys() {
+                for channel in &enrichment_config.channels {
+                    let source = aliases.resolve(channel).to_string();
+                    descriptor.data_keys.insert(
+                        frame_channel_key(det, channel),
+                        DataKey::scalar(&source, ""),
+                    );
+                }
+            }
+            descriptor.configuration.insert(
+                "frame_enrichment.channels".to_string(),
+                enrichment_config.channels.join(","),
+            );
         }
 
         let descriptor_uid = descriptor.uid.clone();
         self.emit_document(Document::Descriptor(descriptor)).await;
 
+        let enrichment = (enrichment_config.is_enabled() && !frame_observers.is_empty())
+            .then(|| self.spawn_enrichment_sampler(enrichment_config, enrichment_cache));
+
         // Initialize run context
         {
+            let drop_baseline = self.device_registry.drop_report().await;
+            let run_start_ns = now_ns();
+            let points_total = u32::try_from(plan.num_points()).unwrap_or(u32::MAX);
             let mut ctx = self.run_context.lock().await;
             *ctx = Some(RunContext {
                 run_uid: run_uid.clone(),
                 descriptor_uid,
                 seq_num: 0,
                 collected_data: HashMap::new(),
+                collected_quality: HashMap::new(),
                 collected_frames: HashMap::new(),
+                collected_frame_channels: HashMap::new(),
+                collected_frame_annotations: HashMap::new(),
+                collected_timestamps: HashMap::new(),
                 current_positions: HashMap::new(),
                 frame_observers,
                 frame_channels,
-                run_start_ns: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .map(|d| d.as_nanos() as u64)
-                    .unwrap_or(0),
+                run_start_ns,
+                plan_type: plan.plan_type().to_string(),
+                devices: plan
+                    .movers()
+                    .into_iter()
+                    .chain(plan.detectors())
+                    .map(|name| self.device_registry.resolve_channel(&name).device_id)
+                    .collect(),
+                points_total,
+                progress: ProgressTracker::new(run_start_ns),
+                latest_progress: None,
+                enrichment,
+                drop_baseline,
+                acquisition_modes,
+                device_events: self.device_registry.subscribe_device_events(),
+                validator: RunValidator::new(self.device_registry.validation_rules()),
+                overruns: Vec::new(),
             });
         }
 
@@ -477,6 +914,8 @@ impl RunEngine {
         let mut num_events = 0u32;
         let mut exit_status = "success";
         let mut exit_reason = String::new();
+        // Command to run again when a run paused by a time budget overrun resumes
+        let mut retry_on_resume = None;
 
         loop {
             // Check for abort
@@ -488,6 +927,9 @@ impl RunEngine {
 
             // Check for pause (only at checkpoints, handled in command processing)
             if *self.state.read().await == EngineState::Paused {
+                // Leave the rig safe for however long the pause lasts
+                let parked = self.park_devices(&parking_actions).await;
+
                 // Wait for resume or abort
                 loop {
                     sleep(Duration::from_millis(100)).await;
@@ -501,37 +943,63 @@ impl RunEngine {
                     }
                 }
                 if exit_reason.is_empty() {
+                    self.unpark_devices(&parked).await;
                     continue;
                 } else {
+                    // An aborted run leaves the devices parked
                     break;
                 }
             }
 
             // Get next command
-            let cmd = match plan.next_command() {
-                Some(cmd) => cmd,
-                None => {
-                    // Plan completed successfully
-                    break;
-                }
+            let Some(cmd) = retry_on_resume.take().or_else(|| plan.next_command()) else {
+                // Plan completed successfully
+                break;
             };
 
+            // Device events land in the run before the data that follows them
+            self.record_device_events().await;
+
             // Process command
-            match self.process_command(cmd).await {
-                Ok(event_emitted) => {
+            let result = match budget.as_mut() {
+                Some(budget) => self.process_budgeted(cmd, budget).await,
+                None => self
+                    .process_command(cmd)
+                    .await
+                    .map(BudgetOutcome::Processed),
+            };
+            match result {
+                Ok(BudgetOutcome::Processed(event_emitted)) => {
                     if event_emitted {
                         num_events += 1;
                     }
                 }
+                Ok(BudgetOutcome::Paused(cmd)) => {
+                    retry_on_resume = Some(cmd);
+                }
+                // An abort cancelled the command in flight; reported at the top of the loop
+                Err(e) if is_cancelled(&e) => {
+                    debug!("Command cancelled by abort");
+                }
                 Err(e) => {
                     error!(error = %e, "Plan execution failed");
                     exit_status = "fail";
                     exit_reason = e.to_string();
+                    // Nobody is left to resume: leave the rig parked
+                    self.park_devices(&parking_actions).await;
                     break;
                 }
             }
         }
 
+        // Leave the hardware safe, however the run ended early
+        let cleanup_report = match &cleanup {
+            Some(cleanup) if exit_status != "success" => {
+                Some(self.run_cleanup(cleanup, exit_status).await)
+            }
+            _ => None,
+        };
+
         // Clean up frame observers before emitting StopDoc (bd-b86g.3)
         {
             let mut ctx_guard = self.run_context.lock().await;
@@ -551,20 +1019,132 @@ impl RunEngine {
                 }
                 // Clear channels
                 ctx.frame_channels.clear();
+                if let Some(sampler) = ctx.enrichment.take() {
+                    sampler.task.abort();
+                }
             }
         }
 
+        self.record_device_events().await;
+
         // Emit StopDoc
-        let stop_doc = match exit_status {
+        let mut stop_doc = match exit_status {
             "success" => StopDoc::success(&run_uid, num_events),
             "abort" => StopDoc::abort(&run_uid, &exit_reason, num_events),
             _ => StopDoc::fail(&run_uid, &exit_reason, num_events),
         };
-        self.emit_document(Document::Stop(stop_doc)).await;
+
+        if let Some(report) = cleanup_report {
+            match serde_json::to_string(&report) {
+                Ok(json) => {
+                    stop_doc
+                        .metadata
+                        .insert(CLEANUP_METADATA_KEY.to_string(), json);
+                }
+                Err(e) => warn!(error = %e, "Failed to encode cleanup report"),
+            }
+        }
+
+        if let Some(summary) = budget.map(|budget| budget.summary) {
+            if !summary.is_empty() {
+                warn!(
+                    run_uid = %run_uid,
+                    overruns = summary.overruns,
+                    "Operations overran their time budget during the run"
+                );
+                match serde_json::to_string(&summary) {
+                    Ok(json) => {
+                        stop_doc
+                            .metadata
+                            .insert(TIME_BUDGET_METADATA_KEY.to_string(), json);
+                    }
+                    Err(e) => warn!(error = %e, "Failed to encode time budget summary"),
+                }
+            }
+        }
+
+        // Ambient conditions over the run, for correlating drift with data
+        if let Some(run_start_ns) = self.current_run_start_ns().await {
+            let environment = self
+                .device_registry
+                .environment_log()
+                .summary(run_start_ns, stop_doc.time_ns);
+            if !environment.is_empty() {
+                match serde_json::to_string(&environment) {
+                    Ok(json) => {
+                        stop_doc
+                            .metadata
+                            .insert(ENVIRONMENT_METADATA_KEY.to_string(), json);
+                    }
+                    Err(e) => warn!(error = %e, "Failed to encode environment summary"),
+                }
+            }
+        }
+
+        // How many points the validation rules flagged
+        let validation = self
+            .run_context
+            .lock()
+            .await
+            .as_ref()
+            .filter(|ctx| !ctx.validator.is_empty())
+            .map(|ctx| ctx.validator.summary().clone());
+        if let Some(summary) = validation {
+            if !summary.is_clean() {
+                warn!(
+                    run_uid = %run_uid,
+                    flagged = summary.flagged_points,
+                    points = summary.points,
+                    "Points failed validation during the run"
+                );
+            }
+            match serde_json::to_string(&summary) {
+                Ok(json) => {
+                    stop_doc
+                        .metadata
+                        .insert(VALIDATION_METADATA_KEY.to_string(), json);
+                }
+                Err(e) => warn!(error = %e, "Failed to encode validation summary"),
+            }
+        }
+
+        // Frames and samples lost during the run, per source and stage
+        let drop_baseline = self
+            .run_context
+            .lock()
+            .await
+            .as_mut()
+            .map(|ctx| std::mem::take(&mut ctx.drop_baseline));
+        if let Some(baseline) = drop_baseline {
+            let dropped = dropped_since(&self.device_registry.drop_report().await, &baseline);
+            if !dropped.is_empty() {
+                warn!(
+                    run_uid = %run_uid,
+                    dropped = total_dropped(&dropped),
+                    "Data was dropped during the run"
+                );
+                match serde_json::to_string(&dropped) {
+                    Ok(json) => {
+                        stop_doc
+                            .metadata
+                            .insert(INTEGRITY_METADATA_KEY.to_string(), json);
+                    }
+                    Err(e) => warn!(error = %e, "Failed to encode drop counts"),
+                }
+            }
+        }
+        self.emit_document(Document::Stop(stop_doc.clone())).await;
+        self.record_batch_outcome(
+            &run_uid,
+            &stop_doc.exit_status,
+            &stop_doc.reason,
+            stop_doc.num_events,
+        )
+        .await;
 
         // Clear run context
         *self.run_context.lock().await = None;
-        *self.state.write().await = EngineState::Idle;
+        self.set_state(EngineState::Idle, "finished").await;
 
         info!(
             run_uid = %run_uid,
@@ -590,14 +1170,49 @@ impl RunEngine {
 
                 // Update current positions in context
                 if let Some(ctx) = self.run_context.lock().await.as_mut() {
+                    // Frames captured right after a move should carry the new
+                    // position, not the last periodic sample
+                    if let Some(sampler) = &ctx.enrichment {
+                        let moved = self.device_registry.resolve_channel(&device_id);
+                        for channel in &sampler.channels {
+                            if self.device_registry.resolve_channel(channel) == moved {
+                                sampler.cache.record(channel, position, now_ns());
+                            }
+                        }
+                    }
                     ctx.current_positions.insert(device_id, position);
                 }
                 Ok(false)
             }
 
+            PlanCommand::MoveToPosition {
+                device_id,
+                position_name,
+            } => {
+                debug!(device = %device_id, position = %position_name, "Moving to named position");
+                let positioner = self
+                    .device_registry
+                    .get_discrete_positioner(&device_id)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Device '{}' is not a discrete positioner", device_id)
+                    })?;
+                positioner
+                    .move_to_position_cancellable(&position_name, &self.cancel_token().await)
+                    .await?;
+
+                // Events record the slot index as the positioner's position
+                if let Some(index) = positioner.position_index(&position_name) {
+                    if let Some(ctx) = self.run_context.lock().await.as_mut() {
+                        ctx.current_positions.insert(device_id, index as f64);
+                    }
+                }
+                Ok(false)
+            }
+
             PlanCommand::Read { device_id } => {
                 // Check if we have a frame channel for this device
                 let mut is_frame_device = false;
+                let cancel = self.cancel_token().await;
 
                 {
                     // Scope to hold lock briefly
@@ -605,12 +1220,35 @@ impl RunEngine {
                     if let Some(ctx) = ctx_guard.as_mut() {
                         if let Some(rx) = ctx.frame_channels.get_mut(&device_id) {
                             is_frame_device = true;
-                            // Wait for a frame (async, non-blocking channel receive)
-                            match rx.recv().await {
+                            // Wait for a frame, or until the run is aborted
+                            let capture = tokio::select! {
+                                capture = rx.recv() => capture,
+                                () = cancel.cancelled() => return Err(Cancelled.into()),
+                            };
+                            match capture {
                                 Some(capture) => {
                                     let data_len = capture.data.len();
                                     let frame_num = capture.frame_number;
+                                    if capture.timestamp_ns > 0 {
+                                        let timestamp_ns = self
+                                            .device_registry
+                                            .timestamp_corrections()
+                                            .correct(&device_id, capture.timestamp_ns);
+                                        ctx.collected_timestamps
+                                            .insert(device_id.clone(), timestamp_ns);
+                                    }
                                     ctx.collected_frames.insert(device_id.clone(), capture.data);
+                                    ctx.collected_frame_channels.extend(
+                                        capture.channels.into_iter().map(|(channel, value)| {
+                                            (frame_channel_key(&device_id, &channel), value)
+                                        }),
+                                    );
+                                    if !capture.annotations.is_empty() {
+                                        ctx.collected_frame_annotations.insert(
+                                            frame_annotations_key(&device_id),
+                                            annotations_to_json(&capture.annotations),
+                                        );
+                                    }
                                     debug!(
                                         device = %device_id,
                                         size = %data_len,
@@ -627,11 +1265,32 @@ impl RunEngine {
                 }
 
                 if !is_frame_device {
-                    // Standard scalar read
-                    let value = self.execute_read(&device_id).await?;
+                    // Standard scalar read, in the mode the run asked for
+                    let mode = self
+                        .run_context
+                        .lock()
+                        .await
+                        .as_ref()
+                        .and_then(|ctx| ctx.acquisition_modes.get(&device_id).copied())
+                        .unwrap_or_default();
+                    let before_ns = now_ns();
+                    let (value, quality) = self.execute_read(&device_id, mode).await?;
+                    // The reading was taken somewhere during the call
+                    let read_ns = before_ns + now_ns().saturating_sub(before_ns) / 2;
+                    let timestamp_ns = self
+                        .device_registry
+                        .timestamp_corrections()
+                        .correct(&device_id, read_ns);
 
                     // Store in context for next EmitEvent
                     if let Some(ctx) = self.run_context.lock().await.as_mut() {
+                        ctx.collected_timestamps
+                            .insert(device_id.clone(), timestamp_ns);
+                        if quality.is_good() {
+                            ctx.collected_quality.remove(&device_id);
+                        } else {
+                            ctx.collected_quality.insert(device_id.clone(), quality);
+                        }
                         ctx.collected_data.insert(device_id, value);
                     }
                 }
@@ -646,29 +1305,21 @@ impl RunEngine {
             PlanCommand::Wait { seconds } => {
                 debug!(seconds = %seconds, "Waiting");
 
-                // Make wait interruptible by checking abort flag periodically (bd-lnoi)
-                // Using chunked sleep approach: check every 100ms for responsiveness
+                // Make wait interruptible by abort (bd-lnoi)
                 let total = Duration::from_secs_f64(seconds);
-                let chunk = Duration::from_millis(100);
-                let mut elapsed = Duration::ZERO;
-
-                while elapsed < total {
-                    // Check for abort before each chunk
-                    if *self.abort_requested.read().await {
+                let started = std::time::Instant::now();
+                let cancel = self.cancel_token().await;
+                tokio::select! {
+                    () = sleep(total) => {}
+                    () = cancel.cancelled() => {
                         info!(
-                            elapsed_ms = %elapsed.as_millis(),
+                            elapsed_ms = %started.elapsed().as_millis(),
                             total_ms = %total.as_millis(),
                             "Wait interrupted by abort request"
                         );
                         // Return Ok here - the abort will be handled by the main loop
                         // after this command returns, ensuring proper cleanup
-                        return Ok(false);
                     }
-
-                    let remaining = total - elapsed;
-                    let sleep_duration = chunk.min(remaining);
-                    sleep(sleep_duration).await;
-                    elapsed += sleep_duration;
                 }
 
                 Ok(false)
@@ -681,7 +1332,7 @@ impl RunEngine {
                 // Check if pause was requested
                 if *self.pause_requested.read().await {
                     info!("Pausing at checkpoint");
-                    *self.state.write().await = EngineState::Paused;
+                    self.set_state(EngineState::Paused, "checkpoint").await;
                 }
                 Ok(false)
             }
@@ -698,6 +1349,7 @@ impl RunEngine {
 
                 // Merge collected data
                 data.extend(ctx.collected_data.drain());
+                data.extend(ctx.collected_frame_channels.drain());
 
                 // Get frames
                 let collected_arrays = if !ctx.collected_frames.is_empty() {
@@ -718,11 +1370,39 @@ impl RunEngine {
                 event.data = data;
                 event.arrays = collected_arrays;
                 event.positions = all_positions;
+                event.timestamps = std::mem::take(&mut ctx.collected_timestamps);
+                event
+                    .metadata
+                    .extend(ctx.collected_frame_annotations.drain());
+                for (field, quality) in ctx.collected_quality.drain() {
+                    event.set_quality(&field, quality);
+                }
+                if !ctx.overruns.is_empty() {
+                    event
+                        .metadata
+                        .insert(TIME_BUDGET_OVERRUN_KEY.to_string(), ctx.overruns.join(","));
+                    ctx.overruns.clear();
+                }
+                let failed = ctx.validator.apply(&mut event);
+                if !failed.is_empty() {
+                    debug!(seq_num = %ctx.seq_num, rules = ?failed, "Point failed validation");
+                }
 
                 ctx.seq_num += 1;
 
+                // Progress follows every event so clients needn't count events
+                ctx.progress.record_point(event.time_ns);
+                let progress = ctx.progress.snapshot(
+                    &ctx.run_uid,
+                    ctx.seq_num,
+                    ctx.points_total,
+                    &event.positions,
+                );
+                ctx.latest_progress = Some(progress.clone());
+
                 drop(ctx_guard);
                 self.emit_document(Document::Event(event)).await;
+                self.emit_document(Document::Progress(progress)).await;
                 Ok(true)
             }
 
@@ -736,17 +1416,247 @@ impl RunEngine {
                     .await?;
                 Ok(false)
             }
+
+            PlanCommand::Ramp {
+                device_id,
+                parameter,
+                ramp,
+            } => {
+                let reached = self.execute_ramp(&device_id, &parameter, ramp).await?;
+                if let Some(value) = reached.filter(|_| parameter == POSITION_PARAMETER) {
+                    if let Some(ctx) = self.run_context.lock().await.as_mut() {
+                        ctx.current_positions.insert(device_id, value);
+                    }
+                }
+                Ok(false)
+            }
+        }
+    }
+
+    /// Process a command, cancelling a read or move that overruns its time
+    /// budget and applying the budget's overrun policy
+    async fn process_budgeted(
+        &self,
+        cmd: PlanCommand,
+        budget: &mut RunBudget,
+    ) -> anyhow::Result<BudgetOutcome> {
+        let (operation, device_id) = match &cmd {
+            PlanCommand::Read { device_id } => (BudgetedOperation::Read, device_id.clone()),
+            PlanCommand::MoveTo { device_id, .. }
+            | PlanCommand::MoveToPosition { device_id, .. } => {
+                (BudgetedOperation::Move, device_id.clone())
+            }
+            _ => {
+                return self
+                    .process_command(cmd)
+                    .await
+                    .map(BudgetOutcome::Processed)
+            }
+        };
+        let Some(limit) = budget.budget.limit(operation) else {
+            return self
+                .process_command(cmd)
+                .await
+                .map(BudgetOutcome::Processed);
+        };
+
+        let mut retries = 0;
+        loop {
+            if let Ok(result) = tokio::time::timeout(limit, self.process_command(cmd.clone())).await
+            {
+                return result.map(BudgetOutcome::Processed);
+            }
+            budget.summary.record_overrun(&device_id);
+            warn!(
+                device = %device_id,
+                operation = %operation,
+                budget_s = limit.as_secs_f64(),
+                policy = %budget.budget.on_overrun,
+                "Operation overran its time budget"
+            );
+            if *self.abort_requested.read().await {
+                return Ok(BudgetOutcome::Processed(false));
+            }
+
+            match budget.budget.on_overrun {
+                OverrunPolicy::Retry { attempts } if retries < attempts => {
+                    retries += 1;
+                    budget.summary.retried += 1;
+                }
+                OverrunPolicy::Retry { .. } => anyhow::bail!(
+                    "{} of '{}' overran its {} s time budget {} times",
+                    operation,
+                    device_id,
+                    limit.as_secs_f64(),
+                    retries + 1
+                ),
+                OverrunPolicy::SkipAndFlag => {
+                    budget.summary.skipped += 1;
+                    if let Some(ctx) = self.run_context.lock().await.as_mut() {
+                        // A skipped scalar read still gets a (flagged) value
+                        if operation == BudgetedOperation::Read
+                            && !ctx.frame_channels.contains_key(&device_id)
+                        {
+                            ctx.collected_data.insert(device_id.clone(), f64::NAN);
+                            ctx.collected_quality
+                                .insert(device_id.clone(), DataQuality::Suspect);
+                        }
+                        ctx.overruns.push(format!("{} {}", operation, device_id));
+                    }
+                    return Ok(BudgetOutcome::Processed(false));
+                }
+                OverrunPolicy::Pause => {
+                    budget.summary.paused += 1;
+                    *self.pause_requested.write().await = true;
+                    self.set_state(EngineState::Paused, "time_budget").await;
+                    return Ok(BudgetOutcome::Paused(cmd));
+                }
+            }
+        }
+    }
+
+    /// Start periodic sampling of frame enrichment channels into `cache`
+    fn spawn_enrichment_sampler(
+        &self,
+        config: FrameEnrichmentConfig,
+        cache: ChannelSampleCache,
+    ) -> EnrichmentSampler {
+        let registry = self.device_registry.clone();
+        let interval = config.sample_interval();
+        let channels = config.channels;
+        let task = {
+            let channels = channels.clone();
+            let cache = cache.clone();
+            tokio::spawn(async move {
+                loop {
+                    for channel in &channels {
+                        match registry.read_channel_value(channel).await {
+                            Ok(value) => cache.record(channel, value, now_ns()),
+                            Err(e) => {
+                                debug!(channel = %channel, error = %e, "Frame enrichment sample failed");
+                            }
+                        }
+                    }
+                    sleep(interval).await;
+                }
+            })
+        };
+        EnrichmentSampler {
+            channels,
+            cache,
+            task,
         }
     }
 
     /// Execute a move command
+    /// Run the pause actions of each device with parking actions
+    ///
+    /// Used when a run pauses, and to leave the rig safe when a run fails.
+    async fn park_devices(
+        &self,
+        parking_actions: &HashMap<String, ParkingActions>,
+    ) -> Vec<ParkedDevice> {
+        // Sorted so devices park in the same order on every pause
+        let sorted: BTreeMap<_, _> = parking_actions.iter().collect();
+        let mut parked = Vec::with_capacity(sorted.len());
+        for (device_id, actions) in sorted {
+            if actions.is_empty() {
+                continue;
+            }
+            let target = self.device_registry.parking_target(device_id);
+            let device = parking::park(device_id, &target, actions).await;
+            for (action, error) in &device.failures {
+                warn!(device = %device_id, action = %action, error = %error, "Parking action failed");
+            }
+            info!(device = %device_id, "Parked device");
+            parked.push(device);
+        }
+        parked
+    }
+
+    /// Run the resume actions of parked devices, in reverse parking order
+    async fn unpark_devices(&self, parked: &[ParkedDevice]) {
+        for device in parked.iter().rev() {
+            let target = self.device_registry.parking_target(&device.device_id);
+            for (action, error) in parking::unpark(&target, device).await {
+                warn!(
+                    device = %device.device_id,
+                    action = %action,
+                    error = %error,
+                    "Resume action failed"
+                );
+            }
+            info!(device = %device.device_id, "Restored parked device");
+        }
+    }
+
+    /// Run a cleanup sequence within its timeout
+    ///
+    /// Every step is attempted even if an earlier one fails; steps not
+    /// started before the timeout are reported as not run.
+    async fn run_cleanup(&self, cleanup: &CleanupPlan, trigger: &str) -> CleanupReport {
+        info!(steps = cleanup.steps.len(), trigger = %trigger, "Running cleanup");
+        let started = std::time::Instant::now();
+        let deadline = tokio::time::Instant::now() + Duration::from_secs_f64(cleanup.timeout_s);
+        let mut report = CleanupReport::new(trigger);
+        for step in &cleanup.steps {
+            let status = if report.timed_out {
+                CleanupStepStatus::NotRun
+            } else {
+                let device_id = self.device_registry.resolve_channel(&step.device).device_id;
+                let target = self.device_registry.parking_target(&device_id);
+                match tokio::time::timeout_at(deadline, parking::apply(&target, &step.action)).await
+                {
+                    Ok(Ok(())) => CleanupStepStatus::Done,
+                    Ok(Err(e)) => CleanupStepStatus::Failed {
+                        error: e.to_string(),
+                    },
+                    Err(_) => {
+                        report.timed_out = true;
+                        CleanupStepStatus::Failed {
+                            error: format!("Cleanup timed out after {} s", cleanup.timeout_s),
+                        }
+                    }
+                }
+            };
+            if let CleanupStepStatus::Failed { error } = &status {
+                warn!(device = %step.device, action = %step.action, error = %error, "Cleanup step failed");
+            }
+            report.record(step, status);
+        }
+        report.elapsed_s = started.elapsed().as_secs_f64();
+        if report.is_clean() {
+            info!(elapsed_s = report.elapsed_s, "Cleanup complete");
+        } else {
+            warn!(
+                problems = report.problems().count(),
+                timed_out = report.timed_out,
+                "Cleanup did not complete"
+            );
+        }
+        report
+    }
+
+    /// Take a device's command lock, like hardware RPCs, so plan commands
+    /// don't interleave with ExecuteBatch or manual moves; abort ends the wait
+    async fn lock_device(&self, device_id: &str) -> anyhow::Result<DeviceLocks> {
+        let cancel = self.cancel_token().await;
+        tokio::select! {
+            locks = self.device_registry.lock_devices([device_id]) => Ok(locks),
+            () = cancel.cancelled() => Err(Cancelled.into()),
+        }
+    }
+
     async fn execute_move(&self, device_id: &str, position: f64) -> anyhow::Result<()> {
         debug!(device = %device_id, position = %position, "Moving");
 
         // Get the device from registry and move it
         let device = self.device_registry.get_movable(device_id);
         if let Some(device) = device {
-            device.move_abs(position).await?;
+            let _locks = self.lock_device(device_id).await?;
+            device
+                .move_abs_cancellable(position, &self.cancel_token().await)
+                .await?;
         } else {
             warn!(device = %device_id, "Device not found or not movable, skipping move");
         }
@@ -754,21 +1664,57 @@ impl RunEngine {
         Ok(())
     }
 
-    /// Execute a read command
-    async fn execute_read(&self, device_id: &str) -> anyhow::Result<f64> {
-        debug!(device = %device_id, "Reading");
+    /// Execute a read command, returning the value and its quality flag
+    async fn execute_read(
+        &self,
+        device_id: &str,
+        mode: AcquisitionMode,
+    ) -> anyhow::Result<(f64, DataQuality)> {
+        debug!(device = %device_id, mode = %mode, "Reading");
+
+        // Parameter-level channel aliases read the parameter, not the device
+        if let Some(parameter) = self.device_registry.resolve_channel(device_id).parameter {
+            let value = self.read_parameter_channel(device_id, &parameter)?;
+            return Ok((value, DataQuality::Good));
+        }
 
         // Get the device from registry and read it
         let device = self.device_registry.get_readable(device_id);
         if let Some(device) = device {
-            let value = device.read().await?;
-            Ok(value)
+            let AcquiredValue { value, quality, .. } = device
+                .read_with_mode_cancellable(mode, &self.cancel_token().await)
+                .await?;
+            if !quality.is_good() {
+                debug!(device = %device_id, quality = %quality, "Reading flagged");
+            }
+            Ok((value, quality))
         } else {
+            // The placeholder is not a measurement; flag it so analysis can drop it
             warn!(device = %device_id, "Device not found or not readable, returning 0.0");
-            Ok(0.0)
+            Ok((0.0, DataQuality::Suspect))
         }
     }
 
+    /// Read a numeric parameter through a `device:parameter` channel alias
+    fn read_parameter_channel(&self, channel: &str, parameter: &str) -> anyhow::Result<f64> {
+        let parameterized = self
+            .device_registry
+            .get_parameterized(channel)
+            .ok_or_else(|| anyhow::anyhow!("Channel '{}' has no parameters", channel))?;
+        let params = parameterized.parameters();
+        let param = params.get(parameter).ok_or_else(|| {
+            anyhow::anyhow!("Channel '{}': unknown parameter '{}'", channel, parameter)
+        })?;
+        let value = self.device_registry.get_parameter_json(channel, param)?;
+        value.as_f64().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Channel '{}': parameter '{}' is not numeric",
+                channel,
+                parameter
+            )
+        })
+    }
+
     /// Execute a trigger command
     async fn execute_trigger(&self, device_id: &str) -> anyhow::Result<()> {
         debug!(device = %device_id, "Triggering");
@@ -776,7 +1722,9 @@ impl RunEngine {
         // Get the device from registry and trigger it
         let device = self.device_registry.get_triggerable(device_id);
         if let Some(device) = device {
-            device.trigger().await?;
+            device
+                .trigger_cancellable(&self.cancel_token().await)
+                .await?;
         } else {
             debug!(device = %device_id, "Device not triggerable, skipping");
         }
@@ -792,6 +1740,7 @@ impl RunEngine {
         value: &str,
     ) -> anyhow::Result<()> {
         debug!(device = %device_id, param = %parameter, value = %value, "Setting parameter");
+        let _locks = self.lock_device(device_id).await?;
 
         // Try legacy Settable trait first (backwards compatibility)
         let settable = self.device_registry.get_settable(device_id);
@@ -820,8 +1769,10 @@ impl RunEngine {
         if let Some(parameterized) = self.device_registry.get_parameterized(device_id) {
             let params = parameterized.parameters();
             if let Some(param) = params.get(parameter) {
-                // Set the parameter (synchronous call via ParameterBase trait)
-                param.set_json(json_value)?;
+                // Set the parameter within its output limit
+                self.device_registry
+                    .set_parameter_json(device_id, param, json_value)
+                    .await?;
                 return Ok(());
             } else {
                 anyhow::bail!(
@@ -839,12 +1790,88 @@ impl RunEngine {
         );
     }
 
+    /// Ramp a parameter and wait until it reaches the target
+    ///
+    /// Returns the target, or `None` if the run was aborted meanwhile; the
+    /// ramp is then stopped where it is and the main loop handles the abort.
+    async fn execute_ramp(
+        &self,
+        device_id: &str,
+        parameter: &str,
+        spec: RampSpec,
+    ) -> anyhow::Result<Option<f64>> {
+        debug!(device = %device_id, param = %parameter, target = %spec.target, "Ramping");
+        let ramps = self.device_registry.ramps();
+        let mut handle = self
+            .device_registry
+            .start_ramp(device_id, parameter, spec)
+            .await?;
+        let id = handle.id;
+
+        // Abort interrupts the ramp, like Wait
+        let cancel = self.cancel_token().await;
+        let status = tokio::select! {
+            status = handle.wait() => status,
+            () = cancel.cancelled() => {
+                info!(device = %device_id, param = %parameter, "Ramp interrupted by abort request");
+                let _ = ramps.abort(id);
+                handle.wait().await;
+                return Ok(None);
+            }
+        };
+
+        match status.state {
+            RampState::Completed => Ok(Some(status.value)),
+            RampState::Failed => anyhow::bail!(
+                "Ramp of {}.{} failed at {}: {}",
+                device_id,
+                parameter,
+                status.value,
+                status.error.unwrap_or_default()
+            ),
+            state => anyhow::bail!(
+                "Ramp of {}.{} {} at {}",
+                device_id,
+                parameter,
+                state.as_str(),
+                status.value
+            ),
+        }
+    }
+
     /// Emit a document to all subscribers
+    ///
+    /// Never waits on subscribers: a subscriber whose queue is full misses the
+    /// document and sees a sequence gap.
     async fn emit_document(&self, doc: Document) {
         debug!(doc_type = ?std::mem::discriminant(&doc), uid = %doc.uid(), "Emitting document");
+        self.documents.publish(doc);
+    }
 
-        // Ignore send errors (no subscribers)
-        let _ = self.doc_sender.send(doc);
+    /// Emit the device events raised since the last call as documents of
+    /// the active run
+    async fn record_device_events(&self) {
+        let (run_uid, events) = {
+            let mut ctx_guard = self.run_context.lock().await;
+            let Some(ctx) = ctx_guard.as_mut() else {
+                return;
+            };
+            let mut events = Vec::new();
+            loop {
+                match ctx.device_events.try_recv() {
+                    Ok(event) => events.push(event),
+                    Err(broadcast::error::TryRecvError::Lagged(skipped)) => {
+                        warn!(run_uid = %ctx.run_uid, skipped, "Device events lost");
+                    }
+                    Err(_) => break,
+                }
+            }
+            (ctx.run_uid.clone(), events)
+        };
+        for event in events {
+            self.emit_document(Document::DeviceEvent(DeviceEventDoc::new(&run_uid, event)))
+                .await;
+        }
     }
 
     /// Get the number of queued plans
@@ -854,7 +1881,11 @@ impl RunEngine {
 
     /// Clear all queued plans
     pub async fn clear_queue(&self) {
-        self.plan_queue.lock().await.clear();
+        let removed: Vec<QueuedPlan> = self.plan_queue.lock().await.drain(..).collect();
+        for queued in removed {
+            self.record_batch_outcome(&queued.run_uid, "abort", "Queue cleared", 0)
+                .await;
+        }
     }
 
     /// Get the current run UID (if running)
@@ -875,6 +1906,102 @@ impl RunEngine {
             .map(|ctx| ctx.seq_num)
     }
 
+    /// Get structured progress for the active run.
+    ///
+    /// Before the first event this reports zero points completed and no ETA.
+    /// Returns `None` when no run is in progress.
+    pub async fn run_progress(&self) -> Option<ProgressDoc> {
+        let ctx_guard = self.run_context.lock().await;
+        let ctx = ctx_guard.as_ref()?;
+        Some(match &ctx.latest_progress {
+            Some(progress) => progress.clone(),
+            None => ctx.progress.snapshot(
+                &ctx.run_uid,
+                ctx.seq_num,
+                ctx.points_total,
+                &ctx.current_positions,
+            ),
+        })
+    }
+
+    /// Capabilities of a device (by ID or channel alias), for role binding
+    pub fn device_capabilities(&self, device_id: &str) -> Option<Vec<Capability>> {
+        self.device_registry
+            .get_device_info(device_id)
+            .map(|info| info.capabilities)
+    }
+
+    /// Get the plan type of the active run
+    pub async fn current_plan_type(&self) -> Option<String> {
+        self.run_context
+            .lock()
+            .await
+            .as_ref()
+            .map(|ctx| ctx.plan_type.clone())
+    }
+
+    /// Device IDs used by the active run (empty when no run is in progress)
+    pub async fn current_run_devices(&self) -> Vec<String> {
+        self.run_context
+            .lock()
+            .await
+            .as_ref()
+            .map(|ctx| ctx.devices.clone())
+            .unwrap_or_default()
+    }
+
+    /// Simulate a plan without touching hardware.
+    ///
+    /// Device capabilities, travel limits and keep-out zones come from the
+    /// registry. Detectors without an explicit exposure in `options` use their
+    /// current `ExposureControl` setting (a read-only query). The engine state
+    /// and queue are not affected.
+    pub async fn dry_run(&self, plan: &mut dyn Plan, mut options: DryRunOptions) -> DryRunReport {
+        let devices: HashMap<String, SimulatedDevice> = self
+            .device_registry
+            .list_devices()
+            .into_iter()
+            .map(|info| {
+                let keep_out = self.device_registry.keep_out_zones(&info.id);
+                (
+                    info.id,
+                    SimulatedDevice {
+                        capabilities: info.capabilities,
+                        min_position: info.metadata.min_position,
+                        max_position: info.metadata.max_position,
+                        keep_out,
+                    },
+                )
+            })
+            .collect();
+
+        for det in plan.detectors() {
+            if options.exposure_times.contains_key(&det) {
+                continue;
+            }
+            if let Some(exposure) = self.device_registry.get_exposure_control(&det) {
+                match exposure.get_exposure().await {
+                    Ok(seconds) => {
+                        options.exposure_times.insert(det, seconds);
+                    }
+                    Err(e) => {
+                        debug!(device = %det, error = %e, "Exposure unavailable for dry run");
+                    }
+                }
+            }
+        }
+
+        let report = dry_run::simulate(plan, &devices, &options);
+        info!(
+            plan_type = %report.plan_type,
+            total_commands = report.total_commands,
+            estimated_duration_s = report.estimated_duration_s,
+            issues = report.issues.len(),
+            "Dry run complete"
+        );
+        report
+    }
+
     /// Execute a single plan and return results (for yield-based scripting)
     ///
     /// This is a convenience method that:
@@ -983,6 +2110,39 @@ pub struct RunResult {
     pub num_events: u32,
 }
 
+/// Check the run settings carried in queued run metadata
+///
+/// Callers queueing metadata from clients reject it here; settings that only
+/// fail once the run starts end it with a failed StopDoc.
+pub fn validate_run_metadata(metadata: &HashMap<String, String>) -> anyhow::Result<()> {
+    parse_acquisition_modes(metadata)?;
+    if let Some(cleanup) = CleanupPlan::from_metadata(metadata)? {
+        cleanup.validate()?;
+    }
+    if let Some(budget) = TimeBudget::from_metadata(metadata)? {
+        budget.validate()?;
+    }
+    Ok(())
+}
+
+/// Acquisition modes from `acquisition_mode.<device>` run metadata entries
+fn parse_acquisition_modes(
+    metadata: &HashMap<String, String>,
+) -> anyhow::Result<HashMap<String, AcquisitionMode>> {
+    metadata
+        .iter()
+        .filter_map(|(key, value)| {
+            let device = key.strip_prefix(ACQUISITION_MODE_METADATA_PREFIX)?;
+            Some(
+                value
+                    .parse()
+                    .map(|mode| (device.to_string(), mode))
+                    .map_err(|e: anyhow::Error| e.context(format!("Invalid metadata '{}'", key))),
+            )
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1003,6 +2163,92 @@ mod tests {
         assert!(engine.resume().await.is_err());
     }
 
+    #[test]
+    fn test_parse_acquisition_modes() {
+        let mut metadata = HashMap::new();
+        metadata.insert("operator".to_string(), "alice".to_string());
+        metadata.insert(
+            "acquisition_mode.power_meter".to_string(),
+            "average:16".to_string(),
+        );
+        let modes = parse_acquisition_modes(&metadata).unwrap();
+        assert_eq!(modes.len(), 1);
+        assert_eq!(
+            modes["power_meter"],
+            AcquisitionMode::Average { samples: 16 }
+        );
+
+        metadata.insert("acquisition_mode.diode".to_string(), "median:3".to_string());
+        let err = parse_acquisition_modes(&metadata).unwrap_err();
+        assert!(format!("{:#}", err).contains("acquisition_mode.diode"));
+    }
+
+    /// Queue a count with `metadata` the engine rejects at start, check the
+    /// run is closed and the engine can run the next plan
+    async fn assert_run_setup_fails(metadata: HashMap<String, String>) -> StopDoc {
+        assert!(validate_run_metadata(&metadata).is_err());
+        let engine = RunEngine::new(Arc::new(DeviceRegistry::new()));
+        let mut rx = engine.subscribe();
+        let run_uid = engine
+            .queue_with_metadata(Box::new(Count::new(1)), metadata)
+            .await;
+        assert!(engine.start().await.is_err());
+        assert_eq!(engine.state().await, EngineState::Idle);
+
+        assert!(matches!(rx.recv().await, Ok(Document::Start(start)) if start.uid == run_uid));
+        let Ok(Document::Stop(stop)) = rx.recv().await else {
+            panic!("expected a StopDoc");
+        };
+        assert_eq!(stop.run_uid, run_uid);
+        assert_eq!(stop.exit_status, "fail");
+
+        engine.queue(Box::new(Count::new(1))).await;
+        engine.start().await.unwrap();
+        stop
+    }
+
+    #[tokio::test]
+    async fn test_invalid_acquisition_mode_fails_the_run() {
+        let stop = assert_run_setup_fails(HashMap::from([(
+            "acquisition_mode.diode".to_string(),
+            "median:3".to_string(),
+        )]))
+        .await;
+        assert!(stop.reason.contains("acquisition_mode.diode"));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_cleanup_plan_fails_the_run() {
+        use common::cleanup::CLEANUP_PLAN_METADATA_KEY;
+
+        let cleanup = CleanupPlan::new()
+            .with_step("stage_x", parking::ParkAction::MoveTo { position: 3.0 })
+            .with_timeout(0.0);
+        let stop = assert_run_setup_fails(HashMap::from([(
+            CLEANUP_PLAN_METADATA_KEY.to_string(),
+            cleanup.to_metadata_value(),
+        )]))
+        .await;
+        assert!(stop.reason.contains("Cleanup timeout"));
+
+        assert_run_setup_fails(HashMap::from([(
+            CLEANUP_PLAN_METADATA_KEY.to_string(),
+            "not json".to_string(),
+        )]))
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_invalid_time_budget_fails_the_run() {
+        let budget = TimeBudget::new().with_move_budget(-1.0);
+        let stop = assert_run_setup_fails(HashMap::from([(
+            TIME_BUDGET_METADATA_KEY.to_string(),
+            budget.to_metadata_value(),
+        )]))
+        .await;
+        assert!(stop.reason.contains("move_s"));
+    }
+
     #[tokio::test]
     async fn test_queue_plan() {
         let registry = Arc::new(DeviceRegistry::new());
@@ -1064,6 +2310,7 @@ mod tests {
                         width: 10,
                         height: 10,
                     },
+                    simulated: false,
                 })
                 .await
                 .unwrap();
@@ -1155,7 +2402,7 @@ mod tests {
         // Give the Wait command time to start executing
         tokio::time::sleep(Duration::from_millis(50)).await;
 
-        // Request abort - should take effect within 200ms (2 check cycles)
+        // Request abort - cancels the wait right away
         let abort_start = tokio::time::Instant::now();
         engine
             .abort("Test abort")
@@ -1181,7 +2428,6 @@ mod tests {
         assert_eq!(stop.exit_status, "abort", "Exit status should be 'abort'");
 
         // Verify abort was fast (< 500ms, well under the 60s wait)
-        // The chunked sleep checks every 100ms, so abort should complete in ~200ms max
         assert!(
             abort_elapsed < Duration::from_millis(500),
             "Abort took too long: {:?} (expected < 500ms)",
@@ -1246,4 +2492,350 @@ mod tests {
             expected_max
         );
     }
+
+    #[tokio::test]
+    async fn test_pause_parks_and_resume_restores_devices() {
+        use crate::plans::LineScan;
+        use hardware::registry::{DeviceConfig, DriverType};
+
+        let registry = Arc::new(DeviceRegistry::new());
+        for (id, initial_position) in [("stage_x", 2.0), ("stage_y", 0.0)] {
+            registry
+                .register(DeviceConfig {
+                    id: id.to_string(),
+                    name: id.to_string(),
+                    driver: DriverType::MockStage { initial_position },
+                    simulated: false,
+                })
+                .await
+                .unwrap();
+        }
+        registry.set_parking_actions(HashMap::from([(
+            "stage_x".to_string(),
+            ParkingActions {
+                on_pause: vec![parking::ParkAction::MoveTo { position: 5.0 }],
+                on_resume: vec![],
+            },
+        )]));
+
+        let engine = Arc::new(RunEngine::new(registry.clone()));
+        let mut rx = engine.subscribe();
+        engine
+            .queue(Box::new(
+                LineScan::new("stage_y", 0.0, 0.2, 5).with_settle_time(0.1),
+            ))
+            .await;
+        let engine_for_task = engine.clone();
+        let run = tokio::spawn(async move { engine_for_task.start().await });
+
+        let doc = tokio::time::timeout(Duration::from_secs(1), rx.recv()).await;
+        assert!(matches!(doc, Ok(Ok(Document::Start(_)))));
+        engine.pause().await.unwrap();
+
+        let stage_x = registry.get_movable("stage_x").unwrap();
+        tokio::time::timeout(Duration::from_secs(3), async {
+            while (stage_x.position().await.unwrap() - 5.0).abs() > 1e-9 {
+                sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("stage_x should be parked");
+        assert_eq!(engine.state().await, EngineState::Paused);
+
+        engine.resume().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), run)
+            .await
+            .expect("run should complete")
+            .unwrap()
+            .unwrap();
+        assert!((stage_x.position().await.unwrap() - 2.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_abort_runs_cleanup_and_reports_it() {
+        use crate::plans::LineScan;
+        use common::cleanup::CLEANUP_PLAN_METADATA_KEY;
+        use hardware::registry::{DeviceConfig, DriverType};
+
+        let registry = Arc::new(DeviceRegistry::new());
+        for id in ["stage_x", "stage_y"] {
+            registry
+                .register(DeviceConfig {
+                    id: id.to_string(),
+                    name: id.to_string(),
+                    driver: DriverType::MockStage {
+                        initial_position: 0.0,
+                    },
+                    simulated: false,
+                })
+                .await
+                .unwrap();
+        }
+
+        let cleanup = CleanupPlan::new()
+            .with_step("missing", parking::ParkAction::CloseShutter)
+            .with_step("stage_x", parking::ParkAction::MoveTo { position: 3.0 })
+            .with_timeout(5.0);
+        let engine = Arc::new(RunEngine::new(registry.clone()));
+        let mut rx = engine.subscribe();
+        engine
+            .queue_with_metadata(
+                Box::new(LineScan::new("stage_y", 0.0, 1.0, 50).with_settle_time(0.1)),
+                HashMap::from([(
+                    CLEANUP_PLAN_METADATA_KEY.to_string(),
+                    cleanup.to_metadata_value(),
+                )]),
+            )
+            .await;
+        let engine_for_task = engine.clone();
+        let run = tokio::spawn(async move { engine_for_task.start().await });
+
+        let doc = tokio::time::timeout(Duration::from_secs(1), rx.recv()).await;
+        assert!(matches!(doc, Ok(Ok(Document::Start(_)))));
+        engine.abort("test").await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), run)
+            .await
+            .expect("run should end")
+            .unwrap()
+            .unwrap();
+
+        let stop = loop {
+            if let Document::Stop(stop) = rx.recv().await.unwrap() {
+                break stop;
+            }
+        };
+        assert_eq!(stop.exit_status, "abort");
+        let report: CleanupReport =
+            serde_json::from_str(&stop.metadata[CLEANUP_METADATA_KEY]).unwrap();
+        assert_eq!(report.trigger, "abort");
+        assert!(!report.timed_out);
+        assert!(matches!(
+            report.steps[0].status,
+            CleanupStepStatus::Failed { .. }
+        ));
+        assert_eq!(report.steps[1].status, CleanupStepStatus::Done);
+
+        let stage_x = registry.get_movable("stage_x").unwrap();
+        assert!((stage_x.position().await.unwrap() - 3.0).abs() < 1e-9);
+    }
+
+    /// Register a mock stage that takes real time to move (10 mm/s), unlike
+    /// the instant `DriverType::MockStage`
+    async fn register_moving_stage(registry: &DeviceRegistry, id: &str) {
+        use common::driver::{Capability, DeviceComponents, DriverFactory};
+        use daq_driver_mock::{MockMode, MockStage};
+        use futures::future::BoxFuture;
+
+        struct MovingStageFactory;
+
+        impl DriverFactory for MovingStageFactory {
+            fn driver_type(&self) -> &'static str {
+                "moving_mock_stage"
+            }
+
+            fn name(&self) -> &'static str {
+                "Moving Mock Stage"
+            }
+
+            fn capabilities(&self) -> &'static [Capability] {
+                &[Capability::Movable, Capability::Parameterized]
+            }
+
+            fn validate(&self, _config: &toml::Value) -> anyhow::Result<()> {
+                Ok(())
+            }
+
+            fn build(
+                &self,
+                _config: toml::Value,
+            ) -> BoxFuture<'static, anyhow::Result<DeviceComponents>> {
+                Box::pin(async {
+                    let stage = Arc::new(MockStage::builder().mode(MockMode::Realistic).build());
+                    Ok(DeviceComponents {
+                        movable: Some(stage.clone()),
+                        status: Some(stage.status()),
+                        parameterized: Some(stage),
+                        ..Default::default()
+                    })
+                })
+            }
+        }
+
+        registry.register_factory(Box::new(MovingStageFactory));
+        registry
+            .register_from_toml(
+                id,
+                id,
+                "moving_mock_stage",
+                toml::Value::Table(Default::default()),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_move_overrunning_time_budget_is_skipped_and_flagged() {
+        use crate::plans::LineScan;
+
+        let registry = Arc::new(DeviceRegistry::new());
+        register_moving_stage(&registry, "stage_y").await;
+
+        // The second point is a 50 mm move, which takes seconds on the mock
+        let budget = TimeBudget::new()
+            .with_move_budget(0.2)
+            .with_policy(OverrunPolicy::SkipAndFlag);
+        let engine = RunEngine::new(registry);
+        let mut rx = engine.subscribe();
+        engine
+            .queue_with_metadata(
+                Box::new(LineScan::new("stage_y", 0.0, 50.0, 2)),
+                HashMap::from([(
+                    TIME_BUDGET_METADATA_KEY.to_string(),
+                    budget.to_metadata_value(),
+                )]),
+            )
+            .await;
+        tokio::time::timeout(Duration::from_secs(3), engine.start())
+            .await
+            .expect("overrunning move should not hang the run")
+            .unwrap();
+
+        let mut events = Vec::new();
+        let stop = loop {
+            match rx.recv().await.unwrap() {
+                Document::Event(event) => events.push(event),
+                Document::Stop(stop) => break stop,
+                _ => {}
+            }
+        };
+        assert_eq!(stop.exit_status, "success");
+        assert_eq!(events.len(), 2);
+        assert!(!events[0].metadata.contains_key(TIME_BUDGET_OVERRUN_KEY));
+        assert_eq!(events[1].metadata[TIME_BUDGET_OVERRUN_KEY], "move stage_y");
+
+        let summary: TimeBudgetSummary =
+            serde_json::from_str(&stop.metadata[TIME_BUDGET_METADATA_KEY]).unwrap();
+        assert_eq!(summary.overruns, 1);
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(summary.devices["stage_y"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_abort_cancels_move_in_flight() {
+        use crate::plans::LineScan;
+
+        let registry = Arc::new(DeviceRegistry::new());
+        register_moving_stage(&registry, "stage_z").await;
+
+        // The second point is a 50 mm move, which takes seconds on the mock
+        let engine = Arc::new(RunEngine::new(registry.clone()));
+        let mut rx = engine.subscribe();
+        engine
+            .queue(Box::new(LineScan::new("stage_z", 0.0, 50.0, 2)))
+            .await;
+        let engine_for_task = engine.clone();
+        let run = tokio::spawn(async move { engine_for_task.start().await });
+
+        // Abort once the long move is under way
+        loop {
+            if let Document::Event(_) = rx.recv().await.unwrap() {
+                break;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        engine.abort("Test abort").await.unwrap();
+
+        let stop = tokio::time::timeout(Duration::from_millis(500), async {
+            loop {
+                if let Document::Stop(stop) = rx.recv().await.unwrap() {
+                    return stop;
+                }
+            }
+        })
+        .await
+        .expect("abort should not wait for the move to finish");
+        assert_eq!(stop.exit_status, "abort");
+        run.await.unwrap().unwrap();
+
+        // The stage was stopped short of the target
+        let stage_z = registry.get_movable("stage_z").unwrap();
+        assert!(stage_z.position().await.unwrap() < 50.0);
+    }
+
+    #[tokio::test]
+    async fn test_move_waits_for_device_command_lock() {
+        use crate::plans::LineScan;
+
+        let registry = Arc::new(DeviceRegistry::new());
+        register_moving_stage(&registry, "stage_z").await;
+        let held = registry.lock_devices(["stage_z"]).await;
+
+        let engine = Arc::new(RunEngine::new(registry.clone()));
+        let mut rx = engine.subscribe();
+        engine
+            .queue(Box::new(LineScan::new("stage_z", 0.0, 0.5, 2)))
+            .await;
+        let engine_for_task = engine.clone();
+        let run = tokio::spawn(async move { engine_for_task.start().await });
+
+        // The plan's first move waits while a command holds the stage
+        let first_event = tokio::time::timeout(Duration::from_millis(200), async {
+            loop {
+                if let Document::Event(_) = rx.recv().await.unwrap() {
+                    return;
+                }
+            }
+        })
+        .await;
+        assert!(first_event.is_err());
+        drop(held);
+        run.await.unwrap().unwrap();
+        let stage_z = registry.get_movable("stage_z").unwrap();
+        assert!((stage_z.position().await.unwrap() - 0.5).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_batch_summary_follows_last_child() {
+        use crate::templates::PlanTemplate;
+
+        let engine = RunEngine::new(Arc::new(DeviceRegistry::new()));
+        let mut rx = engine.subscribe();
+
+        let template = PlanTemplate {
+            name: "per sample".to_string(),
+            plan_type: "count".to_string(),
+            metadata: HashMap::from([("sample".to_string(), "{sample}".to_string())]),
+            ..Default::default()
+        };
+        let items: Vec<BTreeMap<String, String>> = ["S-1", "S-2"]
+            .iter()
+            .map(|sample| BTreeMap::from([("sample".to_string(), sample.to_string())]))
+            .collect();
+        let runs = template
+            .expand(&items)
+            .unwrap()
+            .into_iter()
+            .map(|run| (Box::new(Count::new(1)) as Box<dyn Plan>, run))
+            .collect();
+        let (batch_uid, run_uids) = engine.queue_batch(&template.name, runs).await;
+        assert_eq!(run_uids, engine.queued_run_uids().await);
+
+        engine.start().await.unwrap();
+        engine.start().await.unwrap();
+
+        let mut starts = Vec::new();
+        let summary = loop {
+            match rx.recv().await.unwrap() {
+                Document::Start(start) => starts.push(start),
+                Document::BatchSummary(summary) => break summary,
+                _ => {}
+            }
+        };
+        assert_eq!(starts.len(), 2);
+        assert_eq!(starts[1].metadata["sample"], "S-2");
+        assert_eq!(starts[1].metadata["batch_uid"], batch_uid);
+        assert_eq!(summary.batch_uid, batch_uid);
+        assert_eq!(summary.count("success"), 2);
+        assert_eq!(summary.runs[0].run_uid, run_uids[0]);
+    }
 }
@@ -0,0 +1,315 @@
+//! Plan templates expanded over sweep items
+//!
+//! A [`PlanTemplate`] is a plan configuration whose parameters, device
+//! mapping and metadata may contain `{variable}` placeholders. Expanding it
+//! over a list of sweep items (samples, wavelengths, files) gives one child
+//! run per item, so a sample batch is described once instead of copied N
+//! times.
+//!
+//! Child runs are queued together as a batch. Each carries the batch UID, its
+//! index, the UIDs of its siblings and its sweep variables in its metadata;
+//! once the last one has stopped, the RunEngine emits a
+//! [`BatchSummaryDoc`] with the outcome of every child.
+//!
+//! ```toml
+//! name = "absorbance per sample"
+//! plan_type = "line_scan"
+//! parameters = { start = "400", stop = "700", num_points = "301" }
+//! device_mapping = { motor = "mono", detector = "pd_{channel}" }
+//! metadata = { sample = "{sample}", operator = "ab" }
+//! ```
+
+use common::experiment::document::{new_uid, now_ns, BatchRunOutcome, BatchSummaryDoc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// StartDoc metadata key: UID shared by the child runs of a batch
+pub const BATCH_UID_METADATA_KEY: &str = "batch_uid";
+/// StartDoc metadata key: position of the run in its batch
+pub const BATCH_INDEX_METADATA_KEY: &str = "batch_index";
+/// StartDoc metadata key: JSON list of the run UIDs of the batch, in order
+pub const BATCH_RUN_UIDS_METADATA_KEY: &str = "batch_run_uids";
+/// StartDoc metadata key: name of the template the run was expanded from
+pub const BATCH_TEMPLATE_METADATA_KEY: &str = "batch_template";
+/// Prefix of the StartDoc metadata keys holding sweep variables
+pub const SWEEP_METADATA_PREFIX: &str = "sweep.";
+
+/// Largest number of child runs a template expands to
+pub const MAX_BATCH_SIZE: usize = 10_000;
+
+/// A plan configuration with `{variable}` placeholders
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlanTemplate {
+    pub name: String,
+    pub plan_type: String,
+    #[serde(default)]
+    pub parameters: HashMap<String, String>,
+    #[serde(default)]
+    pub device_mapping: HashMap<String, String>,
+    /// Shared by all child runs
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+/// One child run of an expanded template
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateRun {
+    pub index: u32,
+    pub variables: BTreeMap<String, String>,
+    pub parameters: HashMap<String, String>,
+    pub device_mapping: HashMap<String, String>,
+    /// Template metadata plus the item's sweep variables
+    pub metadata: HashMap<String, String>,
+}
+
+impl PlanTemplate {
+    /// Expand the template once per sweep item
+    ///
+    /// Fails if an item leaves a placeholder unfilled, so a typo never
+    /// queues a batch of runs against a device called `{detectr}`.
+    pub fn expand(&self, items: &[BTreeMap<String, String>]) -> Result<Vec<TemplateRun>, String> {
+        if items.is_empty() {
+            return Err("Template needs at least one sweep item".to_string());
+        }
+        if items.len() > MAX_BATCH_SIZE {
+            return Err(format!(
+                "Template expands to {} runs (max {})",
+                items.len(),
+                MAX_BATCH_SIZE
+            ));
+        }
+
+        items
+            .iter()
+            .enumerate()
+            .map(|(index, variables)| {
+                let fill = |map: &HashMap<String, String>| {
+                    map.iter()
+                        .map(|(key, value)| {
+                            substitute(value, variables)
+                                .map(|value| (key.clone(), value))
+                                .map_err(|e| format!("Item {}: {}: {}", index, key, e))
+                        })
+                        .collect::<Result<HashMap<_, _>, _>>()
+                };
+                let mut metadata = fill(&self.metadata)?;
+                for (name, value) in variables {
+                    metadata.insert(format!("{}{}", SWEEP_METADATA_PREFIX, name), value.clone());
+                }
+                Ok(TemplateRun {
+                    index: index as u32,
+                    variables: variables.clone(),
+                    parameters: fill(&self.parameters)?,
+                    device_mapping: fill(&self.device_mapping)?,
+                    metadata,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Replace `{name}` with the variable's value; `{{` and `}}` are literal braces
+pub fn substitute(text: &str, variables: &BTreeMap<String, String>) -> Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                out.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                out.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(format!("unclosed placeholder {{{}", name)),
+                    }
+                }
+                let value = variables
+                    .get(name.trim())
+                    .ok_or_else(|| format!("no value for placeholder {{{}}}", name))?;
+                out.push_str(value);
+            }
+            '}' => return Err("unmatched '}'".to_string()),
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+/// Metadata linking a child run to its batch
+pub fn batch_metadata(
+    batch_uid: &str,
+    template_name: &str,
+    index: u32,
+    run_uids: &[String],
+) -> HashMap<String, String> {
+    HashMap::from([
+        (BATCH_UID_METADATA_KEY.to_string(), batch_uid.to_string()),
+        (BATCH_INDEX_METADATA_KEY.to_string(), index.to_string()),
+        (
+            BATCH_RUN_UIDS_METADATA_KEY.to_string(),
+            serde_json::to_string(run_uids).unwrap_or_default(),
+        ),
+        (
+            BATCH_TEMPLATE_METADATA_KEY.to_string(),
+            template_name.to_string(),
+        ),
+    ])
+}
+
+/// Collects the outcomes of a batch's child runs
+#[derive(Debug, Clone)]
+pub struct BatchTracker {
+    batch_uid: String,
+    template_name: String,
+    runs: Vec<BatchRunOutcome>,
+}
+
+impl BatchTracker {
+    /// Track child runs given as (run UID, sweep variables), in batch order
+    pub fn new(
+        template_name: &str,
+        runs: impl IntoIterator<Item = (String, BTreeMap<String, String>)>,
+    ) -> Self {
+        Self {
+            batch_uid: new_uid(),
+            template_name: template_name.to_string(),
+            runs: runs
+                .into_iter()
+                .enumerate()
+                .map(|(index, (run_uid, variables))| BatchRunOutcome {
+                    run_uid,
+                    index: index as u32,
+                    variables,
+                    exit_status: String::new(),
+                    reason: String::new(),
+                    num_events: 0,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn batch_uid(&self) -> &str {
+        &self.batch_uid
+    }
+
+    pub fn template_name(&self) -> &str {
+        &self.template_name
+    }
+
+    pub fn run_uids(&self) -> Vec<String> {
+        self.runs.iter().map(|run| run.run_uid.clone()).collect()
+    }
+
+    pub fn contains(&self, run_uid: &str) -> bool {
+        self.runs.iter().any(|run| run.run_uid == run_uid)
+    }
+
+    /// Record how a child run ended; returns false if it is not in the batch
+    pub fn record(
+        &mut self,
+        run_uid: &str,
+        exit_status: &str,
+        reason: &str,
+        num_events: u32,
+    ) -> bool {
+        let Some(run) = self.runs.iter_mut().find(|run| run.run_uid == run_uid) else {
+            return false;
+        };
+        run.exit_status = exit_status.to_string();
+        run.reason = reason.to_string();
+        run.num_events = num_events;
+        true
+    }
+
+    /// Whether every child run has ended
+    pub fn is_complete(&self) -> bool {
+        self.runs.iter().all(|run| !run.exit_status.is_empty())
+    }
+
+    pub fn summary(&self) -> BatchSummaryDoc {
+        BatchSummaryDoc {
+            uid: new_uid(),
+            batch_uid: self.batch_uid.clone(),
+            template_name: self.template_name.clone(),
+            runs: self.runs.clone(),
+            time_ns: now_ns(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_expand_template_over_samples() {
+        let template = PlanTemplate {
+            name: "per sample".to_string(),
+            plan_type: "count".to_string(),
+            parameters: HashMap::from([("num_points".to_string(), "{points}".to_string())]),
+            device_mapping: HashMap::from([("detector".to_string(), "pd_{channel}".to_string())]),
+            metadata: HashMap::from([("note".to_string(), "{{raw}} {sample}".to_string())]),
+        };
+        let items = [
+            item(&[("sample", "S-1"), ("points", "5"), ("channel", "a")]),
+            item(&[("sample", "S-2"), ("points", "7"), ("channel", "b")]),
+        ];
+
+        let runs = template.expand(&items).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[1].index, 1);
+        assert_eq!(runs[1].parameters["num_points"], "7");
+        assert_eq!(runs[1].device_mapping["detector"], "pd_b");
+        assert_eq!(runs[0].metadata["note"], "{raw} S-1");
+        assert_eq!(runs[0].metadata["sweep.sample"], "S-1");
+
+        // Unfilled placeholders fail the whole expansion
+        let missing = [item(&[("sample", "S-3"), ("points", "5")])];
+        let err = template.expand(&missing).unwrap_err();
+        assert!(err.contains("{channel}"), "{}", err);
+        assert!(template.expand(&[]).is_err());
+    }
+
+    #[test]
+    fn test_batch_tracker_completes_after_every_child() {
+        let mut tracker = BatchTracker::new(
+            "per sample",
+            [
+                ("run-a".to_string(), item(&[("sample", "S-1")])),
+                ("run-b".to_string(), item(&[("sample", "S-2")])),
+            ],
+        );
+        assert!(!tracker.record("other", "success", "", 3));
+        assert!(tracker.record("run-a", "success", "", 3));
+        assert!(!tracker.is_complete());
+        assert!(tracker.record("run-b", "fail", "stage fault", 1));
+        assert!(tracker.is_complete());
+
+        let summary = tracker.summary();
+        assert_eq!(summary.batch_uid, tracker.batch_uid());
+        assert_eq!(summary.count("success"), 1);
+        assert_eq!(summary.runs[1].reason, "stage fault");
+
+        let metadata = batch_metadata(tracker.batch_uid(), "per sample", 1, &tracker.run_uids());
+        assert_eq!(
+            metadata[BATCH_RUN_UIDS_METADATA_KEY],
+            r#"["run-a","run-b"]"#
+        );
+    }
+}
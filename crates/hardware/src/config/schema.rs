@@ -326,6 +326,27 @@ pub enum ConnectionType {
     Tcp,
     /// UDP socket
     Udp,
+    /// VXI-11 LAN instrument (SCPI over ONC RPC)
+    Vxi11,
+    /// HiSLIP LAN instrument (IVI-6.1)
+    Hislip,
+}
+
+impl ConnectionType {
+    /// Whether this is a VXI-11 or HiSLIP LAN instrument
+    pub fn is_lan(&self) -> bool {
+        matches!(self, ConnectionType::Vxi11 | ConnectionType::Hislip)
+    }
+
+    /// The LAN protocol of a VXI-11 or HiSLIP connection
+    #[cfg(feature = "lan")]
+    pub fn lan_transport(&self) -> Option<common::lan::LanTransport> {
+        match self {
+            ConnectionType::Vxi11 => Some(common::lan::LanTransport::Vxi11),
+            ConnectionType::Hislip => Some(common::lan::LanTransport::Hislip),
+            _ => None,
+        }
+    }
 }
 
 /// Parity bit setting.
@@ -0,0 +1,300 @@
+//! Display calibration of channels.
+//!
+//! A display transform turns a channel's raw value into what operators want
+//! to read, e.g. a photodiode's volts as estimated mW. Only clients apply it
+//! (`display = raw * scale + offset`): drivers, run documents and files keep
+//! the raw values. Because the table lives in the daemon, every GUI shows a
+//! channel with the same calibration and unit.
+//!
+//! Channels are named like alias targets (`device` or `device:parameter`)
+//! or by alias. Transforms come from the hardware config and can be changed
+//! at runtime; runtime changes are kept in a JSON file and take precedence
+//! over the config on the next start.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [display]
+//! path = "/var/lib/rust-daq/display_calibration.json"  # default: user data dir
+//!
+//! [display.channels.photodiode]
+//! scale = 12.5        # mW per V
+//! offset = -0.3
+//! unit = "mW"
+//! log_scale = false   # hint for plots
+//! ```
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Current file format version
+const FORMAT_VERSION: u32 = 1;
+
+/// How a channel's raw value is shown
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayTransform {
+    pub scale: f64,
+    pub offset: f64,
+    /// Unit of the displayed value (empty = the raw unit)
+    pub unit: String,
+    /// Plot on a logarithmic axis
+    pub log_scale: bool,
+}
+
+impl Default for DisplayTransform {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            offset: 0.0,
+            unit: String::new(),
+            log_scale: false,
+        }
+    }
+}
+
+impl DisplayTransform {
+    /// Displayed value of a raw value
+    pub fn apply(&self, raw: f64) -> f64 {
+        raw * self.scale + self.offset
+    }
+
+    /// Check that the transform can be inverted
+    pub fn validate(&self) -> Result<()> {
+        if !self.scale.is_finite() || self.scale == 0.0 {
+            bail!(
+                "Display scale must be finite and non-zero, got {}",
+                self.scale
+            );
+        }
+        if !self.offset.is_finite() {
+            bail!("Display offset must be finite, got {}", self.offset);
+        }
+        Ok(())
+    }
+}
+
+/// Display calibration settings (`[display]` in the hardware config)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayCalibrationConfig {
+    /// File keeping runtime changes (default: `<data dir>/rust-daq/display_calibration.json`)
+    pub path: Option<PathBuf>,
+    /// Transforms keyed by channel name
+    pub channels: BTreeMap<String, DisplayTransform>,
+}
+
+impl DisplayCalibrationConfig {
+    /// Check every configured transform
+    pub fn validate(&self) -> Result<()> {
+        for (channel, transform) in &self.channels {
+            transform
+                .validate()
+                .with_context(|| format!("Display transform of '{}'", channel))?;
+        }
+        Ok(())
+    }
+
+    /// Runtime change file location
+    pub fn path(&self) -> PathBuf {
+        self.path
+            .clone()
+            .unwrap_or_else(default_display_calibration_path)
+    }
+}
+
+/// Default location of the runtime change file
+pub fn default_display_calibration_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rust-daq")
+        .join("display_calibration.json")
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OverrideFile {
+    version: u32,
+    /// `null` removes a transform set in the config
+    overrides: BTreeMap<String, Option<DisplayTransform>>,
+}
+
+/// Configured transforms with the runtime changes made on top of them
+#[derive(Debug, Clone, Default)]
+pub struct DisplayCalibration {
+    configured: BTreeMap<String, DisplayTransform>,
+    overrides: BTreeMap<String, Option<DisplayTransform>>,
+    /// Where runtime changes are saved (`None` = memory only)
+    path: Option<PathBuf>,
+}
+
+impl DisplayCalibration {
+    /// Configured transforms plus the changes saved at the configured path
+    ///
+    /// A missing file means no changes.
+    pub fn load(config: &DisplayCalibrationConfig) -> Result<Self> {
+        let path = config.path();
+        let overrides = match std::fs::read_to_string(&path) {
+            Ok(contents) => {
+                let file: OverrideFile = serde_json::from_str(&contents).with_context(|| {
+                    format!("Failed to parse display calibration {}", path.display())
+                })?;
+                if file.version > FORMAT_VERSION {
+                    bail!(
+                        "Display calibration {} has format version {} (supported: {})",
+                        path.display(),
+                        file.version,
+                        FORMAT_VERSION
+                    );
+                }
+                file.overrides
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!("Failed to read display calibration {}", path.display())
+                })
+            }
+        };
+        Ok(Self {
+            configured: config.channels.clone(),
+            overrides,
+            path: Some(path),
+        })
+    }
+
+    /// Configured transforms only; runtime changes are not saved
+    pub fn in_memory(config: &DisplayCalibrationConfig) -> Self {
+        Self {
+            configured: config.channels.clone(),
+            ..Self::default()
+        }
+    }
+
+    /// Current transform of a channel
+    pub fn get(&self, channel: &str) -> Option<&DisplayTransform> {
+        match self.overrides.get(channel) {
+            Some(transform) => transform.as_ref(),
+            None => self.configured.get(channel),
+        }
+    }
+
+    /// All current transforms, keyed by channel name
+    pub fn transforms(&self) -> BTreeMap<String, DisplayTransform> {
+        let mut transforms = self.configured.clone();
+        for (channel, transform) in &self.overrides {
+            match transform {
+                Some(transform) => transforms.insert(channel.clone(), transform.clone()),
+                None => transforms.remove(channel),
+            };
+        }
+        transforms
+    }
+
+    /// Set (or with `None`, remove) a channel's transform and save the change
+    pub fn set(&mut self, channel: &str, transform: Option<DisplayTransform>) -> Result<()> {
+        let channel = channel.trim();
+        if channel.is_empty() {
+            bail!("Display transform needs a channel name");
+        }
+        if let Some(transform) = &transform {
+            transform.validate()?;
+        }
+        let previous = self.overrides.insert(channel.to_string(), transform);
+        if let Err(e) = self.save() {
+            // Keep memory and file in step
+            match previous {
+                Some(previous) => self.overrides.insert(channel.to_string(), previous),
+                None => self.overrides.remove(channel),
+            };
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let file = OverrideFile {
+            version: FORMAT_VERSION,
+            overrides: self.overrides.clone(),
+        };
+        let json = serde_json::to_string_pretty(&file)?;
+        // Write then rename so a crash never leaves a truncated file
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)
+            .with_context(|| format!("Failed to write display calibration {}", tmp.display()))?;
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("Failed to replace display calibration {}", path.display()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    fn milliwatts(scale: f64) -> DisplayTransform {
+        DisplayTransform {
+            scale,
+            unit: "mW".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_runtime_changes_persist_over_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = DisplayCalibrationConfig {
+            path: Some(dir.path().join("display.json")),
+            channels: BTreeMap::from([
+                ("photodiode".to_string(), milliwatts(12.5)),
+                ("power_meter".to_string(), milliwatts(1000.0)),
+            ]),
+        };
+
+        let mut calibration = DisplayCalibration::load(&config).unwrap();
+        assert_eq!(calibration.get("photodiode").unwrap().apply(2.0), 25.0);
+
+        calibration
+            .set("photodiode", Some(milliwatts(10.0)))
+            .unwrap();
+        calibration.set("power_meter", None).unwrap();
+        assert!(calibration.set("stage", Some(milliwatts(0.0))).is_err());
+
+        // A restart keeps the changes, not the config values
+        let reloaded = DisplayCalibration::load(&config).unwrap();
+        let transforms = reloaded.transforms();
+        assert_eq!(transforms.len(), 1);
+        assert_eq!(transforms["photodiode"].scale, 10.0);
+    }
+
+    #[test]
+    fn test_config_parses_and_validates() {
+        let config: DisplayCalibrationConfig = toml::from_str(
+            r#"
+            [channels.photodiode]
+            scale = 12.5
+            unit = "mW"
+            "#,
+        )
+        .unwrap();
+        let transform = &config.channels["photodiode"];
+        assert_eq!(transform.offset, 0.0);
+        assert!(!transform.log_scale);
+        assert!(config.validate().is_ok());
+
+        let bad = DisplayCalibrationConfig {
+            channels: BTreeMap::from([("x".to_string(), milliwatts(f64::NAN))]),
+            ..Default::default()
+        };
+        assert!(bad.validate().is_err());
+    }
+}
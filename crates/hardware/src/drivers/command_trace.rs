@@ -0,0 +1,154 @@
+//! Command Trace - per-device logging and dry run of config-driven commands
+//!
+//! Debugging a TOML command template usually means finding out exactly which
+//! bytes went down the wire and what came back. With tracing enabled, the
+//! [`GenericSerialDriver`](super::generic_serial::GenericSerialDriver) records
+//! every exchange as a [`CommandTraceEvent`]: the bytes sent (terminator
+//! included), the bytes received and how long the round trip took. Events are
+//! logged under the `command_trace` target and echoed to any subscriber.
+//!
+//! In dry-run mode commands are rendered through the template engine and
+//! traced, but nothing is written to the port: no response is read, response
+//! expectations of the init sequence are skipped and queries return no value.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [devices.config]
+//! port = "/dev/ttyUSB1"
+//! address = "2"
+//! trace = true      # log exact bytes and timing
+//! dry_run = false   # true: render and trace commands, never open the port
+//! ```
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::broadcast;
+
+/// Events buffered for each trace subscriber before it lags
+const TRACE_CHANNEL_CAPACITY: usize = 256;
+
+/// One command sent (or, in dry run, rendered) by a driver
+#[derive(Debug, Clone)]
+pub struct CommandTraceEvent {
+    /// Device name and bus address, e.g. `ELL14 Rotator@2`
+    pub device: String,
+    /// Exact bytes written, terminator included
+    pub sent: Vec<u8>,
+    /// Exact bytes read, before trimming (`None` when no response was read)
+    pub received: Option<Vec<u8>>,
+    /// Write to last byte read
+    pub elapsed: Duration,
+    pub timestamp: SystemTime,
+    /// The command was rendered but not sent
+    pub dry_run: bool,
+    /// Why the exchange failed, if it did
+    pub error: Option<String>,
+}
+
+/// Bytes as printable ASCII with escapes (`\r`, `\n`, `\x02`, ...)
+pub fn escape_bytes(bytes: &[u8]) -> String {
+    bytes.escape_ascii().to_string()
+}
+
+/// Trace and dry-run switches of one driver, shared by its clones
+#[derive(Clone)]
+pub struct CommandTrace {
+    inner: Arc<TraceInner>,
+}
+
+struct TraceInner {
+    enabled: AtomicBool,
+    dry_run: AtomicBool,
+    sender: broadcast::Sender<CommandTraceEvent>,
+}
+
+impl Default for CommandTrace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandTrace {
+    /// A trace with both switches off
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(TRACE_CHANNEL_CAPACITY);
+        Self {
+            inner: Arc::new(TraceInner {
+                enabled: AtomicBool::new(false),
+                dry_run: AtomicBool::new(false),
+                sender,
+            }),
+        }
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.inner.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Dry run implies tracing
+    pub fn set_dry_run(&self, dry_run: bool) {
+        self.inner.dry_run.store(dry_run, Ordering::Relaxed);
+    }
+
+    /// Whether exchanges are recorded
+    pub fn is_enabled(&self) -> bool {
+        self.inner.enabled.load(Ordering::Relaxed) || self.is_dry_run()
+    }
+
+    /// Whether commands are rendered without being sent
+    pub fn is_dry_run(&self) -> bool {
+        self.inner.dry_run.load(Ordering::Relaxed)
+    }
+
+    /// Receive every event recorded from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<CommandTraceEvent> {
+        self.inner.sender.subscribe()
+    }
+
+    /// Log an event and echo it to subscribers
+    pub fn record(&self, event: CommandTraceEvent) {
+        let received = event.received.as_deref().map(escape_bytes);
+        match &event.error {
+            Some(error) => tracing::warn!(
+                target: "command_trace",
+                device = %event.device,
+                sent = %escape_bytes(&event.sent),
+                received = ?received,
+                elapsed_us = event.elapsed.as_micros() as u64,
+                error = %error,
+                "Command failed"
+            ),
+            None => tracing::info!(
+                target: "command_trace",
+                device = %event.device,
+                sent = %escape_bytes(&event.sent),
+                received = ?received,
+                elapsed_us = event.elapsed.as_micros() as u64,
+                dry_run = event.dry_run,
+                "Command"
+            ),
+        }
+        // No subscribers is the normal case
+        let _ = self.inner.sender.send(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dry_run_implies_tracing() {
+        let trace = CommandTrace::new();
+        assert!(!trace.is_enabled());
+        trace.clone().set_dry_run(true);
+        assert!(trace.is_enabled());
+        assert!(trace.is_dry_run());
+        assert_eq!(
+            escape_bytes(b"2ma00004600\r\n\x02"),
+            "2ma00004600\\r\\n\\x02"
+        );
+    }
+}
@@ -88,7 +88,7 @@
 //! }
 //! ```
 
-use crate::capabilities::{Movable, Parameterized};
+use crate::capabilities::{Movable, Parameterized, RawTerminal};
 use crate::port_resolver::resolve_port;
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
@@ -96,6 +96,7 @@ use common::error::DaqError;
 use common::error_recovery::RetryPolicy;
 use common::observable::ParameterSet;
 use common::parameter::Parameter;
+use common::serial::PortResync;
 use futures::future::BoxFuture;
 use std::future::Future;
 use std::sync::Arc;
@@ -650,6 +651,8 @@ pub struct DiscoveredDevice {
 pub struct Ell14Driver {
     /// Serial port protected by Arc<Mutex> for shared access across multiple drivers
     port: SharedPort,
+    /// Drains replies left by this driver's interrupted transactions
+    resync: PortResync,
     /// Physical device address (0-9, A-F) - never changes
     physical_address: String,
     /// Active address for commands - may differ when in group mode
@@ -1065,6 +1068,7 @@ impl Ell14Driver {
 
         Self {
             port,
+            resync: PortResync::new(),
             physical_address: address.clone(),
             active_address: address,
             pulses_per_degree,
@@ -1172,6 +1176,7 @@ impl Ell14Driver {
     /// ELL14 protocol is ASCII based with format: {Address}{Command}{Data}
     async fn transaction_once(&self, command: &str) -> Result<String> {
         let mut port = self.port.lock().await;
+        let in_flight = self.resync.begin(&mut *port).await;
 
         // Construct packet: Address + Command
         // Example: "0gs" (Get Status for device 0)
@@ -1268,6 +1273,7 @@ impl Ell14Driver {
             // Don't fail here - let caller decide what to do with errors
             // Some commands return status codes that aren't fatal
         }
+        in_flight.finish();
 
         Ok(response.to_string())
     }
@@ -2634,6 +2640,20 @@ impl Parameterized for Ell14Driver {
     }
 }
 
+#[async_trait]
+impl RawTerminal for Ell14Driver {
+    async fn send_raw(&self, command: &str, expect_response: bool) -> Result<String> {
+        // Single attempt: retrying e.g. "ho" would home the device again
+        match self.transaction_once(command).await {
+            Ok(resp) => Ok(resp),
+            Err(e) if !expect_response && e.to_string().contains("empty response") => {
+                Ok(String::new())
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
 #[async_trait]
 impl Movable for Ell14Driver {
     #[instrument(skip(self), fields(address = %self.physical_address, position_deg), err)]
@@ -3137,6 +3157,32 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn cancelled_transaction_does_not_answer_the_next() -> Result<()> {
+        let (mut host, device) = tokio::io::duplex(64);
+        let port: SharedPort = Arc::new(Mutex::new(Box::new(device)));
+        let driver = Ell14Driver::with_test_port(port, "0", 398.2222);
+
+        // Cancel a status query before the rotator answers it
+        let cancelled =
+            tokio::time::timeout(Duration::from_millis(20), driver.transaction_once("gs")).await;
+        assert!(cancelled.is_err());
+        let mut buf = vec![0u8; 32];
+        let _n = host.read(&mut buf).await?;
+        host.write_all(b"0GS00\r\n").await?;
+
+        let responder = tokio::spawn(async move {
+            let n = host.read(&mut buf).await.unwrap();
+            assert_eq!(&buf[..n], b"0gp");
+            host.write_all(b"0PO00004600\r\n").await.unwrap();
+            host
+        });
+        assert_eq!(driver.transaction_once("gp").await?, "0PO00004600");
+        responder.await?;
+
+        Ok(())
+    }
+
     /// Test helper: parse position response without needing a real driver
     fn parse_position(response: &str, pulses_per_degree: f64) -> Result<f64> {
         // Look for position response marker "PO"
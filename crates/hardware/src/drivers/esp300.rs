@@ -31,13 +31,14 @@
 //! }
 //! ```
 
-use crate::capabilities::{Movable, Parameterized};
+use crate::capabilities::{Movable, Parameterized, RawTerminal};
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use common::error::DaqError;
 use common::error_recovery::RetryPolicy;
 use common::observable::ParameterSet;
 use common::parameter::Parameter;
+use common::serial::PortResync;
 use futures::future::BoxFuture;
 use std::future::Future;
 use std::sync::Arc;
@@ -60,6 +61,8 @@ type SharedPort = Arc<Mutex<BufReader<DynSerial>>>;
 pub struct Esp300Driver {
     /// Serial port protected by Mutex for exclusive access
     port: SharedPort,
+    /// Drains replies left by interrupted queries
+    resync: PortResync,
     /// Axis number (1-3)
     axis: u8,
     /// Command timeout duration
@@ -233,6 +236,7 @@ impl Esp300Driver {
 
         Self {
             port,
+            resync: PortResync::new(),
             axis,
             timeout: Duration::from_secs(5),
             position_mm: position,
@@ -312,6 +316,8 @@ impl Esp300Driver {
 
     async fn query_once(&self, command: &str) -> Result<String> {
         let mut port = self.port.lock().await;
+        // A timed-out attempt leaves its reply for the retry to read
+        let in_flight = self.resync.begin(&mut *port).await;
 
         // Write command with terminator
         let cmd = format!("{}\r\n", command);
@@ -326,6 +332,7 @@ impl Esp300Driver {
             .await
             .context("ESP300 read timeout")?
             .context("ESP300 read error")?;
+        in_flight.finish();
 
         Ok(response.trim().to_string())
     }
@@ -368,6 +375,18 @@ impl Parameterized for Esp300Driver {
     }
 }
 
+#[async_trait]
+impl RawTerminal for Esp300Driver {
+    async fn send_raw(&self, command: &str, expect_response: bool) -> Result<String> {
+        if expect_response {
+            self.query(command).await
+        } else {
+            self.send_command(command).await?;
+            Ok(String::new())
+        }
+    }
+}
+
 #[async_trait]
 impl Movable for Esp300Driver {
     #[instrument(skip(self), fields(axis = self.axis, position), err)]
@@ -463,4 +482,29 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn cancelled_query_does_not_answer_the_next() -> Result<()> {
+        let (mut host, device) = tokio::io::duplex(64);
+        let port: SharedPort = Arc::new(Mutex::new(BufReader::new(Box::new(device))));
+        let driver = Esp300Driver::with_test_port(port, 1);
+
+        // Cancel a position read before the controller answers it
+        let cancelled = tokio::time::timeout(Duration::from_millis(20), driver.position()).await;
+        assert!(cancelled.is_err());
+        let mut buf = vec![0u8; 64];
+        let _n = host.read(&mut buf).await?;
+        host.write_all(b"5.000000\r\n").await?;
+
+        let responder = tokio::spawn(async move {
+            let n = host.read(&mut buf).await.unwrap();
+            assert_eq!(&buf[..n], b"1TP?\r\n");
+            host.write_all(b"7.000000\r\n").await.unwrap();
+            host
+        });
+        assert!((driver.position().await? - 7.0).abs() < 1e-9);
+        responder.await?;
+
+        Ok(())
+    }
 }
@@ -32,8 +32,10 @@
 
 use crate::capabilities::{Movable, Readable, ShutterControl, WavelengthTunable};
 use crate::config::schema::{DeviceConfig, ErrorSeverity, FieldType, RetryConfig};
+use crate::drivers::command_trace::{CommandTrace, CommandTraceEvent};
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
+use common::serial::PortResync;
 use evalexpr::{eval_number_with_context, ContextWithMutableVariables, HashMapContext, Value};
 use regex::Regex;
 use std::collections::HashMap;
@@ -163,12 +165,16 @@ pub struct GenericSerialDriver {
     config: Arc<DeviceConfig>,
     /// Shared serial port
     port: SharedPort,
+    /// Drains replies left by interrupted exchanges
+    resync: PortResync,
     /// Device address (for RS-485 multidrop)
     address: String,
     /// Cached parameter values (for conversions)
     parameters: Arc<Mutex<HashMap<String, f64>>>,
     /// Compiled regex patterns for responses
     response_patterns: Arc<HashMap<String, Regex>>,
+    /// Command trace and dry-run switches
+    trace: CommandTrace,
     /// Compiled Rhai scripts (when scripting feature is enabled)
     #[cfg(feature = "scripting")]
     compiled_scripts: Arc<CompiledScripts>,
@@ -221,9 +227,11 @@ impl GenericSerialDriver {
         Ok(Self {
             config: Arc::new(config),
             port,
+            resync: PortResync::new(),
             address: address.to_string(),
             parameters: Arc::new(Mutex::new(parameters)),
             response_patterns: Arc::new(response_patterns),
+            trace: CommandTrace::new(),
             #[cfg(feature = "scripting")]
             compiled_scripts,
             #[cfg(feature = "scripting")]
@@ -591,100 +599,32 @@ impl GenericSerialDriver {
     /// 3. Returns raw response string
     #[instrument(skip(self), fields(address = %self.address), err)]
     pub async fn transaction(&self, command: &str) -> Result<String> {
-        let timeout = Duration::from_millis(self.config.connection.timeout_ms as u64);
-        let mut port = self.port.lock().await;
-
-        trace!(command = %command, "Sending command");
-
-        // Write command
-        port.write_all(command.as_bytes())
-            .await
-            .context("Failed to write command")?;
-
-        // Add TX terminator if configured
-        if !self.config.connection.terminator_tx.is_empty() {
-            port.write_all(self.config.connection.terminator_tx.as_bytes())
-                .await
-                .context("Failed to write terminator")?;
-        }
-
-        // Small delay for device to process
-        tokio::time::sleep(Duration::from_millis(50)).await;
-
-        // Read response with timeout
-        let mut response_buf = Vec::with_capacity(64);
-        let mut buf = [0u8; 64];
-        let deadline = tokio::time::Instant::now() + timeout;
-
-        loop {
-            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
-            if remaining.is_zero() {
-                break;
-            }
-
-            match tokio::time::timeout(
-                remaining.min(Duration::from_millis(100)),
-                port.read(&mut buf),
-            )
-            .await
-            {
-                Ok(Ok(n)) if n > 0 => {
-                    response_buf.extend_from_slice(&buf[..n]);
-                    // Brief delay for remaining bytes
-                    tokio::time::sleep(Duration::from_millis(20)).await;
-                }
-                Ok(Ok(_)) => {
-                    if !response_buf.is_empty() {
-                        break;
-                    }
-                }
-                Ok(Err(_)) | Err(_) => {
-                    if !response_buf.is_empty() {
-                        break;
-                    }
-                }
-            }
-
-            // If we have data, try one more read
-            if !response_buf.is_empty() {
-                tokio::time::sleep(Duration::from_millis(30)).await;
-                if let Ok(Ok(n)) =
-                    tokio::time::timeout(Duration::from_millis(50), port.read(&mut buf)).await
-                {
-                    if n > 0 {
-                        response_buf.extend_from_slice(&buf[..n]);
-                    }
-                }
-                break;
-            }
-        }
-
-        let response = std::str::from_utf8(&response_buf)
-            .context("Invalid UTF-8 in response")?
-            .trim()
-            .to_string();
-
-        debug!(command = %command, response = %response, "Transaction complete");
-
-        Ok(response)
+        self.transaction_with_timeout(command, None).await
     }
 
     /// Send a command without waiting for response.
     #[instrument(skip(self), fields(address = %self.address), err)]
     pub async fn send_command(&self, command: &str) -> Result<()> {
-        let mut port = self.port.lock().await;
+        let frame = self.frame_command(command);
+        if self.trace.is_dry_run() {
+            self.record_dry_run(frame);
+            return Ok(());
+        }
 
-        trace!(command = %command, "Sending command (no response)");
+        let started = std::time::Instant::now();
+        let result = {
+            let mut port = self.port.lock().await;
 
-        port.write_all(command.as_bytes())
-            .await
-            .context("Failed to write command")?;
+            trace!(command = %command, "Sending command (no response)");
 
-        if !self.config.connection.terminator_tx.is_empty() {
-            port.write_all(self.config.connection.terminator_tx.as_bytes())
+            port.write_all(&frame)
                 .await
-                .context("Failed to write terminator")?;
+                .context("Failed to write command")
+        };
+        if self.trace.is_enabled() {
+            self.record_exchange(frame, None, started.elapsed(), result.as_ref().err());
         }
+        result?;
 
         // Brief delay
         tokio::time::sleep(Duration::from_millis(50)).await;
@@ -692,6 +632,13 @@ impl GenericSerialDriver {
         Ok(())
     }
 
+    /// Command bytes as written to the port, TX terminator included
+    fn frame_command(&self, command: &str) -> Vec<u8> {
+        let mut frame = command.as_bytes().to_vec();
+        frame.extend_from_slice(self.config.connection.terminator_tx.as_bytes());
+        frame
+    }
+
     // =========================================================================
     // Error Detection and Retry
     // =========================================================================
@@ -832,22 +779,44 @@ impl GenericSerialDriver {
     ) -> Result<String> {
         let timeout =
             Duration::from_millis(timeout_ms.unwrap_or(self.config.connection.timeout_ms) as u64);
-        let mut port = self.port.lock().await;
+        let frame = self.frame_command(command);
+        if self.trace.is_dry_run() {
+            self.record_dry_run(frame);
+            return Ok(String::new());
+        }
 
         trace!(command = %command, timeout_ms = ?timeout.as_millis(), "Sending command with timeout");
 
+        let started = std::time::Instant::now();
+        let result = self.exchange(&frame, timeout).await;
+        if self.trace.is_enabled() {
+            let (received, error) = match &result {
+                Ok(received) => (Some(received.clone()), None),
+                Err(e) => (None, Some(e)),
+            };
+            self.record_exchange(frame, received, started.elapsed(), error);
+        }
+
+        let response = std::str::from_utf8(&result?)
+            .context("Invalid UTF-8 in response")?
+            .trim()
+            .to_string();
+
+        debug!(command = %command, response = %response, "Transaction complete");
+
+        Ok(response)
+    }
+
+    /// Write a framed command and collect the raw response bytes.
+    async fn exchange(&self, frame: &[u8], timeout: Duration) -> Result<Vec<u8>> {
+        let mut port = self.port.lock().await;
+        let in_flight = self.resync.begin(&mut *port).await;
+
         // Write command
-        port.write_all(command.as_bytes())
+        port.write_all(frame)
             .await
             .context("Failed to write command")?;
 
-        // Add TX terminator if configured
-        if !self.config.connection.terminator_tx.is_empty() {
-            port.write_all(self.config.connection.terminator_tx.as_bytes())
-                .await
-                .context("Failed to write terminator")?;
-        }
-
         // Small delay for device to process
         tokio::time::sleep(Duration::from_millis(50)).await;
 
@@ -870,6 +839,7 @@ impl GenericSerialDriver {
             {
                 Ok(Ok(n)) if n > 0 => {
                     response_buf.extend_from_slice(&buf[..n]);
+                    // Brief delay for remaining bytes
                     tokio::time::sleep(Duration::from_millis(20)).await;
                 }
                 Ok(Ok(_)) => {
@@ -884,6 +854,7 @@ impl GenericSerialDriver {
                 }
             }
 
+            // If we have data, try one more read
             if !response_buf.is_empty() {
                 tokio::time::sleep(Duration::from_millis(30)).await;
                 if let Ok(Ok(n)) =
@@ -896,15 +867,70 @@ impl GenericSerialDriver {
                 break;
             }
         }
+        // A device that has not answered yet may still do so
+        if !response_buf.is_empty() {
+            in_flight.finish();
+        }
 
-        let response = std::str::from_utf8(&response_buf)
-            .context("Invalid UTF-8 in response")?
-            .trim()
-            .to_string();
+        Ok(response_buf)
+    }
 
-        debug!(command = %command, response = %response, "Transaction complete");
+    // =========================================================================
+    // Command Trace
+    // =========================================================================
 
-        Ok(response)
+    /// Trace and dry-run switches of this driver
+    pub fn trace(&self) -> &CommandTrace {
+        &self.trace
+    }
+
+    /// Render a command exactly as it would be written to the port, without
+    /// sending it, and record it in the trace as a dry run.
+    ///
+    /// Useful for checking a TOML command template against a device manual:
+    /// the result includes the TX terminator.
+    pub async fn dry_run_command(
+        &self,
+        command_name: &str,
+        params: &HashMap<String, f64>,
+    ) -> Result<Vec<u8>> {
+        let frame = self.frame_command(&self.format_command(command_name, params).await?);
+        self.record_dry_run(frame.clone());
+        Ok(frame)
+    }
+
+    fn trace_device(&self) -> String {
+        format!("{}@{}", self.config.device.name, self.address)
+    }
+
+    fn record_dry_run(&self, sent: Vec<u8>) {
+        self.trace.record(CommandTraceEvent {
+            device: self.trace_device(),
+            sent,
+            received: None,
+            elapsed: Duration::ZERO,
+            timestamp: std::time::SystemTime::now(),
+            dry_run: true,
+            error: None,
+        });
+    }
+
+    fn record_exchange(
+        &self,
+        sent: Vec<u8>,
+        received: Option<Vec<u8>>,
+        elapsed: Duration,
+        error: Option<&anyhow::Error>,
+    ) {
+        self.trace.record(CommandTraceEvent {
+            device: self.trace_device(),
+            sent,
+            received,
+            elapsed,
+            timestamp: std::time::SystemTime::now(),
+            dry_run: false,
+            error: error.map(|e| format!("{:#}", e)),
+        });
     }
 
     // =========================================================================
@@ -948,9 +974,10 @@ impl GenericSerialDriver {
 
             match result {
                 Ok(cmd_result) => {
-                    // Validate expected response if configured
+                    // Validate expected response if configured (a dry run
+                    // reads no response)
                     if let Some(ref expect) = step.expect {
-                        if !cmd_result.response.contains(expect) {
+                        if !self.trace.is_dry_run() && !cmd_result.response.contains(expect) {
                             if step.required {
                                 return Err(anyhow!(
                                     "Init step {} failed: expected '{}' in response, got '{}'",
@@ -1081,6 +1108,11 @@ impl GenericSerialDriver {
             String::new()
         };
 
+        // A dry run has no response to parse
+        if self.trace.is_dry_run() {
+            return Ok(None);
+        }
+
         // Parse response and apply output conversion
         if let Some(ref response_name) = cmd_config.response {
             let parsed = self.parse_response(response_name, &response)?;
@@ -1144,6 +1176,11 @@ impl GenericSerialDriver {
             let cmd = self.format_command(poll_command, &HashMap::new()).await?;
             let response = self.transaction(&cmd).await?;
 
+            // Nothing settles in a dry run: one traced poll is enough
+            if self.trace.is_dry_run() {
+                return Ok(());
+            }
+
             // Get command's response definition
             let cmd_config = self
                 .config
@@ -1656,6 +1693,87 @@ timeout_ms = 5000
         assert!(!driver.evaluate_condition("code != 0", &parsed).unwrap());
     }
 
+    #[tokio::test]
+    async fn test_trace_records_exact_bytes() {
+        let config = load_device_config_from_str(TEST_CONFIG).unwrap();
+        let mock = MockPort::new();
+        mock.set_response("2PO00004600\r\n");
+        let port: SharedPort = Arc::new(Mutex::new(Box::new(mock)));
+        let driver = GenericSerialDriver::new(config, port, "2").unwrap();
+        driver.trace().set_enabled(true);
+        let mut events = driver.trace().subscribe();
+
+        let position = driver.position().await.unwrap();
+        assert!((position - 45.0).abs() < 0.1);
+
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.device, "Test ELL14@2");
+        assert_eq!(event.sent, b"2gp");
+        assert_eq!(event.received.as_deref(), Some(&b"2PO00004600\r\n"[..]));
+        assert!(!event.dry_run);
+        assert!(event.error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_exchange_does_not_answer_the_next() {
+        let config = load_device_config_from_str(TEST_CONFIG).unwrap();
+        let (mut host, device) = tokio::io::duplex(64);
+        let port: SharedPort = Arc::new(Mutex::new(Box::new(device)));
+        let driver = GenericSerialDriver::new(config, port, "2").unwrap();
+
+        // Cancel a position read before the device answers it
+        let cancelled = tokio::time::timeout(Duration::from_millis(20), driver.position()).await;
+        assert!(cancelled.is_err());
+        let mut buf = [0u8; 16];
+        let _n = host.read(&mut buf).await.unwrap();
+        host.write_all(b"2PO00000000\r\n").await.unwrap();
+
+        let responder = tokio::spawn(async move {
+            let n = host.read(&mut buf).await.unwrap();
+            assert_eq!(&buf[..n], b"2gp");
+            host.write_all(b"2PO00004600\r\n").await.unwrap();
+            host
+        });
+        let position = driver.position().await.unwrap();
+        assert!((position - 45.0).abs() < 0.1);
+        responder.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_leaves_port_untouched() {
+        let config = load_device_config_from_str(TEST_CONFIG).unwrap();
+        let mock = MockPort::new();
+        let written = mock.write_buf.clone();
+        let port: SharedPort = Arc::new(Mutex::new(Box::new(mock)));
+        let driver = GenericSerialDriver::new(config, port, "2").unwrap();
+        driver.trace().set_dry_run(true);
+        let mut events = driver.trace().subscribe();
+
+        driver.stop().await.unwrap();
+        driver.wait_settled().await.unwrap();
+        assert!(
+            driver.position().await.is_err(),
+            "dry-run queries have no value"
+        );
+
+        let sent: Vec<_> = std::iter::from_fn(|| events.try_recv().ok())
+            .map(|event| {
+                assert!(event.dry_run && event.received.is_none());
+                event.sent
+            })
+            .collect();
+        assert_eq!(sent, [b"2st", b"2gs", b"2gp"]);
+        assert!(written.lock().unwrap().is_empty());
+
+        let mut params = HashMap::new();
+        params.insert("position_pulses".to_string(), -1.0);
+        let frame = driver
+            .dry_run_command("move_absolute", &params)
+            .await
+            .unwrap();
+        assert_eq!(frame, b"2maFFFFFFFF");
+    }
+
     // =========================================================================
     // Scripting Tests (requires "scripting" feature)
     // =========================================================================
@@ -30,6 +30,10 @@ mod ell14_polling;
 #[cfg(feature = "serial")]
 pub mod generic_serial;
 
+// Command trace and dry run for config-driven devices
+#[cfg(feature = "serial")]
+pub mod command_trace;
+
 // Rhai scripting engine for config-driven drivers
 #[cfg(feature = "scripting")]
 pub mod script_engine;
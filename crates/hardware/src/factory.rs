@@ -33,7 +33,7 @@
 #[allow(unused_imports)]
 use crate::capabilities::{Movable, Readable, ShutterControl, WavelengthTunable};
 use crate::config::load_device_config;
-use crate::config::schema::DeviceConfig;
+use crate::config::schema::{ConnectionType, DeviceConfig};
 use crate::drivers::generic_serial::{GenericSerialDriver, SharedPort};
 use anyhow::{anyhow, Context, Result};
 use common::driver::{
@@ -434,17 +434,43 @@ impl ConfiguredBus {
 ///
 /// This config is passed to `build()` and contains instance-specific
 /// settings like port path and device address.
+///
+/// LAN instruments give a transport and the instrument's address instead of
+/// a serial port:
+///
+/// ```toml
+/// transport = "hislip"     # or "vxi11"
+/// host = "192.168.1.20"    # or a VISA resource, e.g. "TCPIP0::192.168.1.20::inst0::INSTR"
+/// ```
+///
+/// `trace = true` logs every command with the exact bytes sent and received;
+/// `dry_run = true` renders and traces commands without opening the port
+/// (see [`crate::drivers::command_trace`]).
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct GenericSerialInstanceConfig {
-    /// Serial port path (e.g., "/dev/ttyUSB0")
+    /// Serial port path (e.g., "/dev/ttyUSB0"), or the instrument address
+    /// for LAN transports (also accepted as `host`)
+    #[serde(alias = "host")]
     pub port: String,
 
+    /// Connection type overriding the device definition's, e.g. `"hislip"`
+    #[serde(default)]
+    pub transport: Option<ConnectionType>,
+
     /// Device address on the bus (for RS-485 multidrop protocols)
     #[serde(default = "default_address")]
     pub address: String,
 
     /// Baud rate override (uses config default if not specified)
     pub baud_rate: Option<u32>,
+
+    /// Log every command with its exact bytes and timing
+    #[serde(default)]
+    pub trace: bool,
+
+    /// Render and trace commands without sending them
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 fn default_address() -> String {
@@ -573,7 +599,8 @@ impl DriverFactoryTrait for GenericSerialDriverFactory {
         let _: GenericSerialInstanceConfig = config.clone().try_into().map_err(|e| {
             anyhow!(
                 "Invalid instance config for '{}': {}. \
-                 Expected 'port' (string) and optional 'address' (string)",
+                 Expected 'port' (string, or 'host' for LAN transports) and optional \
+                 'address' (string) and 'transport'",
                 self.driver_type,
                 e
             )
@@ -601,48 +628,64 @@ impl DriverFactoryTrait for GenericSerialDriverFactory {
                 .baud_rate
                 .unwrap_or(device_config.connection.baud_rate);
 
-            // Get or open the shared port
-            let resolved_path = crate::port_resolver::resolve_port(&instance.port)
-                .map_err(|e| anyhow!("Failed to resolve port '{}': {}", instance.port, e))?;
-
-            let shared_port = {
-                let cache = port_cache.lock().unwrap_or_else(|p| p.into_inner());
-                cache.get(&resolved_path).cloned()
-            };
-
-            let shared_port = match shared_port {
-                Some(port) => port,
-                None => {
-                    use tokio_serial::SerialPortBuilderExt;
-
-                    let path_clone = resolved_path.clone();
-                    let port = tokio::task::spawn_blocking(move || {
-                        tokio_serial::new(&path_clone, baud_rate)
-                            .data_bits(tokio_serial::DataBits::Eight)
-                            .parity(tokio_serial::Parity::None)
-                            .stop_bits(tokio_serial::StopBits::One)
-                            .flow_control(tokio_serial::FlowControl::None)
-                            .open_native_async()
-                            .context("Failed to open serial port")
-                    })
-                    .await
-                    .context("spawn_blocking failed")??;
-
-                    let boxed: crate::drivers::generic_serial::DynSerial = Box::new(port);
-                    let shared: SharedPort = Arc::new(Mutex::new(boxed));
-
-                    // Cache it
-                    {
-                        let mut cache = port_cache.lock().unwrap_or_else(|p| p.into_inner());
-                        cache.insert(resolved_path, shared.clone());
+            let transport = instance
+                .transport
+                .unwrap_or(device_config.connection.connection_type);
+            let shared_port = if instance.dry_run {
+                // Never read or written: a dry run doesn't touch the hardware
+                let (placeholder, _) = tokio::io::duplex(1);
+                let boxed: crate::drivers::generic_serial::DynSerial = Box::new(placeholder);
+                Arc::new(Mutex::new(boxed))
+            } else if transport.is_lan() {
+                let timeout =
+                    std::time::Duration::from_millis(device_config.connection.timeout_ms as u64);
+                open_lan_port(transport, &instance.port, timeout, &port_cache).await?
+            } else {
+                // Get or open the shared port
+                let resolved_path = crate::port_resolver::resolve_port(&instance.port)
+                    .map_err(|e| anyhow!("Failed to resolve port '{}': {}", instance.port, e))?;
+
+                let shared_port = {
+                    let cache = port_cache.lock().unwrap_or_else(|p| p.into_inner());
+                    cache.get(&resolved_path).cloned()
+                };
+
+                match shared_port {
+                    Some(port) => port,
+                    None => {
+                        use tokio_serial::SerialPortBuilderExt;
+
+                        let path_clone = resolved_path.clone();
+                        let port = tokio::task::spawn_blocking(move || {
+                            tokio_serial::new(&path_clone, baud_rate)
+                                .data_bits(tokio_serial::DataBits::Eight)
+                                .parity(tokio_serial::Parity::None)
+                                .stop_bits(tokio_serial::StopBits::One)
+                                .flow_control(tokio_serial::FlowControl::None)
+                                .open_native_async()
+                                .context("Failed to open serial port")
+                        })
+                        .await
+                        .context("spawn_blocking failed")??;
+
+                        let boxed: crate::drivers::generic_serial::DynSerial = Box::new(port);
+                        let shared: SharedPort = Arc::new(Mutex::new(boxed));
+
+                        // Cache it
+                        {
+                            let mut cache = port_cache.lock().unwrap_or_else(|p| p.into_inner());
+                            cache.insert(resolved_path, shared.clone());
+                        }
+
+                        shared
                     }
-
-                    shared
                 }
             };
 
             // Create the driver
             let driver = GenericSerialDriver::new(device_config, shared_port, &instance.address)?;
+            driver.trace().set_enabled(instance.trace);
+            driver.trace().set_dry_run(instance.dry_run);
 
             // Run init sequence to validate device
             driver.run_init_sequence().await.with_context(|| {
@@ -679,6 +722,58 @@ impl DriverFactoryTrait for GenericSerialDriverFactory {
     }
 }
 
+/// Connect to a VXI-11 or HiSLIP instrument, shared like a serial port by
+/// every instance at the same address
+#[cfg(feature = "lan")]
+async fn open_lan_port(
+    transport: ConnectionType,
+    address: &str,
+    timeout: std::time::Duration,
+    port_cache: &std::sync::Mutex<std::collections::HashMap<String, SharedPort>>,
+) -> Result<SharedPort> {
+    let lan = transport
+        .lan_transport()
+        .ok_or_else(|| anyhow!("{:?} is not a LAN transport", transport))?;
+    let key = format!("{}://{}", lan.as_str(), address);
+    if let Some(port) = port_cache
+        .lock()
+        .unwrap_or_else(|p| p.into_inner())
+        .get(&key)
+    {
+        return Ok(port.clone());
+    }
+
+    let stream = common::lan::connect_stream(lan, address, timeout)
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to connect to {} instrument '{}'",
+                lan.as_str(),
+                address
+            )
+        })?;
+    let boxed: crate::drivers::generic_serial::DynSerial = Box::new(stream);
+    let shared: SharedPort = Arc::new(Mutex::new(boxed));
+    port_cache
+        .lock()
+        .unwrap_or_else(|p| p.into_inner())
+        .insert(key, shared.clone());
+    Ok(shared)
+}
+
+#[cfg(all(feature = "serial", not(feature = "lan")))]
+async fn open_lan_port(
+    transport: ConnectionType,
+    _address: &str,
+    _timeout: std::time::Duration,
+    _port_cache: &std::sync::Mutex<std::collections::HashMap<String, SharedPort>>,
+) -> Result<SharedPort> {
+    Err(anyhow!(
+        "{:?} transport requires the 'lan' feature",
+        transport
+    ))
+}
+
 /// Load all device configs from a directory and create factories.
 ///
 /// This is useful for loading all device definitions at startup.
@@ -852,4 +947,32 @@ type = "serial"
 
         assert_eq!(driver.name(), "Test ELL14");
     }
+
+    #[test]
+    fn test_lan_instance_config() {
+        let instance: GenericSerialInstanceConfig = toml::from_str(
+            r#"
+transport = "hislip"
+host = "192.168.1.20"
+"#,
+        )
+        .unwrap();
+        assert_eq!(instance.transport, Some(ConnectionType::Hislip));
+        assert_eq!(instance.port, "192.168.1.20");
+        assert!(instance.transport.unwrap().is_lan());
+
+        let config = load_device_config_from_str(
+            r#"
+[device]
+name = "LAN Scope"
+protocol = "scpi_scope"
+
+[connection]
+type = "vxi11"
+"#,
+        )
+        .unwrap();
+        assert!(config.connection.connection_type.is_lan());
+        assert!(!ConnectionType::Tcp.is_lan());
+    }
 }
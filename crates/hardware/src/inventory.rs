@@ -0,0 +1,291 @@
+//! Instrument inventory.
+//!
+//! Collects each device's identity (manufacturer, model, serial number and
+//! firmware version) and keeps it in a JSON file across daemon restarts.
+//! Drivers with a vendor SDK report identity through
+//! [`DeviceMetadata::identity`](common::driver::DeviceMetadata::identity);
+//! other devices with a raw command channel are asked `*IDN?`.
+//!
+//! Every refresh is compared with the stored inventory, so a firmware update
+//! (or a swapped unit with a different serial number) between two runs is
+//! flagged in the log and in the run manifest.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [inventory]
+//! path = "/var/lib/rust-daq/inventory.json"  # default: user data dir
+//! timeout_s = 2                                # per *IDN? query
+//! ```
+
+use anyhow::{bail, Context, Result};
+use common::driver::DeviceIdentity;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Current file format version
+const FORMAT_VERSION: u32 = 1;
+
+fn default_timeout_s() -> u64 {
+    2
+}
+
+/// Inventory settings (`[inventory]` in the hardware config)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InventoryConfig {
+    /// Inventory file (default: `<data dir>/rust-daq/inventory.json`)
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    /// Seconds allowed for each `*IDN?` query
+    #[serde(default = "default_timeout_s")]
+    pub timeout_s: u64,
+}
+
+impl Default for InventoryConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            timeout_s: default_timeout_s(),
+        }
+    }
+}
+
+impl InventoryConfig {
+    /// Check the settings
+    pub fn validate(&self) -> Result<()> {
+        if self.timeout_s == 0 {
+            bail!("Inventory timeout_s must be positive");
+        }
+        Ok(())
+    }
+
+    /// Inventory file location
+    pub fn path(&self) -> PathBuf {
+        self.path.clone().unwrap_or_else(default_inventory_path)
+    }
+
+    /// Per-query timeout
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_s)
+    }
+}
+
+/// Default location of the inventory file
+pub fn default_inventory_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rust-daq")
+        .join("inventory.json")
+}
+
+/// Where an identity came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentitySource {
+    /// Reported by the driver (vendor SDK)
+    Driver,
+    /// `*IDN?` over the raw command channel
+    Idn,
+}
+
+/// Identity of one device
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InventoryEntry {
+    pub device: String,
+    pub driver_type: String,
+    pub identity: DeviceIdentity,
+    pub source: IdentitySource,
+    /// When the identity was read (UNIX nanoseconds)
+    pub collected_ns: u64,
+}
+
+/// A device whose identity differs from the stored inventory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdentityChange {
+    pub device: String,
+    pub previous: DeviceIdentity,
+    pub current: DeviceIdentity,
+}
+
+impl IdentityChange {
+    /// Whether the firmware version changed
+    pub fn firmware_changed(&self) -> bool {
+        self.previous.firmware_version != self.current.firmware_version
+    }
+}
+
+impl std::fmt::Display for IdentityChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut changes = Vec::new();
+        for (field, previous, current) in [
+            ("model", &self.previous.model, &self.current.model),
+            (
+                "serial",
+                &self.previous.serial_number,
+                &self.current.serial_number,
+            ),
+            (
+                "firmware",
+                &self.previous.firmware_version,
+                &self.current.firmware_version,
+            ),
+        ] {
+            if previous != current {
+                changes.push(format!("{} {} -> {}", field, previous, current));
+            }
+        }
+        write!(f, "{}: {}", self.device, changes.join(", "))
+    }
+}
+
+/// Result of refreshing the inventory
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InventoryReport {
+    /// Devices whose identity was read, sorted by device ID
+    pub entries: Vec<InventoryEntry>,
+    /// Identity changes since the stored inventory
+    pub changes: Vec<IdentityChange>,
+}
+
+impl InventoryReport {
+    /// Identities keyed by device ID (for run manifests)
+    pub fn identities(&self) -> HashMap<String, DeviceIdentity> {
+        self.entries
+            .iter()
+            .map(|e| (e.device.clone(), e.identity.clone()))
+            .collect()
+    }
+}
+
+/// Stored inventory: the last known identity of every device seen
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Inventory {
+    #[serde(default)]
+    pub devices: BTreeMap<String, InventoryEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct InventoryFile {
+    version: u32,
+    #[serde(flatten)]
+    inventory: Inventory,
+}
+
+impl Inventory {
+    /// Load the inventory; a missing file means an empty inventory.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to read inventory {}", path.display()))
+            }
+        };
+        let file: InventoryFile = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse inventory {}", path.display()))?;
+        if file.version > FORMAT_VERSION {
+            bail!(
+                "Inventory {} has format version {} (supported: {})",
+                path.display(),
+                file.version,
+                FORMAT_VERSION
+            );
+        }
+        Ok(file.inventory)
+    }
+
+    /// Write the inventory, creating parent directories as needed
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let file = InventoryFile {
+            version: FORMAT_VERSION,
+            inventory: self.clone(),
+        };
+        let json = serde_json::to_string_pretty(&file)?;
+        // Write then rename so a crash never leaves a truncated file
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)
+            .with_context(|| format!("Failed to write inventory {}", tmp.display()))?;
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("Failed to replace inventory {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Record freshly collected entries, returning identity changes.
+    ///
+    /// Devices not in `entries` keep their stored identity, so a device that
+    /// is offline for one run is still compared on the next.
+    pub fn update(&mut self, entries: &[InventoryEntry]) -> Vec<IdentityChange> {
+        let mut changes = Vec::new();
+        for entry in entries {
+            if let Some(previous) = self.devices.get(&entry.device) {
+                if previous.identity != entry.identity {
+                    changes.push(IdentityChange {
+                        device: entry.device.clone(),
+                        previous: previous.identity.clone(),
+                        current: entry.identity.clone(),
+                    });
+                }
+            }
+            self.devices.insert(entry.device.clone(), entry.clone());
+        }
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(device: &str, firmware: &str) -> InventoryEntry {
+        InventoryEntry {
+            device: device.to_string(),
+            driver_type: "maitai".to_string(),
+            identity: DeviceIdentity {
+                manufacturer: "Spectra-Physics".to_string(),
+                model: "MaiTai".to_string(),
+                serial_number: "1234".to_string(),
+                firmware_version: firmware.to_string(),
+            },
+            source: IdentitySource::Idn,
+            collected_ns: 1,
+        }
+    }
+
+    #[test]
+    fn test_update_flags_firmware_changes() {
+        let mut inventory = Inventory::default();
+        assert!(inventory.update(&[entry("laser", "2.15")]).is_empty());
+        assert!(inventory.update(&[entry("laser", "2.15")]).is_empty());
+
+        let changes = inventory.update(&[entry("laser", "2.16")]);
+        assert_eq!(changes.len(), 1);
+        assert!(changes[0].firmware_changed());
+        assert_eq!(changes[0].to_string(), "laser: firmware 2.15 -> 2.16");
+        assert_eq!(inventory.devices["laser"].identity.firmware_version, "2.16");
+    }
+
+    #[test]
+    fn test_inventory_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("inventory.json");
+        assert_eq!(Inventory::load(&path).unwrap(), Inventory::default());
+
+        let mut inventory = Inventory::default();
+        inventory.update(&[entry("laser", "2.15"), entry("spare", "1.0")]);
+        inventory.save(&path).unwrap();
+        assert_eq!(Inventory::load(&path).unwrap(), inventory);
+
+        // A device missing from a refresh keeps its stored identity
+        let mut reloaded = Inventory::load(&path).unwrap();
+        assert!(reloaded.update(&[entry("laser", "2.15")]).is_empty());
+        assert!(reloaded.devices.contains_key("spare"));
+    }
+}
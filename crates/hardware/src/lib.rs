@@ -22,6 +22,7 @@
 //!     id: "rotator".into(),
 //!     name: "ELL14 Rotator".into(),
 //!     driver: DriverType::Ell14 { port: "/dev/ttyUSB0".into(), address: "2".into() },
+//!     simulated: false,
 //! }).await?;
 //!
 //! // Access by capability
@@ -49,12 +50,18 @@
 
 pub use common::capabilities;
 pub mod config;
+pub mod display_calibration;
 pub mod drivers;
 pub mod factory;
+pub mod inventory;
 pub mod plugin;
 pub mod port_resolver;
+pub mod recipes;
 pub mod registry;
 pub mod resource_pool;
+pub mod self_test;
+pub mod time_sync;
+pub mod warmup;
 
 pub use capabilities::*;
 pub use registry::{
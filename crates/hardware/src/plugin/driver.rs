@@ -16,8 +16,9 @@ use tokio::net::TcpStream;
 use tokio::sync::{Mutex, RwLock};
 use tokio_serial::SerialStream;
 
-use crate::plugin::schema::{CommandSequence, InstrumentConfig, ValueType};
-use common::driver::DeviceLifecycle;
+use crate::plugin::schema::{CommandSequence, DriverType, InstrumentConfig, ValueType};
+use crate::plugin::scpi::{parse_error_entry, ScpiError};
+use common::driver::{DeviceLifecycle, DeviceStatus, DeviceStatusHandle};
 use common::error::DaqError;
 use common::limits::{self, validate_frame_size};
 use common::observable::ParameterSet; // NEW: For Parameterized trait implementation
@@ -91,6 +92,74 @@ impl crate::capabilities::Parameterized for GenericDriver {
     }
 }
 
+// =============================================================================
+// Commandable Trait Implementation (control panel bindings)
+// =============================================================================
+
+/// Capability access by name, as bound by the plugin's `ui_layout`:
+/// `get` (`{"source": "power"}`), `set` (`{"target": "x", "value": 1.5}`;
+/// axes move, switchables take a bool) and the name of any actionable
+#[async_trait::async_trait]
+impl crate::capabilities::Commandable for GenericDriver {
+    async fn execute_command(&self, command: &str, args: Value) -> Result<Value> {
+        let capabilities = &self.config.capabilities;
+        let is_axis = |name: &str| {
+            capabilities
+                .movable
+                .iter()
+                .flat_map(|m| &m.axes)
+                .any(|axis| axis.name == name)
+        };
+        let is_switch = |name: &str| capabilities.switchable.iter().any(|s| s.name == name);
+        let name_arg = |key: &str| {
+            args.get(key)
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("'{}' needs a string '{}' argument", command, key))
+        };
+
+        match command {
+            "get" => {
+                let source = name_arg("source")?;
+                if capabilities.readable.iter().any(|r| r.name == source) {
+                    Ok(Value::from(self.read_named_f64(source, false).await?))
+                } else if is_axis(source) {
+                    Ok(Value::from(self.get_axis_position(source, false).await?))
+                } else if is_switch(source) {
+                    Ok(Value::from(self.is_named_on(source, false).await?))
+                } else {
+                    self.get_named_value(source, false).await
+                }
+            }
+            "set" => {
+                let target = name_arg("target")?;
+                let value = args
+                    .get("value")
+                    .cloned()
+                    .ok_or_else(|| anyhow!("'set' needs a 'value' argument"))?;
+                if is_axis(target) {
+                    let position = value.as_f64().ok_or_else(|| {
+                        anyhow!("Position for axis '{}' must be a number", target)
+                    })?;
+                    self.move_axis_abs(target, position, false).await?;
+                } else if is_switch(target) {
+                    match value.as_bool() {
+                        Some(true) => self.turn_on_named(target, false).await?,
+                        Some(false) => self.turn_off_named(target, false).await?,
+                        None => return Err(anyhow!("Switch '{}' takes true or false", target)),
+                    }
+                } else {
+                    self.set_named_value(target, value, false).await?;
+                }
+                Ok(Value::Null)
+            }
+            action => {
+                self.execute_named_action(action, false).await?;
+                Ok(Value::Null)
+            }
+        }
+    }
+}
+
 /// A generic instrument driver that interprets commands and responses based on a YAML configuration.
 ///
 /// This driver uses interior mutability (`Mutex`) for the connection, allowing
@@ -160,6 +229,12 @@ pub struct GenericDriver {
     /// Only ONE primary consumer is allowed - it owns frames and controls pool reclamation.
     primary_output:
         std::sync::Arc<RwLock<Option<tokio::sync::mpsc::Sender<crate::capabilities::LoanedFrame>>>>,
+
+    /// Broadcast channel for SCPI error-queue entries (see `subscribe_scpi_errors`).
+    scpi_errors: tokio::sync::broadcast::Sender<ScpiError>,
+
+    /// Structured device status, degraded while the instrument reports SCPI errors.
+    status: DeviceStatusHandle,
 }
 
 impl GenericDriver {
@@ -235,6 +310,7 @@ impl GenericDriver {
 
         // Create broadcast channel with capacity for 100 frames
         let (frame_tx, _) = tokio::sync::broadcast::channel(100);
+        let (scpi_error_tx, _) = tokio::sync::broadcast::channel(64);
 
         Ok(Self {
             config,
@@ -250,12 +326,16 @@ impl GenericDriver {
             observers: std::sync::Arc::new(RwLock::new(Vec::new())),
             next_observer_id: std::sync::Arc::new(std::sync::atomic::AtomicU64::new(1)),
             primary_output: std::sync::Arc::new(RwLock::new(None)),
+            scpi_errors: scpi_error_tx,
+            status: DeviceStatusHandle::default(),
         })
     }
 
     /// Sends a command to the instrument and reads its response.
     ///
     /// Uses interior mutability via Mutex to allow `&self` signature.
+    /// For SCPI plugins with `protocol.error_queue` set, the error queue is
+    /// drained afterwards and the first entry is returned as a driver error.
     async fn execute_command(&self, command: &str) -> Result<String> {
         let cmd_bytes = command.as_bytes();
         let timeout_duration = Duration::from_millis(self.config.protocol.timeout_ms);
@@ -283,9 +363,49 @@ impl GenericDriver {
         }
 
         // Read response
+        let deadline = tokio::time::Instant::now() + timeout_duration;
+        let response_bytes = self.read_response(&mut conn, deadline).await?;
+
+        // Drain the error queue while still holding the connection, so the
+        // entries belong to this command
+        let scpi_errors = match self.error_queue_query(command) {
+            Some(query) => self.drain_error_queue(&mut conn, command, query).await?,
+            None => Vec::new(),
+        };
+
+        // Connection lock is released here
+        drop(conn);
+
+        let response_str = String::from_utf8(response_bytes)?;
+        tracing::debug!("Received response: {:?}", response_str);
+
+        if let Some(first) = scpi_errors.first() {
+            let error = DaqError::Driver(first.to_driver_error());
+            self.report_scpi_errors(scpi_errors);
+            return Err(error.into());
+        }
+        if self.error_queue_query(command).is_some() {
+            self.clear_scpi_degraded();
+        }
+
+        // Check for error patterns
+        for pattern in &self.error_patterns {
+            if pattern.is_match(&response_str) {
+                return Err(anyhow!("Instrument reported error: {}", response_str));
+            }
+        }
+
+        Ok(response_str)
+    }
+
+    /// Reads one terminated response (or until EOF).
+    async fn read_response(
+        &self,
+        conn: &mut Connection,
+        deadline: tokio::time::Instant,
+    ) -> Result<Vec<u8>> {
         let mut response_bytes = Vec::new();
         let mut buf = [0u8; 128];
-        let deadline = tokio::time::Instant::now() + timeout_duration;
 
         loop {
             let n = tokio::time::timeout_at(deadline, conn.read(&mut buf)).await??;
@@ -308,19 +428,100 @@ impl GenericDriver {
             }
         }
 
-        // Connection lock is released here when `conn` goes out of scope
+        Ok(response_bytes)
+    }
 
-        let response_str = String::from_utf8(response_bytes)?;
-        tracing::debug!("Received response: {:?}", response_str);
+    /// Error-queue query to send after `command`, if draining applies.
+    ///
+    /// Only SCPI driver types drain, and never after the query itself.
+    fn error_queue_query(&self, command: &str) -> Option<&str> {
+        if !matches!(
+            self.config.metadata.driver_type,
+            DriverType::SerialScpi | DriverType::TcpScpi
+        ) {
+            return None;
+        }
+        let queue = self.config.protocol.error_queue.as_ref()?;
+        if !queue.enabled || command.trim().eq_ignore_ascii_case(&queue.query) {
+            return None;
+        }
+        Some(queue.query.as_str())
+    }
 
-        // Check for error patterns
-        for pattern in &self.error_patterns {
-            if pattern.is_match(&response_str) {
-                return Err(anyhow!("Instrument reported error: {}", response_str));
+    /// Reads error-queue entries until the instrument reports no error.
+    async fn drain_error_queue(
+        &self,
+        conn: &mut Connection,
+        command: &str,
+        query: &str,
+    ) -> Result<Vec<ScpiError>> {
+        let timeout_duration = Duration::from_millis(self.config.protocol.timeout_ms);
+        let max_drain = self
+            .config
+            .protocol
+            .error_queue
+            .as_ref()
+            .map_or(0, |queue| queue.max_drain);
+
+        let mut query_bytes = query.as_bytes().to_vec();
+        query_bytes.extend_from_slice(&self.termination_bytes);
+
+        let mut errors = Vec::new();
+        for _ in 0..max_drain {
+            tokio::time::timeout(timeout_duration, conn.write_all(&query_bytes)).await??;
+            let deadline = tokio::time::Instant::now() + timeout_duration;
+            let response = String::from_utf8(self.read_response(conn, deadline).await?)?;
+
+            match parse_error_entry(&response) {
+                Some((0, _)) => return Ok(errors),
+                Some((code, message)) => errors.push(ScpiError {
+                    device: self.config.metadata.id.clone(),
+                    command: command.to_string(),
+                    code,
+                    message,
+                }),
+                None => {
+                    tracing::warn!(
+                        device = %self.config.metadata.id,
+                        "Unexpected error-queue response {:?}",
+                        response
+                    );
+                    return Ok(errors);
+                }
             }
         }
 
-        Ok(response_str)
+        tracing::warn!(
+            device = %self.config.metadata.id,
+            "Error queue not empty after {} reads",
+            max_drain
+        );
+        Ok(errors)
+    }
+
+    /// Logs, publishes and records SCPI errors against the device status.
+    fn report_scpi_errors(&self, errors: Vec<ScpiError>) {
+        let reason = errors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ");
+        for error in errors {
+            tracing::warn!(device = %error.device, code = error.code, "{}", error);
+            // No subscribers is fine
+            let _ = self.scpi_errors.send(error);
+        }
+        self.status.set(DeviceStatus::Degraded { reason });
+    }
+
+    /// Restores `Ready` once a command completes with an empty error queue,
+    /// if the device was degraded by SCPI errors.
+    fn clear_scpi_degraded(&self) {
+        if let DeviceStatus::Degraded { reason } = self.status.get() {
+            if reason.starts_with("SCPI ") {
+                self.status.set(DeviceStatus::Ready);
+            }
+        }
     }
 
     /// Executes a sequence of commands, typically for on_connect or on_disconnect.
@@ -1618,6 +1819,18 @@ impl GenericDriver {
         self.on_connect_executed
             .load(std::sync::atomic::Ordering::SeqCst)
     }
+
+    /// Subscribes to errors drained from the instrument's SCPI error queue.
+    ///
+    /// Each entry is published as it is read, after the command that caused it.
+    pub fn subscribe_scpi_errors(&self) -> tokio::sync::broadcast::Receiver<ScpiError> {
+        self.scpi_errors.subscribe()
+    }
+
+    /// Returns the device status handle shared with the registry.
+    pub fn status(&self) -> DeviceStatusHandle {
+        self.status.clone()
+    }
 }
 
 // =============================================================================
@@ -2117,4 +2330,61 @@ mod tests {
         let result = driver.on_unregister().await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_scpi_error_queue_drained_after_command() {
+        use tokio::io::{AsyncBufReadExt, BufReader};
+
+        // Instrument that rejects the first command and accepts the second
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            let mut queue: Vec<&str> = Vec::new();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let reply = match line.as_str() {
+                    "SYST:ERR?" => queue.pop().unwrap_or("+0,\"No error\""),
+                    "VOLT 1000" => {
+                        queue.push("-222,\"Data out of range\"");
+                        "OK"
+                    }
+                    _ => "OK",
+                };
+                writer
+                    .write_all(format!("{}\n", reply).as_bytes())
+                    .await
+                    .unwrap();
+            }
+        });
+
+        let mut config = create_test_config();
+        config.metadata.driver_type = DriverType::TcpScpi;
+        config.protocol.termination = "\n".to_string();
+        config.protocol.error_queue = Some(ErrorQueueConfig::default());
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let driver = GenericDriver::new_tcp(config, stream).unwrap();
+        let mut events = driver.subscribe_scpi_errors();
+
+        let err = driver.execute_command("VOLT 1000").await.unwrap_err();
+        match err.downcast_ref::<DaqError>() {
+            Some(DaqError::Driver(e)) => {
+                assert_eq!(e.kind, common::error::DriverErrorKind::InvalidParameter);
+                assert!(e.message.contains("-222"));
+            }
+            other => panic!("expected driver error, got {:?}", other),
+        }
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.code, -222);
+        assert_eq!(event.command, "VOLT 1000");
+        assert!(matches!(
+            driver.status().get(),
+            DeviceStatus::Degraded { .. }
+        ));
+
+        // A clean command clears the degraded status
+        assert_eq!(driver.execute_command("VOLT 10").await.unwrap(), "OK\n");
+        assert_eq!(driver.status().get(), DeviceStatus::Ready);
+    }
 }
@@ -10,6 +10,7 @@
 //! - `driver` - Generic driver that interprets YAML configs
 //! - `registry` - Plugin factory for loading and spawning drivers
 //! - `handles` - Capability handle types implementing standard traits
+//! - `scpi` - SCPI error-queue entries and their driver error mapping
 //!
 //! ## Native Plugin Discovery
 //! - `manifest` - Plugin.toml manifest types for native/script/WASM plugins
@@ -64,3 +65,4 @@ pub mod lib_reload;
 #[cfg(feature = "serial")]
 pub mod registry;
 pub mod schema;
+pub mod scpi;
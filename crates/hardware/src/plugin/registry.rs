@@ -1000,4 +1000,53 @@ mod tests {
         };
         assert_eq!(entry.priority, 0);
     }
+
+    #[test]
+    fn test_resolved_ui_layout_inherits_capability_ranges() {
+        let mut config = create_minimal_valid_config();
+        config.capabilities.movable = Some(MovableCapability {
+            axes: vec![AxisConfig {
+                name: "x".to_string(),
+                unit: Some("mm".to_string()),
+                min: Some(0.0),
+                max: Some(25.0),
+            }],
+            set_cmd: "MOVE {position}".to_string(),
+            get_cmd: "POS?".to_string(),
+            get_pattern: "{val}".to_string(),
+        });
+        config.ui_layout = serde_yaml::from_str(
+            r"
+- type: group
+  label: Stage
+  children:
+    - type: slider
+      target: x
+    - type: slider
+      target: x
+      max: 10.0
+    - type: gauge
+      source: power
+      min: 0.0
+      max: 1.0
+",
+        )
+        .unwrap();
+
+        let layout = config.resolved_ui_layout();
+        let UiElement::Group(group) = &layout[0] else {
+            panic!("expected group, got {:?}", layout[0]);
+        };
+        let UiElement::Slider(inherited) = &group.children[0] else {
+            panic!("expected slider");
+        };
+        assert_eq!(inherited.min, Some(0.0));
+        assert_eq!(inherited.max, Some(25.0));
+        assert_eq!(inherited.unit.as_deref(), Some("mm"));
+        let UiElement::Slider(overridden) = &group.children[1] else {
+            panic!("expected slider");
+        };
+        assert_eq!(overridden.max, Some(10.0));
+        assert!(matches!(&group.children[2], UiElement::Gauge(g) if g.unit.is_none()));
+    }
 }
@@ -75,6 +75,9 @@ pub struct ProtocolConfig {
     /// TCP port number (required for tcp_scpi/tcp_raw)
     #[serde(default)]
     pub tcp_port: Option<u16>,
+    /// Drain the SCPI error queue after each command (serial_scpi/tcp_scpi only)
+    #[serde(default)]
+    pub error_queue: Option<ErrorQueueConfig>,
 }
 
 impl Default for ProtocolConfig {
@@ -86,10 +89,56 @@ impl Default for ProtocolConfig {
             timeout_ms: default_timeout_ms(),
             tcp_host: None,
             tcp_port: None,
+            error_queue: None,
         }
     }
 }
 
+/// SCPI error-queue draining.
+///
+/// After every command the driver sends `query` and reads entries until the
+/// instrument answers `0,"No error"`. Any other entry fails the command with
+/// a structured driver error, so a rejected command is reported where it
+/// happened instead of as a wrong reading later.
+///
+/// ```yaml
+/// protocol:
+///   error_queue:
+///     query: "SYST:ERR?"
+///     max_drain: 10
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorQueueConfig {
+    #[serde(default = "default_error_queue_enabled")]
+    pub enabled: bool,
+    /// Error-queue query
+    #[serde(default = "default_error_queue_query")]
+    pub query: String,
+    /// Most entries to read after one command
+    #[serde(default = "default_error_queue_max_drain")]
+    pub max_drain: usize,
+}
+
+impl Default for ErrorQueueConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_error_queue_enabled(),
+            query: default_error_queue_query(),
+            max_drain: default_error_queue_max_drain(),
+        }
+    }
+}
+
+fn default_error_queue_enabled() -> bool {
+    true
+}
+fn default_error_queue_query() -> String {
+    "SYST:ERR?".to_string()
+}
+fn default_error_queue_max_drain() -> usize {
+    10
+}
+
 fn default_baud_rate() -> u32 {
     9600
 }
@@ -410,6 +459,8 @@ pub enum UiElement {
     Button(UIButton),
     #[serde(rename = "dropdown")]
     Dropdown(UIDropdown),
+    #[serde(rename = "gauge")]
+    Gauge(UIGauge),
     // Add more UI elements as needed
 }
 
@@ -428,6 +479,12 @@ pub struct UISlider {
     // Min/Max/Unit can be inherited from capability, or optionally overridden here
     #[serde(default)]
     pub label: Option<String>,
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+    #[serde(default)]
+    pub unit: Option<String>,
 }
 
 /// UI Readout element (for readable).
@@ -459,4 +516,91 @@ pub struct UIDropdown {
     pub target: String, // Links to a settable enum capability
     #[serde(default)]
     pub label: Option<String>,
+    // Inherited from the settable's `options` when empty
+    #[serde(default)]
+    pub options: Vec<String>,
+}
+
+/// UI Gauge element (a readable shown as a bar between min and max).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UIGauge {
+    pub source: String, // Links to a readable capability name
+    #[serde(default)]
+    pub label: Option<String>,
+    pub min: f64,
+    pub max: f64,
+    // Inherited from the readable when unset
+    #[serde(default)]
+    pub unit: Option<String>,
+}
+
+impl InstrumentConfig {
+    /// `ui_layout` with ranges, units and options filled in from the
+    /// capabilities each element is bound to
+    ///
+    /// Values given in the layout win over the capability's.
+    pub fn resolved_ui_layout(&self) -> Vec<UiElement> {
+        self.ui_layout
+            .iter()
+            .map(|element| self.resolve_ui_element(element))
+            .collect()
+    }
+
+    fn resolve_ui_element(&self, element: &UiElement) -> UiElement {
+        let caps = &self.capabilities;
+        match element {
+            UiElement::Group(group) => UiElement::Group(UIGroup {
+                label: group.label.clone(),
+                children: group
+                    .children
+                    .iter()
+                    .map(|child| self.resolve_ui_element(child))
+                    .collect(),
+            }),
+            UiElement::Slider(slider) => {
+                let axis = caps
+                    .movable
+                    .iter()
+                    .flat_map(|m| &m.axes)
+                    .find(|a| a.name == slider.target);
+                let settable = caps.settable.iter().find(|s| s.name == slider.target);
+                let (min, max, unit) = match (axis, settable) {
+                    (Some(a), _) => (a.min, a.max, a.unit.clone()),
+                    (None, Some(s)) => (s.min, s.max, s.unit.clone()),
+                    (None, None) => (None, None, None),
+                };
+                UiElement::Slider(UISlider {
+                    target: slider.target.clone(),
+                    label: slider.label.clone(),
+                    min: slider.min.or(min),
+                    max: slider.max.or(max),
+                    unit: slider.unit.clone().or(unit),
+                })
+            }
+            UiElement::Dropdown(dropdown) if dropdown.options.is_empty() => {
+                let options = caps
+                    .settable
+                    .iter()
+                    .find(|s| s.name == dropdown.target)
+                    .map(|s| s.options.clone())
+                    .unwrap_or_default();
+                UiElement::Dropdown(UIDropdown {
+                    options,
+                    ..dropdown.clone()
+                })
+            }
+            UiElement::Gauge(gauge) if gauge.unit.is_none() => {
+                let unit = caps
+                    .readable
+                    .iter()
+                    .find(|r| r.name == gauge.source)
+                    .and_then(|r| r.unit.clone());
+                UiElement::Gauge(UIGauge {
+                    unit,
+                    ..gauge.clone()
+                })
+            }
+            other => other.clone(),
+        }
+    }
 }
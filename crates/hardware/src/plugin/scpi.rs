@@ -0,0 +1,132 @@
+//! SCPI error-queue entries.
+//!
+//! Instruments answer `SYST:ERR?` with `<code>,"<message>"`, where code 0
+//! means the queue is empty. Negative codes are the standard SCPI classes
+//! (IEEE 488.2 / SCPI-99 §21.8); positive codes are instrument-specific.
+//! [`ScpiError`] maps both onto [`DriverErrorKind`] so they reach clients as
+//! ordinary driver errors.
+
+use common::error::{DriverError, DriverErrorKind};
+use serde::{Deserialize, Serialize};
+
+/// One entry read from an instrument's error queue
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScpiError {
+    /// Device that reported the error (plugin id)
+    pub device: String,
+    /// Command that was sent before the entry was read
+    pub command: String,
+    pub code: i32,
+    pub message: String,
+}
+
+impl ScpiError {
+    /// Standard SCPI class for the code
+    pub fn class(&self) -> &'static str {
+        match self.code {
+            -199..=-100 => "command error",
+            -299..=-200 => "execution error",
+            -399..=-300 => "device-specific error",
+            -499..=-400 => "query error",
+            -599..=-500 => "power-on event",
+            -699..=-600 => "user request event",
+            -799..=-700 => "request control event",
+            -899..=-800 => "operation complete event",
+            _ => "instrument error",
+        }
+    }
+
+    /// Driver error kind for the code
+    pub fn kind(&self) -> DriverErrorKind {
+        match self.code {
+            // Timeout errors within the execution and device-specific classes
+            -214 | -365 => DriverErrorKind::Timeout,
+            // Header/syntax errors and parameter/data-out-of-range errors
+            -199..=-100 | -229..=-220 => DriverErrorKind::InvalidParameter,
+            -399..=-200 => DriverErrorKind::Hardware,
+            -499..=-400 => DriverErrorKind::Communication,
+            c if c > 0 => DriverErrorKind::Hardware,
+            _ => DriverErrorKind::Unknown,
+        }
+    }
+
+    /// Convert to a structured driver error
+    pub fn to_driver_error(&self) -> DriverError {
+        DriverError::new(self.device.clone(), self.kind(), self.to_string())
+    }
+}
+
+impl std::fmt::Display for ScpiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SCPI {} {} after '{}': {}",
+            self.class(),
+            self.code,
+            self.command,
+            self.message
+        )
+    }
+}
+
+/// Parse an error-queue response into `(code, message)`.
+///
+/// Accepts `-113,"Undefined header"`, `+0,"No error"` and bare codes.
+/// Returns `None` for responses that don't start with a code.
+pub fn parse_error_entry(response: &str) -> Option<(i32, String)> {
+    let response = response.trim();
+    let (code, message) = match response.split_once(',') {
+        Some((code, message)) => (code, message.trim().trim_matches('"')),
+        None => (response, ""),
+    };
+    let code = code.trim().trim_start_matches('+').parse().ok()?;
+    Some((code, message.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(code: i32) -> ScpiError {
+        ScpiError {
+            device: "dmm".to_string(),
+            command: "CONF:VOLT 1000".to_string(),
+            code,
+            message: "Data out of range".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_error_entry() {
+        assert_eq!(
+            parse_error_entry("+0,\"No error\"\r\n"),
+            Some((0, "No error".to_string()))
+        );
+        assert_eq!(
+            parse_error_entry("-113,\"Undefined header\""),
+            Some((-113, "Undefined header".to_string()))
+        );
+        assert_eq!(
+            parse_error_entry("-222,\"Data out of range;CONF:VOLT 1000\""),
+            Some((-222, "Data out of range;CONF:VOLT 1000".to_string()))
+        );
+        assert_eq!(parse_error_entry("0"), Some((0, String::new())));
+        assert_eq!(parse_error_entry("OK"), None);
+    }
+
+    #[test]
+    fn test_codes_map_to_driver_error_kinds() {
+        assert_eq!(entry(-113).kind(), DriverErrorKind::InvalidParameter);
+        assert_eq!(entry(-222).kind(), DriverErrorKind::InvalidParameter);
+        assert_eq!(entry(-240).kind(), DriverErrorKind::Hardware);
+        assert_eq!(entry(-214).kind(), DriverErrorKind::Timeout);
+        assert_eq!(entry(-410).kind(), DriverErrorKind::Communication);
+        assert_eq!(entry(501).kind(), DriverErrorKind::Hardware);
+
+        let error = entry(-222).to_driver_error();
+        assert_eq!(error.driver_type, "dmm");
+        assert!(error
+            .message
+            .contains("execution error -222 after 'CONF:VOLT 1000'"));
+    }
+}
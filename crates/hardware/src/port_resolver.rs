@@ -18,8 +18,23 @@
 //! // Hardware spec (auto-resolved)
 //! let spec = PortSpec::new().vendor("FTDI").model("FT230X");
 //! let port = spec.resolve()?;
+//!
+//! // USB identifiers (any platform, needs the `serial` feature)
+//! let port = resolve_port("serial:DJ00XXXX")?;
+//! let port = resolve_port("usb:0403:6015:DJ00XXXX")?;
 //! ```
 //!
+//! # Hardware ID Specs
+//!
+//! Device configs can name a port by its USB identity instead of its path:
+//!
+//! - `serial:<SERIAL>` - the port whose USB serial number is `SERIAL`
+//! - `usb:<VID>:<PID>` - the only port with this vendor/product ID (hex)
+//! - `usb:<VID>:<PID>:<SERIAL>` - both
+//!
+//! These are matched against [`enumerate_ports`], so they survive
+//! `/dev/ttyUSB*` renumbering and also work on macOS and Windows.
+//!
 //! # Linux `/dev/serial/by-id/` Format
 //!
 //! Linux udev creates stable symlinks in `/dev/serial/by-id/` with the format:
@@ -31,6 +46,7 @@
 //! - `usb-FTDI_FT230X_Basic_UART_DJ00XXXX-if00-port0` (FTDI chip)
 //! - `usb-Silicon_Labs_CP2102_USB_to_UART_Bridge_0001-if00-port0` (CP2102)
 
+use std::fmt;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
@@ -57,6 +73,18 @@ pub enum PortResolveError {
     #[error("Serial by-id directory not available: {0}")]
     ByIdNotAvailable(String),
 
+    /// A `serial:`/`usb:` spec is malformed.
+    #[error("Invalid port spec '{0}': {1}")]
+    InvalidSpec(String, String),
+
+    /// No enumerated port matches a `serial:`/`usb:` spec.
+    #[error("No serial port matches '{0}'")]
+    NoPortMatch(String),
+
+    /// The operating system's port list could not be read.
+    #[error("Serial port enumeration failed: {0}")]
+    Enumeration(String),
+
     /// IO error during port resolution.
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
@@ -190,11 +218,17 @@ impl PortSpec {
 ///   - A direct device path (e.g., `/dev/ttyUSB0`)
 ///   - A by-id symlink path (e.g., `/dev/serial/by-id/usb-FTDI_...`)
 ///   - A short by-id name (e.g., `usb-FTDI_FT230X_DJ00XXXX-if00-port0`)
+///   - A hardware ID spec (e.g., `serial:DJ00XXXX`, see [`PortSelector`])
 ///
 /// # Returns
 ///
 /// The resolved port path that can be passed to serial port libraries.
 pub fn resolve_port(port_or_spec: &str) -> Result<String, PortResolveError> {
+    // Hardware ID specs are matched against the enumerated ports
+    if let Some(selector) = PortSelector::parse(port_or_spec)? {
+        return selector.resolve();
+    }
+
     // If it's already a full path that exists, use it
     if port_or_spec.starts_with("/dev/") {
         let path = Path::new(port_or_spec);
@@ -288,6 +322,267 @@ pub struct PortInfo {
     pub serial: Option<String>,
 }
 
+/// Whether a port string is a `serial:`/`usb:` hardware ID spec rather than a path.
+pub fn is_hardware_spec(port: &str) -> bool {
+    port.starts_with("serial:") || port.starts_with("usb:")
+}
+
+/// USB identity a port is selected by, parsed from a hardware ID spec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortSelector {
+    /// `serial:<SERIAL>`
+    SerialNumber(String),
+    /// `usb:<VID>:<PID>[:<SERIAL>]`
+    Usb {
+        vid: u16,
+        pid: u16,
+        serial: Option<String>,
+    },
+}
+
+impl PortSelector {
+    /// Parse a hardware ID spec; `Ok(None)` for plain paths and by-id names.
+    pub fn parse(spec: &str) -> Result<Option<Self>, PortResolveError> {
+        let invalid = |reason: &str| PortResolveError::InvalidSpec(spec.to_string(), reason.into());
+
+        if let Some(serial) = spec.strip_prefix("serial:") {
+            if serial.is_empty() {
+                return Err(invalid("missing serial number"));
+            }
+            return Ok(Some(Self::SerialNumber(serial.to_string())));
+        }
+
+        let Some(ids) = spec.strip_prefix("usb:") else {
+            return Ok(None);
+        };
+        let mut parts = ids.splitn(3, ':');
+        let mut hex_id = |name: &str| {
+            let text = parts.next().unwrap_or_default();
+            u16::from_str_radix(text.trim_start_matches("0x"), 16)
+                .map_err(|_| invalid(&format!("{} must be a hex USB ID, got '{}'", name, text)))
+        };
+        let vid = hex_id("vendor ID")?;
+        let pid = hex_id("product ID")?;
+        let serial = match parts.next() {
+            Some("") => return Err(invalid("empty serial number")),
+            serial => serial.map(str::to_string),
+        };
+        Ok(Some(Self::Usb { vid, pid, serial }))
+    }
+
+    /// Whether an enumerated port has this identity.
+    ///
+    /// Serial numbers compare case-insensitively; some adapters report them
+    /// in a different case on Windows.
+    pub fn matches(&self, port: &PortMetadata) -> bool {
+        let serial_matches = |serial: &str| {
+            port.serial_number
+                .as_deref()
+                .is_some_and(|s| s.eq_ignore_ascii_case(serial))
+        };
+        match self {
+            Self::SerialNumber(serial) => serial_matches(serial),
+            Self::Usb { vid, pid, serial } => {
+                port.vid == Some(*vid)
+                    && port.pid == Some(*pid)
+                    && serial.as_deref().is_none_or(serial_matches)
+            }
+        }
+    }
+
+    /// The single port matching this identity.
+    pub fn select<'a>(
+        &self,
+        ports: &'a [PortMetadata],
+    ) -> Result<&'a PortMetadata, PortResolveError> {
+        let mut matches = ports.iter().filter(|port| self.matches(port));
+        match (matches.next(), matches.next()) {
+            (None, _) => Err(PortResolveError::NoPortMatch(self.to_string())),
+            (Some(port), None) => Ok(port),
+            (Some(first), Some(second)) => Err(PortResolveError::AmbiguousMatch(
+                [first, second]
+                    .into_iter()
+                    .chain(matches)
+                    .map(|port| port.path.clone())
+                    .collect(),
+            )),
+        }
+    }
+
+    /// Resolve to the device path of the single matching port.
+    pub fn resolve(&self) -> Result<String, PortResolveError> {
+        let ports = enumerate_ports()?;
+        self.select(&ports).map(|port| port.path.clone())
+    }
+}
+
+impl fmt::Display for PortSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SerialNumber(serial) => write!(f, "serial:{}", serial),
+            Self::Usb {
+                vid,
+                pid,
+                serial: None,
+            } => write!(f, "usb:{:04x}:{:04x}", vid, pid),
+            Self::Usb {
+                vid,
+                pid,
+                serial: Some(serial),
+            } => write!(f, "usb:{:04x}:{:04x}:{}", vid, pid, serial),
+        }
+    }
+}
+
+/// Kind of serial port, as reported by the operating system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortKind {
+    Usb,
+    Pci,
+    Bluetooth,
+    Unknown,
+}
+
+impl PortKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Usb => "usb",
+            Self::Pci => "pci",
+            Self::Bluetooth => "bluetooth",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// A serial port with its hardware identifiers, from [`enumerate_ports`].
+#[derive(Debug, Clone)]
+pub struct PortMetadata {
+    /// Device path (`/dev/ttyUSB0`, `/dev/cu.usbserial-…`, `COM3`)
+    pub path: String,
+    pub kind: PortKind,
+    /// USB vendor ID
+    pub vid: Option<u16>,
+    /// USB product ID
+    pub pid: Option<u16>,
+    /// USB serial number
+    pub serial_number: Option<String>,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    /// Kernel driver bound to the port (Linux only, e.g. `ftdi_sio`)
+    pub driver: Option<String>,
+    /// Stable `/dev/serial/by-id/` symlink pointing at the port (Linux only)
+    pub by_id_path: Option<String>,
+}
+
+impl PortMetadata {
+    /// Spec that finds this port again after renumbering.
+    ///
+    /// Prefers the serial number, then VID:PID, then the by-id symlink;
+    /// `None` if the port has no stable identity (e.g. a built-in UART).
+    pub fn stable_spec(&self) -> Option<String> {
+        if let Some(serial) = self.serial_number.as_deref().filter(|s| !s.is_empty()) {
+            return Some(PortSelector::SerialNumber(serial.to_string()).to_string());
+        }
+        if let (Some(vid), Some(pid)) = (self.vid, self.pid) {
+            return Some(
+                PortSelector::Usb {
+                    vid,
+                    pid,
+                    serial: None,
+                }
+                .to_string(),
+            );
+        }
+        self.by_id_path.clone()
+    }
+
+    #[cfg(feature = "serial")]
+    fn from_serialport(
+        port: serialport::SerialPortInfo,
+        by_id: &std::collections::HashMap<String, String>,
+    ) -> Self {
+        let mut metadata = Self {
+            driver: port_driver(&port.port_name),
+            by_id_path: by_id.get(&port.port_name).cloned(),
+            path: port.port_name,
+            kind: PortKind::Unknown,
+            vid: None,
+            pid: None,
+            serial_number: None,
+            manufacturer: None,
+            product: None,
+        };
+        match port.port_type {
+            serialport::SerialPortType::UsbPort(usb) => {
+                metadata.kind = PortKind::Usb;
+                metadata.vid = Some(usb.vid);
+                metadata.pid = Some(usb.pid);
+                metadata.serial_number = usb.serial_number;
+                metadata.manufacturer = usb.manufacturer;
+                metadata.product = usb.product;
+            }
+            serialport::SerialPortType::PciPort => metadata.kind = PortKind::Pci,
+            serialport::SerialPortType::BluetoothPort => metadata.kind = PortKind::Bluetooth,
+            serialport::SerialPortType::Unknown => {}
+        }
+        metadata
+    }
+}
+
+/// Enumerate serial ports with USB VID/PID, serial number and driver.
+///
+/// Works on Linux, macOS and Windows. Without the `serial` feature there is
+/// no enumeration backend and the list is empty.
+#[cfg(feature = "serial")]
+pub fn enumerate_ports() -> Result<Vec<PortMetadata>, PortResolveError> {
+    let by_id = by_id_links();
+    let mut ports: Vec<PortMetadata> = serialport::available_ports()
+        .map_err(|e| PortResolveError::Enumeration(e.to_string()))?
+        .into_iter()
+        .map(|port| PortMetadata::from_serialport(port, &by_id))
+        .collect();
+    ports.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(ports)
+}
+
+/// Enumerate serial ports (empty without the `serial` feature).
+#[cfg(not(feature = "serial"))]
+pub fn enumerate_ports() -> Result<Vec<PortMetadata>, PortResolveError> {
+    Ok(Vec::new())
+}
+
+/// Map of device path to its `/dev/serial/by-id/` symlink (empty if absent).
+#[cfg(feature = "serial")]
+fn by_id_links() -> std::collections::HashMap<String, String> {
+    let Ok(entries) = std::fs::read_dir("/dev/serial/by-id") else {
+        return std::collections::HashMap::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let target = std::fs::canonicalize(entry.path()).ok()?;
+            Some((
+                target.to_string_lossy().into_owned(),
+                entry.path().to_string_lossy().into_owned(),
+            ))
+        })
+        .collect()
+}
+
+/// Kernel driver bound to a tty, from sysfs.
+#[cfg(all(feature = "serial", target_os = "linux"))]
+fn port_driver(path: &str) -> Option<String> {
+    let name = Path::new(path).file_name()?;
+    let link =
+        std::fs::read_link(Path::new("/sys/class/tty").join(name).join("device/driver")).ok()?;
+    Some(link.file_name()?.to_string_lossy().into_owned())
+}
+
+#[cfg(all(feature = "serial", not(target_os = "linux")))]
+fn port_driver(_path: &str) -> Option<String> {
+    None
+}
+
 /// Parse vendor, model, and serial from a by-id name.
 ///
 /// Format: `usb-{VENDOR}_{MODEL}_{SERIAL}-if{N}-port{N}`
@@ -349,6 +644,94 @@ mod tests {
         assert_eq!(serial, Some("0001".to_string()));
     }
 
+    fn usb_port(path: &str, vid: u16, pid: u16, serial: Option<&str>) -> PortMetadata {
+        PortMetadata {
+            path: path.to_string(),
+            kind: PortKind::Usb,
+            vid: Some(vid),
+            pid: Some(pid),
+            serial_number: serial.map(str::to_string),
+            manufacturer: None,
+            product: None,
+            driver: None,
+            by_id_path: None,
+        }
+    }
+
+    #[test]
+    fn test_port_selector_parse() {
+        assert_eq!(PortSelector::parse("/dev/ttyUSB0").unwrap(), None);
+        assert_eq!(
+            PortSelector::parse("usb-FTDI_FT230X-if00-port0").unwrap(),
+            None
+        );
+        assert_eq!(
+            PortSelector::parse("serial:DJ00XXXX").unwrap(),
+            Some(PortSelector::SerialNumber("DJ00XXXX".to_string()))
+        );
+        assert_eq!(
+            PortSelector::parse("usb:0403:6015").unwrap(),
+            Some(PortSelector::Usb {
+                vid: 0x0403,
+                pid: 0x6015,
+                serial: None
+            })
+        );
+        let selector = PortSelector::parse("usb:0x0403:6015:DJ00XXXX")
+            .unwrap()
+            .unwrap();
+        assert_eq!(selector.to_string(), "usb:0403:6015:DJ00XXXX");
+
+        assert!(PortSelector::parse("serial:").is_err());
+        assert!(PortSelector::parse("usb:0403").is_err());
+        assert!(PortSelector::parse("usb:xyz:6015").is_err());
+        assert!(PortSelector::parse("usb:0403:6015:").is_err());
+    }
+
+    #[test]
+    fn test_port_selector_select() {
+        let ports = [
+            usb_port("/dev/ttyUSB0", 0x0403, 0x6015, Some("DJ00AAAA")),
+            usb_port("/dev/ttyUSB1", 0x0403, 0x6015, Some("DJ00BBBB")),
+            usb_port("/dev/ttyUSB2", 0x10c4, 0xea60, None),
+        ];
+
+        let by_serial = PortSelector::SerialNumber("dj00bbbb".to_string());
+        assert_eq!(by_serial.select(&ports).unwrap().path, "/dev/ttyUSB1");
+
+        let by_ids = PortSelector::parse("usb:10c4:ea60").unwrap().unwrap();
+        assert_eq!(by_ids.select(&ports).unwrap().path, "/dev/ttyUSB2");
+
+        let ambiguous = PortSelector::parse("usb:0403:6015").unwrap().unwrap();
+        assert!(matches!(
+            ambiguous.select(&ports),
+            Err(PortResolveError::AmbiguousMatch(paths)) if paths.len() == 2
+        ));
+
+        let missing = PortSelector::SerialNumber("NOPE".to_string());
+        assert!(matches!(
+            missing.select(&ports),
+            Err(PortResolveError::NoPortMatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_stable_spec() {
+        let port = usb_port("/dev/ttyUSB0", 0x0403, 0x6015, Some("DJ00AAAA"));
+        assert_eq!(port.stable_spec().as_deref(), Some("serial:DJ00AAAA"));
+
+        let no_serial = usb_port("/dev/ttyUSB2", 0x10c4, 0xea60, None);
+        assert_eq!(no_serial.stable_spec().as_deref(), Some("usb:10c4:ea60"));
+
+        let uart = PortMetadata {
+            kind: PortKind::Unknown,
+            vid: None,
+            pid: None,
+            ..no_serial
+        };
+        assert_eq!(uart.stable_spec(), None);
+    }
+
     #[test]
     fn test_port_spec_validation() {
         let empty = PortSpec::new();
@@ -0,0 +1,464 @@
+//! Device initialization recipes.
+//!
+//! A recipe is a named, ordered list of steps that brings one device to a
+//! known state: parameter sets (optionally read back), device commands, and
+//! verification reads. Recipes replace the manual bring-up checklist for
+//! stacks like MaiTai + Newport 1830-C + ESP300.
+//!
+//! Recipes run:
+//! - at daemon startup, after all devices are registered (`run_on = ["startup"]`)
+//! - when a device is registered again after having been removed
+//!   (`run_on = ["reconnect"]`)
+//! - on demand via the `RunInitRecipe` RPC
+//!
+//! Every run produces a [`RecipeReport`] that is logged step by step and kept
+//! by the registry for inspection.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [[recipes]]
+//! name = "maitai_ready"
+//! device = "maitai"
+//! run_on = ["startup", "reconnect"]
+//!
+//! [[recipes.steps]]
+//! action = "set"
+//! parameter = "wavelength_nm"
+//! value = 800.0
+//! verify = true
+//! tolerance = 0.5
+//!
+//! [[recipes.steps]]
+//! action = "wait"
+//! ms = 2000
+//!
+//! [[recipes.steps]]
+//! action = "command"
+//! command = "open_shutter"
+//!
+//! [[recipes.steps]]
+//! action = "verify"
+//! parameter = "shutter_open"
+//! expect = true
+//! ```
+//!
+//! A failed step stops the recipe (remaining steps are reported as skipped)
+//! unless `continue_on_error = true`.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use common::experiment::document::now_ns;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+/// Tolerance used for numeric comparisons when a step doesn't set one
+const DEFAULT_TOLERANCE: f64 = 1e-9;
+
+/// When a recipe runs automatically
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecipeTrigger {
+    /// Daemon startup, after device registration
+    Startup,
+    /// Device registered again after having been removed
+    Reconnect,
+    /// Explicit request (gRPC); never configured in `run_on`
+    Manual,
+    /// Warm-up ahead of a scheduled run (see `warmup`); never configured in `run_on`
+    Warmup,
+}
+
+impl std::fmt::Display for RecipeTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Startup => write!(f, "startup"),
+            Self::Reconnect => write!(f, "reconnect"),
+            Self::Manual => write!(f, "manual"),
+            Self::Warmup => write!(f, "warmup"),
+        }
+    }
+}
+
+/// Named initialization sequence for one device
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InitRecipe {
+    pub name: String,
+    /// Device the steps act on
+    pub device: String,
+    #[serde(default)]
+    pub description: String,
+    /// Automatic triggers (empty = on demand only)
+    #[serde(default)]
+    pub run_on: Vec<RecipeTrigger>,
+    /// Keep going after a failed step
+    #[serde(default)]
+    pub continue_on_error: bool,
+    pub steps: Vec<RecipeStep>,
+}
+
+impl InitRecipe {
+    /// Whether this recipe runs automatically for `trigger`
+    pub fn runs_on(&self, trigger: RecipeTrigger) -> bool {
+        self.run_on.contains(&trigger)
+    }
+}
+
+/// One step of a recipe
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RecipeStep {
+    /// Set a parameter, optionally reading it back
+    Set {
+        parameter: String,
+        value: Value,
+        /// Read the parameter back and compare with `value`
+        #[serde(default)]
+        verify: bool,
+        /// Allowed numeric deviation for the read-back
+        #[serde(default)]
+        tolerance: Option<f64>,
+    },
+    /// Execute a device command (see `Commandable`)
+    Command {
+        command: String,
+        #[serde(default)]
+        args: Option<Value>,
+    },
+    /// Read a parameter and compare it with an expected value
+    Verify {
+        parameter: String,
+        expect: Value,
+        #[serde(default)]
+        tolerance: Option<f64>,
+    },
+    /// Pause (e.g. for a laser to settle)
+    Wait { ms: u64 },
+}
+
+impl RecipeStep {
+    /// Human-readable summary for logs and reports
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Set {
+                parameter, value, ..
+            } => format!("set {} = {}", parameter, value),
+            Self::Command { command, .. } => format!("command {}", command),
+            Self::Verify {
+                parameter, expect, ..
+            } => format!("verify {} == {}", parameter, expect),
+            Self::Wait { ms } => format!("wait {} ms", ms),
+        }
+    }
+}
+
+/// Device access needed to execute recipes
+///
+/// Implemented by [`DeviceRegistry`](crate::registry::DeviceRegistry).
+#[async_trait]
+pub trait RecipeTarget: Send + Sync {
+    async fn set_parameter(&self, device: &str, parameter: &str, value: Value) -> Result<()>;
+
+    async fn read_parameter(&self, device: &str, parameter: &str) -> Result<Value>;
+
+    async fn execute_command(&self, device: &str, command: &str, args: Value) -> Result<Value>;
+}
+
+/// Outcome of one step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Ok,
+    Failed,
+    /// Not run because an earlier step failed
+    Skipped,
+}
+
+/// Result of one executed (or skipped) step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepResult {
+    pub index: usize,
+    pub description: String,
+    pub status: StepStatus,
+    /// Read-back or command result on success, error on failure
+    pub message: String,
+}
+
+/// Result of one recipe run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecipeReport {
+    pub recipe: String,
+    pub device: String,
+    pub trigger: RecipeTrigger,
+    /// Start time (UNIX nanoseconds)
+    pub started_ns: u64,
+    pub duration: Duration,
+    pub steps: Vec<StepResult>,
+}
+
+impl RecipeReport {
+    /// Whether every step succeeded
+    pub fn success(&self) -> bool {
+        self.steps.iter().all(|s| s.status == StepStatus::Ok)
+    }
+}
+
+/// Execute a recipe against `target`, logging each step
+pub async fn run_recipe(
+    recipe: &InitRecipe,
+    target: &dyn RecipeTarget,
+    trigger: RecipeTrigger,
+) -> RecipeReport {
+    let started_ns = now_ns();
+    let start = Instant::now();
+    tracing::info!(
+        recipe = %recipe.name,
+        device = %recipe.device,
+        %trigger,
+        steps = recipe.steps.len(),
+        "Running init recipe"
+    );
+
+    let mut steps = Vec::with_capacity(recipe.steps.len());
+    let mut failed = false;
+    for (index, step) in recipe.steps.iter().enumerate() {
+        let description = step.describe();
+        if failed && !recipe.continue_on_error {
+            steps.push(StepResult {
+                index,
+                description,
+                status: StepStatus::Skipped,
+                message: String::new(),
+            });
+            continue;
+        }
+
+        let (status, message) = match run_step(&recipe.device, step, target).await {
+            Ok(message) => {
+                tracing::info!(recipe = %recipe.name, step = index, %description, %message, "Recipe step ok");
+                (StepStatus::Ok, message)
+            }
+            Err(e) => {
+                tracing::warn!(recipe = %recipe.name, step = index, %description, error = %e, "Recipe step failed");
+                failed = true;
+                (StepStatus::Failed, e.to_string())
+            }
+        };
+        steps.push(StepResult {
+            index,
+            description,
+            status,
+            message,
+        });
+    }
+
+    let report = RecipeReport {
+        recipe: recipe.name.clone(),
+        device: recipe.device.clone(),
+        trigger,
+        started_ns,
+        duration: start.elapsed(),
+        steps,
+    };
+    if report.success() {
+        tracing::info!(recipe = %recipe.name, duration_ms = report.duration.as_millis() as u64, "Init recipe completed");
+    } else {
+        tracing::error!(recipe = %recipe.name, device = %recipe.device, "Init recipe failed");
+    }
+    report
+}
+
+async fn run_step(device: &str, step: &RecipeStep, target: &dyn RecipeTarget) -> Result<String> {
+    match step {
+        RecipeStep::Set {
+            parameter,
+            value,
+            verify,
+            tolerance,
+        } => {
+            target
+                .set_parameter(device, parameter, value.clone())
+                .await?;
+            if *verify {
+                let actual = target.read_parameter(device, parameter).await?;
+                check_value(parameter, value, &actual, *tolerance)?;
+                Ok(format!("read back {}", actual))
+            } else {
+                Ok(String::new())
+            }
+        }
+        RecipeStep::Command { command, args } => {
+            let args = args.clone().unwrap_or_else(|| serde_json::json!({}));
+            let result = target.execute_command(device, command, args).await?;
+            Ok(result.to_string())
+        }
+        RecipeStep::Verify {
+            parameter,
+            expect,
+            tolerance,
+        } => {
+            let actual = target.read_parameter(device, parameter).await?;
+            check_value(parameter, expect, &actual, *tolerance)?;
+            Ok(format!("read {}", actual))
+        }
+        RecipeStep::Wait { ms } => {
+            tokio::time::sleep(Duration::from_millis(*ms)).await;
+            Ok(String::new())
+        }
+    }
+}
+
+pub(crate) fn check_value(
+    parameter: &str,
+    expected: &Value,
+    actual: &Value,
+    tolerance: Option<f64>,
+) -> Result<()> {
+    if values_match(expected, actual, tolerance.unwrap_or(DEFAULT_TOLERANCE)) {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "{} is {}, expected {}",
+            parameter,
+            actual,
+            expected
+        ))
+    }
+}
+
+/// Compare an expected value with a read-back
+///
+/// Numbers match within `tolerance`. Drivers that report values as strings
+/// (e.g. `"800.0"` or `"true"`) are compared by their parsed value.
+fn values_match(expected: &Value, actual: &Value, tolerance: f64) -> bool {
+    let parsed;
+    let actual = match actual {
+        Value::String(s) if !expected.is_string() => {
+            parsed = serde_json::from_str::<Value>(s.trim()).unwrap_or_else(|_| actual.clone());
+            &parsed
+        }
+        _ => actual,
+    };
+    match (expected.as_f64(), actual.as_f64()) {
+        (Some(e), Some(a)) => (e - a).abs() <= tolerance,
+        _ => expected == actual,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Parameters stored in memory; `wavelength_nm` settles 0.2 off target
+    #[derive(Default)]
+    struct FakeDevice {
+        params: Mutex<HashMap<String, Value>>,
+    }
+
+    #[async_trait]
+    impl RecipeTarget for FakeDevice {
+        async fn set_parameter(&self, _device: &str, parameter: &str, value: Value) -> Result<()> {
+            let value = match (parameter, value.as_f64()) {
+                ("wavelength_nm", Some(v)) => serde_json::json!(v + 0.2),
+                _ => value,
+            };
+            self.params
+                .lock()
+                .unwrap()
+                .insert(parameter.to_string(), value);
+            Ok(())
+        }
+
+        async fn read_parameter(&self, _device: &str, parameter: &str) -> Result<Value> {
+            self.params
+                .lock()
+                .unwrap()
+                .get(parameter)
+                .cloned()
+                .ok_or_else(|| anyhow!("unknown parameter {}", parameter))
+        }
+
+        async fn execute_command(
+            &self,
+            _device: &str,
+            command: &str,
+            _args: Value,
+        ) -> Result<Value> {
+            match command {
+                "open_shutter" => {
+                    self.params
+                        .lock()
+                        .unwrap()
+                        .insert("shutter_open".to_string(), Value::String("true".into()));
+                    Ok(Value::Bool(true))
+                }
+                _ => Err(anyhow!("unknown command {}", command)),
+            }
+        }
+    }
+
+    fn recipe(toml: &str) -> InitRecipe {
+        toml::from_str(toml).unwrap()
+    }
+
+    const MAITAI: &str = r#"
+        name = "maitai_ready"
+        device = "maitai"
+        run_on = ["startup"]
+
+        [[steps]]
+        action = "set"
+        parameter = "wavelength_nm"
+        value = 800.0
+        verify = true
+        tolerance = 0.5
+
+        [[steps]]
+        action = "command"
+        command = "open_shutter"
+
+        [[steps]]
+        action = "verify"
+        parameter = "shutter_open"
+        expect = true
+    "#;
+
+    #[tokio::test]
+    async fn test_recipe_runs_all_steps() {
+        let recipe = recipe(MAITAI);
+        assert!(recipe.runs_on(RecipeTrigger::Startup));
+        assert!(!recipe.runs_on(RecipeTrigger::Reconnect));
+
+        let report = run_recipe(&recipe, &FakeDevice::default(), RecipeTrigger::Startup).await;
+        assert!(report.success(), "{:?}", report.steps);
+        assert_eq!(report.steps.len(), 3);
+        assert_eq!(report.steps[0].message, "read back 800.2");
+    }
+
+    #[tokio::test]
+    async fn test_failed_verification_skips_remaining_steps() {
+        let mut recipe = recipe(MAITAI);
+        recipe.steps[0] = RecipeStep::Set {
+            parameter: "wavelength_nm".into(),
+            value: serde_json::json!(800.0),
+            verify: true,
+            tolerance: None,
+        };
+
+        let report = run_recipe(&recipe, &FakeDevice::default(), RecipeTrigger::Manual).await;
+        assert!(!report.success());
+        let statuses: Vec<StepStatus> = report.steps.iter().map(|s| s.status).collect();
+        assert_eq!(
+            statuses,
+            vec![StepStatus::Failed, StepStatus::Skipped, StepStatus::Skipped]
+        );
+        assert!(report.steps[0].message.contains("expected 800.0"));
+
+        recipe.continue_on_error = true;
+        let report = run_recipe(&recipe, &FakeDevice::default(), RecipeTrigger::Manual).await;
+        assert_eq!(report.steps[2].status, StepStatus::Ok);
+    }
+}
@@ -24,6 +24,12 @@
 //! └─────────────────────────────────────────────────────────────────┘
 //! ```
 //!
+//! Every kind of driver ends up here: built-in drivers from
+//! [`DriverType`], `DriverFactory` plugins, YAML plugin devices and
+//! pre-spawned plugin instances ([`DeviceRegistry::register_plugin_instance`]).
+//! There is no second registry for an older driver architecture, so the gRPC
+//! `HardwareService` lists every device the daemon knows about.
+//!
 //! # Known Instruments (from docs/HARDWARE_INVENTORY.md)
 //!
 //! | Device | Driver | Port | Capabilities |
@@ -47,6 +53,7 @@
 //!         id: "power_meter".into(),
 //!         name: "Newport 1830-C".into(),
 //!         driver: DriverType::Newport1830C { port: "/dev/ttyS0".into() },
+//!         simulated: false,
 //!     }).await?;
 //!
 //!     registry.register(DeviceConfig {
@@ -56,6 +63,7 @@
 //!             port: "/dev/ttyUSB0".into(),
 //!             address: "2".into(),
 //!         },
+//!         simulated: false,
 //!     }).await?;
 //!
 //!     // List all devices
@@ -74,21 +82,61 @@
 
 use anyhow::{anyhow, Result};
 use common::capabilities::{
-    Commandable, EmissionControl, ExposureControl, FrameProducer, Movable, Parameterized, Readable,
-    Settable, ShutterControl, Stageable, Triggerable, WavelengthTunable,
+    Commandable, DeviceCategory, DiscretePositioner, EmissionControl, ExposureControl,
+    FrameProducer, Movable, Parameterized, RawTerminal, Readable, Settable, ShutterControl,
+    Stageable, Triggerable, WavelengthTunable,
+};
+use common::channel_alias::{ChannelAliases, ChannelRef};
+use common::composite_trigger::{
+    CompositeTrigger, CompositeTriggerConfig, TriggerEvaluator, TriggerInput,
 };
+use common::coordinates::{SampleRegistration, SampleRegistrationConfig};
 use common::data::Frame;
-use common::driver::{Capability, DeviceComponents, DeviceLifecycle, DriverFactory};
+use common::device_events::DeviceEvent;
+use common::discrete_positioner::{
+    DiscretePositionerConfig, MappedPositioner, PositionerDrive, PositionerOutput,
+};
+use common::document_transform::{DocumentTransformConfig, DocumentTransforms};
+use common::driver::{
+    Capability, DeviceComponents, DeviceIdentity, DeviceLifecycle, DeviceStatus,
+    DeviceStatusHandle, DriverFactory,
+};
+use common::environment::EnvironmentLog;
 use common::error::DaqError;
+use common::frame_annotations::{validate_annotations, FrameAnnotation, FrameAnnotationStore};
+use common::frame_enrichment::FrameEnrichmentConfig;
+use common::health::{ErrorSeverity, HealthError};
+use common::integrity::{merge_counts, DropLedger, DropReport};
+use common::introspection::CapabilityDescriptor;
+use common::keep_out::{check_move, KeepOutMovable, KeepOutZone};
+use common::motion_correction::{CorrectedMovable, MotionCorrection};
+use common::motion_group::{Kinematics, MotionGroup, MotionGroupConfig};
+use common::observable::ParameterBase;
+use common::output_limits::{
+    LimitViolation, LimitedMovable, LimitedSettable, OutputLimit, OutputLimiter, POSITION_PARAMETER,
+};
+use common::parking::{ParkingActions, ParkingTarget};
 use common::pipeline::MeasurementSource;
+use common::preprocessing::PreprocessingConfig;
+use common::ramp::{RampHandle, RampManager, RampOutput, RampSpec};
+use common::state_machine::{MachineKind, StateTracker};
+use common::time_sync::TimestampCorrections;
+use common::validation::{self, ValidationRule};
 
 #[cfg(feature = "serial")]
 use crate::plugin::driver::GenericDriver;
 // use crate::plugin::driver::{Connection, GenericDriver};
 // use crate::plugin::schema::{DriverType, InstrumentConfig, PluginMetadata, ScriptType};
-use dashmap::DashMap;
+use crate::display_calibration::{DisplayCalibration, DisplayCalibrationConfig, DisplayTransform};
+use crate::inventory::{
+    IdentitySource, Inventory, InventoryConfig, InventoryEntry, InventoryReport,
+};
+use crate::recipes::{InitRecipe, RecipeReport, RecipeTarget, RecipeTrigger};
+use crate::self_test::{Diagnostic, SelfTestConfig, SelfTestTarget};
+use crate::warmup::WarmupSchedule;
+use dashmap::{DashMap, DashSet};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 #[cfg(feature = "serial")]
 use tokio::sync::RwLock;
@@ -272,6 +320,60 @@ fn validate_serial_port(_port: &str, _device_name: &str) -> Result<(), DaqError>
     Ok(())
 }
 
+/// Serial port of a driver config, if it has one
+#[cfg(feature = "serial")]
+fn driver_serial_port(driver: &mut DriverType) -> Option<&mut String> {
+    match driver {
+        DriverType::Newport1830C { port }
+        | DriverType::MaiTai { port }
+        | DriverType::Ell14 { port, .. }
+        | DriverType::Esp300 { port, .. }
+        | DriverType::Plugin { address: port, .. } => Some(port),
+        _ => None,
+    }
+}
+
+/// Replace a `serial:`/`usb:` hardware ID port spec with the current device path
+///
+/// Plain paths are left alone so validation can report them as usual.
+#[cfg(feature = "serial")]
+fn resolve_driver_port(driver: &mut DriverType) -> Result<(), DaqError> {
+    let Some(port) = driver_serial_port(driver) else {
+        return Ok(());
+    };
+    if !crate::port_resolver::is_hardware_spec(port) {
+        return Ok(());
+    }
+    let resolved = crate::port_resolver::resolve_port(port)
+        .map_err(|e| DaqError::Configuration(e.to_string()))?;
+    tracing::info!(spec = %port, path = %resolved, "Resolved serial port");
+    *port = resolved;
+    Ok(())
+}
+
+#[cfg(not(feature = "serial"))]
+fn resolve_driver_port(_driver: &mut DriverType) -> Result<(), DaqError> {
+    Ok(())
+}
+
+/// Resolve a hardware ID spec in a factory config's `port` key
+fn resolve_toml_port(config: &mut toml::Value) -> Result<(), DaqError> {
+    let Some(port) = config.get_mut("port") else {
+        return Ok(());
+    };
+    let Some(spec) = port
+        .as_str()
+        .filter(|p| crate::port_resolver::is_hardware_spec(p))
+    else {
+        return Ok(());
+    };
+    let resolved = crate::port_resolver::resolve_port(spec)
+        .map_err(|e| DaqError::Configuration(e.to_string()))?;
+    tracing::info!(spec = %spec, path = %resolved, "Resolved serial port");
+    *port = toml::Value::String(resolved);
+    Ok(())
+}
+
 /// Validate ELL14 device address
 ///
 /// ELL14 addresses must be hex digits 0-F
@@ -301,6 +403,15 @@ fn validate_ell14_address(address: &str) -> Result<(), DaqError> {
 /// Format: lowercase alphanumeric with underscores (e.g., "power_meter", "rotator_2")
 pub type DeviceId = String;
 
+/// Driver type reported for motion groups and their axes
+pub const MOTION_GROUP_DRIVER_TYPE: &str = "motion_group";
+
+/// Driver type reported for configured discrete positioners
+pub const DISCRETE_POSITIONER_DRIVER_TYPE: &str = "discrete_positioner";
+
+/// Driver type reported for configured composite triggers
+pub const COMPOSITE_TRIGGER_DRIVER_TYPE: &str = "composite_trigger";
+
 /// Capabilities a device can have (for introspection)
 // =============================================================================
 // Driver Types (Configuration)
@@ -433,7 +544,11 @@ impl DriverType {
         match self {
             #[cfg(feature = "serial")]
             DriverType::Newport1830C { .. } => {
-                vec![Capability::Readable, Capability::WavelengthTunable]
+                vec![
+                    Capability::Readable,
+                    Capability::WavelengthTunable,
+                    Capability::RawTerminal,
+                ]
             }
             #[cfg(feature = "serial")]
             DriverType::MaiTai { .. } => vec![
@@ -442,11 +557,12 @@ impl DriverType {
                 Capability::WavelengthTunable,
                 Capability::EmissionControl,
                 Capability::Parameterized,
+                Capability::RawTerminal,
             ],
             #[cfg(feature = "serial")]
-            DriverType::Ell14 { .. } => vec![Capability::Movable],
+            DriverType::Ell14 { .. } => vec![Capability::Movable, Capability::RawTerminal],
             #[cfg(feature = "serial")]
-            DriverType::Esp300 { .. } => vec![Capability::Movable],
+            DriverType::Esp300 { .. } => vec![Capability::Movable, Capability::RawTerminal],
             DriverType::MockStage { .. } => vec![Capability::Movable],
             DriverType::MockPowerMeter { .. } => vec![Capability::Readable],
             DriverType::MockCamera { .. } => vec![
@@ -510,6 +626,47 @@ impl DriverType {
             }
         }
     }
+
+    /// Mock factory and its TOML config standing in for this driver when
+    /// the device is simulated, or `None` if it has no mock counterpart
+    ///
+    /// Mock drivers simulate themselves. Other drivers get a mock with the
+    /// same main capability and default settings.
+    pub fn simulated_backend(&self) -> Option<(&'static str, toml::Value)> {
+        let empty = || toml::Value::Table(toml::map::Map::new());
+        match self {
+            #[cfg(feature = "serial")]
+            DriverType::Newport1830C { .. } => Some(("mock_power_meter", empty())),
+            #[cfg(feature = "serial")]
+            DriverType::MaiTai { .. } => Some(("mock_laser", empty())),
+            #[cfg(feature = "serial")]
+            DriverType::Ell14 { .. } => Some(("mock_rotator", empty())),
+            #[cfg(feature = "serial")]
+            DriverType::Esp300 { .. } => Some(("mock_stage", empty())),
+            DriverType::MockStage { .. }
+            | DriverType::MockPowerMeter { .. }
+            | DriverType::MockCamera { .. } => toml::Value::try_from(self)
+                .ok()
+                .map(|config| (self.driver_name(), config)),
+            #[cfg(feature = "pvcam")]
+            DriverType::Pvcam { .. } => Some(("mock_camera", empty())),
+            #[cfg(feature = "comedi")]
+            DriverType::Comedi { .. } | DriverType::ComediAnalogInput { .. } => {
+                Some(("mock_power_meter", empty()))
+            }
+            #[cfg(feature = "comedi")]
+            DriverType::ComediAnalogOutput { channel, .. } => {
+                let mut config = toml::map::Map::new();
+                config.insert(
+                    "channel".to_string(),
+                    toml::Value::Integer(i64::from(*channel)),
+                );
+                Some(("mock_daq_output", toml::Value::Table(config)))
+            }
+            #[cfg(feature = "serial")]
+            DriverType::Plugin { .. } => None,
+        }
+    }
 }
 
 // =============================================================================
@@ -525,6 +682,10 @@ pub struct DeviceConfig {
     pub name: String,
     /// Driver type and configuration
     pub driver: DriverType,
+    /// Register a mock backend in place of the real driver, keeping the
+    /// rest of the configuration (see [`DriverType::simulated_backend`])
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub simulated: bool,
 }
 
 // =============================================================================
@@ -544,6 +705,23 @@ pub struct DeviceInfo {
     pub capabilities: Vec<Capability>,
     /// Capability-specific metadata
     pub metadata: DeviceMetadata,
+    /// Channel aliases that target this device
+    pub aliases: Vec<String>,
+    /// Free-form tags from the hardware config, for filtering listings
+    pub tags: Vec<String>,
+    /// Running on a mock backend in place of its configured driver
+    pub simulated: bool,
+    /// Operational status (ready, busy, degraded, fault, ...)
+    pub status: DeviceStatus,
+}
+
+/// A capability trait implemented by a registered device
+#[derive(Debug, Clone)]
+pub struct CapabilityReport {
+    /// Trait name, version and method metadata
+    pub descriptor: &'static CapabilityDescriptor,
+    /// What this device reports for the capability (units, ranges, parameter names)
+    pub details: BTreeMap<String, String>,
 }
 
 /// Capability-specific metadata for a device
@@ -576,6 +754,13 @@ pub struct DeviceMetadata {
     pub min_wavelength_nm: Option<f64>,
     /// For WavelengthTunable devices: maximum wavelength in nm (bd-pwjo)
     pub max_wavelength_nm: Option<f64>,
+    /// Identity reported by the driver (model, serial, firmware)
+    pub identity: Option<common::driver::DeviceIdentity>,
+    /// For Movable devices: backlash/scale/offset correction applied to positions
+    pub position_correction: Option<MotionCorrection>,
+    /// Control panel shipped with the driver, rendered by the GUI in place
+    /// of a hand-written panel (empty for none)
+    pub ui_layout: Vec<crate::plugin::schema::UiElement>,
 }
 
 // =============================================================================
@@ -617,8 +802,14 @@ struct RegisteredDevice {
     emission_control: Option<Arc<dyn EmissionControl>>,
     /// WavelengthTunable implementation (if supported) - tunable laser wavelength (bd-pwjo)
     wavelength_tunable: Option<Arc<dyn WavelengthTunable>>,
+    /// RawTerminal implementation (if supported) - instrument console passthrough
+    raw_terminal: Option<Arc<dyn RawTerminal>>,
+    /// DiscretePositioner implementation (if supported) - named slots
+    discrete_positioner: Option<Arc<dyn DiscretePositioner>>,
     /// Optional lifecycle hooks for registration/shutdown
     lifecycle: Option<Arc<dyn DeviceLifecycle>>,
+    /// Operational status, shared with the driver if it reports one
+    status: DeviceStatusHandle,
     /// Device metadata (units, ranges, etc.)
     metadata: DeviceMetadata,
 }
@@ -662,9 +853,77 @@ impl RegisteredDevice {
         if self.wavelength_tunable.is_some() {
             caps.push(Capability::WavelengthTunable);
         }
+        if self.raw_terminal.is_some() {
+            caps.push(Capability::RawTerminal);
+        }
+        if self.discrete_positioner.is_some() {
+            caps.push(Capability::DiscretePositioner);
+        }
 
         caps
     }
+
+    /// Device-specific details for one of its capabilities
+    fn capability_details(&self, capability: Capability) -> BTreeMap<String, String> {
+        let mut details = BTreeMap::new();
+        let mut put = |key: &str, value: Option<String>| {
+            if let Some(value) = value {
+                details.insert(key.to_string(), value);
+            }
+        };
+        let meta = &self.metadata;
+        match capability {
+            Capability::Movable => {
+                put("units", meta.position_units.clone());
+                put("min_position", meta.min_position.map(|v| v.to_string()));
+                put("max_position", meta.max_position.map(|v| v.to_string()));
+            }
+            Capability::Readable => put("units", meta.measurement_units.clone()),
+            Capability::ExposureControl => {
+                put(
+                    "min_exposure_ms",
+                    meta.min_exposure_ms.map(|v| v.to_string()),
+                );
+                put(
+                    "max_exposure_ms",
+                    meta.max_exposure_ms.map(|v| v.to_string()),
+                );
+            }
+            Capability::FrameProducer => {
+                if let Some(producer) = &self.frame_producer {
+                    let (width, height) = producer.resolution();
+                    put("width", Some(width.to_string()));
+                    put("height", Some(height.to_string()));
+                    put(
+                        "supports_observers",
+                        Some(producer.supports_observers().to_string()),
+                    );
+                }
+                put("bits_per_pixel", meta.bits_per_pixel.map(|v| v.to_string()));
+            }
+            Capability::WavelengthTunable => {
+                if let Some(tunable) = &self.wavelength_tunable {
+                    let (min, max) = tunable.wavelength_range();
+                    put("min_wavelength_nm", Some(min.to_string()));
+                    put("max_wavelength_nm", Some(max.to_string()));
+                }
+            }
+            Capability::Parameterized => {
+                if let Some(parameterized) = &self.parameterized {
+                    let mut names = parameterized.parameters().names();
+                    names.sort_unstable();
+                    put("parameters", Some(names.join(",")));
+                }
+            }
+            Capability::DiscretePositioner => {
+                if let Some(positioner) = &self.discrete_positioner {
+                    put("positions", Some(positioner.position_names().join(",")));
+                }
+            }
+            _ => {}
+        }
+        details
+    }
 }
 
 // =============================================================================
@@ -725,6 +984,113 @@ pub struct DeviceRegistry {
 
     /// Registration failures for debugging (device_id, driver_type, error_message)
     registration_failures: DashMap<DeviceId, RegistrationFailure>,
+
+    /// Experiment-level channel aliases (e.g. "sample_temp" -> "lakeshore1:inputA")
+    aliases: std::sync::RwLock<ChannelAliases>,
+
+    /// Channels sampled and attached to every acquired frame
+    frame_enrichment: std::sync::RwLock<FrameEnrichmentConfig>,
+
+    /// Overlay annotations per camera, streamed and stored with frames
+    frame_annotations: FrameAnnotationStore,
+
+    /// How clients display channel values (never applied to stored data)
+    display_calibration: std::sync::RwLock<DisplayCalibration>,
+
+    /// Per-consumer transforms of the run document stream
+    document_transforms: Arc<DocumentTransforms>,
+
+    /// Setpoint ramps in progress
+    ramps: RampManager,
+
+    /// Frame preprocessing (dark/flat, binning, histogram) per camera ID
+    preprocessing: std::sync::RwLock<HashMap<String, PreprocessingConfig>>,
+
+    /// Device initialization recipes
+    recipes: std::sync::RwLock<Vec<InitRecipe>>,
+
+    /// Warm-up schedules (run by `warmup::WarmupScheduler`)
+    warmups: std::sync::RwLock<Vec<WarmupSchedule>>,
+
+    /// Startup self-test settings (run by `self_test::run_self_test`)
+    self_test: std::sync::RwLock<SelfTestConfig>,
+
+    /// Instrument inventory settings
+    inventory: std::sync::RwLock<InventoryConfig>,
+
+    /// Result of the last inventory refresh
+    inventory_report: std::sync::RwLock<Option<InventoryReport>>,
+
+    /// Free-form tags keyed by device ID
+    device_tags: std::sync::RwLock<HashMap<String, Vec<String>>>,
+
+    /// Per-parameter policies (dangerous flags) keyed by device ID
+    parameter_policies: std::sync::RwLock<HashMap<String, HashMap<String, ParameterPolicy>>>,
+
+    /// Latest report per recipe name
+    recipe_reports: DashMap<String, RecipeReport>,
+
+    /// Every device ID registered so far (a repeat registration is a reconnect)
+    registered_ids: DashSet<DeviceId>,
+
+    /// Devices registered with a mock backend in place of their driver
+    simulated_ids: DashSet<DeviceId>,
+
+    /// Coordinated motion groups by group ID
+    motion_groups: DashMap<String, Arc<MotionGroup>>,
+
+    /// Backlash/scale/offset corrections keyed by device ID
+    motion_corrections: std::sync::RwLock<HashMap<String, MotionCorrection>>,
+
+    /// Driver Movable and raw travel limits of corrected devices
+    uncorrected: DashMap<DeviceId, UncorrectedMovable>,
+
+    /// Setpoint limits keyed by device ID
+    output_limiters: DashMap<DeviceId, Arc<OutputLimiter>>,
+
+    /// Keep-out ranges of Movable devices, keyed by device ID
+    keep_out_zones: DashMap<DeviceId, Arc<Vec<KeepOutZone>>>,
+
+    /// Actions parking each device while a run is paused, keyed by device ID
+    parking_actions: std::sync::RwLock<HashMap<String, ParkingActions>>,
+
+    /// Rules checked against every point of a run
+    validation_rules: std::sync::RwLock<Vec<ValidationRule>>,
+
+    /// Offsets putting each device's timestamps on the common time base
+    timestamp_corrections: std::sync::RwLock<TimestampCorrections>,
+
+    /// Setpoints refused or clamped by output limits
+    limit_violations: tokio::sync::broadcast::Sender<LimitViolation>,
+
+    /// State changes, errors, limit hits and range changes of all devices
+    device_events: tokio::sync::broadcast::Sender<DeviceEvent>,
+
+    /// Command locks keyed by device ID (see [`DeviceRegistry::lock_devices`])
+    command_locks: DashMap<DeviceId, Arc<tokio::sync::Mutex<()>>>,
+
+    /// Fiducial registrations keyed by sample ID
+    sample_registrations: DashMap<String, SampleRegistration>,
+
+    /// Sample whose registration is recorded with runs
+    active_sample: std::sync::RwLock<Option<String>>,
+
+    /// Ambient environment channels, summarised per run
+    environment: Arc<EnvironmentLog>,
+}
+
+/// Command locks on a set of devices, released when dropped
+#[must_use = "the locks are released when dropped"]
+pub struct DeviceLocks {
+    _guards: Vec<tokio::sync::OwnedMutexGuard<()>>,
+}
+
+/// What a device looked like before its motion correction was applied
+#[derive(Clone)]
+struct UncorrectedMovable {
+    movable: Arc<dyn Movable>,
+    min_position: Option<f64>,
+    max_position: Option<f64>,
 }
 
 /// Information about a failed device registration
@@ -803,6 +1169,37 @@ impl DeviceRegistry {
             #[cfg(feature = "serial")]
             plugin_factory: Arc::new(RwLock::new(crate::plugin::registry::PluginFactory::new())),
             registration_failures: DashMap::new(),
+            aliases: std::sync::RwLock::new(ChannelAliases::new()),
+            frame_enrichment: std::sync::RwLock::new(FrameEnrichmentConfig::default()),
+            frame_annotations: FrameAnnotationStore::new(),
+            display_calibration: std::sync::RwLock::new(DisplayCalibration::default()),
+            document_transforms: Arc::new(DocumentTransforms::new()),
+            ramps: RampManager::new(),
+            preprocessing: std::sync::RwLock::new(HashMap::new()),
+            recipes: std::sync::RwLock::new(Vec::new()),
+            warmups: std::sync::RwLock::new(Vec::new()),
+            self_test: std::sync::RwLock::new(SelfTestConfig::default()),
+            inventory: std::sync::RwLock::new(InventoryConfig::default()),
+            inventory_report: std::sync::RwLock::new(None),
+            device_tags: std::sync::RwLock::new(HashMap::new()),
+            parameter_policies: std::sync::RwLock::new(HashMap::new()),
+            recipe_reports: DashMap::new(),
+            registered_ids: DashSet::new(),
+            simulated_ids: DashSet::new(),
+            motion_groups: DashMap::new(),
+            motion_corrections: std::sync::RwLock::new(HashMap::new()),
+            uncorrected: DashMap::new(),
+            output_limiters: DashMap::new(),
+            keep_out_zones: DashMap::new(),
+            parking_actions: std::sync::RwLock::new(HashMap::new()),
+            validation_rules: std::sync::RwLock::new(Vec::new()),
+            timestamp_corrections: std::sync::RwLock::new(TimestampCorrections::default()),
+            limit_violations: tokio::sync::broadcast::channel(64).0,
+            device_events: tokio::sync::broadcast::channel(256).0,
+            command_locks: DashMap::new(),
+            sample_registrations: DashMap::new(),
+            active_sample: std::sync::RwLock::new(None),
+            environment: Arc::new(EnvironmentLog::default()),
         }
     }
 
@@ -818,6 +1215,37 @@ impl DeviceRegistry {
             ell14_shared_ports: RwLock::new(HashMap::new()),
             plugin_factory,
             registration_failures: DashMap::new(),
+            aliases: std::sync::RwLock::new(ChannelAliases::new()),
+            frame_enrichment: std::sync::RwLock::new(FrameEnrichmentConfig::default()),
+            frame_annotations: FrameAnnotationStore::new(),
+            display_calibration: std::sync::RwLock::new(DisplayCalibration::default()),
+            document_transforms: Arc::new(DocumentTransforms::new()),
+            ramps: RampManager::new(),
+            preprocessing: std::sync::RwLock::new(HashMap::new()),
+            recipes: std::sync::RwLock::new(Vec::new()),
+            warmups: std::sync::RwLock::new(Vec::new()),
+            self_test: std::sync::RwLock::new(SelfTestConfig::default()),
+            inventory: std::sync::RwLock::new(InventoryConfig::default()),
+            inventory_report: std::sync::RwLock::new(None),
+            device_tags: std::sync::RwLock::new(HashMap::new()),
+            parameter_policies: std::sync::RwLock::new(HashMap::new()),
+            recipe_reports: DashMap::new(),
+            registered_ids: DashSet::new(),
+            simulated_ids: DashSet::new(),
+            motion_groups: DashMap::new(),
+            motion_corrections: std::sync::RwLock::new(HashMap::new()),
+            uncorrected: DashMap::new(),
+            output_limiters: DashMap::new(),
+            keep_out_zones: DashMap::new(),
+            parking_actions: std::sync::RwLock::new(HashMap::new()),
+            validation_rules: std::sync::RwLock::new(Vec::new()),
+            timestamp_corrections: std::sync::RwLock::new(TimestampCorrections::default()),
+            limit_violations: tokio::sync::broadcast::channel(64).0,
+            device_events: tokio::sync::broadcast::channel(256).0,
+            command_locks: DashMap::new(),
+            sample_registrations: DashMap::new(),
+            active_sample: std::sync::RwLock::new(None),
+            environment: Arc::new(EnvironmentLog::default()),
         }
     }
 
@@ -976,7 +1404,7 @@ impl DeviceRegistry {
         device_id: &str,
         device_name: &str,
         driver_type: &str,
-        config: toml::Value,
+        mut config: toml::Value,
     ) -> Result<(), DaqError> {
         if self.devices.contains_key(device_id) {
             return Err(DaqError::Configuration(format!(
@@ -984,6 +1412,7 @@ impl DeviceRegistry {
                 device_id
             )));
         }
+        self.ensure_not_alias(device_id)?;
 
         // Look up factory
         let factory = self.factories.get(driver_type).ok_or_else(|| {
@@ -996,6 +1425,9 @@ impl DeviceRegistry {
         })?;
 
         // Validate configuration
+        resolve_toml_port(&mut config).map_err(|e| {
+            DaqError::Configuration(format!("Device '{}' ({}): {}", device_id, driver_type, e))
+        })?;
         factory.validate(&config).map_err(|e| {
             DaqError::Driver(common::error::DriverError::new(
                 driver_type,
@@ -1046,9 +1478,38 @@ impl DeviceRegistry {
 
         self.devices.insert(device_id.to_string(), registered);
         tracing::info!(device_id = %device_id, "Device registered successfully");
+        self.after_register(device_id).await;
+        Ok(())
+    }
+
+    /// Register a device with the mock backend standing in for its driver
+    ///
+    /// The driver's port or address is never opened, so a full experiment
+    /// configuration can be exercised without the hardware attached.
+    pub async fn register_simulated(&self, config: &DeviceConfig) -> Result<(), DaqError> {
+        let (mock_type, mock_config) = config.driver.simulated_backend().ok_or_else(|| {
+            DaqError::Configuration(format!(
+                "Device '{}' ({}) cannot be simulated",
+                config.id,
+                config.driver.driver_name()
+            ))
+        })?;
+        if !self.has_factory(mock_type) {
+            register_mock_factories(self);
+        }
+
+        tracing::info!(device_id = %config.id, mock_type, "Simulating device");
+        self.register_from_toml(&config.id, &config.name, mock_type, mock_config)
+            .await?;
+        self.simulated_ids.insert(config.id.clone());
         Ok(())
     }
 
+    /// Whether a device runs on a mock backend in place of its driver
+    pub fn is_simulated(&self, device_id: &str) -> bool {
+        self.simulated_ids.contains(device_id)
+    }
+
     /// Convert DeviceComponents from a factory into a RegisteredDevice.
     ///
     /// This bridges the new DriverFactory pattern with the legacy RegisteredDevice
@@ -1069,6 +1530,7 @@ impl DeviceRegistry {
             driver: DriverType::MockStage {
                 initial_position: 0.0,
             },
+            simulated: false,
         };
 
         // Convert common::driver::DeviceMetadata to local DeviceMetadata
@@ -1085,6 +1547,9 @@ impl DeviceRegistry {
             max_exposure_ms: components.metadata.max_exposure_ms,
             min_wavelength_nm: components.metadata.min_wavelength_nm,
             max_wavelength_nm: components.metadata.max_wavelength_nm,
+            identity: components.metadata.identity.clone(),
+            position_correction: None,
+            ui_layout: Vec::new(),
         };
 
         // Log the actual driver_type for debugging (not the synthetic one)
@@ -1094,6 +1559,15 @@ impl DeviceRegistry {
             "Converting DeviceComponents to RegisteredDevice"
         );
 
+        // Publish the driver's events and status changes under its ID
+        let status = components.status.unwrap_or_default();
+        status
+            .events()
+            .attach(&config.id, self.device_events.clone());
+        if let Some(events) = &components.events {
+            events.attach(&config.id, self.device_events.clone());
+        }
+
         RegisteredDevice {
             config,
             driver_type,
@@ -1110,7 +1584,10 @@ impl DeviceRegistry {
             shutter_control: components.shutter_control,
             emission_control: components.emission_control,
             wavelength_tunable: components.wavelength_tunable,
+            raw_terminal: components.raw_terminal,
+            discrete_positioner: components.discrete_positioner,
             lifecycle: components.lifecycle,
+            status,
             metadata,
         }
     }
@@ -1131,25 +1608,31 @@ impl DeviceRegistry {
     /// # Thread Safety (bd-pf31)
     /// This method is thread-safe and can be called concurrently. Registration of
     /// the same device ID from multiple threads will fail for all but one caller.
-    pub async fn register(&self, config: DeviceConfig) -> Result<(), DaqError> {
+    pub async fn register(&self, mut config: DeviceConfig) -> Result<(), DaqError> {
         if self.devices.contains_key(&config.id) {
             return Err(DaqError::Configuration(format!(
                 "Device '{}' is already registered",
                 config.id
             )));
         }
+        self.ensure_not_alias(&config.id)?;
+        if config.simulated {
+            return self.register_simulated(&config).await;
+        }
 
         let driver_type = config.driver.driver_name().to_string();
 
         // Validate configuration before attempting to instantiate
-        validate_driver_config(&config.driver).map_err(|e| {
-            DaqError::Configuration(format!(
-                "Configuration validation failed for device '{}' ({}): {}",
-                config.id,
-                config.driver.driver_name(),
-                e
-            ))
-        })?;
+        resolve_driver_port(&mut config.driver)
+            .and_then(|()| validate_driver_config(&config.driver))
+            .map_err(|e| {
+                DaqError::Configuration(format!(
+                    "Configuration validation failed for device '{}' ({}): {}",
+                    config.id,
+                    config.driver.driver_name(),
+                    e
+                ))
+            })?;
 
         let registered = self.instantiate_device(config).await.map_err(|e| {
             DaqError::Driver(common::error::DriverError::new(
@@ -1167,8 +1650,15 @@ impl DeviceRegistry {
                 .await;
             return Err(err);
         }
-        self.devices
-            .insert(registered.config.id.clone(), registered);
+        let device_id = registered.config.id.clone();
+        if !registered.status.events().is_attached() {
+            registered
+                .status
+                .events()
+                .attach(&device_id, self.device_events.clone());
+        }
+        self.devices.insert(device_id.clone(), registered);
+        self.after_register(&device_id).await;
         Ok(())
     }
 
@@ -1198,6 +1688,7 @@ impl DeviceRegistry {
                 config.id
             )));
         }
+        self.ensure_not_alias(&config.id)?;
 
         let registered = self
             .create_registered_plugin(config, driver)
@@ -1236,6 +1727,9 @@ impl DeviceRegistry {
     /// This method is thread-safe and can be called concurrently.
     pub async fn unregister(&self, id: &str) -> Result<bool, DaqError> {
         if let Some((_, device)) = self.devices.remove(id) {
+            self.uncorrected.remove(id);
+            self.simulated_ids.remove(id);
+            StateTracker::global().remove(MachineKind::Device, id);
             let driver_type = device.driver_type.clone();
             self.run_on_unregister(&device.config.id, &driver_type, &device.lifecycle)
                 .await?;
@@ -1284,11 +1778,28 @@ impl DeviceRegistry {
                     // not the config's DriverType enum (which may be synthetic)
                     capabilities: d.capabilities(),
                     metadata: d.metadata.clone(),
+                    aliases: self.aliases_for_device(&d.config.id),
+                    tags: self.device_tags(&d.config.id),
+                    simulated: self.is_simulated(&d.config.id),
+                    status: d.status.get(),
                 }
             })
             .collect()
     }
 
+    /// Configurations of all registered devices, sorted by ID
+    ///
+    /// Used to compare the running device set against a desired configuration.
+    pub fn device_configs(&self) -> Vec<DeviceConfig> {
+        let mut configs: Vec<DeviceConfig> = self
+            .devices
+            .iter()
+            .map(|entry| entry.value().config.clone())
+            .collect();
+        configs.sort_by(|a, b| a.id.cmp(&b.id));
+        configs
+    }
+
     /// Record a registration failure for debugging
     ///
     /// Called when a device fails to register, allowing the failure to be
@@ -1333,7 +1844,7 @@ impl DeviceRegistry {
 
     /// Get device info by ID
     pub fn get_device_info(&self, id: &str) -> Option<DeviceInfo> {
-        self.devices.get(id).map(|d| DeviceInfo {
+        self.device_entry(id).map(|d| DeviceInfo {
             id: d.config.id.clone(),
             name: d.config.name.clone(),
             driver_type: d.driver_type.clone(),
@@ -1341,12 +1852,78 @@ impl DeviceRegistry {
             // not the config's DriverType enum (which may be synthetic)
             capabilities: d.capabilities(),
             metadata: d.metadata.clone(),
+            aliases: self.aliases_for_device(&d.config.id),
+            tags: self.device_tags(&d.config.id),
+            simulated: self.is_simulated(&d.config.id),
+            status: d.status.get(),
         })
     }
 
-    /// Check if a device is registered
+    /// Operational status of a device (by ID or channel alias)
+    pub fn device_status(&self, id: &str) -> Option<DeviceStatus> {
+        self.device_entry(id).map(|d| d.status.get())
+    }
+
+    /// Set a device's status, returning the previous one (None if the
+    /// device isn't registered)
+    ///
+    /// Drivers normally report through the `DeviceStatusHandle` they passed
+    /// in `DeviceComponents`; this is for the registry's own observations
+    /// and the health monitor, and overrides the driver until it next
+    /// reports.
+    pub fn set_device_status(&self, id: &str, status: DeviceStatus) -> Option<DeviceStatus> {
+        let device = self.device_entry(id)?;
+        let previous = device.status.set(status.clone());
+        if previous != status {
+            tracing::info!(device_id = %device.config.id, from = %previous, to = %status, "Device status changed");
+        }
+        Some(previous)
+    }
+
+    /// Apply a health monitor error to the device named in its `device_id`
+    /// context: warnings and errors degrade the device, critical errors
+    /// fault it (with the `code` context, or 0). Returns whether a device
+    /// status was set.
+    pub fn apply_health_error(&self, error: &HealthError) -> bool {
+        let Some(device_id) = error.context.get("device_id") else {
+            return false;
+        };
+        let status = match error.severity {
+            ErrorSeverity::Info => return false,
+            ErrorSeverity::Warning | ErrorSeverity::Error => DeviceStatus::Degraded {
+                reason: error.message.clone(),
+            },
+            ErrorSeverity::Critical => DeviceStatus::Fault {
+                code: error
+                    .context
+                    .get("code")
+                    .and_then(|code| code.parse().ok())
+                    .unwrap_or(0),
+                message: error.message.clone(),
+            },
+        };
+        self.set_device_status(device_id, status).is_some()
+    }
+
+    /// Capability traits a device implements, with trait metadata and
+    /// device-specific details (by ID or channel alias)
+    pub fn introspect_capabilities(&self, id: &str) -> Option<Vec<CapabilityReport>> {
+        let device = self.device_entry(id)?;
+        Some(
+            device
+                .capabilities()
+                .into_iter()
+                .map(|capability| CapabilityReport {
+                    descriptor: capability.descriptor(),
+                    details: device.capability_details(capability),
+                })
+                .collect(),
+        )
+    }
+
+    /// Check if a device is registered (by ID or channel alias)
     pub fn contains(&self, id: &str) -> bool {
-        self.devices.contains_key(id)
+        self.device_entry(id).is_some()
     }
 
     /// Get count of registered devices
@@ -1360,272 +1937,1572 @@ impl DeviceRegistry {
     }
 
     // =========================================================================
-    // Capability Access
+    // Channel Aliases
     // =========================================================================
 
-    /// Get a device as Movable (if it supports this capability)
-    pub fn get_movable(&self, id: &str) -> Option<Arc<dyn Movable>> {
-        self.devices.get(id).and_then(|d| d.movable.clone())
+    /// Replace the channel alias table
+    ///
+    /// Fails if an alias shadows a registered device ID. Aliases whose target
+    /// device is not registered are kept (the device may register later) and
+    /// logged as warnings.
+    pub fn set_aliases(&self, aliases: ChannelAliases) -> Result<(), DaqError> {
+        let device_ids: Vec<DeviceId> = self.devices.iter().map(|e| e.key().clone()).collect();
+        let dangling = aliases.validate(device_ids.iter().map(String::as_str))?;
+        for alias in dangling {
+            tracing::warn!(
+                alias = %alias,
+                alias_target = aliases.target(&alias).unwrap_or_default(),
+                "Channel alias targets an unregistered device"
+            );
+        }
+        *self.aliases.write().unwrap_or_else(|p| p.into_inner()) = aliases;
+        Ok(())
     }
 
-    /// Get a device as Readable (if it supports this capability)
-    pub fn get_readable(&self, id: &str) -> Option<Arc<dyn Readable>> {
-        self.devices.get(id).and_then(|d| d.readable.clone())
+    /// Current channel alias table
+    pub fn aliases(&self) -> ChannelAliases {
+        self.aliases
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
     }
 
-    /// Get a device as Triggerable (if it supports this capability)
-    pub fn get_triggerable(&self, id: &str) -> Option<Arc<dyn Triggerable>> {
-        self.devices.get(id).and_then(|d| d.triggerable.clone())
+    /// Resolve a channel name (alias or device ID) to its hardware channel
+    pub fn resolve_channel(&self, name: &str) -> ChannelRef {
+        self.aliases
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .resolve(name)
     }
 
-    /// Get a device as FrameProducer (if it supports this capability)
-    pub fn get_frame_producer(&self, id: &str) -> Option<Arc<dyn FrameProducer>> {
-        self.devices.get(id).and_then(|d| d.frame_producer.clone())
+    /// Aliases targeting a device (including parameter-level aliases)
+    pub fn aliases_for_device(&self, device_id: &str) -> Vec<String> {
+        self.aliases
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .aliases_for_device(device_id)
+            .map(str::to_string)
+            .collect()
     }
 
-    /// Get MeasurementSource (frames) capability for a device (if supported)
-    pub fn get_measurement_source_frame(
-        &self,
-        id: &str,
-    ) -> Option<Arc<dyn MeasurementSource<Output = Arc<Frame>, Error = anyhow::Error>>> {
-        self.devices.get(id).and_then(|d| d.source_frame.clone())
-    }
+    // =========================================================================
+    // Display Calibration
+    // =========================================================================
 
-    /// Get a device as ExposureControl (if it supports this capability)
-    pub fn get_exposure_control(&self, id: &str) -> Option<Arc<dyn ExposureControl>> {
-        self.devices
-            .get(id)
-            .and_then(|d| d.exposure_control.clone())
+    /// Replace the display calibration table
+    pub fn set_display_calibration(&self, calibration: DisplayCalibration) {
+        *self
+            .display_calibration
+            .write()
+            .unwrap_or_else(|p| p.into_inner()) = calibration;
     }
 
-    /// Get Stageable capability for a device
-    pub fn get_stageable(&self, device_id: &str) -> Option<Arc<dyn Stageable>> {
-        self.devices
-            .get(device_id)
-            .and_then(|d| d.stageable.clone())
+    /// Current display transforms keyed by channel name
+    pub fn display_transforms(&self) -> BTreeMap<String, DisplayTransform> {
+        self.display_calibration
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .transforms()
     }
 
-    /// Get parameterized trait for a device (bd-9clg)
-    ///
-    /// Enables generic code (gRPC, presets, HDF5 writers) to enumerate and subscribe
-    /// to device parameters. Returns None if device doesn't implement Parameterized.
-    ///
-    /// # Example
-    ///
-    /// ```rust,ignore
-    /// if let Some(parameterized) = registry.get_parameterized("mock_camera") {
-    ///     let params = parameterized.parameters();
-    ///     for name in params.names() {
-    ///         println!("Parameter: {}", name);
-    ///     }
-    /// }
-    /// ```
+    /// Set (or with `None`, remove) the display transform of a channel
     ///
-    /// # Thread Safety (bd-pf31)
-    /// Returns an Arc that can be used outside the registry lock scope.
-    pub fn get_parameterized(&self, device_id: &str) -> Option<Arc<dyn Parameterized>> {
-        self.devices
-            .get(device_id)
-            .and_then(|d| d.parameterized.clone())
+    /// The change is saved and outlives a restart. Stored data is unaffected.
+    pub fn set_display_transform(
+        &self,
+        channel: &str,
+        transform: Option<DisplayTransform>,
+    ) -> Result<(), DaqError> {
+        self.display_calibration
+            .write()
+            .unwrap_or_else(|p| p.into_inner())
+            .set(channel, transform)
+            .map_err(|e| DaqError::Configuration(format!("{:#}", e)))
     }
 
-    /// Get a device as ShutterControl (if it supports this capability)
-    pub fn get_shutter_control(&self, id: &str) -> Option<Arc<dyn ShutterControl>> {
-        self.devices.get(id).and_then(|d| d.shutter_control.clone())
+    /// Select the channels attached to every acquired frame
+    pub fn set_frame_enrichment(&self, config: FrameEnrichmentConfig) {
+        *self
+            .frame_enrichment
+            .write()
+            .unwrap_or_else(|p| p.into_inner()) = config;
     }
 
-    /// Get a device as EmissionControl (if it supports this capability)
-    pub fn get_emission_control(&self, id: &str) -> Option<Arc<dyn EmissionControl>> {
-        self.devices
-            .get(id)
-            .and_then(|d| d.emission_control.clone())
+    /// Current frame enrichment configuration
+    pub fn frame_enrichment(&self) -> FrameEnrichmentConfig {
+        self.frame_enrichment
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
     }
 
-    /// Get a device as WavelengthTunable (if it supports this capability) - bd-pwjo
-    pub fn get_wavelength_tunable(&self, id: &str) -> Option<Arc<dyn WavelengthTunable>> {
-        self.devices
-            .get(id)
-            .and_then(|d| d.wavelength_tunable.clone())
+    /// Overlay annotations of every camera
+    pub fn frame_annotations(&self) -> FrameAnnotationStore {
+        self.frame_annotations.clone()
     }
 
-    /// Get a device as Settable (if it supports this capability)
-    pub fn get_settable(&self, id: &str) -> Option<Arc<dyn Settable>> {
-        self.devices.get(id).and_then(|d| d.settable.clone())
+    /// Replace the overlay annotations of a camera (an empty set clears them)
+    pub fn set_frame_annotations(
+        &self,
+        device_id: &str,
+        annotations: Vec<FrameAnnotation>,
+    ) -> Result<(), DaqError> {
+        if self.get_frame_producer(device_id).is_none() {
+            return Err(DaqError::Configuration(format!(
+                "Device '{}' not found or not a camera",
+                device_id
+            )));
+        }
+        self.frame_annotations
+            .set(device_id, annotations)
+            .map_err(|e| DaqError::Configuration(format!("{:#}", e)))
     }
 
-    /// Get a device as Commandable (if it supports this capability)
-    pub fn get_commandable(&self, id: &str) -> Option<Arc<dyn Commandable>> {
-        self.devices.get(id).and_then(|d| d.commandable.clone())
+    /// Replace the configured document transforms (plugin transforms are kept)
+    pub fn set_document_transforms(&self, configs: &[DocumentTransformConfig]) {
+        self.document_transforms.set_config(configs);
     }
 
-    /// Get all devices that support a specific capability
+    /// Document transform chains, shared with the document consumers
     ///
-    /// # Thread Safety (bd-pf31)
-    /// This method iterates over all devices with fine-grained locking per entry.
-    pub fn devices_with_capability(&self, capability: Capability) -> Vec<DeviceId> {
-        self.devices
-            .iter()
-            .filter(|entry| {
-                entry
-                    .value()
-                    .config
-                    .driver
-                    .capabilities()
-                    .contains(&capability)
-            })
-            .map(|entry| entry.key().clone())
-            .collect()
+    /// Plugins register their own transforms here.
+    pub fn document_transforms(&self) -> Arc<DocumentTransforms> {
+        self.document_transforms.clone()
     }
 
-    // =========================================================================
-    // Device Instantiation (Private)
-    // =========================================================================
+    /// Start ramping `parameter` of `device_id` to a new setpoint
+    ///
+    /// `position` ramps a Movable; any other name a numeric Settable
+    /// parameter. Output limits apply to every intermediate setpoint.
+    pub async fn start_ramp(
+        &self,
+        device_id: &str,
+        parameter: &str,
+        spec: RampSpec,
+    ) -> Result<RampHandle> {
+        let output = if parameter == POSITION_PARAMETER {
+            self.get_movable(device_id).map(RampOutput::Position)
+        } else {
+            self.get_settable(device_id)
+                .map(|device| RampOutput::Parameter {
+                    device,
+                    parameter: parameter.to_string(),
+                })
+        };
+        let output =
+            output.ok_or_else(|| anyhow!("Device '{}' cannot ramp '{}'", device_id, parameter))?;
+        self.ramps.start(device_id, parameter, output, spec).await
+    }
 
-    /// Instantiate a device from configuration
-    async fn instantiate_device(&self, config: DeviceConfig) -> Result<RegisteredDevice> {
-        // Clone driver before matching to avoid borrow issues
-        let driver_type_name = config.driver.driver_name().to_string();
-        let driver = config.driver.clone();
+    /// Ramps running on this registry's devices
+    pub fn ramps(&self) -> &RampManager {
+        &self.ramps
+    }
 
-        match driver {
-            DriverType::MockStage { initial_position } => {
-                let driver = Arc::new(crate::drivers::mock::MockStage::with_position(
-                    initial_position,
-                ));
-                Ok(RegisteredDevice {
-                    config,
-                    driver_type: driver_type_name.clone(),
-                    movable: Some(driver.clone()),
-                    readable: None,
-                    triggerable: None,
-                    frame_producer: None,
-                    source_frame: None,
-                    exposure_control: None,
-                    settable: None,
-                    stageable: None,
-                    commandable: None,
-                    parameterized: Some(driver.clone()),
-                    shutter_control: None,
-                    emission_control: None,
-                    wavelength_tunable: None,
-                    lifecycle: None,
-                    metadata: DeviceMetadata {
-                        position_units: Some("mm".to_string()),
-                        min_position: Some(-100.0),
-                        max_position: Some(100.0),
-                        ..Default::default()
-                    },
-                })
-            }
+    /// Set frame preprocessing per camera ID
+    pub fn set_preprocessing(&self, preprocessing: HashMap<String, PreprocessingConfig>) {
+        *self
+            .preprocessing
+            .write()
+            .unwrap_or_else(|p| p.into_inner()) = preprocessing;
+    }
 
-            DriverType::MockPowerMeter { reading } => {
-                let driver = Arc::new(crate::drivers::mock::MockPowerMeter::new(reading));
-                Ok(RegisteredDevice {
-                    config,
-                    driver_type: driver_type_name.clone(),
-                    movable: None,
-                    readable: Some(driver.clone()),
-                    triggerable: None,
-                    frame_producer: None,
-                    source_frame: None,
-                    exposure_control: None,
-                    settable: None,
-                    stageable: None,
-                    commandable: None,
-                    parameterized: Some(driver.clone()),
-                    shutter_control: None,
-                    emission_control: None,
-                    wavelength_tunable: None,
-                    lifecycle: None,
-                    metadata: DeviceMetadata {
-                        measurement_units: Some("W".to_string()),
-                        ..Default::default()
-                    },
-                })
-            }
+    /// Frame preprocessing configured for a camera
+    pub fn preprocessing_for(&self, device_id: &str) -> Option<PreprocessingConfig> {
+        self.preprocessing
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(device_id)
+            .cloned()
+    }
 
-            DriverType::MockCamera { width, height } => {
-                let driver = Arc::new(crate::drivers::mock::MockCamera::new(width, height));
-                Ok(RegisteredDevice {
-                    config,
-                    driver_type: driver_type_name.clone(),
-                    movable: None,
-                    readable: None,
-                    triggerable: Some(driver.clone()),
-                    frame_producer: Some(driver.clone()),
-                    source_frame: Some(driver.clone()),
-                    exposure_control: Some(driver.clone()),
-                    settable: None,
-                    stageable: Some(driver.clone()),
-                    commandable: None,
-                    parameterized: Some(driver.clone()),
-                    shutter_control: None,
-                    emission_control: None,
-                    wavelength_tunable: None,
-                    lifecycle: None,
-                    metadata: DeviceMetadata {
-                        frame_width: Some(width),
-                        frame_height: Some(height),
-                        ..Default::default()
-                    },
-                })
+    /// Set device tags keyed by device ID
+    pub fn set_device_tags(&self, tags: HashMap<String, Vec<String>>) {
+        *self.device_tags.write().unwrap_or_else(|p| p.into_inner()) = tags;
+    }
+
+    /// Tags configured for a device
+    pub fn device_tags(&self, device_id: &str) -> Vec<String> {
+        self.device_tags
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(device_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Set parameter policies keyed by device ID, then parameter name
+    pub fn set_parameter_policies(
+        &self,
+        policies: HashMap<String, HashMap<String, ParameterPolicy>>,
+    ) {
+        *self
+            .parameter_policies
+            .write()
+            .unwrap_or_else(|p| p.into_inner()) = policies;
+    }
+
+    /// Configured policy for a device parameter
+    pub fn parameter_policy(&self, device_id: &str, parameter: &str) -> Option<ParameterPolicy> {
+        self.parameter_policies
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(device_id)
+            .and_then(|params| params.get(parameter))
+            .cloned()
+    }
+
+    /// Replace the motion corrections, re-wrapping affected Movable devices
+    ///
+    /// Devices registered later pick up their correction on registration.
+    pub fn set_motion_corrections(
+        &self,
+        corrections: HashMap<String, MotionCorrection>,
+    ) -> Result<(), DaqError> {
+        for (device_id, correction) in &corrections {
+            correction.validate().map_err(|e| {
+                DaqError::Configuration(format!(
+                    "Invalid motion correction for '{}': {}",
+                    device_id, e
+                ))
+            })?;
+        }
+        let previous = std::mem::replace(
+            &mut *self
+                .motion_corrections
+                .write()
+                .unwrap_or_else(|p| p.into_inner()),
+            corrections,
+        );
+        let current: Vec<String> = self
+            .motion_corrections
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .keys()
+            .cloned()
+            .collect();
+        for device_id in previous.keys().chain(&current) {
+            self.apply_motion_correction(device_id);
+        }
+        Ok(())
+    }
+
+    /// Replace the output limits, keyed by device ID then parameter name
+    /// ([`POSITION_PARAMETER`] for Movable devices)
+    ///
+    /// Limits are enforced on the Movable and Settable handles returned by
+    /// the registry, on top of any motion correction, and on parameters set
+    /// through [`Self::set_parameter_json`].
+    pub fn set_output_limits(
+        &self,
+        limits: HashMap<String, HashMap<String, OutputLimit>>,
+    ) -> Result<(), DaqError> {
+        let mut limiters = Vec::with_capacity(limits.len());
+        for (device_id, params) in limits {
+            let limiter =
+                OutputLimiter::new(device_id.clone(), params, self.limit_violations.clone())
+                    .map_err(|e| DaqError::Configuration(format!("Invalid output limit: {}", e)))?;
+            limiters.push((device_id, Arc::new(limiter)));
+        }
+        self.output_limiters.clear();
+        for (device_id, limiter) in limiters {
+            self.output_limiters.insert(device_id, limiter);
+        }
+        Ok(())
+    }
+
+    /// Output limit configured for a device parameter
+    pub fn output_limit(&self, device_id: &str, parameter: &str) -> Option<OutputLimit> {
+        self.output_limiters
+            .get(device_id)
+            .and_then(|limiter| limiter.limit(parameter).cloned())
+    }
+
+    /// Replace the keep-out zones of Movable devices, keyed by device ID
+    ///
+    /// Only `range` zones apply to single devices; polygons belong to
+    /// motion groups. Zones are enforced outside the output limits, on the
+    /// requested target, both on the Movable handle and on position
+    /// parameters set through [`Self::set_parameter_json`].
+    pub fn set_keep_out_zones(
+        &self,
+        zones: HashMap<String, Vec<KeepOutZone>>,
+    ) -> Result<(), DaqError> {
+        for (device_id, device_zones) in &zones {
+            for zone in device_zones {
+                zone.validate().map_err(|e| {
+                    DaqError::Configuration(format!("Keep-out zone of '{}': {}", device_id, e))
+                })?;
+                if zone.range.is_none() {
+                    return Err(DaqError::Configuration(format!(
+                        "Keep-out zone {} of '{}' is a polygon; configure it on a motion group",
+                        zone, device_id
+                    )));
+                }
+            }
+        }
+        self.keep_out_zones.clear();
+        for (device_id, device_zones) in zones {
+            if !device_zones.is_empty() {
+                self.keep_out_zones
+                    .insert(device_id, Arc::new(device_zones));
             }
+        }
+        Ok(())
+    }
 
-            #[cfg(feature = "serial")]
-            DriverType::Plugin { plugin_id, address } => {
-                self.instantiate_plugin_device(config, &plugin_id, &address)
-                    .await
+    /// Keep-out zones configured for a device
+    pub fn keep_out_zones(&self, device_id: &str) -> Vec<KeepOutZone> {
+        self.keep_out_zones
+            .get(device_id)
+            .map(|zones| zones.as_ref().clone())
+            .unwrap_or_default()
+    }
+
+    /// Replace the rules checked against every point of a run
+    pub fn set_validation_rules(&self, rules: Vec<ValidationRule>) -> Result<(), DaqError> {
+        validation::validate_rules(&rules).map_err(|e| DaqError::Configuration(e.to_string()))?;
+        *self
+            .validation_rules
+            .write()
+            .unwrap_or_else(|p| p.into_inner()) = rules;
+        Ok(())
+    }
+
+    /// Rules checked against every point of a run
+    pub fn validation_rules(&self) -> Vec<ValidationRule> {
+        self.validation_rules
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+
+    /// Replace the parking actions, keyed by device ID
+    pub fn set_parking_actions(&self, actions: HashMap<String, ParkingActions>) {
+        *self
+            .parking_actions
+            .write()
+            .unwrap_or_else(|p| p.into_inner()) = actions;
+    }
+
+    /// Parking actions configured for each device
+    pub fn parking_actions(&self) -> HashMap<String, ParkingActions> {
+        self.parking_actions
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+
+    /// Replace the timestamp corrections (see `common::time_sync`)
+    pub fn set_timestamp_corrections(&self, corrections: TimestampCorrections) {
+        *self
+            .timestamp_corrections
+            .write()
+            .unwrap_or_else(|p| p.into_inner()) = corrections;
+    }
+
+    /// Set one device's timestamp offset in nanoseconds (0 removes it)
+    pub fn set_timestamp_offset(&self, device_id: &str, offset_ns: i64) {
+        self.timestamp_corrections
+            .write()
+            .unwrap_or_else(|p| p.into_inner())
+            .set(device_id, offset_ns);
+    }
+
+    /// Offsets subtracted from each device's timestamps
+    pub fn timestamp_corrections(&self) -> TimestampCorrections {
+        self.timestamp_corrections
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+
+    /// The capabilities parking actions can use on a device
+    pub fn parking_target(&self, id: &str) -> ParkingTarget {
+        ParkingTarget {
+            shutter: self.get_shutter_control(id),
+            movable: self.get_movable(id),
+            frame_producer: self.get_frame_producer(id),
+            stageable: self.get_stageable(id),
+            triggerable: self.get_triggerable(id),
+            settable: self.get_settable(id),
+        }
+    }
+
+    /// Subscribe to setpoints refused or clamped by output limits
+    pub fn subscribe_limit_violations(&self) -> tokio::sync::broadcast::Receiver<LimitViolation> {
+        self.limit_violations.subscribe()
+    }
+
+    /// Subscribe to the events of every registered device
+    pub fn subscribe_device_events(&self) -> tokio::sync::broadcast::Receiver<DeviceEvent> {
+        self.device_events.subscribe()
+    }
+
+    /// Motion correction configured for a device
+    pub fn motion_correction(&self, device_id: &str) -> Option<MotionCorrection> {
+        self.motion_corrections
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(device_id)
+            .cloned()
+    }
+
+    /// Wrap a device's Movable in its configured correction, or restore the
+    /// driver's Movable if it no longer has one
+    fn apply_motion_correction(&self, device_id: &str) {
+        let correction = self.motion_correction(device_id);
+        let Some(mut device) = self.devices.get_mut(device_id) else {
+            return;
+        };
+        let original = match self.uncorrected.get(device_id) {
+            Some(original) => original.clone(),
+            None => match &device.movable {
+                Some(movable) => UncorrectedMovable {
+                    movable: Arc::clone(movable),
+                    min_position: device.metadata.min_position,
+                    max_position: device.metadata.max_position,
+                },
+                None => return,
+            },
+        };
+
+        let Some(correction) = correction else {
+            if self.uncorrected.remove(device_id).is_some() {
+                device.movable = Some(original.movable);
+                device.metadata.min_position = original.min_position;
+                device.metadata.max_position = original.max_position;
+                device.metadata.position_correction = None;
             }
+            return;
+        };
+        let corrected =
+            match CorrectedMovable::new(Arc::clone(&original.movable), correction.clone()) {
+                Ok(corrected) => corrected,
+                Err(e) => {
+                    tracing::warn!(device_id, "Motion correction not applied: {}", e);
+                    return;
+                }
+            };
+        let (min_position, max_position) =
+            correction.range_from_raw(original.min_position, original.max_position);
+        device.movable = Some(Arc::new(corrected));
+        device.metadata.min_position = min_position;
+        device.metadata.max_position = max_position;
+        device.metadata.position_correction = Some(correction);
+        self.uncorrected.insert(device_id.to_string(), original);
+        tracing::info!(device_id, "Motion correction applied");
+    }
 
-            #[cfg(feature = "pvcam")]
-            DriverType::Pvcam { camera_name } => {
-                let driver = Arc::new(
-                    crate::drivers::pvcam::PvcamDriver::new_async(camera_name.clone()).await?,
+    /// Replace the configured initialization recipes
+    pub fn set_recipes(&self, recipes: Vec<InitRecipe>) {
+        *self.recipes.write().unwrap_or_else(|p| p.into_inner()) = recipes;
+    }
+
+    /// Configured initialization recipes
+    pub fn recipes(&self) -> Vec<InitRecipe> {
+        self.recipes
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+
+    /// Replace the configured warm-up schedules
+    pub fn set_warmups(&self, warmups: Vec<WarmupSchedule>) {
+        *self.warmups.write().unwrap_or_else(|p| p.into_inner()) = warmups;
+    }
+
+    /// Configured warm-up schedules
+    pub fn warmups(&self) -> Vec<WarmupSchedule> {
+        self.warmups
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+
+    /// Replace the self-test settings
+    pub fn set_self_test(&self, config: SelfTestConfig) {
+        *self.self_test.write().unwrap_or_else(|p| p.into_inner()) = config;
+    }
+
+    /// Self-test settings
+    pub fn self_test(&self) -> SelfTestConfig {
+        self.self_test
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+
+    /// Replace the inventory settings
+    pub fn set_inventory(&self, config: InventoryConfig) {
+        *self.inventory.write().unwrap_or_else(|p| p.into_inner()) = config;
+    }
+
+    /// Inventory settings
+    pub fn inventory(&self) -> InventoryConfig {
+        self.inventory
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+
+    /// Result of the last inventory refresh, if one has run
+    pub fn inventory_report(&self) -> Option<InventoryReport> {
+        self.inventory_report
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+
+    /// Read the identity of every device.
+    ///
+    /// Uses the identity the driver reports if it has one, otherwise asks
+    /// `*IDN?` over the raw command channel. Devices with neither, or whose
+    /// query fails, are left out.
+    pub async fn collect_inventory(&self) -> Vec<InventoryEntry> {
+        let timeout = self.inventory().timeout();
+        let candidates: Vec<_> = self
+            .devices
+            .iter()
+            .map(|d| {
+                (
+                    d.key().clone(),
+                    d.driver_type.clone(),
+                    d.metadata.identity.clone(),
+                    d.raw_terminal.clone(),
+                )
+            })
+            .collect();
+
+        let mut entries = Vec::new();
+        for (device, driver_type, identity, terminal) in candidates {
+            let (identity, source) = match (identity, terminal) {
+                (Some(identity), _) => (identity, IdentitySource::Driver),
+                (None, Some(terminal)) => {
+                    match tokio::time::timeout(timeout, terminal.send_raw("*IDN?", true)).await {
+                        Ok(Ok(response)) => match DeviceIdentity::from_idn(&response) {
+                            Some(identity) => (identity, IdentitySource::Idn),
+                            None => {
+                                tracing::warn!(device = %device, "Empty *IDN? response");
+                                continue;
+                            }
+                        },
+                        Ok(Err(e)) => {
+                            tracing::warn!(device = %device, error = %e, "*IDN? failed");
+                            continue;
+                        }
+                        Err(_) => {
+                            tracing::warn!(device = %device, "*IDN? timed out after {:?}", timeout);
+                            continue;
+                        }
+                    }
+                }
+                (None, None) => continue,
+            };
+            entries.push(InventoryEntry {
+                device,
+                driver_type,
+                identity,
+                source,
+                collected_ns: common::experiment::document::now_ns(),
+            });
+        }
+        entries.sort_by(|a, b| a.device.cmp(&b.device));
+        entries
+    }
+
+    /// Collect identities, compare them with the stored inventory and save.
+    ///
+    /// Identity changes since the last refresh (firmware updates, swapped
+    /// units) are logged as warnings and returned in the report.
+    pub async fn refresh_inventory(&self) -> Result<InventoryReport> {
+        let entries = self.collect_inventory().await;
+
+        // Nothing to record (e.g. only simulated devices): leave the file alone
+        let mut changes = Vec::new();
+        if !entries.is_empty() {
+            let path = self.inventory().path();
+            let mut inventory = Inventory::load(&path)?;
+            changes = inventory.update(&entries);
+            for change in &changes {
+                tracing::warn!(
+                    device = %change.device,
+                    firmware_changed = change.firmware_changed(),
+                    "Instrument identity changed since last run: {}",
+                    change
                 );
-                let (width, height) = driver.resolution();
-                Ok(RegisteredDevice {
-                    config,
-                    driver_type: driver_type_name.clone(),
-                    movable: None,
-                    readable: None,
-                    triggerable: Some(driver.clone()),
-                    frame_producer: Some(driver.clone()),
-                    source_frame: Some(driver.clone()),
-                    exposure_control: Some(driver.clone()),
-                    settable: None,
-                    stageable: None,
-                    commandable: Some(driver.clone()),
-                    parameterized: Some(driver.clone()),
-                    shutter_control: None,
-                    emission_control: None,
-                    wavelength_tunable: None,
-                    lifecycle: None,
-                    metadata: DeviceMetadata {
-                        frame_width: Some(width),
-                        frame_height: Some(height),
-                        ..Default::default()
-                    },
-                })
             }
+            inventory.save(&path)?;
+        }
 
-            #[cfg(feature = "comedi")]
-            DriverType::Comedi { device_path } => {
-                let device =
-                    crate::drivers::comedi::ComediDevice::open(&device_path).map_err(|e| {
-                        DaqError::Instrument(format!("Failed to open Comedi device: {}", e))
-                    })?;
-                let _info = device.info().map_err(|e| {
-                    DaqError::Instrument(format!("Failed to get Comedi device info: {}", e))
-                })?;
-                // Note: Comedi doesn't implement full HAL traits directly;
-                // subsystems (AnalogInput, AnalogOutput, etc.) need to be accessed separately.
-                // For registry purposes, we register the device as having Readable/Settable capabilities.
-                // TODO: Implement HAL trait wrappers for Comedi subsystems
-                Ok(RegisteredDevice {
-                    config,
-                    driver_type: driver_type_name.clone(),
-                    movable: None,
-                    readable: None,
+        let report = InventoryReport { entries, changes };
+        *self
+            .inventory_report
+            .write()
+            .unwrap_or_else(|p| p.into_inner()) = Some(report.clone());
+        Ok(report)
+    }
+
+    /// Latest report of a recipe, if it has run
+    pub fn recipe_report(&self, name: &str) -> Option<RecipeReport> {
+        self.recipe_reports.get(name).map(|r| r.clone())
+    }
+
+    /// Run one recipe by name
+    pub async fn run_recipe(
+        &self,
+        name: &str,
+        trigger: RecipeTrigger,
+    ) -> Result<RecipeReport, DaqError> {
+        let recipe = self
+            .recipes()
+            .into_iter()
+            .find(|r| r.name == name)
+            .ok_or_else(|| DaqError::Configuration(format!("Unknown init recipe '{}'", name)))?;
+        let report = crate::recipes::run_recipe(&recipe, self, trigger).await;
+        self.recipe_reports
+            .insert(recipe.name.clone(), report.clone());
+        Ok(report)
+    }
+
+    /// Run every recipe configured for `trigger`, in configuration order
+    ///
+    /// With `device_id`, only that device's recipes run. Failures are logged
+    /// and reported, not returned as errors.
+    pub async fn run_recipes(
+        &self,
+        trigger: RecipeTrigger,
+        device_id: Option<&str>,
+    ) -> Vec<RecipeReport> {
+        let recipes: Vec<InitRecipe> = self
+            .recipes()
+            .into_iter()
+            .filter(|r| r.runs_on(trigger) && device_id.is_none_or(|id| r.device == id))
+            .collect();
+        let mut reports = Vec::with_capacity(recipes.len());
+        for recipe in recipes {
+            let report = crate::recipes::run_recipe(&recipe, self, trigger).await;
+            self.recipe_reports.insert(recipe.name, report.clone());
+            reports.push(report);
+        }
+        reports
+    }
+
+    /// Applies the device's motion correction, and runs reconnect recipes
+    /// when a device ID is registered a second time
+    async fn after_register(&self, device_id: &str) {
+        if let Some(device) = self.devices.get(device_id) {
+            StateTracker::global().record(
+                MachineKind::Device,
+                device_id,
+                device.status.get().as_str(),
+                "registered",
+            );
+        }
+        // A (re)registered device has a fresh driver to correct
+        self.uncorrected.remove(device_id);
+        self.apply_motion_correction(device_id);
+        if !self.registered_ids.insert(device_id.to_string()) {
+            self.run_recipes(RecipeTrigger::Reconnect, Some(device_id))
+                .await;
+        }
+    }
+
+    /// Read the current numeric value of a channel (device ID, alias or
+    /// `device:parameter`)
+    ///
+    /// Parameter channels read the parameter, movable devices report their
+    /// position and readable devices are read.
+    pub async fn read_channel_value(&self, name: &str) -> Result<f64> {
+        let channel = self.resolve_channel(name);
+        if let Some(parameter) = &channel.parameter {
+            let parameterized = self
+                .get_parameterized(&channel.device_id)
+                .ok_or_else(|| anyhow!("Channel '{}' has no parameters", name))?;
+            let params = parameterized.parameters();
+            let param = params
+                .get(parameter)
+                .ok_or_else(|| anyhow!("Channel '{}': unknown parameter '{}'", name, parameter))?;
+            let value = self.get_parameter_json(&channel.device_id, param)?;
+            return value.as_f64().ok_or_else(|| {
+                anyhow!(
+                    "Channel '{}': parameter '{}' is not numeric",
+                    name,
+                    parameter
+                )
+            });
+        }
+        if let Some(movable) = self.get_movable(&channel.device_id) {
+            return movable.position().await;
+        }
+        if let Some(readable) = self.get_readable(&channel.device_id) {
+            return readable.read().await;
+        }
+        Err(anyhow!("Channel '{}' is not readable", name))
+    }
+
+    /// Look up a device by ID, falling back to channel aliases
+    fn device_entry(
+        &self,
+        id: &str,
+    ) -> Option<dashmap::mapref::one::Ref<'_, DeviceId, RegisteredDevice>> {
+        self.devices
+            .get(id)
+            .or_else(|| self.devices.get(&self.resolve_channel(id).device_id))
+    }
+
+    /// Take the command locks of devices, waiting for their current holders
+    ///
+    /// Commands that change a device's state hold its lock, so a batch
+    /// holding the locks of all its devices runs without interleaving.
+    /// Hardware RPCs and the RunEngine's plan moves and parameter sets both
+    /// take it; reads and triggers don't.
+    /// Aliases lock their device. Locks are taken in device ID order, so
+    /// callers locking overlapping sets can't deadlock.
+    pub async fn lock_devices<I, S>(&self, ids: I) -> DeviceLocks
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let ids: std::collections::BTreeSet<DeviceId> = ids
+            .into_iter()
+            .map(|id| self.resolve_channel(id.as_ref()).device_id)
+            .collect();
+        let mut guards = Vec::with_capacity(ids.len());
+        for id in ids {
+            let lock = self.command_locks.entry(id).or_default().clone();
+            guards.push(lock.lock_owned().await);
+        }
+        DeviceLocks { _guards: guards }
+    }
+
+    fn ensure_not_alias(&self, id: &str) -> Result<(), DaqError> {
+        let aliases = self.aliases.read().unwrap_or_else(|p| p.into_inner());
+        if aliases.target(id).is_some() {
+            return Err(DaqError::Configuration(format!(
+                "Device ID '{}' is already used as a channel alias",
+                id
+            )));
+        }
+        Ok(())
+    }
+
+    // =========================================================================
+    // Capability Access
+    // =========================================================================
+
+    /// Get a device as Movable (if it supports this capability)
+    pub fn get_movable(&self, id: &str) -> Option<Arc<dyn Movable>> {
+        let device = self.device_entry(id)?;
+        let movable = device.movable.clone()?;
+        let movable: Arc<dyn Movable> = match self.output_limiters.get(&device.config.id) {
+            Some(limiter) if limiter.limit(POSITION_PARAMETER).is_some() => {
+                Arc::new(LimitedMovable::new(movable, limiter.clone()))
+            }
+            _ => movable,
+        };
+        match self.keep_out_zones.get(&device.config.id) {
+            Some(zones) => Some(Arc::new(KeepOutMovable::new(
+                movable,
+                device.config.id.clone(),
+                zones.clone(),
+            ))),
+            None => Some(movable),
+        }
+    }
+
+    /// Get a device as Readable (if it supports this capability)
+    pub fn get_readable(&self, id: &str) -> Option<Arc<dyn Readable>> {
+        self.device_entry(id).and_then(|d| d.readable.clone())
+    }
+
+    /// Get a device as Triggerable (if it supports this capability)
+    pub fn get_triggerable(&self, id: &str) -> Option<Arc<dyn Triggerable>> {
+        self.device_entry(id).and_then(|d| d.triggerable.clone())
+    }
+
+    /// Get a device as FrameProducer (if it supports this capability)
+    pub fn get_frame_producer(&self, id: &str) -> Option<Arc<dyn FrameProducer>> {
+        self.device_entry(id).and_then(|d| d.frame_producer.clone())
+    }
+
+    /// Get MeasurementSource (frames) capability for a device (if supported)
+    pub fn get_measurement_source_frame(
+        &self,
+        id: &str,
+    ) -> Option<Arc<dyn MeasurementSource<Output = Arc<Frame>, Error = anyhow::Error>>> {
+        self.device_entry(id).and_then(|d| d.source_frame.clone())
+    }
+
+    /// Get a device as ExposureControl (if it supports this capability)
+    pub fn get_exposure_control(&self, id: &str) -> Option<Arc<dyn ExposureControl>> {
+        self.device_entry(id)
+            .and_then(|d| d.exposure_control.clone())
+    }
+
+    /// Get Stageable capability for a device
+    pub fn get_stageable(&self, device_id: &str) -> Option<Arc<dyn Stageable>> {
+        self.device_entry(device_id)
+            .and_then(|d| d.stageable.clone())
+    }
+
+    /// Get parameterized trait for a device (bd-9clg)
+    ///
+    /// Enables generic code (gRPC, presets, HDF5 writers) to enumerate and subscribe
+    /// to device parameters. Returns None if device doesn't implement Parameterized.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// if let Some(parameterized) = registry.get_parameterized("mock_camera") {
+    ///     let params = parameterized.parameters();
+    ///     for name in params.names() {
+    ///         println!("Parameter: {}", name);
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Thread Safety (bd-pf31)
+    /// Returns an Arc that can be used outside the registry lock scope.
+    pub fn get_parameterized(&self, device_id: &str) -> Option<Arc<dyn Parameterized>> {
+        self.device_entry(device_id)
+            .and_then(|d| d.parameterized.clone())
+    }
+
+    /// Get a device as ShutterControl (if it supports this capability)
+    pub fn get_shutter_control(&self, id: &str) -> Option<Arc<dyn ShutterControl>> {
+        self.device_entry(id)
+            .and_then(|d| d.shutter_control.clone())
+    }
+
+    /// Get a device as EmissionControl (if it supports this capability)
+    pub fn get_emission_control(&self, id: &str) -> Option<Arc<dyn EmissionControl>> {
+        self.device_entry(id)
+            .and_then(|d| d.emission_control.clone())
+    }
+
+    /// Get a device as WavelengthTunable (if it supports this capability) - bd-pwjo
+    pub fn get_wavelength_tunable(&self, id: &str) -> Option<Arc<dyn WavelengthTunable>> {
+        self.device_entry(id)
+            .and_then(|d| d.wavelength_tunable.clone())
+    }
+
+    /// Set a parameter of a Parameterized device within its output limit
+    ///
+    /// Every write of a device parameter by name (gRPC, presets, recipes,
+    /// modules, plans) goes through here rather than `set_json`, so the
+    /// keep-out zones and limits enforced on the Movable and Settable handles
+    /// also hold for it. On a stage with a motion correction the position
+    /// parameter is written in corrected units, like the Movable handle.
+    pub async fn set_parameter_json(
+        &self,
+        device_id: &str,
+        parameter: &dyn ParameterBase,
+        value: serde_json::Value,
+    ) -> Result<()> {
+        let Some(device) = self.device_entry(device_id) else {
+            return parameter.set_json(value);
+        };
+        let config_id = device.config.id.clone();
+        drop(device);
+
+        // A position parameter moves the stage like `move_abs` does
+        if parameter.name() == POSITION_PARAMETER {
+            // A corrected stage's zones and limits are in corrected units,
+            // so the move goes through the same handle plans use
+            if self.motion_correction(&config_id).is_some() {
+                if let (Some(movable), Some(target)) =
+                    (self.get_movable(&config_id), value.as_f64())
+                {
+                    return movable.move_abs(target).await;
+                }
+            }
+            let zones = self
+                .keep_out_zones
+                .get(&config_id)
+                .map(|zones| zones.clone());
+            if let (Some(zones), Some(target)) = (zones, value.as_f64()) {
+                let current = parameter.get_json().ok().and_then(|v| v.as_f64());
+                check_move(
+                    &config_id,
+                    &zones,
+                    current.as_ref().map(std::slice::from_ref),
+                    &[target],
+                )?;
+            }
+        }
+
+        let limiter = self
+            .output_limiters
+            .get(&config_id)
+            .map(|limiter| limiter.clone());
+        match limiter {
+            Some(limiter) => limiter.set_parameter(parameter, value).await,
+            None => parameter.set_json(value),
+        }
+    }
+
+    /// Read a parameter of a Parameterized device
+    ///
+    /// The counterpart of [`Self::set_parameter_json`]: on a stage with a
+    /// motion correction the position parameter reads in corrected units.
+    pub fn get_parameter_json(
+        &self,
+        device_id: &str,
+        parameter: &dyn ParameterBase,
+    ) -> Result<serde_json::Value> {
+        let value = parameter.get_json()?;
+        if parameter.name() != POSITION_PARAMETER {
+            return Ok(value);
+        }
+        let Some(config_id) = self.device_entry(device_id).map(|d| d.config.id.clone()) else {
+            return Ok(value);
+        };
+        match (self.motion_correction(&config_id), value.as_f64()) {
+            (Some(correction), Some(raw)) => Ok(serde_json::json!(correction.from_raw(raw))),
+            _ => Ok(value),
+        }
+    }
+
+    /// Get a device as Settable (if it supports this capability)
+    pub fn get_settable(&self, id: &str) -> Option<Arc<dyn Settable>> {
+        let device = self.device_entry(id)?;
+        let settable = device.settable.clone()?;
+        match self.output_limiters.get(&device.config.id) {
+            Some(limiter) => Some(Arc::new(LimitedSettable::new(settable, limiter.clone()))),
+            None => Some(settable),
+        }
+    }
+
+    /// Get a device as Commandable (if it supports this capability)
+    pub fn get_commandable(&self, id: &str) -> Option<Arc<dyn Commandable>> {
+        self.device_entry(id).and_then(|d| d.commandable.clone())
+    }
+
+    /// Get a device as DiscretePositioner (if it supports this capability)
+    pub fn get_discrete_positioner(&self, id: &str) -> Option<Arc<dyn DiscretePositioner>> {
+        self.device_entry(id)
+            .and_then(|d| d.discrete_positioner.clone())
+    }
+
+    /// Get a device as RawTerminal (if it supports this capability)
+    pub fn get_raw_terminal(&self, id: &str) -> Option<Arc<dyn RawTerminal>> {
+        self.device_entry(id).and_then(|d| d.raw_terminal.clone())
+    }
+
+    /// Register a motion group over already-registered Movable devices
+    ///
+    /// The group itself is registered as a Commandable device under its ID
+    /// (group `move`, `position`, `wait_settled`, `stop`), and each group
+    /// axis as a Movable device `<group>_<axis>` that plans can scan like any
+    /// stage.
+    pub fn register_motion_group(&self, config: MotionGroupConfig) -> Result<(), DaqError> {
+        config
+            .validate()
+            .map_err(|e| DaqError::Configuration(e.to_string()))?;
+
+        let axis_ids: Vec<String> = config
+            .axis_names()
+            .iter()
+            .map(|axis| config.axis_device_id(axis))
+            .collect();
+        for id in std::iter::once(&config.id).chain(&axis_ids) {
+            if self.devices.contains_key(id) {
+                return Err(DaqError::Configuration(format!(
+                    "Device '{}' is already registered",
+                    id
+                )));
+            }
+            self.ensure_not_alias(id)?;
+        }
+
+        let mut members = Vec::with_capacity(config.members.len());
+        let mut member_units = None;
+        for member in &config.members {
+            let movable = self.get_movable(member).ok_or_else(|| {
+                DaqError::Configuration(format!(
+                    "Motion group '{}': member '{}' is not a registered Movable device",
+                    config.id, member
+                ))
+            })?;
+            members.push(movable);
+            member_units = member_units.or_else(|| {
+                self.device_entry(member)
+                    .and_then(|d| d.metadata.position_units.clone())
+            });
+        }
+
+        let group = Arc::new(
+            MotionGroup::new(config.clone(), members)
+                .map_err(|e| DaqError::Configuration(e.to_string()))?,
+        );
+        let name = if config.name.is_empty() {
+            config.id.clone()
+        } else {
+            config.name.clone()
+        };
+
+        let components = DeviceComponents::new()
+            .with_commandable(group.clone())
+            .with_metadata(common::driver::DeviceMetadata {
+                category: Some(DeviceCategory::Stage),
+                ..Default::default()
+            });
+        let registered = self.components_to_registered(
+            config.id.clone(),
+            name.clone(),
+            MOTION_GROUP_DRIVER_TYPE.to_string(),
+            components,
+        );
+        self.devices.insert(config.id.clone(), registered);
+
+        for (index, (axis, axis_id)) in group.axis_names().iter().zip(axis_ids).enumerate() {
+            let (min_position, max_position) = group.axis_limits(axis).unzip();
+            let units = match (&config.kinematics, axis.as_str()) {
+                (Kinematics::Polar { .. }, "theta") => Some("deg".to_string()),
+                _ => member_units.clone(),
+            };
+            let components = DeviceComponents::new()
+                .with_movable(Arc::new(group.axis(index)))
+                .with_metadata(common::driver::DeviceMetadata {
+                    category: Some(DeviceCategory::Stage),
+                    position_units: units,
+                    min_position,
+                    max_position,
+                    ..Default::default()
+                });
+            let registered = self.components_to_registered(
+                axis_id.clone(),
+                format!("{} {}", name, axis),
+                MOTION_GROUP_DRIVER_TYPE.to_string(),
+                components,
+            );
+            self.devices.insert(axis_id, registered);
+        }
+
+        tracing::info!(
+            group_id = %config.id,
+            axes = ?group.axis_names(),
+            "Motion group registered"
+        );
+        self.motion_groups.insert(config.id.clone(), group);
+        Ok(())
+    }
+
+    /// Get a motion group by ID
+    pub fn get_motion_group(&self, id: &str) -> Option<Arc<MotionGroup>> {
+        self.motion_groups.get(id).map(|g| Arc::clone(g.value()))
+    }
+
+    /// IDs of all registered motion groups
+    pub fn list_motion_groups(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.motion_groups.iter().map(|g| g.key().clone()).collect();
+        ids.sort();
+        ids
+    }
+
+    /// Register a named-slot positioner over a registered device
+    ///
+    /// The positioner drives a Movable (through its limits and corrections)
+    /// or the parameters of a Settable, and is registered as a
+    /// DiscretePositioner device under its own ID.
+    pub fn register_discrete_positioner(
+        &self,
+        config: DiscretePositionerConfig,
+    ) -> Result<(), DaqError> {
+        if self.devices.contains_key(&config.id) {
+            return Err(DaqError::Configuration(format!(
+                "Device '{}' is already registered",
+                config.id
+            )));
+        }
+        self.ensure_not_alias(&config.id)?;
+
+        let device = config.drive.device();
+        let output = match &config.drive {
+            PositionerDrive::Movable { .. } => {
+                self.get_movable(device).map(PositionerOutput::Movable)
+            }
+            PositionerDrive::Outputs { .. } => {
+                self.get_settable(device).map(PositionerOutput::Outputs)
+            }
+        }
+        .ok_or_else(|| {
+            DaqError::Configuration(format!(
+                "Positioner '{}': '{}' is not a registered device of the right kind",
+                config.id, device
+            ))
+        })?;
+
+        let name = if config.name.is_empty() {
+            config.id.clone()
+        } else {
+            config.name.clone()
+        };
+        let positioner = MappedPositioner::new(config.clone(), output)
+            .map_err(|e| DaqError::Configuration(e.to_string()))?;
+        let components = DeviceComponents::new().with_discrete_positioner(Arc::new(positioner));
+        let registered = self.components_to_registered(
+            config.id.clone(),
+            name,
+            DISCRETE_POSITIONER_DRIVER_TYPE.to_string(),
+            components,
+        );
+        self.devices.insert(config.id.clone(), registered);
+
+        tracing::info!(
+            positioner_id = %config.id,
+            device = %device,
+            positions = ?config.position_names(),
+            "Discrete positioner registered"
+        );
+        Ok(())
+    }
+
+    /// Register a composite trigger over registered devices
+    ///
+    /// Condition channels resolve through the channel aliases, so aliases
+    /// must be set first. The trigger is registered as a Triggerable device
+    /// under its own ID, with its software signals as Settable parameters.
+    pub fn register_composite_trigger(
+        &self,
+        config: CompositeTriggerConfig,
+    ) -> Result<(), DaqError> {
+        if self.devices.contains_key(&config.id) {
+            return Err(DaqError::Configuration(format!(
+                "Device '{}' is already registered",
+                config.id
+            )));
+        }
+        self.ensure_not_alias(&config.id)?;
+
+        let evaluator = TriggerEvaluator::compile(&config.condition)
+            .map_err(|e| DaqError::Configuration(format!("Trigger '{}': {}", config.id, e)))?;
+        let mut inputs = HashMap::new();
+        for channel in evaluator.channels() {
+            let ChannelRef {
+                device_id,
+                parameter,
+            } = self.resolve_channel(channel);
+            let input = match parameter {
+                Some(parameter) => self
+                    .get_parameterized(&device_id)
+                    .map(|device| TriggerInput::Parameter(device, parameter)),
+                None => self.get_readable(&device_id).map(TriggerInput::Readable),
+            }
+            .ok_or_else(|| {
+                DaqError::Configuration(format!(
+                    "Trigger '{}': channel '{}' is not a readable device or parameter",
+                    config.id, channel
+                ))
+            })?;
+            inputs.insert(channel.clone(), input);
+        }
+        let target = match &config.target {
+            Some(target) => Some(self.get_triggerable(target).ok_or_else(|| {
+                DaqError::Configuration(format!(
+                    "Trigger '{}': target '{}' is not a Triggerable device",
+                    config.id, target
+                ))
+            })?),
+            None => None,
+        };
+
+        let name = if config.name.is_empty() {
+            config.id.clone()
+        } else {
+            config.name.clone()
+        };
+        let trigger = Arc::new(
+            CompositeTrigger::new(config.clone(), inputs, target)
+                .map_err(|e| DaqError::Configuration(e.to_string()))?,
+        );
+        let components = DeviceComponents::new()
+            .with_triggerable(trigger.clone())
+            .with_settable(trigger);
+        let registered = self.components_to_registered(
+            config.id.clone(),
+            name,
+            COMPOSITE_TRIGGER_DRIVER_TYPE.to_string(),
+            components,
+        );
+        self.devices.insert(config.id.clone(), registered);
+
+        tracing::info!(
+            trigger_id = %config.id,
+            target = ?config.target,
+            channels = ?evaluator.channels(),
+            signals = ?evaluator.signals(),
+            "Composite trigger registered"
+        );
+        Ok(())
+    }
+
+    /// Remove a motion group and its axis devices
+    fn remove_motion_group(&self, id: &str) {
+        if let Some((_, group)) = self.motion_groups.remove(id) {
+            self.devices.remove(id);
+            for axis in group.axis_names() {
+                self.devices.remove(&group.config().axis_device_id(axis));
+            }
+        }
+    }
+
+    /// Register a sample coordinate system and make it the active sample
+    ///
+    /// The sample axes are registered as a motion group named after the
+    /// sample. Registering a sample ID again replaces its registration
+    /// (e.g. after re-measuring fiducials).
+    pub fn register_sample(&self, registration: SampleRegistration) -> Result<(), DaqError> {
+        let sample_id = registration.sample_id().to_string();
+        let previous = self.sample_registrations.remove(&sample_id);
+        if previous.is_some() {
+            self.remove_motion_group(&sample_id);
+        }
+        if let Err(e) = self.register_motion_group(registration.motion_group_config()) {
+            // Keep the old registration working if the new one is rejected
+            if let Some((_, previous)) = previous {
+                if self
+                    .register_motion_group(previous.motion_group_config())
+                    .is_ok()
+                {
+                    self.sample_registrations.insert(sample_id, previous);
+                }
+            }
+            return Err(e);
+        }
+
+        tracing::info!(
+            sample_id = %sample_id,
+            rms_residual = registration.rms_residual,
+            "Sample registered"
+        );
+        self.sample_registrations
+            .insert(sample_id.clone(), registration);
+        *self
+            .active_sample
+            .write()
+            .unwrap_or_else(|p| p.into_inner()) = Some(sample_id);
+        Ok(())
+    }
+
+    /// Fit a registration from fiducials and register it
+    pub fn register_sample_fiducials(
+        &self,
+        config: SampleRegistrationConfig,
+    ) -> Result<SampleRegistration, DaqError> {
+        let registration =
+            SampleRegistration::fit(config).map_err(|e| DaqError::Configuration(e.to_string()))?;
+        self.register_sample(registration.clone())?;
+        Ok(registration)
+    }
+
+    /// Get a sample registration by sample ID
+    pub fn sample_registration(&self, sample_id: &str) -> Option<SampleRegistration> {
+        self.sample_registrations.get(sample_id).map(|r| r.clone())
+    }
+
+    /// IDs of all registered samples
+    pub fn list_samples(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .sample_registrations
+            .iter()
+            .map(|r| r.key().clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Select the sample recorded with runs (`None` = no sample)
+    pub fn set_active_sample(&self, sample_id: Option<&str>) -> Result<(), DaqError> {
+        if let Some(id) = sample_id {
+            if !self.sample_registrations.contains_key(id) {
+                return Err(DaqError::Configuration(format!(
+                    "Sample '{}' is not registered",
+                    id
+                )));
+            }
+        }
+        *self
+            .active_sample
+            .write()
+            .unwrap_or_else(|p| p.into_inner()) = sample_id.map(str::to_string);
+        Ok(())
+    }
+
+    /// Registration of the active sample, if any
+    pub fn active_sample_registration(&self) -> Option<SampleRegistration> {
+        let active = self
+            .active_sample
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()?;
+        self.sample_registration(&active)
+    }
+
+    /// Shared log of ambient environment channels
+    pub fn environment_log(&self) -> Arc<EnvironmentLog> {
+        Arc::clone(&self.environment)
+    }
+
+    /// Cumulative dropped frames and samples, per device and stage
+    ///
+    /// Merges the drivers' own counts with the process-wide [`DropLedger`]
+    /// (taps, storage queues).
+    pub async fn drop_report(&self) -> DropReport {
+        let producers: Vec<(DeviceId, Arc<dyn FrameProducer>)> = self
+            .devices
+            .iter()
+            .filter_map(|entry| {
+                let producer = entry.value().frame_producer.clone()?;
+                Some((entry.key().clone(), producer))
+            })
+            .collect();
+
+        let mut report = DropLedger::global().snapshot();
+        for (id, producer) in producers {
+            merge_counts(&mut report, &id, &producer.drop_counts().await);
+        }
+        report
+    }
+
+    /// Get all devices that support a specific capability
+    ///
+    /// # Thread Safety (bd-pf31)
+    /// This method iterates over all devices with fine-grained locking per entry.
+    pub fn devices_with_capability(&self, capability: Capability) -> Vec<DeviceId> {
+        self.devices
+            .iter()
+            .filter(|entry| {
+                entry
+                    .value()
+                    .config
+                    .driver
+                    .capabilities()
+                    .contains(&capability)
+            })
+            .map(|entry| entry.key().clone())
+            .collect()
+    }
+
+    // =========================================================================
+    // Device Instantiation (Private)
+    // =========================================================================
+
+    /// Instantiate a device from configuration
+    async fn instantiate_device(&self, config: DeviceConfig) -> Result<RegisteredDevice> {
+        // Clone driver before matching to avoid borrow issues
+        let driver_type_name = config.driver.driver_name().to_string();
+        let driver = config.driver.clone();
+
+        match driver {
+            DriverType::MockStage { initial_position } => {
+                let driver = Arc::new(crate::drivers::mock::MockStage::with_position(
+                    initial_position,
+                ));
+                Ok(RegisteredDevice {
+                    config,
+                    driver_type: driver_type_name.clone(),
+                    movable: Some(driver.clone()),
+                    readable: None,
+                    triggerable: None,
+                    frame_producer: None,
+                    source_frame: None,
+                    exposure_control: None,
+                    settable: None,
+                    stageable: None,
+                    commandable: None,
+                    parameterized: Some(driver.clone()),
+                    shutter_control: None,
+                    emission_control: None,
+                    wavelength_tunable: None,
+                    raw_terminal: None,
+                    discrete_positioner: None,
+                    lifecycle: None,
+                    status: DeviceStatusHandle::default(),
+                    metadata: DeviceMetadata {
+                        position_units: Some("mm".to_string()),
+                        min_position: Some(-100.0),
+                        max_position: Some(100.0),
+                        ..Default::default()
+                    },
+                })
+            }
+
+            DriverType::MockPowerMeter { reading } => {
+                let driver = Arc::new(crate::drivers::mock::MockPowerMeter::new(reading));
+                Ok(RegisteredDevice {
+                    config,
+                    driver_type: driver_type_name.clone(),
+                    movable: None,
+                    readable: Some(driver.clone()),
+                    triggerable: None,
+                    frame_producer: None,
+                    source_frame: None,
+                    exposure_control: None,
+                    settable: None,
+                    stageable: None,
+                    commandable: None,
+                    parameterized: Some(driver.clone()),
+                    shutter_control: None,
+                    emission_control: None,
+                    wavelength_tunable: None,
+                    raw_terminal: None,
+                    discrete_positioner: None,
+                    lifecycle: None,
+                    status: DeviceStatusHandle::default(),
+                    metadata: DeviceMetadata {
+                        measurement_units: Some("W".to_string()),
+                        ..Default::default()
+                    },
+                })
+            }
+
+            DriverType::MockCamera { width, height } => {
+                let driver = Arc::new(crate::drivers::mock::MockCamera::new(width, height));
+                Ok(RegisteredDevice {
+                    config,
+                    driver_type: driver_type_name.clone(),
+                    movable: None,
+                    readable: None,
+                    triggerable: Some(driver.clone()),
+                    frame_producer: Some(driver.clone()),
+                    source_frame: Some(driver.clone()),
+                    exposure_control: Some(driver.clone()),
+                    settable: None,
+                    stageable: Some(driver.clone()),
+                    commandable: None,
+                    parameterized: Some(driver.clone()),
+                    shutter_control: None,
+                    emission_control: None,
+                    wavelength_tunable: None,
+                    raw_terminal: None,
+                    discrete_positioner: None,
+                    lifecycle: None,
+                    status: DeviceStatusHandle::default(),
+                    metadata: DeviceMetadata {
+                        frame_width: Some(width),
+                        frame_height: Some(height),
+                        ..Default::default()
+                    },
+                })
+            }
+
+            #[cfg(feature = "serial")]
+            DriverType::Plugin { plugin_id, address } => {
+                self.instantiate_plugin_device(config, &plugin_id, &address)
+                    .await
+            }
+
+            #[cfg(feature = "pvcam")]
+            DriverType::Pvcam { camera_name } => {
+                let driver = Arc::new(
+                    crate::drivers::pvcam::PvcamDriver::new_async(camera_name.clone()).await?,
+                );
+                let (width, height) = driver.resolution();
+                Ok(RegisteredDevice {
+                    config,
+                    driver_type: driver_type_name.clone(),
+                    movable: None,
+                    readable: None,
+                    triggerable: Some(driver.clone()),
+                    frame_producer: Some(driver.clone()),
+                    source_frame: Some(driver.clone()),
+                    exposure_control: Some(driver.clone()),
+                    settable: None,
+                    stageable: Some(driver.clone()),
+                    commandable: Some(driver.clone()),
+                    parameterized: Some(driver.clone()),
+                    shutter_control: None,
+                    emission_control: None,
+                    wavelength_tunable: None,
+                    raw_terminal: None,
+                    discrete_positioner: None,
+                    lifecycle: None,
+                    status: DeviceStatusHandle::default(),
+                    metadata: DeviceMetadata {
+                        frame_width: Some(width),
+                        frame_height: Some(height),
+                        ..Default::default()
+                    },
+                })
+            }
+
+            #[cfg(feature = "comedi")]
+            DriverType::Comedi { device_path } => {
+                let device =
+                    crate::drivers::comedi::ComediDevice::open(&device_path).map_err(|e| {
+                        DaqError::Instrument(format!("Failed to open Comedi device: {}", e))
+                    })?;
+                let _info = device.info().map_err(|e| {
+                    DaqError::Instrument(format!("Failed to get Comedi device info: {}", e))
+                })?;
+                // Note: Comedi doesn't implement full HAL traits directly;
+                // subsystems (AnalogInput, AnalogOutput, etc.) need to be accessed separately.
+                // For registry purposes, we register the device as having Readable/Settable capabilities.
+                // TODO: Implement HAL trait wrappers for Comedi subsystems
+                Ok(RegisteredDevice {
+                    config,
+                    driver_type: driver_type_name.clone(),
+                    movable: None,
+                    readable: None,
+                    triggerable: None,
+                    frame_producer: None,
+                    source_frame: None,
+                    exposure_control: None,
+                    settable: None,
+                    stageable: None,
+                    commandable: None,
+                    parameterized: None,
+                    shutter_control: None,
+                    emission_control: None,
+                    wavelength_tunable: None,
+                    raw_terminal: None,
+                    discrete_positioner: None,
+                    lifecycle: None,
+                    status: DeviceStatusHandle::default(),
+                    metadata: DeviceMetadata {
+                        measurement_units: Some("V".to_string()), // Voltage
+                        ..Default::default()
+                    },
+                })
+            }
+
+            #[cfg(feature = "thorlabs")]
+            DriverType::Ell14 { port, address } => {
+                // Use shared port for multidrop bus - multiple ELL14 devices share one serial connection
+                let shared_port = {
+                    // Check if port already exists
+                    let read_guard = self.ell14_shared_ports.read().await;
+                    if let Some(existing) = read_guard.get(&port) {
+                        existing.clone()
+                    } else {
+                        drop(read_guard); // Release read lock before acquiring write lock
+                        let new_port = crate::drivers::ell14::Ell14Driver::open_shared_port(&port)?;
+                        let mut write_guard = self.ell14_shared_ports.write().await;
+                        // Double-check in case another task created it
+                        if let Some(existing) = write_guard.get(&port) {
+                            existing.clone()
+                        } else {
+                            write_guard.insert(port.clone(), new_port.clone());
+                            new_port
+                        }
+                    }
+                };
+
+                // Use with_shared_port_calibrated() to validate device responds and get calibration
+                let driver = Arc::new(
+                    crate::drivers::ell14::Ell14Driver::with_shared_port_calibrated(
+                        shared_port,
+                        &address,
+                    )
+                    .await?,
+                );
+                Ok(RegisteredDevice {
+                    config,
+                    driver_type: driver_type_name.clone(),
+                    movable: Some(driver.clone()),
+                    readable: None,
                     triggerable: None,
                     frame_producer: None,
                     source_frame: None,
@@ -1633,920 +3510,2445 @@ impl DeviceRegistry {
                     settable: None,
                     stageable: None,
                     commandable: None,
-                    parameterized: None,
+                    parameterized: Some(driver.clone()),
                     shutter_control: None,
                     emission_control: None,
                     wavelength_tunable: None,
+                    raw_terminal: Some(driver),
+                    discrete_positioner: None,
                     lifecycle: None,
+                    status: DeviceStatusHandle::default(),
                     metadata: DeviceMetadata {
-                        measurement_units: Some("V".to_string()), // Voltage
+                        position_units: Some("degrees".to_string()),
+                        min_position: Some(0.0),
+                        max_position: Some(360.0),
+                        ..Default::default()
+                    },
+                })
+            }
+
+            #[cfg(feature = "newport")]
+            DriverType::Newport1830C { port } => {
+                // Use new_async() to validate device responds correctly on connection
+                let driver = Arc::new(
+                    crate::drivers::newport_1830c::Newport1830CDriver::new_async(&port).await?,
+                );
+                // Newport1830C implements WavelengthTunable (bd-3xw2.5)
+                let wavelength_range = driver.wavelength_range();
+                Ok(RegisteredDevice {
+                    config,
+                    driver_type: driver_type_name.clone(),
+                    movable: None,
+                    readable: Some(driver.clone()),
+                    triggerable: None,
+                    frame_producer: None,
+                    source_frame: None,
+                    exposure_control: None,
+                    settable: None,
+                    stageable: None,
+                    commandable: None,
+                    parameterized: Some(driver.clone()),
+                    shutter_control: None,
+                    emission_control: None,
+                    wavelength_tunable: Some(driver.clone()),
+                    raw_terminal: Some(driver),
+                    discrete_positioner: None,
+                    lifecycle: None,
+                    status: DeviceStatusHandle::default(),
+                    metadata: DeviceMetadata {
+                        measurement_units: Some("W".to_string()),
+                        min_wavelength_nm: Some(wavelength_range.0),
+                        max_wavelength_nm: Some(wavelength_range.1),
+                        ..Default::default()
+                    },
+                })
+            }
+
+            #[cfg(feature = "spectra_physics")]
+            DriverType::MaiTai { port } => {
+                // Use new_async() to validate device identity on connection
+                let driver = Arc::new(
+                    daq_driver_spectra_physics::MaiTaiDriver::new_async_default(&port).await?,
+                );
+                Ok(RegisteredDevice {
+                    config,
+                    driver_type: driver_type_name.clone(),
+                    movable: None,
+                    readable: Some(driver.clone()),
+                    triggerable: None,
+                    frame_producer: None,
+                    source_frame: None,
+                    exposure_control: None,
+                    settable: None,
+                    stageable: None,
+                    commandable: None,
+                    parameterized: Some(driver.clone()),
+                    shutter_control: Some(driver.clone()),
+                    emission_control: Some(driver.clone()),
+                    wavelength_tunable: Some(driver.clone()),
+                    raw_terminal: Some(driver),
+                    discrete_positioner: None,
+                    lifecycle: None,
+                    status: DeviceStatusHandle::default(),
+                    metadata: DeviceMetadata {
+                        measurement_units: Some("W".to_string()),
+                        ..Default::default()
+                    },
+                })
+            }
+
+            #[cfg(feature = "newport")]
+            DriverType::Esp300 { port, axis } => {
+                // Use new_async() to validate device responds correctly on connection
+                let driver =
+                    Arc::new(crate::drivers::esp300::Esp300Driver::new_async(&port, axis).await?);
+                Ok(RegisteredDevice {
+                    config,
+                    driver_type: driver_type_name.clone(),
+                    movable: Some(driver.clone()),
+                    readable: None,
+                    triggerable: None,
+                    frame_producer: None,
+                    source_frame: None,
+                    exposure_control: None,
+                    settable: None,
+                    stageable: None,
+                    commandable: None,
+                    parameterized: Some(driver.clone()),
+                    shutter_control: None,
+                    emission_control: None,
+                    wavelength_tunable: None,
+                    raw_terminal: Some(driver),
+                    discrete_positioner: None,
+                    lifecycle: None,
+                    status: DeviceStatusHandle::default(),
+                    metadata: DeviceMetadata {
+                        position_units: Some("mm".to_string()),
+                        min_position: Some(-25.0), // Typical ESP300 stage range
+                        max_position: Some(25.0),
                         ..Default::default()
                     },
                 })
             }
 
-            #[cfg(feature = "thorlabs")]
-            DriverType::Ell14 { port, address } => {
-                // Use shared port for multidrop bus - multiple ELL14 devices share one serial connection
-                let shared_port = {
-                    // Check if port already exists
-                    let read_guard = self.ell14_shared_ports.read().await;
-                    if let Some(existing) = read_guard.get(&port) {
-                        existing.clone()
-                    } else {
-                        drop(read_guard); // Release read lock before acquiring write lock
-                        let new_port = crate::drivers::ell14::Ell14Driver::open_shared_port(&port)?;
-                        let mut write_guard = self.ell14_shared_ports.write().await;
-                        // Double-check in case another task created it
-                        if let Some(existing) = write_guard.get(&port) {
-                            existing.clone()
-                        } else {
-                            write_guard.insert(port.clone(), new_port.clone());
-                            new_port
-                        }
-                    }
-                };
+            // Handle disabled features
+            #[cfg(all(not(feature = "thorlabs"), feature = "serial"))]
+            DriverType::Ell14 { .. } => Err(anyhow!("ELL14 driver requires 'thorlabs' feature")),
+
+            #[cfg(all(not(feature = "newport"), feature = "serial"))]
+            DriverType::Newport1830C { .. } => {
+                Err(anyhow!("Newport 1830-C driver requires 'newport' feature"))
+            }
+
+            #[cfg(all(not(feature = "spectra_physics"), feature = "serial"))]
+            DriverType::MaiTai { .. } => {
+                Err(anyhow!("MaiTai driver requires 'spectra_physics' feature"))
+            }
+
+            #[cfg(all(not(feature = "newport"), feature = "serial"))]
+            DriverType::Esp300 { .. } => Err(anyhow!("ESP300 driver requires 'newport' feature")),
+
+            // Factory-based Comedi drivers (must use factory registration)
+            #[cfg(feature = "comedi")]
+            DriverType::ComediAnalogInput { .. } => Err(anyhow!(
+                "ComediAnalogInput devices must be registered via DriverFactory system"
+            )),
+
+            #[cfg(feature = "comedi")]
+            DriverType::ComediAnalogOutput { .. } => Err(anyhow!(
+                "ComediAnalogOutput devices must be registered via DriverFactory system"
+            )),
+        }
+    }
+
+    /// Instantiate a plugin-based device
+    #[cfg(feature = "serial")]
+    async fn instantiate_plugin_device(
+        &self,
+        config: DeviceConfig,
+        plugin_id: &str,
+        address: &str,
+    ) -> Result<RegisteredDevice> {
+        // Spawn the driver from the plugin factory
+        let factory = self.plugin_factory.read().await;
+        let driver = Arc::new(factory.spawn(plugin_id, address).await?);
+        drop(factory); // Release lock before calling helper
+
+        // Create the registered device using the common helper
+        self.create_registered_plugin(config, driver).await
+    }
+
+    /// Creates a RegisteredDevice from a pre-spawned plugin driver
+    ///
+    /// This is the shared implementation used by both `instantiate_plugin_device`
+    /// (for config-based registration) and `register_plugin_instance` (for
+    /// PluginService-managed registration).
+    #[cfg(feature = "serial")]
+    async fn create_registered_plugin(
+        &self,
+        config: DeviceConfig,
+        driver: Arc<GenericDriver>,
+    ) -> Result<RegisteredDevice> {
+        let plugin_id = match &config.driver {
+            DriverType::Plugin { plugin_id, .. } => plugin_id,
+            _ => {
+                return Err(anyhow!(
+                    "Invalid driver type for create_registered_plugin: expected Plugin"
+                ));
+            }
+        };
+        let driver_type_name = config.driver.driver_name().to_string();
+
+        // Introspect capabilities from the plugin configuration
+        let factory = self.plugin_factory.read().await;
+        let plugin_config = factory
+            .get_config(plugin_id)
+            .ok_or_else(|| anyhow!("Plugin '{}' not found in factory", plugin_id))?;
+
+        let mut metadata = DeviceMetadata {
+            ui_layout: plugin_config.resolved_ui_layout(),
+            ..Default::default()
+        };
+
+        // Check for movable capability
+        let movable: Option<Arc<dyn Movable>> = if plugin_config.capabilities.movable.is_some() {
+            // Extract metadata from first axis
+            if let Some(movable_cap) = &plugin_config.capabilities.movable {
+                if let Some(first_axis) = movable_cap.axes.first() {
+                    metadata.position_units = first_axis.unit.clone();
+                    metadata.min_position = first_axis.min;
+                    metadata.max_position = first_axis.max;
+                }
+            }
+
+            // Create axis handle for the first axis (convention)
+            let axis_name = plugin_config
+                .capabilities
+                .movable
+                .as_ref()
+                .and_then(|m| m.axes.first())
+                .map(|a| a.name.as_str())
+                .unwrap_or("axis");
+
+            Some(Arc::new(crate::plugin::handles::PluginAxisHandle::new(
+                driver.clone(),
+                axis_name.to_string(),
+                false, // not mocking
+            )))
+        } else {
+            None
+        };
+
+        // Check for readable capability
+        let readable: Option<Arc<dyn Readable>> = if !plugin_config.capabilities.readable.is_empty()
+        {
+            // Extract metadata from first readable
+            if let Some(first_readable) = plugin_config.capabilities.readable.first() {
+                metadata.measurement_units = first_readable.unit.clone();
+            }
+
+            // Create readable handle for the first readable capability (convention)
+            let readable_name = plugin_config
+                .capabilities
+                .readable
+                .first()
+                .map(|r| r.name.as_str())
+                .unwrap_or("reading");
+
+            Some(Arc::new(crate::plugin::handles::PluginSensorHandle::new(
+                driver.clone(),
+                readable_name.to_string(),
+                false, // not mocking
+            )))
+        } else {
+            None
+        };
+
+        // Note: FrameProducer, Triggerable, and ExposureControl are not yet
+        // supported by the plugin system, so we leave them as None
+
+        Ok(RegisteredDevice {
+            config,
+            driver_type: driver_type_name.clone(),
+            movable,
+            readable,
+            triggerable: None,
+            frame_producer: None,
+            source_frame: None,
+            exposure_control: None,
+            settable: None,
+            stageable: None,
+            // Control panel bindings (get/set/actions by capability name)
+            commandable: Some(driver.clone()),
+            parameterized: Some(driver.clone()), // bd-plb6: Wire Parameterized for plugin devices
+            shutter_control: None,
+            emission_control: None,
+            wavelength_tunable: None,
+            raw_terminal: None,
+            discrete_positioner: None,
+            lifecycle: None,
+            status: driver.status(),
+            metadata,
+        })
+    }
+
+    /// Snapshot all parameters from all devices with Parameterized trait (bd-ej44)
+    ///
+    /// Returns a nested map: device_id -> parameter_name -> JSON value
+    /// This is used for experiment manifests to capture complete hardware state.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let snapshot = registry.snapshot_all_parameters();
+    /// // Returns:
+    /// // {
+    /// //   "mock_camera": {
+    /// //     "exposure_ms": 100.0,
+    /// //     "gain": 1.5
+    /// //   },
+    /// //   "mock_stage": {
+    /// //     "position": 0.0
+    /// //   }
+    /// // }
+    /// ```
+    ///
+    /// # Thread Safety (bd-pf31)
+    /// This method iterates over all devices with fine-grained locking per entry.
+    pub fn snapshot_all_parameters(&self) -> HashMap<String, HashMap<String, serde_json::Value>> {
+        let mut snapshot = HashMap::new();
+
+        for entry in self.devices.iter() {
+            let device_id = entry.key();
+            let device = entry.value();
+            if let Some(parameterized) = &device.parameterized {
+                let params = parameterized.parameters();
+                let mut device_params = HashMap::new();
+
+                for (name, param) in params.iter() {
+                    // Get JSON value for each parameter
+                    if let Ok(value) = param.get_json() {
+                        device_params.insert(name.to_string(), value);
+                    } else {
+                        // If serialization fails, store error marker
+                        device_params.insert(
+                            name.to_string(),
+                            serde_json::json!({"error": "serialization_failed"}),
+                        );
+                    }
+                }
+
+                if !device_params.is_empty() {
+                    snapshot.insert(device_id.clone(), device_params);
+                }
+            }
+        }
+
+        snapshot
+    }
+}
+
+impl Default for DeviceRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Recipe steps go through the same capability traits as the gRPC parameter
+/// and command endpoints: `Settable` first, then `Parameterized`.
+#[async_trait::async_trait]
+impl RecipeTarget for DeviceRegistry {
+    async fn set_parameter(
+        &self,
+        device: &str,
+        parameter: &str,
+        value: serde_json::Value,
+    ) -> Result<()> {
+        if let Some(settable) = self.get_settable(device) {
+            return settable.set_value(parameter, value).await;
+        }
+        let parameterized = self
+            .get_parameterized(device)
+            .ok_or_else(|| anyhow!("Device '{}' has no settable parameters", device))?;
+        let params = parameterized.parameters();
+        let param = params
+            .get(parameter)
+            .ok_or_else(|| anyhow!("Device '{}' has no parameter '{}'", device, parameter))?;
+        self.set_parameter_json(device, param, value).await
+    }
+
+    async fn read_parameter(&self, device: &str, parameter: &str) -> Result<serde_json::Value> {
+        if let Some(settable) = self.get_settable(device) {
+            return settable.get_value(parameter).await;
+        }
+        let parameterized = self
+            .get_parameterized(device)
+            .ok_or_else(|| anyhow!("Device '{}' has no readable parameters", device))?;
+        let params = parameterized.parameters();
+        let param = params
+            .get(parameter)
+            .ok_or_else(|| anyhow!("Device '{}' has no parameter '{}'", device, parameter))?;
+        self.get_parameter_json(device, param)
+    }
+
+    async fn execute_command(
+        &self,
+        device: &str,
+        command: &str,
+        args: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let commandable = self
+            .get_commandable(device)
+            .ok_or_else(|| anyhow!("Device '{}' does not support commands", device))?;
+        commandable.execute_command(command, args).await
+    }
+}
+
+/// Self-test diagnostics only read: identity over the raw command channel,
+/// `Movable::position`, `Readable::read`, and one frame seen by an observer.
+#[async_trait::async_trait]
+impl SelfTestTarget for DeviceRegistry {
+    fn device_ids(&self) -> Vec<String> {
+        self.list_devices().into_iter().map(|d| d.id).collect()
+    }
+
+    fn diagnostics(&self, device: &str) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        if self.get_raw_terminal(device).is_some() {
+            diagnostics.push(Diagnostic::Identity);
+        }
+        if self.get_movable(device).is_some() {
+            diagnostics.push(Diagnostic::Position);
+        }
+        if self.get_readable(device).is_some() {
+            diagnostics.push(Diagnostic::Power);
+        }
+        if self
+            .get_frame_producer(device)
+            .is_some_and(|camera| camera.supports_observers())
+        {
+            diagnostics.push(Diagnostic::Frame);
+        }
+        diagnostics
+    }
+
+    async fn run_diagnostic(&self, device: &str, diagnostic: Diagnostic) -> Result<String> {
+        let unsupported = || anyhow!("Device '{}' does not support {}", device, diagnostic);
+        match diagnostic {
+            Diagnostic::Identity => {
+                let terminal = self.get_raw_terminal(device).ok_or_else(unsupported)?;
+                let identity = terminal.send_raw("*IDN?", true).await?;
+                if identity.is_empty() {
+                    anyhow::bail!("Empty identity response");
+                }
+                Ok(identity)
+            }
+            Diagnostic::Position => {
+                let movable = self.get_movable(device).ok_or_else(unsupported)?;
+                let position = movable.position().await?;
+                if !position.is_finite() {
+                    anyhow::bail!("Position readback is {}", position);
+                }
+                Ok(position.to_string())
+            }
+            Diagnostic::Power => {
+                let readable = self.get_readable(device).ok_or_else(unsupported)?;
+                let value = readable.read().await?;
+                if !value.is_finite() {
+                    anyhow::bail!("Reading is {}", value);
+                }
+                Ok(value.to_string())
+            }
+            Diagnostic::Frame => {
+                let camera = self.get_frame_producer(device).ok_or_else(unsupported)?;
+                grab_one_frame(camera).await
+            }
+        }
+    }
+}
+
+/// Observer that reports the size of the first frame it sees
+struct FirstFrameObserver(std::sync::Mutex<Option<tokio::sync::oneshot::Sender<(u32, u32)>>>);
+
+impl common::capabilities::FrameObserver for FirstFrameObserver {
+    fn on_frame(&self, frame: &common::data::FrameView<'_>) {
+        if let Some(tx) = self.0.lock().unwrap_or_else(|p| p.into_inner()).take() {
+            let _ = tx.send((frame.width, frame.height));
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "self_test"
+    }
+}
+
+/// Stops the stream and removes the observer when dropped, including when
+/// the self-test timeout cancels the grab
+struct FrameGrab {
+    camera: Arc<dyn FrameProducer>,
+    handle: common::capabilities::ObserverHandle,
+}
+
+impl Drop for FrameGrab {
+    fn drop(&mut self) {
+        let camera = self.camera.clone();
+        let handle = self.handle;
+        tokio::spawn(async move {
+            // Errors only mean the finite stream already stopped
+            let _ = camera.stop_stream().await;
+            let _ = camera.unregister_observer(handle).await;
+        });
+    }
+}
+
+/// Acquire a single frame
+async fn grab_one_frame(camera: Arc<dyn FrameProducer>) -> Result<String> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let handle = camera
+        .register_observer(Box::new(FirstFrameObserver(std::sync::Mutex::new(Some(
+            tx,
+        )))))
+        .await?;
+    let _grab = FrameGrab {
+        camera: camera.clone(),
+        handle,
+    };
+    camera.start_stream_finite(Some(1)).await?;
+    let (width, height) = rx
+        .await
+        .map_err(|_| anyhow!("Stream ended without a frame"))?;
+    Ok(format!("{}x{} frame", width, height))
+}
+
+// =============================================================================
+// Hardware Configuration File Support
+// =============================================================================
+
+/// Hardware configuration loaded from a TOML file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HardwareConfig {
+    /// Plugin search paths (in priority order, first = highest priority)
+    /// Convention: user paths before system paths
+    #[serde(default)]
+    pub plugin_paths: Vec<std::path::PathBuf>,
+
+    /// List of devices to register
+    pub devices: Vec<DeviceConfig>,
+
+    /// Channel aliases (`alias = "device"` or `alias = "device:parameter"`)
+    #[serde(default)]
+    pub aliases: ChannelAliases,
+
+    /// Channels sampled and stored with every acquired frame
+    #[serde(default)]
+    pub frame_enrichment: FrameEnrichmentConfig,
+
+    /// Overlay annotations keyed by camera device ID
+    #[serde(default)]
+    pub frame_annotations: HashMap<String, Vec<FrameAnnotation>>,
+
+    /// Transforms applied to the run document stream per consumer
+    #[serde(default)]
+    pub document_transforms: Vec<DocumentTransformConfig>,
+
+    /// Frame preprocessing keyed by camera device ID
+    #[serde(default)]
+    pub preprocessing: HashMap<String, PreprocessingConfig>,
+
+    /// Device initialization recipes
+    #[serde(default)]
+    pub recipes: Vec<InitRecipe>,
+
+    /// Recipes run ahead of scheduled runs, with status checks
+    #[serde(default)]
+    pub warmups: Vec<WarmupSchedule>,
+
+    /// Diagnostics run before the daemon reports ready
+    #[serde(default)]
+    pub self_test: SelfTestConfig,
+
+    /// Where the instrument inventory is kept
+    #[serde(default)]
+    pub inventory: InventoryConfig,
+
+    /// How clients display channel values (offset/scale/unit per channel)
+    #[serde(default)]
+    pub display: DisplayCalibrationConfig,
+
+    /// Free-form tags keyed by device ID (e.g. bench or experiment names),
+    /// used to filter device listings
+    #[serde(default)]
+    pub device_tags: HashMap<String, Vec<String>>,
+
+    /// Parameter policies keyed by device ID, then parameter name
+    #[serde(default)]
+    pub parameter_policies: HashMap<String, HashMap<String, ParameterPolicy>>,
+
+    /// Setpoint limits keyed by device ID, then parameter name
+    /// (`position` for Movable devices)
+    #[serde(default)]
+    pub output_limits: HashMap<String, HashMap<String, OutputLimit>>,
+
+    /// Forbidden position ranges of Movable devices, keyed by device ID
+    #[serde(default)]
+    pub keep_out: HashMap<String, Vec<KeepOutZone>>,
+
+    /// Actions parking devices while a run is paused, keyed by device ID
+    #[serde(default)]
+    pub parking: HashMap<String, ParkingActions>,
+
+    /// Checks flagging untrustworthy points of every run
+    #[serde(default)]
+    pub validation_rules: Vec<ValidationRule>,
+
+    /// Nanoseconds subtracted from each device's timestamps, keyed by device ID
+    #[serde(default)]
+    pub timestamp_offsets: HashMap<String, i64>,
+
+    /// Coordinated multi-axis motion groups over configured devices
+    #[serde(default)]
+    pub motion_groups: Vec<MotionGroupConfig>,
+
+    /// Named-slot positioners (filter wheels, flip mirrors) over configured
+    /// devices or motion group axes
+    #[serde(default)]
+    pub discrete_positioners: Vec<DiscretePositionerConfig>,
+
+    /// Triggers gated on combined conditions over configured devices
+    #[serde(default)]
+    pub composite_triggers: Vec<CompositeTriggerConfig>,
+
+    /// Backlash/scale/offset corrections keyed by Movable device ID
+    #[serde(default)]
+    pub motion_corrections: HashMap<String, MotionCorrection>,
+
+    /// Sample coordinate systems fitted from fiducials
+    #[serde(default)]
+    pub sample_registrations: Vec<SampleRegistrationConfig>,
+
+    /// Simulate every device, as if each had `simulated = true`
+    /// (also set by the daemon's `--simulate-all` flag)
+    #[serde(default)]
+    pub simulate_all: bool,
+}
+
+/// Config-level policy for a single device parameter
+///
+/// Lets a site flag parameters as dangerous without changing the driver,
+/// e.g. the power setpoint of a particular laser.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParameterPolicy {
+    /// Remote changes become proposals that must be confirmed
+    #[serde(default)]
+    pub dangerous: bool,
+    /// How long a proposal stays confirmable (daemon default if unset)
+    #[serde(default)]
+    pub confirm_timeout_s: Option<u64>,
+}
+
+impl HardwareConfig {
+    /// Load hardware configuration from a TOML file
+    pub fn from_file(path: &std::path::Path) -> Result<Self, DaqError> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            DaqError::Configuration(format!("Failed to read hardware config file: {}", e))
+        })?;
+        toml::from_str(&content).map_err(|e| {
+            DaqError::Configuration(format!("Failed to parse hardware config file: {}", e))
+        })
+    }
+}
+
+/// Create a DeviceRegistry from a hardware configuration file
+///
+/// # Example TOML format:
+/// ```toml
+/// # Optional: Plugin search paths (first = highest priority)
+/// plugin_paths = [
+///     "~/.config/rust-daq/plugins",
+///     "/usr/share/rust-daq/plugins"
+/// ]
+///
+/// [[devices]]
+/// id = "rotator_2"
+/// name = "ELL14 Rotation Mount (Addr 2)"
+/// [devices.driver]
+/// type = "ell14"
+/// port = "/dev/ttyUSB0"
+/// address = "2"
+///
+/// # Optional: use a mock backend, e.g. to try the config on a laptop
+/// # (the daemon's `--simulate-all` flag simulates every device)
+/// [[devices]]
+/// id = "stage_x"
+/// name = "ESP300 Axis 1"
+/// simulated = true
+/// [devices.driver]
+/// type = "esp300"
+/// port = "/dev/ttyUSB1"
+/// axis = 1
+///
+/// [[devices]]
+/// id = "my_sensor"
+/// name = "Custom Sensor (Plugin-Based)"
+/// [devices.driver]
+/// type = "plugin"
+/// plugin_id = "my-sensor-v1"
+/// address = "/dev/ttyUSB2"
+///
+/// # Optional: experiment-level channel names
+/// [aliases]
+/// sample_angle = "rotator_2"
+/// sample_temp = "my_sensor:temperature"
+///
+/// # Optional: channel values stored with every frame
+/// [frame_enrichment]
+/// channels = ["sample_angle", "sample_temp"]
+///
+/// # Optional: overlays stored with frames (see `common::frame_annotations`)
+/// [frame_annotations]
+/// camera = [{ type = "crosshair", x = 320, y = 240 }]
+///
+/// # Optional: reshape run documents per consumer (see `common::document_transform`)
+/// [[document_transforms]]
+/// type = "strip_arrays"
+/// consumers = ["stream"]
+/// max_bytes = 65536
+///
+/// # Optional: per-camera dark/flat correction, binning and histogram
+/// [preprocessing.camera]
+/// dark_frame = "calibration/dark.raw"
+/// binning = 2
+///
+/// # Optional: initialization recipes (see `recipes` module)
+/// [[recipes]]
+/// name = "rotator_home"
+/// device = "rotator_2"
+/// run_on = ["startup", "reconnect"]
+/// [[recipes.steps]]
+/// action = "command"
+/// command = "home"
+///
+/// # Optional: warm-up ahead of a daily run (see `warmup` module)
+/// [[warmups]]
+/// name = "morning"
+/// run_at = "09:00"
+/// lead_minutes = 30
+/// recipes = ["rotator_home"]
+///
+/// # Optional: startup self-test (see `self_test` module)
+/// [self_test]
+/// on_start = true
+/// [[self_test.devices]]
+/// device = "rotator_2"
+/// critical = true
+///
+/// # Optional: instrument inventory file (see `inventory` module)
+/// [inventory]
+/// path = "/var/lib/rust-daq/inventory.json"
+///
+/// # Optional: display calibration, e.g. photodiode volts shown as mW
+/// # (see `display_calibration` module; files keep the raw values)
+/// [display.channels.photodiode]
+/// scale = 12.5
+/// unit = "mW"
+///
+/// # Optional: tags for filtering device listings
+/// [device_tags]
+/// rotator_2 = ["polarization", "table_1"]
+///
+/// # Optional: parameters whose changes must be confirmed
+/// [parameter_policies.my_sensor]
+/// heater_power = { dangerous = true, confirm_timeout_s = 15 }
+///
+/// # Optional: backlash, scale and offset corrections
+/// [motion_corrections.rotator_2]
+/// backlash = 0.2
+/// approach = "positive"
+/// offset = -12.5
+///
+/// # Optional: output limits (see `common::output_limits`)
+/// [output_limits.piezo_amp.voltage]
+/// min = 0.0
+/// max = 1.0
+/// max_slew_rate = 0.5
+/// alarm = true
+///
+/// # Optional: keep-out zones (see `common::keep_out`)
+/// [[keep_out.stage_z]]
+/// name = "sample holder"
+/// range = [12.0, 15.5]
+///
+/// # Optional: parking while a run is paused (see `common::parking`)
+/// [parking.shutter]
+/// on_pause = [{ action = "close_shutter" }]
+///
+/// # Optional: per-point validation (see `common::validation`)
+/// [[validation_rules]]
+/// type = "noise_floor"
+/// field = "power_meter"
+/// min = 0.002
+///
+/// # Optional: timestamp offsets in ns (see `common::time_sync`)
+/// [timestamp_offsets]
+/// camera = 4200000
+///
+/// # Optional: motion groups (see `common::motion_group`)
+/// [[motion_groups]]
+/// id = "sample_polar"
+/// members = ["stage_x", "stage_y"]
+/// kinematics = { type = "polar", center_x = 12.5, center_y = 8.0 }
+/// limits = { r = [0.0, 5.0] }
+///
+/// # Optional: named-slot positioners (see `common::discrete_positioner`)
+/// [[discrete_positioners]]
+/// id = "nd_wheel"
+/// drive = { type = "movable", device = "wheel_rotator", tolerance = 0.5 }
+/// positions = [{ name = "open", position = 0.0 }, { name = "nd1", position = 60.0 }]
+///
+/// # Optional: composite triggers (see `common::composite_trigger`)
+/// [[composite_triggers]]
+/// id = "gated_camera"
+/// target = "camera"
+/// condition = { type = "all", conditions = [
+///     { type = "threshold", channel = "shutter:open", above = 0.5 },
+///     { type = "digital_edge", channel = "chopper_ref", edge = "rising" },
+/// ] }
+///
+/// # Optional: sample coordinates (see `common::coordinates`)
+/// [[sample_registrations]]
+/// sample_id = "wafer_07"
+/// stage_axes = ["stage_x", "stage_y"]
+/// fiducials = [
+///     { stage = [12.10, 4.95], sample = [0.0, 0.0] },
+///     { stage = [22.08, 5.31], sample = [10.0, 0.0] },
+///     { stage = [11.75, 14.93], sample = [0.0, 10.0] },
+/// ]
+/// ```
+pub async fn create_registry_from_config(
+    config: &HardwareConfig,
+) -> Result<DeviceRegistry, DaqError> {
+    let registry = DeviceRegistry::new();
+
+    // Register all available driver factories BEFORE loading devices
+    register_all_factories(&registry, None).await?;
+
+    // Validate all device configurations first (fail fast)
+    let mut validation_errors = Vec::new();
+    for device_config in &config.devices {
+        if device_config.simulated || config.simulate_all {
+            // Simulated devices never open their port
+            if device_config.driver.simulated_backend().is_some() {
+                continue;
+            }
+            if device_config.simulated {
+                validation_errors.push(format!(
+                    "Device '{}' ({}) cannot be simulated",
+                    device_config.id,
+                    device_config.driver.driver_name()
+                ));
+                continue;
+            }
+            tracing::warn!(
+                device_id = %device_config.id,
+                driver_type = %device_config.driver.driver_name(),
+                "No simulated backend, registering the real driver"
+            );
+        }
+        let mut driver = device_config.driver.clone();
+        if let Err(e) =
+            resolve_driver_port(&mut driver).and_then(|()| validate_driver_config(&driver))
+        {
+            validation_errors.push(format!(
+                "Device '{}' ({}): {}",
+                device_config.id,
+                device_config.driver.driver_name(),
+                e
+            ));
+        }
+    }
+
+    // Samples are registered as motion groups too
+    let mut sample_groups = Vec::new();
+    for sample in &config.sample_registrations {
+        match SampleRegistration::fit(sample.clone()) {
+            Ok(registration) => sample_groups.push(registration.motion_group_config()),
+            Err(e) => validation_errors.push(e.to_string()),
+        }
+        for axis in &sample.stage_axes {
+            if !config.devices.iter().any(|d| &d.id == axis) {
+                validation_errors.push(format!(
+                    "Sample '{}' uses unknown stage axis '{}'",
+                    sample.sample_id, axis
+                ));
+            }
+        }
+    }
+
+    // Aliases may also name motion groups and their axes
+    let group_device_ids: Vec<String> = config
+        .motion_groups
+        .iter()
+        .chain(&sample_groups)
+        .flat_map(|g| {
+            let axes = g
+                .axis_names()
+                .into_iter()
+                .map(|axis| g.axis_device_id(&axis));
+            std::iter::once(g.id.clone())
+                .chain(axes)
+                .collect::<Vec<_>>()
+        })
+        .chain(config.discrete_positioners.iter().map(|p| p.id.clone()))
+        .chain(config.composite_triggers.iter().map(|t| t.id.clone()))
+        .collect();
+    if let Err(e) = config.aliases.validate(
+        config
+            .devices
+            .iter()
+            .map(|d| d.id.as_str())
+            .chain(group_device_ids.iter().map(String::as_str)),
+    ) {
+        validation_errors.push(e.to_string());
+    }
+
+    let mut recipe_names = std::collections::HashSet::new();
+    for recipe in &config.recipes {
+        if !recipe_names.insert(recipe.name.as_str()) {
+            validation_errors.push(format!("Duplicate init recipe '{}'", recipe.name));
+        }
+        if !config.devices.iter().any(|d| d.id == recipe.device) {
+            validation_errors.push(format!(
+                "Init recipe '{}' targets unknown device '{}'",
+                recipe.name, recipe.device
+            ));
+        }
+    }
+
+    let mut warmup_names = std::collections::HashSet::new();
+    for warmup in &config.warmups {
+        if !warmup_names.insert(warmup.name.as_str()) {
+            validation_errors.push(format!("Duplicate warm-up '{}'", warmup.name));
+        }
+        if let Err(e) = warmup.validate() {
+            validation_errors.push(e.to_string());
+        }
+        for recipe in warmup.recipes.iter().chain(&warmup.abort_recipes) {
+            if !recipe_names.contains(recipe.as_str()) {
+                validation_errors.push(format!(
+                    "Warm-up '{}' uses unknown init recipe '{}'",
+                    warmup.name, recipe
+                ));
+            }
+        }
+        for check in &warmup.checks {
+            if !config.devices.iter().any(|d| d.id == check.device) {
+                validation_errors.push(format!(
+                    "Warm-up '{}' checks unknown device '{}'",
+                    warmup.name, check.device
+                ));
+            }
+        }
+    }
+
+    if let Err(e) = config.self_test.validate() {
+        validation_errors.push(e.to_string());
+    }
+    if let Err(e) = config.inventory.validate() {
+        validation_errors.push(e.to_string());
+    }
+    if let Err(e) = config.display.validate() {
+        validation_errors.push(format!("{:#}", e));
+    }
+    for device in &config.self_test.devices {
+        if !config.devices.iter().any(|d| d.id == device.device) {
+            validation_errors.push(format!(
+                "Self-test targets unknown device '{}'",
+                device.device
+            ));
+        }
+    }
+
+    for device_id in config.device_tags.keys() {
+        if !config.devices.iter().any(|d| &d.id == device_id) {
+            validation_errors.push(format!("Device tags target unknown device '{}'", device_id));
+        }
+    }
+
+    for device_id in config.parameter_policies.keys() {
+        if !config.devices.iter().any(|d| &d.id == device_id) {
+            validation_errors.push(format!(
+                "Parameter policies target unknown device '{}'",
+                device_id
+            ));
+        }
+    }
+
+    for (device_id, correction) in &config.motion_corrections {
+        if !config.devices.iter().any(|d| &d.id == device_id) {
+            validation_errors.push(format!(
+                "Motion correction targets unknown device '{}'",
+                device_id
+            ));
+        }
+        if let Err(e) = correction.validate() {
+            validation_errors.push(format!("Motion correction for '{}': {}", device_id, e));
+        }
+    }
+
+    for device_id in config.timestamp_offsets.keys() {
+        if !config.devices.iter().any(|d| &d.id == device_id) {
+            validation_errors.push(format!(
+                "Timestamp offset targets unknown device '{}'",
+                device_id
+            ));
+        }
+    }
+
+    for device_id in config.parking.keys() {
+        if !config.devices.iter().any(|d| &d.id == device_id) {
+            validation_errors.push(format!(
+                "Parking actions target unknown device '{}'",
+                device_id
+            ));
+        }
+    }
+
+    for (device_id, params) in &config.output_limits {
+        if !config.devices.iter().any(|d| &d.id == device_id) {
+            validation_errors.push(format!(
+                "Output limits target unknown device '{}'",
+                device_id
+            ));
+        }
+        for (parameter, limit) in params {
+            if let Err(e) = limit.validate() {
+                validation_errors.push(format!(
+                    "Output limit for '{}' {}: {}",
+                    device_id, parameter, e
+                ));
+            }
+        }
+    }
+
+    for (device_id, zones) in &config.keep_out {
+        if !config.devices.iter().any(|d| &d.id == device_id) {
+            validation_errors.push(format!(
+                "Keep-out zones target unknown device '{}'",
+                device_id
+            ));
+        }
+        for zone in zones {
+            if let Err(e) = zone.validate() {
+                validation_errors.push(format!("Keep-out zone of '{}': {}", device_id, e));
+            } else if zone.range.is_none() {
+                validation_errors.push(format!(
+                    "Keep-out zone {} of '{}' is a polygon; configure it on a motion group",
+                    zone, device_id
+                ));
+            }
+        }
+    }
+
+    if let Err(e) = validation::validate_rules(&config.validation_rules) {
+        validation_errors.push(e.to_string());
+    }
+
+    let mut group_ids = std::collections::HashSet::new();
+    for group in &config.motion_groups {
+        if !group_ids.insert(group.id.as_str()) {
+            validation_errors.push(format!("Duplicate motion group '{}'", group.id));
+        }
+        if let Err(e) = group.validate() {
+            validation_errors.push(e.to_string());
+        }
+        for member in &group.members {
+            if !config.devices.iter().any(|d| &d.id == member) {
+                validation_errors.push(format!(
+                    "Motion group '{}' uses unknown device '{}'",
+                    group.id, member
+                ));
+            }
+        }
+    }
+    let mut positioner_ids = std::collections::HashSet::new();
+    for positioner in &config.discrete_positioners {
+        if !positioner_ids.insert(positioner.id.as_str())
+            || config.devices.iter().any(|d| d.id == positioner.id)
+        {
+            validation_errors.push(format!("Positioner '{}' reuses a device ID", positioner.id));
+        }
+        if let Err(e) = positioner.validate() {
+            validation_errors.push(e.to_string());
+        }
+        let device = positioner.drive.device();
+        if !config.devices.iter().any(|d| d.id == device)
+            && !group_device_ids.iter().any(|id| id == device)
+        {
+            validation_errors.push(format!(
+                "Positioner '{}' drives unknown device '{}'",
+                positioner.id, device
+            ));
+        }
+    }
+    let mut trigger_ids = std::collections::HashSet::new();
+    for trigger in &config.composite_triggers {
+        if !trigger_ids.insert(trigger.id.as_str())
+            || config.devices.iter().any(|d| d.id == trigger.id)
+            || positioner_ids.contains(trigger.id.as_str())
+        {
+            validation_errors.push(format!("Trigger '{}' reuses a device ID", trigger.id));
+        }
+        let evaluator = match TriggerEvaluator::compile(&trigger.condition) {
+            Ok(evaluator) => evaluator,
+            Err(e) => {
+                validation_errors.push(format!("Trigger '{}': {}", trigger.id, e));
+                continue;
+            }
+        };
+        if let Err(e) = trigger.validate() {
+            validation_errors.push(e.to_string());
+        }
+        let known = |name: &str| {
+            let device = config.aliases.resolve(name).device_id;
+            config.devices.iter().any(|d| d.id == device) || group_device_ids.contains(&device)
+        };
+        for channel in evaluator.channels() {
+            if !known(channel) {
+                validation_errors.push(format!(
+                    "Trigger '{}' reads unknown channel '{}'",
+                    trigger.id, channel
+                ));
+            }
+        }
+        if let Some(target) = trigger.target.as_deref().filter(|t| !known(t)) {
+            validation_errors.push(format!(
+                "Trigger '{}' fires unknown device '{}'",
+                trigger.id, target
+            ));
+        }
+    }
+    for (device_id, annotations) in &config.frame_annotations {
+        if !config.devices.iter().any(|d| d.id == *device_id) {
+            validation_errors.push(format!("Annotations for unknown camera '{}'", device_id));
+        } else if let Err(e) = validate_annotations(annotations) {
+            validation_errors.push(format!("Annotations for '{}': {}", device_id, e));
+        }
+    }
+    for sample in &config.sample_registrations {
+        if !group_ids.insert(sample.sample_id.as_str()) {
+            validation_errors.push(format!(
+                "Sample '{}' reuses a motion group or sample ID",
+                sample.sample_id
+            ));
+        }
+    }
+
+    if !validation_errors.is_empty() {
+        return Err(DaqError::Configuration(format!(
+            "Hardware configuration validation failed:\n  - {}",
+            validation_errors.join("\n  - ")
+        )));
+    }
+
+    // Load plugins from configured search paths
+    #[cfg(feature = "serial")]
+    {
+        let mut factory = registry.plugin_factory.write().await;
+        for path in &config.plugin_paths {
+            // Expand ~ to home directory
+            let expanded = if path.starts_with("~") {
+                if let Some(home) = dirs::home_dir() {
+                    home.join(path.strip_prefix("~").unwrap_or(path))
+                } else {
+                    path.clone()
+                }
+            } else {
+                path.clone()
+            };
+            factory.add_search_path(expanded);
+        }
+
+        // Scan all paths and report errors
+        let errors = factory.scan().await;
+        for err in &errors {
+            tracing::warn!("Plugin load warning: {}", err);
+        }
+
+        // Log loaded plugins
+        let plugins = factory.available_plugins();
+        if !plugins.is_empty() {
+            tracing::info!("Loaded {} plugin(s): {:?}", plugins.len(), plugins);
+        }
+    }
+
+    // Register all configured devices
+    let mut success_count = 0;
+    let mut failure_count = 0;
+
+    for device_config in &config.devices {
+        tracing::info!(
+            device_id = %device_config.id,
+            device_name = %device_config.name,
+            driver_type = %device_config.driver.driver_name(),
+            "Registering device"
+        );
+
+        let driver_type = device_config.driver.driver_name();
+        let simulated = (device_config.simulated || config.simulate_all)
+            && device_config.driver.simulated_backend().is_some();
+
+        // Check if a factory is registered for this driver type
+        let result = if simulated {
+            registry.register_simulated(device_config).await
+        } else if registry.has_factory(driver_type) {
+            // Use factory-based registration
+            match toml::Value::try_from(&device_config.driver) {
+                Ok(toml_config) => {
+                    registry
+                        .register_from_toml(
+                            &device_config.id,
+                            &device_config.name,
+                            driver_type,
+                            toml_config,
+                        )
+                        .await
+                }
+                Err(e) => Err(DaqError::Configuration(format!(
+                    "Failed to convert driver config to TOML: {}",
+                    e
+                ))),
+            }
+        } else {
+            // Use legacy DriverType enum registration
+            registry.register(device_config.clone()).await
+        };
+
+        if let Err(e) = result {
+            failure_count += 1;
+            registry.record_registration_failure(RegistrationFailure {
+                device_id: device_config.id.clone(),
+                device_name: device_config.name.clone(),
+                driver_type: device_config.driver.driver_name().to_string(),
+                error: e.to_string(),
+            });
+        } else {
+            success_count += 1;
+            tracing::info!(
+                device_id = %device_config.id,
+                "Device registered successfully"
+            );
+        }
+    }
+
+    // Groups move members through their corrections, so apply those first
+    registry.set_motion_corrections(config.motion_corrections.clone())?;
+    // Likewise limits, which groups get through `get_movable`
+    registry.set_output_limits(config.output_limits.clone())?;
+    registry.set_keep_out_zones(config.keep_out.clone())?;
+    registry.set_parking_actions(config.parking.clone());
+    registry.set_validation_rules(config.validation_rules.clone())?;
+    registry.set_timestamp_corrections(config.timestamp_offsets.clone().into());
+
+    // Groups need their members, so they come after all devices
+    for group in &config.motion_groups {
+        if let Err(e) = registry.register_motion_group(group.clone()) {
+            failure_count += 1;
+            registry.record_registration_failure(RegistrationFailure {
+                device_id: group.id.clone(),
+                device_name: group.name.clone(),
+                driver_type: MOTION_GROUP_DRIVER_TYPE.to_string(),
+                error: e.to_string(),
+            });
+        }
+    }
+    for sample in &config.sample_registrations {
+        if let Err(e) = registry.register_sample_fiducials(sample.clone()) {
+            failure_count += 1;
+            registry.record_registration_failure(RegistrationFailure {
+                device_id: sample.sample_id.clone(),
+                device_name: format!("Sample {}", sample.sample_id),
+                driver_type: MOTION_GROUP_DRIVER_TYPE.to_string(),
+                error: e.to_string(),
+            });
+        }
+    }
 
-                // Use with_shared_port_calibrated() to validate device responds and get calibration
-                let driver = Arc::new(
-                    crate::drivers::ell14::Ell14Driver::with_shared_port_calibrated(
-                        shared_port,
-                        &address,
-                    )
-                    .await?,
-                );
-                Ok(RegisteredDevice {
-                    config,
-                    driver_type: driver_type_name.clone(),
-                    movable: Some(driver.clone()),
-                    readable: None,
-                    triggerable: None,
-                    frame_producer: None,
-                    source_frame: None,
-                    exposure_control: None,
-                    settable: None,
-                    stageable: None,
-                    commandable: None,
-                    parameterized: Some(driver.clone()),
-                    shutter_control: None,
-                    emission_control: None,
-                    wavelength_tunable: None,
-                    lifecycle: None,
-                    metadata: DeviceMetadata {
-                        position_units: Some("degrees".to_string()),
-                        min_position: Some(0.0),
-                        max_position: Some(360.0),
-                        ..Default::default()
-                    },
-                })
-            }
+    // Positioners may drive group axes, so they come last
+    for positioner in &config.discrete_positioners {
+        if let Err(e) = registry.register_discrete_positioner(positioner.clone()) {
+            failure_count += 1;
+            registry.record_registration_failure(RegistrationFailure {
+                device_id: positioner.id.clone(),
+                device_name: positioner.name.clone(),
+                driver_type: DISCRETE_POSITIONER_DRIVER_TYPE.to_string(),
+                error: e.to_string(),
+            });
+        }
+    }
 
-            #[cfg(feature = "newport")]
-            DriverType::Newport1830C { port } => {
-                // Use new_async() to validate device responds correctly on connection
-                let driver = Arc::new(
-                    crate::drivers::newport_1830c::Newport1830CDriver::new_async(&port).await?,
-                );
-                // Newport1830C implements WavelengthTunable (bd-3xw2.5)
-                let wavelength_range = driver.wavelength_range();
-                Ok(RegisteredDevice {
-                    config,
-                    driver_type: driver_type_name.clone(),
-                    movable: None,
-                    readable: Some(driver.clone()),
-                    triggerable: None,
-                    frame_producer: None,
-                    source_frame: None,
-                    exposure_control: None,
-                    settable: None,
-                    stageable: None,
-                    commandable: None,
-                    parameterized: Some(driver.clone()),
-                    shutter_control: None,
-                    emission_control: None,
-                    wavelength_tunable: Some(driver),
-                    lifecycle: None,
-                    metadata: DeviceMetadata {
-                        measurement_units: Some("W".to_string()),
-                        min_wavelength_nm: Some(wavelength_range.0),
-                        max_wavelength_nm: Some(wavelength_range.1),
-                        ..Default::default()
-                    },
-                })
-            }
+    registry.set_aliases(config.aliases.clone())?;
 
-            #[cfg(feature = "spectra_physics")]
-            DriverType::MaiTai { port } => {
-                // Use new_async() to validate device identity on connection
-                let driver = Arc::new(
-                    daq_driver_spectra_physics::MaiTaiDriver::new_async_default(&port).await?,
-                );
-                Ok(RegisteredDevice {
-                    config,
-                    driver_type: driver_type_name.clone(),
-                    movable: None,
-                    readable: Some(driver.clone()),
-                    triggerable: None,
-                    frame_producer: None,
-                    source_frame: None,
-                    exposure_control: None,
-                    settable: None,
-                    stageable: None,
-                    commandable: None,
-                    parameterized: Some(driver.clone()),
-                    shutter_control: Some(driver.clone()),
-                    emission_control: Some(driver.clone()),
-                    wavelength_tunable: Some(driver),
-                    lifecycle: None,
-                    metadata: DeviceMetadata {
-                        measurement_units: Some("W".to_string()),
-                        ..Default::default()
-                    },
-                })
-            }
+    // Trigger channels may be aliases, so triggers come after the aliases
+    for trigger in &config.composite_triggers {
+        if let Err(e) = registry.register_composite_trigger(trigger.clone()) {
+            failure_count += 1;
+            registry.record_registration_failure(RegistrationFailure {
+                device_id: trigger.id.clone(),
+                device_name: trigger.name.clone(),
+                driver_type: COMPOSITE_TRIGGER_DRIVER_TYPE.to_string(),
+                error: e.to_string(),
+            });
+        }
+    }
+    registry.set_frame_enrichment(config.frame_enrichment.clone());
+    for (device_id, annotations) in &config.frame_annotations {
+        if let Err(e) = registry.set_frame_annotations(device_id, annotations.clone()) {
+            tracing::warn!(device_id = %device_id, error = %e, "Frame annotations not applied");
+        }
+    }
+    registry.set_document_transforms(&config.document_transforms);
+    registry.set_preprocessing(config.preprocessing.clone());
+    registry.set_recipes(config.recipes.clone());
+    registry.set_warmups(config.warmups.clone());
+    registry.set_self_test(config.self_test.clone());
+    registry.set_inventory(config.inventory.clone());
+    registry.set_display_calibration(DisplayCalibration::load(&config.display).unwrap_or_else(
+        |e| {
+            tracing::warn!(
+                error = %format!("{:#}", e),
+                "Display calibration file unusable; runtime changes will not be saved"
+            );
+            DisplayCalibration::in_memory(&config.display)
+        },
+    ));
+    registry.set_device_tags(config.device_tags.clone());
+    registry.set_parameter_policies(config.parameter_policies.clone());
 
-            #[cfg(feature = "newport")]
-            DriverType::Esp300 { port, axis } => {
-                // Use new_async() to validate device responds correctly on connection
-                let driver =
-                    Arc::new(crate::drivers::esp300::Esp300Driver::new_async(&port, axis).await?);
-                Ok(RegisteredDevice {
-                    config,
-                    driver_type: driver_type_name.clone(),
-                    movable: Some(driver.clone()),
-                    readable: None,
-                    triggerable: None,
-                    frame_producer: None,
-                    source_frame: None,
-                    exposure_control: None,
-                    settable: None,
-                    stageable: None,
-                    commandable: None,
-                    parameterized: Some(driver),
-                    shutter_control: None,
-                    emission_control: None,
-                    wavelength_tunable: None,
-                    lifecycle: None,
-                    metadata: DeviceMetadata {
-                        position_units: Some("mm".to_string()),
-                        min_position: Some(-25.0), // Typical ESP300 stage range
-                        max_position: Some(25.0),
-                        ..Default::default()
-                    },
-                })
-            }
+    // Summary logging
+    if failure_count > 0 {
+        tracing::warn!(
+            success_count,
+            failure_count,
+            "Device registration completed with failures"
+        );
+    } else {
+        tracing::info!(success_count, "All devices registered successfully");
+    }
 
-            // Handle disabled features
-            #[cfg(all(not(feature = "thorlabs"), feature = "serial"))]
-            DriverType::Ell14 { .. } => Err(anyhow!("ELL14 driver requires 'thorlabs' feature")),
+    // Bring devices to their known state; failures are logged, not fatal
+    let failed_recipes = registry
+        .run_recipes(RecipeTrigger::Startup, None)
+        .await
+        .iter()
+        .filter(|report| !report.success())
+        .count();
+    if failed_recipes > 0 {
+        tracing::warn!(failed_recipes, "Some startup init recipes failed");
+    }
 
-            #[cfg(all(not(feature = "newport"), feature = "serial"))]
-            DriverType::Newport1830C { .. } => {
-                Err(anyhow!("Newport 1830-C driver requires 'newport' feature"))
-            }
+    Ok(registry)
+}
 
-            #[cfg(all(not(feature = "spectra_physics"), feature = "serial"))]
-            DriverType::MaiTai { .. } => {
-                Err(anyhow!("MaiTai driver requires 'spectra_physics' feature"))
-            }
+/// Load hardware configuration from a file and create a DeviceRegistry
+pub async fn create_registry_from_file(path: &std::path::Path) -> Result<DeviceRegistry, DaqError> {
+    let config = HardwareConfig::from_file(path)?;
+    create_registry_from_config(&config).await
+}
 
-            #[cfg(all(not(feature = "newport"), feature = "serial"))]
-            DriverType::Esp300 { .. } => Err(anyhow!("ESP300 driver requires 'newport' feature")),
+// =============================================================================
+// Convenience Functions for Lab Configuration
+// =============================================================================
 
-            // Factory-based Comedi drivers (must use factory registration)
-            #[cfg(feature = "comedi")]
-            DriverType::ComediAnalogInput { .. } => Err(anyhow!(
-                "ComediAnalogInput devices must be registered via DriverFactory system"
-            )),
+/// Create a DeviceRegistry pre-configured for the maitai@100.117.5.12 lab system
+///
+/// This registers all known instruments from docs/HARDWARE_INVENTORY.md:
+/// - Newport 1830-C Power Meter on /dev/ttyS0
+/// - MaiTai Laser on /dev/ttyUSB5
+/// - ELL14 Rotators on /dev/ttyUSB0 (addresses 2, 3, 8)
+/// - ESP300 on /dev/ttyUSB1 (if available)
+#[cfg(feature = "serial")]
+pub async fn create_lab_registry() -> Result<DeviceRegistry, DaqError> {
+    let registry = DeviceRegistry::new();
 
-            #[cfg(feature = "comedi")]
-            DriverType::ComediAnalogOutput { .. } => Err(anyhow!(
-                "ComediAnalogOutput devices must be registered via DriverFactory system"
-            )),
+    // Newport 1830-C Power Meter
+    if let Err(e) = registry
+        .register(DeviceConfig {
+            id: "power_meter".into(),
+            name: "Newport 1830-C Power Meter".into(),
+            driver: DriverType::Newport1830C {
+                port: "/dev/ttyS0".into(),
+            },
+            simulated: false,
+        })
+        .await
+    {
+        tracing::warn!("Newport 1830-C registration failed: {}", e);
+    }
+
+    // MaiTai Laser
+    if let Err(e) = registry
+        .register(DeviceConfig {
+            id: "maitai".into(),
+            name: "Spectra-Physics MaiTai Ti:Sapphire Laser".into(),
+            driver: DriverType::MaiTai {
+                port: "/dev/ttyUSB5".into(),
+            },
+            simulated: false,
+        })
+        .await
+    {
+        tracing::warn!("MaiTai registration failed: {}", e);
+    }
+
+    // ELL14 Rotators (3 units on multidrop bus)
+    for (addr, serial) in [("2", "005172023"), ("3", "002842021"), ("8", "006092023")] {
+        if let Err(e) = registry
+            .register(DeviceConfig {
+                id: format!("rotator_{}", addr),
+                name: format!(
+                    "Thorlabs ELL14 Rotation Mount (Addr {}, SN {})",
+                    addr, serial
+                ),
+                driver: DriverType::Ell14 {
+                    port: "/dev/ttyUSB0".into(),
+                    address: addr.into(),
+                },
+                simulated: false,
+            })
+            .await
+        {
+            tracing::warn!("ELL14 (addr {}) registration failed: {}", addr, e);
         }
     }
 
-    /// Instantiate a plugin-based device
-    #[cfg(feature = "serial")]
-    async fn instantiate_plugin_device(
-        &self,
-        config: DeviceConfig,
-        plugin_id: &str,
-        address: &str,
-    ) -> Result<RegisteredDevice> {
-        // Spawn the driver from the plugin factory
-        let factory = self.plugin_factory.read().await;
-        let driver = Arc::new(factory.spawn(plugin_id, address).await?);
-        drop(factory); // Release lock before calling helper
+    // ESP300 Motion Controller (may not be powered on)
+    // Note: This often fails if ESP300 is not powered
+    if let Err(e) = registry
+        .register(DeviceConfig {
+            id: "stage_1".into(),
+            name: "Newport ESP300 Axis 1".into(),
+            driver: DriverType::Esp300 {
+                port: "/dev/ttyUSB1".into(),
+                axis: 1,
+            },
+            simulated: false,
+        })
+        .await
+    {
+        tracing::warn!("ESP300 registration failed (likely powered off): {}", e);
+    }
 
-        // Create the registered device using the common helper
-        self.create_registered_plugin(config, driver).await
+    // Prime BSI Camera (PVCAM)
+    #[cfg(feature = "pvcam")]
+    if let Err(e) = registry
+        .register(DeviceConfig {
+            id: "prime_bsi".into(),
+            name: "Teledyne Prime BSI sCMOS".into(),
+            driver: DriverType::Pvcam {
+                camera_name: "PMUSBCam00".into(),
+            },
+            simulated: false,
+        })
+        .await
+    {
+        tracing::warn!("Prime BSI camera registration failed: {}", e);
     }
 
-    /// Creates a RegisteredDevice from a pre-spawned plugin driver
-    ///
-    /// This is the shared implementation used by both `instantiate_plugin_device`
-    /// (for config-based registration) and `register_plugin_instance` (for
-    /// PluginService-managed registration).
-    #[cfg(feature = "serial")]
-    async fn create_registered_plugin(
-        &self,
-        config: DeviceConfig,
-        driver: Arc<GenericDriver>,
-    ) -> Result<RegisteredDevice> {
-        let plugin_id = match &config.driver {
-            DriverType::Plugin { plugin_id, .. } => plugin_id,
-            _ => {
-                return Err(anyhow!(
-                    "Invalid driver type for create_registered_plugin: expected Plugin"
-                ));
-            }
-        };
-        let driver_type_name = config.driver.driver_name().to_string();
+    Ok(registry)
+}
 
-        // Introspect capabilities from the plugin configuration
-        let factory = self.plugin_factory.read().await;
-        let plugin_config = factory
-            .get_config(plugin_id)
-            .ok_or_else(|| anyhow!("Plugin '{}' not found in factory", plugin_id))?;
+#[cfg(not(feature = "serial"))]
+pub async fn create_lab_registry() -> Result<DeviceRegistry, DaqError> {
+    Ok(DeviceRegistry::new())
+}
 
-        let mut metadata = DeviceMetadata::default();
+/// Create a DeviceRegistry with mock devices for testing
+pub async fn create_mock_registry() -> Result<DeviceRegistry, DaqError> {
+    let registry = DeviceRegistry::new();
 
-        // Check for movable capability
-        let movable: Option<Arc<dyn Movable>> = if plugin_config.capabilities.movable.is_some() {
-            // Extract metadata from first axis
-            if let Some(movable_cap) = &plugin_config.capabilities.movable {
-                if let Some(first_axis) = movable_cap.axes.first() {
-                    metadata.position_units = first_axis.unit.clone();
-                    metadata.min_position = first_axis.min;
-                    metadata.max_position = first_axis.max;
-                }
-            }
+    registry
+        .register(DeviceConfig {
+            id: "mock_stage".into(),
+            name: "Mock Stage".into(),
+            driver: DriverType::MockStage {
+                initial_position: 0.0,
+            },
+            simulated: false,
+        })
+        .await?;
 
-            // Create axis handle for the first axis (convention)
-            let axis_name = plugin_config
-                .capabilities
-                .movable
-                .as_ref()
-                .and_then(|m| m.axes.first())
-                .map(|a| a.name.as_str())
-                .unwrap_or("axis");
+    registry
+        .register(DeviceConfig {
+            id: "mock_power_meter".into(),
+            name: "Mock Power Meter".into(),
+            driver: DriverType::MockPowerMeter { reading: 1e-6 },
+            simulated: false,
+        })
+        .await?;
 
-            Some(Arc::new(crate::plugin::handles::PluginAxisHandle::new(
-                driver.clone(),
-                axis_name.to_string(),
-                false, // not mocking
-            )))
-        } else {
-            None
-        };
+    registry
+        .register(DeviceConfig {
+            id: "mock_camera".into(),
+            name: "Mock Camera".into(),
+            driver: DriverType::MockCamera {
+                width: 640,
+                height: 480,
+            },
+            simulated: false,
+        })
+        .await?;
 
-        // Check for readable capability
-        let readable: Option<Arc<dyn Readable>> = if !plugin_config.capabilities.readable.is_empty()
-        {
-            // Extract metadata from first readable
-            if let Some(first_readable) = plugin_config.capabilities.readable.first() {
-                metadata.measurement_units = first_readable.unit.clone();
-            }
+    Ok(registry)
+}
 
-            // Create readable handle for the first readable capability (convention)
-            let readable_name = plugin_config
-                .capabilities
-                .readable
-                .first()
-                .map(|r| r.name.as_str())
-                .unwrap_or("reading");
+/// Register all mock driver factories with a registry.
+///
+/// This enables using `register_from_toml()` for mock devices:
+///
+/// ```rust,ignore
+/// use daq_hardware::registry::{DeviceRegistry, register_mock_factories};
+///
+/// let registry = DeviceRegistry::new();
+/// register_mock_factories(&registry);
+///
+/// // Now register mock devices via TOML config
+/// registry.register_from_toml(
+///     "my_stage",
+///     "My Test Stage",
+///     "mock_stage",
+///     toml::Value::Table(Default::default()),
+/// ).await?;
+/// ```
+pub fn register_mock_factories(registry: &DeviceRegistry) {
+    use daq_driver_mock::{
+        MockCameraFactory, MockDAQOutputFactory, MockLaserFactory, MockPowerMeterFactory,
+        MockRotatorFactory, MockSignalFactory, MockStageFactory,
+    };
 
-            Some(Arc::new(crate::plugin::handles::PluginSensorHandle::new(
-                driver.clone(),
-                readable_name.to_string(),
-                false, // not mocking
-            )))
-        } else {
-            None
-        };
+    registry.register_factory(Box::new(MockStageFactory));
+    registry.register_factory(Box::new(MockCameraFactory));
+    registry.register_factory(Box::new(MockPowerMeterFactory));
+    // Stand-ins for simulated hardware (see `DriverType::simulated_backend`)
+    registry.register_factory(Box::new(MockLaserFactory));
+    registry.register_factory(Box::new(MockRotatorFactory));
+    registry.register_factory(Box::new(MockDAQOutputFactory));
+    // Synthetic scalar channels (see the server's simulator mode)
+    registry.register_factory(Box::new(MockSignalFactory));
+}
 
-        // Note: FrameProducer, Triggerable, and ExposureControl are not yet
-        // supported by the plugin system, so we leave them as None
+/// Register all available hardware driver factories.
+///
+/// This registers factories for all enabled hardware drivers:
+/// - Mock drivers (always available)
+/// - Thorlabs ELL14 (when `thorlabs` feature enabled)
+/// - Newport ESP300 and 1830-C (when `newport` feature enabled)
+/// - Spectra-Physics MaiTai (when `spectra_physics` feature enabled)
+/// - Config-driven devices from TOML files
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use daq_hardware::registry::{DeviceRegistry, register_all_factories};
+/// use std::path::Path;
+///
+/// let registry = DeviceRegistry::new();
+/// register_all_factories(&registry, Some(Path::new("config/devices"))).await?;
+///
+/// // Now use register_from_toml() for any supported driver type
+/// ```
+pub async fn register_all_factories(
+    registry: &DeviceRegistry,
+    config_dir: Option<&std::path::Path>,
+) -> Result<(), DaqError> {
+    // Register mock factories (always available)
+    register_mock_factories(registry);
 
-        Ok(RegisteredDevice {
-            config,
-            driver_type: driver_type_name.clone(),
-            movable,
-            readable,
-            triggerable: None,
-            frame_producer: None,
-            source_frame: None,
-            exposure_control: None,
-            settable: None,
-            stageable: None,
-            commandable: None,
-            parameterized: Some(driver.clone()), // bd-plb6: Wire Parameterized for plugin devices
-            shutter_control: None,
-            emission_control: None,
-            wavelength_tunable: None,
-            lifecycle: None,
-            metadata,
-        })
+    // Register Thorlabs factories
+    #[cfg(feature = "thorlabs")]
+    {
+        use daq_driver_thorlabs::Ell14Factory;
+        registry.register_factory(Box::new(Ell14Factory));
     }
 
-    /// Snapshot all parameters from all devices with Parameterized trait (bd-ej44)
-    ///
-    /// Returns a nested map: device_id -> parameter_name -> JSON value
-    /// This is used for experiment manifests to capture complete hardware state.
-    ///
-    /// # Example
-    ///
-    /// ```rust,ignore
-    /// let snapshot = registry.snapshot_all_parameters();
-    /// // Returns:
-    /// // {
-    /// //   "mock_camera": {
-    /// //     "exposure_ms": 100.0,
-    /// //     "gain": 1.5
-    /// //   },
-    /// //   "mock_stage": {
-    /// //     "position": 0.0
-    /// //   }
-    /// // }
-    /// ```
-    ///
-    /// # Thread Safety (bd-pf31)
-    /// This method iterates over all devices with fine-grained locking per entry.
-    pub fn snapshot_all_parameters(&self) -> HashMap<String, HashMap<String, serde_json::Value>> {
-        let mut snapshot = HashMap::new();
+    // Register Newport factories
+    #[cfg(feature = "newport")]
+    {
+        use daq_driver_newport::{Esp300Factory, Newport1830CFactory};
+        registry.register_factory(Box::new(Esp300Factory));
+        registry.register_factory(Box::new(Newport1830CFactory));
+    }
 
-        for entry in self.devices.iter() {
-            let device_id = entry.key();
-            let device = entry.value();
-            if let Some(parameterized) = &device.parameterized {
-                let params = parameterized.parameters();
-                let mut device_params = HashMap::new();
+    // Register Spectra-Physics factories
+    #[cfg(feature = "spectra_physics")]
+    {
+        use daq_driver_spectra_physics::MaiTaiFactory;
+        registry.register_factory(Box::new(MaiTaiFactory));
+    }
+
+    // Register Red Pitaya factories
+    #[cfg(feature = "red_pitaya")]
+    {
+        use daq_driver_red_pitaya::RedPitayaPidFactory;
+        registry.register_factory(Box::new(RedPitayaPidFactory));
+    }
 
-                for (name, param) in params.iter() {
-                    // Get JSON value for each parameter
-                    if let Ok(value) = param.get_json() {
-                        device_params.insert(name.to_string(), value);
-                    } else {
-                        // If serialization fails, store error marker
-                        device_params.insert(
-                            name.to_string(),
-                            serde_json::json!({"error": "serialization_failed"}),
-                        );
+    // Register Comedi factories (NI DAQ, etc.)
+    #[cfg(feature = "comedi")]
+    {
+        use daq_driver_comedi::{ComediAnalogInputFactory, ComediAnalogOutputFactory};
+        registry.register_factory(Box::new(ComediAnalogInputFactory));
+        registry.register_factory(Box::new(ComediAnalogOutputFactory));
+    }
+
+    // Load and register config-driven factories from TOML files
+    #[cfg(feature = "serial")]
+    if let Some(dir) = config_dir {
+        if dir.exists() {
+            match crate::factory::load_all_factories(dir) {
+                Ok(factories) => {
+                    for factory in factories {
+                        let driver_type = factory.driver_type().to_string();
+                        registry.register_factory(Box::new(factory));
+                        tracing::debug!(driver_type = %driver_type, "Registered config factory");
                     }
                 }
-
-                if !device_params.is_empty() {
-                    snapshot.insert(device_id.clone(), device_params);
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to load config factories from {}: {}",
+                        dir.display(),
+                        e
+                    );
                 }
             }
         }
-
-        snapshot
     }
-}
 
-impl Default for DeviceRegistry {
-    fn default() -> Self {
-        Self::new()
-    }
+    Ok(())
 }
 
 // =============================================================================
-// Hardware Configuration File Support
+// Tests
 // =============================================================================
 
-/// Hardware configuration loaded from a TOML file
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct HardwareConfig {
-    /// Plugin search paths (in priority order, first = highest priority)
-    /// Convention: user paths before system paths
-    #[serde(default)]
-    pub plugin_paths: Vec<std::path::PathBuf>,
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    /// List of devices to register
-    pub devices: Vec<DeviceConfig>,
-}
+    #[test]
+    fn test_mock_camera_deserializes_with_default_resolution() {
+        let driver: DriverType = serde_json::from_value(serde_json::json!({
+            "type": "mock_camera"
+        }))
+        .unwrap();
 
-impl HardwareConfig {
-    /// Load hardware configuration from a TOML file
-    pub fn from_file(path: &std::path::Path) -> Result<Self, DaqError> {
-        let content = std::fs::read_to_string(path).map_err(|e| {
-            DaqError::Configuration(format!("Failed to read hardware config file: {}", e))
-        })?;
-        toml::from_str(&content).map_err(|e| {
-            DaqError::Configuration(format!("Failed to parse hardware config file: {}", e))
-        })
+        match driver {
+            DriverType::MockCamera { width, height } => {
+                assert_eq!(width, 640);
+                assert_eq!(height, 480);
+            }
+            _ => panic!("Expected MockCamera driver"),
+        }
     }
-}
 
-/// Create a DeviceRegistry from a hardware configuration file
-///
-/// # Example TOML format:
-/// ```toml
-/// # Optional: Plugin search paths (first = highest priority)
-/// plugin_paths = [
-///     "~/.config/rust-daq/plugins",
-///     "/usr/share/rust-daq/plugins"
-/// ]
-///
-/// [[devices]]
-/// id = "rotator_2"
-/// name = "ELL14 Rotation Mount (Addr 2)"
-/// [devices.driver]
-/// type = "ell14"
-/// port = "/dev/ttyUSB0"
-/// address = "2"
-///
-/// [[devices]]
-/// id = "my_sensor"
-/// name = "Custom Sensor (Plugin-Based)"
-/// [devices.driver]
-/// type = "plugin"
-/// plugin_id = "my-sensor-v1"
-/// address = "/dev/ttyUSB2"
-/// ```
-pub async fn create_registry_from_config(
-    config: &HardwareConfig,
-) -> Result<DeviceRegistry, DaqError> {
-    let registry = DeviceRegistry::new();
+    #[tokio::test]
+    async fn test_register_mock_devices() {
+        let registry = create_mock_registry().await.unwrap();
 
-    // Register all available driver factories BEFORE loading devices
-    register_all_factories(&registry, None).await?;
+        assert_eq!(registry.len(), 3);
+        assert!(registry.contains("mock_stage"));
+        assert!(registry.contains("mock_power_meter"));
+        assert!(registry.contains("mock_camera"));
+    }
 
-    // Validate all device configurations first (fail fast)
-    let mut validation_errors = Vec::new();
-    for device_config in &config.devices {
-        if let Err(e) = validate_driver_config(&device_config.driver) {
-            validation_errors.push(format!(
-                "Device '{}' ({}): {}",
-                device_config.id,
-                device_config.driver.driver_name(),
-                e
-            ));
-        }
+    #[tokio::test]
+    async fn test_list_devices() {
+        let registry = create_mock_registry().await.unwrap();
+        let devices = registry.list_devices();
+
+        assert_eq!(devices.len(), 3);
+
+        let stage = devices.iter().find(|d| d.id == "mock_stage").unwrap();
+        assert_eq!(stage.driver_type, "mock_stage");
+        assert!(stage.capabilities.contains(&Capability::Movable));
+
+        let meter = devices.iter().find(|d| d.id == "mock_power_meter").unwrap();
+        assert_eq!(meter.driver_type, "mock_power_meter");
+        assert!(meter.capabilities.contains(&Capability::Readable));
+
+        let camera = devices.iter().find(|d| d.id == "mock_camera").unwrap();
+        assert_eq!(camera.driver_type, "mock_camera");
+        assert!(camera.capabilities.contains(&Capability::FrameProducer));
+        assert!(camera.capabilities.contains(&Capability::Triggerable));
+        assert!(camera.capabilities.contains(&Capability::ExposureControl));
     }
 
-    if !validation_errors.is_empty() {
-        return Err(DaqError::Configuration(format!(
-            "Hardware configuration validation failed:\n  - {}",
-            validation_errors.join("\n  - ")
+    #[tokio::test]
+    async fn test_device_status_follows_health_errors() {
+        let registry = create_mock_registry().await.unwrap();
+        assert_eq!(
+            registry.device_status("mock_camera"),
+            Some(DeviceStatus::Ready)
+        );
+
+        let error = |severity, context: &[(&str, &str)]| HealthError {
+            module_name: "camera".to_string(),
+            severity,
+            message: "Sensor temperature drifting".to_string(),
+            timestamp: std::time::Instant::now(),
+            context: context
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        };
+
+        assert!(registry.apply_health_error(&error(
+            ErrorSeverity::Warning,
+            &[("device_id", "mock_camera")]
+        )));
+        let camera = registry.get_device_info("mock_camera").unwrap();
+        assert_eq!(camera.status.as_str(), "degraded");
+
+        assert!(registry.apply_health_error(&error(
+            ErrorSeverity::Critical,
+            &[("device_id", "mock_camera"), ("code", "-300")]
         )));
+        assert!(matches!(
+            registry.device_status("mock_camera"),
+            Some(DeviceStatus::Fault { code: -300, .. })
+        ));
+
+        // Informational, unattributed and unknown-device errors change nothing
+        assert!(!registry
+            .apply_health_error(&error(ErrorSeverity::Info, &[("device_id", "mock_stage")])));
+        assert!(!registry.apply_health_error(&error(ErrorSeverity::Critical, &[])));
+        assert!(
+            !registry.apply_health_error(&error(ErrorSeverity::Critical, &[("device_id", "nope")]))
+        );
+        assert_eq!(
+            registry.device_status("mock_stage"),
+            Some(DeviceStatus::Ready)
+        );
     }
 
-    // Load plugins from configured search paths
-    #[cfg(feature = "serial")]
-    {
-        let mut factory = registry.plugin_factory.write().await;
-        for path in &config.plugin_paths {
-            // Expand ~ to home directory
-            let expanded = if path.starts_with("~") {
-                if let Some(home) = dirs::home_dir() {
-                    home.join(path.strip_prefix("~").unwrap_or(path))
-                } else {
-                    path.clone()
-                }
-            } else {
-                path.clone()
-            };
-            factory.add_search_path(expanded);
-        }
+    #[tokio::test]
+    async fn test_inventory_flags_firmware_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = create_mock_registry().await.unwrap();
+        registry.set_inventory(InventoryConfig {
+            path: Some(dir.path().join("inventory.json")),
+            ..Default::default()
+        });
 
-        // Scan all paths and report errors
-        let errors = factory.scan().await;
-        for err in &errors {
-            tracing::warn!("Plugin load warning: {}", err);
-        }
+        let register_laser = |firmware: &str| {
+            let components =
+                DeviceComponents::new().with_metadata(common::driver::DeviceMetadata {
+                    identity: Some(DeviceIdentity {
+                        manufacturer: "Spectra-Physics".to_string(),
+                        model: "MaiTai".to_string(),
+                        serial_number: "1234".to_string(),
+                        firmware_version: firmware.to_string(),
+                    }),
+                    ..Default::default()
+                });
+            let device = registry.components_to_registered(
+                "laser".to_string(),
+                "Laser".to_string(),
+                "maitai".to_string(),
+                components,
+            );
+            registry.devices.insert("laser".to_string(), device);
+        };
 
-        // Log loaded plugins
-        let plugins = factory.available_plugins();
-        if !plugins.is_empty() {
-            tracing::info!("Loaded {} plugin(s): {:?}", plugins.len(), plugins);
-        }
+        register_laser("2.15");
+        let first = registry.refresh_inventory().await.unwrap();
+        // Mock devices report no identity and have no raw terminal
+        assert_eq!(first.entries.len(), 1);
+        assert_eq!(first.entries[0].source, IdentitySource::Driver);
+        assert!(first.changes.is_empty());
+
+        register_laser("2.16");
+        let second = registry.refresh_inventory().await.unwrap();
+        assert_eq!(second.changes.len(), 1);
+        assert!(second.changes[0].firmware_changed());
+        assert_eq!(second.identities()["laser"].firmware_version, "2.16");
+        assert_eq!(registry.inventory_report(), Some(second));
+    }
+
+    #[tokio::test]
+    async fn test_legacy_toml_config_registers_mock_devices() {
+        let toml_str = r#"
+[[devices]]
+id = "legacy_stage"
+name = "Legacy Stage"
+[devices.driver]
+type = "mock_stage"
+initial_position = 1.23
+
+[[devices]]
+id = "legacy_camera"
+name = "Legacy Camera"
+[devices.driver]
+type = "mock_camera"
+width = 320
+height = 240
+"#;
+
+        let config: HardwareConfig = toml::from_str(toml_str).unwrap();
+        let registry = create_registry_from_config(&config).await.unwrap();
+
+        let devices = registry.list_devices();
+        assert_eq!(devices.len(), 2);
+
+        let stage = devices.iter().find(|d| d.id == "legacy_stage").unwrap();
+        assert_eq!(stage.driver_type, "mock_stage");
+        assert!(stage.capabilities.contains(&Capability::Movable));
+
+        let camera = devices.iter().find(|d| d.id == "legacy_camera").unwrap();
+        assert_eq!(camera.driver_type, "mock_camera");
+        assert!(camera.capabilities.contains(&Capability::FrameProducer));
+        assert!(camera.capabilities.contains(&Capability::Triggerable));
+        assert!(camera.capabilities.contains(&Capability::ExposureControl));
+
+        assert!(registry.get_movable("legacy_stage").is_some());
+        assert!(registry.get_frame_producer("legacy_camera").is_some());
     }
 
-    // Register all configured devices
-    let mut success_count = 0;
-    let mut failure_count = 0;
+    #[tokio::test]
+    async fn test_channel_aliases_resolve_to_devices() {
+        let toml_str = r#"
+[[devices]]
+id = "stage_x"
+name = "Stage X"
+[devices.driver]
+type = "mock_stage"
+initial_position = 0.0
+
+[aliases]
+sample_x = "stage_x"
+"#;
+
+        let config: HardwareConfig = toml::from_str(toml_str).unwrap();
+        let registry = create_registry_from_config(&config).await.unwrap();
+
+        assert!(registry.contains("sample_x"));
+        assert!(registry.get_movable("sample_x").is_some());
+        assert_eq!(registry.resolve_channel("sample_x").device_id, "stage_x");
+
+        let info = registry.get_device_info("sample_x").unwrap();
+        assert_eq!(info.id, "stage_x");
+        assert_eq!(info.aliases, vec!["sample_x".to_string()]);
+
+        // An alias may not shadow a device ID
+        let shadowing = ChannelAliases::new()
+            .with_alias("stage_x", "other")
+            .unwrap();
+        assert!(registry.set_aliases(shadowing).is_err());
+    }
 
-    for device_config in &config.devices {
-        tracing::info!(
-            device_id = %device_config.id,
-            device_name = %device_config.name,
-            driver_type = %device_config.driver.driver_name(),
-            "Registering device"
-        );
+    #[tokio::test]
+    async fn test_device_tags_from_config() {
+        let toml_str = r#"
+[[devices]]
+id = "stage_x"
+name = "Stage X"
+[devices.driver]
+type = "mock_stage"
+initial_position = 0.0
 
-        let driver_type = device_config.driver.driver_name();
+[[devices]]
+id = "power_meter"
+name = "Power Meter"
+[devices.driver]
+type = "mock_power_meter"
+reading = 1.0
 
-        // Check if a factory is registered for this driver type
-        let result = if registry.has_factory(driver_type) {
-            // Use factory-based registration
-            match toml::Value::try_from(&device_config.driver) {
-                Ok(toml_config) => {
-                    registry
-                        .register_from_toml(
-                            &device_config.id,
-                            &device_config.name,
-                            driver_type,
-                            toml_config,
-                        )
-                        .await
-                }
-                Err(e) => Err(DaqError::Configuration(format!(
-                    "Failed to convert driver config to TOML: {}",
-                    e
-                ))),
-            }
-        } else {
-            // Use legacy DriverType enum registration
-            registry.register(device_config.clone()).await
-        };
+[device_tags]
+stage_x = ["table_1", "sample"]
+"#;
 
-        if let Err(e) = result {
-            failure_count += 1;
-            registry.record_registration_failure(RegistrationFailure {
-                device_id: device_config.id.clone(),
-                device_name: device_config.name.clone(),
-                driver_type: device_config.driver.driver_name().to_string(),
-                error: e.to_string(),
-            });
-        } else {
-            success_count += 1;
-            tracing::info!(
-                device_id = %device_config.id,
-                "Device registered successfully"
-            );
-        }
-    }
+        let config: HardwareConfig = toml::from_str(toml_str).unwrap();
+        let registry = create_registry_from_config(&config).await.unwrap();
 
-    // Summary logging
-    if failure_count > 0 {
-        tracing::warn!(
-            success_count,
-            failure_count,
-            "Device registration completed with failures"
+        assert_eq!(
+            registry.get_device_info("stage_x").unwrap().tags,
+            ["table_1", "sample"]
         );
-    } else {
-        tracing::info!(success_count, "All devices registered successfully");
+        assert!(registry
+            .get_device_info("power_meter")
+            .unwrap()
+            .tags
+            .is_empty());
+
+        let mut bad = config.clone();
+        bad.device_tags.insert("missing".to_string(), vec![]);
+        assert!(create_registry_from_config(&bad).await.is_err());
     }
 
-    Ok(registry)
-}
+    #[cfg(feature = "serial")]
+    #[tokio::test]
+    async fn test_simulated_devices_from_config() {
+        let toml_str = r#"
+[[devices]]
+id = "stage_x"
+name = "ESP300 Axis 1"
+simulated = true
+[devices.driver]
+type = "esp300"
+port = "/dev/nonexistent_serial"
+axis = 1
 
-/// Load hardware configuration from a file and create a DeviceRegistry
-pub async fn create_registry_from_file(path: &std::path::Path) -> Result<DeviceRegistry, DaqError> {
-    let config = HardwareConfig::from_file(path)?;
-    create_registry_from_config(&config).await
-}
+[[devices]]
+id = "rotator"
+name = "ELL14 Rotation Mount"
+[devices.driver]
+type = "ell14"
+port = "/dev/nonexistent_serial"
+address = "2"
+"#;
 
-// =============================================================================
-// Convenience Functions for Lab Configuration
-// =============================================================================
+        // Only the real rotator's port is checked
+        let mut config: HardwareConfig = toml::from_str(toml_str).unwrap();
+        let err = create_registry_from_config(&config)
+            .await
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("rotator") && !err.contains("stage_x"));
 
-/// Create a DeviceRegistry pre-configured for the maitai@100.117.5.12 lab system
-///
-/// This registers all known instruments from docs/HARDWARE_INVENTORY.md:
-/// - Newport 1830-C Power Meter on /dev/ttyS0
-/// - MaiTai Laser on /dev/ttyUSB5
-/// - ELL14 Rotators on /dev/ttyUSB0 (addresses 2, 3, 8)
-/// - ESP300 on /dev/ttyUSB1 (if available)
-#[cfg(feature = "serial")]
-pub async fn create_lab_registry() -> Result<DeviceRegistry, DaqError> {
-    let registry = DeviceRegistry::new();
+        config.simulate_all = true;
+        let registry = create_registry_from_config(&config).await.unwrap();
+        assert!(registry.get_movable("stage_x").is_some());
+        let stage = registry.get_device_info("stage_x").unwrap();
+        assert!(stage.simulated);
+        assert_eq!(stage.driver_type, "mock_stage");
+        let rotator = registry.get_device_info("rotator").unwrap();
+        assert!(rotator.simulated);
+        assert_eq!(rotator.driver_type, "mock_rotator");
 
-    // Newport 1830-C Power Meter
-    if let Err(e) = registry
-        .register(DeviceConfig {
-            id: "power_meter".into(),
-            name: "Newport 1830-C Power Meter".into(),
-            driver: DriverType::Newport1830C {
-                port: "/dev/ttyS0".into(),
-            },
-        })
-        .await
-    {
-        tracing::warn!("Newport 1830-C registration failed: {}", e);
+        config.devices[0].driver = DriverType::Plugin {
+            plugin_id: "my-sensor-v1".into(),
+            address: "/dev/nonexistent_serial".into(),
+        };
+        assert!(create_registry_from_config(&config).await.is_err());
     }
 
-    // MaiTai Laser
-    if let Err(e) = registry
-        .register(DeviceConfig {
-            id: "maitai".into(),
-            name: "Spectra-Physics MaiTai Ti:Sapphire Laser".into(),
-            driver: DriverType::MaiTai {
-                port: "/dev/ttyUSB5".into(),
-            },
-        })
-        .await
-    {
-        tracing::warn!("MaiTai registration failed: {}", e);
-    }
+    #[tokio::test]
+    async fn test_parameter_policies_from_config() {
+        let toml_str = r#"
+[[devices]]
+id = "stage_x"
+name = "Stage X"
+[devices.driver]
+type = "mock_stage"
+initial_position = 0.0
 
-    // ELL14 Rotators (3 units on multidrop bus)
-    for (addr, serial) in [("2", "005172023"), ("3", "002842021"), ("8", "006092023")] {
-        if let Err(e) = registry
-            .register(DeviceConfig {
-                id: format!("rotator_{}", addr),
-                name: format!(
-                    "Thorlabs ELL14 Rotation Mount (Addr {}, SN {})",
-                    addr, serial
-                ),
-                driver: DriverType::Ell14 {
-                    port: "/dev/ttyUSB0".into(),
-                    address: addr.into(),
-                },
-            })
-            .await
-        {
-            tracing::warn!("ELL14 (addr {}) registration failed: {}", addr, e);
-        }
-    }
+[parameter_policies.stage_x]
+velocity = { dangerous = true, confirm_timeout_s = 10 }
+"#;
 
-    // ESP300 Motion Controller (may not be powered on)
-    // Note: This often fails if ESP300 is not powered
-    if let Err(e) = registry
-        .register(DeviceConfig {
-            id: "stage_1".into(),
-            name: "Newport ESP300 Axis 1".into(),
-            driver: DriverType::Esp300 {
-                port: "/dev/ttyUSB1".into(),
-                axis: 1,
-            },
-        })
-        .await
-    {
-        tracing::warn!("ESP300 registration failed (likely powered off): {}", e);
+        let config: HardwareConfig = toml::from_str(toml_str).unwrap();
+        let registry = create_registry_from_config(&config).await.unwrap();
+
+        let policy = registry.parameter_policy("stage_x", "velocity").unwrap();
+        assert!(policy.dangerous);
+        assert_eq!(policy.confirm_timeout_s, Some(10));
+        assert!(registry.parameter_policy("stage_x", "position").is_none());
+
+        // Policies for devices that are not configured are rejected
+        let mut bad = config.clone();
+        bad.parameter_policies
+            .insert("missing".to_string(), HashMap::new());
+        assert!(create_registry_from_config(&bad).await.is_err());
     }
 
-    // Prime BSI Camera (PVCAM)
-    #[cfg(feature = "pvcam")]
-    if let Err(e) = registry
-        .register(DeviceConfig {
-            id: "prime_bsi".into(),
-            name: "Teledyne Prime BSI sCMOS".into(),
-            driver: DriverType::Pvcam {
-                camera_name: "PMUSBCam00".into(),
-            },
-        })
-        .await
-    {
-        tracing::warn!("Prime BSI camera registration failed: {}", e);
+    #[tokio::test]
+    async fn test_motion_group_from_config() {
+        let toml_str = r#"
+[[devices]]
+id = "stage_x"
+name = "Stage X"
+[devices.driver]
+type = "mock_stage"
+initial_position = 3.0
+
+[[devices]]
+id = "stage_y"
+name = "Stage Y"
+[devices.driver]
+type = "mock_stage"
+initial_position = 4.0
+
+[[motion_groups]]
+id = "sample"
+members = ["stage_x", "stage_y"]
+kinematics = { type = "polar" }
+limits = { r = [0.0, 10.0] }
+
+[aliases]
+sample_angle = "sample_theta"
+"#;
+
+        let config: HardwareConfig = toml::from_str(toml_str).unwrap();
+        let registry = create_registry_from_config(&config).await.unwrap();
+
+        assert_eq!(registry.list_motion_groups(), vec!["sample".to_string()]);
+        assert!(registry.get_commandable("sample").is_some());
+
+        let r = registry.get_movable("sample_r").unwrap();
+        assert!((r.position().await.unwrap() - 5.0).abs() < 1e-9);
+        let info = registry.get_device_info("sample_r").unwrap();
+        assert_eq!(info.driver_type, MOTION_GROUP_DRIVER_TYPE);
+        assert_eq!(info.metadata.max_position, Some(10.0));
+        assert_eq!(
+            registry
+                .get_device_info("sample_angle")
+                .unwrap()
+                .metadata
+                .position_units,
+            Some("deg".to_string())
+        );
+
+        // Members must be configured devices
+        let mut bad = config.clone();
+        bad.motion_groups[0].members[1] = "stage_z".to_string();
+        assert!(create_registry_from_config(&bad).await.is_err());
     }
 
-    Ok(registry)
-}
+    #[tokio::test]
+    async fn test_discrete_positioner_from_config() {
+        let toml_str = r#"
+[[devices]]
+id = "wheel_rotator"
+name = "Filter wheel rotator"
+[devices.driver]
+type = "mock_stage"
+initial_position = 0.0
+
+[[discrete_positioners]]
+id = "nd_wheel"
+drive = { type = "movable", device = "wheel_rotator", tolerance = 0.1 }
+positions = [
+    { name = "open", position = 0.0 },
+    { name = "nd1", position = 2.0 },
+]
+"#;
 
-#[cfg(not(feature = "serial"))]
-pub async fn create_lab_registry() -> Result<DeviceRegistry, DaqError> {
-    Ok(DeviceRegistry::new())
-}
+        let config: HardwareConfig = toml::from_str(toml_str).unwrap();
+        let registry = create_registry_from_config(&config).await.unwrap();
 
-/// Create a DeviceRegistry with mock devices for testing
-pub async fn create_mock_registry() -> Result<DeviceRegistry, DaqError> {
-    let registry = DeviceRegistry::new();
+        let wheel = registry.get_discrete_positioner("nd_wheel").unwrap();
+        assert_eq!(
+            wheel.current_position().await.unwrap().as_deref(),
+            Some("open")
+        );
+        wheel.move_to_position("nd1").await.unwrap();
+        assert_eq!(
+            wheel.current_position().await.unwrap().as_deref(),
+            Some("nd1")
+        );
+        let rotator = registry.get_movable("wheel_rotator").unwrap();
+        assert!((rotator.position().await.unwrap() - 2.0).abs() < 1e-9);
 
-    registry
-        .register(DeviceConfig {
-            id: "mock_stage".into(),
-            name: "Mock Stage".into(),
-            driver: DriverType::MockStage {
-                initial_position: 0.0,
-            },
-        })
-        .await?;
+        let info = registry.get_device_info("nd_wheel").unwrap();
+        assert_eq!(info.driver_type, DISCRETE_POSITIONER_DRIVER_TYPE);
+        assert!(info.capabilities.contains(&Capability::DiscretePositioner));
 
-    registry
-        .register(DeviceConfig {
-            id: "mock_power_meter".into(),
-            name: "Mock Power Meter".into(),
-            driver: DriverType::MockPowerMeter { reading: 1e-6 },
-        })
-        .await?;
+        let mut bad = config.clone();
+        bad.discrete_positioners[0].drive = PositionerDrive::Movable {
+            device: "wheel_motor".to_string(),
+            tolerance: 0.1,
+        };
+        assert!(create_registry_from_config(&bad).await.is_err());
+    }
 
-    registry
-        .register(DeviceConfig {
-            id: "mock_camera".into(),
-            name: "Mock Camera".into(),
-            driver: DriverType::MockCamera {
-                width: 640,
-                height: 480,
-            },
-        })
-        .await?;
+    #[tokio::test]
+    async fn test_composite_trigger_from_config() {
+        let toml_str = r#"
+[[devices]]
+id = "shutter_diode"
+name = "Shutter photodiode"
+[devices.driver]
+type = "mock_power_meter"
+reading = 1.0
 
-    Ok(registry)
-}
+[[devices]]
+id = "camera"
+name = "Camera"
+[devices.driver]
+type = "mock_camera"
 
-/// Register all mock driver factories with a registry.
-///
-/// This enables using `register_from_toml()` for mock devices:
-///
-/// ```rust,ignore
-/// use daq_hardware::registry::{DeviceRegistry, register_mock_factories};
-///
-/// let registry = DeviceRegistry::new();
-/// register_mock_factories(&registry);
-///
-/// // Now register mock devices via TOML config
-/// registry.register_from_toml(
-///     "my_stage",
-///     "My Test Stage",
-///     "mock_stage",
-///     toml::Value::Table(Default::default()),
-/// ).await?;
-/// ```
-pub fn register_mock_factories(registry: &DeviceRegistry) {
-    use daq_driver_mock::{MockCameraFactory, MockPowerMeterFactory, MockStageFactory};
+[aliases]
+shutter = "shutter_diode"
+
+[[composite_triggers]]
+id = "gated_camera"
+target = "camera"
+timeout_s = 2.0
+condition = { type = "all", conditions = [
+    { type = "threshold", channel = "shutter", above = 0.5 },
+    { type = "software", signal = "ready" },
+] }
+"#;
 
-    registry.register_factory(Box::new(MockStageFactory));
-    registry.register_factory(Box::new(MockCameraFactory));
-    registry.register_factory(Box::new(MockPowerMeterFactory));
-}
+        let config: HardwareConfig = toml::from_str(toml_str).unwrap();
+        let registry = create_registry_from_config(&config).await.unwrap();
 
-/// Register all available hardware driver factories.
-///
-/// This registers factories for all enabled hardware drivers:
-/// - Mock drivers (always available)
-/// - Thorlabs ELL14 (when `thorlabs` feature enabled)
-/// - Newport ESP300 and 1830-C (when `newport` feature enabled)
-/// - Spectra-Physics MaiTai (when `spectra_physics` feature enabled)
-/// - Config-driven devices from TOML files
-///
-/// # Example
-///
-/// ```rust,ignore
-/// use daq_hardware::registry::{DeviceRegistry, register_all_factories};
-/// use std::path::Path;
-///
-/// let registry = DeviceRegistry::new();
-/// register_all_factories(&registry, Some(Path::new("config/devices"))).await?;
-///
-/// // Now use register_from_toml() for any supported driver type
-/// ```
-pub async fn register_all_factories(
-    registry: &DeviceRegistry,
-    config_dir: Option<&std::path::Path>,
-) -> Result<(), DaqError> {
-    // Register mock factories (always available)
-    register_mock_factories(registry);
+        let info = registry.get_device_info("gated_camera").unwrap();
+        assert_eq!(info.driver_type, COMPOSITE_TRIGGER_DRIVER_TYPE);
+        assert!(info.capabilities.contains(&Capability::Triggerable));
 
-    // Register Thorlabs factories
-    #[cfg(feature = "thorlabs")]
-    {
-        use daq_driver_thorlabs::Ell14Factory;
-        registry.register_factory(Box::new(Ell14Factory));
-    }
+        let trigger = registry.get_triggerable("gated_camera").unwrap();
+        trigger.arm().await.unwrap();
+        assert!(registry
+            .get_triggerable("camera")
+            .unwrap()
+            .is_armed()
+            .await
+            .unwrap());
+        registry
+            .get_settable("gated_camera")
+            .unwrap()
+            .set_value("signal.ready", serde_json::json!(true))
+            .await
+            .unwrap();
+        trigger.trigger().await.unwrap();
 
-    // Register Newport factories
-    #[cfg(feature = "newport")]
-    {
-        use daq_driver_newport::{Esp300Factory, Newport1830CFactory};
-        registry.register_factory(Box::new(Esp300Factory));
-        registry.register_factory(Box::new(Newport1830CFactory));
+        let mut bad = config.clone();
+        bad.composite_triggers[0].target = Some("camera_2".to_string());
+        assert!(create_registry_from_config(&bad).await.is_err());
     }
 
-    // Register Spectra-Physics factories
-    #[cfg(feature = "spectra_physics")]
-    {
-        use daq_driver_spectra_physics::MaiTaiFactory;
-        registry.register_factory(Box::new(MaiTaiFactory));
-    }
+    #[tokio::test]
+    async fn test_motion_correction_from_config() {
+        let toml_str = r#"
+[[devices]]
+id = "stage_x"
+name = "Stage X"
+[devices.driver]
+type = "mock_stage"
+initial_position = 2.0
 
-    // Register Red Pitaya factories
-    #[cfg(feature = "red_pitaya")]
-    {
-        use daq_driver_red_pitaya::RedPitayaPidFactory;
-        registry.register_factory(Box::new(RedPitayaPidFactory));
-    }
+[motion_corrections.stage_x]
+scale = -2.0
+offset = 1.0
+backlash = 0.1
+"#;
 
-    // Register Comedi factories (NI DAQ, etc.)
-    #[cfg(feature = "comedi")]
-    {
-        use daq_driver_comedi::{ComediAnalogInputFactory, ComediAnalogOutputFactory};
-        registry.register_factory(Box::new(ComediAnalogInputFactory));
-        registry.register_factory(Box::new(ComediAnalogOutputFactory));
+        let config: HardwareConfig = toml::from_str(toml_str).unwrap();
+        let registry = create_registry_from_config(&config).await.unwrap();
+
+        let stage = registry.get_movable("stage_x").unwrap();
+        assert!((stage.position().await.unwrap() + 3.0).abs() < 1e-9);
+
+        // The position parameter reads corrected through the registry
+        let parameterized = registry.get_parameterized("stage_x").unwrap();
+        let position = parameterized.parameters().get("position").unwrap();
+        assert_eq!(
+            registry.get_parameter_json("stage_x", position).unwrap(),
+            serde_json::json!(-3.0)
+        );
+        assert_eq!(position.get_json().unwrap(), serde_json::json!(2.0));
+
+        // The mock stage reports no travel range, so there is none to convert
+        let info = registry.get_device_info("stage_x").unwrap();
+        assert_eq!(info.metadata.min_position, None);
+        assert_eq!(info.metadata.max_position, None);
+        assert!(info.metadata.position_correction.is_some());
+
+        // Removing the correction restores the driver's positions
+        registry.set_motion_corrections(HashMap::new()).unwrap();
+        let stage = registry.get_movable("stage_x").unwrap();
+        assert!((stage.position().await.unwrap() - 2.0).abs() < 1e-9);
+        let info = registry.get_device_info("stage_x").unwrap();
+        assert!(info.metadata.position_correction.is_none());
+
+        // Corrections must name configured devices
+        let mut bad = config.clone();
+        let correction = bad.motion_corrections.remove("stage_x").unwrap();
+        bad.motion_corrections
+            .insert("stage_z".to_string(), correction);
+        assert!(create_registry_from_config(&bad).await.is_err());
     }
 
-    // Load and register config-driven factories from TOML files
-    #[cfg(feature = "serial")]
-    if let Some(dir) = config_dir {
-        if dir.exists() {
-            match crate::factory::load_all_factories(dir) {
-                Ok(factories) => {
-                    for factory in factories {
-                        let driver_type = factory.driver_type().to_string();
-                        registry.register_factory(Box::new(factory));
-                        tracing::debug!(driver_type = %driver_type, "Registered config factory");
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!(
-                        "Failed to load config factories from {}: {}",
-                        dir.display(),
-                        e
-                    );
-                }
-            }
-        }
+    #[tokio::test]
+    async fn test_output_limits_from_config() {
+        let toml_str = r#"
+[[devices]]
+id = "stage_x"
+name = "Stage X"
+[devices.driver]
+type = "mock_stage"
+initial_position = 2.0
+
+[output_limits.stage_x.position]
+min = 0.0
+max = 10.0
+alarm = true
+"#;
+
+        let config: HardwareConfig = toml::from_str(toml_str).unwrap();
+        let registry = create_registry_from_config(&config).await.unwrap();
+        let mut violations = registry.subscribe_limit_violations();
+
+        let stage = registry.get_movable("stage_x").unwrap();
+        assert!(stage.move_abs(50.0).await.is_err());
+        assert!(stage.move_rel(-5.0).await.is_err());
+        assert!((stage.position().await.unwrap() - 2.0).abs() < 1e-9);
+
+        let violation = violations.try_recv().unwrap();
+        assert_eq!(violation.device_id, "stage_x");
+        assert_eq!(violation.applied, None);
+        assert!(violation.limit.alarm);
+        assert!(registry.output_limit("stage_x", "position").is_some());
+
+        // Setting the position parameter is limited the same way
+        let parameterized = registry.get_parameterized("stage_x").unwrap();
+        let position = parameterized.parameters().get("position").unwrap();
+        assert!(registry
+            .set_parameter_json("stage_x", position, serde_json::json!(50.0))
+            .await
+            .is_err());
+        registry
+            .set_parameter_json("stage_x", position, serde_json::json!(7.0))
+            .await
+            .unwrap();
+        assert!((stage.position().await.unwrap() - 7.0).abs() < 1e-9);
+
+        // Limits must name configured devices and be satisfiable
+        let mut bad = config.clone();
+        let limits = bad.output_limits.remove("stage_x").unwrap();
+        bad.output_limits.insert("stage_z".to_string(), limits);
+        assert!(create_registry_from_config(&bad).await.is_err());
+
+        let mut bad = config.clone();
+        bad.output_limits.get_mut("stage_x").unwrap().insert(
+            "position".to_string(),
+            toml::from_str("min = 5.0\nmax = 1.0").unwrap(),
+        );
+        assert!(create_registry_from_config(&bad).await.is_err());
     }
 
-    Ok(())
-}
+    #[tokio::test]
+    async fn test_keep_out_zones_from_config() {
+        let toml_str = r#"
+[[devices]]
+id = "stage_z"
+name = "Stage Z"
+[devices.driver]
+type = "mock_stage"
+initial_position = 2.0
 
-// =============================================================================
-// Tests
-// =============================================================================
+[[keep_out.stage_z]]
+name = "sample holder"
+range = [5.0, 8.0]
+"#;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let config: HardwareConfig = toml::from_str(toml_str).unwrap();
+        let registry = create_registry_from_config(&config).await.unwrap();
 
-    #[test]
-    fn test_mock_camera_deserializes_with_default_resolution() {
-        let driver: DriverType = serde_json::from_value(serde_json::json!({
-            "type": "mock_camera"
-        }))
-        .unwrap();
+        let stage = registry.get_movable("stage_z").unwrap();
+        let err = stage.move_abs(10.0).await.unwrap_err();
+        assert!(err.to_string().contains("sample holder"));
+        assert!((stage.position().await.unwrap() - 2.0).abs() < 1e-9);
+        stage.move_abs(4.0).await.unwrap();
+        assert_eq!(registry.keep_out_zones("stage_z").len(), 1);
+
+        // Setting the position parameter moves the stage too
+        let parameterized = registry.get_parameterized("stage_z").unwrap();
+        let position = parameterized.parameters().get("position").unwrap();
+        let err = registry
+            .set_parameter_json("stage_z", position, serde_json::json!(6.0))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("sample holder"));
+        assert!(registry
+            .set_parameter_json("stage_z", position, serde_json::json!(10.0))
+            .await
+            .is_err());
+        registry
+            .set_parameter_json("stage_z", position, serde_json::json!(1.0))
+            .await
+            .unwrap();
+        assert!((stage.position().await.unwrap() - 1.0).abs() < 1e-9);
 
-        match driver {
-            DriverType::MockCamera { width, height } => {
-                assert_eq!(width, 640);
-                assert_eq!(height, 480);
-            }
-            _ => panic!("Expected MockCamera driver"),
-        }
+        // Single devices only take ranges
+        let mut bad = config.clone();
+        bad.keep_out.get_mut("stage_z").unwrap()[0] =
+            KeepOutZone::polygon("clamp", vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]]);
+        assert!(create_registry_from_config(&bad).await.is_err());
     }
 
     #[tokio::test]
-    async fn test_register_mock_devices() {
-        let registry = create_mock_registry().await.unwrap();
+    async fn test_keep_out_zones_with_motion_correction() {
+        let toml_str = r#"
+[[devices]]
+id = "stage_z"
+name = "Stage Z"
+[devices.driver]
+type = "mock_stage"
+initial_position = 2.0
 
-        assert_eq!(registry.len(), 3);
-        assert!(registry.contains("mock_stage"));
-        assert!(registry.contains("mock_power_meter"));
-        assert!(registry.contains("mock_camera"));
+[motion_corrections.stage_z]
+scale = 2.0
+offset = 10.0
+
+[[keep_out.stage_z]]
+name = "sample holder"
+range = [15.0, 20.0]
+"#;
+
+        let config: HardwareConfig = toml::from_str(toml_str).unwrap();
+        let registry = create_registry_from_config(&config).await.unwrap();
+        let stage = registry.get_movable("stage_z").unwrap();
+        assert!((stage.position().await.unwrap() - 14.0).abs() < 1e-9);
+
+        // Position parameter writes are corrected positions, checked
+        // against the zones in the same units
+        let parameterized = registry.get_parameterized("stage_z").unwrap();
+        let position = parameterized.parameters().get("position").unwrap();
+        registry
+            .set_parameter_json("stage_z", position, serde_json::json!(4.0))
+            .await
+            .unwrap();
+        assert!((stage.position().await.unwrap() - 4.0).abs() < 1e-9);
+
+        for target in [17.0, 25.0] {
+            let err = registry
+                .set_parameter_json("stage_z", position, serde_json::json!(target))
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("sample holder"), "{}", err);
+        }
+        assert!((stage.position().await.unwrap() - 4.0).abs() < 1e-9);
     }
 
     #[tokio::test]
-    async fn test_list_devices() {
-        let registry = create_mock_registry().await.unwrap();
-        let devices = registry.list_devices();
+    async fn test_status_changes_publish_device_events() {
+        let toml_str = r#"
+[[devices]]
+id = "stage_x"
+name = "Stage X"
+[devices.driver]
+type = "mock_stage"
+initial_position = 0.0
+"#;
 
-        assert_eq!(devices.len(), 3);
+        let config: HardwareConfig = toml::from_str(toml_str).unwrap();
+        let registry = create_registry_from_config(&config).await.unwrap();
+        let mut events = registry.subscribe_device_events();
+
+        registry.set_device_status("stage_x", DeviceStatus::Busy);
+        registry.set_device_status(
+            "stage_x",
+            DeviceStatus::Fault {
+                code: 3,
+                message: "driver stalled".to_string(),
+            },
+        );
 
-        let stage = devices.iter().find(|d| d.id == "mock_stage").unwrap();
-        assert_eq!(stage.driver_type, "mock_stage");
-        assert!(stage.capabilities.contains(&Capability::Movable));
+        // Ready -> Busy is routine; only the fault is an event
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.device_id, "stage_x");
+        assert_eq!(
+            event.kind,
+            common::device_events::DeviceEventKind::StateChange
+        );
+        assert_eq!(event.payload["from"], "busy");
+        assert!(events.try_recv().is_err());
+    }
 
-        let meter = devices.iter().find(|d| d.id == "mock_power_meter").unwrap();
-        assert_eq!(meter.driver_type, "mock_power_meter");
-        assert!(meter.capabilities.contains(&Capability::Readable));
+    #[tokio::test]
+    async fn test_parking_actions_from_config() {
+        let toml_str = r#"
+[[devices]]
+id = "stage_x"
+name = "Stage X"
+[devices.driver]
+type = "mock_stage"
+initial_position = 2.0
 
-        let camera = devices.iter().find(|d| d.id == "mock_camera").unwrap();
-        assert_eq!(camera.driver_type, "mock_camera");
-        assert!(camera.capabilities.contains(&Capability::FrameProducer));
-        assert!(camera.capabilities.contains(&Capability::Triggerable));
-        assert!(camera.capabilities.contains(&Capability::ExposureControl));
+[parking.stage_x]
+on_pause = [{ action = "stop_motion" }, { action = "move_to", position = 0.0 }]
+"#;
+
+        let config: HardwareConfig = toml::from_str(toml_str).unwrap();
+        let registry = create_registry_from_config(&config).await.unwrap();
+        let actions = registry.parking_actions().remove("stage_x").unwrap();
+
+        let target = registry.parking_target("stage_x");
+        let parked = common::parking::park("stage_x", &target, &actions).await;
+        assert!(parked.failures.is_empty());
+        let stage = registry.get_movable("stage_x").unwrap();
+        assert!(stage.position().await.unwrap().abs() < 1e-9);
+
+        // Resuming returns the stage to where the run left it
+        assert!(common::parking::unpark(&target, &parked).await.is_empty());
+        assert!((stage.position().await.unwrap() - 2.0).abs() < 1e-9);
+
+        let mut bad = config.clone();
+        let actions = bad.parking.remove("stage_x").unwrap();
+        bad.parking.insert("stage_z".to_string(), actions);
+        assert!(create_registry_from_config(&bad).await.is_err());
     }
 
     #[tokio::test]
-    async fn test_legacy_toml_config_registers_mock_devices() {
+    async fn test_sample_registration_from_config() {
         let toml_str = r#"
 [[devices]]
-id = "legacy_stage"
-name = "Legacy Stage"
+id = "stage_x"
+name = "Stage X"
 [devices.driver]
 type = "mock_stage"
-initial_position = 1.23
+initial_position = 5.0
 
 [[devices]]
-id = "legacy_camera"
-name = "Legacy Camera"
+id = "stage_y"
+name = "Stage Y"
 [devices.driver]
-type = "mock_camera"
-width = 320
-height = 240
+type = "mock_stage"
+initial_position = 3.0
+
+[[sample_registrations]]
+sample_id = "wafer"
+stage_axes = ["stage_x", "stage_y"]
+fiducials = [
+    { stage = [5.0, 3.0], sample = [0.0, 0.0] },
+    { stage = [5.0, 5.0], sample = [1.0, 0.0] },
+    { stage = [3.0, 3.0], sample = [0.0, 1.0] },
+]
 "#;
 
         let config: HardwareConfig = toml::from_str(toml_str).unwrap();
         let registry = create_registry_from_config(&config).await.unwrap();
 
-        let devices = registry.list_devices();
-        assert_eq!(devices.len(), 2);
-
-        let stage = devices.iter().find(|d| d.id == "legacy_stage").unwrap();
-        assert_eq!(stage.driver_type, "mock_stage");
-        assert!(stage.capabilities.contains(&Capability::Movable));
-
-        let camera = devices.iter().find(|d| d.id == "legacy_camera").unwrap();
-        assert_eq!(camera.driver_type, "mock_camera");
-        assert!(camera.capabilities.contains(&Capability::FrameProducer));
-        assert!(camera.capabilities.contains(&Capability::Triggerable));
-        assert!(camera.capabilities.contains(&Capability::ExposureControl));
+        assert_eq!(registry.list_samples(), vec!["wafer".to_string()]);
+        let u = registry.get_movable("wafer_u").unwrap();
+        assert!(u.position().await.unwrap().abs() < 1e-9);
+        assert_eq!(
+            registry.active_sample_registration().unwrap().sample_id(),
+            "wafer"
+        );
 
-        assert!(registry.get_movable("legacy_stage").is_some());
-        assert!(registry.get_frame_producer("legacy_camera").is_some());
+        // Re-measured fiducials replace the registration
+        let mut remeasured = config.sample_registrations[0].clone();
+        for fiducial in &mut remeasured.fiducials {
+            fiducial.stage[1] -= 2.0;
+        }
+        registry.register_sample_fiducials(remeasured).unwrap();
+        let u = registry.get_movable("wafer_u").unwrap();
+        assert!((u.position().await.unwrap() - 1.0).abs() < 1e-9);
+        assert_eq!(registry.list_motion_groups(), vec!["wafer".to_string()]);
+
+        registry.set_active_sample(None).unwrap();
+        assert!(registry.active_sample_registration().is_none());
+        assert!(registry.set_active_sample(Some("slide")).is_err());
+
+        // Collinear fiducials cannot be fitted
+        let mut bad = config.clone();
+        bad.sample_registrations[0].fiducials[2].stage = vec![5.0, 7.0];
+        bad.sample_registrations[0].fiducials[2].sample = vec![2.0, 0.0];
+        assert!(create_registry_from_config(&bad).await.is_err());
     }
 
     #[tokio::test]
@@ -2595,6 +5997,7 @@ height = 240
                 driver: DriverType::MockStage {
                     initial_position: 0.0,
                 },
+                simulated: false,
             })
             .await
             .unwrap();
@@ -2606,6 +6009,7 @@ height = 240
                 driver: DriverType::MockStage {
                     initial_position: 0.0,
                 },
+                simulated: false,
             })
             .await;
 
@@ -2991,9 +6395,9 @@ height = 240
             capabilities
         );
         assert_eq!(
-            capabilities.len(),
-            1,
-            "ELL14 should have exactly one capability (Movable)"
+            capabilities,
+            vec![Capability::Movable, Capability::RawTerminal],
+            "ELL14 should be Movable with a raw terminal"
         );
 
         // Verify driver name
@@ -3075,6 +6479,7 @@ height = 240
                 driver: DriverType::Newport1830C {
                     port: "/dev/definitely_does_not_exist_xyz".into(),
                 },
+                simulated: false,
             })
             .await;
 
@@ -0,0 +1,500 @@
+//! Startup self-test.
+//!
+//! Exercises every configured device with safe, read-only diagnostics before
+//! the daemon reports itself ready: an identity query on instruments with a
+//! raw command channel, a position readback on stages, a reading on
+//! detectors and a single-frame grab on cameras. Nothing is moved or
+//! switched on.
+//!
+//! Results use the recipe step format (see `recipes`), one [`StepResult`]
+//! per diagnostic, grouped per device in a [`TestReport`]. A failure on a
+//! device marked `critical` makes the report not ready, and the daemon
+//! stays out of the serving state until a later run passes. Reports
+//! serialize to JSON and, for CI artifacts, to JUnit XML.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [self_test]
+//! on_start = true
+//! timeout_s = 10
+//!
+//! # Devices not listed get every diagnostic they support, non-critical
+//! [[self_test.devices]]
+//! device = "maitai"
+//! critical = true
+//!
+//! [[self_test.devices]]
+//! device = "camera"
+//! checks = ["identity", "frame"]
+//! critical = true
+//!
+//! [[self_test.devices]]
+//! device = "spare_stage"
+//! skip = true
+//! ```
+
+use crate::recipes::{StepResult, StepStatus};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use common::experiment::document::now_ns;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+fn default_timeout_s() -> u64 {
+    10
+}
+
+/// Self-test settings (`[self_test]` in the hardware config)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SelfTestConfig {
+    /// Run the self-test when the daemon starts
+    #[serde(default)]
+    pub on_start: bool,
+    /// Seconds allowed for each diagnostic
+    #[serde(default = "default_timeout_s")]
+    pub timeout_s: u64,
+    /// Per-device overrides
+    #[serde(default)]
+    pub devices: Vec<DeviceSelfTest>,
+}
+
+impl Default for SelfTestConfig {
+    fn default() -> Self {
+        Self {
+            on_start: false,
+            timeout_s: default_timeout_s(),
+            devices: Vec::new(),
+        }
+    }
+}
+
+impl SelfTestConfig {
+    /// Check the settings on their own (device names are checked with the
+    /// rest of the hardware configuration)
+    pub fn validate(&self) -> Result<()> {
+        if self.timeout_s == 0 {
+            bail!("Self-test timeout_s must be positive");
+        }
+        let mut seen = HashSet::new();
+        for device in &self.devices {
+            if !seen.insert(device.device.as_str()) {
+                bail!("Self-test lists device '{}' twice", device.device);
+            }
+            if device.skip && device.critical {
+                bail!(
+                    "Self-test device '{}' cannot be both skipped and critical",
+                    device.device
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Per-diagnostic timeout
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_s)
+    }
+
+    fn device(&self, id: &str) -> Option<&DeviceSelfTest> {
+        self.devices.iter().find(|d| d.device == id)
+    }
+}
+
+/// Self-test settings for one device
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeviceSelfTest {
+    pub device: String,
+    /// Diagnostics to run (empty = every diagnostic the device supports).
+    /// Listed diagnostics the device doesn't support fail.
+    #[serde(default)]
+    pub checks: Vec<Diagnostic>,
+    /// Failures keep the daemon from becoming ready
+    #[serde(default)]
+    pub critical: bool,
+    /// Leave this device out of the self-test
+    #[serde(default)]
+    pub skip: bool,
+}
+
+/// A safe, read-only device diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Diagnostic {
+    /// `*IDN?` over the raw command channel
+    Identity,
+    /// Position readback
+    Position,
+    /// One reading (power, temperature, ...)
+    Power,
+    /// Grab a single frame
+    Frame,
+}
+
+impl Diagnostic {
+    pub const ALL: [Diagnostic; 4] = [
+        Diagnostic::Identity,
+        Diagnostic::Position,
+        Diagnostic::Power,
+        Diagnostic::Frame,
+    ];
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Identity => "identity",
+            Self::Position => "position",
+            Self::Power => "power",
+            Self::Frame => "frame",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Device access needed to run diagnostics
+///
+/// Implemented by [`DeviceRegistry`](crate::registry::DeviceRegistry).
+#[async_trait]
+pub trait SelfTestTarget: Send + Sync {
+    /// IDs of the devices to test
+    fn device_ids(&self) -> Vec<String>;
+
+    /// Diagnostics `device` supports
+    fn diagnostics(&self, device: &str) -> Vec<Diagnostic>;
+
+    /// Run one diagnostic, returning a short summary of the result
+    async fn run_diagnostic(&self, device: &str, diagnostic: Diagnostic) -> Result<String>;
+}
+
+/// Diagnostics run on one device
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceTestResult {
+    pub device: String,
+    pub critical: bool,
+    pub steps: Vec<StepResult>,
+}
+
+impl DeviceTestResult {
+    /// Whether every diagnostic passed
+    pub fn passed(&self) -> bool {
+        self.steps.iter().all(|s| s.status != StepStatus::Failed)
+    }
+}
+
+/// Result of one self-test run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestReport {
+    /// Start time (UNIX nanoseconds)
+    pub started_ns: u64,
+    pub duration: Duration,
+    pub devices: Vec<DeviceTestResult>,
+}
+
+impl TestReport {
+    /// Whether every diagnostic on every device passed
+    pub fn passed(&self) -> bool {
+        self.devices.iter().all(DeviceTestResult::passed)
+    }
+
+    /// Critical devices with a failed diagnostic
+    pub fn critical_failures(&self) -> Vec<&str> {
+        self.devices
+            .iter()
+            .filter(|d| d.critical && !d.passed())
+            .map(|d| d.device.as_str())
+            .collect()
+    }
+
+    /// Whether the daemon may enter the ready state
+    pub fn ready(&self) -> bool {
+        self.critical_failures().is_empty()
+    }
+
+    /// JUnit XML for CI: one `testsuite` per device, one `testcase` per step
+    pub fn to_junit_xml(&self, name: &str) -> String {
+        let count = |status: StepStatus| {
+            self.devices
+                .iter()
+                .flat_map(|d| &d.steps)
+                .filter(|s| s.status == status)
+                .count()
+        };
+        let tests: usize = self.devices.iter().map(|d| d.steps.len()).sum();
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">",
+            xml_escape(name),
+            tests,
+            count(StepStatus::Failed),
+            count(StepStatus::Skipped),
+            self.duration.as_secs_f64()
+        );
+        for device in &self.devices {
+            let failures = device
+                .steps
+                .iter()
+                .filter(|s| s.status == StepStatus::Failed)
+                .count();
+            let _ = writeln!(
+                xml,
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">",
+                xml_escape(&device.device),
+                device.steps.len(),
+                failures
+            );
+            for step in &device.steps {
+                let case = format!(
+                    "    <testcase classname=\"{}\" name=\"{}\"",
+                    xml_escape(&device.device),
+                    xml_escape(&step.description)
+                );
+                let _ = match step.status {
+                    StepStatus::Ok => writeln!(xml, "{}/>", case),
+                    StepStatus::Failed => writeln!(
+                        xml,
+                        "{}>\n      <failure message=\"{}\"/>\n    </testcase>",
+                        case,
+                        xml_escape(&step.message)
+                    ),
+                    StepStatus::Skipped => writeln!(
+                        xml,
+                        "{}>\n      <skipped message=\"{}\"/>\n    </testcase>",
+                        case,
+                        xml_escape(&step.message)
+                    ),
+                };
+            }
+            xml.push_str("  </testsuite>\n");
+        }
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Run the self-test against `target`, logging each diagnostic
+pub async fn run_self_test(config: &SelfTestConfig, target: &dyn SelfTestTarget) -> TestReport {
+    let started_ns = now_ns();
+    let start = Instant::now();
+
+    let mut ids = target.device_ids();
+    ids.sort();
+    let mut devices = Vec::with_capacity(ids.len());
+    for id in ids {
+        let settings = config.device(&id);
+        if settings.is_some_and(|s| s.skip) {
+            continue;
+        }
+        let critical = settings.is_some_and(|s| s.critical);
+        let supported = target.diagnostics(&id);
+        let checks = match settings {
+            Some(s) if !s.checks.is_empty() => s.checks.clone(),
+            _ => Diagnostic::ALL
+                .into_iter()
+                .filter(|d| supported.contains(d))
+                .collect(),
+        };
+
+        let mut steps = Vec::with_capacity(checks.len());
+        for (index, diagnostic) in checks.into_iter().enumerate() {
+            let result = if !supported.contains(&diagnostic) {
+                Err(anyhow::anyhow!("not supported by this device"))
+            } else {
+                match tokio::time::timeout(config.timeout(), target.run_diagnostic(&id, diagnostic))
+                    .await
+                {
+                    Ok(result) => result,
+                    Err(_) => Err(anyhow::anyhow!("timed out after {:?}", config.timeout())),
+                }
+            };
+            let (status, message) = match result {
+                Ok(message) => {
+                    tracing::info!(device = %id, %diagnostic, %message, "Self-test ok");
+                    (StepStatus::Ok, message)
+                }
+                Err(e) => {
+                    tracing::warn!(device = %id, %diagnostic, critical, error = %e, "Self-test failed");
+                    (StepStatus::Failed, e.to_string())
+                }
+            };
+            steps.push(StepResult {
+                index,
+                description: diagnostic.to_string(),
+                status,
+                message,
+            });
+        }
+        devices.push(DeviceTestResult {
+            device: id,
+            critical,
+            steps,
+        });
+    }
+
+    let report = TestReport {
+        started_ns,
+        duration: start.elapsed(),
+        devices,
+    };
+    if report.ready() {
+        tracing::info!(
+            devices = report.devices.len(),
+            passed = report.passed(),
+            "Self-test complete"
+        );
+    } else {
+        tracing::error!(
+            failures = ?report.critical_failures(),
+            "Self-test found critical failures"
+        );
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    /// Stage that reads back, camera that never delivers a frame
+    struct FakeLab;
+
+    #[async_trait]
+    impl SelfTestTarget for FakeLab {
+        fn device_ids(&self) -> Vec<String> {
+            vec!["stage".to_string(), "camera".to_string()]
+        }
+
+        fn diagnostics(&self, device: &str) -> Vec<Diagnostic> {
+            match device {
+                "stage" => vec![Diagnostic::Identity, Diagnostic::Position],
+                _ => vec![Diagnostic::Frame],
+            }
+        }
+
+        async fn run_diagnostic(&self, device: &str, diagnostic: Diagnostic) -> Result<String> {
+            match (device, diagnostic) {
+                ("stage", Diagnostic::Identity) => Ok("ACME,ST1,42,1.0".to_string()),
+                ("stage", Diagnostic::Position) => Ok("12.5".to_string()),
+                ("camera", Diagnostic::Frame) => {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                    Err(anyhow!("unreachable"))
+                }
+                _ => Err(anyhow!("unexpected diagnostic")),
+            }
+        }
+    }
+
+    fn config(toml: &str) -> SelfTestConfig {
+        let config: SelfTestConfig = toml::from_str(toml).unwrap();
+        config.validate().unwrap();
+        config
+    }
+
+    #[tokio::test]
+    async fn test_supported_diagnostics_run_by_default() {
+        let report = run_self_test(&config("timeout_s = 1"), &FakeLab).await;
+        assert_eq!(report.devices.len(), 2);
+
+        let stage = &report.devices[1];
+        assert_eq!(stage.device, "stage");
+        assert!(stage.passed());
+        assert_eq!(stage.steps[1].description, "position");
+        assert_eq!(stage.steps[1].message, "12.5");
+
+        let camera = &report.devices[0];
+        assert!(!camera.passed());
+        assert!(camera.steps[0].message.contains("timed out"));
+        // Non-critical failures don't block readiness
+        assert!(!report.passed());
+        assert!(report.ready());
+    }
+
+    #[tokio::test]
+    async fn test_critical_failure_blocks_ready() {
+        let config = config(
+            r#"
+            timeout_s = 1
+
+            [[devices]]
+            device = "camera"
+            critical = true
+
+            [[devices]]
+            device = "stage"
+            checks = ["power"]
+            "#,
+        );
+        let report = run_self_test(&config, &FakeLab).await;
+        assert_eq!(report.critical_failures(), vec!["camera"]);
+        assert!(!report.ready());
+
+        let stage = &report.devices[1];
+        assert_eq!(stage.steps.len(), 1);
+        assert_eq!(stage.steps[0].status, StepStatus::Failed);
+        assert!(stage.steps[0].message.contains("not supported"));
+    }
+
+    #[tokio::test]
+    async fn test_junit_report_marks_failures() {
+        let report = run_self_test(&config("timeout_s = 1"), &FakeLab).await;
+        let xml = report.to_junit_xml("self-test <startup>");
+        assert!(
+            xml.contains(r#"<testsuites name="self-test &lt;startup&gt;" tests="3" failures="1""#)
+        );
+        assert!(xml.contains(r#"<testcase classname="stage" name="position"/>"#));
+        assert!(xml.contains(r#"<testsuite name="camera" tests="1" failures="1">"#));
+        assert!(xml.contains("<failure message=\"timed out after"));
+    }
+
+    #[tokio::test]
+    async fn test_skipped_devices_are_left_out() {
+        let config = config(
+            r#"
+            [[devices]]
+            device = "camera"
+            skip = true
+            "#,
+        );
+        let report = run_self_test(&config, &FakeLab).await;
+        assert_eq!(report.devices.len(), 1);
+        assert!(report.passed());
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_settings() {
+        let twice: SelfTestConfig = toml::from_str(
+            r#"
+            [[devices]]
+            device = "camera"
+            [[devices]]
+            device = "camera"
+            "#,
+        )
+        .unwrap();
+        assert!(twice.validate().is_err());
+
+        let zero: SelfTestConfig = toml::from_str("timeout_s = 0").unwrap();
+        assert!(zero.validate().is_err());
+    }
+}
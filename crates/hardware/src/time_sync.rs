@@ -0,0 +1,398 @@
+//! Time-synchronization check across devices.
+//!
+//! Fires a common stimulus several times and records when each probed device
+//! reports seeing it, then reports each device's offset and jitter (see
+//! [`common::time_sync`]):
+//!
+//! - **Stimulus**: a software trigger on a Triggerable device, or a step of a
+//!   Settable parameter (e.g. an analog output wired to every input) that is
+//!   returned to its low value after each repetition.
+//! - **Cameras** report the timestamp of the first frame delivered after the
+//!   stimulus, so they must be set up to acquire on the trigger.
+//! - **Readable devices** are polled until the reading moves by at least
+//!   `threshold` from its value before the stimulus; the host time of that
+//!   read is the device's timestamp.
+//!
+//! The resulting offsets can be stored as the registry's timestamp
+//! corrections.
+
+use crate::registry::DeviceRegistry;
+use anyhow::{anyhow, bail, Result};
+use common::capabilities::{FrameObserver, FrameProducer, ObserverHandle, Readable};
+use common::data::FrameView;
+use common::experiment::document::now_ns;
+use common::time_sync::{analyze, SkewSample, SyncReport};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::{sleep, timeout_at, Instant};
+
+/// Largest number of repetitions in one check
+pub const MAX_REPETITIONS: u32 = 1_000;
+
+/// Time between reads of a polled device
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// The common event every probed device should see
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stimulus {
+    /// Software trigger
+    Trigger { device: String },
+    /// Step a Settable parameter from `low` to `high`
+    Step {
+        device: String,
+        parameter: String,
+        low: f64,
+        high: f64,
+    },
+}
+
+/// Settings of one time-sync check
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeSyncRequest {
+    pub stimulus: Stimulus,
+    /// Devices whose timestamps are compared
+    pub devices: Vec<String>,
+    pub repetitions: u32,
+    /// Pause between repetitions
+    pub interval: Duration,
+    /// How long a device has to respond to each stimulus
+    pub timeout: Duration,
+    /// Change in reading that counts as a response, for Readable devices
+    pub threshold: f64,
+    /// Device the offsets are relative to (default: the stimulus time)
+    pub reference: Option<String>,
+}
+
+impl TimeSyncRequest {
+    pub fn validate(&self) -> Result<()> {
+        if self.devices.is_empty() {
+            bail!("Time-sync check needs at least one device");
+        }
+        if self.repetitions == 0 || self.repetitions > MAX_REPETITIONS {
+            bail!("repetitions must be between 1 and {}", MAX_REPETITIONS);
+        }
+        if self.timeout.is_zero() {
+            bail!("timeout must be positive");
+        }
+        if !self.threshold.is_finite() || self.threshold <= 0.0 {
+            bail!("threshold must be a positive finite number");
+        }
+        if let Stimulus::Step { low, high, .. } = &self.stimulus {
+            if !low.is_finite() || !high.is_finite() || (high - low).abs() < f64::EPSILON {
+                bail!("Step stimulus needs distinct finite low and high values");
+            }
+        }
+        if let Some(reference) = &self.reference {
+            if !self.devices.contains(reference) {
+                bail!("Reference device '{}' is not probed", reference);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Forwards the timestamp of every frame
+struct TimestampObserver(mpsc::UnboundedSender<u64>);
+
+impl FrameObserver for TimestampObserver {
+    fn on_frame(&self, frame: &FrameView<'_>) {
+        let _ = self.0.send(frame.timestamp_ns);
+    }
+
+    fn name(&self) -> &'static str {
+        "time_sync"
+    }
+}
+
+/// How a probed device reports the stimulus
+enum Probe {
+    Frames {
+        camera: Arc<dyn FrameProducer>,
+        handle: ObserverHandle,
+        rx: mpsc::UnboundedReceiver<u64>,
+        /// Started by the check, so stopped by it too
+        started: bool,
+    },
+    Reading {
+        readable: Arc<dyn Readable>,
+        threshold: f64,
+        baseline: f64,
+    },
+}
+
+impl Probe {
+    async fn new(registry: &DeviceRegistry, device: &str, threshold: f64) -> Result<Self> {
+        if let Some(camera) = registry
+            .get_frame_producer(device)
+            .filter(|camera| camera.supports_observers())
+        {
+            let (tx, rx) = mpsc::unbounded_channel();
+            let handle = camera
+                .register_observer(Box::new(TimestampObserver(tx)))
+                .await?;
+            let started = !camera.is_streaming().await.unwrap_or(false);
+            if started {
+                if let Err(e) = camera.start_stream().await {
+                    let _ = camera.unregister_observer(handle).await;
+                    return Err(e);
+                }
+            }
+            return Ok(Self::Frames {
+                camera,
+                handle,
+                rx,
+                started,
+            });
+        }
+        if let Some(readable) = registry.get_readable(device) {
+            return Ok(Self::Reading {
+                readable,
+                threshold,
+                baseline: 0.0,
+            });
+        }
+        bail!(
+            "Device '{}' has neither frames nor readings to probe",
+            device
+        )
+    }
+
+    /// Forget anything seen before the next stimulus
+    async fn arm(&mut self) -> Result<()> {
+        match self {
+            Self::Frames { rx, .. } => while rx.try_recv().is_ok() {},
+            Self::Reading {
+                readable, baseline, ..
+            } => *baseline = readable.read().await?,
+        }
+        Ok(())
+    }
+
+    /// Device timestamp of the response, or `None` on timeout
+    async fn observe(&mut self, deadline: Instant) -> Result<Option<u64>> {
+        match self {
+            Self::Frames { rx, .. } => Ok(timeout_at(deadline, rx.recv()).await.ok().flatten()),
+            Self::Reading {
+                readable,
+                threshold,
+                baseline,
+            } => {
+                while Instant::now() < deadline {
+                    let before = now_ns();
+                    let value = readable.read().await?;
+                    if (value - *baseline).abs() >= *threshold {
+                        // The reading happened somewhere during the call
+                        return Ok(Some(before + (now_ns().saturating_sub(before)) / 2));
+                    }
+                    sleep(POLL_INTERVAL).await;
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    async fn close(self) {
+        if let Self::Frames {
+            camera,
+            handle,
+            started,
+            ..
+        } = self
+        {
+            if started {
+                let _ = camera.stop_stream().await;
+            }
+            let _ = camera.unregister_observer(handle).await;
+        }
+    }
+}
+
+/// Run a time-sync check against the registry's devices
+pub async fn verify_time_sync(
+    registry: &DeviceRegistry,
+    request: &TimeSyncRequest,
+) -> Result<SyncReport> {
+    request.validate()?;
+
+    let mut probes = Vec::with_capacity(request.devices.len());
+    for device in &request.devices {
+        match Probe::new(registry, device, request.threshold).await {
+            Ok(probe) => probes.push((device.clone(), probe)),
+            Err(e) => {
+                for (_, probe) in probes {
+                    probe.close().await;
+                }
+                return Err(e);
+            }
+        }
+    }
+
+    let result = run_repetitions(registry, request, &mut probes).await;
+    for (_, probe) in probes {
+        probe.close().await;
+    }
+    let observations = result?;
+
+    let report = analyze(
+        &observations,
+        request.repetitions,
+        request.reference.as_deref(),
+    );
+    tracing::info!(
+        devices = report.devices.len(),
+        max_skew_ns = report.max_skew_ns(),
+        "Time-sync check complete"
+    );
+    Ok(report)
+}
+
+async fn run_repetitions(
+    registry: &DeviceRegistry,
+    request: &TimeSyncRequest,
+    probes: &mut [(String, Probe)],
+) -> Result<BTreeMap<String, Vec<SkewSample>>> {
+    let mut observations: BTreeMap<String, Vec<SkewSample>> = request
+        .devices
+        .iter()
+        .map(|device| (device.clone(), Vec::new()))
+        .collect();
+
+    if let Stimulus::Step {
+        device,
+        parameter,
+        low,
+        ..
+    } = &request.stimulus
+    {
+        set_step(registry, device, parameter, *low).await?;
+        sleep(request.interval).await;
+    }
+
+    for repetition in 0..request.repetitions {
+        for (_, probe) in probes.iter_mut() {
+            probe.arm().await?;
+        }
+
+        let stimulus_ns = now_ns();
+        fire(registry, &request.stimulus).await?;
+        let deadline = Instant::now() + request.timeout;
+
+        let responses =
+            futures::future::join_all(probes.iter_mut().map(|(device, probe)| async move {
+                (device.clone(), probe.observe(deadline).await)
+            }))
+            .await;
+        for (device, response) in responses {
+            match response? {
+                Some(observed_ns) => {
+                    if let Some(samples) = observations.get_mut(&device) {
+                        samples.push(SkewSample {
+                            repetition,
+                            stimulus_ns,
+                            observed_ns,
+                        });
+                    }
+                }
+                None => tracing::debug!(device = %device, repetition, "No response to stimulus"),
+            }
+        }
+
+        if let Stimulus::Step {
+            device,
+            parameter,
+            low,
+            ..
+        } = &request.stimulus
+        {
+            set_step(registry, device, parameter, *low).await?;
+        }
+        sleep(request.interval).await;
+    }
+    Ok(observations)
+}
+
+async fn fire(registry: &DeviceRegistry, stimulus: &Stimulus) -> Result<()> {
+    match stimulus {
+        Stimulus::Trigger { device } => {
+            registry
+                .get_triggerable(device)
+                .ok_or_else(|| anyhow!("Stimulus device '{}' is not triggerable", device))?
+                .trigger()
+                .await
+        }
+        Stimulus::Step {
+            device,
+            parameter,
+            high,
+            ..
+        } => set_step(registry, device, parameter, *high).await,
+    }
+}
+
+async fn set_step(
+    registry: &DeviceRegistry,
+    device: &str,
+    parameter: &str,
+    value: f64,
+) -> Result<()> {
+    registry
+        .get_settable(device)
+        .ok_or_else(|| anyhow!("Stimulus device '{}' is not settable", device))?
+        .set_value(parameter, serde_json::json!(value))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> TimeSyncRequest {
+        TimeSyncRequest {
+            stimulus: Stimulus::Step {
+                device: "ao".to_string(),
+                parameter: "voltage".to_string(),
+                low: 0.0,
+                high: 1.0,
+            },
+            devices: vec!["camera".to_string(), "daq".to_string()],
+            repetitions: 10,
+            interval: Duration::from_millis(100),
+            timeout: Duration::from_secs(1),
+            threshold: 0.5,
+            reference: Some("daq".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_validate_request() {
+        assert!(request().validate().is_ok());
+
+        let mut bad = request();
+        bad.reference = Some("power_meter".to_string());
+        assert!(bad.validate().is_err());
+
+        let mut bad = request();
+        bad.stimulus = Stimulus::Step {
+            device: "ao".to_string(),
+            parameter: "voltage".to_string(),
+            low: 1.0,
+            high: 1.0,
+        };
+        assert!(bad.validate().is_err());
+
+        let mut bad = request();
+        bad.repetitions = 0;
+        assert!(bad.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unprobeable_device_is_rejected() {
+        let registry = DeviceRegistry::new();
+        let mut request = request();
+        request.reference = None;
+        let err = verify_time_sync(&registry, &request).await.unwrap_err();
+        assert!(err.to_string().contains("camera"), "{}", err);
+    }
+}
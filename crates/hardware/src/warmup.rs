@@ -0,0 +1,695 @@
+//! Instrument warm-up scheduling.
+//!
+//! A warm-up runs a list of init recipes (see `recipes`) a configured lead
+//! time before a scheduled run: open shutters onto the beam dump, put lasers
+//! into standby, start temperature ramps. Status checks are then repeated
+//! until the run start, so the experiment can begin as soon as everything is
+//! settled instead of losing the first hour of the day to warm-up.
+//!
+//! A failed recipe, or a check that stops passing after the instruments
+//! were ready, is a fault: the `abort_recipes` are run to bring everything
+//! back to a safe state. Checks that simply haven't passed yet at the run
+//! start leave the instruments as they are and report the warm-up as not
+//! ready.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [[warmups]]
+//! name = "morning"
+//! run_at = "09:00"                  # daily (local time), or RFC 3339 for a single run
+//! days = ["mon", "tue", "wed", "thu", "fri"]
+//! lead_minutes = 60
+//! recipes = ["dump_shutter_open", "maitai_standby", "cryostat_ramp"]
+//! abort_recipes = ["maitai_off", "dump_shutter_close"]
+//! check_interval_s = 60
+//!
+//! [[warmups.checks]]
+//! device = "maitai"
+//! parameter = "emission_on"
+//! expect = true
+//!
+//! [[warmups.checks]]
+//! device = "cryostat"
+//! parameter = "temperature_k"
+//! expect = 4.2
+//! tolerance = 0.05
+//! ```
+
+use crate::recipes::{
+    check_value, run_recipe, InitRecipe, RecipeReport, RecipeTarget, RecipeTrigger,
+};
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Datelike, Local, NaiveTime, TimeZone, Weekday};
+use common::experiment::document::now_ns;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+fn default_check_interval_s() -> u64 {
+    60
+}
+
+/// Warm-up ahead of a scheduled run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WarmupSchedule {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Run start: `"HH:MM"` daily in local time, or an RFC 3339 timestamp
+    pub run_at: String,
+    /// Days a daily schedule applies to (empty = every day)
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    /// How long before the run start the warm-up begins
+    pub lead_minutes: u64,
+    /// Init recipes run in order at the warm-up start
+    pub recipes: Vec<String>,
+    /// Init recipes run in order after a fault or abort
+    #[serde(default)]
+    pub abort_recipes: Vec<String>,
+    /// Conditions that must hold before the run can start
+    #[serde(default)]
+    pub checks: Vec<WarmupCheck>,
+    /// Seconds between status checks
+    #[serde(default = "default_check_interval_s")]
+    pub check_interval_s: u64,
+}
+
+/// Parameter read that must match for the instruments to be ready
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WarmupCheck {
+    pub device: String,
+    pub parameter: String,
+    pub expect: Value,
+    /// Allowed numeric deviation
+    #[serde(default)]
+    pub tolerance: Option<f64>,
+}
+
+impl WarmupCheck {
+    /// Human-readable summary for reports
+    pub fn describe(&self) -> String {
+        format!("{}.{} == {}", self.device, self.parameter, self.expect)
+    }
+}
+
+/// When a schedule's runs start
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RunTime {
+    /// Every (configured) day at this local time
+    Daily(NaiveTime),
+    /// A single run
+    Once(DateTime<Local>),
+}
+
+impl WarmupSchedule {
+    /// Parse `run_at`
+    pub fn run_time(&self) -> Result<RunTime> {
+        let spec = self.run_at.trim();
+        if let Ok(time) = NaiveTime::parse_from_str(spec, "%H:%M")
+            .or_else(|_| NaiveTime::parse_from_str(spec, "%H:%M:%S"))
+        {
+            return Ok(RunTime::Daily(time));
+        }
+        DateTime::parse_from_rfc3339(spec)
+            .map(|t| RunTime::Once(t.with_timezone(&Local)))
+            .map_err(|_| {
+                anyhow!(
+                    "Warm-up '{}': run_at '{}' is neither HH:MM nor an RFC 3339 timestamp",
+                    self.name,
+                    self.run_at
+                )
+            })
+    }
+
+    /// Check the schedule on its own (recipe and device names are checked
+    /// with the rest of the hardware configuration)
+    pub fn validate(&self) -> Result<()> {
+        if let (RunTime::Once(_), false) = (self.run_time()?, self.days.is_empty()) {
+            bail!(
+                "Warm-up '{}': days only apply to daily schedules",
+                self.name
+            );
+        }
+        if self.recipes.is_empty() && self.checks.is_empty() {
+            bail!("Warm-up '{}' has no recipes and no checks", self.name);
+        }
+        if self.check_interval_s == 0 {
+            bail!("Warm-up '{}': check_interval_s must be positive", self.name);
+        }
+        Ok(())
+    }
+
+    /// Time from the warm-up start to the run start
+    pub fn lead(&self) -> Duration {
+        Duration::from_secs(self.lead_minutes * 60)
+    }
+
+    /// First run start after `now`, if any
+    pub fn next_run_after(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        match self.run_time().ok()? {
+            RunTime::Once(start) => (start > now).then_some(start),
+            RunTime::Daily(time) => (0..=7)
+                .filter_map(|offset| now.date_naive().checked_add_days(chrono::Days::new(offset)))
+                .filter(|date| self.days.is_empty() || self.days.contains(&date.weekday()))
+                .filter_map(|date| Local.from_local_datetime(&date.and_time(time)).earliest())
+                .find(|start| *start > now),
+        }
+    }
+}
+
+/// Progress of a warm-up schedule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarmupState {
+    /// Waiting for the warm-up start
+    Scheduled,
+    /// Recipes running, or checks not passing yet
+    WarmingUp,
+    /// All checks pass; monitored until the run start
+    Ready,
+    /// Checks still failing at the run start
+    NotReady,
+    /// A recipe or check failed; abort recipes were run
+    Faulted,
+    /// Aborted on request
+    Aborted,
+    /// No further runs scheduled
+    Idle,
+}
+
+impl std::fmt::Display for WarmupState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Scheduled => "scheduled",
+            Self::WarmingUp => "warming_up",
+            Self::Ready => "ready",
+            Self::NotReady => "not_ready",
+            Self::Faulted => "faulted",
+            Self::Aborted => "aborted",
+            Self::Idle => "idle",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Current state of one schedule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WarmupStatus {
+    pub name: String,
+    pub state: WarmupState,
+    /// Final state of the previous warm-up, if any
+    pub last_result: Option<WarmupState>,
+    pub message: String,
+    /// Next (or current) run start, UNIX nanoseconds
+    pub run_start_ns: Option<u64>,
+    /// Warm-up start for that run, UNIX nanoseconds
+    pub warmup_start_ns: Option<u64>,
+    /// Checks that did not pass at the last evaluation, with the reason
+    pub failing_checks: Vec<String>,
+    pub last_check_ns: Option<u64>,
+    /// Recipes run by the current or last warm-up (including abort recipes)
+    pub reports: Vec<RecipeReport>,
+}
+
+impl WarmupStatus {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            state: WarmupState::Scheduled,
+            last_result: None,
+            message: String::new(),
+            run_start_ns: None,
+            warmup_start_ns: None,
+            failing_checks: Vec::new(),
+            last_check_ns: None,
+            reports: Vec::new(),
+        }
+    }
+}
+
+fn unix_ns(time: DateTime<Local>) -> u64 {
+    time.timestamp_nanos_opt().unwrap_or_default().max(0) as u64
+}
+
+struct Entry {
+    schedule: WarmupSchedule,
+    status: RwLock<WarmupStatus>,
+    abort_requested: AtomicBool,
+    abort: Notify,
+}
+
+impl Entry {
+    fn update(&self, f: impl FnOnce(&mut WarmupStatus)) {
+        f(&mut self.status.write().unwrap_or_else(|p| p.into_inner()));
+    }
+
+    /// Wait until `deadline`; false if an abort was requested first
+    async fn wait_until(&self, deadline: DateTime<Local>) -> bool {
+        loop {
+            if self.abort_requested.swap(false, Ordering::SeqCst) {
+                return false;
+            }
+            let Ok(wait) = (deadline - Local::now()).to_std() else {
+                return true;
+            };
+            if wait.is_zero() {
+                return true;
+            }
+            tokio::select! {
+                () = tokio::time::sleep(wait) => {}
+                () = self.abort.notified() => {}
+            }
+        }
+    }
+}
+
+/// Runs warm-up schedules against the devices
+pub struct WarmupScheduler {
+    target: Arc<dyn RecipeTarget>,
+    recipes: Vec<InitRecipe>,
+    entries: Vec<Entry>,
+}
+
+impl std::fmt::Debug for WarmupScheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WarmupScheduler")
+            .field("schedules", &self.entries.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl WarmupScheduler {
+    /// Create a scheduler; `recipes` are the configured init recipes the
+    /// schedules refer to by name
+    pub fn new(
+        target: Arc<dyn RecipeTarget>,
+        recipes: Vec<InitRecipe>,
+        schedules: Vec<WarmupSchedule>,
+    ) -> Self {
+        let entries = schedules
+            .into_iter()
+            .map(|schedule| Entry {
+                status: RwLock::new(WarmupStatus::new(&schedule.name)),
+                schedule,
+                abort_requested: AtomicBool::new(false),
+                abort: Notify::new(),
+            })
+            .collect();
+        Self {
+            target,
+            recipes,
+            entries,
+        }
+    }
+
+    /// Configured schedules
+    pub fn schedules(&self) -> Vec<WarmupSchedule> {
+        self.entries.iter().map(|e| e.schedule.clone()).collect()
+    }
+
+    /// Current state of every schedule
+    pub fn statuses(&self) -> Vec<WarmupStatus> {
+        self.entries
+            .iter()
+            .map(|e| e.status.read().unwrap_or_else(|p| p.into_inner()).clone())
+            .collect()
+    }
+
+    /// Abort a schedule's warm-up in progress, or skip its next one
+    ///
+    /// Returns false if there is no schedule of that name.
+    pub fn abort(&self, name: &str) -> bool {
+        let Some(entry) = self.entries.iter().find(|e| e.schedule.name == name) else {
+            return false;
+        };
+        entry.abort_requested.store(true, Ordering::SeqCst);
+        entry.abort.notify_one();
+        true
+    }
+
+    /// Start one background task per schedule
+    pub fn spawn(self: &Arc<Self>) -> Vec<tokio::task::JoinHandle<()>> {
+        (0..self.entries.len())
+            .map(|index| {
+                let scheduler = Arc::clone(self);
+                tokio::spawn(async move { scheduler.run_schedule(index).await })
+            })
+            .collect()
+    }
+
+    async fn run_schedule(&self, index: usize) {
+        let entry = &self.entries[index];
+        loop {
+            let Some(run_start) = entry.schedule.next_run_after(Local::now()) else {
+                entry.update(|s| {
+                    s.state = WarmupState::Idle;
+                    s.run_start_ns = None;
+                    s.warmup_start_ns = None;
+                });
+                return;
+            };
+            let warmup_start =
+                run_start - chrono::Duration::from_std(entry.schedule.lead()).unwrap_or_default();
+            entry.update(|s| {
+                s.state = WarmupState::Scheduled;
+                s.message = format!("Warm-up starts {}", warmup_start.format("%Y-%m-%d %H:%M"));
+                s.run_start_ns = Some(unix_ns(run_start));
+                s.warmup_start_ns = Some(unix_ns(warmup_start));
+            });
+            tracing::info!(warmup = %entry.schedule.name, %run_start, "Warm-up scheduled");
+
+            if entry.wait_until(warmup_start).await {
+                let result = self.warm_up(index, run_start).await;
+                entry.update(|s| s.last_result = Some(result));
+            } else {
+                tracing::info!(warmup = %entry.schedule.name, "Warm-up skipped on request");
+                entry.update(|s| {
+                    s.state = WarmupState::Aborted;
+                    s.last_result = Some(WarmupState::Aborted);
+                    s.message = "Skipped on request".to_string();
+                });
+            }
+
+            // Keep the outcome visible until the run starts
+            while !entry.wait_until(run_start).await {}
+        }
+    }
+
+    /// Run one warm-up for the run starting at `run_start`
+    ///
+    /// Returns the final state; the status is updated as it goes.
+    pub async fn warm_up(&self, index: usize, run_start: DateTime<Local>) -> WarmupState {
+        let entry = &self.entries[index];
+        let schedule = &entry.schedule;
+        tracing::info!(warmup = %schedule.name, recipes = schedule.recipes.len(), "Warm-up started");
+        entry.update(|s| {
+            s.state = WarmupState::WarmingUp;
+            s.message = "Running warm-up recipes".to_string();
+            s.failing_checks.clear();
+            s.reports.clear();
+        });
+
+        for name in &schedule.recipes {
+            if entry.abort_requested.swap(false, Ordering::SeqCst) {
+                return self
+                    .shut_down(
+                        entry,
+                        WarmupState::Aborted,
+                        "Aborted on request".to_string(),
+                    )
+                    .await;
+            }
+            let report = match self.run_named_recipe(name).await {
+                Ok(report) => report,
+                Err(e) => {
+                    return self
+                        .shut_down(entry, WarmupState::Faulted, e.to_string())
+                        .await
+                }
+            };
+            let success = report.success();
+            entry.update(|s| s.reports.push(report));
+            if !success {
+                return self
+                    .shut_down(
+                        entry,
+                        WarmupState::Faulted,
+                        format!("Recipe '{}' failed", name),
+                    )
+                    .await;
+            }
+        }
+
+        let interval = chrono::Duration::seconds(schedule.check_interval_s as i64);
+        let mut ready = false;
+        loop {
+            let failing = self.failing_checks(schedule).await;
+            let passed = failing.is_empty();
+            entry.update(|s| {
+                s.failing_checks.clone_from(&failing);
+                s.last_check_ns = Some(now_ns());
+            });
+
+            if passed && !ready {
+                ready = true;
+                tracing::info!(warmup = %schedule.name, "Instruments ready");
+                entry.update(|s| {
+                    s.state = WarmupState::Ready;
+                    s.message = "All checks pass".to_string();
+                });
+            } else if !passed && ready {
+                return self
+                    .shut_down(
+                        entry,
+                        WarmupState::Faulted,
+                        format!("Check failed after warm-up: {}", failing.join("; ")),
+                    )
+                    .await;
+            } else if !passed {
+                entry.update(|s| s.message = format!("Waiting for {} check(s)", failing.len()));
+            }
+
+            let now = Local::now();
+            if now >= run_start {
+                if ready {
+                    return WarmupState::Ready;
+                }
+                tracing::warn!(warmup = %schedule.name, failing = ?failing, "Instruments not ready at run start");
+                entry.update(|s| {
+                    s.state = WarmupState::NotReady;
+                    s.message = format!("Not ready at run start: {}", failing.join("; "));
+                });
+                return WarmupState::NotReady;
+            }
+            if !entry.wait_until((now + interval).min(run_start)).await {
+                return self
+                    .shut_down(
+                        entry,
+                        WarmupState::Aborted,
+                        "Aborted on request".to_string(),
+                    )
+                    .await;
+            }
+        }
+    }
+
+    async fn run_named_recipe(&self, name: &str) -> Result<RecipeReport> {
+        let recipe = self
+            .recipes
+            .iter()
+            .find(|r| r.name == name)
+            .ok_or_else(|| anyhow!("Unknown init recipe '{}'", name))?;
+        Ok(run_recipe(recipe, self.target.as_ref(), RecipeTrigger::Warmup).await)
+    }
+
+    /// Checks that don't pass, with the reason
+    async fn failing_checks(&self, schedule: &WarmupSchedule) -> Vec<String> {
+        let mut failing = Vec::new();
+        for check in &schedule.checks {
+            let result = match self
+                .target
+                .read_parameter(&check.device, &check.parameter)
+                .await
+            {
+                Ok(actual) => {
+                    check_value(&check.parameter, &check.expect, &actual, check.tolerance)
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                failing.push(format!("{}: {}", check.describe(), e));
+            }
+        }
+        failing
+    }
+
+    /// Run the abort recipes and record the final state
+    async fn shut_down(&self, entry: &Entry, state: WarmupState, reason: String) -> WarmupState {
+        tracing::error!(warmup = %entry.schedule.name, %reason, "Warm-up stopped, running abort recipes");
+        for name in &entry.schedule.abort_recipes {
+            match self.run_named_recipe(name).await {
+                Ok(report) => entry.update(|s| s.reports.push(report)),
+                Err(e) => {
+                    tracing::error!(warmup = %entry.schedule.name, error = %e, "Abort recipe missing");
+                }
+            }
+        }
+        entry.update(|s| {
+            s.state = state;
+            s.message = reason;
+        });
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Parameters in memory; `temperature_k` drops 1 K per read down to 4.0
+    #[derive(Default)]
+    struct FakeLab {
+        params: Mutex<HashMap<String, Value>>,
+        commands: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl RecipeTarget for FakeLab {
+        async fn set_parameter(&self, device: &str, parameter: &str, value: Value) -> Result<()> {
+            self.params
+                .lock()
+                .unwrap()
+                .insert(format!("{}.{}", device, parameter), value);
+            Ok(())
+        }
+
+        async fn read_parameter(&self, device: &str, parameter: &str) -> Result<Value> {
+            let key = format!("{}.{}", device, parameter);
+            let mut params = self.params.lock().unwrap();
+            let value = params
+                .get(&key)
+                .cloned()
+                .ok_or_else(|| anyhow!("unknown parameter {}", key))?;
+            if parameter == "temperature_k" {
+                let next = (value.as_f64().unwrap() - 1.0).max(4.0);
+                params.insert(key, serde_json::json!(next));
+            }
+            Ok(value)
+        }
+
+        async fn execute_command(
+            &self,
+            device: &str,
+            command: &str,
+            _args: Value,
+        ) -> Result<Value> {
+            self.commands
+                .lock()
+                .unwrap()
+                .push(format!("{}.{}", device, command));
+            if command == "fail" {
+                bail!("device fault");
+            }
+            Ok(Value::Null)
+        }
+    }
+
+    fn recipe(name: &str, device: &str, command: &str) -> InitRecipe {
+        InitRecipe {
+            name: name.to_string(),
+            device: device.to_string(),
+            description: String::new(),
+            run_on: vec![],
+            continue_on_error: false,
+            steps: vec![crate::recipes::RecipeStep::Command {
+                command: command.to_string(),
+                args: None,
+            }],
+        }
+    }
+
+    fn schedule(toml: &str) -> WarmupSchedule {
+        let schedule: WarmupSchedule = toml::from_str(toml).unwrap();
+        schedule.validate().unwrap();
+        schedule
+    }
+
+    const MORNING: &str = r#"
+        name = "morning"
+        run_at = "09:00"
+        days = ["mon", "wed"]
+        lead_minutes = 60
+        recipes = ["laser_standby"]
+        abort_recipes = ["laser_off"]
+        check_interval_s = 1
+
+        [[checks]]
+        device = "cryostat"
+        parameter = "temperature_k"
+        expect = 4.0
+        tolerance = 0.1
+    "#;
+
+    #[test]
+    fn test_next_run_follows_days() {
+        let schedule = schedule(MORNING);
+        // 2026-10-16 is a Friday
+        let friday = Local.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap();
+        let next = schedule.next_run_after(friday).unwrap();
+        assert_eq!(next, Local.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap());
+
+        let monday_early = Local.with_ymd_and_hms(2026, 10, 19, 8, 30, 0).unwrap();
+        assert_eq!(schedule.next_run_after(monday_early), Some(next));
+
+        let once = WarmupSchedule {
+            run_at: "2026-10-17T09:00:00Z".to_string(),
+            days: vec![],
+            ..schedule
+        };
+        assert!(once.next_run_after(friday).is_some());
+        assert!(once
+            .next_run_after(Local.with_ymd_and_hms(2026, 10, 18, 0, 0, 0).unwrap())
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_waits_for_checks() {
+        let lab = Arc::new(FakeLab::default());
+        lab.set_parameter("cryostat", "temperature_k", serde_json::json!(6.0))
+            .await
+            .unwrap();
+        let scheduler = WarmupScheduler::new(
+            lab.clone(),
+            vec![
+                recipe("laser_standby", "maitai", "standby"),
+                recipe("laser_off", "maitai", "off"),
+            ],
+            vec![schedule(MORNING)],
+        );
+
+        // Not cold enough at the run start
+        let state = scheduler.warm_up(0, Local::now()).await;
+        assert_eq!(state, WarmupState::NotReady);
+        assert_eq!(scheduler.statuses()[0].failing_checks.len(), 1);
+
+        // Cold after two more reads
+        let state = scheduler
+            .warm_up(0, Local::now() + chrono::Duration::milliseconds(1500))
+            .await;
+        assert_eq!(state, WarmupState::Ready);
+        assert_eq!(
+            *lab.commands.lock().unwrap(),
+            ["maitai.standby", "maitai.standby"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fault_runs_abort_recipes() {
+        let lab = Arc::new(FakeLab::default());
+        let scheduler = WarmupScheduler::new(
+            lab.clone(),
+            vec![
+                recipe("laser_standby", "maitai", "fail"),
+                recipe("laser_off", "maitai", "off"),
+            ],
+            vec![schedule(MORNING)],
+        );
+
+        let state = scheduler.warm_up(0, Local::now()).await;
+        assert_eq!(state, WarmupState::Faulted);
+        let status = &scheduler.statuses()[0];
+        assert_eq!(status.message, "Recipe 'laser_standby' failed");
+        assert_eq!(status.reports.len(), 2);
+        assert_eq!(*lab.commands.lock().unwrap(), ["maitai.fail", "maitai.off"]);
+    }
+}
@@ -2,23 +2,71 @@
 //!
 //! Generates gRPC/protobuf bindings during `cargo build`.
 
+/// Messages whose proto definition is also their JSON schema (see `src/schema.rs`)
+const SCHEMA_MESSAGES: &[&str] = &[
+    ".daq.ModuleDataPoint",
+    ".daq.ModuleEvent",
+    ".daq.Document",
+    ".daq.StartDocument",
+    ".daq.DescriptorDocument",
+    ".daq.DataKey",
+    ".daq.EventDocument",
+    ".daq.FlightTicket",
+    ".daq.StopDocument",
+    ".daq.ProgressDocument",
+    ".daq.BatchSummaryDocument",
+    ".daq.BatchRunOutcome",
+    ".daq.DeviceEventDocument",
+    ".daq.DeviceEvent",
+    ".daq.DataRecord",
+];
+
+/// Enum fields of schema messages, serialized by name: (field, module in `schema`)
+const SCHEMA_ENUM_FIELDS: &[(&str, &str)] = &[
+    (".daq.ModuleEvent.severity", "module_event_severity"),
+    (".daq.Document.doc_type", "document_type"),
+    (".daq.DeviceEvent.severity", "device_event_severity"),
+    (".daq.DataRecord.quality", "data_quality"),
+];
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let target_arch = std::env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_default();
     let is_wasm = target_arch == "wasm32";
 
-    tonic_build::configure()
+    // Descriptors of every service, for gRPC reflection and the JSON gateway
+    let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR")?);
+
+    let mut builder = tonic_build::configure()
+        .file_descriptor_set_path(out_dir.join("daq_descriptor.bin"))
         .build_server(!is_wasm)
         .build_client(true)
         .build_transport(!is_wasm)
-        .type_attribute(".", "#[allow(missing_docs)]")
-        .compile(
-            &[
-                "proto/daq.proto",
-                "proto/health.proto",
-                "proto/ni_daq.proto",
-            ],
-            &["proto"],
-        )?;
+        .type_attribute(".", "#[allow(missing_docs)]");
+    for message in SCHEMA_MESSAGES {
+        builder = builder.message_attribute(
+            message,
+            "#[derive(serde::Serialize, serde::Deserialize)] #[serde(default)]",
+        );
+    }
+    for (field, module) in SCHEMA_ENUM_FIELDS {
+        builder = builder.field_attribute(
+            field,
+            format!("#[serde(with = \"crate::schema::{}\")]", module),
+        );
+    }
+    builder = builder.enum_attribute(
+        ".daq.Document.payload",
+        "#[derive(serde::Serialize, serde::Deserialize)] #[serde(rename_all = \"snake_case\")]",
+    );
+
+    builder.compile(
+        &[
+            "proto/daq.proto",
+            "proto/health.proto",
+            "proto/ni_daq.proto",
+        ],
+        &["proto"],
+    )?;
 
     Ok(())
 }
@@ -23,7 +23,21 @@ use crate::daq::{CompressionType, FrameData};
 /// compress_frame(&mut frame);
 /// // frame.data is now LZ4 compressed
 /// ```
+///
+/// Delta frames from chunked delivery have each tile compressed instead, and
+/// `uncompressed_size` counts the bytes of all tiles.
 pub fn compress_frame(frame: &mut FrameData) {
+    if frame.delta {
+        let mut uncompressed_size = 0;
+        for tile in &mut frame.tiles {
+            uncompressed_size += tile.data.len() as u32;
+            tile.data = lz4_flex::compress_prepend_size(&tile.data);
+        }
+        frame.compression = CompressionType::CompressionLz4 as i32;
+        frame.uncompressed_size = uncompressed_size;
+        return;
+    }
+
     let uncompressed_size = frame.data.len() as u32;
     let compressed = lz4_flex::compress_prepend_size(&frame.data);
 
@@ -49,6 +63,22 @@ pub fn compress_frame(frame: &mut FrameData) {
 pub fn decompress_frame(frame: &mut FrameData) -> Result<(), String> {
     match CompressionType::try_from(frame.compression) {
         Ok(CompressionType::CompressionNone) => Ok(()),
+        Ok(CompressionType::CompressionLz4) if frame.delta => {
+            let mut decompressed_size = 0;
+            for tile in &mut frame.tiles {
+                tile.data = lz4_flex::decompress_size_prepended(&tile.data)
+                    .map_err(|e| format!("LZ4 decompression of tile failed: {e}"))?;
+                decompressed_size += tile.data.len();
+            }
+            if decompressed_size != frame.uncompressed_size as usize {
+                return Err(format!(
+                    "Decompressed size mismatch: got {} bytes, expected {}",
+                    decompressed_size, frame.uncompressed_size
+                ));
+            }
+            frame.compression = CompressionType::CompressionNone as i32;
+            Ok(())
+        }
         Ok(CompressionType::CompressionLz4) => {
             let decompressed = lz4_flex::decompress_size_prepended(&frame.data)
                 .map_err(|e| format!("LZ4 decompression failed: {e}"))?;
@@ -75,15 +105,17 @@ pub fn decompress_frame(frame: &mut FrameData) -> Result<(), String> {
 /// Returns the ratio of uncompressed to compressed size.
 /// A value of 3.0 means the data was compressed to 1/3 of its original size.
 pub fn compression_ratio(frame: &FrameData) -> f64 {
-    if frame.data.is_empty() || frame.uncompressed_size == 0 {
+    let compressed_size = crate::frame_tiles::payload_len(frame);
+    if compressed_size == 0 || frame.uncompressed_size == 0 {
         return 1.0;
     }
-    frame.uncompressed_size as f64 / frame.data.len() as f64
+    frame.uncompressed_size as f64 / compressed_size as f64
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::daq::FrameTile;
 
     #[test]
     fn test_compress_decompress_roundtrip() {
@@ -186,4 +218,35 @@ mod tests {
         decompress_frame(&mut frame).expect("Should decompress");
         assert_eq!(frame.data.len(), original_size);
     }
+    #[test]
+    fn test_delta_tiles_roundtrip() {
+        let mut frame = FrameData {
+            delta: true,
+            tiles: vec![
+                FrameTile {
+                    width: 16,
+                    height: 16,
+                    data: vec![7u8; 256],
+                    ..Default::default()
+                },
+                FrameTile {
+                    x: 16,
+                    width: 16,
+                    height: 16,
+                    data: vec![9u8; 256],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        compress_frame(&mut frame);
+        assert!(frame.data.is_empty());
+        assert_eq!(frame.uncompressed_size, 512);
+        assert!(compression_ratio(&frame) > 1.0);
+
+        decompress_frame(&mut frame).expect("Should decompress tiles");
+        assert_eq!(frame.tiles[0].data, vec![7u8; 256]);
+        assert_eq!(frame.tiles[1].data, vec![9u8; 256]);
+    }
 }
@@ -1,4 +1,9 @@
 use crate::daq;
+use crate::schema;
+use common::acquisition::AcquisitionMode;
+use common::core::DataQuality;
+use common::data_record::DataRecord;
+use common::frame_annotations::FrameAnnotation;
 use common::modules;
 
 /// Trait for converting proto types to domain types
@@ -39,6 +44,72 @@ impl ToDomain<modules::ModuleState> for daq::ModuleState {
     }
 }
 
+impl From<DataQuality> for daq::DataQuality {
+    fn from(quality: DataQuality) -> Self {
+        match quality {
+            DataQuality::Good => daq::DataQuality::Good,
+            DataQuality::Suspect => daq::DataQuality::Suspect,
+            DataQuality::Stale => daq::DataQuality::Stale,
+            DataQuality::OutOfRange => daq::DataQuality::OutOfRange,
+            DataQuality::Interpolated => daq::DataQuality::Interpolated,
+        }
+    }
+}
+
+impl ToDomain<DataQuality> for daq::DataQuality {
+    fn to_domain(self) -> DataQuality {
+        match self {
+            daq::DataQuality::Good => DataQuality::Good,
+            daq::DataQuality::Suspect => DataQuality::Suspect,
+            daq::DataQuality::Stale => DataQuality::Stale,
+            daq::DataQuality::OutOfRange => DataQuality::OutOfRange,
+            daq::DataQuality::Interpolated => DataQuality::Interpolated,
+        }
+    }
+}
+
+impl From<AcquisitionMode> for daq::AcquisitionMode {
+    fn from(mode: AcquisitionMode) -> Self {
+        let (kind, samples, window_ms) = match mode {
+            AcquisitionMode::Single => (daq::AcquisitionModeKind::AcquisitionModeSingle, 0, 0),
+            AcquisitionMode::Average { samples } => {
+                (daq::AcquisitionModeKind::AcquisitionModeAverage, samples, 0)
+            }
+            AcquisitionMode::Peak { window_ms } => {
+                (daq::AcquisitionModeKind::AcquisitionModePeak, 0, window_ms)
+            }
+            AcquisitionMode::Integrate { window_ms } => (
+                daq::AcquisitionModeKind::AcquisitionModeIntegrate,
+                0,
+                window_ms,
+            ),
+        };
+        daq::AcquisitionMode {
+            kind: kind as i32,
+            samples,
+            window_ms,
+        }
+    }
+}
+
+// Not validated; callers check with `AcquisitionMode::validate`
+impl ToDomain<AcquisitionMode> for daq::AcquisitionMode {
+    fn to_domain(self) -> AcquisitionMode {
+        match self.kind() {
+            daq::AcquisitionModeKind::AcquisitionModeSingle => AcquisitionMode::Single,
+            daq::AcquisitionModeKind::AcquisitionModeAverage => AcquisitionMode::Average {
+                samples: self.samples,
+            },
+            daq::AcquisitionModeKind::AcquisitionModePeak => AcquisitionMode::Peak {
+                window_ms: self.window_ms,
+            },
+            daq::AcquisitionModeKind::AcquisitionModeIntegrate => AcquisitionMode::Integrate {
+                window_ms: self.window_ms,
+            },
+        }
+    }
+}
+
 // ModuleEvent conversion
 impl From<modules::ModuleEvent> for daq::ModuleEvent {
     fn from(event: modules::ModuleEvent) -> Self {
@@ -80,28 +151,54 @@ impl ToDomain<modules::ModuleEvent> for daq::ModuleEvent {
     }
 }
 
-// ModuleDataPoint conversion
+// DataRecord conversion: same field names, mapped through the schema
+impl From<DataRecord> for daq::DataRecord {
+    fn from(record: DataRecord) -> Self {
+        schema::map_fields(&record).expect("DataRecord fields match daq.proto")
+    }
+}
+
+impl ToDomain<DataRecord> for daq::DataRecord {
+    fn to_domain(self) -> DataRecord {
+        schema::map_fields(&self).expect("DataRecord fields match daq.proto")
+    }
+}
+
+impl daq::DataPoint {
+    /// Point of one channel of a record
+    pub fn from_record(record: &DataRecord, channel: &str) -> Option<Self> {
+        Some(daq::DataPoint {
+            channel: channel.to_string(),
+            value: record.value(channel)?,
+            timestamp_ns: record.timestamp_ns,
+            quality: daq::DataQuality::from(record.quality) as i32,
+        })
+    }
+}
+
+impl daq::ValueUpdate {
+    /// Update of one channel of a record
+    pub fn from_record(record: &DataRecord, channel: &str) -> Option<Self> {
+        Some(daq::ValueUpdate {
+            device_id: record.source_id.clone(),
+            value: record.value(channel)?,
+            units: record.unit(channel).to_string(),
+            timestamp_ns: record.timestamp_ns,
+            quality: daq::DataQuality::from(record.quality) as i32,
+        })
+    }
+}
+
+// ModuleDataPoint conversion: same field names, mapped through the schema
 impl From<modules::ModuleDataPoint> for daq::ModuleDataPoint {
     fn from(dp: modules::ModuleDataPoint) -> Self {
-        daq::ModuleDataPoint {
-            module_id: dp.module_id,
-            data_type: dp.data_type,
-            timestamp_ns: dp.timestamp_ns,
-            values: dp.values,
-            metadata: dp.metadata,
-        }
+        schema::map_fields(&dp).expect("ModuleDataPoint fields match daq.proto")
     }
 }
 
 impl ToDomain<modules::ModuleDataPoint> for daq::ModuleDataPoint {
     fn to_domain(self) -> modules::ModuleDataPoint {
-        modules::ModuleDataPoint {
-            module_id: self.module_id,
-            data_type: self.data_type,
-            timestamp_ns: self.timestamp_ns,
-            values: self.values,
-            metadata: self.metadata,
-        }
+        schema::map_fields(&self).expect("ModuleDataPoint fields match daq.proto")
     }
 }
 
@@ -118,6 +215,7 @@ impl From<modules::ModuleTypeInfo> for daq::ModuleTypeInfo {
             parameters: info.parameters.into_iter().map(|p| p.into()).collect(),
             event_types: info.event_types,
             data_types: info.data_types,
+            config_schema: info.config_schema.unwrap_or_default(),
         }
     }
 }
@@ -142,6 +240,7 @@ impl ToDomain<modules::ModuleTypeInfo> for daq::ModuleTypeInfo {
             parameters: self.parameters.into_iter().map(|p| p.to_domain()).collect(),
             event_types: self.event_types,
             data_types: self.data_types,
+            config_schema: Some(self.config_schema).filter(|s| !s.is_empty()),
         }
     }
 }
@@ -205,3 +304,73 @@ impl ToDomain<modules::ModuleRole> for daq::ModuleRole {
         }
     }
 }
+
+impl From<FrameAnnotation> for daq::FrameAnnotation {
+    fn from(annotation: FrameAnnotation) -> Self {
+        use daq::frame_annotation::Shape;
+        let label = annotation.label().to_string();
+        let shape = match annotation {
+            FrameAnnotation::Roi {
+                x,
+                y,
+                width,
+                height,
+                ..
+            } => Shape::Roi(daq::RoiAnnotation {
+                x,
+                y,
+                width,
+                height,
+            }),
+            FrameAnnotation::Point { x, y, .. } => Shape::Point(daq::PointAnnotation { x, y }),
+            FrameAnnotation::Crosshair { x, y, .. } => {
+                Shape::Crosshair(daq::PointAnnotation { x, y })
+            }
+            FrameAnnotation::ScaleBar {
+                pixel_size,
+                unit,
+                length,
+            } => Shape::ScaleBar(daq::ScaleBarAnnotation {
+                pixel_size,
+                unit,
+                length,
+            }),
+        };
+        daq::FrameAnnotation {
+            label,
+            shape: Some(shape),
+        }
+    }
+}
+
+/// `None` when the annotation has no shape
+impl ToDomain<Option<FrameAnnotation>> for daq::FrameAnnotation {
+    fn to_domain(self) -> Option<FrameAnnotation> {
+        use daq::frame_annotation::Shape;
+        let label = self.label;
+        Some(match self.shape? {
+            Shape::Roi(roi) => FrameAnnotation::Roi {
+                label,
+                x: roi.x,
+                y: roi.y,
+                width: roi.width,
+                height: roi.height,
+            },
+            Shape::Point(point) => FrameAnnotation::Point {
+                label,
+                x: point.x,
+                y: point.y,
+            },
+            Shape::Crosshair(point) => FrameAnnotation::Crosshair {
+                label,
+                x: point.x,
+                y: point.y,
+            },
+            Shape::ScaleBar(bar) => FrameAnnotation::ScaleBar {
+                pixel_size: bar.pixel_size,
+                unit: bar.unit,
+                length: bar.length,
+            },
+        })
+    }
+}
@@ -0,0 +1,428 @@
+//! Chunked frame delivery for remote previews.
+//!
+//! Streaming every frame of a large detector over a VPN link is wasteful
+//! when most of the image does not change between frames. With chunked
+//! delivery the server sends a full keyframe every
+//! [`StreamFramesRequest::keyframe_interval`] frames, and in between only the
+//! tiles that changed, optionally limited to selected regions of interest.
+//!
+//! - [`TileEncoder`] (server) turns full frames into keyframes or deltas.
+//! - [`TileAssembler`] (client) pastes delta tiles over the last frame and
+//!   hands on complete frames.
+//!
+//! Tiles are compressed like whole frames by
+//! [`compress_frame`](crate::compression::compress_frame). Clients call
+//! [`decompress_frame`](crate::compression::decompress_frame) first, then
+//! [`TileAssembler::apply`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! let settings = TileSettings { tile_size: 64, ..Default::default() };
+//! let stream = client.stream_frames_tiled("camera", 10, StreamQuality::Full, &settings).await?;
+//! let mut assembler = TileAssembler::new();
+//! while let Some(mut frame) = stream.next().await.transpose()? {
+//!     decompress_frame(&mut frame)?;
+//!     assembler.apply(&mut frame)?;
+//!     // frame.data now holds the whole image
+//! }
+//! ```
+
+use crate::daq::{FrameData, FrameRegion, FrameTile, StreamFramesRequest};
+
+/// Keyframe interval used when the request leaves it at 0
+pub const DEFAULT_KEYFRAME_INTERVAL: u32 = 30;
+
+/// Smallest tile edge accepted; smaller tiles cost more in overhead than they save
+pub const MIN_TILE_SIZE: u32 = 16;
+
+/// Chunked delivery settings of a frame stream request
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TileSettings {
+    /// Tile edge in pixels (0 disables chunked delivery)
+    pub tile_size: u32,
+    /// Frames between keyframes (0 for [`DEFAULT_KEYFRAME_INTERVAL`])
+    pub keyframe_interval: u32,
+    /// Pixel differences up to this value count as unchanged
+    pub change_threshold: u32,
+    /// Only tiles touching these regions are updated (empty for all)
+    pub regions: Vec<FrameRegion>,
+}
+
+impl TileSettings {
+    pub fn from_request(request: &StreamFramesRequest) -> Self {
+        Self {
+            tile_size: request.tile_size,
+            keyframe_interval: request.keyframe_interval,
+            change_threshold: request.change_threshold,
+            regions: request.regions.clone(),
+        }
+    }
+
+    /// Copy the settings into a stream request
+    pub fn apply_to(&self, request: &mut StreamFramesRequest) {
+        request.tile_size = self.tile_size;
+        request.keyframe_interval = self.keyframe_interval;
+        request.change_threshold = self.change_threshold;
+        request.regions.clone_from(&self.regions);
+    }
+
+    /// Check the settings are usable
+    pub fn validate(&self) -> Result<(), String> {
+        if self.tile_size != 0 && self.tile_size < MIN_TILE_SIZE {
+            return Err(format!(
+                "tile_size must be 0 or at least {}, got {}",
+                MIN_TILE_SIZE, self.tile_size
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Last complete image on one side of the stream
+#[derive(Debug, Clone)]
+struct Image {
+    width: u32,
+    height: u32,
+    bit_depth: u32,
+    data: Vec<u8>,
+}
+
+impl Image {
+    fn of(frame: &FrameData) -> Option<Self> {
+        (frame.data.len() == frame_len(frame.width, frame.height, frame.bit_depth)).then(|| Self {
+            width: frame.width,
+            height: frame.height,
+            bit_depth: frame.bit_depth,
+            data: frame.data.clone(),
+        })
+    }
+
+    fn matches(&self, frame: &FrameData) -> bool {
+        self.width == frame.width
+            && self.height == frame.height
+            && self.bit_depth == frame.bit_depth
+    }
+}
+
+fn bytes_per_pixel(bit_depth: u32) -> usize {
+    (bit_depth as usize).div_ceil(8).max(1)
+}
+
+fn frame_len(width: u32, height: u32, bit_depth: u32) -> usize {
+    (width as usize)
+        .saturating_mul(height as usize)
+        .saturating_mul(bytes_per_pixel(bit_depth))
+}
+
+/// Bytes of pixel data a frame carries, whole or as tiles
+pub fn payload_len(frame: &FrameData) -> usize {
+    frame.data.len() + frame.tiles.iter().map(|t| t.data.len()).sum::<usize>()
+}
+
+/// Server side of chunked delivery for one stream
+#[derive(Debug)]
+pub struct TileEncoder {
+    settings: TileSettings,
+    /// What the client currently shows
+    reference: Option<Image>,
+    frames_since_keyframe: u32,
+}
+
+impl TileEncoder {
+    /// Encoder for the request, or `None` if it asks for whole frames
+    pub fn from_request(request: &StreamFramesRequest) -> Option<Self> {
+        let settings = TileSettings::from_request(request);
+        (settings.tile_size > 0).then(|| Self::new(settings))
+    }
+
+    pub fn new(mut settings: TileSettings) -> Self {
+        settings.tile_size = settings.tile_size.max(MIN_TILE_SIZE);
+        if settings.keyframe_interval == 0 {
+            settings.keyframe_interval = DEFAULT_KEYFRAME_INTERVAL;
+        }
+        Self {
+            settings,
+            reference: None,
+            frames_since_keyframe: 0,
+        }
+    }
+
+    /// Send the next frame whole
+    pub fn request_keyframe(&mut self) {
+        self.reference = None;
+    }
+
+    /// Turn a full, uncompressed frame into a keyframe or a delta
+    ///
+    /// Keyframes are left as they are. Deltas get `delta = true`, an empty
+    /// `data` and the changed tiles. A delta that would carry most of the
+    /// image is sent as a keyframe instead.
+    pub fn encode(&mut self, frame: &mut FrameData) {
+        self.frames_since_keyframe += 1;
+        let tiles = match &self.reference {
+            Some(reference)
+                if reference.matches(frame)
+                    && frame.data.len() == reference.data.len()
+                    && self.frames_since_keyframe < self.settings.keyframe_interval =>
+            {
+                changed_tiles(&self.settings, reference, frame)
+            }
+            _ => return self.keyframe(frame),
+        };
+        let tile_bytes: usize = tiles.iter().map(|t| t.data.len()).sum();
+        if tile_bytes * 4 >= frame.data.len() * 3 {
+            return self.keyframe(frame);
+        }
+
+        if let Some(reference) = self.reference.as_mut() {
+            paste(reference, &tiles);
+        }
+        frame.data = Vec::new();
+        frame.delta = true;
+        frame.tiles = tiles;
+    }
+
+    fn keyframe(&mut self, frame: &mut FrameData) {
+        self.reference = Image::of(frame);
+        self.frames_since_keyframe = 0;
+        frame.delta = false;
+        frame.tiles.clear();
+    }
+}
+
+/// Copy tiles into an image; tiles must already be checked to fit
+fn paste(image: &mut Image, tiles: &[FrameTile]) {
+    let bpp = bytes_per_pixel(image.bit_depth);
+    let stride = image.width as usize * bpp;
+    for tile in tiles {
+        let row_len = tile.width as usize * bpp;
+        if row_len == 0 {
+            continue;
+        }
+        for (row, chunk) in tile.data.chunks_exact(row_len).enumerate() {
+            let start = (tile.y as usize + row) * stride + tile.x as usize * bpp;
+            image.data[start..start + row_len].copy_from_slice(chunk);
+        }
+    }
+}
+
+fn intersects(region: &FrameRegion, x: u32, y: u32, width: u32, height: u32) -> bool {
+    region.x < x + width
+        && x < region.x.saturating_add(region.width)
+        && region.y < y + height
+        && y < region.y.saturating_add(region.height)
+}
+
+/// Tiles of `frame` that differ from `reference` by more than the threshold
+fn changed_tiles(settings: &TileSettings, reference: &Image, frame: &FrameData) -> Vec<FrameTile> {
+    let bpp = bytes_per_pixel(frame.bit_depth);
+    let stride = frame.width as usize * bpp;
+    let size = settings.tile_size;
+    let mut tiles = Vec::new();
+
+    for y in (0..frame.height).step_by(size as usize) {
+        for x in (0..frame.width).step_by(size as usize) {
+            let width = size.min(frame.width - x);
+            let height = size.min(frame.height - y);
+            if !settings.regions.is_empty()
+                && !settings
+                    .regions
+                    .iter()
+                    .any(|r| intersects(r, x, y, width, height))
+            {
+                continue;
+            }
+
+            let row_len = width as usize * bpp;
+            let rows = (y..y + height).map(|row| {
+                let start = row as usize * stride + x as usize * bpp;
+                start..start + row_len
+            });
+            let changed = rows.clone().any(|range| {
+                pixels_differ(
+                    &reference.data[range.clone()],
+                    &frame.data[range],
+                    bpp,
+                    settings.change_threshold,
+                )
+            });
+            if changed {
+                let mut data = Vec::with_capacity(row_len * height as usize);
+                for range in rows {
+                    data.extend_from_slice(&frame.data[range]);
+                }
+                tiles.push(FrameTile {
+                    x,
+                    y,
+                    width,
+                    height,
+                    data,
+                });
+            }
+        }
+    }
+    tiles
+}
+
+fn pixels_differ(old: &[u8], new: &[u8], bpp: usize, threshold: u32) -> bool {
+    if threshold == 0 {
+        return old != new;
+    }
+    match bpp {
+        1 => old
+            .iter()
+            .zip(new)
+            .any(|(a, b)| u32::from(a.abs_diff(*b)) > threshold),
+        2 => old.chunks_exact(2).zip(new.chunks_exact(2)).any(|(a, b)| {
+            let a = u16::from_le_bytes([a[0], a[1]]);
+            let b = u16::from_le_bytes([b[0], b[1]]);
+            u32::from(a.abs_diff(b)) > threshold
+        }),
+        _ => old != new,
+    }
+}
+
+/// Client side of chunked delivery: rebuilds whole frames from deltas
+#[derive(Debug, Default)]
+pub struct TileAssembler {
+    current: Option<Image>,
+}
+
+impl TileAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make `frame` whole
+    ///
+    /// Keyframes are remembered; deltas are pasted over the last frame and
+    /// `frame.data` is set to the result. Frames must be decompressed first.
+    /// Fails for a delta arriving before any keyframe or one that doesn't
+    /// fit the last frame.
+    pub fn apply(&mut self, frame: &mut FrameData) -> Result<(), String> {
+        if !frame.delta {
+            self.current = Image::of(frame);
+            return Ok(());
+        }
+
+        let current = match self.current.as_mut() {
+            Some(current) if current.matches(frame) => current,
+            Some(_) => return Err("Delta frame does not match the last keyframe".to_string()),
+            None => return Err("Delta frame received before a keyframe".to_string()),
+        };
+        let bpp = bytes_per_pixel(frame.bit_depth);
+        if let Some(tile) = frame.tiles.iter().find(|tile| {
+            tile.x.saturating_add(tile.width) > frame.width
+                || tile.y.saturating_add(tile.height) > frame.height
+                || tile.data.len() != tile.width as usize * bpp * tile.height as usize
+        }) {
+            return Err(format!(
+                "Tile {}x{} at ({}, {}) does not fit the frame",
+                tile.width, tile.height, tile.x, tile.y
+            ));
+        }
+        paste(current, &frame.tiles);
+
+        frame.data = current.data.clone();
+        frame.delta = false;
+        frame.tiles.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(pixels: Vec<u8>) -> FrameData {
+        FrameData {
+            width: 64,
+            height: 64,
+            bit_depth: 8,
+            data: pixels,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_deltas_carry_only_changed_tiles() {
+        let mut encoder = TileEncoder::new(TileSettings {
+            tile_size: 16,
+            keyframe_interval: 3,
+            ..Default::default()
+        });
+        let mut assembler = TileAssembler::new();
+
+        let mut image = vec![0u8; 64 * 64];
+        let mut first = frame(image.clone());
+        encoder.encode(&mut first);
+        assert!(!first.delta);
+        assembler.apply(&mut first).unwrap();
+
+        // One pixel in the tile at (16, 32) changes
+        image[40 * 64 + 20] = 200;
+        let mut second = frame(image.clone());
+        encoder.encode(&mut second);
+        assert!(second.delta);
+        assert!(second.data.is_empty());
+        assert_eq!(second.tiles.len(), 1);
+        assert_eq!((second.tiles[0].x, second.tiles[0].y), (16, 32));
+        assert_eq!(payload_len(&second), 16 * 16);
+        assembler.apply(&mut second).unwrap();
+        assert_eq!(second.data, image);
+
+        let mut unchanged = frame(image.clone());
+        encoder.encode(&mut unchanged);
+        assert!(unchanged.delta && unchanged.tiles.is_empty());
+
+        // Keyframe interval reached
+        let mut third = frame(image.clone());
+        encoder.encode(&mut third);
+        assert!(!third.delta);
+        assert_eq!(third.data, image);
+    }
+
+    #[test]
+    fn test_regions_and_threshold_limit_updates() {
+        let mut encoder = TileEncoder::new(TileSettings {
+            tile_size: 16,
+            change_threshold: 2,
+            regions: vec![FrameRegion {
+                x: 0,
+                y: 0,
+                width: 20,
+                height: 10,
+            }],
+            ..Default::default()
+        });
+        encoder.encode(&mut frame(vec![10u8; 64 * 64]));
+
+        let mut image = vec![10u8; 64 * 64];
+        image[5 * 64 + 5] = 11; // below threshold
+        image[60 * 64 + 60] = 90; // outside the regions
+        image[3 * 64 + 18] = 90; // in tile (16, 0), which touches the region
+        let mut next = frame(image);
+        encoder.encode(&mut next);
+        let origins: Vec<_> = next.tiles.iter().map(|t| (t.x, t.y)).collect();
+        assert_eq!(origins, vec![(16, 0)]);
+    }
+
+    #[test]
+    fn test_assembler_needs_keyframe_and_large_changes_resend_it() {
+        let mut assembler = TileAssembler::new();
+        let mut orphan = frame(Vec::new());
+        orphan.delta = true;
+        assert!(assembler.apply(&mut orphan).is_err());
+
+        let mut encoder = TileEncoder::new(TileSettings {
+            tile_size: 16,
+            ..Default::default()
+        });
+        encoder.encode(&mut frame(vec![0u8; 64 * 64]));
+        let mut everything_changed = frame(vec![1u8; 64 * 64]);
+        encoder.encode(&mut everything_changed);
+        assert!(!everything_changed.delta);
+        assert_eq!(everything_changed.data.len(), 64 * 64);
+    }
+}
@@ -0,0 +1,366 @@
+//! JSON gateway: every RPC over plain HTTP and JSON.
+//!
+//! Tools like curl, Postman or a Python script can call the daemon without
+//! compiling protobuf stubs. Nothing here is written per RPC: requests and
+//! responses are transcoded at runtime from the descriptors in
+//! [`FILE_DESCRIPTOR_SET`](crate::FILE_DESCRIPTOR_SET), so a method added to a
+//! `.proto` file is reachable through the gateway as soon as it is served.
+//!
+//! - RPC `daq.HardwareService/ListDevices` is `POST /v1/daq.HardwareService/ListDevices`
+//!   with the request message as JSON (proto3 JSON mapping; an empty body is
+//!   the default request).
+//! - Unary responses are one JSON object; server-streaming responses are
+//!   newline-delimited JSON, one message per line.
+//! - Client-streaming RPCs are not available through the gateway.
+//! - [`openapi`] describes every route as an OpenAPI 3 document.
+//!
+//! This module does the transcoding and the gRPC call; the daemon's HTTP
+//! front end maps requests and errors onto it.
+
+// Errors are the `Status` the gRPC call would have returned
+#![allow(clippy::result_large_err)]
+
+use crate::FILE_DESCRIPTOR_SET;
+use prost::Message;
+use prost_reflect::{
+    DescriptorPool, DynamicMessage, FieldDescriptor, Kind, MessageDescriptor, MethodDescriptor,
+    SerializeOptions,
+};
+use serde_json::{json, Map, Value};
+use std::sync::OnceLock;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::transport::Channel;
+use tonic::{Request, Status, Streaming};
+
+/// Path prefix of gateway routes
+pub const GATEWAY_PATH_PREFIX: &str = "/v1/";
+
+/// Descriptors of every service, decoded once
+pub fn descriptor_pool() -> &'static DescriptorPool {
+    static POOL: OnceLock<DescriptorPool> = OnceLock::new();
+    POOL.get_or_init(|| {
+        DescriptorPool::decode(FILE_DESCRIPTOR_SET).expect("built-in descriptor set is valid")
+    })
+}
+
+/// Method served at a gateway path (`/v1/<package.Service>/<Method>`)
+pub fn resolve_method(path: &str) -> Option<MethodDescriptor> {
+    let (service, method) = path.strip_prefix(GATEWAY_PATH_PREFIX)?.split_once('/')?;
+    descriptor_pool()
+        .get_service_by_name(service)?
+        .methods()
+        .find(|m| m.name() == method)
+}
+
+/// Gateway path of a method
+pub fn method_path(method: &MethodDescriptor) -> String {
+    format!(
+        "{}{}/{}",
+        GATEWAY_PATH_PREFIX,
+        method.parent_service().full_name(),
+        method.name()
+    )
+}
+
+/// Request message of `method` from a JSON body
+///
+/// Unknown fields are rejected so typos don't silently become defaults.
+pub fn decode_request(method: &MethodDescriptor, body: &[u8]) -> Result<DynamicMessage, Status> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(DynamicMessage::new(method.input()));
+    }
+    let mut deserializer = serde_json::Deserializer::from_slice(body);
+    let message = DynamicMessage::deserialize(method.input(), &mut deserializer)
+        .and_then(|message| deserializer.end().map(|()| message))
+        .map_err(|e| {
+            Status::invalid_argument(format!(
+                "Invalid {} JSON: {}",
+                method.input().full_name(),
+                e
+            ))
+        })?;
+    Ok(message)
+}
+
+/// JSON of a response message, with default-valued fields included
+pub fn encode_response(message: &DynamicMessage) -> Result<Vec<u8>, Status> {
+    let options = SerializeOptions::new().skip_default_fields(false);
+    let mut serializer = serde_json::Serializer::new(Vec::new());
+    message
+        .serialize_with_options(&mut serializer, &options)
+        .map_err(|e| Status::internal(format!("Failed to encode response JSON: {}", e)))?;
+    Ok(serializer.into_inner())
+}
+
+/// Response of a gateway call
+pub enum GatewayResponse {
+    Unary(DynamicMessage),
+    Stream(Streaming<DynamicMessage>),
+}
+
+/// Call `method` on the daemon behind `channel`
+pub async fn call(
+    channel: Channel,
+    method: &MethodDescriptor,
+    request: Request<DynamicMessage>,
+) -> Result<GatewayResponse, Status> {
+    if method.is_client_streaming() {
+        return Err(Status::unimplemented(format!(
+            "{} is client-streaming, which the JSON gateway does not support",
+            method.full_name()
+        )));
+    }
+    let path = PathAndQuery::try_from(format!(
+        "/{}/{}",
+        method.parent_service().full_name(),
+        method.name()
+    ))
+    .map_err(|e| Status::internal(e.to_string()))?;
+    let codec = DynamicCodec::new(method.output());
+
+    let mut grpc = tonic::client::Grpc::new(channel);
+    grpc.ready()
+        .await
+        .map_err(|e| Status::unavailable(format!("Daemon not reachable: {}", e)))?;
+    if method.is_server_streaming() {
+        let response = grpc.server_streaming(request, path, codec).await?;
+        Ok(GatewayResponse::Stream(response.into_inner()))
+    } else {
+        let response = grpc.unary(request, path, codec).await?;
+        Ok(GatewayResponse::Unary(response.into_inner()))
+    }
+}
+
+/// Protobuf codec for messages known only by descriptor
+#[derive(Debug, Clone)]
+pub struct DynamicCodec {
+    response: MessageDescriptor,
+}
+
+impl DynamicCodec {
+    pub fn new(response: MessageDescriptor) -> Self {
+        Self { response }
+    }
+}
+
+impl Codec for DynamicCodec {
+    type Encode = DynamicMessage;
+    type Decode = DynamicMessage;
+    type Encoder = DynamicEncoder;
+    type Decoder = DynamicDecoder;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        DynamicEncoder
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        DynamicDecoder(self.response.clone())
+    }
+}
+
+#[derive(Debug)]
+pub struct DynamicEncoder;
+
+impl Encoder for DynamicEncoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        item.encode(dst)
+            .map_err(|e| Status::internal(format!("Failed to encode request: {}", e)))
+    }
+}
+
+#[derive(Debug)]
+pub struct DynamicDecoder(MessageDescriptor);
+
+impl Decoder for DynamicDecoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        DynamicMessage::decode(self.0.clone(), src)
+            .map(Some)
+            .map_err(|e| Status::internal(format!("Failed to decode response: {}", e)))
+    }
+}
+
+/// OpenAPI 3 document of every gateway route
+pub fn openapi(title: &str, version: &str) -> Value {
+    let pool = descriptor_pool();
+    let mut paths = Map::new();
+    for service in pool.services() {
+        for method in service.methods() {
+            if method.is_client_streaming() {
+                continue;
+            }
+            let (media_type, description) = if method.is_server_streaming() {
+                (
+                    "application/x-ndjson",
+                    "Stream of messages, one JSON object per line",
+                )
+            } else {
+                ("application/json", "Response message")
+            };
+            paths.insert(
+                method_path(&method),
+                json!({
+                    "post": {
+                        "operationId": format!("{}_{}", service.name(), method.name()),
+                        "tags": [service.full_name()],
+                        "requestBody": {
+                            "required": false,
+                            "content": {
+                                "application/json": { "schema": schema_ref(&method.input()) }
+                            }
+                        },
+                        "responses": {
+                            "200": {
+                                "description": description,
+                                "content": {
+                                    media_type: { "schema": schema_ref(&method.output()) }
+                                }
+                            },
+                            "default": {
+                                "description": "gRPC error",
+                                "content": {
+                                    "application/json": {
+                                        "schema": { "$ref": "#/components/schemas/GatewayError" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }),
+            );
+        }
+    }
+
+    let mut schemas = Map::new();
+    for message in pool.all_messages() {
+        if !message.is_map_entry() {
+            schemas.insert(message.full_name().to_string(), message_schema(&message));
+        }
+    }
+    schemas.insert(
+        "GatewayError".to_string(),
+        json!({
+            "type": "object",
+            "properties": {
+                "code": { "type": "string", "description": "gRPC status code name" },
+                "message": { "type": "string" }
+            }
+        }),
+    );
+
+    json!({
+        "openapi": "3.0.3",
+        "info": { "title": title, "version": version },
+        "paths": paths,
+        "components": { "schemas": schemas }
+    })
+}
+
+fn schema_ref(message: &MessageDescriptor) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", message.full_name()) })
+}
+
+fn message_schema(message: &MessageDescriptor) -> Value {
+    let properties: Map<String, Value> = message
+        .fields()
+        .map(|field| (field.json_name().to_string(), field_schema(&field)))
+        .collect();
+    json!({ "type": "object", "properties": properties })
+}
+
+fn field_schema(field: &FieldDescriptor) -> Value {
+    if field.is_map() {
+        let Kind::Message(entry) = field.kind() else {
+            return json!({ "type": "object" });
+        };
+        return json!({
+            "type": "object",
+            "additionalProperties": kind_schema(&entry.map_entry_value_field().kind())
+        });
+    }
+    let schema = kind_schema(&field.kind());
+    if field.is_list() {
+        json!({ "type": "array", "items": schema })
+    } else {
+        schema
+    }
+}
+
+/// Schema of a value under the proto3 JSON mapping
+fn kind_schema(kind: &Kind) -> Value {
+    match kind {
+        Kind::Double | Kind::Float => json!({ "type": "number" }),
+        Kind::Int32 | Kind::Sint32 | Kind::Sfixed32 => {
+            json!({ "type": "integer", "format": "int32" })
+        }
+        Kind::Uint32 | Kind::Fixed32 => json!({ "type": "integer", "format": "int64" }),
+        // 64-bit integers are strings in proto3 JSON
+        Kind::Int64 | Kind::Sint64 | Kind::Sfixed64 => {
+            json!({ "type": "string", "format": "int64" })
+        }
+        Kind::Uint64 | Kind::Fixed64 => json!({ "type": "string", "format": "uint64" }),
+        Kind::Bool => json!({ "type": "boolean" }),
+        Kind::String => json!({ "type": "string" }),
+        Kind::Bytes => json!({ "type": "string", "format": "byte" }),
+        Kind::Enum(descriptor) => json!({
+            "type": "string",
+            "enum": descriptor.values().map(|v| v.name().to_string()).collect::<Vec<_>>()
+        }),
+        Kind::Message(descriptor) => schema_ref(descriptor),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost_reflect::ReflectMessage;
+
+    #[test]
+    fn test_routes_resolve_and_transcode() {
+        let method = resolve_method("/v1/daq.HardwareService/ListDevices").unwrap();
+        assert_eq!(method_path(&method), "/v1/daq.HardwareService/ListDevices");
+        assert!(resolve_method("/v1/daq.HardwareService/NoSuchMethod").is_none());
+        assert!(resolve_method("/daq.HardwareService/ListDevices").is_none());
+
+        // Empty body is the default request
+        let request = decode_request(&method, b"").unwrap();
+        assert_eq!(request.descriptor(), method.input());
+
+        let err = decode_request(&method, br#"{"noSuchField": 1}"#).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+
+        // Protobuf round trip through the codec types
+        let bytes = request.encode_to_vec();
+        let decoded = DynamicMessage::decode(method.input(), bytes.as_slice()).unwrap();
+        let json: Value = serde_json::from_slice(&encode_response(&decoded).unwrap()).unwrap();
+        assert!(json.is_object());
+    }
+
+    #[test]
+    fn test_openapi_lists_every_unary_and_server_streaming_rpc() {
+        let doc = openapi("rust-daq", "0.1.0");
+        let paths = doc["paths"].as_object().unwrap();
+        let list = &paths["/v1/daq.HardwareService/ListDevices"]["post"];
+        assert_eq!(
+            list["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/daq.ListDevicesRequest"
+        );
+
+        let routes: usize = descriptor_pool()
+            .services()
+            .flat_map(|s| s.methods().collect::<Vec<_>>())
+            .filter(|m| !m.is_client_streaming())
+            .count();
+        assert_eq!(paths.len(), routes);
+
+        let schemas = doc["components"]["schemas"].as_object().unwrap();
+        assert!(schemas.contains_key("daq.ListDevicesResponse"));
+        assert!(schemas.contains_key("GatewayError"));
+    }
+}
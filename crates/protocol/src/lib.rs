@@ -5,6 +5,9 @@
 //! - Health check service from `proto/health.proto` - gRPC health checking
 //! - NI DAQ extensions from `proto/ni_daq.proto` - NI-specific hardware access
 //! - Conversion traits between proto types and domain types in `common`
+//! - JSON serialization of module data and documents driven by the proto schema
+//! - Encoded descriptors of all services, for gRPC reflection and the JSON gateway
+//! - Unix domain socket and named pipe transport for local clients
 //!
 //! # Architecture
 //!
@@ -19,12 +22,29 @@
 //! - [`health`] - gRPC health checking protocol
 //! - [`ni_daq`] - NI DAQ-specific extensions for Comedi hardware
 //! - [`convert`] - Type conversions between proto and domain types
+//! - [`frame_tiles`] - Chunked frame delivery (keyframes plus changed tiles)
+//! - [`schema`] - Schema-first JSON/protobuf mapping for module data and documents
+//! - `gateway` - JSON transcoding and OpenAPI for every RPC (feature `json_gateway`)
+//! - `local_socket` - gRPC over Unix domain sockets and Windows named pipes
 
 #![allow(missing_docs)] // Generated code doesn't have docs
 
 pub mod compression;
 pub mod convert;
 pub mod downsample;
+pub mod frame_tiles;
+#[cfg(all(feature = "json_gateway", not(target_arch = "wasm32")))]
+pub mod gateway;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod local_socket;
+pub mod schema;
+
+/// Encoded `FileDescriptorSet` of every proto file in this crate
+///
+/// Served by gRPC reflection so tools like grpcurl can call the daemon
+/// without the `.proto` files.
+pub const FILE_DESCRIPTOR_SET: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/daq_descriptor.bin"));
 
 /// Generated DAQ protocol buffer types.
 pub mod daq {
@@ -0,0 +1,346 @@
+//! Local IPC transport for gRPC: Unix domain sockets and Windows named pipes.
+//!
+//! When the GUI and the daemon run on the same acquisition machine they can
+//! talk over a local socket instead of TCP, which saves the loopback network
+//! stack on every call and leaves no port to collide with other software.
+//!
+//! Addresses are written as `unix:///run/rust-daq/daq.sock` or
+//! `pipe://rust-daq` (the pipe `\\.\pipe\rust-daq`). The daemon listens with
+//! [`incoming`]; clients connect with [`connect`] or [`connect_lazy`].
+
+use futures::Stream;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tonic::codegen::http::Uri;
+use tonic::codegen::Service;
+use tonic::transport::server::Connected;
+use tonic::transport::{Channel, Endpoint};
+
+/// Address prefix of Unix domain sockets
+pub const UNIX_SCHEME: &str = "unix://";
+
+/// Address prefix of Windows named pipes
+pub const PIPE_SCHEME: &str = "pipe://";
+
+/// Placeholder URI of channels over a local socket; only its authority is
+/// sent (as the HTTP/2 `:authority` header)
+const LOCAL_URI: &str = "http://localhost";
+
+/// A local socket the daemon listens on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalSocket {
+    /// Unix domain socket at this path
+    Unix(PathBuf),
+    /// Windows named pipe with this name (without `\\.\pipe\`)
+    Pipe(String),
+}
+
+impl LocalSocket {
+    /// Parse a `unix://` or `pipe://` address; `None` for other addresses
+    pub fn parse(address: &str) -> Option<Self> {
+        let address = address.trim();
+        if let Some(path) = strip_prefix_ignore_case(address, UNIX_SCHEME) {
+            return (!path.is_empty()).then(|| Self::Unix(PathBuf::from(path)));
+        }
+        if let Some(name) = strip_prefix_ignore_case(address, PIPE_SCHEME) {
+            let name = name
+                .strip_prefix(r"\\.\pipe\")
+                .unwrap_or(name)
+                .trim_end_matches('/');
+            return (!name.is_empty()).then(|| Self::Pipe(name.to_string()));
+        }
+        None
+    }
+
+    /// Full path of a named pipe (`\\.\pipe\<name>`)
+    pub fn pipe_path(name: &str) -> String {
+        format!(r"\\.\pipe\{}", name)
+    }
+}
+
+impl fmt::Display for LocalSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unix(path) => write!(f, "{}{}", UNIX_SCHEME, path.display()),
+            Self::Pipe(name) => write!(f, "{}{}", PIPE_SCHEME, name),
+        }
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    s.get(..prefix.len())
+        .filter(|head| head.eq_ignore_ascii_case(prefix))
+        .map(|_| &s[prefix.len()..])
+}
+
+/// Endpoint for a channel over a local socket
+///
+/// Configure timeouts and keepalive on it as for TCP, then pass it to
+/// [`connect`] or [`connect_lazy`].
+pub fn endpoint() -> Endpoint {
+    Endpoint::from_static(LOCAL_URI)
+}
+
+/// Connect a channel to the daemon listening on `socket`
+pub async fn connect(
+    endpoint: Endpoint,
+    socket: &LocalSocket,
+) -> Result<Channel, tonic::transport::Error> {
+    endpoint
+        .connect_with_connector(Connector(socket.clone()))
+        .await
+}
+
+/// Channel to the daemon on `socket`, connected on first use
+pub fn connect_lazy(endpoint: Endpoint, socket: &LocalSocket) -> Channel {
+    endpoint.connect_with_connector_lazy(Connector(socket.clone()))
+}
+
+#[cfg(unix)]
+type ClientStream = tokio::net::UnixStream;
+#[cfg(windows)]
+type ClientStream = tokio::net::windows::named_pipe::NamedPipeClient;
+
+#[derive(Clone)]
+struct Connector(LocalSocket);
+
+impl Service<Uri> for Connector {
+    type Response = ClientStream;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = io::Result<ClientStream>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _uri: Uri) -> Self::Future {
+        let socket = self.0.clone();
+        Box::pin(async move { open_client(&socket).await })
+    }
+}
+
+#[cfg(unix)]
+async fn open_client(socket: &LocalSocket) -> io::Result<ClientStream> {
+    match socket {
+        LocalSocket::Unix(path) => tokio::net::UnixStream::connect(path).await,
+        LocalSocket::Pipe(_) => Err(unsupported(socket)),
+    }
+}
+
+#[cfg(windows)]
+async fn open_client(socket: &LocalSocket) -> io::Result<ClientStream> {
+    use tokio::net::windows::named_pipe::ClientOptions;
+    /// All pipe instances are connected; the server creates a new one
+    const ERROR_PIPE_BUSY: i32 = 231;
+
+    let LocalSocket::Pipe(name) = socket else {
+        return Err(unsupported(socket));
+    };
+    let path = LocalSocket::pipe_path(name);
+    for _ in 0..100 {
+        match ClientOptions::new().open(&path) {
+            Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY) => {
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+            result => return result,
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::TimedOut,
+        format!("named pipe {} stayed busy", path),
+    ))
+}
+
+fn unsupported(socket: &LocalSocket) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{} is not supported on this platform", socket),
+    )
+}
+
+// =============================================================================
+// Server side
+// =============================================================================
+
+#[cfg(unix)]
+type ServerStream = tokio::net::UnixStream;
+#[cfg(windows)]
+type ServerStream = tokio::net::windows::named_pipe::NamedPipeServer;
+
+/// An accepted connection on a local socket
+pub struct LocalConnection(ServerStream);
+
+impl Connected for LocalConnection {
+    type ConnectInfo = ();
+
+    fn connect_info(&self) -> Self::ConnectInfo {}
+}
+
+impl AsyncRead for LocalConnection {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for LocalConnection {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.0.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+    }
+}
+
+/// Connections accepted on `socket`, for `Server::serve_with_incoming`
+///
+/// A Unix socket file left behind by a daemon that did not shut down cleanly
+/// is replaced; one that a running daemon still answers on is an
+/// `AddrInUse` error. Must be called from within a Tokio runtime.
+#[cfg(unix)]
+pub fn incoming(
+    socket: &LocalSocket,
+) -> io::Result<impl Stream<Item = io::Result<LocalConnection>>> {
+    let LocalSocket::Unix(path) = socket else {
+        return Err(unsupported(socket));
+    };
+    if path.exists() {
+        if std::os::unix::net::UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("another process is listening on {}", socket),
+            ));
+        }
+        std::fs::remove_file(path)?;
+    }
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let listener = tokio::net::UnixListener::bind(path)?;
+
+    Ok(futures::stream::unfold(listener, |listener| async move {
+        let connection = listener
+            .accept()
+            .await
+            .map(|(stream, _)| LocalConnection(stream));
+        Some((connection, listener))
+    }))
+}
+
+/// Connections accepted on `socket`, for `Server::serve_with_incoming`
+///
+/// Fails if another process already owns the pipe name. Must be called from
+/// within a Tokio runtime.
+#[cfg(windows)]
+pub fn incoming(
+    socket: &LocalSocket,
+) -> io::Result<impl Stream<Item = io::Result<LocalConnection>>> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let LocalSocket::Pipe(name) = socket else {
+        return Err(unsupported(socket));
+    };
+    let path = LocalSocket::pipe_path(name);
+    let first = ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(&path)?;
+
+    // Each instance serves one client; a fresh one waits for the next
+    Ok(futures::stream::unfold(Some(first), move |pending| {
+        let path = path.clone();
+        async move {
+            let server = match pending {
+                Some(server) => server,
+                None => match ServerOptions::new().create(&path) {
+                    Ok(server) => server,
+                    Err(e) => return Some((Err(e), None)),
+                },
+            };
+            match server.connect().await {
+                Ok(()) => Some((Ok(LocalConnection(server)), None)),
+                Err(e) => Some((Err(e), None)),
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_local_addresses() {
+        assert_eq!(
+            LocalSocket::parse("unix:///run/rust-daq/daq.sock"),
+            Some(LocalSocket::Unix(PathBuf::from("/run/rust-daq/daq.sock")))
+        );
+        assert_eq!(
+            LocalSocket::parse("PIPE://rust-daq/"),
+            Some(LocalSocket::Pipe("rust-daq".to_string()))
+        );
+        assert_eq!(
+            LocalSocket::parse(r"pipe://\\.\pipe\rust-daq"),
+            Some(LocalSocket::Pipe("rust-daq".to_string()))
+        );
+        assert_eq!(LocalSocket::parse("unix://"), None);
+        assert_eq!(LocalSocket::parse("http://127.0.0.1:50051"), None);
+
+        let socket = LocalSocket::Unix(PathBuf::from("/tmp/daq.sock"));
+        assert_eq!(LocalSocket::parse(&socket.to_string()), Some(socket));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_socket_round_trip() {
+        use futures::StreamExt;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let path = std::env::temp_dir().join(format!("daq-local-{}.sock", std::process::id()));
+        let socket = LocalSocket::Unix(path.clone());
+        let mut connections = Box::pin(incoming(&socket).unwrap());
+
+        let mut client = open_client(&socket).await.unwrap();
+        let mut server = connections.next().await.unwrap().unwrap();
+        client.write_all(b"ping").await.unwrap();
+        let mut buf = [0u8; 4];
+        server.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+
+        // A live listener is not replaced
+        assert_eq!(
+            incoming(&socket).err().map(|e| e.kind()),
+            Some(io::ErrorKind::AddrInUse)
+        );
+        drop(connections);
+        let _ = std::fs::remove_file(path);
+    }
+}
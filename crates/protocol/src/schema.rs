@@ -0,0 +1,191 @@
+//! Schema-first serialization for module data and documents.
+//!
+//! `proto/daq.proto` is the single schema for [`ModuleDataPoint`],
+//! [`ModuleEvent`] and the run [`Document`] types. The build script derives
+//! serde on the generated types (see `SCHEMA_MESSAGES` in `build.rs`), so
+//! the same struct is encoded as protobuf for gRPC and as JSON for storage,
+//! logs and Python consumers. Adding a field to the proto message adds it to
+//! every encoding; there is no serializer to edit by hand.
+//!
+//! JSON uses the proto field names. Missing fields take their proto default,
+//! so older files and clients stay readable, and enum fields are written by
+//! their proto name (`"DOC_EVENT"`); numbers are accepted when reading.
+//! Enums with a short form in the domain (`DataQuality` as `"suspect"`)
+//! also accept it.
+//!
+//! [`map_fields`] converts between a schema type and a domain type with the
+//! same field names (e.g. `common::data_record::DataRecord`), so those
+//! conversions also pick up new fields without changes.
+//!
+//! [`ModuleDataPoint`]: crate::daq::ModuleDataPoint
+//! [`ModuleEvent`]: crate::daq::ModuleEvent
+//! [`Document`]: crate::daq::Document
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// A message with one definition for protobuf and JSON
+pub trait SchemaMessage: prost::Message + Default + Serialize + DeserializeOwned {
+    /// Encode as JSON
+    fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// Encode as a JSON value
+    fn to_json_value(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+
+    /// Decode from JSON
+    fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Encode as protobuf
+    fn to_protobuf(&self) -> Vec<u8> {
+        self.encode_to_vec()
+    }
+
+    /// Decode from protobuf
+    fn from_protobuf(bytes: &[u8]) -> Result<Self, prost::DecodeError> {
+        Self::decode(bytes)
+    }
+}
+
+impl<T: prost::Message + Default + Serialize + DeserializeOwned> SchemaMessage for T {}
+
+/// Convert between types by field name
+///
+/// Fields missing from `value` take their default in the target (schema
+/// types default every field); fields the target doesn't have are ignored.
+pub fn map_fields<A: Serialize, B: DeserializeOwned>(value: &A) -> serde_json::Result<B> {
+    serde_json::from_value(serde_json::to_value(value)?)
+}
+
+/// Proto enum name, or the number for values the schema doesn't know
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum NameOrNumber {
+    Name(String),
+    Number(i32),
+}
+
+/// serde `with` modules for enum fields of schema messages
+///
+/// With a prefix, names without it in any case are also accepted
+/// (`"out_of_range"` for `DATA_QUALITY_OUT_OF_RANGE`).
+macro_rules! enum_by_name {
+    ($module:ident, $enum:ty $(, $prefix:literal)?) => {
+        #[doc = concat!("Serialize `", stringify!($enum), "` fields by name")]
+        pub mod $module {
+            use super::NameOrNumber;
+            use serde::{Deserialize, Deserializer, Serializer};
+
+            pub fn serialize<S: Serializer>(value: &i32, serializer: S) -> Result<S::Ok, S::Error> {
+                match <$enum>::try_from(*value) {
+                    Ok(known) => serializer.serialize_str(known.as_str_name()),
+                    Err(_) => serializer.serialize_i32(*value),
+                }
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<i32, D::Error> {
+                match NameOrNumber::deserialize(deserializer)? {
+                    NameOrNumber::Number(value) => Ok(value),
+                    NameOrNumber::Name(name) => <$enum>::from_str_name(&name)
+                        $(.or_else(|| {
+                            <$enum>::from_str_name(&format!("{}{}", $prefix, name.to_uppercase()))
+                        }))?
+                        .map(|known| known as i32)
+                        .ok_or_else(|| {
+                            serde::de::Error::custom(format!(
+                                "unknown {} '{}'",
+                                stringify!($enum),
+                                name
+                            ))
+                        }),
+                }
+            }
+        }
+    };
+}
+
+enum_by_name!(module_event_severity, crate::daq::ModuleEventSeverity);
+enum_by_name!(document_type, crate::daq::DocumentType);
+enum_by_name!(device_event_severity, crate::daq::DeviceEventSeverity);
+enum_by_name!(data_quality, crate::daq::DataQuality, "DATA_QUALITY_");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daq::{document::Payload, Document, DocumentType, EventDocument, ModuleDataPoint};
+    use std::collections::HashMap;
+
+    fn event_document() -> Document {
+        Document {
+            doc_type: DocumentType::DocEvent as i32,
+            uid: "event-1".to_string(),
+            timestamp_ns: 42,
+            sequence: 3,
+            payload: Some(Payload::Event(EventDocument {
+                descriptor_uid: "desc-1".to_string(),
+                seq_num: 1,
+                data: HashMap::from([("power".to_string(), 1.5)]),
+                ..Default::default()
+            })),
+        }
+    }
+
+    #[test]
+    fn test_document_json_round_trip() {
+        let doc = event_document();
+        let json = doc.to_json_value().unwrap();
+        assert_eq!(json["doc_type"], "DOC_EVENT");
+        assert_eq!(json["payload"]["event"]["data"]["power"], 1.5);
+
+        assert_eq!(Document::from_json(&doc.to_json().unwrap()).unwrap(), doc);
+        assert_eq!(Document::from_protobuf(&doc.to_protobuf()).unwrap(), doc);
+    }
+
+    #[test]
+    fn test_missing_fields_take_defaults() {
+        let point = ModuleDataPoint::from_json(r#"{"module_id": "m1", "added_later": 7}"#).unwrap();
+        assert_eq!(point.module_id, "m1");
+        assert!(point.values.is_empty());
+
+        let doc = Document::from_json(r#"{"doc_type": 2, "uid": "x"}"#).unwrap();
+        assert_eq!(doc.doc_type, 2);
+        assert!(Document::from_json(r#"{"doc_type": "DOC_NOPE"}"#).is_err());
+    }
+
+    #[test]
+    fn test_map_fields_to_domain() {
+        let point = ModuleDataPoint {
+            module_id: "m1".to_string(),
+            data_type: "power_average".to_string(),
+            timestamp_ns: 7,
+            values: HashMap::from([("mean".to_string(), 2.0)]),
+            metadata: HashMap::new(),
+        };
+        let domain: common::modules::ModuleDataPoint = map_fields(&point).unwrap();
+        assert_eq!(domain.values.len(), 1);
+        let back: ModuleDataPoint = map_fields(&domain).unwrap();
+        assert_eq!(back, point);
+    }
+
+    #[test]
+    fn test_data_record_quality_maps_both_ways() {
+        let domain = common::data_record::DataRecord::scalar("pm", "power", 0.5, "W", 9)
+            .with_quality(common::core::DataQuality::OutOfRange);
+        let record: crate::daq::DataRecord = map_fields(&domain).unwrap();
+        assert_eq!(record.quality(), crate::daq::DataQuality::OutOfRange);
+        assert_eq!(
+            record.to_json_value().unwrap()["quality"],
+            "DATA_QUALITY_OUT_OF_RANGE"
+        );
+
+        let back: common::data_record::DataRecord = map_fields(&record).unwrap();
+        assert_eq!(back, domain);
+    }
+}
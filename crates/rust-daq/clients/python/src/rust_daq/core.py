@@ -6,15 +6,51 @@ Provides async-first gRPC client with:
 - Timeout support for all operations
 - Automatic error translation to Python exceptions
 - Type hints for better IDE support
+- Token authentication for daemons with auth enabled
 """
 
-from typing import Optional, Dict, List, AsyncIterator, Any
+import inspect
+import os
+from typing import Optional, Dict, List, AsyncIterator, Any, Awaitable, Callable, Union
 import grpc
 from grpc.aio import Channel, insecure_channel
 
 from .exceptions import translate_grpc_error, DaqError, DeviceError
 
 
+#: Environment variable holding the token used when none is passed
+TOKEN_ENV_VAR = "RUSTDAQ_TOKEN"
+
+#: Returns the current token; may be async. Called before every request, so
+#: a provider can refresh tokens that expire during long scripts.
+TokenProvider = Callable[[], Union[Optional[str], Awaitable[Optional[str]]]]
+
+
+class _TokenInterceptor(
+    grpc.aio.UnaryUnaryClientInterceptor, grpc.aio.UnaryStreamClientInterceptor
+):
+    """Adds ``authorization: Bearer <token>`` metadata to every call."""
+
+    def __init__(self, provider: TokenProvider):
+        self._provider = provider
+
+    async def _with_token(self, client_call_details):
+        token = self._provider()
+        if inspect.isawaitable(token):
+            token = await token
+        if not token:
+            return client_call_details
+        metadata = list(client_call_details.metadata or [])
+        metadata.append(("authorization", f"Bearer {token}"))
+        return client_call_details._replace(metadata=metadata)
+
+    async def intercept_unary_unary(self, continuation, client_call_details, request):
+        return await continuation(await self._with_token(client_call_details), request)
+
+    async def intercept_unary_stream(self, continuation, client_call_details, request):
+        return await continuation(await self._with_token(client_call_details), request)
+
+
 class AsyncClient:
     """
     Async gRPC client for rust-daq daemon.
@@ -32,6 +68,12 @@ class AsyncClient:
             info = await client.get_device_info("mock_stage")
             await client.move_absolute("mock_stage", 10.0)
 
+    Daemons with authentication enabled need a token. It is read from the
+    ``RUSTDAQ_TOKEN`` environment variable unless ``token`` or
+    ``token_provider`` is given; scheduled scripts should use a token issued
+    with ``rust-daq-daemon token <user> --scope ...`` limited to what they
+    run. The daemon attributes the script's actions to the token's user.
+
     Note: Generated protobuf code is imported lazily to avoid import errors
     before running setup.py.
     """
@@ -41,6 +83,8 @@ class AsyncClient:
         address: str = "localhost:50051",
         timeout: float = 10.0,
         max_message_length: int = 100 * 1024 * 1024,  # 100MB for camera frames
+        token: Optional[str] = None,
+        token_provider: Optional[TokenProvider] = None,
     ):
         """
         Initialize AsyncClient.
@@ -49,13 +93,24 @@ class AsyncClient:
             address: Daemon address in "host:port" format
             timeout: Default timeout for operations in seconds
             max_message_length: Maximum gRPC message size in bytes
+            token: Access token (default: ``RUSTDAQ_TOKEN`` environment variable)
+            token_provider: Callable returning the token for each request,
+                for tokens that are refreshed; overrides ``token``
         """
+        if token is not None and token_provider is not None:
+            raise ValueError("Pass either token or token_provider, not both")
+        if token_provider is None:
+            if token is None:
+                token = os.environ.get(TOKEN_ENV_VAR) or None
+            token_provider = (lambda: token) if token else None
+
         self.address = address
         self.timeout = timeout
         self._channel: Optional[Channel] = None
         self._hardware_stub = None
         self._control_stub = None
         self._max_message_length = max_message_length
+        self._token_provider = token_provider
 
     async def __aenter__(self):
         """Async context manager entry - connects to daemon."""
@@ -84,7 +139,14 @@ class AsyncClient:
                 ("grpc.max_send_message_length", self._max_message_length),
             ]
 
-            self._channel = insecure_channel(self.address, options=options)
+            interceptors = (
+                [_TokenInterceptor(self._token_provider)]
+                if self._token_provider
+                else None
+            )
+            self._channel = insecure_channel(
+                self.address, options=options, interceptors=interceptors
+            )
             self._hardware_stub = daq_pb2_grpc.HardwareServiceStub(self._channel)
             self._control_stub = daq_pb2_grpc.ControlServiceStub(self._channel)
 
@@ -477,7 +477,11 @@ class Detector(Device):
 
 
 @contextmanager
-def connect(host: str = "localhost:50051", timeout: float = 10.0):
+def connect(
+    host: str = "localhost:50051",
+    timeout: float = 10.0,
+    token: Optional[str] = None,
+):
     """
     Context manager for connecting to rust-daq daemon.
 
@@ -486,6 +490,8 @@ def connect(host: str = "localhost:50051", timeout: float = 10.0):
     Args:
         host: Daemon address in "host:port" format
         timeout: Default timeout for operations in seconds
+        token: Access token for daemons with auth enabled
+            (default: ``RUSTDAQ_TOKEN`` environment variable)
 
     Yields:
         None (client is stored in thread-local storage)
@@ -496,7 +502,7 @@ def connect(host: str = "localhost:50051", timeout: float = 10.0):
             motor.position = 10.0
     """
     # Create AsyncClient
-    client = AsyncClient(host, timeout=timeout)
+    client = AsyncClient(host, timeout=timeout, token=token)
 
     # Start blocking portal for async-to-sync conversion
     with start_blocking_portal() as portal:
@@ -105,6 +105,42 @@ def test_client_initialization():
     assert client._max_message_length == 200 * 1024 * 1024
 
 
+def test_client_token_defaults_to_environment(monkeypatch):
+    """Test that the token comes from RUSTDAQ_TOKEN unless one is passed."""
+    monkeypatch.delenv("RUSTDAQ_TOKEN", raising=False)
+    assert AsyncClient()._token_provider is None
+
+    monkeypatch.setenv("RUSTDAQ_TOKEN", "from-env")
+    assert AsyncClient()._token_provider() == "from-env"
+    assert AsyncClient(token="explicit")._token_provider() == "explicit"
+
+    with pytest.raises(ValueError):
+        AsyncClient(token="a", token_provider=lambda: "b")
+
+
+@pytest.mark.asyncio
+async def test_token_interceptor_adds_bearer_metadata():
+    """Test that each call carries the provider's current token."""
+    from grpc.aio import ClientCallDetails
+    from rust_daq.core import _TokenInterceptor
+
+    tokens = iter(["first", "second"])
+
+    async def provider():
+        return next(tokens)
+
+    interceptor = _TokenInterceptor(provider)
+    details = ClientCallDetails("/daq.ControlService/StartScript", None, None, None, None)
+    seen = []
+
+    async def continuation(call_details, request):
+        seen.append(dict(call_details.metadata)["authorization"])
+
+    await interceptor.intercept_unary_unary(continuation, details, None)
+    await interceptor.intercept_unary_stream(continuation, details, None)
+    assert seen == ["Bearer first", "Bearer second"]
+
+
 # ============================================================================
 # Integration Test Markers (require running daemon)
 # ============================================================================
@@ -104,6 +104,7 @@ async fn list_devices(
 
     let request = tonic::Request::new(ListDevicesRequest {
         capability_filter: None,
+        ..Default::default()
     });
 
     let response = client
@@ -250,6 +251,7 @@ async fn read_value(
 
     let request = tonic::Request::new(ReadValueRequest {
         device_id: device_id.clone(),
+        mode: None,
     });
 
     let response = client
@@ -489,6 +491,7 @@ async fn set_parameter(
         device_id,
         parameter_name,
         value,
+        typed_value: None,
     });
 
     let response = client
@@ -345,6 +345,7 @@ async fn set_parameter(
         device_id: device_id.to_string(),
         parameter_name: name.to_string(),
         value: value.to_string(),
+        typed_value: None,
     };
 
     match client.set_parameter(request).await {
@@ -394,6 +395,7 @@ async fn run_stream(
         device_id: config.camera_id.clone(),
         max_fps: config.max_fps,
         quality: StreamQuality::Full.into(), // Full resolution for harness testing
+        ..Default::default()
     };
     let mut stream = client
         .stream_frames(stream_request)
@@ -681,6 +683,7 @@ async fn run_secondary_client(config: &HarnessConfig, duration: Duration) -> Sec
             device_id: config.camera_id.clone(),
             max_fps: config.max_fps,
             quality: StreamQuality::Full.into(),
+            ..Default::default()
         };
 
         let mut stream = match client.stream_frames(request).await {
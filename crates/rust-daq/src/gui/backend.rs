@@ -282,6 +282,7 @@ impl Backend {
 
         let rpc_call = client.list_devices(ListDevicesRequest {
             capability_filter: None,
+            ..Default::default()
         });
 
         log::error!("Debug: Waiting for response from list_devices... [LOG]");
@@ -350,6 +351,7 @@ impl Backend {
         match client
             .read_value(ReadValueRequest {
                 device_id: device_id.to_string(),
+                mode: None,
             })
             .await
         {
@@ -466,6 +468,7 @@ impl Backend {
                 device_id: device_id.to_string(),
                 parameter_name: name.to_string(),
                 value: value.to_string(),
+                typed_value: None,
             })
             .await
         {
@@ -699,6 +702,7 @@ impl Backend {
                     device_id: device_id_task.clone(),
                     max_fps: 30,
                     quality: protocol::daq::StreamQuality::Full.into(),
+                    ..Default::default()
                 };
 
                 match client.stream_frames(request).await {
@@ -75,6 +75,21 @@
 #![allow(clippy::must_use_candidate)]
 #![allow(clippy::unwrap_used)]
 
+// Minimal headless profile: heavy backends must stay out of the dependency graph
+#[cfg(all(
+    feature = "minimal",
+    any(
+        feature = "gui_egui",
+        feature = "storage_hdf5",
+        feature = "storage_arrow",
+        feature = "scripting_python",
+        feature = "wasm_plugins"
+    )
+))]
+compile_error!(
+    "the `minimal` profile excludes the egui GUI, HDF5, Arrow, Python scripting and WASM plugins; drop `minimal` or the conflicting feature"
+);
+
 pub mod config;
 pub mod prelude;
 
@@ -125,7 +140,11 @@ pub mod log_capture;
 pub mod modules;
 #[cfg(all(
     not(target_arch = "wasm32"),
-    any(feature = "scripting", feature = "native_plugins")
+    any(
+        feature = "scripting",
+        feature = "native_plugins",
+        feature = "wasm_plugins"
+    )
 ))]
 pub mod plugins;
 
@@ -906,6 +906,64 @@ impl ModuleRegistry {
         );
         Ok(id)
     }
+
+    /// Register module types from a WASM plugin loader.
+    ///
+    /// Like `register_plugin_types`, instances must be created through
+    /// `create_wasm_module()` because each one needs its own sandbox.
+    ///
+    /// # Returns
+    ///
+    /// The number of module types registered.
+    #[cfg(feature = "wasm_plugins")]
+    pub fn register_wasm_types(&mut self, loader: &crate::plugins::WasmPluginLoader) -> usize {
+        let mut count = 0;
+        for type_info in loader.list_module_types() {
+            let type_id = type_info.type_id.clone();
+
+            if self.type_info_cache.contains_key(&type_id) {
+                warn!("Skipping duplicate WASM module type '{}'", type_id);
+                continue;
+            }
+
+            self.type_info_cache
+                .insert(type_id.clone(), type_info.clone());
+
+            let panic_factory: ModuleFactory = || {
+                panic!(
+                    "WASM module factory should not be called directly. \
+                     Use create_wasm_module() instead."
+                )
+            };
+            self.module_types.insert(type_id.clone(), panic_factory);
+
+            info!("Registered WASM module type: {}", type_id);
+            count += 1;
+        }
+        count
+    }
+
+    /// Create a sandboxed module instance from a WASM plugin.
+    ///
+    /// # Returns
+    ///
+    /// The unique instance ID on success.
+    #[cfg(feature = "wasm_plugins")]
+    pub fn create_wasm_module(
+        &mut self,
+        type_id: &str,
+        name: &str,
+        loader: &crate::plugins::WasmPluginLoader,
+    ) -> Result<String> {
+        let id = Uuid::new_v4().to_string();
+        let module: Box<dyn Module> = Box::new(loader.create_module(type_id, id.clone())?);
+
+        let instance = ModuleInstance::new(id.clone(), name.to_string(), module);
+        self.instances.insert(id.clone(), instance);
+
+        info!("Created WASM module instance: {} (type: {})", id, type_id);
+        Ok(id)
+    }
 }
 
 // =============================================================================
@@ -261,6 +261,7 @@ impl Module for PowerMonitor {
                 "threshold_normal".to_string(),
             ],
             data_types: vec!["power_reading".to_string(), "statistics".to_string()],
+            config_schema: None,
         }
     }
 
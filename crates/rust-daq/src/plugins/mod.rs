@@ -4,6 +4,8 @@
 //!
 //! - **Native plugins** (via daq-plugin-api): Compiled Rust plugins using abi_stable
 //! - **Script plugins** (this module): Rhai and Python scripts that implement modules
+//! - **WASM plugins** (wasmtime components): Sandboxed modules with capability-restricted
+//!   host functions, for untrusted or cross-compiled code
 //!
 //! # Architecture
 //!
@@ -13,9 +15,12 @@
 //! ├── Native plugins (daq-plugin-api) [requires native_plugins feature]
 //! │   ├── FfiModuleWrapper - Adapts FFI to Module trait
 //! │   └── PluginModuleFactory - Creates wrapped instances
-//! └── Script plugins (this module) [requires scripting feature]
-//!     ├── ScriptPluginLoader - Discovery and loading
-//!     └── ScriptModule - Script-based Module implementation
+//! ├── Script plugins (this module) [requires scripting feature]
+//! │   ├── ScriptPluginLoader - Discovery and loading
+//! │   └── ScriptModule - Script-based Module implementation
+//! └── WASM plugins (wit/daq-module.wit) [requires wasm_plugins feature]
+//!     ├── WasmPluginLoader - Discovery and compilation
+//!     └── WasmModule - Sandboxed Module implementation
 //! ```
 
 // Script plugins - requires scripting feature (depends on scripting)
@@ -38,3 +43,10 @@ pub use native_plugins::{FfiModuleWrapper, PluginModuleFactory};
 
 #[cfg(feature = "native_plugins")]
 pub use daq_plugin_api::{LoadedPlugin, PluginManager};
+
+// WASM plugins (wasmtime components) - requires wasm_plugins feature
+#[cfg(feature = "wasm_plugins")]
+mod wasm_plugins;
+
+#[cfg(feature = "wasm_plugins")]
+pub use wasm_plugins::{WasmModule, WasmPluginLoader};
@@ -75,6 +75,11 @@ fn convert_type_info(info: &FfiModuleTypeInfo) -> ModuleTypeInfo {
         data_types: info.data_types.iter().map(|s| s.to_string()).collect(),
         required_roles: info.required_roles.iter().map(convert_role).collect(),
         optional_roles: info.optional_roles.iter().map(convert_role).collect(),
+        config_schema: info
+            .config_schema
+            .as_ref()
+            .map(|s| s.to_string())
+            .into_option(),
     }
 }
 
@@ -257,6 +257,7 @@ impl ScriptModule {
             optional_roles,
             event_types,
             data_types,
+            config_schema: get_string(&map, "config_schema"),
         })
     }
 
@@ -332,6 +333,7 @@ impl Module for ScriptModule {
             optional_roles: vec![],
             event_types: vec![],
             data_types: vec![],
+            config_schema: None,
         }
     }
 
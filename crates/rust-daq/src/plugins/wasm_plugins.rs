@@ -0,0 +1,929 @@
+//! WASM plugin support for sandboxed modules.
+//!
+//! Modules compiled to WebAssembly components (see `wit/daq-module.wit`) run
+//! inside wasmtime with no WASI imports. The only host functions they can
+//! call are the ones in the `host` interface: publish data, emit events, read
+//! their configuration, sleep and poll for a stop request. Each instance gets
+//! its own store with a memory cap, and a guest that ignores `should-stop`
+//! is interrupted via epoch deadlines once the stop grace period expires.
+//! Instantiation, `type-info` and `configure` get [`CALL_TIMEOUT`] to
+//! return before they are interrupted the same way.
+//!
+//! A `WasmModule` implements the `Module` trait of this crate and, with the
+//! `server` feature, that of the daemon, which gets the loaded types through
+//! [`WasmPluginLoader::plugin_module_types`].
+//!
+//! Unlike native plugins, a WASM module cannot crash or corrupt the daemon,
+//! so community-contributed or cross-compiled modules can be loaded safely.
+
+use crate::modules::ModuleContext;
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use common::modules::{
+    ModuleEventSeverity, ModuleParameter, ModuleRole, ModuleState, ModuleTypeInfo,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+use wasmtime::component::{Component, Linker};
+use wasmtime::{Config, Engine, Store, StoreLimits, StoreLimitsBuilder, UpdateDeadline};
+
+/// Bindings generated from `wit/daq-module.wit`
+mod bindings {
+    wasmtime::component::bindgen!({
+        path: "wit",
+        world: "daq-module",
+    });
+}
+
+use bindings::rudaq::module::types::{self as wit_types, Severity};
+use bindings::DaqModule;
+
+/// Interval between epoch ticks (granularity of forced interruption)
+const EPOCH_TICK: Duration = Duration::from_millis(50);
+
+/// How long a stopping guest may keep running before it is interrupted
+const STOP_GRACE: Duration = Duration::from_secs(2);
+
+/// Time limit for instantiation, `type-info` and `configure`, which should
+/// return immediately
+pub const CALL_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Linear memory limit per module instance
+const MAX_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+
+/// Core instances per module; components built by wit-component have
+/// several (the guest plus adapter and shim modules)
+const MAX_CORE_INSTANCES: usize = 16;
+
+/// Slice used by `sleep-ms` so stop requests are noticed promptly
+const SLEEP_SLICE: Duration = Duration::from_millis(10);
+
+// =============================================================================
+// Type Conversions: WIT -> Internal
+// =============================================================================
+
+fn convert_role(role: wit_types::ModuleRole) -> ModuleRole {
+    ModuleRole {
+        role_id: role.role_id,
+        description: role.description,
+        display_name: role.display_name,
+        required_capability: role.required_capability,
+        allows_multiple: role.allows_multiple,
+    }
+}
+
+fn convert_parameter(param: wit_types::ModuleParameter) -> ModuleParameter {
+    ModuleParameter {
+        param_id: param.param_id,
+        display_name: param.display_name,
+        description: param.description,
+        param_type: param.param_type,
+        default_value: param.default_value,
+        min_value: param.min_value,
+        max_value: param.max_value,
+        enum_values: param.enum_values,
+        units: param.units,
+        required: param.required,
+    }
+}
+
+fn convert_type_info(info: wit_types::ModuleTypeInfo) -> ModuleTypeInfo {
+    ModuleTypeInfo {
+        type_id: info.type_id,
+        display_name: info.display_name,
+        description: info.description,
+        version: info.version,
+        parameters: info.parameters.into_iter().map(convert_parameter).collect(),
+        event_types: info.event_types,
+        data_types: info.data_types,
+        required_roles: info.required_roles.into_iter().map(convert_role).collect(),
+        optional_roles: info.optional_roles.into_iter().map(convert_role).collect(),
+        config_schema: info.config_schema,
+    }
+}
+
+fn convert_severity(severity: Severity) -> ModuleEventSeverity {
+    match severity {
+        Severity::Info => ModuleEventSeverity::Info,
+        Severity::Warning => ModuleEventSeverity::Warning,
+        Severity::Error => ModuleEventSeverity::Error,
+        Severity::Critical => ModuleEventSeverity::Critical,
+    }
+}
+
+// =============================================================================
+// Guest Context
+// =============================================================================
+
+/// The parts of a module context a running guest reaches
+///
+/// Implemented for this crate's `ModuleContext` and the daemon's, which have
+/// the same API.
+#[async_trait(?Send)]
+trait GuestContext: Send {
+    fn module_id(&self) -> &str;
+
+    async fn emit_data_with_metadata(
+        &self,
+        data_type: &str,
+        values: HashMap<String, f64>,
+        metadata: HashMap<String, String>,
+    );
+
+    async fn emit_event(&self, event_type: &str, severity: ModuleEventSeverity, message: &str);
+
+    fn is_shutdown_requested(&mut self) -> bool;
+}
+
+macro_rules! impl_guest_context {
+    ($context:ty) => {
+        #[async_trait(?Send)]
+        impl GuestContext for $context {
+            fn module_id(&self) -> &str {
+                &self.module_id
+            }
+
+            async fn emit_data_with_metadata(
+                &self,
+                data_type: &str,
+                values: HashMap<String, f64>,
+                metadata: HashMap<String, String>,
+            ) {
+                <$context>::emit_data_with_metadata(self, data_type, values, metadata).await;
+            }
+
+            async fn emit_event(
+                &self,
+                event_type: &str,
+                severity: ModuleEventSeverity,
+                message: &str,
+            ) {
+                <$context>::emit_event(self, event_type, severity, message).await;
+            }
+
+            fn is_shutdown_requested(&mut self) -> bool {
+                <$context>::is_shutdown_requested(self)
+            }
+        }
+    };
+}
+
+impl_guest_context!(ModuleContext);
+#[cfg(feature = "server")]
+impl_guest_context!(server::modules::ModuleContext);
+
+// =============================================================================
+// Host State
+// =============================================================================
+
+/// Flags shared between a `WasmModule` and its running guest
+#[derive(Debug, Default)]
+struct RunFlags {
+    /// Cooperative stop request (seen through `should-stop`)
+    stop: AtomicBool,
+    /// Forced interruption once the grace period is over
+    kill: AtomicBool,
+    /// `sleep-ms` blocks while set
+    paused: AtomicBool,
+    /// The guest's `run` returned an error or trapped
+    failed: AtomicBool,
+}
+
+/// Per-instance store data: everything the guest can reach
+struct HostState {
+    module_id: String,
+    config: HashMap<String, String>,
+    flags: Arc<RunFlags>,
+    /// Present only while `run` executes
+    ctx: Option<(Box<dyn GuestContext>, tokio::runtime::Handle)>,
+    /// Set while a call limited to [`CALL_TIMEOUT`] executes
+    call_deadline: Option<Instant>,
+    limits: StoreLimits,
+}
+
+impl wit_types::Host for HostState {}
+
+impl bindings::rudaq::module::host::Host for HostState {
+    fn publish_data(
+        &mut self,
+        data_type: String,
+        values: Vec<(String, f64)>,
+        metadata: Vec<(String, String)>,
+    ) {
+        let Some((ctx, handle)) = &self.ctx else {
+            warn!(
+                "WASM module {} published data while not running",
+                self.module_id
+            );
+            return;
+        };
+        handle.block_on(ctx.emit_data_with_metadata(
+            &data_type,
+            values.into_iter().collect(),
+            metadata.into_iter().collect(),
+        ));
+    }
+
+    fn emit_event(&mut self, event_type: String, severity: Severity, message: String) {
+        let Some((ctx, handle)) = &self.ctx else {
+            warn!(
+                "WASM module {} emitted an event while not running",
+                self.module_id
+            );
+            return;
+        };
+        handle.block_on(ctx.emit_event(&event_type, convert_severity(severity), &message));
+    }
+
+    fn get_config(&mut self, key: String) -> Option<String> {
+        self.config.get(&key).cloned()
+    }
+
+    fn sleep_ms(&mut self, ms: u32) {
+        let deadline = Instant::now() + Duration::from_millis(u64::from(ms));
+        loop {
+            if self.flags.stop.load(Ordering::Relaxed) {
+                return;
+            }
+            let paused = self.flags.paused.load(Ordering::Relaxed);
+            if !paused && Instant::now() >= deadline {
+                return;
+            }
+            std::thread::sleep(SLEEP_SLICE);
+        }
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if let Some((ctx, _)) = &mut self.ctx {
+            if ctx.is_shutdown_requested() {
+                self.flags.stop.store(true, Ordering::Relaxed);
+            }
+        }
+        self.flags.stop.load(Ordering::Relaxed)
+    }
+
+    fn log(&mut self, message: String) {
+        info!(module_id = %self.module_id, "{}", message);
+    }
+}
+
+/// A guest instance and the store it lives in
+struct WasmInstance {
+    store: Store<HostState>,
+    bindings: DaqModule,
+}
+
+/// Run a guest call that is interrupted if it takes longer than [`CALL_TIMEOUT`]
+fn call_with_timeout<R>(
+    store: &mut Store<HostState>,
+    call: impl FnOnce(&mut Store<HostState>) -> Result<R>,
+) -> Result<R> {
+    store.data_mut().call_deadline = Some(Instant::now() + CALL_TIMEOUT);
+    let result = call(&mut *store);
+    store.data_mut().call_deadline = None;
+    result
+}
+
+// =============================================================================
+// WasmModule
+// =============================================================================
+
+/// A module running inside a WASM sandbox, adapted to the `Module` traits.
+pub struct WasmModule {
+    type_info: ModuleTypeInfo,
+    instance: Arc<Mutex<WasmInstance>>,
+    config: HashMap<String, String>,
+    flags: Arc<RunFlags>,
+    state: ModuleState,
+    task: Option<JoinHandle<()>>,
+}
+
+impl WasmModule {
+    fn new(
+        engine: &Engine,
+        linker: &Linker<HostState>,
+        component: &Component,
+        type_info: ModuleTypeInfo,
+        module_id: String,
+    ) -> Result<Self> {
+        let flags = Arc::new(RunFlags::default());
+        let (store, bindings) = instantiate(engine, linker, component, module_id, &flags)?;
+
+        Ok(Self {
+            type_info,
+            instance: Arc::new(Mutex::new(WasmInstance { store, bindings })),
+            config: HashMap::new(),
+            flags,
+            state: ModuleState::Created,
+            task: None,
+        })
+    }
+
+    fn is_running(&self) -> bool {
+        self.task.as_ref().is_some_and(|task| !task.is_finished())
+    }
+
+    fn configure_guest(&mut self, params: HashMap<String, String>) -> Result<Vec<String>> {
+        if self.is_running() {
+            return Err(anyhow!("Cannot configure a running WASM module"));
+        }
+
+        let mut guard = self.instance.lock().unwrap_or_else(|p| p.into_inner());
+        let WasmInstance { store, bindings } = &mut *guard;
+
+        let config: Vec<(String, String)> =
+            params.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        let warnings = call_with_timeout(store, |store| bindings.call_configure(store, &config))
+            .context("WASM module trapped in configure")?
+            .map_err(|e| anyhow!("{}", e))?;
+
+        store.data_mut().config = params.clone();
+        self.config = params;
+        self.state = ModuleState::Configured;
+        Ok(warnings)
+    }
+
+    fn start_guest(&mut self, ctx: Box<dyn GuestContext>) -> Result<()> {
+        if self.is_running() {
+            return Err(anyhow!("WASM module is already running"));
+        }
+
+        self.flags.stop.store(false, Ordering::Relaxed);
+        self.flags.kill.store(false, Ordering::Relaxed);
+        self.flags.paused.store(false, Ordering::Relaxed);
+        self.flags.failed.store(false, Ordering::Relaxed);
+
+        let instance = Arc::clone(&self.instance);
+        let flags = Arc::clone(&self.flags);
+        let handle = tokio::runtime::Handle::current();
+
+        self.task = Some(tokio::task::spawn_blocking(move || {
+            let mut guard = instance.lock().unwrap_or_else(|p| p.into_inner());
+            let WasmInstance { store, bindings } = &mut *guard;
+            store.data_mut().ctx = Some((ctx, handle.clone()));
+
+            let message = match bindings.call_run(&mut *store) {
+                Ok(Ok(())) => None,
+                Ok(Err(e)) => Some(e),
+                Err(_) if flags.kill.load(Ordering::Relaxed) => {
+                    Some("Module did not stop within the grace period".to_string())
+                }
+                Err(trap) => Some(format!("Module trapped: {:#}", trap)),
+            };
+            let Some((ctx, _)) = store.data_mut().ctx.take() else {
+                return;
+            };
+
+            if let Some(message) = message {
+                warn!("WASM module {} failed: {}", ctx.module_id(), message);
+                flags.failed.store(true, Ordering::Relaxed);
+                handle.block_on(ctx.emit_event("error", ModuleEventSeverity::Error, &message));
+            } else {
+                debug!("WASM module {} finished", ctx.module_id());
+            }
+        }));
+
+        self.state = ModuleState::Running;
+        Ok(())
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        self.flags.paused.store(paused, Ordering::Relaxed);
+        self.state = if paused {
+            ModuleState::Paused
+        } else {
+            ModuleState::Running
+        };
+    }
+
+    async fn stop_guest(&mut self) {
+        self.flags.stop.store(true, Ordering::Relaxed);
+        self.flags.paused.store(false, Ordering::Relaxed);
+
+        if let Some(mut task) = self.task.take() {
+            if tokio::time::timeout(STOP_GRACE, &mut task).await.is_err() {
+                warn!(
+                    "WASM module {} ignored stop request, interrupting",
+                    self.type_info.type_id
+                );
+                self.flags.kill.store(true, Ordering::Relaxed);
+                let _ = task.await;
+            }
+        }
+
+        self.state = ModuleState::Stopped;
+    }
+
+    fn current_state(&self) -> ModuleState {
+        let finished = self.task.as_ref().is_some_and(JoinHandle::is_finished);
+        match self.state {
+            ModuleState::Running | ModuleState::Paused if finished => {
+                if self.flags.failed.load(Ordering::Relaxed) {
+                    ModuleState::Error
+                } else {
+                    ModuleState::Stopped
+                }
+            }
+            state => state,
+        }
+    }
+}
+
+/// Create a sandboxed store and instantiate the component in it
+fn instantiate(
+    engine: &Engine,
+    linker: &Linker<HostState>,
+    component: &Component,
+    module_id: String,
+    flags: &Arc<RunFlags>,
+) -> Result<(Store<HostState>, DaqModule)> {
+    let state = HostState {
+        module_id,
+        config: HashMap::new(),
+        flags: Arc::clone(flags),
+        ctx: None,
+        call_deadline: Some(Instant::now() + CALL_TIMEOUT),
+        limits: StoreLimitsBuilder::new()
+            .memory_size(MAX_MEMORY_BYTES)
+            .instances(MAX_CORE_INSTANCES)
+            .build(),
+    };
+
+    let mut store = Store::new(engine, state);
+    store.limiter(|state| &mut state.limits);
+    store.set_epoch_deadline(1);
+    let flags = Arc::clone(flags);
+    store.epoch_deadline_callback(move |store| {
+        if flags.kill.load(Ordering::Relaxed) {
+            Err(anyhow!("module interrupted by host"))
+        } else if store
+            .data()
+            .call_deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            Err(anyhow!("module did not return within {:?}", CALL_TIMEOUT))
+        } else {
+            Ok(UpdateDeadline::Continue(1))
+        }
+    });
+
+    // Start functions run during instantiation, under the initial deadline
+    let bindings = DaqModule::instantiate(&mut store, component, linker)
+        .context("Failed to instantiate WASM module")?;
+    store.data_mut().call_deadline = None;
+    Ok((store, bindings))
+}
+
+/// Implement a `Module` trait (this crate's or the daemon's) for `WasmModule`
+macro_rules! impl_module {
+    ($($modules:ident)::+) => {
+        #[async_trait]
+        impl $($modules)::+::Module for WasmModule {
+            fn type_info() -> ModuleTypeInfo
+            where
+                Self: Sized,
+            {
+                panic!("WasmModule::type_info() should not be called - use instance type_info")
+            }
+
+            fn type_id(&self) -> &str {
+                &self.type_info.type_id
+            }
+
+            fn configure(&mut self, params: HashMap<String, String>) -> Result<Vec<String>> {
+                self.configure_guest(params)
+            }
+
+            fn get_config(&self) -> HashMap<String, String> {
+                self.config.clone()
+            }
+
+            async fn start(&mut self, ctx: $($modules)::+::ModuleContext) -> Result<()> {
+                self.start_guest(Box::new(ctx))
+            }
+
+            async fn pause(&mut self) -> Result<()> {
+                self.set_paused(true);
+                Ok(())
+            }
+
+            async fn resume(&mut self) -> Result<()> {
+                self.set_paused(false);
+                Ok(())
+            }
+
+            async fn stop(&mut self) -> Result<()> {
+                self.stop_guest().await;
+                Ok(())
+            }
+
+            fn state(&self) -> ModuleState {
+                self.current_state()
+            }
+        }
+    };
+}
+
+impl_module!(crate::modules);
+#[cfg(feature = "server")]
+impl_module!(server::modules);
+
+impl std::fmt::Debug for WasmModule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WasmModule")
+            .field("type_info", &self.type_info)
+            .field("config", &self.config)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+// =============================================================================
+// WasmPluginLoader
+// =============================================================================
+
+/// A compiled WASM module type
+struct WasmPlugin {
+    path: PathBuf,
+    component: Component,
+    type_info: ModuleTypeInfo,
+}
+
+/// Discovers `.wasm` components and creates sandboxed module instances.
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// use rust_daq::plugins::WasmPluginLoader;
+///
+/// let mut loader = WasmPluginLoader::new()?;
+/// loader.add_search_path("./plugins/wasm");
+/// loader.discover()?;
+///
+/// let module = loader.create_module("drift_stabilizer", module_id)?;
+/// ```
+pub struct WasmPluginLoader {
+    engine: Engine,
+    linker: Arc<Linker<HostState>>,
+    search_paths: Vec<PathBuf>,
+    /// Loaded module types: type_id -> plugin
+    plugins: HashMap<String, WasmPlugin>,
+}
+
+impl WasmPluginLoader {
+    /// Create a loader with a sandboxing engine
+    pub fn new() -> Result<Self> {
+        let mut config = Config::new();
+        config.wasm_component_model(true);
+        config.epoch_interruption(true);
+        let engine = Engine::new(&config)?;
+
+        let mut linker = Linker::new(&engine);
+        DaqModule::add_to_linker(&mut linker, |state: &mut HostState| state)?;
+
+        // Drive epoch deadlines; the thread exits once the engine is dropped
+        let weak = engine.weak();
+        std::thread::Builder::new()
+            .name("wasm-epoch".to_string())
+            .spawn(move || {
+                while let Some(engine) = weak.upgrade() {
+                    engine.increment_epoch();
+                    drop(engine);
+                    std::thread::sleep(EPOCH_TICK);
+                }
+            })?;
+
+        Ok(Self {
+            engine,
+            linker: Arc::new(linker),
+            search_paths: Vec::new(),
+            plugins: HashMap::new(),
+        })
+    }
+
+    /// Add a directory to scan for `.wasm` files
+    pub fn add_search_path(&mut self, path: impl AsRef<Path>) {
+        self.search_paths.push(path.as_ref().to_path_buf());
+    }
+
+    /// Compile every `.wasm` component in the search paths.
+    ///
+    /// Files that fail to load are logged and skipped. Returns the type IDs
+    /// that were loaded.
+    pub fn discover(&mut self) -> Result<Vec<String>> {
+        let mut loaded = Vec::new();
+
+        for dir in self.search_paths.clone() {
+            if !dir.is_dir() {
+                debug!("WASM plugin path does not exist: {:?}", dir);
+                continue;
+            }
+
+            for entry in std::fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.extension().and_then(|e| e.to_str()) != Some("wasm") {
+                    continue;
+                }
+                match self.load_file(&path) {
+                    Ok(type_id) => loaded.push(type_id),
+                    Err(e) => warn!("Failed to load WASM plugin {:?}: {:#}", path, e),
+                }
+            }
+        }
+
+        info!("Discovered {} WASM module types", loaded.len());
+        Ok(loaded)
+    }
+
+    /// Compile a single component and register its module type
+    pub fn load_file(&mut self, path: &Path) -> Result<String> {
+        let component = Component::from_file(&self.engine, path)
+            .with_context(|| format!("Failed to compile {:?}", path))?;
+
+        // Instantiate once in a throwaway store to read the type info
+        let flags = Arc::new(RunFlags::default());
+        let (mut store, bindings) = instantiate(
+            &self.engine,
+            &self.linker,
+            &component,
+            String::new(),
+            &flags,
+        )?;
+        let type_info = convert_type_info(
+            call_with_timeout(&mut store, |store| bindings.call_type_info(store))
+                .context("WASM module trapped in type-info")?,
+        );
+
+        let type_id = type_info.type_id.clone();
+        if type_id.is_empty() {
+            return Err(anyhow!("WASM module {:?} has an empty type_id", path));
+        }
+        if let Some(existing) = self.plugins.get(&type_id) {
+            return Err(anyhow!(
+                "Module type '{}' is already provided by {:?}",
+                type_id,
+                existing.path
+            ));
+        }
+
+        info!("Loaded WASM module type: {} ({:?})", type_id, path);
+        self.plugins.insert(
+            type_id.clone(),
+            WasmPlugin {
+                path: path.to_path_buf(),
+                component,
+                type_info,
+            },
+        );
+        Ok(type_id)
+    }
+
+    /// All loaded module types
+    pub fn list_module_types(&self) -> impl Iterator<Item = &ModuleTypeInfo> {
+        self.plugins.values().map(|p| &p.type_info)
+    }
+
+    /// Type info for a loaded module type
+    pub fn type_info(&self, type_id: &str) -> Option<&ModuleTypeInfo> {
+        self.plugins.get(type_id).map(|p| &p.type_info)
+    }
+
+    /// Instantiate a module in its own sandbox
+    pub fn create_module(&self, type_id: &str, module_id: String) -> Result<WasmModule> {
+        let plugin = self
+            .plugins
+            .get(type_id)
+            .ok_or_else(|| anyhow!("No WASM plugin provides module type: {}", type_id))?;
+
+        WasmModule::new(
+            &self.engine,
+            &self.linker,
+            &plugin.component,
+            plugin.type_info.clone(),
+            module_id,
+        )
+    }
+
+    /// Loaded module types for the daemon's module registry
+    ///
+    /// Each factory instantiates the component in a new sandbox; the types
+    /// stay usable after the loader is dropped.
+    #[cfg(feature = "server")]
+    pub fn plugin_module_types(&self) -> Vec<server::modules::PluginModuleType> {
+        self.plugins
+            .values()
+            .map(|plugin| {
+                let engine = self.engine.clone();
+                let linker = Arc::clone(&self.linker);
+                let component = plugin.component.clone();
+                let type_info = plugin.type_info.clone();
+                server::modules::PluginModuleType {
+                    info: plugin.type_info.clone(),
+                    factory: Arc::new(move |module_id: &str| {
+                        let module = WasmModule::new(
+                            &engine,
+                            &linker,
+                            &component,
+                            type_info.clone(),
+                            module_id.to_string(),
+                        )?;
+                        Ok(Box::new(module) as Box<dyn server::modules::Module>)
+                    }),
+                }
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for WasmPluginLoader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WasmPluginLoader")
+            .field("search_paths", &self.search_paths)
+            .field("plugins", &self.plugins.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hardware::registry::DeviceRegistry;
+    use crate::modules::Module;
+    use tokio::sync::{broadcast, mpsc};
+
+    /// Component whose `run` publishes one data point and whose `configure`
+    /// never returns when given any configuration
+    const TEST_COMPONENT: &str = r#"
+(component
+  (import "rudaq:module/host@0.1.0" (instance $host
+    (export "publish-data" (func
+      (param "data-type" string)
+      (param "values" (list (tuple string f64)))
+      (param "metadata" (list (tuple string string)))))))
+
+  (core module $Libc
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 4096))
+    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr
+        (i32.and
+          (i32.add (global.get $heap) (i32.sub (local.get 2) (i32.const 1)))
+          (i32.sub (i32.const 0) (local.get 2))))
+      (global.set $heap (i32.add (local.get $ptr) (local.get 3)))
+      (local.get $ptr)))
+  (core instance $libc (instantiate $Libc))
+
+  (core func $publish (canon lower (func $host "publish-data")
+    (memory $libc "memory") (realloc (func $libc "realloc"))))
+
+  (core module $Main
+    (import "libc" "memory" (memory 1))
+    (import "host" "publish-data" (func $publish (param i32 i32 i32 i32 i32 i32)))
+    (data (i32.const 16) "wasm_test")
+    (data (i32.const 32) "WASM test")
+    (data (i32.const 48) "reading")
+    (data (i32.const 64) "value")
+    (data (i32.const 80) "1.0")
+    ;; values: [("value", 42.0)]
+    (data (i32.const 128) "\40\00\00\00\05\00\00\00\00\00\00\00\00\00\45\40")
+    ;; module-type-info: type-id, display-name, description, version, the rest empty
+    (data (i32.const 256)
+      "\10\00\00\00\09\00\00\00\20\00\00\00\09\00\00\00"
+      "\00\00\00\00\00\00\00\00\50\00\00\00\03\00\00\00")
+    ;; 512 and 544 stay zeroed: ok(empty list) and ok(())
+    (func (export "type-info") (result i32) (i32.const 256))
+    (func (export "configure") (param i32 i32) (result i32)
+      (if (local.get 1) (then (loop $spin (br $spin))))
+      (i32.const 512))
+    (func (export "run") (result i32)
+      (call $publish
+        (i32.const 48) (i32.const 7) (i32.const 128) (i32.const 1) (i32.const 0) (i32.const 0))
+      (i32.const 544)))
+  (core instance $main (instantiate $Main
+    (with "libc" (instance $libc))
+    (with "host" (instance (export "publish-data" (func $publish))))))
+
+  (type $parameter' (record
+    (field "param-id" string) (field "display-name" string) (field "description" string)
+    (field "param-type" string) (field "default-value" string)
+    (field "min-value" (option string)) (field "max-value" (option string))
+    (field "enum-values" (list string)) (field "units" string) (field "required" bool)))
+  (export $parameter "module-parameter" (type $parameter'))
+  (type $role' (record
+    (field "role-id" string) (field "description" string) (field "display-name" string)
+    (field "required-capability" string) (field "allows-multiple" bool)))
+  (export $role "module-role" (type $role'))
+  (type $info' (record
+    (field "type-id" string) (field "display-name" string) (field "description" string)
+    (field "version" string) (field "parameters" (list $parameter))
+    (field "event-types" (list string)) (field "data-types" (list string))
+    (field "required-roles" (list $role)) (field "optional-roles" (list $role))
+    (field "config-schema" (option string))))
+  (export $info "module-type-info" (type $info'))
+
+  (func (export "type-info") (result $info)
+    (canon lift (core func $main "type-info") (memory $libc "memory")))
+  (func (export "configure")
+    (param "config" (list (tuple string string)))
+    (result (result (list string) (error string)))
+    (canon lift (core func $main "configure")
+      (memory $libc "memory") (realloc (func $libc "realloc"))))
+  (func (export "run") (result (result (error string)))
+    (canon lift (core func $main "run") (memory $libc "memory"))))
+"#;
+
+    /// Loader with [`TEST_COMPONENT`] loaded from a `.wasm` file
+    fn load_test_component() -> WasmPluginLoader {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test_module.wasm");
+        std::fs::write(&path, wat::parse_str(TEST_COMPONENT).unwrap()).unwrap();
+        let mut loader = WasmPluginLoader::new().unwrap();
+        assert_eq!(loader.load_file(&path).unwrap(), "wasm_test");
+        loader
+    }
+
+    #[test]
+    fn test_severity_conversion() {
+        assert_eq!(convert_severity(Severity::Info), ModuleEventSeverity::Info);
+        assert_eq!(
+            convert_severity(Severity::Warning),
+            ModuleEventSeverity::Warning
+        );
+        assert_eq!(
+            convert_severity(Severity::Error),
+            ModuleEventSeverity::Error
+        );
+        assert_eq!(
+            convert_severity(Severity::Critical),
+            ModuleEventSeverity::Critical
+        );
+    }
+
+    #[test]
+    fn test_discover_skips_invalid_components() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("broken.wasm"), b"not a component").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), b"ignored").unwrap();
+
+        let mut loader = WasmPluginLoader::new().unwrap();
+        loader.add_search_path(dir.path());
+        let loaded = loader.discover().unwrap();
+
+        assert!(loaded.is_empty());
+        assert_eq!(loader.list_module_types().count(), 0);
+        assert!(loader.create_module("broken", "id".to_string()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_component_runs_and_publishes_data() {
+        let loader = load_test_component();
+        let info = loader.type_info("wasm_test").unwrap();
+        assert_eq!(info.display_name, "WASM test");
+        assert_eq!(info.version, "1.0");
+
+        let mut module = loader.create_module("wasm_test", "m1".to_string()).unwrap();
+        assert!(module.configure(HashMap::new()).unwrap().is_empty());
+
+        let (event_tx, _event_rx) = mpsc::channel(8);
+        let (data_tx, mut data_rx) = mpsc::channel(8);
+        let (_shutdown_tx, shutdown_rx) = broadcast::channel(1);
+        let ctx = ModuleContext::new(
+            "m1".to_string(),
+            HashMap::new(),
+            Arc::new(DeviceRegistry::new()),
+            event_tx,
+            data_tx,
+            shutdown_rx,
+        );
+        module.start(ctx).await.unwrap();
+
+        let point = tokio::time::timeout(Duration::from_secs(5), data_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(point.module_id, "m1");
+        assert_eq!(point.data_type, "reading");
+        assert_eq!(point.values["value"], 42.0);
+
+        module.stop().await.unwrap();
+        assert_eq!(module.state(), ModuleState::Stopped);
+    }
+
+    #[test]
+    fn test_configure_is_interrupted_after_call_timeout() {
+        let loader = load_test_component();
+        let mut module = loader.create_module("wasm_test", "m2".to_string()).unwrap();
+
+        let started = Instant::now();
+        let config = HashMap::from([("spin".to_string(), "forever".to_string())]);
+        let err = module.configure(config).unwrap_err();
+        assert!(format!("{:#}", err).contains("trapped in configure"));
+        assert!(started.elapsed() < CALL_TIMEOUT * 3);
+    }
+}
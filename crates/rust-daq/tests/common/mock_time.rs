@@ -0,0 +1,161 @@
+//! Virtual time for timing-dependent integration tests
+//!
+//! Every timer in the daemon is a tokio timer: RunEngine waits and
+//! timeouts, storage flush tickers, module polling loops, stream keyframe
+//! intervals. On a runtime whose clock is paused they fire only when the
+//! test moves the clock, so a test can check "nothing happens for 999 ms,
+//! then one event" exactly, instead of sleeping for real and widening its
+//! assertions with [`TimingTolerance`](super::TimingTolerance).
+//!
+//! ```ignore
+//! #[tokio::test(start_paused = true)]
+//! async fn count_waits_between_points() {
+//!     let time = MockTime::paused();
+//!     let run = tokio::spawn(async move { engine.start().await });
+//!     time.advance(Duration::from_millis(999)).await;
+//!     // ...nothing new yet...
+//!     time.advance(Duration::from_millis(1)).await;
+//!     // ...second point...
+//! }
+//! ```
+//!
+//! Caveats:
+//!
+//! - Paused time needs the current-thread runtime (the `#[tokio::test]`
+//!   default), either `start_paused = true` or [`MockTime::pause`].
+//! - When every task is idle, tokio jumps the clock to the next timer by
+//!   itself, so awaiting something that waits on a timer also moves time.
+//!   Check progress with non-blocking calls (`try_recv`, state getters)
+//!   between advances.
+//! - `std::time::Instant`, `std::thread::sleep` and work on blocking
+//!   threads do not follow the paused clock.
+
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Yields after each step, so woken tasks run before the next one
+const SETTLE_YIELDS: usize = 64;
+
+/// Default clock step of [`MockTime::advance`]
+const DEFAULT_RESOLUTION: Duration = Duration::from_millis(1);
+
+/// Handle on the paused tokio clock of the current test
+#[derive(Debug, Clone)]
+pub struct MockTime {
+    origin: Instant,
+    resolution: Duration,
+}
+
+impl MockTime {
+    /// Clock of a runtime started with `start_paused = true`
+    pub fn paused() -> Self {
+        Self {
+            origin: Instant::now(),
+            resolution: DEFAULT_RESOLUTION,
+        }
+    }
+
+    /// Pause the current runtime's clock and take control of it
+    pub fn pause() -> Self {
+        tokio::time::pause();
+        Self::paused()
+    }
+
+    /// Move the clock in steps of `resolution` (default 1 ms)
+    ///
+    /// Code that re-arms a timer after each tick (`sleep` in a loop) only
+    /// sees one tick per step, so coarser steps run faster but skip ticks.
+    pub fn with_resolution(mut self, resolution: Duration) -> Self {
+        assert!(
+            !resolution.is_zero(),
+            "MockTime resolution must be positive"
+        );
+        self.resolution = resolution;
+        self
+    }
+
+    /// Virtual time since this handle was created
+    pub fn elapsed(&self) -> Duration {
+        self.origin.elapsed()
+    }
+
+    /// Let woken tasks run until they wait on the clock again
+    pub async fn settle(&self) {
+        for _ in 0..SETTLE_YIELDS {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    /// Move the clock forward by `by`, firing every timer that falls due
+    pub async fn advance(&self, by: Duration) {
+        let end = Instant::now() + by;
+        self.settle().await;
+        loop {
+            let now = Instant::now();
+            if now >= end {
+                break;
+            }
+            tokio::time::advance(self.resolution.min(end - now)).await;
+            self.settle().await;
+        }
+    }
+
+    /// Move the clock until `done` holds, at most `limit`
+    ///
+    /// Returns the virtual time it took. Panics if `done` still does not
+    /// hold after `limit`.
+    pub async fn advance_until<F, Fut>(&self, limit: Duration, mut done: F) -> Duration
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = bool>,
+    {
+        let start = Instant::now();
+        self.settle().await;
+        while !done().await {
+            let waited = start.elapsed();
+            assert!(
+                waited < limit,
+                "condition not met after {:?} of virtual time",
+                waited
+            );
+            tokio::time::advance(self.resolution).await;
+            self.settle().await;
+        }
+        start.elapsed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_advance_fires_each_tick() {
+        let time = MockTime::paused();
+        let ticks = Arc::new(AtomicU32::new(0));
+        let counter = ticks.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        time.advance(Duration::from_millis(999)).await;
+        assert_eq!(ticks.load(Ordering::SeqCst), 9);
+        time.advance(Duration::from_millis(1)).await;
+        assert_eq!(ticks.load(Ordering::SeqCst), 10);
+        assert_eq!(time.elapsed(), Duration::from_secs(1));
+
+        let took = time
+            .advance_until(Duration::from_secs(5), || {
+                let ticks = ticks.clone();
+                async move { ticks.load(Ordering::SeqCst) >= 25 }
+            })
+            .await;
+        assert_eq!(took, Duration::from_millis(1500));
+    }
+}
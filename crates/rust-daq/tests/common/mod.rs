@@ -1,12 +1,17 @@
 //! Common test utilities for rust-daq integration tests
 //!
 //! This module provides reusable test helpers for:
+//! - Virtual time driving the daemon's timers (see [`mock_time`])
 //! - Timing assertions with appropriate tolerances
 //! - Environment-aware tolerance selection
 //! - Test setup utilities
 
 #![allow(dead_code)] // Utilities may not all be used immediately
 
+pub mod mock_time;
+
+pub use mock_time::MockTime;
+
 use std::time::Duration;
 
 /// Tolerance levels for real-time timing assertions.
@@ -47,7 +47,7 @@
 //! - Ubuntu: `sudo apt-get install libhdf5-dev`
 //! - If HDF5 is not available, those tests will be skipped automatically.
 
-use common::core::Measurement;
+use common::core::{DataQuality, Measurement};
 use std::sync::Arc;
 use tempfile::TempDir;
 
@@ -63,6 +63,7 @@ fn create_test_scalar(name: &str, value: f64) -> Measurement {
         value,
         unit: "V".to_string(),
         timestamp: chrono::Utc::now(),
+        quality: DataQuality::Good,
     }
 }
 
@@ -42,6 +42,7 @@ async fn create_test_registry() -> DeviceRegistry {
             driver: DriverType::MockStage {
                 initial_position: 0.0,
             },
+            simulated: false,
         })
         .await
         .expect("Failed to register mock stage");
@@ -52,6 +53,7 @@ async fn create_test_registry() -> DeviceRegistry {
             id: "power_meter".into(),
             name: "Test Power Meter".into(),
             driver: DriverType::MockPowerMeter { reading: 1e-3 },
+            simulated: false,
         })
         .await
         .expect("Failed to register power_meter");
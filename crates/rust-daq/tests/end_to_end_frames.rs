@@ -29,6 +29,7 @@ async fn test_end_to_end_frames() -> anyhow::Result<()> {
             driver: DriverType::Pvcam {
                 camera_name: "MockCam".into(),
             },
+            simulated: false,
         })
         .await?;
     let registry_arc = Arc::new(registry);
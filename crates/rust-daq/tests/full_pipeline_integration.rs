@@ -29,7 +29,7 @@
 //! ```
 
 use anyhow::Result;
-use common::core::Measurement;
+use common::core::{DataQuality, Measurement};
 use rust_daq::hardware::capabilities::{FrameProducer, Movable, Readable, Triggerable};
 use rust_daq::hardware::mock::{MockCamera, MockPowerMeter, MockStage};
 use std::sync::Arc;
@@ -50,6 +50,7 @@ async fn acquire_power_measurement(
         value,
         unit: "W".to_string(),
         timestamp: chrono::Utc::now(),
+        quality: DataQuality::Good,
     })
 }
 
@@ -61,6 +62,7 @@ async fn acquire_position_measurement(stage: &MockStage, name: &str) -> Result<M
         value: position,
         unit: "mm".to_string(),
         timestamp: chrono::Utc::now(),
+        quality: DataQuality::Good,
     })
 }
 
@@ -228,6 +230,7 @@ async fn test_full_pipeline_to_csv() {
                 value,
                 unit,
                 timestamp,
+                ..
             } => {
                 writeln!(file, "{},{},{},{}", name, value, unit, timestamp).unwrap();
             }
@@ -91,6 +91,7 @@ fn test_grpc_types_accessible() {
         script_id: "script-123".to_string(),
         progress_percent: 50,
         current_line: String::new(),
+        started_by: String::new(),
     };
     assert_eq!(script_status.state, "RUNNING");
 
@@ -98,6 +99,7 @@ fn test_grpc_types_accessible() {
     let measurement_req = MeasurementRequest {
         channels: vec!["ch1".to_string()],
         max_rate_hz: 100,
+        decimation: None,
     };
     assert_eq!(measurement_req.channels[0], "ch1");
 
@@ -105,6 +107,7 @@ fn test_grpc_types_accessible() {
         channel: "test".to_string(),
         value: 1.0,
         timestamp_ns: 0,
+        quality: 0,
     };
     assert_eq!(data_point.channel, "test");
 }
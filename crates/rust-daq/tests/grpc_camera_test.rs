@@ -52,6 +52,7 @@ async fn test_grpc_camera_control_stream() -> Result<()> {
             driver: DriverType::Pvcam {
                 camera_name: "MockCamera".to_string(),
             },
+            simulated: false,
         })
         .await?;
 
@@ -67,6 +68,7 @@ async fn test_grpc_camera_control_stream() -> Result<()> {
         device_id: "prime_bsi".to_string(),
         parameter_name: "acquisition.exposure_ms".to_string(),
         value: "50.0".to_string(),
+        typed_value: None,
     });
     let response = service.set_parameter(request).await?;
     let set_resp = response.into_inner();
@@ -95,6 +97,7 @@ async fn test_grpc_camera_control_stream() -> Result<()> {
         device_id: "prime_bsi".to_string(),
         max_fps: 0,
         quality: StreamQuality::Full.into(),
+        ..Default::default()
     });
     let mut stream = service.stream_frames(request).await?.into_inner();
 
@@ -46,6 +46,7 @@ mod camera_integration_tests {
                     width: 640,
                     height: 480,
                 },
+                simulated: false,
             })
             .await
             .unwrap();
@@ -61,6 +62,7 @@ mod camera_integration_tests {
 
         let request = Request::new(ListDevicesRequest {
             capability_filter: None,
+            ..Default::default()
         });
         let response = service.list_devices(request).await.unwrap();
         let devices = response.into_inner().devices;
@@ -84,6 +86,7 @@ mod camera_integration_tests {
 
         let request = Request::new(ListDevicesRequest {
             capability_filter: Some("triggerable".to_string()),
+            ..Default::default()
         });
         let response = service.list_devices(request).await.unwrap();
         let devices = response.into_inner().devices;
@@ -100,6 +103,7 @@ mod camera_integration_tests {
 
         let request = Request::new(ListDevicesRequest {
             capability_filter: Some("frame_producer".to_string()),
+            ..Default::default()
         });
         let response = service.list_devices(request).await.unwrap();
         let devices = response.into_inner().devices;
@@ -305,6 +309,7 @@ mod camera_integration_tests {
             device_id: "test_camera".to_string(),
             max_fps: 10,
             quality: StreamQuality::Full.into(),
+            ..Default::default()
         });
         let mut stream = service.stream_frames(request).await.unwrap().into_inner();
 
@@ -365,6 +370,7 @@ mod camera_integration_tests {
             device_id: "test_camera".to_string(),
             max_fps: 10,
             quality: StreamQuality::Full.into(),
+            ..Default::default()
         });
         let mut stream = service.stream_frames(request).await.unwrap().into_inner();
 
@@ -424,6 +430,7 @@ mod scan_integration_tests {
                 driver: DriverType::MockStage {
                     initial_position: 0.0,
                 },
+                simulated: false,
             })
             .await
             .unwrap();
@@ -434,6 +441,7 @@ mod scan_integration_tests {
                 id: "test_meter".into(),
                 name: "Test Power Meter".into(),
                 driver: DriverType::MockPowerMeter { reading: 1.0 },
+                simulated: false,
             })
             .await
             .unwrap();
@@ -51,6 +51,7 @@ async fn test_basic_parameter_integration() -> Result<()> {
                 width: 640,
                 height: 480,
             },
+            simulated: false,
         })
         .await?;
 
@@ -72,6 +73,7 @@ async fn test_basic_parameter_integration() -> Result<()> {
         device_id: "mock_camera".to_string(),
         parameter_name: "exposure_s".to_string(),
         value: "0.1".to_string(), // 100ms exposure
+        typed_value: None,
     });
     let response = service.set_parameter(request).await?;
     let set_response = response.into_inner();
@@ -111,6 +113,7 @@ async fn test_parameter_change_notifications() -> Result<()> {
                 width: 640,
                 height: 480,
             },
+            simulated: false,
         })
         .await?;
 
@@ -133,6 +136,7 @@ async fn test_parameter_change_notifications() -> Result<()> {
         device_id: "mock_camera".to_string(),
         parameter_name: "exposure_s".to_string(),
         value: "0.25".to_string(),
+        typed_value: None,
     });
     service.set_parameter(request).await?;
 
@@ -280,6 +284,7 @@ async fn test_invalid_parameter_name() -> Result<()> {
                 width: 640,
                 height: 480,
             },
+            simulated: false,
         })
         .await?;
 
@@ -291,6 +296,7 @@ async fn test_invalid_parameter_name() -> Result<()> {
         device_id: "mock_camera".to_string(),
         parameter_name: "invalid_param".to_string(),
         value: "123".to_string(),
+        typed_value: None,
     });
 
     let result = service.set_parameter(request).await;
@@ -311,6 +317,7 @@ async fn test_out_of_range_value() -> Result<()> {
                 width: 640,
                 height: 480,
             },
+            simulated: false,
         })
         .await?;
 
@@ -322,6 +329,7 @@ async fn test_out_of_range_value() -> Result<()> {
         device_id: "mock_camera".to_string(),
         parameter_name: "exposure_s".to_string(),
         value: "100.0".to_string(), // Too large
+        typed_value: None,
     });
 
     let result = service.set_parameter(request).await;
@@ -342,6 +350,7 @@ async fn test_type_mismatch() -> Result<()> {
                 width: 640,
                 height: 480,
             },
+            simulated: false,
         })
         .await?;
 
@@ -353,6 +362,7 @@ async fn test_type_mismatch() -> Result<()> {
         device_id: "mock_camera".to_string(),
         parameter_name: "exposure_s".to_string(),
         value: "\"not_a_number\"".to_string(),
+        typed_value: None,
     });
 
     let result = service.set_parameter(request).await;
@@ -373,6 +383,7 @@ async fn test_device_not_found() -> Result<()> {
         device_id: "nonexistent_device".to_string(),
         parameter_name: "some_param".to_string(),
         value: "123".to_string(),
+        typed_value: None,
     });
 
     let result = service.set_parameter(request).await;
@@ -398,6 +409,7 @@ async fn test_concurrent_parameter_access_no_deadlock() -> Result<()> {
                 width: 640,
                 height: 480,
             },
+            simulated: false,
         })
         .await?;
 
@@ -429,6 +441,7 @@ async fn test_concurrent_parameter_access_no_deadlock() -> Result<()> {
                 device_id: "mock_camera".to_string(),
                 parameter_name: "exposure_s".to_string(),
                 value: format!("{}", exposure_value),
+                typed_value: None,
             });
 
             // This acquires: Registry RwLock → Parameter Lock → Driver Mutex (via callback)
@@ -478,6 +491,7 @@ async fn test_multiple_devices_parameter_isolation() -> Result<()> {
                 width: 640,
                 height: 480,
             },
+            simulated: false,
         })
         .await?;
     registry
@@ -488,6 +502,7 @@ async fn test_multiple_devices_parameter_isolation() -> Result<()> {
                 width: 640,
                 height: 480,
             },
+            simulated: false,
         })
         .await?;
 
@@ -499,6 +514,7 @@ async fn test_multiple_devices_parameter_isolation() -> Result<()> {
         device_id: "camera1".to_string(),
         parameter_name: "exposure_s".to_string(),
         value: "0.1".to_string(),
+        typed_value: None,
     });
     service.set_parameter(request1).await?;
 
@@ -506,6 +522,7 @@ async fn test_multiple_devices_parameter_isolation() -> Result<()> {
         device_id: "camera2".to_string(),
         parameter_name: "exposure_s".to_string(),
         value: "0.5".to_string(),
+        typed_value: None,
     });
     service.set_parameter(request2).await?;
 
@@ -543,6 +560,7 @@ async fn test_filtered_parameter_notifications() -> Result<()> {
                 width: 640,
                 height: 480,
             },
+            simulated: false,
         })
         .await?;
     registry
@@ -553,6 +571,7 @@ async fn test_filtered_parameter_notifications() -> Result<()> {
                 width: 640,
                 height: 480,
             },
+            simulated: false,
         })
         .await?;
 
@@ -574,6 +593,7 @@ async fn test_filtered_parameter_notifications() -> Result<()> {
         device_id: "camera2".to_string(),
         parameter_name: "exposure_s".to_string(),
         value: "0.2".to_string(),
+        typed_value: None,
     });
     service.set_parameter(request).await?;
 
@@ -582,6 +602,7 @@ async fn test_filtered_parameter_notifications() -> Result<()> {
         device_id: "camera1".to_string(),
         parameter_name: "exposure_s".to_string(),
         value: "0.3".to_string(),
+        typed_value: None,
     });
     service.set_parameter(request).await?;
 
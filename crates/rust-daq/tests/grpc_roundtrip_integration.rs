@@ -54,6 +54,7 @@ async fn create_test_registry() -> DeviceRegistry {
             driver: DriverType::MockStage {
                 initial_position: 0.0,
             },
+            simulated: false,
         })
         .await
         .expect("Failed to register mock stage");
@@ -67,6 +68,7 @@ async fn create_test_registry() -> DeviceRegistry {
                 width: 640,
                 height: 480,
             },
+            simulated: false,
         })
         .await
         .expect("Failed to register mock camera");
@@ -79,6 +81,7 @@ async fn create_test_registry() -> DeviceRegistry {
             driver: DriverType::MockPowerMeter {
                 reading: 1e-3, // 1 mW
             },
+            simulated: false,
         })
         .await
         .expect("Failed to register mock power meter");
@@ -98,6 +101,7 @@ async fn test_list_devices() {
 
     let request = Request::new(ListDevicesRequest {
         capability_filter: None,
+        ..Default::default()
     });
 
     let response = timeout(Duration::from_secs(5), service.list_devices(request))
@@ -123,6 +127,7 @@ async fn test_list_devices_filter_movable() {
 
     let request = Request::new(ListDevicesRequest {
         capability_filter: Some("movable".to_string()),
+        ..Default::default()
     });
 
     let response = timeout(Duration::from_secs(5), service.list_devices(request))
@@ -143,6 +148,7 @@ async fn test_list_devices_filter_readable() {
 
     let request = Request::new(ListDevicesRequest {
         capability_filter: Some("readable".to_string()),
+        ..Default::default()
     });
 
     let response = timeout(Duration::from_secs(5), service.list_devices(request))
@@ -193,6 +199,7 @@ async fn test_read_power_meter() {
 
     let request = Request::new(ReadValueRequest {
         device_id: "test_power".to_string(),
+        mode: None,
     });
 
     let response = timeout(Duration::from_secs(5), service.read_value(request))
@@ -246,6 +253,7 @@ async fn test_read_nonexistent_device() {
 
     let request = Request::new(ReadValueRequest {
         device_id: "nonexistent_device".to_string(),
+        mode: None,
     });
 
     let result = timeout(Duration::from_secs(5), service.read_value(request))
@@ -309,6 +317,7 @@ async fn test_concurrent_list_devices() {
             tokio::spawn(async move {
                 let request = Request::new(ListDevicesRequest {
                     capability_filter: None,
+                    ..Default::default()
                 });
                 service.list_devices(request).await
             })
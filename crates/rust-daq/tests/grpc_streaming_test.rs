@@ -9,7 +9,7 @@
 
 mod streaming_tests {
     use chrono::Utc;
-    use common::core::Measurement;
+    use common::core::{DataQuality, Measurement};
     use experiment::RunEngine;
     use hardware::registry::DeviceRegistry;
     use protocol::daq::MeasurementRequest;
@@ -38,6 +38,7 @@ mod streaming_tests {
         let request = Request::new(MeasurementRequest {
             channels: vec![],
             max_rate_hz: 0,
+            decimation: None,
         });
 
         let response = server.stream_measurements(request).await.unwrap();
@@ -51,6 +52,7 @@ mod streaming_tests {
                     value: i as f64 * 10.0,
                     unit: "V".to_string(),
                     timestamp: Utc::now(),
+                    quality: DataQuality::Good,
                 });
                 tokio::time::sleep(std::time::Duration::from_millis(10)).await;
             }
@@ -82,6 +84,7 @@ mod streaming_tests {
         let request = Request::new(MeasurementRequest {
             channels: vec!["temperature".to_string()],
             max_rate_hz: 0,
+            decimation: None,
         });
 
         let response = server.stream_measurements(request).await.unwrap();
@@ -96,6 +99,7 @@ mod streaming_tests {
                     value: i as f64,
                     unit: "V".to_string(),
                     timestamp: Utc::now(),
+                    quality: DataQuality::Good,
                 });
                 tokio::time::sleep(std::time::Duration::from_millis(5)).await;
             }
@@ -128,10 +132,12 @@ mod streaming_tests {
         let request1 = Request::new(MeasurementRequest {
             channels: vec![],
             max_rate_hz: 0,
+            decimation: None,
         });
         let request2 = Request::new(MeasurementRequest {
             channels: vec![],
             max_rate_hz: 0,
+            decimation: None,
         });
 
         let response1 = server.stream_measurements(request1).await.unwrap();
@@ -148,6 +154,7 @@ mod streaming_tests {
                     value: i as f64 * 100.0,
                     unit: "V".to_string(),
                     timestamp: Utc::now(),
+                    quality: DataQuality::Good,
                 });
                 tokio::time::sleep(std::time::Duration::from_millis(10)).await;
             }
@@ -28,9 +28,8 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use experiment::plans::{Count, GridScan, LineScan, Plan};
-use experiment::{Document, EngineState, RunEngine};
+use experiment::{Document, DocumentReceiver, EngineState, RunEngine};
 use rust_daq::hardware::registry::{DeviceConfig, DeviceRegistry, DriverType};
-use tokio::sync::broadcast;
 use tokio::time::timeout;
 
 // =============================================================================
@@ -49,6 +48,7 @@ async fn create_multi_device_registry() -> DeviceRegistry {
             driver: DriverType::MockStage {
                 initial_position: 0.0,
             },
+            simulated: false,
         })
         .await
         .expect("Failed to register stage_x");
@@ -61,6 +61,7 @@ async fn create_multi_device_registry() -> DeviceRegistry {
             driver: DriverType::MockStage {
                 initial_position: 0.0,
             },
+            simulated: false,
         })
         .await
         .expect("Failed to register stage_y");
@@ -74,6 +75,7 @@ async fn create_multi_device_registry() -> DeviceRegistry {
                 width: 64,
                 height: 64,
             },
+            simulated: false,
         })
         .await
         .expect("Failed to register camera");
@@ -84,6 +86,7 @@ async fn create_multi_device_registry() -> DeviceRegistry {
             id: "power_meter".into(),
             name: "Test Power Meter".into(),
             driver: DriverType::MockPowerMeter { reading: 1e-3 },
+            simulated: false,
         })
         .await
         .expect("Failed to register power_meter");
@@ -92,10 +95,7 @@ async fn create_multi_device_registry() -> DeviceRegistry {
 }
 
 /// Collect all documents from a subscription until Stop
-async fn collect_documents(
-    mut rx: broadcast::Receiver<Document>,
-    timeout_duration: Duration,
-) -> Vec<Document> {
+async fn collect_documents(mut rx: DocumentReceiver, timeout_duration: Duration) -> Vec<Document> {
     let mut docs = Vec::new();
     let start = tokio::time::Instant::now();
 
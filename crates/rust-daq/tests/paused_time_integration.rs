@@ -0,0 +1,96 @@
+//! RunEngine timing on virtual time
+//!
+//! These tests drive the RunEngine with [`MockTime`] instead of real sleeps,
+//! so their timing assertions are exact.
+
+#![cfg(not(target_arch = "wasm32"))]
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic, missing_docs)]
+
+mod common;
+
+use common::MockTime;
+use experiment::plans::Count;
+use experiment::{Document, DocumentReceiver, EngineState, RunEngine};
+use rust_daq::hardware::registry::DeviceRegistry;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Event documents received since the last call, and whether the run stopped
+fn drain_events(rx: &mut DocumentReceiver) -> (usize, bool) {
+    let mut events = 0;
+    let mut stopped = false;
+    while let Some(sequenced) = rx.try_recv().unwrap() {
+        match sequenced.doc {
+            Document::Event(_) => events += 1,
+            Document::Stop(_) => stopped = true,
+            _ => {}
+        }
+    }
+    (events, stopped)
+}
+
+fn engine() -> Arc<RunEngine> {
+    Arc::new(RunEngine::new(Arc::new(DeviceRegistry::new())))
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_count_delay_follows_virtual_clock() {
+    let time = MockTime::paused();
+    let engine = engine();
+    let mut rx = engine.subscribe();
+    engine.queue(Box::new(Count::new(3).with_delay(1.0))).await;
+
+    let runner = engine.clone();
+    let run = tokio::spawn(async move { runner.start().await });
+
+    // First point right away, the next ones one second apart
+    time.settle().await;
+    assert_eq!(drain_events(&mut rx), (1, false));
+    time.advance(Duration::from_millis(999)).await;
+    assert_eq!(drain_events(&mut rx), (0, false));
+    time.advance(Duration::from_millis(1)).await;
+    assert_eq!(drain_events(&mut rx), (1, false));
+    time.advance(Duration::from_secs(1)).await;
+    assert_eq!(drain_events(&mut rx), (1, true));
+
+    run.await.unwrap().unwrap();
+    assert_eq!(time.elapsed(), Duration::from_secs(2));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_pause_holds_run_on_virtual_clock() {
+    let time = MockTime::paused();
+    let engine = engine();
+    let mut rx = engine.subscribe();
+    engine.queue(Box::new(Count::new(3).with_delay(1.0))).await;
+
+    let runner = engine.clone();
+    let run = tokio::spawn(async move { runner.start().await });
+    time.settle().await;
+    assert_eq!(drain_events(&mut rx), (1, false));
+
+    // The pause takes effect at the checkpoint after the current wait
+    engine.pause().await.unwrap();
+    let control = engine.clone();
+    let took = time
+        .advance_until(Duration::from_secs(2), || {
+            let engine = control.clone();
+            async move { engine.state().await == EngineState::Paused }
+        })
+        .await;
+    assert_eq!(took, Duration::from_secs(1));
+
+    // Nothing is acquired however long the pause lasts
+    time.advance(Duration::from_secs(60)).await;
+    assert_eq!(drain_events(&mut rx), (0, false));
+
+    engine.resume().await.unwrap();
+    let control = engine.clone();
+    time.advance_until(Duration::from_secs(5), || {
+        let engine = control.clone();
+        async move { engine.state().await == EngineState::Idle }
+    })
+    .await;
+    assert_eq!(drain_events(&mut rx), (2, true));
+    run.await.unwrap().unwrap();
+}
@@ -28,6 +28,7 @@ async fn test_pvcam_with_run_engine() -> anyhow::Result<()> {
                 camera_name: "MockCamera".into(), // Mock mode uses any name or specific mock flag?
                                                   // Feature "mock" is enabled in dev-dependencies
             },
+            simulated: false,
         })
         .await?;
 
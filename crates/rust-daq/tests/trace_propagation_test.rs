@@ -158,6 +158,7 @@ async fn test_request_id_propagates_to_spans() {
     // Create request with custom header
     let mut request = Request::new(ListDevicesRequest {
         capability_filter: None,
+        ..Default::default()
     });
     request.metadata_mut().insert(
         REQUEST_ID_HEADER,
@@ -254,6 +255,7 @@ async fn test_hardware_service_creates_spans() {
     let _ = client
         .list_devices(Request::new(ListDevicesRequest {
             capability_filter: None,
+            ..Default::default()
         }))
         .await;
 
@@ -42,7 +42,7 @@ use std::time::Duration;
 use tokio::sync::broadcast;
 
 use crate::run_blocking;
-use common::core::Measurement;
+use common::core::{DataQuality, Measurement};
 use hardware::capabilities::{Camera, Movable, Readable, ShutterControl}; // bd-q2kl.5
 
 // =============================================================================
@@ -344,6 +344,7 @@ pub fn register_hardware(engine: &mut Engine) {
                     value: pos,
                     unit: "mm".to_string(),
                     timestamp: Utc::now(),
+                    quality: DataQuality::Good,
                 };
 
                 // Ignore errors if no receivers (non-critical)
@@ -471,6 +472,7 @@ pub fn register_hardware(engine: &mut Engine) {
                     value: 1.0, // Trigger event indicator
                     unit: "event".to_string(),
                     timestamp: Utc::now(),
+                    quality: DataQuality::Good,
                 };
 
                 // Ignore errors if no receivers (non-critical)
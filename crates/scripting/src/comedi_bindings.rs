@@ -35,7 +35,7 @@ use std::sync::Arc;
 use tokio::sync::broadcast;
 
 use crate::rhai_error;
-use common::core::Measurement; // bd-q2kl.5
+use common::core::{DataQuality, Measurement}; // bd-q2kl.5
 
 // Simpler helper for synchronous operations that may error
 fn map_error<T, E: std::fmt::Display>(
@@ -217,6 +217,7 @@ pub fn register_comedi_hardware(engine: &mut Engine) {
                     value: voltage,
                     unit: "V".to_string(),
                     timestamp: Utc::now(),
+                    quality: DataQuality::Good,
                 };
                 let _ = tx.send(measurement);
             }
@@ -284,6 +285,7 @@ pub fn register_comedi_hardware(engine: &mut Engine) {
                     value: voltage,
                     unit: "V".to_string(),
                     timestamp: Utc::now(),
+                    quality: DataQuality::Good,
                 };
                 let _ = tx.send(measurement);
             }
@@ -362,6 +364,7 @@ pub fn register_comedi_hardware(engine: &mut Engine) {
                     value: if state { 1.0 } else { 0.0 },
                     unit: "".to_string(),
                     timestamp: Utc::now(),
+                    quality: DataQuality::Good,
                 };
                 let _ = tx.send(measurement);
             }
@@ -433,6 +436,7 @@ pub fn register_comedi_hardware(engine: &mut Engine) {
                     value: count as f64,
                     unit: "counts".to_string(),
                     timestamp: Utc::now(),
+                    quality: DataQuality::Good,
                 };
                 let _ = tx.send(measurement);
             }
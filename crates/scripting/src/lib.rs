@@ -2,6 +2,15 @@
 #![allow(rustdoc::invalid_html_tags)]
 #![allow(rustdoc::broken_intra_doc_links)]
 
+// Minimal headless profile: heavy backends must stay out of the dependency graph
+#[cfg(all(
+    feature = "minimal",
+    any(feature = "python", feature = "hdf5_scripting")
+))]
+compile_error!(
+    "the `minimal` profile excludes Python scripting and HDF5 from scripts; drop `minimal` or the conflicting feature"
+);
+
 pub mod bindings;
 pub mod comedi_bindings;
 pub mod engine;
@@ -109,6 +109,8 @@ pub struct ScriptRunConfig {
     pub max_plans: usize,
     /// Whether to continue on plan failure
     pub continue_on_error: bool,
+    /// Metadata attached to every run the script queues, e.g. `operator`
+    pub run_metadata: HashMap<String, String>,
 }
 
 impl Default for ScriptRunConfig {
@@ -117,6 +119,7 @@ impl Default for ScriptRunConfig {
             timeout: Duration::from_secs(3600), // 1 hour default
             max_plans: 1000,
             continue_on_error: false,
+            run_metadata: HashMap::new(),
         }
     }
 }
@@ -372,7 +375,10 @@ impl ScriptPlanRunner {
         let mut doc_rx = self.run_engine.subscribe();
 
         // Queue the plan
-        let run_uid = self.run_engine.queue(plan).await;
+        let run_uid = self
+            .run_engine
+            .queue_with_metadata(plan, self.config.run_metadata.clone())
+            .await;
         debug!("Plan queued with run_uid: {}", run_uid);
 
         // Start execution
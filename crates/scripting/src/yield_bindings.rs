@@ -31,14 +31,19 @@
 //! // Set a parameter
 //! yield_set("laser", "wavelength", 800.0);
 //!
+//! // Ramp a parameter to a new setpoint over 30 s
+//! yield_ramp("laser", "power", 1.5, 30.0);
+//!
 //! // Wait for a duration
 //! yield_wait(0.5);
 //! ```
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use rhai::{Dynamic, Engine, EvalAltResult, Map};
 
+use common::ramp::RampSpec;
 use experiment::plans_imperative::ImperativePlan;
 
 use crate::plan_bindings::PlanHandle;
@@ -61,6 +66,7 @@ pub fn register_yield_bindings(engine: &mut Engine) {
     engine.register_fn("yield_move", yield_move_impl);
     engine.register_fn("yield_set", yield_set_impl);
     engine.register_fn("yield_set_f64", yield_set_f64_impl);
+    engine.register_fn("yield_ramp", yield_ramp_impl);
     engine.register_fn("yield_wait", yield_wait_impl);
     engine.register_fn("yield_trigger", yield_trigger_impl);
     engine.register_fn("yield_read", yield_read_impl);
@@ -190,6 +196,38 @@ fn yield_set_f64_impl(
     yield_set_impl(handle, device_id, parameter, &value.to_string())
 }
 
+/// Implementation of yield_ramp helper
+///
+/// Called from Rhai scripts as: `yield_ramp("laser", "power", 1.5, 30.0);`
+fn yield_ramp_impl(
+    handle: Arc<YieldHandle>,
+    device_id: &str,
+    parameter: &str,
+    target: f64,
+    duration_s: f64,
+) -> Result<YieldResult, Box<EvalAltResult>> {
+    tracing::debug!(
+        target: "daq_scripting::yield",
+        device = %device_id,
+        parameter = %parameter,
+        target_value = %target,
+        duration_s = %duration_s,
+        "yield_ramp"
+    );
+
+    let duration =
+        Duration::try_from_secs_f64(duration_s).map_err(|e| rhai_error("yield_ramp", e))?;
+    let plan = Box::new(ImperativePlan::ramp(
+        device_id,
+        parameter,
+        RampSpec::over(target, duration),
+    ));
+
+    handle
+        .yield_plan(plan)
+        .map_err(|e| rhai_error("yield_ramp", e))
+}
+
 /// Implementation of yield_wait helper
 ///
 /// Called from Rhai scripts as: `yield_wait(0.5);`
@@ -0,0 +1,175 @@
+//! Append-only audit trail of operator actions.
+//!
+//! The audited actions are:
+//!
+//! - `raw_command`: raw instrument console commands, which bypass the
+//!   normal driver abstractions
+//! - `script_upload`: script uploads
+//! - `script_start`, `script_finish` and `script_stop`: script executions
+//!
+//! Each is recorded as one JSON object per line, so they can be reviewed
+//! after the fact. Every record is also emitted as a tracing event with
+//! target `audit`, which makes it visible in remote log streams.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A single audited action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Unix timestamp in nanoseconds
+    pub timestamp_ns: u64,
+    /// What was attempted, e.g. `raw_command`
+    pub action: String,
+    /// Device the action targeted (empty if none)
+    pub device_id: String,
+    /// Authenticated user that made the request (empty if unknown)
+    #[serde(default)]
+    pub user: String,
+    /// Client session that made the request (empty if unknown)
+    pub session_id: String,
+    /// Network peer of the request (empty if unknown)
+    pub peer: String,
+    /// Action details, e.g. the command sent
+    pub detail: String,
+    /// `ok`, `failed` or `blocked`
+    pub outcome: String,
+    /// Response or reason for the outcome
+    pub message: String,
+}
+
+impl AuditEntry {
+    /// Create an entry stamped with the current time
+    pub fn new(action: &str, device_id: &str) -> Self {
+        Self {
+            timestamp_ns: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos() as u64,
+            action: action.to_string(),
+            device_id: device_id.to_string(),
+            user: String::new(),
+            session_id: String::new(),
+            peer: String::new(),
+            detail: String::new(),
+            outcome: String::new(),
+            message: String::new(),
+        }
+    }
+}
+
+/// Default location of the audit log
+pub fn default_audit_log_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rust-daq")
+        .join("audit.jsonl")
+}
+
+/// JSON-lines audit log file
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+    /// Serializes appends so concurrent records never interleave
+    write_lock: Mutex<()>,
+}
+
+impl AuditLog {
+    /// Audit log appending to `path` (created on first record)
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Path of the log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Record an entry.
+    ///
+    /// Failing to write the file is logged but not returned: the tracing
+    /// event is still emitted, and the audited action has already happened.
+    pub fn record(&self, entry: &AuditEntry) {
+        tracing::info!(
+            target: "audit",
+            action = %entry.action,
+            device_id = %entry.device_id,
+            user = %entry.user,
+            session_id = %entry.session_id,
+            peer = %entry.peer,
+            outcome = %entry.outcome,
+            "{}: {}",
+            entry.detail,
+            entry.message
+        );
+        if let Err(e) = self.append(entry) {
+            tracing::error!("Failed to write audit log {}: {:#}", self.path.display(), e);
+        }
+    }
+
+    fn append(&self, entry: &AuditEntry) -> Result<()> {
+        let _guard = self.write_lock.lock().unwrap_or_else(|p| p.into_inner());
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// Read back all entries (oldest first); a missing file means no entries.
+    pub fn read_all(&self) -> Result<Vec<AuditEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read {}", self.path.display()))?;
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line)
+                    .with_context(|| format!("Malformed entry in {}", self.path.display()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_entries_are_appended() {
+        let dir = TempDir::new().unwrap();
+        let log = AuditLog::new(dir.path().join("logs").join("audit.jsonl"));
+        assert!(log.read_all().unwrap().is_empty());
+
+        let mut first = AuditEntry::new("raw_command", "esp300");
+        first.detail = "1TP?".to_string();
+        first.outcome = "ok".to_string();
+        first.message = "12.5".to_string();
+        log.record(&first);
+
+        let mut second = AuditEntry::new("raw_command", "maitai");
+        second.outcome = "blocked".to_string();
+        log.record(&second);
+
+        assert_eq!(log.read_all().unwrap(), vec![first, second]);
+    }
+}
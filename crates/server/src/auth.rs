@@ -0,0 +1,358 @@
+//! Authenticated principals and scopes.
+//!
+//! When `grpc.auth_enabled` is set, every request carries either the shared
+//! `grpc.auth_token` or an HS256 JWT signed with it. The interceptor turns
+//! the credential into a [`Principal`] stored in the request extensions, so
+//! services can attribute actions to a user and check scopes.
+//!
+//! JWTs name the user in `sub` and may restrict access with a
+//! space-separated `scope` claim. Tokens without one have full access, as
+//! before scopes existed. Scopes are either a plain permission such as
+//! `scripts:run` or a permission limited to one resource, such as
+//! `scripts:run:nightly_calibration` (only scripts uploaded under that
+//! name). Unattended jobs such as scheduled scripts should get a token from
+//! [`issue_token`] with only the scopes they need.
+//!
+//! The interceptor checks [`method_scope`] for every call, so a scoped token
+//! can read daemon state but only change what its scopes cover. Script
+//! control and admin sessions are checked per resource by their services.
+
+use anyhow::{Context, Result};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::{Request, Status};
+
+/// Every permission
+pub const SCOPE_ALL: &str = "*";
+/// Upload and validate scripts
+pub const SCOPE_SCRIPTS_UPLOAD: &str = "scripts:upload";
+/// Start uploaded scripts (optionally `scripts:run:<script name>`)
+pub const SCOPE_SCRIPTS_RUN: &str = "scripts:run";
+/// Stop running scripts
+pub const SCOPE_SCRIPTS_STOP: &str = "scripts:stop";
+/// Open admin sessions, which may revoke other sessions
+pub const SCOPE_SESSIONS_ADMIN: &str = "sessions:admin";
+/// Operate instruments: moves, parameters, acquisition, plans and modules
+pub const SCOPE_HARDWARE_CONTROL: &str = "hardware:control";
+/// Send raw commands through the instrument console
+pub const SCOPE_HARDWARE_CONSOLE: &str = "hardware:console";
+/// Change the daemon configuration and storage location
+pub const SCOPE_CONFIG_ADMIN: &str = "config:admin";
+
+/// Services whose state-changing methods need [`SCOPE_HARDWARE_CONTROL`]
+const HARDWARE_SERVICES: &[&str] = &[
+    "HardwareService",
+    "NiDaqService",
+    "ModuleService",
+    "PluginService",
+    "PresetService",
+    "ScanService",
+    "RunEngineService",
+    "StorageService",
+];
+
+/// Method name prefixes of calls that only read state
+const READ_PREFIXES: &[&str] = &[
+    "Get",
+    "List",
+    "Stream",
+    "Subscribe",
+    "Describe",
+    "Read",
+    "Query",
+    "Compare",
+    "Verify",
+    "Download",
+    "DryRun",
+    "Wait",
+];
+
+/// Environment variable clients read their token from
+pub const TOKEN_ENV_VAR: &str = "RUSTDAQ_TOKEN";
+
+/// Claims of tokens accepted by the daemon
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenClaims {
+    pub exp: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    pub sub: Option<String>,
+    /// Space-separated scopes; absent means full access
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+/// Who made a request and what they may do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    pub user: String,
+    pub scopes: Vec<String>,
+}
+
+impl Principal {
+    /// Principal of requests when authentication is disabled
+    pub fn anonymous() -> Self {
+        Self {
+            user: "anonymous".to_string(),
+            scopes: vec![SCOPE_ALL.to_string()],
+        }
+    }
+
+    /// Principal of requests presenting the shared API key
+    pub fn api_key() -> Self {
+        Self {
+            user: "api-key".to_string(),
+            scopes: vec![SCOPE_ALL.to_string()],
+        }
+    }
+
+    /// Principal described by verified token claims
+    pub fn from_claims(claims: &TokenClaims) -> Self {
+        let scopes = match &claims.scope {
+            Some(scope) => scope.split_whitespace().map(str::to_string).collect(),
+            None => vec![SCOPE_ALL.to_string()],
+        };
+        Self {
+            user: claims
+                .sub
+                .clone()
+                .filter(|sub| !sub.trim().is_empty())
+                .unwrap_or_else(|| "token".to_string()),
+            scopes,
+        }
+    }
+
+    /// Whether `scope` is granted for `resource` (empty for no resource)
+    pub fn allows(&self, scope: &str, resource: &str) -> bool {
+        self.scopes.iter().any(|granted| {
+            granted == SCOPE_ALL
+                || granted == scope
+                || (!resource.is_empty()
+                    && granted
+                        .strip_prefix(scope)
+                        .and_then(|rest| rest.strip_prefix(':'))
+                        == Some(resource))
+        })
+    }
+}
+
+/// Principal attached to a request by the auth interceptor
+///
+/// Requests that never passed through it (in-process calls, tests) are
+/// treated as anonymous.
+pub fn principal<T>(request: &Request<T>) -> Principal {
+    request
+        .extensions()
+        .get::<Principal>()
+        .cloned()
+        .unwrap_or_else(Principal::anonymous)
+}
+
+/// Principal of a request, if it was granted `scope` for `resource`
+pub fn require_scope<T>(
+    request: &Request<T>,
+    scope: &str,
+    resource: &str,
+) -> Result<Principal, Status> {
+    let principal = principal(request);
+    if principal.allows(scope, resource) {
+        Ok(principal)
+    } else if resource.is_empty() {
+        Err(Status::permission_denied(format!(
+            "'{}' lacks scope {}",
+            principal.user, scope
+        )))
+    } else {
+        Err(Status::permission_denied(format!(
+            "'{}' lacks scope {} for '{}'",
+            principal.user, scope, resource
+        )))
+    }
+}
+
+/// gRPC path of a request (`/daq.HardwareService/MoveAbsolute`)
+///
+/// Tonic interceptors do not see the URI, so [`record_path`] stores it in
+/// the request extensions before authentication.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrpcPath(pub String);
+
+/// Keep the gRPC path of `request` for the auth interceptor
+pub fn record_path<B>(mut request: http::Request<B>) -> http::Request<B> {
+    let path = GrpcPath(request.uri().path().to_string());
+    request.extensions_mut().insert(path);
+    request
+}
+
+/// Scope needed to call the gRPC method at `path`
+///
+/// `None` means any authenticated principal may call it: reads, and the
+/// methods whose services check scopes themselves.
+pub fn method_scope(path: &str) -> Option<&'static str> {
+    let (service, method) = path.trim_start_matches('/').split_once('/')?;
+    let service = service.rsplit('.').next().unwrap_or(service);
+    match (service, method) {
+        ("InstrumentConsoleService", _) => Some(SCOPE_HARDWARE_CONSOLE),
+        ("ConfigService", "ApplyConfig" | "RestoreConfigSnapshot")
+        | ("StorageService", "ConfigureStorage") => Some(SCOPE_CONFIG_ADMIN),
+        ("LibraryService", "UploadLibraryFile" | "DeleteLibraryFile") => Some(SCOPE_SCRIPTS_UPLOAD),
+        (service, method)
+            if HARDWARE_SERVICES.contains(&service)
+                && !READ_PREFIXES
+                    .iter()
+                    .any(|prefix| method.starts_with(prefix)) =>
+        {
+            Some(SCOPE_HARDWARE_CONTROL)
+        }
+        _ => None,
+    }
+}
+
+/// Sign a token for `user` limited to `scopes`, valid for `ttl`
+///
+/// `secret` is the daemon's `grpc.auth_token`. An empty `scopes` list
+/// issues an unrestricted token.
+pub fn issue_token(secret: &str, user: &str, scopes: &[String], ttl: Duration) -> Result<String> {
+    let exp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .saturating_add(ttl)
+        .as_secs();
+    let claims = TokenClaims {
+        exp: Some(usize::try_from(exp).unwrap_or(usize::MAX)),
+        iss: None,
+        aud: None,
+        sub: Some(user.to_string()),
+        scope: (!scopes.is_empty()).then(|| scopes.join(" ")),
+    };
+    jsonwebtoken::encode(
+        &Header::new(Algorithm::HS256),
+        &claims,
+        &EncodingKey::from_secret(secret.as_bytes()),
+    )
+    .context("Failed to sign token")
+}
+
+/// Client-side interceptor presenting a bearer token on every call
+#[derive(Debug, Clone, Default)]
+pub struct BearerToken {
+    header: Option<MetadataValue<Ascii>>,
+}
+
+impl BearerToken {
+    pub fn new(token: &str) -> Result<Self> {
+        let header = format!("Bearer {}", token.trim())
+            .parse()
+            .context("Token contains characters not allowed in a header")?;
+        Ok(Self {
+            header: Some(header),
+        })
+    }
+
+    /// Token from [`TOKEN_ENV_VAR`], or none if it is unset
+    pub fn from_env() -> Result<Self> {
+        match std::env::var(TOKEN_ENV_VAR) {
+            Ok(token) if !token.trim().is_empty() => Self::new(&token),
+            _ => Ok(Self::default()),
+        }
+    }
+}
+
+impl tonic::service::Interceptor for BearerToken {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(header) = &self.header {
+            request
+                .metadata_mut()
+                .insert("authorization", header.clone());
+        }
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scopes_restrict_by_resource() {
+        let scheduled = Principal {
+            user: "nightly".to_string(),
+            scopes: vec!["scripts:run:calibration".to_string()],
+        };
+        assert!(scheduled.allows(SCOPE_SCRIPTS_RUN, "calibration"));
+        assert!(!scheduled.allows(SCOPE_SCRIPTS_RUN, "calibration_v2"));
+        assert!(!scheduled.allows(SCOPE_SCRIPTS_RUN, ""));
+        assert!(!scheduled.allows(SCOPE_SCRIPTS_UPLOAD, "calibration"));
+        assert!(Principal::api_key().allows(SCOPE_SCRIPTS_UPLOAD, ""));
+
+        let claims = TokenClaims {
+            exp: None,
+            iss: None,
+            aud: None,
+            sub: Some("alice".to_string()),
+            scope: None,
+        };
+        let alice = Principal::from_claims(&claims);
+        assert_eq!(alice.user, "alice");
+        assert!(alice.allows(SCOPE_SCRIPTS_STOP, ""));
+    }
+
+    #[test]
+    fn test_require_scope_reads_request_extension() {
+        let mut request = Request::new(());
+        assert_eq!(principal(&request), Principal::anonymous());
+
+        request.extensions_mut().insert(Principal {
+            user: "bob".to_string(),
+            scopes: vec![SCOPE_SCRIPTS_RUN.to_string()],
+        });
+        assert_eq!(
+            require_scope(&request, SCOPE_SCRIPTS_RUN, "any")
+                .unwrap()
+                .user,
+            "bob"
+        );
+        let denied = require_scope(&request, SCOPE_SCRIPTS_UPLOAD, "").unwrap_err();
+        assert_eq!(denied.code(), tonic::Code::PermissionDenied);
+    }
+
+    #[test]
+    fn test_method_scope_covers_state_changes() {
+        assert_eq!(
+            method_scope("/daq.HardwareService/MoveAbsolute"),
+            Some(SCOPE_HARDWARE_CONTROL)
+        );
+        assert_eq!(
+            method_scope("/daq.ni_daq.NiDaqService/SetAnalogOutput"),
+            Some(SCOPE_HARDWARE_CONTROL)
+        );
+        assert_eq!(
+            method_scope("/daq.InstrumentConsoleService/SendRawCommand"),
+            Some(SCOPE_HARDWARE_CONSOLE)
+        );
+        assert_eq!(
+            method_scope("/daq.ConfigService/ApplyConfig"),
+            Some(SCOPE_CONFIG_ADMIN)
+        );
+        assert_eq!(
+            method_scope("/daq.StorageService/ConfigureStorage"),
+            Some(SCOPE_CONFIG_ADMIN)
+        );
+        assert_eq!(
+            method_scope("/daq.LibraryService/UploadLibraryFile"),
+            Some(SCOPE_SCRIPTS_UPLOAD)
+        );
+
+        assert_eq!(method_scope("/daq.HardwareService/ListDevices"), None);
+        assert_eq!(method_scope("/daq.ConfigService/GetDaemonConfig"), None);
+        assert_eq!(
+            method_scope("/daq.LibraryService/DownloadLibraryFile"),
+            None
+        );
+        assert_eq!(method_scope("/daq.ControlService/StartScript"), None);
+    }
+}
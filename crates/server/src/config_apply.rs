@@ -0,0 +1,788 @@
+//! Differential, transactional configuration apply.
+//!
+//! A desired daemon configuration (devices, modules, storage) is compared
+//! with what the daemon is currently running and only the differences are
+//! applied, one change at a time. If a change fails, every change already
+//! applied is undone in reverse order, so the daemon ends up where it
+//! started. The outcome of every step is returned as an [`ApplyReport`].
+//!
+//! Devices are not changed under a running plan: an apply touching a device
+//! the active run uses is refused up front, and the devices it touches stay
+//! locked against other commands until it (and any rollback) is done.
+//! Moving `storage.output_directory` creates the new directory, which is
+//! why applying needs the `config:admin` scope.
+//!
+//! ```toml
+//! # Sections left out are not touched.
+//! [[devices]]
+//! id = "stage"
+//! name = "Mock Stage"
+//! [devices.driver]
+//! type = "mock_stage"
+//!
+//! [[modules]]
+//! module_id = "laser-power"
+//! type_id = "power_monitor"
+//! instance_name = "Laser Power"
+//! config = { high_threshold = "100" }
+//! assignments = { power_meter = "power_meter" }
+//!
+//! [storage]
+//! output_directory = "./data"
+//! compression = "zstd"
+//! ```
+
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use experiment::RunEngine;
+use experiment::config_snapshot::{ConfigSnapshotSource, DaemonConfigFuture};
+use hardware::registry::{DeviceConfig, DeviceLocks, DeviceRegistry};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::sync::Arc;
+use storage::DocumentWriter;
+use tokio::sync::RwLock;
+
+use crate::grpc::module_service::ModuleServiceImpl;
+use crate::grpc::storage_service::StorageSettings;
+use crate::modules::PersistedModule;
+
+/// Desired (or current) daemon configuration
+///
+/// A section set to `None` is left as it is when applying.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DaemonConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub devices: Option<Vec<DeviceConfig>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modules: Option<Vec<PersistedModule>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage: Option<StorageSettings>,
+}
+
+impl DaemonConfig {
+    /// Parse a configuration document
+    pub fn from_toml(content: &str) -> Result<Self> {
+        toml::from_str(content).context("Failed to parse daemon configuration")
+    }
+
+    /// Render as a configuration document
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string(self).context("Failed to serialize daemon configuration")
+    }
+}
+
+/// Configuration component a change belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Component {
+    Device,
+    Module,
+    Storage,
+}
+
+impl Component {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Component::Device => "device",
+            Component::Module => "module",
+            Component::Storage => "storage",
+        }
+    }
+}
+
+/// A single change needed to move from the current to the desired config
+#[derive(Debug, Clone)]
+pub enum ConfigChange {
+    AddDevice(DeviceConfig),
+    RemoveDevice(DeviceConfig),
+    UpdateDevice {
+        before: DeviceConfig,
+        after: DeviceConfig,
+    },
+    AddModule(PersistedModule),
+    RemoveModule(PersistedModule),
+    UpdateModule {
+        before: PersistedModule,
+        after: PersistedModule,
+    },
+    UpdateStorage {
+        before: StorageSettings,
+        after: StorageSettings,
+    },
+}
+
+impl ConfigChange {
+    pub fn component(&self) -> Component {
+        match self {
+            Self::AddDevice(_) | Self::RemoveDevice(_) | Self::UpdateDevice { .. } => {
+                Component::Device
+            }
+            Self::AddModule(_) | Self::RemoveModule(_) | Self::UpdateModule { .. } => {
+                Component::Module
+            }
+            Self::UpdateStorage { .. } => Component::Storage,
+        }
+    }
+
+    /// ID of the device or module affected ("storage" for storage settings)
+    pub fn target(&self) -> &str {
+        match self {
+            Self::AddDevice(d) | Self::RemoveDevice(d) | Self::UpdateDevice { after: d, .. } => {
+                &d.id
+            }
+            Self::AddModule(m) | Self::RemoveModule(m) | Self::UpdateModule { after: m, .. } => {
+                &m.module_id
+            }
+            Self::UpdateStorage { .. } => "storage",
+        }
+    }
+
+    /// "add", "remove" or "update"
+    pub fn action(&self) -> &'static str {
+        match self {
+            Self::AddDevice(_) | Self::AddModule(_) => "add",
+            Self::RemoveDevice(_) | Self::RemoveModule(_) => "remove",
+            Self::UpdateDevice { .. } | Self::UpdateModule { .. } | Self::UpdateStorage { .. } => {
+                "update"
+            }
+        }
+    }
+
+    /// The change that undoes this one
+    pub fn inverse(&self) -> ConfigChange {
+        match self.clone() {
+            Self::AddDevice(d) => Self::RemoveDevice(d),
+            Self::RemoveDevice(d) => Self::AddDevice(d),
+            Self::UpdateDevice { before, after } => Self::UpdateDevice {
+                before: after,
+                after: before,
+            },
+            Self::AddModule(m) => Self::RemoveModule(m),
+            Self::RemoveModule(m) => Self::AddModule(m),
+            Self::UpdateModule { before, after } => Self::UpdateModule {
+                before: after,
+                after: before,
+            },
+            Self::UpdateStorage { before, after } => Self::UpdateStorage {
+                before: after,
+                after: before,
+            },
+        }
+    }
+}
+
+/// Compute the changes that turn `current` into `desired`.
+///
+/// Changes are ordered so dependencies hold at every step: modules are
+/// removed before the devices they use, and devices are added before the
+/// modules bound to them.
+pub fn diff(current: &DaemonConfig, desired: &DaemonConfig) -> Vec<ConfigChange> {
+    let mut module_removals = Vec::new();
+    let mut module_changes = Vec::new();
+    if let Some(desired_modules) = &desired.modules {
+        let current_modules = keyed(current.modules.as_deref(), |m| &m.module_id);
+        let desired_modules = keyed(Some(desired_modules.as_slice()), |m| &m.module_id);
+        for (id, before) in &current_modules {
+            match desired_modules.get(id) {
+                None => module_removals.push(ConfigChange::RemoveModule((*before).clone())),
+                Some(after) if module_differs(before, after) => {
+                    module_changes.push(ConfigChange::UpdateModule {
+                        before: (*before).clone(),
+                        after: (*after).clone(),
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+        for (id, after) in &desired_modules {
+            if !current_modules.contains_key(id) {
+                module_changes.push(ConfigChange::AddModule((*after).clone()));
+            }
+        }
+    }
+
+    let mut device_changes = Vec::new();
+    if let Some(desired_devices) = &desired.devices {
+        let current_devices = keyed(current.devices.as_deref(), |d| &d.id);
+        let desired_devices = keyed(Some(desired_devices.as_slice()), |d| &d.id);
+        for (id, before) in &current_devices {
+            if !desired_devices.contains_key(id) {
+                device_changes.push(ConfigChange::RemoveDevice((*before).clone()));
+            }
+        }
+        for (id, after) in &desired_devices {
+            match current_devices.get(id) {
+                None => device_changes.push(ConfigChange::AddDevice((*after).clone())),
+                Some(before) if device_differs(before, after) => {
+                    device_changes.push(ConfigChange::UpdateDevice {
+                        before: (*before).clone(),
+                        after: (*after).clone(),
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    let mut changes = module_removals;
+    changes.extend(device_changes);
+    changes.extend(module_changes);
+
+    if let (Some(before), Some(after)) = (&current.storage, &desired.storage)
+        && before != after
+    {
+        changes.push(ConfigChange::UpdateStorage {
+            before: before.clone(),
+            after: after.clone(),
+        });
+    }
+
+    changes
+}
+
+fn keyed<T>(items: Option<&[T]>, key: impl Fn(&T) -> &String) -> BTreeMap<String, &T> {
+    items
+        .unwrap_or_default()
+        .iter()
+        .map(|item| (key(item).clone(), item))
+        .collect()
+}
+
+fn device_differs(before: &DeviceConfig, after: &DeviceConfig) -> bool {
+    // DriverType has no PartialEq (cfg-gated variants); compare serialized forms
+    before.name != after.name
+        || serde_json::to_value(&before.driver).ok() != serde_json::to_value(&after.driver).ok()
+}
+
+fn module_differs(before: &PersistedModule, after: &PersistedModule) -> bool {
+    // The running config includes defaults; only keys given in the desired
+    // config are compared.
+    before.type_id != after.type_id
+        || before.instance_name != after.instance_name
+        || before.assignments != after.assignments
+        || after
+            .config
+            .iter()
+            .any(|(key, value)| before.config.get(key) != Some(value))
+}
+
+/// The running system a configuration is applied to
+///
+/// Implemented by [`DaemonTarget`]. Each change must either take full effect
+/// or fail without side effects.
+#[async_trait]
+pub trait ConfigTarget: Send + Sync {
+    /// Configuration currently in effect (every section populated)
+    async fn current(&self) -> Result<DaemonConfig>;
+
+    /// Apply one change
+    async fn apply_change(&self, change: &ConfigChange) -> Result<()>;
+
+    /// Claim what `changes` touch until the returned claim is dropped
+    ///
+    /// Held while the changes and any rollback are applied. Fails with
+    /// [`TargetBusy`] if something they touch is in use.
+    async fn claim(&self, _changes: &[ConfigChange]) -> Result<ChangeClaim> {
+        Ok(ChangeClaim::default())
+    }
+}
+
+/// What a [`ConfigTarget`] holds for the duration of an apply
+#[derive(Default)]
+pub struct ChangeClaim {
+    _devices: Option<DeviceLocks>,
+}
+
+/// An apply was refused because it touches something in use; nothing was
+/// changed
+#[derive(Debug)]
+pub struct TargetBusy(pub String);
+
+impl fmt::Display for TargetBusy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for TargetBusy {}
+
+/// Outcome of one change
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepStatus {
+    /// Planned but not executed (dry run)
+    Planned,
+    Applied,
+    Failed,
+    /// Not executed because an earlier change failed
+    Skipped,
+    /// Applied, then undone after a later failure
+    RolledBack,
+    /// Applied, but undoing it failed; needs manual attention
+    RollbackFailed,
+}
+
+/// Report entry for one change
+#[derive(Debug, Clone)]
+pub struct ApplyStep {
+    pub component: Component,
+    pub target: String,
+    pub action: &'static str,
+    pub status: StepStatus,
+    /// Error text for failed steps
+    pub message: String,
+}
+
+/// Result of applying a configuration
+#[derive(Debug, Clone, Default)]
+pub struct ApplyReport {
+    pub dry_run: bool,
+    pub steps: Vec<ApplyStep>,
+    /// Earlier steps were undone after a failure
+    pub rolled_back: bool,
+    /// Why the apply failed
+    pub error: Option<String>,
+}
+
+impl ApplyReport {
+    /// Whether the desired configuration is now in effect (or would be, for a dry run)
+    pub fn success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Diff `desired` against the target and apply it, rolling back on failure.
+///
+/// With `dry_run` the changes are computed and reported as
+/// [`StepStatus::Planned`] without touching the target.
+pub async fn apply_config(
+    target: &dyn ConfigTarget,
+    desired: &DaemonConfig,
+    dry_run: bool,
+) -> Result<ApplyReport> {
+    let current = target.current().await?;
+    let changes = diff(&current, desired);
+
+    let mut report = ApplyReport {
+        dry_run,
+        steps: changes
+            .iter()
+            .map(|change| ApplyStep {
+                component: change.component(),
+                target: change.target().to_string(),
+                action: change.action(),
+                status: if dry_run {
+                    StepStatus::Planned
+                } else {
+                    StepStatus::Skipped
+                },
+                message: String::new(),
+            })
+            .collect(),
+        ..Default::default()
+    };
+    if dry_run {
+        return Ok(report);
+    }
+
+    let _claim = target.claim(&changes).await?;
+    let mut failed_at = None;
+    for (index, change) in changes.iter().enumerate() {
+        match target.apply_change(change).await {
+            Ok(()) => report.steps[index].status = StepStatus::Applied,
+            Err(e) => {
+                let step = &mut report.steps[index];
+                step.status = StepStatus::Failed;
+                step.message = format!("{:#}", e);
+                report.error = Some(format!(
+                    "Failed to {} {} '{}': {:#}",
+                    step.action,
+                    step.component.as_str(),
+                    step.target,
+                    e
+                ));
+                failed_at = Some(index);
+                break;
+            }
+        }
+    }
+
+    let Some(failed_at) = failed_at else {
+        return Ok(report);
+    };
+
+    tracing::warn!(
+        "Config apply failed at step {}; rolling back {} change(s)",
+        failed_at + 1,
+        failed_at
+    );
+    for index in (0..failed_at).rev() {
+        let step = &mut report.steps[index];
+        match target.apply_change(&changes[index].inverse()).await {
+            Ok(()) => step.status = StepStatus::RolledBack,
+            Err(e) => {
+                tracing::error!(
+                    "Rollback of {} {} '{}' failed: {:#}",
+                    step.action,
+                    step.component.as_str(),
+                    step.target,
+                    e
+                );
+                step.status = StepStatus::RollbackFailed;
+                step.message = format!("Rollback failed: {:#}", e);
+            }
+        }
+    }
+    report.rolled_back = true;
+    Ok(report)
+}
+
+/// [`ConfigTarget`] backed by the daemon's device registry, module
+/// registry and storage settings
+pub struct DaemonTarget {
+    devices: Arc<DeviceRegistry>,
+    modules: ModuleServiceImpl,
+    storage: Arc<RwLock<StorageSettings>>,
+    /// Run file writer following the chunking settings
+    document_writer: Option<Arc<DocumentWriter>>,
+    /// Engine whose active run's devices can't be changed
+    run_engine: Option<Arc<RunEngine>>,
+}
+
+impl DaemonTarget {
+    pub fn new(
+        devices: Arc<DeviceRegistry>,
+        modules: ModuleServiceImpl,
+        storage: Arc<RwLock<StorageSettings>>,
+    ) -> Self {
+        Self {
+            devices,
+            modules,
+            storage,
+            document_writer: None,
+            run_engine: None,
+        }
+    }
+
+    /// Apply storage chunking changes to the run files written by `writer`
+    pub fn with_document_writer(mut self, writer: Arc<DocumentWriter>) -> Self {
+        self.document_writer = Some(writer);
+        self
+    }
+
+    /// Refuse device changes that touch the devices of `run_engine`'s active run
+    pub fn with_run_engine(mut self, run_engine: Arc<RunEngine>) -> Self {
+        self.run_engine = Some(run_engine);
+        self
+    }
+
+    async fn add_device(&self, config: &DeviceConfig) -> Result<()> {
+        self.devices
+            .register(config.clone())
+            .await
+            .map_err(|e| anyhow!("{}", e))
+    }
+
+    async fn remove_device(&self, id: &str) -> Result<()> {
+        match self.devices.unregister(id).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(anyhow!("Device '{}' is not registered", id)),
+            Err(e) => Err(anyhow!("{}", e)),
+        }
+    }
+}
+
+/// Each run's config snapshot records the configuration in effect at its start
+impl ConfigSnapshotSource for DaemonTarget {
+    fn daemon_config(&self) -> DaemonConfigFuture<'_> {
+        Box::pin(async move { self.current().await?.to_toml() })
+    }
+}
+
+#[async_trait]
+impl ConfigTarget for DaemonTarget {
+    async fn current(&self) -> Result<DaemonConfig> {
+        Ok(DaemonConfig {
+            devices: Some(self.devices.device_configs()),
+            modules: Some(self.modules.registry().read().await.snapshot()),
+            storage: Some(self.storage.read().await.clone()),
+        })
+    }
+
+    async fn claim(&self, changes: &[ConfigChange]) -> Result<ChangeClaim> {
+        let device_ids: BTreeSet<&str> = changes
+            .iter()
+            .filter(|change| change.component() == Component::Device)
+            .map(ConfigChange::target)
+            .collect();
+        if device_ids.is_empty() {
+            return Ok(ChangeClaim::default());
+        }
+
+        // Lock before checking, so a run starting now waits for the apply
+        let locks = self.devices.lock_devices(&device_ids).await;
+        if let Some(run_engine) = &self.run_engine {
+            let run_devices = run_engine.current_run_devices().await;
+            let in_use: Vec<&str> = device_ids
+                .iter()
+                .copied()
+                .filter(|id| run_devices.iter().any(|device| device == id))
+                .collect();
+            if !in_use.is_empty() {
+                let run_uid = run_engine.current_run_uid().await.unwrap_or_default();
+                return Err(TargetBusy(format!(
+                    "Device {} is in use by run {}",
+                    in_use.join(", "),
+                    run_uid
+                ))
+                .into());
+            }
+        }
+        Ok(ChangeClaim {
+            _devices: Some(locks),
+        })
+    }
+
+    async fn apply_change(&self, change: &ConfigChange) -> Result<()> {
+        match change {
+            ConfigChange::AddDevice(config) => self.add_device(config).await,
+            ConfigChange::RemoveDevice(config) => self.remove_device(&config.id).await,
+            ConfigChange::UpdateDevice { before, after } => {
+                self.remove_device(&before.id).await?;
+                if let Err(e) = self.add_device(after).await {
+                    // Put the old device back so the step has no side effects
+                    if let Err(restore) = self.add_device(before).await {
+                        tracing::error!("Failed to restore device '{}': {}", before.id, restore);
+                    }
+                    return Err(e);
+                }
+                Ok(())
+            }
+            ConfigChange::AddModule(module) => {
+                let registry = self.modules.registry();
+                let mut registry = registry.write().await;
+                registry.instantiate(module)?;
+                self.modules.persist(&registry);
+                Ok(())
+            }
+            ConfigChange::RemoveModule(module) => {
+                let registry = self.modules.registry();
+                let mut registry = registry.write().await;
+                registry.delete_module(&module.module_id, false).await?;
+                self.modules.persist(&registry);
+                Ok(())
+            }
+            ConfigChange::UpdateModule { before, after } => {
+                let registry = self.modules.registry();
+                let mut registry = registry.write().await;
+                registry.delete_module(&before.module_id, false).await?;
+                if let Err(e) = registry.instantiate(after) {
+                    if let Err(restore) = registry.instantiate(before) {
+                        tracing::error!(
+                            "Failed to restore module '{}': {}",
+                            before.module_id,
+                            restore
+                        );
+                    }
+                    self.modules.persist(&registry);
+                    return Err(e);
+                }
+                self.modules.persist(&registry);
+                Ok(())
+            }
+            ConfigChange::UpdateStorage { after, .. } => {
+                if !after.output_directory.exists() {
+                    tokio::fs::create_dir_all(&after.output_directory)
+                        .await
+                        .with_context(|| {
+                            format!(
+                                "Failed to create output directory {}",
+                                after.output_directory.display()
+                            )
+                        })?;
+                }
+                *self.storage.write().await = after.clone();
+                if let Some(writer) = &self.document_writer {
+                    writer.set_chunking(after.chunking());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeSet, HashMap};
+    use std::sync::Mutex;
+
+    fn module(id: &str, threshold: &str) -> PersistedModule {
+        PersistedModule {
+            module_id: id.to_string(),
+            type_id: "power_monitor".to_string(),
+            instance_name: id.to_string(),
+            config: HashMap::from([("high_threshold".to_string(), threshold.to_string())]),
+            assignments: HashMap::new(),
+        }
+    }
+
+    /// In-memory target whose modules can be made to fail on apply or be
+    /// in use
+    #[derive(Default)]
+    struct FakeTarget {
+        modules: Mutex<BTreeMap<String, PersistedModule>>,
+        storage: Mutex<StorageSettings>,
+        failing: BTreeSet<String>,
+        busy: BTreeSet<String>,
+    }
+
+    #[async_trait]
+    impl ConfigTarget for FakeTarget {
+        async fn current(&self) -> Result<DaemonConfig> {
+            Ok(DaemonConfig {
+                devices: Some(Vec::new()),
+                modules: Some(self.modules.lock().unwrap().values().cloned().collect()),
+                storage: Some(self.storage.lock().unwrap().clone()),
+            })
+        }
+
+        async fn apply_change(&self, change: &ConfigChange) -> Result<()> {
+            if self.failing.contains(change.target()) {
+                return Err(anyhow!("simulated failure"));
+            }
+            let mut modules = self.modules.lock().unwrap();
+            match change {
+                ConfigChange::AddModule(m) | ConfigChange::UpdateModule { after: m, .. } => {
+                    modules.insert(m.module_id.clone(), m.clone());
+                }
+                ConfigChange::RemoveModule(m) => {
+                    modules.remove(&m.module_id);
+                }
+                ConfigChange::UpdateStorage { after, .. } => {
+                    *self.storage.lock().unwrap() = after.clone();
+                }
+                _ => unreachable!("no devices in these tests"),
+            }
+            Ok(())
+        }
+
+        async fn claim(&self, changes: &[ConfigChange]) -> Result<ChangeClaim> {
+            match changes.iter().find(|c| self.busy.contains(c.target())) {
+                Some(change) => Err(TargetBusy(format!("{} is in use", change.target())).into()),
+                None => Ok(ChangeClaim::default()),
+            }
+        }
+    }
+
+    #[test]
+    fn diff_orders_changes_and_ignores_missing_sections() {
+        let current = DaemonConfig {
+            devices: Some(Vec::new()),
+            modules: Some(vec![module("a", "1"), module("b", "1")]),
+            storage: Some(StorageSettings::default()),
+        };
+        let desired = DaemonConfig {
+            modules: Some(vec![module("b", "2"), module("c", "1")]),
+            ..Default::default()
+        };
+
+        let changes = diff(&current, &desired);
+        let summary: Vec<_> = changes.iter().map(|c| (c.action(), c.target())).collect();
+        assert_eq!(
+            summary,
+            vec![("remove", "a"), ("update", "b"), ("add", "c")]
+        );
+
+        // Re-applying the same configuration is a no-op
+        assert!(diff(&current, &current).is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_step_rolls_back_earlier_changes() {
+        let target = FakeTarget {
+            failing: BTreeSet::from(["c".to_string()]),
+            ..Default::default()
+        };
+        target
+            .modules
+            .lock()
+            .unwrap()
+            .insert("a".to_string(), module("a", "1"));
+
+        let desired = DaemonConfig {
+            modules: Some(vec![module("a", "5"), module("c", "1")]),
+            storage: Some(StorageSettings {
+                compression: "zstd".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let report = apply_config(&target, &desired, false).await.unwrap();
+        assert!(!report.success());
+        assert!(report.rolled_back);
+        let statuses: Vec<_> = report.steps.iter().map(|s| s.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                StepStatus::RolledBack,
+                StepStatus::Failed,
+                StepStatus::Skipped
+            ]
+        );
+
+        // Everything is back where it started
+        assert_eq!(target.modules.lock().unwrap()["a"], module("a", "1"));
+        assert_eq!(target.storage.lock().unwrap().compression, "gzip");
+    }
+
+    #[tokio::test]
+    async fn busy_target_refuses_whole_apply() {
+        let target = FakeTarget {
+            busy: BTreeSet::from(["c".to_string()]),
+            ..Default::default()
+        };
+        let desired = DaemonConfig {
+            modules: Some(vec![module("a", "1"), module("c", "1")]),
+            ..Default::default()
+        };
+
+        let err = apply_config(&target, &desired, false).await.unwrap_err();
+        assert!(err.is::<TargetBusy>());
+        assert!(target.modules.lock().unwrap().is_empty());
+
+        // Planning is still possible
+        let report = apply_config(&target, &desired, true).await.unwrap();
+        assert_eq!(report.steps.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn dry_run_leaves_target_untouched() {
+        let target = FakeTarget::default();
+        let desired = DaemonConfig {
+            modules: Some(vec![module("a", "1")]),
+            ..Default::default()
+        };
+
+        let report = apply_config(&target, &desired, true).await.unwrap();
+        assert!(report.success());
+        assert_eq!(report.steps.len(), 1);
+        assert_eq!(report.steps[0].status, StepStatus::Planned);
+        assert!(target.modules.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn config_document_round_trip() {
+        let config = DaemonConfig {
+            modules: Some(vec![module("a", "1")]),
+            storage: Some(StorageSettings::default()),
+            ..Default::default()
+        };
+        let parsed = DaemonConfig::from_toml(&config.to_toml().unwrap()).unwrap();
+        assert!(parsed.devices.is_none());
+        assert_eq!(parsed.modules, config.modules);
+        assert_eq!(parsed.storage, config.storage);
+    }
+}
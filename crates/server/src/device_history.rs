@@ -0,0 +1,262 @@
+//! Event-sourced history of device state changes.
+//!
+//! Every parameter change broadcast by the hardware service (driver updates,
+//! user sets, script sets) and every motion setpoint is appended to an
+//! in-memory log. The log answers two kinds of question:
+//!
+//! - what changed on a device between two times ([`DeviceHistory::query`]);
+//! - what the device looked like at a given time ([`DeviceHistory::state_at`]),
+//!   e.g. the waveplate angle when a frame was taken.
+//!
+//! Events older than the retention window are dropped, as are the oldest
+//! events once [`MAX_HISTORY_EVENTS`] is reached.
+
+use crate::grpc::proto::ParameterChange;
+use common::experiment::document::now_ns;
+use common::memory::{MemoryAccount, MemoryLedger, MemorySubsystem};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// Default time events are kept for
+pub const DEFAULT_HISTORY_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Upper bound on stored events, whatever the retention window
+pub const MAX_HISTORY_EVENTS: usize = 200_000;
+
+/// Filter for [`DeviceHistory::query`]
+#[derive(Debug, Clone, Default)]
+pub struct HistoryQuery {
+    /// Device to return events for (`None` = all devices)
+    pub device_id: Option<String>,
+    /// Parameter names to return (empty = all)
+    pub names: Vec<String>,
+    /// Earliest timestamp, inclusive
+    pub start_ns: u64,
+    /// Latest timestamp, inclusive (`None` = now)
+    pub end_ns: Option<u64>,
+    /// Maximum number of events, keeping the most recent (0 = no limit)
+    pub limit: usize,
+}
+
+struct HistoryState {
+    retention: Duration,
+    /// Events ordered by timestamp
+    events: VecDeque<ParameterChange>,
+    /// Timestamp before which events have been dropped (0 = nothing dropped)
+    dropped_before_ns: u64,
+    /// Approximate heap footprint of `events`
+    memory: MemoryAccount,
+}
+
+/// In-memory, time-ordered log of device state changes
+pub struct DeviceHistory {
+    state: Mutex<HistoryState>,
+}
+
+impl Default for DeviceHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_RETENTION)
+    }
+}
+
+impl DeviceHistory {
+    /// Empty history keeping events for `retention`
+    pub fn new(retention: Duration) -> Self {
+        Self {
+            state: Mutex::new(HistoryState {
+                retention,
+                events: VecDeque::new(),
+                dropped_before_ns: 0,
+                memory: MemoryLedger::global().account("device_history", MemorySubsystem::History),
+            }),
+        }
+    }
+
+    /// Change the retention window (applies from the next recorded event)
+    pub fn set_retention(&self, retention: Duration) {
+        self.lock().retention = retention;
+    }
+
+    /// Current retention window
+    pub fn retention(&self) -> Duration {
+        self.lock().retention
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HistoryState> {
+        self.state.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Append an event, dropping events that fell out of the retention window
+    pub fn record(&self, event: ParameterChange) {
+        let mut state = self.lock();
+
+        // Events normally arrive in order; a late one is inserted in place
+        let index = state
+            .events
+            .partition_point(|e| e.timestamp_ns <= event.timestamp_ns);
+        state.memory.add(event_bytes(&event));
+        state.events.insert(index, event);
+
+        let cutoff = now_ns().saturating_sub(state.retention.as_nanos() as u64);
+        while state.events.len() > MAX_HISTORY_EVENTS
+            || state
+                .events
+                .front()
+                .is_some_and(|e| e.timestamp_ns < cutoff)
+        {
+            if let Some(dropped) = state.events.pop_front() {
+                state.memory.sub(event_bytes(&dropped));
+                state.dropped_before_ns = state.dropped_before_ns.max(dropped.timestamp_ns + 1);
+            }
+        }
+    }
+
+    /// Events matching `query`, oldest first
+    ///
+    /// The flag is true when `limit` cut off older matching events.
+    pub fn query(&self, query: &HistoryQuery) -> (Vec<ParameterChange>, bool) {
+        let end_ns = query.end_ns.unwrap_or(u64::MAX);
+        let state = self.lock();
+        let start = state
+            .events
+            .partition_point(|e| e.timestamp_ns < query.start_ns);
+        let mut events: Vec<ParameterChange> = state
+            .events
+            .range(start..)
+            .take_while(|e| e.timestamp_ns <= end_ns)
+            .filter(|e| query.device_id.as_ref().is_none_or(|id| &e.device_id == id))
+            .filter(|e| query.names.is_empty() || query.names.contains(&e.name))
+            .cloned()
+            .collect();
+
+        let truncated = query.limit > 0 && events.len() > query.limit;
+        if truncated {
+            events.drain(..events.len() - query.limit);
+        }
+        (events, truncated)
+    }
+
+    /// Latest event per parameter of `device_id` at or before `timestamp_ns`
+    ///
+    /// The flag is false when events before the retention window were
+    /// dropped, so parameters last changed before then are missing.
+    pub fn state_at(&self, device_id: &str, timestamp_ns: u64) -> (Vec<ParameterChange>, bool) {
+        let state = self.lock();
+        let end = state
+            .events
+            .partition_point(|e| e.timestamp_ns <= timestamp_ns);
+        let mut latest: BTreeMap<&str, &ParameterChange> = BTreeMap::new();
+        for event in state.events.range(..end) {
+            if event.device_id == device_id {
+                latest.insert(&event.name, event);
+            }
+        }
+        let complete = state.dropped_before_ns == 0;
+        (latest.into_values().cloned().collect(), complete)
+    }
+
+    /// Timestamp of the oldest retained event (0 if empty)
+    pub fn oldest_ns(&self) -> u64 {
+        self.lock().events.front().map_or(0, |e| e.timestamp_ns)
+    }
+
+    /// Record every change sent on `rx` until the channel closes
+    pub fn spawn_recorder(
+        self: &Arc<Self>,
+        mut rx: broadcast::Receiver<ParameterChange>,
+    ) -> JoinHandle<()> {
+        let history = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(change) => history.record(change),
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!("Device history lagged, {} changes not recorded", n);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+/// Memory held by a stored event: the struct plus its strings
+fn event_bytes(event: &ParameterChange) -> u64 {
+    (std::mem::size_of::<ParameterChange>()
+        + event.device_id.len()
+        + event.name.len()
+        + event.old_value.len()
+        + event.new_value.len()
+        + event.units.len()
+        + event.source.len()) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(device_id: &str, name: &str, value: &str, timestamp_ns: u64) -> ParameterChange {
+        ParameterChange {
+            device_id: device_id.to_string(),
+            name: name.to_string(),
+            old_value: String::new(),
+            new_value: value.to_string(),
+            units: String::new(),
+            timestamp_ns,
+            source: "user".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_query_and_state_at() {
+        let base = now_ns();
+        let history = DeviceHistory::default();
+        history.record(change("waveplate", "angle", "10", base + 10));
+        history.record(change("waveplate", "angle", "20", base + 30));
+        history.record(change("laser", "power", "1.5", base + 20));
+        // Arrives late, but belongs between the two angle changes
+        history.record(change("waveplate", "speed", "3", base + 15));
+
+        let (state, complete) = history.state_at("waveplate", base + 25);
+        assert!(complete);
+        let values: Vec<_> = state
+            .iter()
+            .map(|e| (e.name.as_str(), e.new_value.as_str()))
+            .collect();
+        assert_eq!(values, [("angle", "10"), ("speed", "3")]);
+
+        let (events, truncated) = history.query(&HistoryQuery {
+            device_id: Some("waveplate".to_string()),
+            names: vec!["angle".to_string()],
+            ..Default::default()
+        });
+        assert!(!truncated);
+        assert_eq!(events.len(), 2);
+
+        let (events, truncated) = history.query(&HistoryQuery {
+            start_ns: base + 15,
+            limit: 2,
+            ..Default::default()
+        });
+        assert!(truncated);
+        let values: Vec<_> = events.iter().map(|e| e.new_value.as_str()).collect();
+        assert_eq!(values, ["1.5", "20"]);
+    }
+
+    #[test]
+    fn test_events_outside_retention_are_dropped() {
+        let history = DeviceHistory::new(Duration::from_secs(60));
+        let now = now_ns();
+        history.record(change("stage", "position", "1", now - 120_000_000_000));
+        history.record(change("stage", "position", "2", now));
+
+        let (state, complete) = history.state_at("stage", now);
+        assert!(!complete);
+        assert_eq!(state.len(), 1);
+        assert_eq!(state[0].new_value, "2");
+        assert_eq!(history.oldest_ns(), now);
+    }
+}
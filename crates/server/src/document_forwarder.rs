@@ -0,0 +1,709 @@
+//! Forwarding of run documents to external message queues.
+//!
+//! Facility data pipelines can ingest runs as they happen instead of polling
+//! data files: each configured forwarder subscribes to the run engine's
+//! document stream and publishes every document to a NATS subject or a Kafka
+//! topic. Forwarders are configured in `config/config.v4.toml`:
+//!
+//! ```toml
+//! [[forwarders]]
+//! name = "facility"
+//! backend = "nats"                 # or "kafka" (needs the `kafka` feature)
+//! url = "nats://broker.lab:4222"   # Kafka: comma-separated bootstrap servers
+//! topic = "rust_daq.{type}"        # {type} and {run_uid} are substituted
+//! serialization = "json"           # or "avro" (see DOCUMENT_AVRO_SCHEMA)
+//! delivery = "at_least_once"       # or "at_most_once"
+//! document_types = ["start", "descriptor", "event", "stop"]  # empty = all
+//! ```
+//!
+//! Each forwarder is also a consumer in the [document transform] chain under
+//! its own name, so e.g. operator names can be redacted from the external
+//! copy only.
+//!
+//! # Delivery
+//!
+//! - `at_most_once`: each document is published once. While the broker is
+//!   unreachable, documents are dropped and counted in the drop ledger.
+//! - `at_least_once`: documents go out in batches the broker must confirm. A
+//!   failed batch is sent again after reconnecting, so consumers may see
+//!   duplicates and should de-duplicate on the document `uid`. Documents are
+//!   only lost if the broker stays down longer than `queue_capacity`
+//!   documents take to arrive.
+//!
+//! [document transform]: common::document_transform
+
+use anyhow::{Context, Result, anyhow, bail};
+use async_trait::async_trait;
+use common::experiment::document::Document;
+use common::integrity::{DropLedger, DropStage};
+use experiment::document_bus::{DocumentRecvError, SequencedDocument};
+use experiment::run_engine::RunEngine;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
+use tokio::net::TcpStream;
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::task::JoinHandle;
+
+/// Documents a forwarder can fall behind by before some are dropped
+pub const DEFAULT_FORWARDER_QUEUE: usize = 10_000;
+
+/// Most documents published per broker confirmation
+const MAX_BATCH: usize = 256;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+const MIN_RETRY_DELAY: Duration = Duration::from_millis(500);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Default NATS client port
+const NATS_DEFAULT_PORT: u16 = 4222;
+
+/// Avro schema of documents forwarded with `serialization = "avro"`
+///
+/// Messages are single Avro binary-encoded records without a container or
+/// schema registry header. `data` holds an event's scalar values (empty for
+/// other documents); `document` is the full document as JSON.
+pub const DOCUMENT_AVRO_SCHEMA: &str = r#"{
+  "type": "record",
+  "name": "RunDocument",
+  "namespace": "rust_daq",
+  "fields": [
+    {"name": "doc_type", "type": "string"},
+    {"name": "uid", "type": "string"},
+    {"name": "run_uid", "type": "string"},
+    {"name": "sequence", "type": "long"},
+    {"name": "time_ns", "type": "long"},
+    {"name": "data", "type": {"type": "map", "values": "double"}},
+    {"name": "document", "type": "string"}
+  ]
+}"#;
+
+/// Document type names, as used in topics and `document_types`
+const DOCUMENT_TYPES: [&str; 6] = [
+    "start",
+    "descriptor",
+    "event",
+    "stop",
+    "manifest",
+    "progress",
+];
+
+/// Message queue a forwarder publishes to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ForwarderBackend {
+    #[default]
+    Nats,
+    Kafka,
+}
+
+/// Wire format of forwarded documents
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentSerialization {
+    #[default]
+    Json,
+    Avro,
+}
+
+/// What a forwarder does when the broker is unavailable
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryGuarantee {
+    /// Publish once, drop documents the broker doesn't take
+    AtMostOnce,
+    /// Wait for broker confirmation, re-sending after failures
+    #[default]
+    AtLeastOnce,
+}
+
+/// One `[[forwarders]]` entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ForwarderConfig {
+    /// Name for logs, and the forwarder's document transform consumer name
+    pub name: String,
+    pub backend: ForwarderBackend,
+    /// `nats://host:port`, or Kafka bootstrap servers
+    pub url: String,
+    /// Subject or topic; `{type}` and `{run_uid}` are substituted
+    pub topic: String,
+    pub serialization: DocumentSerialization,
+    pub delivery: DeliveryGuarantee,
+    /// Document types to forward (empty = all)
+    pub document_types: Vec<String>,
+    pub queue_capacity: usize,
+}
+
+impl Default for ForwarderConfig {
+    fn default() -> Self {
+        Self {
+            name: "forwarder".to_string(),
+            backend: ForwarderBackend::default(),
+            url: String::new(),
+            topic: "rust_daq.{type}".to_string(),
+            serialization: DocumentSerialization::default(),
+            delivery: DeliveryGuarantee::default(),
+            document_types: Vec::new(),
+            queue_capacity: DEFAULT_FORWARDER_QUEUE,
+        }
+    }
+}
+
+impl ForwarderConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.url.trim().is_empty() {
+            bail!("Forwarder '{}' has no url", self.name);
+        }
+        if self.topic.trim().is_empty() {
+            bail!("Forwarder '{}' has no topic", self.name);
+        }
+        if self.queue_capacity == 0 {
+            bail!("Forwarder '{}' needs a queue_capacity above 0", self.name);
+        }
+        if let Some(unknown) = self
+            .document_types
+            .iter()
+            .find(|t| !DOCUMENT_TYPES.contains(&t.as_str()))
+        {
+            bail!(
+                "Forwarder '{}': unknown document type '{}' (expected one of {})",
+                self.name,
+                unknown,
+                DOCUMENT_TYPES.join(", ")
+            );
+        }
+        if self.backend == ForwarderBackend::Kafka && !cfg!(feature = "kafka") {
+            bail!(
+                "Forwarder '{}' uses Kafka, but the daemon was built without the `kafka` feature",
+                self.name
+            );
+        }
+        Ok(())
+    }
+
+    fn forwards(&self, doc: &Document) -> bool {
+        self.document_types.is_empty() || self.document_types.iter().any(|t| t == doc_type(doc))
+    }
+
+    /// Subject or topic for `doc`
+    pub fn topic_for(&self, doc: &Document) -> String {
+        self.topic
+            .replace("{type}", doc_type(doc))
+            .replace("{run_uid}", doc.run_uid())
+    }
+}
+
+fn doc_type(doc: &Document) -> &'static str {
+    match doc {
+        Document::Start(_) => "start",
+        Document::Descriptor(_) => "descriptor",
+        Document::Event(_) => "event",
+        Document::Stop(_) => "stop",
+        Document::Manifest(_) => "manifest",
+        Document::Progress(_) => "progress",
+        Document::BatchSummary(_) => "batch_summary",
+        Document::DeviceEvent(_) => "device_event",
+    }
+}
+
+/// Encode `doc` (stream sequence number `seq`) for publishing
+pub fn serialize_document(
+    doc: &Document,
+    seq: u64,
+    serialization: DocumentSerialization,
+) -> Result<Vec<u8>> {
+    let json = serde_json::to_vec(doc).context("Failed to serialize document")?;
+    if serialization == DocumentSerialization::Json {
+        return Ok(json);
+    }
+
+    let mut out = Vec::with_capacity(json.len() + 128);
+    avro_string(&mut out, doc_type(doc));
+    avro_string(&mut out, doc.uid());
+    avro_string(&mut out, doc.run_uid());
+    avro_long(&mut out, i64::try_from(seq).unwrap_or(i64::MAX));
+    avro_long(
+        &mut out,
+        i64::try_from(doc.timestamp_ns()).unwrap_or(i64::MAX),
+    );
+    // Sorted so identical events encode identically
+    let data: BTreeMap<&String, &f64> = match doc {
+        Document::Event(event) => event.data.iter().collect(),
+        _ => BTreeMap::new(),
+    };
+    if !data.is_empty() {
+        avro_long(&mut out, data.len() as i64);
+        for (key, value) in data {
+            avro_string(&mut out, key);
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    avro_long(&mut out, 0);
+    avro_long(&mut out, json.len() as i64);
+    out.extend_from_slice(&json);
+    Ok(out)
+}
+
+/// Avro `long`: zig-zag encoded variable-length integer
+fn avro_long(out: &mut Vec<u8>, value: i64) {
+    let mut n = ((value << 1) ^ (value >> 63)) as u64;
+    while n >= 0x80 {
+        out.push((n as u8 & 0x7f) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+/// Avro `string`: length then UTF-8 bytes
+fn avro_string(out: &mut Vec<u8>, value: &str) {
+    avro_long(out, value.len() as i64);
+    out.extend_from_slice(value.as_bytes());
+}
+
+/// Connection to a message broker
+#[async_trait]
+pub trait MessageSink: Send {
+    /// Publish `payload` to `topic`; `key` is the run UID (Kafka partition key)
+    async fn publish(&mut self, topic: &str, key: &str, payload: &[u8]) -> Result<()>;
+
+    /// Send anything still buffered, without waiting for the broker
+    async fn flush(&mut self) -> Result<()>;
+
+    /// Return once the broker has everything published so far
+    async fn confirm(&mut self) -> Result<()>;
+}
+
+/// Publisher speaking the NATS core client protocol
+pub struct NatsSink {
+    reader: BufReader<OwnedReadHalf>,
+    writer: BufWriter<OwnedWriteHalf>,
+}
+
+impl NatsSink {
+    /// Connect to `nats://host[:port]`
+    pub async fn connect(url: &str) -> Result<Self> {
+        let address = nats_address(url)?;
+        let stream = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(&address))
+            .await
+            .map_err(|_| anyhow!("Timed out connecting to NATS at {}", address))?
+            .with_context(|| format!("Failed to connect to NATS at {}", address))?;
+        let (reader, writer) = stream.into_split();
+        let mut sink = Self {
+            reader: BufReader::new(reader),
+            writer: BufWriter::new(writer),
+        };
+
+        let info = sink.read_line().await?;
+        if !info.starts_with("INFO") {
+            bail!("Not a NATS server at {}: {}", address, info);
+        }
+        sink.writer
+            .write_all(b"CONNECT {\"verbose\":false,\"pedantic\":false,\"lang\":\"rust\",\"name\":\"rust-daq\"}\r\n")
+            .await?;
+        sink.confirm().await?;
+        Ok(sink)
+    }
+
+    async fn read_line(&mut self) -> Result<String> {
+        let mut line = String::new();
+        let read = tokio::time::timeout(REPLY_TIMEOUT, self.reader.read_line(&mut line))
+            .await
+            .map_err(|_| anyhow!("NATS server did not reply"))??;
+        if read == 0 {
+            bail!("NATS server closed the connection");
+        }
+        Ok(line.trim_end().to_string())
+    }
+}
+
+fn nats_address(url: &str) -> Result<String> {
+    let host = url.trim().strip_prefix("nats://").unwrap_or(url.trim());
+    let host = host.trim_end_matches('/');
+    if host.is_empty() {
+        bail!("Invalid NATS url '{}'", url);
+    }
+    if host
+        .rsplit_once(':')
+        .is_some_and(|(_, port)| port.parse::<u16>().is_ok())
+    {
+        Ok(host.to_string())
+    } else {
+        Ok(format!("{}:{}", host, NATS_DEFAULT_PORT))
+    }
+}
+
+#[async_trait]
+impl MessageSink for NatsSink {
+    async fn publish(&mut self, topic: &str, _key: &str, payload: &[u8]) -> Result<()> {
+        if topic.is_empty() || topic.contains(char::is_whitespace) {
+            bail!("Invalid NATS subject '{}'", topic);
+        }
+        self.writer
+            .write_all(format!("PUB {} {}\r\n", topic, payload.len()).as_bytes())
+            .await?;
+        self.writer.write_all(payload).await?;
+        self.writer.write_all(b"\r\n").await?;
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        self.writer.flush().await?;
+        Ok(())
+    }
+
+    async fn confirm(&mut self) -> Result<()> {
+        // The server answers PINGs in order, so a PONG means everything
+        // before it was processed
+        self.writer.write_all(b"PING\r\n").await?;
+        self.writer.flush().await?;
+        loop {
+            let line = self.read_line().await?;
+            if line == "PONG" {
+                return Ok(());
+            } else if line == "PING" {
+                self.writer.write_all(b"PONG\r\n").await?;
+                self.writer.flush().await?;
+            } else if let Some(error) = line.strip_prefix("-ERR") {
+                bail!("NATS error:{}", error);
+            }
+        }
+    }
+}
+
+/// Publisher to a Kafka cluster via librdkafka
+#[cfg(feature = "kafka")]
+pub struct KafkaSink {
+    producer: rdkafka::producer::FutureProducer,
+    delivery: DeliveryGuarantee,
+    pending: Vec<rdkafka::producer::DeliveryFuture>,
+}
+
+#[cfg(feature = "kafka")]
+impl KafkaSink {
+    /// Create a producer for the comma-separated `bootstrap_servers`
+    pub fn connect(bootstrap_servers: &str, delivery: DeliveryGuarantee) -> Result<Self> {
+        let mut config = rdkafka::ClientConfig::new();
+        config
+            .set("bootstrap.servers", bootstrap_servers)
+            .set("client.id", "rust-daq");
+        match delivery {
+            DeliveryGuarantee::AtMostOnce => config.set("acks", "0"),
+            DeliveryGuarantee::AtLeastOnce => {
+                config.set("acks", "all").set("enable.idempotence", "true")
+            }
+        };
+        let producer = config.create().context("Failed to create Kafka producer")?;
+        Ok(Self {
+            producer,
+            delivery,
+            pending: Vec::new(),
+        })
+    }
+}
+
+#[cfg(feature = "kafka")]
+#[async_trait]
+impl MessageSink for KafkaSink {
+    async fn publish(&mut self, topic: &str, key: &str, payload: &[u8]) -> Result<()> {
+        let record = rdkafka::producer::FutureRecord::to(topic)
+            .key(key)
+            .payload(payload);
+        let delivery = self
+            .producer
+            .send_result(record)
+            .map_err(|(e, _)| anyhow!("Kafka publish failed: {}", e))?;
+        // At most once: librdkafka sends in the background, nothing to wait for
+        if self.delivery == DeliveryGuarantee::AtLeastOnce {
+            self.pending.push(delivery);
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn confirm(&mut self) -> Result<()> {
+        for delivery in self.pending.drain(..) {
+            delivery
+                .await
+                .map_err(|_| anyhow!("Kafka delivery cancelled"))?
+                .map_err(|(e, _)| anyhow!("Kafka delivery failed: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+async fn connect(config: &ForwarderConfig) -> Result<Box<dyn MessageSink>> {
+    match config.backend {
+        ForwarderBackend::Nats => Ok(Box::new(NatsSink::connect(&config.url).await?)),
+        #[cfg(feature = "kafka")]
+        ForwarderBackend::Kafka => Ok(Box::new(KafkaSink::connect(&config.url, config.delivery)?)),
+        #[cfg(not(feature = "kafka"))]
+        ForwarderBackend::Kafka => bail!("Kafka support requires the `kafka` feature"),
+    }
+}
+
+/// A document ready to publish
+struct Message {
+    topic: String,
+    key: String,
+    payload: Vec<u8>,
+}
+
+struct Forwarder {
+    config: ForwarderConfig,
+    sink: Option<Box<dyn MessageSink>>,
+    retry_delay: Duration,
+    /// At most once: no reconnect attempts before this
+    retry_at: Option<Instant>,
+}
+
+impl Forwarder {
+    async fn send(&mut self, messages: &[Message]) -> Result<()> {
+        if self.sink.is_none() {
+            self.sink = Some(connect(&self.config).await?);
+            tracing::info!(forwarder = %self.config.name, url = %self.config.url, "Document forwarder connected");
+        }
+        let sink = self.sink.as_mut().expect("connected above");
+        for message in messages {
+            sink.publish(&message.topic, &message.key, &message.payload)
+                .await?;
+        }
+        match self.config.delivery {
+            DeliveryGuarantee::AtMostOnce => sink.flush().await,
+            DeliveryGuarantee::AtLeastOnce => sink.confirm().await,
+        }
+    }
+
+    /// Publish a batch according to the delivery guarantee
+    async fn deliver(&mut self, messages: &[Message]) {
+        loop {
+            if self.retry_at.is_some_and(|at| Instant::now() < at) {
+                drop_documents(messages.len());
+                return;
+            }
+            let error = match self.send(messages).await {
+                Ok(()) => {
+                    self.retry_delay = MIN_RETRY_DELAY;
+                    self.retry_at = None;
+                    return;
+                }
+                Err(e) => e,
+            };
+            self.sink = None;
+            tracing::warn!(
+                forwarder = %self.config.name,
+                error = %format!("{:#}", error),
+                retry_in = ?self.retry_delay,
+                "Document forwarding failed"
+            );
+            let delay = self.retry_delay;
+            self.retry_delay = (self.retry_delay * 2).min(MAX_RETRY_DELAY);
+            if self.config.delivery == DeliveryGuarantee::AtMostOnce {
+                self.retry_at = Some(Instant::now() + delay);
+                drop_documents(messages.len());
+                return;
+            }
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+fn drop_documents(count: usize) {
+    DropLedger::global().record("documents", DropStage::Delivery, count as u64);
+}
+
+/// Start forwarding `engine`'s documents as `config` describes
+///
+/// Fails if the configuration is invalid; broker connection problems are
+/// handled (and logged) by the forwarding task.
+pub fn spawn_document_forwarder(
+    config: ForwarderConfig,
+    engine: Arc<RunEngine>,
+) -> Result<JoinHandle<()>> {
+    config.validate()?;
+    let mut rx = engine.subscribe_with_capacity(config.queue_capacity);
+    let transforms = engine.document_transforms();
+    let mut forwarder = Forwarder {
+        config,
+        sink: None,
+        retry_delay: MIN_RETRY_DELAY,
+        retry_at: None,
+    };
+
+    Ok(tokio::spawn(async move {
+        let name = forwarder.config.name.clone();
+        let on_gap = |missed: u64| {
+            tracing::error!(forwarder = %name, missed, "Document forwarder fell behind, documents lost");
+            DropLedger::global().record("documents", DropStage::Delivery, missed);
+        };
+        loop {
+            // Wait for a document, then take whatever else is already queued
+            let mut batch: Vec<SequencedDocument> = Vec::new();
+            match rx.recv_sequenced().await {
+                Ok(sequenced) => batch.push(sequenced),
+                Err(DocumentRecvError::Gap { missed }) => {
+                    on_gap(missed);
+                    continue;
+                }
+                Err(DocumentRecvError::Closed) => break,
+            }
+            while batch.len() < MAX_BATCH {
+                match rx.try_recv() {
+                    Ok(Some(sequenced)) => batch.push(sequenced),
+                    Ok(None) | Err(DocumentRecvError::Closed) => break,
+                    Err(DocumentRecvError::Gap { missed }) => on_gap(missed),
+                }
+            }
+
+            let config = &forwarder.config;
+            let messages: Vec<Message> = batch
+                .into_iter()
+                .filter_map(|SequencedDocument { seq, doc }| {
+                    let doc = transforms.apply(&config.name, doc);
+                    if !config.forwards(&doc) {
+                        return None;
+                    }
+                    match serialize_document(&doc, seq, config.serialization) {
+                        Ok(payload) => Some(Message {
+                            topic: config.topic_for(&doc),
+                            key: doc.run_uid().to_string(),
+                            payload,
+                        }),
+                        Err(e) => {
+                            tracing::error!(forwarder = %config.name, error = %e, "Document not forwarded");
+                            None
+                        }
+                    }
+                })
+                .collect();
+            if !messages.is_empty() {
+                forwarder.deliver(&messages).await;
+            }
+        }
+        tracing::info!(forwarder = %forwarder.config.name, "Document forwarder stopped");
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::experiment::document::{EventDoc, StartDoc};
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_config_and_topics() {
+        let config: ForwarderConfig = toml::from_str(
+            r#"
+            name = "facility"
+            url = "nats://broker.lab"
+            topic = "daq.{run_uid}.{type}"
+            serialization = "avro"
+            delivery = "at_most_once"
+            document_types = ["start", "stop"]
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.backend, ForwarderBackend::Nats);
+        assert_eq!(config.queue_capacity, DEFAULT_FORWARDER_QUEUE);
+
+        let start = Document::Start(StartDoc::new("count", "Count"));
+        let run_uid = start.run_uid().to_string();
+        assert_eq!(config.topic_for(&start), format!("daq.{}.start", run_uid));
+        assert!(config.forwards(&start));
+        assert!(!config.forwards(&Document::Event(EventDoc::new(&run_uid, "d", 0))));
+
+        let bad = ForwarderConfig {
+            document_types: vec!["events".to_string()],
+            ..config
+        };
+        assert!(bad.validate().is_err());
+        assert_eq!(
+            nats_address("nats://broker.lab").unwrap(),
+            "broker.lab:4222"
+        );
+        assert_eq!(nats_address("10.0.0.2:4333").unwrap(), "10.0.0.2:4333");
+    }
+
+    #[test]
+    fn test_avro_encoding() {
+        let mut out = Vec::new();
+        for value in [0, -1, 1, 64] {
+            avro_long(&mut out, value);
+        }
+        assert_eq!(out, [0x00, 0x01, 0x02, 0x80, 0x01]);
+
+        let mut event = EventDoc::new("run", "desc", 0);
+        event.data.insert("power".to_string(), 1.5);
+        let doc = Document::Event(event);
+        let encoded = serialize_document(&doc, 7, DocumentSerialization::Avro).unwrap();
+        // doc_type "event" comes first
+        assert_eq!(&encoded[..6], b"\x0aevent");
+        let json = serialize_document(&doc, 7, DocumentSerialization::Json).unwrap();
+        assert!(encoded.ends_with(&json));
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(value["type"], "event");
+    }
+
+    /// Minimal NATS server recording what it's sent
+    async fn fake_nats(listener: TcpListener) -> Vec<(String, Vec<u8>)> {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+        writer
+            .write_all(b"INFO {\"server_id\":\"test\"}\r\n")
+            .await
+            .unwrap();
+        let mut published = Vec::new();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).await.unwrap() == 0 {
+                return published;
+            }
+            if line.starts_with("PING") {
+                writer.write_all(b"PONG\r\n").await.unwrap();
+            } else if let Some(rest) = line.strip_prefix("PUB ") {
+                let mut parts = rest.split_whitespace();
+                let subject = parts.next().unwrap().to_string();
+                let len: usize = parts.last().unwrap().parse().unwrap();
+                let mut payload = vec![0; len + 2];
+                reader.read_exact(&mut payload).await.unwrap();
+                payload.truncate(len);
+                published.push((subject, payload));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nats_sink_publishes_and_confirms() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("nats://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(fake_nats(listener));
+
+        let mut sink = NatsSink::connect(&url).await.unwrap();
+        sink.publish("rust_daq.start", "run", b"{\"a\":1}")
+            .await
+            .unwrap();
+        sink.publish("rust_daq.stop", "run", b"").await.unwrap();
+        assert!(sink.publish("bad subject", "run", b"").await.is_err());
+        sink.confirm().await.unwrap();
+        drop(sink);
+
+        let published = server.await.unwrap();
+        assert_eq!(
+            published,
+            [
+                ("rust_daq.start".to_string(), b"{\"a\":1}".to_vec()),
+                ("rust_daq.stop".to_string(), Vec::new()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! ConfigService implementation for differential configuration apply
+//!
+//! Exposes [`crate::config_apply`] over gRPC: clients fetch the running
+//! configuration as TOML, edit it, and send it back. Only the differences are
+//! applied, and a failure rolls back everything applied before it.
+//!
+//! `RestoreConfigSnapshot` applies the configuration snapshot recorded with a
+//! run (see [`experiment::config_snapshot`]) the same way, optionally
+//! followed by its parameter values, to reproduce the run's setup.
+//!
+//! Both fail with `FAILED_PRECONDITION` when they would change a device the
+//! active run uses.
+
+use crate::config_apply::{
+    ApplyReport, ConfigTarget, DaemonConfig, StepStatus, TargetBusy, apply_config,
+};
+use crate::grpc::proto::{
+    ApplyConfigRequest, ConfigApplyReport, ConfigApplyStep, ConfigStepStatus, DaemonConfigDocument,
+    GetDaemonConfigRequest, RestoreConfigSnapshotRequest, RestoreConfigSnapshotResponse,
+    config_service_server::ConfigService, restore_config_snapshot_request::Source,
+};
+use experiment::config_snapshot::ConfigSnapshot;
+use hardware::recipes::RecipeTarget;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tonic::{Request, Response, Status};
+
+/// gRPC ConfigService backed by a [`ConfigTarget`]
+pub struct ConfigServiceImpl {
+    target: Arc<dyn ConfigTarget>,
+    /// Serializes applies so two diffs are never computed against the same state
+    apply_lock: Mutex<()>,
+    /// Directory run config snapshots are saved in
+    snapshot_dir: Option<PathBuf>,
+    /// Where snapshot parameter values are restored
+    parameters: Option<Arc<dyn RecipeTarget>>,
+}
+
+impl ConfigServiceImpl {
+    pub fn new(target: Arc<dyn ConfigTarget>) -> Self {
+        Self {
+            target,
+            apply_lock: Mutex::new(()),
+            snapshot_dir: None,
+            parameters: None,
+        }
+    }
+
+    /// Enable restoring run config snapshots saved in `snapshot_dir`, with
+    /// parameter values set through `parameters`
+    pub fn with_snapshots(
+        mut self,
+        snapshot_dir: PathBuf,
+        parameters: Arc<dyn RecipeTarget>,
+    ) -> Self {
+        self.snapshot_dir = Some(snapshot_dir);
+        self.parameters = Some(parameters);
+        self
+    }
+
+    /// Set every parameter value recorded in `snapshot`
+    ///
+    /// Continues past failures (e.g. read-only parameters) and returns the
+    /// number restored with one error line per failure.
+    async fn restore_parameters(&self, snapshot: &ConfigSnapshot) -> (u32, Vec<String>) {
+        let Some(target) = &self.parameters else {
+            return (0, vec!["Parameter restore is not available".to_string()]);
+        };
+        let mut restored = 0;
+        let mut errors = Vec::new();
+        for (device, parameters) in &snapshot.parameters {
+            for (name, value) in parameters {
+                match target.set_parameter(device, name, value.clone()).await {
+                    Ok(()) => restored += 1,
+                    Err(e) => errors.push(format!("{}.{}: {:#}", device, name, e)),
+                }
+            }
+        }
+        (restored, errors)
+    }
+}
+
+#[tonic::async_trait]
+impl ConfigService for ConfigServiceImpl {
+    async fn get_daemon_config(
+        &self,
+        _request: Request<GetDaemonConfigRequest>,
+    ) -> Result<Response<DaemonConfigDocument>, Status> {
+        let config = self
+            .target
+            .current()
+            .await
+            .map_err(|e| Status::internal(format!("{:#}", e)))?;
+        let config_toml = config
+            .to_toml()
+            .map_err(|e| Status::internal(format!("{:#}", e)))?;
+        Ok(Response::new(DaemonConfigDocument { config_toml }))
+    }
+
+    async fn apply_config(
+        &self,
+        request: Request<ApplyConfigRequest>,
+    ) -> Result<Response<ConfigApplyReport>, Status> {
+        let req = request.into_inner();
+        let desired = DaemonConfig::from_toml(&req.config_toml)
+            .map_err(|e| Status::invalid_argument(format!("{:#}", e)))?;
+
+        let _guard = self.apply_lock.lock().await;
+        let report = apply_config(self.target.as_ref(), &desired, req.dry_run)
+            .await
+            .map_err(apply_error)?;
+        Ok(Response::new(report_to_proto(report)))
+    }
+
+    async fn restore_config_snapshot(
+        &self,
+        request: Request<RestoreConfigSnapshotRequest>,
+    ) -> Result<Response<RestoreConfigSnapshotResponse>, Status> {
+        let req = request.into_inner();
+        let snapshot = match req.source {
+            Some(Source::RunUid(run_uid)) => {
+                let dir = self.snapshot_dir.as_ref().ok_or_else(|| {
+                    Status::failed_precondition("Run config snapshots are not available")
+                })?;
+                ConfigSnapshot::find(dir, &run_uid)
+                    .map_err(|e| Status::not_found(format!("{:#}", e)))?
+            }
+            Some(Source::SnapshotJson(json)) => ConfigSnapshot::from_json(&json)
+                .map_err(|e| Status::invalid_argument(format!("{:#}", e)))?,
+            None => {
+                return Err(Status::invalid_argument(
+                    "run_uid or snapshot_json is required",
+                ));
+            }
+        };
+        let desired = DaemonConfig::from_toml(&snapshot.daemon_config)
+            .map_err(|e| Status::invalid_argument(format!("{:#}", e)))?;
+
+        let _guard = self.apply_lock.lock().await;
+        let report = apply_config(self.target.as_ref(), &desired, req.dry_run)
+            .await
+            .map_err(apply_error)?;
+
+        // Parameters belong to the restored devices, so only set them once
+        // the configuration is in place
+        let (parameters_restored, parameter_errors) =
+            if req.restore_parameters && !req.dry_run && report.success() {
+                self.restore_parameters(&snapshot).await
+            } else {
+                (0, Vec::new())
+            };
+
+        Ok(Response::new(RestoreConfigSnapshotResponse {
+            report: Some(report_to_proto(report)),
+            parameters_restored,
+            parameter_errors,
+            software: snapshot.software.into_iter().collect(),
+        }))
+    }
+}
+
+/// Status of an apply that could not start
+fn apply_error(e: anyhow::Error) -> Status {
+    if e.is::<TargetBusy>() {
+        Status::failed_precondition(e.to_string())
+    } else {
+        Status::internal(format!("{:#}", e))
+    }
+}
+
+fn report_to_proto(report: ApplyReport) -> ConfigApplyReport {
+    ConfigApplyReport {
+        success: report.success(),
+        dry_run: report.dry_run,
+        rolled_back: report.rolled_back,
+        error_message: report.error.unwrap_or_default(),
+        steps: report
+            .steps
+            .into_iter()
+            .map(|step| ConfigApplyStep {
+                component: step.component.as_str().to_string(),
+                target: step.target,
+                action: step.action.to_string(),
+                status: match step.status {
+                    StepStatus::Planned => ConfigStepStatus::ConfigStepPlanned,
+                    StepStatus::Applied => ConfigStepStatus::ConfigStepApplied,
+                    StepStatus::Failed => ConfigStepStatus::ConfigStepFailed,
+                    StepStatus::Skipped => ConfigStepStatus::ConfigStepSkipped,
+                    StepStatus::RolledBack => ConfigStepStatus::ConfigStepRolledBack,
+                    StepStatus::RollbackFailed => ConfigStepStatus::ConfigStepRollbackFailed,
+                } as i32,
+                message: step.message,
+            })
+            .collect(),
+    }
+}
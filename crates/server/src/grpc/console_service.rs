@@ -0,0 +1,159 @@
+//! InstrumentConsoleService implementation for raw command passthrough
+//!
+//! Lets an operator type commands straight to an instrument (e.g. `1TP?` to
+//! an ESP300) and see the unparsed reply, for debugging wiring and firmware
+//! behaviour without stopping the daemon. Commands go through the driver's
+//! [`RawTerminal`](common::capabilities::RawTerminal) implementation, so they
+//! share the port lock with normal driver traffic.
+//!
+//! Two guards apply:
+//! - A device used by the active run is refused, so a stray command can't
+//!   disturb an acquisition.
+//! - Every attempt, including refused ones, goes to the [`AuditLog`].
+
+use crate::audit::{AuditEntry, AuditLog};
+use crate::auth;
+use crate::grpc::proto::{
+    RawCommandRequest, RawCommandResponse,
+    instrument_console_service_server::InstrumentConsoleService,
+};
+use experiment::RunEngine;
+use hardware::registry::DeviceRegistry;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tonic::{Request, Response, Status};
+
+/// Upper bound on a single raw command round trip
+pub const RAW_COMMAND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Audit action name for console commands
+const AUDIT_ACTION: &str = "raw_command";
+
+/// gRPC InstrumentConsoleService backed by the device registry
+pub struct ConsoleServiceImpl {
+    registry: Arc<DeviceRegistry>,
+    run_engine: Arc<RunEngine>,
+    audit: Arc<AuditLog>,
+}
+
+impl ConsoleServiceImpl {
+    pub fn new(
+        registry: Arc<DeviceRegistry>,
+        run_engine: Arc<RunEngine>,
+        audit: Arc<AuditLog>,
+    ) -> Self {
+        Self {
+            registry,
+            run_engine,
+            audit,
+        }
+    }
+
+    /// Record a refused attempt and return the error for the caller
+    fn refuse(&self, mut entry: AuditEntry, status: Status) -> Status {
+        entry.outcome = "blocked".to_string();
+        entry.message = status.message().to_string();
+        self.audit.record(&entry);
+        status
+    }
+}
+
+#[tonic::async_trait]
+impl InstrumentConsoleService for ConsoleServiceImpl {
+    async fn send_raw_command(
+        &self,
+        request: Request<RawCommandRequest>,
+    ) -> Result<Response<RawCommandResponse>, Status> {
+        let peer = request
+            .remote_addr()
+            .map(|addr| addr.to_string())
+            .unwrap_or_default();
+        let user = auth::principal(&request).user;
+        let req = request.into_inner();
+
+        // Audit under the canonical device ID, not whatever alias was typed
+        let device_id = self
+            .registry
+            .get_device_info(&req.device_id)
+            .map_or_else(|| req.device_id.clone(), |info| info.id);
+        let mut entry = AuditEntry::new(AUDIT_ACTION, &device_id);
+        entry.user = user;
+        entry.session_id = req.session_id.clone();
+        entry.peer = peer;
+        entry.detail = req.command.clone();
+
+        if req.command.trim().is_empty() {
+            return Err(self.refuse(entry, Status::invalid_argument("command is empty")));
+        }
+        if !self.registry.contains(&device_id) {
+            return Err(self.refuse(
+                entry,
+                Status::not_found(format!("Device not found: {}", req.device_id)),
+            ));
+        }
+        let Some(terminal) = self.registry.get_raw_terminal(&device_id) else {
+            return Err(self.refuse(
+                entry,
+                Status::unimplemented(format!(
+                    "Device {} does not support raw commands",
+                    device_id
+                )),
+            ));
+        };
+        if self
+            .run_engine
+            .current_run_devices()
+            .await
+            .contains(&device_id)
+        {
+            let run_uid = self.run_engine.current_run_uid().await.unwrap_or_default();
+            return Err(self.refuse(
+                entry,
+                Status::failed_precondition(format!(
+                    "Device {} is in use by run {}",
+                    device_id, run_uid
+                )),
+            ));
+        }
+
+        let started = Instant::now();
+        let result = tokio::time::timeout(
+            RAW_COMMAND_TIMEOUT,
+            terminal.send_raw(&req.command, req.expect_response),
+        )
+        .await
+        .unwrap_or_else(|_| {
+            Err(anyhow::anyhow!(
+                "No reply within {} s",
+                RAW_COMMAND_TIMEOUT.as_secs()
+            ))
+        });
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+
+        let response = match result {
+            Ok(response) => {
+                entry.outcome = "ok".to_string();
+                entry.message.clone_from(&response);
+                RawCommandResponse {
+                    success: true,
+                    response,
+                    error_message: String::new(),
+                    elapsed_ms,
+                }
+            }
+            Err(e) => {
+                let error_message = format!("{:#}", e);
+                entry.outcome = "failed".to_string();
+                entry.message.clone_from(&error_message);
+                RawCommandResponse {
+                    success: false,
+                    response: String::new(),
+                    error_message,
+                    elapsed_ms,
+                }
+            }
+        };
+        self.audit.record(&entry);
+        Ok(Response::new(response))
+    }
+}
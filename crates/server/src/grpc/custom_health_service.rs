@@ -3,18 +3,27 @@
 //! Provides remote monitoring of system health for headless operation.
 
 use crate::grpc::proto::{
-    ErrorSeverityLevel, GetErrorHistoryRequest, GetErrorHistoryResponse, GetModuleHealthRequest,
-    GetModuleHealthResponse, GetSystemHealthRequest, GetSystemHealthResponse, HealthErrorRecord,
-    HealthUpdate, ModuleHealthStatus as ProtoModuleHealthStatus, StreamHealthUpdatesRequest,
-    SystemHealthStatus as ProtoSystemHealthStatus, health_service_server::HealthService,
+    ErrorSeverityLevel, GetErrorHistoryRequest, GetErrorHistoryResponse, GetMemoryUsageRequest,
+    GetMemoryUsageResponse, GetModuleHealthRequest, GetModuleHealthResponse,
+    GetStateMachinesRequest, GetStateMachinesResponse, GetSystemHealthRequest,
+    GetSystemHealthResponse, HealthErrorRecord, HealthUpdate, MemorySubsystemUsage,
+    ModuleHealthStatus as ProtoModuleHealthStatus, StateMachineEdge, StateMachineGraph,
+    StateMachineInstance, StateTransitionEvent, StreamHealthUpdatesRequest,
+    StreamStateTransitionsRequest, SystemHealthStatus as ProtoSystemHealthStatus,
+    health_service_server::HealthService,
 };
 use common::health::{ErrorSeverity, SystemHealth, SystemHealthMonitor};
 use common::limits::HEALTH_CHECK_INTERVAL;
+use common::memory::MemoryLedger;
+use common::state_machine::{
+    MachineKind, StateGraph, StateTracker, StateTransition, TrackedMachine,
+};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::time::interval;
 use tokio_stream::StreamExt;
-use tokio_stream::wrappers::IntervalStream;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::{BroadcastStream, IntervalStream};
 use tonic::{Request, Response, Status};
 
 /// gRPC service for health monitoring
@@ -72,6 +81,60 @@ fn error_severity_to_proto(severity: ErrorSeverity) -> ErrorSeverityLevel {
     }
 }
 
+/// Parse requested state machine kinds (empty = all)
+fn parse_kinds(kinds: &[String]) -> Result<Vec<MachineKind>, Status> {
+    if kinds.is_empty() {
+        return Ok(MachineKind::ALL.to_vec());
+    }
+    kinds
+        .iter()
+        .map(|name| {
+            MachineKind::parse(name).ok_or_else(|| {
+                Status::invalid_argument(format!("Unknown state machine kind '{}'", name))
+            })
+        })
+        .collect()
+}
+
+fn graph_to_proto(graph: &StateGraph) -> StateMachineGraph {
+    StateMachineGraph {
+        kind: graph.kind.as_str().to_string(),
+        initial_state: graph.initial.to_string(),
+        states: graph.states.iter().map(|s| (*s).to_string()).collect(),
+        edges: graph
+            .edges
+            .iter()
+            .map(|e| StateMachineEdge {
+                from_state: e.from.to_string(),
+                to_state: e.to.to_string(),
+                trigger: e.trigger.to_string(),
+            })
+            .collect(),
+    }
+}
+
+fn transition_to_proto(transition: &StateTransition) -> StateTransitionEvent {
+    StateTransitionEvent {
+        kind: transition.kind.as_str().to_string(),
+        instance_id: transition.instance_id.clone(),
+        from_state: transition.from.clone(),
+        to_state: transition.to.clone(),
+        trigger: transition.trigger.clone(),
+        timestamp_ns: transition.timestamp_ns,
+        expected: transition.expected,
+    }
+}
+
+fn machine_to_proto(machine: &TrackedMachine) -> StateMachineInstance {
+    StateMachineInstance {
+        kind: machine.kind.as_str().to_string(),
+        instance_id: machine.instance_id.clone(),
+        state: machine.state.clone(),
+        entered_ns: machine.entered_ns,
+        recent_transitions: machine.recent.iter().map(transition_to_proto).collect(),
+    }
+}
+
 /// Convert proto ErrorSeverityLevel to ErrorSeverity
 fn proto_to_error_severity(level: ErrorSeverityLevel) -> ErrorSeverity {
     match level {
@@ -252,4 +315,82 @@ impl HealthService for HealthServiceImpl {
 
         Ok(Response::new(Box::pin(stream)))
     }
+
+    async fn get_state_machines(
+        &self,
+        request: Request<GetStateMachinesRequest>,
+    ) -> Result<Response<GetStateMachinesResponse>, Status> {
+        let kinds = parse_kinds(&request.into_inner().kinds)?;
+
+        let response = GetStateMachinesResponse {
+            graphs: kinds.iter().map(|k| graph_to_proto(k.graph())).collect(),
+            instances: StateTracker::global()
+                .snapshot()
+                .iter()
+                .filter(|m| kinds.contains(&m.kind))
+                .map(machine_to_proto)
+                .collect(),
+            timestamp_ns: now_ns(),
+        };
+
+        Ok(Response::new(response))
+    }
+
+    type StreamStateTransitionsStream = std::pin::Pin<
+        Box<dyn tokio_stream::Stream<Item = Result<StateTransitionEvent, Status>> + Send>,
+    >;
+
+    async fn stream_state_transitions(
+        &self,
+        request: Request<StreamStateTransitionsRequest>,
+    ) -> Result<Response<<Self as HealthService>::StreamStateTransitionsStream>, Status> {
+        let req = request.into_inner();
+        let kinds = parse_kinds(&req.kinds)?;
+        let instance_ids = req.instance_ids;
+
+        let stream =
+            BroadcastStream::new(StateTracker::global().subscribe()).filter_map(move |result| {
+                match result {
+                    Ok(transition) => (kinds.contains(&transition.kind)
+                        && (instance_ids.is_empty()
+                            || instance_ids.contains(&transition.instance_id)))
+                    .then(|| Ok(transition_to_proto(&transition))),
+                    // A slow client missed transitions; it can re-read the
+                    // current states with GetStateMachines
+                    Err(BroadcastStreamRecvError::Lagged(n)) => {
+                        tracing::warn!("State transition stream lagged, dropped {} transitions", n);
+                        None
+                    }
+                }
+            });
+
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn get_memory_usage(
+        &self,
+        _request: Request<GetMemoryUsageRequest>,
+    ) -> Result<Response<GetMemoryUsageResponse>, Status> {
+        let report = MemoryLedger::global().report();
+        let rss = crate::health::memory_monitor::process_rss_bytes(&mut sysinfo::System::new());
+
+        let response = GetMemoryUsageResponse {
+            subsystems: report
+                .subsystems
+                .into_iter()
+                .map(|usage| MemorySubsystemUsage {
+                    subsystem: usage.subsystem.to_string(),
+                    bytes: usage.bytes,
+                    budget_bytes: usage.budget_bytes,
+                    status: usage.status.to_string(),
+                    accounts: usage.accounts.into_iter().collect(),
+                })
+                .collect(),
+            accounted_bytes: report.total_bytes,
+            process_rss_bytes: rss.unwrap_or(0),
+            timestamp_ns: now_ns(),
+        };
+
+        Ok(Response::new(response))
+    }
 }
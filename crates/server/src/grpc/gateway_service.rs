@@ -0,0 +1,230 @@
+//! JSON/REST gateway to the gRPC services over plain HTTP
+//!
+//! Lets curl, Postman or a short Python script call the daemon without
+//! protobuf stubs. Every request is transcoded by [`protocol::gateway`] and
+//! forwarded to the daemon's own gRPC port, so authentication, locking and
+//! auditing behave exactly as for a gRPC client.
+//!
+//! # Endpoints
+//!
+//! - `POST /v1/<package.Service>/<Method>` - call an RPC with the request
+//!   message as JSON; server-streaming RPCs answer with newline-delimited JSON
+//! - `GET /openapi.json` - OpenAPI 3 description of every route
+//! - `GET /health` - liveness check
+//!
+//! The `Authorization` and `x-api-key` headers are passed on as gRPC
+//! metadata. gRPC errors become a JSON body `{"code": ..., "message": ...}`
+//! with the matching HTTP status.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use daq_server::grpc::gateway_service::start_gateway_server;
+//! use tonic::transport::Channel;
+//!
+//! let channel = Channel::builder("http://127.0.0.1:50051".parse()?).connect_lazy();
+//! let handle = start_gateway_server(8082, channel)?;
+//! // Dropping handle stops the server
+//! ```
+//!
+//! ```text
+//! curl -X POST http://localhost:8082/v1/daq.HardwareService/ListDevices -d '{}'
+//! ```
+
+use futures::StreamExt;
+use hyper::body::Bytes;
+use protocol::gateway::{self, GatewayResponse};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tonic::metadata::MetadataValue;
+use tonic::transport::Channel;
+use tonic::{Code, Status};
+
+/// Request headers forwarded to the gRPC call as metadata
+const FORWARDED_HEADERS: &[&str] = &["authorization", "x-api-key"];
+
+/// Largest accepted request body
+const MAX_REQUEST_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Handle to the running gateway; dropping it stops the server
+pub struct GatewayServerHandle {
+    _shutdown_tx: tokio::sync::oneshot::Sender<()>,
+}
+
+struct GatewayState {
+    channel: Channel,
+    openapi: Bytes,
+}
+
+/// Start the JSON gateway HTTP server
+///
+/// Calls are forwarded over `grpc_channel` (TCP or a local socket). Pass a
+/// lazily connected channel to start the gateway before the gRPC server is
+/// listening. Must be called from within a Tokio runtime.
+pub fn start_gateway_server(
+    port: u16,
+    grpc_channel: Channel,
+) -> Result<GatewayServerHandle, Box<dyn std::error::Error + Send + Sync>> {
+    let addr: SocketAddr = ([0, 0, 0, 0], port).into();
+    let builder = hyper::Server::try_bind(&addr)?;
+
+    let openapi = gateway::openapi("rust-daq", env!("CARGO_PKG_VERSION"));
+    let state = Arc::new(GatewayState {
+        channel: grpc_channel,
+        openapi: Bytes::from(serde_json::to_vec_pretty(&openapi)?),
+    });
+
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let make_service = hyper::service::make_service_fn(move |_conn| {
+        let state = state.clone();
+        async move {
+            Ok::<_, Infallible>(hyper::service::service_fn(move |req| {
+                let state = state.clone();
+                async move { Ok::<_, Infallible>(handle_gateway_request(req, state).await) }
+            }))
+        }
+    });
+    let server = builder.serve(make_service).with_graceful_shutdown(async {
+        let _ = shutdown_rx.await;
+    });
+
+    tracing::info!(port = port, "Starting JSON gateway server");
+
+    tokio::spawn(async move {
+        if let Err(e) = server.await {
+            tracing::error!("JSON gateway server error: {}", e);
+        }
+    });
+
+    Ok(GatewayServerHandle {
+        _shutdown_tx: shutdown_tx,
+    })
+}
+
+async fn handle_gateway_request(
+    req: hyper::Request<hyper::Body>,
+    state: Arc<GatewayState>,
+) -> hyper::Response<hyper::Body> {
+    match (req.method(), req.uri().path()) {
+        (&hyper::Method::GET, "/openapi.json") => hyper::Response::builder()
+            .status(200)
+            .header("Content-Type", "application/json")
+            .body(hyper::Body::from(state.openapi.clone()))
+            .expect("static response"),
+        (&hyper::Method::GET, "/health") => hyper::Response::builder()
+            .status(200)
+            .body(hyper::Body::from("OK"))
+            .expect("static response"),
+        (&hyper::Method::POST, path) if path.starts_with(gateway::GATEWAY_PATH_PREFIX) => {
+            match call_rpc(req, &state).await {
+                Ok(response) => response,
+                Err(status) => error_response(&status),
+            }
+        }
+        _ => error_response(&Status::not_found("No such gateway route")),
+    }
+}
+
+async fn call_rpc(
+    req: hyper::Request<hyper::Body>,
+    state: &GatewayState,
+) -> Result<hyper::Response<hyper::Body>, Status> {
+    let method = gateway::resolve_method(req.uri().path())
+        .ok_or_else(|| Status::not_found(format!("No RPC at {}", req.uri().path())))?;
+
+    let (parts, body) = req.into_parts();
+    if hyper::body::HttpBody::size_hint(&body)
+        .upper()
+        .is_some_and(|len| len > MAX_REQUEST_BYTES)
+    {
+        return Err(Status::invalid_argument("Request body too large"));
+    }
+    let body = hyper::body::to_bytes(body)
+        .await
+        .map_err(|e| Status::invalid_argument(format!("Failed to read request body: {}", e)))?;
+
+    let mut request = tonic::Request::new(gateway::decode_request(&method, &body)?);
+    for name in FORWARDED_HEADERS {
+        if let Some(value) = parts.headers.get(*name).and_then(|v| v.to_str().ok())
+            && let Ok(value) = MetadataValue::try_from(value)
+        {
+            request.metadata_mut().insert(*name, value);
+        }
+    }
+
+    match gateway::call(state.channel.clone(), &method, request).await? {
+        GatewayResponse::Unary(message) => Ok(hyper::Response::builder()
+            .status(200)
+            .header("Content-Type", "application/json")
+            .body(hyper::Body::from(gateway::encode_response(&message)?))
+            .expect("static response")),
+        GatewayResponse::Stream(stream) => {
+            // One message per line; an error ends the stream with an error line
+            let lines = stream.map(|item| {
+                let mut line = match item.and_then(|message| gateway::encode_response(&message)) {
+                    Ok(json) => json,
+                    Err(status) => error_json(&status),
+                };
+                line.push(b'\n');
+                Ok::<_, Infallible>(Bytes::from(line))
+            });
+            Ok(hyper::Response::builder()
+                .status(200)
+                .header("Content-Type", "application/x-ndjson")
+                .body(hyper::Body::wrap_stream(lines))
+                .expect("static response"))
+        }
+    }
+}
+
+fn error_json(status: &Status) -> Vec<u8> {
+    serde_json::to_vec(&serde_json::json!({
+        "code": format!("{:?}", status.code()),
+        "message": status.message(),
+    }))
+    .unwrap_or_default()
+}
+
+fn error_response(status: &Status) -> hyper::Response<hyper::Body> {
+    hyper::Response::builder()
+        .status(http_status(status.code()))
+        .header("Content-Type", "application/json")
+        .body(hyper::Body::from(error_json(status)))
+        .expect("static response")
+}
+
+/// HTTP status for a gRPC code (as in the gRPC HTTP mapping)
+fn http_status(code: Code) -> u16 {
+    match code {
+        Code::Ok => 200,
+        Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => 400,
+        Code::Unauthenticated => 401,
+        Code::PermissionDenied => 403,
+        Code::NotFound => 404,
+        Code::AlreadyExists | Code::Aborted => 409,
+        Code::ResourceExhausted => 429,
+        Code::Cancelled => 499,
+        Code::Unimplemented => 501,
+        Code::Unavailable => 503,
+        Code::DeadlineExceeded => 504,
+        Code::Unknown | Code::Internal | Code::DataLoss => 500,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_body_and_status() {
+        let status = Status::not_found("device 'stage' not found");
+        assert_eq!(http_status(status.code()), 404);
+        let body: serde_json::Value = serde_json::from_slice(&error_json(&status)).unwrap();
+        assert_eq!(body["code"], "NotFound");
+        assert_eq!(body["message"], "device 'stage' not found");
+
+        assert_eq!(http_status(Code::Unauthenticated), 401);
+        assert_eq!(http_status(Code::Unavailable), 503);
+    }
+}
@@ -4,48 +4,121 @@
 //! bypassing the scripting layer. It connects to the DeviceRegistry for
 //! capability-based access to hardware devices.
 
+use crate::device_history::{DeviceHistory, HistoryQuery};
+use crate::grpc::health_service::HealthServiceImpl;
+use crate::grpc::proto::health::health_check_response::ServingStatus;
+use crate::grpc::stream_stats::{StreamStatsRegistry, TrackedStream};
 use crate::grpc::{
     map_daq_error_to_status,
+    parameter_proposals::{DEFAULT_CONFIRM_TIMEOUT, ProposalStore},
     proto::{
+        AbortRampRequest,
+        AbortRampResponse,
+        AbortWarmupRequest,
+        AbortWarmupResponse,
         ArmRequest,
         ArmResponse,
+        BatchCommandResult,
+        BatchCommandStatus,
+        BatchMode,
+        CancelParameterChangeRequest,
+        CancelParameterChangeResponse,
+        CapabilityDescriptor as ProtoCapabilityDescriptor,
+        CapabilityMethod,
+        CapabilityMethodParam,
+        ChannelAlias as ProtoChannelAlias,
+        ChannelDisplayTransform,
         CompressionType,
+        ConfirmParameterChangeRequest,
+        DataQuality,
+        DescribeCapabilitiesRequest,
+        DescribeCapabilitiesResponse,
         DeviceCommandRequest,
         DeviceCommandResponse,
+        DeviceEvent as ProtoDeviceEvent,
+        DeviceEventSeverity as ProtoDeviceEventSeverity,
+        DeviceHistoryRequest,
+        DeviceHistoryResponse,
+        DeviceIdentity as ProtoDeviceIdentity,
         DeviceInfo,
         DeviceMetadata as ProtoDeviceMetadata,
+        DeviceSelfTestResult,
+        DeviceStateAtRequest,
+        DeviceStateAtResponse,
         DeviceStateRequest,
         DeviceStateResponse,
         DeviceStateSubscribeRequest,
         DeviceStateUpdate,
+        DeviceStatus as ProtoDeviceStatus,
+        DeviceStatusState,
+        DeviceTimeSkew,
+        DropCount,
+        ExecuteBatchRequest,
+        ExecuteBatchResponse,
         FrameData,
+        GetDataIntegrityRequest,
+        GetDataIntegrityResponse,
         GetEmissionRequest,
         GetEmissionResponse,
         GetExposureRequest,
         GetExposureResponse,
+        GetFrameAnnotationsRequest,
+        GetFrameAnnotationsResponse,
+        GetInventoryRequest,
+        GetInventoryResponse,
         GetParameterRequest,
+        GetSelfTestReportRequest,
+        GetSelfTestReportResponse,
         GetShutterRequest,
         GetShutterResponse,
         GetWavelengthRequest,
         GetWavelengthResponse,
+        IdentityChange as ProtoIdentityChange,
+        InitRecipeInfo,
+        InitRecipeReport,
+        InitRecipeStepResult,
+        InventoryEntry as ProtoInventoryEntry,
         ListDevicesRequest,
         ListDevicesResponse,
+        ListDisplayTransformsRequest,
+        ListDisplayTransformsResponse,
+        ListInitRecipesRequest,
+        ListInitRecipesResponse,
         ListParametersRequest,
         ListParametersResponse,
+        ListRampsRequest,
+        ListRampsResponse,
+        ListSerialPortsRequest,
+        ListSerialPortsResponse,
+        ListWarmupsRequest,
+        ListWarmupsResponse,
         MoveRequest,
         MoveResponse,
         ObservableValue,
         ParameterChange,
         ParameterDescriptor,
         ParameterValue,
+        PluginUiElement,
         PositionUpdate,
+        RampProfile as ProtoRampProfile,
+        RampState as ProtoRampState,
+        RampStatus as ProtoRampStatus,
         ReadValueRequest,
         ReadValueResponse,
+        RecipeStepStatus,
         RegistrationFailure as ProtoRegistrationFailure,
+        RunInitRecipeRequest,
+        RunSelfTestRequest,
+        SelfTestReport,
+        SerialPortInfo,
+        SetDisplayTransformRequest,
+        SetDisplayTransformResponse,
         SetEmissionRequest,
         SetEmissionResponse,
         SetExposureRequest,
         SetExposureResponse,
+        SetFrameAnnotationsRequest,
+        SetFrameAnnotationsResponse,
         SetParameterRequest,
         SetParameterResponse,
         // Laser control types (bd-pwjo)
@@ -55,40 +128,69 @@ use crate::grpc::{
         SetWavelengthResponse,
         StageDeviceRequest,
         StageDeviceResponse,
+        StartRampRequest,
+        StartRampResponse,
         StartStreamRequest,
         StartStreamResponse,
         StopMotionRequest,
         StopMotionResponse,
         StopStreamRequest,
         StopStreamResponse,
+        StreamDeviceEventsRequest,
         // Stream quality for server-side downsampling
         StreamFramesRequest,
         StreamObservablesRequest,
         StreamParameterChangesRequest,
         StreamPositionRequest,
         StreamQuality,
+        StreamRampEventsRequest,
         StreamValuesRequest,
         StreamingMetrics,
+        TimeSyncReport,
         TriggerRequest,
         TriggerResponse,
+        TypedValue,
         UnstageDeviceRequest,
         UnstageDeviceResponse,
         ValueUpdate,
+        VerifyTimeSyncRequest,
         WaitSettledRequest,
         WaitSettledResponse,
+        WarmupInfo,
+        WarmupState as ProtoWarmupState,
+        batch_command::Command as BatchCommandKind,
         hardware_service_server::HardwareService,
+        typed_value::Kind as TypedKind,
     },
 };
 use anyhow::Error as AnyError;
+use common::acquisition::AcquiredValue;
 use common::capabilities::FrameObserver;
+use common::core::ParameterValue as TypedParameterValue;
 use common::data::FrameView;
-use common::driver::Capability;
+use common::data_record::DataRecord;
+use common::device_events::{DeviceEvent, DeviceEventSeverity};
+use common::driver::{Capability, DeviceIdentity, DeviceStatus};
 use common::error::DaqError;
+use common::integrity::{DropReport, total_dropped};
 use common::limits::{FPS_WINDOW, MAX_STREAMS_PER_CLIENT, RPC_TIMEOUT};
+use common::listing::{ListFilter, paginate};
 use common::observable::Observable;
+use common::on_change::{Deadbands, OnChangeFilter};
 use common::parameter::Parameter;
-use hardware::registry::DeviceRegistry;
+use common::ramp::{DEFAULT_STEP_INTERVAL_MS, RampProfile, RampSpec, RampState, RampStatus};
+use hardware::display_calibration::DisplayTransform;
+use hardware::inventory::{IdentitySource, InventoryReport};
+use hardware::plugin::schema::UiElement;
+use hardware::port_resolver::{PortMetadata, enumerate_ports};
+use hardware::recipes::{RecipeReport, RecipeTrigger, StepResult, StepStatus};
+use hardware::registry::{DeviceLocks, DeviceRegistry};
+use hardware::self_test::{TestReport, run_self_test};
+use hardware::time_sync::{Stimulus, TimeSyncRequest, verify_time_sync};
+use hardware::warmup::{WarmupSchedule, WarmupScheduler, WarmupState, WarmupStatus};
+use protocol::convert::ToDomain;
 use protocol::downsample::{downsample_2x2, downsample_4x4};
+use protocol::frame_tiles::{TileEncoder, TileSettings, payload_len};
 use serde_json;
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, VecDeque};
@@ -314,12 +416,27 @@ impl StreamLimiter {
 ///
 /// Provides direct access to hardware devices through the DeviceRegistry.
 /// All hardware operations are delegated to the appropriate capability traits.
+/// Marks requests issued by ExecuteBatch, which already holds the locks of
+/// every device in the batch
+#[derive(Debug, Clone, Copy)]
+struct BatchLocked;
+
 pub struct HardwareServiceImpl {
     registry: Arc<DeviceRegistry>,
     /// Per-client stream limiter for DoS prevention (bd-64hu)
     stream_limiter: Arc<StreamLimiter>,
     /// Broadcast sender for parameter changes (enables real-time GUI synchronization)
     param_change_tx: tokio::sync::broadcast::Sender<ParameterChange>,
+    /// Pending changes to dangerous parameters awaiting confirmation
+    proposals: Arc<ProposalStore>,
+    /// Recorded parameter and setpoint changes for history queries
+    history: Arc<DeviceHistory>,
+    /// Warm-up schedules, if configured
+    warmups: Option<Arc<WarmupScheduler>>,
+    /// Last self-test report
+    self_test_report: Arc<std::sync::RwLock<Option<TestReport>>>,
+    /// Readiness gated on the self-test, if wired to the health service
+    readiness: Option<HealthServiceImpl>,
 }
 
 impl HardwareServiceImpl {
@@ -398,10 +515,18 @@ impl HardwareServiceImpl {
             }
         });
 
+        let history = Arc::new(DeviceHistory::default());
+        history.spawn_recorder(param_change_tx.subscribe());
+
         Self {
             registry,
             stream_limiter: Arc::new(StreamLimiter::new()),
             param_change_tx,
+            proposals: Arc::new(ProposalStore::new()),
+            history,
+            warmups: None,
+            self_test_report: Arc::new(std::sync::RwLock::new(None)),
+            readiness: None,
         }
     }
 
@@ -411,10 +536,18 @@ impl HardwareServiceImpl {
         registry: Arc<DeviceRegistry>,
         param_change_tx: tokio::sync::broadcast::Sender<ParameterChange>,
     ) -> Self {
+        let history = Arc::new(DeviceHistory::default());
+        history.spawn_recorder(param_change_tx.subscribe());
+
         Self {
             registry,
             stream_limiter: Arc::new(StreamLimiter::new()),
             param_change_tx,
+            proposals: Arc::new(ProposalStore::new()),
+            history,
+            warmups: None,
+            self_test_report: Arc::new(std::sync::RwLock::new(None)),
+            readiness: None,
         }
     }
 
@@ -422,6 +555,408 @@ impl HardwareServiceImpl {
     pub fn param_change_sender(&self) -> tokio::sync::broadcast::Sender<ParameterChange> {
         self.param_change_tx.clone()
     }
+
+    /// Keep device history for `retention` instead of the default 24 h
+    pub fn with_history_retention(self, retention: Duration) -> Self {
+        self.history.set_retention(retention);
+        self
+    }
+
+    /// Report and abort warm-ups run by `scheduler`
+    pub fn with_warmups(mut self, scheduler: Arc<WarmupScheduler>) -> Self {
+        self.warmups = Some(scheduler);
+        self
+    }
+
+    /// Report self-test results as the daemon's serving status: critical
+    /// failures mark the server and HardwareService `NOT_SERVING` until a
+    /// later self-test passes
+    pub fn with_readiness(mut self, health: HealthServiceImpl) -> Self {
+        self.readiness = Some(health);
+        self
+    }
+
+    /// Run the configured self-test, keep its report and update readiness
+    pub async fn run_configured_self_test(&self) -> TestReport {
+        let report = run_self_test(&self.registry.self_test(), self.registry.as_ref()).await;
+        if let Some(health) = &self.readiness {
+            let status = if report.ready() {
+                ServingStatus::Serving
+            } else {
+                ServingStatus::NotServing
+            };
+            health.set_serving_status("", status);
+            health.set_serving_status("daq.HardwareService", status);
+        }
+        *self
+            .self_test_report
+            .write()
+            .unwrap_or_else(|p| p.into_inner()) = Some(report.clone());
+        report
+    }
+
+    /// Recorded parameter and setpoint changes
+    pub fn history(&self) -> Arc<DeviceHistory> {
+        self.history.clone()
+    }
+
+    /// Record a completed move in the device history
+    ///
+    /// Moves are setpoints, not parameter sets, so they don't appear on the
+    /// parameter change stream; recording them lets the position of stages
+    /// and rotators be reconstructed for any data timestamp.
+    fn record_motion(&self, device_id: &str, target: Option<f64>, position: f64) {
+        let device_id = self.registry.resolve_channel(device_id).device_id;
+        let event = |name: &str, value: f64| ParameterChange {
+            device_id: device_id.clone(),
+            name: name.to_string(),
+            old_value: String::new(),
+            new_value: value.to_string(),
+            units: String::new(),
+            timestamp_ns: now_ns(),
+            source: "user".to_string(),
+        };
+        if let Some(target) = target {
+            self.history.record(event("target_position", target));
+        }
+        if position.is_finite() {
+            self.history.record(event("position", position));
+        }
+    }
+
+    /// Whether a parameter is dangerous, from driver metadata or config policy
+    fn is_dangerous(&self, device_id: &str, parameter: &str) -> bool {
+        let device_id = self.registry.resolve_channel(device_id).device_id;
+        let by_policy = self
+            .registry
+            .parameter_policy(&device_id, parameter)
+            .is_some_and(|policy| policy.dangerous);
+        by_policy
+            || self
+                .registry
+                .get_parameterized(&device_id)
+                .and_then(|device| {
+                    device
+                        .parameters()
+                        .get(parameter)
+                        .map(|param| param.metadata().dangerous)
+                })
+                .unwrap_or(false)
+    }
+
+    /// How long a proposal for this parameter stays confirmable
+    fn confirm_timeout(&self, device_id: &str, parameter: &str) -> Duration {
+        let device_id = self.registry.resolve_channel(device_id).device_id;
+        self.registry
+            .parameter_policy(&device_id, parameter)
+            .and_then(|policy| policy.confirm_timeout_s)
+            .map_or(DEFAULT_CONFIRM_TIMEOUT, Duration::from_secs)
+    }
+
+    /// Take the command locks of devices, waiting at most `timeout`
+    async fn lock_devices<I, S>(&self, ids: I, timeout: Duration) -> Result<DeviceLocks, Status>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        tokio::time::timeout(timeout, self.registry.lock_devices(ids))
+            .await
+            .map_err(|_| {
+                Status::aborted(format!(
+                    "Device is busy with another command (waited {:?})",
+                    timeout
+                ))
+            })
+    }
+
+    /// Command lock of the device a request changes, unless it was issued by
+    /// ExecuteBatch, which holds the locks of all its devices
+    async fn command_lock<T>(
+        &self,
+        request: &Request<T>,
+        device_id: &str,
+    ) -> Result<Option<DeviceLocks>, Status> {
+        if request.extensions().get::<BatchLocked>().is_some() {
+            return Ok(None);
+        }
+        self.lock_devices([device_id], RPC_TIMEOUT).await.map(Some)
+    }
+
+    /// Refuse a batch command that can't succeed, before anything is applied
+    fn check_batch_command(&self, command: &BatchCommandKind) -> Result<(), Status> {
+        let device_id = batch_device_id(command);
+        let supported = match command {
+            BatchCommandKind::SetParameter(req) => {
+                if self.is_dangerous(&req.device_id, &req.parameter_name) {
+                    return Err(dangerous_in_batch(&req.parameter_name));
+                }
+                self.registry.get_settable(device_id).is_some()
+                    || self.registry.get_parameterized(device_id).is_some()
+            }
+            BatchCommandKind::MoveAbsolute(_) | BatchCommandKind::MoveRelative(_) => {
+                self.registry.get_movable(device_id).is_some()
+            }
+            BatchCommandKind::SetShutter(_) => {
+                self.registry.get_shutter_control(device_id).is_some()
+            }
+            BatchCommandKind::SetWavelength(_) => {
+                self.registry.get_wavelength_tunable(device_id).is_some()
+            }
+            BatchCommandKind::DeviceCommand(_) => {
+                self.registry.get_commandable(device_id).is_some()
+            }
+        };
+        if supported {
+            Ok(())
+        } else {
+            Err(Status::failed_precondition(format!(
+                "Device '{}' not found or does not support {}",
+                device_id,
+                batch_command_name(command)
+            )))
+        }
+    }
+
+    /// Run one batch command through its RPC, returning its result as a string
+    async fn run_batch_command(&self, command: BatchCommandKind) -> Result<String, Status> {
+        fn batched<T>(message: T) -> Request<T> {
+            let mut request = Request::new(message);
+            request.extensions_mut().insert(BatchLocked);
+            request
+        }
+        match command {
+            BatchCommandKind::SetParameter(req) => {
+                // A proposal could never be confirmed while the batch runs
+                if self.is_dangerous(&req.device_id, &req.parameter_name) {
+                    return Err(dangerous_in_batch(&req.parameter_name));
+                }
+                let response = self.set_parameter(batched(req)).await?.into_inner();
+                if response.success {
+                    Ok(response.actual_value)
+                } else {
+                    Err(Status::failed_precondition(response.error_message))
+                }
+            }
+            BatchCommandKind::MoveAbsolute(req) => Ok(self
+                .move_absolute(batched(req))
+                .await?
+                .into_inner()
+                .final_position
+                .to_string()),
+            BatchCommandKind::MoveRelative(req) => Ok(self
+                .move_relative(batched(req))
+                .await?
+                .into_inner()
+                .final_position
+                .to_string()),
+            BatchCommandKind::SetShutter(req) => Ok(self
+                .set_shutter(batched(req))
+                .await?
+                .into_inner()
+                .is_open
+                .to_string()),
+            BatchCommandKind::SetWavelength(req) => Ok(self
+                .set_wavelength(batched(req))
+                .await?
+                .into_inner()
+                .actual_wavelength_nm
+                .to_string()),
+            BatchCommandKind::DeviceCommand(req) => Ok(self
+                .execute_device_command(batched(req))
+                .await?
+                .into_inner()
+                .results),
+        }
+    }
+
+    /// Command restoring what `command` is about to change, if it has one
+    ///
+    /// Device commands are opaque and have no inverse.
+    async fn undo_command(&self, command: &BatchCommandKind) -> Option<BatchCommandKind> {
+        match command {
+            BatchCommandKind::SetParameter(req) => {
+                let previous = self
+                    .get_parameter(Request::new(GetParameterRequest {
+                        device_id: req.device_id.clone(),
+                        parameter_name: req.parameter_name.clone(),
+                    }))
+                    .await
+                    .ok()?
+                    .into_inner();
+                Some(BatchCommandKind::SetParameter(SetParameterRequest {
+                    device_id: req.device_id.clone(),
+                    parameter_name: previous.name,
+                    value: previous.value,
+                    typed_value: previous.typed,
+                }))
+            }
+            BatchCommandKind::MoveAbsolute(req) | BatchCommandKind::MoveRelative(req) => {
+                let position = self
+                    .registry
+                    .get_movable(&req.device_id)?
+                    .position()
+                    .await
+                    .ok()?;
+                Some(BatchCommandKind::MoveAbsolute(MoveRequest {
+                    device_id: req.device_id.clone(),
+                    value: position,
+                    wait_for_completion: Some(true),
+                    timeout_ms: req.timeout_ms,
+                }))
+            }
+            BatchCommandKind::SetShutter(req) => {
+                let is_open = self
+                    .registry
+                    .get_shutter_control(&req.device_id)?
+                    .is_shutter_open()
+                    .await
+                    .ok()?;
+                Some(BatchCommandKind::SetShutter(SetShutterRequest {
+                    device_id: req.device_id.clone(),
+                    open: is_open,
+                }))
+            }
+            BatchCommandKind::SetWavelength(req) => {
+                let wavelength_nm = self
+                    .registry
+                    .get_wavelength_tunable(&req.device_id)?
+                    .get_wavelength()
+                    .await
+                    .ok()?;
+                Some(BatchCommandKind::SetWavelength(SetWavelengthRequest {
+                    device_id: req.device_id.clone(),
+                    wavelength_nm,
+                }))
+            }
+            BatchCommandKind::DeviceCommand(_) => None,
+        }
+    }
+
+    /// Apply a parameter change (shared by SetParameter and confirmed proposals)
+    async fn apply_parameter(
+        &self,
+        req: SetParameterRequest,
+    ) -> Result<Response<SetParameterResponse>, Status> {
+        // Try legacy Settable trait first (backwards compatibility)
+        if let Some(settable) = self.registry.get_settable(&req.device_id) {
+            // Get old value before setting (for change notification)
+            let old_value = settable
+                .get_value(&req.parameter_name)
+                .await
+                .map(|v| v.to_string())
+                .unwrap_or_default();
+
+            // Parse the value string to JSON
+            let json_value: serde_json::Value = serde_json::from_str(&req.value)
+                .or_else(|_| {
+                    // Try as raw string if JSON parsing fails
+                    Ok::<_, serde_json::Error>(serde_json::Value::String(req.value.clone()))
+                })
+                .map_err(|e| Status::invalid_argument(format!("Invalid value format: {}", e)))?;
+
+            // Set the parameter
+            settable
+                .set_value(&req.parameter_name, json_value)
+                .await
+                .map_err(|e| Status::invalid_argument(format!("Failed to set parameter: {}", e)))?;
+
+            // Read back the actual value
+            let actual_json = settable.get_value(&req.parameter_name).await.ok();
+            let actual_typed = actual_json
+                .as_ref()
+                .and_then(|v| typed_value_to_proto(&TypedParameterValue::from_json(v)));
+            let actual_value = actual_json.map_or_else(|| req.value.clone(), |v| v.to_string());
+
+            // Broadcast parameter change notification (ignore send errors - no subscribers is ok)
+            let _ = self.param_change_tx.send(ParameterChange {
+                device_id: req.device_id.clone(),
+                name: req.parameter_name.clone(),
+                old_value,
+                new_value: actual_value.clone(),
+                units: String::new(), // Would need parameter metadata for units
+                timestamp_ns: now_ns(),
+                source: "user".to_string(),
+            });
+
+            return Ok(Response::new(SetParameterResponse {
+                success: true,
+                error_message: String::new(),
+                actual_value,
+                confirmation_required: false,
+                proposal_token: String::new(),
+                proposal_expires_ns: 0,
+                actual_typed,
+            }));
+        }
+
+        // New path - use Parameterized trait
+        if let Some(parameterized) = self.registry.get_parameterized(&req.device_id) {
+            let params = parameterized.parameters();
+
+            if let Some(param) = params.get(&req.parameter_name) {
+                let old_value = self
+                    .registry
+                    .get_parameter_json(&req.device_id, param)
+                    .map(|v| v.to_string())
+                    .unwrap_or_default();
+
+                // Parse the value string to JSON
+                let json_value: serde_json::Value = serde_json::from_str(&req.value)
+                    .or_else(|_| {
+                        // Try as raw string if JSON parsing fails
+                        Ok::<_, serde_json::Error>(serde_json::Value::String(req.value.clone()))
+                    })
+                    .map_err(|e| {
+                        Status::invalid_argument(format!("Invalid value format: {}", e))
+                    })?;
+
+                // Set the parameter within its output limit
+                self.registry
+                    .set_parameter_json(&req.device_id, param, json_value)
+                    .await
+                    .map_err(|e| {
+                        Status::invalid_argument(format!("Failed to set parameter: {}", e))
+                    })?;
+
+                let actual_value = self
+                    .registry
+                    .get_parameter_json(&req.device_id, param)
+                    .map(|v| v.to_string())
+                    .unwrap_or_else(|_| req.value.clone());
+
+                // Broadcast parameter change notification
+                let _ = self.param_change_tx.send(ParameterChange {
+                    device_id: req.device_id.clone(),
+                    name: req.parameter_name.clone(),
+                    old_value,
+                    new_value: actual_value.clone(),
+                    units: String::new(), // Could get from metadata
+                    timestamp_ns: now_ns(),
+                    source: "user".to_string(),
+                });
+
+                return Ok(Response::new(SetParameterResponse {
+                    success: true,
+                    error_message: String::new(),
+                    actual_value,
+                    confirmation_required: false,
+                    proposal_token: String::new(),
+                    proposal_expires_ns: 0,
+                    actual_typed: param
+                        .get_typed()
+                        .ok()
+                        .as_ref()
+                        .and_then(typed_value_to_proto),
+                }));
+            }
+        }
+
+        // Neither Settable nor Parameterized - device not found
+        Err(Status::not_found(format!(
+            "Device '{}' does not support settable parameters",
+            req.device_id
+        )))
+    }
 }
 
 /// Helper macro to reduce boilerplate for capability lookups
@@ -461,7 +996,10 @@ impl HardwareService for HardwareServiceImpl {
     ) -> Result<Response<ListDevicesResponse>, Status> {
         let req = request.into_inner();
 
-        let devices: Vec<DeviceInfo> = if let Some(capability_filter) = req.capability_filter {
+        let filter = ListFilter::parse(&req.filter, DEVICE_FILTER_KEYS)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        let mut devices: Vec<DeviceInfo> = if let Some(capability_filter) = req.capability_filter {
             // Filter by capability
             let cap = match capability_filter.to_lowercase().as_str() {
                 "movable" => Capability::Movable,
@@ -469,12 +1007,9 @@ impl HardwareService for HardwareServiceImpl {
                 "triggerable" => Capability::Triggerable,
                 "frame_producer" | "frameproducer" => Capability::FrameProducer,
                 "exposure_control" | "exposurecontrol" => Capability::ExposureControl,
-                _ => {
-                    return Err(Status::invalid_argument(format!(
-                        "Unknown capability: {}",
-                        capability_filter
-                    )));
-                }
+                other => Capability::from_str_name(other).ok_or_else(|| {
+                    Status::invalid_argument(format!("Unknown capability: {}", capability_filter))
+                })?,
             };
 
             self.registry
@@ -492,11 +1027,26 @@ impl HardwareService for HardwareServiceImpl {
                 .collect()
         };
 
-        // Include registration failures for debugging visibility
+        devices.retain(|d| filter.matches(|key| device_filter_values(d, key)));
+        sort_devices(&mut devices, &req.sort_by, req.descending)?;
+        let total_count = devices.len() as u32;
+        let devices = paginate(devices, req.offset as usize, req.limit as usize);
+
+        // Include registration failures for debugging visibility; they count
+        // as offline devices for filtering
         let registration_failures: Vec<ProtoRegistrationFailure> = self
             .registry
             .list_registration_failures()
             .into_iter()
+            .filter(|f| {
+                filter.matches(|key| match key {
+                    "id" => vec![f.device_id.clone()],
+                    "name" => vec![f.device_name.clone()],
+                    "driver_type" => vec![f.driver_type.clone()],
+                    "online" => vec!["false".to_string()],
+                    _ => vec![],
+                })
+            })
             .map(|f| ProtoRegistrationFailure {
                 device_id: f.device_id,
                 device_name: f.device_name,
@@ -512,9 +1062,46 @@ impl HardwareService for HardwareServiceImpl {
             );
         }
 
+        let channel_aliases = self
+            .registry
+            .aliases()
+            .iter()
+            .map(|(alias, target)| ProtoChannelAlias {
+                alias: alias.to_string(),
+                target: target.to_string(),
+            })
+            .collect();
+
         Ok(Response::new(ListDevicesResponse {
             devices,
             registration_failures,
+            channel_aliases,
+            total_count,
+        }))
+    }
+
+    #[instrument(skip(self, request), fields(method = "describe_capabilities"))]
+    async fn describe_capabilities(
+        &self,
+        request: Request<DescribeCapabilitiesRequest>,
+    ) -> Result<Response<DescribeCapabilitiesResponse>, Status> {
+        let req = request.into_inner();
+        let (Some(info), Some(reports)) = (
+            self.registry.get_device_info(&req.device_id),
+            self.registry.introspect_capabilities(&req.device_id),
+        ) else {
+            return Err(Status::not_found(format!(
+                "Device not found: {}",
+                req.device_id
+            )));
+        };
+
+        Ok(Response::new(DescribeCapabilitiesResponse {
+            device_id: info.id,
+            capabilities: reports
+                .into_iter()
+                .map(capability_report_to_proto)
+                .collect(),
         }))
     }
 
@@ -544,9 +1131,14 @@ impl HardwareService for HardwareServiceImpl {
             )));
         }
 
+        let status = self
+            .registry
+            .device_status(&req.device_id)
+            .unwrap_or(DeviceStatus::Ready);
         let mut response = DeviceStateResponse {
             device_id: req.device_id.clone(),
-            online: true,
+            online: status.is_operational(),
+            status: Some(device_status_to_proto(&status)),
             position: None,
             last_reading: None,
             armed: None,
@@ -646,7 +1238,12 @@ impl HardwareService for HardwareServiceImpl {
             loop {
                 ticker.tick().await;
                 for device_id in device_ids.iter() {
-                    let state = match fetch_device_state(&registry, device_id).await {
+                    let Some(state) =
+                        unless_disconnected(&tx, fetch_device_state(&registry, device_id)).await
+                    else {
+                        return;
+                    };
+                    let state = match state {
                         Ok(s) => s,
                         Err(status) => {
                             let _ = tx.send(Err(status)).await;
@@ -700,6 +1297,9 @@ impl HardwareService for HardwareServiceImpl {
         &self,
         request: Request<MoveRequest>,
     ) -> Result<Response<MoveResponse>, Status> {
+        let _lock = self
+            .command_lock(&request, &request.get_ref().device_id)
+            .await?;
         let req = request.into_inner();
 
         // Extract Arc without lock before awaiting hardware
@@ -757,6 +1357,7 @@ impl HardwareService for HardwareServiceImpl {
             })?;
             (pos, None)
         };
+        self.record_motion(&req.device_id, Some(req.value), final_position);
 
         Ok(Response::new(MoveResponse {
             success: true,
@@ -771,6 +1372,9 @@ impl HardwareService for HardwareServiceImpl {
         &self,
         request: Request<MoveRequest>,
     ) -> Result<Response<MoveResponse>, Status> {
+        let _lock = self
+            .command_lock(&request, &request.get_ref().device_id)
+            .await?;
         let req = request.into_inner();
 
         // Extract Arc without lock before awaiting hardware
@@ -828,6 +1432,7 @@ impl HardwareService for HardwareServiceImpl {
             })?;
             (pos, None)
         };
+        self.record_motion(&req.device_id, None, final_position);
 
         Ok(Response::new(MoveResponse {
             success: true,
@@ -842,6 +1447,7 @@ impl HardwareService for HardwareServiceImpl {
         &self,
         request: Request<StopMotionRequest>,
     ) -> Result<Response<StopMotionResponse>, Status> {
+        // No command lock: a stop must never wait behind a batch
         let req = request.into_inner();
 
         // Extract Arc without lock before awaiting hardware
@@ -945,7 +1551,10 @@ impl HardwareService for HardwareServiceImpl {
                 let movable = registry.get_movable(&device_id);
 
                 if let Some(movable) = movable {
-                    let position = movable.position().await.unwrap_or(f64::NAN);
+                    let Some(position) = unless_disconnected(&tx, movable.position()).await else {
+                        break; // Client disconnected
+                    };
+                    let position = position.unwrap_or(f64::NAN);
                     let is_moving = (position - last_position).abs() > 0.0001;
                     last_position = position;
 
@@ -998,15 +1607,28 @@ impl HardwareService for HardwareServiceImpl {
             .and_then(|info| info.metadata.measurement_units.clone())
             .unwrap_or_default();
 
-        let value = self
-            .await_with_timeout("read_value", readable.read())
+        let mode = req.mode.map(ToDomain::to_domain).unwrap_or_default();
+        mode.validate()
+            .map_err(|e| Status::invalid_argument(format!("{:#}", e)))?;
+
+        let acquired = self
+            .await_with_timeout("read_value", readable.read_with_mode(mode))
             .await?;
+        let AcquiredValue {
+            value,
+            quality,
+            samples,
+            native,
+            ..
+        } = acquired;
 
         tracing::debug!(
-            "read_value response: device_id={}, value={}, units='{}'",
+            "read_value response: device_id={}, value={}, units='{}', quality={}, mode={}",
             req.device_id,
             value,
-            units
+            units,
+            quality,
+            mode
         );
 
         Ok(Response::new(ReadValueResponse {
@@ -1018,15 +1640,25 @@ impl HardwareService for HardwareServiceImpl {
                 .duration_since(UNIX_EPOCH)
                 .unwrap_or_default()
                 .as_nanos() as u64,
+            quality: DataQuality::from(quality) as i32,
+            mode: Some(mode.into()),
+            samples,
+            native_mode: native,
         }))
     }
 
-    type StreamValuesStream = tokio_stream::wrappers::ReceiverStream<Result<ValueUpdate, Status>>;
+    type StreamValuesStream =
+        TrackedStream<tokio_stream::wrappers::ReceiverStream<Result<ValueUpdate, Status>>>;
 
     async fn stream_values(
         &self,
         request: Request<StreamValuesRequest>,
     ) -> Result<Response<Self::StreamValuesStream>, Status> {
+        let stats = StreamStatsRegistry::global().open(
+            "HardwareService/StreamValues",
+            request.get_ref().device_id.clone(),
+            &request,
+        );
         let req = request.into_inner();
         let registry = self.registry.clone();
         let device_id = req.device_id.clone();
@@ -1057,15 +1689,20 @@ impl HardwareService for HardwareServiceImpl {
                     .unwrap_or_default();
 
                 if let Some(readable) = readable {
-                    if let Ok(value) = readable.read().await {
-                        let update = ValueUpdate {
-                            device_id: device_id.clone(),
-                            value,
-                            units,
-                            timestamp_ns: SystemTime::now()
-                                .duration_since(UNIX_EPOCH)
-                                .unwrap_or_default()
-                                .as_nanos() as u64,
+                    let Some(reading) = unless_disconnected(&tx, readable.read_qualified()).await
+                    else {
+                        break;
+                    };
+                    if let Ok((value, quality)) = reading {
+                        let timestamp_ns = SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .unwrap_or_default()
+                            .as_nanos() as u64;
+                        let record =
+                            DataRecord::scalar(&device_id, "value", value, units, timestamp_ns)
+                                .with_quality(quality);
+                        let Some(update) = ValueUpdate::from_record(&record, "value") else {
+                            continue;
                         };
 
                         if tx.send(Ok(update)).await.is_err() {
@@ -1078,9 +1715,9 @@ impl HardwareService for HardwareServiceImpl {
             }
         });
 
-        Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(
-            rx,
-        )))
+        Ok(Response::new(
+            stats.track(tokio_stream::wrappers::ReceiverStream::new(rx)),
+        ))
     }
 
     // =========================================================================
@@ -1236,6 +1873,9 @@ impl HardwareService for HardwareServiceImpl {
         &self,
         request: Request<SetShutterRequest>,
     ) -> Result<Response<SetShutterResponse>, Status> {
+        let _lock = self
+            .command_lock(&request, &request.get_ref().device_id)
+            .await?;
         let req = request.into_inner();
 
         let shutter_ctrl = require_capability!(
@@ -1290,6 +1930,9 @@ impl HardwareService for HardwareServiceImpl {
         &self,
         request: Request<SetWavelengthRequest>,
     ) -> Result<Response<SetWavelengthResponse>, Status> {
+        let _lock = self
+            .command_lock(&request, &request.get_ref().device_id)
+            .await?;
         let req = request.into_inner();
 
         let wavelength_ctrl = require_capability!(
@@ -1476,7 +2119,7 @@ impl HardwareService for HardwareServiceImpl {
         }
     }
 
-    type StreamFramesStream = ReceiverStream<Result<FrameData, Status>>;
+    type StreamFramesStream = TrackedStream<ReceiverStream<Result<FrameData, Status>>>;
 
     /// Stream frames from a FrameProducer device to GUI clients (bd-0dax.6.3).
     ///
@@ -1501,13 +2144,26 @@ impl HardwareService for HardwareServiceImpl {
             .map(|addr| addr.ip())
             .unwrap_or_else(|| IpAddr::V4(std::net::Ipv4Addr::LOCALHOST));
 
+        TileSettings::from_request(request.get_ref())
+            .validate()
+            .map_err(Status::invalid_argument)?;
+
         // Check per-client stream limit (bd-64hu)
         self.stream_limiter.try_acquire(client_ip)?;
 
+        let stats = StreamStatsRegistry::global().open(
+            "HardwareService/StreamFrames",
+            request.get_ref().device_id.clone(),
+            &request,
+        );
+        let stream_entry = stats.entry();
         let req = request.into_inner();
         let device_id = req.device_id.clone();
         let max_fps = req.max_fps;
         let quality = req.quality();
+        // Chunked delivery: keyframes plus changed tiles in between
+        let tile_encoder =
+            TileEncoder::from_request(&req).map(|e| Arc::new(std::sync::Mutex::new(e)));
 
         // Get frame producer
         let frame_producer = require_capability!(
@@ -1573,6 +2229,7 @@ impl HardwareService for HardwareServiceImpl {
         let device_id_clone = device_id.clone();
         let frame_producer_clone = frame_producer.clone();
         let stream_limiter_clone = self.stream_limiter.clone();
+        let annotation_store = self.registry.frame_annotations();
         tokio::spawn(async move {
             // Initialize to allow first frame through immediately
             let mut last_frame_time = match min_interval {
@@ -1625,6 +2282,7 @@ impl HardwareService for HardwareServiceImpl {
                         let queue_len = GRPC_CHANNEL_CAPACITY - grpc_tx.capacity();
                         if queue_len >= GRPC_SKIP_THRESHOLD {
                             frames_dropped = frames_dropped.saturating_add(1);
+                            stream_entry.record_dropped(1);
                             if frames_dropped % 10 == 1 {
                                 tracing::debug!(
                                     device_id = %device_id_clone,
@@ -1686,6 +2344,12 @@ impl HardwareService for HardwareServiceImpl {
 
                         // Build FrameData proto and apply compression in blocking task
                         let device_id_for_frame = device_id_clone.clone();
+                        let tile_encoder = tile_encoder.clone();
+                        let annotations = annotation_store
+                            .get(&device_id_clone)
+                            .into_iter()
+                            .map(Into::into)
+                            .collect();
                         let processing_result = tokio::task::spawn_blocking(move || {
                             let mut frame_data = FrameData {
                                 device_id: device_id_for_frame,
@@ -1708,12 +2372,22 @@ impl HardwareService for HardwareServiceImpl {
                                 metrics: Some(metrics),
                                 compression: CompressionType::CompressionNone as i32,
                                 uncompressed_size: 0,
+                                delta: false,
+                                tiles: Vec::new(),
+                                annotations,
                             };
 
-                            // Apply LZ4 compression (bd-7rk0)
                             let uncompressed_size = frame_data.data.len();
+                            if let Some(encoder) = &tile_encoder {
+                                encoder
+                                    .lock()
+                                    .unwrap_or_else(|p| p.into_inner())
+                                    .encode(&mut frame_data);
+                            }
+
+                            // Apply LZ4 compression (bd-7rk0)
                             crate::grpc::compression::compress_frame(&mut frame_data);
-                            let compressed_size = frame_data.data.len();
+                            let compressed_size = payload_len(&frame_data);
 
                             (frame_data, uncompressed_size, compressed_size)
                         })
@@ -1843,7 +2517,7 @@ impl HardwareService for HardwareServiceImpl {
             );
         });
 
-        Ok(Response::new(ReceiverStream::new(grpc_rx)))
+        Ok(Response::new(stats.track(ReceiverStream::new(grpc_rx))))
     }
 
     // =========================================================================
@@ -1954,6 +2628,9 @@ impl HardwareService for HardwareServiceImpl {
         &self,
         request: Request<DeviceCommandRequest>,
     ) -> Result<Response<DeviceCommandResponse>, Status> {
+        let _lock = self
+            .command_lock(&request, &request.get_ref().device_id)
+            .await?;
         let req = request.into_inner();
 
         // Try the new generic Commandable interface first
@@ -1998,31 +2675,348 @@ impl HardwareService for HardwareServiceImpl {
         )))
     }
 
-    // =========================================================================
-    // Observable Parameters (QCodes/ScopeFoundry pattern)
-    // =========================================================================
-
-    async fn list_parameters(
+    #[instrument(skip(self, request), fields(method = "execute_batch"))]
+    async fn execute_batch(
         &self,
-        request: Request<ListParametersRequest>,
-    ) -> Result<Response<ListParametersResponse>, Status> {
+        request: Request<ExecuteBatchRequest>,
+    ) -> Result<Response<ExecuteBatchResponse>, Status> {
         let req = request.into_inner();
+        let all_or_nothing = req.mode() == BatchMode::AllOrNothing;
+        let commands = req
+            .commands
+            .into_iter()
+            .enumerate()
+            .map(|(index, command)| {
+                command.command.ok_or_else(|| {
+                    Status::invalid_argument(format!("Batch command {} has no command set", index))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
-        // Check if device exists
-        if !self.registry.contains(&req.device_id) {
-            return Err(Status::not_found(format!(
-                "Device '{}' not found",
-                req.device_id
-            )));
+        // Nothing is applied for a batch that can't complete
+        if all_or_nothing {
+            for command in &commands {
+                self.check_batch_command(command)?;
+            }
         }
 
-        let mut parameters = Vec::new();
+        let lock_timeout = match req.lock_timeout_ms {
+            0 => RPC_TIMEOUT,
+            ms => Duration::from_millis(u64::from(ms)),
+        };
+        let _locks = self
+            .lock_devices(commands.iter().map(batch_device_id), lock_timeout)
+            .await?;
 
-        // 1. Get V5 parameters from Parameterized devices
-        if let Some(parameterized) = self.registry.get_parameterized(&req.device_id) {
-            let param_set = parameterized.parameters();
-            for param_name in param_set.names() {
-                if let Some(param) = param_set.get(param_name) {
+        let mut results = Vec::with_capacity(commands.len());
+        let mut completed = Vec::new();
+        let mut first_error: Option<String> = None;
+        for (index, command) in commands.into_iter().enumerate() {
+            let mut result = BatchCommandResult {
+                index: index as u32,
+                device_id: batch_device_id(&command).to_string(),
+                ..Default::default()
+            };
+            if all_or_nothing && first_error.is_some() {
+                result.set_status(BatchCommandStatus::BatchCommandSkipped);
+                results.push(result);
+                continue;
+            }
+
+            let undo = if all_or_nothing {
+                self.undo_command(&command).await
+            } else {
+                None
+            };
+            let name = batch_command_name(&command);
+            match self.run_batch_command(command).await {
+                Ok(value) => {
+                    result.result = value;
+                    completed.push((index, undo));
+                }
+                Err(status) => {
+                    result.set_status(BatchCommandStatus::BatchCommandFailed);
+                    result.error_message = status.message().to_string();
+                    first_error.get_or_insert_with(|| {
+                        format!(
+                            "Command {} ({} on '{}') failed: {}",
+                            index,
+                            name,
+                            result.device_id,
+                            status.message()
+                        )
+                    });
+                }
+            }
+            results.push(result);
+        }
+
+        // Undo in reverse order, so each command sees the state it started from
+        if all_or_nothing && first_error.is_some() {
+            for (index, undo) in completed.into_iter().rev() {
+                let outcome = match undo {
+                    Some(undo) => self.run_batch_command(undo).await.map(|_| ()),
+                    None => Err(Status::unimplemented("command can't be undone")),
+                };
+                let result = &mut results[index];
+                match outcome {
+                    Ok(()) => result.set_status(BatchCommandStatus::BatchCommandRolledBack),
+                    Err(status) => {
+                        result.set_status(BatchCommandStatus::BatchCommandRollbackFailed);
+                        result.error_message = format!("Rollback failed: {}", status.message());
+                    }
+                }
+            }
+        }
+
+        match &first_error {
+            Some(error) => tracing::warn!(
+                commands = results.len(),
+                all_or_nothing,
+                "Batch failed: {}",
+                error
+            ),
+            None => tracing::info!(commands = results.len(), "Batch completed"),
+        }
+        Ok(Response::new(ExecuteBatchResponse {
+            success: first_error.is_none(),
+            error_message: first_error.unwrap_or_default(),
+            results,
+        }))
+    }
+
+    // =========================================================================
+    // Initialization Recipes
+    // =========================================================================
+
+    async fn list_init_recipes(
+        &self,
+        request: Request<ListInitRecipesRequest>,
+    ) -> Result<Response<ListInitRecipesResponse>, Status> {
+        let req = request.into_inner();
+        let recipes = self
+            .registry
+            .recipes()
+            .into_iter()
+            .filter(|r| req.device_id.as_ref().is_none_or(|id| &r.device == id))
+            .map(|recipe| InitRecipeInfo {
+                last_report: self
+                    .registry
+                    .recipe_report(&recipe.name)
+                    .map(recipe_report_to_proto),
+                run_on: recipe.run_on.iter().map(ToString::to_string).collect(),
+                steps: recipe.steps.iter().map(|step| step.describe()).collect(),
+                name: recipe.name,
+                device_id: recipe.device,
+                description: recipe.description,
+            })
+            .collect();
+        Ok(Response::new(ListInitRecipesResponse { recipes }))
+    }
+
+    async fn run_init_recipe(
+        &self,
+        request: Request<RunInitRecipeRequest>,
+    ) -> Result<Response<InitRecipeReport>, Status> {
+        let req = request.into_inner();
+        let report = self
+            .registry
+            .run_recipe(&req.name, RecipeTrigger::Manual)
+            .await
+            .map_err(|e| Status::not_found(e.to_string()))?;
+        Ok(Response::new(recipe_report_to_proto(report)))
+    }
+
+    async fn list_warmups(
+        &self,
+        _request: Request<ListWarmupsRequest>,
+    ) -> Result<Response<ListWarmupsResponse>, Status> {
+        let warmups = self
+            .warmups
+            .as_ref()
+            .map(|scheduler| {
+                scheduler
+                    .schedules()
+                    .into_iter()
+                    .zip(scheduler.statuses())
+                    .map(|(schedule, status)| warmup_to_proto(schedule, status))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(Response::new(ListWarmupsResponse { warmups }))
+    }
+
+    async fn abort_warmup(
+        &self,
+        request: Request<AbortWarmupRequest>,
+    ) -> Result<Response<AbortWarmupResponse>, Status> {
+        let req = request.into_inner();
+        let found = self
+            .warmups
+            .as_ref()
+            .is_some_and(|scheduler| scheduler.abort(&req.name));
+        Ok(Response::new(AbortWarmupResponse { found }))
+    }
+
+    async fn run_self_test(
+        &self,
+        _request: Request<RunSelfTestRequest>,
+    ) -> Result<Response<SelfTestReport>, Status> {
+        let report = self.run_configured_self_test().await;
+        Ok(Response::new(self_test_report_to_proto(report)))
+    }
+
+    async fn get_self_test_report(
+        &self,
+        _request: Request<GetSelfTestReportRequest>,
+    ) -> Result<Response<GetSelfTestReportResponse>, Status> {
+        let report = self
+            .self_test_report
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone();
+        Ok(Response::new(GetSelfTestReportResponse {
+            report: report.map(self_test_report_to_proto),
+        }))
+    }
+
+    async fn verify_time_sync(
+        &self,
+        request: Request<VerifyTimeSyncRequest>,
+    ) -> Result<Response<TimeSyncReport>, Status> {
+        let req = request.into_inner();
+        let (stimulus, default_threshold) = match req.stimulus_parameter {
+            Some(parameter) => (
+                Stimulus::Step {
+                    device: req.stimulus_device,
+                    parameter,
+                    low: req.step_low,
+                    high: req.step_high,
+                },
+                (req.step_high - req.step_low).abs() / 2.0,
+            ),
+            None => (
+                Stimulus::Trigger {
+                    device: req.stimulus_device,
+                },
+                1.0,
+            ),
+        };
+        let or_default = |value: u32, default: u32| if value == 0 { default } else { value };
+        let request = TimeSyncRequest {
+            stimulus,
+            devices: req.device_ids,
+            repetitions: or_default(req.repetitions, 20),
+            interval: Duration::from_millis(u64::from(or_default(req.interval_ms, 200))),
+            timeout: Duration::from_millis(u64::from(or_default(req.timeout_ms, 1000))),
+            threshold: if req.threshold > 0.0 {
+                req.threshold
+            } else {
+                default_threshold
+            },
+            reference: req.reference_device.filter(|r| !r.is_empty()),
+        };
+        request
+            .validate()
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        let report = verify_time_sync(&self.registry, &request)
+            .await
+            .map_err(|e| Status::failed_precondition(format!("Time-sync check failed: {}", e)))?;
+
+        if req.apply_corrections {
+            for (device_id, offset_ns) in report.corrections().offsets() {
+                self.registry.set_timestamp_offset(device_id, *offset_ns);
+            }
+            tracing::info!(
+                devices = report.devices.len(),
+                "Applied timestamp corrections"
+            );
+        }
+
+        Ok(Response::new(TimeSyncReport {
+            reference_device: report.reference.clone(),
+            repetitions: report.repetitions,
+            max_skew_ns: report.max_skew_ns(),
+            corrections_applied: req.apply_corrections,
+            corrections_ns: self
+                .registry
+                .timestamp_corrections()
+                .offsets()
+                .clone()
+                .into_iter()
+                .collect(),
+            devices: report
+                .devices
+                .into_iter()
+                .map(|device| DeviceTimeSkew {
+                    device_id: device.device_id,
+                    samples: device.samples,
+                    missed: device.missed,
+                    mean_delay_ns: device.mean_delay_ns,
+                    offset_ns: device.offset_ns,
+                    jitter_ns: device.jitter_ns,
+                    min_offset_ns: device.min_offset_ns,
+                    max_offset_ns: device.max_offset_ns,
+                })
+                .collect(),
+        }))
+    }
+
+    async fn get_inventory(
+        &self,
+        request: Request<GetInventoryRequest>,
+    ) -> Result<Response<GetInventoryResponse>, Status> {
+        let refresh = request.into_inner().refresh;
+        let report = match self.registry.inventory_report() {
+            Some(report) if !refresh => report,
+            _ => self
+                .registry
+                .refresh_inventory()
+                .await
+                .map_err(|e| Status::internal(format!("Inventory refresh failed: {}", e)))?,
+        };
+        Ok(Response::new(inventory_report_to_proto(report)))
+    }
+
+    async fn list_serial_ports(
+        &self,
+        _request: Request<ListSerialPortsRequest>,
+    ) -> Result<Response<ListSerialPortsResponse>, Status> {
+        let ports = tokio::task::spawn_blocking(enumerate_ports)
+            .await
+            .map_err(|e| Status::internal(format!("Port enumeration panicked: {}", e)))?
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(ListSerialPortsResponse {
+            ports: ports.into_iter().map(serial_port_to_proto).collect(),
+        }))
+    }
+
+    // =========================================================================
+    // Observable Parameters (QCodes/ScopeFoundry pattern)
+    // =========================================================================
+
+    async fn list_parameters(
+        &self,
+        request: Request<ListParametersRequest>,
+    ) -> Result<Response<ListParametersResponse>, Status> {
+        let req = request.into_inner();
+
+        // Check if device exists
+        if !self.registry.contains(&req.device_id) {
+            return Err(Status::not_found(format!(
+                "Device '{}' not found",
+                req.device_id
+            )));
+        }
+
+        let mut parameters = Vec::new();
+
+        // 1. Get V5 parameters from Parameterized devices
+        if let Some(parameterized) = self.registry.get_parameterized(&req.device_id) {
+            let param_set = parameterized.parameters();
+            for param_name in param_set.names() {
+                if let Some(param) = param_set.get(param_name) {
                     let metadata = param.metadata();
 
                     // Use introspectable dtype from metadata if available,
@@ -2058,6 +3052,8 @@ impl HardwareService for HardwareServiceImpl {
                         min_value: metadata.min_value, // Phase 2 (bd-cdh5.2): introspectable from metadata
                         max_value: metadata.max_value, // Phase 2 (bd-cdh5.2): introspectable from metadata
                         enum_values: metadata.enum_values.clone(), // Phase 2 (bd-cdh5.2): introspectable from metadata
+                        dangerous: metadata.dangerous
+                            || self.is_dangerous(&req.device_id, &metadata.name),
                     });
                 }
             }
@@ -2075,7 +3071,15 @@ impl HardwareService for HardwareServiceImpl {
         &self,
         request: Request<GetParameterRequest>,
     ) -> Result<Response<ParameterValue>, Status> {
-        let req = request.into_inner();
+        let mut req = request.into_inner();
+
+        // A parameter-level channel alias ("sample_temp" -> "lakeshore1:inputA")
+        // supplies the parameter name when the request leaves it empty
+        if req.parameter_name.is_empty()
+            && let Some(parameter) = self.registry.resolve_channel(&req.device_id).parameter
+        {
+            req.parameter_name = parameter;
+        }
 
         // Try legacy Settable trait first (backwards compatibility)
         if let Some(settable) = self.registry.get_settable(&req.device_id) {
@@ -2093,6 +3097,7 @@ impl HardwareService for HardwareServiceImpl {
             return Ok(Response::new(ParameterValue {
                 device_id: req.device_id,
                 name: req.parameter_name,
+                typed: typed_value_to_proto(&TypedParameterValue::from_json(&value)),
                 value: value.to_string(),
                 units: String::new(), // Would need parameter metadata
                 timestamp_ns,
@@ -2103,9 +3108,12 @@ impl HardwareService for HardwareServiceImpl {
         if let Some(parameterized) = self.registry.get_parameterized(&req.device_id) {
             let params = parameterized.parameters();
             if let Some(param) = params.get(&req.parameter_name) {
-                let value = param.get_json().map_err(|e| {
-                    map_hardware_error_to_status(&format!("Failed to get parameter: {}", e))
-                })?;
+                let value = self
+                    .registry
+                    .get_parameter_json(&req.device_id, param)
+                    .map_err(|e| {
+                        map_hardware_error_to_status(&format!("Failed to get parameter: {}", e))
+                    })?;
                 let timestamp_ns = SystemTime::now()
                     .duration_since(UNIX_EPOCH)
                     .map(|d| d.as_nanos() as u64)
@@ -2114,6 +3122,11 @@ impl HardwareService for HardwareServiceImpl {
                 return Ok(Response::new(ParameterValue {
                     device_id: req.device_id,
                     name: req.parameter_name,
+                    typed: param
+                        .get_typed()
+                        .ok()
+                        .as_ref()
+                        .and_then(typed_value_to_proto),
                     value: value.to_string(),
                     units: String::new(), // Could extract from metadata
                     timestamp_ns,
@@ -2133,107 +3146,103 @@ impl HardwareService for HardwareServiceImpl {
         &self,
         request: Request<SetParameterRequest>,
     ) -> Result<Response<SetParameterResponse>, Status> {
-        let req = request.into_inner();
-
-        // Try legacy Settable trait first (backwards compatibility)
-        if let Some(settable) = self.registry.get_settable(&req.device_id) {
-            // Get old value before setting (for change notification)
-            let old_value = settable
-                .get_value(&req.parameter_name)
-                .await
-                .map(|v| v.to_string())
-                .unwrap_or_default();
-
-            // Parse the value string to JSON
-            let json_value: serde_json::Value = serde_json::from_str(&req.value)
-                .or_else(|_| {
-                    // Try as raw string if JSON parsing fails
-                    Ok::<_, serde_json::Error>(serde_json::Value::String(req.value.clone()))
-                })
-                .map_err(|e| Status::invalid_argument(format!("Invalid value format: {}", e)))?;
-
-            // Set the parameter
-            settable
-                .set_value(&req.parameter_name, json_value)
-                .await
-                .map_err(|e| Status::invalid_argument(format!("Failed to set parameter: {}", e)))?;
+        let _lock = self
+            .command_lock(&request, &request.get_ref().device_id)
+            .await?;
+        let mut req = request.into_inner();
 
-            // Read back the actual value
-            let actual_value = settable
-                .get_value(&req.parameter_name)
-                .await
-                .map(|v| v.to_string())
-                .unwrap_or_else(|_| req.value.clone());
+        // A parameter-level channel alias ("sample_temp" -> "lakeshore1:inputA")
+        // supplies the parameter name when the request leaves it empty
+        if req.parameter_name.is_empty()
+            && let Some(parameter) = self.registry.resolve_channel(&req.device_id).parameter
+        {
+            req.parameter_name = parameter;
+        }
 
-            // Broadcast parameter change notification (ignore send errors - no subscribers is ok)
-            let _ = self.param_change_tx.send(ParameterChange {
-                device_id: req.device_id.clone(),
-                name: req.parameter_name.clone(),
-                old_value,
-                new_value: actual_value.clone(),
-                units: String::new(), // Would need parameter metadata for units
-                timestamp_ns: now_ns(),
-                source: "user".to_string(),
-            });
+        // A typed value replaces the string form, after checking it against the
+        // parameter's dtype and limits so bad input never reaches the driver
+        if let Some(typed) = req.typed_value.take() {
+            let value = typed_value_from_proto(&typed)
+                .ok_or_else(|| Status::invalid_argument("typed_value has no value set"))?;
+            let value = match self
+                .registry
+                .get_parameterized(&req.device_id)
+                .and_then(|p| {
+                    p.parameters()
+                        .get(&req.parameter_name)
+                        .map(|p| p.metadata())
+                }) {
+                Some(metadata) => metadata
+                    .validate_value(value)
+                    .map_err(|e| Status::invalid_argument(e.to_string()))?,
+                None => value,
+            };
+            req.value = value.to_json().to_string();
+        }
 
+        if self.is_dangerous(&req.device_id, &req.parameter_name) {
+            let (proposal_token, proposal_expires_ns) = self.proposals.propose(
+                &req.device_id,
+                &req.parameter_name,
+                &req.value,
+                self.confirm_timeout(&req.device_id, &req.parameter_name),
+            );
+            tracing::info!(
+                device_id = %req.device_id,
+                parameter = %req.parameter_name,
+                value = %req.value,
+                "Dangerous parameter change proposed, awaiting confirmation"
+            );
             return Ok(Response::new(SetParameterResponse {
-                success: true,
-                error_message: String::new(),
-                actual_value,
+                success: false,
+                error_message: format!(
+                    "Parameter '{}' is dangerous; confirm the proposed change",
+                    req.parameter_name
+                ),
+                actual_value: String::new(),
+                confirmation_required: true,
+                proposal_token,
+                proposal_expires_ns,
+                actual_typed: None,
             }));
         }
 
-        // New path - use Parameterized trait
-        if let Some(parameterized) = self.registry.get_parameterized(&req.device_id) {
-            let params = parameterized.parameters();
-
-            if let Some(param) = params.get(&req.parameter_name) {
-                let old_value = param.get_json().map(|v| v.to_string()).unwrap_or_default();
-
-                // Parse the value string to JSON
-                let json_value: serde_json::Value = serde_json::from_str(&req.value)
-                    .or_else(|_| {
-                        // Try as raw string if JSON parsing fails
-                        Ok::<_, serde_json::Error>(serde_json::Value::String(req.value.clone()))
-                    })
-                    .map_err(|e| {
-                        Status::invalid_argument(format!("Invalid value format: {}", e))
-                    })?;
-
-                // Set the parameter (synchronous call, no await needed)
-                param.set_json(json_value).map_err(|e| {
-                    Status::invalid_argument(format!("Failed to set parameter: {}", e))
-                })?;
-
-                let actual_value = param
-                    .get_json()
-                    .map(|v| v.to_string())
-                    .unwrap_or_else(|_| req.value.clone());
+        self.apply_parameter(req).await
+    }
 
-                // Broadcast parameter change notification
-                let _ = self.param_change_tx.send(ParameterChange {
-                    device_id: req.device_id.clone(),
-                    name: req.parameter_name.clone(),
-                    old_value,
-                    new_value: actual_value.clone(),
-                    units: String::new(), // Could get from metadata
-                    timestamp_ns: now_ns(),
-                    source: "user".to_string(),
-                });
+    async fn confirm_parameter_change(
+        &self,
+        request: Request<ConfirmParameterChangeRequest>,
+    ) -> Result<Response<SetParameterResponse>, Status> {
+        let req = request.into_inner();
+        let proposal = self.proposals.take(&req.proposal_token)?;
+        let _lock = self
+            .lock_devices([&proposal.device_id], RPC_TIMEOUT)
+            .await?;
 
-                return Ok(Response::new(SetParameterResponse {
-                    success: true,
-                    error_message: String::new(),
-                    actual_value,
-                }));
-            }
-        }
+        tracing::info!(
+            device_id = %proposal.device_id,
+            parameter = %proposal.parameter_name,
+            value = %proposal.value,
+            "Dangerous parameter change confirmed"
+        );
+        self.apply_parameter(SetParameterRequest {
+            device_id: proposal.device_id,
+            parameter_name: proposal.parameter_name,
+            value: proposal.value,
+            typed_value: None,
+        })
+        .await
+    }
 
-        // Neither Settable nor Parameterized - device not found
-        Err(Status::not_found(format!(
-            "Device '{}' does not support settable parameters",
-            req.device_id
-        )))
+    async fn cancel_parameter_change(
+        &self,
+        request: Request<CancelParameterChangeRequest>,
+    ) -> Result<Response<CancelParameterChangeResponse>, Status> {
+        let req = request.into_inner();
+        Ok(Response::new(CancelParameterChangeResponse {
+            cancelled: self.proposals.cancel(&req.proposal_token),
+        }))
     }
 
     type StreamParameterChangesStream =
@@ -2292,6 +3301,295 @@ impl HardwareService for HardwareServiceImpl {
         Ok(Response::new(ReceiverStream::new(stream_rx)))
     }
 
+    async fn query_device_history(
+        &self,
+        request: Request<DeviceHistoryRequest>,
+    ) -> Result<Response<DeviceHistoryResponse>, Status> {
+        let req = request.into_inner();
+        if req.end_ns != 0 && req.end_ns < req.start_ns {
+            return Err(Status::invalid_argument("end_ns is before start_ns"));
+        }
+
+        // Aliases resolve to the device the history is recorded under
+        let device_id = (!req.device_id.is_empty())
+            .then(|| self.registry.resolve_channel(&req.device_id).device_id);
+        let (events, truncated) = self.history.query(&HistoryQuery {
+            device_id,
+            names: req.parameter_names,
+            start_ns: req.start_ns,
+            end_ns: (req.end_ns != 0).then_some(req.end_ns),
+            limit: req.limit as usize,
+        });
+        Ok(Response::new(DeviceHistoryResponse {
+            events,
+            truncated,
+            oldest_retained_ns: self.history.oldest_ns(),
+        }))
+    }
+
+    async fn get_device_state_at(
+        &self,
+        request: Request<DeviceStateAtRequest>,
+    ) -> Result<Response<DeviceStateAtResponse>, Status> {
+        let req = request.into_inner();
+        let device_id = self.registry.resolve_channel(&req.device_id).device_id;
+        if !self.registry.contains(&device_id) {
+            return Err(Status::not_found(format!(
+                "Device '{}' not found",
+                req.device_id
+            )));
+        }
+
+        let (values, complete) = self.history.state_at(&device_id, req.timestamp_ns);
+        Ok(Response::new(DeviceStateAtResponse {
+            device_id,
+            timestamp_ns: req.timestamp_ns,
+            values,
+            complete,
+        }))
+    }
+
+    async fn get_data_integrity(
+        &self,
+        request: Request<GetDataIntegrityRequest>,
+    ) -> Result<Response<GetDataIntegrityResponse>, Status> {
+        let req = request.into_inner();
+        let mut report = self.registry.drop_report().await;
+        if !req.device_id.is_empty() {
+            let device_id = self.registry.resolve_channel(&req.device_id).device_id;
+            report.retain(|source, _| *source == device_id);
+        }
+
+        Ok(Response::new(GetDataIntegrityResponse {
+            total_dropped: total_dropped(&report),
+            counts: drop_report_to_proto(&report),
+        }))
+    }
+
+    async fn list_display_transforms(
+        &self,
+        _request: Request<ListDisplayTransformsRequest>,
+    ) -> Result<Response<ListDisplayTransformsResponse>, Status> {
+        let transforms = self
+            .registry
+            .display_transforms()
+            .into_iter()
+            .map(|(channel, t)| ChannelDisplayTransform {
+                target: self.registry.resolve_channel(&channel).to_string(),
+                channel,
+                scale: t.scale,
+                offset: t.offset,
+                unit: t.unit,
+                log_scale: t.log_scale,
+            })
+            .collect();
+        Ok(Response::new(ListDisplayTransformsResponse { transforms }))
+    }
+
+    async fn set_display_transform(
+        &self,
+        request: Request<SetDisplayTransformRequest>,
+    ) -> Result<Response<SetDisplayTransformResponse>, Status> {
+        let req = request.into_inner();
+        let proto = req
+            .transform
+            .ok_or_else(|| Status::invalid_argument("transform is required"))?;
+        let transform = (!req.clear).then(|| DisplayTransform {
+            scale: proto.scale,
+            offset: proto.offset,
+            unit: proto.unit,
+            log_scale: proto.log_scale,
+        });
+
+        let response = match self
+            .registry
+            .set_display_transform(&proto.channel, transform)
+        {
+            Ok(()) => {
+                tracing::info!(
+                    channel = %proto.channel,
+                    cleared = req.clear,
+                    "Display transform changed"
+                );
+                SetDisplayTransformResponse {
+                    success: true,
+                    error_message: String::new(),
+                }
+            }
+            Err(e) => SetDisplayTransformResponse {
+                success: false,
+                error_message: e.to_string(),
+            },
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn get_frame_annotations(
+        &self,
+        request: Request<GetFrameAnnotationsRequest>,
+    ) -> Result<Response<GetFrameAnnotationsResponse>, Status> {
+        let req = request.into_inner();
+        let annotations = self
+            .registry
+            .frame_annotations()
+            .get(&req.device_id)
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        Ok(Response::new(GetFrameAnnotationsResponse { annotations }))
+    }
+
+    async fn set_frame_annotations(
+        &self,
+        request: Request<SetFrameAnnotationsRequest>,
+    ) -> Result<Response<SetFrameAnnotationsResponse>, Status> {
+        let req = request.into_inner();
+        let annotations = req
+            .annotations
+            .into_iter()
+            .map(|a| a.to_domain())
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| Status::invalid_argument("every annotation needs a shape"))?;
+        let count = annotations.len();
+
+        let response = match self
+            .registry
+            .set_frame_annotations(&req.device_id, annotations)
+        {
+            Ok(()) => {
+                tracing::info!(
+                    device_id = %req.device_id,
+                    count,
+                    "Frame annotations changed"
+                );
+                SetFrameAnnotationsResponse {
+                    success: true,
+                    error_message: String::new(),
+                }
+            }
+            Err(e) => SetFrameAnnotationsResponse {
+                success: false,
+                error_message: e.to_string(),
+            },
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn start_ramp(
+        &self,
+        request: Request<StartRampRequest>,
+    ) -> Result<Response<StartRampResponse>, Status> {
+        let req = request.into_inner();
+        let profile = match ProtoRampProfile::try_from(req.profile) {
+            Ok(profile) => ramp_profile_from_proto(profile),
+            Err(_) => {
+                return Err(Status::invalid_argument(format!(
+                    "Unknown ramp profile {}",
+                    req.profile
+                )));
+            }
+        };
+        let spec = RampSpec {
+            target: req.target,
+            duration_s: req.duration_s,
+            rate: req.rate,
+            profile,
+            step_interval_ms: match req.step_interval_ms {
+                0 => DEFAULT_STEP_INTERVAL_MS,
+                ms => ms,
+            },
+        };
+
+        let response = match self
+            .registry
+            .start_ramp(&req.device_id, &req.parameter, spec)
+            .await
+        {
+            Ok(handle) => StartRampResponse {
+                success: true,
+                error_message: String::new(),
+                status: Some(ramp_status_to_proto(&handle.status())),
+            },
+            Err(e) => StartRampResponse {
+                success: false,
+                error_message: format!("{:#}", e),
+                status: None,
+            },
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn abort_ramp(
+        &self,
+        request: Request<AbortRampRequest>,
+    ) -> Result<Response<AbortRampResponse>, Status> {
+        let req = request.into_inner();
+        let ramps = self.registry.ramps();
+        let aborted = if req.device_id.is_empty() {
+            usize::from(ramps.abort(req.ramp_id).is_ok())
+        } else {
+            ramps.abort_device(&req.device_id)
+        };
+        Ok(Response::new(AbortRampResponse {
+            aborted: aborted as u32,
+        }))
+    }
+
+    async fn list_ramps(
+        &self,
+        _request: Request<ListRampsRequest>,
+    ) -> Result<Response<ListRampsResponse>, Status> {
+        let ramps = self
+            .registry
+            .ramps()
+            .list()
+            .iter()
+            .map(ramp_status_to_proto)
+            .collect();
+        Ok(Response::new(ListRampsResponse { ramps }))
+    }
+
+    type StreamRampEventsStream = TrackedStream<ReceiverStream<Result<ProtoRampStatus, Status>>>;
+
+    async fn stream_ramp_events(
+        &self,
+        request: Request<StreamRampEventsRequest>,
+    ) -> Result<Response<Self::StreamRampEventsStream>, Status> {
+        let stats = StreamStatsRegistry::global().open(
+            "HardwareService/StreamRampEvents",
+            request.get_ref().device_ids.join(","),
+            &request,
+        );
+        let entry = stats.entry();
+        let req = request.into_inner();
+
+        let mut events = self.registry.ramps().subscribe();
+        let (tx, rx) = tokio::sync::mpsc::channel::<Result<ProtoRampStatus, Status>>(128);
+        tokio::spawn(async move {
+            loop {
+                let status = tokio::select! {
+                    _ = tx.closed() => break,
+                    received = events.recv() => match received {
+                        Ok(status) => status,
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                            entry.record_dropped(skipped);
+                            continue;
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    },
+                };
+                let wanted =
+                    req.device_ids.is_empty() || req.device_ids.contains(&status.device_id);
+                if wanted && tx.send(Ok(ramp_status_to_proto(&status))).await.is_err() {
+                    break;
+                }
+            }
+            tracing::debug!("StreamRampEvents: Client disconnected");
+        });
+
+        Ok(Response::new(stats.track(ReceiverStream::new(rx))))
+    }
+
     // =========================================================================
     // Observable Streaming (bd-qqjq, bd-ijre)
     //
@@ -2302,12 +3600,17 @@ impl HardwareService for HardwareServiceImpl {
     // =========================================================================
 
     type StreamObservablesStream =
-        tokio_stream::wrappers::ReceiverStream<Result<ObservableValue, Status>>;
+        TrackedStream<tokio_stream::wrappers::ReceiverStream<Result<ObservableValue, Status>>>;
 
     async fn stream_observables(
         &self,
         request: Request<StreamObservablesRequest>,
     ) -> Result<Response<Self::StreamObservablesStream>, Status> {
+        let stats = StreamStatsRegistry::global().open(
+            "HardwareService/StreamObservables",
+            request.get_ref().device_ids.join(","),
+            &request,
+        );
         let req = request.into_inner();
         let device_ids = req.device_ids;
         let observable_names = req.observable_names;
@@ -2321,6 +3624,23 @@ impl HardwareService for HardwareServiceImpl {
         } else {
             req.deadband.max(f64::EPSILON)
         };
+        // Per-channel deadbands may be zero (send every change)
+        if let Some((channel, value)) = req
+            .channel_deadbands
+            .iter()
+            .find(|(_, value)| !value.is_finite() || **value < 0.0)
+        {
+            return Err(Status::invalid_argument(format!(
+                "Deadband for '{}' must be a non-negative number, got {}",
+                channel, value
+            )));
+        }
+        let deadbands = Deadbands {
+            default: deadband,
+            channels: req.channel_deadbands,
+        };
+        let keyframe_interval = (req.keyframe_interval_ms > 0)
+            .then(|| std::time::Duration::from_millis(u64::from(req.keyframe_interval_ms)));
 
         // Calculate sample interval
         let sample_interval = std::time::Duration::from_secs_f64(1.0 / sample_rate_hz as f64);
@@ -2341,17 +3661,26 @@ impl HardwareService for HardwareServiceImpl {
                 String,                            // units
                 tokio::sync::watch::Receiver<f64>, // subscription
                 std::time::Instant,                // last_sent
-                f64,                               // last_value (for change detection)
             )> = Vec::new();
 
+            // Empty lists select every device / every f64 observable
+            let device_ids = if device_ids.is_empty() {
+                registry.list_devices().into_iter().map(|d| d.id).collect()
+            } else {
+                device_ids
+            };
             for device_id in &device_ids {
                 if let Some(parameterized) = registry.get_parameterized(device_id) {
                     let param_set = parameterized.parameters();
-                    for obs_name in &observable_names {
+                    let names: Vec<String> = if observable_names.is_empty() {
+                        param_set.names().into_iter().map(String::from).collect()
+                    } else {
+                        observable_names.clone()
+                    };
+                    for obs_name in &names {
                         // Try to get Observable<f64> for this name
                         if let Some(observable) = param_set.get_typed::<Observable<f64>>(obs_name) {
                             let rx = observable.subscribe();
-                            let initial_value = *rx.borrow();
                             let units = observable.metadata().units.clone().unwrap_or_default();
                             subscriptions.push((
                                 device_id.clone(),
@@ -2359,7 +3688,6 @@ impl HardwareService for HardwareServiceImpl {
                                 units,
                                 rx,
                                 std::time::Instant::now(),
-                                initial_value,
                             ));
                         }
                     }
@@ -2381,6 +3709,26 @@ impl HardwareService for HardwareServiceImpl {
                 sample_rate_hz
             );
 
+            let mut filter = OnChangeFilter::new(deadbands, keyframe_interval);
+            if keyframe_interval.is_none() {
+                // Changes only: start from the current values without sending them
+                let current: Vec<f64> = subscriptions.iter().map(|s| *s.3.borrow()).collect();
+                filter.keyframe_sent(
+                    std::time::Instant::now(),
+                    subscriptions
+                        .iter()
+                        .zip(current)
+                        .map(|(s, value)| (s.0.as_str(), s.1.as_str(), value)),
+                );
+            }
+
+            let timestamp_ns = || {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_nanos() as u64)
+                    .unwrap_or(0)
+            };
+
             // Stream loop - check each subscription for updates
             let mut interval = tokio::time::interval(sample_interval / 2); // Check at 2x rate
 
@@ -2393,13 +3741,45 @@ impl HardwareService for HardwareServiceImpl {
                     break;
                 }
 
+                // Keyframe: every channel's current value, regardless of deadband
+                let now = std::time::Instant::now();
+                if filter.keyframe_due(now) {
+                    let mut values = Vec::with_capacity(subscriptions.len());
+                    for (device_id, obs_name, units, rx, last_sent) in &mut subscriptions {
+                        let value = *rx.borrow();
+                        let msg = ObservableValue {
+                            device_id: device_id.clone(),
+                            observable_name: obs_name.clone(),
+                            value,
+                            units: units.clone(),
+                            timestamp_ns: timestamp_ns(),
+                            keyframe: true,
+                            quality: DataQuality::Good as i32,
+                        };
+                        if tx.send(Ok(msg)).await.is_err() {
+                            tracing::debug!("StreamObservables: Failed to send, client gone");
+                            return;
+                        }
+                        *last_sent = now;
+                        values.push(value);
+                    }
+                    filter.keyframe_sent(
+                        now,
+                        subscriptions
+                            .iter()
+                            .zip(values)
+                            .map(|(s, value)| (s.0.as_str(), s.1.as_str(), value)),
+                    );
+                    continue;
+                }
+
                 // Check each subscription for new values
-                for (device_id, obs_name, units, rx, last_sent, last_value) in &mut subscriptions {
+                for (device_id, obs_name, units, rx, last_sent) in &mut subscriptions {
                     // Get current value from watch receiver
                     let current_value = *rx.borrow();
 
                     // Only send if value changed beyond deadband and rate limit elapsed
-                    if (current_value - *last_value).abs() > deadband
+                    if filter.changed(device_id, obs_name, current_value)
                         && last_sent.elapsed() >= sample_interval
                     {
                         let msg = ObservableValue {
@@ -2407,10 +3787,9 @@ impl HardwareService for HardwareServiceImpl {
                             observable_name: obs_name.clone(),
                             value: current_value,
                             units: units.clone(),
-                            timestamp_ns: std::time::SystemTime::now()
-                                .duration_since(std::time::UNIX_EPOCH)
-                                .map(|d| d.as_nanos() as u64)
-                                .unwrap_or(0),
+                            timestamp_ns: timestamp_ns(),
+                            keyframe: false,
+                            quality: DataQuality::Good as i32,
                         };
 
                         if tx.send(Ok(msg)).await.is_err() {
@@ -2419,15 +3798,81 @@ impl HardwareService for HardwareServiceImpl {
                         }
 
                         *last_sent = std::time::Instant::now();
-                        *last_value = current_value;
+                        filter.sent(device_id, obs_name, current_value);
                     }
                 }
             }
         });
 
-        Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(
-            rx,
-        )))
+        Ok(Response::new(
+            stats.track(tokio_stream::wrappers::ReceiverStream::new(rx)),
+        ))
+    }
+
+    type StreamDeviceEventsStream = TrackedStream<ReceiverStream<Result<ProtoDeviceEvent, Status>>>;
+
+    async fn stream_device_events(
+        &self,
+        request: Request<StreamDeviceEventsRequest>,
+    ) -> Result<Response<Self::StreamDeviceEventsStream>, Status> {
+        let stats = StreamStatsRegistry::global().open(
+            "HardwareService/StreamDeviceEvents",
+            request.get_ref().device_ids.join(","),
+            &request,
+        );
+        let entry = stats.entry();
+        let req = request.into_inner();
+        let min_severity = match ProtoDeviceEventSeverity::try_from(req.min_severity) {
+            Ok(severity) => device_event_severity_from_proto(severity),
+            Err(_) => {
+                return Err(Status::invalid_argument(format!(
+                    "Unknown severity {}",
+                    req.min_severity
+                )));
+            }
+        };
+
+        let mut events = self.registry.subscribe_device_events();
+        let (tx, rx) = tokio::sync::mpsc::channel::<Result<ProtoDeviceEvent, Status>>(128);
+        tokio::spawn(async move {
+            loop {
+                let event = tokio::select! {
+                    _ = tx.closed() => break,
+                    received = events.recv() => match received {
+                        Ok(event) => event,
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                            entry.record_dropped(skipped);
+                            continue;
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    },
+                };
+                let wanted = (req.device_ids.is_empty()
+                    || req.device_ids.contains(&event.device_id))
+                    && (req.kinds.is_empty()
+                        || req.kinds.iter().any(|kind| kind == event.kind.as_str()))
+                    && min_severity.is_none_or(|min| event.severity >= min);
+                if wanted && tx.send(Ok(device_event_to_proto(&event))).await.is_err() {
+                    break;
+                }
+            }
+            tracing::debug!("StreamDeviceEvents: Client disconnected");
+        });
+
+        Ok(Response::new(stats.track(ReceiverStream::new(rx))))
+    }
+}
+
+// Helper: run a hardware await of a streaming RPC's task, giving up (`None`)
+// as soon as the client disconnects rather than keeping the device busy
+async fn unless_disconnected<T, U>(
+    tx: &tokio::sync::mpsc::Sender<U>,
+    operation: impl Future<Output = T>,
+) -> Option<T> {
+    tokio::select! {
+        biased;
+        () = tx.closed() => None,
+        value = operation => Some(value),
     }
 }
 
@@ -2453,9 +3898,13 @@ async fn fetch_device_state(
         )));
     }
 
+    let status = registry
+        .device_status(device_id)
+        .unwrap_or(DeviceStatus::Ready);
     let mut response = DeviceStateResponse {
         device_id: device_id.to_string(),
-        online: true,
+        online: status.is_operational(),
+        status: Some(device_status_to_proto(&status)),
         position: None,
         last_reading: None,
         armed: None,
@@ -2492,6 +3941,9 @@ async fn fetch_device_state(
 fn device_state_to_fields_json(state: &DeviceStateResponse) -> HashMap<String, String> {
     let mut map = HashMap::new();
     map.insert("online".into(), state.online.to_string());
+    if let Some(status) = &state.status {
+        map.insert("status".into(), device_status_to_json(status));
+    }
     if let Some(p) = state.position {
         map.insert("position".into(), p.to_string());
     }
@@ -2507,7 +3959,36 @@ fn device_state_to_fields_json(state: &DeviceStateResponse) -> HashMap<String, S
     if let Some(e) = state.exposure_ms {
         map.insert("exposure_ms".into(), e.to_string());
     }
-    map
+    map
+}
+
+/// Device a batch command acts on
+fn batch_device_id(command: &BatchCommandKind) -> &str {
+    match command {
+        BatchCommandKind::SetParameter(req) => &req.device_id,
+        BatchCommandKind::MoveAbsolute(req) | BatchCommandKind::MoveRelative(req) => &req.device_id,
+        BatchCommandKind::SetShutter(req) => &req.device_id,
+        BatchCommandKind::SetWavelength(req) => &req.device_id,
+        BatchCommandKind::DeviceCommand(req) => &req.device_id,
+    }
+}
+
+fn batch_command_name(command: &BatchCommandKind) -> &'static str {
+    match command {
+        BatchCommandKind::SetParameter(_) => "set_parameter",
+        BatchCommandKind::MoveAbsolute(_) => "move_absolute",
+        BatchCommandKind::MoveRelative(_) => "move_relative",
+        BatchCommandKind::SetShutter(_) => "set_shutter",
+        BatchCommandKind::SetWavelength(_) => "set_wavelength",
+        BatchCommandKind::DeviceCommand(_) => "device_command",
+    }
+}
+
+fn dangerous_in_batch(parameter: &str) -> Status {
+    Status::failed_precondition(format!(
+        "Parameter '{}' is dangerous and can't be set in a batch; use SetParameter and confirm it",
+        parameter
+    ))
 }
 
 fn now_ns() -> u64 {
@@ -2541,6 +4022,227 @@ fn monitor_parameter<T: std::fmt::Display + Clone + Send + Sync + 'static>(
 }
 
 /// Map anyhow errors to gRPC Status, preferring structured DaqError mapping.
+fn recipe_report_to_proto(report: RecipeReport) -> InitRecipeReport {
+    InitRecipeReport {
+        success: report.success(),
+        recipe: report.recipe,
+        device_id: report.device,
+        trigger: report.trigger.to_string(),
+        started_ns: report.started_ns,
+        duration_ms: report.duration.as_millis() as u64,
+        steps: report.steps.into_iter().map(step_result_to_proto).collect(),
+    }
+}
+
+fn step_result_to_proto(step: StepResult) -> InitRecipeStepResult {
+    InitRecipeStepResult {
+        index: step.index as u32,
+        description: step.description,
+        status: match step.status {
+            StepStatus::Ok => RecipeStepStatus::RecipeStepOk,
+            StepStatus::Failed => RecipeStepStatus::RecipeStepFailed,
+            StepStatus::Skipped => RecipeStepStatus::RecipeStepSkipped,
+        } as i32,
+        message: step.message,
+    }
+}
+
+fn self_test_report_to_proto(report: TestReport) -> SelfTestReport {
+    SelfTestReport {
+        passed: report.passed(),
+        ready: report.ready(),
+        critical_failures: report
+            .critical_failures()
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        started_ns: report.started_ns,
+        duration_ms: report.duration.as_millis() as u64,
+        devices: report
+            .devices
+            .into_iter()
+            .map(|device| DeviceSelfTestResult {
+                passed: device.passed(),
+                device_id: device.device,
+                critical: device.critical,
+                steps: device.steps.into_iter().map(step_result_to_proto).collect(),
+            })
+            .collect(),
+    }
+}
+
+fn identity_to_proto(identity: DeviceIdentity) -> ProtoDeviceIdentity {
+    ProtoDeviceIdentity {
+        manufacturer: identity.manufacturer,
+        model: identity.model,
+        serial_number: identity.serial_number,
+        firmware_version: identity.firmware_version,
+    }
+}
+
+fn inventory_report_to_proto(report: InventoryReport) -> GetInventoryResponse {
+    GetInventoryResponse {
+        devices: report
+            .entries
+            .into_iter()
+            .map(|entry| ProtoInventoryEntry {
+                device_id: entry.device,
+                driver_type: entry.driver_type,
+                identity: Some(identity_to_proto(entry.identity)),
+                source: match entry.source {
+                    IdentitySource::Driver => "driver",
+                    IdentitySource::Idn => "idn",
+                }
+                .to_string(),
+                collected_ns: entry.collected_ns,
+            })
+            .collect(),
+        changes: report
+            .changes
+            .into_iter()
+            .map(|change| ProtoIdentityChange {
+                firmware_changed: change.firmware_changed(),
+                description: change.to_string(),
+                device_id: change.device,
+                previous: Some(identity_to_proto(change.previous)),
+                current: Some(identity_to_proto(change.current)),
+            })
+            .collect(),
+    }
+}
+
+fn serial_port_to_proto(port: PortMetadata) -> SerialPortInfo {
+    SerialPortInfo {
+        port_type: port.kind.as_str().to_string(),
+        vid: port.vid.map(u32::from),
+        pid: port.pid.map(u32::from),
+        stable_spec: port.stable_spec().unwrap_or_default(),
+        serial_number: port.serial_number.unwrap_or_default(),
+        manufacturer: port.manufacturer.unwrap_or_default(),
+        product: port.product.unwrap_or_default(),
+        driver: port.driver.unwrap_or_default(),
+        by_id_path: port.by_id_path.unwrap_or_default(),
+        path: port.path,
+    }
+}
+
+fn warmup_state_to_proto(state: WarmupState) -> ProtoWarmupState {
+    match state {
+        WarmupState::Scheduled => ProtoWarmupState::Scheduled,
+        WarmupState::WarmingUp => ProtoWarmupState::WarmingUp,
+        WarmupState::Ready => ProtoWarmupState::Ready,
+        WarmupState::NotReady => ProtoWarmupState::NotReady,
+        WarmupState::Faulted => ProtoWarmupState::Faulted,
+        WarmupState::Aborted => ProtoWarmupState::Aborted,
+        WarmupState::Idle => ProtoWarmupState::Idle,
+    }
+}
+
+fn warmup_to_proto(schedule: WarmupSchedule, status: WarmupStatus) -> WarmupInfo {
+    WarmupInfo {
+        name: schedule.name,
+        description: schedule.description,
+        run_at: schedule.run_at,
+        days: schedule.days.iter().map(ToString::to_string).collect(),
+        lead_minutes: schedule.lead_minutes,
+        checks: schedule.checks.iter().map(|c| c.describe()).collect(),
+        recipes: schedule.recipes,
+        abort_recipes: schedule.abort_recipes,
+        state: warmup_state_to_proto(status.state) as i32,
+        last_result: status
+            .last_result
+            .map(|state| warmup_state_to_proto(state) as i32),
+        message: status.message,
+        run_start_ns: status.run_start_ns.unwrap_or_default(),
+        warmup_start_ns: status.warmup_start_ns.unwrap_or_default(),
+        failing_checks: status.failing_checks,
+        last_check_ns: status.last_check_ns.unwrap_or_default(),
+        reports: status
+            .reports
+            .into_iter()
+            .map(recipe_report_to_proto)
+            .collect(),
+    }
+}
+
+/// Non-zero drop counts, by source and stage
+pub(crate) fn drop_report_to_proto(report: &DropReport) -> Vec<DropCount> {
+    report
+        .iter()
+        .flat_map(|(source, counts)| {
+            counts
+                .iter()
+                .filter(|(_, count)| **count > 0)
+                .map(|(stage, count)| DropCount {
+                    source: source.clone(),
+                    stage: stage.to_string(),
+                    count: *count,
+                })
+        })
+        .collect()
+}
+
+/// Proto form of a device event
+pub(crate) fn device_event_to_proto(event: &DeviceEvent) -> ProtoDeviceEvent {
+    let severity = match event.severity {
+        DeviceEventSeverity::Info => ProtoDeviceEventSeverity::Info,
+        DeviceEventSeverity::Warning => ProtoDeviceEventSeverity::Warning,
+        DeviceEventSeverity::Error => ProtoDeviceEventSeverity::Error,
+        DeviceEventSeverity::Critical => ProtoDeviceEventSeverity::Critical,
+    };
+    ProtoDeviceEvent {
+        device_id: event.device_id.clone(),
+        kind: event.kind.to_string(),
+        severity: severity as i32,
+        message: event.message.clone(),
+        payload_json: event.payload_json().into_iter().collect(),
+        timestamp_ns: event.timestamp_ns,
+    }
+}
+
+fn ramp_profile_from_proto(profile: ProtoRampProfile) -> RampProfile {
+    match profile {
+        ProtoRampProfile::Linear => RampProfile::Linear,
+        ProtoRampProfile::EaseIn => RampProfile::EaseIn,
+        ProtoRampProfile::EaseOut => RampProfile::EaseOut,
+        ProtoRampProfile::EaseInOut => RampProfile::EaseInOut,
+    }
+}
+
+fn ramp_status_to_proto(status: &RampStatus) -> ProtoRampStatus {
+    let state = match status.state {
+        RampState::Running => ProtoRampState::Running,
+        RampState::Completed => ProtoRampState::Completed,
+        RampState::Aborted => ProtoRampState::Aborted,
+        RampState::Failed => ProtoRampState::Failed,
+    };
+    ProtoRampStatus {
+        ramp_id: status.id,
+        device_id: status.device_id.clone(),
+        parameter: status.parameter.clone(),
+        start: status.start,
+        target: status.target,
+        value: status.value,
+        progress: status.progress,
+        state: state as i32,
+        error_message: status.error.clone().unwrap_or_default(),
+        started_ns: status.started_ns,
+        duration_ms: status.duration.as_millis() as u64,
+    }
+}
+
+fn device_event_severity_from_proto(
+    severity: ProtoDeviceEventSeverity,
+) -> Option<DeviceEventSeverity> {
+    match severity {
+        ProtoDeviceEventSeverity::Unspecified => None,
+        ProtoDeviceEventSeverity::Info => Some(DeviceEventSeverity::Info),
+        ProtoDeviceEventSeverity::Warning => Some(DeviceEventSeverity::Warning),
+        ProtoDeviceEventSeverity::Error => Some(DeviceEventSeverity::Error),
+        ProtoDeviceEventSeverity::Critical => Some(DeviceEventSeverity::Critical),
+    }
+}
+
 fn map_anyhow_error_to_status(err: AnyError) -> Status {
     match err.downcast::<DaqError>() {
         Ok(daq_err) => map_daq_error_to_status(daq_err),
@@ -2617,6 +4319,11 @@ fn device_info_to_proto(info: &hardware::registry::DeviceInfo) -> DeviceInfo {
             position_units: info.metadata.position_units.clone(),
             min_position: info.metadata.min_position,
             max_position: info.metadata.max_position,
+            position_correction: info
+                .metadata
+                .position_correction
+                .as_ref()
+                .map(ToString::to_string),
             reading_units: info.metadata.measurement_units.clone(),
             frame_width: info.metadata.frame_width,
             frame_height: info.metadata.frame_height,
@@ -2633,6 +4340,248 @@ fn device_info_to_proto(info: &hardware::registry::DeviceInfo) -> DeviceInfo {
             .iter()
             .map(|c| c.as_str().to_string())
             .collect(),
+        aliases: info.aliases.clone(),
+        tags: info.tags.clone(),
+        simulated: info.simulated,
+        status: Some(device_status_to_proto(&info.status)),
+        ui_layout: info
+            .metadata
+            .ui_layout
+            .iter()
+            .map(ui_element_to_proto)
+            .collect(),
+    }
+}
+
+/// Convert a driver-shipped control panel element to proto
+pub(crate) fn ui_element_to_proto(elem: &UiElement) -> PluginUiElement {
+    let base = PluginUiElement::default();
+    match elem {
+        UiElement::Group(g) => PluginUiElement {
+            element_type: "group".to_string(),
+            label: g.label.clone(),
+            children: g.children.iter().map(ui_element_to_proto).collect(),
+            ..base
+        },
+        UiElement::Slider(s) => PluginUiElement {
+            element_type: "slider".to_string(),
+            label: s.label.clone().unwrap_or_default(),
+            target: Some(s.target.clone()),
+            min: s.min,
+            max: s.max,
+            unit: s.unit.clone(),
+            ..base
+        },
+        UiElement::Readout(r) => PluginUiElement {
+            element_type: "readout".to_string(),
+            label: r.label.clone().unwrap_or_default(),
+            source: Some(r.source.clone()),
+            ..base
+        },
+        UiElement::Toggle(t) => PluginUiElement {
+            element_type: "toggle".to_string(),
+            label: t.label.clone().unwrap_or_default(),
+            target: Some(t.target.clone()),
+            ..base
+        },
+        UiElement::Button(b) => PluginUiElement {
+            element_type: "button".to_string(),
+            label: b.label.clone(),
+            action: Some(b.action.clone()),
+            ..base
+        },
+        UiElement::Dropdown(d) => PluginUiElement {
+            element_type: "dropdown".to_string(),
+            label: d.label.clone().unwrap_or_default(),
+            target: Some(d.target.clone()),
+            options: d.options.clone(),
+            ..base
+        },
+        UiElement::Gauge(g) => PluginUiElement {
+            element_type: "gauge".to_string(),
+            label: g.label.clone().unwrap_or_default(),
+            source: Some(g.source.clone()),
+            min: Some(g.min),
+            max: Some(g.max),
+            unit: g.unit.clone(),
+            ..base
+        },
+    }
+}
+
+fn device_status_to_proto(status: &DeviceStatus) -> ProtoDeviceStatus {
+    let (state, reason, fault_code) = match status {
+        DeviceStatus::Uninitialized => (DeviceStatusState::DeviceStatusUninitialized, "", 0),
+        DeviceStatus::Ready => (DeviceStatusState::DeviceStatusReady, "", 0),
+        DeviceStatus::Busy => (DeviceStatusState::DeviceStatusBusy, "", 0),
+        DeviceStatus::Degraded { reason } => {
+            (DeviceStatusState::DeviceStatusDegraded, reason.as_str(), 0)
+        }
+        DeviceStatus::Fault { code, message } => (
+            DeviceStatusState::DeviceStatusFault,
+            message.as_str(),
+            *code,
+        ),
+    };
+    ProtoDeviceStatus {
+        state: state as i32,
+        reason: reason.to_string(),
+        fault_code,
+    }
+}
+
+/// Short lowercase state name, e.g. "degraded"
+fn device_status_state_name(status: Option<&ProtoDeviceStatus>) -> String {
+    status
+        .map(ProtoDeviceStatus::state)
+        .unwrap_or(DeviceStatusState::DeviceStatusReady)
+        .as_str_name()
+        .trim_start_matches("DEVICE_STATUS_")
+        .to_ascii_lowercase()
+}
+
+/// Status as a `fields_json` value for state subscriptions
+fn device_status_to_json(status: &ProtoDeviceStatus) -> String {
+    let mut json = serde_json::json!({ "state": device_status_state_name(Some(status)) });
+    if !status.reason.is_empty() {
+        json["reason"] = status.reason.clone().into();
+    }
+    if status.state() == DeviceStatusState::DeviceStatusFault {
+        json["code"] = status.fault_code.into();
+    }
+    json.to_string()
+}
+
+/// Keys accepted by `ListDevicesRequest.filter`
+const DEVICE_FILTER_KEYS: &[&str] = &[
+    "id",
+    "name",
+    "driver_type",
+    "category",
+    "capability",
+    "tag",
+    "online",
+    "status",
+    "simulated",
+];
+
+/// Values of a registered device for a filter key (IDs include aliases)
+fn device_filter_values(device: &DeviceInfo, key: &str) -> Vec<String> {
+    match key {
+        "id" => std::iter::once(device.id.clone())
+            .chain(device.aliases.iter().cloned())
+            .collect(),
+        "name" => vec![device.name.clone()],
+        "driver_type" => vec![device.driver_type.clone()],
+        "category" => vec![category_name(device.category)],
+        "capability" => device.capabilities.clone(),
+        "tag" => device.tags.clone(),
+        "online" => {
+            let state = device.status.as_ref().map(ProtoDeviceStatus::state);
+            let offline = matches!(
+                state,
+                Some(
+                    DeviceStatusState::DeviceStatusFault
+                        | DeviceStatusState::DeviceStatusUninitialized
+                )
+            );
+            vec![(!offline).to_string()]
+        }
+        "status" => vec![device_status_state_name(device.status.as_ref())],
+        "simulated" => vec![device.simulated.to_string()],
+        _ => vec![],
+    }
+}
+
+/// Short lowercase category name, e.g. "power_meter"
+fn category_name(category: i32) -> String {
+    protocol::DeviceCategory::try_from(category)
+        .unwrap_or(protocol::DeviceCategory::Unspecified)
+        .as_str_name()
+        .trim_start_matches("DEVICE_CATEGORY_")
+        .to_ascii_lowercase()
+}
+
+/// Sort devices by a `ListDevicesRequest.sort_by` key (ID if empty)
+fn sort_devices(devices: &mut [DeviceInfo], sort_by: &str, descending: bool) -> Result<(), Status> {
+    match sort_by {
+        "" | "id" => devices.sort_by(|a, b| a.id.cmp(&b.id)),
+        "name" => devices.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id))),
+        "driver_type" => devices.sort_by(|a, b| {
+            a.driver_type
+                .cmp(&b.driver_type)
+                .then_with(|| a.id.cmp(&b.id))
+        }),
+        "category" => devices.sort_by(|a, b| {
+            category_name(a.category)
+                .cmp(&category_name(b.category))
+                .then_with(|| a.id.cmp(&b.id))
+        }),
+        other => {
+            return Err(Status::invalid_argument(format!(
+                "Unknown sort key '{}' (expected id, name, driver_type or category)",
+                other
+            )));
+        }
+    }
+    if descending {
+        devices.reverse();
+    }
+    Ok(())
+}
+
+/// Convert a typed parameter value to proto (`None` for non-scalar values)
+fn typed_value_to_proto(value: &TypedParameterValue) -> Option<TypedValue> {
+    let kind = match value {
+        TypedParameterValue::Float(f) => TypedKind::FloatValue(*f),
+        TypedParameterValue::Int(i) => TypedKind::IntValue(*i),
+        TypedParameterValue::Bool(b) => TypedKind::BoolValue(*b),
+        TypedParameterValue::String(s) => TypedKind::StringValue(s.clone()),
+        _ => return None,
+    };
+    Some(TypedValue { kind: Some(kind) })
+}
+
+/// Convert a proto typed value (`None` if no variant is set)
+fn typed_value_from_proto(value: &TypedValue) -> Option<TypedParameterValue> {
+    Some(match value.kind.as_ref()? {
+        TypedKind::FloatValue(f) => TypedParameterValue::Float(*f),
+        TypedKind::IntValue(i) => TypedParameterValue::Int(*i),
+        TypedKind::BoolValue(b) => TypedParameterValue::Bool(*b),
+        TypedKind::StringValue(s) => TypedParameterValue::String(s.clone()),
+    })
+}
+
+/// Convert a registry capability report to proto
+fn capability_report_to_proto(
+    report: hardware::registry::CapabilityReport,
+) -> ProtoCapabilityDescriptor {
+    let descriptor = report.descriptor;
+    ProtoCapabilityDescriptor {
+        capability: descriptor.capability.as_str().to_string(),
+        trait_name: descriptor.trait_name.to_string(),
+        version: descriptor.version,
+        description: descriptor.description.to_string(),
+        methods: descriptor
+            .methods
+            .iter()
+            .map(|method| CapabilityMethod {
+                name: method.name.to_string(),
+                description: method.description.to_string(),
+                params: method
+                    .params
+                    .iter()
+                    .map(|param| CapabilityMethodParam {
+                        name: param.name.to_string(),
+                        type_name: param.type_name.to_string(),
+                        units: param.units.to_string(),
+                    })
+                    .collect(),
+                returns: method.returns.to_string(),
+                provided: method.provided,
+            })
+            .collect(),
+        details: report.details.into_iter().collect(),
     }
 }
 
@@ -2714,6 +4663,7 @@ fn get_device_category(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::grpc::proto::BatchCommand;
     use hardware::registry::create_mock_registry;
 
     #[tokio::test]
@@ -2723,6 +4673,7 @@ mod tests {
 
         let request = Request::new(ListDevicesRequest {
             capability_filter: None,
+            ..Default::default()
         });
         let response = service.list_devices(request).await.unwrap();
         let devices = response.into_inner().devices;
@@ -2744,6 +4695,7 @@ mod tests {
         // Filter for movable devices
         let request = Request::new(ListDevicesRequest {
             capability_filter: Some("movable".to_string()),
+            ..Default::default()
         });
         let response = service.list_devices(request).await.unwrap();
         let devices = response.into_inner().devices;
@@ -2759,6 +4711,115 @@ mod tests {
         let _ = devices[0].is_movable; // Accessing triggers deprecation warning at compile time
     }
 
+    #[tokio::test]
+    async fn test_list_devices_filter_sort_and_page() {
+        let registry = create_mock_registry().await.unwrap();
+        let service = HardwareServiceImpl::new(Arc::new(registry));
+        let list = |filter: &str, sort_by: &str, offset: u32, limit: u32| {
+            Request::new(ListDevicesRequest {
+                filter: filter.to_string(),
+                sort_by: sort_by.to_string(),
+                descending: true,
+                offset,
+                limit,
+                ..Default::default()
+            })
+        };
+        let ids = |response: &ListDevicesResponse| -> Vec<String> {
+            response.devices.iter().map(|d| d.id.clone()).collect()
+        };
+
+        let response = service
+            .list_devices(list("", "id", 1, 1))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.total_count, 3);
+        assert_eq!(ids(&response), ["mock_power_meter"]);
+
+        let response = service
+            .list_devices(list(
+                "capability=frame_producer|movable online=true",
+                "",
+                0,
+                0,
+            ))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(ids(&response), ["mock_stage", "mock_camera"]);
+
+        let response = service
+            .list_devices(list("driver_type!=mock_* category=stage", "", 0, 0))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.total_count, 0);
+
+        assert!(
+            service
+                .list_devices(list("colour=red", "", 0, 0))
+                .await
+                .is_err()
+        );
+        assert!(
+            service
+                .list_devices(list("", "colour", 0, 0))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_device_status_in_listing_and_state() {
+        let registry = Arc::new(create_mock_registry().await.unwrap());
+        let service = HardwareServiceImpl::new(registry.clone());
+        registry.set_device_status(
+            "mock_camera",
+            DeviceStatus::Degraded {
+                reason: "Sensor not at setpoint".to_string(),
+            },
+        );
+        registry.set_device_status(
+            "mock_power_meter",
+            DeviceStatus::Fault {
+                code: 7,
+                message: "Head disconnected".to_string(),
+            },
+        );
+
+        let list = |filter: &str| {
+            Request::new(ListDevicesRequest {
+                filter: filter.to_string(),
+                ..Default::default()
+            })
+        };
+        let response = service.list_devices(list("status=degraded")).await.unwrap();
+        let devices = response.into_inner().devices;
+        assert_eq!(devices.len(), 1);
+        let status = devices[0].status.clone().unwrap();
+        assert_eq!(status.state(), DeviceStatusState::DeviceStatusDegraded);
+        assert_eq!(status.reason, "Sensor not at setpoint");
+
+        let response = service.list_devices(list("online=false")).await.unwrap();
+        let offline: Vec<_> = response
+            .into_inner()
+            .devices
+            .into_iter()
+            .map(|d| d.id)
+            .collect();
+        assert_eq!(offline, ["mock_power_meter"]);
+
+        let state = fetch_device_state(&registry, "mock_power_meter")
+            .await
+            .unwrap();
+        assert!(!state.online);
+        let fields = device_state_to_fields_json(&state);
+        let status: serde_json::Value = serde_json::from_str(&fields["status"]).unwrap();
+        assert_eq!(status["state"], "fault");
+        assert_eq!(status["code"], 7);
+    }
+
     /// Test that DeviceInfo includes the dynamic capabilities list (bd-4myc).
     ///
     /// The `capabilities` field is the canonical source of truth for device capabilities.
@@ -2770,6 +4831,7 @@ mod tests {
 
         let request = Request::new(ListDevicesRequest {
             capability_filter: None,
+            ..Default::default()
         });
         let response = service.list_devices(request).await.unwrap();
         let devices = response.into_inner().devices;
@@ -2822,6 +4884,7 @@ mod tests {
 
         let request = Request::new(ReadValueRequest {
             device_id: "mock_power_meter".to_string(),
+            mode: None,
         });
         let response = service.read_value(request).await.unwrap();
         let resp = response.into_inner();
@@ -2836,6 +4899,29 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_read_value_acquisition_mode() {
+        let registry = create_mock_registry().await.unwrap();
+        let service = HardwareServiceImpl::new(Arc::new(registry));
+
+        let mode = common::acquisition::AcquisitionMode::Average { samples: 4 };
+        let request = Request::new(ReadValueRequest {
+            device_id: "mock_power_meter".to_string(),
+            mode: Some(mode.into()),
+        });
+        let resp = service.read_value(request).await.unwrap().into_inner();
+        assert_eq!(resp.samples, 4);
+        assert!(resp.native_mode);
+        assert_eq!(resp.mode.unwrap().to_domain(), mode);
+
+        let request = Request::new(ReadValueRequest {
+            device_id: "mock_power_meter".to_string(),
+            mode: Some(common::acquisition::AcquisitionMode::Average { samples: 0 }.into()),
+        });
+        let status = service.read_value(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
     /// Test that ReadValueResponse includes the measurement units from device metadata.
     ///
     /// This is critical for the GUI to correctly normalize power readings.
@@ -2848,6 +4934,7 @@ mod tests {
 
         let request = Request::new(ReadValueRequest {
             device_id: "mock_power_meter".to_string(),
+            mode: None,
         });
         let response = service.read_value(request).await.unwrap();
         let resp = response.into_inner();
@@ -2873,6 +4960,7 @@ mod tests {
 
         let request = Request::new(ReadValueRequest {
             device_id: "mock_power_meter".to_string(),
+            mode: None,
         });
         let response = service.read_value(request).await.unwrap();
         let resp = response.into_inner();
@@ -2898,6 +4986,7 @@ mod tests {
         // mock_stage is Movable, not Readable
         let request = Request::new(ReadValueRequest {
             device_id: "mock_stage".to_string(),
+            mode: None,
         });
         let result = service.read_value(request).await;
 
@@ -3185,6 +5274,357 @@ mod tests {
         // units might differ based on mock implementation details
     }
 
+    #[tokio::test]
+    async fn test_device_state_at_after_move() {
+        let registry = create_mock_registry().await.unwrap();
+        let service = HardwareServiceImpl::new(Arc::new(registry));
+
+        let before_move = now_ns();
+        service
+            .move_absolute(Request::new(MoveRequest {
+                device_id: "mock_stage".to_string(),
+                value: 5.0,
+                wait_for_completion: None,
+                timeout_ms: None,
+            }))
+            .await
+            .unwrap();
+
+        let state = service
+            .get_device_state_at(Request::new(DeviceStateAtRequest {
+                device_id: "mock_stage".to_string(),
+                timestamp_ns: now_ns(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(state.complete);
+        let target = state
+            .values
+            .iter()
+            .find(|v| v.name == "target_position")
+            .expect("move target recorded");
+        assert_eq!(target.new_value, "5");
+
+        // Nothing was recorded for the stage before the move
+        let earlier = service
+            .get_device_state_at(Request::new(DeviceStateAtRequest {
+                device_id: "mock_stage".to_string(),
+                timestamp_ns: before_move,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(earlier.values.iter().all(|v| v.name != "target_position"));
+
+        let history = service
+            .query_device_history(Request::new(DeviceHistoryRequest {
+                device_id: "mock_stage".to_string(),
+                parameter_names: vec!["target_position".to_string()],
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(history.events.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_typed_parameter_set() {
+        let registry = create_mock_registry().await.unwrap();
+        let service = HardwareServiceImpl::new(Arc::new(registry));
+        let typed_request = |kind| {
+            Request::new(SetParameterRequest {
+                device_id: "mock_stage".to_string(),
+                parameter_name: "position".to_string(),
+                value: String::new(),
+                typed_value: Some(TypedValue { kind: Some(kind) }),
+            })
+        };
+
+        // An integer is accepted for a float parameter and read back as float
+        let response = service
+            .set_parameter(typed_request(TypedKind::IntValue(3)))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(response.success);
+        assert_eq!(
+            response.actual_typed.and_then(|t| t.kind),
+            Some(TypedKind::FloatValue(3.0))
+        );
+
+        let value = service
+            .get_parameter(Request::new(GetParameterRequest {
+                device_id: "mock_stage".to_string(),
+                parameter_name: "position".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(
+            value.typed.and_then(|t| t.kind),
+            Some(TypedKind::FloatValue(3.0))
+        );
+
+        // Wrong types are refused before reaching the driver
+        let status = service
+            .set_parameter(typed_request(TypedKind::BoolValue(true)))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    fn set_position(value: &str) -> Request<SetParameterRequest> {
+        Request::new(SetParameterRequest {
+            device_id: "mock_stage".to_string(),
+            parameter_name: "position".to_string(),
+            value: value.to_string(),
+            typed_value: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_set_parameter_respects_output_limits() {
+        let registry = create_mock_registry().await.unwrap();
+        registry
+            .set_output_limits(HashMap::from([(
+                "mock_stage".to_string(),
+                HashMap::from([(
+                    "position".to_string(),
+                    common::output_limits::OutputLimit {
+                        min: Some(0.0),
+                        max: Some(10.0),
+                        ..Default::default()
+                    },
+                )]),
+            )]))
+            .unwrap();
+        let service = HardwareServiceImpl::new(Arc::new(registry));
+
+        let status = service
+            .set_parameter(set_position("50.0"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("outside limits"));
+        let response = service
+            .set_parameter(set_position("5.0"))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(response.success);
+        assert_eq!(response.actual_value, "5.0");
+    }
+
+    #[tokio::test]
+    async fn test_set_parameter_respects_keep_out_zones() {
+        let registry = create_mock_registry().await.unwrap();
+        registry
+            .set_keep_out_zones(HashMap::from([(
+                "mock_stage".to_string(),
+                vec![common::keep_out::KeepOutZone::range(
+                    "sample holder",
+                    20.0,
+                    30.0,
+                )],
+            )]))
+            .unwrap();
+        let service = HardwareServiceImpl::new(Arc::new(registry));
+
+        // Into the zone, and across it
+        let status = service
+            .set_parameter(set_position("25.0"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("sample holder"));
+        assert!(service.set_parameter(set_position("40.0")).await.is_err());
+
+        let response = service
+            .set_parameter(set_position("5.0"))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(response.success);
+    }
+
+    fn batch_move(device_id: &str, value: f64) -> BatchCommand {
+        BatchCommand {
+            command: Some(BatchCommandKind::MoveAbsolute(MoveRequest {
+                device_id: device_id.to_string(),
+                value,
+                wait_for_completion: Some(true),
+                timeout_ms: None,
+            })),
+        }
+    }
+
+    fn batch_set(parameter_name: &str, value: &str) -> BatchCommand {
+        BatchCommand {
+            command: Some(BatchCommandKind::SetParameter(SetParameterRequest {
+                device_id: "mock_stage".to_string(),
+                parameter_name: parameter_name.to_string(),
+                value: value.to_string(),
+                typed_value: None,
+            })),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_all_or_nothing_rolls_back() {
+        let registry = Arc::new(create_mock_registry().await.unwrap());
+        let service = HardwareServiceImpl::new(registry.clone());
+
+        let response = service
+            .execute_batch(Request::new(ExecuteBatchRequest {
+                commands: vec![
+                    batch_move("mock_stage", 2.0),
+                    batch_set("no_such_parameter", "1"),
+                    batch_move("mock_stage", 4.0),
+                ],
+                mode: BatchMode::AllOrNothing.into(),
+                lock_timeout_ms: 0,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!response.success);
+        assert!(response.error_message.contains("Command 1"));
+        let statuses: Vec<_> = response.results.iter().map(|r| r.status()).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                BatchCommandStatus::BatchCommandRolledBack,
+                BatchCommandStatus::BatchCommandFailed,
+                BatchCommandStatus::BatchCommandSkipped,
+            ]
+        );
+        let stage = registry.get_movable("mock_stage").unwrap();
+        assert!(stage.position().await.unwrap().abs() < 1e-9);
+
+        // Unsupported commands are refused before anything runs
+        let status = service
+            .execute_batch(Request::new(ExecuteBatchRequest {
+                commands: vec![
+                    batch_move("mock_stage", 2.0),
+                    batch_move("mock_power_meter", 1.0),
+                ],
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+        assert!(stage.position().await.unwrap().abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_batch_best_effort_runs_every_command() {
+        let registry = Arc::new(create_mock_registry().await.unwrap());
+        let service = HardwareServiceImpl::new(registry.clone());
+
+        let response = service
+            .execute_batch(Request::new(ExecuteBatchRequest {
+                commands: vec![
+                    batch_set("no_such_parameter", "1"),
+                    batch_move("mock_stage", 1.5),
+                ],
+                mode: BatchMode::BestEffort.into(),
+                lock_timeout_ms: 0,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!response.success);
+        assert_eq!(
+            response.results[0].status(),
+            BatchCommandStatus::BatchCommandFailed
+        );
+        assert_eq!(
+            response.results[1].status(),
+            BatchCommandStatus::BatchCommandOk
+        );
+        let stage = registry.get_movable("mock_stage").unwrap();
+        assert!((stage.position().await.unwrap() - 1.5).abs() < 1e-9);
+
+        // A held device lock makes the batch wait, then give up
+        let _held = registry.lock_devices(["mock_stage"]).await;
+        let status = service
+            .execute_batch(Request::new(ExecuteBatchRequest {
+                commands: vec![batch_move("mock_stage", 0.0)],
+                mode: BatchMode::BestEffort.into(),
+                lock_timeout_ms: 50,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Aborted);
+    }
+
+    #[tokio::test]
+    async fn test_dangerous_parameter_requires_confirmation() {
+        use hardware::registry::ParameterPolicy;
+
+        let registry = create_mock_registry().await.unwrap();
+        registry.set_parameter_policies(HashMap::from([(
+            "mock_stage".to_string(),
+            HashMap::from([(
+                "position".to_string(),
+                ParameterPolicy {
+                    dangerous: true,
+                    confirm_timeout_s: None,
+                },
+            )]),
+        )]));
+        let service = HardwareServiceImpl::new(Arc::new(registry));
+
+        let parameters = service
+            .list_parameters(Request::new(ListParametersRequest {
+                device_id: "mock_stage".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .parameters;
+        assert!(
+            parameters
+                .iter()
+                .any(|p| p.name == "position" && p.dangerous)
+        );
+
+        // The set only proposes the change
+        let proposed = service
+            .set_parameter(Request::new(SetParameterRequest {
+                device_id: "mock_stage".to_string(),
+                parameter_name: "position".to_string(),
+                value: "12.5".to_string(),
+                typed_value: None,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!proposed.success);
+        assert!(proposed.confirmation_required);
+        assert!(!proposed.proposal_token.is_empty());
+
+        let confirmed = service
+            .confirm_parameter_change(Request::new(ConfirmParameterChangeRequest {
+                proposal_token: proposed.proposal_token.clone(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(confirmed.success);
+        assert_eq!(confirmed.actual_value, "12.5");
+
+        // A token can only be confirmed once
+        let again = service
+            .confirm_parameter_change(Request::new(ConfirmParameterChangeRequest {
+                proposal_token: proposed.proposal_token,
+            }))
+            .await;
+        assert_eq!(again.unwrap_err().code(), tonic::Code::NotFound);
+    }
+
     // =========================================================================
     // StreamLimiter Tests (bd-64hu)
     // =========================================================================
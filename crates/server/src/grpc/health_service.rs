@@ -52,11 +52,11 @@ impl Health for HealthServiceImpl {
         let service = request.into_inner().service;
         let status = {
             let statuses = self.statuses.lock().unwrap_or_else(|p| p.into_inner());
-            if service.is_empty() {
-                // Overall server status
-                ServingStatus::Serving
-            } else if let Some(tx) = statuses.get(&service) {
+            if let Some(tx) = statuses.get(&service) {
                 *tx.borrow()
+            } else if service.is_empty() {
+                // Overall server status, unless set (e.g. by the self-test)
+                ServingStatus::Serving
             } else {
                 // Service not found in our map
                 // Standard behavior is to return NOT_FOUND status code
@@ -78,9 +78,14 @@ impl Health for HealthServiceImpl {
         let rx = {
             let mut statuses = self.statuses.lock().unwrap_or_else(|p| p.into_inner());
             if service.is_empty() {
-                // Overall health - simplified to always SERVING for now
-                let (_, rx) = watch::channel(ServingStatus::Serving);
-                rx
+                if let Some(tx) = statuses.get(&service) {
+                    // Overall health, as set by the server (e.g. after the self-test)
+                    tx.subscribe()
+                } else {
+                    // Overall health - simplified to always SERVING for now
+                    let (_, rx) = watch::channel(ServingStatus::Serving);
+                    rx
+                }
             } else {
                 // If known service, subscribe
                 // If unknown, we can either return error or start tracking as UNKNOWN
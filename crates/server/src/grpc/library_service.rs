@@ -0,0 +1,725 @@
+//! LibraryService implementation: versioned scripts, plans and device configs
+//!
+//! Lets clients manage the daemon's script, plan and device configuration
+//! files remotely instead of relying on a shared filesystem. Files are kept
+//! in the library directory, one subdirectory per kind:
+//!
+//! ```text
+//! <library>/scripts/scan.rhai                    latest content
+//! <library>/scripts/.history/scan.rhai/index.json
+//! <library>/scripts/.history/scan.rhai/3         retained versions
+//! ```
+//!
+//! Uploads arrive as a header followed by content chunks. Size and SHA-256
+//! are checked against the header, the content is validated for its kind
+//! (Rhai syntax, TOML/JSON parsing, device config schema) and only then
+//! stored as a new version. Files dropped into the directory on the daemon
+//! host show up as the next version of the file.
+
+use crate::grpc::proto::{
+    DeleteLibraryFileRequest, DeleteLibraryFileResponse, DownloadLibraryFileRequest,
+    LibraryFileChunk, LibraryFileHeader, LibraryFileInfo, LibraryFileKind, LibraryFileVersion,
+    ListLibraryFilesRequest, ListLibraryFilesResponse, UploadLibraryFileResponse,
+    library_file_chunk::Payload, library_service_server::LibraryService,
+};
+use common::experiment::document::now_ns;
+use common::limits::{LIBRARY_CHUNK_SIZE, MAX_LIBRARY_FILE_SIZE};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+use tokio::fs;
+use tokio::sync::Mutex;
+use tonic::{Code, Request, Response, Status, Streaming};
+
+/// Versions retained per file by default
+pub const DEFAULT_RETAINED_VERSIONS: usize = 10;
+
+const HISTORY_DIR: &str = ".history";
+const INDEX_FILENAME: &str = "index.json";
+const MAX_NAME_LEN: usize = 128;
+
+const ALL_KINDS: [LibraryFileKind; 3] = [
+    LibraryFileKind::Script,
+    LibraryFileKind::Plan,
+    LibraryFileKind::DeviceConfig,
+];
+
+/// Subdirectory of the library holding files of a kind
+fn kind_dir(kind: LibraryFileKind) -> &'static str {
+    match kind {
+        LibraryFileKind::Script => "scripts",
+        LibraryFileKind::Plan => "plans",
+        LibraryFileKind::DeviceConfig => "devices",
+    }
+}
+
+/// File extensions accepted for a kind
+fn kind_extensions(kind: LibraryFileKind) -> &'static [&'static str] {
+    match kind {
+        LibraryFileKind::Script => &["rhai"],
+        LibraryFileKind::Plan => &["rhai", "toml", "json"],
+        LibraryFileKind::DeviceConfig => &["toml"],
+    }
+}
+
+fn hash_content(content: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Check a library file name
+///
+/// Names are plain file names (no directories, not hidden) with an extension
+/// accepted for the kind.
+pub fn validate_name(kind: LibraryFileKind, name: &str) -> Result<(), Status> {
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err(Status::invalid_argument(format!(
+            "File name must be 1-{} characters",
+            MAX_NAME_LEN
+        )));
+    }
+    if name.starts_with('.')
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    {
+        return Err(Status::invalid_argument(format!(
+            "Invalid file name '{}' (letters, digits, '.', '_' and '-' only, not starting with '.')",
+            name
+        )));
+    }
+    let extension = Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    let allowed = kind_extensions(kind);
+    if !allowed.contains(&extension) {
+        return Err(Status::invalid_argument(format!(
+            "{} files must end in .{}",
+            kind.as_str_name(),
+            allowed.join(", .")
+        )));
+    }
+    Ok(())
+}
+
+/// Check that content is usable as a file of the given kind
+///
+/// Returns a message describing the first problem found.
+pub fn validate_content(kind: LibraryFileKind, name: &str, content: &[u8]) -> Result<(), String> {
+    let text = std::str::from_utf8(content).map_err(|e| format!("Not valid UTF-8: {}", e))?;
+    let extension = Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+
+    match (kind, extension) {
+        (LibraryFileKind::DeviceConfig, _) => hardware::config::load_device_config_from_str(text)
+            .map(|_| ())
+            .map_err(|e| format!("Invalid device config: {:#}", e)),
+        (_, "rhai") => validate_rhai(text),
+        (_, "toml") => toml::from_str::<toml::Table>(text)
+            .map(|_| ())
+            .map_err(|e| format!("Invalid TOML: {}", e)),
+        (_, "json") => serde_json::from_str::<serde_json::Value>(text)
+            .map(|_| ())
+            .map_err(|e| format!("Invalid JSON: {}", e)),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(feature = "scripting")]
+fn validate_rhai(text: &str) -> Result<(), String> {
+    scripting::rhai::Engine::new()
+        .compile(text)
+        .map(|_| ())
+        .map_err(|e| format!("Parse error: {}", e))
+}
+
+#[cfg(not(feature = "scripting"))]
+fn validate_rhai(_text: &str) -> Result<(), String> {
+    Ok(())
+}
+
+/// One stored version of a library file (index.json entry)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VersionEntry {
+    version: u32,
+    size: u64,
+    sha256: String,
+    created_ns: u64,
+    #[serde(default)]
+    comment: String,
+    /// Latest content placed on the daemon host, not yet in the history
+    #[serde(skip)]
+    untracked: bool,
+}
+
+impl VersionEntry {
+    fn to_proto(&self) -> LibraryFileVersion {
+        LibraryFileVersion {
+            version: self.version,
+            size: self.size,
+            sha256: self.sha256.clone(),
+            created_ns: self.created_ns,
+            comment: self.comment.clone(),
+        }
+    }
+}
+
+/// Versioned file store behind the LibraryService
+#[derive(Debug, Clone)]
+pub struct FileLibrary {
+    root: PathBuf,
+    retained_versions: usize,
+    /// Serializes writes so version numbers stay unique
+    write_lock: Arc<Mutex<()>>,
+}
+
+impl FileLibrary {
+    /// Create a library rooted at the given directory
+    pub fn new(root: PathBuf) -> Self {
+        for kind in ALL_KINDS {
+            if let Err(e) = std::fs::create_dir_all(root.join(kind_dir(kind))) {
+                tracing::warn!("Failed to create library directory: {}", e);
+            }
+        }
+        Self {
+            root,
+            retained_versions: DEFAULT_RETAINED_VERSIONS,
+            write_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Set how many versions are kept per file (at least 1)
+    pub fn with_retained_versions(mut self, retained_versions: usize) -> Self {
+        self.retained_versions = retained_versions.max(1);
+        self
+    }
+
+    /// Library root directory
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path of the latest content of a file
+    pub fn file_path(&self, kind: LibraryFileKind, name: &str) -> PathBuf {
+        self.root.join(kind_dir(kind)).join(name)
+    }
+
+    fn history_dir(&self, kind: LibraryFileKind, name: &str) -> PathBuf {
+        self.root.join(kind_dir(kind)).join(HISTORY_DIR).join(name)
+    }
+
+    /// Versions of a file, oldest first
+    ///
+    /// If the file on disk does not match the newest indexed version, it is
+    /// reported as an untracked newer version.
+    async fn versions(
+        &self,
+        kind: LibraryFileKind,
+        name: &str,
+    ) -> Result<Vec<VersionEntry>, Status> {
+        let index_path = self.history_dir(kind, name).join(INDEX_FILENAME);
+        let mut entries: Vec<VersionEntry> = match fs::read_to_string(&index_path).await {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring corrupt library index {:?}: {}", index_path, e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+
+        let path = self.file_path(kind, name);
+        if let Ok(content) = fs::read(&path).await {
+            let sha256 = hash_content(&content);
+            if entries.last().is_none_or(|last| last.sha256 != sha256) {
+                let created_ns = fs::metadata(&path)
+                    .await
+                    .ok()
+                    .and_then(|meta| meta.modified().ok())
+                    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                    .map_or_else(now_ns, |d| d.as_nanos() as u64);
+                entries.push(VersionEntry {
+                    version: entries.last().map_or(1, |last| last.version + 1),
+                    size: content.len() as u64,
+                    sha256,
+                    created_ns,
+                    comment: "Changed on the daemon host".to_string(),
+                    untracked: true,
+                });
+            }
+        } else {
+            // Deleted on the daemon host
+            entries.clear();
+        }
+        Ok(entries)
+    }
+
+    /// Files in the library, sorted by kind and name
+    pub async fn list(
+        &self,
+        kind: Option<LibraryFileKind>,
+    ) -> Result<Vec<LibraryFileInfo>, Status> {
+        let kinds: Vec<LibraryFileKind> = match kind {
+            Some(kind) => vec![kind],
+            None => ALL_KINDS.to_vec(),
+        };
+
+        let mut files = Vec::new();
+        for kind in kinds {
+            let mut names = Vec::new();
+            let Ok(mut dir) = fs::read_dir(self.root.join(kind_dir(kind))).await else {
+                continue;
+            };
+            while let Some(entry) = dir
+                .next_entry()
+                .await
+                .map_err(|e| Status::internal(format!("Failed to read library: {}", e)))?
+            {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let is_file = entry.file_type().await.is_ok_and(|t| t.is_file());
+                if is_file && validate_name(kind, &name).is_ok() {
+                    names.push(name);
+                }
+            }
+            names.sort();
+
+            for name in names {
+                let versions = self.versions(kind, &name).await?;
+                let Some(latest) = versions.last() else {
+                    continue;
+                };
+                files.push(LibraryFileInfo {
+                    kind: kind as i32,
+                    name,
+                    latest: Some(latest.to_proto()),
+                    history: versions.iter().rev().map(VersionEntry::to_proto).collect(),
+                });
+            }
+        }
+        Ok(files)
+    }
+
+    /// Store new content as the next version of a file
+    ///
+    /// `base_version` is the version the new content was derived from; the
+    /// save is refused with `FailedPrecondition` if the file has moved on
+    /// since (0 skips the check). Invalid content is refused with
+    /// `InvalidArgument`.
+    pub async fn save(
+        &self,
+        kind: LibraryFileKind,
+        name: &str,
+        content: &[u8],
+        base_version: u32,
+        comment: &str,
+    ) -> Result<LibraryFileVersion, Status> {
+        validate_name(kind, name)?;
+        if content.len() > MAX_LIBRARY_FILE_SIZE {
+            return Err(Status::invalid_argument(format!(
+                "File too large: {} bytes (max {} bytes)",
+                content.len(),
+                MAX_LIBRARY_FILE_SIZE
+            )));
+        }
+        validate_content(kind, name, content).map_err(Status::invalid_argument)?;
+
+        let _guard = self.write_lock.lock().await;
+        let mut versions = self.versions(kind, name).await?;
+        let current = versions.last().map_or(0, |last| last.version);
+        if base_version != 0 && base_version != current {
+            return Err(Status::failed_precondition(format!(
+                "{} was changed since version {} (now at version {})",
+                name, base_version, current
+            )));
+        }
+
+        let history_dir = self.history_dir(kind, name);
+        fs::create_dir_all(&history_dir)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to create history directory: {}", e)))?;
+
+        // Keep content that was placed on the daemon host before replacing it
+        if let Some(last) = versions.last_mut()
+            && last.untracked
+        {
+            fs::copy(
+                self.file_path(kind, name),
+                history_dir.join(last.version.to_string()),
+            )
+            .await
+            .map_err(|e| Status::internal(format!("Failed to archive file: {}", e)))?;
+            last.untracked = false;
+        }
+
+        let entry = VersionEntry {
+            version: current + 1,
+            size: content.len() as u64,
+            sha256: hash_content(content),
+            created_ns: now_ns(),
+            comment: comment.to_string(),
+            untracked: false,
+        };
+        let write_err =
+            |e: std::io::Error| Status::internal(format!("Failed to write file: {}", e));
+        fs::write(history_dir.join(entry.version.to_string()), content)
+            .await
+            .map_err(write_err)?;
+        // Write-then-rename so readers never see partial content
+        let path = self.file_path(kind, name);
+        let temp_path = path.with_file_name(format!(".{}.tmp", name));
+        fs::write(&temp_path, content).await.map_err(write_err)?;
+        fs::rename(&temp_path, &path).await.map_err(write_err)?;
+        versions.push(entry.clone());
+
+        // Drop the oldest versions beyond the retention limit
+        let excess = versions.len().saturating_sub(self.retained_versions);
+        for old in versions.drain(..excess) {
+            let _ = fs::remove_file(history_dir.join(old.version.to_string())).await;
+        }
+
+        let index = serde_json::to_string_pretty(&versions)
+            .map_err(|e| Status::internal(format!("Failed to serialize index: {}", e)))?;
+        fs::write(history_dir.join(INDEX_FILENAME), index)
+            .await
+            .map_err(write_err)?;
+
+        tracing::info!(
+            kind = kind.as_str_name(),
+            name,
+            version = entry.version,
+            "Stored library file"
+        );
+        Ok(entry.to_proto())
+    }
+
+    /// Content of a version of a file (0 = latest)
+    pub async fn load(
+        &self,
+        kind: LibraryFileKind,
+        name: &str,
+        version: u32,
+    ) -> Result<(LibraryFileVersion, Vec<u8>), Status> {
+        validate_name(kind, name)?;
+        let versions = self.versions(kind, name).await?;
+        let entry = if version == 0 {
+            versions.last()
+        } else {
+            versions.iter().find(|entry| entry.version == version)
+        }
+        .ok_or_else(|| Status::not_found(format!("{} version {} not found", name, version)))?;
+
+        let path = if entry.untracked {
+            self.file_path(kind, name)
+        } else {
+            self.history_dir(kind, name).join(entry.version.to_string())
+        };
+        let content = fs::read(&path)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to read {}: {}", name, e)))?;
+        if hash_content(&content) != entry.sha256 {
+            return Err(Status::data_loss(format!(
+                "{} version {} failed its integrity check",
+                name, entry.version
+            )));
+        }
+        Ok((entry.to_proto(), content))
+    }
+
+    /// Remove a file and its history; false if it did not exist
+    pub async fn delete(&self, kind: LibraryFileKind, name: &str) -> Result<bool, Status> {
+        validate_name(kind, name)?;
+        let _guard = self.write_lock.lock().await;
+        let _ = fs::remove_dir_all(self.history_dir(kind, name)).await;
+        match fs::remove_file(self.file_path(kind, name)).await {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(Status::internal(format!(
+                "Failed to delete {}: {}",
+                name, e
+            ))),
+        }
+    }
+}
+
+/// Split content into a header chunk followed by data chunks
+pub fn content_chunks(header: LibraryFileHeader, content: &[u8]) -> Vec<LibraryFileChunk> {
+    std::iter::once(Payload::Header(header))
+        .chain(
+            content
+                .chunks(LIBRARY_CHUNK_SIZE)
+                .map(|chunk| Payload::Data(chunk.to_vec())),
+        )
+        .map(|payload| LibraryFileChunk {
+            payload: Some(payload),
+        })
+        .collect()
+}
+
+fn file_kind(value: i32) -> Result<LibraryFileKind, Status> {
+    LibraryFileKind::try_from(value)
+        .map_err(|_| Status::invalid_argument(format!("Unknown library file kind {}", value)))
+}
+
+/// LibraryService gRPC implementation
+#[derive(Debug, Clone)]
+pub struct LibraryServiceImpl {
+    library: FileLibrary,
+}
+
+impl LibraryServiceImpl {
+    pub fn new(library: FileLibrary) -> Self {
+        Self { library }
+    }
+}
+
+#[tonic::async_trait]
+impl LibraryService for LibraryServiceImpl {
+    type DownloadLibraryFileStream = std::pin::Pin<
+        Box<dyn tokio_stream::Stream<Item = Result<LibraryFileChunk, Status>> + Send>,
+    >;
+
+    async fn list_library_files(
+        &self,
+        request: Request<ListLibraryFilesRequest>,
+    ) -> Result<Response<ListLibraryFilesResponse>, Status> {
+        let req = request.into_inner();
+        let kind = req.kind.map(file_kind).transpose()?;
+        let files = self.library.list(kind).await?;
+        Ok(Response::new(ListLibraryFilesResponse { files }))
+    }
+
+    async fn upload_library_file(
+        &self,
+        request: Request<Streaming<LibraryFileChunk>>,
+    ) -> Result<Response<UploadLibraryFileResponse>, Status> {
+        let mut stream = request.into_inner();
+
+        let Some(Payload::Header(header)) = stream.message().await?.and_then(|chunk| chunk.payload)
+        else {
+            return Err(Status::invalid_argument(
+                "First upload chunk must carry the file header",
+            ));
+        };
+        let kind = file_kind(header.kind)?;
+        validate_name(kind, &header.name)?;
+        if header.total_size > MAX_LIBRARY_FILE_SIZE as u64 {
+            return Err(Status::invalid_argument(format!(
+                "File too large: {} bytes (max {} bytes)",
+                header.total_size, MAX_LIBRARY_FILE_SIZE
+            )));
+        }
+
+        let mut content = Vec::with_capacity(header.total_size as usize);
+        while let Some(chunk) = stream.message().await? {
+            match chunk.payload {
+                Some(Payload::Data(data)) => {
+                    if (content.len() + data.len()) as u64 > header.total_size {
+                        return Err(Status::invalid_argument(format!(
+                            "Received more than the announced {} bytes",
+                            header.total_size
+                        )));
+                    }
+                    content.extend_from_slice(&data);
+                }
+                Some(Payload::Header(_)) => {
+                    return Err(Status::invalid_argument("Duplicate file header"));
+                }
+                None => {}
+            }
+        }
+        if content.len() as u64 != header.total_size {
+            return Err(Status::invalid_argument(format!(
+                "Upload incomplete: received {} of {} bytes",
+                content.len(),
+                header.total_size
+            )));
+        }
+        if !header.sha256.eq_ignore_ascii_case(&hash_content(&content)) {
+            return Err(Status::data_loss(
+                "Uploaded content does not match its SHA-256",
+            ));
+        }
+
+        match self
+            .library
+            .save(
+                kind,
+                &header.name,
+                &content,
+                header.version,
+                &header.comment,
+            )
+            .await
+        {
+            Ok(version) => Ok(Response::new(UploadLibraryFileResponse {
+                success: true,
+                error_message: String::new(),
+                version: Some(version),
+            })),
+            // Rejected content or a stale base version is reported, not raised
+            Err(status)
+                if matches!(
+                    status.code(),
+                    Code::InvalidArgument | Code::FailedPrecondition
+                ) =>
+            {
+                Ok(Response::new(UploadLibraryFileResponse {
+                    success: false,
+                    error_message: status.message().to_string(),
+                    version: None,
+                }))
+            }
+            Err(status) => Err(status),
+        }
+    }
+
+    async fn download_library_file(
+        &self,
+        request: Request<DownloadLibraryFileRequest>,
+    ) -> Result<Response<Self::DownloadLibraryFileStream>, Status> {
+        let req = request.into_inner();
+        let kind = file_kind(req.kind)?;
+        let (version, content) = self.library.load(kind, &req.name, req.version).await?;
+
+        let header = LibraryFileHeader {
+            kind: req.kind,
+            name: req.name,
+            total_size: version.size,
+            sha256: version.sha256,
+            version: version.version,
+            comment: version.comment,
+        };
+        let chunks = content_chunks(header, &content);
+        Ok(Response::new(Box::pin(tokio_stream::iter(
+            chunks.into_iter().map(Ok),
+        ))))
+    }
+
+    async fn delete_library_file(
+        &self,
+        request: Request<DeleteLibraryFileRequest>,
+    ) -> Result<Response<DeleteLibraryFileResponse>, Status> {
+        let req = request.into_inner();
+        let kind = file_kind(req.kind)?;
+        let deleted = self.library.delete(kind, &req.name).await?;
+        Ok(Response::new(DeleteLibraryFileResponse { deleted }))
+    }
+}
+
+/// Default library directory
+pub fn default_library_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rust-daq")
+        .join("library")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const SCRIPT: &[u8] = b"let x = 1;\nprint(x);\n";
+
+    #[tokio::test]
+    async fn test_versions_and_conflicts() {
+        let temp = TempDir::new().unwrap();
+        let library = FileLibrary::new(temp.path().to_path_buf()).with_retained_versions(2);
+        let kind = LibraryFileKind::Script;
+
+        let v1 = library
+            .save(kind, "scan.rhai", SCRIPT, 0, "first")
+            .await
+            .unwrap();
+        assert_eq!(v1.version, 1);
+        let v2 = library
+            .save(kind, "scan.rhai", b"let x = 2;", 1, "second")
+            .await
+            .unwrap();
+        assert_eq!(v2.version, 2);
+
+        // An edit based on version 1 would overwrite version 2
+        let err = library
+            .save(kind, "scan.rhai", b"let x = 3;", 1, "")
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::FailedPrecondition);
+
+        library
+            .save(kind, "scan.rhai", b"let x = 3;", 2, "")
+            .await
+            .unwrap();
+        let files = library.list(Some(kind)).await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].latest.as_ref().unwrap().version, 3);
+        let history: Vec<u32> = files[0].history.iter().map(|v| v.version).collect();
+        assert_eq!(history, [3, 2]);
+
+        let (version, content) = library.load(kind, "scan.rhai", 2).await.unwrap();
+        assert_eq!(
+            (version.comment.as_str(), content.as_slice()),
+            ("second", &b"let x = 2;"[..])
+        );
+        assert_eq!(
+            library.load(kind, "scan.rhai", 1).await.unwrap_err().code(),
+            Code::NotFound
+        );
+
+        assert!(library.delete(kind, "scan.rhai").await.unwrap());
+        assert!(library.list(None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_files_changed_on_host_become_versions() {
+        let temp = TempDir::new().unwrap();
+        let library = FileLibrary::new(temp.path().to_path_buf());
+        let kind = LibraryFileKind::Plan;
+
+        std::fs::write(library.file_path(kind, "grid.toml"), "points = 3\n").unwrap();
+        let (version, _) = library.load(kind, "grid.toml", 0).await.unwrap();
+        assert_eq!(version.version, 1);
+
+        library
+            .save(kind, "grid.toml", b"points = 5\n", 1, "")
+            .await
+            .unwrap();
+        let (version, content) = library.load(kind, "grid.toml", 1).await.unwrap();
+        assert_eq!(
+            (version.version, content.as_slice()),
+            (1, &b"points = 3\n"[..])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_invalid_names_and_content() {
+        let temp = TempDir::new().unwrap();
+        let library = FileLibrary::new(temp.path().to_path_buf());
+
+        for name in ["../escape.rhai", ".hidden.rhai", "scan.py", ""] {
+            let err = library
+                .save(LibraryFileKind::Script, name, SCRIPT, 0, "")
+                .await
+                .unwrap_err();
+            assert_eq!(err.code(), Code::InvalidArgument, "{}", name);
+        }
+        let err = library
+            .save(LibraryFileKind::Plan, "grid.json", b"{ not json", 0, "")
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert!(library.list(None).await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_content_chunks() {
+        let content = vec![7u8; LIBRARY_CHUNK_SIZE * 2 + 1];
+        let chunks = content_chunks(LibraryFileHeader::default(), &content);
+        assert_eq!(chunks.len(), 4);
+        assert!(matches!(chunks[0].payload, Some(Payload::Header(_))));
+        assert!(matches!(&chunks[3].payload, Some(Payload::Data(data)) if data.len() == 1));
+    }
+}
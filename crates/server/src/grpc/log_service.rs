@@ -0,0 +1,326 @@
+//! LogService implementation for structured daemon log streaming
+//!
+//! A [`LogStreamLayer`] installed in the daemon's tracing subscriber copies
+//! every event into a [`LogBroadcaster`], which keeps a bounded history and
+//! fans records out to `StreamLogs` subscribers. Level, target and device
+//! filters are applied server-side, so a GUI on a slow link only receives
+//! what it asked for.
+//!
+//! Events are tagged with a device when they (or an enclosing span) carry a
+//! `device_id` field:
+//!
+//! ```rust,ignore
+//! tracing::warn!(device_id = "ell14_2", "Position read timed out");
+//! ```
+
+use crate::grpc::proto::{LogLevel, LogRecord, StreamLogsRequest, log_service_server::LogService};
+use common::experiment::document::now_ns;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::broadcast;
+use tokio_stream::StreamExt;
+use tokio_stream::wrappers::BroadcastStream;
+use tonic::{Request, Response, Status};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Records kept for replay to new subscribers
+pub const DEFAULT_LOG_HISTORY: usize = 2000;
+
+/// Records buffered per live subscriber before it starts dropping
+const LOG_CHANNEL_CAPACITY: usize = 1024;
+
+/// Field names that tag an event with a device
+const DEVICE_FIELDS: [&str; 2] = ["device_id", "device"];
+
+/// Bounded log history plus live fan-out to stream subscribers
+pub struct LogBroadcaster {
+    history: Mutex<VecDeque<LogRecord>>,
+    capacity: usize,
+    tx: broadcast::Sender<LogRecord>,
+    next_sequence: AtomicU64,
+}
+
+impl LogBroadcaster {
+    /// Create a broadcaster keeping the last `capacity` records
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(LOG_CHANNEL_CAPACITY);
+        Self {
+            history: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            tx,
+            next_sequence: AtomicU64::new(0),
+        }
+    }
+
+    /// Process-wide broadcaster shared by the tracing layer and the LogService
+    pub fn global() -> Arc<LogBroadcaster> {
+        static GLOBAL: OnceLock<Arc<LogBroadcaster>> = OnceLock::new();
+        GLOBAL
+            .get_or_init(|| Arc::new(LogBroadcaster::new(DEFAULT_LOG_HISTORY)))
+            .clone()
+    }
+
+    /// Tracing layer feeding this broadcaster
+    pub fn layer(self: &Arc<Self>) -> LogStreamLayer {
+        LogStreamLayer { hub: self.clone() }
+    }
+
+    /// Record a log entry (assigns its sequence number)
+    pub fn publish(&self, mut record: LogRecord) {
+        let mut history = self.history.lock().unwrap_or_else(|p| p.into_inner());
+        record.sequence = self.next_sequence.fetch_add(1, Ordering::Relaxed);
+        if history.len() == self.capacity {
+            history.pop_front();
+        }
+        if self.capacity > 0 {
+            history.push_back(record.clone());
+        }
+        // Sent under the history lock so a new subscriber sees every record
+        // exactly once: either in its replay or on the channel
+        let _ = self.tx.send(record);
+    }
+
+    /// Matching history (up to `limit` most recent) and a receiver for what follows
+    fn subscribe(
+        &self,
+        filter: &LogFilter,
+        limit: usize,
+    ) -> (Vec<LogRecord>, broadcast::Receiver<LogRecord>) {
+        let history = self.history.lock().unwrap_or_else(|p| p.into_inner());
+        let mut replay: Vec<LogRecord> = history
+            .iter()
+            .rev()
+            .filter(|record| filter.matches(record))
+            .take(limit)
+            .cloned()
+            .collect();
+        replay.reverse();
+        (replay, self.tx.subscribe())
+    }
+}
+
+/// Server-side filter from a `StreamLogs` request
+#[derive(Debug, Clone, Default)]
+struct LogFilter {
+    min_level: i32,
+    targets: Vec<String>,
+    device_ids: Vec<String>,
+}
+
+impl LogFilter {
+    fn from_request(request: &StreamLogsRequest) -> Self {
+        Self {
+            min_level: request.min_level,
+            targets: request.targets.clone(),
+            device_ids: request.device_ids.clone(),
+        }
+    }
+
+    fn matches(&self, record: &LogRecord) -> bool {
+        record.level >= self.min_level
+            && (self.targets.is_empty()
+                || self
+                    .targets
+                    .iter()
+                    .any(|prefix| record.target.starts_with(prefix.as_str())))
+            && (self.device_ids.is_empty() || self.device_ids.contains(&record.device_id))
+    }
+}
+
+fn level_to_proto(level: Level) -> LogLevel {
+    match level {
+        Level::TRACE => LogLevel::Trace,
+        Level::DEBUG => LogLevel::Debug,
+        Level::INFO => LogLevel::Info,
+        Level::WARN => LogLevel::Warn,
+        Level::ERROR => LogLevel::Error,
+    }
+}
+
+/// Collects message, device tag and remaining fields of an event or span
+#[derive(Default)]
+struct RecordVisitor {
+    message: String,
+    device_id: Option<String>,
+    fields: HashMap<String, String>,
+}
+
+impl RecordVisitor {
+    fn record_value(&mut self, field: &Field, value: String) {
+        if field.name() == "message" {
+            self.message = value;
+        } else if DEVICE_FIELDS.contains(&field.name()) {
+            self.device_id = Some(value);
+        } else {
+            self.fields.insert(field.name().to_string(), value);
+        }
+    }
+}
+
+impl Visit for RecordVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_value(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.record_value(field, format!("{:?}", value));
+    }
+}
+
+/// Device tag stored on spans that carry a `device_id` field
+struct SpanDevice(String);
+
+/// Tracing layer that forwards events to a [`LogBroadcaster`]
+pub struct LogStreamLayer {
+    hub: Arc<LogBroadcaster>,
+}
+
+impl<S> Layer<S> for LogStreamLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = RecordVisitor::default();
+        attrs.record(&mut visitor);
+        if let (Some(device_id), Some(span)) = (visitor.device_id, ctx.span(id)) {
+            span.extensions_mut().insert(SpanDevice(device_id));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut visitor = RecordVisitor::default();
+        event.record(&mut visitor);
+
+        let device_id = visitor.device_id.or_else(|| {
+            ctx.event_scope(event)?.find_map(|span| {
+                span.extensions()
+                    .get::<SpanDevice>()
+                    .map(|device| device.0.clone())
+            })
+        });
+
+        let metadata = event.metadata();
+        self.hub.publish(LogRecord {
+            sequence: 0,
+            timestamp_ns: now_ns(),
+            level: level_to_proto(*metadata.level()) as i32,
+            target: metadata.target().to_string(),
+            message: visitor.message,
+            device_id: device_id.unwrap_or_default(),
+            fields: visitor.fields,
+        });
+    }
+}
+
+/// gRPC LogService streaming records from a [`LogBroadcaster`]
+pub struct LogServiceImpl {
+    hub: Arc<LogBroadcaster>,
+}
+
+impl LogServiceImpl {
+    pub fn new(hub: Arc<LogBroadcaster>) -> Self {
+        Self { hub }
+    }
+}
+
+#[tonic::async_trait]
+impl LogService for LogServiceImpl {
+    type StreamLogsStream =
+        std::pin::Pin<Box<dyn tokio_stream::Stream<Item = Result<LogRecord, Status>> + Send>>;
+
+    async fn stream_logs(
+        &self,
+        request: Request<StreamLogsRequest>,
+    ) -> Result<Response<Self::StreamLogsStream>, Status> {
+        let req = request.into_inner();
+        let filter = LogFilter::from_request(&req);
+        let (replay, rx) = self.hub.subscribe(&filter, req.history as usize);
+
+        let live = BroadcastStream::new(rx).filter_map(move |result| match result {
+            Ok(record) => filter.matches(&record).then_some(Ok(record)),
+            // A slow client skipped records; tell it instead of silently dropping
+            Err(tokio_stream::wrappers::errors::BroadcastStreamRecvError::Lagged(n)) => {
+                Some(Ok(LogRecord {
+                    sequence: 0,
+                    timestamp_ns: now_ns(),
+                    level: LogLevel::Warn as i32,
+                    target: module_path!().to_string(),
+                    message: format!("{} log records dropped (client too slow)", n),
+                    device_id: String::new(),
+                    fields: HashMap::new(),
+                }))
+            }
+        });
+        let stream = tokio_stream::iter(replay.into_iter().map(Ok)).chain(live);
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_layer_captures_device_tags_and_filters() {
+        let hub = Arc::new(LogBroadcaster::new(16));
+        let subscriber = tracing_subscriber::registry().with(hub.layer());
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(target: "hardware::drivers::ell14", device_id = "rot_2", "homed");
+            let span = tracing::info_span!("poll", device_id = "stage_x");
+            span.in_scope(|| tracing::warn!(target: "hardware::drivers::esp300", "slow reply"));
+            tracing::debug!(target: "server::grpc", count = 3, "served");
+        });
+
+        let all = LogFilter::default();
+        let (records, _) = hub.subscribe(&all, 10);
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].device_id, "rot_2");
+        assert_eq!(records[1].device_id, "stage_x");
+        assert_eq!(
+            records[2].fields.get("count").map(String::as_str),
+            Some("3")
+        );
+        assert!(records.windows(2).all(|w| w[0].sequence < w[1].sequence));
+
+        let warnings = LogFilter {
+            min_level: LogLevel::Warn as i32,
+            ..Default::default()
+        };
+        assert_eq!(hub.subscribe(&warnings, 10).0.len(), 1);
+
+        let hardware = LogFilter {
+            targets: vec!["hardware::".to_string()],
+            device_ids: vec!["rot_2".to_string()],
+            ..Default::default()
+        };
+        let (records, _) = hub.subscribe(&hardware, 10);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].message, "homed");
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let hub = LogBroadcaster::new(3);
+        for i in 0..5 {
+            hub.publish(LogRecord {
+                message: i.to_string(),
+                ..Default::default()
+            });
+        }
+
+        let (records, _) = hub.subscribe(&LogFilter::default(), 10);
+        let messages: Vec<_> = records.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(messages, vec!["2", "3", "4"]);
+        // Replay is limited to the most recent records
+        assert_eq!(hub.subscribe(&LogFilter::default(), 1).0[0].message, "4");
+    }
+}
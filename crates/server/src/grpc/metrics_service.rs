@@ -7,10 +7,15 @@
 //!
 //! ## Gauges (current values)
 //! - `run_engine_active_streams`: Current number of active document streams
+//! - `storage_flush_interval_seconds{writer}`: Current wait between flushes
+//! - `storage_flush_fill_ratio{writer}`: Buffer fill at the last flush decision
 //!
 //! ## Counters (monotonically increasing)
 //! - `run_engine_documents_total`: Total documents converted and streamed
 //! - `run_engine_lag_events_total`: Total lag events (client fell behind)
+//! - `storage_flush_decisions_total{writer,pressure}`: Flush decisions by
+//!   buffer pressure (idle, normal, high)
+//! - `storage_flush_bytes_total{writer}`: Bytes written by flushes
 //!
 //! # Usage
 //!
@@ -26,15 +31,21 @@
 
 use lazy_static::lazy_static;
 use prometheus::{
-    Encoder, IntCounter, IntGauge, Registry, TextEncoder, register_int_counter_with_registry,
-    register_int_gauge_with_registry,
+    Encoder, GaugeVec, IntCounter, IntCounterVec, IntGauge, Registry, TextEncoder,
+    register_gauge_vec_with_registry, register_int_counter_vec_with_registry,
+    register_int_counter_with_registry, register_int_gauge_with_registry,
 };
+use std::collections::BTreeMap;
 use std::convert::Infallible;
 use std::net::SocketAddr;
+use storage::flush_policy::{FlushMonitor, FlushPressure, FlushStats};
 
 lazy_static! {
     /// Global metrics registry for DAQ server
     pub static ref REGISTRY: Registry = Registry::new();
+
+    /// Storage flush decisions, refreshed from the [`FlushMonitor`] on scrape
+    static ref STORAGE_FLUSH: StorageFlushMetrics = StorageFlushMetrics::with_registry(&REGISTRY);
 }
 
 /// DAQ server metrics collection
@@ -164,6 +175,74 @@ pub enum EngineState {
     Error = 3,
 }
 
+/// Flush decisions of the storage writers
+pub struct StorageFlushMetrics {
+    /// Current wait between flushes per writer
+    pub interval_seconds: GaugeVec,
+    /// Buffer fill at the last decision per writer
+    pub fill_ratio: GaugeVec,
+    /// Decisions per writer and pressure level
+    pub decisions_total: IntCounterVec,
+    /// Bytes written per writer
+    pub bytes_total: IntCounterVec,
+}
+
+impl StorageFlushMetrics {
+    /// Create the metrics registered with `registry`
+    pub fn with_registry(registry: &Registry) -> Self {
+        Self {
+            interval_seconds: register_gauge_vec_with_registry!(
+                "storage_flush_interval_seconds",
+                "Current wait between storage flushes",
+                &["writer"],
+                registry
+            )
+            .expect("Failed to create storage_flush_interval_seconds gauge"),
+            fill_ratio: register_gauge_vec_with_registry!(
+                "storage_flush_fill_ratio",
+                "Upstream buffer fill (0-1) at the last flush decision",
+                &["writer"],
+                registry
+            )
+            .expect("Failed to create storage_flush_fill_ratio gauge"),
+            decisions_total: register_int_counter_vec_with_registry!(
+                "storage_flush_decisions_total",
+                "Storage flush decisions by buffer pressure",
+                &["writer", "pressure"],
+                registry
+            )
+            .expect("Failed to create storage_flush_decisions_total counter"),
+            bytes_total: register_int_counter_vec_with_registry!(
+                "storage_flush_bytes_total",
+                "Bytes written by storage flushes",
+                &["writer"],
+                registry
+            )
+            .expect("Failed to create storage_flush_bytes_total counter"),
+        }
+    }
+
+    /// Bring the metrics up to date with the writers' cumulative stats
+    pub fn update(&self, writers: &BTreeMap<String, FlushStats>) {
+        for (writer, stats) in writers {
+            let writer = writer.as_str();
+            self.interval_seconds
+                .with_label_values(&[writer])
+                .set(stats.interval.as_secs_f64());
+            self.fill_ratio.with_label_values(&[writer]).set(stats.fill);
+            for pressure in FlushPressure::ALL {
+                let total = stats.decisions.get(&pressure).copied().unwrap_or(0);
+                let counter = self
+                    .decisions_total
+                    .with_label_values(&[writer, pressure.as_str()]);
+                counter.inc_by(total.saturating_sub(counter.get()));
+            }
+            let counter = self.bytes_total.with_label_values(&[writer]);
+            counter.inc_by(stats.bytes_written.saturating_sub(counter.get()));
+        }
+    }
+}
+
 /// Handle returned by start_metrics_server for cleanup
 pub struct MetricsServerHandle {
     _shutdown_tx: tokio::sync::oneshot::Sender<()>,
@@ -224,6 +303,7 @@ async fn handle_metrics_request(
     match (req.method(), req.uri().path()) {
         (&hyper::Method::GET, "/metrics") => {
             // Gather and encode metrics
+            STORAGE_FLUSH.update(&FlushMonitor::global().snapshot());
             let encoder = TextEncoder::new();
             let metric_families = REGISTRY.gather();
             let mut buffer = Vec::new();
@@ -292,6 +372,26 @@ mod tests {
         assert_eq!(metrics.documents_total.get(), 3);
     }
 
+    #[test]
+    fn test_storage_flush_metrics_follow_monitor() {
+        use storage::flush_policy::{FlushPolicy, FlushPolicyConfig};
+
+        let registry = Registry::new();
+        let metrics = StorageFlushMetrics::with_registry(&registry);
+        let monitor = FlushMonitor::default();
+        let mut policy = FlushPolicy::new(FlushPolicyConfig::default());
+
+        monitor.record("hdf5", &policy.next(0.9), 100);
+        metrics.update(&monitor.snapshot());
+        monitor.record("hdf5", &policy.next(0.9), 50);
+        metrics.update(&monitor.snapshot());
+
+        let high = metrics.decisions_total.with_label_values(&["hdf5", "high"]);
+        assert_eq!(high.get(), 2);
+        assert_eq!(metrics.bytes_total.with_label_values(&["hdf5"]).get(), 150);
+        assert!(metrics.interval_seconds.with_label_values(&["hdf5"]).get() < 1.0);
+    }
+
     #[test]
     fn test_engine_state() {
         let registry = Registry::new();
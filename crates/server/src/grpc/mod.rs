@@ -1,15 +1,25 @@
+#[cfg(feature = "modules")]
+pub mod config_service;
+pub mod console_service;
 pub mod custom_health_service;
 pub mod error_mapping;
 #[cfg(test)]
 mod error_mapping_tests;
+#[cfg(feature = "json_gateway")]
+pub mod gateway_service;
 pub mod hardware_service;
 pub mod health_service;
+pub mod library_service;
+pub mod log_service;
 #[cfg(feature = "metrics")]
 pub mod metrics_service;
 pub mod module_service;
 pub mod ni_daq_service;
+pub mod parameter_proposals;
 pub mod plugin_service;
 pub mod preset_service;
+#[cfg(feature = "preview")]
+pub mod preview_service;
 pub mod run_engine_service;
 pub mod scan_service;
 /// gRPC server for remote DAQ control (Phase 3)
@@ -39,7 +49,9 @@ pub mod scan_service;
 /// ```
 #[cfg(feature = "server")]
 pub mod server;
+pub mod session_service;
 pub mod storage_service;
+pub mod stream_stats;
 
 /// Protocol Buffer definitions for the DAQ Control Service
 ///
@@ -68,20 +80,34 @@ pub mod proto {
 /// Re-export compression helpers for frame streaming (bd-7rk0)
 pub use protocol::compression;
 
+#[cfg(feature = "modules")]
+pub use config_service::ConfigServiceImpl;
+pub use console_service::ConsoleServiceImpl;
+#[cfg(feature = "json_gateway")]
+pub use gateway_service::{GatewayServerHandle, start_gateway_server};
 pub use hardware_service::HardwareServiceImpl;
 pub use health_service::HealthServiceImpl;
+pub use library_service::{FileLibrary, LibraryServiceImpl, default_library_path};
+pub use log_service::{LogBroadcaster, LogServiceImpl, LogStreamLayer};
 #[cfg(feature = "metrics")]
 pub use metrics_service::{DaqMetrics, MetricsServerHandle, start_metrics_server};
 pub use module_service::ModuleServiceImpl;
 pub use ni_daq_service::NiDaqServiceImpl;
 pub use plugin_service::PluginServiceImpl;
 pub use preset_service::{PresetServiceImpl, default_preset_storage_path};
+#[cfg(feature = "preview")]
+pub use preview_service::{PreviewConfig, PreviewServerHandle, start_preview_server};
 pub use run_engine_service::RunEngineServiceImpl;
 #[allow(deprecated)] // ScanService kept for backwards compatibility until v0.8.0
 pub use scan_service::ScanServiceImpl;
 #[cfg(feature = "server")]
-pub use server::{DaqServer, start_server, start_server_with_hardware};
-pub use storage_service::StorageServiceImpl;
+pub use server::{
+    DaqServer, ServerOptions, issue_auth_token, start_server, start_server_with_hardware,
+    start_server_with_options, start_server_with_shutdown,
+};
+pub use session_service::{SessionManager, SessionServiceImpl};
+pub use storage_service::{StorageServiceImpl, StorageSettings};
+pub use stream_stats::StreamStatsRegistry;
 
 // Error mapping (bd-cxvg)
 pub use error_mapping::{DaqResultExt, map_daq_error_to_status};
@@ -235,6 +261,7 @@ pub use proto::{
     EventDocument,
     GetEngineStatusRequest,
     GetPlanTypeInfoRequest,
+    GetRunProgressRequest,
     HaltEngineRequest,
     HaltEngineResponse,
     // Plan type discovery
@@ -246,11 +273,12 @@ pub use proto::{
     PlanParameter,
     PlanTypeInfo,
     PlanTypeSummary,
-    // Plan execution
+    ProgressDocument,
     QueuePlanRequest,
     QueuePlanResponse,
     ResumeEngineRequest,
     ResumeEngineResponse,
+    RunProgress,
     StartDocument,
     StartEngineRequest,
     StartEngineResponse,
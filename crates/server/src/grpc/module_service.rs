@@ -30,7 +30,9 @@ use crate::grpc::proto::{
 #[cfg(not(feature = "modules"))]
 use crate::grpc::proto::{ModuleParameter, ModuleRole};
 #[cfg(feature = "modules")]
-use crate::modules::ModuleRegistry;
+use crate::modules::persistence::{self, PersistedModule, RestoreReport};
+#[cfg(feature = "modules")]
+use crate::modules::{ModuleRegistry, PluginModuleType};
 use hardware::registry::DeviceRegistry;
 #[cfg(not(feature = "modules"))]
 use std::collections::HashMap;
@@ -131,6 +133,8 @@ fn get_module_type_required_roles(type_id: &str) -> Vec<&'static str> {
         "position_tracker" => vec!["stage"],
         "data_logger" => vec!["data_source"],
         "multi_channel_logger" => vec![],
+        "auto_exposure" => vec!["camera"],
+        "drift_tracker" => vec!["camera"],
         _ => vec![],
     }
 }
@@ -163,6 +167,31 @@ fn get_builtin_module_types() -> Vec<ModuleTypeSummary> {
             description: "Logs multiple data sources with synchronized timestamps".to_string(),
             categories: vec!["logging".to_string(), "multi-channel".to_string()],
         },
+        ModuleTypeSummary {
+            type_id: "environment_monitor".to_string(),
+            display_name: "Environment Monitor".to_string(),
+            description: "Polls lab temperature, humidity and table vibration".to_string(),
+            categories: vec!["monitoring".to_string(), "environment".to_string()],
+        },
+        ModuleTypeSummary {
+            type_id: "auto_exposure".to_string(),
+            display_name: "Auto Exposure".to_string(),
+            description: "Adjusts camera exposure or gain to keep the histogram on target"
+                .to_string(),
+            categories: vec!["control".to_string(), "camera".to_string()],
+        },
+        ModuleTypeSummary {
+            type_id: "drift_tracker".to_string(),
+            display_name: "Drift Tracker".to_string(),
+            description:
+                "Tracks XY image drift by cross-correlation and corrects it with the stage"
+                    .to_string(),
+            categories: vec![
+                "control".to_string(),
+                "camera".to_string(),
+                "motion".to_string(),
+            ],
+        },
     ]
 }
 
@@ -231,6 +260,7 @@ fn get_static_module_type_info(type_id: &str) -> Option<ModuleTypeInfo> {
                 "power_average".to_string(),
                 "statistics".to_string(),
             ],
+            config_schema: String::new(),
         }),
         "position_tracker" => Some(ModuleTypeInfo {
             type_id: "position_tracker".to_string(),
@@ -259,6 +289,7 @@ fn get_static_module_type_info(type_id: &str) -> Option<ModuleTypeInfo> {
             }],
             event_types: vec!["motion_started".to_string(), "motion_stopped".to_string()],
             data_types: vec!["position".to_string(), "velocity".to_string()],
+            config_schema: String::new(),
         }),
         "data_logger" => Some(ModuleTypeInfo {
             type_id: "data_logger".to_string(),
@@ -301,6 +332,7 @@ fn get_static_module_type_info(type_id: &str) -> Option<ModuleTypeInfo> {
             ],
             event_types: vec!["file_rotated".to_string(), "buffer_flushed".to_string()],
             data_types: vec!["logged_value".to_string()],
+            config_schema: String::new(),
         }),
         "multi_channel_logger" => Some(ModuleTypeInfo {
             type_id: "multi_channel_logger".to_string(),
@@ -329,6 +361,7 @@ fn get_static_module_type_info(type_id: &str) -> Option<ModuleTypeInfo> {
             }],
             event_types: vec!["sync_error".to_string()],
             data_types: vec!["multi_channel_sample".to_string()],
+            config_schema: String::new(),
         }),
         _ => None,
     }
@@ -346,6 +379,7 @@ fn get_static_module_type_info(type_id: &str) -> Option<ModuleTypeInfo> {
 /// - Configuration and device assignment
 /// - Execution control (start, pause, resume, stop)
 /// - Event and data streaming
+#[derive(Clone)]
 pub struct ModuleServiceImpl {
     /// Device registry for hardware access
     device_registry: Arc<DeviceRegistry>,
@@ -357,6 +391,14 @@ pub struct ModuleServiceImpl {
     /// Real module registry (when modules feature is enabled)
     #[cfg(feature = "modules")]
     module_registry: Arc<RwLock<ModuleRegistry>>,
+
+    /// File the module set is saved to after every change
+    #[cfg(feature = "modules")]
+    persist_path: Option<std::path::PathBuf>,
+
+    /// Persisted entries that could not be restored; written back unchanged
+    #[cfg(feature = "modules")]
+    unrestored: Arc<std::sync::Mutex<Vec<PersistedModule>>>,
 }
 
 impl ModuleServiceImpl {
@@ -376,6 +418,69 @@ impl ModuleServiceImpl {
         Self {
             device_registry: registry,
             module_registry: Arc::new(RwLock::new(module_registry)),
+            persist_path: None,
+            unrestored: Arc::new(std::sync::Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Save module instances (type, config, role bindings) to `path`
+    /// whenever they change
+    #[cfg(feature = "modules")]
+    pub fn with_persistence(mut self, path: std::path::PathBuf) -> Self {
+        self.persist_path = Some(path);
+        self
+    }
+
+    /// Offer module types provided by plugins; call before
+    /// [`Self::restore_modules`] so saved instances of them come back
+    #[cfg(feature = "modules")]
+    pub async fn register_plugin_types(&self, types: Vec<PluginModuleType>) {
+        let mut registry = self.module_registry.write().await;
+        for module_type in types {
+            let type_id = module_type.info.type_id.clone();
+            if let Err(e) = registry.register_plugin_type(module_type) {
+                tracing::warn!("Skipping plugin module type '{}': {}", type_id, e);
+            }
+        }
+    }
+
+    /// Recreate the module set saved by a previous daemon run
+    #[cfg(feature = "modules")]
+    pub async fn restore_modules(&self) -> anyhow::Result<RestoreReport> {
+        let Some(path) = &self.persist_path else {
+            return Ok(RestoreReport::default());
+        };
+        let modules = persistence::load_modules(path)?;
+        let report = self.module_registry.write().await.restore(modules);
+        for warning in &report.warnings {
+            tracing::warn!("Module restore: {}", warning);
+        }
+        *self.unrestored.lock().unwrap_or_else(|p| p.into_inner()) = report.skipped.clone();
+        Ok(report)
+    }
+
+    /// Shared handle to the module registry
+    #[cfg(feature = "modules")]
+    pub fn registry(&self) -> Arc<RwLock<ModuleRegistry>> {
+        self.module_registry.clone()
+    }
+
+    /// Save the module set (no-op unless persistence is enabled)
+    #[cfg(feature = "modules")]
+    pub(crate) fn persist(&self, registry: &ModuleRegistry) {
+        let Some(path) = &self.persist_path else {
+            return;
+        };
+        let mut modules = registry.snapshot();
+        modules.extend(
+            self.unrestored
+                .lock()
+                .unwrap_or_else(|p| p.into_inner())
+                .iter()
+                .cloned(),
+        );
+        if let Err(e) = persistence::save_modules(path, &modules) {
+            tracing::warn!("Failed to persist modules to {}: {:#}", path.display(), e);
         }
     }
 }
@@ -457,9 +562,14 @@ impl ModuleService for ModuleServiceImpl {
         match registry.create_module(&req.type_id, &req.instance_name) {
             Ok(module_id) => {
                 // Apply initial config if provided
-                if !req.initial_config.is_empty()
-                    && let Err(e) = registry.configure_module(&module_id, req.initial_config)
-                {
+                let config_result = if req.initial_config.is_empty() {
+                    Ok(Vec::new())
+                } else {
+                    registry.configure_module(&module_id, req.initial_config)
+                };
+                self.persist(&registry);
+
+                if let Err(e) = config_result {
                     // Module created but config failed - still return success with warning
                     return Ok(Response::new(CreateModuleResponse {
                         success: true,
@@ -490,10 +600,13 @@ impl ModuleService for ModuleServiceImpl {
         let mut registry = self.module_registry.write().await;
 
         match registry.delete_module(&req.module_id, req.force).await {
-            Ok(()) => Ok(Response::new(DeleteModuleResponse {
-                success: true,
-                error_message: String::new(),
-            })),
+            Ok(()) => {
+                self.persist(&registry);
+                Ok(Response::new(DeleteModuleResponse {
+                    success: true,
+                    error_message: String::new(),
+                }))
+            }
             Err(e) => Ok(Response::new(DeleteModuleResponse {
                 success: false,
                 error_message: e.to_string(),
@@ -656,11 +769,14 @@ impl ModuleService for ModuleServiceImpl {
         };
 
         match registry.configure_module(&req.module_id, params) {
-            Ok(warnings) => Ok(Response::new(ConfigureModuleResponse {
-                success: true,
-                error_message: String::new(),
-                warnings,
-            })),
+            Ok(warnings) => {
+                self.persist(&registry);
+                Ok(Response::new(ConfigureModuleResponse {
+                    success: true,
+                    error_message: String::new(),
+                    warnings,
+                }))
+            }
             Err(e) => Ok(Response::new(ConfigureModuleResponse {
                 success: false,
                 error_message: e.to_string(),
@@ -718,6 +834,7 @@ impl ModuleService for ModuleServiceImpl {
 
         match registry.assign_device(&req.module_id, &req.role_id, &req.device_id) {
             Ok(()) => {
+                self.persist(&registry);
                 // Check if module is now ready
                 let instance = registry.get_module(&req.module_id);
                 let ready = instance.is_some_and(|inst| {
@@ -757,10 +874,13 @@ impl ModuleService for ModuleServiceImpl {
         let mut registry = self.module_registry.write().await;
 
         match registry.unassign_device(&req.module_id, &req.role_id) {
-            Ok(()) => Ok(Response::new(UnassignDeviceResponse {
-                success: true,
-                error_message: String::new(),
-            })),
+            Ok(()) => {
+                self.persist(&registry);
+                Ok(Response::new(UnassignDeviceResponse {
+                    success: true,
+                    error_message: String::new(),
+                }))
+            }
             Err(e) => Ok(Response::new(UnassignDeviceResponse {
                 success: false,
                 error_message: e.to_string(),
@@ -897,24 +1017,35 @@ impl ModuleService for ModuleServiceImpl {
         request: Request<StreamModuleEventsRequest>,
     ) -> Result<Response<Self::StreamModuleEventsStream>, Status> {
         let req = request.into_inner();
-        let mut registry = self.module_registry.write().await;
-
-        // Get the module's event receiver
-        let instance = registry
-            .get_module_mut(&req.module_id)
-            .ok_or_else(|| Status::not_found(format!("Module not found: {}", req.module_id)))?;
+        let registry = self.module_registry.read().await;
 
-        let event_rx = instance.take_event_rx().ok_or_else(|| {
-            Status::resource_exhausted("Event stream already taken for this module")
-        })?;
+        // Each stream gets its own subscription, so several clients can follow one module
+        let mut event_rx = registry
+            .get_module(&req.module_id)
+            .ok_or_else(|| Status::not_found(format!("Module not found: {}", req.module_id)))?
+            .subscribe_events();
+        drop(registry);
 
         let (tx, rx) = tokio::sync::mpsc::channel(100);
         let event_types = req.event_types;
+        let module_id = req.module_id;
 
         // Forward events from module to gRPC stream
         tokio::spawn(async move {
-            let mut event_rx = event_rx;
-            while let Some(event) = event_rx.recv().await {
+            loop {
+                let event = match event_rx.recv().await {
+                    Ok(event) => event,
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(
+                            "Event stream for module {} lagged, {} events skipped",
+                            module_id,
+                            skipped
+                        );
+                        continue;
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                };
+
                 // Filter by event type if specified
                 if !event_types.is_empty() && !event_types.contains(&event.event_type) {
                     continue;
@@ -1758,4 +1889,40 @@ mod tests {
         let stop_resp = service.stop_module(stop_req).await.unwrap().into_inner();
         assert!(stop_resp.success);
     }
+
+    #[cfg(feature = "modules")]
+    #[tokio::test]
+    async fn test_modules_persist_across_restart() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("modules.json");
+
+        let service = create_test_service().with_persistence(path.clone());
+        let create_resp = service
+            .create_module(Request::new(CreateModuleRequest {
+                type_id: "power_monitor".to_string(),
+                instance_name: "laser_power".to_string(),
+                initial_config: HashMap::from([("high_threshold".to_string(), "250".to_string())]),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(create_resp.success);
+
+        // A fresh service (daemon restart) restores the same instance
+        let restarted = create_test_service().with_persistence(path);
+        let report = restarted.restore_modules().await.unwrap();
+        assert_eq!(report.restored, 1);
+
+        let config = restarted
+            .get_module_config(Request::new(GetModuleConfigRequest {
+                module_id: create_resp.module_id,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(
+            config.parameters.get("high_threshold").map(String::as_str),
+            Some("250")
+        );
+    }
 }
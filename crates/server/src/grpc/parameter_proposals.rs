@@ -0,0 +1,141 @@
+//! Pending changes to dangerous parameters.
+//!
+//! Parameters flagged `dangerous` (in driver metadata or under
+//! `[parameter_policies]` in the hardware config) are not applied by
+//! `SetParameter`. Instead the change is stored here as a proposal and the
+//! caller receives a token that must be confirmed with
+//! `ConfirmParameterChange` before the proposal expires.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tonic::Status;
+
+/// How long a proposal stays confirmable unless the config overrides it
+pub const DEFAULT_CONFIRM_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A proposed parameter change awaiting confirmation.
+#[derive(Debug, Clone)]
+pub struct ParameterProposal {
+    /// Target device
+    pub device_id: String,
+    /// Target parameter
+    pub parameter_name: String,
+    /// Requested value, as sent to `SetParameter`
+    pub value: String,
+    expires_at: Instant,
+}
+
+/// Pending proposals keyed by token.
+#[derive(Debug, Default)]
+pub struct ProposalStore {
+    proposals: Mutex<HashMap<String, ParameterProposal>>,
+}
+
+impl ProposalStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a proposal, returning its token and expiry (ns since epoch).
+    pub fn propose(
+        &self,
+        device_id: &str,
+        parameter_name: &str,
+        value: &str,
+        timeout: Duration,
+    ) -> (String, u64) {
+        let token = uuid::Uuid::new_v4().to_string();
+        let now = Instant::now();
+        let expires_ns = SystemTime::now()
+            .checked_add(timeout)
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+
+        let mut proposals = self.proposals.lock().unwrap_or_else(|p| p.into_inner());
+        proposals.retain(|_, p| p.expires_at > now);
+        proposals.insert(
+            token.clone(),
+            ParameterProposal {
+                device_id: device_id.to_string(),
+                parameter_name: parameter_name.to_string(),
+                value: value.to_string(),
+                expires_at: now + timeout,
+            },
+        );
+        (token, expires_ns)
+    }
+
+    /// Remove and return a proposal for confirmation.
+    ///
+    /// Unknown tokens are `NOT_FOUND`; expired proposals are dropped and
+    /// reported as `DEADLINE_EXCEEDED` so nothing stale is ever applied.
+    pub fn take(&self, token: &str) -> Result<ParameterProposal, Status> {
+        let proposal = self
+            .proposals
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .remove(token)
+            .ok_or_else(|| Status::not_found(format!("Unknown proposal '{}'", token)))?;
+
+        if proposal.expires_at <= Instant::now() {
+            return Err(Status::deadline_exceeded(format!(
+                "Proposal to set {}.{} expired",
+                proposal.device_id, proposal.parameter_name
+            )));
+        }
+        Ok(proposal)
+    }
+
+    /// Drop a proposal. Returns false if it was unknown or already expired.
+    pub fn cancel(&self, token: &str) -> bool {
+        self.proposals
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .remove(token)
+            .is_some_and(|p| p.expires_at > Instant::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn confirm_takes_proposal_once() {
+        let store = ProposalStore::new();
+        let (token, expires_ns) =
+            store.propose("laser", "power_mw", "250", DEFAULT_CONFIRM_TIMEOUT);
+        assert!(expires_ns > 0);
+
+        let proposal = store.take(&token).unwrap();
+        assert_eq!(proposal.device_id, "laser");
+        assert_eq!(proposal.parameter_name, "power_mw");
+        assert_eq!(proposal.value, "250");
+
+        let err = store.take(&token).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+    }
+
+    #[test]
+    fn expired_proposal_is_rejected() {
+        let store = ProposalStore::new();
+        let (token, _) = store.propose("hv", "voltage", "1000", Duration::ZERO);
+
+        let err = store.take(&token).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::DeadlineExceeded);
+    }
+
+    #[test]
+    fn cancelled_proposal_cannot_be_confirmed() {
+        let store = ProposalStore::new();
+        let (token, _) = store.propose("laser", "power_mw", "250", DEFAULT_CONFIRM_TIMEOUT);
+
+        assert!(store.cancel(&token));
+        assert!(!store.cancel(&token));
+        assert!(store.take(&token).is_err());
+    }
+}
@@ -10,7 +10,7 @@ use hardware::plugin::driver::GenericDriver;
 #[cfg(feature = "serial")]
 use hardware::plugin::registry::PluginFactory;
 #[cfg(feature = "serial")]
-use hardware::plugin::schema::{DriverType, UiElement};
+use hardware::plugin::schema::DriverType;
 #[cfg(feature = "serial")]
 use hardware::registry::{DeviceConfig, DeviceRegistry, DriverType as RegistryDriverType};
 
@@ -22,6 +22,8 @@ use crate::grpc::proto::{
     plugin_service_server::PluginService,
 };
 
+#[cfg(feature = "serial")]
+use crate::grpc::hardware_service::ui_element_to_proto;
 #[cfg(feature = "serial")]
 use crate::grpc::proto::{
     PluginActionable, PluginAxis, PluginCapabilities, PluginLoggable, PluginMovable,
@@ -126,60 +128,6 @@ fn driver_type_to_string(dt: &DriverType) -> String {
     }
 }
 
-#[cfg(feature = "serial")]
-fn ui_element_to_proto(elem: &UiElement) -> PluginUiElement {
-    match elem {
-        UiElement::Group(g) => PluginUiElement {
-            element_type: "group".to_string(),
-            label: g.label.clone(),
-            target: None,
-            source: None,
-            action: None,
-            children: g.children.iter().map(ui_element_to_proto).collect(),
-        },
-        UiElement::Slider(s) => PluginUiElement {
-            element_type: "slider".to_string(),
-            label: s.label.clone().unwrap_or_default(),
-            target: Some(s.target.clone()),
-            source: None,
-            action: None,
-            children: vec![],
-        },
-        UiElement::Readout(r) => PluginUiElement {
-            element_type: "readout".to_string(),
-            label: r.label.clone().unwrap_or_default(),
-            target: None,
-            source: Some(r.source.clone()),
-            action: None,
-            children: vec![],
-        },
-        UiElement::Toggle(t) => PluginUiElement {
-            element_type: "toggle".to_string(),
-            label: t.label.clone().unwrap_or_default(),
-            target: Some(t.target.clone()),
-            source: None,
-            action: None,
-            children: vec![],
-        },
-        UiElement::Button(b) => PluginUiElement {
-            element_type: "button".to_string(),
-            label: b.label.clone(),
-            target: None,
-            source: None,
-            action: Some(b.action.clone()),
-            children: vec![],
-        },
-        UiElement::Dropdown(d) => PluginUiElement {
-            element_type: "dropdown".to_string(),
-            label: d.label.clone().unwrap_or_default(),
-            target: Some(d.target.clone()),
-            source: None,
-            action: None,
-            children: vec![],
-        },
-    }
-}
-
 #[tonic::async_trait]
 impl PluginService for PluginServiceImpl {
     async fn list_plugins(
@@ -340,8 +288,11 @@ impl PluginService for PluginServiceImpl {
                 })
                 .collect();
 
-            let ui_layout: Vec<PluginUiElement> =
-                config.ui_layout.iter().map(ui_element_to_proto).collect();
+            let ui_layout: Vec<PluginUiElement> = config
+                .resolved_ui_layout()
+                .iter()
+                .map(ui_element_to_proto)
+                .collect();
 
             Ok(Response::new(PluginInfo {
                 plugin_id: config.metadata.id.clone(),
@@ -419,6 +370,7 @@ impl PluginService for PluginServiceImpl {
                             plugin_id: req.plugin_id.clone(),
                             address: req.address.clone(),
                         },
+                        simulated: false,
                     };
 
                     // 2. Register with DeviceRegistry
@@ -440,7 +440,11 @@ impl PresetServiceImpl {
                         }
 
                         if let Some(parameter) = param_set.get(param_name) {
-                            match parameter.set_json(value.clone()) {
+                            match self
+                                .registry
+                                .set_parameter_json(device_id, parameter, value.clone())
+                                .await
+                            {
                                 Ok(_) => applied_count += 1,
                                 Err(e) => errors.push(format!(
                                     "Failed to set parameter '{}.{}': {}",
@@ -0,0 +1,555 @@
+//! Live camera preview over plain HTTP (MJPEG)
+//!
+//! Serves the newest camera image from the ring buffer as JPEG, so a browser
+//! (including phones and tablets) or an external tool can watch the camera
+//! for quick alignment checks without a gRPC client.
+//!
+//! # Endpoints
+//!
+//! - `GET /` - index page with a live view of every image stream
+//! - `GET /snapshot/<name>.jpg` - newest frame of stream `<name>` as JPEG
+//! - `GET /stream/<name>` - MJPEG stream (`multipart/x-mixed-replace`)
+//! - `GET /health` - liveness check
+//!
+//! Frames come from a ring buffer tap, so the preview never blocks
+//! acquisition. Images are autoscaled to 8-bit, downsampled so neither side
+//! exceeds [`PreviewConfig::max_dimension`], and encoded at most
+//! [`PreviewConfig::max_fps`] times per second per stream.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use daq_server::grpc::preview_service::{start_preview_server, PreviewConfig};
+//!
+//! let handle = start_preview_server(8081, ring_buffer, PreviewConfig::from_env())?;
+//! // Dropping handle stops the server and removes the ring buffer tap
+//! ```
+//!
+//! Then open `http://<daemon-host>:8081/` in a browser.
+
+use common::core::{Measurement, PixelBuffer};
+use common::memory::{MemoryAccount, MemoryLedger, MemorySubsystem};
+use hyper::body::Bytes;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use storage::ring_buffer::RingBuffer;
+use tokio::sync::{mpsc, watch};
+
+/// Ring buffer tap used by the preview server
+const TAP_ID: &str = "http_preview";
+
+/// Multipart boundary for MJPEG streams
+const BOUNDARY: &str = "rudaqframe";
+
+/// Preview encoding settings
+#[derive(Debug, Clone)]
+pub struct PreviewConfig {
+    /// Maximum JPEG encodes per second per stream
+    pub max_fps: f64,
+    /// JPEG quality (1-100)
+    pub jpeg_quality: u8,
+    /// Frames larger than this on either side are downsampled
+    pub max_dimension: u32,
+}
+
+impl Default for PreviewConfig {
+    fn default() -> Self {
+        Self {
+            max_fps: 10.0,
+            jpeg_quality: 80,
+            max_dimension: 1024,
+        }
+    }
+}
+
+impl PreviewConfig {
+    /// Defaults overridden by `PREVIEW_MAX_FPS`, `PREVIEW_JPEG_QUALITY` and
+    /// `PREVIEW_MAX_DIMENSION`
+    pub fn from_env() -> Self {
+        fn env<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok().and_then(|v| v.parse().ok())
+        }
+        let defaults = Self::default();
+        Self {
+            max_fps: env("PREVIEW_MAX_FPS").unwrap_or(defaults.max_fps),
+            jpeg_quality: env("PREVIEW_JPEG_QUALITY").unwrap_or(defaults.jpeg_quality),
+            max_dimension: env("PREVIEW_MAX_DIMENSION").unwrap_or(defaults.max_dimension),
+        }
+    }
+
+    fn min_frame_interval(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.max_fps.max(0.1))
+    }
+}
+
+/// Newest encoded frame of one image stream
+#[derive(Debug, Clone)]
+pub struct PreviewFrame {
+    pub jpeg: Bytes,
+    /// Encoded width (after downsampling)
+    pub width: u32,
+    /// Encoded height (after downsampling)
+    pub height: u32,
+    /// Increments with every published frame
+    pub seq: u64,
+}
+
+/// Latest frames shared between the tap consumer and HTTP handlers
+struct PreviewState {
+    frames: RwLock<HashMap<String, PreviewFrame>>,
+    /// Bytes of the cached JPEGs
+    memory: MemoryAccount,
+    /// Bumped on every published frame to wake MJPEG streams
+    updates: watch::Sender<u64>,
+    closed: AtomicBool,
+}
+
+impl PreviewState {
+    fn new() -> Self {
+        Self {
+            frames: RwLock::new(HashMap::new()),
+            memory: MemoryLedger::global().account("camera_preview", MemorySubsystem::Cache),
+            updates: watch::channel(0).0,
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    fn publish(&self, name: String, jpeg: Vec<u8>, width: u32, height: u32) {
+        let seq = *self.updates.borrow() + 1;
+        self.memory.add(jpeg.len() as u64);
+        let previous = self
+            .frames
+            .write()
+            .unwrap_or_else(|p| p.into_inner())
+            .insert(
+                name,
+                PreviewFrame {
+                    jpeg: Bytes::from(jpeg),
+                    width,
+                    height,
+                    seq,
+                },
+            );
+        if let Some(previous) = previous {
+            self.memory.sub(previous.jpeg.len() as u64);
+        }
+        self.updates.send_replace(seq);
+    }
+
+    fn frame(&self, name: &str) -> Option<PreviewFrame> {
+        self.frames
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(name)
+            .cloned()
+    }
+
+    fn stream_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .frames
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .keys()
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// End all MJPEG streams
+    fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.updates.send_modify(|seq| *seq += 1);
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+}
+
+/// Handle returned by [`start_preview_server`]
+///
+/// Dropping it stops the HTTP server and removes the ring buffer tap.
+pub struct PreviewServerHandle {
+    _shutdown_tx: tokio::sync::oneshot::Sender<()>,
+    ring_buffer: Arc<RingBuffer>,
+}
+
+impl Drop for PreviewServerHandle {
+    fn drop(&mut self) {
+        if let Err(e) = self.ring_buffer.unregister_tap(TAP_ID) {
+            tracing::warn!(error = %e, "Failed to remove preview tap");
+        }
+    }
+}
+
+/// Start the MJPEG/JPEG preview HTTP server
+///
+/// Registers a tap on `ring_buffer` and serves image measurements written to
+/// it. Must be called from within a Tokio runtime. Fails if the port can't be
+/// bound or the tap is already registered.
+pub fn start_preview_server(
+    port: u16,
+    ring_buffer: Arc<RingBuffer>,
+    config: PreviewConfig,
+) -> Result<PreviewServerHandle, Box<dyn std::error::Error + Send + Sync>> {
+    let addr: SocketAddr = ([0, 0, 0, 0], port).into();
+    let builder = hyper::Server::try_bind(&addr)?;
+
+    // Every record: the tap can't tell images from scalars, so throttling
+    // happens per stream in the consumer
+    let tap_rx = ring_buffer.register_tap(TAP_ID.to_string(), 1)?;
+
+    let state = Arc::new(PreviewState::new());
+    tokio::spawn(run_tap_consumer(tap_rx, state.clone(), config));
+
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let make_service = hyper::service::make_service_fn(move |_conn| {
+        let state = state.clone();
+        async move {
+            Ok::<_, Infallible>(hyper::service::service_fn(move |req| {
+                let response = handle_preview_request(&req, state.clone());
+                async move { Ok::<_, Infallible>(response) }
+            }))
+        }
+    });
+    let server = builder.serve(make_service).with_graceful_shutdown(async {
+        let _ = shutdown_rx.await;
+    });
+
+    tracing::info!(port = port, "Starting camera preview server");
+
+    tokio::spawn(async move {
+        if let Err(e) = server.await {
+            tracing::error!("Preview server error: {}", e);
+        }
+    });
+
+    Ok(PreviewServerHandle {
+        _shutdown_tx: shutdown_tx,
+        ring_buffer,
+    })
+}
+
+/// Decode image measurements from the tap and publish JPEGs
+async fn run_tap_consumer(
+    mut rx: mpsc::Receiver<Vec<u8>>,
+    state: Arc<PreviewState>,
+    config: PreviewConfig,
+) {
+    let min_interval = config.min_frame_interval();
+    let mut last_encoded: HashMap<String, Instant> = HashMap::new();
+
+    while let Some(record) = rx.recv().await {
+        let Some(Measurement::Image {
+            name,
+            width,
+            height,
+            buffer,
+            ..
+        }) = decode_ring_record(&record)
+        else {
+            continue;
+        };
+        if last_encoded
+            .get(&name)
+            .is_some_and(|t| t.elapsed() < min_interval)
+        {
+            continue;
+        }
+        last_encoded.insert(name.clone(), Instant::now());
+
+        let quality = config.jpeg_quality;
+        let max_dimension = config.max_dimension;
+        let encoded = tokio::task::spawn_blocking(move || {
+            encode_preview_jpeg(width, height, &buffer, quality, max_dimension)
+        })
+        .await;
+        match encoded {
+            Ok(Ok((jpeg, w, h))) => state.publish(name, jpeg, w, h),
+            Ok(Err(e)) => tracing::debug!(stream = %name, error = %e, "Skipping preview frame"),
+            Err(e) => tracing::warn!(error = %e, "Preview encoder task failed"),
+        }
+    }
+
+    // Tap removed: end open MJPEG streams
+    state.close();
+}
+
+/// Decode a length-prefixed bincode measurement as written to the ring buffer
+fn decode_ring_record(record: &[u8]) -> Option<Measurement> {
+    let len_bytes: [u8; 4] = record.get(..4)?.try_into().ok()?;
+    let len = u32::from_le_bytes(len_bytes) as usize;
+    bincode::deserialize(record.get(4..4 + len)?).ok()
+}
+
+/// Autoscale an image to 8-bit, downsample to `max_dimension` and encode as JPEG
+///
+/// Returns the JPEG bytes and the encoded dimensions.
+fn encode_preview_jpeg(
+    width: u32,
+    height: u32,
+    buffer: &PixelBuffer,
+    quality: u8,
+    max_dimension: u32,
+) -> Result<(Vec<u8>, u32, u32), String> {
+    let (w, h) = (width as usize, height as usize);
+    if w == 0 || h == 0 || buffer.len() < w * h {
+        return Err(format!(
+            "image buffer has {} pixels, expected {}x{}",
+            buffer.len(),
+            width,
+            height
+        ));
+    }
+
+    let step = width.max(height).div_ceil(max_dimension.max(1)).max(1) as usize;
+    let value = |idx: usize| -> f64 {
+        match buffer {
+            PixelBuffer::U8(data) => f64::from(data[idx]),
+            PixelBuffer::U16(data) => f64::from(data[idx]),
+            PixelBuffer::F64(data) => data[idx],
+        }
+    };
+    let samples: Vec<f64> = (0..h)
+        .step_by(step)
+        .flat_map(|y| (0..w).step_by(step).map(move |x| y * w + x))
+        .map(value)
+        .collect();
+
+    let (min, max) = samples
+        .iter()
+        .filter(|v| v.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        });
+    let range = max - min;
+    let pixels: Vec<u8> = samples
+        .iter()
+        .map(|&v| {
+            if range > 0.0 && v.is_finite() {
+                ((v - min) / range * 255.0).round() as u8
+            } else {
+                0
+            }
+        })
+        .collect();
+
+    let out_width = w.div_ceil(step) as u32;
+    let out_height = h.div_ceil(step) as u32;
+    let mut jpeg = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, quality.clamp(1, 100))
+        .encode(&pixels, out_width, out_height, image::ExtendedColorType::L8)
+        .map_err(|e| e.to_string())?;
+    Ok((jpeg, out_width, out_height))
+}
+
+fn handle_preview_request(
+    req: &hyper::Request<hyper::Body>,
+    state: Arc<PreviewState>,
+) -> hyper::Response<hyper::Body> {
+    if req.method() != hyper::Method::GET {
+        return text_response(405, "Method Not Allowed");
+    }
+
+    let path = req.uri().path();
+    match path {
+        "/" => hyper::Response::builder()
+            .status(200)
+            .header("Content-Type", "text/html; charset=utf-8")
+            .body(hyper::Body::from(index_page(&state.stream_names())))
+            .unwrap(),
+        "/health" => text_response(200, "OK"),
+        _ => {
+            if let Some(name) = path
+                .strip_prefix("/snapshot/")
+                .and_then(|p| p.strip_suffix(".jpg"))
+            {
+                match state.frame(name) {
+                    Some(frame) => hyper::Response::builder()
+                        .status(200)
+                        .header("Content-Type", "image/jpeg")
+                        .header("Cache-Control", "no-store")
+                        .body(hyper::Body::from(frame.jpeg))
+                        .unwrap(),
+                    None => text_response(404, "No frame for stream"),
+                }
+            } else if let Some(name) = path.strip_prefix("/stream/") {
+                hyper::Response::builder()
+                    .status(200)
+                    .header(
+                        "Content-Type",
+                        format!("multipart/x-mixed-replace; boundary={}", BOUNDARY),
+                    )
+                    .header("Cache-Control", "no-store")
+                    .body(mjpeg_body(state, name.to_string()))
+                    .unwrap()
+            } else {
+                text_response(404, "Not Found")
+            }
+        }
+    }
+}
+
+fn text_response(status: u16, body: &'static str) -> hyper::Response<hyper::Body> {
+    hyper::Response::builder()
+        .status(status)
+        .body(hyper::Body::from(body))
+        .unwrap()
+}
+
+/// Body that yields one multipart JPEG part per new frame of `name`
+fn mjpeg_body(state: Arc<PreviewState>, name: String) -> hyper::Body {
+    let updates = state.updates.subscribe();
+    let parts = futures::stream::unfold((updates, None::<u64>), move |(mut updates, last_seq)| {
+        let state = state.clone();
+        let name = name.clone();
+        async move {
+            loop {
+                if state.is_closed() {
+                    return None;
+                }
+                if let Some(frame) = state.frame(&name)
+                    && Some(frame.seq) != last_seq
+                {
+                    let seq = frame.seq;
+                    return Some((
+                        Ok::<_, Infallible>(mjpeg_part(&frame.jpeg)),
+                        (updates, Some(seq)),
+                    ));
+                }
+                updates.changed().await.ok()?;
+            }
+        }
+    });
+    hyper::Body::wrap_stream(parts)
+}
+
+fn mjpeg_part(jpeg: &[u8]) -> Bytes {
+    let header = format!(
+        "--{}\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\n\r\n",
+        BOUNDARY,
+        jpeg.len()
+    );
+    let mut part = Vec::with_capacity(header.len() + jpeg.len() + 2);
+    part.extend_from_slice(header.as_bytes());
+    part.extend_from_slice(jpeg);
+    part.extend_from_slice(b"\r\n");
+    Bytes::from(part)
+}
+
+fn index_page(streams: &[String]) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\">\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+         <title>rust-daq camera preview</title></head><body>",
+    );
+    if streams.is_empty() {
+        html.push_str("<p>No camera frames received yet. Reload once acquisition is running.</p>");
+    }
+    for name in streams {
+        // Stream names come from device measurements, not from this server
+        let _ = write!(
+            html,
+            "<h3>{0}</h3><a href=\"/snapshot/{0}.jpg\">\
+             <img src=\"/stream/{0}\" style=\"max-width:100%\" alt=\"{0}\"></a>",
+            html_escape(name)
+        );
+    }
+    html.push_str("</body></html>");
+    html
+}
+
+fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn encode_record(measurement: &Measurement) -> Vec<u8> {
+        let payload = bincode::serialize(measurement).unwrap();
+        let mut record = (payload.len() as u32).to_le_bytes().to_vec();
+        record.extend_from_slice(&payload);
+        record
+    }
+
+    #[test]
+    fn test_decode_ring_record_image() {
+        let record = encode_record(&Measurement::Image {
+            name: "camera".to_string(),
+            width: 2,
+            height: 2,
+            buffer: PixelBuffer::U16(vec![0, 100, 200, 300]),
+            unit: "counts".to_string(),
+            metadata: Default::default(),
+            timestamp: Utc::now(),
+        });
+        match decode_ring_record(&record) {
+            Some(Measurement::Image { name, width, .. }) => {
+                assert_eq!(name, "camera");
+                assert_eq!(width, 2);
+            }
+            other => panic!("unexpected record: {:?}", other),
+        }
+        assert!(decode_ring_record(&record[..3]).is_none());
+    }
+
+    #[test]
+    fn test_encode_preview_jpeg_downsamples_16bit() {
+        let pixels: Vec<u16> = (0..64 * 32).map(|i| (i * 16) as u16).collect();
+        let (jpeg, w, h) = encode_preview_jpeg(64, 32, &PixelBuffer::U16(pixels), 80, 16).unwrap();
+        assert_eq!((w, h), (16, 8));
+        // JPEG SOI marker
+        assert_eq!(&jpeg[..2], &[0xFF, 0xD8]);
+
+        assert!(encode_preview_jpeg(4, 4, &PixelBuffer::U8(vec![0; 3]), 80, 16).is_err());
+    }
+
+    #[test]
+    fn test_mjpeg_part_framing() {
+        let part = mjpeg_part(&[1, 2, 3]);
+        let text = String::from_utf8_lossy(&part);
+        assert!(text.starts_with("--rudaqframe\r\nContent-Type: image/jpeg\r\n"));
+        assert!(text.contains("Content-Length: 3\r\n\r\n"));
+        assert!(part.ends_with(&[1, 2, 3, b'\r', b'\n']));
+    }
+
+    #[test]
+    fn test_cached_frames_are_accounted() {
+        let state = PreviewState::new();
+        state.publish("cam1".to_string(), vec![0; 1000], 8, 8);
+        state.publish("cam2".to_string(), vec![0; 300], 8, 8);
+        assert_eq!(state.memory.bytes(), 1300);
+
+        // A newer frame replaces the stream's cached one
+        state.publish("cam1".to_string(), vec![0; 400], 8, 8);
+        assert_eq!(state.memory.bytes(), 700);
+    }
+
+    #[test]
+    fn test_index_page_escapes_stream_names() {
+        let html = index_page(&["cam<script>alert(1)</script>\"".to_string()]);
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("<h3>cam&lt;script&gt;alert(1)&lt;/script&gt;&quot;</h3>"));
+        assert!(html.contains("alt=\"cam&lt;script&gt;alert(1)&lt;/script&gt;&quot;\""));
+    }
+}
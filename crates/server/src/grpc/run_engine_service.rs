@@ -3,23 +3,51 @@
 //! Provides gRPC interface for the Bluesky-inspired RunEngine.
 //! Enables declarative plan execution with pause/resume/abort capabilities.
 
+use crate::grpc::hardware_service::{device_event_to_proto, drop_report_to_proto};
 use crate::grpc::proto::{
-    AbortPlanRequest, AbortPlanResponse, EngineStatus, GetEngineStatusRequest, HaltEngineRequest,
-    HaltEngineResponse, ListPlanTypesRequest, ListPlanTypesResponse, PauseEngineRequest,
-    PauseEngineResponse, PlanTypeInfo, QueuePlanRequest, QueuePlanResponse, ResumeEngineRequest,
-    ResumeEngineResponse, StartEngineRequest, StartEngineResponse, StreamDocumentsRequest,
+    AbortPlanRequest, AbortPlanResponse, AddRunMarkerRequest, AddRunMarkerResponse,
+    ChannelRecordingChange, ChannelRecordingStatus, CompareRunsRequest, DryRunPlanRequest,
+    DryRunPlanResponse, EngineStatus, GetChannelRecordingRequest, GetEngineStatusRequest,
+    GetRunProgressRequest, HaltEngineRequest, HaltEngineResponse, ListPlanTypesRequest,
+    ListPlanTypesResponse, ListRunsRequest, ListRunsResponse, PauseEngineRequest,
+    PauseEngineResponse, PlanTypeInfo, QueuePlanRequest, QueuePlanResponse, QueueTemplateRequest,
+    QueueTemplateResponse, ResumeEngineRequest, ResumeEngineResponse, RunComparison, RunProgress,
+    SetChannelRecordingRequest, SetRecordingDecimationRequest, StartEngineRequest,
+    StartEngineResponse, StreamDocumentsRequest, VerifyRunRequest, VerifyRunResponse,
     run_engine_service_server::RunEngineService,
 };
-use experiment::Document; // Re-exported from common
-use experiment::plans::{CountBuilder, GridScanBuilder, LineScanBuilder, PlanRegistry};
-use experiment::run_engine::RunEngine;
+use crate::grpc::stream_stats::StreamStatsRegistry;
+use common::decimation::Decimation;
+use common::document_transform::{STORAGE_CONSUMER, STREAM_CONSUMER};
+use common::experiment::document::now_ns;
+use common::integrity::{DropLedger, DropStage};
+use common::listing::{ListFilter, paginate};
+use common::provenance::{RunSigner, SignedManifest, Verification, parse_public_key};
+use experiment::config_snapshot::ConfigSnapshot;
+use experiment::document_bus::{DocumentRecvError, SequencedDocument};
+use experiment::dry_run::{DryRunOptions, DryRunReport, DryRunSeverity, DryRunStep};
+use experiment::plans::PlanCommand;
+use experiment::plans::{
+    CountBuilder, GridScanBuilder, LineScanBuilder, PlanRegistry, PositionScanBuilder,
+};
+use experiment::recording::ChannelRecording;
+use experiment::run_comparison::{
+    ChannelComparison, ChannelStats, DEFAULT_HISTORY_CAPACITY, FieldDiff, HistoryWriter, RunDiff,
+    RunHistory, RunMarker, RunSummary,
+};
+use experiment::run_engine::{RunEngine, validate_run_metadata};
+use experiment::templates::PlanTemplate;
+use experiment::{Document, StartDoc}; // Re-exported from common
 use futures::StreamExt; // For .filter_map() with async
-use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use storage::DocumentWriter;
 use tokio_stream::wrappers::{BroadcastStream, errors::BroadcastStreamRecvError};
 use tonic::{Request, Response, Status};
 
+/// Queue depth for the persistence subscriber
+const PERSISTENCE_QUEUE_CAPACITY: usize = 16_384;
+
 /// RunEngine gRPC service implementation.
 ///
 /// Wraps the domain RunEngine and exposes its capabilities over gRPC.
@@ -39,6 +67,14 @@ pub struct RunEngineServiceImpl {
     plan_registry: Arc<PlanRegistry>,
     /// Persists documents to HDF5 (bd-jwsc)
     document_writer: Arc<DocumentWriter>,
+    /// Summaries of completed runs for ListRuns/CompareRuns
+    run_history: Arc<tokio::sync::RwLock<RunHistory>>,
+    /// Persists run history changes outside the `run_history` lock
+    history_writer: HistoryWriter,
+    /// Signs completed run files (`None` = files are not signed)
+    signer: Option<Arc<RunSigner>>,
+    /// Channels left out of persisted documents
+    channel_recording: Arc<Mutex<ChannelRecording>>,
 }
 
 impl RunEngineServiceImpl {
@@ -47,6 +83,11 @@ impl RunEngineServiceImpl {
     /// Spawns a background task that converts domain documents to proto and broadcasts
     /// them to all gRPC clients. This ensures O(M) conversions instead of O(N×M).
     pub fn new(engine: Arc<RunEngine>) -> Self {
+        Self::with_signer(engine, None)
+    }
+
+    /// Construct the service, signing every completed run file with `signer`
+    pub fn with_signer(engine: Arc<RunEngine>, signer: Option<Arc<RunSigner>>) -> Self {
         // Create proto document broadcast channel
         let (proto_doc_sender, _) = tokio::sync::broadcast::channel(1024);
 
@@ -58,30 +99,87 @@ impl RunEngineServiceImpl {
         registry.register("count", CountBuilder);
         registry.register("line_scan", LineScanBuilder);
         registry.register("grid_scan", GridScanBuilder);
+        registry.register("position_scan", PositionScanBuilder);
         let plan_registry = Arc::new(registry);
 
         // Initialize document writer (data stored in ./data directory)
         let data_dir = std::path::Path::new("data").to_path_buf();
         std::fs::create_dir_all(&data_dir).ok(); // Ensure directory exists
-        let document_writer = Arc::new(DocumentWriter::new(data_dir));
+        let mut document_writer = DocumentWriter::new(data_dir.clone());
+        if let Some(signer) = &signer {
+            tracing::info!(key_id = %signer.key_id(), "Signing completed run files");
+            document_writer = document_writer.with_signer(signer.clone());
+        }
+        let document_writer = Arc::new(document_writer);
+
+        // Run summaries are kept next to the HDF5 files so comparisons survive restarts
+        let run_history =
+            RunHistory::with_directory(data_dir.join("runs"), DEFAULT_HISTORY_CAPACITY)
+                .unwrap_or_else(|e| {
+                    tracing::warn!(error = %e, "Run history not persisted, keeping it in memory");
+                    RunHistory::new(DEFAULT_HISTORY_CAPACITY)
+                });
+        let run_history = Arc::new(tokio::sync::RwLock::new(run_history));
+        let history_writer = HistoryWriter::spawn();
+        let channel_recording = Arc::new(Mutex::new(ChannelRecording::new()));
 
         // Spawn persistence task (bd-jwsc)
         let engine_clone_writer = engine.clone();
         let writer_clone = document_writer.clone();
+        let history_clone = run_history.clone();
+        let history_writer_clone = history_writer.clone();
+        let recording_clone = channel_recording.clone();
+        let storage_transforms = engine.document_transforms();
         tokio::spawn(async move {
-            let mut domain_rx = engine_clone_writer.subscribe();
+            // Deeper queue than live streams: missed documents are lost data
+            let mut domain_rx =
+                engine_clone_writer.subscribe_with_capacity(PERSISTENCE_QUEUE_CAPACITY);
             loop {
                 match domain_rx.recv().await {
                     Ok(doc) => {
+                        {
+                            let mut history = history_clone.write().await;
+                            history.observe(&doc);
+                            history_writer_clone.submit(history.take_writes());
+                        }
+
+                        // The run's config snapshot goes beside its data file
+                        if let Document::Start(start) = &doc {
+                            save_config_snapshot(&writer_clone, start);
+                        }
+
+                        // Disabled channels and decimated events stay in live
+                        // streams but not in the file
+                        let doc = {
+                            let mut recording =
+                                recording_clone.lock().unwrap_or_else(|p| p.into_inner());
+                            if !recording.admit(&doc) {
+                                continue;
+                            }
+                            recording.apply(doc)
+                        };
+                        let doc = storage_transforms.apply(STORAGE_CONSUMER, doc);
+
                         // Forward to writer (handles HDF5 interaction on blocking thread)
-                        if let Err(e) = writer_clone.write(doc).await {
-                            tracing::error!(error = %e, "Failed to persist document");
+                        match writer_clone.write(doc).await {
+                            Ok(Some(signed)) => match signed.manifest() {
+                                // Keep the catalog copy the file is verified against
+                                Ok(manifest) => {
+                                    let mut history = history_clone.write().await;
+                                    history.attach_provenance(&manifest.run_uid, signed);
+                                    history_writer_clone.submit(history.take_writes());
+                                }
+                                Err(e) => tracing::error!(error = %e, "Unreadable run signature"),
+                            },
+                            Ok(None) => {}
+                            Err(e) => tracing::error!(error = %e, "Failed to persist document"),
                         }
                     }
-                    Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
-                        tracing::warn!(skipped = n, "Persistence task lagged");
+                    Err(DocumentRecvError::Gap { missed }) => {
+                        tracing::error!(missed, "Persistence task fell behind, documents lost");
+                        DropLedger::global().record("documents", DropStage::Storage, missed);
                     }
-                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    Err(DocumentRecvError::Closed) => break,
                 }
             }
         });
@@ -89,17 +187,24 @@ impl RunEngineServiceImpl {
         // Spawn converter task that subscribes to domain stream and broadcasts proto
         let engine_clone = engine.clone();
         let proto_sender_clone = proto_doc_sender.clone();
+        let stream_transforms = engine.document_transforms();
         tokio::spawn(async move {
             let mut domain_rx = engine_clone.subscribe();
             let mut total_converted = 0u64;
 
             loop {
-                match domain_rx.recv().await {
-                    Ok(domain_doc) => {
-                        // Convert domain → proto (ONCE for all clients)
+                match domain_rx.recv_sequenced().await {
+                    Ok(SequencedDocument {
+                        seq,
+                        doc: domain_doc,
+                    }) => {
+                        // Reshape for network clients, then convert domain → proto
+                        // (ONCE for all clients)
                         let start = std::time::Instant::now();
+                        let domain_doc = stream_transforms.apply(STREAM_CONSUMER, domain_doc);
                         match domain_to_proto_document(domain_doc) {
-                            Ok(Some(proto_doc)) => {
+                            Ok(Some(mut proto_doc)) => {
+                                proto_doc.sequence = seq;
                                 let conversion_micros = start.elapsed().as_micros();
                                 total_converted += 1;
 
@@ -140,14 +245,14 @@ impl RunEngineServiceImpl {
                             }
                         }
                     }
-                    Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    Err(DocumentRecvError::Gap { missed }) => {
                         tracing::warn!(
-                            skipped,
+                            missed,
                             total_converted,
                             "Converter task lagged, skipped domain documents"
                         );
                     }
-                    Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                    Err(DocumentRecvError::Closed) => {
                         tracing::info!(
                             total_converted,
                             "RunEngine document stream closed, stopping converter task"
@@ -164,8 +269,42 @@ impl RunEngineServiceImpl {
             active_streams,
             plan_registry,
             document_writer,
+            run_history,
+            history_writer,
+            signer,
+            channel_recording,
         }
     }
+
+    /// Directory run data files and their config snapshots are written to
+    pub fn data_directory(&self) -> &std::path::Path {
+        self.document_writer.base_path()
+    }
+
+    /// Writer persisting run documents to HDF5
+    pub fn document_writer(&self) -> Arc<DocumentWriter> {
+        self.document_writer.clone()
+    }
+
+    /// Start in commissioning mode, persisting only the decimated events
+    pub fn set_storage_decimation(&self, decimation: Decimation) {
+        tracing::info!(?decimation, "Storage decimation set from config");
+        self.channel_recording
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .set_decimation(decimation, "config", now_ns());
+    }
+}
+
+/// Write the config snapshot recorded in `start` next to the run's data file
+fn save_config_snapshot(writer: &DocumentWriter, start: &StartDoc) {
+    let Some(snapshot) = ConfigSnapshot::from_start(start) else {
+        return;
+    };
+    let path = ConfigSnapshot::path_for(&writer.run_file_path(&start.uid, start.time_ns));
+    if let Err(e) = snapshot.and_then(|snapshot| snapshot.save(&path)) {
+        tracing::error!(run_uid = %start.uid, error = %e, "Failed to save config snapshot");
+    }
 }
 
 impl Clone for RunEngineServiceImpl {
@@ -176,6 +315,10 @@ impl Clone for RunEngineServiceImpl {
             active_streams: self.active_streams.clone(),
             plan_registry: self.plan_registry.clone(),
             document_writer: self.document_writer.clone(),
+            run_history: self.run_history.clone(),
+            history_writer: self.history_writer.clone(),
+            signer: self.signer.clone(),
+            channel_recording: self.channel_recording.clone(),
         }
     }
 }
@@ -194,13 +337,7 @@ impl RunEngineService for RunEngineServiceImpl {
             .list_types()
             .into_iter()
             .map(|(type_id, description, categories)| {
-                let display_name = match type_id.as_str() {
-                    "count" => "Count",
-                    "line_scan" => "Line Scan",
-                    "grid_scan" => "Grid Scan",
-                    s => s, // Fallback to ID
-                }
-                .to_string();
+                let display_name = plan_display_name(&type_id);
 
                 PlanTypeSummary {
                     type_id,
@@ -216,11 +353,48 @@ impl RunEngineService for RunEngineServiceImpl {
 
     async fn get_plan_type_info(
         &self,
-        _request: Request<crate::grpc::proto::GetPlanTypeInfoRequest>,
+        request: Request<crate::grpc::proto::GetPlanTypeInfoRequest>,
     ) -> Result<Response<PlanTypeInfo>, Status> {
-        Err(Status::unimplemented(
-            "get_plan_type_info not yet implemented",
-        ))
+        use crate::grpc::proto::PlanDeviceRole;
+
+        let type_id = request.into_inner().type_id;
+        let (_, description, _) = self
+            .plan_registry
+            .list_types()
+            .into_iter()
+            .find(|(id, _, _)| *id == type_id)
+            .ok_or_else(|| Status::not_found(format!("Unknown plan type: {}", type_id)))?;
+        let device_roles = self
+            .plan_registry
+            .device_roles(&type_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|role| PlanDeviceRole {
+                role_id: role.role_id,
+                required_capability: role
+                    .capabilities
+                    .first()
+                    .map(|c| c.as_str().to_string())
+                    .unwrap_or_default(),
+                description: role.description,
+                accepted_capabilities: role
+                    .capabilities
+                    .iter()
+                    .map(|c| c.as_str().to_string())
+                    .collect(),
+                optional: role.optional,
+                default_binding: role.default_binding,
+            })
+            .collect();
+
+        // Parameter metadata is not yet declared by builders
+        Ok(Response::new(PlanTypeInfo {
+            display_name: plan_display_name(&type_id),
+            type_id,
+            description,
+            parameters: Vec::new(),
+            device_roles,
+        }))
     }
 
     async fn queue_plan(
@@ -229,12 +403,18 @@ impl RunEngineService for RunEngineServiceImpl {
     ) -> Result<Response<QueuePlanResponse>, Status> {
         let req = request.get_ref();
 
-        // Create plan from request parameters using the registry
+        // Create plan from request parameters using the registry, binding
+        // device roles against the capabilities of registered devices
         let plan = self
             .plan_registry
-            .create_plan(&req.plan_type, &req.parameters, &req.device_mapping)
+            .create_plan_with_roles(&req.plan_type, &req.parameters, &req.device_mapping, |id| {
+                self.engine.device_capabilities(id)
+            })
             .map_err(|e| Status::invalid_argument(format!("Failed to create plan: {}", e)))?;
 
+        validate_run_metadata(&req.metadata)
+            .map_err(|e| Status::invalid_argument(format!("Invalid run metadata: {:#}", e)))?;
+
         // Queue the plan
         let run_uid = if req.metadata.is_empty() {
             self.engine.queue(plan).await
@@ -254,6 +434,98 @@ impl RunEngineService for RunEngineServiceImpl {
         }))
     }
 
+    async fn queue_template(
+        &self,
+        request: Request<QueueTemplateRequest>,
+    ) -> Result<Response<QueueTemplateResponse>, Status> {
+        let req = request.into_inner();
+        let template = PlanTemplate {
+            name: req.template_name,
+            plan_type: req.plan_type,
+            parameters: req.parameters,
+            device_mapping: req.device_mapping,
+            metadata: req.metadata,
+        };
+        let items: Vec<_> = req
+            .items
+            .into_iter()
+            .map(|item| item.variables.into_iter().collect())
+            .collect();
+        let expanded = template
+            .expand(&items)
+            .map_err(|e| Status::invalid_argument(format!("Failed to expand template: {}", e)))?;
+
+        // Build every child before queueing any, so a bad item queues nothing
+        let mut runs = Vec::with_capacity(expanded.len());
+        for run in expanded {
+            let plan = self
+                .plan_registry
+                .create_plan_with_roles(
+                    &template.plan_type,
+                    &run.parameters,
+                    &run.device_mapping,
+                    |id| self.engine.device_capabilities(id),
+                )
+                .map_err(|e| {
+                    Status::invalid_argument(format!(
+                        "Failed to create plan for item {}: {}",
+                        run.index, e
+                    ))
+                })?;
+            validate_run_metadata(&run.metadata).map_err(|e| {
+                Status::invalid_argument(format!(
+                    "Invalid run metadata for item {}: {:#}",
+                    run.index, e
+                ))
+            })?;
+            runs.push((plan, run));
+        }
+
+        let (batch_uid, run_uids) = self.engine.queue_batch(&template.name, runs).await;
+        let queue_len = self.engine.queue_len().await;
+
+        Ok(Response::new(QueueTemplateResponse {
+            batch_uid,
+            run_uids,
+            queue_position: queue_len as u32,
+        }))
+    }
+
+    async fn dry_run_plan(
+        &self,
+        request: Request<DryRunPlanRequest>,
+    ) -> Result<Response<DryRunPlanResponse>, Status> {
+        let req = request.into_inner();
+
+        let mut plan = self
+            .plan_registry
+            .create_plan_with_roles(&req.plan_type, &req.parameters, &req.device_mapping, |id| {
+                self.engine.device_capabilities(id)
+            })
+            .map_err(|e| Status::invalid_argument(format!("Failed to create plan: {}", e)))?;
+
+        let mut options = DryRunOptions {
+            move_speeds: req.move_speeds,
+            exposure_times: req.exposure_times_s,
+            initial_positions: req.initial_positions,
+            ..Default::default()
+        };
+        if let Some(speed) = req.default_move_speed {
+            if !speed.is_finite() || speed <= 0.0 {
+                return Err(Status::invalid_argument(
+                    "default_move_speed must be a positive finite number",
+                ));
+            }
+            options.default_move_speed = speed;
+        }
+        if req.max_commands > 0 {
+            options.max_recorded_commands = req.max_commands as usize;
+        }
+
+        let report = self.engine.dry_run(plan.as_mut(), options).await;
+        Ok(Response::new(dry_run_report_to_proto(report)))
+    }
+
     async fn start_engine(
         &self,
         _request: Request<StartEngineRequest>,
@@ -366,18 +638,235 @@ impl RunEngineService for RunEngineServiceImpl {
             0
         };
 
+        let progress = self.engine.run_progress().await;
+
         Ok(Response::new(EngineStatus {
             state: proto_state as i32,
-            current_run_uid: None,
-            current_plan_type: None,
-            current_event_number: None,
-            total_events_expected: None,
+            current_run_uid: progress.as_ref().map(|p| p.run_uid.clone()),
+            current_plan_type: self.engine.current_plan_type().await,
+            current_event_number: progress.as_ref().map(|p| p.points_completed),
+            total_events_expected: progress.as_ref().map(|p| p.points_total),
             queued_plans: queue_len,
             run_start_ns,
             elapsed_ns,
         }))
     }
 
+    async fn get_run_progress(
+        &self,
+        _request: Request<GetRunProgressRequest>,
+    ) -> Result<Response<RunProgress>, Status> {
+        let progress = self.engine.run_progress().await;
+        Ok(Response::new(RunProgress {
+            active: progress.is_some(),
+            progress: progress.as_ref().map(progress_doc_to_proto),
+        }))
+    }
+
+    async fn list_runs(
+        &self,
+        request: Request<ListRunsRequest>,
+    ) -> Result<Response<ListRunsResponse>, Status> {
+        let req = request.into_inner();
+        let filter = ListFilter::parse(&req.filter, RUN_FILTER_KEYS)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let history = self.run_history.read().await;
+        let mut runs: Vec<&RunSummary> = history
+            .list()
+            .filter(|run| filter.matches(|key| run_filter_values(run, key)))
+            .collect();
+        sort_runs(&mut runs, &req.sort_by, req.descending)?;
+        let total_count = runs.len() as u32;
+        let runs = paginate(runs, req.offset as usize, req.limit as usize)
+            .into_iter()
+            .map(run_summary_to_proto)
+            .collect();
+        Ok(Response::new(ListRunsResponse { runs, total_count }))
+    }
+
+    async fn compare_runs(
+        &self,
+        request: Request<CompareRunsRequest>,
+    ) -> Result<Response<RunComparison>, Status> {
+        let req = request.into_inner();
+        let diff = self
+            .run_history
+            .read()
+            .await
+            .compare(&req.baseline_run_uid, &req.candidate_run_uid)
+            .map_err(|e| Status::not_found(e.to_string()))?;
+        Ok(Response::new(run_diff_to_proto(diff)))
+    }
+
+    async fn verify_run(
+        &self,
+        request: Request<VerifyRunRequest>,
+    ) -> Result<Response<VerifyRunResponse>, Status> {
+        let req = request.into_inner();
+        let mut trusted_keys = req
+            .trusted_public_keys
+            .iter()
+            .map(|key| parse_public_key(key))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        if let Some(signer) = &self.signer {
+            trusted_keys.push(signer.verifying_key());
+        }
+
+        let (start_ns, catalog) = {
+            let history = self.run_history.read().await;
+            let summary = history
+                .get(&req.run_uid)
+                .ok_or_else(|| Status::not_found(format!("Unknown run '{}'", req.run_uid)))?;
+            (summary.start_ns, summary.provenance.clone())
+        };
+        let path = self.document_writer.run_file_path(&req.run_uid, start_ns);
+        if !path.exists() {
+            return Err(Status::not_found(format!(
+                "Run file {} not found",
+                path.display()
+            )));
+        }
+
+        let file_path = path.clone();
+        let (embedded, verification) = tokio::task::spawn_blocking(move || {
+            let Some(signed) = storage::read_signed_manifest(&file_path)? else {
+                return Ok((None, None));
+            };
+            let digests = storage::provenance::file_digests(&file_path)?;
+            let verification = common::provenance::verify(&signed, &digests, &trusted_keys)?;
+            anyhow::Ok((Some(signed), Some(verification)))
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?
+        .map_err(|e| Status::internal(e.to_string()))?;
+
+        if embedded.is_none() && catalog.is_none() {
+            return Err(Status::failed_precondition(format!(
+                "Run '{}' was not signed",
+                req.run_uid
+            )));
+        }
+        Ok(Response::new(verify_run_to_proto(
+            &req.run_uid,
+            &path,
+            embedded.as_ref(),
+            catalog.as_ref(),
+            verification,
+        )))
+    }
+
+    async fn add_run_marker(
+        &self,
+        request: Request<AddRunMarkerRequest>,
+    ) -> Result<Response<AddRunMarkerResponse>, Status> {
+        let req = request.into_inner();
+        let marker = req
+            .marker
+            .ok_or_else(|| Status::invalid_argument("Missing marker"))?;
+        if marker.text.trim().is_empty() {
+            return Err(Status::invalid_argument("Marker text must not be empty"));
+        }
+        let time_ns = if marker.time_ns > 0 {
+            marker.time_ns
+        } else {
+            now_ns()
+        };
+
+        let run_uid = (!req.run_uid.is_empty()).then_some(req.run_uid.as_str());
+        let mut history = self.run_history.write().await;
+        let run_uid = history
+            .add_marker(
+                run_uid,
+                RunMarker {
+                    time_ns,
+                    text: marker.text,
+                    author: marker.author,
+                },
+            )
+            .map_err(|e| match run_uid {
+                Some(_) => Status::not_found(e.to_string()),
+                None => Status::failed_precondition(e.to_string()),
+            })?;
+        self.history_writer.submit(history.take_writes());
+        Ok(Response::new(AddRunMarkerResponse { run_uid }))
+    }
+
+    async fn set_channel_recording(
+        &self,
+        request: Request<SetChannelRecordingRequest>,
+    ) -> Result<Response<ChannelRecordingStatus>, Status> {
+        let req = request.into_inner();
+        let channel = req.channel.trim();
+        if channel.is_empty() {
+            return Err(Status::invalid_argument("Channel must not be empty"));
+        }
+        let time_ns = now_ns();
+
+        let mut recording = self
+            .channel_recording
+            .lock()
+            .unwrap_or_else(|p| p.into_inner());
+        if recording.set_enabled(channel, req.enabled, &req.author, time_ns) {
+            tracing::info!(
+                channel,
+                enabled = req.enabled,
+                author = %req.author,
+                run_uid = recording.active_run().unwrap_or_default(),
+                "Channel recording changed"
+            );
+        }
+        Ok(Response::new(channel_recording_to_proto(&recording)))
+    }
+
+    async fn set_recording_decimation(
+        &self,
+        request: Request<SetRecordingDecimationRequest>,
+    ) -> Result<Response<ChannelRecordingStatus>, Status> {
+        let req = request.into_inner();
+        let decimation = match (req.every_nth, req.max_rate_hz) {
+            (0, hz) if hz == 0.0 => Decimation::All,
+            (0, hz) if hz.is_finite() && hz > 0.0 => Decimation::wall_clock_rate(hz),
+            (0, hz) => {
+                return Err(Status::invalid_argument(format!(
+                    "max_rate_hz must be positive, got {}",
+                    hz
+                )));
+            }
+            (n, hz) if hz == 0.0 => Decimation::EveryNth { n },
+            _ => {
+                return Err(Status::invalid_argument(
+                    "Set either every_nth or max_rate_hz, not both",
+                ));
+            }
+        };
+
+        let mut recording = self
+            .channel_recording
+            .lock()
+            .unwrap_or_else(|p| p.into_inner());
+        if recording.set_decimation(decimation, &req.author, now_ns()) {
+            tracing::info!(
+                ?decimation,
+                author = %req.author,
+                run_uid = recording.active_run().unwrap_or_default(),
+                "Storage decimation changed"
+            );
+        }
+        Ok(Response::new(channel_recording_to_proto(&recording)))
+    }
+
+    async fn get_channel_recording(
+        &self,
+        _request: Request<GetChannelRecordingRequest>,
+    ) -> Result<Response<ChannelRecordingStatus>, Status> {
+        let recording = self
+            .channel_recording
+            .lock()
+            .unwrap_or_else(|p| p.into_inner());
+        Ok(Response::new(channel_recording_to_proto(&recording)))
+    }
+
     type StreamDocumentsStream = std::pin::Pin<
         Box<dyn tokio_stream::Stream<Item = Result<crate::grpc::proto::Document, Status>> + Send>,
     >;
@@ -390,6 +879,13 @@ impl RunEngineService for RunEngineServiceImpl {
         // Performance: O(M) conversions instead of O(N×M) for N clients, M events
         let proto_rx = self.proto_doc_sender.subscribe();
 
+        let stats = StreamStatsRegistry::global().open(
+            "RunEngineService/StreamDocuments",
+            request.get_ref().run_uid.clone().unwrap_or_default(),
+            &request,
+        );
+        let stream_entry = stats.entry();
+
         // Extract filters from request
         let req = request.into_inner();
         let run_uid_filter = req.run_uid.filter(|s| !s.is_empty()).map(Arc::new);
@@ -439,6 +935,7 @@ impl RunEngineService for RunEngineServiceImpl {
             let docs_filtered = docs_filtered.clone();
             let docs_sent = docs_sent.clone();
             let lag_events = lag_events.clone();
+            let stream_entry = stream_entry.clone();
 
             async move {
                 match result {
@@ -468,6 +965,20 @@ impl RunEngineService for RunEngineServiceImpl {
                                     let map = descriptor_map.lock().await;
                                     map.get(&e.descriptor_uid).cloned()
                                 }
+                                Some(crate::grpc::proto::document::Payload::Progress(p)) => {
+                                    Some(p.run_uid.clone())
+                                }
+                                Some(crate::grpc::proto::document::Payload::DeviceEvent(d)) => {
+                                    Some(d.run_uid.clone())
+                                }
+                                // Sent to clients following any of its runs
+                                Some(crate::grpc::proto::document::Payload::BatchSummary(b)) => {
+                                    if b.runs.iter().any(|r| r.run_uid == filter_uid.as_str()) {
+                                        Some(filter_uid.to_string())
+                                    } else {
+                                        Some(b.batch_uid.clone())
+                                    }
+                                }
                                 None => None,
                             };
 
@@ -518,6 +1029,7 @@ impl RunEngineService for RunEngineServiceImpl {
                     Err(BroadcastStreamRecvError::Lagged(skipped)) => {
                         // Receiver fell behind - log and continue without terminating stream
                         let lag_count = lag_events.fetch_add(1, Ordering::Relaxed) + 1;
+                        stream_entry.record_dropped(skipped);
                         let received = docs_received.load(Ordering::Relaxed);
                         let sent = docs_sent.load(Ordering::Relaxed);
                         tracing::warn!(
@@ -547,7 +1059,7 @@ impl RunEngineService for RunEngineServiceImpl {
             lag_events: lag_events_outer,
         };
 
-        Ok(Response::new(Box::pin(wrapped_stream)))
+        Ok(Response::new(Box::pin(stats.track(wrapped_stream))))
     }
 }
 
@@ -594,6 +1106,17 @@ where
     }
 }
 
+/// Human-readable name for a registered plan type
+fn plan_display_name(type_id: &str) -> String {
+    match type_id {
+        "count" => "Count",
+        "line_scan" => "Line Scan",
+        "grid_scan" => "Grid Scan",
+        s => s, // Fallback to ID
+    }
+    .to_string()
+}
+
 /// Convert domain Document to proto Document
 /// Returns Ok(None) for documents that have no proto equivalent (e.g., Manifest)
 fn domain_to_proto_document(doc: Document) -> Result<Option<crate::grpc::proto::Document>, String> {
@@ -628,6 +1151,7 @@ fn domain_to_proto_document(doc: Document) -> Result<Option<crate::grpc::proto::
                 reason: stop.reason.clone(),
                 time_ns: stop.time_ns,
                 num_events: stop.num_events,
+                metadata: stop.metadata.clone(),
             };
             (
                 ProtoDocType::DocStop as i32,
@@ -690,6 +1214,55 @@ fn domain_to_proto_document(doc: Document) -> Result<Option<crate::grpc::proto::
                 )),
             )
         }
+        DomainDoc::Progress(progress) => {
+            let proto_progress = progress_doc_to_proto(&progress);
+            (
+                ProtoDocType::DocProgress as i32,
+                progress.uid,
+                progress.time_ns,
+                Some(crate::grpc::proto::document::Payload::Progress(
+                    proto_progress,
+                )),
+            )
+        }
+        DomainDoc::BatchSummary(summary) => {
+            let proto_summary = crate::grpc::proto::BatchSummaryDocument {
+                batch_uid: summary.batch_uid.clone(),
+                template_name: summary.template_name.clone(),
+                runs: summary
+                    .runs
+                    .iter()
+                    .map(|run| crate::grpc::proto::BatchRunOutcome {
+                        run_uid: run.run_uid.clone(),
+                        index: run.index,
+                        variables: run.variables.clone().into_iter().collect(),
+                        exit_status: run.exit_status.clone(),
+                        reason: run.reason.clone(),
+                        num_events: run.num_events,
+                    })
+                    .collect(),
+                time_ns: summary.time_ns,
+            };
+            (
+                ProtoDocType::DocBatchSummary as i32,
+                summary.uid,
+                summary.time_ns,
+                Some(crate::grpc::proto::document::Payload::BatchSummary(
+                    proto_summary,
+                )),
+            )
+        }
+        DomainDoc::DeviceEvent(doc) => (
+            ProtoDocType::DocDeviceEvent as i32,
+            doc.uid,
+            doc.event.timestamp_ns,
+            Some(crate::grpc::proto::document::Payload::DeviceEvent(
+                crate::grpc::proto::DeviceEventDocument {
+                    run_uid: doc.run_uid,
+                    event: Some(device_event_to_proto(&doc.event)),
+                },
+            )),
+        ),
         DomainDoc::Manifest(_manifest) => {
             // Manifest has no proto equivalent - skip gracefully
             tracing::debug!("Skipping Manifest document (no proto mapping)");
@@ -701,6 +1274,312 @@ fn domain_to_proto_document(doc: Document) -> Result<Option<crate::grpc::proto::
         doc_type,
         uid,
         timestamp_ns,
+        // Assigned by the converter task from the engine's sequence number
+        sequence: 0,
         payload,
     }))
 }
+
+/// Convert a domain dry-run report to its proto response
+fn dry_run_report_to_proto(report: DryRunReport) -> DryRunPlanResponse {
+    use crate::grpc::proto::{DryRunIssue as ProtoIssue, DryRunSeverity as ProtoSeverity};
+
+    let issues = report
+        .issues
+        .into_iter()
+        .map(|issue| ProtoIssue {
+            severity: match issue.severity {
+                DryRunSeverity::Warning => ProtoSeverity::DryRunWarning,
+                DryRunSeverity::Error => ProtoSeverity::DryRunError,
+            } as i32,
+            device_id: issue.device_id.unwrap_or_default(),
+            message: issue.message,
+        })
+        .collect();
+
+    DryRunPlanResponse {
+        success: true,
+        error_message: String::new(),
+        commands: report
+            .steps
+            .into_iter()
+            .enumerate()
+            .map(|(index, step)| dry_run_step_to_proto(index as u32, step))
+            .collect(),
+        total_commands: report.total_commands as u32,
+        commands_truncated: report.truncated,
+        num_events: report.num_events,
+        estimated_duration_s: report.estimated_duration_s,
+        issues,
+    }
+}
+
+fn dry_run_step_to_proto(index: u32, step: DryRunStep) -> crate::grpc::proto::DryRunCommand {
+    let (command_type, device_id, description) = match step.command {
+        PlanCommand::MoveTo {
+            device_id,
+            position,
+        } => {
+            let description = format!("Move {} to {}", device_id, position);
+            ("move_to", device_id, description)
+        }
+        PlanCommand::MoveToPosition {
+            device_id,
+            position_name,
+        } => {
+            let description = format!("Move {} to '{}'", device_id, position_name);
+            ("move_to_position", device_id, description)
+        }
+        PlanCommand::Read { device_id } => {
+            let description = format!("Read {}", device_id);
+            ("read", device_id, description)
+        }
+        PlanCommand::Trigger { device_id } => {
+            let description = format!("Trigger {}", device_id);
+            ("trigger", device_id, description)
+        }
+        PlanCommand::Wait { seconds } => ("wait", String::new(), format!("Wait {} s", seconds)),
+        PlanCommand::Checkpoint { label } => {
+            ("checkpoint", String::new(), format!("Checkpoint {}", label))
+        }
+        PlanCommand::EmitEvent { stream, .. } => (
+            "emit_event",
+            String::new(),
+            format!("Emit event on '{}'", stream),
+        ),
+        PlanCommand::Set {
+            device_id,
+            parameter,
+            value,
+        } => {
+            let description = format!("Set {}.{} = {}", device_id, parameter, value);
+            ("set", device_id, description)
+        }
+        PlanCommand::Ramp {
+            device_id,
+            parameter,
+            ramp,
+        } => {
+            let description = format!(
+                "Ramp {}.{} to {} ({})",
+                device_id, parameter, ramp.target, ramp.profile
+            );
+            ("ramp", device_id, description)
+        }
+    };
+
+    crate::grpc::proto::DryRunCommand {
+        index,
+        command_type: command_type.to_string(),
+        device_id,
+        description,
+        start_offset_s: step.start_offset_s,
+        duration_s: step.duration_s,
+    }
+}
+
+fn progress_doc_to_proto(
+    progress: &experiment::ProgressDoc,
+) -> crate::grpc::proto::ProgressDocument {
+    crate::grpc::proto::ProgressDocument {
+        run_uid: progress.run_uid.clone(),
+        points_completed: progress.points_completed,
+        points_total: progress.points_total,
+        positions: progress.positions.clone(),
+        mean_point_duration_s: progress.mean_point_duration_s,
+        eta_s: progress.eta_s,
+        elapsed_s: progress.elapsed_s,
+        time_ns: progress.time_ns,
+    }
+}
+
+fn run_summary_to_proto(summary: &RunSummary) -> crate::grpc::proto::RunSummary {
+    crate::grpc::proto::RunSummary {
+        run_uid: summary.run_uid.clone(),
+        plan_type: summary.plan_type.clone(),
+        plan_name: summary.plan_name.clone(),
+        exit_status: summary.exit_status.clone().unwrap_or_default(),
+        num_events: summary.num_events,
+        start_ns: summary.start_ns,
+        stop_ns: summary.stop_ns,
+        channels: summary.channels.keys().cloned().collect(),
+        dropped: drop_report_to_proto(&summary.dropped),
+        signing_key_id: summary
+            .provenance
+            .as_ref()
+            .map(|signed| signed.key_id.clone())
+            .unwrap_or_default(),
+        markers: summary
+            .markers
+            .iter()
+            .map(|marker| crate::grpc::proto::RunMarker {
+                time_ns: marker.time_ns,
+                text: marker.text.clone(),
+                author: marker.author.clone(),
+            })
+            .collect(),
+    }
+}
+
+fn channel_recording_to_proto(recording: &ChannelRecording) -> ChannelRecordingStatus {
+    ChannelRecordingStatus {
+        disabled_channels: recording.disabled_channels().map(str::to_string).collect(),
+        known_channels: recording.known_channels().map(str::to_string).collect(),
+        run_uid: recording.active_run().unwrap_or_default().to_string(),
+        changes: recording
+            .run_changes()
+            .iter()
+            .map(|change| ChannelRecordingChange {
+                time_ns: change.time_ns,
+                channel: change.channel.clone(),
+                enabled: change.enabled,
+                author: change.author.clone(),
+            })
+            .collect(),
+        decimation_every_nth: match recording.decimation() {
+            Decimation::EveryNth { n } => n,
+            _ => 0,
+        },
+        decimation_max_rate_hz: match recording.decimation() {
+            Decimation::Rate { hz, .. } => hz,
+            _ => 0.0,
+        },
+        events_decimated: recording.events_decimated(),
+    }
+}
+
+/// Combine the file check with the run catalog's copy of the signature
+fn verify_run_to_proto(
+    run_uid: &str,
+    path: &std::path::Path,
+    embedded: Option<&SignedManifest>,
+    catalog: Option<&SignedManifest>,
+    verification: Option<Verification>,
+) -> VerifyRunResponse {
+    // A file whose signature was stripped still has its catalog entry
+    let verification = verification.unwrap_or_else(|| Verification {
+        run_uid: run_uid.to_string(),
+        key_id: catalog.map(|c| c.key_id.clone()).unwrap_or_default(),
+        ..Default::default()
+    });
+    let mut problems = if embedded.is_some() {
+        verification.problems()
+    } else {
+        vec!["run file has no provenance signature".to_string()]
+    };
+    let catalog_matches = match (embedded, catalog) {
+        (Some(embedded), Some(catalog)) => embedded.signature == catalog.signature,
+        _ => false,
+    };
+    if embedded.is_some() && !catalog_matches {
+        problems.push(match catalog {
+            Some(_) => "signature differs from the run catalog".to_string(),
+            None => "run catalog has no signature for this run".to_string(),
+        });
+    }
+
+    VerifyRunResponse {
+        run_uid: run_uid.to_string(),
+        file_path: path.to_string_lossy().to_string(),
+        verified: verification.is_intact() && verification.key_trusted && catalog_matches,
+        signature_valid: verification.signature_valid,
+        key_trusted: verification.key_trusted,
+        catalog_matches,
+        key_id: verification.key_id,
+        signed_at_ns: verification.signed_at_ns,
+        modified: verification.modified,
+        missing: verification.missing,
+        unexpected: verification.unexpected,
+        problems,
+    }
+}
+
+/// Keys accepted by `ListRunsRequest.filter` (`meta.` is a prefix)
+const RUN_FILTER_KEYS: &[&str] = &[
+    "run_uid",
+    "plan_type",
+    "plan_name",
+    "exit_status",
+    "channel",
+    "meta.",
+];
+
+/// Values of a run summary for a filter key
+fn run_filter_values(run: &RunSummary, key: &str) -> Vec<String> {
+    match key {
+        "run_uid" => vec![run.run_uid.clone()],
+        "plan_type" => vec![run.plan_type.clone()],
+        "plan_name" => vec![run.plan_name.clone()],
+        "exit_status" => run.exit_status.iter().cloned().collect(),
+        "channel" => run.channels.keys().cloned().collect(),
+        _ => key
+            .strip_prefix("meta.")
+            .and_then(|meta_key| run.metadata.get(meta_key))
+            .into_iter()
+            .cloned()
+            .collect(),
+    }
+}
+
+/// Sort runs by a `ListRunsRequest.sort_by` key (history order if empty)
+fn sort_runs(runs: &mut [&RunSummary], sort_by: &str, descending: bool) -> Result<(), Status> {
+    match sort_by {
+        "" => return Ok(()),
+        "start" => runs.sort_by_key(|run| run.start_ns),
+        "stop" => runs.sort_by_key(|run| run.stop_ns),
+        "plan_type" => runs.sort_by(|a, b| a.plan_type.cmp(&b.plan_type)),
+        "plan_name" => runs.sort_by(|a, b| a.plan_name.cmp(&b.plan_name)),
+        "num_events" => runs.sort_by_key(|run| run.num_events),
+        other => {
+            return Err(Status::invalid_argument(format!(
+                "Unknown sort key '{}' (expected start, stop, plan_type, plan_name or num_events)",
+                other
+            )));
+        }
+    }
+    if descending {
+        runs.reverse();
+    }
+    Ok(())
+}
+
+fn run_diff_to_proto(diff: RunDiff) -> RunComparison {
+    fn fields(diffs: Vec<FieldDiff>) -> Vec<crate::grpc::proto::FieldDifference> {
+        diffs
+            .into_iter()
+            .map(|d| crate::grpc::proto::FieldDifference {
+                key: d.key,
+                baseline: d.baseline,
+                candidate: d.candidate,
+            })
+            .collect()
+    }
+    fn stats(stats: ChannelStats) -> crate::grpc::proto::ChannelStatistics {
+        crate::grpc::proto::ChannelStatistics {
+            count: stats.count,
+            mean: stats.mean,
+            std_dev: stats.std_dev(),
+            min: stats.min,
+            max: stats.max,
+        }
+    }
+    fn channel(channel: ChannelComparison) -> crate::grpc::proto::ChannelComparison {
+        crate::grpc::proto::ChannelComparison {
+            mean_shift_sigma: channel.mean_shift_sigma(),
+            baseline: channel.baseline.map(stats),
+            candidate: channel.candidate.map(stats),
+            channel: channel.channel,
+        }
+    }
+
+    RunComparison {
+        baseline: Some(run_summary_to_proto(&diff.baseline)),
+        candidate: Some(run_summary_to_proto(&diff.candidate)),
+        run_info: fields(diff.run_info),
+        plan_args: fields(diff.plan_args),
+        device_parameters: fields(diff.device_parameters),
+        configuration: fields(diff.configuration),
+        metadata: fields(diff.metadata),
+        channels: diff.channels.into_iter().map(channel).collect(),
+    }
+}
@@ -1,5 +1,11 @@
+#[cfg(feature = "scripting")]
+use crate::audit::{AuditEntry, AuditLog, default_audit_log_path};
+use crate::auth::{self, GrpcPath, Principal, TokenClaims};
 use crate::grpc::proto::run_engine_service_server::RunEngineServiceServer;
-use crate::grpc::proto::{DaemonInfoRequest, DaemonInfoResponse, SystemStatus};
+use crate::grpc::proto::{
+    DaemonInfoRequest, DaemonInfoResponse, ListStreamStatsRequest, ListStreamStatsResponse,
+    SystemStatus,
+};
 #[cfg(feature = "scripting")]
 use crate::grpc::proto::{
     ListExecutionsRequest, ListExecutionsResponse, ListScriptsRequest, ListScriptsResponse,
@@ -8,9 +14,14 @@ use crate::grpc::proto::{
     control_service_server::{ControlService, ControlServiceServer},
 };
 use crate::grpc::run_engine_service::RunEngineServiceImpl;
+use crate::grpc::stream_stats::{StreamStatsRegistry, TrackedStream};
 #[cfg(feature = "serial")]
 use crate::grpc::{PluginServiceImpl, PluginServiceServer};
 use common::core::Measurement;
+use common::data_record::DataRecord;
+#[cfg(feature = "scripting")]
+use common::decimation::{ClockDomain, Decimation, Decimator};
+use common::fault_injection::{FaultInjector, points};
 #[cfg(feature = "scripting")]
 use common::limits;
 #[cfg(feature = "scripting")]
@@ -19,10 +30,11 @@ use scripting::ScriptEngine; // Trait import
 #[cfg(feature = "scripting")]
 use experiment::RunEngine;
 use protocol::daq::{UploadRequest, UploadResponse};
+use protocol::local_socket::LocalSocket;
 #[cfg(feature = "scripting")]
 use scripting::RhaiEngine;
 #[cfg(feature = "scripting")]
-use scripting::ScriptPlanRunner;
+use scripting::{ScriptPlanRunner, ScriptRunConfig};
 use std::collections::HashMap;
 use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
@@ -38,6 +50,7 @@ use tokio::task::JoinHandle;
 use tonic::service::interceptor::interceptor;
 use tonic::transport::{Identity, Server, ServerTlsConfig};
 use tonic::{Request, Response, Status};
+use tower::util::MapRequestLayer;
 use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 use uuid::Uuid;
 
@@ -59,6 +72,8 @@ struct ScriptMetadata {
     name: String,
     upload_time: u64,
     metadata: HashMap<String, String>,
+    /// Authenticated user that uploaded the script
+    uploaded_by: String,
 }
 
 #[cfg(feature = "scripting")]
@@ -72,12 +87,77 @@ struct ExecutionState {
     error: Option<String>,
     progress_percent: u32,
     current_line: String,
+    /// Authenticated user that started the execution
+    started_by: String,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 #[serde(default)]
 struct GrpcConfigFile {
     grpc: GrpcSettings,
+    /// Document forwarders to external message queues
+    forwarders: Vec<crate::document_forwarder::ForwarderConfig>,
+    /// Webhook notifications of run and alarm events
+    webhooks: Vec<crate::webhooks::WebhookConfig>,
+    commissioning: CommissioningSettings,
+    storage: StorageSettings,
+    /// Memory budgets per subsystem (`[memory]`, see `common::memory`)
+    memory: common::memory::MemoryBudgetConfig,
+}
+
+/// Commissioning mode: record decimated data while aligning
+///
+/// ```toml
+/// [commissioning]
+/// decimation = { mode = "every_nth", n = 10 }
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct CommissioningSettings {
+    /// Events persisted to run files (live streams stay at full rate)
+    decimation: common::decimation::Decimation,
+}
+
+/// Storage writer settings
+///
+/// ```toml
+/// [storage.flush]
+/// adaptive = true
+/// interval_ms = 1000
+/// high_water = 0.5
+///
+/// [storage.spill]
+/// enabled = true
+/// memory_budget_mb = 64
+/// max_disk_mb = 4096
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct StorageSettings {
+    /// When the ring buffer HDF5 writer flushes
+    flush: storage::flush_policy::FlushPolicyConfig,
+    /// Spill measurements to disk instead of blocking the pipeline while the
+    /// HDF5 writer falls behind
+    spill: storage::spill::SpillConfig,
+}
+
+impl GrpcConfigFile {
+    fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        let config_path = PathBuf::from("config/config.v4.toml");
+        let mut figment = Figment::from(Serialized::defaults(GrpcConfigFile::default()))
+            .merge(Env::prefixed("RUSTDAQ_").split("__"));
+
+        if config_path.exists() {
+            figment = figment.merge(Toml::file(&config_path));
+        } else {
+            eprintln!(
+                "⚠️  gRPC config file not found at {} (using defaults/env overrides)",
+                config_path.display()
+            );
+        }
+
+        Ok(figment.extract()?)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -89,6 +169,9 @@ struct GrpcSettings {
     auth_token: Option<String>,
     allowed_origins: Vec<String>,
     bind_address: Option<IpAddr>,
+    /// Serve on this `unix://` or `pipe://` address instead of TCP, for a
+    /// GUI on the same machine
+    local_socket: Option<String>,
 }
 
 impl Default for GrpcSettings {
@@ -100,35 +183,14 @@ impl Default for GrpcSettings {
             auth_token: None,
             allowed_origins: Vec::new(),
             bind_address: Some(IpAddr::V4(std::net::Ipv4Addr::LOCALHOST)),
+            local_socket: None,
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct JwtClaims {
-    exp: Option<usize>,
-    iss: Option<String>,
-    aud: Option<String>,
-    sub: Option<String>,
-}
-
 impl GrpcSettings {
     fn load() -> Result<Self, Box<dyn std::error::Error>> {
-        let config_path = PathBuf::from("config/config.v4.toml");
-        let mut figment = Figment::from(Serialized::defaults(GrpcConfigFile::default()))
-            .merge(Env::prefixed("RUSTDAQ_").split("__"));
-
-        if config_path.exists() {
-            figment = figment.merge(Toml::file(&config_path));
-        } else {
-            eprintln!(
-                "⚠️  gRPC config file not found at {} (using defaults/env overrides)",
-                config_path.display()
-            );
-        }
-
-        let settings: GrpcConfigFile = figment.extract()?;
-        Ok(settings.grpc)
+        Ok(GrpcConfigFile::load()?.grpc)
     }
 
     fn auth_token(&self) -> Option<&str> {
@@ -144,6 +206,25 @@ impl GrpcSettings {
             .unwrap_or(IpAddr::V4(std::net::Ipv4Addr::LOCALHOST));
         SocketAddr::new(bind_ip, default_port)
     }
+
+    /// Local socket replacing the TCP listener, if configured
+    fn local_socket(&self) -> Result<Option<LocalSocket>, Box<dyn std::error::Error>> {
+        let Some(address) = self
+            .local_socket
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+        else {
+            return Ok(None);
+        };
+        LocalSocket::parse(address).map(Some).ok_or_else(|| {
+            format!(
+                "grpc.local_socket must be a unix:// or pipe:// address, got '{}'",
+                address
+            )
+            .into()
+        })
+    }
 }
 
 fn build_tls_config(
@@ -165,6 +246,18 @@ fn build_tls_config(
     Ok(Some(ServerTlsConfig::new().identity(identity)))
 }
 
+/// gRPC server reflection over every service in the protocol crate
+fn build_reflection_service() -> Result<
+    tonic_reflection::server::ServerReflectionServer<
+        impl tonic_reflection::server::ServerReflection,
+    >,
+    Box<dyn std::error::Error>,
+> {
+    Ok(tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(protocol::FILE_DESCRIPTOR_SET)
+        .build()?)
+}
+
 fn build_cors_layer(settings: &GrpcSettings) -> Result<CorsLayer, Box<dyn std::error::Error>> {
     let mut cors = CorsLayer::new().allow_headers(Any).allow_methods(Any);
 
@@ -184,9 +277,10 @@ fn build_cors_layer(settings: &GrpcSettings) -> Result<CorsLayer, Box<dyn std::e
     Ok(cors)
 }
 
-fn validate_auth(settings: &GrpcSettings, request: &Request<()>) -> Result<(), Status> {
+/// Authenticate a request, returning who made it
+fn validate_auth(settings: &GrpcSettings, request: &Request<()>) -> Result<Principal, Status> {
     if !settings.auth_enabled {
-        return Ok(());
+        return Ok(Principal::anonymous());
     }
 
     let expected = settings.auth_token().ok_or_else(|| {
@@ -210,17 +304,51 @@ fn validate_auth(settings: &GrpcSettings, request: &Request<()>) -> Result<(), S
     };
 
     if token == expected {
-        return Ok(());
+        return Ok(Principal::api_key());
     }
 
     let mut validation = Validation::new(Algorithm::HS256);
     validation.validate_exp = true;
     let decoding_key = DecodingKey::from_secret(expected.as_bytes());
-    decode::<JwtClaims>(token, &decoding_key, &validation)
-        .map(|_| ())
+    decode::<TokenClaims>(token, &decoding_key, &validation)
+        .map(|data| Principal::from_claims(&data.claims))
         .map_err(|_| Status::unauthenticated("invalid authentication token"))
 }
 
+/// Authenticate a request and attach its [`Principal`] for the services
+///
+/// Calls to methods that change state must also carry the scope
+/// [`auth::method_scope`] names for them.
+fn authenticate(settings: &GrpcSettings, mut request: Request<()>) -> Result<Request<()>, Status> {
+    let principal = validate_auth(settings, &request)?;
+    request.extensions_mut().insert(principal);
+    let scope = request
+        .extensions()
+        .get::<GrpcPath>()
+        .and_then(|path| auth::method_scope(&path.0));
+    if let Some(scope) = scope {
+        auth::require_scope(&request, scope, "")?;
+    }
+    Ok(request)
+}
+
+/// Sign a token with the configured `grpc.auth_token` for `user`, limited to
+/// `scopes` (none for full access)
+///
+/// Used to hand unattended clients, such as scheduled scripts, a credential
+/// that expires and can only do what the job needs.
+pub fn issue_auth_token(
+    user: &str,
+    scopes: &[String],
+    ttl: std::time::Duration,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let settings = GrpcSettings::load()?;
+    let secret = settings
+        .auth_token()
+        .ok_or("grpc.auth_token is not configured; tokens cannot be signed")?;
+    Ok(auth::issue_token(secret, user, scopes, ttl)?)
+}
+
 fn extract_bearer_token(header_value: &str) -> Option<&str> {
     let trimmed = header_value.trim();
     let mut parts = trimmed.splitn(2, ' ');
@@ -259,6 +387,9 @@ pub struct DaqServer {
     /// Scripts use ScriptPlanRunner which executes plans through this engine,
     /// ensuring all script operations emit Documents and coordinate with gRPC services.
     run_engine: Arc<RunEngine>,
+    #[cfg(feature = "scripting")]
+    /// Records who uploaded, started and stopped scripts
+    audit: Arc<AuditLog>,
     start_time: SystemTime,
 
     /// Broadcast channel for distributing hardware measurements to multiple consumers.
@@ -384,6 +515,8 @@ impl DaqServer {
             running_tasks: Arc::new(RwLock::new(HashMap::new())),
             #[cfg(feature = "scripting")]
             run_engine,
+            #[cfg(feature = "scripting")]
+            audit: Arc::new(AuditLog::new(default_audit_log_path())),
             start_time: SystemTime::now(),
             data_tx,
             #[cfg(feature = "storage_hdf5")]
@@ -411,6 +544,7 @@ impl DaqServer {
             executions: Arc::new(RwLock::new(HashMap::new())),
             running_tasks: Arc::new(RwLock::new(HashMap::new())),
             run_engine,
+            audit: Arc::new(AuditLog::new(default_audit_log_path())),
             start_time: SystemTime::now(),
             data_tx,
         })
@@ -452,6 +586,31 @@ impl DaqServer {
     pub fn data_sender(&self) -> Arc<broadcast::Sender<Measurement>> {
         Arc::clone(&self.data_tx)
     }
+
+    /// Record script actions in `audit` instead of the default audit log
+    #[cfg(feature = "scripting")]
+    pub fn with_audit_log(mut self, audit: Arc<AuditLog>) -> Self {
+        self.audit = audit;
+        self
+    }
+}
+
+/// Audit entry for a script action taken by `user`
+#[cfg(feature = "scripting")]
+fn script_audit_entry(action: &str, user: &str, peer: String, detail: String) -> AuditEntry {
+    let mut entry = AuditEntry::new(action, "");
+    entry.user = user.to_string();
+    entry.peer = peer;
+    entry.detail = detail;
+    entry
+}
+
+#[cfg(feature = "scripting")]
+fn request_peer<T>(request: &Request<T>) -> String {
+    request
+        .remote_addr()
+        .map(|addr| addr.to_string())
+        .unwrap_or_default()
 }
 
 fn encode_measurement_frame(measurement: &Measurement) -> Result<Vec<u8>, bincode::Error> {
@@ -462,6 +621,117 @@ fn encode_measurement_frame(measurement: &Measurement) -> Result<Vec<u8>, bincod
     Ok(frame)
 }
 
+/// Write measurements to the ring buffer through a [`SpillBuffer`]
+///
+/// Measurements are taken off `rx` as fast as they arrive, so the pipeline
+/// never blocks on storage. A frame is only written once the ring buffer has
+/// room for it without overwriting data the HDF5 writer hasn't consumed yet;
+/// until then frames wait in memory and, beyond the memory budget, on disk.
+///
+/// [`SpillBuffer`]: storage::spill::SpillBuffer
+fn spawn_spilling_ring_buffer_writer(
+    mut rx: tokio::sync::mpsc::Receiver<Measurement>,
+    rb: Arc<storage::ring_buffer::RingBuffer>,
+    config: storage::spill::SpillConfig,
+) {
+    use common::integrity::{DropLedger, DropStage};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use storage::spill::{Admission, SpillBuffer};
+
+    const FULL_POLL: std::time::Duration = std::time::Duration::from_millis(5);
+
+    let buffer = Arc::new(std::sync::Mutex::new(SpillBuffer::new(config)));
+    let ready = Arc::new(tokio::sync::Notify::new());
+    let closed = Arc::new(AtomicBool::new(false));
+
+    {
+        let buffer = buffer.clone();
+        let ready = ready.clone();
+        let closed = closed.clone();
+        tokio::spawn(async move {
+            let mut spilling = false;
+            while let Some(measurement) = rx.recv().await {
+                let Ok(frame) = encode_measurement_frame(&measurement) else {
+                    continue;
+                };
+                let admission = buffer.lock().unwrap_or_else(|p| p.into_inner()).push(frame);
+                match admission {
+                    Admission::Memory => spilling = false,
+                    Admission::Spilled if !spilling => {
+                        spilling = true;
+                        tracing::warn!("Storage writer behind, spilling measurements to disk");
+                    }
+                    Admission::Spilled => {}
+                    Admission::Dropped => {
+                        DropLedger::global().record("ring_buffer", DropStage::Storage, 1);
+                    }
+                }
+                ready.notify_one();
+            }
+            closed.store(true, Ordering::Release);
+            ready.notify_one();
+        });
+    }
+
+    tokio::spawn(async move {
+        let capacity = rb.capacity();
+        let mut had_spilled = false;
+        let mut seen_spilled = 0;
+        loop {
+            let (frame, spilled) = {
+                let mut buffer = buffer.lock().unwrap_or_else(|p| p.into_inner());
+                (buffer.pop(), buffer.stats().spilled)
+            };
+            let Some(frame) = frame else {
+                if had_spilled {
+                    had_spilled = false;
+                    tracing::info!("Spilled measurements drained to the ring buffer");
+                }
+                if closed.load(Ordering::Acquire) {
+                    break;
+                }
+                ready.notified().await;
+                continue;
+            };
+            had_spilled |= spilled > seen_spilled;
+            seen_spilled = spilled;
+
+            // Wait for the HDF5 writer to make room rather than overwrite
+            // unconsumed data
+            while rb.write_head().saturating_sub(rb.read_tail()) + frame.len() as u64 > capacity
+                && frame.len() as u64 <= capacity
+            {
+                tokio::time::sleep(FULL_POLL).await;
+            }
+            if let Err(e) = rb.write(&frame) {
+                tracing::error!(error = %e, "Failed to write measurement to ring buffer");
+            }
+            tokio::task::yield_now().await;
+        }
+    });
+}
+
+/// Map a subscriber's requested decimation onto the distribution-layer policy
+#[cfg(feature = "scripting")]
+fn decimation_from_proto(decimation: &crate::grpc::proto::StreamDecimation) -> Decimation {
+    if decimation.rate_hz > 0.0 {
+        Decimation::Rate {
+            hz: decimation.rate_hz,
+            clock: if decimation.align_to_wall_clock {
+                ClockDomain::WallClock
+            } else {
+                ClockDomain::Monotonic
+            },
+        }
+    } else if decimation.every_nth > 1 {
+        Decimation::EveryNth {
+            n: decimation.every_nth,
+        }
+    } else {
+        Decimation::All
+    }
+}
+
 #[cfg(feature = "scripting")]
 impl std::fmt::Debug for DaqServer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -538,8 +808,16 @@ impl ControlService for DaqServer {
         &self,
         request: Request<UploadRequest>,
     ) -> Result<Response<UploadResponse>, Status> {
+        let principal = auth::require_scope(&request, auth::SCOPE_SCRIPTS_UPLOAD, "")?;
+        let peer = request_peer(&request);
         let req = request.into_inner();
         let script_id = Uuid::new_v4().to_string();
+        let mut entry = script_audit_entry(
+            "script_upload",
+            &principal.user,
+            peer,
+            format!("script '{}' ({})", req.name, script_id),
+        );
 
         let script_size = req.script_content.len();
         if script_size > limits::MAX_SCRIPT_SIZE {
@@ -557,6 +835,9 @@ impl ControlService for DaqServer {
         // Validate script syntax
         let engine = self.script_engine.read().await;
         if let Err(e) = engine.validate_script(&req.script_content).await {
+            entry.outcome = "failed".to_string();
+            entry.message = e.to_string();
+            self.audit.record(&entry);
             return Ok(Response::new(UploadResponse {
                 script_id: String::new(),
                 success: false,
@@ -580,8 +861,11 @@ impl ControlService for DaqServer {
                     .unwrap_or_default()
                     .as_nanos() as u64,
                 metadata: req.metadata,
+                uploaded_by: principal.user,
             },
         );
+        entry.outcome = "ok".to_string();
+        self.audit.record(&entry);
 
         Ok(Response::new(UploadResponse {
             script_id,
@@ -595,14 +879,40 @@ impl ControlService for DaqServer {
         &self,
         request: Request<StartRequest>,
     ) -> Result<Response<StartResponse>, Status> {
+        let principal = auth::principal(&request);
+        let peer = request_peer(&request);
         let req = request.into_inner();
         let scripts = self.scripts.read().await;
 
         let script = scripts
             .get(&req.script_id)
             .ok_or_else(|| Status::not_found("Script not found"))?;
+        let script_name = self
+            .script_metadata
+            .read()
+            .await
+            .get(&req.script_id)
+            .map(|meta| meta.name.clone())
+            .unwrap_or_default();
 
         let execution_id = Uuid::new_v4().to_string();
+        let detail = format!(
+            "execution {} of script '{}' ({})",
+            execution_id, script_name, req.script_id
+        );
+        let mut entry = script_audit_entry("script_start", &principal.user, peer, detail.clone());
+
+        // Scheduled jobs get tokens limited to named scripts
+        if !principal.allows(auth::SCOPE_SCRIPTS_RUN, &script_name) {
+            let status = Status::permission_denied(format!(
+                "'{}' may not run script '{}'",
+                principal.user, script_name
+            ));
+            entry.outcome = "blocked".to_string();
+            entry.message = status.message().to_string();
+            self.audit.record(&entry);
+            return Err(status);
+        }
 
         // Record execution start
         self.executions.write().await.insert(
@@ -618,8 +928,11 @@ impl ControlService for DaqServer {
                 error: None,
                 progress_percent: 0,
                 current_line: String::new(),
+                started_by: principal.user.clone(),
             },
         );
+        entry.outcome = "ok".to_string();
+        self.audit.record(&entry);
 
         // Execute script via ScriptPlanRunner using shared RunEngine (bd-si2c)
         // This ensures all yielded plans emit Documents and coordinate with gRPC services
@@ -629,12 +942,37 @@ impl ControlService for DaqServer {
         let exec_id_clone = execution_id.clone();
         let running_tasks_clone = self.running_tasks.clone();
         let exec_id_for_cleanup = execution_id.clone();
+        let audit = self.audit.clone();
+        let user = principal.user;
+
+        // Runs queued by the script are attributed to whoever started it
+        let config = ScriptRunConfig {
+            run_metadata: HashMap::from([
+                ("operator".to_string(), user.clone()),
+                ("script_execution_id".to_string(), execution_id.clone()),
+            ]),
+            ..ScriptRunConfig::default()
+        };
 
         let handle = tokio::spawn(async move {
             // Create a ScriptPlanRunner with the shared RunEngine
-            let runner = ScriptPlanRunner::new(run_engine_clone);
+            let runner = ScriptPlanRunner::with_config(run_engine_clone, config);
             let result = runner.run(&script_clone).await;
 
+            let mut entry = script_audit_entry("script_finish", &user, String::new(), detail);
+            match &result {
+                Ok(report) if report.success => entry.outcome = "ok".to_string(),
+                Ok(report) => {
+                    entry.outcome = "failed".to_string();
+                    entry.message = report.error.clone().unwrap_or_default();
+                }
+                Err(e) => {
+                    entry.outcome = "failed".to_string();
+                    entry.message = e.to_string();
+                }
+            }
+            audit.record(&entry);
+
             // Update execution state with result
             let mut executions = executions_clone.write().await;
             if let Some(exec) = executions.get_mut(&exec_id_clone) {
@@ -687,7 +1025,15 @@ impl ControlService for DaqServer {
         &self,
         request: Request<StopRequest>,
     ) -> Result<Response<StopResponse>, Status> {
+        let principal = auth::principal(&request);
+        let peer = request_peer(&request);
         let req = request.into_inner();
+        let mut entry = script_audit_entry(
+            "script_stop",
+            &principal.user,
+            peer,
+            format!("execution {}", req.execution_id),
+        );
 
         // First check if execution exists and is running
         {
@@ -696,6 +1042,19 @@ impl ControlService for DaqServer {
                 .get(&req.execution_id)
                 .ok_or_else(|| Status::not_found("Execution not found"))?;
 
+            // Anyone may stop their own executions
+            if exec.started_by != principal.user && !principal.allows(auth::SCOPE_SCRIPTS_STOP, "")
+            {
+                let status = Status::permission_denied(format!(
+                    "'{}' may not stop executions started by '{}'",
+                    principal.user, exec.started_by
+                ));
+                entry.outcome = "blocked".to_string();
+                entry.message = status.message().to_string();
+                self.audit.record(&entry);
+                return Err(status);
+            }
+
             if exec.state != "RUNNING" {
                 return Ok(Response::new(StopResponse {
                     stopped: false,
@@ -730,6 +1089,9 @@ impl ControlService for DaqServer {
                     .as_nanos() as u64,
             );
         }
+        entry.outcome = "ok".to_string();
+        entry.message = msg.to_string();
+        self.audit.record(&entry);
 
         Ok(Response::new(StopResponse {
             stopped: true,
@@ -758,6 +1120,7 @@ impl ControlService for DaqServer {
             script_id: exec.script_id.clone(),
             progress_percent: exec.progress_percent,
             current_line: exec.current_line.clone(),
+            started_by: exec.started_by.clone(),
         }))
     }
 
@@ -822,14 +1185,21 @@ impl ControlService for DaqServer {
         )))
     }
 
-    type StreamMeasurementsStream =
-        tokio_stream::wrappers::ReceiverStream<Result<crate::grpc::proto::DataPoint, Status>>;
+    type StreamMeasurementsStream = TrackedStream<
+        tokio_stream::wrappers::ReceiverStream<Result<crate::grpc::proto::DataPoint, Status>>,
+    >;
 
     /// Stream measurement data from specified channels
     async fn stream_measurements(
         &self,
         request: Request<crate::grpc::proto::MeasurementRequest>,
     ) -> Result<Response<Self::StreamMeasurementsStream>, Status> {
+        let stats = StreamStatsRegistry::global().open(
+            "ControlService/StreamMeasurements",
+            request.get_ref().channels.join(","),
+            &request,
+        );
+        let stream_entry = stats.entry();
         let req = request.into_inner();
         let (tx, rx) = tokio::sync::mpsc::channel(100);
 
@@ -837,11 +1207,15 @@ impl ControlService for DaqServer {
         let mut data_rx = self.data_tx.subscribe();
         let channels = req.channels;
         let max_rate_hz = req.max_rate_hz;
+        let decimation = req.decimation.as_ref().map(decimation_from_proto);
 
         // Spawn background task to forward hardware measurements to gRPC client
         tokio::spawn(async move {
+            // Per-channel decimation state; samples are dropped before queueing
+            let mut decimators: HashMap<String, Decimator> = HashMap::new();
+
             // Setup rate limiting if specified (applied to SEND side, not receive)
-            let mut rate_limiter = if max_rate_hz > 0 {
+            let mut rate_limiter = if max_rate_hz > 0 && decimation.is_none() {
                 Some(tokio::time::interval(std::time::Duration::from_secs_f64(
                     1.0 / max_rate_hz as f64,
                 )))
@@ -861,6 +1235,7 @@ impl ControlService for DaqServer {
                     Err(broadcast::error::RecvError::Lagged(skipped)) => {
                         // Throttle lag warnings to once per second max (bd-jnfu.15)
                         total_skipped += skipped;
+                        stream_entry.record_dropped(skipped);
                         if last_lag_warning.elapsed() > std::time::Duration::from_secs(1) {
                             tracing::debug!(
                                 skipped = total_skipped,
@@ -881,63 +1256,33 @@ impl ControlService for DaqServer {
                     limiter.tick().await;
                 }
 
-                // Extract channel and value from Measurement for filtering and conversion
-                let (name, value, timestamp_ns) = match &data_point {
-                    Measurement::Scalar {
-                        name,
-                        value,
-                        timestamp,
-                        ..
-                    } => {
-                        let ts_ns = timestamp.timestamp_nanos_opt().unwrap_or(0) as u64;
-                        (name.clone(), *value, ts_ns)
-                    }
-                    Measurement::Vector {
-                        name,
-                        values,
-                        timestamp,
-                        ..
-                    } => {
-                        let ts_ns = timestamp.timestamp_nanos_opt().unwrap_or(0) as u64;
-                        // For vectors, we can emit the length or first value
-                        (format!("{}_len", name), values.len() as f64, ts_ns)
-                    }
-                    Measurement::Image {
-                        name,
-                        width,
-                        height,
-                        timestamp,
-                        ..
-                    } => {
-                        let ts_ns = timestamp.timestamp_nanos_opt().unwrap_or(0) as u64;
-                        (name.clone(), (width * height) as f64, ts_ns)
-                    }
-                    Measurement::Spectrum {
-                        name,
-                        amplitudes,
-                        timestamp,
-                        ..
-                    } => {
-                        let ts_ns = timestamp.timestamp_nanos_opt().unwrap_or(0) as u64;
-                        (format!("{}_spectrum", name), amplitudes.len() as f64, ts_ns)
+                // Canonical record of the measurement (arrays are summarised)
+                let record = DataRecord::from_measurement("", &data_point);
+                for channel in record.values.keys() {
+                    // Filter by channel if specified
+                    if !channels.is_empty() && !channels.contains(channel) {
+                        continue;
                     }
-                };
 
-                // Filter by channel if specified
-                if !channels.is_empty() && !channels.contains(&name) {
-                    continue;
-                }
+                    if let Some(decimation) = decimation {
+                        let decimator = decimators
+                            .entry(channel.clone())
+                            .or_insert_with(|| Decimator::new(decimation));
+                        if !decimator.admit_sample(record.timestamp_ns) {
+                            continue;
+                        }
+                    }
 
-                // Convert to proto DataPoint
-                let proto_data_point = crate::grpc::proto::DataPoint {
-                    channel: name,
-                    value,
-                    timestamp_ns,
-                };
+                    let Some(proto_data_point) =
+                        crate::grpc::proto::DataPoint::from_record(&record, channel)
+                    else {
+                        continue;
+                    };
 
-                // Forward to gRPC client
-                if tx.send(Ok(proto_data_point)).await.is_err() {
-                    break; // Client disconnected
+                    // Forward to gRPC client
+                    if tx.send(Ok(proto_data_point)).await.is_err() {
+                        return; // Client disconnected
+                    }
                 }
 
                 // Yield to allow other tasks to run
@@ -945,9 +1290,9 @@ impl ControlService for DaqServer {
             }
         });
 
-        Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(
-            rx,
-        )))
+        Ok(Response::new(
+            stats.track(tokio_stream::wrappers::ReceiverStream::new(rx)),
+        ))
     }
 
     /// List all uploaded scripts
@@ -964,6 +1309,7 @@ impl ControlService for DaqServer {
                 name: meta.name.clone(),
                 upload_time_ns: meta.upload_time,
                 metadata: meta.metadata.clone(),
+                uploaded_by: meta.uploaded_by.clone(),
             })
             .collect();
 
@@ -1006,6 +1352,7 @@ impl ControlService for DaqServer {
                 script_id: exec.script_id.clone(),
                 progress_percent: exec.progress_percent,
                 current_line: exec.current_line.clone(),
+                started_by: exec.started_by.clone(),
             })
             .collect();
 
@@ -1039,6 +1386,19 @@ impl ControlService for DaqServer {
             uptime_seconds: uptime,
         }))
     }
+
+    /// Statistics of every open server-streaming RPC, busiest first
+    async fn list_stream_stats(
+        &self,
+        _request: Request<ListStreamStatsRequest>,
+    ) -> Result<Response<ListStreamStatsResponse>, Status> {
+        let streams = StreamStatsRegistry::global().snapshot();
+        Ok(Response::new(ListStreamStatsResponse {
+            total_messages_per_sec: streams.iter().map(|s| s.messages_per_sec).sum(),
+            total_bytes_per_sec: streams.iter().map(|s| s.bytes_per_sec).sum(),
+            streams,
+        }))
+    }
 }
 
 /// Start the DAQ gRPC server
@@ -1056,7 +1416,8 @@ pub async fn start_server(addr: std::net::SocketAddr) -> Result<(), Box<dyn std:
     }
 
     let bind_addr = grpc_settings.bind_socket(addr.port());
-    if bind_addr.ip() != addr.ip() {
+    let local_socket = grpc_settings.local_socket()?;
+    if local_socket.is_none() && bind_addr.ip() != addr.ip() {
         eprintln!(
             "⚠️  Overriding gRPC bind address {} -> {} (set grpc.bind_address to change)",
             addr.ip(),
@@ -1082,7 +1443,10 @@ pub async fn start_server(addr: std::net::SocketAddr) -> Result<(), Box<dyn std:
     health_service.set_serving_status("daq.ControlService", ServingStatus::Serving);
     health_service.set_serving_status("daq.RunEngineService", ServingStatus::Serving);
 
-    println!("DAQ gRPC server listening on {}", bind_addr);
+    match &local_socket {
+        Some(socket) => println!("DAQ gRPC server listening on {}", socket),
+        None => println!("DAQ gRPC server listening on {}", bind_addr),
+    }
 
     if !grpc_settings.auth_enabled {
         eprintln!("⚠️  gRPC auth is disabled (set grpc.auth_enabled=true to require auth)");
@@ -1100,9 +1464,9 @@ pub async fn start_server(addr: std::net::SocketAddr) -> Result<(), Box<dyn std:
     let mut builder = Server::builder()
         .accept_http1(true)
         .layer(cors)
+        .layer(MapRequestLayer::new(auth::record_path))
         .layer(interceptor(move |request: Request<()>| {
-            validate_auth(&auth_settings, &request)?;
-            Ok(request)
+            authenticate(&auth_settings, request)
         }));
 
     if let Some(tls_config) = tls_config {
@@ -1112,11 +1476,18 @@ pub async fn start_server(addr: std::net::SocketAddr) -> Result<(), Box<dyn std:
     #[cfg(feature = "scripting")]
     let builder = builder.add_service(tonic_web::enable(ControlServiceServer::new(server)));
 
-    builder
+    let router = builder
         .add_service(tonic_web::enable(HealthServer::new(health_service)))
         .add_service(tonic_web::enable(RunEngineServiceServer::new(run_engine)))
-        .serve(bind_addr)
-        .await?;
+        .add_service(build_reflection_service()?);
+    match local_socket {
+        Some(socket) => {
+            router
+                .serve_with_incoming(protocol::local_socket::incoming(&socket)?)
+                .await?
+        }
+        None => router.serve(bind_addr).await?,
+    }
 
     Ok(())
 }
@@ -1125,6 +1496,44 @@ use common::pipeline::{MeasurementSink, Tee};
 
 // ... (existing imports)
 
+/// Daemon startup options for [`start_server_with_options`]
+#[derive(Debug, Clone)]
+pub struct ServerOptions {
+    /// Recreate module instances saved by the previous run
+    pub restore_modules: bool,
+    /// File module instances are persisted to
+    pub module_state_path: std::path::PathBuf,
+    /// How long device parameter and setpoint history is kept
+    pub history_retention: std::time::Duration,
+    /// How long the on-disk history of scalar channels is kept (zero disables it)
+    pub channel_history_retention: std::time::Duration,
+    /// Sign completed run files with the key in this file (created if missing)
+    pub run_signing_key: Option<std::path::PathBuf>,
+    /// Directory of the script, plan and device config library
+    pub library_dir: std::path::PathBuf,
+    /// Module types provided by plugins (e.g. WASM components)
+    #[cfg(feature = "modules")]
+    pub plugin_module_types: Vec<crate::modules::PluginModuleType>,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            restore_modules: true,
+            #[cfg(feature = "modules")]
+            module_state_path: crate::modules::persistence::default_module_state_path(),
+            #[cfg(not(feature = "modules"))]
+            module_state_path: std::path::PathBuf::new(),
+            history_retention: crate::device_history::DEFAULT_HISTORY_RETENTION,
+            channel_history_retention: storage::channel_history::DEFAULT_CHANNEL_HISTORY_RETENTION,
+            run_signing_key: None,
+            library_dir: crate::grpc::library_service::default_library_path(),
+            #[cfg(feature = "modules")]
+            plugin_module_types: Vec::new(),
+        }
+    }
+}
+
 /// Start the DAQ gRPC server with hardware control (bd-4x6q)
 ///
 /// Provides HardwareService for direct device control and optionally ControlService
@@ -1149,35 +1558,100 @@ pub async fn start_server_with_hardware(
     addr: std::net::SocketAddr,
     registry: std::sync::Arc<hardware::registry::DeviceRegistry>,
     health_monitor: std::sync::Arc<common::health::SystemHealthMonitor>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    start_server_with_options(addr, registry, health_monitor, ServerOptions::default()).await
+}
+
+/// Start the DAQ gRPC server with hardware control and explicit startup options
+///
+/// Same as [`start_server_with_hardware`], but lets the caller control module
+/// persistence (e.g. the daemon's `--no-restore` flag).
+pub async fn start_server_with_options(
+    addr: std::net::SocketAddr,
+    registry: std::sync::Arc<hardware::registry::DeviceRegistry>,
+    health_monitor: std::sync::Arc<common::health::SystemHealthMonitor>,
+    options: ServerOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let run_engine = std::sync::Arc::new(experiment::RunEngine::new(registry.clone()));
+    start_server_with_shutdown(
+        addr,
+        registry,
+        run_engine,
+        health_monitor,
+        options,
+        std::future::pending(),
+    )
+    .await
+}
+
+/// Start the DAQ gRPC server around an existing RunEngine until `shutdown` resolves
+///
+/// Used when embedding the daemon ([`crate::runtime::DaqRuntime`]): the host
+/// application drives the same RunEngine the gRPC services expose, and stops
+/// the server without tearing down its own runtime.
+pub async fn start_server_with_shutdown(
+    addr: std::net::SocketAddr,
+    registry: std::sync::Arc<hardware::registry::DeviceRegistry>,
+    run_engine: std::sync::Arc<experiment::RunEngine>,
+    health_monitor: std::sync::Arc<common::health::SystemHealthMonitor>,
+    options: ServerOptions,
+    shutdown: impl std::future::Future<Output = ()>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use crate::grpc::hardware_service::HardwareServiceImpl;
     use crate::grpc::module_service::ModuleServiceImpl;
     use crate::grpc::ni_daq_service::NiDaqServiceImpl;
+    use common::supervisor::{RestartPolicy, supervise};
     use storage::hdf5_writer::HDF5Writer;
     use storage::ring_buffer::RingBuffer;
     // use crate::grpc::plugin_service::PluginServiceImpl; // Unused
+    use crate::audit::{AuditLog, default_audit_log_path};
+    use crate::grpc::console_service::ConsoleServiceImpl;
+    use crate::grpc::library_service::{FileLibrary, LibraryServiceImpl};
+    use crate::grpc::log_service::{LogBroadcaster, LogServiceImpl};
     use crate::grpc::preset_service::{PresetServiceImpl, default_preset_storage_path};
     use crate::grpc::proto::hardware_service_server::HardwareServiceServer;
     use crate::grpc::proto::health::health_check_response::ServingStatus;
     use crate::grpc::proto::health::health_server::HealthServer;
     use crate::grpc::proto::health_service_server::HealthServiceServer; // Custom HealthService
+    use crate::grpc::proto::instrument_console_service_server::InstrumentConsoleServiceServer;
+    use crate::grpc::proto::library_service_server::LibraryServiceServer;
+    use crate::grpc::proto::log_service_server::LogServiceServer;
     use crate::grpc::proto::module_service_server::ModuleServiceServer;
     use protocol::ni_daq::ni_daq_service_server::NiDaqServiceServer;
     // use crate::grpc::proto::plugin_service_server::PluginServiceServer; // Unused
+    #[cfg(feature = "modules")]
+    use crate::config_apply::DaemonTarget;
+    #[cfg(feature = "modules")]
+    use crate::grpc::config_service::ConfigServiceImpl;
+    #[cfg(feature = "modules")]
+    use crate::grpc::proto::config_service_server::ConfigServiceServer;
     use crate::grpc::proto::preset_service_server::PresetServiceServer;
     use crate::grpc::proto::scan_service_server::ScanServiceServer;
+    use crate::grpc::proto::session_service_server::SessionServiceServer;
     use crate::grpc::proto::storage_service_server::StorageServiceServer;
     #[allow(deprecated)] // ScanService kept for backwards compatibility until v0.8.0
     use crate::grpc::scan_service::ScanServiceImpl;
-    use crate::grpc::storage_service::StorageServiceImpl;
-
-    let grpc_settings = GrpcSettings::load()?;
+    use crate::grpc::session_service::{SessionManager, SessionServiceImpl};
+    use crate::grpc::storage_service::{StorageServiceImpl, spawn_channel_history_recorder};
+    use crate::preferences::{PreferenceStore, default_preferences_path};
+
+    let GrpcConfigFile {
+        grpc: grpc_settings,
+        forwarders,
+        webhooks,
+        commissioning,
+        storage: storage_settings,
+        memory,
+    } = GrpcConfigFile::load()?;
     if grpc_settings.auth_enabled && grpc_settings.auth_token().is_none() {
         return Err("grpc.auth_enabled is true but grpc.auth_token is not configured".into());
     }
+    memory.validate().map_err(|e| e.to_string())?;
+    common::memory::MemoryLedger::global().set_budgets(memory.clone());
 
     let bind_addr = grpc_settings.bind_socket(addr.port());
-    if bind_addr.ip() != addr.ip() {
+    let local_socket = grpc_settings.local_socket()?;
+    if local_socket.is_none() && bind_addr.ip() != addr.ip() {
         eprintln!(
             "⚠️  Overriding gRPC bind address {} -> {} (set grpc.bind_address to change)",
             addr.ip(),
@@ -1210,6 +1684,7 @@ pub async fn start_server_with_hardware(
 
     // Spawn HDF5Writer background task if ring buffer is available
     // This is the "Business in the Back" of The Mullet Strategy
+    let mut _writer_task = None;
     if let Some(ref rb) = ring_buffer {
         let hdf5_output_path = if cfg!(target_os = "linux") {
             Path::new("/tmp/rust_daq_scan_data.h5")
@@ -1218,14 +1693,30 @@ pub async fn start_server_with_hardware(
         };
 
         match HDF5Writer::new(hdf5_output_path, rb.clone()) {
-            Ok(writer) => {
+            Ok(mut writer) => {
+                let flush = storage_settings.flush;
+                writer.set_flush_policy(flush);
                 println!(
-                    "  - HDF5Writer: {} (1 Hz flush)",
-                    hdf5_output_path.display()
+                    "  - HDF5Writer: {} ({} ms flush{})",
+                    hdf5_output_path.display(),
+                    flush.interval_ms,
+                    if flush.adaptive {
+                        ", adaptive to ring buffer fill"
+                    } else {
+                        ""
+                    }
                 );
-                tokio::spawn(async move {
-                    writer.run().await;
-                });
+                // The writer keeps its read position across restarts, so a
+                // restarted writer resumes after the last flushed record
+                let writer = Arc::new(writer);
+                _writer_task = Some(AbortOnDrop(tokio::spawn(async move {
+                    let policy = RestartPolicy::default();
+                    if let Err(e) =
+                        supervise("storage_writer", &policy, || writer.clone().run_shared()).await
+                    {
+                        eprintln!("HDF5 writer stopped: {:#}", e);
+                    }
+                })));
             }
             Err(e) => {
                 eprintln!(
@@ -1236,8 +1727,7 @@ pub async fn start_server_with_hardware(
         }
     }
 
-    // Create shared RunEngine FIRST - used by both RunEngineService and ControlService/scripts (bd-si2c)
-    let run_engine = std::sync::Arc::new(experiment::RunEngine::new(registry.clone()));
+    // The shared RunEngine is used by both RunEngineService and ControlService/scripts (bd-si2c)
 
     // Initialize control server WITHOUT internal RingBuffer logic (we wire it manually)
     // Pass shared run_engine for script execution (bd-si2c)
@@ -1250,8 +1740,26 @@ pub async fn start_server_with_hardware(
     #[cfg(all(not(feature = "storage_hdf5"), not(feature = "scripting")))]
     let control_server = DaqServer::new()?;
 
+    // Console commands and script actions share one audit log
+    let audit_log = std::sync::Arc::new(AuditLog::new(default_audit_log_path()));
+    #[cfg(feature = "scripting")]
+    let control_server = control_server.with_audit_log(audit_log.clone());
+
     // Setup Reliable Sink (RingBuffer Writer)
-    let reliable_sink_tx = if let Some(ref rb) = ring_buffer {
+    let reliable_sink_tx = if let Some(ref rb) = ring_buffer
+        && storage_settings.spill.enabled
+        && _writer_task.is_some()
+    {
+        let (tx, rx) = tokio::sync::mpsc::channel::<Measurement>(512);
+        println!(
+            "  - Storage spill: {} MB in memory, up to {} MB in {}",
+            storage_settings.spill.memory_budget_mb,
+            storage_settings.spill.max_disk_mb,
+            storage_settings.spill.base_directory().display()
+        );
+        spawn_spilling_ring_buffer_writer(rx, rb.clone(), storage_settings.spill.clone());
+        Some(tx)
+    } else if let Some(ref rb) = ring_buffer {
         let (tx, mut rx) = tokio::sync::mpsc::channel::<Measurement>(512);
         let rb_clone = rb.clone();
 
@@ -1314,9 +1822,70 @@ pub async fn start_server_with_hardware(
             let (meas_tx, meas_rx) = tokio::sync::mpsc::channel(meas_chan);
             let device_id_clone = device_id.clone();
 
+            // Optional dark/flat correction, binning and histogram (GPU or CPU)
+            let mut preprocessor = registry
+                .preprocessing_for(&device_id)
+                .filter(|config| config.is_active())
+                .and_then(
+                    |config| match common::preprocessing::Preprocessor::new(&config) {
+                        Ok(preprocessor) => {
+                            println!(
+                                "    Preprocessing frames on {} backend",
+                                preprocessor.backend_name()
+                            );
+                            Some(preprocessor)
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to set up preprocessing for {}: {}", device_id, e);
+                            None
+                        }
+                    },
+                );
+
             // 4. Spawn Converter Task (Frame -> Measurement)
             tokio::spawn(async move {
-                while let Some(frame) = frame_rx.recv().await {
+                while let Some(mut frame) = frame_rx.recv().await {
+                    if let Some(mut active) = preprocessor.take() {
+                        let raw = frame.clone();
+                        let result = tokio::task::spawn_blocking(move || {
+                            let result = active.process(&raw);
+                            (active, result)
+                        })
+                        .await;
+                        match result {
+                            Ok((active, Ok(processed))) => {
+                                preprocessor = Some(active);
+                                frame = std::sync::Arc::new(processed.frame);
+                                if let Some(histogram) = processed.histogram {
+                                    let histogram = common::core::Measurement::Vector {
+                                        name: format!("{}.histogram", device_id_clone),
+                                        values: histogram.iter().map(|&c| c as f64).collect(),
+                                        unit: "counts".to_string(),
+                                        timestamp: chrono::Utc::now(),
+                                    };
+                                    if meas_tx.send(histogram).await.is_err() {
+                                        break;
+                                    }
+                                }
+                            }
+                            Ok((active, Err(e))) => {
+                                tracing::warn!(
+                                    device = %device_id_clone,
+                                    error = %e,
+                                    "Frame preprocessing failed, forwarding raw frame"
+                                );
+                                preprocessor = Some(active);
+                            }
+                            Err(e) => {
+                                tracing::error!(
+                                    device = %device_id_clone,
+                                    error = %e,
+                                    "Preprocessing task panicked, disabling preprocessing"
+                                );
+                            }
+                        }
+                    }
+
                     let buffer = match frame.bit_depth {
                         16 => {
                             if let Some(slice) = frame.as_u16_slice() {
@@ -1414,10 +1983,76 @@ pub async fn start_server_with_hardware(
     }
 
     // RunEngine was already created above (bd-si2c) - shared between RunEngineService and scripts
-    let run_engine_server = RunEngineServiceImpl::new(run_engine.clone());
+    let run_signer = match &options.run_signing_key {
+        Some(path) => Some(std::sync::Arc::new(
+            common::provenance::RunSigner::from_key_file(path)?,
+        )),
+        None => None,
+    };
+    let run_engine_server = RunEngineServiceImpl::with_signer(run_engine.clone(), run_signer);
+    if commissioning.decimation != common::decimation::Decimation::All {
+        run_engine_server.set_storage_decimation(commissioning.decimation);
+        println!(
+            "  - Commissioning mode: storage decimation {:?}",
+            commissioning.decimation
+        );
+    }
+
+    // Runs published to the facility's message queues as they happen
+    for config in forwarders {
+        let name = config.name.clone();
+        crate::document_forwarder::spawn_document_forwarder(config, run_engine.clone())
+            .map_err(|e| format!("Forwarder '{}': {:#}", name, e))?;
+        println!("  - Document forwarder: {}", name);
+    }
+
+    // Run and alarm notifications for lab chat channels and LIMS
+    for config in webhooks {
+        let name = config.name.clone();
+        crate::webhooks::spawn_webhook(config, run_engine.clone(), &health_monitor)
+            .map_err(|e| format!("Webhook '{}': {:#}", name, e))?;
+        println!("  - Webhook: {}", name);
+    }
 
-    let hardware_server = HardwareServiceImpl::new(registry.clone());
+    // Standard gRPC Health Check (grpc.health.v1)
+    let standard_health_service = crate::grpc::health_service::HealthServiceImpl::new();
+
+    let warmups = registry.warmups();
+    let hardware_server = if warmups.is_empty() {
+        HardwareServiceImpl::new(registry.clone())
+    } else {
+        let scheduler = Arc::new(hardware::warmup::WarmupScheduler::new(
+            registry.clone(),
+            registry.recipes(),
+            warmups,
+        ));
+        scheduler.spawn();
+        println!(
+            "  - Warm-up scheduler: {} schedule(s)",
+            scheduler.schedules().len()
+        );
+        HardwareServiceImpl::new(registry.clone()).with_warmups(scheduler)
+    }
+    .with_history_retention(options.history_retention)
+    .with_readiness(standard_health_service.clone());
     let module_server = ModuleServiceImpl::new(registry.clone());
+    #[cfg(feature = "modules")]
+    let module_server = {
+        let module_server = module_server.with_persistence(options.module_state_path.clone());
+        module_server
+            .register_plugin_types(options.plugin_module_types.clone())
+            .await;
+        if options.restore_modules {
+            match module_server.restore_modules().await {
+                Ok(report) if report.restored > 0 => {
+                    println!("  - Restored {} module instance(s)", report.restored)
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("Warning: Failed to restore modules: {}", e),
+            }
+        }
+        module_server
+    };
     let ni_daq_server = NiDaqServiceImpl::new(registry.clone());
 
     // Create PluginService with shared factory and registry (bd-0451)
@@ -1437,11 +2072,120 @@ pub async fn start_server_with_hardware(
         ScanServiceImpl::new(registry.clone())
     };
 
+    // Run files follow the storage chunking settings
+    let storage_server = StorageServiceImpl::new(ring_buffer.clone())
+        .with_document_writer(run_engine_server.document_writer());
+
+    // Last hours of every scalar channel on disk, run or no run, across restarts
+    let storage_server = if options.channel_history_retention.is_zero() {
+        storage_server
+    } else {
+        let config = storage::ChannelHistoryConfig {
+            retention: options.channel_history_retention,
+            ..Default::default()
+        };
+        let history_dir = run_engine_server.data_directory().join("channel_history");
+        match storage::ChannelHistory::open(&history_dir, config) {
+            Ok(history) => {
+                let history = Arc::new(history);
+                spawn_channel_history_recorder(
+                    history.clone(),
+                    control_server.data_sender().subscribe(),
+                );
+                storage_server.with_channel_history(history)
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "Channel history disabled");
+                storage_server
+            }
+        }
+    };
+
+    // Differential config apply with rollback across devices, modules and storage
+    // Runs record the configuration in effect, which can be restored from there
+    #[cfg(feature = "modules")]
+    let config_server = {
+        let target = std::sync::Arc::new(
+            DaemonTarget::new(
+                registry.clone(),
+                module_server.clone(),
+                storage_server.settings(),
+            )
+            .with_document_writer(run_engine_server.document_writer())
+            .with_run_engine(run_engine.clone()),
+        );
+        run_engine.set_config_snapshot_source(target.clone());
+        ConfigServiceImpl::new(target).with_snapshots(
+            run_engine_server.data_directory().to_path_buf(),
+            registry.clone(),
+        )
+    };
+
+    // Raw instrument console, refused for devices in use by a run and audited
+    let console_server = ConsoleServiceImpl::new(registry.clone(), run_engine.clone(), audit_log);
+
     let preset_server = PresetServiceImpl::new(registry, default_preset_storage_path());
-    let storage_server = StorageServiceImpl::new(ring_buffer.clone());
 
-    // Standard gRPC Health Check (grpc.health.v1)
-    let standard_health_service = crate::grpc::health_service::HealthServiceImpl::new();
+    // Multi-user presence: connected clients, roles and advisory device locks,
+    // plus client preferences that follow each user between machines
+    let session_manager = std::sync::Arc::new(SessionManager::default().with_preferences(
+        std::sync::Arc::new(PreferenceStore::new(Some(default_preferences_path()))),
+    ));
+    let _session_reaper = session_manager.spawn_reaper();
+    let session_server = SessionServiceImpl::new(session_manager);
+
+    // Remote log streaming; records come from the layer installed by the daemon binary
+    let log_server = LogServiceImpl::new(LogBroadcaster::global());
+
+    // Versioned scripts, plans and device configs managed by remote clients
+    let library_server = LibraryServiceImpl::new(FileLibrary::new(options.library_dir.clone()));
+
+    // Devices named in health monitor errors are marked degraded or faulted
+    let mut health_errors = health_monitor.subscribe_errors();
+    let status_registry = registry.clone();
+    tokio::spawn(async move {
+        loop {
+            match health_errors.recv().await {
+                Ok(error) => {
+                    status_registry.apply_health_error(&error);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    // Output limit violations flagged `alarm` become health warnings, which
+    // mark the device degraded and reach clients as alarms
+    let mut limit_violations = registry.subscribe_limit_violations();
+    let alarm_monitor = health_monitor.clone();
+    tokio::spawn(async move {
+        loop {
+            match limit_violations.recv().await {
+                Ok(violation) if violation.limit.alarm => {
+                    alarm_monitor
+                        .report_error(
+                            "output_limits",
+                            common::health::ErrorSeverity::Warning,
+                            violation.to_string(),
+                            [
+                                ("device_id", violation.device_id.clone()),
+                                ("parameter", violation.parameter.clone()),
+                            ],
+                        )
+                        .await;
+                }
+                Ok(_) => {}
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    // Subsystems over their memory budget become health warnings and alarms
+    tokio::spawn(
+        crate::health::memory_monitor::MemoryWatchdog::new(health_monitor.clone(), &memory).run(),
+    );
 
     // Custom System Health Monitoring    // Custom health service with monitoring
     let custom_health_service =
@@ -1455,12 +2199,41 @@ pub async fn start_server_with_hardware(
     standard_health_service.set_serving_status("daq.ScanService", ServingStatus::Serving);
     standard_health_service.set_serving_status("daq.PresetService", ServingStatus::Serving);
     standard_health_service.set_serving_status("daq.StorageService", ServingStatus::Serving);
+    standard_health_service.set_serving_status("daq.SessionService", ServingStatus::Serving);
+    standard_health_service.set_serving_status("daq.LogService", ServingStatus::Serving);
+    standard_health_service
+        .set_serving_status("daq.InstrumentConsoleService", ServingStatus::Serving);
+    standard_health_service.set_serving_status("daq.LibraryService", ServingStatus::Serving);
     standard_health_service.set_serving_status("daq.RunEngineService", ServingStatus::Serving);
     standard_health_service.set_serving_status("daq.HealthService", ServingStatus::Serving); // Register custom service too
     #[cfg(feature = "serial")]
     standard_health_service.set_serving_status("daq.PluginService", ServingStatus::Serving);
 
-    println!("DAQ gRPC server (with hardware) listening on {}", bind_addr);
+    // Startup self-test: critical failures keep the server NOT_SERVING
+    if registry.self_test().on_start {
+        let report = hardware_server.run_configured_self_test().await;
+        if report.ready() {
+            println!(
+                "  - Self-test: {} device(s), {}",
+                report.devices.len(),
+                if report.passed() {
+                    "all passed"
+                } else {
+                    "non-critical failures"
+                }
+            );
+        } else {
+            eprintln!(
+                "Self-test failed on critical device(s): {} (server stays NOT_SERVING)",
+                report.critical_failures().join(", ")
+            );
+        }
+    }
+
+    match &local_socket {
+        Some(socket) => println!("DAQ gRPC server (with hardware) listening on {}", socket),
+        None => println!("DAQ gRPC server (with hardware) listening on {}", bind_addr),
+    }
     println!("  - ControlService: script management");
     println!("  - HardwareService: direct device control");
     println!("  - HealthService: system health monitoring (bd-ergo)");
@@ -1470,6 +2243,12 @@ pub async fn start_server_with_hardware(
     println!("  - ScanService: coordinated multi-axis scans");
     println!("  - PresetService: configuration save/load (bd-akcm)");
     println!("  - StorageService: HDF5 data storage (bd-p6im)");
+    println!("  - SessionService: multi-user presence, device locks and preferences");
+    println!("  - LogService: structured log streaming with filters");
+    println!("  - InstrumentConsoleService: audited raw device commands");
+    println!("  - LibraryService: versioned scripts, plans and device configs");
+    #[cfg(feature = "modules")]
+    println!("  - ConfigService: differential config apply with rollback");
 
     if !grpc_settings.auth_enabled {
         eprintln!("⚠️  gRPC auth is disabled (set grpc.auth_enabled=true to require auth)");
@@ -1489,9 +2268,9 @@ pub async fn start_server_with_hardware(
         Server::builder()
             .accept_http1(true)
             .layer(cors.clone())
+            .layer(MapRequestLayer::new(auth::record_path))
             .layer(interceptor(move |request: Request<()>| {
-                validate_auth(&auth_settings, &request)?;
-                Ok(request)
+                authenticate(&auth_settings, request)
             }))
     };
 
@@ -1526,7 +2305,8 @@ pub async fn start_server_with_hardware(
         .add_service(tonic_web::enable(PluginServiceServer::new(plugin_server)))
         .add_service(tonic_web::enable(ScanServiceServer::new(scan_server)))
         .add_service(tonic_web::enable(PresetServiceServer::new(preset_server)))
-        .add_service(tonic_web::enable(StorageServiceServer::new(storage_server)));
+        .add_service(tonic_web::enable(StorageServiceServer::new(storage_server)))
+        .add_service(tonic_web::enable(SessionServiceServer::new(session_server)));
 
     #[cfg(not(feature = "serial"))]
     let mut server_builder = {
@@ -1534,9 +2314,9 @@ pub async fn start_server_with_hardware(
         Server::builder()
             .accept_http1(true)
             .layer(cors.clone())
+            .layer(MapRequestLayer::new(auth::record_path))
             .layer(interceptor(move |request: Request<()>| {
-                validate_auth(&auth_settings, &request)?;
-                Ok(request)
+                authenticate(&auth_settings, request)
             }))
     };
 
@@ -1568,7 +2348,21 @@ pub async fn start_server_with_hardware(
         .add_service(tonic_web::enable(ModuleServiceServer::new(module_server)))
         .add_service(tonic_web::enable(ScanServiceServer::new(scan_server)))
         .add_service(tonic_web::enable(PresetServiceServer::new(preset_server)))
-        .add_service(tonic_web::enable(StorageServiceServer::new(storage_server)));
+        .add_service(tonic_web::enable(StorageServiceServer::new(storage_server)))
+        .add_service(tonic_web::enable(SessionServiceServer::new(session_server)));
+
+    let server_builder = server_builder
+        .add_service(tonic_web::enable(LogServiceServer::new(log_server)))
+        .add_service(tonic_web::enable(InstrumentConsoleServiceServer::new(
+            console_server,
+        )))
+        .add_service(tonic_web::enable(LibraryServiceServer::new(library_server)))
+        .add_service(build_reflection_service()?);
+    println!("  - Server reflection: grpcurl/Postman can list and call every service");
+
+    #[cfg(feature = "modules")]
+    let server_builder =
+        server_builder.add_service(tonic_web::enable(ConfigServiceServer::new(config_server)));
 
     // Start Prometheus metrics server if enabled (bd-v299)
     #[cfg(feature = "metrics")]
@@ -1592,22 +2386,127 @@ pub async fn start_server_with_hardware(
         }
     };
 
-    server_builder.serve(bind_addr).await?;
+    // Start JSON/REST gateway if enabled; it calls back into this server
+    #[cfg(feature = "json_gateway")]
+    let _gateway_handle = if grpc_settings.tls_cert_path.is_some() {
+        eprintln!("⚠️  JSON gateway disabled: it forwards over plaintext gRPC and TLS is enabled");
+        None
+    } else {
+        let gateway_port: u16 = std::env::var("GATEWAY_PORT")
+            .ok()
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(8082);
+        let grpc_channel = match &local_socket {
+            Some(socket) => {
+                protocol::local_socket::connect_lazy(protocol::local_socket::endpoint(), socket)
+            }
+            None => {
+                let grpc_ip = if bind_addr.ip().is_unspecified() {
+                    IpAddr::V4(std::net::Ipv4Addr::LOCALHOST)
+                } else {
+                    bind_addr.ip()
+                };
+                let grpc_uri: tonic::codegen::http::Uri =
+                    format!("http://{}", SocketAddr::new(grpc_ip, bind_addr.port())).parse()?;
+                tonic::transport::Channel::builder(grpc_uri).connect_lazy()
+            }
+        };
+        match crate::grpc::gateway_service::start_gateway_server(gateway_port, grpc_channel) {
+            Ok(handle) => {
+                println!(
+                    "  - JSON Gateway: http://0.0.0.0:{}/v1/ (OpenAPI at /openapi.json)",
+                    gateway_port
+                );
+                Some(handle)
+            }
+            Err(e) => {
+                eprintln!("⚠️  Failed to start JSON gateway: {}", e);
+                None
+            }
+        }
+    };
+
+    // Start HTTP camera preview (JPEG snapshots + MJPEG) if enabled
+    #[cfg(feature = "preview")]
+    let _preview_handle = ring_buffer.clone().and_then(|rb| {
+        let preview_port: u16 = std::env::var("PREVIEW_PORT")
+            .ok()
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(8081);
+        match crate::grpc::preview_service::start_preview_server(
+            preview_port,
+            rb,
+            crate::grpc::preview_service::PreviewConfig::from_env(),
+        ) {
+            Ok(handle) => {
+                println!("  - Camera Preview: http://0.0.0.0:{}/", preview_port);
+                Some(handle)
+            }
+            Err(e) => {
+                eprintln!("⚠️  Failed to start preview server: {}", e);
+                None
+            }
+        }
+    });
+
+    // An injected fault stops the server as if it had crashed
+    let crashed = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let shutdown = {
+        let crashed = crashed.clone();
+        async move {
+            tokio::select! {
+                () = shutdown => {}
+                fault = FaultInjector::global().triggered(points::GRPC_SERVER) => {
+                    eprintln!("gRPC server stopping: {}", fault);
+                    crashed.store(true, std::sync::atomic::Ordering::SeqCst);
+                }
+            }
+        }
+    };
+
+    match local_socket {
+        Some(socket) => {
+            server_builder
+                .serve_with_incoming_shutdown(protocol::local_socket::incoming(&socket)?, shutdown)
+                .await?
+        }
+        None => {
+            server_builder
+                .serve_with_shutdown(bind_addr, shutdown)
+                .await?
+        }
+    }
 
+    if crashed.load(std::sync::atomic::Ordering::SeqCst) {
+        return Err("gRPC server stopped by injected fault".into());
+    }
     Ok(())
 }
 
+/// Aborts a background task when the server that spawned it exits
+struct AbortOnDrop(JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use chrono::Utc;
+    use common::core::DataQuality;
 
     /// Create a test DaqServer with a mock RunEngine (bd-si2c)
     #[cfg(feature = "scripting")]
     fn create_test_server() -> DaqServer {
         let registry = std::sync::Arc::new(hardware::registry::DeviceRegistry::new());
         let run_engine = std::sync::Arc::new(experiment::RunEngine::new(registry));
-        DaqServer::new(run_engine).expect("failed to create test DaqServer")
+        let audit = std::env::temp_dir().join(format!("daq-test-audit-{}.jsonl", Uuid::new_v4()));
+        DaqServer::new(run_engine)
+            .expect("failed to create test DaqServer")
+            .with_audit_log(Arc::new(AuditLog::new(audit)))
     }
 
     #[tokio::test]
@@ -1698,6 +2597,69 @@ mod tests {
         assert_eq!(status_resp.error_message, "");
     }
 
+    #[tokio::test]
+    #[cfg(feature = "scripting")]
+    async fn test_script_actions_are_scoped_and_audited() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let audit = Arc::new(AuditLog::new(dir.path().join("audit.jsonl")));
+        let server = create_test_server().with_audit_log(audit.clone());
+
+        let upload_req = Request::new(UploadRequest {
+            script_content: "let x = 1;".to_string(),
+            name: "calibration".to_string(),
+            metadata: HashMap::new(),
+        });
+        let upload_resp = server.upload_script(upload_req).await.unwrap().into_inner();
+        assert!(upload_resp.success);
+
+        // A scheduled job's token only covers the script it was issued for
+        let start_as = |scope: &str| {
+            let mut request = Request::new(StartRequest {
+                script_id: upload_resp.script_id.clone(),
+                parameters: HashMap::new(),
+            });
+            request.extensions_mut().insert(Principal {
+                user: "nightly".to_string(),
+                scopes: vec![scope.to_string()],
+            });
+            request
+        };
+        let denied = server
+            .start_script(start_as("scripts:run:alignment"))
+            .await
+            .unwrap_err();
+        assert_eq!(denied.code(), tonic::Code::PermissionDenied);
+        let start_resp = server
+            .start_script(start_as("scripts:run:calibration"))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let status_req = Request::new(StatusRequest {
+            execution_id: start_resp.execution_id,
+        });
+        let status_resp = server
+            .get_script_status(status_req)
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(status_resp.started_by, "nightly");
+
+        let entries = audit.read_all().unwrap();
+        let actions: Vec<_> = entries
+            .iter()
+            .map(|e| (e.action.as_str(), e.user.as_str(), e.outcome.as_str()))
+            .collect();
+        assert_eq!(
+            actions[..3],
+            [
+                ("script_upload", "anonymous", "ok"),
+                ("script_start", "nightly", "blocked"),
+                ("script_start", "nightly", "ok"),
+            ]
+        );
+    }
+
     #[tokio::test]
     #[cfg(feature = "scripting")]
     async fn test_stream_measurements_basic() {
@@ -1712,6 +2674,7 @@ mod tests {
         let request = Request::new(crate::grpc::proto::MeasurementRequest {
             channels: vec![],
             max_rate_hz: 0,
+            decimation: None,
         });
 
         let response = server.stream_measurements(request).await.unwrap();
@@ -1725,6 +2688,7 @@ mod tests {
                     value: i as f64,
                     unit: "V".to_string(),
                     timestamp: Utc::now(),
+                    quality: DataQuality::Good,
                 });
                 tokio::time::sleep(std::time::Duration::from_millis(10)).await;
             }
@@ -1759,6 +2723,7 @@ mod tests {
         let request = Request::new(crate::grpc::proto::MeasurementRequest {
             channels: vec!["channel_a".to_string()],
             max_rate_hz: 0,
+            decimation: None,
         });
 
         let response = server.stream_measurements(request).await.unwrap();
@@ -1773,6 +2738,7 @@ mod tests {
                     value: i as f64,
                     unit: "V".to_string(),
                     timestamp: Utc::now(),
+                    quality: DataQuality::Good,
                 });
                 tokio::time::sleep(std::time::Duration::from_millis(5)).await;
             }
@@ -1813,6 +2779,7 @@ mod tests {
         let request = Request::new(crate::grpc::proto::MeasurementRequest {
             channels: vec![],
             max_rate_hz: 10,
+            decimation: None,
         });
 
         let response = server.stream_measurements(request).await.unwrap();
@@ -1826,6 +2793,7 @@ mod tests {
                     value: i as f64,
                     unit: "V".to_string(),
                     timestamp: Utc::now(),
+                    quality: DataQuality::Good,
                 });
                 tokio::time::sleep(std::time::Duration::from_millis(1)).await;
             }
@@ -1857,6 +2825,62 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    #[cfg(feature = "scripting")]
+    async fn test_stream_measurements_wall_clock_decimation() {
+        use chrono::DurationRound;
+        use tokio_stream::StreamExt;
+
+        let server = create_test_server();
+        let data_sender = server.data_sender();
+
+        // 10 Hz per channel, aligned to wall-clock boundaries
+        let request = Request::new(crate::grpc::proto::MeasurementRequest {
+            channels: vec![],
+            max_rate_hz: 0,
+            decimation: Some(crate::grpc::proto::StreamDecimation {
+                every_nth: 0,
+                rate_hz: 10.0,
+                align_to_wall_clock: true,
+            }),
+        });
+
+        let response = server.stream_measurements(request).await.unwrap();
+        let mut stream = response.into_inner();
+
+        // Two channels, 5 samples each, all stamped inside the same 100 ms period
+        let base = Utc::now()
+            .duration_trunc(chrono::Duration::milliseconds(100))
+            .unwrap();
+        tokio::spawn(async move {
+            for i in 0..5 {
+                for channel in ["a", "b"] {
+                    let _ = data_sender.send(Measurement::Scalar {
+                        name: channel.to_string(),
+                        value: i as f64,
+                        unit: "V".to_string(),
+                        timestamp: base + chrono::Duration::milliseconds(i * 10),
+                        quality: DataQuality::Good,
+                    });
+                }
+            }
+        });
+
+        // One sample per channel per period, the first of each
+        let mut received = Vec::new();
+        while let Ok(Some(result)) =
+            tokio::time::timeout(std::time::Duration::from_millis(200), stream.next()).await
+        {
+            let data_point = result.unwrap();
+            received.push((data_point.channel, data_point.value));
+        }
+        received.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            received,
+            vec![("a".to_string(), 0.0), ("b".to_string(), 0.0)]
+        );
+    }
+
     #[tokio::test]
     #[cfg(feature = "scripting")]
     async fn test_stream_measurements_multiple_clients() {
@@ -1869,10 +2893,12 @@ mod tests {
         let request1 = Request::new(crate::grpc::proto::MeasurementRequest {
             channels: vec![],
             max_rate_hz: 0,
+            decimation: None,
         });
         let request2 = Request::new(crate::grpc::proto::MeasurementRequest {
             channels: vec![],
             max_rate_hz: 0,
+            decimation: None,
         });
 
         let response1 = server.stream_measurements(request1).await.unwrap();
@@ -1889,6 +2915,7 @@ mod tests {
                     value: i as f64,
                     unit: "V".to_string(),
                     timestamp: Utc::now(),
+                    quality: DataQuality::Good,
                 });
                 tokio::time::sleep(std::time::Duration::from_millis(10)).await;
             }
@@ -1912,6 +2939,26 @@ mod tests {
         assert_eq!(client1_data, client2_data);
     }
 
+    #[test]
+    fn test_local_socket_setting() {
+        assert!(GrpcSettings::default().local_socket().unwrap().is_none());
+
+        let settings = GrpcSettings {
+            local_socket: Some("unix:///run/rust-daq/daq.sock".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.local_socket().unwrap(),
+            Some(LocalSocket::Unix(PathBuf::from("/run/rust-daq/daq.sock")))
+        );
+
+        let settings = GrpcSettings {
+            local_socket: Some("tcp://127.0.0.1:50051".to_string()),
+            ..Default::default()
+        };
+        assert!(settings.local_socket().is_err());
+    }
+
     #[test]
     fn test_auth_rejects_missing_token() {
         let settings = GrpcSettings {
@@ -1961,6 +3008,75 @@ mod tests {
 
         let result = validate_auth(&settings, &request);
 
-        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), Principal::api_key());
+    }
+
+    #[test]
+    fn test_auth_token_carries_user_and_scopes() {
+        let settings = GrpcSettings {
+            auth_enabled: true,
+            auth_token: Some("secret".to_string()),
+            ..Default::default()
+        };
+        let scopes = vec!["scripts:run:nightly".to_string()];
+        let token = auth::issue_token(
+            "secret",
+            "scheduler",
+            &scopes,
+            std::time::Duration::from_secs(60),
+        )
+        .unwrap();
+
+        let mut request = Request::new(());
+        request.metadata_mut().insert(
+            "authorization",
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        let request = authenticate(&settings, request).unwrap();
+
+        let principal = auth::principal(&request);
+        assert_eq!(principal.user, "scheduler");
+        assert_eq!(principal.scopes, scopes);
+        assert!(!principal.allows(auth::SCOPE_SCRIPTS_UPLOAD, ""));
+    }
+
+    #[test]
+    fn test_scoped_token_cannot_change_hardware_or_config() {
+        let settings = GrpcSettings {
+            auth_enabled: true,
+            auth_token: Some("secret".to_string()),
+            ..Default::default()
+        };
+        let token = auth::issue_token(
+            "secret",
+            "scheduler",
+            &["scripts:run:nightly".to_string()],
+            std::time::Duration::from_secs(60),
+        )
+        .unwrap();
+        let call = |path: &str| {
+            let mut request = Request::new(());
+            request.metadata_mut().insert(
+                "authorization",
+                format!("Bearer {}", token).parse().unwrap(),
+            );
+            request.extensions_mut().insert(GrpcPath(path.to_string()));
+            authenticate(&settings, request)
+        };
+
+        for path in [
+            "/daq.HardwareService/MoveAbsolute",
+            "/daq.HardwareService/SetParameter",
+            "/daq.InstrumentConsoleService/SendRawCommand",
+            "/daq.ConfigService/ApplyConfig",
+            "/daq.ConfigService/RestoreConfigSnapshot",
+            "/daq.LibraryService/UploadLibraryFile",
+        ] {
+            let denied = call(path).unwrap_err();
+            assert_eq!(denied.code(), tonic::Code::PermissionDenied, "{}", path);
+        }
+
+        assert!(call("/daq.HardwareService/ListDevices").is_ok());
+        assert!(call("/daq.ControlService/StartScript").is_ok());
     }
 }
@@ -0,0 +1,800 @@
+//! SessionService implementation for multi-user presence
+//!
+//! Tracks every connected client as a session (name, host, role, held device
+//! locks) so users can see who else is driving the rig. Clients open a
+//! session on connect and heartbeat it; sessions that stop heartbeating
+//! expire and release their locks.
+//!
+//! Device locks are advisory. They don't gate HardwareService calls, but a
+//! session can't take a lock another session already holds, and every lock
+//! change is broadcast so the GUI can show who controls which device.
+//!
+//! Admin sessions can revoke other sessions. The revoked client learns about
+//! it from the presence stream and its next heartbeat fails with NOT_FOUND.
+//! The Admin role isn't taken on the client's word: opening an admin session
+//! and revoking both need an authenticated credential with the
+//! `sessions:admin` scope (see [`crate::auth`]), so with authentication
+//! disabled nobody is an admin.
+//!
+//! Sessions also carry client preferences (see [`crate::preferences`]):
+//! user-scoped ones are keyed by the session's client name, so a user gets
+//! the same favorites and panel defaults on every machine they connect from.
+
+use crate::auth::{self, Principal};
+use crate::grpc::proto::{
+    CloseSessionRequest, CloseSessionResponse, DeletePreferencesRequest, DeletePreferencesResponse,
+    DeviceLockRequest, DeviceLockResponse, GetPreferencesRequest, GetPreferencesResponse,
+    ListSessionsRequest, ListSessionsResponse, OpenSessionRequest, Preference, PreferenceScope,
+    PreferenceUpdate, PresenceEventKind, PresenceUpdate, RevokeSessionRequest,
+    RevokeSessionResponse, SessionHeartbeatRequest, SessionInfo, SessionRole,
+    SetPreferencesRequest, SetPreferencesResponse, StreamPreferencesRequest, StreamPresenceRequest,
+    session_service_server::SessionService,
+};
+use crate::preferences::{PreferenceChange, PreferenceEntry, PreferenceOwner, PreferenceStore};
+use common::experiment::document::now_ns;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tokio_stream::StreamExt;
+use tokio_stream::wrappers::BroadcastStream;
+use tonic::{Request, Response, Status};
+
+/// Sessions that haven't heartbeated for this long are expired
+pub const DEFAULT_SESSION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Presence updates buffered per stream subscriber
+const PRESENCE_CHANNEL_CAPACITY: usize = 64;
+
+#[derive(Debug, Clone)]
+struct Session {
+    info: SessionInfo,
+    last_seen: Instant,
+}
+
+/// Registry of connected client sessions and their device locks
+#[derive(Debug)]
+pub struct SessionManager {
+    sessions: RwLock<HashMap<String, Session>>,
+    timeout: Duration,
+    presence_tx: broadcast::Sender<PresenceUpdate>,
+    preferences: Arc<PreferenceStore>,
+}
+
+impl Default for SessionManager {
+    fn default() -> Self {
+        Self::new(DEFAULT_SESSION_TIMEOUT)
+    }
+}
+
+impl SessionManager {
+    /// Create a manager that expires sessions after `timeout` without a heartbeat
+    pub fn new(timeout: Duration) -> Self {
+        let (presence_tx, _) = broadcast::channel(PRESENCE_CHANNEL_CAPACITY);
+        Self {
+            sessions: RwLock::new(HashMap::new()),
+            timeout,
+            presence_tx,
+            preferences: Arc::new(PreferenceStore::default()),
+        }
+    }
+
+    /// Use `preferences` for client preferences (memory-only by default)
+    pub fn with_preferences(mut self, preferences: Arc<PreferenceStore>) -> Self {
+        self.preferences = preferences;
+        self
+    }
+
+    /// Client preference store
+    pub fn preferences(&self) -> &Arc<PreferenceStore> {
+        &self.preferences
+    }
+
+    /// Register a new session and announce it
+    pub fn open(&self, client_name: &str, host: &str, role: SessionRole) -> SessionInfo {
+        let now = now_ns();
+        let info = SessionInfo {
+            session_id: uuid::Uuid::new_v4().to_string(),
+            client_name: client_name.to_string(),
+            host: host.to_string(),
+            role: role as i32,
+            connected_at_ns: now,
+            last_seen_ns: now,
+            held_locks: Vec::new(),
+        };
+        self.write().insert(
+            info.session_id.clone(),
+            Session {
+                info: info.clone(),
+                last_seen: Instant::now(),
+            },
+        );
+        tracing::info!(
+            session_id = %info.session_id,
+            client = %info.client_name,
+            host = %info.host,
+            role = ?role,
+            "Session opened"
+        );
+        self.announce(PresenceEventKind::PresenceJoined, &info.session_id, "");
+        info
+    }
+
+    /// Refresh a session's last-seen time
+    pub fn heartbeat(&self, session_id: &str) -> Result<SessionInfo, Status> {
+        let mut sessions = self.write();
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| unknown_session(session_id))?;
+        session.last_seen = Instant::now();
+        session.info.last_seen_ns = now_ns();
+        Ok(session.info.clone())
+    }
+
+    /// Current info of a session
+    pub fn get(&self, session_id: &str) -> Result<SessionInfo, Status> {
+        self.read()
+            .get(session_id)
+            .map(|session| session.info.clone())
+            .ok_or_else(|| unknown_session(session_id))
+    }
+
+    /// End a session, releasing its locks
+    pub fn close(&self, session_id: &str) -> Result<(), Status> {
+        self.remove(session_id, PresenceEventKind::PresenceLeft, "")
+    }
+
+    /// All sessions, oldest first
+    pub fn list(&self) -> Vec<SessionInfo> {
+        let mut sessions: Vec<SessionInfo> = self
+            .read()
+            .values()
+            .map(|session| session.info.clone())
+            .collect();
+        sessions.sort_by_key(|info| info.connected_at_ns);
+        sessions
+    }
+
+    /// Session currently holding the lock on `device_id`
+    pub fn lock_holder(&self, device_id: &str) -> Option<SessionInfo> {
+        self.read()
+            .values()
+            .find(|session| session.info.held_locks.iter().any(|id| id == device_id))
+            .map(|session| session.info.clone())
+    }
+
+    /// Take the advisory lock on `device_id`
+    ///
+    /// Re-acquiring a lock the session already holds succeeds. Fails with
+    /// `Err(holder)` if another session holds it.
+    pub fn acquire_lock(
+        &self,
+        session_id: &str,
+        device_id: &str,
+    ) -> Result<Result<(), SessionInfo>, Status> {
+        {
+            let mut sessions = self.write();
+            if !sessions.contains_key(session_id) {
+                return Err(unknown_session(session_id));
+            }
+            if let Some(holder) = sessions.values().find(|session| {
+                session.info.session_id != session_id
+                    && session.info.held_locks.iter().any(|id| id == device_id)
+            }) {
+                return Ok(Err(holder.info.clone()));
+            }
+            let session = sessions
+                .get_mut(session_id)
+                .ok_or_else(|| unknown_session(session_id))?;
+            if session.info.held_locks.iter().any(|id| id == device_id) {
+                return Ok(Ok(()));
+            }
+            session.info.held_locks.push(device_id.to_string());
+            session.info.held_locks.sort();
+        }
+        self.announce(PresenceEventKind::PresenceLocksChanged, session_id, "");
+        Ok(Ok(()))
+    }
+
+    /// Release the advisory lock on `device_id` (no-op if not held)
+    pub fn release_lock(&self, session_id: &str, device_id: &str) -> Result<(), Status> {
+        let released = {
+            let mut sessions = self.write();
+            let session = sessions
+                .get_mut(session_id)
+                .ok_or_else(|| unknown_session(session_id))?;
+            let before = session.info.held_locks.len();
+            session.info.held_locks.retain(|id| id != device_id);
+            session.info.held_locks.len() != before
+        };
+        if released {
+            self.announce(PresenceEventKind::PresenceLocksChanged, session_id, "");
+        }
+        Ok(())
+    }
+
+    /// Forcefully end `target_session_id` on behalf of an admin session
+    pub fn revoke(
+        &self,
+        admin_session_id: &str,
+        target_session_id: &str,
+        reason: &str,
+    ) -> Result<(), Status> {
+        let admin_role = self
+            .read()
+            .get(admin_session_id)
+            .map(|session| session.info.role())
+            .ok_or_else(|| unknown_session(admin_session_id))?;
+        if admin_role != SessionRole::Admin {
+            return Err(Status::permission_denied(
+                "only admin sessions can revoke other sessions",
+            ));
+        }
+        tracing::warn!(
+            admin = %admin_session_id,
+            target = %target_session_id,
+            reason = %reason,
+            "Revoking session"
+        );
+        self.remove(
+            target_session_id,
+            PresenceEventKind::PresenceRevoked,
+            reason,
+        )
+    }
+
+    /// Remove sessions that missed their heartbeats; returns how many expired
+    pub fn expire_stale(&self) -> usize {
+        let stale: Vec<String> = self
+            .read()
+            .values()
+            .filter(|session| session.last_seen.elapsed() > self.timeout)
+            .map(|session| session.info.session_id.clone())
+            .collect();
+        for session_id in &stale {
+            tracing::info!(session_id = %session_id, "Session expired");
+            let _ = self.remove(session_id, PresenceEventKind::PresenceExpired, "");
+        }
+        stale.len()
+    }
+
+    /// Periodically expire stale sessions until the manager is dropped
+    pub fn spawn_reaper(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let manager: Weak<Self> = Arc::downgrade(self);
+        let period = (self.timeout / 2).max(Duration::from_millis(100));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                match manager.upgrade() {
+                    Some(manager) => {
+                        manager.expire_stale();
+                    }
+                    None => break,
+                }
+            }
+        })
+    }
+
+    /// Subscribe to presence changes
+    pub fn subscribe(&self) -> broadcast::Receiver<PresenceUpdate> {
+        self.presence_tx.subscribe()
+    }
+
+    /// Current state as a snapshot update
+    pub fn snapshot(&self) -> PresenceUpdate {
+        PresenceUpdate {
+            kind: PresenceEventKind::PresenceSnapshot as i32,
+            session_id: String::new(),
+            reason: String::new(),
+            sessions: self.list(),
+            timestamp_ns: now_ns(),
+        }
+    }
+
+    fn remove(
+        &self,
+        session_id: &str,
+        kind: PresenceEventKind,
+        reason: &str,
+    ) -> Result<(), Status> {
+        self.write()
+            .remove(session_id)
+            .ok_or_else(|| unknown_session(session_id))?;
+        self.preferences.drop_session(session_id);
+        self.announce(kind, session_id, reason);
+        Ok(())
+    }
+
+    fn announce(&self, kind: PresenceEventKind, session_id: &str, reason: &str) {
+        // No receivers is fine: nobody is watching presence
+        let _ = self.presence_tx.send(PresenceUpdate {
+            kind: kind as i32,
+            session_id: session_id.to_string(),
+            reason: reason.to_string(),
+            sessions: self.list(),
+            timestamp_ns: now_ns(),
+        });
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<String, Session>> {
+        self.sessions.read().unwrap_or_else(|p| p.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<String, Session>> {
+        self.sessions.write().unwrap_or_else(|p| p.into_inner())
+    }
+}
+
+fn unknown_session(session_id: &str) -> Status {
+    Status::not_found(format!("Session not found: {}", session_id))
+}
+
+/// Owner of the preferences a session sees in `scope`
+fn preference_owner(info: &SessionInfo, scope: PreferenceScope) -> PreferenceOwner {
+    match scope {
+        PreferenceScope::User => PreferenceOwner::User(info.client_name.clone()),
+        PreferenceScope::Session => PreferenceOwner::Session(info.session_id.clone()),
+    }
+}
+
+fn preferences_to_proto(
+    entries: BTreeMap<String, PreferenceEntry>,
+    scope: PreferenceScope,
+) -> impl Iterator<Item = Preference> {
+    entries.into_iter().map(move |(key, entry)| Preference {
+        key,
+        value: entry.value,
+        scope: scope as i32,
+        updated_ns: entry.updated_ns,
+        updated_by: entry.updated_by,
+    })
+}
+
+/// All preferences a session sees, user scope first
+fn visible_preferences(
+    store: &PreferenceStore,
+    info: &SessionInfo,
+    key_prefix: &str,
+) -> Vec<Preference> {
+    [PreferenceScope::User, PreferenceScope::Session]
+        .into_iter()
+        .flat_map(|scope| {
+            let entries = store.get(&preference_owner(info, scope), key_prefix);
+            preferences_to_proto(entries, scope)
+        })
+        .collect()
+}
+
+/// Stream message for a change, if the session can see it
+fn preference_update(info: &SessionInfo, change: PreferenceChange) -> Option<PreferenceUpdate> {
+    let scope = [PreferenceScope::User, PreferenceScope::Session]
+        .into_iter()
+        .find(|&scope| preference_owner(info, scope) == change.owner)?;
+    Some(PreferenceUpdate {
+        snapshot: false,
+        preferences: preferences_to_proto(change.set, scope).collect(),
+        scope: scope as i32,
+        deleted_keys: change.deleted,
+        source_session_id: change.source_session_id,
+        timestamp_ns: now_ns(),
+    })
+}
+
+/// Principal of a request, if its credential may act as a session admin
+///
+/// Anonymous requests (authentication disabled, in-process calls) never
+/// qualify, even though they are otherwise allowed everything.
+fn require_session_admin<T>(request: &Request<T>) -> Result<Principal, Status> {
+    let principal = auth::require_scope(request, auth::SCOPE_SESSIONS_ADMIN, "")?;
+    if principal == Principal::anonymous() {
+        return Err(Status::permission_denied(
+            "admin sessions need an authenticated credential",
+        ));
+    }
+    Ok(principal)
+}
+
+/// Session gRPC service implementation
+#[derive(Debug, Clone)]
+pub struct SessionServiceImpl {
+    manager: Arc<SessionManager>,
+}
+
+impl SessionServiceImpl {
+    /// Create a service backed by `manager`
+    pub fn new(manager: Arc<SessionManager>) -> Self {
+        Self { manager }
+    }
+
+    /// Shared session manager
+    pub fn manager(&self) -> &Arc<SessionManager> {
+        &self.manager
+    }
+}
+
+#[tonic::async_trait]
+impl SessionService for SessionServiceImpl {
+    async fn open_session(
+        &self,
+        request: Request<OpenSessionRequest>,
+    ) -> Result<Response<SessionInfo>, Status> {
+        let peer = request.remote_addr();
+        if request.get_ref().role() == SessionRole::Admin {
+            require_session_admin(&request)?;
+        }
+        let req = request.into_inner();
+        let role = req.role();
+        if req.client_name.trim().is_empty() {
+            return Err(Status::invalid_argument("client_name is required"));
+        }
+        let host = if req.host.is_empty() {
+            peer.map(|addr| addr.ip().to_string()).unwrap_or_default()
+        } else {
+            req.host
+        };
+        let info = self.manager.open(req.client_name.trim(), &host, role);
+        Ok(Response::new(info))
+    }
+
+    async fn heartbeat(
+        &self,
+        request: Request<SessionHeartbeatRequest>,
+    ) -> Result<Response<SessionInfo>, Status> {
+        let info = self.manager.heartbeat(&request.into_inner().session_id)?;
+        Ok(Response::new(info))
+    }
+
+    async fn close_session(
+        &self,
+        request: Request<CloseSessionRequest>,
+    ) -> Result<Response<CloseSessionResponse>, Status> {
+        self.manager.close(&request.into_inner().session_id)?;
+        Ok(Response::new(CloseSessionResponse { success: true }))
+    }
+
+    async fn list_sessions(
+        &self,
+        _request: Request<ListSessionsRequest>,
+    ) -> Result<Response<ListSessionsResponse>, Status> {
+        Ok(Response::new(ListSessionsResponse {
+            sessions: self.manager.list(),
+        }))
+    }
+
+    async fn acquire_device_lock(
+        &self,
+        request: Request<DeviceLockRequest>,
+    ) -> Result<Response<DeviceLockResponse>, Status> {
+        let req = request.into_inner();
+        let response = match self.manager.acquire_lock(&req.session_id, &req.device_id)? {
+            Ok(()) => DeviceLockResponse {
+                success: true,
+                error_message: String::new(),
+                holder: None,
+            },
+            Err(holder) => DeviceLockResponse {
+                success: false,
+                error_message: format!(
+                    "{} is locked by {}@{}",
+                    req.device_id, holder.client_name, holder.host
+                ),
+                holder: Some(holder),
+            },
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn release_device_lock(
+        &self,
+        request: Request<DeviceLockRequest>,
+    ) -> Result<Response<DeviceLockResponse>, Status> {
+        let req = request.into_inner();
+        self.manager.release_lock(&req.session_id, &req.device_id)?;
+        Ok(Response::new(DeviceLockResponse {
+            success: true,
+            error_message: String::new(),
+            holder: None,
+        }))
+    }
+
+    async fn revoke_session(
+        &self,
+        request: Request<RevokeSessionRequest>,
+    ) -> Result<Response<RevokeSessionResponse>, Status> {
+        // Session IDs are public through ListSessions, so naming an admin
+        // session isn't enough; the caller's credential must allow it too
+        require_session_admin(&request)?;
+        let req = request.into_inner();
+        let response =
+            match self
+                .manager
+                .revoke(&req.admin_session_id, &req.target_session_id, &req.reason)
+            {
+                Ok(()) => RevokeSessionResponse {
+                    success: true,
+                    error_message: String::new(),
+                },
+                Err(status) if status.code() == tonic::Code::PermissionDenied => {
+                    return Err(status);
+                }
+                Err(status) => RevokeSessionResponse {
+                    success: false,
+                    error_message: status.message().to_string(),
+                },
+            };
+        Ok(Response::new(response))
+    }
+
+    type StreamPresenceStream =
+        std::pin::Pin<Box<dyn tokio_stream::Stream<Item = Result<PresenceUpdate, Status>> + Send>>;
+
+    async fn stream_presence(
+        &self,
+        _request: Request<StreamPresenceRequest>,
+    ) -> Result<Response<Self::StreamPresenceStream>, Status> {
+        // Subscribe before taking the snapshot so no change falls in between
+        let rx = self.manager.subscribe();
+        let snapshot = self.manager.snapshot();
+        let manager = self.manager.clone();
+        let updates = BroadcastStream::new(rx).map(move |result| match result {
+            Ok(update) => Ok(update),
+            // Missed updates are harmless: the next snapshot carries full state
+            Err(_) => Ok(manager.snapshot()),
+        });
+        let stream = tokio_stream::once(Ok(snapshot)).chain(updates);
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn get_preferences(
+        &self,
+        request: Request<GetPreferencesRequest>,
+    ) -> Result<Response<GetPreferencesResponse>, Status> {
+        let req = request.into_inner();
+        let info = self.manager.get(&req.session_id)?;
+        Ok(Response::new(GetPreferencesResponse {
+            preferences: visible_preferences(self.manager.preferences(), &info, &req.key_prefix),
+        }))
+    }
+
+    async fn set_preferences(
+        &self,
+        request: Request<SetPreferencesRequest>,
+    ) -> Result<Response<SetPreferencesResponse>, Status> {
+        let req = request.into_inner();
+        let scope = req.scope();
+        let info = self.manager.get(&req.session_id)?;
+        let stored = self
+            .manager
+            .preferences()
+            .set(
+                &preference_owner(&info, scope),
+                req.values.into_iter().collect(),
+                &info.host,
+                &info.session_id,
+            )
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        Ok(Response::new(SetPreferencesResponse {
+            preferences: preferences_to_proto(stored, scope).collect(),
+        }))
+    }
+
+    async fn delete_preferences(
+        &self,
+        request: Request<DeletePreferencesRequest>,
+    ) -> Result<Response<DeletePreferencesResponse>, Status> {
+        let req = request.into_inner();
+        let info = self.manager.get(&req.session_id)?;
+        let deleted = self.manager.preferences().delete(
+            &preference_owner(&info, req.scope()),
+            &req.keys,
+            &info.session_id,
+        );
+        Ok(Response::new(DeletePreferencesResponse {
+            deleted: deleted as u32,
+        }))
+    }
+
+    type StreamPreferencesStream = std::pin::Pin<
+        Box<dyn tokio_stream::Stream<Item = Result<PreferenceUpdate, Status>> + Send>,
+    >;
+
+    async fn stream_preferences(
+        &self,
+        request: Request<StreamPreferencesRequest>,
+    ) -> Result<Response<Self::StreamPreferencesStream>, Status> {
+        let info = self.manager.get(&request.into_inner().session_id)?;
+        let store = self.manager.preferences().clone();
+
+        // Subscribe before taking the snapshot so no change falls in between
+        let rx = store.subscribe();
+        let snapshot = preference_snapshot(&store, &info);
+        let updates = BroadcastStream::new(rx).filter_map(move |result| match result {
+            Ok(change) => preference_update(&info, change).map(Ok),
+            // Missed changes are recovered by sending full state again
+            Err(_) => Some(Ok(preference_snapshot(&store, &info))),
+        });
+        let stream = tokio_stream::once(Ok(snapshot)).chain(updates);
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+fn preference_snapshot(store: &PreferenceStore, info: &SessionInfo) -> PreferenceUpdate {
+    PreferenceUpdate {
+        snapshot: true,
+        preferences: visible_preferences(store, info, ""),
+        scope: PreferenceScope::User as i32,
+        deleted_keys: Vec::new(),
+        source_session_id: String::new(),
+        timestamp_ns: now_ns(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locks_are_exclusive_and_released_on_close() {
+        let manager = SessionManager::default();
+        let alice = manager.open("alice", "lab-pc", SessionRole::Operator);
+        let bob = manager.open("bob", "laptop", SessionRole::Operator);
+
+        assert_eq!(
+            manager.acquire_lock(&alice.session_id, "stage_x").unwrap(),
+            Ok(())
+        );
+        // Re-acquiring your own lock is fine
+        assert_eq!(
+            manager.acquire_lock(&alice.session_id, "stage_x").unwrap(),
+            Ok(())
+        );
+        let holder = manager
+            .acquire_lock(&bob.session_id, "stage_x")
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(holder.client_name, "alice");
+        assert_eq!(
+            manager.lock_holder("stage_x").unwrap().session_id,
+            alice.session_id
+        );
+
+        manager.close(&alice.session_id).unwrap();
+        assert!(manager.lock_holder("stage_x").is_none());
+        assert_eq!(
+            manager.acquire_lock(&bob.session_id, "stage_x").unwrap(),
+            Ok(())
+        );
+        assert_eq!(manager.list().len(), 1);
+    }
+
+    #[test]
+    fn test_only_admins_can_revoke() {
+        let manager = SessionManager::default();
+        let admin = manager.open("admin", "control-room", SessionRole::Admin);
+        let operator = manager.open("carol", "lab-pc", SessionRole::Operator);
+        let mut presence = manager.subscribe();
+
+        let err = manager
+            .revoke(&operator.session_id, &admin.session_id, "mine now")
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+
+        manager
+            .revoke(
+                &admin.session_id,
+                &operator.session_id,
+                "left running overnight",
+            )
+            .unwrap();
+        let update = presence.try_recv().unwrap();
+        assert_eq!(update.kind(), PresenceEventKind::PresenceRevoked);
+        assert_eq!(update.session_id, operator.session_id);
+        assert_eq!(update.reason, "left running overnight");
+        assert_eq!(update.sessions.len(), 1);
+        assert_eq!(
+            manager.heartbeat(&operator.session_id).unwrap_err().code(),
+            tonic::Code::NotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_admin_role_needs_credential() {
+        let service = SessionServiceImpl::new(Arc::new(SessionManager::default()));
+        let open = |principal: Option<Principal>| {
+            let mut request = Request::new(OpenSessionRequest {
+                client_name: "frank".to_string(),
+                host: "lab-pc".to_string(),
+                role: SessionRole::Admin.into(),
+            });
+            if let Some(principal) = principal {
+                request.extensions_mut().insert(principal);
+            }
+            request
+        };
+
+        // Self-declared admin without a credential
+        let err = service.open_session(open(None)).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        let operator_only = Principal {
+            user: "frank".to_string(),
+            scopes: vec![auth::SCOPE_SCRIPTS_RUN.to_string()],
+        };
+        let err = service
+            .open_session(open(Some(operator_only)))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+
+        let admin = Principal {
+            user: "frank".to_string(),
+            scopes: vec![auth::SCOPE_SESSIONS_ADMIN.to_string()],
+        };
+        let info = service
+            .open_session(open(Some(admin)))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(info.role(), SessionRole::Admin);
+
+        // Knowing the admin session's ID doesn't let an anonymous caller revoke
+        let target = service
+            .manager()
+            .open("gina", "laptop", SessionRole::Operator);
+        let err = service
+            .revoke_session(Request::new(RevokeSessionRequest {
+                admin_session_id: info.session_id,
+                target_session_id: target.session_id,
+                reason: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+    }
+
+    #[test]
+    fn test_preferences_follow_the_user() {
+        let manager = SessionManager::default();
+        let desk = manager.open("erin", "control-room", SessionRole::Operator);
+        let laptop = manager.open("erin", "rack-laptop", SessionRole::Operator);
+        let store = manager.preferences();
+
+        let values = BTreeMap::from([("plot.favorites".to_string(), "[\"power\"]".to_string())]);
+        let owner = preference_owner(&desk, PreferenceScope::User);
+        let mut change_rx = store.subscribe();
+        store
+            .set(&owner, values, &desk.host, &desk.session_id)
+            .unwrap();
+        let session_owner = preference_owner(&desk, PreferenceScope::Session);
+        store
+            .set(
+                &session_owner,
+                BTreeMap::from([("panel.layout".to_string(), "wide".to_string())]),
+                &desk.host,
+                &desk.session_id,
+            )
+            .unwrap();
+
+        // The laptop sees the user preference but not the desk's session one
+        let prefs = visible_preferences(store, &laptop, "");
+        assert_eq!(prefs.len(), 1);
+        assert_eq!(prefs[0].updated_by, "control-room");
+        let update = preference_update(&laptop, change_rx.try_recv().unwrap()).unwrap();
+        assert_eq!(update.preferences[0].key, "plot.favorites");
+        assert!(preference_update(&laptop, change_rx.try_recv().unwrap()).is_none());
+
+        // Session preferences end with the session
+        manager.close(&desk.session_id).unwrap();
+        assert!(store.get(&session_owner, "").is_empty());
+        assert_eq!(visible_preferences(store, &laptop, "plot.").len(), 1);
+    }
+
+    #[test]
+    fn test_stale_sessions_expire() {
+        let manager = SessionManager::new(Duration::ZERO);
+        let session = manager.open("dave", "pi", SessionRole::Observer);
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(manager.expire_stale(), 1);
+        assert!(manager.heartbeat(&session.session_id).is_err());
+    }
+}
@@ -9,24 +9,30 @@
 //! Output paths are validated to remain within the configured output directory.
 
 use crate::grpc::proto::{
-    AcquisitionInfo, AcquisitionSummary, ConfigureStorageRequest, ConfigureStorageResponse,
-    DeleteAcquisitionRequest, DeleteAcquisitionResponse, FlushToStorageRequest,
-    FlushToStorageResponse, GetAcquisitionInfoRequest, GetRecordingStatusRequest,
-    GetRingBufferTapInfoRequest, GetStorageConfigRequest, Hdf5Config, Hdf5Structure,
-    ListAcquisitionsRequest, ListAcquisitionsResponse, RecordingProgress, RecordingState,
-    RecordingStatus, RingBufferTapInfo, StartRecordingRequest, StartRecordingResponse,
-    StopRecordingRequest, StopRecordingResponse, StorageConfig, StreamRecordingProgressRequest,
+    AcquisitionInfo, AcquisitionSummary, ChannelHistorySeries, ConfigureStorageRequest,
+    ConfigureStorageResponse, DeleteAcquisitionRequest, DeleteAcquisitionResponse,
+    FlushToStorageRequest, FlushToStorageResponse, GetAcquisitionInfoRequest,
+    GetRecordingStatusRequest, GetRingBufferTapInfoRequest, GetStorageConfigRequest, Hdf5Config,
+    Hdf5Structure, ListAcquisitionsRequest, ListAcquisitionsResponse, QueryChannelHistoryRequest,
+    QueryChannelHistoryResponse, RecordingProgress, RecordingState, RecordingStatus,
+    RingBufferTapInfo, StartRecordingRequest, StartRecordingResponse, StopRecordingRequest,
+    StopRecordingResponse, StorageConfig, StreamRecordingProgressRequest,
     storage_service_server::StorageService,
 };
+use common::core::Measurement;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use storage::channel_history::{ChannelHistory, ChannelHistoryQuery};
+use storage::document_writer::DocumentWriter;
+use storage::hdf5_chunking::ChunkingConfig;
 use storage::hdf5_writer::HDF5Writer;
 use storage::ring_buffer::RingBuffer;
 use tokio::fs;
-use tokio::sync::{Mutex, RwLock, mpsc};
+use tokio::sync::{Mutex, RwLock, broadcast, mpsc};
 use tokio::task::JoinHandle;
 use tonic::{Request, Response, Status};
 use uuid::Uuid;
@@ -197,16 +203,23 @@ struct ActiveWriter {
 }
 
 /// Storage configuration
-struct StorageSettings {
-    output_directory: PathBuf,
-    compression: String,
-    compression_level: u32,
-    chunk_size: u32,
-    filename_pattern: String,
-    include_timestamps: bool,
-    include_device_metadata: bool,
-    flush_interval_ms: u32,
-    max_buffer_mb: u32,
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageSettings {
+    pub output_directory: PathBuf,
+    pub compression: String,
+    pub compression_level: u32,
+    /// Rows per scalar chunk when `auto_chunking` is off
+    pub chunk_size: u32,
+    /// Pick chunk shapes from frame size, rate and compression
+    pub auto_chunking: bool,
+    /// Frame chunk shape (frames, rows, columns) overriding the heuristics
+    pub frame_chunk_shape: Option<[u32; 3]>,
+    pub filename_pattern: String,
+    pub include_timestamps: bool,
+    pub include_device_metadata: bool,
+    pub flush_interval_ms: u32,
+    pub max_buffer_mb: u32,
 }
 
 impl Default for StorageSettings {
@@ -216,6 +229,8 @@ impl Default for StorageSettings {
             compression: "gzip".to_string(),
             compression_level: 4,
             chunk_size: 4096,
+            auto_chunking: true,
+            frame_chunk_shape: None,
             filename_pattern: "{name}_{timestamp}.h5".to_string(),
             include_timestamps: true,
             include_device_metadata: true,
@@ -225,6 +240,21 @@ impl Default for StorageSettings {
     }
 }
 
+impl StorageSettings {
+    /// Chunk shape selection for run files written with these settings
+    pub fn chunking(&self) -> ChunkingConfig {
+        ChunkingConfig {
+            auto: self.auto_chunking,
+            compressed: !matches!(self.compression.as_str(), "" | "none"),
+            frame_chunk: self
+                .frame_chunk_shape
+                .map(|shape| shape.map(|dim| dim as usize)),
+            scalar_chunk_rows: (!self.auto_chunking).then_some(self.chunk_size as usize),
+            ..Default::default()
+        }
+    }
+}
+
 /// StorageService implementation for HDF5 data storage
 pub struct StorageServiceImpl {
     settings: Arc<RwLock<StorageSettings>>,
@@ -232,6 +262,9 @@ pub struct StorageServiceImpl {
     acquisitions: Arc<RwLock<HashMap<String, AcquisitionRecord>>>,
     is_recording: AtomicBool,
     ring_buffer: Option<Arc<RingBuffer>>,
+    channel_history: Option<Arc<ChannelHistory>>,
+    /// Run file writer following the chunking settings
+    document_writer: Option<Arc<DocumentWriter>>,
 }
 
 impl StorageServiceImpl {
@@ -243,7 +276,29 @@ impl StorageServiceImpl {
             acquisitions: Arc::new(RwLock::new(HashMap::new())),
             is_recording: AtomicBool::new(false),
             ring_buffer,
+            channel_history: None,
+            document_writer: None,
+        }
+    }
+
+    /// Apply the chunking settings to the run files written by `writer`
+    pub fn with_document_writer(mut self, writer: Arc<DocumentWriter>) -> Self {
+        if let Ok(settings) = self.settings.try_read() {
+            writer.set_chunking(settings.chunking());
         }
+        self.document_writer = Some(writer);
+        self
+    }
+
+    /// Serve `QueryChannelHistory` from `history`
+    pub fn with_channel_history(mut self, history: Arc<ChannelHistory>) -> Self {
+        self.channel_history = Some(history);
+        self
+    }
+
+    /// Shared handle to the storage settings (used by differential config apply)
+    pub fn settings(&self) -> Arc<RwLock<StorageSettings>> {
+        self.settings.clone()
     }
 
     /// Generate output filename from pattern
@@ -378,6 +433,25 @@ impl StorageService for StorageServiceImpl {
             if let Some(chunk) = hdf5_config.chunk_size {
                 settings.chunk_size = chunk;
             }
+            if let Some(auto) = hdf5_config.auto_chunking {
+                settings.auto_chunking = auto;
+            }
+            match hdf5_config.frame_chunk_shape.as_slice() {
+                [] => {}
+                [0, 0, 0] => settings.frame_chunk_shape = None,
+                &[frames, rows, cols] if frames > 0 && rows > 0 && cols > 0 => {
+                    settings.frame_chunk_shape = Some([frames, rows, cols]);
+                }
+                _ => {
+                    return Ok(Response::new(ConfigureStorageResponse {
+                        success: false,
+                        error_message:
+                            "frame_chunk_shape must be [frames, rows, columns] of positive values"
+                                .to_string(),
+                        resolved_output_directory: String::new(),
+                    }));
+                }
+            }
             if let Some(pattern) = hdf5_config.filename_pattern {
                 settings.filename_pattern = pattern;
             }
@@ -391,6 +465,9 @@ impl StorageService for StorageServiceImpl {
         if let Some(max_mb) = req.max_buffer_mb {
             settings.max_buffer_mb = max_mb;
         }
+        if let Some(writer) = &self.document_writer {
+            writer.set_chunking(settings.chunking());
+        }
 
         Ok(Response::new(ConfigureStorageResponse {
             success: true,
@@ -416,6 +493,11 @@ impl StorageService for StorageServiceImpl {
                 filename_pattern: Some(settings.filename_pattern.clone()),
                 include_timestamps: settings.include_timestamps,
                 include_device_metadata: settings.include_device_metadata,
+                auto_chunking: Some(settings.auto_chunking),
+                frame_chunk_shape: settings
+                    .frame_chunk_shape
+                    .map(|shape| shape.to_vec())
+                    .unwrap_or_default(),
             }),
             flush_interval_ms: settings.flush_interval_ms,
             max_buffer_mb: settings.max_buffer_mb,
@@ -1046,6 +1128,96 @@ impl StorageService for StorageServiceImpl {
             arrow_schema_json,
         }))
     }
+
+    /// Query the rolling on-disk history of scalar channels
+    async fn query_channel_history(
+        &self,
+        request: Request<QueryChannelHistoryRequest>,
+    ) -> Result<Response<QueryChannelHistoryResponse>, Status> {
+        let req = request.into_inner();
+        let history = self
+            .channel_history
+            .clone()
+            .ok_or_else(|| Status::unavailable("Channel history not enabled"))?;
+        let query = ChannelHistoryQuery {
+            channels: req.channels,
+            start_ns: req.start_ns,
+            end_ns: (req.end_ns > 0).then_some(req.end_ns),
+            max_points: req.max_points as usize,
+        };
+
+        // Segment files are read synchronously
+        let (series, oldest_ns, retention) = tokio::task::spawn_blocking(move || {
+            let series = history.query(&query)?;
+            let oldest_ns = history.oldest_ns()?;
+            anyhow::Ok((series, oldest_ns, history.config().retention))
+        })
+        .await
+        .map_err(|e| Status::internal(format!("History query task failed: {}", e)))?
+        .map_err(|e| Status::internal(format!("{:#}", e)))?;
+
+        Ok(Response::new(QueryChannelHistoryResponse {
+            series: series
+                .into_iter()
+                .map(|s| ChannelHistorySeries {
+                    channel: s.channel,
+                    timestamps_ns: s.timestamps_ns,
+                    values: s.values,
+                    quality: s.quality.into_iter().map(|q| q as i32).collect(),
+                    total_points: s.total_points as u64,
+                })
+                .collect(),
+            oldest_ns: oldest_ns.unwrap_or(0),
+            retention_secs: retention.as_secs(),
+        }))
+    }
+}
+
+/// How often recorded history samples are written through to disk
+const CHANNEL_HISTORY_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Record every scalar measurement sent on `rx` into `history`
+///
+/// Runs until the channel closes, flushing to disk every
+/// [`CHANNEL_HISTORY_FLUSH_INTERVAL`] so a crash loses at most that much.
+pub fn spawn_channel_history_recorder(
+    history: Arc<ChannelHistory>,
+    mut rx: broadcast::Receiver<Measurement>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut flush = tokio::time::interval(CHANNEL_HISTORY_FLUSH_INTERVAL);
+        // Throttle write failures (e.g. disk full) to one warning per flush interval
+        let mut failed = 0u64;
+        loop {
+            tokio::select! {
+                received = rx.recv() => match received {
+                    Ok(Measurement::Scalar { name, value, timestamp, quality, .. }) => {
+                        let timestamp_ns = timestamp.timestamp_nanos_opt().unwrap_or(0) as u64;
+                        if history.record(&name, timestamp_ns, value, quality).is_err() {
+                            failed += 1;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!("Channel history lagged, {} measurements not recorded", n);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = flush.tick() => {
+                    if let Err(e) = history.flush() {
+                        tracing::warn!(error = %e, "Failed to flush channel history");
+                    }
+                    if failed > 0 {
+                        tracing::warn!(failed, "Channel history samples could not be recorded");
+                        failed = 0;
+                    }
+                }
+            }
+        }
+        if let Err(e) = history.flush() {
+            tracing::warn!(error = %e, "Failed to flush channel history");
+        }
+    })
 }
 
 #[cfg(test)]
@@ -1053,6 +1225,49 @@ mod tests {
     use super::*;
     use std::sync::Arc;
 
+    #[tokio::test]
+    async fn test_query_channel_history() {
+        let service = StorageServiceImpl::new(None);
+        let status = service
+            .query_channel_history(Request::new(QueryChannelHistoryRequest::default()))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let history = Arc::new(ChannelHistory::open(temp_dir.path(), Default::default()).unwrap());
+        let (tx, rx) = broadcast::channel(16);
+        let recorder = spawn_channel_history_recorder(history.clone(), rx);
+        for i in 0..3 {
+            tx.send(Measurement::Scalar {
+                name: "power".to_string(),
+                value: f64::from(i),
+                unit: "W".to_string(),
+                timestamp: chrono::Utc::now(),
+                quality: common::core::DataQuality::Good,
+            })
+            .unwrap();
+        }
+        drop(tx);
+        recorder.await.unwrap();
+
+        let service = StorageServiceImpl::new(None).with_channel_history(history);
+        let response = service
+            .query_channel_history(Request::new(QueryChannelHistoryRequest {
+                channels: vec!["power".to_string()],
+                max_points: 2,
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.series.len(), 1);
+        assert_eq!(response.series[0].values, [0.0, 2.0]);
+        assert_eq!(response.series[0].total_points, 3);
+        assert!(response.oldest_ns > 0);
+        assert_eq!(response.retention_secs, 24 * 3600);
+    }
+
     #[tokio::test]
     async fn test_configure_storage() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -1069,6 +1284,8 @@ mod tests {
                 filename_pattern: Some("{name}_{datetime}.h5".to_string()),
                 include_timestamps: true,
                 include_device_metadata: false,
+                auto_chunking: Some(false),
+                frame_chunk_shape: vec![1, 256, 256],
             }),
             flush_interval_ms: Some(500),
             max_buffer_mb: Some(128),
@@ -1079,6 +1296,12 @@ mod tests {
 
         assert!(resp.success);
         assert!(resp.error_message.is_empty());
+
+        let chunking = service.settings().read().await.chunking();
+        assert!(!chunking.auto);
+        assert!(chunking.compressed);
+        assert_eq!(chunking.frame_chunk, Some([1, 256, 256]));
+        assert_eq!(chunking.scalar_chunk_rows, Some(8192));
     }
 
     #[tokio::test]
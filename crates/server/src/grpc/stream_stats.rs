@@ -0,0 +1,330 @@
+//! Per-subscriber statistics of server-streaming RPCs
+//!
+//! When latency rises, the question is usually which client is pulling the
+//! most out of the daemon. Every tracked stream registers itself in the
+//! process-wide [`StreamStatsRegistry`] with the client's address, user and
+//! user agent, and counts the messages and encoded bytes it hands to tonic
+//! plus the messages its producer skipped because the client was too slow.
+//!
+//! Handlers wrap their output stream with [`StreamHandle::track`]; the entry
+//! disappears when tonic drops the stream (client disconnect or end of
+//! stream). `ControlService/ListStreamStats` reports all open streams,
+//! busiest first, and the GUI shows the totals in its status bar.
+
+use crate::auth::principal;
+use crate::grpc::proto::StreamStats;
+use std::collections::{BTreeMap, VecDeque};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock, Weak};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio_stream::Stream;
+use tonic::{Request, Status};
+
+/// Interval between rate samples of a stream
+const RATE_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Rates are averaged over about this long
+const RATE_WINDOW: Duration = Duration::from_secs(5);
+
+/// Open streams of the daemon
+#[derive(Debug, Default)]
+pub struct StreamStatsRegistry {
+    streams: RwLock<BTreeMap<u64, Arc<StreamEntry>>>,
+    next_id: AtomicU64,
+}
+
+/// Counters of one open stream
+#[derive(Debug)]
+pub struct StreamEntry {
+    id: u64,
+    method: String,
+    detail: String,
+    peer: String,
+    user: String,
+    user_agent: String,
+    started: Instant,
+    started_at_ns: u64,
+    messages: AtomicU64,
+    bytes: AtomicU64,
+    dropped: AtomicU64,
+    /// (time, messages, bytes) about once per second, for recent rates
+    samples: Mutex<VecDeque<(Instant, u64, u64)>>,
+}
+
+impl StreamStatsRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process-wide registry shared by every service
+    pub fn global() -> Arc<StreamStatsRegistry> {
+        static GLOBAL: OnceLock<Arc<StreamStatsRegistry>> = OnceLock::new();
+        GLOBAL
+            .get_or_init(|| Arc::new(StreamStatsRegistry::new()))
+            .clone()
+    }
+
+    /// Register a stream opened by `request`
+    ///
+    /// `method` names the RPC (`"HardwareService/StreamFrames"`), `detail`
+    /// what the stream follows (a device, a run), if anything.
+    pub fn open<T>(
+        self: &Arc<Self>,
+        method: &str,
+        detail: impl Into<String>,
+        request: &Request<T>,
+    ) -> StreamHandle {
+        let user_agent = request
+            .metadata()
+            .get("user-agent")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let entry = Arc::new(StreamEntry {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed) + 1,
+            method: method.to_string(),
+            detail: detail.into(),
+            peer: request
+                .remote_addr()
+                .map(|addr| addr.to_string())
+                .unwrap_or_default(),
+            user: principal(request).user,
+            user_agent,
+            started: Instant::now(),
+            started_at_ns: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or(0),
+            messages: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            samples: Mutex::new(VecDeque::new()),
+        });
+        self.streams
+            .write()
+            .unwrap_or_else(|p| p.into_inner())
+            .insert(entry.id, entry.clone());
+        tracing::debug!(
+            stream_id = entry.id,
+            method,
+            peer = %entry.peer,
+            user = %entry.user,
+            "Stream opened"
+        );
+        StreamHandle {
+            entry,
+            registry: Arc::downgrade(self),
+        }
+    }
+
+    /// Number of open streams
+    pub fn len(&self) -> usize {
+        self.streams.read().unwrap_or_else(|p| p.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Statistics of every open stream, highest byte rate first
+    pub fn snapshot(&self) -> Vec<StreamStats> {
+        let now = Instant::now();
+        let mut stats: Vec<StreamStats> = self
+            .streams
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .values()
+            .map(|entry| entry.stats(now))
+            .collect();
+        stats.sort_by(|a, b| b.bytes_per_sec.total_cmp(&a.bytes_per_sec));
+        stats
+    }
+
+    fn close(&self, id: u64) {
+        self.streams
+            .write()
+            .unwrap_or_else(|p| p.into_inner())
+            .remove(&id);
+    }
+}
+
+impl StreamEntry {
+    /// Count a message handed to the transport
+    pub fn record_sent(&self, bytes: usize) {
+        let messages = self.messages.fetch_add(1, Ordering::Relaxed) + 1;
+        let bytes = self.bytes.fetch_add(bytes as u64, Ordering::Relaxed) + bytes as u64;
+
+        let now = Instant::now();
+        let mut samples = self.samples.lock().unwrap_or_else(|p| p.into_inner());
+        if samples
+            .back()
+            .is_none_or(|(at, _, _)| now.duration_since(*at) >= RATE_SAMPLE_INTERVAL)
+        {
+            samples.push_back((now, messages, bytes));
+            while samples
+                .front()
+                .is_some_and(|(at, _, _)| now.duration_since(*at) > RATE_WINDOW)
+            {
+                samples.pop_front();
+            }
+        }
+    }
+
+    /// Count messages skipped because the client was not keeping up
+    pub fn record_dropped(&self, count: u64) {
+        self.dropped.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn stats(&self, now: Instant) -> StreamStats {
+        let messages = self.messages.load(Ordering::Relaxed);
+        let bytes = self.bytes.load(Ordering::Relaxed);
+        let (messages_per_sec, bytes_per_sec) = {
+            let samples = self.samples.lock().unwrap_or_else(|p| p.into_inner());
+            // Oldest sample still in the window; an idle stream's rates
+            // decay from its newest one
+            let base = samples
+                .iter()
+                .find(|(at, _, _)| now.duration_since(*at) <= RATE_WINDOW)
+                .or(samples.back());
+            match base {
+                Some((at, base_messages, base_bytes)) => {
+                    let elapsed = now.duration_since(*at).as_secs_f64();
+                    if elapsed > 0.0 {
+                        (
+                            messages.saturating_sub(*base_messages) as f64 / elapsed,
+                            bytes.saturating_sub(*base_bytes) as f64 / elapsed,
+                        )
+                    } else {
+                        (0.0, 0.0)
+                    }
+                }
+                None => (0.0, 0.0),
+            }
+        };
+
+        StreamStats {
+            stream_id: self.id,
+            method: self.method.clone(),
+            peer: self.peer.clone(),
+            user: self.user.clone(),
+            user_agent: self.user_agent.clone(),
+            started_at_ns: self.started_at_ns,
+            uptime_s: now.duration_since(self.started).as_secs_f64(),
+            messages_sent: messages,
+            bytes_sent: bytes,
+            dropped: self.dropped.load(Ordering::Relaxed),
+            messages_per_sec,
+            bytes_per_sec,
+            detail: self.detail.clone(),
+        }
+    }
+}
+
+/// Registration of one open stream; unregisters it when dropped
+#[derive(Debug)]
+pub struct StreamHandle {
+    entry: Arc<StreamEntry>,
+    registry: Weak<StreamStatsRegistry>,
+}
+
+impl StreamHandle {
+    /// Counters for the task producing the stream's messages
+    pub fn entry(&self) -> Arc<StreamEntry> {
+        self.entry.clone()
+    }
+
+    /// Count the messages of `stream` as they are handed to tonic
+    pub fn track<S>(self, stream: S) -> TrackedStream<S> {
+        TrackedStream {
+            inner: stream,
+            handle: self,
+        }
+    }
+}
+
+impl Drop for StreamHandle {
+    fn drop(&mut self) {
+        if let Some(registry) = self.registry.upgrade() {
+            registry.close(self.entry.id);
+        }
+        tracing::debug!(
+            stream_id = self.entry.id,
+            method = %self.entry.method,
+            messages = self.entry.messages.load(Ordering::Relaxed),
+            dropped = self.entry.dropped.load(Ordering::Relaxed),
+            "Stream closed"
+        );
+    }
+}
+
+/// Stream counting the messages it yields
+pub struct TrackedStream<S> {
+    inner: S,
+    handle: StreamHandle,
+}
+
+impl<S, T> Stream for TrackedStream<S>
+where
+    S: Stream<Item = Result<T, Status>> + Unpin,
+    T: prost::Message,
+{
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let poll = Pin::new(&mut self.inner).poll_next(cx);
+        if let Poll::Ready(Some(Ok(message))) = &poll {
+            self.handle.entry.record_sent(message.encoded_len());
+        }
+        poll
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc::proto::ValueUpdate;
+    use tokio_stream::StreamExt;
+
+    #[tokio::test]
+    async fn test_tracked_stream_counts_until_dropped() {
+        let registry = Arc::new(StreamStatsRegistry::new());
+        let mut request = Request::new(());
+        request
+            .metadata_mut()
+            .insert("user-agent", "rust-daq-gui/0.1".parse().unwrap());
+
+        let handle = registry.open("HardwareService/StreamValues", "power_meter", &request);
+        let entry = handle.entry();
+        let update = ValueUpdate {
+            device_id: "power_meter".to_string(),
+            value: 1.5,
+            ..Default::default()
+        };
+        let size = prost::Message::encoded_len(&update);
+        let mut stream = handle.track(tokio_stream::iter(vec![
+            Ok(update.clone()),
+            Err(Status::internal("read failed")),
+            Ok(update),
+        ]));
+        while stream.next().await.is_some() {}
+        entry.record_dropped(3);
+
+        let stats = registry.snapshot();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].messages_sent, 2);
+        assert_eq!(stats[0].bytes_sent, 2 * size as u64);
+        assert_eq!(stats[0].dropped, 3);
+        assert_eq!(stats[0].user, "anonymous");
+        assert_eq!(stats[0].user_agent, "rust-daq-gui/0.1");
+        assert_eq!(stats[0].detail, "power_meter");
+
+        drop(stream);
+        assert!(registry.is_empty());
+    }
+}
@@ -0,0 +1,180 @@
+//! Memory budget watchdog
+//!
+//! Checks the process-wide [`MemoryLedger`] against its budgets on an
+//! interval and reports to the SystemHealthMonitor: a heartbeat with the
+//! accounted total and the daemon's RSS, a warning when a subsystem reaches
+//! its warning fraction and an error (an alarm for clients) when it goes
+//! over budget. Each escalation is reported once; recoveries are logged.
+
+use common::health::{ErrorSeverity, SystemHealthMonitor};
+use common::memory::{BudgetStatus, MemoryBudgetConfig, MemoryLedger, MemorySubsystem};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
+
+const MB: f64 = 1024.0 * 1024.0;
+
+/// Resident set size of this process, in bytes
+pub fn process_rss_bytes(system: &mut System) -> Option<u64> {
+    let pid = sysinfo::get_current_pid().ok()?;
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        false,
+        ProcessRefreshKind::nothing().with_memory(),
+    );
+    system.process(pid).map(sysinfo::Process::memory)
+}
+
+/// Reports memory budget violations to the health monitor
+pub struct MemoryWatchdog {
+    monitor: Arc<SystemHealthMonitor>,
+    check_interval: Duration,
+    system: System,
+    /// Status last reported per subsystem
+    reported: HashMap<MemorySubsystem, BudgetStatus>,
+}
+
+impl MemoryWatchdog {
+    pub fn new(monitor: Arc<SystemHealthMonitor>, config: &MemoryBudgetConfig) -> Self {
+        Self {
+            monitor,
+            check_interval: Duration::from_secs(config.check_interval_secs.max(1)),
+            system: System::new(),
+            reported: HashMap::new(),
+        }
+    }
+
+    /// Check the budgets until the task is dropped
+    pub async fn run(mut self) {
+        let mut interval = tokio::time::interval(self.check_interval);
+        loop {
+            interval.tick().await;
+            self.check().await;
+        }
+    }
+
+    async fn check(&mut self) {
+        let report = MemoryLedger::global().report();
+        let rss = process_rss_bytes(&mut self.system);
+        let status = match rss {
+            Some(rss) => format!(
+                "Accounted: {:.1} MB of {:.1} MB RSS",
+                report.total_bytes as f64 / MB,
+                rss as f64 / MB
+            ),
+            None => format!("Accounted: {:.1} MB", report.total_bytes as f64 / MB),
+        };
+        self.monitor
+            .heartbeat_with_message("memory", Some(status))
+            .await;
+
+        for usage in &report.subsystems {
+            let previous = self
+                .reported
+                .insert(usage.subsystem, usage.status)
+                .unwrap_or(BudgetStatus::Ok);
+            if usage.status <= previous {
+                if usage.status < previous {
+                    tracing::info!(
+                        subsystem = %usage.subsystem,
+                        status = %usage.status,
+                        mb = usage.bytes as f64 / MB,
+                        "Memory usage back within budget"
+                    );
+                }
+                continue;
+            }
+
+            let budget = usage.budget_bytes.unwrap_or_default();
+            let (severity, message) = if usage.status == BudgetStatus::Exceeded {
+                (
+                    ErrorSeverity::Error,
+                    format!(
+                        "{} memory over budget: {:.1} MB of {:.1} MB",
+                        usage.subsystem,
+                        usage.bytes as f64 / MB,
+                        budget as f64 / MB
+                    ),
+                )
+            } else {
+                (
+                    ErrorSeverity::Warning,
+                    format!(
+                        "{} memory near budget: {:.1} MB of {:.1} MB",
+                        usage.subsystem,
+                        usage.bytes as f64 / MB,
+                        budget as f64 / MB
+                    ),
+                )
+            };
+            // Name the largest consumer so the growth can be attributed
+            let largest = usage
+                .accounts
+                .iter()
+                .max_by_key(|(_, bytes)| **bytes)
+                .map(|(name, _)| name.clone())
+                .unwrap_or_default();
+            tracing::warn!(subsystem = %usage.subsystem, largest = %largest, "{}", message);
+            self.monitor
+                .report_error(
+                    "memory",
+                    severity,
+                    message,
+                    [
+                        ("subsystem", usage.subsystem.to_string()),
+                        ("bytes", usage.bytes.to_string()),
+                        ("budget_bytes", budget.to_string()),
+                        ("largest_account", largest),
+                    ],
+                )
+                .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::health::HealthMonitorConfig;
+    use common::memory::SubsystemBudgets;
+
+    #[tokio::test]
+    async fn test_escalations_are_reported_once() {
+        let monitor = Arc::new(SystemHealthMonitor::new(HealthMonitorConfig::default()));
+        let config = MemoryBudgetConfig {
+            budgets_mb: SubsystemBudgets {
+                cache: Some(1),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        MemoryLedger::global().set_budgets(config.clone());
+        let mut watchdog = MemoryWatchdog::new(monitor.clone(), &config);
+        let account = MemoryLedger::global().account("test_cache", MemorySubsystem::Cache);
+
+        account.set(900 * 1024);
+        watchdog.check().await;
+        watchdog.check().await;
+        account.set(2 * 1024 * 1024);
+        watchdog.check().await;
+
+        let errors = monitor.get_module_errors("memory", None).await;
+        let severities: Vec<_> = errors.iter().map(|e| e.severity).collect();
+        assert_eq!(errors.len(), 2, "{:?}", severities);
+        assert!(severities.contains(&ErrorSeverity::Warning));
+        assert!(severities.contains(&ErrorSeverity::Error));
+        assert!(
+            errors
+                .iter()
+                .all(|e| e.context["largest_account"] == "test_cache")
+        );
+        assert!(
+            monitor
+                .get_module_health()
+                .await
+                .iter()
+                .any(|m| m.name == "memory")
+        );
+    }
+}
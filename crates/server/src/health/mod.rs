@@ -1,3 +1,4 @@
+pub mod memory_monitor;
 pub mod sys_monitor;
 
 pub use common::health::{HealthMonitorConfig, SystemHealthMonitor};
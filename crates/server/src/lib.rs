@@ -17,6 +17,7 @@
 //! | `ControlService` | Script upload, validation, and execution |
 //! | `RunEngineService` | Plan execution with pause/resume/abort |
 //! | `PresetService` | Save/load device configuration presets |
+//! | `LibraryService` | Versioned scripts, plans and device configs |
 //!
 //! ## Quick Example
 //!
@@ -29,6 +30,12 @@
 //! server.serve("0.0.0.0:50051").await?;
 //! ```
 //!
+//! ## Embedding
+//!
+//! [`runtime::DaqRuntimeBuilder`] runs the whole daemon (registry, RunEngine,
+//! storage, gRPC server) inside another Rust application, on its tokio runtime
+//! or on a dedicated one.
+//!
 //! ## Feature Flags
 //!
 //! - `server` - Core gRPC server functionality
@@ -52,15 +59,43 @@
 #![allow(clippy::if_same_then_else)]
 #![allow(clippy::io_other_error)]
 
+// Minimal headless profile: heavy backends must stay out of the dependency graph
+#[cfg(all(
+    feature = "minimal",
+    any(
+        feature = "storage_hdf5",
+        feature = "storage_arrow",
+        feature = "rerun_sink",
+        feature = "gpu_preprocessing",
+        feature = "kafka"
+    )
+))]
+compile_error!(
+    "the `minimal` profile excludes HDF5, Arrow, Rerun, GPU preprocessing and Kafka; drop `minimal` or the conflicting feature"
+);
+
+pub mod audit;
+pub mod auth;
+#[cfg(feature = "modules")]
+pub mod config_apply;
+pub mod device_history;
+pub mod document_forwarder;
 pub mod grpc;
 pub mod health;
 #[cfg(feature = "modules")]
 pub mod modules;
+pub mod preferences;
 #[cfg(feature = "rerun_sink")]
 pub mod rerun_sink;
+#[cfg(feature = "server")]
+pub mod runtime;
+pub mod simulator;
+pub mod webhooks;
 
 #[cfg(feature = "server")]
 pub use grpc::server::DaqServer;
+#[cfg(feature = "server")]
+pub use runtime::{DaqRuntime, DaqRuntimeBuilder};
 
 // Re-export Rerun types for server configuration
 #[cfg(feature = "rerun_sink")]
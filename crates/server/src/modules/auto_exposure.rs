@@ -0,0 +1,975 @@
+//! AutoExposure Module
+//!
+//! Watches a camera's frames and keeps the bright end of the histogram at a
+//! target level by adjusting exposure (or a gain parameter) between frames,
+//! within configured bounds. Every change is emitted as an event so analysis
+//! can account for it.
+//!
+//! # Control Loop
+//!
+//! For each evaluated frame the module computes the `percentile` pixel value
+//! as a fraction of full scale (from the frame's bit depth) and the fraction
+//! of saturated pixels:
+//!
+//! - more than `max_saturation` saturated pixels: halve the setting;
+//! - otherwise scale the setting by `target_level / level`, limited to
+//!   `max_step` per change, unless the level is within `tolerance` of the
+//!   target.
+//!
+//! Signal is assumed to be linear in exposure and gain. After a change the
+//! next `settle_frames` frames are skipped, since they may have been exposed
+//! with the old setting. The module only watches frames; start the camera
+//! stream separately.
+//!
+//! # Roles
+//!
+//! | Role ID | Required Capability | Description |
+//! |---------|---------------------|-------------|
+//! | `camera` | `FrameProducer` | Camera to control; needs `ExposureControl` when `control = exposure` |
+//!
+//! # Parameters
+//!
+//! | Parameter | Type | Default | Units | Description |
+//! |-----------|------|---------|-------|-------------|
+//! | `control` | enum | exposure | - | Setting to adjust: `exposure` or `gain` |
+//! | `gain_parameter` | string | gain | - | Camera parameter adjusted when `control = gain` |
+//! | `target_level` | float | 0.6 | - | Target percentile level as a fraction of full scale |
+//! | `percentile` | float | 99.0 | % | Histogram percentile that is controlled |
+//! | `max_saturation` | float | 0.001 | - | Saturated pixel fraction that forces a cut |
+//! | `tolerance` | float | 0.1 | - | Relative deadband around the target |
+//! | `max_step` | float | 2.0 | - | Largest factor applied in one change |
+//! | `min_exposure_ms` | float | 0.1 | ms | Lower exposure bound |
+//! | `max_exposure_ms` | float | 1000.0 | ms | Upper exposure bound |
+//! | `min_gain` | float | 1.0 | - | Lower gain bound |
+//! | `max_gain` | float | 16.0 | - | Upper gain bound |
+//! | `settle_frames` | int | 2 | frames | Frames skipped after a change |
+//!
+//! # Events
+//!
+//! - `setting_changed` - Exposure or gain was changed (data: `old`, `new`, `level`, `saturated_fraction`)
+//! - `limit_reached` - The target can't be reached within the bounds
+//! - `control_error` - The setting could not be read or written
+//!
+//! # Data Types
+//!
+//! - `exposure_stats` - Per evaluated frame: `{level, saturated_fraction, setting}`
+
+use super::{Module, ModuleContext};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use common::data::FrameView;
+use common::limits::SHUTDOWN_TIMEOUT;
+use common::modules::{
+    ModuleEventSeverity, ModuleParameter, ModuleRole, ModuleState, ModuleTypeInfo,
+};
+use common::observable::ParameterBase;
+use hardware::capabilities::{
+    ExposureControl, FrameObserver, FrameProducer, ObserverHandle, Parameterized,
+};
+use hardware::registry::DeviceRegistry;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// Histogram resolution used for the percentile
+const HISTOGRAM_BINS: usize = 1024;
+
+/// Setting adjusted by the controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlTarget {
+    Exposure,
+    Gain,
+}
+
+impl ControlTarget {
+    fn as_str(self) -> &'static str {
+        match self {
+            ControlTarget::Exposure => "exposure",
+            ControlTarget::Gain => "gain",
+        }
+    }
+}
+
+/// AutoExposure module configuration
+#[derive(Debug, Clone)]
+pub struct AutoExposureConfig {
+    pub control: ControlTarget,
+    /// Camera parameter adjusted when `control` is gain
+    pub gain_parameter: String,
+    /// Target percentile level as a fraction of full scale
+    pub target_level: f64,
+    /// Histogram percentile that is controlled (0-100)
+    pub percentile: f64,
+    /// Saturated pixel fraction above which the setting is halved
+    pub max_saturation: f64,
+    /// Relative deadband around the target
+    pub tolerance: f64,
+    /// Largest factor applied in one change
+    pub max_step: f64,
+    pub min_exposure_ms: f64,
+    pub max_exposure_ms: f64,
+    pub min_gain: f64,
+    pub max_gain: f64,
+    /// Frames skipped after a change
+    pub settle_frames: u32,
+}
+
+impl Default for AutoExposureConfig {
+    fn default() -> Self {
+        Self {
+            control: ControlTarget::Exposure,
+            gain_parameter: "gain".to_string(),
+            target_level: 0.6,
+            percentile: 99.0,
+            max_saturation: 0.001,
+            tolerance: 0.1,
+            max_step: 2.0,
+            min_exposure_ms: 0.1,
+            max_exposure_ms: 1000.0,
+            min_gain: 1.0,
+            max_gain: 16.0,
+            settle_frames: 2,
+        }
+    }
+}
+
+impl AutoExposureConfig {
+    /// Bounds of the controlled setting (exposure in ms, or gain)
+    fn bounds(&self) -> (f64, f64) {
+        match self.control {
+            ControlTarget::Exposure => (self.min_exposure_ms, self.max_exposure_ms),
+            ControlTarget::Gain => (self.min_gain, self.max_gain),
+        }
+    }
+
+    /// New value for the setting after a frame, or `None` to keep it
+    ///
+    /// The result is within the bounds. `None` is also returned when the
+    /// setting is already pinned at the bound it would move towards.
+    pub fn next_setting(&self, current: f64, stats: &ExposureStats) -> Option<f64> {
+        let factor = if stats.saturated_fraction > self.max_saturation {
+            0.5
+        } else if stats.level <= 0.0 {
+            self.max_step
+        } else {
+            let ratio = self.target_level / stats.level;
+            if (ratio - 1.0).abs() <= self.tolerance {
+                return None;
+            }
+            ratio.clamp(1.0 / self.max_step, self.max_step)
+        };
+
+        let (min, max) = self.bounds();
+        let next = (current * factor).clamp(min, max);
+        ((next - current).abs() > current.abs() * 1e-6).then_some(next)
+    }
+}
+
+/// Brightness statistics of one frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExposureStats {
+    /// Percentile pixel value as a fraction of full scale
+    pub level: f64,
+    /// Fraction of pixels at full scale
+    pub saturated_fraction: f64,
+}
+
+impl ExposureStats {
+    /// Statistics of raw pixels, or `None` for empty frames and unsupported bit depths
+    ///
+    /// Pixels deeper than 8 bits are little-endian `u16`.
+    pub fn from_pixels(pixels: &[u8], bit_depth: u32, percentile: f64) -> Option<Self> {
+        let full_scale = match bit_depth {
+            1..=16 => (1u32 << bit_depth) - 1,
+            _ => return None,
+        };
+        let mut histogram = vec![0u64; HISTOGRAM_BINS];
+        let mut saturated = 0u64;
+        let mut count = 0u64;
+        let mut add = |value: u32| {
+            let value = value.min(full_scale);
+            let bin =
+                (u64::from(value) * (HISTOGRAM_BINS as u64 - 1) / u64::from(full_scale)) as usize;
+            histogram[bin] += 1;
+            saturated += u64::from(value == full_scale);
+            count += 1;
+        };
+        if bit_depth <= 8 {
+            for &v in pixels {
+                add(u32::from(v));
+            }
+        } else {
+            for b in pixels.chunks_exact(2) {
+                add(u32::from(u16::from_le_bytes([b[0], b[1]])));
+            }
+        }
+        if count == 0 {
+            return None;
+        }
+
+        let rank = ((percentile / 100.0) * count as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        let bin = histogram
+            .iter()
+            .position(|&n| {
+                seen += n;
+                seen >= rank
+            })
+            .unwrap_or(HISTOGRAM_BINS - 1);
+        Some(Self {
+            level: bin as f64 / (HISTOGRAM_BINS - 1) as f64,
+            saturated_fraction: saturated as f64 / count as f64,
+        })
+    }
+}
+
+/// Frame copied out of the driver loop for evaluation
+struct FrameSample {
+    pixels: Vec<u8>,
+    bit_depth: u32,
+    frame_number: u64,
+}
+
+/// Hands frames to the control task, one at a time
+struct SampleObserver {
+    tx: mpsc::Sender<FrameSample>,
+    /// Frames still to skip after a change
+    skip: Arc<AtomicU32>,
+}
+
+impl FrameObserver for SampleObserver {
+    fn on_frame(&self, frame: &FrameView<'_>) {
+        if self
+            .skip
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+            .is_ok()
+        {
+            return;
+        }
+        // Only copy when the task is waiting for a frame
+        if self.tx.capacity() == 0 {
+            return;
+        }
+        let _ = self.tx.try_send(FrameSample {
+            pixels: frame.pixels().to_vec(),
+            bit_depth: frame.bit_depth,
+            frame_number: frame.frame_number,
+        });
+    }
+
+    fn name(&self) -> &'static str {
+        "auto_exposure"
+    }
+}
+
+/// Camera interfaces used by the control loop
+struct Camera {
+    device_id: String,
+    registry: Arc<DeviceRegistry>,
+    exposure: Option<Arc<dyn ExposureControl>>,
+    parameters: Option<Arc<dyn Parameterized>>,
+}
+
+impl Camera {
+    /// Current setting (exposure in ms, or gain)
+    async fn read(&self, config: &AutoExposureConfig) -> Result<f64> {
+        match config.control {
+            ControlTarget::Exposure => {
+                let exposure = self
+                    .exposure
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("Camera has no exposure control"))?;
+                Ok(exposure.get_exposure().await? * 1000.0)
+            }
+            ControlTarget::Gain => self
+                .gain_parameter(config)?
+                .get_json()?
+                .as_f64()
+                .ok_or_else(|| anyhow!("Parameter '{}' is not numeric", config.gain_parameter)),
+        }
+    }
+
+    async fn write(&self, config: &AutoExposureConfig, value: f64) -> Result<()> {
+        match config.control {
+            ControlTarget::Exposure => {
+                let exposure = self
+                    .exposure
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("Camera has no exposure control"))?;
+                exposure.set_exposure(value / 1000.0).await
+            }
+            ControlTarget::Gain => {
+                self.registry
+                    .set_parameter_json(
+                        &self.device_id,
+                        self.gain_parameter(config)?,
+                        serde_json::json!(value),
+                    )
+                    .await
+            }
+        }
+    }
+
+    fn gain_parameter<'a>(&'a self, config: &AutoExposureConfig) -> Result<&'a dyn ParameterBase> {
+        self.parameters
+            .as_ref()
+            .and_then(|p| p.parameters().get(&config.gain_parameter))
+            .ok_or_else(|| anyhow!("Camera has no '{}' parameter", config.gain_parameter))
+    }
+}
+
+/// AutoExposure module
+pub struct AutoExposure {
+    config: AutoExposureConfig,
+    state: ModuleState,
+    running: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    task_handle: Option<tokio::task::JoinHandle<()>>,
+    /// Camera and handle of the registered frame observer
+    observer: Option<(Arc<dyn FrameProducer>, ObserverHandle)>,
+}
+
+impl std::fmt::Debug for AutoExposure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AutoExposure")
+            .field("config", &self.config)
+            .field("state", &self.state)
+            .field("running", &self.running.load(Ordering::Relaxed))
+            .field("paused", &self.paused.load(Ordering::Relaxed))
+            .field("task_handle", &self.task_handle.is_some())
+            .field(
+                "observer",
+                &self.observer.as_ref().map(|(_, handle)| handle),
+            )
+            .finish()
+    }
+}
+
+impl Default for AutoExposure {
+    fn default() -> Self {
+        Self {
+            config: AutoExposureConfig::default(),
+            state: ModuleState::Created,
+            running: Arc::new(AtomicBool::new(false)),
+            paused: Arc::new(AtomicBool::new(false)),
+            task_handle: None,
+            observer: None,
+        }
+    }
+}
+
+/// Build a float parameter description
+fn float_parameter(
+    param_id: &str,
+    display_name: &str,
+    description: &str,
+    default_value: f64,
+    range: RangeInclusive<f64>,
+    units: &str,
+) -> ModuleParameter {
+    ModuleParameter {
+        param_id: param_id.to_string(),
+        display_name: display_name.to_string(),
+        description: description.to_string(),
+        param_type: "float".to_string(),
+        default_value: default_value.to_string(),
+        min_value: Some(range.start().to_string()),
+        max_value: Some(range.end().to_string()),
+        enum_values: vec![],
+        units: units.to_string(),
+        required: false,
+    }
+}
+
+/// Parse a float parameter, clamping it into `range`
+fn parse_float(
+    params: &HashMap<String, String>,
+    key: &str,
+    range: RangeInclusive<f64>,
+    value: &mut f64,
+    warnings: &mut Vec<String>,
+) {
+    let Some(val) = params.get(key) else {
+        return;
+    };
+    match val.parse::<f64>() {
+        Ok(parsed) if range.contains(&parsed) => *value = parsed,
+        Ok(parsed) if !parsed.is_nan() => {
+            *value = parsed.clamp(*range.start(), *range.end());
+            warnings.push(format!("{} clamped to {}", key, value));
+        }
+        _ => warnings.push(format!("Invalid {}: {}", key, val)),
+    }
+}
+
+const TARGET_LEVEL_RANGE: RangeInclusive<f64> = 0.05..=0.95;
+const PERCENTILE_RANGE: RangeInclusive<f64> = 50.0..=100.0;
+const MAX_SATURATION_RANGE: RangeInclusive<f64> = 0.0..=0.5;
+const TOLERANCE_RANGE: RangeInclusive<f64> = 0.01..=0.5;
+const MAX_STEP_RANGE: RangeInclusive<f64> = 1.1..=10.0;
+const EXPOSURE_MS_RANGE: RangeInclusive<f64> = 0.001..=600_000.0;
+const GAIN_RANGE: RangeInclusive<f64> = 0.0..=10_000.0;
+
+#[async_trait]
+impl Module for AutoExposure {
+    fn type_info() -> ModuleTypeInfo {
+        ModuleTypeInfo {
+            type_id: "auto_exposure".to_string(),
+            display_name: "Auto Exposure".to_string(),
+            description: "Adjusts camera exposure or gain between frames to keep the \
+                          histogram at a target level"
+                .to_string(),
+            version: "1.0.0".to_string(),
+            required_roles: vec![ModuleRole {
+                role_id: "camera".to_string(),
+                display_name: "Camera".to_string(),
+                description: "Camera whose exposure or gain is controlled".to_string(),
+                required_capability: "frame_producer".to_string(),
+                allows_multiple: false,
+            }],
+            optional_roles: vec![],
+            parameters: vec![
+                ModuleParameter {
+                    param_id: "control".to_string(),
+                    display_name: "Control".to_string(),
+                    description: "Camera setting to adjust".to_string(),
+                    param_type: "enum".to_string(),
+                    default_value: "exposure".to_string(),
+                    min_value: None,
+                    max_value: None,
+                    enum_values: vec!["exposure".to_string(), "gain".to_string()],
+                    units: String::new(),
+                    required: false,
+                },
+                ModuleParameter {
+                    param_id: "gain_parameter".to_string(),
+                    display_name: "Gain Parameter".to_string(),
+                    description: "Camera parameter adjusted when control is gain".to_string(),
+                    param_type: "string".to_string(),
+                    default_value: "gain".to_string(),
+                    min_value: None,
+                    max_value: None,
+                    enum_values: vec![],
+                    units: String::new(),
+                    required: false,
+                },
+                float_parameter(
+                    "target_level",
+                    "Target Level",
+                    "Target percentile level as a fraction of full scale",
+                    0.6,
+                    TARGET_LEVEL_RANGE,
+                    "",
+                ),
+                float_parameter(
+                    "percentile",
+                    "Percentile",
+                    "Histogram percentile that is controlled",
+                    99.0,
+                    PERCENTILE_RANGE,
+                    "%",
+                ),
+                float_parameter(
+                    "max_saturation",
+                    "Max Saturation",
+                    "Saturated pixel fraction that forces a cut",
+                    0.001,
+                    MAX_SATURATION_RANGE,
+                    "",
+                ),
+                float_parameter(
+                    "tolerance",
+                    "Tolerance",
+                    "Relative deadband around the target level",
+                    0.1,
+                    TOLERANCE_RANGE,
+                    "",
+                ),
+                float_parameter(
+                    "max_step",
+                    "Max Step",
+                    "Largest factor applied in one change",
+                    2.0,
+                    MAX_STEP_RANGE,
+                    "",
+                ),
+                float_parameter(
+                    "min_exposure_ms",
+                    "Min Exposure",
+                    "Lower exposure bound",
+                    0.1,
+                    EXPOSURE_MS_RANGE,
+                    "ms",
+                ),
+                float_parameter(
+                    "max_exposure_ms",
+                    "Max Exposure",
+                    "Upper exposure bound",
+                    1000.0,
+                    EXPOSURE_MS_RANGE,
+                    "ms",
+                ),
+                float_parameter(
+                    "min_gain",
+                    "Min Gain",
+                    "Lower gain bound",
+                    1.0,
+                    GAIN_RANGE,
+                    "",
+                ),
+                float_parameter(
+                    "max_gain",
+                    "Max Gain",
+                    "Upper gain bound",
+                    16.0,
+                    GAIN_RANGE,
+                    "",
+                ),
+                ModuleParameter {
+                    param_id: "settle_frames".to_string(),
+                    display_name: "Settle Frames".to_string(),
+                    description: "Frames skipped after a change".to_string(),
+                    param_type: "int".to_string(),
+                    default_value: "2".to_string(),
+                    min_value: Some("0".to_string()),
+                    max_value: Some("100".to_string()),
+                    enum_values: vec![],
+                    units: "frames".to_string(),
+                    required: false,
+                },
+            ],
+            event_types: vec![
+                "setting_changed".to_string(),
+                "limit_reached".to_string(),
+                "control_error".to_string(),
+            ],
+            data_types: vec!["exposure_stats".to_string()],
+            config_schema: None,
+        }
+    }
+
+    fn type_id(&self) -> &str {
+        "auto_exposure"
+    }
+
+    fn configure(&mut self, params: HashMap<String, String>) -> Result<Vec<String>> {
+        let mut warnings = Vec::new();
+        let mut config = self.config.clone();
+
+        if let Some(val) = params.get("control") {
+            match val.trim() {
+                "exposure" => config.control = ControlTarget::Exposure,
+                "gain" => config.control = ControlTarget::Gain,
+                _ => warnings.push(format!("Invalid control: {}", val)),
+            }
+        }
+        if let Some(val) = params.get("gain_parameter") {
+            if val.trim().is_empty() {
+                warnings.push("gain_parameter must not be empty".to_string());
+            } else {
+                config.gain_parameter = val.trim().to_string();
+            }
+        }
+
+        parse_float(
+            &params,
+            "target_level",
+            TARGET_LEVEL_RANGE,
+            &mut config.target_level,
+            &mut warnings,
+        );
+        parse_float(
+            &params,
+            "percentile",
+            PERCENTILE_RANGE,
+            &mut config.percentile,
+            &mut warnings,
+        );
+        parse_float(
+            &params,
+            "max_saturation",
+            MAX_SATURATION_RANGE,
+            &mut config.max_saturation,
+            &mut warnings,
+        );
+        parse_float(
+            &params,
+            "tolerance",
+            TOLERANCE_RANGE,
+            &mut config.tolerance,
+            &mut warnings,
+        );
+        parse_float(
+            &params,
+            "max_step",
+            MAX_STEP_RANGE,
+            &mut config.max_step,
+            &mut warnings,
+        );
+        parse_float(
+            &params,
+            "min_exposure_ms",
+            EXPOSURE_MS_RANGE,
+            &mut config.min_exposure_ms,
+            &mut warnings,
+        );
+        parse_float(
+            &params,
+            "max_exposure_ms",
+            EXPOSURE_MS_RANGE,
+            &mut config.max_exposure_ms,
+            &mut warnings,
+        );
+        parse_float(
+            &params,
+            "min_gain",
+            GAIN_RANGE,
+            &mut config.min_gain,
+            &mut warnings,
+        );
+        parse_float(
+            &params,
+            "max_gain",
+            GAIN_RANGE,
+            &mut config.max_gain,
+            &mut warnings,
+        );
+
+        if let Some(val) = params.get("settle_frames") {
+            match val.parse::<u32>() {
+                Ok(frames) => config.settle_frames = frames.min(100),
+                Err(_) => warnings.push(format!("Invalid settle_frames: {}", val)),
+            }
+        }
+
+        if config.min_exposure_ms > config.max_exposure_ms {
+            return Err(anyhow!(
+                "min_exposure_ms ({}) is above max_exposure_ms ({})",
+                config.min_exposure_ms,
+                config.max_exposure_ms
+            ));
+        }
+        if config.min_gain > config.max_gain {
+            return Err(anyhow!(
+                "min_gain ({}) is above max_gain ({})",
+                config.min_gain,
+                config.max_gain
+            ));
+        }
+
+        self.config = config;
+        self.state = ModuleState::Configured;
+        Ok(warnings)
+    }
+
+    fn get_config(&self) -> HashMap<String, String> {
+        let config = &self.config;
+        HashMap::from([
+            ("control".to_string(), config.control.as_str().to_string()),
+            ("gain_parameter".to_string(), config.gain_parameter.clone()),
+            ("target_level".to_string(), config.target_level.to_string()),
+            ("percentile".to_string(), config.percentile.to_string()),
+            (
+                "max_saturation".to_string(),
+                config.max_saturation.to_string(),
+            ),
+            ("tolerance".to_string(), config.tolerance.to_string()),
+            ("max_step".to_string(), config.max_step.to_string()),
+            (
+                "min_exposure_ms".to_string(),
+                config.min_exposure_ms.to_string(),
+            ),
+            (
+                "max_exposure_ms".to_string(),
+                config.max_exposure_ms.to_string(),
+            ),
+            ("min_gain".to_string(), config.min_gain.to_string()),
+            ("max_gain".to_string(), config.max_gain.to_string()),
+            (
+                "settle_frames".to_string(),
+                config.settle_frames.to_string(),
+            ),
+        ])
+    }
+
+    async fn start(&mut self, ctx: ModuleContext) -> Result<()> {
+        if self.state == ModuleState::Running {
+            return Err(anyhow!("Module is already running"));
+        }
+
+        let producer = ctx
+            .get_frame_producer("camera")
+            .ok_or_else(|| anyhow!("No camera assigned"))?;
+        if !producer.supports_observers() {
+            return Err(anyhow!("Camera does not support frame observers"));
+        }
+        let camera = Camera {
+            device_id: ctx.device_id("camera").unwrap_or_default().to_string(),
+            registry: ctx.registry().clone(),
+            exposure: ctx.get_exposure_control("camera"),
+            parameters: ctx.get_parameterized("camera"),
+        };
+        // Fail early on a camera that lacks the controlled setting
+        camera.read(&self.config).await?;
+
+        let (tx, sample_rx) = mpsc::channel(1);
+        let skip = Arc::new(AtomicU32::new(0));
+        let observer = SampleObserver {
+            tx,
+            skip: Arc::clone(&skip),
+        };
+        let handle = producer.register_observer(Box::new(observer)).await?;
+        self.observer = Some((producer, handle));
+
+        self.running.store(true, Ordering::SeqCst);
+        self.paused.store(false, Ordering::SeqCst);
+        self.state = ModuleState::Running;
+
+        let config = self.config.clone();
+        let running = Arc::clone(&self.running);
+        let paused = Arc::clone(&self.paused);
+
+        let handle = tokio::spawn(async move {
+            auto_exposure_task(ctx, config, running, paused, camera, sample_rx, skip).await;
+        });
+
+        self.task_handle = Some(handle);
+        info!("AutoExposure started");
+        Ok(())
+    }
+
+    async fn pause(&mut self) -> Result<()> {
+        if self.state != ModuleState::Running {
+            return Err(anyhow!("Module is not running"));
+        }
+
+        self.paused.store(true, Ordering::SeqCst);
+        self.state = ModuleState::Paused;
+        info!("AutoExposure paused");
+        Ok(())
+    }
+
+    async fn resume(&mut self) -> Result<()> {
+        if self.state != ModuleState::Paused {
+            return Err(anyhow!("Module is not paused"));
+        }
+
+        self.paused.store(false, Ordering::SeqCst);
+        self.state = ModuleState::Running;
+        info!("AutoExposure resumed");
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        if self.state != ModuleState::Running && self.state != ModuleState::Paused {
+            return Err(anyhow!("Module is not running"));
+        }
+
+        self.running.store(false, Ordering::SeqCst);
+
+        if let Some(handle) = self.task_handle.take() {
+            handle.abort();
+            tokio::time::timeout(SHUTDOWN_TIMEOUT, handle).await.ok();
+        }
+        if let Some((producer, handle)) = self.observer.take()
+            && let Err(e) = producer.unregister_observer(handle).await
+        {
+            warn!("Failed to unregister frame observer: {}", e);
+        }
+
+        self.state = ModuleState::Stopped;
+        info!("AutoExposure stopped");
+        Ok(())
+    }
+
+    fn state(&self) -> ModuleState {
+        self.state
+    }
+}
+
+/// Main control task
+async fn auto_exposure_task(
+    mut ctx: ModuleContext,
+    config: AutoExposureConfig,
+    running: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    camera: Camera,
+    mut sample_rx: mpsc::Receiver<FrameSample>,
+    skip: Arc<AtomicU32>,
+) {
+    let control = config.control.as_str();
+    let mut at_limit = false;
+
+    info!(
+        "AutoExposure task started: control={}, target={:.2} at p{}",
+        control, config.target_level, config.percentile
+    );
+
+    while running.load(Ordering::SeqCst) {
+        let Some(frame) = sample_rx.recv().await else {
+            break;
+        };
+
+        if ctx.is_shutdown_requested() {
+            break;
+        }
+        if paused.load(Ordering::SeqCst) {
+            continue;
+        }
+        let Some(stats) =
+            ExposureStats::from_pixels(&frame.pixels, frame.bit_depth, config.percentile)
+        else {
+            continue;
+        };
+
+        let current = match camera.read(&config).await {
+            Ok(value) => value,
+            Err(e) => {
+                warn!("Failed to read {}: {}", control, e);
+                ctx.emit_event(
+                    "control_error",
+                    ModuleEventSeverity::Warning,
+                    &format!("Failed to read {}: {}", control, e),
+                )
+                .await;
+                continue;
+            }
+        };
+        ctx.emit_data(
+            "exposure_stats",
+            HashMap::from([
+                ("level".to_string(), stats.level),
+                ("saturated_fraction".to_string(), stats.saturated_fraction),
+                ("setting".to_string(), current),
+            ]),
+        )
+        .await;
+
+        let Some(next) = config.next_setting(current, &stats) else {
+            let off_target = stats.saturated_fraction > config.max_saturation
+                || (config.target_level / stats.level.max(f64::MIN_POSITIVE) - 1.0).abs()
+                    > config.tolerance;
+            if off_target && !at_limit {
+                ctx.emit_event(
+                    "limit_reached",
+                    ModuleEventSeverity::Warning,
+                    &format!(
+                        "Target level can't be reached: {} is at its bound ({})",
+                        control, current
+                    ),
+                )
+                .await;
+            }
+            at_limit = off_target;
+            continue;
+        };
+        at_limit = false;
+
+        if let Err(e) = camera.write(&config, next).await {
+            warn!("Failed to set {}: {}", control, e);
+            ctx.emit_event(
+                "control_error",
+                ModuleEventSeverity::Warning,
+                &format!("Failed to set {} to {}: {}", control, next, e),
+            )
+            .await;
+            continue;
+        }
+        // A frame queued while the setting changed was exposed with the old one
+        skip.store(config.settle_frames, Ordering::Relaxed);
+        while sample_rx.try_recv().is_ok() {}
+        ctx.emit_event_with_data(
+            "setting_changed",
+            ModuleEventSeverity::Info,
+            &format!("{} changed from {:.4} to {:.4}", control, current, next),
+            HashMap::from([
+                ("setting".to_string(), control.to_string()),
+                ("old".to_string(), current.to_string()),
+                ("new".to_string(), next.to_string()),
+                ("level".to_string(), stats.level.to_string()),
+                (
+                    "saturated_fraction".to_string(),
+                    stats.saturated_fraction.to_string(),
+                ),
+                ("frame_number".to_string(), frame.frame_number.to_string()),
+            ]),
+        )
+        .await;
+    }
+
+    info!("AutoExposure task ended");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(pixels: &[u16], percentile: f64) -> Option<ExposureStats> {
+        let bytes: Vec<u8> = pixels.iter().flat_map(|p| p.to_le_bytes()).collect();
+        ExposureStats::from_pixels(&bytes, 16, percentile)
+    }
+
+    #[test]
+    fn test_exposure_stats() {
+        // 16-bit frame, half the pixels at ~25% and one saturated pixel
+        let mut pixels = vec![16_384u16; 50];
+        pixels.extend(vec![100u16; 49]);
+        pixels.push(u16::MAX);
+        let p99 = stats(&pixels, 99.0).unwrap();
+        assert!((p99.level - 0.25).abs() < 0.01);
+        assert!((p99.saturated_fraction - 0.01).abs() < 1e-9);
+
+        assert!((stats(&pixels, 100.0).unwrap().level - 1.0).abs() < 1e-9);
+        assert!(stats(&[], 99.0).is_none());
+        assert!(
+            ExposureStats::from_pixels(&[255, 0], 8, 100.0)
+                .unwrap()
+                .level
+                > 0.99
+        );
+    }
+
+    #[test]
+    fn test_next_setting() {
+        let config = AutoExposureConfig::default();
+        let stats = |level, saturated_fraction| ExposureStats {
+            level,
+            saturated_fraction,
+        };
+
+        // Too dark: scale up, limited to max_step
+        assert!((config.next_setting(10.0, &stats(0.3, 0.0)).unwrap() - 20.0).abs() < 1e-9);
+        assert!((config.next_setting(10.0, &stats(0.0, 0.0)).unwrap() - 20.0).abs() < 1e-9);
+        // Slightly bright: scale down proportionally
+        assert!((config.next_setting(10.0, &stats(0.8, 0.0)).unwrap() - 7.5).abs() < 1e-9);
+        // Saturated: halve regardless of the level
+        assert!((config.next_setting(10.0, &stats(0.5, 0.05)).unwrap() - 5.0).abs() < 1e-9);
+        // Within tolerance
+        assert!(config.next_setting(10.0, &stats(0.62, 0.0)).is_none());
+        // Bounded, and no change once pinned at the bound
+        assert!((config.next_setting(800.0, &stats(0.1, 0.0)).unwrap() - 1000.0).abs() < 1e-9);
+        assert!(config.next_setting(1000.0, &stats(0.1, 0.0)).is_none());
+    }
+
+    #[test]
+    fn test_config_parsing() {
+        let mut module = AutoExposure::default();
+        let params = HashMap::from([
+            ("control".to_string(), "gain".to_string()),
+            ("target_level".to_string(), "2.0".to_string()),
+            ("max_gain".to_string(), "8".to_string()),
+        ]);
+        let warnings = module.configure(params).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(module.config.control, ControlTarget::Gain);
+        assert!((module.config.target_level - 0.95).abs() < f64::EPSILON);
+        assert_eq!(module.config.bounds(), (1.0, 8.0));
+        assert_eq!(module.get_config()["control"], "gain");
+
+        let params = HashMap::from([("min_gain".to_string(), "10".to_string())]);
+        assert!(module.configure(params).is_err());
+    }
+}
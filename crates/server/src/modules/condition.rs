@@ -0,0 +1,480 @@
+//! Expression conditions over module channels
+//!
+//! Alarm conditions are evalexpr expressions over named channels, with
+//! windowed aggregates and an optional hold time:
+//!
+//! ```text
+//! mean(power, 10s) < 0.8 * setpoint for 30s
+//! max(reference, 500ms) - min(reference, 500ms) > 0.05 || power > 120
+//! ```
+//!
+//! - A bare channel name is the channel's latest value.
+//! - `mean`, `min`, `max`, `std` and `delta` take a channel and a window
+//!   (`ms`, `s`, `m`/`min`, `h`) and aggregate the samples received in it.
+//!   With any other arguments they are evalexpr's own functions.
+//! - A trailing `for <duration>` requires the expression to stay true that
+//!   long before the condition becomes active.
+//!
+//! The expression is parsed once by [`Condition::compile`]. Samples are
+//! pushed into rolling per-window buffers as they arrive, keeping running
+//! sums and, for `min`/`max`, a monotonic queue of candidates, so
+//! [`Condition::evaluate`] costs one walk of the expression tree however
+//! long the windows are. Until every referenced channel has data the
+//! expression counts as false.
+
+use anyhow::{Result, anyhow};
+use evalexpr::{ContextWithMutableVariables, HashMapContext, Node, Value};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Prefix of the variables standing in for window aggregates
+const WINDOW_VARIABLE_PREFIX: &str = "__window_";
+
+/// Aggregate computed over a window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Aggregate {
+    Mean,
+    Min,
+    Max,
+    Std,
+    /// Newest minus oldest sample
+    Delta,
+}
+
+impl Aggregate {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "mean" => Some(Self::Mean),
+            "min" => Some(Self::Min),
+            "max" => Some(Self::Max),
+            "std" => Some(Self::Std),
+            "delta" => Some(Self::Delta),
+            _ => None,
+        }
+    }
+}
+
+/// Rolling buffer of one channel's samples over a time span
+#[derive(Debug, Clone)]
+struct Window {
+    channel: String,
+    aggregate: Aggregate,
+    span: Duration,
+    samples: VecDeque<(Instant, f64)>,
+    sum: f64,
+    sum_sq: f64,
+    /// For min/max: the samples no later sample beats, best first
+    extremes: VecDeque<(Instant, f64)>,
+}
+
+impl Window {
+    fn new(channel: String, aggregate: Aggregate, span: Duration) -> Self {
+        Self {
+            channel,
+            aggregate,
+            span,
+            samples: VecDeque::new(),
+            sum: 0.0,
+            sum_sq: 0.0,
+            extremes: VecDeque::new(),
+        }
+    }
+
+    fn push(&mut self, at: Instant, value: f64) {
+        self.samples.push_back((at, value));
+        self.sum += value;
+        self.sum_sq += value * value;
+        let beaten = |best: f64| match self.aggregate {
+            Aggregate::Min => value <= best,
+            Aggregate::Max => value >= best,
+            _ => false,
+        };
+        if matches!(self.aggregate, Aggregate::Min | Aggregate::Max) && !value.is_nan() {
+            while self.extremes.back().is_some_and(|&(_, best)| beaten(best)) {
+                self.extremes.pop_back();
+            }
+            self.extremes.push_back((at, value));
+        }
+        self.expire(at);
+    }
+
+    /// Drop samples older than the span
+    fn expire(&mut self, now: Instant) {
+        while let Some(&(at, value)) = self.samples.front() {
+            if now.saturating_duration_since(at) <= self.span {
+                break;
+            }
+            self.samples.pop_front();
+            self.sum -= value;
+            self.sum_sq -= value * value;
+        }
+        while let Some(&(at, _)) = self.extremes.front() {
+            if now.saturating_duration_since(at) <= self.span {
+                break;
+            }
+            self.extremes.pop_front();
+        }
+        if self.samples.is_empty() {
+            // Reset so rounding errors don't accumulate
+            self.sum = 0.0;
+            self.sum_sq = 0.0;
+        }
+    }
+
+    /// Aggregate of the samples in the window (`None` if empty)
+    fn value(&self) -> Option<f64> {
+        let n = self.samples.len() as f64;
+        match self.aggregate {
+            _ if self.samples.is_empty() => None,
+            Aggregate::Mean => Some(self.sum / n),
+            Aggregate::Min | Aggregate::Max => self.extremes.front().map(|&(_, v)| v),
+            Aggregate::Std => {
+                let mean = self.sum / n;
+                Some((self.sum_sq / n - mean * mean).max(0.0).sqrt())
+            }
+            Aggregate::Delta => {
+                let first = self.samples.front()?.1;
+                let last = self.samples.back()?.1;
+                Some(last - first)
+            }
+        }
+    }
+}
+
+/// Change of a condition's state after an evaluation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    Unchanged,
+    /// The expression has now been true for the hold time
+    Activated,
+    /// The expression became false while the condition was active
+    Cleared,
+}
+
+/// A compiled condition with its rolling channel state
+#[derive(Debug, Clone)]
+pub struct Condition {
+    source: String,
+    tree: Node,
+    /// Channels used bare, by latest value
+    channels: Vec<String>,
+    windows: Vec<Window>,
+    latest: HashMap<String, f64>,
+    hold: Duration,
+    context: HashMapContext,
+    true_since: Option<Instant>,
+    active: bool,
+}
+
+impl Condition {
+    /// Parse `source`, accepting only channel names from `known_channels`
+    pub fn compile(source: &str, known_channels: &[&str]) -> Result<Self> {
+        let (expression, hold) = split_hold(source.trim())?;
+        let (rewritten, windows) = rewrite_windows(expression)?;
+        if rewritten.trim().is_empty() {
+            return Err(anyhow!("Condition is empty"));
+        }
+        let tree = evalexpr::build_operator_tree(&rewritten)
+            .map_err(|e| anyhow!("Invalid condition '{}': {}", source, e))?;
+
+        let mut channels: Vec<String> = Vec::new();
+        for identifier in tree.iter_variable_identifiers() {
+            if identifier.starts_with(WINDOW_VARIABLE_PREFIX) {
+                continue;
+            }
+            if !known_channels.contains(&identifier) {
+                return Err(unknown_channel(identifier, known_channels));
+            }
+            if !channels.iter().any(|c| c == identifier) {
+                channels.push(identifier.to_string());
+            }
+        }
+        for window in &windows {
+            if !known_channels.contains(&window.channel.as_str()) {
+                return Err(unknown_channel(&window.channel, known_channels));
+            }
+        }
+
+        Ok(Self {
+            source: source.trim().to_string(),
+            tree,
+            channels,
+            windows,
+            latest: HashMap::new(),
+            hold,
+            context: HashMapContext::new(),
+            true_since: None,
+            active: false,
+        })
+    }
+
+    /// The expression as written
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether the condition is currently active
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Whether `channel` appears in the expression, bare or in a window
+    pub fn uses_channel(&self, channel: &str) -> bool {
+        self.channels.iter().any(|c| c == channel)
+            || self.windows.iter().any(|w| w.channel == channel)
+    }
+
+    /// Record a sample of `channel` taken at `at`
+    pub fn push(&mut self, channel: &str, value: f64, at: Instant) {
+        if self.channels.iter().any(|c| c == channel) {
+            self.latest.insert(channel.to_string(), value);
+        }
+        for window in self.windows.iter_mut().filter(|w| w.channel == channel) {
+            window.push(at, value);
+        }
+    }
+
+    /// Evaluate the expression at `now` and update the hold state
+    ///
+    /// Fails if the expression does not produce a boolean.
+    pub fn evaluate(&mut self, now: Instant) -> Result<Transition> {
+        let holds = self.expression_holds(now)?;
+
+        if !holds {
+            self.true_since = None;
+            if self.active {
+                self.active = false;
+                return Ok(Transition::Cleared);
+            }
+            return Ok(Transition::Unchanged);
+        }
+
+        let since = *self.true_since.get_or_insert(now);
+        if !self.active && now.saturating_duration_since(since) >= self.hold {
+            self.active = true;
+            return Ok(Transition::Activated);
+        }
+        Ok(Transition::Unchanged)
+    }
+
+    fn expression_holds(&mut self, now: Instant) -> Result<bool> {
+        for (index, window) in self.windows.iter_mut().enumerate() {
+            window.expire(now);
+            let Some(value) = window.value() else {
+                return Ok(false);
+            };
+            self.context
+                .set_value(window_variable(index), Value::Float(value))
+                .map_err(|e| anyhow!("{}", e))?;
+        }
+        for channel in &self.channels {
+            let Some(&value) = self.latest.get(channel) else {
+                return Ok(false);
+            };
+            self.context
+                .set_value(channel.clone(), Value::Float(value))
+                .map_err(|e| anyhow!("{}", e))?;
+        }
+        self.tree
+            .eval_boolean_with_context(&self.context)
+            .map_err(|e| anyhow!("Condition '{}' failed: {}", self.source, e))
+    }
+}
+
+fn unknown_channel(name: &str, known_channels: &[&str]) -> anyhow::Error {
+    anyhow!(
+        "Unknown channel '{}' (available: {})",
+        name,
+        known_channels.join(", ")
+    )
+}
+
+fn window_variable(index: usize) -> String {
+    format!("{}{}", WINDOW_VARIABLE_PREFIX, index)
+}
+
+/// Split a trailing `for <duration>` off the expression
+fn split_hold(source: &str) -> Result<(&str, Duration)> {
+    for (index, _) in source.rmatch_indices("for") {
+        let before = &source[..index];
+        let after = &source[index + 3..];
+        if before.ends_with(char::is_whitespace) && after.starts_with(char::is_whitespace) {
+            let hold = parse_duration(after.trim())
+                .map_err(|e| anyhow!("Invalid hold time after 'for': {}", e))?;
+            return Ok((before.trim_end(), hold));
+        }
+    }
+    Ok((source, Duration::ZERO))
+}
+
+/// Parse a duration literal such as `500ms`, `10s`, `2min` or `1h`
+pub fn parse_duration(text: &str) -> Result<Duration> {
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| anyhow!("'{}' is not a duration", text))?;
+    let scale = match unit.trim() {
+        "ms" => 1e-3,
+        "s" => 1.0,
+        "m" | "min" => 60.0,
+        "h" => 3600.0,
+        _ => return Err(anyhow!("'{}' needs a unit (ms, s, min, h)", text)),
+    };
+    Duration::try_from_secs_f64(number * scale).map_err(|_| anyhow!("'{}' is out of range", text))
+}
+
+fn is_identifier(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_alphabetic() || c == '_')
+        && text.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Replace `aggregate(channel, duration)` calls with window variables
+fn rewrite_windows(expression: &str) -> Result<(String, Vec<Window>)> {
+    let mut rewritten = String::with_capacity(expression.len());
+    let mut windows: Vec<Window> = Vec::new();
+    let mut rest = expression;
+
+    while let Some(start) = rest.find(|c: char| c.is_alphabetic() || c == '_') {
+        rewritten.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let length = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let (name, after) = rest.split_at(length);
+        rest = after;
+
+        // Window calls have exactly a channel and a duration, no nesting
+        let call = Aggregate::from_name(name).and_then(|aggregate| {
+            let args_and_tail = after.trim_start().strip_prefix('(')?;
+            let close = args_and_tail.find(')')?;
+            let args = &args_and_tail[..close];
+            if args.contains('(') {
+                return None;
+            }
+            let (channel, span) = args.split_once(',')?;
+            let span = parse_duration(span.trim()).ok()?;
+            Some((aggregate, channel.trim(), span, &args_and_tail[close + 1..]))
+        });
+        let Some((aggregate, channel, span, tail)) = call else {
+            rewritten.push_str(name);
+            continue;
+        };
+        if !is_identifier(channel) {
+            return Err(anyhow!(
+                "{}() takes a channel name, not '{}'",
+                name,
+                channel
+            ));
+        }
+
+        let index = windows
+            .iter()
+            .position(|w| w.channel == channel && w.aggregate == aggregate && w.span == span)
+            .unwrap_or_else(|| {
+                windows.push(Window::new(channel.to_string(), aggregate, span));
+                windows.len() - 1
+            });
+        rewritten.push_str(&window_variable(index));
+        rest = tail;
+    }
+    rewritten.push_str(rest);
+    Ok((rewritten, windows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANNELS: &[&str] = &["power", "reference", "setpoint"];
+
+    #[test]
+    fn test_parse_windows_and_hold() {
+        let condition =
+            Condition::compile("mean(power, 10s) < 0.8 * setpoint for 30s", CHANNELS).unwrap();
+        assert_eq!(condition.hold, Duration::from_secs(30));
+        assert_eq!(condition.windows.len(), 1);
+        assert_eq!(condition.windows[0].span, Duration::from_secs(10));
+        assert!(condition.uses_channel("power"));
+        assert!(condition.uses_channel("setpoint"));
+        assert!(!condition.uses_channel("reference"));
+
+        // Repeated windows share a buffer; evalexpr's own min/max still work
+        let condition = Condition::compile(
+            "max(reference, 500ms) - min(reference, 500ms) > max(0.05, 0.1 * min(power, 1s))",
+            CHANNELS,
+        )
+        .unwrap();
+        assert_eq!(condition.windows.len(), 3);
+        assert_eq!(condition.hold, Duration::ZERO);
+
+        assert!(Condition::compile("mean(powr, 1s) > 1", CHANNELS).is_err());
+        assert!(Condition::compile("power > 1 for ever", CHANNELS).is_err());
+        assert!(Condition::compile("mean(power * 2, 1s) > 1", CHANNELS).is_err());
+    }
+
+    #[test]
+    fn test_windowed_condition_with_hold() {
+        let mut condition =
+            Condition::compile("mean(power, 2s) < 0.8 * setpoint for 3s", CHANNELS).unwrap();
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        // No data yet: never true
+        assert_eq!(condition.evaluate(at(0)).unwrap(), Transition::Unchanged);
+
+        condition.push("setpoint", 10.0, at(0));
+        for t in 0..=10 {
+            // Power drops to 5 at t = 3; the 2 s mean is below 8 from t = 4
+            let power = if t < 3 { 10.0 } else { 5.0 };
+            condition.push("power", power, at(t));
+            let transition = condition.evaluate(at(t)).unwrap();
+            let expected = if t == 7 {
+                Transition::Activated
+            } else {
+                Transition::Unchanged
+            };
+            assert_eq!(transition, expected, "t = {}", t);
+        }
+        assert!(condition.is_active());
+
+        condition.push("power", 10.0, at(11));
+        condition.push("power", 10.0, at(12));
+        condition.push("power", 10.0, at(13));
+        assert_eq!(condition.evaluate(at(13)).unwrap(), Transition::Cleared);
+    }
+
+    #[test]
+    fn test_min_max_follow_the_sliding_window() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut min = Window::new("power".to_string(), Aggregate::Min, Duration::from_secs(2));
+        let mut max = Window::new("power".to_string(), Aggregate::Max, Duration::from_secs(2));
+
+        let values = [5.0, 1.0, 4.0, 3.0, 6.0, 2.0];
+        for (t, &value) in values.iter().enumerate() {
+            min.push(at(t as u64), value);
+            max.push(at(t as u64), value);
+            let window = &values[t.saturating_sub(2)..=t];
+            let expected_min = window.iter().copied().reduce(f64::min);
+            let expected_max = window.iter().copied().reduce(f64::max);
+            assert_eq!(min.value(), expected_min, "t = {}", t);
+            assert_eq!(max.value(), expected_max, "t = {}", t);
+        }
+
+        // Expiry alone moves the extremes on
+        min.expire(at(7));
+        assert_eq!(min.value(), Some(2.0));
+        min.expire(at(8));
+        assert_eq!(min.value(), None);
+    }
+
+    #[test]
+    fn test_non_boolean_expression_fails() {
+        let mut condition = Condition::compile("power * 2", CHANNELS).unwrap();
+        condition.push("power", 1.0, Instant::now());
+        assert!(condition.evaluate(Instant::now()).is_err());
+    }
+}
@@ -0,0 +1,1225 @@
+//! DriftTracker Module
+//!
+//! Keeps long imaging sessions stabilized. The module grabs a reference
+//! ROI from the camera when it starts, then periodically measures how far
+//! the image has moved against that reference and publishes the XY drift.
+//! Optionally it feeds corrections back to the stage.
+//!
+//! # Measurement
+//!
+//! Drift is the shift that maximizes the normalized cross-correlation
+//! between the reference ROI and the same region of a new frame. The search
+//! covers `max_shift_px` pixels in each direction, and the peak is refined
+//! to sub-pixel precision with a parabolic fit. The peak correlation is
+//! published too. Below `min_correlation` the measurement is rejected as
+//! lost tracking, e.g. when the sample bleached or moved further than the
+//! search range.
+//!
+//! Positive drift means the image content moved towards increasing pixel
+//! coordinates.
+//!
+//! # Correction
+//!
+//! With `correct = true` every accepted measurement outside the
+//! `deadband_px` moves the assigned stage axes against the drift by
+//! `gain` times the drift, converted with `pixel_size_x`/`pixel_size_y`
+//! (stage units per pixel, negative where the axes are inverted) and
+//! limited to `max_correction` per step. The reference is kept, so the
+//! loop drives the image back to where it started. After a correction the
+//! next `settle_frames` frames are skipped. The module only watches frames;
+//! start the camera stream separately.
+//!
+//! # Roles
+//!
+//! | Role ID | Required Capability | Description |
+//! |---------|---------------------|-------------|
+//! | `camera` | `FrameProducer` | Camera whose frames are tracked |
+//! | `stage_x` | `Movable` | Optional axis corrected for horizontal drift |
+//! | `stage_y` | `Movable` | Optional axis corrected for vertical drift |
+//!
+//! # Parameters
+//!
+//! | Parameter | Type | Default | Units | Description |
+//! |-----------|------|---------|-------|-------------|
+//! | `roi_x` | int | -1 | px | Left edge of the reference ROI (-1 = centered) |
+//! | `roi_y` | int | -1 | px | Top edge of the reference ROI (-1 = centered) |
+//! | `roi_width` | int | 128 | px | Reference ROI width |
+//! | `roi_height` | int | 128 | px | Reference ROI height |
+//! | `max_shift_px` | int | 16 | px | Search range in each direction |
+//! | `interval_s` | float | 5.0 | s | Time between measurements |
+//! | `min_correlation` | float | 0.5 | - | Correlation below which tracking is lost |
+//! | `correct` | bool | false | - | Move the stage against the drift |
+//! | `pixel_size_x` | float | 1.0 | units/px | Stage units per pixel along x |
+//! | `pixel_size_y` | float | 1.0 | units/px | Stage units per pixel along y |
+//! | `gain` | float | 0.5 | - | Fraction of the drift corrected per step |
+//! | `deadband_px` | float | 0.5 | px | Drift left uncorrected |
+//! | `max_correction` | float | 10.0 | units | Largest move per step and axis |
+//! | `settle_frames` | int | 2 | frames | Frames skipped after a correction |
+//!
+//! # Events
+//!
+//! - `reference_acquired` - The reference ROI was captured (data: `x`, `y`, `width`, `height`)
+//! - `correction_applied` - The stage was moved (data: `dx`, `dy`, `drift_x_px`, `drift_y_px`)
+//! - `tracking_lost` - The correlation dropped below `min_correlation`
+//! - `tracking_recovered` - An accepted measurement followed lost tracking
+//! - `control_error` - The reference could not be captured or a stage move failed
+//!
+//! # Data Types
+//!
+//! - `drift` - Per measurement: `{drift_x_px, drift_y_px, drift_x, drift_y, correlation}`
+
+use super::{Module, ModuleContext};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use common::data::FrameView;
+use common::limits::SHUTDOWN_TIMEOUT;
+use common::modules::{
+    ModuleEventSeverity, ModuleParameter, ModuleRole, ModuleState, ModuleTypeInfo,
+};
+use hardware::capabilities::{FrameObserver, FrameProducer, Movable, ObserverHandle};
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// DriftTracker module configuration
+#[derive(Debug, Clone)]
+pub struct DriftTrackerConfig {
+    /// Left edge of the reference ROI, centered when `None`
+    pub roi_x: Option<u32>,
+    /// Top edge of the reference ROI, centered when `None`
+    pub roi_y: Option<u32>,
+    pub roi_width: u32,
+    pub roi_height: u32,
+    /// Search range in each direction, in pixels
+    pub max_shift_px: u32,
+    /// Time between measurements, in seconds
+    pub interval_s: f64,
+    /// Correlation below which a measurement is rejected
+    pub min_correlation: f64,
+    /// Whether the stage is moved against the drift
+    pub correct: bool,
+    /// Stage units per pixel along x
+    pub pixel_size_x: f64,
+    /// Stage units per pixel along y
+    pub pixel_size_y: f64,
+    /// Fraction of the drift corrected per step
+    pub gain: f64,
+    /// Drift left uncorrected, in pixels
+    pub deadband_px: f64,
+    /// Largest move per step and axis, in stage units
+    pub max_correction: f64,
+    /// Frames skipped after a correction
+    pub settle_frames: u32,
+}
+
+impl Default for DriftTrackerConfig {
+    fn default() -> Self {
+        Self {
+            roi_x: None,
+            roi_y: None,
+            roi_width: 128,
+            roi_height: 128,
+            max_shift_px: 16,
+            interval_s: 5.0,
+            min_correlation: 0.5,
+            correct: false,
+            pixel_size_x: 1.0,
+            pixel_size_y: 1.0,
+            gain: 0.5,
+            deadband_px: 0.5,
+            max_correction: 10.0,
+            settle_frames: 2,
+        }
+    }
+}
+
+impl DriftTrackerConfig {
+    /// Reference ROI within a frame of the given size
+    pub fn roi(&self, frame_width: u32, frame_height: u32) -> Result<Roi> {
+        let place = |edge: Option<u32>, size: u32, frame: u32, axis: &str| {
+            if size > frame {
+                return Err(anyhow!(
+                    "ROI {} of {} px does not fit the {} px frame",
+                    axis,
+                    size,
+                    frame
+                ));
+            }
+            let edge = edge.unwrap_or((frame - size) / 2);
+            if edge + size > frame {
+                return Err(anyhow!(
+                    "ROI {} from {} to {} px is outside the {} px frame",
+                    axis,
+                    edge,
+                    edge + size,
+                    frame
+                ));
+            }
+            Ok(edge)
+        };
+        Ok(Roi {
+            x: place(self.roi_x, self.roi_width, frame_width, "width")?,
+            y: place(self.roi_y, self.roi_height, frame_height, "height")?,
+            width: self.roi_width,
+            height: self.roi_height,
+        })
+    }
+
+    /// Stage moves `(x, y)` correcting a measured drift
+    ///
+    /// An axis inside the deadband is left alone (0.0).
+    pub fn correction(&self, drift: &DriftMeasurement) -> (f64, f64) {
+        let axis = |drift_px: f64, pixel_size: f64| {
+            if drift_px.abs() <= self.deadband_px {
+                return 0.0;
+            }
+            (-self.gain * drift_px * pixel_size).clamp(-self.max_correction, self.max_correction)
+        };
+        (
+            axis(drift.dx, self.pixel_size_x),
+            axis(drift.dy, self.pixel_size_y),
+        )
+    }
+}
+
+/// Rectangle in frame pixel coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Roi {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Grayscale frame with one `f32` per pixel, row-major
+#[derive(Debug, Clone)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<f32>,
+}
+
+impl Image {
+    /// Decode raw pixels, or `None` for a buffer that does not fit the size
+    ///
+    /// Pixels deeper than 8 bits are little-endian `u16`.
+    pub fn from_raw(raw: &[u8], width: u32, height: u32, bit_depth: u32) -> Option<Self> {
+        let count = width as usize * height as usize;
+        let pixels: Vec<f32> = if bit_depth <= 8 {
+            raw.iter().take(count).map(|&v| f32::from(v)).collect()
+        } else {
+            raw.chunks_exact(2)
+                .take(count)
+                .map(|b| f32::from(u16::from_le_bytes([b[0], b[1]])))
+                .collect()
+        };
+        (count > 0 && pixels.len() == count).then_some(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    fn row(&self, x: u32, y: u32, width: u32) -> &[f32] {
+        let start = y as usize * self.width as usize + x as usize;
+        &self.pixels[start..start + width as usize]
+    }
+}
+
+/// Drift of a frame against the reference, in pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DriftMeasurement {
+    pub dx: f64,
+    pub dy: f64,
+    /// Normalized cross-correlation at the peak (1.0 = identical)
+    pub correlation: f64,
+}
+
+/// Zero-mean copy of the reference ROI
+#[derive(Debug, Clone)]
+pub struct DriftReference {
+    roi: Roi,
+    template: Vec<f64>,
+    /// Root of the template's sum of squares
+    norm: f64,
+}
+
+impl DriftReference {
+    /// Capture the ROI of `image`, or `None` if it has no contrast
+    pub fn capture(image: &Image, roi: Roi) -> Option<Self> {
+        if roi.x + roi.width > image.width || roi.y + roi.height > image.height {
+            return None;
+        }
+        let mut template: Vec<f64> = (0..roi.height)
+            .flat_map(|row| image.row(roi.x, roi.y + row, roi.width))
+            .map(|&v| f64::from(v))
+            .collect();
+        let mean = template.iter().sum::<f64>() / template.len() as f64;
+        template.iter_mut().for_each(|v| *v -= mean);
+        let norm = template.iter().map(|v| v * v).sum::<f64>().sqrt();
+        (norm > 0.0).then_some(Self {
+            roi,
+            template,
+            norm,
+        })
+    }
+
+    pub fn roi(&self) -> Roi {
+        self.roi
+    }
+
+    /// Correlation of the template with the window shifted by `(dx, dy)`
+    ///
+    /// `None` when the window leaves the image or has no contrast.
+    fn correlation_at(&self, image: &Image, dx: i64, dy: i64) -> Option<f64> {
+        let x = i64::from(self.roi.x) + dx;
+        let y = i64::from(self.roi.y) + dy;
+        if x < 0
+            || y < 0
+            || x + i64::from(self.roi.width) > i64::from(image.width)
+            || y + i64::from(self.roi.height) > i64::from(image.height)
+        {
+            return None;
+        }
+        let (mut sum, mut sum_sq, mut cross) = (0.0, 0.0, 0.0);
+        let rows = self.template.chunks_exact(self.roi.width as usize);
+        for (row, template) in rows.enumerate() {
+            let window = image.row(x as u32, y as u32 + row as u32, self.roi.width);
+            for (&t, &w) in template.iter().zip(window) {
+                let w = f64::from(w);
+                sum += w;
+                sum_sq += w * w;
+                cross += t * w;
+            }
+        }
+        // The template is zero-mean, so the window mean drops out of `cross`
+        let variance = sum_sq - sum * sum / self.template.len() as f64;
+        (variance > 0.0).then(|| cross / (self.norm * variance.sqrt()))
+    }
+
+    /// Drift of `image` against the reference within `max_shift` pixels
+    pub fn measure(&self, image: &Image, max_shift: u32) -> Option<DriftMeasurement> {
+        let range = i64::from(max_shift);
+        let side = (2 * range + 1) as usize;
+        let scores: Vec<Option<f64>> = (-range..=range)
+            .flat_map(|dy| (-range..=range).map(move |dx| (dx, dy)))
+            .map(|(dx, dy)| self.correlation_at(image, dx, dy))
+            .collect();
+        let (best, correlation) = scores
+            .iter()
+            .enumerate()
+            .filter_map(|(i, score)| score.map(|s| (i, s)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))?;
+        let (col, row) = (best % side, best / side);
+
+        let score = |col: usize, row: usize| scores[row * side + col];
+        let left = col.checked_sub(1).and_then(|c| score(c, row));
+        let right = (col + 1 < side).then(|| score(col + 1, row)).flatten();
+        let up = row.checked_sub(1).and_then(|r| score(col, r));
+        let down = (row + 1 < side).then(|| score(col, row + 1)).flatten();
+
+        Some(DriftMeasurement {
+            dx: col as f64 - range as f64 + subpixel_offset(left, correlation, right),
+            dy: row as f64 - range as f64 + subpixel_offset(up, correlation, down),
+            correlation,
+        })
+    }
+}
+
+/// Peak offset from a parabola through three samples, within ±0.5
+fn subpixel_offset(before: Option<f64>, peak: f64, after: Option<f64>) -> f64 {
+    let (Some(before), Some(after)) = (before, after) else {
+        return 0.0;
+    };
+    let curvature = before - 2.0 * peak + after;
+    if curvature >= 0.0 {
+        return 0.0;
+    }
+    (0.5 * (before - after) / curvature).clamp(-0.5, 0.5)
+}
+
+/// Frame copied out of the driver loop for evaluation
+struct FrameSample {
+    pixels: Vec<u8>,
+    width: u32,
+    height: u32,
+    bit_depth: u32,
+    frame_number: u64,
+}
+
+/// Hands frames to the tracking task, one at a time
+struct SampleObserver {
+    tx: mpsc::Sender<FrameSample>,
+    /// Frames still to skip after a correction
+    skip: Arc<AtomicU32>,
+}
+
+impl FrameObserver for SampleObserver {
+    fn on_frame(&self, frame: &FrameView<'_>) {
+        if self
+            .skip
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+            .is_ok()
+        {
+            return;
+        }
+        // Only copy when the task is waiting for a frame
+        if self.tx.capacity() == 0 {
+            return;
+        }
+        let _ = self.tx.try_send(FrameSample {
+            pixels: frame.pixels().to_vec(),
+            width: frame.width,
+            height: frame.height,
+            bit_depth: frame.bit_depth,
+            frame_number: frame.frame_number,
+        });
+    }
+
+    fn name(&self) -> &'static str {
+        "drift_tracker"
+    }
+}
+
+/// Stage axes corrected by the tracker
+struct Stage {
+    x: Option<Arc<dyn Movable>>,
+    y: Option<Arc<dyn Movable>>,
+}
+
+impl Stage {
+    /// Move both axes by the given distances and wait for them to settle
+    async fn correct(&self, dx: f64, dy: f64) -> Result<()> {
+        for (axis, distance) in [(&self.x, dx), (&self.y, dy)] {
+            if let Some(axis) = axis
+                && distance != 0.0
+            {
+                axis.move_rel(distance).await?;
+            }
+        }
+        for (axis, distance) in [(&self.x, dx), (&self.y, dy)] {
+            if let Some(axis) = axis
+                && distance != 0.0
+            {
+                axis.wait_settled().await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// DriftTracker module
+pub struct DriftTracker {
+    config: DriftTrackerConfig,
+    state: ModuleState,
+    running: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    task_handle: Option<tokio::task::JoinHandle<()>>,
+    /// Camera and handle of the registered frame observer
+    observer: Option<(Arc<dyn FrameProducer>, ObserverHandle)>,
+}
+
+impl std::fmt::Debug for DriftTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DriftTracker")
+            .field("config", &self.config)
+            .field("state", &self.state)
+            .field("running", &self.running.load(Ordering::Relaxed))
+            .field("paused", &self.paused.load(Ordering::Relaxed))
+            .field("task_handle", &self.task_handle.is_some())
+            .field(
+                "observer",
+                &self.observer.as_ref().map(|(_, handle)| handle),
+            )
+            .finish()
+    }
+}
+
+impl Default for DriftTracker {
+    fn default() -> Self {
+        Self {
+            config: DriftTrackerConfig::default(),
+            state: ModuleState::Created,
+            running: Arc::new(AtomicBool::new(false)),
+            paused: Arc::new(AtomicBool::new(false)),
+            task_handle: None,
+            observer: None,
+        }
+    }
+}
+
+/// Build a numeric parameter description
+fn numeric_parameter(
+    param_id: &str,
+    display_name: &str,
+    description: &str,
+    param_type: &str,
+    default_value: f64,
+    range: RangeInclusive<f64>,
+    units: &str,
+) -> ModuleParameter {
+    ModuleParameter {
+        param_id: param_id.to_string(),
+        display_name: display_name.to_string(),
+        description: description.to_string(),
+        param_type: param_type.to_string(),
+        default_value: default_value.to_string(),
+        min_value: Some(range.start().to_string()),
+        max_value: Some(range.end().to_string()),
+        enum_values: vec![],
+        units: units.to_string(),
+        required: false,
+    }
+}
+
+/// Parse a float parameter, clamping it into `range`
+fn parse_float(
+    params: &HashMap<String, String>,
+    key: &str,
+    range: RangeInclusive<f64>,
+    value: &mut f64,
+    warnings: &mut Vec<String>,
+) {
+    let Some(val) = params.get(key) else {
+        return;
+    };
+    match val.parse::<f64>() {
+        Ok(parsed) if range.contains(&parsed) => *value = parsed,
+        Ok(parsed) if !parsed.is_nan() => {
+            *value = parsed.clamp(*range.start(), *range.end());
+            warnings.push(format!("{} clamped to {}", key, value));
+        }
+        _ => warnings.push(format!("Invalid {}: {}", key, val)),
+    }
+}
+
+/// Parse an integer parameter, clamping it into `range`
+fn parse_int(
+    params: &HashMap<String, String>,
+    key: &str,
+    range: RangeInclusive<u32>,
+    value: &mut u32,
+    warnings: &mut Vec<String>,
+) {
+    let Some(val) = params.get(key) else {
+        return;
+    };
+    match val.trim().parse::<u32>() {
+        Ok(parsed) if range.contains(&parsed) => *value = parsed,
+        Ok(parsed) => {
+            *value = parsed.clamp(*range.start(), *range.end());
+            warnings.push(format!("{} clamped to {}", key, value));
+        }
+        Err(_) => warnings.push(format!("Invalid {}: {}", key, val)),
+    }
+}
+
+/// Parse an ROI edge; any negative value centers the ROI
+fn parse_edge(
+    params: &HashMap<String, String>,
+    key: &str,
+    value: &mut Option<u32>,
+    warnings: &mut Vec<String>,
+) {
+    let Some(val) = params.get(key) else {
+        return;
+    };
+    match val.trim().parse::<i64>() {
+        Ok(parsed) if parsed < 0 => *value = None,
+        Ok(parsed) => match u32::try_from(parsed) {
+            Ok(edge) => *value = Some(edge),
+            Err(_) => warnings.push(format!("Invalid {}: {}", key, val)),
+        },
+        Err(_) => warnings.push(format!("Invalid {}: {}", key, val)),
+    }
+}
+
+const ROI_SIZE_RANGE: RangeInclusive<u32> = 8..=1024;
+const MAX_SHIFT_RANGE: RangeInclusive<u32> = 1..=128;
+const INTERVAL_RANGE: RangeInclusive<f64> = 0.1..=3600.0;
+const MIN_CORRELATION_RANGE: RangeInclusive<f64> = 0.0..=1.0;
+const PIXEL_SIZE_RANGE: RangeInclusive<f64> = -1000.0..=1000.0;
+const GAIN_RANGE: RangeInclusive<f64> = 0.0..=1.0;
+const DEADBAND_RANGE: RangeInclusive<f64> = 0.0..=100.0;
+const MAX_CORRECTION_RANGE: RangeInclusive<f64> = 0.0..=10_000.0;
+
+fn u32_range(range: &RangeInclusive<u32>) -> RangeInclusive<f64> {
+    f64::from(*range.start())..=f64::from(*range.end())
+}
+
+#[async_trait]
+impl Module for DriftTracker {
+    fn type_info() -> ModuleTypeInfo {
+        let stage_role = |role_id: &str, display_name: &str, description: &str| ModuleRole {
+            role_id: role_id.to_string(),
+            display_name: display_name.to_string(),
+            description: description.to_string(),
+            required_capability: "movable".to_string(),
+            allows_multiple: false,
+        };
+        ModuleTypeInfo {
+            type_id: "drift_tracker".to_string(),
+            display_name: "Drift Tracker".to_string(),
+            description: "Measures XY image drift by cross-correlation against a reference \
+                          ROI and optionally corrects it with the stage"
+                .to_string(),
+            version: "1.0.0".to_string(),
+            required_roles: vec![ModuleRole {
+                role_id: "camera".to_string(),
+                display_name: "Camera".to_string(),
+                description: "Camera whose frames are tracked".to_string(),
+                required_capability: "frame_producer".to_string(),
+                allows_multiple: false,
+            }],
+            optional_roles: vec![
+                stage_role("stage_x", "Stage X", "Axis corrected for horizontal drift"),
+                stage_role("stage_y", "Stage Y", "Axis corrected for vertical drift"),
+            ],
+            parameters: vec![
+                numeric_parameter(
+                    "roi_x",
+                    "ROI X",
+                    "Left edge of the reference ROI (-1 = centered)",
+                    "int",
+                    -1.0,
+                    -1.0..=65_535.0,
+                    "px",
+                ),
+                numeric_parameter(
+                    "roi_y",
+                    "ROI Y",
+                    "Top edge of the reference ROI (-1 = centered)",
+                    "int",
+                    -1.0,
+                    -1.0..=65_535.0,
+                    "px",
+                ),
+                numeric_parameter(
+                    "roi_width",
+                    "ROI Width",
+                    "Reference ROI width",
+                    "int",
+                    128.0,
+                    u32_range(&ROI_SIZE_RANGE),
+                    "px",
+                ),
+                numeric_parameter(
+                    "roi_height",
+                    "ROI Height",
+                    "Reference ROI height",
+                    "int",
+                    128.0,
+                    u32_range(&ROI_SIZE_RANGE),
+                    "px",
+                ),
+                numeric_parameter(
+                    "max_shift_px",
+                    "Max Shift",
+                    "Search range in each direction",
+                    "int",
+                    16.0,
+                    u32_range(&MAX_SHIFT_RANGE),
+                    "px",
+                ),
+                numeric_parameter(
+                    "interval_s",
+                    "Interval",
+                    "Time between measurements",
+                    "float",
+                    5.0,
+                    INTERVAL_RANGE,
+                    "s",
+                ),
+                numeric_parameter(
+                    "min_correlation",
+                    "Min Correlation",
+                    "Correlation below which tracking is lost",
+                    "float",
+                    0.5,
+                    MIN_CORRELATION_RANGE,
+                    "",
+                ),
+                ModuleParameter {
+                    param_id: "correct".to_string(),
+                    display_name: "Correct".to_string(),
+                    description: "Move the stage against the measured drift".to_string(),
+                    param_type: "bool".to_string(),
+                    default_value: "false".to_string(),
+                    min_value: None,
+                    max_value: None,
+                    enum_values: vec![],
+                    units: String::new(),
+                    required: false,
+                },
+                numeric_parameter(
+                    "pixel_size_x",
+                    "Pixel Size X",
+                    "Stage units per pixel along x (negative if inverted)",
+                    "float",
+                    1.0,
+                    PIXEL_SIZE_RANGE,
+                    "units/px",
+                ),
+                numeric_parameter(
+                    "pixel_size_y",
+                    "Pixel Size Y",
+                    "Stage units per pixel along y (negative if inverted)",
+                    "float",
+                    1.0,
+                    PIXEL_SIZE_RANGE,
+                    "units/px",
+                ),
+                numeric_parameter(
+                    "gain",
+                    "Gain",
+                    "Fraction of the drift corrected per step",
+                    "float",
+                    0.5,
+                    GAIN_RANGE,
+                    "",
+                ),
+                numeric_parameter(
+                    "deadband_px",
+                    "Deadband",
+                    "Drift left uncorrected",
+                    "float",
+                    0.5,
+                    DEADBAND_RANGE,
+                    "px",
+                ),
+                numeric_parameter(
+                    "max_correction",
+                    "Max Correction",
+                    "Largest stage move per step and axis",
+                    "float",
+                    10.0,
+                    MAX_CORRECTION_RANGE,
+                    "units",
+                ),
+                numeric_parameter(
+                    "settle_frames",
+                    "Settle Frames",
+                    "Frames skipped after a correction",
+                    "int",
+                    2.0,
+                    0.0..=100.0,
+                    "frames",
+                ),
+            ],
+            event_types: vec![
+                "reference_acquired".to_string(),
+                "correction_applied".to_string(),
+                "tracking_lost".to_string(),
+                "tracking_recovered".to_string(),
+                "control_error".to_string(),
+            ],
+            data_types: vec!["drift".to_string()],
+            config_schema: None,
+        }
+    }
+
+    fn type_id(&self) -> &str {
+        "drift_tracker"
+    }
+
+    fn configure(&mut self, params: HashMap<String, String>) -> Result<Vec<String>> {
+        let mut warnings = Vec::new();
+        let mut config = self.config.clone();
+
+        parse_edge(&params, "roi_x", &mut config.roi_x, &mut warnings);
+        parse_edge(&params, "roi_y", &mut config.roi_y, &mut warnings);
+        parse_int(
+            &params,
+            "roi_width",
+            ROI_SIZE_RANGE,
+            &mut config.roi_width,
+            &mut warnings,
+        );
+        parse_int(
+            &params,
+            "roi_height",
+            ROI_SIZE_RANGE,
+            &mut config.roi_height,
+            &mut warnings,
+        );
+        parse_int(
+            &params,
+            "max_shift_px",
+            MAX_SHIFT_RANGE,
+            &mut config.max_shift_px,
+            &mut warnings,
+        );
+        parse_int(
+            &params,
+            "settle_frames",
+            0..=100,
+            &mut config.settle_frames,
+            &mut warnings,
+        );
+        parse_float(
+            &params,
+            "interval_s",
+            INTERVAL_RANGE,
+            &mut config.interval_s,
+            &mut warnings,
+        );
+        parse_float(
+            &params,
+            "min_correlation",
+            MIN_CORRELATION_RANGE,
+            &mut config.min_correlation,
+            &mut warnings,
+        );
+        parse_float(
+            &params,
+            "pixel_size_x",
+            PIXEL_SIZE_RANGE,
+            &mut config.pixel_size_x,
+            &mut warnings,
+        );
+        parse_float(
+            &params,
+            "pixel_size_y",
+            PIXEL_SIZE_RANGE,
+            &mut config.pixel_size_y,
+            &mut warnings,
+        );
+        parse_float(&params, "gain", GAIN_RANGE, &mut config.gain, &mut warnings);
+        parse_float(
+            &params,
+            "deadband_px",
+            DEADBAND_RANGE,
+            &mut config.deadband_px,
+            &mut warnings,
+        );
+        parse_float(
+            &params,
+            "max_correction",
+            MAX_CORRECTION_RANGE,
+            &mut config.max_correction,
+            &mut warnings,
+        );
+
+        if let Some(val) = params.get("correct") {
+            match val.trim() {
+                "true" => config.correct = true,
+                "false" => config.correct = false,
+                _ => warnings.push(format!("Invalid correct: {}", val)),
+            }
+        }
+
+        self.config = config;
+        self.state = ModuleState::Configured;
+        Ok(warnings)
+    }
+
+    fn get_config(&self) -> HashMap<String, String> {
+        let config = &self.config;
+        let edge = |edge: Option<u32>| edge.map_or("-1".to_string(), |e| e.to_string());
+        HashMap::from([
+            ("roi_x".to_string(), edge(config.roi_x)),
+            ("roi_y".to_string(), edge(config.roi_y)),
+            ("roi_width".to_string(), config.roi_width.to_string()),
+            ("roi_height".to_string(), config.roi_height.to_string()),
+            ("max_shift_px".to_string(), config.max_shift_px.to_string()),
+            ("interval_s".to_string(), config.interval_s.to_string()),
+            (
+                "min_correlation".to_string(),
+                config.min_correlation.to_string(),
+            ),
+            ("correct".to_string(), config.correct.to_string()),
+            ("pixel_size_x".to_string(), config.pixel_size_x.to_string()),
+            ("pixel_size_y".to_string(), config.pixel_size_y.to_string()),
+            ("gain".to_string(), config.gain.to_string()),
+            ("deadband_px".to_string(), config.deadband_px.to_string()),
+            (
+                "max_correction".to_string(),
+                config.max_correction.to_string(),
+            ),
+            (
+                "settle_frames".to_string(),
+                config.settle_frames.to_string(),
+            ),
+        ])
+    }
+
+    async fn start(&mut self, ctx: ModuleContext) -> Result<()> {
+        if self.state == ModuleState::Running {
+            return Err(anyhow!("Module is already running"));
+        }
+
+        let producer = ctx
+            .get_frame_producer("camera")
+            .ok_or_else(|| anyhow!("No camera assigned"))?;
+        if !producer.supports_observers() {
+            return Err(anyhow!("Camera does not support frame observers"));
+        }
+        let stage = Stage {
+            x: ctx.get_movable("stage_x"),
+            y: ctx.get_movable("stage_y"),
+        };
+        if self.config.correct && stage.x.is_none() && stage.y.is_none() {
+            return Err(anyhow!("Correction needs a stage_x or stage_y assignment"));
+        }
+
+        let (tx, sample_rx) = mpsc::channel(1);
+        let skip = Arc::new(AtomicU32::new(0));
+        let observer = SampleObserver {
+            tx,
+            skip: Arc::clone(&skip),
+        };
+        let handle = producer.register_observer(Box::new(observer)).await?;
+        self.observer = Some((producer, handle));
+
+        self.running.store(true, Ordering::SeqCst);
+        self.paused.store(false, Ordering::SeqCst);
+        self.state = ModuleState::Running;
+
+        let config = self.config.clone();
+        let running = Arc::clone(&self.running);
+        let paused = Arc::clone(&self.paused);
+
+        let handle = tokio::spawn(async move {
+            drift_tracker_task(ctx, config, running, paused, stage, sample_rx, skip).await;
+        });
+
+        self.task_handle = Some(handle);
+        info!("DriftTracker started");
+        Ok(())
+    }
+
+    async fn pause(&mut self) -> Result<()> {
+        if self.state != ModuleState::Running {
+            return Err(anyhow!("Module is not running"));
+        }
+
+        self.paused.store(true, Ordering::SeqCst);
+        self.state = ModuleState::Paused;
+        info!("DriftTracker paused");
+        Ok(())
+    }
+
+    async fn resume(&mut self) -> Result<()> {
+        if self.state != ModuleState::Paused {
+            return Err(anyhow!("Module is not paused"));
+        }
+
+        self.paused.store(false, Ordering::SeqCst);
+        self.state = ModuleState::Running;
+        info!("DriftTracker resumed");
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        if self.state != ModuleState::Running && self.state != ModuleState::Paused {
+            return Err(anyhow!("Module is not running"));
+        }
+
+        self.running.store(false, Ordering::SeqCst);
+
+        if let Some(handle) = self.task_handle.take() {
+            handle.abort();
+            tokio::time::timeout(SHUTDOWN_TIMEOUT, handle).await.ok();
+        }
+        if let Some((producer, handle)) = self.observer.take()
+            && let Err(e) = producer.unregister_observer(handle).await
+        {
+            warn!("Failed to unregister frame observer: {}", e);
+        }
+
+        self.state = ModuleState::Stopped;
+        info!("DriftTracker stopped");
+        Ok(())
+    }
+
+    fn state(&self) -> ModuleState {
+        self.state
+    }
+}
+
+/// Main tracking task
+async fn drift_tracker_task(
+    mut ctx: ModuleContext,
+    config: DriftTrackerConfig,
+    running: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    stage: Stage,
+    mut sample_rx: mpsc::Receiver<FrameSample>,
+    skip: Arc<AtomicU32>,
+) {
+    let interval = Duration::from_secs_f64(config.interval_s);
+    let mut reference: Option<DriftReference> = None;
+    let mut lost = false;
+
+    info!(
+        "DriftTracker task started: {}x{} ROI, search ±{} px, correction {}",
+        config.roi_width,
+        config.roi_height,
+        config.max_shift_px,
+        if config.correct { "on" } else { "off" }
+    );
+
+    while running.load(Ordering::SeqCst) {
+        // A frame queued during the last interval is stale by now
+        while sample_rx.try_recv().is_ok() {}
+        let Some(frame) = sample_rx.recv().await else {
+            break;
+        };
+
+        if ctx.is_shutdown_requested() {
+            break;
+        }
+        if paused.load(Ordering::SeqCst) {
+            tokio::time::sleep(interval).await;
+            continue;
+        }
+        let Some(image) =
+            Image::from_raw(&frame.pixels, frame.width, frame.height, frame.bit_depth)
+        else {
+            continue;
+        };
+
+        let Some(tracked) = reference.as_ref() else {
+            match config.roi(image.width, image.height).and_then(|roi| {
+                DriftReference::capture(&image, roi)
+                    .ok_or_else(|| anyhow!("Reference ROI has no contrast"))
+            }) {
+                Ok(captured) => {
+                    let roi = captured.roi();
+                    ctx.emit_event_with_data(
+                        "reference_acquired",
+                        ModuleEventSeverity::Info,
+                        &format!(
+                            "Reference ROI {}x{} at ({}, {}) captured from frame {}",
+                            roi.width, roi.height, roi.x, roi.y, frame.frame_number
+                        ),
+                        HashMap::from([
+                            ("x".to_string(), roi.x.to_string()),
+                            ("y".to_string(), roi.y.to_string()),
+                            ("width".to_string(), roi.width.to_string()),
+                            ("height".to_string(), roi.height.to_string()),
+                            ("frame_number".to_string(), frame.frame_number.to_string()),
+                        ]),
+                    )
+                    .await;
+                    reference = Some(captured);
+                }
+                Err(e) => {
+                    warn!("Failed to capture drift reference: {}", e);
+                    ctx.emit_event(
+                        "control_error",
+                        ModuleEventSeverity::Warning,
+                        &format!("Failed to capture reference: {}", e),
+                    )
+                    .await;
+                }
+            }
+            tokio::time::sleep(interval).await;
+            continue;
+        };
+
+        let drift = tracked
+            .measure(&image, config.max_shift_px)
+            .filter(|drift| drift.correlation >= config.min_correlation);
+        let Some(drift) = drift else {
+            if !lost {
+                ctx.emit_event(
+                    "tracking_lost",
+                    ModuleEventSeverity::Warning,
+                    &format!(
+                        "Frame {} no longer matches the reference within ±{} px",
+                        frame.frame_number, config.max_shift_px
+                    ),
+                )
+                .await;
+            }
+            lost = true;
+            tokio::time::sleep(interval).await;
+            continue;
+        };
+        if lost {
+            ctx.emit_event(
+                "tracking_recovered",
+                ModuleEventSeverity::Info,
+                &format!("Tracking recovered at frame {}", frame.frame_number),
+            )
+            .await;
+            lost = false;
+        }
+
+        ctx.emit_data(
+            "drift",
+            HashMap::from([
+                ("drift_x_px".to_string(), drift.dx),
+                ("drift_y_px".to_string(), drift.dy),
+                ("drift_x".to_string(), drift.dx * config.pixel_size_x),
+                ("drift_y".to_string(), drift.dy * config.pixel_size_y),
+                ("correlation".to_string(), drift.correlation),
+            ]),
+        )
+        .await;
+
+        if config.correct {
+            let (dx, dy) = config.correction(&drift);
+            if dx != 0.0 || dy != 0.0 {
+                match stage.correct(dx, dy).await {
+                    Ok(()) => {
+                        // Frames exposed during the move show a smeared image
+                        skip.store(config.settle_frames, Ordering::Relaxed);
+                        ctx.emit_event_with_data(
+                            "correction_applied",
+                            ModuleEventSeverity::Info,
+                            &format!(
+                                "Stage moved by ({:.4}, {:.4}) for drift ({:.2}, {:.2}) px",
+                                dx, dy, drift.dx, drift.dy
+                            ),
+                            HashMap::from([
+                                ("dx".to_string(), dx.to_string()),
+                                ("dy".to_string(), dy.to_string()),
+                                ("drift_x_px".to_string(), drift.dx.to_string()),
+                                ("drift_y_px".to_string(), drift.dy.to_string()),
+                                ("frame_number".to_string(), frame.frame_number.to_string()),
+                            ]),
+                        )
+                        .await;
+                    }
+                    Err(e) => {
+                        warn!("Drift correction failed: {}", e);
+                        ctx.emit_event(
+                            "control_error",
+                            ModuleEventSeverity::Warning,
+                            &format!("Failed to move stage by ({}, {}): {}", dx, dy, e),
+                        )
+                        .await;
+                    }
+                }
+            }
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+
+    info!("DriftTracker task ended");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 96x96 frame with two Gaussian spots, shifted by `(dx, dy)` pixels
+    fn spots(dx: f64, dy: f64) -> Image {
+        let size = 96;
+        let pixels = (0..size * size)
+            .map(|i| {
+                let (x, y) = (f64::from(i % size), f64::from(i / size));
+                let spot = |cx: f64, cy: f64| {
+                    let r2 = (x - cx - dx).powi(2) + (y - cy - dy).powi(2);
+                    1000.0 * (-r2 / 18.0).exp()
+                };
+                (100.0 + spot(40.0, 44.0) + spot(55.0, 50.0)) as f32
+            })
+            .collect();
+        Image {
+            width: size,
+            height: size,
+            pixels,
+        }
+    }
+
+    #[test]
+    fn test_measures_drift() {
+        let config = DriftTrackerConfig {
+            roi_width: 48,
+            roi_height: 48,
+            ..Default::default()
+        };
+        let roi = config.roi(96, 96).unwrap();
+        assert_eq!((roi.x, roi.y), (24, 24));
+        let reference = DriftReference::capture(&spots(0.0, 0.0), roi).unwrap();
+
+        let still = reference.measure(&spots(0.0, 0.0), 8).unwrap();
+        assert!(still.dx.abs() < 1e-6 && still.dy.abs() < 1e-6);
+        assert!((still.correlation - 1.0).abs() < 1e-9);
+
+        let moved = reference.measure(&spots(3.0, -2.0), 8).unwrap();
+        assert!((moved.dx - 3.0).abs() < 0.05, "{moved:?}");
+        assert!((moved.dy + 2.0).abs() < 0.05, "{moved:?}");
+
+        let subpixel = reference.measure(&spots(1.5, 0.25), 8).unwrap();
+        assert!((subpixel.dx - 1.5).abs() < 0.2, "{subpixel:?}");
+        assert!((subpixel.dy - 0.25).abs() < 0.2, "{subpixel:?}");
+
+        // Out of the search range, the best match is poor
+        let far = reference.measure(&spots(20.0, 0.0), 4).unwrap();
+        assert!(far.correlation < config.min_correlation, "{far:?}");
+
+        let flat = Image {
+            width: 96,
+            height: 96,
+            pixels: vec![5.0; 96 * 96],
+        };
+        assert!(DriftReference::capture(&flat, roi).is_none());
+    }
+
+    #[test]
+    fn test_roi_and_correction() {
+        let config = DriftTrackerConfig {
+            roi_x: Some(10),
+            roi_width: 64,
+            roi_height: 32,
+            pixel_size_x: 0.1,
+            pixel_size_y: -0.1,
+            max_correction: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(
+            config.roi(100, 40).unwrap(),
+            Roi {
+                x: 10,
+                y: 4,
+                width: 64,
+                height: 32
+            }
+        );
+        assert!(config.roi(70, 40).is_err());
+        assert!(config.roi(100, 16).is_err());
+
+        let drift = |dx, dy| DriftMeasurement {
+            dx,
+            dy,
+            correlation: 0.9,
+        };
+        // Half the drift, against its direction, in stage units
+        let (x, y) = config.correction(&drift(4.0, 2.0));
+        assert!((x + 0.2).abs() < 1e-9 && (y - 0.1).abs() < 1e-9);
+        // Deadband and clamp
+        assert_eq!(config.correction(&drift(0.3, -40.0)), (0.0, -0.5));
+    }
+
+    #[test]
+    fn test_config_parsing() {
+        let mut module = DriftTracker::default();
+        let params = HashMap::from([
+            ("roi_x".to_string(), "12".to_string()),
+            ("roi_width".to_string(), "4".to_string()),
+            ("correct".to_string(), "true".to_string()),
+            ("gain".to_string(), "oops".to_string()),
+        ]);
+        let warnings = module.configure(params).unwrap();
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(module.config.roi_x, Some(12));
+        assert_eq!(module.config.roi_width, 8);
+        assert!(module.config.correct);
+        assert!((module.config.gain - 0.5).abs() < f64::EPSILON);
+
+        let params = HashMap::from([("roi_x".to_string(), "-1".to_string())]);
+        module.configure(params).unwrap();
+        assert_eq!(module.config.roi_x, None);
+        assert_eq!(module.get_config()["roi_x"], "-1");
+        assert_eq!(module.get_config()["correct"], "true");
+    }
+}
@@ -0,0 +1,389 @@
+//! EnvironmentMonitor Module
+//!
+//! Polls ambient sensors at a low rate and records them in the device
+//! registry's environment log, which the run engine summarises into every
+//! run's StopDoc (see [`common::environment`]).
+//!
+//! Any `Readable` device can fill a role, e.g. a USB temperature/humidity
+//! logger or a serial accelerometer on the optical table. Polling is slow,
+//! so the vibration device should report a band-limited RMS rather than an
+//! instantaneous acceleration.
+//!
+//! # Roles
+//!
+//! | Role ID | Required Capability | Description |
+//! |---------|---------------------|-------------|
+//! | `temperature` | `Readable` | Lab temperature in °C (optional) |
+//! | `humidity` | `Readable` | Relative humidity in % (optional) |
+//! | `vibration` | `Readable` | Optical table vibration RMS in mg (optional) |
+//!
+//! At least one role must be assigned.
+//!
+//! # Parameters
+//!
+//! | Parameter | Type | Default | Units | Description |
+//! |-----------|------|---------|-------|-------------|
+//! | `poll_interval_s` | float | 10.0 | s | Time between readings |
+//! | `channel_prefix` | string | - | - | Prepended to channel names, e.g. `room_b_` (optional) |
+//!
+//! # Events
+//!
+//! - `read_error` - A sensor could not be read
+//!
+//! # Data Types
+//!
+//! - `environment` - One value per assigned sensor, keyed by channel name
+
+use super::{Module, ModuleContext, current_time_ns};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use common::environment::EnvironmentLog;
+use common::limits::SHUTDOWN_TIMEOUT;
+use common::modules::{
+    ModuleEventSeverity, ModuleParameter, ModuleRole, ModuleState, ModuleTypeInfo,
+};
+use hardware::capabilities::Readable;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Sensor roles: (role ID, display name, units)
+const SENSOR_ROLES: &[(&str, &str, &str)] = &[
+    ("temperature", "Temperature", "degC"),
+    ("humidity", "Humidity", "%RH"),
+    ("vibration", "Vibration", "mg"),
+];
+
+/// EnvironmentMonitor module configuration
+#[derive(Debug, Clone)]
+pub struct EnvironmentMonitorConfig {
+    /// Time between readings in seconds
+    pub poll_interval_s: f64,
+    /// Prepended to every channel name
+    pub channel_prefix: String,
+}
+
+impl Default for EnvironmentMonitorConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_s: 10.0,
+            channel_prefix: String::new(),
+        }
+    }
+}
+
+/// A sensor assigned to one of the roles
+struct Sensor {
+    channel: String,
+    units: &'static str,
+    device: Arc<dyn Readable>,
+}
+
+/// EnvironmentMonitor module
+pub struct EnvironmentMonitor {
+    config: EnvironmentMonitorConfig,
+    state: ModuleState,
+    running: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    task_handle: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl std::fmt::Debug for EnvironmentMonitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EnvironmentMonitor")
+            .field("config", &self.config)
+            .field("state", &self.state)
+            .field("running", &self.running.load(Ordering::Relaxed))
+            .field("paused", &self.paused.load(Ordering::Relaxed))
+            .field("task_handle", &self.task_handle.is_some())
+            .finish()
+    }
+}
+
+impl Default for EnvironmentMonitor {
+    fn default() -> Self {
+        Self {
+            config: EnvironmentMonitorConfig::default(),
+            state: ModuleState::Created,
+            running: Arc::new(AtomicBool::new(false)),
+            paused: Arc::new(AtomicBool::new(false)),
+            task_handle: None,
+        }
+    }
+}
+
+#[async_trait]
+impl Module for EnvironmentMonitor {
+    fn type_info() -> ModuleTypeInfo {
+        ModuleTypeInfo {
+            type_id: "environment_monitor".to_string(),
+            display_name: "Environment Monitor".to_string(),
+            description: "Polls lab temperature, humidity and table vibration and \
+                          summarises them in every run's stop document"
+                .to_string(),
+            version: "1.0.0".to_string(),
+            required_roles: vec![],
+            optional_roles: SENSOR_ROLES
+                .iter()
+                .map(|&(role_id, display_name, units)| ModuleRole {
+                    role_id: role_id.to_string(),
+                    display_name: display_name.to_string(),
+                    description: format!("{} sensor reading in {}", display_name, units),
+                    required_capability: "readable".to_string(),
+                    allows_multiple: false,
+                })
+                .collect(),
+            parameters: vec![
+                ModuleParameter {
+                    param_id: "poll_interval_s".to_string(),
+                    display_name: "Poll Interval".to_string(),
+                    description: "Time between sensor readings".to_string(),
+                    param_type: "float".to_string(),
+                    default_value: "10.0".to_string(),
+                    min_value: Some("1.0".to_string()),
+                    max_value: Some("3600.0".to_string()),
+                    enum_values: vec![],
+                    units: "s".to_string(),
+                    required: false,
+                },
+                ModuleParameter {
+                    param_id: "channel_prefix".to_string(),
+                    display_name: "Channel Prefix".to_string(),
+                    description: "Prepended to channel names, to tell rooms apart".to_string(),
+                    param_type: "string".to_string(),
+                    default_value: String::new(),
+                    min_value: None,
+                    max_value: None,
+                    enum_values: vec![],
+                    units: String::new(),
+                    required: false,
+                },
+            ],
+            event_types: vec!["read_error".to_string()],
+            data_types: vec!["environment".to_string()],
+            config_schema: None,
+        }
+    }
+
+    fn type_id(&self) -> &str {
+        "environment_monitor"
+    }
+
+    fn configure(&mut self, params: HashMap<String, String>) -> Result<Vec<String>> {
+        let mut warnings = Vec::new();
+
+        if let Some(val) = params.get("poll_interval_s") {
+            match val.parse::<f64>() {
+                Ok(interval) if (1.0..=3600.0).contains(&interval) => {
+                    self.config.poll_interval_s = interval;
+                }
+                Ok(interval) if !interval.is_nan() => {
+                    self.config.poll_interval_s = interval.clamp(1.0, 3600.0);
+                    warnings.push(format!(
+                        "poll_interval_s clamped to {} s",
+                        self.config.poll_interval_s
+                    ));
+                }
+                _ => warnings.push(format!("Invalid poll_interval_s: {}", val)),
+            }
+        }
+
+        if let Some(val) = params.get("channel_prefix") {
+            self.config.channel_prefix = val.trim().to_string();
+        }
+
+        self.state = ModuleState::Configured;
+        Ok(warnings)
+    }
+
+    fn get_config(&self) -> HashMap<String, String> {
+        let mut config = HashMap::new();
+        config.insert(
+            "poll_interval_s".to_string(),
+            format!("{}", self.config.poll_interval_s),
+        );
+        if !self.config.channel_prefix.is_empty() {
+            config.insert(
+                "channel_prefix".to_string(),
+                self.config.channel_prefix.clone(),
+            );
+        }
+        config
+    }
+
+    async fn start(&mut self, ctx: ModuleContext) -> Result<()> {
+        if self.state == ModuleState::Running {
+            return Err(anyhow!("Module is already running"));
+        }
+
+        let sensors: Vec<Sensor> = SENSOR_ROLES
+            .iter()
+            .filter_map(|&(role_id, _, units)| {
+                Some(Sensor {
+                    channel: format!("{}{}", self.config.channel_prefix, role_id),
+                    units,
+                    device: ctx.get_readable(role_id)?,
+                })
+            })
+            .collect();
+        if sensors.is_empty() {
+            return Err(anyhow!(
+                "No sensors assigned. Assign a readable device to 'temperature', \
+                 'humidity' or 'vibration'."
+            ));
+        }
+        let log = ctx.registry().environment_log();
+
+        self.running.store(true, Ordering::SeqCst);
+        self.paused.store(false, Ordering::SeqCst);
+        self.state = ModuleState::Running;
+
+        let config = self.config.clone();
+        let running = Arc::clone(&self.running);
+        let paused = Arc::clone(&self.paused);
+
+        let handle = tokio::spawn(async move {
+            environment_monitor_task(ctx, config, running, paused, sensors, log).await;
+        });
+
+        self.task_handle = Some(handle);
+        info!("EnvironmentMonitor started");
+        Ok(())
+    }
+
+    async fn pause(&mut self) -> Result<()> {
+        if self.state != ModuleState::Running {
+            return Err(anyhow!("Module is not running"));
+        }
+
+        self.paused.store(true, Ordering::SeqCst);
+        self.state = ModuleState::Paused;
+        info!("EnvironmentMonitor paused");
+        Ok(())
+    }
+
+    async fn resume(&mut self) -> Result<()> {
+        if self.state != ModuleState::Paused {
+            return Err(anyhow!("Module is not paused"));
+        }
+
+        self.paused.store(false, Ordering::SeqCst);
+        self.state = ModuleState::Running;
+        info!("EnvironmentMonitor resumed");
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        if self.state != ModuleState::Running && self.state != ModuleState::Paused {
+            return Err(anyhow!("Module is not running"));
+        }
+
+        self.running.store(false, Ordering::SeqCst);
+
+        if let Some(handle) = self.task_handle.take() {
+            tokio::time::timeout(SHUTDOWN_TIMEOUT, handle).await.ok();
+        }
+
+        self.state = ModuleState::Stopped;
+        info!("EnvironmentMonitor stopped");
+        Ok(())
+    }
+
+    fn state(&self) -> ModuleState {
+        self.state
+    }
+}
+
+/// Main polling task
+async fn environment_monitor_task(
+    mut ctx: ModuleContext,
+    config: EnvironmentMonitorConfig,
+    running: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    sensors: Vec<Sensor>,
+    log: Arc<EnvironmentLog>,
+) {
+    let mut ticker = tokio::time::interval(Duration::from_secs_f64(config.poll_interval_s));
+
+    info!(
+        "EnvironmentMonitor task started: interval={:.1}s, channels={:?}",
+        config.poll_interval_s,
+        sensors
+            .iter()
+            .map(|s| s.channel.as_str())
+            .collect::<Vec<_>>()
+    );
+
+    while running.load(Ordering::SeqCst) {
+        ticker.tick().await;
+
+        if ctx.is_shutdown_requested() {
+            break;
+        }
+        if paused.load(Ordering::SeqCst) {
+            continue;
+        }
+
+        let mut values = HashMap::new();
+        for sensor in &sensors {
+            match sensor.device.read().await {
+                Ok(value) => {
+                    log.record(&sensor.channel, sensor.units, value, current_time_ns());
+                    values.insert(sensor.channel.clone(), value);
+                }
+                Err(e) => {
+                    warn!("Failed to read {}: {}", sensor.channel, e);
+                    ctx.emit_event(
+                        "read_error",
+                        ModuleEventSeverity::Warning,
+                        &format!("Failed to read {}: {}", sensor.channel, e),
+                    )
+                    .await;
+                }
+            }
+        }
+        if !values.is_empty() {
+            ctx.emit_data("environment", values).await;
+        }
+    }
+
+    info!("EnvironmentMonitor task ended");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_parsing() {
+        let mut monitor = EnvironmentMonitor::default();
+
+        let mut params = HashMap::new();
+        params.insert("poll_interval_s".to_string(), "30".to_string());
+        params.insert("channel_prefix".to_string(), "room_b_".to_string());
+        let warnings = monitor.configure(params).unwrap();
+        assert!(warnings.is_empty());
+        assert!((monitor.config.poll_interval_s - 30.0).abs() < f64::EPSILON);
+        assert_eq!(monitor.get_config()["channel_prefix"], "room_b_");
+
+        let mut params = HashMap::new();
+        params.insert("poll_interval_s".to_string(), "0.1".to_string());
+        let warnings = monitor.configure(params).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!((monitor.config.poll_interval_s - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_all_roles_optional() {
+        let info = EnvironmentMonitor::type_info();
+        assert!(info.required_roles.is_empty());
+        let roles: Vec<_> = info
+            .optional_roles
+            .iter()
+            .map(|r| r.role_id.as_str())
+            .collect();
+        assert_eq!(roles, ["temperature", "humidity", "vibration"]);
+    }
+}
@@ -24,7 +24,12 @@
 //! registry.start_module(&module_id).await?;
 //! ```
 
+pub mod auto_exposure;
+pub mod condition;
 pub mod document;
+pub mod drift_tracker;
+pub mod environment_monitor;
+pub mod persistence;
 pub mod power_monitor;
 pub mod run_engine;
 
@@ -33,7 +38,8 @@ use async_trait::async_trait;
 use common::modules::{
     ModuleDataPoint, ModuleEvent, ModuleEventSeverity, ModuleState, ModuleTypeInfo,
 };
-use hardware::capabilities::Readable;
+use common::state_machine::{MachineKind, StateTracker};
+use hardware::capabilities::{ExposureControl, FrameProducer, Movable, Parameterized, Readable};
 use hardware::registry::DeviceRegistry;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -43,8 +49,12 @@ use tracing::{info, warn};
 use uuid::Uuid;
 
 // Re-export for convenience
+pub use auto_exposure::AutoExposure;
 pub use common::observable::{Observable, ObservableMetadata, ParameterSet};
 pub use document::{DataKey, Document, StopReason};
+pub use drift_tracker::DriftTracker;
+pub use environment_monitor::EnvironmentMonitor;
+pub use persistence::{PersistedModule, RestoreReport};
 pub use power_monitor::PowerMonitor;
 pub use run_engine::{RunConfig, RunEngine, RunReport};
 
@@ -145,8 +155,8 @@ pub struct ModuleContext {
     /// Device registry for accessing hardware
     registry: Arc<DeviceRegistry>,
 
-    /// Channel for emitting events
-    event_tx: mpsc::Sender<ModuleEvent>,
+    /// Channel for emitting events (every event stream subscriber gets a copy)
+    event_tx: broadcast::Sender<ModuleEvent>,
 
     /// Channel for emitting data points
     data_tx: mpsc::Sender<ModuleDataPoint>,
@@ -161,7 +171,7 @@ impl std::fmt::Debug for ModuleContext {
             .field("module_id", &self.module_id)
             .field("assignments", &self.assignments)
             .field("registry", &"<Arc<DeviceRegistry>>")
-            .field("event_tx", &"<mpsc::Sender>")
+            .field("event_tx", &"<broadcast::Sender>")
             .field("data_tx", &"<mpsc::Sender>")
             .field("shutdown_rx", &"<broadcast::Receiver>")
             .finish()
@@ -174,7 +184,7 @@ impl ModuleContext {
         module_id: String,
         assignments: HashMap<String, String>,
         registry: Arc<DeviceRegistry>,
-        event_tx: mpsc::Sender<ModuleEvent>,
+        event_tx: broadcast::Sender<ModuleEvent>,
         data_tx: mpsc::Sender<ModuleDataPoint>,
         shutdown_rx: broadcast::Receiver<()>,
     ) -> Self {
@@ -188,12 +198,46 @@ impl ModuleContext {
         }
     }
 
+    /// Device registry the module runs against
+    pub fn registry(&self) -> &Arc<DeviceRegistry> {
+        &self.registry
+    }
+
+    /// ID of the device assigned to a role
+    pub fn device_id(&self, role_id: &str) -> Option<&str> {
+        self.assignments.get(role_id).map(String::as_str)
+    }
+
     /// Get a Readable device assigned to a role
     pub fn get_readable(&self, role_id: &str) -> Option<Arc<dyn Readable>> {
         let device_id = self.assignments.get(role_id)?;
         self.registry.get_readable(device_id)
     }
 
+    /// Get a FrameProducer device assigned to a role
+    pub fn get_frame_producer(&self, role_id: &str) -> Option<Arc<dyn FrameProducer>> {
+        let device_id = self.assignments.get(role_id)?;
+        self.registry.get_frame_producer(device_id)
+    }
+
+    /// Get a Movable device assigned to a role
+    pub fn get_movable(&self, role_id: &str) -> Option<Arc<dyn Movable>> {
+        let device_id = self.assignments.get(role_id)?;
+        self.registry.get_movable(device_id)
+    }
+
+    /// Get the exposure control of the device assigned to a role
+    pub fn get_exposure_control(&self, role_id: &str) -> Option<Arc<dyn ExposureControl>> {
+        let device_id = self.assignments.get(role_id)?;
+        self.registry.get_exposure_control(device_id)
+    }
+
+    /// Get the parameters of the device assigned to a role
+    pub fn get_parameterized(&self, role_id: &str) -> Option<Arc<dyn Parameterized>> {
+        let device_id = self.assignments.get(role_id)?;
+        self.registry.get_parameterized(device_id)
+    }
+
     /// Emit an event
     pub async fn emit_event(&self, event_type: &str, severity: ModuleEventSeverity, message: &str) {
         self.emit_event_with_data(event_type, severity, message, HashMap::new())
@@ -217,9 +261,8 @@ impl ModuleContext {
             data,
         };
 
-        if let Err(e) = self.event_tx.send(event).await {
-            warn!("Failed to emit event: {}", e);
-        }
+        // Fails only when nobody is subscribed, which is fine for events
+        let _ = self.event_tx.send(event);
     }
 
     /// Emit a data point
@@ -290,11 +333,8 @@ pub struct ModuleInstance {
     /// Device assignments: role_id -> device_id
     assignments: HashMap<String, String>,
 
-    /// Event sender for this module
-    event_tx: mpsc::Sender<ModuleEvent>,
-
-    /// Event receiver (for streaming)
-    event_rx: Option<mpsc::Receiver<ModuleEvent>>,
+    /// Event sender for this module (subscribe for streaming)
+    event_tx: broadcast::Sender<ModuleEvent>,
 
     /// Data sender for this module
     data_tx: mpsc::Sender<ModuleDataPoint>,
@@ -319,8 +359,7 @@ impl std::fmt::Debug for ModuleInstance {
             .field("name", &self.name)
             .field("module", &"<Box<dyn Module>>")
             .field("assignments", &self.assignments)
-            .field("event_tx", &"<mpsc::Sender>")
-            .field("event_rx", &format!("{:?}", self.event_rx.is_some()))
+            .field("event_tx", &"<broadcast::Sender>")
             .field("data_tx", &"<mpsc::Sender>")
             .field("data_rx", &format!("{:?}", self.data_rx.is_some()))
             .field("shutdown_tx", &"<broadcast::Sender>")
@@ -335,17 +374,17 @@ impl std::fmt::Debug for ModuleInstance {
 impl ModuleInstance {
     /// Create a new module instance
     pub fn new(id: String, name: String, module: Box<dyn Module>) -> Self {
-        let (event_tx, event_rx) = mpsc::channel(100);
+        let (event_tx, _) = broadcast::channel(100);
         let (data_tx, data_rx) = mpsc::channel(100);
         let (shutdown_tx, _) = broadcast::channel(1);
 
+        StateTracker::global().record(MachineKind::Module, &id, module.state().as_str(), "create");
         Self {
             id,
             name,
             module,
             assignments: HashMap::new(),
             event_tx,
-            event_rx: Some(event_rx),
             data_tx,
             data_rx: Some(data_rx),
             shutdown_tx,
@@ -362,13 +401,25 @@ impl ModuleInstance {
     }
 
     /// Get current state
+    ///
+    /// Also records the state, so changes made by the module itself (a
+    /// task finishing or failing) show up in the state tracker.
     pub fn state(&self) -> ModuleState {
-        self.module.state()
+        self.track_state("observed")
+    }
+
+    /// Record the current state with the process-wide state tracker
+    fn track_state(&self, trigger: &str) -> ModuleState {
+        let state = self.module.state();
+        StateTracker::global().record(MachineKind::Module, &self.id, state.as_str(), trigger);
+        state
     }
 
     /// Configure the module
     pub fn configure(&mut self, params: HashMap<String, String>) -> Result<Vec<String>> {
-        self.module.configure(params)
+        let result = self.module.configure(params);
+        self.track_state("configure");
+        result
     }
 
     /// Get current configuration
@@ -401,7 +452,9 @@ impl ModuleInstance {
             self.data_tx.clone(),
             self.shutdown_tx.subscribe(),
         );
-        self.module.stage(&ctx).await
+        let result = self.module.stage(&ctx).await;
+        self.track_state("stage");
+        result
     }
 
     /// Unstage the module (Bluesky pattern - release resources after stop)
@@ -414,7 +467,9 @@ impl ModuleInstance {
             self.data_tx.clone(),
             self.shutdown_tx.subscribe(),
         );
-        self.module.unstage(&ctx).await
+        let result = self.module.unstage(&ctx).await;
+        self.track_state("unstage");
+        result
     }
 
     /// Start the module
@@ -429,29 +484,37 @@ impl ModuleInstance {
         );
 
         self.start_time_ns = Some(current_time_ns());
-        self.module.start(ctx).await
+        let result = self.module.start(ctx).await;
+        self.track_state("start");
+        result
     }
 
     /// Pause the module
     pub async fn pause(&mut self) -> Result<()> {
-        self.module.pause().await
+        let result = self.module.pause().await;
+        self.track_state("pause");
+        result
     }
 
     /// Resume the module
     pub async fn resume(&mut self) -> Result<()> {
-        self.module.resume().await
+        let result = self.module.resume().await;
+        self.track_state("resume");
+        result
     }
 
     /// Stop the module
     pub async fn stop(&mut self) -> Result<()> {
         // Send shutdown signal
         let _ = self.shutdown_tx.send(());
-        self.module.stop().await
+        let result = self.module.stop().await;
+        self.track_state("stop");
+        result
     }
 
-    /// Take the event receiver (for streaming)
-    pub fn take_event_rx(&mut self) -> Option<mpsc::Receiver<ModuleEvent>> {
-        self.event_rx.take()
+    /// Subscribe to events emitted from now on (for streaming)
+    pub fn subscribe_events(&self) -> broadcast::Receiver<ModuleEvent> {
+        self.event_tx.subscribe()
     }
 
     /// Take the data receiver (for streaming)
@@ -467,6 +530,25 @@ impl ModuleInstance {
 /// Factory function for creating modules
 pub type ModuleFactory = fn() -> Box<dyn Module>;
 
+/// Factory for module types whose instances need their own setup, such as
+/// plugins that each run in a sandbox; gets the new instance's ID
+pub type InstanceFactory = Arc<dyn Fn(&str) -> Result<Box<dyn Module>> + Send + Sync>;
+
+/// A module type provided by a plugin loader rather than built in
+#[derive(Clone)]
+pub struct PluginModuleType {
+    pub info: ModuleTypeInfo,
+    pub factory: InstanceFactory,
+}
+
+impl std::fmt::Debug for PluginModuleType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PluginModuleType")
+            .field("type_id", &self.info.type_id)
+            .finish_non_exhaustive()
+    }
+}
+
 /// Registry for module types and instances
 pub struct ModuleRegistry {
     /// Device registry for hardware access
@@ -475,6 +557,9 @@ pub struct ModuleRegistry {
     /// Registered module types: type_id -> factory
     module_types: HashMap<String, ModuleFactory>,
 
+    /// Module types provided by plugins: type_id -> factory
+    plugin_types: HashMap<String, InstanceFactory>,
+
     /// Module type info cache
     type_info_cache: HashMap<String, ModuleTypeInfo>,
 
@@ -508,6 +593,7 @@ impl ModuleRegistry {
         let mut registry = Self {
             device_registry,
             module_types: HashMap::new(),
+            plugin_types: HashMap::new(),
             type_info_cache: HashMap::new(),
             instances: HashMap::new(),
         };
@@ -521,6 +607,9 @@ impl ModuleRegistry {
     /// Register built-in module types
     fn register_builtin_modules(&mut self) {
         self.register_type::<PowerMonitor>();
+        self.register_type::<EnvironmentMonitor>();
+        self.register_type::<AutoExposure>();
+        self.register_type::<DriftTracker>();
     }
 
     /// Register a module type
@@ -531,6 +620,21 @@ impl ModuleRegistry {
         self.module_types.insert(type_id, || Box::new(M::default()));
     }
 
+    /// Register a module type provided by a plugin
+    ///
+    /// Fails if the type ID is taken, so a plugin can't replace a built-in
+    /// module.
+    pub fn register_plugin_type(&mut self, module_type: PluginModuleType) -> Result<()> {
+        let type_id = module_type.info.type_id.clone();
+        if self.type_info_cache.contains_key(&type_id) {
+            return Err(anyhow!("Module type already registered: {}", type_id));
+        }
+        self.type_info_cache
+            .insert(type_id.clone(), module_type.info);
+        self.plugin_types.insert(type_id, module_type.factory);
+        Ok(())
+    }
+
     /// List all registered module types
     pub fn list_types(&self) -> Vec<&ModuleTypeInfo> {
         self.type_info_cache.values().collect()
@@ -543,13 +647,21 @@ impl ModuleRegistry {
 
     /// Create a new module instance
     pub fn create_module(&mut self, type_id: &str, name: &str) -> Result<String> {
-        let factory = self
-            .module_types
-            .get(type_id)
-            .ok_or_else(|| anyhow!("Unknown module type: {}", type_id))?;
+        self.create_module_with_id(type_id, name, Uuid::new_v4().to_string())
+    }
 
-        let module = factory();
-        let id = Uuid::new_v4().to_string();
+    fn create_module_with_id(&mut self, type_id: &str, name: &str, id: String) -> Result<String> {
+        if self.instances.contains_key(&id) {
+            return Err(anyhow!("Module already exists: {}", id));
+        }
+
+        let module = if let Some(factory) = self.module_types.get(type_id) {
+            factory()
+        } else if let Some(factory) = self.plugin_types.get(type_id) {
+            factory(&id)?
+        } else {
+            return Err(anyhow!("Unknown module type: {}", type_id));
+        };
         let instance = ModuleInstance::new(id.clone(), name.to_string(), module);
         self.instances.insert(id.clone(), instance);
 
@@ -557,6 +669,101 @@ impl ModuleRegistry {
         Ok(id)
     }
 
+    /// Capture type, configuration and role bindings of every instance
+    pub fn snapshot(&self) -> Vec<PersistedModule> {
+        let mut modules: Vec<PersistedModule> = self
+            .instances
+            .values()
+            .map(|instance| PersistedModule {
+                module_id: instance.id.clone(),
+                type_id: instance.type_id().to_string(),
+                instance_name: instance.name.clone(),
+                config: instance.get_config(),
+                assignments: instance.get_assignments().clone(),
+            })
+            .collect();
+        modules.sort_by(|a, b| a.instance_name.cmp(&b.instance_name));
+        modules
+    }
+
+    /// Recreate persisted instances.
+    ///
+    /// Modules of unknown types are skipped. Configuration or assignment
+    /// failures (e.g. a device that is no longer registered) leave the
+    /// module in place without that setting, so it can be fixed by hand.
+    pub fn restore(&mut self, modules: Vec<PersistedModule>) -> RestoreReport {
+        let mut report = RestoreReport::default();
+
+        for persisted in modules {
+            let label = format!("{} ({})", persisted.instance_name, persisted.module_id);
+            let id = match self.create_module_with_id(
+                &persisted.type_id,
+                &persisted.instance_name,
+                persisted.module_id.clone(),
+            ) {
+                Ok(id) => id,
+                Err(e) => {
+                    report.warnings.push(format!("Skipped {}: {}", label, e));
+                    report.skipped.push(persisted);
+                    continue;
+                }
+            };
+
+            if !persisted.config.is_empty()
+                && let Err(e) = self.configure_module(&id, persisted.config)
+            {
+                report
+                    .warnings
+                    .push(format!("{}: configuration not restored: {}", label, e));
+            }
+
+            let mut assignments: Vec<_> = persisted.assignments.into_iter().collect();
+            assignments.sort();
+            for (role_id, device_id) in assignments {
+                if let Err(e) = self.assign_device(&id, &role_id, &device_id) {
+                    report
+                        .warnings
+                        .push(format!("{}: role '{}' not restored: {}", label, role_id, e));
+                }
+            }
+
+            report.restored += 1;
+        }
+
+        report
+    }
+
+    /// Create an instance exactly as described, or nothing at all.
+    ///
+    /// Unlike [`restore`](Self::restore), any configuration or assignment
+    /// failure removes the half-built instance and returns the error.
+    pub fn instantiate(&mut self, module: &PersistedModule) -> Result<String> {
+        let id = self.create_module_with_id(
+            &module.type_id,
+            &module.instance_name,
+            module.module_id.clone(),
+        )?;
+
+        if let Err(e) = self.apply_persisted_settings(&id, module) {
+            self.instances.remove(&id);
+            StateTracker::global().remove(MachineKind::Module, &id);
+            return Err(e);
+        }
+        Ok(id)
+    }
+
+    fn apply_persisted_settings(&mut self, id: &str, module: &PersistedModule) -> Result<()> {
+        if !module.config.is_empty() {
+            self.configure_module(id, module.config.clone())?;
+        }
+        let mut assignments: Vec<_> = module.assignments.iter().collect();
+        assignments.sort();
+        for (role_id, device_id) in assignments {
+            self.assign_device(id, role_id, device_id)?;
+        }
+        Ok(())
+    }
+
     /// Delete a module instance
     pub async fn delete_module(&mut self, module_id: &str, force: bool) -> Result<()> {
         if let Some(instance) = self.instances.get(module_id) {
@@ -578,6 +785,7 @@ impl ModuleRegistry {
         }
 
         self.instances.remove(module_id);
+        StateTracker::global().remove(MachineKind::Module, module_id);
         info!("Deleted module instance: {}", module_id);
         Ok(())
     }
@@ -845,4 +1053,78 @@ mod tests {
         registry.delete_module(&module_id, false).await.unwrap();
         assert!(registry.get_module(&module_id).is_none());
     }
+
+    #[tokio::test]
+    async fn test_snapshot_and_restore() {
+        let device_registry = Arc::new(DeviceRegistry::new());
+        let mut registry = ModuleRegistry::new(device_registry.clone());
+
+        let module_id = registry
+            .create_module("power_monitor", "Test Monitor")
+            .unwrap();
+        let mut params = HashMap::new();
+        params.insert("high_threshold".to_string(), "100.0".to_string());
+        registry.configure_module(&module_id, params).unwrap();
+
+        let mut snapshot = registry.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        // Device no longer registered after restart
+        snapshot[0]
+            .assignments
+            .insert("power_meter".to_string(), "missing_pm".to_string());
+        snapshot.push(PersistedModule {
+            module_id: "gone".to_string(),
+            type_id: "removed_type".to_string(),
+            instance_name: "Old".to_string(),
+            config: HashMap::new(),
+            assignments: HashMap::new(),
+        });
+
+        let mut restored = ModuleRegistry::new(device_registry);
+        let report = restored.restore(snapshot);
+        assert_eq!(report.restored, 1);
+        assert_eq!(report.warnings.len(), 2);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].module_id, "gone");
+
+        let instance = restored.get_module(&module_id).unwrap();
+        assert_eq!(instance.name, "Test Monitor");
+        assert_eq!(
+            instance.get_config().get("high_threshold"),
+            registry
+                .get_module(&module_id)
+                .unwrap()
+                .get_config()
+                .get("high_threshold")
+        );
+        assert!(instance.get_assignments().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_plugin_module_types() {
+        let mut registry = ModuleRegistry::new(Arc::new(DeviceRegistry::new()));
+        let mut info = PowerMonitor::type_info();
+        info.type_id = "plugin_monitor".to_string();
+        let plugin = PluginModuleType {
+            info,
+            factory: Arc::new(|_id: &str| Ok(Box::new(PowerMonitor::default()) as Box<dyn Module>)),
+        };
+
+        registry.register_plugin_type(plugin.clone()).unwrap();
+        assert!(registry.get_type_info("plugin_monitor").is_some());
+        let module_id = registry.create_module("plugin_monitor", "Plugin").unwrap();
+        assert_eq!(
+            registry.get_module(&module_id).unwrap().type_id(),
+            "power_monitor"
+        );
+
+        // Plugins can't replace a registered type
+        let impostor = PluginModuleType {
+            info: PowerMonitor::type_info(),
+            factory: plugin.factory.clone(),
+        };
+        assert!(registry.register_plugin_type(plugin).is_err());
+        assert!(registry.register_plugin_type(impostor).is_err());
+        assert!(registry.create_module("power_monitor", "Built in").is_ok());
+    }
 }
@@ -0,0 +1,128 @@
+//! Persistence of module instances across daemon restarts.
+//!
+//! The module set (type, name, configuration and role bindings of every
+//! instance) is written to a JSON file whenever it changes and restored on
+//! startup, so operators do not have to recreate modules by hand. Runtime
+//! state is not persisted: restored modules come back stopped.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Current file format version
+const FORMAT_VERSION: u32 = 1;
+
+/// A module instance as saved to disk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PersistedModule {
+    /// Instance ID (kept so clients referencing it still work after restart)
+    pub module_id: String,
+    /// Module type to instantiate
+    pub type_id: String,
+    /// User-friendly instance name
+    pub instance_name: String,
+    /// Configuration parameters
+    #[serde(default)]
+    pub config: HashMap<String, String>,
+    /// Role bindings: role_id -> device_id
+    #[serde(default)]
+    pub assignments: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ModuleStateFile {
+    version: u32,
+    modules: Vec<PersistedModule>,
+}
+
+/// Outcome of restoring persisted modules
+#[derive(Debug, Default)]
+pub struct RestoreReport {
+    /// Number of module instances recreated
+    pub restored: usize,
+    /// Problems that did not prevent startup (unknown types, missing devices)
+    pub warnings: Vec<String>,
+    /// Entries that could not be recreated (e.g. their plugin is not loaded).
+    /// They are kept in the file so they come back once the type is available.
+    pub skipped: Vec<PersistedModule>,
+}
+
+/// Default location of the persisted module set
+pub fn default_module_state_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rust-daq")
+        .join("modules.json")
+}
+
+/// Load persisted modules; a missing file means no modules.
+pub fn load_modules(path: &Path) -> Result<Vec<PersistedModule>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let file: ModuleStateFile = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    if file.version > FORMAT_VERSION {
+        anyhow::bail!(
+            "{} was written by a newer version (format {} > {})",
+            path.display(),
+            file.version,
+            FORMAT_VERSION
+        );
+    }
+    Ok(file.modules)
+}
+
+/// Save the module set, replacing the file atomically.
+pub fn save_modules(path: &Path, modules: &[PersistedModule]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let file = ModuleStateFile {
+        version: FORMAT_VERSION,
+        modules: modules.to_vec(),
+    };
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(&file)?)
+        .with_context(|| format!("Failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("Failed to replace {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("state").join("modules.json");
+
+        assert!(load_modules(&path).unwrap().is_empty());
+
+        let module = PersistedModule {
+            module_id: "m1".to_string(),
+            type_id: "power_monitor".to_string(),
+            instance_name: "Laser Power".to_string(),
+            config: HashMap::from([("high_threshold".to_string(), "100".to_string())]),
+            assignments: HashMap::from([("power_meter".to_string(), "pm1".to_string())]),
+        };
+        save_modules(&path, std::slice::from_ref(&module)).unwrap();
+
+        assert_eq!(load_modules(&path).unwrap(), vec![module]);
+    }
+
+    #[test]
+    fn test_newer_format_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("modules.json");
+        std::fs::write(&path, r#"{"version": 99, "modules": []}"#).unwrap();
+
+        assert!(load_modules(&path).is_err());
+    }
+}
@@ -6,6 +6,8 @@
 //!
 //! - Configurable sample rate (0.1 - 100 Hz)
 //! - Low/high threshold alerts
+//! - Expression conditions over power, reference and setpoint with time
+//!   windows (see [`super::condition`])
 //! - Running statistics (mean, std, min, max)
 //! - Event emission for threshold crossings
 //! - Data streaming for power readings and statistics
@@ -15,6 +17,7 @@
 //! | Role ID | Required Capability | Description |
 //! |---------|---------------------|-------------|
 //! | `power_meter` | `Readable` | Device providing power readings |
+//! | `reference` | `Readable` | Second detector, e.g. a pick-off (optional) |
 //!
 //! # Parameters
 //!
@@ -24,18 +27,24 @@
 //! | `low_threshold` | float | - | mW | Alert if below (optional) |
 //! | `high_threshold` | float | - | mW | Alert if above (optional) |
 //! | `averaging_window_s` | float | 1.0 | s | Window for statistics |
+//! | `condition` | string | - | - | Alarm expression, e.g. `mean(power, 10s) < 0.8 * setpoint for 30s` (optional) |
+//! | `setpoint` | float | - | mW | Expected power, usable in `condition` (optional) |
 //!
 //! # Events
 //!
 //! - `threshold_low` - Power dropped below low threshold
 //! - `threshold_high` - Power exceeded high threshold
 //! - `threshold_normal` - Power returned to normal range
+//! - `condition_active` - The alarm condition has held for its hold time
+//! - `condition_cleared` - The alarm condition no longer holds
+//! - `condition_error` - The alarm condition could not be evaluated
 //!
 //! # Data Types
 //!
 //! - `power_reading` - Individual readings: `{value}`
 //! - `statistics` - Computed stats: `{mean, std, min, max, count}`
 
+use super::condition::{Condition, Transition};
 use super::{Module, ModuleContext};
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
@@ -49,6 +58,9 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 use tracing::{info, warn};
 
+/// Channel names available to the alarm condition
+const CONDITION_CHANNELS: &[&str] = &["power", "reference", "setpoint"];
+
 /// PowerMonitor module configuration
 #[derive(Debug, Clone)]
 pub struct PowerMonitorConfig {
@@ -60,6 +72,10 @@ pub struct PowerMonitorConfig {
     pub high_threshold: Option<f64>,
     /// Averaging window in seconds
     pub averaging_window_s: f64,
+    /// Alarm condition expression (optional)
+    pub condition: Option<String>,
+    /// Expected power for the condition (optional)
+    pub setpoint: Option<f64>,
 }
 
 impl Default for PowerMonitorConfig {
@@ -69,6 +85,8 @@ impl Default for PowerMonitorConfig {
             low_threshold: None,
             high_threshold: None,
             averaging_window_s: 1.0,
+            condition: None,
+            setpoint: None,
         }
     }
 }
@@ -205,7 +223,14 @@ impl Module for PowerMonitor {
                 required_capability: "readable".to_string(),
                 allows_multiple: false,
             }],
-            optional_roles: vec![],
+            optional_roles: vec![ModuleRole {
+                role_id: "reference".to_string(),
+                display_name: "Reference".to_string(),
+                description: "Second detector, available as 'reference' in the condition"
+                    .to_string(),
+                required_capability: "readable".to_string(),
+                allows_multiple: false,
+            }],
             parameters: vec![
                 ModuleParameter {
                     param_id: "sample_rate_hz".to_string(),
@@ -255,13 +280,43 @@ impl Module for PowerMonitor {
                     units: "s".to_string(),
                     required: false,
                 },
+                ModuleParameter {
+                    param_id: "condition".to_string(),
+                    display_name: "Alarm Condition".to_string(),
+                    description: "Expression over power, reference and setpoint, \
+                                  e.g. mean(power, 10s) < 0.8 * setpoint for 30s"
+                        .to_string(),
+                    param_type: "string".to_string(),
+                    default_value: String::new(),
+                    min_value: None,
+                    max_value: None,
+                    enum_values: vec![],
+                    units: String::new(),
+                    required: false,
+                },
+                ModuleParameter {
+                    param_id: "setpoint".to_string(),
+                    display_name: "Setpoint".to_string(),
+                    description: "Expected power, usable in the alarm condition".to_string(),
+                    param_type: "float".to_string(),
+                    default_value: String::new(),
+                    min_value: Some("0.0".to_string()),
+                    max_value: None,
+                    enum_values: vec![],
+                    units: "mW".to_string(),
+                    required: false,
+                },
             ],
             event_types: vec![
                 "threshold_low".to_string(),
                 "threshold_high".to_string(),
                 "threshold_normal".to_string(),
+                "condition_active".to_string(),
+                "condition_cleared".to_string(),
+                "condition_error".to_string(),
             ],
             data_types: vec!["power_reading".to_string(), "statistics".to_string()],
+            config_schema: None,
         }
     }
 
@@ -324,6 +379,30 @@ impl Module for PowerMonitor {
             }
         }
 
+        // Parse setpoint
+        if let Some(val) = params.get("setpoint") {
+            if val.is_empty() {
+                self.config.setpoint = None;
+            } else {
+                match val.parse::<f64>() {
+                    Ok(setpoint) => self.config.setpoint = Some(setpoint),
+                    Err(_) => warnings.push(format!("Invalid setpoint: {}", val)),
+                }
+            }
+        }
+
+        // Parse condition (compiled here only to report mistakes early)
+        if let Some(val) = params.get("condition") {
+            if val.trim().is_empty() {
+                self.config.condition = None;
+            } else {
+                match Condition::compile(val, CONDITION_CHANNELS) {
+                    Ok(_) => self.config.condition = Some(val.trim().to_string()),
+                    Err(e) => warnings.push(format!("Invalid condition: {}", e)),
+                }
+            }
+        }
+
         // Validate threshold relationship
         if let (Some(low), Some(high)) = (self.config.low_threshold, self.config.high_threshold)
             && low >= high
@@ -351,6 +430,12 @@ impl Module for PowerMonitor {
             "averaging_window_s".to_string(),
             format!("{}", self.config.averaging_window_s),
         );
+        if let Some(condition) = &self.config.condition {
+            config.insert("condition".to_string(), condition.clone());
+        }
+        if let Some(setpoint) = self.config.setpoint {
+            config.insert("setpoint".to_string(), format!("{}", setpoint));
+        }
         config
     }
 
@@ -363,6 +448,23 @@ impl Module for PowerMonitor {
         let power_meter = ctx.get_readable("power_meter").ok_or_else(|| {
             anyhow!("No power meter assigned. Assign a readable device to the 'power_meter' role.")
         })?;
+        let reference = ctx.get_readable("reference");
+
+        let condition = match &self.config.condition {
+            Some(source) => {
+                let condition = Condition::compile(source, CONDITION_CHANNELS)?;
+                if condition.uses_channel("reference") && reference.is_none() {
+                    return Err(anyhow!(
+                        "Condition uses 'reference' but no device is assigned to the 'reference' role"
+                    ));
+                }
+                if condition.uses_channel("setpoint") && self.config.setpoint.is_none() {
+                    return Err(anyhow!("Condition uses 'setpoint' but no setpoint is set"));
+                }
+                Some(condition)
+            }
+            None => None,
+        };
 
         self.running.store(true, Ordering::SeqCst);
         self.paused.store(false, Ordering::SeqCst);
@@ -374,7 +476,16 @@ impl Module for PowerMonitor {
 
         // Spawn the monitoring task
         let handle = tokio::spawn(async move {
-            power_monitor_task(ctx, config, running, paused, power_meter).await;
+            power_monitor_task(
+                ctx,
+                config,
+                running,
+                paused,
+                power_meter,
+                reference,
+                condition,
+            )
+            .await;
         });
 
         self.task_handle = Some(handle);
@@ -434,6 +545,8 @@ async fn power_monitor_task(
     running: Arc<AtomicBool>,
     paused: Arc<AtomicBool>,
     power_meter: Arc<dyn hardware::capabilities::Readable>,
+    reference: Option<Arc<dyn hardware::capabilities::Readable>>,
+    mut condition: Option<Condition>,
 ) {
     let interval = Duration::from_secs_f64(1.0 / config.sample_rate_hz);
     let window_size = (config.sample_rate_hz * config.averaging_window_s).ceil() as usize;
@@ -503,6 +616,24 @@ async fn power_monitor_task(
             emit_threshold_event(&ctx, threshold_state, new_state, value).await;
             threshold_state = new_state;
         }
+
+        let result = match condition.as_mut() {
+            Some(active) => {
+                update_condition(&ctx, active, reference.as_deref(), config.setpoint, value).await
+            }
+            None => Ok(()),
+        };
+        if let Err(e) = result {
+            // Evaluation fails the same way every tick, so report once
+            warn!("Disabling alarm condition: {:#}", e);
+            ctx.emit_event(
+                "condition_error",
+                ModuleEventSeverity::Error,
+                &format!("{:#}", e),
+            )
+            .await;
+            condition = None;
+        }
     }
 
     // Emit stop event
@@ -573,6 +704,54 @@ async fn emit_threshold_event(
     }
 }
 
+/// Feed the latest readings to the alarm condition and emit its transitions
+async fn update_condition(
+    ctx: &ModuleContext,
+    condition: &mut Condition,
+    reference: Option<&dyn hardware::capabilities::Readable>,
+    setpoint: Option<f64>,
+    value: f64,
+) -> Result<()> {
+    let now = tokio::time::Instant::now().into_std();
+    condition.push("power", value, now);
+    if let Some(setpoint) = setpoint {
+        condition.push("setpoint", setpoint, now);
+    }
+    if let Some(reference) = reference {
+        match reference.read().await {
+            Ok(v) => condition.push("reference", v, now),
+            Err(e) => warn!("Failed to read reference: {}", e),
+        }
+    }
+
+    let mut data = HashMap::new();
+    data.insert("value".to_string(), format!("{:.3}", value));
+    data.insert("condition".to_string(), condition.source().to_string());
+
+    match condition.evaluate(now)? {
+        Transition::Unchanged => {}
+        Transition::Activated => {
+            ctx.emit_event_with_data(
+                "condition_active",
+                ModuleEventSeverity::Warning,
+                &format!("Alarm condition met: {}", condition.source()),
+                data,
+            )
+            .await;
+        }
+        Transition::Cleared => {
+            ctx.emit_event_with_data(
+                "condition_cleared",
+                ModuleEventSeverity::Info,
+                &format!("Alarm condition cleared: {}", condition.source()),
+                data,
+            )
+            .await;
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -638,6 +817,29 @@ mod tests {
         assert_eq!(monitor.config.high_threshold, Some(100.0));
     }
 
+    #[test]
+    fn test_condition_parsing() {
+        let mut monitor = PowerMonitor::default();
+
+        let mut params = HashMap::new();
+        params.insert(
+            "condition".to_string(),
+            "mean(power, 10s) < 0.8 * setpoint for 30s".to_string(),
+        );
+        params.insert("setpoint".to_string(), "50.0".to_string());
+        let warnings = monitor.configure(params).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(monitor.config.setpoint, Some(50.0));
+        assert!(monitor.config.condition.is_some());
+
+        let mut params = HashMap::new();
+        params.insert("condition".to_string(), "mean(temp, 10s) > 1".to_string());
+        let warnings = monitor.configure(params).unwrap();
+        assert_eq!(warnings.len(), 1);
+        // The previous valid condition is kept
+        assert!(monitor.config.condition.unwrap().starts_with("mean(power"));
+    }
+
     #[test]
     fn test_config_clamping() {
         let mut monitor = PowerMonitor::default();
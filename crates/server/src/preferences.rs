@@ -0,0 +1,405 @@
+//! Client preference store.
+//!
+//! GUI clients keep small settings here (favorite channels, panel defaults,
+//! recently used plans) so they follow the user between machines. Values
+//! are opaque strings, JSON by convention.
+//!
+//! Preferences belong either to a user (the session's client name) or to a
+//! single session. User preferences are written to one JSON file per user
+//! when the store has a directory; session preferences live in memory and
+//! are dropped when the session ends. Every change is broadcast so other
+//! clients of the same user can pick it up live.
+
+use anyhow::{Context, Result, bail};
+use common::experiment::document::now_ns;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tokio::sync::broadcast;
+
+/// Longest accepted preference key
+pub const MAX_PREFERENCE_KEY_LEN: usize = 256;
+
+/// Largest accepted preference value
+pub const MAX_PREFERENCE_VALUE_BYTES: usize = 64 * 1024;
+
+/// Most keys a single user or session may store
+pub const MAX_PREFERENCES_PER_OWNER: usize = 1024;
+
+/// Changes buffered per subscriber
+const CHANGE_CHANNEL_CAPACITY: usize = 256;
+
+/// Default directory for persisted user preferences
+pub fn default_preferences_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rust-daq")
+        .join("preferences")
+}
+
+/// Who a preference belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PreferenceOwner {
+    /// A user, by client name
+    User(String),
+    /// A single session, by session ID
+    Session(String),
+}
+
+/// A stored preference value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreferenceEntry {
+    pub value: String,
+    /// Unix timestamp of the last write in nanoseconds
+    pub updated_ns: u64,
+    /// Host of the session that last wrote the value
+    pub updated_by: String,
+}
+
+/// One set or delete applied to an owner's preferences
+#[derive(Debug, Clone)]
+pub struct PreferenceChange {
+    pub owner: PreferenceOwner,
+    /// Entries that were written
+    pub set: BTreeMap<String, PreferenceEntry>,
+    /// Keys that were removed
+    pub deleted: Vec<String>,
+    /// Session that made the change
+    pub source_session_id: String,
+}
+
+/// In-memory preference store with optional per-user persistence
+#[derive(Debug)]
+pub struct PreferenceStore {
+    /// Where user preferences are persisted (`None` = memory only)
+    directory: Option<PathBuf>,
+    owners: RwLock<HashMap<PreferenceOwner, BTreeMap<String, PreferenceEntry>>>,
+    changes_tx: broadcast::Sender<PreferenceChange>,
+}
+
+impl Default for PreferenceStore {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl PreferenceStore {
+    /// Create a store persisting user preferences in `directory`
+    pub fn new(directory: Option<PathBuf>) -> Self {
+        if let Some(dir) = &directory
+            && let Err(e) = std::fs::create_dir_all(dir)
+        {
+            tracing::warn!("Failed to create preferences directory: {}", e);
+        }
+        let (changes_tx, _) = broadcast::channel(CHANGE_CHANNEL_CAPACITY);
+        Self {
+            directory,
+            owners: RwLock::new(HashMap::new()),
+            changes_tx,
+        }
+    }
+
+    /// Preferences of an owner whose keys start with `key_prefix`
+    pub fn get(
+        &self,
+        owner: &PreferenceOwner,
+        key_prefix: &str,
+    ) -> BTreeMap<String, PreferenceEntry> {
+        self.load(owner);
+        self.owners
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(owner)
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter(|(key, _)| key.starts_with(key_prefix))
+                    .map(|(key, entry)| (key.clone(), entry.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Write values for an owner, returning the stored entries
+    ///
+    /// Either all values are stored or, if one is invalid or the owner
+    /// would exceed [`MAX_PREFERENCES_PER_OWNER`], none are.
+    pub fn set(
+        &self,
+        owner: &PreferenceOwner,
+        values: BTreeMap<String, String>,
+        updated_by: &str,
+        source_session_id: &str,
+    ) -> Result<BTreeMap<String, PreferenceEntry>> {
+        for (key, value) in &values {
+            validate(key, value)?;
+        }
+        self.load(owner);
+        let updated_ns = now_ns();
+        let set: BTreeMap<String, PreferenceEntry> = values
+            .into_iter()
+            .map(|(key, value)| {
+                let entry = PreferenceEntry {
+                    value,
+                    updated_ns,
+                    updated_by: updated_by.to_string(),
+                };
+                (key, entry)
+            })
+            .collect();
+        {
+            let mut owners = self.owners.write().unwrap_or_else(|p| p.into_inner());
+            let entries = owners.entry(owner.clone()).or_default();
+            let added = set.keys().filter(|key| !entries.contains_key(*key)).count();
+            if entries.len() + added > MAX_PREFERENCES_PER_OWNER {
+                bail!(
+                    "Too many preferences (limit {} per user or session)",
+                    MAX_PREFERENCES_PER_OWNER
+                );
+            }
+            entries.extend(set.clone());
+            self.persist(owner, entries);
+        }
+        self.announce(PreferenceChange {
+            owner: owner.clone(),
+            set: set.clone(),
+            deleted: Vec::new(),
+            source_session_id: source_session_id.to_string(),
+        });
+        Ok(set)
+    }
+
+    /// Remove keys from an owner's preferences; returns how many existed
+    pub fn delete(
+        &self,
+        owner: &PreferenceOwner,
+        keys: &[String],
+        source_session_id: &str,
+    ) -> usize {
+        self.load(owner);
+        let deleted: Vec<String> = {
+            let mut owners = self.owners.write().unwrap_or_else(|p| p.into_inner());
+            let Some(entries) = owners.get_mut(owner) else {
+                return 0;
+            };
+            let deleted = keys
+                .iter()
+                .filter(|key| entries.remove(*key).is_some())
+                .cloned()
+                .collect::<Vec<_>>();
+            if !deleted.is_empty() {
+                self.persist(owner, entries);
+            }
+            deleted
+        };
+        let count = deleted.len();
+        if count > 0 {
+            self.announce(PreferenceChange {
+                owner: owner.clone(),
+                set: BTreeMap::new(),
+                deleted,
+                source_session_id: source_session_id.to_string(),
+            });
+        }
+        count
+    }
+
+    /// Forget a session's preferences once it has ended
+    pub fn drop_session(&self, session_id: &str) {
+        self.owners
+            .write()
+            .unwrap_or_else(|p| p.into_inner())
+            .remove(&PreferenceOwner::Session(session_id.to_string()));
+    }
+
+    /// Subscribe to preference changes of every owner
+    pub fn subscribe(&self) -> broadcast::Receiver<PreferenceChange> {
+        self.changes_tx.subscribe()
+    }
+
+    fn announce(&self, change: PreferenceChange) {
+        // No receivers is fine: no client is streaming preferences
+        let _ = self.changes_tx.send(change);
+    }
+
+    /// Read a user's persisted preferences the first time they are needed
+    fn load(&self, owner: &PreferenceOwner) {
+        let (Some(path), PreferenceOwner::User(_)) = (self.path_for(owner), owner) else {
+            return;
+        };
+        if self
+            .owners
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .contains_key(owner)
+        {
+            return;
+        }
+        let entries = match read_entries(&path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!(path = %path.display(), "Ignoring unreadable preferences: {:#}", e);
+                BTreeMap::new()
+            }
+        };
+        self.owners
+            .write()
+            .unwrap_or_else(|p| p.into_inner())
+            .entry(owner.clone())
+            .or_insert(entries);
+    }
+
+    /// Save a user's preferences; failures are logged, the change stays in memory
+    fn persist(&self, owner: &PreferenceOwner, entries: &BTreeMap<String, PreferenceEntry>) {
+        let Some(path) = self.path_for(owner) else {
+            return;
+        };
+        if let Err(e) = write_entries(&path, entries) {
+            tracing::warn!(path = %path.display(), "Failed to save preferences: {:#}", e);
+        }
+    }
+
+    /// Persistence file of a user (sessions are never persisted)
+    fn path_for(&self, owner: &PreferenceOwner) -> Option<PathBuf> {
+        match owner {
+            PreferenceOwner::User(name) => Some(
+                self.directory
+                    .as_ref()?
+                    .join(format!("{}.json", file_stem(name))),
+            ),
+            PreferenceOwner::Session(_) => None,
+        }
+    }
+}
+
+fn validate(key: &str, value: &str) -> Result<()> {
+    if key.is_empty() || key.len() > MAX_PREFERENCE_KEY_LEN {
+        bail!(
+            "Preference keys must be 1 to {} bytes long",
+            MAX_PREFERENCE_KEY_LEN
+        );
+    }
+    if key.chars().any(char::is_control) {
+        bail!(
+            "Preference key '{}' contains control characters",
+            key.escape_default()
+        );
+    }
+    if value.len() > MAX_PREFERENCE_VALUE_BYTES {
+        bail!(
+            "Preference '{}' is {} bytes (limit {})",
+            key,
+            value.len(),
+            MAX_PREFERENCE_VALUE_BYTES
+        );
+    }
+    Ok(())
+}
+
+fn read_entries(path: &Path) -> Result<BTreeMap<String, PreferenceEntry>> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn write_entries(path: &Path, entries: &BTreeMap<String, PreferenceEntry>) -> Result<()> {
+    let json = serde_json::to_vec_pretty(entries)?;
+    // Write to a temporary file first so a crash never leaves half a file
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).context("write failed")?;
+    std::fs::rename(&tmp, path).context("rename failed")?;
+    Ok(())
+}
+
+/// File name for a user, escaping anything but ASCII alphanumerics, `-` and `_`
+fn file_stem(name: &str) -> String {
+    name.bytes()
+        .map(|b| match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|&(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_user_preferences_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let alice = PreferenceOwner::User("alice smith".to_string());
+
+        let store = PreferenceStore::new(Some(dir.path().to_path_buf()));
+        let mut changes = store.subscribe();
+        store
+            .set(
+                &alice,
+                values(&[("plot.favorites", r#"["power"]"#), ("recent_plans", "[]")]),
+                "lab-pc",
+                "s1",
+            )
+            .unwrap();
+        assert_eq!(store.delete(&alice, &["recent_plans".to_string()], "s1"), 1);
+
+        let change = changes.try_recv().unwrap();
+        assert_eq!(change.set.len(), 2);
+        assert_eq!(change.source_session_id, "s1");
+        assert_eq!(changes.try_recv().unwrap().deleted, ["recent_plans"]);
+        assert!(dir.path().join("alice%20smith.json").exists());
+
+        // A restarted daemon sees the same preferences
+        let restarted = PreferenceStore::new(Some(dir.path().to_path_buf()));
+        let prefs = restarted.get(&alice, "plot.");
+        assert_eq!(prefs.len(), 1);
+        assert_eq!(prefs["plot.favorites"].value, r#"["power"]"#);
+        assert_eq!(prefs["plot.favorites"].updated_by, "lab-pc");
+    }
+
+    #[test]
+    fn test_session_preferences_and_limits() {
+        let store = PreferenceStore::default();
+        let session = PreferenceOwner::Session("s2".to_string());
+
+        store
+            .set(
+                &session,
+                values(&[("panel.layout", "compact")]),
+                "laptop",
+                "s2",
+            )
+            .unwrap();
+        assert_eq!(store.get(&session, "").len(), 1);
+        store.drop_session("s2");
+        assert!(store.get(&session, "").is_empty());
+
+        let too_big = "x".repeat(MAX_PREFERENCE_VALUE_BYTES + 1);
+        assert!(
+            store
+                .set(
+                    &session,
+                    values(&[("ok", "1"), ("big", &too_big)]),
+                    "laptop",
+                    "s2"
+                )
+                .is_err()
+        );
+        assert!(
+            store
+                .set(&session, values(&[("", "1")]), "laptop", "s2")
+                .is_err()
+        );
+        // Nothing from a rejected batch is stored
+        assert!(store.get(&session, "").is_empty());
+    }
+}
@@ -0,0 +1,411 @@
+//! Embedding the daemon in another Rust application
+//!
+//! [`DaqRuntimeBuilder`] assembles the same stack the `rust-daq daemon`
+//! command runs (device registry, RunEngine, storage, health monitoring and
+//! the gRPC server) and hands back a [`DaqRuntime`] the host application can
+//! drive directly while remote clients keep using gRPC.
+//!
+//! # Runtime ownership
+//!
+//! - [`DaqRuntimeBuilder::start`] runs on the caller's tokio runtime, or on
+//!   the one given with [`DaqRuntimeBuilder::runtime_handle`].
+//! - [`DaqRuntimeBuilder::start_blocking`] is for applications without tokio:
+//!   it creates a dedicated multi-threaded runtime owned by the [`DaqRuntime`]
+//!   (unless a handle was given) and must be called outside an async context.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use server::runtime::DaqRuntimeBuilder;
+//!
+//! let daemon = DaqRuntimeBuilder::new()
+//!     .port(50051)
+//!     .mock_hardware()
+//!     .start()
+//!     .await?;
+//!
+//! // Drive the RunEngine in-process
+//! daemon.run_engine().queue(plan).await;
+//! daemon.run_engine().start().await?;
+//!
+//! daemon.shutdown().await?;
+//! ```
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{Context, Result, anyhow};
+use common::health::SystemHealthMonitor;
+use common::supervisor::{RestartPolicy, supervise};
+use experiment::RunEngine;
+use hardware::registry::{
+    DeviceRegistry, HardwareConfig, create_lab_registry, create_mock_registry,
+    create_registry_from_config, register_all_factories,
+};
+use tokio::runtime::{Handle, Runtime};
+use tokio::sync::{oneshot, watch};
+use tokio::task::JoinHandle;
+
+use crate::grpc::server::{ServerOptions, start_server_with_shutdown};
+use crate::health::HealthMonitorConfig;
+use crate::health::sys_monitor::SystemMetricsCollector;
+use crate::simulator::{SimulatorConfig, create_simulator_registry, run_simulated_scans};
+
+/// Default gRPC port, same as the daemon command
+pub const DEFAULT_PORT: u16 = 50051;
+
+/// Where the device registry comes from
+enum HardwareSource {
+    Mock,
+    Lab,
+    Config(Box<HardwareConfig>),
+    Registry(Arc<DeviceRegistry>),
+    Simulator(SimulatorConfig),
+}
+
+/// Builder for an embedded daemon
+pub struct DaqRuntimeBuilder {
+    addr: SocketAddr,
+    hardware: HardwareSource,
+    factory_config_dir: Option<PathBuf>,
+    options: ServerOptions,
+    health_monitor: Option<Arc<SystemHealthMonitor>>,
+    runtime_handle: Option<Handle>,
+    worker_threads: Option<usize>,
+    restart_policy: RestartPolicy,
+}
+
+impl Default for DaqRuntimeBuilder {
+    fn default() -> Self {
+        Self {
+            addr: SocketAddr::from(([0, 0, 0, 0], DEFAULT_PORT)),
+            hardware: HardwareSource::Mock,
+            factory_config_dir: Some(PathBuf::from("config/devices")),
+            options: ServerOptions::default(),
+            health_monitor: None,
+            runtime_handle: None,
+            worker_threads: None,
+            restart_policy: RestartPolicy::default(),
+        }
+    }
+}
+
+impl DaqRuntimeBuilder {
+    /// Mock devices on `0.0.0.0:50051`, default [`ServerOptions`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Address the gRPC server binds to (`grpc.bind_address` still applies)
+    pub fn bind(mut self, addr: SocketAddr) -> Self {
+        self.addr = addr;
+        self
+    }
+
+    /// Port the gRPC server listens on
+    pub fn port(mut self, port: u16) -> Self {
+        self.addr.set_port(port);
+        self
+    }
+
+    /// Use mock devices only (the default)
+    pub fn mock_hardware(mut self) -> Self {
+        self.hardware = HardwareSource::Mock;
+        self
+    }
+
+    /// Use the built-in lab hardware configuration
+    pub fn lab_hardware(mut self) -> Self {
+        self.hardware = HardwareSource::Lab;
+        self
+    }
+
+    /// Create devices from a hardware configuration
+    pub fn hardware_config(mut self, config: HardwareConfig) -> Self {
+        self.hardware = HardwareSource::Config(Box::new(config));
+        self
+    }
+
+    /// Serve synthetic data instead of devices (see [`crate::simulator`])
+    pub fn simulated_data(mut self, config: SimulatorConfig) -> Self {
+        self.hardware = HardwareSource::Simulator(config);
+        self
+    }
+
+    /// Use a registry the host application already populated
+    pub fn registry(mut self, registry: Arc<DeviceRegistry>) -> Self {
+        self.hardware = HardwareSource::Registry(registry);
+        self
+    }
+
+    /// Directory of driver factory configs (default `config/devices`, `None` to skip)
+    pub fn factory_config_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.factory_config_dir = dir;
+        self
+    }
+
+    /// Module persistence, history retention, run signing and library options
+    pub fn server_options(mut self, options: ServerOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Share an existing health monitor instead of creating one
+    ///
+    /// The caller is then responsible for feeding it system metrics.
+    pub fn health_monitor(mut self, monitor: Arc<SystemHealthMonitor>) -> Self {
+        self.health_monitor = Some(monitor);
+        self
+    }
+
+    /// Run the daemon's tasks on this runtime instead of the caller's
+    pub fn runtime_handle(mut self, handle: Handle) -> Self {
+        self.runtime_handle = Some(handle);
+        self
+    }
+
+    /// Worker threads of the runtime created by [`Self::start_blocking`]
+    pub fn worker_threads(mut self, threads: usize) -> Self {
+        self.worker_threads = Some(threads);
+        self
+    }
+
+    /// How the gRPC server is restarted after it fails
+    pub fn restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.restart_policy = policy;
+        self
+    }
+
+    /// Start the daemon on the caller's (or the configured) tokio runtime
+    pub async fn start(mut self) -> Result<DaqRuntime> {
+        let handle = match self.runtime_handle.take() {
+            Some(handle) => handle,
+            None => {
+                Handle::try_current().context("DaqRuntimeBuilder::start needs a tokio runtime")?
+            }
+        };
+        self.launch(handle).await
+    }
+
+    /// Start the daemon from synchronous code
+    ///
+    /// Creates and owns a multi-threaded runtime unless one was given with
+    /// [`Self::runtime_handle`]. Must not be called from within an async context.
+    pub fn start_blocking(mut self) -> Result<DaqRuntime> {
+        if let Some(handle) = self.runtime_handle.take() {
+            return handle.clone().block_on(self.launch(handle));
+        }
+
+        let mut runtime = tokio::runtime::Builder::new_multi_thread();
+        runtime.enable_all().thread_name("rust-daq");
+        if let Some(threads) = self.worker_threads {
+            runtime.worker_threads(threads);
+        }
+        let runtime = runtime.build().context("Failed to create tokio runtime")?;
+        let handle = runtime.handle().clone();
+        // Attached afterwards: a runtime must not be dropped inside its own block_on
+        let mut daemon = handle.block_on(self.launch(handle.clone()))?;
+        daemon.runtime = Some(runtime);
+        Ok(daemon)
+    }
+
+    async fn launch(self, handle: Handle) -> Result<DaqRuntime> {
+        let mut simulator = None;
+        let registry = match self.hardware {
+            HardwareSource::Mock => Arc::new(create_mock_registry().await?),
+            HardwareSource::Lab => Arc::new(create_lab_registry().await?),
+            HardwareSource::Config(config) => Arc::new(create_registry_from_config(&config).await?),
+            HardwareSource::Registry(registry) => registry,
+            HardwareSource::Simulator(config) => {
+                let registry = Arc::new(create_simulator_registry(&config).await?);
+                simulator = Some(config);
+                registry
+            }
+        };
+        if let Err(e) = register_all_factories(&registry, self.factory_config_dir.as_deref()).await
+        {
+            tracing::warn!("Failed to register some factories: {}", e);
+        }
+
+        let mut background = Vec::new();
+        let health_monitor = match self.health_monitor {
+            Some(monitor) => monitor,
+            None => {
+                let monitor = Arc::new(SystemHealthMonitor::new(HealthMonitorConfig::default()));
+                let collector = SystemMetricsCollector::new(monitor.clone());
+                background.push(handle.spawn(collector.run()));
+                monitor
+            }
+        };
+        background.push(handle.spawn(registry_heartbeat(registry.clone(), health_monitor.clone())));
+
+        let run_engine = Arc::new(RunEngine::new(registry.clone()));
+        if let Some(config) = simulator {
+            background.push(handle.spawn(run_simulated_scans(run_engine.clone(), config)));
+        }
+
+        // The server future is not required to be Send, so it is driven from
+        // its own thread; connection tasks still run on the runtime's workers.
+        // A server that fails or panics is restarted under supervision.
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let (done_tx, done_rx) = oneshot::channel();
+        let server = {
+            let (addr, registry, run_engine, health_monitor, options, policy, handle) = (
+                self.addr,
+                registry.clone(),
+                run_engine.clone(),
+                health_monitor.clone(),
+                self.options,
+                self.restart_policy,
+                handle.clone(),
+            );
+            move || {
+                let result = handle.block_on(supervise("grpc_server", &policy, || {
+                    let mut shutdown_rx = shutdown_rx.clone();
+                    let server = start_server_with_shutdown(
+                        addr,
+                        registry.clone(),
+                        run_engine.clone(),
+                        health_monitor.clone(),
+                        options.clone(),
+                        async move {
+                            // Dropping the DaqRuntime closes the channel, which stops the server too
+                            let _ = shutdown_rx.wait_for(|stop| *stop).await;
+                        },
+                    );
+                    async move { server.await.map_err(|e| anyhow!("{}", e)) }
+                }));
+                let _ = done_tx.send(result.map_err(|e| format!("{:#}", e)));
+            }
+        };
+        std::thread::Builder::new()
+            .name("rust-daq-grpc".to_string())
+            .spawn(server)
+            .context("Failed to spawn gRPC server thread")?;
+
+        Ok(DaqRuntime {
+            addr: self.addr,
+            registry,
+            run_engine,
+            health_monitor,
+            handle,
+            shutdown_tx: Some(shutdown_tx),
+            server_done: Some(done_rx),
+            background,
+            runtime: None,
+        })
+    }
+}
+
+/// Report the registry to the health monitor every 10 s
+async fn registry_heartbeat(registry: Arc<DeviceRegistry>, monitor: Arc<SystemHealthMonitor>) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(10));
+    loop {
+        interval.tick().await;
+        monitor
+            .heartbeat_with_message(
+                "hardware_registry",
+                Some(format!("Managing {} devices", registry.len())),
+            )
+            .await;
+    }
+}
+
+/// A running embedded daemon
+///
+/// Dropping it stops the gRPC server; call [`Self::shutdown`] (or
+/// [`Self::shutdown_blocking`]) to also shut devices down cleanly.
+pub struct DaqRuntime {
+    addr: SocketAddr,
+    registry: Arc<DeviceRegistry>,
+    run_engine: Arc<RunEngine>,
+    health_monitor: Arc<SystemHealthMonitor>,
+    handle: Handle,
+    shutdown_tx: Option<watch::Sender<bool>>,
+    server_done: Option<oneshot::Receiver<Result<(), String>>>,
+    background: Vec<JoinHandle<()>>,
+    // Declared last: an owned runtime must outlive everything spawned on it
+    runtime: Option<Runtime>,
+}
+
+impl DaqRuntime {
+    /// Address the gRPC server was asked to bind
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Devices served by this daemon
+    pub fn registry(&self) -> &Arc<DeviceRegistry> {
+        &self.registry
+    }
+
+    /// The RunEngine behind RunEngineService and script execution
+    pub fn run_engine(&self) -> &Arc<RunEngine> {
+        &self.run_engine
+    }
+
+    pub fn health_monitor(&self) -> &Arc<SystemHealthMonitor> {
+        &self.health_monitor
+    }
+
+    /// Runtime the daemon's tasks run on, for spawning host tasks next to them
+    pub fn handle(&self) -> &Handle {
+        &self.handle
+    }
+
+    /// Whether the tokio runtime is owned by this daemon ([`DaqRuntimeBuilder::start_blocking`])
+    pub fn owns_runtime(&self) -> bool {
+        self.runtime.is_some()
+    }
+
+    /// Wait until the gRPC server exits (e.g. because the port stayed taken
+    /// through every restart)
+    pub async fn wait(&mut self) -> Result<()> {
+        match self.server_done.take() {
+            Some(done) => done
+                .await
+                .map_err(|_| anyhow!("gRPC server thread exited unexpectedly"))?
+                .map_err(|e| anyhow!("gRPC server error: {}", e)),
+            None => Ok(()),
+        }
+    }
+
+    /// Stop the gRPC server and shut all devices down
+    pub async fn shutdown(mut self) -> Result<()> {
+        if let Some(shutdown_tx) = self.shutdown_tx.take() {
+            let _ = shutdown_tx.send(true);
+        }
+        let server = self.wait().await;
+        for task in self.background.drain(..) {
+            task.abort();
+        }
+        self.registry
+            .shutdown_all()
+            .await
+            .map_err(|e| anyhow!("Device shutdown encountered errors: {}", e))?;
+        server
+    }
+
+    /// [`Self::shutdown`] from synchronous code, then drop an owned runtime
+    pub fn shutdown_blocking(mut self) -> Result<()> {
+        let runtime = self.runtime.take();
+        let handle = self.handle.clone();
+        let result = handle.block_on(self.shutdown());
+        if let Some(runtime) = runtime {
+            runtime.shutdown_timeout(std::time::Duration::from_secs(5));
+        }
+        result
+    }
+}
+
+impl Drop for DaqRuntime {
+    fn drop(&mut self) {
+        if let Some(shutdown_tx) = self.shutdown_tx.take() {
+            let _ = shutdown_tx.send(true);
+        }
+        for task in self.background.drain(..) {
+            task.abort();
+        }
+    }
+}
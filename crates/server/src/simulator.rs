@@ -0,0 +1,243 @@
+//! Synthetic data for developing clients without hardware
+//!
+//! Simulator mode (`rust-daq daemon --simulate-data`) serves made-up but
+//! realistic data behind the normal gRPC services, so GUI panels can be
+//! developed without hardware or even a mock device configuration:
+//!
+//! - `sim_signal_1` … `sim_signal_N`: noisy sine channels for value and
+//!   observable streams, channel `n` at `n` times the base frequency and
+//!   with staggered phases;
+//! - `sim_beam_camera`: a camera streaming a drifting Gaussian beam at
+//!   `frame_rate_hz` (at most 30 fps, the mock camera's readout limit);
+//! - `sim_stage`: the stage the simulated scans move;
+//! - every `scan_interval_s`, while the RunEngine is idle with an empty
+//!   queue, a line scan of the signals over `sim_stage`, producing the usual
+//!   start, descriptor, event and stop documents.
+//!
+//! Simulated runs carry [`SIMULATED_METADATA_KEY`] in their metadata so they
+//! can be told apart from runs queued by a client.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use experiment::plans::LineScan;
+use experiment::{EngineState, RunEngine};
+use hardware::registry::{DeviceRegistry, register_mock_factories};
+
+/// Run metadata key marking runs queued by the simulator
+pub const SIMULATED_METADATA_KEY: &str = "simulated";
+
+/// Device ID of the simulated camera
+pub const SIM_CAMERA_ID: &str = "sim_beam_camera";
+
+/// Device ID of the stage moved by simulated scans
+pub const SIM_STAGE_ID: &str = "sim_stage";
+
+/// Fastest frame rate the simulated camera can deliver
+pub const MAX_SIM_FRAME_RATE_HZ: f64 = 30.0;
+
+/// What the simulator serves, and how fast
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatorConfig {
+    /// Number of sine channels
+    pub signals: usize,
+    /// Frequency of the first sine channel
+    pub signal_frequency_hz: f64,
+    /// Uniform noise half-width on the unit-amplitude sines
+    pub noise: f64,
+    /// Camera frame rate while streaming
+    pub frame_rate_hz: f64,
+    pub frame_width: u32,
+    pub frame_height: u32,
+    /// Seconds between simulated scans (0 disables them)
+    pub scan_interval_s: f64,
+    /// Points per simulated scan
+    pub scan_points: usize,
+}
+
+impl Default for SimulatorConfig {
+    fn default() -> Self {
+        Self {
+            signals: 4,
+            signal_frequency_hz: 0.5,
+            noise: 0.05,
+            frame_rate_hz: 10.0,
+            frame_width: 640,
+            frame_height: 480,
+            scan_interval_s: 30.0,
+            scan_points: 50,
+        }
+    }
+}
+
+impl SimulatorConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=64).contains(&self.signals) {
+            bail!("Simulator needs 1 to 64 signals, got {}", self.signals);
+        }
+        if !(self.signal_frequency_hz >= 0.0 && self.noise >= 0.0) {
+            bail!("Simulator signal frequency and noise must not be negative");
+        }
+        if !(self.frame_rate_hz > 0.0 && self.frame_rate_hz <= MAX_SIM_FRAME_RATE_HZ) {
+            bail!(
+                "Simulator frame rate must be between 0 and {} Hz, got {}",
+                MAX_SIM_FRAME_RATE_HZ,
+                self.frame_rate_hz
+            );
+        }
+        if self.frame_width == 0 || self.frame_height == 0 {
+            bail!("Simulator frame size must be non-zero");
+        }
+        if !(self.scan_interval_s >= 0.0 && self.scan_interval_s.is_finite()) {
+            bail!("Invalid simulator scan interval {}", self.scan_interval_s);
+        }
+        if self.scan_points < 2 {
+            bail!("Simulated scans need at least 2 points");
+        }
+        Ok(())
+    }
+
+    /// Device IDs of the sine channels
+    pub fn signal_ids(&self) -> Vec<String> {
+        (1..=self.signals)
+            .map(|n| format!("sim_signal_{}", n))
+            .collect()
+    }
+}
+
+/// Registry of the simulated devices
+pub async fn create_simulator_registry(config: &SimulatorConfig) -> Result<DeviceRegistry> {
+    config.validate()?;
+    let registry = DeviceRegistry::new();
+    register_mock_factories(&registry);
+
+    for (n, id) in (1..).zip(config.signal_ids()) {
+        let mut signal = toml::Table::new();
+        signal.insert(
+            "frequency_hz".into(),
+            (config.signal_frequency_hz * n as f64).into(),
+        );
+        signal.insert(
+            "phase_deg".into(),
+            (360.0 * (n - 1) as f64 / config.signals as f64).into(),
+        );
+        signal.insert("noise".into(), config.noise.into());
+        registry
+            .register_from_toml(
+                &id,
+                &format!("Simulated signal {}", n),
+                "mock_signal",
+                signal.into(),
+            )
+            .await?;
+    }
+
+    let mut camera = toml::Table::new();
+    camera.insert("width".into(), i64::from(config.frame_width).into());
+    camera.insert("height".into(), i64::from(config.frame_height).into());
+    camera.insert("pattern".into(), "gaussian_beam".into());
+    camera.insert("frame_rate_hz".into(), config.frame_rate_hz.into());
+    registry
+        .register_from_toml(
+            SIM_CAMERA_ID,
+            "Simulated beam camera",
+            "mock_camera",
+            camera.into(),
+        )
+        .await?;
+
+    let mut stage = toml::Table::new();
+    stage.insert("speed_mm_per_sec".into(), 20.0.into());
+    registry
+        .register_from_toml(SIM_STAGE_ID, "Simulated stage", "mock_stage", stage.into())
+        .await?;
+
+    Ok(registry)
+}
+
+/// The line scan the simulator queues
+pub fn simulated_scan(config: &SimulatorConfig) -> LineScan {
+    let detectors = config.signal_ids();
+    let detectors: Vec<&str> = detectors.iter().map(String::as_str).collect();
+    LineScan::new(SIM_STAGE_ID, 0.0, 10.0, config.scan_points).with_detectors(&detectors)
+}
+
+/// Queue and run a simulated scan every `scan_interval_s`
+///
+/// A scan is only queued while the engine is idle with an empty queue, so
+/// runs queued by clients always go first.
+pub async fn run_simulated_scans(run_engine: Arc<RunEngine>, config: SimulatorConfig) {
+    if config.scan_interval_s == 0.0 {
+        return;
+    }
+    let mut interval = tokio::time::interval(Duration::from_secs_f64(config.scan_interval_s));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        if run_engine.state().await != EngineState::Idle || run_engine.queue_len().await > 0 {
+            continue;
+        }
+        let metadata = HashMap::from([(SIMULATED_METADATA_KEY.to_string(), "true".to_string())]);
+        let run_uid = run_engine
+            .queue_with_metadata(Box::new(simulated_scan(&config)), metadata)
+            .await;
+        tracing::debug!(run_uid = %run_uid, "Starting simulated scan");
+        // Fails if a client started the engine in the meantime; the scan then
+        // runs after the client's runs
+        if let Err(e) = run_engine.start().await {
+            tracing::debug!(run_uid = %run_uid, error = %e, "Simulated scan did not run");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_simulator_serves_signals_camera_and_scans() {
+        let config = SimulatorConfig {
+            signals: 2,
+            frame_width: 64,
+            frame_height: 64,
+            scan_points: 3,
+            ..Default::default()
+        };
+        let registry = Arc::new(create_simulator_registry(&config).await.unwrap());
+        assert_eq!(registry.len(), 4);
+        let value = registry
+            .get_readable("sim_signal_2")
+            .unwrap()
+            .read()
+            .await
+            .unwrap();
+        assert!(value.abs() <= 1.0 + config.noise);
+        assert_eq!(
+            registry
+                .get_frame_producer(SIM_CAMERA_ID)
+                .unwrap()
+                .resolution(),
+            (64, 64)
+        );
+
+        let run_engine = RunEngine::new(registry);
+        let result = run_engine
+            .queue_and_execute(Box::new(simulated_scan(&config)), Duration::from_secs(30))
+            .await
+            .unwrap();
+        assert_eq!(result.exit_status, "success");
+        assert_eq!(result.num_events, 3);
+        assert!(result.data.contains_key("sim_signal_1"));
+
+        assert!(
+            SimulatorConfig {
+                frame_rate_hz: 60.0,
+                ..config
+            }
+            .validate()
+            .is_err()
+        );
+    }
+}
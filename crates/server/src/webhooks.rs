@@ -0,0 +1,742 @@
+//! Webhook notifications of run and alarm events.
+//!
+//! Lab chat channels and LIMS systems can follow experiments without a
+//! polling service of their own: each configured webhook receives an HTTP
+//! POST with a JSON body when a run starts, finishes or fails, and when an
+//! alarm is raised. Webhooks are configured in `config/config.v4.toml`:
+//!
+//! ```toml
+//! [[webhooks]]
+//! name = "lab-slack"
+//! url = "https://hooks.slack.com/services/T000/B000/XXXX"
+//! format = "slack"                  # or "json" (the full notification)
+//! events = ["run_finish", "run_error", "alarm"]  # empty = all
+//! min_alarm_severity = "error"      # info, warning, error or critical
+//! secret = "shared-signing-key"     # optional, see Signing
+//! ```
+//!
+//! Runs are reported from the run engine's start and stop documents, after
+//! the [document transform] chain for the webhook's name, so e.g. operator
+//! names can be redacted from what leaves the lab. A stop with exit status
+//! `fail` is a `run_error`; `success` and `abort` are a `run_finish`. Alarms
+//! are the errors reported to the health monitor.
+//!
+//! # Delivery
+//!
+//! Notifications are posted one at a time, in order. A request that fails
+//! to connect, times out or is answered with a 5xx or 429 status is retried
+//! with exponential backoff, up to `max_retries` times; any other answer is
+//! final. Notifications that still fail, or that arrive while
+//! `queue_capacity` others are waiting, are dropped and logged.
+//!
+//! # Signing
+//!
+//! With a `secret`, every request carries `X-Rust-Daq-Timestamp`, the Unix
+//! time in seconds it was sent at, and `X-Rust-Daq-Signature: sha256=<hex>`,
+//! the HMAC-SHA256 of `<timestamp>.<body>` keyed with the secret, so
+//! receivers can check the notification came from this daemon and refuse
+//! old ones replayed to them. `X-Rust-Daq-Delivery` is the notification ID,
+//! the same for every retry, for de-duplication.
+//!
+//! Endpoint URLs often embed a credential (Slack's do), so they are left out
+//! of delivery errors and logs.
+//!
+//! [document transform]: common::document_transform
+
+use anyhow::{Context, Result, anyhow, bail};
+use common::experiment::document::{Document, StartDoc};
+use common::health::{ErrorSeverity, HealthError, SystemHealthMonitor};
+use common::log_scrubbing::Redacted;
+use experiment::document_bus::DocumentRecvError;
+use experiment::run_engine::RunEngine;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Notifications a webhook can fall behind by before some are dropped
+pub const DEFAULT_WEBHOOK_QUEUE: usize = 256;
+
+/// Header carrying the HMAC-SHA256 signature of timestamp and body
+pub const SIGNATURE_HEADER: &str = "X-Rust-Daq-Signature";
+
+/// Header carrying the signed send time (Unix seconds)
+pub const TIMESTAMP_HEADER: &str = "X-Rust-Daq-Timestamp";
+
+/// Header carrying the notification ID
+pub const DELIVERY_HEADER: &str = "X-Rust-Daq-Delivery";
+
+/// Header carrying the event name
+pub const EVENT_HEADER: &str = "X-Rust-Daq-Event";
+
+const MIN_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// What a notification is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    RunStart,
+    /// Completed or aborted run
+    RunFinish,
+    /// Failed run
+    RunError,
+    Alarm,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::RunStart => "run_start",
+            WebhookEvent::RunFinish => "run_finish",
+            WebhookEvent::RunError => "run_error",
+            WebhookEvent::Alarm => "alarm",
+        }
+    }
+}
+
+/// Request body layout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    /// The [`Notification`] as JSON
+    #[default]
+    Json,
+    /// `{"text": "<one-line summary>"}`, as Slack and Mattermost incoming
+    /// webhooks expect
+    Slack,
+}
+
+/// One `[[webhooks]]` entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebhookConfig {
+    /// Name for logs, and the webhook's document transform consumer name
+    pub name: String,
+    /// `http://` or `https://` endpoint
+    pub url: String,
+    pub format: WebhookFormat,
+    /// Events to send (empty = all)
+    pub events: Vec<WebhookEvent>,
+    /// Least severe health error sent as an alarm
+    pub min_alarm_severity: ErrorSeverity,
+    /// HMAC-SHA256 signing key (requests are unsigned without one)
+    pub secret: Option<String>,
+    /// Bound on each request, connection included
+    pub timeout_secs: u64,
+    /// Retries of a failed request before the notification is dropped
+    pub max_retries: u32,
+    pub queue_capacity: usize,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            name: "webhook".to_string(),
+            url: String::new(),
+            format: WebhookFormat::default(),
+            events: Vec::new(),
+            min_alarm_severity: ErrorSeverity::Warning,
+            secret: None,
+            timeout_secs: 10,
+            max_retries: 5,
+            queue_capacity: DEFAULT_WEBHOOK_QUEUE,
+        }
+    }
+}
+
+impl WebhookConfig {
+    pub fn validate(&self) -> Result<()> {
+        let url = self.url.trim();
+        if url.is_empty() {
+            bail!("Webhook '{}' has no url", self.name);
+        }
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            bail!(
+                "Webhook '{}': url '{}' must start with http:// or https://",
+                self.name,
+                url
+            );
+        }
+        if self.timeout_secs == 0 {
+            bail!("Webhook '{}' needs a timeout_secs above 0", self.name);
+        }
+        if self.queue_capacity == 0 {
+            bail!("Webhook '{}' needs a queue_capacity above 0", self.name);
+        }
+        Ok(())
+    }
+
+    /// Signing key, if one is configured
+    fn secret(&self) -> Option<&str> {
+        self.secret.as_deref().filter(|s| !s.is_empty())
+    }
+
+    fn sends(&self, notification: &Notification) -> bool {
+        if let Some(alarm) = &notification.alarm
+            && alarm.severity < self.min_alarm_severity
+        {
+            return false;
+        }
+        self.events.is_empty() || self.events.contains(&notification.event)
+    }
+}
+
+/// A run as reported in notifications
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunSummary {
+    pub run_uid: String,
+    pub plan_type: String,
+    pub plan_name: String,
+    pub metadata: BTreeMap<String, String>,
+    /// `success`, `abort` or `fail`; absent at run start
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_events: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+}
+
+/// A health error as reported in notifications
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AlarmSummary {
+    pub module: String,
+    pub severity: ErrorSeverity,
+    pub message: String,
+    pub context: BTreeMap<String, String>,
+}
+
+/// Body of a `json` webhook request
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Notification {
+    /// Unique per notification, kept across retries
+    pub id: String,
+    pub event: WebhookEvent,
+    pub timestamp_ns: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run: Option<RunSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alarm: Option<AlarmSummary>,
+}
+
+impl Notification {
+    fn new(event: WebhookEvent) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            event,
+            timestamp_ns: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos() as u64,
+            run: None,
+            alarm: None,
+        }
+    }
+
+    /// One line for chat channels
+    pub fn summary(&self) -> String {
+        if let Some(alarm) = &self.alarm {
+            return format!(
+                "Alarm ({}) from {}: {}",
+                alarm.severity, alarm.module, alarm.message
+            );
+        }
+        let Some(run) = &self.run else {
+            return self.event.as_str().to_string();
+        };
+        let name = format!("{} ({})", run.plan_name, run.run_uid);
+        match (self.event, run.exit_status.as_deref()) {
+            (WebhookEvent::RunStart, _) => format!("Run started: {}", name),
+            (_, Some("abort")) => format!("Run aborted: {}: {}", name, run.reason_or_none()),
+            (WebhookEvent::RunError, _) => {
+                format!("Run failed: {}: {}", name, run.reason_or_none())
+            }
+            _ => format!(
+                "Run finished: {}, {} events in {:.0} s",
+                name,
+                run.num_events.unwrap_or(0),
+                run.duration_secs.unwrap_or(0.0)
+            ),
+        }
+    }
+}
+
+impl RunSummary {
+    fn from_start(start: &StartDoc) -> Self {
+        Self {
+            run_uid: start.uid.clone(),
+            plan_type: start.plan_type.clone(),
+            plan_name: start.plan_name.clone(),
+            metadata: start
+                .metadata
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            exit_status: None,
+            reason: None,
+            num_events: None,
+            duration_secs: None,
+        }
+    }
+
+    fn reason_or_none(&self) -> &str {
+        self.reason
+            .as_deref()
+            .filter(|r| !r.is_empty())
+            .unwrap_or("no reason given")
+    }
+}
+
+/// Notification for a run document, if it starts or ends a run
+///
+/// `runs` holds the start documents of runs in progress, so the stop
+/// notification can name the plan.
+pub fn run_notification(
+    runs: &mut HashMap<String, StartDoc>,
+    doc: &Document,
+) -> Option<Notification> {
+    match doc {
+        Document::Start(start) => {
+            runs.insert(start.uid.clone(), start.clone());
+            let mut notification = Notification::new(WebhookEvent::RunStart);
+            notification.run = Some(RunSummary::from_start(start));
+            Some(notification)
+        }
+        Document::Stop(stop) => {
+            let start = runs.remove(&stop.run_uid);
+            let event = if stop.exit_status == "fail" {
+                WebhookEvent::RunError
+            } else {
+                WebhookEvent::RunFinish
+            };
+            let mut run = match &start {
+                Some(start) => RunSummary::from_start(start),
+                // Started before the webhook subscribed
+                None => RunSummary {
+                    run_uid: stop.run_uid.clone(),
+                    plan_type: String::new(),
+                    plan_name: String::new(),
+                    metadata: BTreeMap::new(),
+                    exit_status: None,
+                    reason: None,
+                    num_events: None,
+                    duration_secs: None,
+                },
+            };
+            run.exit_status = Some(stop.exit_status.clone());
+            run.reason = Some(stop.reason.clone()).filter(|r| !r.is_empty());
+            run.num_events = Some(stop.num_events);
+            run.duration_secs =
+                start.map(|start| stop.time_ns.saturating_sub(start.time_ns) as f64 / 1e9);
+            let mut notification = Notification::new(event);
+            notification.run = Some(run);
+            Some(notification)
+        }
+        _ => None,
+    }
+}
+
+/// Notification for an error reported to the health monitor
+pub fn alarm_notification(error: &HealthError) -> Notification {
+    let mut notification = Notification::new(WebhookEvent::Alarm);
+    notification.alarm = Some(AlarmSummary {
+        module: error.module_name.clone(),
+        severity: error.severity,
+        message: error.message.clone(),
+        context: error
+            .context
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+    });
+    notification
+}
+
+/// HMAC-SHA256 of `message` keyed with `key`
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+/// `sha256=<hex>` signature header value for `body` sent at `timestamp`
+/// (Unix seconds)
+pub fn signature(secret: &str, timestamp: u64, body: &[u8]) -> String {
+    let mut message = format!("{}.", timestamp).into_bytes();
+    message.extend_from_slice(body);
+    let mac = hmac_sha256(secret.as_bytes(), &message);
+    let hex: String = mac.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256={}", hex)
+}
+
+/// Result of one POST
+enum Attempt {
+    Delivered,
+    /// Worth trying again later
+    Retry(anyhow::Error),
+    /// The receiver refused the notification
+    Rejected(anyhow::Error),
+}
+
+struct WebhookSender {
+    config: WebhookConfig,
+    client: reqwest::Client,
+}
+
+impl WebhookSender {
+    fn new(config: WebhookConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .user_agent(concat!("rust-daq/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .context("Failed to create HTTP client")?;
+        Ok(Self { config, client })
+    }
+
+    fn body(&self, notification: &Notification) -> Result<Vec<u8>> {
+        let body = match self.config.format {
+            WebhookFormat::Json => serde_json::to_vec(notification),
+            WebhookFormat::Slack => {
+                serde_json::to_vec(&serde_json::json!({ "text": notification.summary() }))
+            }
+        };
+        body.context("Failed to serialize notification")
+    }
+
+    async fn post(&self, notification: &Notification, body: &[u8]) -> Attempt {
+        let mut request = self
+            .client
+            .post(self.config.url.trim())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, notification.event.as_str())
+            .header(DELIVERY_HEADER, &notification.id)
+            .body(body.to_vec());
+        if let Some(secret) = self.config.secret() {
+            // Each attempt is signed anew, so retries aren't mistaken for replays
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            request = request
+                .header(TIMESTAMP_HEADER, timestamp)
+                .header(SIGNATURE_HEADER, signature(secret, timestamp, body));
+        }
+        match request.send().await {
+            Ok(response) if response.status().is_success() => Attempt::Delivered,
+            Ok(response) => {
+                let status = response.status();
+                let error = anyhow!(
+                    "Endpoint {} answered HTTP {}",
+                    Redacted::new(self.config.url.trim()),
+                    status
+                );
+                if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                    Attempt::Retry(error)
+                } else {
+                    Attempt::Rejected(error)
+                }
+            }
+            Err(e) => Attempt::Retry(
+                anyhow::Error::new(e.without_url()).context("Webhook request failed"),
+            ),
+        }
+    }
+
+    /// Post `notification`, retrying as configured
+    async fn deliver(&self, notification: &Notification) -> Result<()> {
+        let body = self.body(notification)?;
+        let mut delay = MIN_RETRY_DELAY;
+        let mut retries = 0;
+        loop {
+            let error = match self.post(notification, &body).await {
+                Attempt::Delivered => return Ok(()),
+                Attempt::Rejected(e) => return Err(e),
+                Attempt::Retry(e) => e,
+            };
+            if retries >= self.config.max_retries {
+                return Err(error.context(format!("Gave up after {} retries", retries)));
+            }
+            tracing::warn!(
+                webhook = %self.config.name,
+                event = notification.event.as_str(),
+                error = %format!("{:#}", error),
+                retry_in = ?delay,
+                "Webhook delivery failed"
+            );
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_RETRY_DELAY);
+            retries += 1;
+        }
+    }
+}
+
+/// Start posting `engine`'s runs and `health`'s alarms as `config` describes
+///
+/// Fails if the configuration is invalid; delivery problems are handled (and
+/// logged) by the webhook's task.
+pub fn spawn_webhook(
+    config: WebhookConfig,
+    engine: Arc<RunEngine>,
+    health: &SystemHealthMonitor,
+) -> Result<JoinHandle<()>> {
+    config.validate()?;
+    let mut documents = engine.subscribe();
+    let mut alarms = health.subscribe_errors();
+    let transforms = engine.document_transforms();
+    let (tx, mut rx) = mpsc::channel::<Notification>(config.queue_capacity);
+    let sender = WebhookSender::new(config.clone())?;
+
+    // Run documents and health errors become notifications
+    tokio::spawn(async move {
+        let mut runs = HashMap::new();
+        loop {
+            let notification = tokio::select! {
+                doc = documents.recv() => match doc {
+                    Ok(doc @ (Document::Start(_) | Document::Stop(_))) => {
+                        run_notification(&mut runs, &transforms.apply(&config.name, doc))
+                    }
+                    Ok(_) => None,
+                    Err(DocumentRecvError::Gap { missed }) => {
+                        tracing::warn!(webhook = %config.name, missed, "Webhook fell behind the document stream");
+                        None
+                    }
+                    Err(DocumentRecvError::Closed) => break,
+                },
+                error = alarms.recv() => match error {
+                    Ok(error) => Some(alarm_notification(&error)),
+                    Err(RecvError::Lagged(missed)) => {
+                        tracing::warn!(webhook = %config.name, missed, "Webhook fell behind the health monitor, alarms not sent");
+                        None
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+            };
+            let Some(notification) = notification.filter(|n| config.sends(n)) else {
+                continue;
+            };
+            if let Err(e) = tx.try_send(notification) {
+                let event = match &e {
+                    mpsc::error::TrySendError::Full(n) | mpsc::error::TrySendError::Closed(n) => {
+                        n.event
+                    }
+                };
+                tracing::error!(
+                    webhook = %config.name,
+                    event = event.as_str(),
+                    "Webhook queue full, notification dropped"
+                );
+            }
+        }
+    });
+
+    Ok(tokio::spawn(async move {
+        while let Some(notification) = rx.recv().await {
+            if let Err(e) = sender.deliver(&notification).await {
+                tracing::error!(
+                    webhook = %sender.config.name,
+                    event = notification.event.as_str(),
+                    id = %notification.id,
+                    error = %format!("{:#}", e),
+                    "Webhook notification dropped"
+                );
+            }
+        }
+        tracing::info!(webhook = %sender.config.name, "Webhook stopped");
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::experiment::document::StopDoc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_config_and_filtering() {
+        let config: WebhookConfig = toml::from_str(
+            r#"
+            name = "lab-slack"
+            url = "https://hooks.slack.com/services/T0/B0/X"
+            format = "slack"
+            events = ["run_error", "alarm"]
+            min_alarm_severity = "error"
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.max_retries, 5);
+
+        let mut runs = HashMap::new();
+        let start = StartDoc::new("grid_scan", "Focus scan");
+        let run_uid = start.uid.clone();
+        let started = run_notification(&mut runs, &Document::Start(start)).unwrap();
+        assert_eq!(started.event, WebhookEvent::RunStart);
+        assert!(!config.sends(&started));
+
+        let failed = run_notification(
+            &mut runs,
+            &Document::Stop(StopDoc::fail(&run_uid, "Stage fault", 12)),
+        )
+        .unwrap();
+        assert_eq!(failed.event, WebhookEvent::RunError);
+        assert!(config.sends(&failed));
+        assert!(runs.is_empty());
+        assert_eq!(
+            failed.summary(),
+            format!("Run failed: Focus scan ({}): Stage fault", run_uid)
+        );
+
+        let warning = alarm_notification(&HealthError {
+            module_name: "memory".to_string(),
+            severity: ErrorSeverity::Warning,
+            message: "cache memory near budget".to_string(),
+            timestamp: std::time::Instant::now(),
+            context: HashMap::new(),
+        });
+        assert!(!config.sends(&warning));
+
+        let bad = WebhookConfig {
+            url: "hooks.slack.com".to_string(),
+            ..config
+        };
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn test_hmac_sha256() {
+        // RFC 4231 test case 2
+        let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        let hex: String = mac.iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(
+            hex,
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        // The timestamp is signed with the body
+        assert_eq!(
+            signature("Jefe", 1_700_000_000, b"{}"),
+            format!(
+                "sha256={}",
+                hmac_sha256(b"Jefe", b"1700000000.{}")
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect::<String>()
+            )
+        );
+        assert_ne!(
+            signature("Jefe", 1_700_000_000, b"{}"),
+            signature("Jefe", 1_700_000_001, b"{}")
+        );
+    }
+
+    /// HTTP server answering requests with `statuses` in turn, returning
+    /// the raw requests
+    async fn fake_endpoint(listener: TcpListener, statuses: Vec<u16>) -> Vec<String> {
+        let mut requests = Vec::new();
+        for status in statuses {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                let n = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let length = head
+                        .lines()
+                        .find_map(|l| {
+                            l.to_ascii_lowercase()
+                                .strip_prefix("content-length:")
+                                .map(|v| v.trim().parse::<usize>().unwrap())
+                        })
+                        .unwrap_or(0);
+                    if body.len() >= length {
+                        break;
+                    }
+                }
+            }
+            stream
+                .write_all(
+                    format!(
+                        "HTTP/1.1 {} X\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                        status
+                    )
+                    .as_bytes(),
+                )
+                .await
+                .unwrap();
+            requests.push(String::from_utf8(request).unwrap());
+        }
+        requests
+    }
+
+    #[tokio::test]
+    async fn test_delivery_retries_and_signs() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = tokio::spawn(fake_endpoint(listener, vec![503, 200]));
+
+        let sender = WebhookSender::new(WebhookConfig {
+            url,
+            secret: Some("lims-key".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        let mut notification = Notification::new(WebhookEvent::RunStart);
+        notification.run = Some(RunSummary::from_start(&StartDoc::new("count", "Count")));
+        sender.deliver(&notification).await.unwrap();
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests.len(), 2);
+        let body = serde_json::to_vec(&notification).unwrap();
+        for request in &requests {
+            let request = request.to_ascii_lowercase();
+            assert!(request.starts_with("post /hook"));
+            assert!(request.contains(&format!("x-rust-daq-delivery: {}", notification.id)));
+            let timestamp: u64 = request
+                .lines()
+                .find_map(|l| l.strip_prefix("x-rust-daq-timestamp: "))
+                .unwrap()
+                .parse()
+                .unwrap();
+            assert!(request.contains(&format!(
+                "x-rust-daq-signature: {}",
+                signature("lims-key", timestamp, &body)
+            )));
+            assert!(request.contains("\"event\":\"run_start\""));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delivery_errors_leave_out_the_url() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(fake_endpoint(listener, vec![404]));
+        let sender = WebhookSender::new(WebhookConfig {
+            url: format!("http://{}/services/T0/B0/hook-credential", addr),
+            max_retries: 0,
+            ..Default::default()
+        })
+        .unwrap();
+        let notification = Notification::new(WebhookEvent::RunStart);
+
+        // Refused by the receiver
+        let error = sender.deliver(&notification).await.unwrap_err();
+        server.await.unwrap();
+        let message = format!("{:#}", error);
+        assert!(message.contains("HTTP 404"));
+        assert!(!message.contains("hook-credential"));
+
+        // Nobody listening any more
+        let error = sender.deliver(&notification).await.unwrap_err();
+        assert!(!format!("{:#}", error).contains("hook-credential"));
+    }
+}
@@ -0,0 +1,478 @@
+//! Chaos and recovery suite: kills daemon subsystems mid-run and checks that
+//! they come back.
+//!
+//! - Storage writer: panics and errors injected while records stream in; the
+//!   supervisor restarts it and every record reaches disk exactly once.
+//! - gRPC server: stopped by an injected fault; the embedded daemon restarts
+//!   it and clients get answers again.
+//! - Device actor: a stage fails mid-scan; the run fails, the rig is parked
+//!   in its safe state and the next run succeeds once the fault clears.
+//!
+//! Fault points are process-wide, so the scenarios run one after another in
+//! a single test. Results are written as a self-test report (JSON and JUnit
+//! XML) to `$CHAOS_REPORT_DIR`, default `target/chaos-report`, for CI.
+
+#![cfg(feature = "server")]
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, anyhow, bail, ensure};
+use common::capabilities::Movable;
+use common::driver::{DeviceComponents, DriverFactory};
+use common::experiment::document::Document;
+use common::fault_injection::{Fault, FaultInjector, points};
+use common::parking::{ParkAction, ParkingActions};
+use common::supervisor::{RestartPolicy, SupervisorMonitor, supervise};
+use daq_driver_mock::{ErrorConfig, ErrorScenario, MockStage};
+use experiment::plans::LineScan;
+use experiment::{EngineState, RunEngine};
+use futures::future::BoxFuture;
+use hardware::DeviceRegistry;
+use hardware::recipes::{StepResult, StepStatus};
+use hardware::self_test::{DeviceTestResult, TestReport};
+use server::grpc::HardwareServiceClient;
+use server::grpc::proto::ListDevicesRequest;
+use server::grpc::server::ServerOptions;
+use server::runtime::DaqRuntimeBuilder;
+use storage::flush_policy::FlushPolicyConfig;
+use storage::hdf5_writer::HDF5Writer;
+use storage::ring_buffer::RingBuffer;
+
+/// Steps of one scenario, in the self-test step format
+#[derive(Default)]
+struct Steps(Vec<StepResult>);
+
+impl Steps {
+    /// Run one step; a failed step ends the scenario
+    async fn step<F>(&mut self, description: &str, check: F) -> Result<()>
+    where
+        F: Future<Output = Result<String>>,
+    {
+        let result = tokio::time::timeout(Duration::from_secs(20), check)
+            .await
+            .unwrap_or_else(|_| Err(anyhow!("timed out")));
+        let (status, message) = match &result {
+            Ok(message) => (StepStatus::Ok, message.clone()),
+            Err(e) => (StepStatus::Failed, format!("{:#}", e)),
+        };
+        self.0.push(StepResult {
+            index: self.0.len(),
+            description: description.to_string(),
+            status,
+            message,
+        });
+        result.map(|_| ())
+    }
+}
+
+/// Poll `condition` until it holds or `timeout` passes
+async fn eventually<F, Fut>(timeout: Duration, mut condition: F) -> bool
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = bool>,
+{
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if condition().await {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    false
+}
+
+fn restarts(subsystem: &str) -> u64 {
+    SupervisorMonitor::global()
+        .get(subsystem)
+        .map_or(0, |stats| stats.restarts)
+}
+
+// =============================================================================
+// Storage writer
+// =============================================================================
+
+/// Payload bytes in the writer's fallback file (length-prefixed snapshots)
+fn flushed_bytes(path: &Path) -> usize {
+    let Ok(data) = std::fs::read(path) else {
+        return 0;
+    };
+    let mut total = 0;
+    let mut offset = 0;
+    while offset + 4 <= data.len() {
+        let len = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
+        total += len;
+        offset += 4 + len;
+    }
+    total
+}
+
+async fn storage_writer(steps: &mut Steps) -> Result<()> {
+    const SUBSYSTEM: &str = "chaos.storage_writer";
+    let dir = tempfile::tempdir()?;
+    let ring = Arc::new(RingBuffer::create(&dir.path().join("ring.buf"), 1)?);
+    let mut writer = HDF5Writer::new(&dir.path().join("run.h5"), ring.clone())?;
+    writer.set_flush_policy(FlushPolicyConfig::fixed(Duration::from_millis(20)));
+    let writer = Arc::new(writer);
+    let output = dir.path().join("run.bin");
+
+    let task = tokio::spawn({
+        let writer = writer.clone();
+        async move {
+            supervise(SUBSYSTEM, &RestartPolicy::immediate(5), || {
+                writer.clone().run_shared()
+            })
+            .await
+        }
+    });
+
+    let record = [0xAB_u8; 256];
+    let mut written = 0;
+    steps
+        .step("records stream to disk", async {
+            for _ in 0..10 {
+                ring.write(&record)?;
+                written += record.len();
+            }
+            ensure!(
+                eventually(Duration::from_secs(5), || async {
+                    flushed_bytes(&output) == written
+                })
+                .await,
+                "{} of {} bytes flushed",
+                flushed_bytes(&output),
+                written
+            );
+            Ok(format!("{} bytes flushed", written))
+        })
+        .await?;
+
+    steps
+        .step("writer restarts after panic and error", async {
+            let injector = FaultInjector::global();
+            injector.arm(
+                points::STORAGE_WRITER,
+                Fault::Panic("writer thread killed".into()),
+            );
+            injector.arm(
+                points::STORAGE_WRITER,
+                Fault::Error("disk unplugged".into()),
+            );
+            // Records arriving while the writer is down wait in the ring
+            for _ in 0..10 {
+                ring.write(&record)?;
+                written += record.len();
+            }
+            ensure!(
+                eventually(Duration::from_secs(5), || async {
+                    restarts(SUBSYSTEM) >= 2
+                })
+                .await,
+                "writer restarted {} times",
+                restarts(SUBSYSTEM)
+            );
+            ensure!(!task.is_finished(), "supervisor gave up");
+            Ok(format!("{} restarts", restarts(SUBSYSTEM)))
+        })
+        .await?;
+
+    steps
+        .step("restarted writer resumes at its checkpoint", async {
+            for _ in 0..10 {
+                ring.write(&record)?;
+                written += record.len();
+            }
+            // Nothing lost, nothing flushed twice
+            eventually(Duration::from_secs(5), || async {
+                flushed_bytes(&output) >= written
+            })
+            .await;
+            let flushed = flushed_bytes(&output);
+            ensure!(
+                flushed == written,
+                "{} bytes flushed for {} written",
+                flushed,
+                written
+            );
+            Ok(format!("{} bytes flushed once", flushed))
+        })
+        .await?;
+
+    task.abort();
+    Ok(())
+}
+
+// =============================================================================
+// gRPC server
+// =============================================================================
+
+async fn list_devices(port: u16) -> Result<usize> {
+    let mut client = HardwareServiceClient::connect(format!("http://127.0.0.1:{}", port)).await?;
+    let devices = client
+        .list_devices(ListDevicesRequest::default())
+        .await?
+        .into_inner()
+        .devices;
+    Ok(devices.len())
+}
+
+async fn wait_for_server(port: u16) -> Result<usize> {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        match list_devices(port).await {
+            Ok(count) => return Ok(count),
+            Err(e) if Instant::now() > deadline => return Err(e),
+            Err(_) => tokio::time::sleep(Duration::from_millis(50)).await,
+        }
+    }
+}
+
+async fn grpc_server(steps: &mut Steps) -> Result<()> {
+    const SUBSYSTEM: &str = "grpc_server";
+    let dir = tempfile::tempdir()?;
+    let port = std::net::TcpListener::bind("127.0.0.1:0")?
+        .local_addr()?
+        .port();
+    let options = ServerOptions {
+        restore_modules: false,
+        module_state_path: dir.path().join("modules.json"),
+        channel_history_retention: Duration::ZERO,
+        library_dir: dir.path().join("library"),
+        ..ServerOptions::default()
+    };
+    let daemon = DaqRuntimeBuilder::new()
+        .bind(([127, 0, 0, 1], port).into())
+        .mock_hardware()
+        .factory_config_dir(None)
+        .server_options(options)
+        .restart_policy(RestartPolicy::immediate(3))
+        .start()
+        .await?;
+
+    steps
+        .step("server answers", async {
+            let count = wait_for_server(port).await?;
+            Ok(format!("{} devices listed", count))
+        })
+        .await?;
+
+    let before = restarts(SUBSYSTEM);
+    steps
+        .step("server restarts after crash", async {
+            FaultInjector::global().arm(points::GRPC_SERVER, Fault::Error("server killed".into()));
+            ensure!(
+                eventually(Duration::from_secs(5), || async {
+                    restarts(SUBSYSTEM) > before
+                })
+                .await,
+                "server was not restarted"
+            );
+            let count = wait_for_server(port).await?;
+            Ok(format!("{} devices listed after restart", count))
+        })
+        .await?;
+
+    steps
+        .step("daemon shuts down cleanly", async {
+            daemon.shutdown().await?;
+            Ok("stopped".to_string())
+        })
+        .await
+}
+
+// =============================================================================
+// Device actor
+// =============================================================================
+
+/// Mock stages with injectable errors; `faulty = true` shares `errors`
+struct ChaosStageFactory {
+    errors: ErrorConfig,
+}
+
+impl DriverFactory for ChaosStageFactory {
+    fn driver_type(&self) -> &'static str {
+        "chaos_stage"
+    }
+
+    fn name(&self) -> &'static str {
+        "Chaos Stage"
+    }
+
+    fn validate(&self, _config: &toml::Value) -> Result<()> {
+        Ok(())
+    }
+
+    fn build(&self, config: toml::Value) -> BoxFuture<'static, Result<DeviceComponents>> {
+        let faulty = config
+            .get("faulty")
+            .and_then(toml::Value::as_bool)
+            .unwrap_or(false);
+        let errors = if faulty {
+            self.errors.clone()
+        } else {
+            ErrorConfig::none()
+        };
+        Box::pin(async move {
+            let stage = MockStage::builder().error_config(errors).build();
+            Ok(DeviceComponents::new().with_movable(Arc::new(stage)))
+        })
+    }
+}
+
+/// Run the next queued plan and return its exit status
+async fn run_to_stop(engine: &Arc<RunEngine>) -> Result<String> {
+    let mut rx = engine.subscribe();
+    engine.start().await?;
+    loop {
+        if let Document::Stop(stop) = rx.recv().await? {
+            return Ok(stop.exit_status);
+        }
+    }
+}
+
+async fn device_actor(steps: &mut Steps) -> Result<()> {
+    let errors = ErrorConfig::scenario(ErrorScenario::FailAfterN {
+        operation: "move",
+        count: 2,
+    });
+    let registry = Arc::new(DeviceRegistry::new());
+    registry.register_factory(Box::new(ChaosStageFactory {
+        errors: errors.clone(),
+    }));
+    for (id, faulty) in [("shutter_stage", false), ("scan_stage", true)] {
+        let mut config = toml::map::Map::new();
+        config.insert("faulty".to_string(), toml::Value::Boolean(faulty));
+        registry
+            .register_from_toml(id, id, "chaos_stage", toml::Value::Table(config))
+            .await?;
+    }
+    registry.set_parking_actions(HashMap::from([(
+        "shutter_stage".to_string(),
+        ParkingActions {
+            on_pause: vec![ParkAction::MoveTo { position: 5.0 }],
+            on_resume: vec![],
+        },
+    )]));
+    let engine = Arc::new(RunEngine::new(registry.clone()));
+    let shutter = registry
+        .get_movable("shutter_stage")
+        .context("shutter_stage not registered")?;
+
+    steps
+        .step("device fault fails the run", async {
+            engine
+                .queue(Box::new(LineScan::new("scan_stage", 0.0, 1.0, 5)))
+                .await;
+            let status = run_to_stop(&engine).await?;
+            ensure!(status == "fail", "run ended with '{}'", status);
+            Ok(status)
+        })
+        .await?;
+
+    steps
+        .step("failed run leaves the rig parked", async {
+            let position = shutter.position().await?;
+            ensure!(
+                (position - 5.0).abs() < 1e-9,
+                "shutter_stage at {}",
+                position
+            );
+            let state = engine.state().await;
+            ensure!(state == EngineState::Idle, "engine is {}", state);
+            Ok(format!("shutter_stage parked at {}", position))
+        })
+        .await?;
+
+    steps
+        .step("next run succeeds once the fault clears", async {
+            errors.reset();
+            engine
+                .queue(Box::new(LineScan::new("scan_stage", 0.0, 1.0, 2)))
+                .await;
+            let status = run_to_stop(&engine).await?;
+            if status != "success" {
+                bail!("run ended with '{}'", status);
+            }
+            Ok(status)
+        })
+        .await
+}
+
+// =============================================================================
+// Report
+// =============================================================================
+
+fn report_dir() -> PathBuf {
+    std::env::var_os("CHAOS_REPORT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("../../target/chaos-report"))
+}
+
+fn write_report(report: &TestReport) -> Result<()> {
+    let dir = report_dir();
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(
+        dir.join("chaos_recovery.json"),
+        serde_json::to_string_pretty(report)?,
+    )?;
+    std::fs::write(
+        dir.join("chaos_recovery.xml"),
+        report.to_junit_xml("chaos_recovery"),
+    )?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn subsystems_recover_from_injected_faults() {
+    let started_ns = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64;
+    let start = Instant::now();
+
+    let mut devices = Vec::new();
+    for subsystem in ["storage_writer", "grpc_server", "device_actor"] {
+        let mut steps = Steps::default();
+        let result = match subsystem {
+            "storage_writer" => storage_writer(&mut steps).await,
+            "grpc_server" => grpc_server(&mut steps).await,
+            _ => device_actor(&mut steps).await,
+        };
+        // Errors outside a step (setup) still show up in the report
+        if let Err(e) = result {
+            if steps
+                .0
+                .last()
+                .is_none_or(|s| s.status != StepStatus::Failed)
+            {
+                steps.0.push(StepResult {
+                    index: steps.0.len(),
+                    description: "setup".to_string(),
+                    status: StepStatus::Failed,
+                    message: format!("{:#}", e),
+                });
+            }
+        }
+        FaultInjector::global().disarm_all();
+        devices.push(DeviceTestResult {
+            device: subsystem.to_string(),
+            critical: true,
+            steps: steps.0,
+        });
+    }
+
+    let report = TestReport {
+        started_ns,
+        duration: start.elapsed(),
+        devices,
+    };
+    write_report(&report).unwrap();
+    assert!(
+        report.passed(),
+        "{}",
+        serde_json::to_string_pretty(&report).unwrap()
+    );
+}
@@ -199,8 +199,12 @@ impl ArrowDocumentWriter {
                         }
                     }
                 }
-                Document::Manifest(_) => {
-                    // Manifests are not written to data files
+                Document::Manifest(_)
+                | Document::Progress(_)
+                | Document::BatchSummary(_)
+                | Document::DeviceEvent(_) => {
+                    // Manifests, progress, batch summaries and device events
+                    // are not written to data files
                 }
             }
             Ok(())
@@ -403,7 +407,10 @@ impl ParquetDocumentWriter {
                         }
                     }
                 }
-                Document::Manifest(_) => {}
+                Document::Manifest(_)
+                | Document::Progress(_)
+                | Document::BatchSummary(_)
+                | Document::DeviceEvent(_) => {}
             }
             Ok(())
         })
@@ -565,6 +572,7 @@ mod tests {
             exit_status: "success".to_string(),
             reason: "".to_string(),
             num_events: 10,
+            metadata: HashMap::new(),
         };
         writer.write(Document::Stop(stop)).await.unwrap();
 
@@ -650,6 +658,7 @@ mod tests {
             exit_status: "success".to_string(),
             reason: "".to_string(),
             num_events: 10,
+            metadata: HashMap::new(),
         };
         writer.write(Document::Stop(stop)).await.unwrap();
 
@@ -0,0 +1,518 @@
+//! Rolling on-disk history of scalar channels
+//!
+//! Keeps the last N hours of every scalar measurement published by the
+//! daemon, whether or not a run is active, so "what was happening right
+//! before it broke" can still be answered after a crash or restart. The
+//! in-memory ring buffer loses everything when the process goes away.
+//!
+//! Samples go into time-partitioned segment files
+//! (`history_<partition start ns>_<n>.seg`), one partition per
+//! [`ChannelHistoryConfig::segment_duration`]. Whole segments are deleted
+//! once they fall out of the retention window. Each process writes its own
+//! segment files and never appends to an existing one, so a segment cut
+//! short by a crash is read up to its last complete record.
+//!
+//! # Segment format
+//!
+//! An 8-byte magic followed by little-endian records:
+//!
+//! | Tag | Record |
+//! |-----|--------|
+//! | 0 | channel definition: `u16` id, `u16` name length, UTF-8 name |
+//! | 1 | sample: `u16` id, `u64` timestamp (ns), `f64` value, `u8` quality |
+//!
+//! Channel ids are local to the segment and defined before first use.
+
+use anyhow::{anyhow, bail, Context, Result};
+use common::core::DataQuality;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Magic bytes at the start of every segment file
+const SEGMENT_MAGIC: &[u8; 8] = b"RDQHIST1";
+
+const TAG_CHANNEL: u8 = 0;
+const TAG_SAMPLE: u8 = 1;
+
+/// Default time samples are kept for
+pub const DEFAULT_CHANNEL_HISTORY_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Default time span of one segment file
+pub const DEFAULT_SEGMENT_DURATION: Duration = Duration::from_secs(10 * 60);
+
+/// Retention and partitioning of a [`ChannelHistory`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelHistoryConfig {
+    /// How far back samples are kept
+    pub retention: Duration,
+    /// Time span covered by each segment file
+    pub segment_duration: Duration,
+}
+
+impl Default for ChannelHistoryConfig {
+    fn default() -> Self {
+        Self {
+            retention: DEFAULT_CHANNEL_HISTORY_RETENTION,
+            segment_duration: DEFAULT_SEGMENT_DURATION,
+        }
+    }
+}
+
+/// Filter for [`ChannelHistory::query`]
+#[derive(Debug, Clone, Default)]
+pub struct ChannelHistoryQuery {
+    /// Channels to return (empty = all)
+    pub channels: Vec<String>,
+    /// Earliest timestamp, inclusive
+    pub start_ns: u64,
+    /// Latest timestamp, inclusive (`None` = now)
+    pub end_ns: Option<u64>,
+    /// Maximum samples per channel, evenly decimated (0 = no limit)
+    pub max_points: usize,
+}
+
+/// Samples of one channel, oldest first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChannelSeries {
+    pub channel: String,
+    pub timestamps_ns: Vec<u64>,
+    pub values: Vec<f64>,
+    pub quality: Vec<DataQuality>,
+    /// Samples in range before decimation
+    pub total_points: usize,
+}
+
+/// A segment file on disk
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct SegmentFile {
+    start_ns: u64,
+    seq: u32,
+    path: PathBuf,
+}
+
+struct ActiveSegment {
+    start_ns: u64,
+    writer: BufWriter<File>,
+    channels: HashMap<String, u16>,
+}
+
+impl ActiveSegment {
+    fn write_sample(
+        &mut self,
+        channel: &str,
+        timestamp_ns: u64,
+        value: f64,
+        quality: DataQuality,
+    ) -> Result<()> {
+        let id = match self.channels.get(channel) {
+            Some(&id) => id,
+            None => {
+                let id = u16::try_from(self.channels.len())
+                    .map_err(|_| anyhow!("Too many channels in one history segment"))?;
+                let name = channel.as_bytes();
+                let len = u16::try_from(name.len())
+                    .map_err(|_| anyhow!("Channel name too long: {}", channel))?;
+                self.writer.write_all(&[TAG_CHANNEL])?;
+                self.writer.write_all(&id.to_le_bytes())?;
+                self.writer.write_all(&len.to_le_bytes())?;
+                self.writer.write_all(name)?;
+                self.channels.insert(channel.to_string(), id);
+                id
+            }
+        };
+        self.writer.write_all(&[TAG_SAMPLE])?;
+        self.writer.write_all(&id.to_le_bytes())?;
+        self.writer.write_all(&timestamp_ns.to_le_bytes())?;
+        self.writer.write_all(&value.to_le_bytes())?;
+        self.writer.write_all(&[quality as u8])?;
+        Ok(())
+    }
+}
+
+/// Persistent rolling store of scalar channel samples
+pub struct ChannelHistory {
+    directory: PathBuf,
+    config: ChannelHistoryConfig,
+    active: Mutex<Option<ActiveSegment>>,
+}
+
+impl ChannelHistory {
+    /// Open (creating if needed) the history kept in `directory`
+    pub fn open(directory: impl Into<PathBuf>, config: ChannelHistoryConfig) -> Result<Self> {
+        if config.segment_duration.is_zero() {
+            bail!("Channel history segment duration must be non-zero");
+        }
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory).with_context(|| {
+            format!("Failed to create history directory {}", directory.display())
+        })?;
+        Ok(Self {
+            directory,
+            config,
+            active: Mutex::new(None),
+        })
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    pub fn config(&self) -> ChannelHistoryConfig {
+        self.config
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<ActiveSegment>> {
+        self.active.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Append a sample, starting a new segment when its partition ends
+    ///
+    /// Written samples reach the disk on [`flush`](Self::flush) or when the
+    /// segment is closed.
+    pub fn record(
+        &self,
+        channel: &str,
+        timestamp_ns: u64,
+        value: f64,
+        quality: DataQuality,
+    ) -> Result<()> {
+        let segment_ns = self.config.segment_duration.as_nanos() as u64;
+        let mut active = self.lock();
+        let rotate = active
+            .as_ref()
+            .is_none_or(|segment| timestamp_ns >= segment.start_ns + segment_ns);
+        if rotate {
+            if let Some(mut closed) = active.take() {
+                closed.writer.flush()?;
+            }
+            let start_ns = timestamp_ns - timestamp_ns % segment_ns;
+            *active = Some(self.create_segment(start_ns)?);
+            self.prune(start_ns)?;
+        }
+        // Late samples go into the current segment; queries sort them into place
+        active.as_mut().expect("segment opened above").write_sample(
+            channel,
+            timestamp_ns,
+            value,
+            quality,
+        )
+    }
+
+    /// Write buffered samples to disk
+    pub fn flush(&self) -> Result<()> {
+        if let Some(segment) = self.lock().as_mut() {
+            segment.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    fn create_segment(&self, start_ns: u64) -> Result<ActiveSegment> {
+        // Never append: a restarted daemon starts the partition's next file
+        let seq = self
+            .segments()?
+            .iter()
+            .filter(|s| s.start_ns == start_ns)
+            .map(|s| s.seq + 1)
+            .max()
+            .unwrap_or(0);
+        let path = self.directory.join(segment_name(start_ns, seq));
+        let file = File::create(&path)
+            .with_context(|| format!("Failed to create history segment {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        writer.write_all(SEGMENT_MAGIC)?;
+        Ok(ActiveSegment {
+            start_ns,
+            writer,
+            channels: HashMap::new(),
+        })
+    }
+
+    /// Delete segments whose whole partition is older than the retention window
+    fn prune(&self, now_ns: u64) -> Result<()> {
+        let cutoff = now_ns.saturating_sub(self.config.retention.as_nanos() as u64);
+        let segment_ns = self.config.segment_duration.as_nanos() as u64;
+        for segment in self.segments()? {
+            if segment.start_ns + segment_ns <= cutoff {
+                if let Err(e) = std::fs::remove_file(&segment.path) {
+                    tracing::warn!(path = %segment.path.display(), error = %e, "Failed to delete expired history segment");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Segment files in the directory, oldest first
+    fn segments(&self) -> Result<Vec<SegmentFile>> {
+        let mut segments = Vec::new();
+        for entry in std::fs::read_dir(&self.directory)
+            .with_context(|| format!("Failed to read {}", self.directory.display()))?
+        {
+            let path = entry?.path();
+            let parsed = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(parse_segment_name);
+            if let Some((start_ns, seq)) = parsed {
+                segments.push(SegmentFile {
+                    start_ns,
+                    seq,
+                    path,
+                });
+            }
+        }
+        segments.sort();
+        Ok(segments)
+    }
+
+    /// Start of the oldest retained partition (`None` if nothing is stored)
+    pub fn oldest_ns(&self) -> Result<Option<u64>> {
+        Ok(self.segments()?.first().map(|s| s.start_ns))
+    }
+
+    /// Samples matching `query`, one series per channel sorted by name
+    pub fn query(&self, query: &ChannelHistoryQuery) -> Result<Vec<ChannelSeries>> {
+        self.flush()?;
+        let end_ns = query.end_ns.unwrap_or(u64::MAX);
+        let segment_ns = self.config.segment_duration.as_nanos() as u64;
+
+        let mut series: BTreeMap<String, Vec<(u64, f64, DataQuality)>> = BTreeMap::new();
+        for segment in self.segments()? {
+            // A segment can hold late samples from just before its partition
+            let overlaps = segment.start_ns.saturating_sub(segment_ns) <= end_ns
+                && segment.start_ns.saturating_add(segment_ns) > query.start_ns;
+            if !overlaps {
+                continue;
+            }
+            read_segment(&segment.path, |channel, timestamp_ns, value, quality| {
+                if timestamp_ns < query.start_ns || timestamp_ns > end_ns {
+                    return;
+                }
+                if !query.channels.is_empty() && !query.channels.iter().any(|c| c == channel) {
+                    return;
+                }
+                series
+                    .entry(channel.to_string())
+                    .or_default()
+                    .push((timestamp_ns, value, quality));
+            })?;
+        }
+
+        Ok(series
+            .into_iter()
+            .map(|(channel, mut samples)| {
+                samples.sort_by_key(|&(timestamp_ns, _, _)| timestamp_ns);
+                let total_points = samples.len();
+                let samples = decimate(samples, query.max_points);
+                ChannelSeries {
+                    channel,
+                    timestamps_ns: samples.iter().map(|s| s.0).collect(),
+                    values: samples.iter().map(|s| s.1).collect(),
+                    quality: samples.iter().map(|s| s.2).collect(),
+                    total_points,
+                }
+            })
+            .collect())
+    }
+}
+
+impl Drop for ChannelHistory {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            tracing::warn!(error = %e, "Failed to flush channel history");
+        }
+    }
+}
+
+fn segment_name(start_ns: u64, seq: u32) -> String {
+    format!("history_{:020}_{}.seg", start_ns, seq)
+}
+
+fn parse_segment_name(name: &str) -> Option<(u64, u32)> {
+    let (start, seq) = name
+        .strip_prefix("history_")?
+        .strip_suffix(".seg")?
+        .split_once('_')?;
+    Some((start.parse().ok()?, seq.parse().ok()?))
+}
+
+/// Keep at most `max_points` evenly spaced samples, always including the last
+fn decimate<T: Copy>(samples: Vec<T>, max_points: usize) -> Vec<T> {
+    if max_points == 0 || samples.len() <= max_points {
+        return samples;
+    }
+    if max_points == 1 {
+        return samples.last().copied().into_iter().collect();
+    }
+    let last = samples.len() - 1;
+    (0..max_points)
+        .map(|i| samples[i * last / (max_points - 1)])
+        .collect()
+}
+
+fn quality_from_code(code: u8) -> DataQuality {
+    match code {
+        1 => DataQuality::Suspect,
+        2 => DataQuality::Stale,
+        3 => DataQuality::OutOfRange,
+        4 => DataQuality::Interpolated,
+        _ => DataQuality::Good,
+    }
+}
+
+/// Call `sample` for every sample in a segment file
+///
+/// A record cut short at the end of the file (the daemon stopped while
+/// writing it) ends the segment.
+fn read_segment(path: &Path, mut sample: impl FnMut(&str, u64, f64, DataQuality)) -> Result<()> {
+    let mut bytes = Vec::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut bytes))
+        .with_context(|| format!("Failed to read history segment {}", path.display()))?;
+    let Some(mut rest) = bytes.strip_prefix(SEGMENT_MAGIC.as_slice()) else {
+        tracing::warn!(path = %path.display(), "Not a history segment, skipping");
+        return Ok(());
+    };
+
+    let mut channels: HashMap<u16, String> = HashMap::new();
+    while let Some((&tag, body)) = rest.split_first() {
+        match tag {
+            TAG_CHANNEL if body.len() >= 4 => {
+                let id = u16::from_le_bytes([body[0], body[1]]);
+                let len = usize::from(u16::from_le_bytes([body[2], body[3]]));
+                let Some(name) = body.get(4..4 + len) else {
+                    break;
+                };
+                channels.insert(id, String::from_utf8_lossy(name).into_owned());
+                rest = &body[4 + len..];
+            }
+            TAG_SAMPLE if body.len() >= 19 => {
+                let id = u16::from_le_bytes([body[0], body[1]]);
+                let timestamp_ns = u64::from_le_bytes(body[2..10].try_into()?);
+                let value = f64::from_le_bytes(body[10..18].try_into()?);
+                if let Some(channel) = channels.get(&id) {
+                    sample(channel, timestamp_ns, value, quality_from_code(body[18]));
+                }
+                rest = &body[19..];
+            }
+            TAG_CHANNEL | TAG_SAMPLE => break,
+            other => {
+                tracing::warn!(path = %path.display(), tag = other, "Corrupt history segment, ignoring the rest");
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    const SECOND: u64 = 1_000_000_000;
+
+    fn config() -> ChannelHistoryConfig {
+        ChannelHistoryConfig {
+            retention: Duration::from_secs(60),
+            segment_duration: Duration::from_secs(10),
+        }
+    }
+
+    #[test]
+    fn test_history_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = 1_000 * SECOND;
+        {
+            let history = ChannelHistory::open(dir.path(), config()).unwrap();
+            for i in 0..30 {
+                let t = base + i * SECOND;
+                history
+                    .record("power", t, i as f64, DataQuality::Good)
+                    .unwrap();
+                history
+                    .record("temperature", t, 20.0, DataQuality::Stale)
+                    .unwrap();
+            }
+        }
+
+        // A new process picks up the old segments and writes its own
+        let history = ChannelHistory::open(dir.path(), config()).unwrap();
+        history
+            .record("power", base + 30 * SECOND, 30.0, DataQuality::Good)
+            .unwrap();
+        // Late sample, filed in the current segment
+        history
+            .record("power", base + 5 * SECOND + 1, -1.0, DataQuality::Suspect)
+            .unwrap();
+
+        let series = history
+            .query(&ChannelHistoryQuery {
+                channels: vec!["power".to_string()],
+                start_ns: base + 5 * SECOND,
+                end_ns: Some(base + 30 * SECOND),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(series.len(), 1);
+        let power = &series[0];
+        assert_eq!(power.total_points, 27);
+        assert_eq!(power.values[..3], [5.0, -1.0, 6.0]);
+        assert_eq!(power.quality[1], DataQuality::Suspect);
+        assert_eq!(*power.values.last().unwrap(), 30.0);
+
+        let all = history.query(&ChannelHistoryQuery::default()).unwrap();
+        let names: Vec<_> = all.iter().map(|s| s.channel.as_str()).collect();
+        assert_eq!(names, ["power", "temperature"]);
+        assert_eq!(all[1].quality[0], DataQuality::Stale);
+
+        let decimated = history
+            .query(&ChannelHistoryQuery {
+                channels: vec!["power".to_string()],
+                max_points: 4,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(decimated[0].values.len(), 4);
+        assert_eq!(decimated[0].total_points, 32);
+        assert_eq!(decimated[0].values[3], 30.0);
+    }
+
+    #[test]
+    fn test_expired_segments_are_deleted() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = ChannelHistory::open(dir.path(), config()).unwrap();
+        history.record("power", 0, 1.0, DataQuality::Good).unwrap();
+        history
+            .record("power", 15 * SECOND, 2.0, DataQuality::Good)
+            .unwrap();
+        assert_eq!(history.oldest_ns().unwrap(), Some(0));
+
+        history
+            .record("power", 75 * SECOND, 3.0, DataQuality::Good)
+            .unwrap();
+        assert_eq!(history.oldest_ns().unwrap(), Some(10 * SECOND));
+        let series = history.query(&ChannelHistoryQuery::default()).unwrap();
+        assert_eq!(series[0].values, [2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_truncated_segment_is_read_up_to_last_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = ChannelHistory::open(dir.path(), config()).unwrap();
+        history.record("power", 0, 1.0, DataQuality::Good).unwrap();
+        history.record("power", 1, 2.0, DataQuality::Good).unwrap();
+        history.flush().unwrap();
+
+        let path = history.segments().unwrap()[0].path.clone();
+        let len = std::fs::metadata(&path).unwrap().len();
+        let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(len - 5).unwrap();
+
+        let series = history.query(&ChannelHistoryQuery::default()).unwrap();
+        assert_eq!(series[0].values, [1.0]);
+    }
+}
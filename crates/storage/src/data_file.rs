@@ -0,0 +1,652 @@
+//! Data-file inspection - Open and introspect files written by this crate
+//!
+//! [`DataFile`] reads back the HDF5, Arrow IPC and TIFF files produced by the
+//! writers in this crate, so the run catalog, export tools and tests can list
+//! their contents, read slices and extract metadata without depending on
+//! `hdf5` or `arrow` themselves or hard-coding file layouts.
+//!
+//! Every format maps onto the same model:
+//!
+//! | Format | Datasets | Metadata |
+//! |--------|----------|----------|
+//! | HDF5 | every dataset, by path (`/primary/det1`) | group attributes as `<group>@<name>` |
+//! | Arrow IPC | every column, by name | schema metadata |
+//! | TIFF | `image` (`height x width`) | JSON sidecar written next to the image |
+//!
+//! Slices select rows along the first axis and are returned as `f64` in
+//! row-major order. Colour TIFF images are read as luminance.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use storage::DataFile;
+//!
+//! let file = DataFile::open("/data/runs/run1_1000.h5")?;
+//! for dataset in file.datasets()? {
+//!     println!("{} {} {:?}", dataset.path, dataset.dtype, dataset.shape);
+//! }
+//! let plan = file.metadata()?.get("/start@plan_name").cloned();
+//! let first_ten = file.read_slice("/primary/det1", 0..10)?;
+//! ```
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// HDF5 superblock signature
+const HDF5_MAGIC: &[u8] = b"\x89HDF\r\n\x1a\n";
+/// Arrow IPC file signature
+const ARROW_MAGIC: &[u8] = b"ARROW1";
+
+/// Name of the only dataset of a TIFF file
+pub const TIFF_IMAGE_DATASET: &str = "image";
+
+/// File formats [`DataFile`] can read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataFileFormat {
+    Hdf5,
+    ArrowIpc,
+    Tiff,
+}
+
+impl DataFileFormat {
+    /// Format of a file from its signature, or its extension if unrecognised
+    pub fn detect(path: &Path) -> Result<Self> {
+        let mut magic = Vec::with_capacity(8);
+        std::fs::File::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?
+            .take(8)
+            .read_to_end(&mut magic)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        if magic.starts_with(HDF5_MAGIC) {
+            return Ok(Self::Hdf5);
+        }
+        if magic.starts_with(ARROW_MAGIC) {
+            return Ok(Self::ArrowIpc);
+        }
+        if magic.starts_with(b"II*\0") || magic.starts_with(b"MM\0*") {
+            return Ok(Self::Tiff);
+        }
+
+        // HDF5 files with a user block start with arbitrary bytes
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("h5" | "hdf5" | "nxs") => Ok(Self::Hdf5),
+            Some("arrow" | "ipc" | "feather") => Ok(Self::ArrowIpc),
+            Some("tif" | "tiff") => Ok(Self::Tiff),
+            _ => bail!("{} is not an HDF5, Arrow IPC or TIFF file", path.display()),
+        }
+    }
+}
+
+/// One dataset (HDF5 dataset, Arrow column or TIFF image) of a file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetInfo {
+    /// Path or name to pass to [`DataFile::read_slice`]
+    pub path: String,
+    /// Element type as reported by the format (e.g. `float64`, `uint16`)
+    pub dtype: String,
+    /// Dimensions, outermost (rows) first; empty for scalars
+    pub shape: Vec<usize>,
+    /// Attributes attached to the dataset itself
+    pub attributes: BTreeMap<String, String>,
+}
+
+impl DatasetInfo {
+    /// Number of rows along the first axis
+    pub fn rows(&self) -> usize {
+        self.shape.first().copied().unwrap_or(1)
+    }
+
+    /// Number of values in one row
+    pub fn row_len(&self) -> usize {
+        self.shape.iter().skip(1).product()
+    }
+}
+
+/// Everything [`DataFile`] knows about a file, for catalogs and reports
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileSummary {
+    pub path: PathBuf,
+    pub format: DataFileFormat,
+    pub datasets: Vec<DatasetInfo>,
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// A data file opened for inspection
+///
+/// The file is reopened for each call, so a `DataFile` can be kept around
+/// while the writer is still appending to it.
+#[derive(Debug, Clone)]
+pub struct DataFile {
+    path: PathBuf,
+    format: DataFileFormat,
+}
+
+impl DataFile {
+    /// Open a file, detecting its format
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        Ok(Self {
+            format: DataFileFormat::detect(path)?,
+            path: path.to_path_buf(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn format(&self) -> DataFileFormat {
+        self.format
+    }
+
+    /// All datasets of the file
+    pub fn datasets(&self) -> Result<Vec<DatasetInfo>> {
+        match self.format {
+            DataFileFormat::Hdf5 => hdf5_file::datasets(&self.path),
+            DataFileFormat::ArrowIpc => arrow_file::datasets(&self.path),
+            DataFileFormat::Tiff => tiff_file::datasets(&self.path),
+        }
+    }
+
+    /// One dataset, by path (a leading `/` is optional)
+    pub fn dataset(&self, path: &str) -> Result<DatasetInfo> {
+        let wanted = path.trim_start_matches('/');
+        self.datasets()?
+            .into_iter()
+            .find(|d| d.path.trim_start_matches('/') == wanted)
+            .with_context(|| format!("No dataset '{}' in {}", path, self.path.display()))
+    }
+
+    /// File-level metadata as text
+    pub fn metadata(&self) -> Result<BTreeMap<String, String>> {
+        match self.format {
+            DataFileFormat::Hdf5 => hdf5_file::metadata(&self.path),
+            DataFileFormat::ArrowIpc => arrow_file::metadata(&self.path),
+            DataFileFormat::Tiff => tiff_file::metadata(&self.path),
+        }
+    }
+
+    /// Values of `rows` of a dataset, flattened row-major
+    ///
+    /// Rows past the end are ignored. Datasets of more than three
+    /// dimensions and non-numeric datasets cannot be read.
+    pub fn read_slice(&self, dataset: &str, rows: Range<usize>) -> Result<Vec<f64>> {
+        match self.format {
+            DataFileFormat::Hdf5 => hdf5_file::read_slice(&self.path, dataset, rows),
+            DataFileFormat::ArrowIpc => arrow_file::read_slice(&self.path, dataset, rows),
+            DataFileFormat::Tiff => tiff_file::read_slice(&self.path, dataset, rows),
+        }
+        .with_context(|| format!("Failed to read '{}' from {}", dataset, self.path.display()))
+    }
+
+    /// Datasets and metadata together
+    pub fn summary(&self) -> Result<FileSummary> {
+        Ok(FileSummary {
+            path: self.path.clone(),
+            format: self.format,
+            datasets: self.datasets()?,
+            metadata: self.metadata()?,
+        })
+    }
+}
+
+/// `rows` limited to a dataset of `len` rows
+#[cfg_attr(
+    not(any(
+        feature = "storage_hdf5",
+        feature = "storage_arrow",
+        feature = "storage_tiff"
+    )),
+    allow(dead_code)
+)]
+fn clamp_rows(rows: Range<usize>, len: usize) -> Range<usize> {
+    let end = rows.end.min(len);
+    rows.start.min(end)..end
+}
+
+#[cfg(feature = "storage_hdf5")]
+mod hdf5_file {
+    use super::{clamp_rows, DatasetInfo};
+    use crate::provenance::attribute_text;
+    use anyhow::{bail, Context, Result};
+    use hdf5::{File, Group, Location};
+    use std::collections::BTreeMap;
+    use std::ops::Range;
+    use std::path::Path;
+
+    fn open(path: &Path) -> Result<File> {
+        File::open(path).with_context(|| format!("Failed to open HDF5 file {}", path.display()))
+    }
+
+    pub(super) fn datasets(path: &Path) -> Result<Vec<DatasetInfo>> {
+        let mut datasets = Vec::new();
+        collect_datasets(&open(path)?, &mut datasets)?;
+        Ok(datasets)
+    }
+
+    fn collect_datasets(group: &Group, datasets: &mut Vec<DatasetInfo>) -> Result<()> {
+        for dataset in group.datasets()? {
+            datasets.push(DatasetInfo {
+                path: dataset.name(),
+                dtype: dataset
+                    .dtype()
+                    .and_then(|dtype| dtype.to_descriptor())
+                    .map(|descriptor| descriptor.to_string())
+                    .unwrap_or_else(|_| "unknown".to_string()),
+                shape: dataset.shape(),
+                attributes: attributes(&dataset),
+            });
+        }
+        for child in group.groups()? {
+            collect_datasets(&child, datasets)?;
+        }
+        Ok(())
+    }
+
+    pub(super) fn metadata(path: &Path) -> Result<BTreeMap<String, String>> {
+        let mut metadata = BTreeMap::new();
+        collect_metadata(&open(path)?, &mut metadata)?;
+        Ok(metadata)
+    }
+
+    fn collect_metadata(group: &Group, metadata: &mut BTreeMap<String, String>) -> Result<()> {
+        let group_path = group.name();
+        for (name, value) in attributes(group) {
+            metadata.insert(format!("{}@{}", group_path, name), value);
+        }
+        for child in group.groups()? {
+            collect_metadata(&child, metadata)?;
+        }
+        Ok(())
+    }
+
+    /// Scalar string and number attributes; others are skipped
+    fn attributes(location: &Location) -> BTreeMap<String, String> {
+        location
+            .attr_names()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|name| {
+                let value = location
+                    .attr(&name)
+                    .ok()
+                    .and_then(|attr| attribute_text(&attr).ok())?;
+                Some((name, value))
+            })
+            .collect()
+    }
+
+    pub(super) fn read_slice(path: &Path, name: &str, rows: Range<usize>) -> Result<Vec<f64>> {
+        let file = open(path)?;
+        let dataset = file.dataset(name)?;
+        let shape = dataset.shape();
+        let rows = clamp_rows(rows, shape.first().copied().unwrap_or(0));
+        Ok(match shape.len() {
+            0 => vec![dataset.read_scalar::<f64>()?],
+            1 => dataset
+                .read_slice_1d::<f64, _>(rows)?
+                .iter()
+                .copied()
+                .collect(),
+            2 => dataset
+                .read_slice_2d::<f64, _>((rows, ..))?
+                .iter()
+                .copied()
+                .collect(),
+            3 => {
+                // Frame stacks: one 2D read per frame
+                let mut values = Vec::with_capacity(rows.len() * shape[1] * shape[2]);
+                for row in rows {
+                    values.extend(dataset.read_slice_2d::<f64, _>((row, .., ..))?.iter());
+                }
+                values
+            }
+            ndim => bail!("Cannot slice {}-dimensional datasets", ndim),
+        })
+    }
+}
+
+#[cfg(not(feature = "storage_hdf5"))]
+mod hdf5_file {
+    use super::DatasetInfo;
+    use anyhow::{bail, Result};
+    use std::collections::BTreeMap;
+    use std::ops::Range;
+    use std::path::Path;
+
+    pub(super) fn datasets(_path: &Path) -> Result<Vec<DatasetInfo>> {
+        bail!("HDF5 storage feature not enabled")
+    }
+
+    pub(super) fn metadata(_path: &Path) -> Result<BTreeMap<String, String>> {
+        bail!("HDF5 storage feature not enabled")
+    }
+
+    pub(super) fn read_slice(_path: &Path, _name: &str, _rows: Range<usize>) -> Result<Vec<f64>> {
+        bail!("HDF5 storage feature not enabled")
+    }
+}
+
+#[cfg(feature = "storage_arrow")]
+mod arrow_file {
+    use super::{clamp_rows, DatasetInfo};
+    use anyhow::{Context, Result};
+    use arrow::array::{Array, AsArray};
+    use arrow::compute::cast;
+    use arrow::datatypes::{DataType, Float64Type};
+    use arrow::ipc::reader::FileReader;
+    use std::collections::BTreeMap;
+    use std::fs::File;
+    use std::ops::Range;
+    use std::path::Path;
+
+    fn open(path: &Path) -> Result<FileReader<File>> {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        FileReader::try_new(file, None)
+            .with_context(|| format!("Failed to read Arrow IPC file {}", path.display()))
+    }
+
+    pub(super) fn datasets(path: &Path) -> Result<Vec<DatasetInfo>> {
+        let reader = open(path)?;
+        let schema = reader.schema();
+        let mut rows = 0;
+        for batch in reader {
+            rows += batch?.num_rows();
+        }
+        Ok(schema
+            .fields()
+            .iter()
+            .map(|field| DatasetInfo {
+                path: field.name().clone(),
+                dtype: field.data_type().to_string(),
+                shape: vec![rows],
+                attributes: field
+                    .metadata()
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect(),
+            })
+            .collect())
+    }
+
+    pub(super) fn metadata(path: &Path) -> Result<BTreeMap<String, String>> {
+        Ok(open(path)?
+            .schema()
+            .metadata()
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect())
+    }
+
+    /// Column values; nulls read as NaN
+    pub(super) fn read_slice(path: &Path, name: &str, rows: Range<usize>) -> Result<Vec<f64>> {
+        let reader = open(path)?;
+        let column = reader.schema().index_of(name)?;
+        let mut values = Vec::with_capacity(rows.len());
+        let mut offset = 0;
+        for batch in reader {
+            let batch = batch?;
+            let in_batch = clamp_rows(
+                rows.start.saturating_sub(offset)..rows.end.saturating_sub(offset),
+                batch.num_rows(),
+            );
+            offset += batch.num_rows();
+            if in_batch.is_empty() {
+                continue;
+            }
+            let slice = batch.column(column).slice(in_batch.start, in_batch.len());
+            let floats = cast(&slice, &DataType::Float64)?;
+            values.extend(
+                floats
+                    .as_primitive::<Float64Type>()
+                    .iter()
+                    .map(|v| v.unwrap_or(f64::NAN)),
+            );
+            if offset >= rows.end {
+                break;
+            }
+        }
+        Ok(values)
+    }
+}
+
+#[cfg(not(feature = "storage_arrow"))]
+mod arrow_file {
+    use super::DatasetInfo;
+    use anyhow::{bail, Result};
+    use std::collections::BTreeMap;
+    use std::ops::Range;
+    use std::path::Path;
+
+    pub(super) fn datasets(_path: &Path) -> Result<Vec<DatasetInfo>> {
+        bail!("Arrow storage feature not enabled")
+    }
+
+    pub(super) fn metadata(_path: &Path) -> Result<BTreeMap<String, String>> {
+        bail!("Arrow storage feature not enabled")
+    }
+
+    pub(super) fn read_slice(_path: &Path, _name: &str, _rows: Range<usize>) -> Result<Vec<f64>> {
+        bail!("Arrow storage feature not enabled")
+    }
+}
+
+#[cfg(feature = "storage_tiff")]
+mod tiff_file {
+    use super::{clamp_rows, DatasetInfo, TIFF_IMAGE_DATASET};
+    use anyhow::{bail, Context, Result};
+    use image::{ColorType, DynamicImage, ImageDecoder, ImageReader};
+    use std::collections::BTreeMap;
+    use std::ops::Range;
+    use std::path::Path;
+
+    pub(super) fn datasets(path: &Path) -> Result<Vec<DatasetInfo>> {
+        // The header is enough; pixels are only decoded by read_slice
+        let decoder = ImageReader::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?
+            .with_guessed_format()?
+            .into_decoder()
+            .with_context(|| format!("Failed to read TIFF file {}", path.display()))?;
+        let (width, height) = decoder.dimensions();
+        let dtype = match decoder.color_type() {
+            ColorType::L8 => "uint8".to_string(),
+            ColorType::L16 => "uint16".to_string(),
+            other => format!("{:?}", other).to_lowercase(),
+        };
+        Ok(vec![DatasetInfo {
+            path: TIFF_IMAGE_DATASET.to_string(),
+            dtype,
+            shape: vec![height as usize, width as usize],
+            attributes: BTreeMap::new(),
+        }])
+    }
+
+    /// Top-level fields of the JSON sidecar `TiffWriter` writes, if any
+    pub(super) fn metadata(path: &Path) -> Result<BTreeMap<String, String>> {
+        let sidecar = path.with_extension("json");
+        if !sidecar.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let text = std::fs::read_to_string(&sidecar)
+            .with_context(|| format!("Failed to read {}", sidecar.display()))?;
+        let value: serde_json::Value = serde_json::from_str(&text)
+            .with_context(|| format!("Invalid frame metadata in {}", sidecar.display()))?;
+        Ok(value
+            .as_object()
+            .map(|fields| {
+                fields
+                    .iter()
+                    .map(|(key, value)| {
+                        let text = match value {
+                            serde_json::Value::String(s) => s.clone(),
+                            other => other.to_string(),
+                        };
+                        (key.clone(), text)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    pub(super) fn read_slice(path: &Path, name: &str, rows: Range<usize>) -> Result<Vec<f64>> {
+        if name.trim_start_matches('/') != TIFF_IMAGE_DATASET {
+            bail!(
+                "TIFF files only contain the '{}' dataset",
+                TIFF_IMAGE_DATASET
+            );
+        }
+        let image = image::open(path)
+            .with_context(|| format!("Failed to decode TIFF file {}", path.display()))?;
+        let width = image.width() as usize;
+        let rows = clamp_rows(rows, image.height() as usize);
+        let pixels = rows.start * width..rows.end * width;
+        Ok(match image {
+            DynamicImage::ImageLuma8(buffer) => buffer.as_raw()[pixels]
+                .iter()
+                .map(|&v| f64::from(v))
+                .collect(),
+            DynamicImage::ImageLuma16(buffer) => buffer.as_raw()[pixels]
+                .iter()
+                .map(|&v| f64::from(v))
+                .collect(),
+            other => other.to_luma32f().as_raw()[pixels]
+                .iter()
+                .map(|&v| f64::from(v))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(not(feature = "storage_tiff"))]
+mod tiff_file {
+    use super::DatasetInfo;
+    use anyhow::{bail, Result};
+    use std::collections::BTreeMap;
+    use std::ops::Range;
+    use std::path::Path;
+
+    pub(super) fn datasets(_path: &Path) -> Result<Vec<DatasetInfo>> {
+        bail!("TIFF storage feature not enabled")
+    }
+
+    pub(super) fn metadata(_path: &Path) -> Result<BTreeMap<String, String>> {
+        bail!("TIFF storage feature not enabled")
+    }
+
+    pub(super) fn read_slice(_path: &Path, _name: &str, _rows: Range<usize>) -> Result<Vec<f64>> {
+        bail!("TIFF storage feature not enabled")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_format_from_signature() {
+        let dir = tempfile::tempdir().unwrap();
+        let hdf5 = dir.path().join("run.dat");
+        std::fs::write(&hdf5, [HDF5_MAGIC, b"rest"].concat()).unwrap();
+        assert_eq!(DataFileFormat::detect(&hdf5).unwrap(), DataFileFormat::Hdf5);
+
+        let arrow = dir.path().join("run.h5");
+        std::fs::write(&arrow, b"ARROW1\0\0").unwrap();
+        assert_eq!(
+            DataFileFormat::detect(&arrow).unwrap(),
+            DataFileFormat::ArrowIpc
+        );
+
+        let tiff = dir.path().join("frame.tiff");
+        std::fs::write(&tiff, b"").unwrap();
+        assert_eq!(DataFileFormat::detect(&tiff).unwrap(), DataFileFormat::Tiff);
+
+        let unknown = dir.path().join("notes.txt");
+        std::fs::write(&unknown, b"hello").unwrap();
+        assert!(DataFile::open(&unknown).is_err());
+        assert_eq!(clamp_rows(5..20, 8), 5..8);
+        assert_eq!(clamp_rows(10..20, 8), 8..8);
+    }
+
+    #[cfg(feature = "storage_hdf5")]
+    #[test]
+    fn test_inspects_hdf5_run_file() {
+        use hdf5::types::VarLenUnicode;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run1_1000.h5");
+        {
+            let file = hdf5::File::create(&path).unwrap();
+            let start = file.create_group("start").unwrap();
+            start
+                .new_attr::<VarLenUnicode>()
+                .create("plan_name")
+                .unwrap()
+                .write_scalar(&"count".parse::<VarLenUnicode>().unwrap())
+                .unwrap();
+            let primary = file.create_group("primary").unwrap();
+            primary
+                .new_dataset_builder()
+                .with_data(&[1.0f64, 2.0, 3.0, 4.0][..])
+                .create("det1")
+                .unwrap();
+            primary
+                .new_dataset::<u16>()
+                .shape([3, 2, 2])
+                .create("frames")
+                .unwrap()
+                .write_raw(&(0u16..12).collect::<Vec<_>>())
+                .unwrap();
+        }
+
+        let file = DataFile::open(&path).unwrap();
+        let summary = file.summary().unwrap();
+        assert_eq!(summary.format, DataFileFormat::Hdf5);
+        assert_eq!(summary.metadata["/start@plan_name"], "count");
+        let frames = file.dataset("primary/frames").unwrap();
+        assert_eq!(frames.shape, [3, 2, 2]);
+        assert_eq!(frames.row_len(), 4);
+
+        assert_eq!(
+            file.read_slice("/primary/det1", 1..10).unwrap(),
+            [2.0, 3.0, 4.0]
+        );
+        assert_eq!(
+            file.read_slice("/primary/frames", 2..3).unwrap(),
+            [8.0, 9.0, 10.0, 11.0]
+        );
+    }
+
+    #[cfg(feature = "storage_tiff")]
+    #[test]
+    fn test_inspects_tiff_frame() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("frame.tiff");
+        let pixels: Vec<u16> = (0..12).collect();
+        image::ImageBuffer::<image::Luma<u16>, _>::from_raw(4, 3, pixels)
+            .unwrap()
+            .save(&path)
+            .unwrap();
+        std::fs::write(path.with_extension("json"), r#"{"frame_number": 7}"#).unwrap();
+
+        let file = DataFile::open(&path).unwrap();
+        let image = file.dataset(TIFF_IMAGE_DATASET).unwrap();
+        assert_eq!(image.dtype, "uint16");
+        assert_eq!(image.shape, [3, 4]);
+        assert_eq!(file.metadata().unwrap()["frame_number"], "7");
+        assert_eq!(
+            file.read_slice(TIFF_IMAGE_DATASET, 2..3).unwrap(),
+            [8.0, 9.0, 10.0, 11.0]
+        );
+    }
+}
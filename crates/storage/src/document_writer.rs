@@ -8,20 +8,50 @@
 //! - **Start**: Creates a new HDF5 file (or group if appending)
 //! - **Descriptor**: Creates datasets for each data key
 //! - **Event**: Appends data to the datasets
-//! - **Stop**: Finalizes the file/group
+//! - **Stop**: Finalizes the file/group, and signs it when a signer is set
+//!   (see [`crate::provenance`])
+//!
+//! Frame channel values (see `common::frame_enrichment`) arrive as scalar
+//! event fields named `<detector>.<channel>` and are stored like any other
+//! scalar field: one `f64` dataset per field in the stream group, with a value
+//! for each event that captured a frame.
+//!
+//! Frame overlay annotations (see `common::frame_annotations`) arrive as
+//! JSON event metadata fields named `<detector>.annotations` and go into a
+//! `frame_annotations` group: a table of equal-length `seq_num`, `detector`
+//! and `annotations` (JSON) datasets, one row per annotated frame.
+//!
+//! Scalar quality flags (see `common::core::DataQuality`) go into a `quality`
+//! subgroup with one `u8` dataset per field, indexed like the field's dataset.
+//! A dataset is only created once a field gets its first non-good value, so a
+//! missing dataset, or one shorter than the data, means good.
+//!
+//! Device events (see `common::device_events`) raised during the run go into
+//! a `device_events` group: a table of equal-length `timestamp_ns`,
+//! `device_id`, `kind`, `severity`, `message` and `payload` (JSON) datasets.
+//!
+//! Frames with a known `[height, width]` shape are stored as `(n, height,
+//! width)` datasets. Chunk shapes come from the writer's [`ChunkingConfig`]
+//! and are recorded on each dataset (see [`crate::hdf5_chunking`]).
 //!
 //! This replaces the legacy `ScanProgress` pipeline.
 
+use crate::hdf5_chunking::ChunkingConfig;
 #[cfg(feature = "storage_hdf5")]
 use anyhow::anyhow;
 use anyhow::Result;
 use common::experiment::document::Document;
+use common::provenance::{RunSigner, SignedManifest};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
+#[cfg(feature = "storage_hdf5")]
+use common::device_events::DeviceEvent;
 #[cfg(feature = "storage_hdf5")]
 use common::experiment::document::{EventDoc, StartDoc};
+#[cfg(feature = "storage_hdf5")]
+use common::frame_annotations::annotations_detector;
 
 /// HDF5 Writer for RunEngine Documents
 pub struct DocumentWriter {
@@ -31,6 +61,11 @@ pub struct DocumentWriter {
     /// Using a simple implementation for now: one writer instance per run, or single active run
     #[allow(dead_code)]
     active_run: Arc<Mutex<Option<ActiveRun>>>,
+    /// Signs each run file once its StopDoc is written
+    #[allow(dead_code)]
+    signer: Option<Arc<RunSigner>>,
+    /// Chunk shape selection for datasets of runs started from now on
+    chunking: Arc<Mutex<ChunkingConfig>>,
 }
 
 #[allow(dead_code)]
@@ -52,24 +87,62 @@ impl DocumentWriter {
         Self {
             base_path,
             active_run: Arc::new(Mutex::new(None)),
+            signer: None,
+            chunking: Arc::new(Mutex::new(ChunkingConfig::default())),
         }
     }
 
+    /// Choose dataset chunk shapes with `chunking`
+    pub fn with_chunking(self, chunking: ChunkingConfig) -> Self {
+        self.set_chunking(chunking);
+        self
+    }
+
+    /// Chunk shape selection for datasets created from now on
+    pub fn set_chunking(&self, chunking: ChunkingConfig) {
+        *self.chunking.lock().unwrap_or_else(|p| p.into_inner()) = chunking;
+    }
+
+    pub fn chunking(&self) -> ChunkingConfig {
+        self.chunking
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+
+    /// Sign completed run files with `signer`
+    pub fn with_signer(mut self, signer: Arc<RunSigner>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Directory run files are written to
+    pub fn base_path(&self) -> &Path {
+        &self.base_path
+    }
+
+    /// File a run is written to
+    pub fn run_file_path(&self, run_uid: &str, start_time_ns: u64) -> PathBuf {
+        run_file_path(&self.base_path, run_uid, start_time_ns)
+    }
+
     /// Write a document to storage
     ///
-    /// This spawns a blocking task for HDF5 I/O.
+    /// This spawns a blocking task for HDF5 I/O. Returns the signed manifest
+    /// when a StopDoc completes a run and a signer is set.
     #[cfg(feature = "storage_hdf5")]
-    pub async fn write(&self, doc: Document) -> Result<()> {
+    pub async fn write(&self, doc: Document) -> Result<Option<SignedManifest>> {
         let active_run = self.active_run.clone();
         let base_path = self.base_path.clone();
+        let signer = self.signer.clone();
+        let chunking = self.chunking();
 
-        tokio::task::spawn_blocking(move || -> Result<()> {
+        tokio::task::spawn_blocking(move || -> Result<Option<SignedManifest>> {
             let mut guard = active_run.lock().map_err(|_| anyhow!("Mutex poisoned"))?;
 
             match doc {
                 Document::Start(start) => {
-                    let filename = format!("{}_{}.h5", start.uid, start.time_ns);
-                    let file_path = base_path.join(filename);
+                    let file_path = run_file_path(&base_path, &start.uid, start.time_ns);
 
                     // Create file and write start metadata
                     use hdf5::File;
@@ -122,37 +195,45 @@ impl DocumentWriter {
                         for (key, meta) in &desc.data_keys {
                             // Create extendable dataset (chunked)
                             // Initial dimensions: (0), Max: (Unlimited)
-                            // Create extendable dataset (chunked)
-                            match meta.dtype.as_str() {
-                                "uint16" => {
-                                    // Create u16 dataset
-                                    // For simplicity, we create 1D extendable for now,
-                                    // flattening multidimensional frames if necessary.
-                                    // Ideal: (0.., h, w).
+                            let (ds, layout) = match (meta.dtype.as_str(), meta.shape.as_slice()) {
+                                ("uint16", &[h, w]) if h > 0 && w > 0 => {
+                                    // Frames: (0.., h, w), chunked for ROI reads
+                                    let (h, w) = (h as usize, w as usize);
+                                    let layout = chunking.frame_chunk(h, w, 2, None);
+                                    let ds = group
+                                        .new_dataset::<u16>()
+                                        .chunk((layout.shape[0], layout.shape[1], layout.shape[2]))
+                                        .shape((0.., h, w))
+                                        .create(key.as_str())?;
+                                    (ds, layout)
+                                }
+                                ("uint16", _) => {
+                                    // Unknown shape: flattened 1D extendable
+                                    let layout = chunking.scalar_chunk(2, None);
                                     let ds = group
                                         .new_dataset::<u16>()
-                                        .chunk(1024)
+                                        .chunk(layout.shape[0])
                                         .shape(0..)
                                         .create(key.as_str())?;
-
-                                    // Write metadata
-                                    write_dataset_attr(&ds, "source", &meta.source)?;
-                                    write_dataset_attr(&ds, "dtype", &meta.dtype)?;
-                                    write_dataset_attr(&ds, "shape", &format!("{:?}", meta.shape))?;
+                                    (ds, layout)
                                 }
                                 _ => {
                                     // Default to f64
+                                    let layout = chunking.scalar_chunk(8, None);
                                     let ds = group
                                         .new_dataset::<f64>()
-                                        .chunk(1024)
+                                        .chunk(layout.shape[0])
                                         .shape(0..)
                                         .create(key.as_str())?;
-
-                                    write_dataset_attr(&ds, "source", &meta.source)?;
-                                    write_dataset_attr(&ds, "dtype", &meta.dtype)?;
-                                    write_dataset_attr(&ds, "shape", &format!("{:?}", meta.shape))?;
+                                    (ds, layout)
                                 }
-                            }
+                            };
+
+                            // Write metadata
+                            write_dataset_attr(&ds, "source", &meta.source)?;
+                            write_dataset_attr(&ds, "dtype", &meta.dtype)?;
+                            write_dataset_attr(&ds, "shape", &format!("{:?}", meta.shape))?;
+                            crate::hdf5_chunking::write_chunk_attrs(&ds, &layout)?;
                         }
 
                         run.descriptors.insert(
@@ -193,6 +274,7 @@ impl DocumentWriter {
                                         let current_len = shape[0];
                                         ds.resize((current_len + 1,))?;
                                         ds.write_slice(&[*value], (current_len..))?;
+                                        write_quality(&group, key, current_len, &event)?;
                                     }
                                 }
                             }
@@ -215,8 +297,19 @@ impl DocumentWriter {
                                                     .map(|b| u16::from_le_bytes([b[0], b[1]]))
                                                     .collect();
 
-                                                ds.resize((current_len + u16_data.len(),))?;
-                                                ds.write_slice(&u16_data, current_len..)?;
+                                                if let &[_, h, w] = shape.as_slice() {
+                                                    if u16_data.len() != h * w {
+                                                        continue; // Frame size changed
+                                                    }
+                                                    ds.resize((current_len + 1, h, w))?;
+                                                    ds.write_slice(
+                                                        &u16_data,
+                                                        (current_len..current_len + 1, .., ..),
+                                                    )?;
+                                                } else {
+                                                    ds.resize((current_len + u16_data.len(),))?;
+                                                    ds.write_slice(&u16_data, current_len..)?;
+                                                }
                                             }
                                             _ => {
                                                 // Default/Fallback
@@ -244,11 +337,13 @@ impl DocumentWriter {
                                 )?;
                             } else {
                                 // Create if missing (lazy)
+                                let layout = chunking.scalar_chunk(8, None);
                                 let ds = group
                                     .new_dataset::<f64>()
-                                    .chunk(1024)
+                                    .chunk(layout.shape[0])
                                     .shape((0..))
                                     .create("timestamps")?;
+                                crate::hdf5_chunking::write_chunk_attrs(&ds, &layout)?;
                                 let shape = ds.shape();
                                 ds.resize((shape[0] + 1,))?;
                                 ds.write_slice(
@@ -256,9 +351,15 @@ impl DocumentWriter {
                                     (shape[0]..),
                                 )?;
                             }
+
+                            for (key, json) in &event.metadata {
+                                if let Some(detector) = annotations_detector(key) {
+                                    append_frame_annotations(&file, event.seq_num, detector, json)?;
+                                }
+                            }
                         }
                     }
-                    return Ok(()); // Return Ok from closure
+                    return Ok(None); // Return Ok from closure
                 }
                 Document::Stop(stop) => {
                     if let Some(run) = guard.as_mut() {
@@ -267,30 +368,62 @@ impl DocumentWriter {
                             let file = File::open_rw(&run.file_path)?;
                             let group = file.create_group("stop")?;
                             write_group_attr(&group, "exit_status", &stop.exit_status)?;
+                            for (key, value) in &stop.metadata {
+                                write_group_attr(&group, key, value)?;
+                            }
+                            drop(group);
+                            drop(file);
 
                             // Clear active run
-                            *guard = None;
+                            let run = guard.take().expect("active run checked above");
+                            if let Some(signer) = signer {
+                                let signed = crate::provenance::sign_run_file(
+                                    &run.file_path,
+                                    &run.run_uid,
+                                    &signer,
+                                )?;
+                                tracing::info!(
+                                    run_uid = %run.run_uid,
+                                    key_id = %signed.key_id,
+                                    "Signed run file"
+                                );
+                                return Ok(Some(signed));
+                            }
                         }
                     }
                 }
                 Document::Manifest(_) => {
                     // TODO: Handle manifest writing if needed within stream
-                    return Ok(());
+                    return Ok(None);
+                }
+                Document::DeviceEvent(doc) => {
+                    if let Some(run) = guard.as_ref().filter(|run| run.run_uid == doc.run_uid) {
+                        let file = hdf5::File::open_rw(&run.file_path)?;
+                        append_device_event(&file, &doc.event)?;
+                    }
+                    return Ok(None);
+                }
+                Document::Progress(_) | Document::BatchSummary(_) => {
+                    // Progress is transient UI state and batch summaries
+                    // span several run files; neither is persisted here
+                    return Ok(None);
                 }
             }
-            Ok(())
+            Ok(None)
         })
-        .await??;
-
-        Ok(())
+        .await?
     }
 
     #[cfg(not(feature = "storage_hdf5"))]
-    pub async fn write(&self, _doc: Document) -> Result<()> {
-        Ok(())
+    pub async fn write(&self, _doc: Document) -> Result<Option<SignedManifest>> {
+        Ok(None)
     }
 }
 
+fn run_file_path(base_path: &Path, run_uid: &str, start_time_ns: u64) -> PathBuf {
+    base_path.join(format!("{}_{}.h5", run_uid, start_time_ns))
+}
+
 #[cfg(feature = "storage_hdf5")]
 fn write_group_attr(container: &hdf5::Group, name: &str, value: &str) -> Result<()> {
     use hdf5::types::VarLenUnicode;
@@ -301,6 +434,117 @@ fn write_group_attr(container: &hdf5::Group, name: &str, value: &str) -> Result<
     Ok(())
 }
 
+/// Record the quality of `event.data[key]`, written at `index` of its dataset
+#[cfg(feature = "storage_hdf5")]
+fn write_quality(group: &hdf5::Group, key: &str, index: usize, event: &EventDoc) -> Result<()> {
+    let quality = event.quality(key);
+    let ds = match group.group("quality").and_then(|q| q.dataset(key)) {
+        Ok(ds) => ds,
+        // Nothing flagged yet for this field; good needs no entry
+        Err(_) if quality.is_good() => return Ok(()),
+        Err(_) => {
+            let qualities = match group.group("quality") {
+                Ok(qualities) => qualities,
+                Err(_) => {
+                    let qualities = group.create_group("quality")?;
+                    write_group_attr(
+                        &qualities,
+                        "codes",
+                        "0=good,1=suspect,2=stale,3=out_of_range,4=interpolated",
+                    )?;
+                    qualities
+                }
+            };
+            qualities
+                .new_dataset::<u8>()
+                .chunk(1024)
+                .shape(0..)
+                .create(key)?
+        }
+    };
+    // Earlier entries are zero-filled, i.e. good
+    ds.resize((index + 1,))?;
+    ds.write_slice(&[quality as u8], index..)?;
+    Ok(())
+}
+
+/// Append a row to the run's `device_events` table
+#[cfg(feature = "storage_hdf5")]
+fn append_device_event(file: &hdf5::File, event: &DeviceEvent) -> Result<()> {
+    let group = match file.group("device_events") {
+        Ok(group) => group,
+        Err(_) => file.create_group("device_events")?,
+    };
+    let index = group
+        .dataset("timestamp_ns")
+        .map(|ds| ds.shape()[0])
+        .unwrap_or(0);
+    let payload = serde_json::to_string(&event.payload)?;
+
+    append_row(&group, "timestamp_ns", index, event.timestamp_ns)?;
+    for (name, value) in [
+        ("device_id", event.device_id.as_str()),
+        ("kind", event.kind.as_str()),
+        ("severity", event.severity.as_str()),
+        ("message", event.message.as_str()),
+        ("payload", payload.as_str()),
+    ] {
+        let value = value
+            .parse::<hdf5::types::VarLenUnicode>()
+            .map_err(|e| anyhow!("Device event {} not storable: {}", name, e))?;
+        append_row(&group, name, index, value)?;
+    }
+    Ok(())
+}
+
+/// Append a row to the run's `frame_annotations` table
+#[cfg(feature = "storage_hdf5")]
+fn append_frame_annotations(
+    file: &hdf5::File,
+    seq_num: u32,
+    detector: &str,
+    json: &str,
+) -> Result<()> {
+    let group = match file.group("frame_annotations") {
+        Ok(group) => group,
+        Err(_) => file.create_group("frame_annotations")?,
+    };
+    let index = group
+        .dataset("seq_num")
+        .map(|ds| ds.shape()[0])
+        .unwrap_or(0);
+
+    append_row(&group, "seq_num", index, u64::from(seq_num))?;
+    for (name, value) in [("detector", detector), ("annotations", json)] {
+        let value = value
+            .parse::<hdf5::types::VarLenUnicode>()
+            .map_err(|e| anyhow!("Frame annotation {} not storable: {}", name, e))?;
+        append_row(&group, name, index, value)?;
+    }
+    Ok(())
+}
+
+/// Write `value` at `index` of a 1-D dataset, creating it if needed
+#[cfg(feature = "storage_hdf5")]
+fn append_row<T: hdf5::H5Type>(
+    group: &hdf5::Group,
+    name: &str,
+    index: usize,
+    value: T,
+) -> Result<()> {
+    let ds = match group.dataset(name) {
+        Ok(ds) => ds,
+        Err(_) => group
+            .new_dataset::<T>()
+            .chunk(256)
+            .shape(0..)
+            .create(name)?,
+    };
+    ds.resize((index + 1,))?;
+    ds.write_slice(&[value], index..)?;
+    Ok(())
+}
+
 #[cfg(feature = "storage_hdf5")]
 fn write_dataset_attr(container: &hdf5::Dataset, name: &str, value: &str) -> Result<()> {
     use hdf5::types::VarLenUnicode;
@@ -406,7 +650,10 @@ mod tests {
             data,
             arrays,
             timestamps: HashMap::new(),
-            metadata: HashMap::new(),
+            metadata: HashMap::from([(
+                "cam1.annotations".to_string(),
+                r#"[{"type":"point","label":"fiducial","x":3.0,"y":4.0}]"#.to_string(),
+            )]),
             run_uid: "test_run_1".to_string(),
             time_ns: 1_000_000_000,
             uid: "event_1".to_string(),
@@ -414,6 +661,19 @@ mod tests {
         };
         writer.write(Document::Event(event)).await.unwrap();
 
+        let limit_hit = common::device_events::DeviceEvent::new(
+            common::device_events::DeviceEventKind::LimitSwitch,
+            common::device_events::DeviceEventSeverity::Warning,
+            "max limit hit",
+        )
+        .with("position", 25.0);
+        writer
+            .write(Document::DeviceEvent(
+                common::experiment::document::DeviceEventDoc::new("test_run_1", limit_hit),
+            ))
+            .await
+            .unwrap();
+
         // 4. Stop
         let stop = StopDoc {
             uid: "stop_1".to_string(),
@@ -422,6 +682,7 @@ mod tests {
             exit_status: "success".to_string(),
             reason: "".to_string(),
             num_events: 1,
+            metadata: HashMap::new(),
         };
         writer.write(Document::Stop(stop)).await.unwrap();
 
@@ -430,6 +691,48 @@ mod tests {
         let file_path = temp_dir.path().join(filename);
         assert!(file_path.exists());
 
-        // Verify contents logic would go here (requires hdf5 crate in dev-dependencies)
+        // Frames are stored as (n, h, w), with their chunk shape recorded
+        let file = hdf5::File::open(&file_path).unwrap();
+        let frames = file.dataset("primary/cam1").unwrap();
+        assert_eq!(frames.shape(), [1, 10, 10]);
+        assert_eq!(frames.chunk(), Some(vec![64, 10, 10]));
+        assert_eq!(
+            frames
+                .attr("chunk_shape")
+                .unwrap()
+                .read_scalar::<hdf5::types::VarLenUnicode>()
+                .unwrap()
+                .as_str(),
+            "[64, 10, 10]"
+        );
+
+        // Device events have a table of their own
+        let kinds = file
+            .dataset("device_events/kind")
+            .unwrap()
+            .read_raw::<hdf5::types::VarLenUnicode>()
+            .unwrap();
+        assert_eq!(kinds.len(), 1);
+        assert_eq!(kinds[0].as_str(), "limit_switch");
+        assert_eq!(
+            file.dataset("device_events/timestamp_ns").unwrap().shape(),
+            [1]
+        );
+
+        // So do frame annotations
+        let detectors = file
+            .dataset("frame_annotations/detector")
+            .unwrap()
+            .read_raw::<hdf5::types::VarLenUnicode>()
+            .unwrap();
+        assert_eq!(detectors.len(), 1);
+        assert_eq!(detectors[0].as_str(), "cam1");
+        assert_eq!(
+            file.dataset("frame_annotations/seq_num")
+                .unwrap()
+                .read_raw::<u64>()
+                .unwrap(),
+            vec![1]
+        );
     }
 }
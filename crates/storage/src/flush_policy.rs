@@ -0,0 +1,318 @@
+//! Adaptive Flush Policy - Flush timing driven by buffer pressure
+//!
+//! A fixed flush timer is wrong in both directions: while idle it wakes the
+//! disk every second for nothing, and during a burst the ring buffer can
+//! fill (and be overwritten) between two ticks. [`FlushPolicy`] instead picks
+//! the next interval from how full the upstream buffer was when the writer
+//! woke up:
+//!
+//! | Fill at wake-up | Pressure | Next interval |
+//! |-----------------|----------|---------------|
+//! | `>= high_water` | High | `min_interval_ms` |
+//! | between the marks | Normal | `interval_ms` |
+//! | `<= low_water` | Idle | doubled, up to `max_interval_ms` |
+//!
+//! While waiting, a writer polls the fill every `min_interval_ms` and wakes
+//! early once it crosses `high_water` (see [`FlushPolicy::wake_early`]), so
+//! a relaxed interval never lets a burst overflow the buffer.
+//!
+//! Every decision is recorded in the process-wide [`FlushMonitor`], which the
+//! daemon exports as Prometheus metrics.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [storage.flush]
+//! adaptive = true
+//! interval_ms = 1000
+//! min_interval_ms = 100
+//! max_interval_ms = 5000
+//! high_water = 0.5
+//! low_water = 0.05
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Flush timing settings of a background writer
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlushPolicyConfig {
+    /// `false` flushes every `interval_ms` regardless of pressure
+    pub adaptive: bool,
+    /// Interval between the water marks
+    pub interval_ms: u64,
+    /// Interval under pressure, and how often the fill is polled while waiting
+    pub min_interval_ms: u64,
+    /// Longest interval reached while idle
+    pub max_interval_ms: u64,
+    /// Fill fraction (0-1) at or above which flushing speeds up
+    pub high_water: f64,
+    /// Fill fraction (0-1) at or below which flushing relaxes
+    pub low_water: f64,
+}
+
+impl Default for FlushPolicyConfig {
+    fn default() -> Self {
+        Self {
+            adaptive: true,
+            interval_ms: 1000,
+            min_interval_ms: 100,
+            max_interval_ms: 5000,
+            high_water: 0.5,
+            low_water: 0.05,
+        }
+    }
+}
+
+impl FlushPolicyConfig {
+    /// Flush every `interval`, whatever the pressure
+    pub fn fixed(interval: Duration) -> Self {
+        let ms = interval.as_millis() as u64;
+        Self {
+            adaptive: false,
+            interval_ms: ms,
+            min_interval_ms: ms,
+            max_interval_ms: ms,
+            ..Self::default()
+        }
+    }
+
+    /// Interval between the water marks
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(self.interval_ms)
+    }
+}
+
+/// How full the upstream buffer was when a flush decision was made
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlushPressure {
+    /// At or below the low water mark
+    Idle,
+    /// Between the water marks
+    Normal,
+    /// At or above the high water mark
+    High,
+}
+
+impl FlushPressure {
+    pub const ALL: [FlushPressure; 3] = [Self::Idle, Self::Normal, Self::High];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Idle => "idle",
+            Self::Normal => "normal",
+            Self::High => "high",
+        }
+    }
+}
+
+/// Outcome of one flush decision
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlushDecision {
+    /// Wait before the next flush
+    pub interval: Duration,
+    pub pressure: FlushPressure,
+    /// Fill fraction the decision was based on
+    pub fill: f64,
+}
+
+/// Chooses flush intervals from buffer fill
+#[derive(Debug, Clone)]
+pub struct FlushPolicy {
+    config: FlushPolicyConfig,
+    current: Duration,
+}
+
+impl FlushPolicy {
+    /// Policy starting at the configured interval
+    ///
+    /// Marks are clamped to 0-1 and the intervals ordered, so a hand-edited
+    /// config cannot stop flushing altogether.
+    pub fn new(config: FlushPolicyConfig) -> Self {
+        let mut config = config;
+        config.interval_ms = config.interval_ms.max(1);
+        config.min_interval_ms = config.min_interval_ms.clamp(1, config.interval_ms);
+        config.max_interval_ms = config.max_interval_ms.max(config.interval_ms);
+        config.high_water = clamp_fraction(config.high_water);
+        config.low_water = clamp_fraction(config.low_water).min(config.high_water);
+        Self {
+            current: config.interval(),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &FlushPolicyConfig {
+        &self.config
+    }
+
+    /// Wait before the next flush, as last decided
+    pub fn interval(&self) -> Duration {
+        self.current
+    }
+
+    /// How often to check the fill while waiting
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.config.min_interval_ms)
+    }
+
+    /// Whether a wait should be cut short at this fill
+    pub fn wake_early(&self, fill: f64) -> bool {
+        self.config.adaptive && clamp_fraction(fill) >= self.config.high_water
+    }
+
+    /// Decide the wait before the next flush from the fill found at wake-up
+    pub fn next(&mut self, fill: f64) -> FlushDecision {
+        let fill = clamp_fraction(fill);
+        let pressure = if fill >= self.config.high_water {
+            FlushPressure::High
+        } else if fill <= self.config.low_water {
+            FlushPressure::Idle
+        } else {
+            FlushPressure::Normal
+        };
+
+        let base = self.config.interval();
+        self.current = if !self.config.adaptive {
+            base
+        } else {
+            match pressure {
+                FlushPressure::High => Duration::from_millis(self.config.min_interval_ms),
+                FlushPressure::Normal => base,
+                // Back off gradually; one busy wake-up resets it
+                FlushPressure::Idle => (self.current.max(base) * 2)
+                    .min(Duration::from_millis(self.config.max_interval_ms)),
+            }
+        };
+
+        FlushDecision {
+            interval: self.current,
+            pressure,
+            fill,
+        }
+    }
+}
+
+fn clamp_fraction(value: f64) -> f64 {
+    if value.is_finite() {
+        value.clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Flush decisions of one writer so far
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FlushStats {
+    /// Current wait between flushes
+    pub interval: Duration,
+    /// Fill fraction at the last decision
+    pub fill: f64,
+    /// Pressure at the last decision
+    pub pressure: Option<FlushPressure>,
+    /// Decisions per pressure level
+    pub decisions: BTreeMap<FlushPressure, u64>,
+    /// Flushes that wrote data
+    pub flushes: u64,
+    pub bytes_written: u64,
+}
+
+/// Flush decisions of every background writer, for metrics
+#[derive(Debug, Default)]
+pub struct FlushMonitor {
+    writers: Mutex<BTreeMap<String, FlushStats>>,
+}
+
+impl FlushMonitor {
+    /// Process-wide monitor fed by the storage writers
+    pub fn global() -> &'static FlushMonitor {
+        static GLOBAL: OnceLock<FlushMonitor> = OnceLock::new();
+        GLOBAL.get_or_init(FlushMonitor::default)
+    }
+
+    /// Record a decision of `writer` after a flush that wrote `bytes_written`
+    pub fn record(&self, writer: &str, decision: &FlushDecision, bytes_written: usize) {
+        let mut writers = self.writers.lock().unwrap_or_else(|p| p.into_inner());
+        let stats = writers.entry(writer.to_string()).or_default();
+        if stats.pressure != Some(decision.pressure) {
+            tracing::debug!(
+                writer,
+                pressure = decision.pressure.as_str(),
+                fill = decision.fill,
+                interval_ms = decision.interval.as_millis() as u64,
+                "Flush pressure changed"
+            );
+        }
+        stats.interval = decision.interval;
+        stats.fill = decision.fill;
+        stats.pressure = Some(decision.pressure);
+        *stats.decisions.entry(decision.pressure).or_default() += 1;
+        if bytes_written > 0 {
+            stats.flushes += 1;
+            stats.bytes_written += bytes_written as u64;
+        }
+    }
+
+    /// Current stats by writer name
+    pub fn snapshot(&self) -> BTreeMap<String, FlushStats> {
+        self.writers
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interval_follows_pressure() {
+        let mut policy = FlushPolicy::new(FlushPolicyConfig::default());
+        assert_eq!(policy.interval(), Duration::from_secs(1));
+
+        let busy = policy.next(0.8);
+        assert_eq!(busy.pressure, FlushPressure::High);
+        assert_eq!(busy.interval, Duration::from_millis(100));
+        assert!(policy.wake_early(0.5));
+        assert!(!policy.wake_early(0.2));
+
+        assert_eq!(policy.next(0.2).interval, Duration::from_secs(1));
+
+        // Idle backs off by doubling up to the maximum
+        let waits: Vec<_> = (0..4).map(|_| policy.next(0.0).interval).collect();
+        assert_eq!(
+            waits,
+            [2000, 4000, 5000, 5000].map(Duration::from_millis).to_vec()
+        );
+        assert_eq!(policy.next(f64::NAN).pressure, FlushPressure::Idle);
+        assert_eq!(policy.next(0.9).interval, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_fixed_policy_keeps_interval_but_reports_pressure() {
+        let mut policy = FlushPolicy::new(FlushPolicyConfig::fixed(Duration::from_millis(50)));
+        let decision = policy.next(0.9);
+        assert_eq!(decision.pressure, FlushPressure::High);
+        assert_eq!(decision.interval, Duration::from_millis(50));
+        assert!(!policy.wake_early(1.0));
+        assert_eq!(policy.next(0.0).interval, Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_monitor_counts_decisions() {
+        let monitor = FlushMonitor::default();
+        let mut policy = FlushPolicy::new(FlushPolicyConfig::default());
+        monitor.record("hdf5", &policy.next(0.9), 4096);
+        monitor.record("hdf5", &policy.next(0.0), 0);
+
+        let stats = &monitor.snapshot()["hdf5"];
+        assert_eq!(stats.pressure, Some(FlushPressure::Idle));
+        assert_eq!(stats.decisions[&FlushPressure::High], 1);
+        assert_eq!(stats.decisions[&FlushPressure::Idle], 1);
+        assert_eq!((stats.flushes, stats.bytes_written), (1, 4096));
+    }
+}
@@ -0,0 +1,313 @@
+//! HDF5 Chunk Selection - Chunk shapes matched to how datasets are read
+//!
+//! HDF5 reads and decompresses whole chunks, so the chunk shape decides what
+//! a partial read costs. A frame stack chunked as a flat run of 1024 pixels
+//! makes a 64x64 ROI touch one chunk per row of every frame; a chunk holding
+//! one 2048x2048 frame makes it decompress 8 MiB per frame.
+//!
+//! [`ChunkingConfig`] picks chunk shapes per dataset:
+//!
+//! - **Frames** are stored as `(n, height, width)`. Frames smaller than the
+//!   target chunk size are grouped, up to about a second of acquisition at
+//!   the nominal frame rate; larger frames are split into tiles, so ROI reads
+//!   only touch the tiles they overlap.
+//! - **Scalars** get chunks of about ten seconds of samples, between 256 and
+//!   65536 rows.
+//! - **Compressed** datasets use a smaller target, since every partial read
+//!   decompresses the chunks it touches in full.
+//!
+//! Explicit chunk shapes in the storage configuration override the
+//! heuristics. The chosen shape is recorded on each dataset as the
+//! `chunk_shape`, `chunk_bytes` and `chunking` attributes.
+//!
+//! # Configuration
+//!
+//! In the daemon's storage settings:
+//!
+//! ```toml
+//! [storage]
+//! compression = "gzip"                # compressed chunks are kept smaller
+//! auto_chunking = true                # false: one frame or `chunk_size` rows per chunk
+//! frame_chunk_shape = [1, 256, 256]   # optional: frames, rows, columns
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Chunk size aimed for, matching HDF5's default 1 MiB chunk cache
+pub const DEFAULT_TARGET_CHUNK_BYTES: usize = 1 << 20;
+
+/// Rows of scalar datasets when nothing better is known
+pub const DEFAULT_SCALAR_CHUNK_ROWS: usize = 1024;
+
+/// Compressed chunks are read in full, so they are kept smaller
+const COMPRESSED_TARGET_DIVISOR: usize = 4;
+
+/// Frame tiles are not split below this many pixels per side
+const MIN_TILE: usize = 64;
+
+/// Most frames grouped into one chunk
+const MAX_FRAMES_PER_CHUNK: usize = 64;
+
+/// A frame chunk should fill within this many seconds at the frame rate
+const FRAME_FILL_SECS: f64 = 1.0;
+
+/// A scalar chunk should fill within this many seconds at the sample rate
+const SCALAR_FILL_SECS: f64 = 10.0;
+
+const MIN_SCALAR_CHUNK_ROWS: usize = 256;
+const MAX_SCALAR_CHUNK_ROWS: usize = 65_536;
+
+/// How chunk shapes are chosen
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChunkingConfig {
+    /// Pick chunk shapes from frame size, rate and compression; if false,
+    /// frames are chunked one per chunk and scalars by
+    /// [`DEFAULT_SCALAR_CHUNK_ROWS`]
+    pub auto: bool,
+    /// Chunk size aimed for by the heuristics, before compression
+    pub target_chunk_bytes: usize,
+    /// Whether datasets are compressed
+    pub compressed: bool,
+    /// Fixed frame chunk shape (frames, rows, columns), clamped to the frame
+    pub frame_chunk: Option<[usize; 3]>,
+    /// Fixed rows per scalar chunk
+    pub scalar_chunk_rows: Option<usize>,
+}
+
+impl Default for ChunkingConfig {
+    fn default() -> Self {
+        Self {
+            auto: true,
+            target_chunk_bytes: DEFAULT_TARGET_CHUNK_BYTES,
+            compressed: false,
+            frame_chunk: None,
+            scalar_chunk_rows: None,
+        }
+    }
+}
+
+/// How a chunk shape was chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkSource {
+    /// Heuristics
+    Auto,
+    /// Configured shape
+    Manual,
+    /// Fixed defaults, with auto-chunking off
+    Fixed,
+}
+
+impl fmt::Display for ChunkSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Auto => write!(f, "auto"),
+            Self::Manual => write!(f, "manual"),
+            Self::Fixed => write!(f, "fixed"),
+        }
+    }
+}
+
+/// Chunk shape of one dataset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkLayout {
+    pub shape: Vec<usize>,
+    pub element_size: usize,
+    pub source: ChunkSource,
+}
+
+impl ChunkLayout {
+    /// Uncompressed size of one chunk
+    pub fn bytes(&self) -> usize {
+        self.shape.iter().product::<usize>() * self.element_size
+    }
+}
+
+impl ChunkingConfig {
+    /// Chunk size the heuristics aim for, given compression
+    pub fn effective_target_bytes(&self) -> usize {
+        let target = self.target_chunk_bytes.max(1);
+        if self.compressed {
+            (target / COMPRESSED_TARGET_DIVISOR).max(1)
+        } else {
+            target
+        }
+    }
+
+    /// Chunk shape of a `(n, height, width)` frame dataset
+    ///
+    /// `rate_hz` is the nominal frame rate, if known.
+    pub fn frame_chunk(
+        &self,
+        height: usize,
+        width: usize,
+        element_size: usize,
+        rate_hz: Option<f64>,
+    ) -> ChunkLayout {
+        let (height, width) = (height.max(1), width.max(1));
+        let layout = |shape: [usize; 3], source| ChunkLayout {
+            shape: shape.to_vec(),
+            element_size,
+            source,
+        };
+
+        if let Some([frames, rows, cols]) = self.frame_chunk {
+            return layout(
+                [frames.max(1), rows.clamp(1, height), cols.clamp(1, width)],
+                ChunkSource::Manual,
+            );
+        }
+        if !self.auto {
+            return layout([1, height, width], ChunkSource::Fixed);
+        }
+
+        let target = self.effective_target_bytes();
+        let frame_bytes = height * width * element_size;
+        if frame_bytes > target {
+            // Split the frame into tiles, halving the longer side first
+            let (mut rows, mut cols) = (height, width);
+            while rows * cols * element_size > target {
+                if rows >= cols && rows > MIN_TILE {
+                    rows = rows.div_ceil(2);
+                } else if cols > MIN_TILE {
+                    cols = cols.div_ceil(2);
+                } else {
+                    break;
+                }
+            }
+            return layout([1, rows, cols], ChunkSource::Auto);
+        }
+
+        let mut frames = target / frame_bytes.max(1);
+        if let Some(rate) = rate_hz.filter(|r| r.is_finite() && *r > 0.0) {
+            frames = frames.min((rate * FRAME_FILL_SECS).ceil() as usize);
+        }
+        layout(
+            [frames.clamp(1, MAX_FRAMES_PER_CHUNK), height, width],
+            ChunkSource::Auto,
+        )
+    }
+
+    /// Chunk shape of a 1-D dataset with one row per sample
+    ///
+    /// `rate_hz` is the nominal sample rate, if known.
+    pub fn scalar_chunk(&self, element_size: usize, rate_hz: Option<f64>) -> ChunkLayout {
+        let layout = |rows: usize, source| ChunkLayout {
+            shape: vec![rows],
+            element_size,
+            source,
+        };
+
+        if let Some(rows) = self.scalar_chunk_rows {
+            return layout(rows.max(1), ChunkSource::Manual);
+        }
+        if !self.auto {
+            return layout(DEFAULT_SCALAR_CHUNK_ROWS, ChunkSource::Fixed);
+        }
+        // Slow or irregular channels keep small chunks, so a short run
+        // doesn't allocate megabytes per field
+        let Some(rate) = rate_hz.filter(|r| r.is_finite() && *r > 0.0) else {
+            return layout(DEFAULT_SCALAR_CHUNK_ROWS, ChunkSource::Auto);
+        };
+        let max_rows = (self.effective_target_bytes() / element_size.max(1))
+            .clamp(MIN_SCALAR_CHUNK_ROWS, MAX_SCALAR_CHUNK_ROWS);
+        let rows =
+            ((rate * SCALAR_FILL_SECS).ceil() as usize).clamp(MIN_SCALAR_CHUNK_ROWS, max_rows);
+        layout(rows, ChunkSource::Auto)
+    }
+}
+
+/// Record the chunk layout on a dataset
+#[cfg(feature = "storage_hdf5")]
+pub fn write_chunk_attrs(dataset: &hdf5::Dataset, layout: &ChunkLayout) -> anyhow::Result<()> {
+    use hdf5::types::VarLenUnicode;
+
+    let shape = format!("{:?}", layout.shape);
+    dataset
+        .new_attr::<VarLenUnicode>()
+        .create("chunk_shape")?
+        .write_scalar(&shape.parse::<VarLenUnicode>().expect("Parse VarLenUnicode"))?;
+    dataset
+        .new_attr::<u64>()
+        .create("chunk_bytes")?
+        .write_scalar(&(layout.bytes() as u64))?;
+    dataset
+        .new_attr::<VarLenUnicode>()
+        .create("chunking")?
+        .write_scalar(
+            &layout
+                .source
+                .to_string()
+                .parse::<VarLenUnicode>()
+                .expect("Parse VarLenUnicode"),
+        )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_chunks_group_small_frames_and_tile_large_ones() {
+        let config = ChunkingConfig::default();
+
+        // 256x256 u16 = 128 KiB: 8 frames fit the 1 MiB target
+        let small = config.frame_chunk(256, 256, 2, None);
+        assert_eq!(small.shape, [8, 256, 256]);
+        assert_eq!(small.source, ChunkSource::Auto);
+        // ...but at 4 fps a chunk would take two seconds to fill
+        assert_eq!(
+            config.frame_chunk(256, 256, 2, Some(4.0)).shape,
+            [4, 256, 256]
+        );
+
+        // 2048x2048 u16 = 8 MiB: tiled down to 1 MiB
+        let large = config.frame_chunk(2048, 2048, 2, Some(30.0));
+        assert_eq!(large.shape, [1, 512, 1024]);
+        assert!(large.bytes() <= DEFAULT_TARGET_CHUNK_BYTES);
+
+        // Compressed chunks are smaller
+        let compressed = ChunkingConfig {
+            compressed: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            compressed.frame_chunk(2048, 2048, 2, None).shape,
+            [1, 256, 512]
+        );
+
+        let manual = ChunkingConfig {
+            frame_chunk: Some([2, 128, 4096]),
+            ..Default::default()
+        };
+        let layout = manual.frame_chunk(512, 640, 2, None);
+        assert_eq!(layout.shape, [2, 128, 640]);
+        assert_eq!(layout.source, ChunkSource::Manual);
+    }
+
+    #[test]
+    fn test_scalar_chunks_follow_rate() {
+        let config = ChunkingConfig::default();
+        assert_eq!(
+            config.scalar_chunk(8, None).shape,
+            [DEFAULT_SCALAR_CHUNK_ROWS]
+        );
+        assert_eq!(config.scalar_chunk(8, Some(1.0)).shape, [256]);
+        assert_eq!(config.scalar_chunk(8, Some(1000.0)).shape, [10_000]);
+        // Capped by the target size
+        assert_eq!(config.scalar_chunk(8, Some(1e6)).shape, [65_536]);
+
+        let fixed = ChunkingConfig {
+            auto: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            fixed.scalar_chunk(8, Some(1000.0)).source,
+            ChunkSource::Fixed
+        );
+        assert_eq!(fixed.frame_chunk(64, 64, 2, None).shape, [1, 64, 64]);
+    }
+}
@@ -0,0 +1,866 @@
+//! Multi-rate HDF5 Layout - Mixed-speed channels in one run file
+//!
+//! A run rarely samples everything at one rate: DAQ analog inputs at 1 kHz,
+//! a power meter at 10 Hz, camera frames whenever the camera delivers them.
+//! Forcing these into a single event rate either repeats slow values or
+//! throws fast samples away. [`MultiRateWriter`] instead puts channels into
+//! per-rate groups, each with its own extendable timestamp and channel
+//! datasets.
+//!
+//! # Layout
+//!
+//! ```text
+//! /rates                        t0_ns, time_base, clock, groups (alignment)
+//! /rates/1kHz/timestamps_ns     u64 (n)
+//! /rates/1kHz/sample_index      u64 (n)
+//! /rates/1kHz/AI0               f64 (n)
+//! /rates/1kHz/AI1               f64 (n)
+//! /rates/10Hz/timestamps_ns     u64 (m)
+//! /rates/10Hz/power             f64 (m)
+//! /rates/camera/timestamps_ns   u64 (k)
+//! /rates/camera/camera          u16 (k, height, width)
+//! ```
+//!
+//! Channels in a group are sampled together: one timestamp per row. Channels
+//! with the same rate from unsynchronized sources should be put into
+//! separate groups with [`RateChannel::in_group`]. Frame channels always get
+//! a group of their own, since every camera runs on its own clock.
+//!
+//! # Alignment
+//!
+//! All timestamps are UNIX nanoseconds from the same wall clock. The `/rates`
+//! group records `t0_ns` (the run start), and every rate group records its
+//! `nominal_rate_hz` (0 for irregular groups) and, once the run is finished,
+//! `samples`, `first_timestamp_ns`, `last_timestamp_ns` and
+//! `measured_rate_hz`. To resample coherently, readers convert each group's
+//! `(timestamps_ns - t0_ns) / 1e9` to seconds and interpolate onto a common
+//! time axis, rather than relying on row indices.
+//!
+//! # Chunking
+//!
+//! Dataset chunk shapes come from a [`ChunkingConfig`] and each group's
+//! nominal rate (see [`crate::hdf5_chunking`]); every dataset records its
+//! chunk shape in the `chunk_shape` attribute.
+
+use crate::hdf5_chunking::ChunkingConfig;
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+
+/// Root group of the layout
+pub const RATES_GROUP: &str = "rates";
+
+/// Rows buffered per group before they are written
+const DEFAULT_CHUNK_ROWS: usize = 4096;
+
+/// What a channel stores per sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChannelShape {
+    /// One f64 per sample
+    Scalar,
+    /// One u16 frame per sample
+    Frame { height: usize, width: usize },
+}
+
+/// A channel to be recorded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateChannel {
+    pub name: String,
+    /// Nominal sample rate; None for irregular (event-driven) channels
+    pub rate_hz: Option<f64>,
+    pub shape: ChannelShape,
+    pub units: String,
+    /// Explicit group; by default the group is derived from the rate
+    pub group: Option<String>,
+}
+
+impl RateChannel {
+    /// Scalar channel sampled at `rate_hz`
+    pub fn scalar(name: impl Into<String>, rate_hz: Option<f64>) -> Self {
+        Self {
+            name: name.into(),
+            rate_hz,
+            shape: ChannelShape::Scalar,
+            units: String::new(),
+            group: None,
+        }
+    }
+
+    /// Frame channel (camera) of `height` x `width` pixels
+    pub fn frame(
+        name: impl Into<String>,
+        rate_hz: Option<f64>,
+        height: usize,
+        width: usize,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            rate_hz,
+            shape: ChannelShape::Frame { height, width },
+            units: String::new(),
+            group: None,
+        }
+    }
+
+    /// Set units
+    pub fn with_units(mut self, units: impl Into<String>) -> Self {
+        self.units = units.into();
+        self
+    }
+
+    /// Put the channel into a named group instead of the one for its rate
+    pub fn in_group(mut self, group: impl Into<String>) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+}
+
+/// Channels sharing one timestamp dataset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateGroup {
+    pub name: String,
+    pub nominal_rate_hz: Option<f64>,
+    pub channels: Vec<RateChannel>,
+}
+
+impl RateGroup {
+    /// Frame groups hold a single frame channel
+    pub fn is_frame_group(&self) -> bool {
+        matches!(
+            self.channels.first().map(|c| c.shape),
+            Some(ChannelShape::Frame { .. })
+        )
+    }
+}
+
+/// Default group name for a rate: `1kHz`, `10Hz`, `500mHz` or `irregular`
+pub fn rate_group_name(rate_hz: Option<f64>) -> String {
+    let Some(rate) = rate_hz else {
+        return "irregular".to_string();
+    };
+    let (value, unit) = if rate >= 1e6 {
+        (rate / 1e6, "MHz")
+    } else if rate >= 1e3 {
+        (rate / 1e3, "kHz")
+    } else if rate >= 1.0 {
+        (rate, "Hz")
+    } else {
+        (rate * 1e3, "mHz")
+    };
+    // HDF5 names can't contain '.', so 2.5 kHz becomes 2p5kHz
+    let value = format!("{}", (value * 1000.0).round() / 1000.0).replace('.', "p");
+    format!("{}{}", value, unit)
+}
+
+/// Channels organized into rate groups
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultiRateLayout {
+    pub groups: Vec<RateGroup>,
+}
+
+impl MultiRateLayout {
+    /// Group `channels` by rate (or explicit group), in order of appearance
+    pub fn plan(channels: Vec<RateChannel>) -> Result<Self> {
+        let mut groups: Vec<RateGroup> = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        for channel in channels {
+            if channel.name.is_empty() || channel.name.contains('/') {
+                bail!("Invalid channel name '{}'", channel.name);
+            }
+            if matches!(channel.name.as_str(), "timestamps_ns" | "sample_index") {
+                bail!("Channel name '{}' is reserved", channel.name);
+            }
+            if !seen.insert(channel.name.clone()) {
+                bail!("Duplicate channel '{}'", channel.name);
+            }
+            if let Some(rate) = channel.rate_hz {
+                if !(rate.is_finite() && rate > 0.0) {
+                    bail!("Channel '{}': invalid rate {}", channel.name, rate);
+                }
+            }
+            let is_frame = matches!(channel.shape, ChannelShape::Frame { .. });
+            if let ChannelShape::Frame { height, width } = channel.shape {
+                if height == 0 || width == 0 {
+                    bail!("Channel '{}': empty frame shape", channel.name);
+                }
+            }
+            let name = match (&channel.group, is_frame) {
+                (Some(group), _) => group.clone(),
+                (None, true) => channel.name.clone(),
+                (None, false) => rate_group_name(channel.rate_hz),
+            };
+
+            match groups.iter_mut().find(|g| g.name == name) {
+                Some(group) => {
+                    if is_frame || group.is_frame_group() {
+                        bail!(
+                            "Channel '{}': frame channels need a group of their own ('{}')",
+                            channel.name,
+                            name
+                        );
+                    }
+                    if group.nominal_rate_hz != channel.rate_hz {
+                        bail!(
+                            "Channel '{}' ({:?} Hz) does not match the rate of group '{}' ({:?} Hz)",
+                            channel.name,
+                            channel.rate_hz,
+                            name,
+                            group.nominal_rate_hz
+                        );
+                    }
+                    group.channels.push(channel);
+                }
+                None => groups.push(RateGroup {
+                    name,
+                    nominal_rate_hz: channel.rate_hz,
+                    channels: vec![channel],
+                }),
+            }
+        }
+        if groups.is_empty() {
+            bail!("At least one channel must be configured");
+        }
+        Ok(Self { groups })
+    }
+
+    /// Group by name
+    pub fn group(&self, name: &str) -> Option<&RateGroup> {
+        self.groups.iter().find(|g| g.name == name)
+    }
+
+    /// Group a channel belongs to
+    pub fn group_of(&self, channel: &str) -> Option<&RateGroup> {
+        self.groups
+            .iter()
+            .find(|g| g.channels.iter().any(|c| c.name == channel))
+    }
+}
+
+/// Per-group alignment metadata, written when the run is finished
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupSummary {
+    pub name: String,
+    pub nominal_rate_hz: Option<f64>,
+    pub samples: u64,
+    pub first_timestamp_ns: Option<u64>,
+    pub last_timestamp_ns: Option<u64>,
+}
+
+impl GroupSummary {
+    /// Average rate over the recorded span (None with fewer than 2 samples)
+    pub fn measured_rate_hz(&self) -> Option<f64> {
+        let span_ns = self.last_timestamp_ns? - self.first_timestamp_ns?;
+        (self.samples > 1 && span_ns > 0)
+            .then(|| (self.samples - 1) as f64 / (span_ns as f64 / 1e9))
+    }
+}
+
+/// Rows of one group waiting to be written
+#[derive(Debug, Default)]
+struct GroupBuffer {
+    timestamps: Vec<u64>,
+    /// Scalar groups: one column per channel
+    columns: Vec<Vec<f64>>,
+    /// Frame groups: pixels of all buffered frames
+    pixels: Vec<u16>,
+    /// Rows already written
+    written: u64,
+    first_timestamp_ns: Option<u64>,
+    last_timestamp_ns: Option<u64>,
+}
+
+impl GroupBuffer {
+    fn new(group: &RateGroup) -> Self {
+        Self {
+            columns: if group.is_frame_group() {
+                Vec::new()
+            } else {
+                vec![Vec::new(); group.channels.len()]
+            },
+            ..Self::default()
+        }
+    }
+
+    /// Timestamps must not go backwards within a group
+    fn push_timestamps(&mut self, group: &str, timestamps_ns: &[u64]) -> Result<()> {
+        let mut last = self.last_timestamp_ns;
+        for &ts in timestamps_ns {
+            if last.is_some_and(|last| ts < last) {
+                bail!(
+                    "Group '{}': timestamp {} is before the previous sample ({})",
+                    group,
+                    ts,
+                    last.unwrap_or_default()
+                );
+            }
+            last = Some(ts);
+        }
+        if let Some(&first) = timestamps_ns.first() {
+            self.first_timestamp_ns.get_or_insert(first);
+        }
+        self.last_timestamp_ns = last;
+        self.timestamps.extend_from_slice(timestamps_ns);
+        Ok(())
+    }
+
+    fn summary(&self, group: &RateGroup) -> GroupSummary {
+        GroupSummary {
+            name: group.name.clone(),
+            nominal_rate_hz: group.nominal_rate_hz,
+            samples: self.written + self.timestamps.len() as u64,
+            first_timestamp_ns: self.first_timestamp_ns,
+            last_timestamp_ns: self.last_timestamp_ns,
+        }
+    }
+}
+
+/// Rows taken out of a [`GroupBuffer`] for writing
+#[allow(dead_code)] // Read with storage_hdf5 feature
+struct Chunk {
+    group: RateGroup,
+    start_row: u64,
+    timestamps: Vec<u64>,
+    columns: Vec<Vec<f64>>,
+    pixels: Vec<u16>,
+}
+
+/// Writes channels of different rates into per-rate groups
+///
+/// # Example
+///
+/// ```no_run
+/// use daq_storage::hdf5_multirate::{MultiRateLayout, MultiRateWriter, RateChannel};
+/// use std::path::Path;
+///
+/// # async fn example(t0_ns: u64) -> anyhow::Result<()> {
+/// let layout = MultiRateLayout::plan(vec![
+///     RateChannel::scalar("AI0", Some(1000.0)).with_units("V"),
+///     RateChannel::scalar("AI1", Some(1000.0)).with_units("V"),
+///     RateChannel::scalar("power", Some(10.0)).with_units("W"),
+///     RateChannel::frame("camera", None, 512, 512),
+/// ])?;
+/// let writer = MultiRateWriter::create(Path::new("run.h5"), layout, t0_ns).await?;
+///
+/// writer.append("1kHz", &[t0_ns, t0_ns + 1_000_000], &[&[0.1, 0.2], &[1.5, 1.6]]).await?;
+/// writer.append("10Hz", &[t0_ns], &[&[0.42]]).await?;
+/// writer.append_frame("camera", t0_ns, &vec![0u16; 512 * 512]).await?;
+///
+/// writer.finish().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct MultiRateWriter {
+    #[allow(dead_code)] // Used with storage_hdf5 feature
+    output_path: PathBuf,
+    layout: MultiRateLayout,
+    chunk_rows: usize,
+    buffers: Mutex<HashMap<String, GroupBuffer>>,
+}
+
+impl MultiRateWriter {
+    /// Create the file with the layout's groups and datasets
+    ///
+    /// `t0_ns` is the common time origin recorded for alignment (usually
+    /// the run start).
+    pub async fn create(output_path: &Path, layout: MultiRateLayout, t0_ns: u64) -> Result<Self> {
+        Self::create_with_chunking(output_path, layout, t0_ns, ChunkingConfig::default()).await
+    }
+
+    /// Create the file, choosing dataset chunk shapes with `chunking`
+    pub async fn create_with_chunking(
+        output_path: &Path,
+        layout: MultiRateLayout,
+        t0_ns: u64,
+        chunking: ChunkingConfig,
+    ) -> Result<Self> {
+        let path = output_path.to_path_buf();
+        let file_layout = layout.clone();
+        tokio::task::spawn_blocking(move || create_file(&path, &file_layout, t0_ns, &chunking))
+            .await??;
+
+        let buffers = layout
+            .groups
+            .iter()
+            .map(|group| (group.name.clone(), GroupBuffer::new(group)))
+            .collect();
+        Ok(Self {
+            output_path: output_path.to_path_buf(),
+            layout,
+            chunk_rows: DEFAULT_CHUNK_ROWS,
+            buffers: Mutex::new(buffers),
+        })
+    }
+
+    /// Write scalar groups every `rows` rows instead of every 4096
+    pub fn with_chunk_rows(mut self, rows: usize) -> Self {
+        self.chunk_rows = rows.max(1);
+        self
+    }
+
+    /// Groups and channels of this file
+    pub fn layout(&self) -> &MultiRateLayout {
+        &self.layout
+    }
+
+    /// Append rows to a scalar group
+    ///
+    /// `columns` holds one slice per channel, in layout order, each as long
+    /// as `timestamps_ns`.
+    pub async fn append(
+        &self,
+        group: &str,
+        timestamps_ns: &[u64],
+        columns: &[&[f64]],
+    ) -> Result<()> {
+        let rate_group = self
+            .layout
+            .group(group)
+            .ok_or_else(|| anyhow!("Unknown rate group '{}'", group))?;
+        if rate_group.is_frame_group() {
+            bail!("Group '{}' holds frames; use append_frame", group);
+        }
+        if columns.len() != rate_group.channels.len() {
+            bail!(
+                "Group '{}' has {} channels, got {} columns",
+                group,
+                rate_group.channels.len(),
+                columns.len()
+            );
+        }
+        if let Some(column) = columns.iter().find(|c| c.len() != timestamps_ns.len()) {
+            bail!(
+                "Group '{}': column length {} does not match {} timestamps",
+                group,
+                column.len(),
+                timestamps_ns.len()
+            );
+        }
+
+        let mut buffers = self.buffers.lock().await;
+        let buffer = buffers.get_mut(group).expect("buffer for every group");
+        buffer.push_timestamps(group, timestamps_ns)?;
+        for (column, values) in buffer.columns.iter_mut().zip(columns) {
+            column.extend_from_slice(values);
+        }
+        if buffer.timestamps.len() >= self.chunk_rows {
+            let chunk = take_chunk(rate_group, buffer);
+            self.write_chunk(chunk).await?;
+        }
+        Ok(())
+    }
+
+    /// Append one frame to a frame channel's group
+    pub async fn append_frame(
+        &self,
+        channel: &str,
+        timestamp_ns: u64,
+        pixels: &[u16],
+    ) -> Result<()> {
+        let group = self
+            .layout
+            .group_of(channel)
+            .filter(|g| g.is_frame_group())
+            .ok_or_else(|| anyhow!("Unknown frame channel '{}'", channel))?;
+        if let ChannelShape::Frame { height, width } = group.channels[0].shape {
+            if pixels.len() != height * width {
+                bail!(
+                    "Frame for '{}' has {} pixels, expected {}x{}",
+                    channel,
+                    pixels.len(),
+                    height,
+                    width
+                );
+            }
+        }
+
+        // Frames are large; write each one right away
+        let mut buffers = self.buffers.lock().await;
+        let buffer = buffers
+            .get_mut(&group.name)
+            .expect("buffer for every group");
+        buffer.push_timestamps(&group.name, &[timestamp_ns])?;
+        buffer.pixels.extend_from_slice(pixels);
+        let chunk = take_chunk(group, buffer);
+        self.write_chunk(chunk).await
+    }
+
+    /// Write all buffered rows
+    pub async fn flush(&self) -> Result<()> {
+        // Chunks are written with the buffers locked, so rows of a group
+        // always reach the file in order
+        let mut buffers = self.buffers.lock().await;
+        for group in &self.layout.groups {
+            let Some(buffer) = buffers.get_mut(&group.name) else {
+                continue;
+            };
+            if !buffer.timestamps.is_empty() {
+                let chunk = take_chunk(group, buffer);
+                self.write_chunk(chunk).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Flush and record the per-group alignment summary
+    pub async fn finish(self) -> Result<Vec<GroupSummary>> {
+        self.flush().await?;
+        let summaries: Vec<GroupSummary> = {
+            let buffers = self.buffers.lock().await;
+            self.layout
+                .groups
+                .iter()
+                .map(|group| buffers[&group.name].summary(group))
+                .collect()
+        };
+        let path = self.output_path.clone();
+        let file_summaries = summaries.clone();
+        tokio::task::spawn_blocking(move || write_summaries(&path, &file_summaries)).await??;
+        Ok(summaries)
+    }
+
+    async fn write_chunk(&self, chunk: Chunk) -> Result<()> {
+        let path = self.output_path.clone();
+        tokio::task::spawn_blocking(move || write_chunk(&path, &chunk)).await?
+    }
+}
+
+fn take_chunk(group: &RateGroup, buffer: &mut GroupBuffer) -> Chunk {
+    let rows = buffer.timestamps.len() as u64;
+    let chunk = Chunk {
+        group: group.clone(),
+        start_row: buffer.written,
+        timestamps: std::mem::take(&mut buffer.timestamps),
+        columns: buffer.columns.iter_mut().map(std::mem::take).collect(),
+        pixels: std::mem::take(&mut buffer.pixels),
+    };
+    buffer.written += rows;
+    chunk
+}
+
+#[cfg(feature = "storage_hdf5")]
+fn write_str_attr(location: &hdf5::Location, name: &str, value: &str) -> Result<()> {
+    use hdf5::types::VarLenUnicode;
+    location
+        .new_attr::<VarLenUnicode>()
+        .create(name)?
+        .write_scalar(&value.parse::<VarLenUnicode>().expect("Parse VarLenUnicode"))?;
+    Ok(())
+}
+
+#[cfg(feature = "storage_hdf5")]
+fn create_file(
+    path: &Path,
+    layout: &MultiRateLayout,
+    t0_ns: u64,
+    chunking: &ChunkingConfig,
+) -> Result<()> {
+    use crate::hdf5_chunking::write_chunk_attrs;
+    use hdf5::File;
+
+    let file = if path.exists() {
+        File::open_rw(path)?
+    } else {
+        File::create(path)?
+    };
+    let rates = file.create_group(RATES_GROUP)?;
+    rates
+        .new_attr::<u64>()
+        .create("t0_ns")?
+        .write_scalar(&t0_ns)?;
+    write_str_attr(&rates, "time_base", "unix_ns")?;
+    write_str_attr(&rates, "clock", "wall_clock")?;
+    write_str_attr(&rates, "layout", &serde_json::to_string(layout)?)?;
+
+    for group in &layout.groups {
+        let g = rates.create_group(&group.name)?;
+        g.new_attr::<f64>()
+            .create("nominal_rate_hz")?
+            .write_scalar(&group.nominal_rate_hz.unwrap_or(0.0))?;
+        let channel_names: Vec<&str> = group.channels.iter().map(|c| c.name.as_str()).collect();
+        write_str_attr(&g, "channels", &serde_json::to_string(&channel_names)?)?;
+
+        let rows = chunking.scalar_chunk(8, group.nominal_rate_hz);
+        for name in ["timestamps_ns", "sample_index"] {
+            let ds = g
+                .new_dataset::<u64>()
+                .chunk(rows.shape[0])
+                .shape(0..)
+                .create(name)?;
+            write_chunk_attrs(&ds, &rows)?;
+        }
+
+        for channel in &group.channels {
+            let ds = match channel.shape {
+                ChannelShape::Scalar => {
+                    let ds = g
+                        .new_dataset::<f64>()
+                        .chunk(rows.shape[0])
+                        .shape(0..)
+                        .create(channel.name.as_str())?;
+                    write_chunk_attrs(&ds, &rows)?;
+                    ds
+                }
+                ChannelShape::Frame { height, width } => {
+                    let layout = chunking.frame_chunk(height, width, 2, group.nominal_rate_hz);
+                    let ds = g
+                        .new_dataset::<u16>()
+                        .chunk((layout.shape[0], layout.shape[1], layout.shape[2]))
+                        .shape((0.., height, width))
+                        .create(channel.name.as_str())?;
+                    write_chunk_attrs(&ds, &layout)?;
+                    ds
+                }
+            };
+            write_str_attr(&ds, "units", &channel.units)?;
+            write_str_attr(&ds, "rate_group", &group.name)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "storage_hdf5")]
+fn write_chunk(path: &Path, chunk: &Chunk) -> Result<()> {
+    use hdf5::File;
+
+    let rows = chunk.timestamps.len();
+    if rows == 0 {
+        return Ok(());
+    }
+    let start = chunk.start_row as usize;
+    let end = start + rows;
+
+    let file = File::open_rw(path)?;
+    let g = file.group(&format!("{}/{}", RATES_GROUP, chunk.group.name))?;
+
+    let ts = g.dataset("timestamps_ns")?;
+    ts.resize((end,))?;
+    ts.write_slice(&chunk.timestamps, start..end)?;
+
+    let index: Vec<u64> = (chunk.start_row..chunk.start_row + rows as u64).collect();
+    let idx = g.dataset("sample_index")?;
+    idx.resize((end,))?;
+    idx.write_slice(&index, start..end)?;
+
+    for (channel, values) in chunk.group.channels.iter().zip(&chunk.columns) {
+        let ds = g.dataset(&channel.name)?;
+        ds.resize((end,))?;
+        ds.write_slice(values, start..end)?;
+    }
+    if let Some(channel) = chunk.group.channels.first() {
+        if let ChannelShape::Frame { height, width } = channel.shape {
+            let ds = g.dataset(&channel.name)?;
+            ds.resize((end, height, width))?;
+            ds.write_slice(&chunk.pixels, (start..end, .., ..))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "storage_hdf5")]
+fn write_summaries(path: &Path, summaries: &[GroupSummary]) -> Result<()> {
+    use hdf5::File;
+
+    let file = File::open_rw(path)?;
+    for summary in summaries {
+        let g = file.group(&format!("{}/{}", RATES_GROUP, summary.name))?;
+        g.new_attr::<u64>()
+            .create("samples")?
+            .write_scalar(&summary.samples)?;
+        if let (Some(first), Some(last)) = (summary.first_timestamp_ns, summary.last_timestamp_ns) {
+            g.new_attr::<u64>()
+                .create("first_timestamp_ns")?
+                .write_scalar(&first)?;
+            g.new_attr::<u64>()
+                .create("last_timestamp_ns")?
+                .write_scalar(&last)?;
+        }
+        if let Some(rate) = summary.measured_rate_hz() {
+            g.new_attr::<f64>()
+                .create("measured_rate_hz")?
+                .write_scalar(&rate)?;
+        }
+    }
+    Ok(())
+}
+
+// Mock implementations for non-HDF5 builds
+#[cfg(not(feature = "storage_hdf5"))]
+fn create_file(
+    _path: &Path,
+    _layout: &MultiRateLayout,
+    _t0_ns: u64,
+    _chunking: &ChunkingConfig,
+) -> Result<()> {
+    bail!("HDF5 storage feature not enabled")
+}
+
+#[cfg(not(feature = "storage_hdf5"))]
+fn write_chunk(_path: &Path, _chunk: &Chunk) -> Result<()> {
+    bail!("HDF5 storage feature not enabled")
+}
+
+#[cfg(not(feature = "storage_hdf5"))]
+fn write_summaries(_path: &Path, _summaries: &[GroupSummary]) -> Result<()> {
+    bail!("HDF5 storage feature not enabled")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lab_channels() -> Vec<RateChannel> {
+        vec![
+            RateChannel::scalar("AI0", Some(1000.0)).with_units("V"),
+            RateChannel::scalar("power", Some(10.0)).with_units("W"),
+            RateChannel::scalar("AI1", Some(1000.0)).with_units("V"),
+            RateChannel::frame("camera", None, 4, 3),
+        ]
+    }
+
+    #[test]
+    fn test_rate_group_names() {
+        assert_eq!(rate_group_name(Some(1000.0)), "1kHz");
+        assert_eq!(rate_group_name(Some(2500.0)), "2p5kHz");
+        assert_eq!(rate_group_name(Some(10.0)), "10Hz");
+        assert_eq!(rate_group_name(Some(0.5)), "500mHz");
+        assert_eq!(rate_group_name(Some(2e6)), "2MHz");
+        assert_eq!(rate_group_name(None), "irregular");
+    }
+
+    #[test]
+    fn test_plan_groups_by_rate() {
+        let layout = MultiRateLayout::plan(lab_channels()).unwrap();
+        let names: Vec<&str> = layout.groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, ["1kHz", "10Hz", "camera"]);
+
+        let fast = layout.group("1kHz").unwrap();
+        assert_eq!(fast.nominal_rate_hz, Some(1000.0));
+        let fast_channels: Vec<&str> = fast.channels.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(fast_channels, ["AI0", "AI1"]);
+        assert!(layout.group("camera").unwrap().is_frame_group());
+        assert_eq!(layout.group_of("power").unwrap().name, "10Hz");
+    }
+
+    #[test]
+    fn test_plan_rejects_inconsistent_channels() {
+        let duplicate = vec![
+            RateChannel::scalar("AI0", Some(1000.0)),
+            RateChannel::scalar("AI0", Some(10.0)),
+        ];
+        assert!(MultiRateLayout::plan(duplicate).is_err());
+
+        let mixed_rates = vec![
+            RateChannel::scalar("a", Some(1000.0)).in_group("slow"),
+            RateChannel::scalar("b", Some(10.0)).in_group("slow"),
+        ];
+        assert!(MultiRateLayout::plan(mixed_rates).is_err());
+
+        let shared_frame_group = vec![
+            RateChannel::frame("cam", Some(10.0), 2, 2).in_group("10Hz"),
+            RateChannel::scalar("power", Some(10.0)),
+        ];
+        assert!(MultiRateLayout::plan(shared_frame_group).is_err());
+
+        assert!(MultiRateLayout::plan(vec![RateChannel::scalar("a", Some(0.0))]).is_err());
+        assert!(MultiRateLayout::plan(vec![RateChannel::scalar("timestamps_ns", None)]).is_err());
+        assert!(MultiRateLayout::plan(vec![]).is_err());
+    }
+
+    #[test]
+    fn test_group_buffer_tracks_alignment() {
+        let layout = MultiRateLayout::plan(lab_channels()).unwrap();
+        let group = layout.group("1kHz").unwrap();
+        let mut buffer = GroupBuffer::new(group);
+
+        buffer
+            .push_timestamps("1kHz", &[1_000_000_000, 1_001_000_000])
+            .unwrap();
+        let chunk = take_chunk(group, &mut buffer);
+        assert_eq!(chunk.start_row, 0);
+        assert_eq!(chunk.columns.len(), 2);
+        buffer.push_timestamps("1kHz", &[1_002_000_000]).unwrap();
+        assert!(buffer.push_timestamps("1kHz", &[1_001_500_000]).is_err());
+
+        let summary = buffer.summary(group);
+        assert_eq!(summary.samples, 3);
+        assert_eq!(summary.first_timestamp_ns, Some(1_000_000_000));
+        assert_eq!(summary.last_timestamp_ns, Some(1_002_000_000));
+        let rate = summary.measured_rate_hz().unwrap();
+        assert!((rate - 1000.0).abs() < 1e-6);
+    }
+
+    #[cfg(feature = "storage_hdf5")]
+    #[tokio::test]
+    async fn test_multirate_writer_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.h5");
+        let t0 = 1_000_000_000u64;
+        let layout = MultiRateLayout::plan(lab_channels()).unwrap();
+        let writer = MultiRateWriter::create(&path, layout, t0)
+            .await
+            .unwrap()
+            .with_chunk_rows(2);
+
+        let fast_ts: Vec<u64> = (0..5).map(|i| t0 + i * 1_000_000).collect();
+        let ai0: Vec<f64> = (0..5).map(f64::from).collect();
+        writer
+            .append("1kHz", &fast_ts, &[&ai0, &ai0])
+            .await
+            .unwrap();
+        writer.append("10Hz", &[t0], &[&[0.42]]).await.unwrap();
+        writer
+            .append_frame("camera", t0 + 5, &[7u16; 12])
+            .await
+            .unwrap();
+        assert!(writer
+            .append("10Hz", &[t0], &[&[1.0], &[2.0]])
+            .await
+            .is_err());
+
+        let summaries = writer.finish().await.unwrap();
+        assert_eq!(summaries[0].samples, 5);
+
+        let file = hdf5::File::open(&path).unwrap();
+        let rates = file.group(RATES_GROUP).unwrap();
+        assert_eq!(
+            rates.attr("t0_ns").unwrap().read_scalar::<u64>().unwrap(),
+            t0
+        );
+        let fast = file
+            .dataset("rates/1kHz/AI1")
+            .unwrap()
+            .read_raw::<f64>()
+            .unwrap();
+        assert_eq!(fast, ai0);
+        let ts = file
+            .dataset("rates/1kHz/timestamps_ns")
+            .unwrap()
+            .read_raw::<u64>()
+            .unwrap();
+        assert_eq!(ts, fast_ts);
+        assert_eq!(file.dataset("rates/10Hz/power").unwrap().shape(), [1]);
+        let camera = file.dataset("rates/camera/camera").unwrap();
+        assert_eq!(camera.shape(), [1, 4, 3]);
+        assert_eq!(camera.chunk(), Some(vec![64, 4, 3]));
+        assert_eq!(
+            camera
+                .attr("chunking")
+                .unwrap()
+                .read_scalar::<hdf5::types::VarLenUnicode>()
+                .unwrap()
+                .as_str(),
+            "auto"
+        );
+        let measured = file
+            .group("rates/1kHz")
+            .unwrap()
+            .attr("measured_rate_hz")
+            .unwrap()
+            .read_scalar::<f64>()
+            .unwrap();
+        assert!((measured - 1000.0).abs() < 1e-6);
+    }
+}
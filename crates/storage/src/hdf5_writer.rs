@@ -6,18 +6,24 @@
 //!
 //! Scientists never see Protobuf - they only see f64/Vec<f64> and HDF5 files.
 //! The background writer translates Protobuf → HDF5 at 1 Hz without blocking
-//! the hardware loop.
+//! the hardware loop, flushing faster while the ring buffer fills up and
+//! less often while it is idle (see [`crate::flush_policy`]).
 
 use anyhow::Result;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::time::{interval, Duration};
+use tokio::time::{Duration, Instant};
 
+use super::flush_policy::{FlushMonitor, FlushPolicy, FlushPolicyConfig};
 use super::ring_buffer::RingBuffer;
 #[cfg(feature = "storage_hdf5")]
+use common::fault_injection::{points, FaultInjector};
 use common::observable::ParameterSet;
 
+/// Name of the ring buffer writer in the [`FlushMonitor`]
+pub const FLUSH_MONITOR_NAME: &str = "hdf5_ring_buffer";
+
 /// Background HDF5 writer that persists ring buffer data
 ///
 /// # Architecture
@@ -35,6 +41,7 @@ use common::observable::ParameterSet;
 /// - **Party in front**: Fast Arrow writes, scientists see f64/Vec<f64>
 /// - **Business in back**: HDF5 files for compatibility
 /// - **Never blocking**: Async background task, 1 second flush interval
+///   adapted to ring buffer pressure
 ///
 /// # Example
 ///
@@ -50,7 +57,9 @@ use common::observable::ParameterSet;
 ///     let writer = HDF5Writer::new(Path::new("data.h5"), ring.clone())?;
 ///
 ///     tokio::spawn(async move {
-///         writer.run().await;
+///         if let Err(e) = writer.run().await {
+///             eprintln!("HDF5 writer stopped: {}", e);
+///         }
 ///     });
 ///
 ///     // Hardware loop continues without blocking...
@@ -60,7 +69,7 @@ use common::observable::ParameterSet;
 pub struct HDF5Writer {
     output_path: PathBuf,
     ring_buffer: Arc<RingBuffer>,
-    flush_interval: Duration,
+    flush_policy: FlushPolicyConfig,
     last_read_tail: AtomicU64,
     batch_counter: AtomicU64,
 }
@@ -79,7 +88,7 @@ impl HDF5Writer {
         Ok(Self {
             output_path: output_path.to_path_buf(),
             ring_buffer,
-            flush_interval: Duration::from_secs(1),
+            flush_policy: FlushPolicyConfig::default(),
             last_read_tail: AtomicU64::new(0),
             batch_counter: AtomicU64::new(0),
         })
@@ -87,25 +96,41 @@ impl HDF5Writer {
 
     /// Run background writer loop
     ///
-    /// This never returns - it runs continuously until the task is cancelled.
-    /// Flushes data every `flush_interval` (default 1 second).
-    pub async fn run(self) {
-        self.run_loop().await;
+    /// This runs continuously until the task is cancelled, and only returns
+    /// when a fault is injected at [`points::STORAGE_WRITER`] (flush errors
+    /// are reported and retried on the next tick). Flushes data every
+    /// `flush_interval` (default 1 second), adjusted by the flush policy.
+    pub async fn run(self) -> Result<()> {
+        self.run_loop().await
     }
 
     /// Run the writer loop using a shared reference.
-    pub async fn run_shared(self: Arc<Self>) {
-        self.run_loop().await;
+    ///
+    /// The read position lives in the writer, so running it again after a
+    /// failure resumes after the last flushed record.
+    pub async fn run_shared(self: Arc<Self>) -> Result<()> {
+        self.run_loop().await
     }
 
-    /// Flush interval accessor.
+    /// Flush interval accessor (the interval between the water marks).
     pub fn flush_interval(&self) -> Duration {
-        self.flush_interval
+        self.flush_policy.interval()
     }
 
-    /// Override the flush interval (primarily for tests and recording control).
+    /// Flush at a fixed interval, ignoring ring buffer pressure (primarily
+    /// for tests and recording control).
     pub fn set_flush_interval(&mut self, interval: Duration) {
-        self.flush_interval = interval;
+        self.flush_policy = FlushPolicyConfig::fixed(interval);
+    }
+
+    /// Flush policy accessor.
+    pub fn flush_policy(&self) -> &FlushPolicyConfig {
+        &self.flush_policy
+    }
+
+    /// Replace the flush policy.
+    pub fn set_flush_policy(&mut self, policy: FlushPolicyConfig) {
+        self.flush_policy = policy;
     }
 
     /// Inject a snapshot of all parameters into the HDF5 file as attributes.
@@ -191,13 +216,31 @@ impl HDF5Writer {
         Ok(())
     }
 
-    async fn run_loop(&self) {
-        let mut ticker = interval(self.flush_interval);
+    async fn run_loop(&self) -> Result<()> {
+        let mut policy = FlushPolicy::new(self.flush_policy);
         loop {
-            ticker.tick().await;
-            if let Err(e) = self.flush_to_disk().await {
-                eprintln!("HDF5 flush error: {}", e);
+            // Sleep until the interval is up, or until a burst crosses the
+            // high water mark
+            let deadline = Instant::now() + policy.interval();
+            loop {
+                let now = Instant::now();
+                if now >= deadline || policy.wake_early(self.ring_buffer.fill_ratio()) {
+                    break;
+                }
+                tokio::time::sleep((deadline - now).min(policy.poll_interval())).await;
             }
+
+            FaultInjector::global().check(points::STORAGE_WRITER)?;
+            let fill = self.ring_buffer.fill_ratio();
+            let written = match self.flush_to_disk().await {
+                Ok(written) => written,
+                Err(e) => {
+                    eprintln!("HDF5 flush error: {}", e);
+                    0
+                }
+            };
+            let decision = policy.next(fill);
+            FlushMonitor::global().record(FLUSH_MONITOR_NAME, &decision, written);
         }
     }
 
@@ -741,6 +784,7 @@ mod tests {
         reason = "TempDir used conditionally based on test configuration"
     )]
     use tempfile::{NamedTempFile, TempDir};
+    use tokio::time::interval;
 
     #[tokio::test]
     async fn test_hdf5_writer_create() {
@@ -752,6 +796,22 @@ mod tests {
 
         assert_eq!(writer.batch_count(), 0);
         assert_eq!(writer.flush_interval(), Duration::from_secs(1));
+        assert!(writer.flush_policy().adaptive);
+    }
+
+    #[tokio::test]
+    async fn test_ring_fill_drives_flush_pressure() {
+        let ring_temp = NamedTempFile::new().unwrap();
+        let ring = RingBuffer::create(ring_temp.path(), 1).unwrap();
+        assert!(ring.fill_ratio() <= 0.0);
+
+        // Over half the buffer unconsumed: flush right away
+        ring.write(&vec![0u8; 600 * 1024]).unwrap();
+        let policy = FlushPolicy::new(FlushPolicyConfig::default());
+        assert!(policy.wake_early(ring.fill_ratio()));
+
+        ring.advance_tail(ring.write_head());
+        assert!(!policy.wake_early(ring.fill_ratio()));
     }
 
     #[tokio::test]
@@ -7,6 +7,13 @@
 //! - **[`RingBuffer`]** - Memory-mapped circular buffers for high-speed streaming
 //! - **[`HDF5Writer`]** - HDF5 file output with compression
 //! - **[`DocumentWriter`]** - Bluesky document persistence
+//! - **[`FlushPolicy`]** - Flush intervals adapted to ring buffer pressure
+//! - **[`MultiRateWriter`]** - Mixed-speed channels in per-rate HDF5 groups
+//! - **[`ChunkingConfig`]** - HDF5 chunk shapes chosen from frame size, rate and compression
+//! - **[`ChannelHistory`]** - Rolling on-disk history of every scalar channel
+//! - **[`SpillBuffer`]** - Disk spillover for storage-bound data when the writer falls behind
+//! - **Provenance** - Signing completed run files and verifying them
+//! - **[`DataFile`]** - Listing, slicing and reading metadata of written files
 //! - **Cross-Process Access** - Python and Julia can read ring buffers via mmap
 //!
 //! ## Quick Example
@@ -36,36 +43,72 @@
 //! [`RingBuffer`]: ring_buffer::RingBuffer
 //! [`HDF5Writer`]: hdf5_writer::HDF5Writer
 //! [`DocumentWriter`]: document_writer::DocumentWriter
+//! [`FlushPolicy`]: flush_policy::FlushPolicy
+//! [`MultiRateWriter`]: hdf5_multirate::MultiRateWriter
+//! [`ChunkingConfig`]: hdf5_chunking::ChunkingConfig
+//! [`ChannelHistory`]: channel_history::ChannelHistory
+//! [`SpillBuffer`]: spill::SpillBuffer
+//! [`DataFile`]: data_file::DataFile
 
 // TODO: Fix doc comment generic types to use backticks
 #![allow(rustdoc::invalid_html_tags)]
 #![allow(rustdoc::broken_intra_doc_links)]
 #![allow(rustdoc::private_intra_doc_links)]
 
+// Minimal headless profile: heavy backends must stay out of the dependency graph
+#[cfg(all(
+    feature = "minimal",
+    any(
+        feature = "storage_hdf5",
+        feature = "storage_arrow",
+        feature = "storage_parquet",
+        feature = "storage_zarr"
+    )
+))]
+compile_error!(
+    "the `minimal` profile excludes HDF5, Arrow, Parquet and Zarr storage; drop `minimal` or the conflicting feature"
+);
+
 pub mod arrow_writer;
+pub mod channel_history;
 pub mod comedi_writer;
+pub mod data_file;
 pub mod document_writer;
+pub mod flush_policy;
 #[cfg(feature = "storage_hdf5")]
 pub mod hdf5_annotation;
+pub mod hdf5_chunking;
+pub mod hdf5_multirate;
 pub mod hdf5_writer;
+pub mod provenance;
 pub mod ring_buffer;
 pub mod ring_buffer_reader;
+pub mod spill;
 pub mod tap_registry;
 #[cfg(feature = "storage_tiff")]
 pub mod tiff_writer;
 #[cfg(feature = "storage_zarr")]
 pub mod zarr_writer;
 
+pub use channel_history::{
+    ChannelHistory, ChannelHistoryConfig, ChannelHistoryQuery, ChannelSeries,
+};
 pub use comedi_writer::{
     AcquisitionMetadata, ChannelConfig, ComediStreamWriter, ComediStreamWriterBuilder,
     CompressionType, ContinuousAcquisitionSession, StorageFormat, StreamStats,
 };
+pub use data_file::{DataFile, DataFileFormat, DatasetInfo, FileSummary};
 pub use document_writer::DocumentWriter;
+pub use flush_policy::{FlushMonitor, FlushPolicy, FlushPolicyConfig};
 #[cfg(feature = "storage_hdf5")]
 pub use hdf5_annotation::{add_run_annotation, read_run_annotations, RunAnnotation};
+pub use hdf5_chunking::{ChunkLayout, ChunkSource, ChunkingConfig};
+pub use hdf5_multirate::{MultiRateLayout, MultiRateWriter, RateChannel};
 pub use hdf5_writer::HDF5Writer;
+pub use provenance::{read_signed_manifest, sign_run_file, verify_run_file};
 pub use ring_buffer::{AsyncRingBuffer, RingBuffer};
 pub use ring_buffer_reader::{ReaderStats, RingBufferReader};
+pub use spill::{SpillBuffer, SpillConfig};
 
 #[cfg(feature = "storage_arrow")]
 pub use arrow_writer::ArrowDocumentWriter;
@@ -0,0 +1,277 @@
+//! HDF5 Provenance - Signing and verifying completed run files
+//!
+//! Computes the content digests of a run file, signs them with a
+//! [`RunSigner`] and stores the [`SignedManifest`] as attributes of the
+//! `/provenance` group (see `common::provenance` for the scheme).
+//!
+//! Every dataset is hashed over its raw data, and every group and dataset
+//! with attributes gets an `<path>@attributes` entry. User annotations (see
+//! `hdf5_annotation`) are meant to be edited after the run and are not
+//! covered by the signature.
+
+use anyhow::Result;
+use common::provenance::{RunSigner, SignedManifest, Verification, VerifyingKey};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[cfg(feature = "storage_hdf5")]
+use anyhow::{bail, Context};
+#[cfg(feature = "storage_hdf5")]
+use common::provenance::{attributes_digest, dataset_digest, ProvenanceManifest, PROVENANCE_GROUP};
+#[cfg(feature = "storage_hdf5")]
+use hdf5::{
+    types::{FloatSize, IntSize, TypeDescriptor, VarLenUnicode},
+    Attribute, Dataset, File, Group, Location,
+};
+
+/// Attributes written by `add_run_annotation`, excluded from digests
+#[cfg(feature = "storage_hdf5")]
+const ANNOTATION_ATTRIBUTES: &[&str] = &["user_notes", "tags", "annotated_at_ns"];
+
+/// Sign a completed run file and embed the manifest in it
+///
+/// A previous signature is replaced.
+#[cfg(feature = "storage_hdf5")]
+pub fn sign_run_file(
+    file_path: &Path,
+    run_uid: &str,
+    signer: &RunSigner,
+) -> Result<SignedManifest> {
+    let file = File::open_rw(file_path).context("Failed to open HDF5 file for signing")?;
+    if file.link_exists(PROVENANCE_GROUP) {
+        file.unlink(PROVENANCE_GROUP)?;
+    }
+
+    let file_name = file_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let manifest = ProvenanceManifest::new(run_uid, &file_name, collect_digests(&file)?);
+    let signed = signer.sign(&manifest)?;
+
+    let group = file.create_group(PROVENANCE_GROUP)?;
+    for (name, value) in [
+        ("manifest", &signed.manifest),
+        ("algorithm", &signed.algorithm),
+        ("public_key", &signed.public_key),
+        ("key_id", &signed.key_id),
+        ("signature", &signed.signature),
+    ] {
+        group
+            .new_attr::<VarLenUnicode>()
+            .create(name)?
+            .write_scalar(&value.parse::<VarLenUnicode>()?)?;
+    }
+    Ok(signed)
+}
+
+/// Read the signed manifest embedded in a run file
+///
+/// Returns None if the file was never signed.
+#[cfg(feature = "storage_hdf5")]
+pub fn read_signed_manifest(file_path: &Path) -> Result<Option<SignedManifest>> {
+    let file = File::open(file_path).context("Failed to open HDF5 file")?;
+    let Ok(group) = file.group(PROVENANCE_GROUP) else {
+        return Ok(None);
+    };
+    let read = |name: &str| -> Result<String> {
+        Ok(group
+            .attr(name)
+            .with_context(|| format!("Provenance attribute '{}' missing", name))?
+            .read_scalar::<VarLenUnicode>()?
+            .to_string())
+    };
+    Ok(Some(SignedManifest {
+        manifest: read("manifest")?,
+        algorithm: read("algorithm")?,
+        public_key: read("public_key")?,
+        key_id: read("key_id")?,
+        signature: read("signature")?,
+    }))
+}
+
+/// Current content digests of a run file, keyed as in its manifest
+#[cfg(feature = "storage_hdf5")]
+pub fn file_digests(file_path: &Path) -> Result<BTreeMap<String, String>> {
+    let file = File::open(file_path).context("Failed to open HDF5 file")?;
+    collect_digests(&file)
+}
+
+/// Verify a run file against its embedded signed manifest
+///
+/// See `common::provenance::verify` for how `trusted_keys` is applied.
+#[cfg(feature = "storage_hdf5")]
+pub fn verify_run_file(file_path: &Path, trusted_keys: &[VerifyingKey]) -> Result<Verification> {
+    let Some(signed) = read_signed_manifest(file_path)? else {
+        bail!("{} has no provenance signature", file_path.display());
+    };
+    common::provenance::verify(&signed, &file_digests(file_path)?, trusted_keys)
+}
+
+#[cfg(feature = "storage_hdf5")]
+fn collect_digests(file: &File) -> Result<BTreeMap<String, String>> {
+    let mut digests = BTreeMap::new();
+    collect_group(file, &mut digests)?;
+    Ok(digests)
+}
+
+#[cfg(feature = "storage_hdf5")]
+fn collect_group(group: &Group, digests: &mut BTreeMap<String, String>) -> Result<()> {
+    add_attributes(group, digests)?;
+    for dataset in group.datasets()? {
+        let path = dataset.name();
+        let (dtype, data) =
+            raw_data(&dataset).with_context(|| format!("Failed to read {}", path))?;
+        digests.insert(path, dataset_digest(dtype, &dataset.shape(), &data));
+        add_attributes(&dataset, digests)?;
+    }
+    for child in group.groups()? {
+        if child.name() != format!("/{}", PROVENANCE_GROUP) {
+            collect_group(&child, digests)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "storage_hdf5")]
+fn add_attributes(location: &Location, digests: &mut BTreeMap<String, String>) -> Result<()> {
+    let mut attributes = BTreeMap::new();
+    for name in location.attr_names()? {
+        if !ANNOTATION_ATTRIBUTES.contains(&name.as_str()) {
+            let value = attribute_text(&location.attr(&name)?).with_context(|| {
+                format!("Failed to read attribute {}@{}", location.name(), name)
+            })?;
+            attributes.insert(name, value);
+        }
+    }
+    if !attributes.is_empty() {
+        digests.insert(
+            format!("{}@attributes", location.name()),
+            attributes_digest(&attributes),
+        );
+    }
+    Ok(())
+}
+
+/// Attribute value as text; the document writer only writes strings and numbers
+#[cfg(feature = "storage_hdf5")]
+pub(crate) fn attribute_text(attr: &Attribute) -> Result<String> {
+    Ok(match attr.dtype()?.to_descriptor()? {
+        TypeDescriptor::VarLenUnicode => attr.read_scalar::<VarLenUnicode>()?.to_string(),
+        TypeDescriptor::Unsigned(_) => attr.read_scalar::<u64>()?.to_string(),
+        TypeDescriptor::Integer(_) => attr.read_scalar::<i64>()?.to_string(),
+        TypeDescriptor::Float(_) => attr.read_scalar::<f64>()?.to_string(),
+        other => bail!("Unsupported attribute type {:?}", other),
+    })
+}
+
+/// Element type name and little-endian bytes of a dataset
+#[cfg(feature = "storage_hdf5")]
+fn raw_data(dataset: &Dataset) -> Result<(&'static str, Vec<u8>)> {
+    fn bytes<T: hdf5::H5Type, const N: usize>(
+        dataset: &Dataset,
+        to_le: fn(T) -> [u8; N],
+    ) -> Result<Vec<u8>> {
+        Ok(dataset
+            .read_raw::<T>()?
+            .into_iter()
+            .flat_map(to_le)
+            .collect())
+    }
+
+    Ok(match dataset.dtype()?.to_descriptor()? {
+        TypeDescriptor::Float(FloatSize::U8) => ("float64", bytes(dataset, f64::to_le_bytes)?),
+        TypeDescriptor::Float(FloatSize::U4) => ("float32", bytes(dataset, f32::to_le_bytes)?),
+        TypeDescriptor::Unsigned(IntSize::U1) => ("uint8", bytes(dataset, u8::to_le_bytes)?),
+        TypeDescriptor::Unsigned(IntSize::U2) => ("uint16", bytes(dataset, u16::to_le_bytes)?),
+        TypeDescriptor::Unsigned(IntSize::U4) => ("uint32", bytes(dataset, u32::to_le_bytes)?),
+        TypeDescriptor::Unsigned(IntSize::U8) => ("uint64", bytes(dataset, u64::to_le_bytes)?),
+        TypeDescriptor::Integer(IntSize::U4) => ("int32", bytes(dataset, i32::to_le_bytes)?),
+        TypeDescriptor::Integer(IntSize::U8) => ("int64", bytes(dataset, i64::to_le_bytes)?),
+        other => bail!("Unsupported dataset type {:?}", other),
+    })
+}
+
+// Mock implementations for non-HDF5 builds
+#[cfg(not(feature = "storage_hdf5"))]
+pub fn sign_run_file(
+    _file_path: &Path,
+    _run_uid: &str,
+    _signer: &RunSigner,
+) -> Result<SignedManifest> {
+    anyhow::bail!("HDF5 storage feature not enabled")
+}
+
+#[cfg(not(feature = "storage_hdf5"))]
+pub fn read_signed_manifest(_file_path: &Path) -> Result<Option<SignedManifest>> {
+    Ok(None)
+}
+
+#[cfg(not(feature = "storage_hdf5"))]
+pub fn file_digests(_file_path: &Path) -> Result<BTreeMap<String, String>> {
+    anyhow::bail!("HDF5 storage feature not enabled")
+}
+
+#[cfg(not(feature = "storage_hdf5"))]
+pub fn verify_run_file(_file_path: &Path, _trusted_keys: &[VerifyingKey]) -> Result<Verification> {
+    anyhow::bail!("HDF5 storage feature not enabled")
+}
+
+#[cfg(all(test, feature = "storage_hdf5"))]
+mod tests {
+    use super::*;
+
+    fn write_run(path: &Path) {
+        let file = File::create(path).unwrap();
+        let start = file.create_group("start").unwrap();
+        start
+            .new_attr::<VarLenUnicode>()
+            .create("uid")
+            .unwrap()
+            .write_scalar(&"run1".parse::<VarLenUnicode>().unwrap())
+            .unwrap();
+        let primary = file.create_group("primary").unwrap();
+        primary
+            .new_dataset_builder()
+            .with_data(&[1.0f64, 2.0, 3.0][..])
+            .create("det1")
+            .unwrap();
+    }
+
+    #[test]
+    fn test_sign_and_verify_run_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run1_1000.h5");
+        write_run(&path);
+        let signer = RunSigner::from_seed(&[3; 32]);
+
+        let signed = sign_run_file(&path, "run1", &signer).unwrap();
+        assert_eq!(read_signed_manifest(&path).unwrap(), Some(signed.clone()));
+        let manifest = signed.manifest().unwrap();
+        assert!(manifest.datasets.contains_key("/primary/det1"));
+        assert!(manifest.datasets.contains_key("/start@attributes"));
+
+        let trusted = [signer.verifying_key()];
+        assert!(verify_run_file(&path, &trusted).unwrap().is_intact());
+
+        // Annotations stay editable
+        crate::hdf5_annotation::add_run_annotation(
+            &path,
+            &crate::hdf5_annotation::RunAnnotation {
+                notes: "checked".to_string(),
+                tags: vec![],
+            },
+        )
+        .unwrap();
+        assert!(verify_run_file(&path, &trusted).unwrap().is_intact());
+
+        // Changed data does not
+        {
+            let file = File::open_rw(&path).unwrap();
+            let ds = file.dataset("primary/det1").unwrap();
+            ds.write_slice(&[9.0f64], 1..2).unwrap();
+        }
+        let verification = verify_run_file(&path, &trusted).unwrap();
+        assert_eq!(verification.modified, ["/primary/det1"]);
+    }
+}
@@ -49,6 +49,8 @@ use tokio::sync::mpsc;
 use std::os::unix::fs::OpenOptionsExt;
 
 use crate::tap_registry::TapRegistry;
+use common::decimation::Decimation;
+use common::memory::{MemoryAccount, MemoryLedger, MemorySubsystem};
 
 #[cfg(feature = "storage_arrow")]
 use arrow::record_batch::RecordBatch;
@@ -167,6 +169,9 @@ pub struct RingBuffer {
     /// Registry for live data taps
     taps: Arc<TapRegistry>,
 
+    /// Size of the mapping, accounted as ring buffer memory
+    _memory: MemoryAccount,
+
     /// Arrow schema JSON for cross-process readers (bd-1il7).
     ///
     /// Stored in-memory when first Arrow batch is written. Cross-process
@@ -190,6 +195,18 @@ impl std::fmt::Debug for RingBuffer {
     }
 }
 
+/// Memory account of a ring buffer mapping, named after its file
+fn mapping_account(path: &Path, bytes: usize) -> MemoryAccount {
+    let name = path.file_name().map_or_else(
+        || path.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    let account = MemoryLedger::global()
+        .account(format!("ring_buffer:{}", name), MemorySubsystem::RingBuffer);
+    account.set(bytes as u64);
+    account
+}
+
 // SAFETY: RingBuffer can be safely sent to other threads because:
 // 1. It owns its mmap (MmapMut) which is itself Send
 // 2. Raw pointers (header, data_ptr) point into the mmap and remain valid
@@ -360,6 +377,7 @@ impl RingBuffer {
             capacity: capacity_bytes as u64,
             data_lock: RwLock::new(()),
             taps: Arc::new(TapRegistry::new()),
+            _memory: mapping_account(path, total_size),
             #[cfg(feature = "storage_arrow")]
             arrow_schema_json: RwLock::new(None),
         })
@@ -456,6 +474,7 @@ impl RingBuffer {
             capacity,
             data_lock: RwLock::new(()),
             taps: Arc::new(TapRegistry::new()),
+            _memory: mapping_account(path, expected_size),
             #[cfg(feature = "storage_arrow")]
             arrow_schema_json: RwLock::new(None),
         })
@@ -600,6 +619,35 @@ impl RingBuffer {
         Ok(rx)
     }
 
+    /// Register a tap consumer with its own decimation.
+    ///
+    /// Frames are dropped for this tap before they are queued, so e.g. a GUI
+    /// tap can get 10 Hz aligned to wall-clock boundaries while the storage
+    /// writer reads every record.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::path::Path;
+    /// # use daq_storage::ring_buffer::RingBuffer;
+    /// # use common::decimation::Decimation;
+    /// # fn example() -> anyhow::Result<()> {
+    /// let rb = RingBuffer::create(Path::new("/tmp/test.buf"), 10)?;
+    /// let _rx = rb.register_tap_with("gui".to_string(), Decimation::wall_clock_rate(10.0))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn register_tap_with(
+        &self,
+        id: String,
+        decimation: Decimation,
+    ) -> Result<mpsc::Receiver<Vec<u8>>> {
+        let rx = self.taps.register_with(id.clone(), decimation)?;
+
+        tracing::info!(tap = %id, ?decimation, "Registered tap");
+
+        Ok(rx)
+    }
+
     /// Unregister a tap consumer.
     ///
     /// # Arguments
@@ -1014,6 +1062,19 @@ impl RingBuffer {
         unsafe { (*self.header).read_tail.load(Ordering::Acquire) }
     }
 
+    /// Fraction of the capacity written but not yet consumed (0-1).
+    ///
+    /// At 1.0 the write head is about to overwrite unconsumed data. Writers
+    /// draining the buffer use this to flush sooner under load (see
+    /// [`crate::flush_policy`]).
+    pub fn fill_ratio(&self) -> f64 {
+        if self.capacity == 0 {
+            return 0.0;
+        }
+        let pending = self.write_head().saturating_sub(self.read_tail());
+        (pending as f64 / self.capacity as f64).min(1.0)
+    }
+
     /// Update the read tail position (mark data as consumed).
     ///
     /// This should be called by consumers after processing data to free up space.
@@ -1200,6 +1261,15 @@ impl AsyncRingBuffer {
         self.inner.register_tap(id, nth_frame)
     }
 
+    /// Register a tap consumer with its own decimation.
+    pub fn register_tap_with(
+        &self,
+        id: String,
+        decimation: Decimation,
+    ) -> Result<mpsc::Receiver<Vec<u8>>> {
+        self.inner.register_tap_with(id, decimation)
+    }
+
     /// Unregister a tap consumer.
     ///
     /// # Arguments
@@ -1729,6 +1799,37 @@ mod tests {
         assert_eq!(received_count, 4);
     }
 
+    #[tokio::test]
+    async fn test_tap_rate_decimation_per_consumer() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test_ring.buf");
+
+        let rb = RingBuffer::create(&path, 10).unwrap();
+
+        // Full-rate consumer alongside a 1 Hz wall-clock aligned one
+        let mut full = rb.register_tap("writer".to_string(), 1).unwrap();
+        let mut slow = rb
+            .register_tap_with("gui".to_string(), Decimation::wall_clock_rate(1.0))
+            .unwrap();
+        assert_eq!(rb.list_taps().len(), 2);
+
+        // A burst well inside one second lands in at most two 1 s periods
+        for i in 0..10 {
+            rb.write(format!("frame_{}", i).as_bytes()).unwrap();
+        }
+
+        let drain = |rx: &mut mpsc::Receiver<Vec<u8>>| {
+            let mut count = 0;
+            while rx.try_recv().is_ok() {
+                count += 1;
+            }
+            count
+        };
+        assert_eq!(drain(&mut full), 10);
+        let slow_count = drain(&mut slow);
+        assert!((1..=2).contains(&slow_count), "got {}", slow_count);
+    }
+
     #[tokio::test]
     async fn test_tap_backpressure() {
         let temp_dir = tempfile::tempdir().unwrap();
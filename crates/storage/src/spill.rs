@@ -0,0 +1,468 @@
+//! Disk Spillover - Keep storage-bound data while the writer stalls
+//!
+//! The reliable path to storage is a bounded channel: when the writer stalls
+//! (a slow disk, a long HDF5 flush), either the pipeline blocks and cameras
+//! drop frames at the source, or the data is dropped on the floor. Neither is
+//! acceptable for data destined for permanent storage.
+//!
+//! [`SpillBuffer`] sits between the distribution layer and the storage
+//! writer. Records are kept in memory up to `memory_budget_mb`; beyond that
+//! they are appended to a temporary [`SpillQueue`] on disk, and drained back
+//! to the writer in order once it catches up. Only when the disk budget is
+//! exhausted too are records dropped.
+//!
+//! Once spilling has started, new records also go to disk until the queue is
+//! empty again, so the writer always sees records in arrival order.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [storage.spill]
+//! enabled = true
+//! memory_budget_mb = 64
+//! max_disk_mb = 4096
+//! # directory = "/var/tmp/rust_daq_spill"   # default: system temp directory
+//! ```
+
+use anyhow::{Context, Result};
+use common::memory::{MemoryAccount, MemoryLedger, MemorySubsystem};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+const MB: u64 = 1024 * 1024;
+
+/// Spillover settings of the reliable storage path
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpillConfig {
+    /// `false` keeps the bounded in-memory path (the pipeline blocks when full)
+    pub enabled: bool,
+    /// Records kept in memory before spilling to disk
+    pub memory_budget_mb: u64,
+    /// Most data held on disk; records beyond it are dropped
+    pub max_disk_mb: u64,
+    /// Size at which a new spill file is started, so drained data is freed early
+    pub segment_mb: u64,
+    /// Where spill files go (system temp directory if unset)
+    pub directory: Option<PathBuf>,
+}
+
+impl Default for SpillConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            memory_budget_mb: 64,
+            max_disk_mb: 4096,
+            segment_mb: 16,
+            directory: None,
+        }
+    }
+}
+
+impl SpillConfig {
+    /// Parent directory of the spill queues
+    pub fn base_directory(&self) -> PathBuf {
+        self.directory
+            .clone()
+            .unwrap_or_else(|| std::env::temp_dir().join("rust_daq_spill"))
+    }
+}
+
+/// Disk-backed FIFO of byte records
+///
+/// Records are stored length-prefixed in segment files inside a directory of
+/// their own, which is removed when the queue is dropped. A segment file is
+/// deleted as soon as its last record has been read.
+pub struct SpillQueue {
+    dir: PathBuf,
+    segment_bytes: u64,
+    next_segment: u64,
+    /// Oldest first; the last one is being written
+    segments: VecDeque<Segment>,
+    writer: Option<BufWriter<File>>,
+    reader: Option<BufReader<File>>,
+    records: u64,
+    bytes: u64,
+}
+
+struct Segment {
+    path: PathBuf,
+    /// Records written and not yet read
+    records: u64,
+    /// Bytes written, including length prefixes
+    written: u64,
+}
+
+impl SpillQueue {
+    /// Create an empty queue in a new directory under `base`
+    pub fn create(base: &Path, segment_bytes: u64) -> Result<Self> {
+        let dir = base.join(format!(
+            "spill-{}-{}",
+            std::process::id(),
+            common::experiment::document::now_ns()
+        ));
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create spill directory {:?}", dir))?;
+        Ok(Self {
+            dir,
+            segment_bytes: segment_bytes.max(1),
+            next_segment: 0,
+            segments: VecDeque::new(),
+            writer: None,
+            reader: None,
+            records: 0,
+            bytes: 0,
+        })
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Records waiting to be read
+    pub fn len(&self) -> u64 {
+        self.records
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records == 0
+    }
+
+    /// Bytes of the waiting records (without length prefixes)
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Append a record
+    pub fn push(&mut self, record: &[u8]) -> Result<()> {
+        let len = u32::try_from(record.len()).context("Spill record larger than 4 GiB")?;
+        let roll = self
+            .segments
+            .back()
+            .is_none_or(|segment| segment.written >= self.segment_bytes);
+        if roll {
+            self.start_segment()?;
+        }
+        let writer = self.writer.as_mut().expect("segment writer is open");
+        writer.write_all(&len.to_le_bytes())?;
+        writer.write_all(record)?;
+        let segment = self.segments.back_mut().expect("segment is open");
+        segment.records += 1;
+        segment.written += 4 + u64::from(len);
+        self.records += 1;
+        self.bytes += u64::from(len);
+        Ok(())
+    }
+
+    /// Take the oldest record
+    pub fn pop(&mut self) -> Result<Option<Vec<u8>>> {
+        if self.records == 0 {
+            return Ok(None);
+        }
+        if self.segments.len() == 1 {
+            // Reading the segment being written: make its tail visible
+            if let Some(writer) = self.writer.as_mut() {
+                writer.flush()?;
+            }
+        }
+        if self.reader.is_none() {
+            let path = &self.segments.front().expect("records imply a segment").path;
+            let file = File::open(path)
+                .with_context(|| format!("Failed to open spill file {:?}", path))?;
+            self.reader = Some(BufReader::new(file));
+        }
+        let reader = self.reader.as_mut().expect("segment reader is open");
+        let mut len = [0u8; 4];
+        reader.read_exact(&mut len)?;
+        let mut record = vec![0u8; u32::from_le_bytes(len) as usize];
+        reader.read_exact(&mut record)?;
+
+        self.records -= 1;
+        self.bytes -= record.len() as u64;
+        let front = self.segments.front_mut().expect("records imply a segment");
+        front.records -= 1;
+        if front.records == 0 && (self.segments.len() > 1 || self.records == 0) {
+            self.reader = None;
+            if self.segments.len() == 1 {
+                self.writer = None;
+            }
+            let segment = self.segments.pop_front().expect("front segment exists");
+            if let Err(e) = std::fs::remove_file(&segment.path) {
+                tracing::warn!(path = ?segment.path, error = %e, "Failed to remove drained spill file");
+            }
+        }
+        Ok(Some(record))
+    }
+
+    fn start_segment(&mut self) -> Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+        let path = self
+            .dir
+            .join(format!("segment_{:08}.bin", self.next_segment));
+        self.next_segment += 1;
+        let file = File::create(&path)
+            .with_context(|| format!("Failed to create spill file {:?}", path))?;
+        self.writer = Some(BufWriter::new(file));
+        self.segments.push_back(Segment {
+            path,
+            records: 0,
+            written: 0,
+        });
+        Ok(())
+    }
+}
+
+impl Drop for SpillQueue {
+    fn drop(&mut self) {
+        self.writer = None;
+        self.reader = None;
+        if let Err(e) = std::fs::remove_dir_all(&self.dir) {
+            tracing::warn!(dir = ?self.dir, error = %e, "Failed to remove spill directory");
+        }
+    }
+}
+
+/// Where [`SpillBuffer::push`] put a record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Admission {
+    Memory,
+    Spilled,
+    /// Over both budgets, or spilling is disabled or failed
+    Dropped,
+}
+
+/// Counters of a [`SpillBuffer`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpillStats {
+    pub memory_records: u64,
+    pub memory_bytes: u64,
+    pub disk_records: u64,
+    pub disk_bytes: u64,
+    /// Records spilled to disk so far
+    pub spilled: u64,
+    /// Records dropped so far
+    pub dropped: u64,
+}
+
+/// Memory queue with disk overflow, between the distribution layer and a
+/// storage writer
+pub struct SpillBuffer {
+    config: SpillConfig,
+    memory: VecDeque<Vec<u8>>,
+    memory_bytes: u64,
+    /// `memory_bytes`, as seen by the memory ledger
+    memory_account: MemoryAccount,
+    /// Created on the first spill
+    disk: Option<SpillQueue>,
+    spilled: u64,
+    dropped: u64,
+}
+
+impl SpillBuffer {
+    pub fn new(config: SpillConfig) -> Self {
+        Self {
+            config,
+            memory: VecDeque::new(),
+            memory_bytes: 0,
+            memory_account: MemoryLedger::global().account("storage_spill", MemorySubsystem::Queue),
+            disk: None,
+            spilled: 0,
+            dropped: 0,
+        }
+    }
+
+    pub fn config(&self) -> &SpillConfig {
+        &self.config
+    }
+
+    /// Queue a record, in memory while within budget and on disk beyond it
+    pub fn push(&mut self, record: Vec<u8>) -> Admission {
+        let len = record.len() as u64;
+        let spilling = self.disk.as_ref().is_some_and(|disk| !disk.is_empty());
+        // A single record larger than the budget still fits an empty queue
+        if !spilling
+            && (self.memory.is_empty()
+                || self.memory_bytes + len <= self.config.memory_budget_mb * MB)
+        {
+            self.memory_bytes += len;
+            self.memory_account.set(self.memory_bytes);
+            self.memory.push_back(record);
+            return Admission::Memory;
+        }
+
+        if !self.config.enabled || self.disk_bytes() + len > self.config.max_disk_mb * MB {
+            self.dropped += 1;
+            return Admission::Dropped;
+        }
+        if self.disk.is_none() {
+            match SpillQueue::create(
+                &self.config.base_directory(),
+                self.config.segment_mb.max(1) * MB,
+            ) {
+                Ok(queue) => {
+                    tracing::info!(dir = ?queue.directory(), "Created storage spill queue");
+                    self.disk = Some(queue);
+                }
+                Err(e) => {
+                    tracing::error!(error = %format!("{:#}", e), "Cannot spill to disk");
+                    self.dropped += 1;
+                    return Admission::Dropped;
+                }
+            }
+        }
+        let disk = self.disk.as_mut().expect("spill queue was just created");
+        match disk.push(&record) {
+            Ok(()) => {
+                self.spilled += 1;
+                Admission::Spilled
+            }
+            Err(e) => {
+                tracing::error!(error = %format!("{:#}", e), "Spill write failed");
+                self.dropped += 1;
+                Admission::Dropped
+            }
+        }
+    }
+
+    /// Take the oldest record
+    pub fn pop(&mut self) -> Option<Vec<u8>> {
+        if let Some(record) = self.memory.pop_front() {
+            self.memory_bytes -= record.len() as u64;
+            self.memory_account.set(self.memory_bytes);
+            return Some(record);
+        }
+        let disk = self.disk.as_mut()?;
+        match disk.pop() {
+            Ok(record) => record,
+            Err(e) => {
+                // An unreadable queue can't be drained; count what it held as lost
+                tracing::error!(error = %format!("{:#}", e), "Spill read failed, discarding spilled data");
+                self.dropped += disk.len();
+                self.disk = None;
+                None
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.memory.is_empty() && self.disk.as_ref().is_none_or(SpillQueue::is_empty)
+    }
+
+    pub fn stats(&self) -> SpillStats {
+        SpillStats {
+            memory_records: self.memory.len() as u64,
+            memory_bytes: self.memory_bytes,
+            disk_records: self.disk.as_ref().map_or(0, SpillQueue::len),
+            disk_bytes: self.disk_bytes(),
+            spilled: self.spilled,
+            dropped: self.dropped,
+        }
+    }
+
+    fn disk_bytes(&self) -> u64 {
+        self.disk.as_ref().map_or(0, SpillQueue::bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_queue_rolls_segments_and_removes_drained_files() {
+        let temp = TempDir::new().unwrap();
+        let mut queue = SpillQueue::create(temp.path(), 64).unwrap();
+        for i in 0..10u8 {
+            queue.push(&[i; 30]).unwrap();
+        }
+        let dir = queue.directory().to_path_buf();
+        let files = || std::fs::read_dir(&dir).unwrap().count();
+        assert_eq!(queue.len(), 10);
+        assert_eq!(queue.bytes(), 300);
+        assert_eq!(files(), 5);
+
+        for i in 0..4u8 {
+            assert_eq!(queue.pop().unwrap().unwrap(), vec![i; 30]);
+        }
+        assert_eq!(files(), 3);
+
+        // Interleaved writes land behind the queued records
+        queue.push(&[99; 3]).unwrap();
+        let rest: Vec<Vec<u8>> = std::iter::from_fn(|| queue.pop().unwrap()).collect();
+        assert_eq!(rest.len(), 7);
+        assert_eq!(rest[0], vec![4; 30]);
+        assert_eq!(rest[6], vec![99; 3]);
+        assert!(queue.is_empty());
+        assert_eq!(files(), 0);
+
+        drop(queue);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_buffer_spills_beyond_memory_budget_and_drains_in_order() {
+        let temp = TempDir::new().unwrap();
+        let mut buffer = SpillBuffer::new(SpillConfig {
+            enabled: true,
+            memory_budget_mb: 1,
+            max_disk_mb: 2,
+            segment_mb: 1,
+            directory: Some(temp.path().to_path_buf()),
+        });
+        let record = |i: u32| {
+            let mut r = vec![0u8; 256 * 1024];
+            r[..4].copy_from_slice(&i.to_le_bytes());
+            r
+        };
+
+        // 4 records fill the memory budget, 8 more fill the disk budget
+        let admissions: Vec<Admission> = (0..14).map(|i| buffer.push(record(i))).collect();
+        assert_eq!(admissions[..4], [Admission::Memory; 4]);
+        assert_eq!(admissions[4..12], [Admission::Spilled; 8]);
+        assert_eq!(admissions[12..], [Admission::Dropped; 2]);
+        let stats = buffer.stats();
+        assert_eq!((stats.memory_records, stats.disk_records), (4, 8));
+        assert_eq!((stats.spilled, stats.dropped), (8, 2));
+
+        let mut pop = || {
+            buffer
+                .pop()
+                .map(|r| u32::from_le_bytes(r[..4].try_into().unwrap()))
+        };
+        assert_eq!(
+            (0..5).map(|_| pop().unwrap()).collect::<Vec<_>>(),
+            [0, 1, 2, 3, 4]
+        );
+
+        // Memory has drained, but new records queue behind the spilled ones
+        assert_eq!(buffer.push(record(100)), Admission::Spilled);
+        let drained: Vec<u32> = std::iter::from_fn(|| {
+            buffer
+                .pop()
+                .map(|r| u32::from_le_bytes(r[..4].try_into().unwrap()))
+        })
+        .collect();
+        assert_eq!(drained, vec![5, 6, 7, 8, 9, 10, 11, 100]);
+        assert!(buffer.is_empty());
+
+        // Back within budget, records stay in memory again
+        assert_eq!(buffer.push(record(101)), Admission::Memory);
+    }
+
+    #[test]
+    fn test_disabled_buffer_drops_over_budget() {
+        let mut buffer = SpillBuffer::new(SpillConfig {
+            memory_budget_mb: 1,
+            ..SpillConfig::default()
+        });
+        assert_eq!(buffer.push(vec![0; 800 * 1024]), Admission::Memory);
+        assert_eq!(buffer.push(vec![0; 800 * 1024]), Admission::Dropped);
+        assert_eq!(buffer.stats().dropped, 1);
+    }
+}
@@ -1,4 +1,6 @@
 use anyhow::{anyhow, Result};
+use common::decimation::{Decimation, Decimator};
+use common::integrity::{DropLedger, DropStage};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
@@ -7,17 +9,21 @@ use tokio::sync::mpsc;
 /// Default channel capacity for tap consumers (number of frames buffered)
 const DEFAULT_TAP_CHANNEL_SIZE: usize = 16;
 
-/// A tap consumer that receives every Nth frame from the ring buffer.
+/// A tap consumer that receives a decimated subset of ring buffer frames.
+///
+/// Decimation happens here, before frames are queued, so a 10 Hz GUI tap
+/// doesn't receive (and discard) every frame of a full-rate acquisition.
 #[derive(Debug)]
 pub struct TapConsumer {
     /// Unique identifier for this tap
     pub id: String,
 
-    /// Deliver every nth frame (1 = every frame, 10 = every 10th frame)
+    /// Deliver every nth frame (1 = every frame, 10 = every 10th frame).
+    /// Always 1 for rate-based taps.
     pub nth_frame: usize,
 
-    /// Current frame count for this tap (internal counter)
-    frame_count: AtomicU64,
+    /// Per-tap decimation state
+    decimator: Decimator,
 
     /// Async channel sender for delivering frames
     /// Uses try_send to avoid blocking on backpressure
@@ -30,19 +36,43 @@ pub struct TapConsumer {
 impl TapConsumer {
     /// Create a new tap consumer
     pub fn new(id: String, nth_frame: usize, sender: mpsc::Sender<Vec<u8>>) -> Self {
+        let nth_frame = nth_frame.max(1); // Ensure at least 1
+        Self::with_decimation(
+            id,
+            Decimation::EveryNth {
+                n: nth_frame as u64,
+            },
+            sender,
+        )
+    }
+
+    /// Create a tap consumer with any decimation (e.g. 10 Hz wall-clock aligned)
+    pub fn with_decimation(
+        id: String,
+        decimation: Decimation,
+        sender: mpsc::Sender<Vec<u8>>,
+    ) -> Self {
+        let nth_frame = match decimation {
+            Decimation::EveryNth { n } => n.max(1) as usize,
+            _ => 1,
+        };
         Self {
             id,
-            nth_frame: nth_frame.max(1), // Ensure at least 1
-            frame_count: AtomicU64::new(0),
+            nth_frame,
+            decimator: Decimator::new(decimation),
             sender,
             dropped_frames: AtomicU64::new(0),
         }
     }
 
-    /// Check if this frame should be delivered based on nth_frame setting
+    /// Check if this frame should be delivered based on the tap's decimation
     pub fn should_deliver(&self) -> bool {
-        let count = self.frame_count.fetch_add(1, Ordering::Relaxed);
-        count.is_multiple_of(self.nth_frame as u64)
+        self.decimator.admit()
+    }
+
+    /// Decimation applied to this tap
+    pub fn decimation(&self) -> Decimation {
+        self.decimator.decimation()
     }
 
     /// Attempt to send a frame without blocking
@@ -52,6 +82,7 @@ impl TapConsumer {
             Ok(_) => true,
             Err(mpsc::error::TrySendError::Full(_)) => {
                 self.dropped_frames.fetch_add(1, Ordering::Relaxed);
+                DropLedger::global().record(&format!("tap:{}", self.id), DropStage::Tap, 1);
                 false
             }
             Err(mpsc::error::TrySendError::Closed(_)) => false, // Receiver closed
@@ -79,6 +110,20 @@ impl TapRegistry {
 
     /// Register a new tap
     pub fn register(&self, id: String, nth_frame: usize) -> Result<mpsc::Receiver<Vec<u8>>> {
+        self.register_with(
+            id,
+            Decimation::EveryNth {
+                n: nth_frame.max(1) as u64,
+            },
+        )
+    }
+
+    /// Register a new tap with its own decimation
+    pub fn register_with(
+        &self,
+        id: String,
+        decimation: Decimation,
+    ) -> Result<mpsc::Receiver<Vec<u8>>> {
         let mut taps = self
             .taps
             .write()
@@ -89,7 +134,7 @@ impl TapRegistry {
         }
 
         let (tx, rx) = mpsc::channel(DEFAULT_TAP_CHANNEL_SIZE);
-        let tap = Arc::new(TapConsumer::new(id.clone(), nth_frame, tx));
+        let tap = Arc::new(TapConsumer::with_decimation(id.clone(), decimation, tx));
         taps.insert(id, tap);
 
         Ok(rx)
@@ -146,4 +191,12 @@ impl TapRegistry {
             .map(|t| t.values().map(|t| (t.id.clone(), t.nth_frame)).collect())
             .unwrap_or_default()
     }
+
+    /// List all taps with their decimation
+    pub fn list_decimation(&self) -> Vec<(String, Decimation)> {
+        self.taps
+            .read()
+            .map(|t| t.values().map(|t| (t.id.clone(), t.decimation())).collect())
+            .unwrap_or_default()
+    }
 }
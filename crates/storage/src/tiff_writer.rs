@@ -43,6 +43,15 @@
 //! let frames: Vec<Frame> = acquire_frames();
 //! TiffWriter::write_stack(&frames, "stack.tiff")?;
 //! ```
+//!
+//! ## Per-Frame Channel Values
+//!
+//! Frames enriched with sampled channel values (`FrameMetadata::channels`,
+//! see `FrameEnrichment::enrich_frame` in `common::frame_enrichment`) get a
+//! JSON sidecar next to each TIFF file (`frame.tiff` -> `frame.json`)
+//! holding the frame number, timestamp, exposure and channel values. Overlay
+//! annotations (`FrameMetadata::annotations`, see `common::frame_annotations`)
+//! go into the same sidecar.
 
 use anyhow::{anyhow, Context, Result};
 use common::data::Frame;
@@ -114,7 +123,8 @@ impl TiffWriter {
         match frame.bit_depth {
             16 => Self::write_16bit_frame(frame, path),
             _ => Self::write_8bit_frame(frame, path),
-        }
+        }?;
+        Self::write_channel_sidecar(frame, path)
     }
 
     /// Write pooled frame data to a TIFF file (zero-copy path).
@@ -249,6 +259,7 @@ impl TiffWriter {
                 _ => Self::write_8bit_frame(frame, &numbered_path),
             }
             .with_context(|| format!("Failed to write frame {} to {:?}", i, numbered_path))?;
+            Self::write_channel_sidecar(frame, &numbered_path)?;
         }
 
         tracing::info!(
@@ -262,6 +273,36 @@ impl TiffWriter {
         Ok(())
     }
 
+    /// Write sampled channel values for a frame to a JSON sidecar.
+    ///
+    /// The image crate's TIFF encoder can't write custom tags, so per-frame
+    /// values live next to the image. No file is written for frames without
+    /// channel values.
+    fn write_channel_sidecar(frame: &Frame, path: &Path) -> Result<()> {
+        let Some(metadata) = frame.metadata.as_deref() else {
+            return Ok(());
+        };
+        if metadata.channels.is_empty() && metadata.annotations.is_empty() {
+            return Ok(());
+        }
+
+        let sidecar_path = path.with_extension("json");
+        let mut sidecar = serde_json::json!({
+            "frame_number": frame.frame_number,
+            "timestamp_ns": frame.timestamp_ns,
+            "exposure_ms": frame.exposure_ms,
+            "channels": metadata.channels,
+        });
+        if !metadata.annotations.is_empty() {
+            sidecar["annotations"] = serde_json::to_value(&metadata.annotations)?;
+        }
+        let file = File::create(&sidecar_path)
+            .with_context(|| format!("Failed to create {:?}", sidecar_path))?;
+        serde_json::to_writer_pretty(BufWriter::new(file), &sidecar)
+            .with_context(|| format!("Failed to write frame metadata to {:?}", sidecar_path))?;
+        Ok(())
+    }
+
     /// Write an 8-bit grayscale frame.
     fn write_8bit_frame(frame: &Frame, path: &Path) -> Result<()> {
         // Convert Bytes to Vec<u8> for image crate
@@ -490,6 +531,52 @@ mod tests {
         }
     }
 
+    #[test]
+    #[allow(deprecated)]
+    fn test_write_frame_channel_sidecar() {
+        use common::frame_enrichment::{ChannelEnricher, ChannelSampleCache, FrameEnrichment};
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("enriched.tiff");
+
+        let cache = ChannelSampleCache::new();
+        cache.record("sample_x", 1.25, 1_000);
+        let enrichment = FrameEnrichment::new().with_enricher(std::sync::Arc::new(
+            ChannelEnricher::new(vec!["sample_x".to_string()], cache),
+        ));
+        let frame = enrichment.enrich_frame(create_test_frame(16, 16, 16));
+        TiffWriter::write_frame(&frame, &path).unwrap();
+
+        let sidecar: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(path.with_extension("json")).unwrap()).unwrap();
+        assert_eq!(sidecar["channels"]["sample_x"], 1.25);
+        assert_eq!(sidecar["frame_number"], 1);
+        assert!(sidecar.get("annotations").is_none());
+
+        // Annotations alone also get a sidecar
+        let annotated = temp_dir.path().join("annotated.tiff");
+        let metadata = common::data::FrameMetadata {
+            annotations: vec![common::frame_annotations::FrameAnnotation::Crosshair {
+                label: "beam".to_string(),
+                x: 8.0,
+                y: 7.5,
+            }],
+            ..Default::default()
+        };
+        let frame = create_test_frame(16, 16, 16).with_metadata(metadata);
+        TiffWriter::write_frame(&frame, &annotated).unwrap();
+        let sidecar: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(annotated.with_extension("json")).unwrap())
+                .unwrap();
+        assert_eq!(sidecar["annotations"][0]["type"], "crosshair");
+        assert_eq!(sidecar["annotations"][0]["y"], 7.5);
+
+        // Frames without channel values get no sidecar
+        let plain = temp_dir.path().join("plain.tiff");
+        TiffWriter::write_frame(&create_test_frame(16, 16, 16), &plain).unwrap();
+        assert!(!plain.with_extension("json").exists());
+    }
+
     #[test]
     fn test_empty_stack_error() {
         let temp_dir = TempDir::new().unwrap();
@@ -1,7 +1,7 @@
 //! Main application state and UI logic.
 
-use std::collections::HashMap;
-use std::time::Instant;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 
 use eframe::egui;
 use egui_dock::tab_viewer::OnCloseResponse;
@@ -13,23 +13,32 @@ use crate::connection::{
 };
 use crate::connection_state_ext::ConnectionStateExt;
 use crate::daemon_launcher::{AutoConnectState, DaemonLauncher, DaemonMode};
+use crate::daemon_logs::{DaemonLogMessage, DaemonLogStream};
+use crate::gamepad::GamepadControl;
 use crate::icons;
+use crate::keyboard_control::{KeyboardControl, KeyboardControlMessage};
 use crate::layout;
+use crate::notifications::{Notification, NotificationKind, RunNotifier};
+use crate::offline_cache::{OfflineCache, OfflineSync, OfflineSyncMessage, ReplayResult};
 use crate::panels::{
     ConnectionDiagnostics, ConnectionStatus as LogConnectionStatus, DevicesPanel,
     DocumentViewerPanel, ExperimentDesignerPanel, GettingStartedPanel, ImageViewerPanel,
-    InstrumentManagerPanel, LoggingPanel, ModulesPanel, PlanRunnerPanel, RunComparisonPanel,
-    RunHistoryPanel, ScanBuilderPanel, ScansPanel, ScriptsPanel, SignalPlotterPanel, StoragePanel,
+    InstrumentConsolePanel, InstrumentManagerPanel, LoggingPanel, ModulesPanel,
+    OfflineInstrumentsView, PlanRunnerPanel, RunComparisonPanel, RunHistoryPanel, ScanBuilderPanel,
+    ScansPanel, ScriptsPanel, SignalPlotterPanel, StateMachinesPanel, StoragePanel,
 };
+use crate::presence::{PresenceNotice, PresenceTracker};
 use crate::shortcuts::{CheatSheetPanel, ShortcutAction, ShortcutContext, ShortcutManager};
 use crate::theme::{self, ThemePreference};
 use crate::widgets::{
     AnalogOutputControlPanel, DeviceControlWidget, MaiTaiControlPanel, PowerMeterControlPanel,
-    RotatorControlPanel, StageControlPanel, StatusBar,
+    RotatorControlPanel, StageControlPanel, StatusBar, StatusLevel,
+};
+use client::reconnect::{
+    friendly_error_message, ConnectionManager, ConnectionState, ResyncSnapshot,
 };
-use client::reconnect::{friendly_error_message, ConnectionManager, ConnectionState};
 use client::DaqClient;
-use protocol::daq::DeviceInfo;
+use protocol::daq::{DeviceInfo, ListStreamStatsResponse};
 
 /// Layout version constant. Increment this when the default dock layout changes
 /// to force users with stale saved layouts to get the new default.
@@ -40,6 +49,12 @@ const LAYOUT_VERSION: u32 = 2;
 /// Storage key for layout version
 const LAYOUT_VERSION_KEY: &str = "layout_version";
 
+/// How often the offline cache is updated from the panels while connected
+const OFFLINE_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often the status bar's stream statistics are refreshed while connected
+const STREAM_STATS_INTERVAL: Duration = Duration::from_secs(2);
+
 /// Result of a health check sent through the channel (bd-j3xz.3.3: includes RTT).
 enum HealthCheckResult {
     /// Health check succeeded with round-trip time in milliseconds.
@@ -86,11 +101,13 @@ pub struct DaqApp {
     run_history_panel: RunHistoryPanel,
     run_comparison_panel: RunComparisonPanel,
     modules_panel: ModulesPanel,
+    state_machines_panel: StateMachinesPanel,
     plan_runner_panel: PlanRunnerPanel,
     scan_builder_panel: ScanBuilderPanel,
     experiment_designer_panel: ExperimentDesignerPanel,
     document_viewer_panel: DocumentViewerPanel,
     instrument_manager_panel: InstrumentManagerPanel,
+    console_panel: InstrumentConsolePanel,
     signal_plotter_panel: SignalPlotterPanel,
     image_viewer_panel: ImageViewerPanel,
     logging_panel: LoggingPanel,
@@ -102,6 +119,12 @@ pub struct DaqApp {
     health_tx: mpsc::Sender<HealthCheckResult>,
     health_rx: mpsc::Receiver<HealthCheckResult>,
 
+    /// Channel for daemon state fetched after a lost connection was restored
+    resync_tx: mpsc::Sender<Result<ResyncSnapshot, String>>,
+    resync_rx: mpsc::Receiver<Result<ResyncSnapshot, String>>,
+    /// A stream broke; resync if the health check finds the connection intact
+    stream_resync_pending: bool,
+
     /// Previous connection state (for detecting transitions)
     was_connected: bool,
 
@@ -122,6 +145,29 @@ pub struct DaqApp {
 
     /// Status bar widget for connection indicator and version display
     status_bar: StatusBar,
+    /// Channel for the daemon's stream statistics shown in the status bar
+    stream_stats_tx: mpsc::Sender<ListStreamStatsResponse>,
+    stream_stats_rx: mpsc::Receiver<ListStreamStatsResponse>,
+    /// Last time stream statistics were requested
+    stream_stats_at: Option<Instant>,
+
+    /// This GUI's daemon session and the other connected users
+    presence: PresenceTracker,
+    /// Remote daemon log stream feeding the logging panel
+    daemon_logs: DaemonLogStream,
+    /// Run completion and alarm notifications
+    notifier: RunNotifier,
+
+    /// Last-known daemon state and actions queued while offline
+    offline_cache: OfflineCache,
+    /// Read-only Instruments view shown while the daemon is unreachable
+    offline_view: OfflineInstrumentsView,
+    /// Schema fetches and queued-action replay
+    offline_sync: OfflineSync,
+    /// Devices whose parameter schemas were fetched on this connection
+    offline_schemas_requested: HashSet<String>,
+    /// Last time the offline cache was updated from the panels
+    offline_snapshot_at: Option<Instant>,
 
     /// Device control panel ID to device info mapping (for dockable device panels)
     device_panel_info: HashMap<usize, DevicePanelInfo>,
@@ -165,6 +211,12 @@ pub struct DaqApp {
 
     /// Cheat sheet visibility state
     show_cheat_sheet: bool,
+
+    /// Keyboard jog/shutter/run control (disarmed at startup)
+    keyboard_control: KeyboardControl,
+
+    /// Gamepad stage jogging, configured per workspace
+    gamepad: GamepadControl,
 }
 
 /// Action to perform on the UI state
@@ -232,6 +284,11 @@ impl From<PersistedPanelInfo> for DeviceInfo {
             is_parameterized: false,
             capabilities: vec![],
             metadata: None,
+            aliases: vec![],
+            tags: vec![],
+            simulated: false,
+            status: None, // Will be updated when daemon connects
+            ui_layout: vec![],
         }
     }
 }
@@ -256,6 +313,8 @@ pub enum Panel {
     SignalPlotter,
     ImageViewer,
     Logs,
+    Console,
+    StateMachines,
     /// Dockable device control panel (uses id to lookup device_id in app state)
     DeviceControl {
         id: usize,
@@ -284,8 +343,19 @@ impl DaqApp {
         // Load or initialize keyboard shortcuts
         let shortcut_manager: ShortcutManager = cc
             .storage
-            .and_then(|s| eframe::get_value(s, "shortcut_manager"))
+            .and_then(|s| eframe::get_value::<ShortcutManager>(s, "shortcut_manager"))
+            .map(ShortcutManager::with_missing_defaults)
             .unwrap_or_default();
+        let keyboard_control = KeyboardControl::new(
+            cc.storage
+                .and_then(|s| eframe::get_value(s, "keyboard_control"))
+                .unwrap_or_default(),
+        );
+        let gamepad = GamepadControl::new(
+            cc.storage
+                .and_then(|s| eframe::get_value(s, "gamepad_profiles"))
+                .unwrap_or_default(),
+        );
 
         // Configure egui style with consistent spacing
         let mut style = (*cc.egui_ctx.style()).clone();
@@ -341,6 +411,8 @@ impl DaqApp {
 
         // Create health check channel
         let (health_tx, health_rx) = mpsc::channel(4);
+        let (resync_tx, resync_rx) = mpsc::channel(1);
+        let (stream_stats_tx, stream_stats_rx) = mpsc::channel(2);
 
         // Load application settings from storage
         let app_settings: crate::settings::AppSettings = cc
@@ -348,6 +420,12 @@ impl DaqApp {
             .and_then(|s| eframe::get_value(s, "app_settings"))
             .unwrap_or_default();
 
+        // Load last-known daemon state for offline mode
+        let offline_cache: OfflineCache = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, "offline_cache"))
+            .unwrap_or_default();
+
         // Load persisted device panel info
         let (
             device_panel_info,
@@ -472,9 +550,13 @@ impl DaqApp {
             );
         }
 
+        let mut connection =
+            ConnectionManager::with_config(app_settings.connection.reconnect_config());
+        connection.set_health_config(app_settings.connection.health_config());
+
         Self {
             client: None,
-            connection: ConnectionManager::new(),
+            connection,
             daemon_address,
             address_input,
             address_error: None,
@@ -490,17 +572,22 @@ impl DaqApp {
             run_history_panel: RunHistoryPanel::default(),
             run_comparison_panel: RunComparisonPanel::default(),
             modules_panel: ModulesPanel::default(),
+            state_machines_panel: StateMachinesPanel::default(),
             plan_runner_panel: PlanRunnerPanel::default(),
             scan_builder_panel: ScanBuilderPanel::default(),
             experiment_designer_panel: ExperimentDesignerPanel::default(),
             document_viewer_panel: DocumentViewerPanel::default(),
             instrument_manager_panel: InstrumentManagerPanel::default(),
+            console_panel: InstrumentConsolePanel::default(),
             signal_plotter_panel: SignalPlotterPanel::new(),
             image_viewer_panel: ImageViewerPanel::new(),
             logging_panel: LoggingPanel::new(),
             runtime,
             health_tx,
             health_rx,
+            resync_tx,
+            resync_rx,
+            stream_resync_pending: false,
             was_connected: false,
             daemon_mode,
             daemon_launcher,
@@ -508,6 +595,17 @@ impl DaqApp {
             log_receiver,
             theme_preference,
             status_bar: StatusBar::new(),
+            stream_stats_tx,
+            stream_stats_rx,
+            stream_stats_at: None,
+            presence: PresenceTracker::default(),
+            daemon_logs: DaemonLogStream::default(),
+            notifier: RunNotifier::default(),
+            offline_cache,
+            offline_view: OfflineInstrumentsView::default(),
+            offline_sync: OfflineSync::default(),
+            offline_schemas_requested: HashSet::new(),
+            offline_snapshot_at: None,
             device_panel_info,
             next_device_panel_id,
             docked_maitai_panels,
@@ -524,6 +622,8 @@ impl DaqApp {
             shortcut_manager,
             cheat_sheet_panel: CheatSheetPanel::new(),
             show_cheat_sheet: false,
+            keyboard_control,
+            gamepad,
         }
     }
 
@@ -578,7 +678,13 @@ impl DaqApp {
 
     /// Disconnect from the daemon
     fn disconnect(&mut self) {
-        self.client = None;
+        self.presence.stop(self.client.take(), &self.runtime);
+        self.daemon_logs.stop();
+        self.notifier.stop();
+        self.keyboard_control.disarm();
+        self.gamepad.reset();
+        self.status_bar.set_presence(Vec::new(), false);
+        self.status_bar.set_stream_stats(None);
         self.daemon_version = None;
         self.connection.disconnect();
         self.logging_panel.connection_status = LogConnectionStatus::Disconnected;
@@ -760,7 +866,38 @@ impl DaqApp {
                         self.ui_actions.push(UiAction::FocusTab(Panel::Modules));
                         ui.close();
                     }
+                    ui.separator();
+
+                    if ui.button("Keyboard Control").clicked() {
+                        self.keyboard_control.window_open = true;
+                        if let Some(client) = self.client.clone() {
+                            self.keyboard_control.refresh_devices(client, &self.runtime);
+                        }
+                        ui.close();
+                    }
+                    if ui.button("Gamepad").clicked() {
+                        self.gamepad.window_open = true;
+                        if let Some(client) = self.client.clone() {
+                            self.gamepad.refresh_devices(client, &self.runtime);
+                        }
+                        ui.close();
+                    }
+                    if ui.button("Keyboard Shortcuts").clicked() {
+                        self.show_cheat_sheet = true;
+                        ui.close();
+                    }
                 });
+
+                if self.keyboard_control.is_armed() {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(220, 60, 60),
+                        format!(
+                            "⌨ KEYBOARD ARMED (step {})",
+                            self.keyboard_control.settings.step()
+                        ),
+                    )
+                    .on_hover_text("Keys move hardware - press Ctrl+K or use View > Keyboard Control to disarm");
+                }
             });
         });
     }
@@ -1179,27 +1316,112 @@ impl DaqApp {
             match result {
                 HealthCheckResult::Success { rtt_ms } => {
                     self.connection.record_health_success(rtt_ms);
+                    if std::mem::take(&mut self.stream_resync_pending) {
+                        self.spawn_resync();
+                    }
                 }
                 HealthCheckResult::Failed(error) => {
                     let should_reconnect = self.connection.record_health_failure(&error);
 
                     if should_reconnect {
-                        // Clear client - connection is stale
-                        self.client = None;
-                        self.daemon_version = None;
-                        self.logging_panel.connection_status = LogConnectionStatus::Connecting;
-                        self.logging_panel.warn(
-                            "Connection",
-                            &format!("Connection lost ({}), reconnecting...", error),
-                        );
-
-                        // Trigger reconnect
-                        self.connection
-                            .trigger_health_reconnect(self.daemon_address.clone(), &self.runtime);
+                        self.connection_lost(&error);
                     }
                 }
             }
         }
+
+        // A stream failing with a transport error is a hint the daemon went away
+        if self.client.is_some()
+            && (self.scan_builder_panel.take_stream_lost()
+                | self.document_viewer_panel.take_stream_lost())
+        {
+            self.connection.check_health_now();
+            self.stream_resync_pending = true;
+        }
+    }
+
+    /// Drop the stale client and start reconnecting with backoff
+    fn connection_lost(&mut self, error: &str) {
+        // Clear client - connection is stale
+        self.client = None;
+        self.presence.stop(None, &self.runtime);
+        self.status_bar.set_stream_stats(None);
+        self.daemon_logs.stop();
+        self.notifier.stop();
+        self.keyboard_control.disarm();
+        self.gamepad.reset();
+        self.daemon_version = None;
+        self.stream_resync_pending = false;
+        self.scan_builder_panel.suspend();
+        self.document_viewer_panel.suspend();
+        self.logging_panel.connection_status = LogConnectionStatus::Connecting;
+        if self.connection.config().enabled {
+            self.logging_panel.warn(
+                "Connection",
+                &format!("Connection lost ({}), reconnecting...", error),
+            );
+        } else {
+            self.logging_panel.error(
+                "Connection",
+                &format!("Connection lost ({}); auto-reconnect is disabled", error),
+            );
+        }
+
+        self.connection
+            .trigger_health_reconnect(self.daemon_address.clone(), &self.runtime);
+    }
+
+    /// Fetch daemon state that went stale while the connection was down
+    fn spawn_resync(&mut self) {
+        let Some(ref client) = self.client else {
+            return;
+        };
+        let mut client = client.clone();
+        let tx = self.resync_tx.clone();
+        self.runtime.spawn(async move {
+            let result = client.resync().await.map_err(|e| e.to_string());
+            let _ = tx.send(result).await;
+        });
+    }
+
+    /// Apply resynchronized daemon state after a reconnect
+    fn poll_resync(&mut self) {
+        let Ok(result) = self.resync_rx.try_recv() else {
+            return;
+        };
+        let snapshot = match result {
+            Ok(snapshot) => {
+                self.logging_panel.info(
+                    "Connection",
+                    &format!("Resynchronized after reconnect: {}", snapshot.summary()),
+                );
+                let known: HashSet<&str> = self
+                    .device_panel_info
+                    .values()
+                    .map(|info| info.device_info.id.as_str())
+                    .collect();
+                for id in snapshot.missing_devices(known) {
+                    self.logging_panel.warn(
+                        "Connection",
+                        &format!("Device '{}' is no longer available on the daemon", id),
+                    );
+                }
+                snapshot
+            }
+            Err(e) => {
+                self.logging_panel.warn(
+                    "Connection",
+                    &format!("Resync after reconnect failed: {}", e),
+                );
+                ResyncSnapshot::default()
+            }
+        };
+
+        if let Some(ref mut client) = self.client {
+            self.scan_builder_panel
+                .resume(client, &self.runtime, snapshot.run_progress.as_ref());
+            self.document_viewer_panel.resume(client, &self.runtime);
+        }
     }
 
     /// Update the logging panel's connection diagnostics from the ConnectionManager (bd-j3xz.3.3).
@@ -1273,9 +1495,11 @@ impl DaqApp {
         self.devices_panel = DevicesPanel::default();
         self.scripts_panel = ScriptsPanel::default();
         self.modules_panel = ModulesPanel::default();
+        self.state_machines_panel = StateMachinesPanel::default();
         self.storage_panel = StoragePanel::default();
         self.run_history_panel = RunHistoryPanel::default();
         self.run_comparison_panel = RunComparisonPanel::default();
+        self.console_panel = InstrumentConsolePanel::default();
 
         // Reset InstrumentManagerPanel to trigger auto-refresh on reconnect
         // (keeps panel state like selected device, but clears device list and refresh flag)
@@ -1283,6 +1507,280 @@ impl DaqApp {
 
         self.logging_panel
             .info("Connection", "Connected - panels will refresh data");
+
+        self.signal_plotter_panel
+            .set_marker_author(&self.app_settings.connection.session_name);
+        self.storage_panel
+            .set_author(&self.app_settings.connection.session_name);
+
+        // Announce this GUI to other users of the daemon
+        if let Some(ref client) = self.client {
+            let connection = &self.app_settings.connection;
+            self.presence.start(
+                client.clone(),
+                &self.runtime,
+                connection.session_name.clone(),
+                connection.session_role.to_proto(),
+            );
+        }
+        self.restart_daemon_logs();
+        if let Some(ref client) = self.client {
+            self.notifier.start(client.clone(), &self.runtime);
+        }
+        self.gamepad.set_workspace(self.daemon_address.as_str());
+        self.replay_offline_queue();
+        if self.connection.take_resync() {
+            self.spawn_resync();
+        }
+    }
+
+    /// Apply actions queued while offline, in order
+    fn replay_offline_queue(&mut self) {
+        self.offline_schemas_requested.clear();
+        self.offline_snapshot_at = None;
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        let daemon = self.daemon_address.as_str().to_string();
+        if let Some(actions) = self.offline_cache.take_queue_for(&daemon) {
+            self.logging_panel.info(
+                "Offline",
+                &format!("Applying {} action(s) queued while offline", actions.len()),
+            );
+            self.offline_sync.replay(client, &self.runtime, actions);
+        } else if !self.offline_cache.queue.is_empty() {
+            self.logging_panel.warn(
+                "Offline",
+                &format!(
+                    "{} action(s) queued for {} will be discarded (connected to {})",
+                    self.offline_cache.queue.len(),
+                    self.offline_cache.daemon,
+                    daemon
+                ),
+            );
+        }
+    }
+
+    /// Refresh the per-stream statistics shown in the status bar
+    fn poll_stream_stats(&mut self) {
+        while let Ok(stats) = self.stream_stats_rx.try_recv() {
+            if self.client.is_some() {
+                self.status_bar.set_stream_stats(Some(stats));
+            }
+        }
+
+        let Some(mut client) = self.client.clone() else {
+            return;
+        };
+        if self
+            .stream_stats_at
+            .is_some_and(|at| at.elapsed() < STREAM_STATS_INTERVAL)
+        {
+            return;
+        }
+        self.stream_stats_at = Some(Instant::now());
+        let tx = self.stream_stats_tx.clone();
+        self.runtime.spawn(async move {
+            // Daemons without ListStreamStats leave the status bar unchanged
+            if let Ok(stats) = client.list_stream_stats().await {
+                let _ = tx.send(stats).await;
+            }
+        });
+    }
+
+    /// Keep the offline cache current and report replayed actions
+    fn poll_offline_cache(&mut self) {
+        for message in self.offline_sync.poll() {
+            match message {
+                OfflineSyncMessage::Schemas {
+                    device_id,
+                    parameters,
+                } => {
+                    self.offline_cache.set_parameters(&device_id, parameters);
+                }
+                OfflineSyncMessage::Replayed { action, result } => match result {
+                    ReplayResult::Applied => {
+                        self.logging_panel
+                            .info("Offline", &format!("Applied queued action: {}", action));
+                    }
+                    ReplayResult::NeedsConfirmation => {
+                        self.logging_panel.warn(
+                            "Offline",
+                            &format!(
+                                "Not applied, dangerous parameter needs confirmation: {}",
+                                action
+                            ),
+                        );
+                    }
+                    ReplayResult::Failed(error) => {
+                        self.logging_panel.error(
+                            "Offline",
+                            &format!("Queued action failed: {}: {}", action, error),
+                        );
+                    }
+                },
+            }
+        }
+
+        if !self.connection.state().is_connected()
+            || self
+                .offline_snapshot_at
+                .is_some_and(|at| at.elapsed() < OFFLINE_SNAPSHOT_INTERVAL)
+        {
+            return;
+        }
+        self.offline_snapshot_at = Some(Instant::now());
+        self.instrument_manager_panel
+            .record_offline_state(&mut self.offline_cache, self.daemon_address.as_str());
+
+        // Fetch each device's parameter schema once per connection
+        let missing: Vec<String> = self
+            .offline_cache
+            .devices
+            .iter()
+            .filter(|d| !self.offline_schemas_requested.contains(&d.id))
+            .map(|d| d.id.clone())
+            .collect();
+        if let (false, Some(client)) = (missing.is_empty(), self.client.clone()) {
+            self.offline_schemas_requested
+                .extend(missing.iter().cloned());
+            self.offline_sync
+                .fetch_schemas(client, &self.runtime, missing);
+        }
+    }
+
+    /// Announce finished runs and alarms
+    fn poll_notifications(&mut self, ctx: &egui::Context) {
+        let notifications = self.notifier.poll();
+        if notifications.is_empty() {
+            return;
+        }
+        let settings = &self.app_settings.notifications;
+        let focused = ctx.input(|i| i.viewport().focused.unwrap_or(true));
+        let mut request_attention = false;
+
+        for notification in notifications {
+            let message = format!("{} - {}", notification.title, notification.body);
+            let level = match notification.kind {
+                NotificationKind::RunCompleted => {
+                    self.logging_panel.info("Runs", &message);
+                    StatusLevel::Success
+                }
+                NotificationKind::RunFailed => {
+                    self.logging_panel.error("Runs", &message);
+                    StatusLevel::Error
+                }
+                NotificationKind::Alarm => {
+                    self.logging_panel.warn("Alarms", &message);
+                    StatusLevel::Warning
+                }
+            };
+            self.status_bar.set_persistent_status(message, level);
+
+            let preference = settings.preference(notification.kind);
+            if !preference.desktop || (settings.only_when_unfocused && focused) {
+                continue;
+            }
+            request_attention = true;
+            self.show_desktop_notification(notification, preference.sound);
+        }
+
+        if request_attention {
+            ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(
+                egui::UserAttentionType::Informational,
+            ));
+        }
+    }
+
+    #[cfg(feature = "desktop_notifications")]
+    fn show_desktop_notification(&self, notification: Notification, sound: bool) {
+        // The notification service call can block (D-Bus round trip)
+        self.runtime.spawn_blocking(move || {
+            crate::notifications::show_desktop(&notification, sound);
+        });
+    }
+
+    #[cfg(not(feature = "desktop_notifications"))]
+    fn show_desktop_notification(&self, _notification: Notification, _sound: bool) {}
+
+    /// (Re)start the daemon log stream with the logging panel's filters
+    fn restart_daemon_logs(&mut self) {
+        self.daemon_logs.stop();
+        self.logging_panel.set_daemon_stream_status(None);
+        if let (Some(client), Some(request)) =
+            (self.client.clone(), self.logging_panel.daemon_log_request())
+        {
+            self.daemon_logs.start(client, &self.runtime, request);
+        }
+    }
+
+    /// Feed daemon log records into the logging panel
+    fn poll_daemon_logs(&mut self) {
+        if self.logging_panel.take_daemon_filter_change() {
+            self.restart_daemon_logs();
+        }
+        for message in self.daemon_logs.poll() {
+            match message {
+                DaemonLogMessage::Record(record) => {
+                    self.logging_panel.log_daemon_record(&record);
+                }
+                DaemonLogMessage::Unsupported => {
+                    self.logging_panel.set_daemon_stream_status(Some(
+                        "Daemon does not support log streaming".to_string(),
+                    ));
+                }
+                DaemonLogMessage::Failed(error) => {
+                    self.logging_panel.warn("Connection", &error);
+                    self.logging_panel
+                        .set_daemon_stream_status(Some("Stream stopped".to_string()));
+                }
+            }
+        }
+    }
+
+    /// Apply presence updates and handle session revocation
+    fn poll_presence(&mut self) {
+        match self.presence.poll() {
+            Some(PresenceNotice::Revoked { reason }) => {
+                let message = if reason.is_empty() {
+                    "Session revoked by an admin".to_string()
+                } else {
+                    format!("Session revoked by an admin: {}", reason)
+                };
+                self.logging_panel.error("Session", &message);
+                self.disconnect();
+                self.status_bar
+                    .set_persistent_status(message, StatusLevel::Error);
+                return;
+            }
+            Some(PresenceNotice::Failed(error)) => {
+                self.logging_panel.warn("Session", &error);
+            }
+            None => {}
+        }
+
+        let is_admin = self
+            .presence
+            .own_session()
+            .is_some_and(|own| own.role() == protocol::daq::SessionRole::Admin);
+        self.status_bar
+            .set_presence(self.presence.others(), is_admin);
+
+        if let Some(target) = self.status_bar.take_revoke_request() {
+            let (Some(mut client), Some(own)) = (self.client.clone(), self.presence.own_session())
+            else {
+                return;
+            };
+            let admin_session_id = own.session_id.clone();
+            self.runtime.spawn(async move {
+                if let Err(e) = client
+                    .revoke_session(&admin_session_id, &target, "Revoked from GUI")
+                    .await
+                {
+                    tracing::error!("Failed to revoke session {}: {}", target, e);
+                }
+            });
+        }
     }
 
     /// Detect connection state transitions and handle them
@@ -1311,6 +1809,70 @@ impl DaqApp {
         // Note: Other global shortcuts (OpenSettings, SaveCurrent) will be handled
         // by specific panels or settings UI when implemented
     }
+
+    /// Jog stages from the gamepad and show the Gamepad window
+    fn update_gamepad(&mut self, ctx: &egui::Context) {
+        for error in self
+            .gamepad
+            .update(ctx, self.client.as_ref(), &self.runtime)
+        {
+            self.logging_panel.error("Gamepad", &error);
+        }
+        if self.gamepad.window_open && self.gamepad.show(ctx) {
+            if let Some(client) = self.client.clone() {
+                self.gamepad.refresh_devices(client, &self.runtime);
+            }
+        }
+    }
+
+    /// Arm/disarm keyboard control and send jog, shutter and run commands
+    fn handle_keyboard_control(&mut self, ctx: &egui::Context) {
+        let was_armed = self.keyboard_control.is_armed();
+        let commands = self
+            .keyboard_control
+            .handle_input(ctx, &self.shortcut_manager);
+        if self.keyboard_control.is_armed() != was_armed {
+            let state = if was_armed { "disarmed" } else { "armed" };
+            self.logging_panel
+                .info("Keyboard", &format!("Keyboard control {}", state));
+        }
+
+        if !commands.is_empty() {
+            match self.client.clone() {
+                Some(client) => {
+                    for command in commands {
+                        self.keyboard_control
+                            .execute(command, client.clone(), &self.runtime);
+                    }
+                }
+                None => {
+                    self.status_bar
+                        .set_status("Keyboard control: not connected", StatusLevel::Warning);
+                }
+            }
+        }
+
+        for message in self.keyboard_control.poll() {
+            match message {
+                KeyboardControlMessage::Done(done) => {
+                    self.status_bar.set_status(done, StatusLevel::Info);
+                }
+                KeyboardControlMessage::Failed(error) => {
+                    self.logging_panel.error("Keyboard", &error);
+                    self.status_bar.set_status(error, StatusLevel::Error);
+                }
+                KeyboardControlMessage::Devices(_) => {}
+            }
+        }
+
+        if self.keyboard_control.window_open
+            && self.keyboard_control.show(ctx, &self.shortcut_manager)
+        {
+            if let Some(client) = self.client.clone() {
+                self.keyboard_control.refresh_devices(client, &self.runtime);
+            }
+        }
+    }
 }
 
 struct DaqTabViewer<'a> {
@@ -1341,6 +1903,8 @@ impl<'a> TabViewer for DaqTabViewer<'a> {
             Panel::SignalPlotter => format!("{} Signal Plotter", icons::nav::SIGNAL_PLOTTER).into(),
             Panel::ImageViewer => format!("{} Image Viewer", icons::nav::IMAGE_VIEWER).into(),
             Panel::Logs => format!("{} Logs", icons::nav::LOGGING).into(),
+            Panel::Console => format!("{} Console", icons::nav::CONSOLE).into(),
+            Panel::StateMachines => "🔀 State Machines".into(),
             Panel::DeviceControl { id } => {
                 // Look up device name from the panel ID mapping
                 if let Some(info) = self.app.device_panel_info.get(id) {
@@ -1368,11 +1932,18 @@ impl<'a> TabViewer for DaqTabViewer<'a> {
         match tab {
             Panel::Nav => self.render_nav(ui),
             Panel::GettingStarted => self.app.getting_started_panel.ui(ui),
-            Panel::Instruments => self.app.instrument_manager_panel.ui(
-                ui,
-                self.app.client.as_mut(),
-                &self.app.runtime,
-            ),
+            Panel::Instruments => {
+                // Last-known state instead of a blank screen while offline
+                if self.app.client.is_none() && self.app.offline_cache.has_state() {
+                    self.app.offline_view.ui(ui, &mut self.app.offline_cache);
+                } else {
+                    self.app.instrument_manager_panel.ui(
+                        ui,
+                        self.app.client.as_mut(),
+                        &self.app.runtime,
+                    );
+                }
+            }
             Panel::Devices => {
                 self.app
                     .devices_panel
@@ -1430,7 +2001,9 @@ impl<'a> TabViewer for DaqTabViewer<'a> {
             }
             Panel::SignalPlotter => {
                 self.app.signal_plotter_panel.drain_updates();
-                self.app.signal_plotter_panel.ui(ui);
+                self.app
+                    .signal_plotter_panel
+                    .ui(ui, self.app.client.as_mut(), &self.app.runtime);
             }
             Panel::ImageViewer => {
                 self.app
@@ -1438,6 +2011,24 @@ impl<'a> TabViewer for DaqTabViewer<'a> {
                     .ui(ui, self.app.client.as_mut(), &self.app.runtime)
             }
             Panel::Logs => self.app.logging_panel.ui(ui),
+            Panel::StateMachines => {
+                self.app
+                    .state_machines_panel
+                    .ui(ui, self.app.client.as_mut(), &self.app.runtime)
+            }
+            Panel::Console => {
+                let session_id = self
+                    .app
+                    .presence
+                    .own_session()
+                    .map(|s| s.session_id.clone());
+                self.app.console_panel.ui(
+                    ui,
+                    self.app.client.as_mut(),
+                    &self.app.runtime,
+                    session_id.as_deref(),
+                );
+            }
             Panel::DeviceControl { id } => {
                 self.render_device_control(ui, *id);
             }
@@ -1527,6 +2118,8 @@ impl<'a> DaqTabViewer<'a> {
             Self::section_label(ui, "System");
             self.nav_button(ui, icons::nav::MODULES, "Modules", Panel::Modules);
             self.nav_button(ui, icons::nav::LOGGING, "Logs", Panel::Logs);
+            self.nav_button(ui, icons::nav::CONSOLE, "Console", Panel::Console);
+            self.nav_button(ui, "🔀", "State Machines", Panel::StateMachines);
 
             ui.separator();
             ui.add_space(layout::SECTION_SPACING / 2.0);
@@ -1616,6 +2209,12 @@ impl eframe::App for DaqApp {
         self.poll_connect_results(ctx);
         self.maybe_spawn_health_check();
         self.poll_health_checks();
+        self.poll_resync();
+        self.poll_presence();
+        self.poll_stream_stats();
+        self.poll_daemon_logs();
+        self.poll_offline_cache();
+        self.poll_notifications(ctx);
         self.update_connection_diagnostics(); // bd-j3xz.3.3
 
         // Process auto-connect state machine
@@ -1626,6 +2225,8 @@ impl eframe::App for DaqApp {
 
         // Check global keyboard shortcuts
         self.check_global_shortcuts(ctx);
+        self.handle_keyboard_control(ctx);
+        self.update_gamepad(ctx);
 
         // Handle additional keyboard shortcuts (Ctrl+, opens settings)
         ctx.input(|i| {
@@ -1647,6 +2248,10 @@ impl eframe::App for DaqApp {
             }
             // Font and UI scale changes will be applied on next frame
             ctx.set_zoom_factor(self.app_settings.appearance.ui_scale);
+            self.connection
+                .set_config(self.app_settings.connection.reconnect_config());
+            self.connection
+                .set_health_config(self.app_settings.connection.health_config());
         }
 
         let error_count = self.connection.health_status().total_errors;
@@ -1801,6 +2406,8 @@ impl eframe::App for DaqApp {
 
         // Persist keyboard shortcuts
         eframe::set_value(storage, "shortcut_manager", &self.shortcut_manager);
+        eframe::set_value(storage, "keyboard_control", &self.keyboard_control.settings);
+        eframe::set_value(storage, "gamepad_profiles", self.gamepad.profiles());
 
         // Persist device panel info for layout restoration
         let persisted_panels: HashMap<usize, PersistedPanelInfo> = self
@@ -1810,6 +2417,13 @@ impl eframe::App for DaqApp {
             .collect();
         eframe::set_value(storage, "device_panel_info", &persisted_panels);
         eframe::set_value(storage, "next_device_panel_id", &self.next_device_panel_id);
+
+        // Persist last-known daemon state and queued actions for offline mode
+        if self.connection.state().is_connected() {
+            self.instrument_manager_panel
+                .record_offline_state(&mut self.offline_cache, self.daemon_address.as_str());
+        }
+        eframe::set_value(storage, "offline_cache", &self.offline_cache);
     }
 }
 
@@ -0,0 +1,118 @@
+//! Remote daemon log streaming for the GUI.
+//!
+//! Follows the daemon's `StreamLogs` RPC in the background so the logging
+//! panel can show daemon and driver logs next to the GUI's own, without
+//! shelling into the rack machine.
+
+use futures::StreamExt;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use client::DaqClient;
+use protocol::daq::{LogRecord, StreamLogsRequest};
+
+/// Messages from the background stream task.
+pub enum DaemonLogMessage {
+    /// A log record from the daemon
+    Record(LogRecord),
+    /// The daemon does not offer log streaming (older version)
+    Unsupported,
+    /// The stream ended or failed
+    Failed(String),
+}
+
+/// Owns the background task following the daemon log stream.
+pub struct DaemonLogStream {
+    tx: mpsc::Sender<DaemonLogMessage>,
+    rx: mpsc::Receiver<DaemonLogMessage>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl Default for DaemonLogStream {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel(1024);
+        Self { tx, rx, task: None }
+    }
+}
+
+impl DaemonLogStream {
+    /// Start following daemon logs with the given filters (restarts if running).
+    pub fn start(
+        &mut self,
+        client: DaqClient,
+        runtime: &tokio::runtime::Runtime,
+        request: StreamLogsRequest,
+    ) {
+        self.stop();
+        let tx = self.tx.clone();
+        self.task = Some(runtime.spawn(run_stream(client, tx, request)));
+    }
+
+    /// Stop following daemon logs, discarding undelivered records.
+    pub fn stop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        while self.rx.try_recv().is_ok() {}
+    }
+
+    /// Drain messages from the background task.
+    pub fn poll(&mut self) -> Vec<DaemonLogMessage> {
+        let mut messages = Vec::new();
+        while let Ok(message) = self.rx.try_recv() {
+            if matches!(
+                message,
+                DaemonLogMessage::Unsupported | DaemonLogMessage::Failed(_)
+            ) {
+                self.task = None;
+            }
+            messages.push(message);
+        }
+        messages
+    }
+}
+
+async fn run_stream(
+    mut client: DaqClient,
+    tx: mpsc::Sender<DaemonLogMessage>,
+    request: StreamLogsRequest,
+) {
+    let mut stream = match client.stream_logs(request).await {
+        Ok(stream) => Box::pin(stream),
+        Err(e) => {
+            let unsupported = e
+                .downcast_ref::<tonic::Status>()
+                .is_some_and(|status| status.code() == tonic::Code::Unimplemented);
+            let message = if unsupported {
+                DaemonLogMessage::Unsupported
+            } else {
+                DaemonLogMessage::Failed(format!("Daemon log stream failed: {}", e))
+            };
+            let _ = tx.send(message).await;
+            return;
+        }
+    };
+
+    while let Some(item) = stream.next().await {
+        let message = match item {
+            Ok(record) => DaemonLogMessage::Record(record),
+            Err(status) => {
+                let _ = tx
+                    .send(DaemonLogMessage::Failed(format!(
+                        "Daemon log stream failed: {}",
+                        status.message()
+                    )))
+                    .await;
+                return;
+            }
+        };
+        if tx.send(message).await.is_err() {
+            return;
+        }
+    }
+    let _ = tx
+        .send(DaemonLogMessage::Failed(
+            "Daemon log stream closed".to_string(),
+        ))
+        .await;
+}
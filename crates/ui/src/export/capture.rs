@@ -0,0 +1,95 @@
+//! PNG export of an on-screen region (plots, image views)
+//!
+//! egui delivers screenshots asynchronously: [`ScreenCapture::request`] asks
+//! the viewport for one, and [`ScreenCapture::poll`] crops the matching reply
+//! to the widget's rectangle and writes it on a later frame.
+
+use super::{write_png_rgba, FigureMetadata};
+use eframe::egui;
+use std::path::PathBuf;
+
+/// Identifies which capture a screenshot reply belongs to
+struct CaptureToken(egui::Id);
+
+struct PendingCapture {
+    id: egui::Id,
+    rect: egui::Rect,
+    path: PathBuf,
+    metadata: FigureMetadata,
+}
+
+/// One outstanding screenshot export
+#[derive(Default)]
+pub struct ScreenCapture {
+    pending: Option<PendingCapture>,
+}
+
+impl ScreenCapture {
+    /// Request a screenshot of `rect` to be written to `path`
+    ///
+    /// `id` must be unique among widgets capturing at the same time.
+    pub fn request(
+        &mut self,
+        ctx: &egui::Context,
+        id: egui::Id,
+        rect: egui::Rect,
+        path: PathBuf,
+        metadata: FigureMetadata,
+    ) {
+        ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(egui::UserData::new(
+            CaptureToken(id),
+        )));
+        self.pending = Some(PendingCapture {
+            id,
+            rect,
+            path,
+            metadata,
+        });
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Write the capture once its screenshot has arrived
+    ///
+    /// Returns the written path, or an error message.
+    pub fn poll(&mut self, ctx: &egui::Context) -> Option<Result<PathBuf, String>> {
+        let pending = self.pending.as_ref()?;
+        let image = ctx.input(|input| {
+            input.raw.events.iter().find_map(|event| match event {
+                egui::Event::Screenshot {
+                    user_data, image, ..
+                } if user_data
+                    .data
+                    .as_ref()
+                    .and_then(|data| data.downcast_ref::<CaptureToken>())
+                    .is_some_and(|token| token.0 == pending.id) =>
+                {
+                    Some(image.clone())
+                }
+                _ => None,
+            })
+        })?;
+
+        let pending = self.pending.take()?;
+        let region = image.region(&pending.rect, Some(ctx.pixels_per_point()));
+        let [width, height] = region.size;
+        let rgba: Vec<u8> = region
+            .pixels
+            .iter()
+            .flat_map(|pixel| pixel.to_srgba_unmultiplied())
+            .collect();
+        Some(
+            write_png_rgba(
+                &pending.path,
+                width as u32,
+                height as u32,
+                &rgba,
+                &pending.metadata,
+            )
+            .map(|()| pending.path)
+            .map_err(|e| e.to_string()),
+        )
+    }
+}
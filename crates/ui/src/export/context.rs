@@ -0,0 +1,76 @@
+//! Acquisition context gathered from the daemon for figure exports
+
+use super::FigureMetadata;
+use client::DaqClient;
+
+/// Run and device settings at the time of an export
+#[derive(Debug, Clone, Default)]
+pub struct ExportContext {
+    /// UID of the run in progress (`None` = idle or offline)
+    pub run_uid: Option<String>,
+    /// Readable parameters per device: (device_id, [(name, value with units)])
+    pub device_settings: Vec<(String, Vec<(String, String)>)>,
+}
+
+impl ExportContext {
+    /// Run UID and one `device.parameter` entry per setting
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let run = self
+            .run_uid
+            .iter()
+            .map(|uid| ("Run UID".to_string(), uid.clone()));
+        let settings = self
+            .device_settings
+            .iter()
+            .flat_map(|(device_id, settings)| {
+                settings
+                    .iter()
+                    .map(move |(name, value)| (format!("{}.{}", device_id, name), value.clone()))
+            });
+        run.chain(settings).collect()
+    }
+
+    /// Add [`Self::entries`] to figure metadata
+    pub fn apply(&self, metadata: FigureMetadata) -> FigureMetadata {
+        self.entries()
+            .into_iter()
+            .fold(metadata, |metadata, (key, value)| metadata.with(key, value))
+    }
+}
+
+/// Query the active run and the readable parameters of `device_ids`
+///
+/// Best effort: parameters that fail to read are left out, so an export
+/// never fails because one device is busy.
+pub async fn fetch_export_context(client: &mut DaqClient, device_ids: &[String]) -> ExportContext {
+    let run_uid = client
+        .get_engine_status()
+        .await
+        .ok()
+        .and_then(|status| status.current_run_uid)
+        .filter(|uid| !uid.is_empty());
+
+    let mut device_settings = Vec::new();
+    for device_id in device_ids {
+        let Ok(descriptors) = client.list_parameters(device_id).await else {
+            continue;
+        };
+        let mut settings = Vec::new();
+        for descriptor in descriptors.iter().filter(|d| d.readable) {
+            if let Ok(value) = client.get_parameter(device_id, &descriptor.name).await {
+                let text = if value.units.is_empty() {
+                    value.value
+                } else {
+                    format!("{} {}", value.value, value.units)
+                };
+                settings.push((descriptor.name.clone(), text));
+            }
+        }
+        device_settings.push((device_id.clone(), settings));
+    }
+
+    ExportContext {
+        run_uid,
+        device_settings,
+    }
+}
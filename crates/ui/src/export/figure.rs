@@ -0,0 +1,441 @@
+//! Figure export (PNG, SVG) with embedded acquisition metadata
+//!
+//! Exported figures carry the context needed to reproduce them: run UID,
+//! channels, time range and the settings of the devices involved.
+//! - PNG: one `iTXt` chunk per metadata entry (shown by most image tools)
+//! - SVG: a `<metadata>` element plus a `<desc>` summary
+//! - CSV: `#` comment lines (see [`write_metadata_comments`](super::write_metadata_comments))
+
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+
+/// Output format of a figure export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FigureFormat {
+    Png,
+    Svg,
+    Csv,
+}
+
+impl FigureFormat {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FigureFormat::Png => "PNG image",
+            FigureFormat::Svg => "SVG vector graphic",
+            FigureFormat::Csv => "CSV data",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            FigureFormat::Png => "png",
+            FigureFormat::Svg => "svg",
+            FigureFormat::Csv => "csv",
+        }
+    }
+}
+
+/// Ordered key/value context embedded in an exported figure
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FigureMetadata {
+    entries: Vec<(String, String)>,
+}
+
+impl FigureMetadata {
+    /// Metadata with exporter, export type and timestamp
+    pub fn new(export_type: &str) -> Self {
+        Self::default()
+            .with("Exported by", "rust-daq GUI")
+            .with("Export type", export_type)
+            .with("Timestamp", chrono::Local::now().to_rfc3339())
+    }
+
+    /// Append an entry (empty values are skipped)
+    pub fn with(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        let value = value.into();
+        if !value.is_empty() {
+            self.entries.push((key.into(), value));
+        }
+        self
+    }
+
+    /// Append the displayed time range
+    pub fn with_time_range(self, start_s: f64, end_s: f64) -> Self {
+        self.with("Time range (s)", format!("{:.3} – {:.3}", start_s, end_s))
+    }
+
+    pub fn entries(&self) -> &[(String, String)] {
+        &self.entries
+    }
+
+    /// Entries as borrowed pairs, for CSV comment headers
+    pub fn as_pairs(&self) -> Vec<(&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect()
+    }
+}
+
+/// Write an RGBA8 image (e.g. a screenshot of a plot) as PNG
+pub fn write_png_rgba<P: AsRef<Path>>(
+    path: P,
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+    metadata: &FigureMetadata,
+) -> io::Result<()> {
+    write_png(
+        path.as_ref(),
+        width,
+        height,
+        png::ColorType::Rgba,
+        png::BitDepth::Eight,
+        rgba,
+        metadata,
+    )
+}
+
+/// Write raw camera pixels as 16-bit grayscale PNG (values are not rescaled)
+pub fn write_png_gray16<P: AsRef<Path>>(
+    path: P,
+    width: u32,
+    height: u32,
+    pixels: &[u16],
+    metadata: &FigureMetadata,
+) -> io::Result<()> {
+    let bytes: Vec<u8> = pixels.iter().flat_map(|p| p.to_be_bytes()).collect();
+    write_png(
+        path.as_ref(),
+        width,
+        height,
+        png::ColorType::Grayscale,
+        png::BitDepth::Sixteen,
+        &bytes,
+        metadata,
+    )
+}
+
+fn write_png(
+    path: &Path,
+    width: u32,
+    height: u32,
+    color: png::ColorType,
+    depth: png::BitDepth,
+    data: &[u8],
+    metadata: &FigureMetadata,
+) -> io::Result<()> {
+    let file = File::create(path)?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(color);
+    encoder.set_depth(depth);
+    for (key, value) in metadata.entries() {
+        encoder
+            .add_itxt_chunk(png_keyword(key), value.clone())
+            .map_err(io::Error::other)?;
+    }
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(data).map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)
+}
+
+/// PNG keywords are 1-79 Latin-1 characters without leading/trailing spaces
+fn png_keyword(key: &str) -> String {
+    let keyword: String = key
+        .chars()
+        .map(|c| if c.is_ascii_graphic() { c } else { ' ' })
+        .take(79)
+        .collect();
+    let keyword = keyword.trim();
+    if keyword.is_empty() {
+        "Comment".to_string()
+    } else {
+        keyword.to_string()
+    }
+}
+
+/// One line of an SVG plot
+#[derive(Debug, Clone)]
+pub struct SvgSeries {
+    pub label: String,
+    pub color: [u8; 3],
+    pub points: Vec<(f64, f64)>,
+}
+
+/// Line plot rendered to SVG
+#[derive(Debug, Clone, Default)]
+pub struct SvgPlot {
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+    pub x_range: (f64, f64),
+    pub y_range: (f64, f64),
+    pub series: Vec<SvgSeries>,
+    /// Vertical markers: (x, text)
+    pub markers: Vec<(f64, String)>,
+}
+
+const SVG_WIDTH: f64 = 800.0;
+const SVG_HEIGHT: f64 = 450.0;
+const SVG_MARGIN: f64 = 60.0;
+
+impl SvgPlot {
+    /// Data range of all series, padded by 5% in y
+    pub fn fit_ranges(&mut self) {
+        let points = self.series.iter().flat_map(|s| s.points.iter());
+        let (mut x_min, mut x_max, mut y_min, mut y_max) = (
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        );
+        for &(x, y) in points.filter(|(x, y)| x.is_finite() && y.is_finite()) {
+            x_min = x_min.min(x);
+            x_max = x_max.max(x);
+            y_min = y_min.min(y);
+            y_max = y_max.max(y);
+        }
+        if x_min > x_max {
+            return;
+        }
+        let pad = ((y_max - y_min) * 0.05).max(1e-12);
+        self.x_range = (x_min, x_max);
+        self.y_range = (y_min - pad, y_max + pad);
+    }
+
+    /// Render the plot as a standalone SVG document
+    pub fn render(&self, metadata: &FigureMetadata) -> String {
+        let (x0, x1) = widen(self.x_range);
+        let (y0, y1) = widen(self.y_range);
+        let plot_w = SVG_WIDTH - 2.0 * SVG_MARGIN;
+        let plot_h = SVG_HEIGHT - 2.0 * SVG_MARGIN;
+        let sx = |x: f64| SVG_MARGIN + (x - x0) / (x1 - x0) * plot_w;
+        let sy = |y: f64| SVG_HEIGHT - SVG_MARGIN - (y - y0) / (y1 - y0) * plot_h;
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="12">"#,
+            w = SVG_WIDTH,
+            h = SVG_HEIGHT
+        );
+        let _ = writeln!(svg, "<title>{}</title>", xml_escape(&self.title));
+        let summary: Vec<String> = metadata
+            .entries()
+            .iter()
+            .map(|(k, v)| format!("{}: {}", k, v))
+            .collect();
+        let _ = writeln!(svg, "<desc>{}</desc>", xml_escape(&summary.join("\n")));
+        svg.push_str("<metadata>\n<daq:export xmlns:daq=\"urn:rust-daq:export\">\n");
+        for (key, value) in metadata.entries() {
+            let _ = writeln!(
+                svg,
+                r#"<daq:entry key="{}">{}</daq:entry>"#,
+                xml_escape(key),
+                xml_escape(value)
+            );
+        }
+        svg.push_str("</daq:export>\n</metadata>\n");
+        let _ = writeln!(
+            svg,
+            r#"<rect width="100%" height="100%" fill="white"/>
+<rect x="{x}" y="{y}" width="{w}" height="{h}" fill="none" stroke="black"/>"#,
+            x = SVG_MARGIN,
+            y = SVG_MARGIN,
+            w = plot_w,
+            h = plot_h
+        );
+
+        // Axis ticks
+        for i in 0..=4 {
+            let fraction = f64::from(i) / 4.0;
+            let (x, y) = (x0 + fraction * (x1 - x0), y0 + fraction * (y1 - y0));
+            let _ = writeln!(
+                svg,
+                r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">{}</text>"#,
+                sx(x),
+                SVG_HEIGHT - SVG_MARGIN + 16.0,
+                format_tick(x)
+            );
+            let _ = writeln!(
+                svg,
+                r#"<text x="{:.1}" y="{:.1}" text-anchor="end">{}</text>"#,
+                SVG_MARGIN - 6.0,
+                sy(y) + 4.0,
+                format_tick(y)
+            );
+        }
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">{}</text>
+<text x="14" y="{:.1}" text-anchor="middle" transform="rotate(-90 14 {:.1})">{}</text>
+<text x="{:.1}" y="24" text-anchor="middle" font-size="14">{}</text>"#,
+            SVG_WIDTH / 2.0,
+            SVG_HEIGHT - 16.0,
+            xml_escape(&self.x_label),
+            SVG_HEIGHT / 2.0,
+            SVG_HEIGHT / 2.0,
+            xml_escape(&self.y_label),
+            SVG_WIDTH / 2.0,
+            xml_escape(&self.title)
+        );
+
+        for series in &self.series {
+            let points: Vec<String> = series
+                .points
+                .iter()
+                .filter(|(x, y)| x.is_finite() && y.is_finite() && *x >= x0 && *x <= x1)
+                .map(|&(x, y)| format!("{:.2},{:.2}", sx(x), sy(y)))
+                .collect();
+            let [r, g, b] = series.color;
+            let _ = writeln!(
+                svg,
+                r#"<polyline fill="none" stroke="rgb({},{},{})" stroke-width="1.5" points="{}"><title>{}</title></polyline>"#,
+                r,
+                g,
+                b,
+                points.join(" "),
+                xml_escape(&series.label)
+            );
+        }
+
+        for (x, text) in self.markers.iter().filter(|(x, _)| *x >= x0 && *x <= x1) {
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{x:.1}" y1="{top}" x2="{x:.1}" y2="{bottom}" stroke="gray" stroke-dasharray="4 4"/>
+<text x="{tx:.1}" y="{ty}">{text}</text>"#,
+                x = sx(*x),
+                top = SVG_MARGIN,
+                bottom = SVG_HEIGHT - SVG_MARGIN,
+                tx = sx(*x) + 3.0,
+                ty = SVG_MARGIN + 12.0,
+                text = xml_escape(text)
+            );
+        }
+
+        // Legend
+        for (i, series) in self.series.iter().enumerate() {
+            let y = SVG_MARGIN + 16.0 + 16.0 * i as f64;
+            let [r, g, b] = series.color;
+            let _ = writeln!(
+                svg,
+                r#"<text x="{:.1}" y="{:.1}" text-anchor="end" fill="rgb({},{},{})">{}</text>"#,
+                SVG_WIDTH - SVG_MARGIN - 6.0,
+                y,
+                r,
+                g,
+                b,
+                xml_escape(&series.label)
+            );
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// Write a plot as SVG
+pub fn write_svg<P: AsRef<Path>>(
+    path: P,
+    plot: &SvgPlot,
+    metadata: &FigureMetadata,
+) -> io::Result<()> {
+    std::fs::write(path, plot.render(metadata))
+}
+
+/// Avoid a zero-width range (single point or flat line)
+fn widen((min, max): (f64, f64)) -> (f64, f64) {
+    if (max - min).abs() < f64::EPSILON {
+        (min - 0.5, max + 0.5)
+    } else {
+        (min, max)
+    }
+}
+
+fn format_tick(value: f64) -> String {
+    if value != 0.0 && (value.abs() >= 1e4 || value.abs() < 1e-2) {
+        format!("{:.2e}", value)
+    } else {
+        format!("{:.3}", value)
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> FigureMetadata {
+        FigureMetadata::new("Signal plot")
+            .with("Run UID", "run-42")
+            .with("Empty", "")
+            .with_time_range(1.0, 2.5)
+    }
+
+    #[test]
+    fn test_metadata_skips_empty_values() {
+        let metadata = metadata();
+        let keys: Vec<_> = metadata.as_pairs().into_iter().map(|(k, _)| k).collect();
+        assert_eq!(
+            keys,
+            [
+                "Exported by",
+                "Export type",
+                "Timestamp",
+                "Run UID",
+                "Time range (s)"
+            ]
+        );
+        assert_eq!(png_keyword("Device (a<b>)\n"), "Device (a<b>)");
+    }
+
+    #[test]
+    fn test_png_embeds_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("frame.png");
+        write_png_gray16(&path, 2, 1, &[0, 4095], &metadata()).unwrap();
+
+        let decoder = png::Decoder::new(File::open(&path).unwrap());
+        let reader = decoder.read_info().unwrap();
+        let info = reader.info();
+        assert_eq!((info.width, info.height), (2, 1));
+        assert_eq!(info.bit_depth, png::BitDepth::Sixteen);
+        assert!(info
+            .utf8_text
+            .iter()
+            .any(|chunk| chunk.keyword == "Run UID" && chunk.get_text().unwrap() == "run-42"));
+    }
+
+    #[test]
+    fn test_svg_contains_series_and_metadata() {
+        let mut plot = SvgPlot {
+            title: "Signal <scope>".to_string(),
+            x_label: "Time (s)".to_string(),
+            y_label: "Value".to_string(),
+            series: vec![SvgSeries {
+                label: "power".to_string(),
+                color: [255, 0, 0],
+                points: vec![(0.0, 1.0), (1.0, 2.0), (2.0, f64::NAN)],
+            }],
+            markers: vec![(0.5, "door opened".to_string())],
+            ..Default::default()
+        };
+        plot.fit_ranges();
+        assert_eq!(plot.x_range, (0.0, 1.0));
+
+        let svg = plot.render(&metadata());
+        assert!(svg.contains("<title>Signal &lt;scope&gt;</title>"));
+        assert!(svg.contains(r#"<daq:entry key="Run UID">run-42</daq:entry>"#));
+        assert!(svg.contains("stroke=\"rgb(255,0,0)\""));
+        assert!(svg.contains("door opened"));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
@@ -0,0 +1,125 @@
+//! Camera frame export
+//!
+//! Raw pixel values (not the colormapped display) as 16-bit grayscale PNG
+//! or as a CSV matrix, one image row per line.
+
+use super::{write_metadata_comments, write_png_gray16, CsvExportOptions, FigureMetadata};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Raw camera frame as delivered by the stream
+#[derive(Debug, Clone, Copy)]
+pub struct RawFrame<'a> {
+    pub width: u32,
+    pub height: u32,
+    pub bit_depth: u32,
+    /// 1 byte per pixel for 8-bit, 2 bytes (little-endian) otherwise
+    pub data: &'a [u8],
+}
+
+impl RawFrame<'_> {
+    /// Pixel values, row-major; `None` if the buffer does not match the size
+    pub fn pixels(&self) -> Option<Vec<u16>> {
+        let count = self.width as usize * self.height as usize;
+        let pixels: Vec<u16> = match self.bit_depth {
+            8 => self.data.iter().map(|&b| u16::from(b)).collect(),
+            12 | 16 => self
+                .data
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect(),
+            _ => return None,
+        };
+        (pixels.len() >= count).then(|| pixels[..count].to_vec())
+    }
+}
+
+/// Write a frame's raw values as 16-bit grayscale PNG
+pub fn export_frame_png<P: AsRef<Path>>(
+    path: P,
+    frame: &RawFrame<'_>,
+    metadata: &FigureMetadata,
+) -> io::Result<()> {
+    let pixels = frame.pixels().ok_or_else(|| unsupported(frame))?;
+    write_png_gray16(path, frame.width, frame.height, &pixels, metadata)
+}
+
+/// Write a frame's raw values as a CSV matrix
+pub fn export_frame_csv<P: AsRef<Path>>(
+    path: P,
+    frame: &RawFrame<'_>,
+    metadata: &FigureMetadata,
+    options: &CsvExportOptions,
+) -> io::Result<()> {
+    let pixels = frame.pixels().ok_or_else(|| unsupported(frame))?;
+    let mut file = BufWriter::new(File::create(path.as_ref())?);
+    if options.include_metadata {
+        write_metadata_comments(&mut file, &metadata.as_pairs())?;
+    }
+    let delimiter = options.delimiter.as_str();
+    if options.include_header {
+        let columns: Vec<String> = (0..frame.width).map(|x| format!("x{}", x)).collect();
+        writeln!(file, "y{}{}", delimiter, columns.join(delimiter))?;
+    }
+    for (y, row) in pixels.chunks(frame.width.max(1) as usize).enumerate() {
+        write!(file, "{}", y)?;
+        for value in row {
+            write!(file, "{}{}", delimiter, value)?;
+        }
+        writeln!(file)?;
+    }
+    file.flush()
+}
+
+fn unsupported(frame: &RawFrame<'_>) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "Frame data does not match {}x{} at {} bits",
+            frame.width, frame.height, frame.bit_depth
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_pixels() {
+        let data = [0x01, 0x00, 0xff, 0x0f, 0x00, 0x10];
+        let frame = RawFrame {
+            width: 3,
+            height: 1,
+            bit_depth: 12,
+            data: &data,
+        };
+        assert_eq!(frame.pixels().unwrap(), [1, 4095, 4096]);
+
+        let short = RawFrame { width: 4, ..frame };
+        assert!(short.pixels().is_none());
+        let odd_depth = RawFrame {
+            bit_depth: 10,
+            ..frame
+        };
+        assert!(odd_depth.pixels().is_none());
+    }
+
+    #[test]
+    fn test_export_frame_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("frame.csv");
+        let frame = RawFrame {
+            width: 2,
+            height: 2,
+            bit_depth: 8,
+            data: &[1, 2, 3, 4],
+        };
+        let metadata = FigureMetadata::default().with("Camera", "cam0");
+        export_frame_csv(&path, &frame, &metadata, &CsvExportOptions::default()).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content, "# Camera: cam0\n\ny,x0,x1\n0,1,2\n1,3,4\n");
+    }
+}
@@ -1,18 +1,32 @@
-//! CSV export functionality for signal and image data
+//! Export functionality for signal and image data
 //!
-//! This module provides CSV export capabilities for:
-//! - Signal traces from SignalPlotter
+//! This module provides export capabilities for:
+//! - Signal traces from SignalPlotter (CSV, SVG, PNG)
+//! - Camera frames from ImageViewer (PNG, CSV)
 //! - Line profiles from ImageViewer (future)
 //! - ROI statistics over time (future)
 //!
+//! Every export embeds acquisition metadata (run UID, channels, time range,
+//! device settings), so figures keep their context once they leave the GUI.
+//!
 //! Features:
 //! - Configurable delimiter (comma, tab, semicolon)
 //! - Optional header row with column labels
 //! - Metadata comments (prefixed with #)
 //! - Large dataset support via streaming writes
 
+mod capture;
+mod context;
+mod figure;
+mod frame;
 mod signal;
 
+pub use capture::ScreenCapture;
+pub use context::{fetch_export_context, ExportContext};
+pub use figure::{
+    write_png_gray16, write_png_rgba, write_svg, FigureFormat, FigureMetadata, SvgPlot, SvgSeries,
+};
+pub use frame::{export_frame_csv, export_frame_png, RawFrame};
 pub use signal::{export_signal_traces, SignalExportOptions, SignalTraceData};
 
 use std::fs::File;
@@ -16,6 +16,8 @@ pub struct SignalExportOptions {
     pub csv_options: CsvExportOptions,
     /// Include trace labels in filename
     pub include_labels_in_filename: bool,
+    /// Extra metadata lines (run UID, time range, device settings)
+    pub extra_metadata: Vec<(String, String)>,
 }
 
 impl Default for SignalExportOptions {
@@ -23,6 +25,7 @@ impl Default for SignalExportOptions {
         Self {
             csv_options: CsvExportOptions::default(),
             include_labels_in_filename: false,
+            extra_metadata: Vec::new(),
         }
     }
 }
@@ -100,6 +103,12 @@ pub fn export_signal_traces<P: AsRef<Path>>(
             .map(|(label, id)| (label.as_str(), *id))
             .collect();
         metadata.extend(device_refs);
+        metadata.extend(
+            options
+                .extra_metadata
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str())),
+        );
 
         write_metadata_comments(&mut file, &metadata)?;
     }
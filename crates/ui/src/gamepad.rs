@@ -0,0 +1,620 @@
+//! Gamepad/joystick stage control.
+//!
+//! The left stick jogs the X and Y axes and the right stick the Z axis, with
+//! a speed proportional to stick deflection beyond the deadzone. Nothing moves
+//! unless the safety enable button is held; releasing it or centering a stick
+//! stops the axis. Each tick sends a short relative move, and an axis whose
+//! previous move has not returned yet is skipped so moves never pile up.
+//!
+//! Settings are kept per workspace (the daemon the GUI is connected to), since
+//! each rig has its own stages. Reading gamepads needs the `gamepad` feature
+//! (gilrs); without it the window only explains how to enable it.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use client::DaqClient;
+use eframe::egui;
+
+use crate::keyboard_control::{device_combo, fetch_control_devices, ControlDevice};
+
+/// Interval between jog moves while a stick is deflected
+pub const TICK: Duration = Duration::from_millis(100);
+
+/// Button that must be held for the sticks to move anything
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EnableButton {
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl EnableButton {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::LeftBumper => "Left bumper (LB)",
+            Self::RightBumper => "Right bumper (RB)",
+            Self::LeftTrigger => "Left trigger (LT)",
+            Self::RightTrigger => "Right trigger (RT)",
+        }
+    }
+}
+
+/// Gamepad mapping for one workspace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GamepadSettings {
+    /// Gamepad control on/off
+    pub enabled: bool,
+    /// Device jogged by the left stick X
+    pub x_axis: Option<String>,
+    /// Device jogged by the left stick Y
+    pub y_axis: Option<String>,
+    /// Device jogged by the right stick Y
+    pub z_axis: Option<String>,
+    /// Flip the Y direction
+    pub invert_y: bool,
+    /// Stick deflection ignored around center (0..1)
+    pub deadzone: f32,
+    /// X/Y speed at full deflection (device units per second)
+    pub max_velocity: f64,
+    /// Z speed at full deflection (device units per second)
+    pub z_max_velocity: f64,
+    /// Safety enable button
+    pub enable_button: EnableButton,
+}
+
+impl Default for GamepadSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            x_axis: None,
+            y_axis: None,
+            z_axis: None,
+            invert_y: false,
+            deadzone: 0.15,
+            max_velocity: 1.0,
+            z_max_velocity: 0.1,
+            enable_button: EnableButton::LeftBumper,
+        }
+    }
+}
+
+/// Stick and enable-button snapshot
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StickState {
+    pub enable: bool,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+/// Rescale a stick value so motion starts at the deadzone edge
+pub fn apply_deadzone(value: f32, deadzone: f32) -> f32 {
+    let magnitude = value.abs().min(1.0);
+    if magnitude <= deadzone {
+        0.0
+    } else {
+        value.signum() * (magnitude - deadzone) / (1.0 - deadzone)
+    }
+}
+
+/// Command sent to the daemon
+#[derive(Debug, Clone, PartialEq)]
+pub enum GamepadCommand {
+    Jog { device_id: String, distance: f64 },
+    Stop { device_id: String },
+}
+
+/// Turns stick snapshots into jog and stop commands.
+#[derive(Debug, Default)]
+pub struct GamepadJogger {
+    /// Axes commanded to move since they were last stopped
+    moving: HashSet<String>,
+}
+
+impl GamepadJogger {
+    /// Commands for one tick of `dt` seconds
+    pub fn tick(
+        &mut self,
+        settings: &GamepadSettings,
+        state: StickState,
+        dt: f64,
+    ) -> Vec<GamepadCommand> {
+        let y = if settings.invert_y { -state.y } else { state.y };
+        let axes = [
+            (&settings.x_axis, state.x, settings.max_velocity),
+            (&settings.y_axis, y, settings.max_velocity),
+            (&settings.z_axis, state.z, settings.z_max_velocity),
+        ];
+
+        let mut commands = Vec::new();
+        for (axis, value, max_velocity) in axes {
+            let Some(device_id) = axis else {
+                continue;
+            };
+            let deflection = if state.enable {
+                apply_deadzone(value, settings.deadzone)
+            } else {
+                0.0
+            };
+            if deflection == 0.0 {
+                if self.moving.remove(device_id) {
+                    commands.push(GamepadCommand::Stop {
+                        device_id: device_id.clone(),
+                    });
+                }
+            } else {
+                self.moving.insert(device_id.clone());
+                commands.push(GamepadCommand::Jog {
+                    device_id: device_id.clone(),
+                    distance: f64::from(deflection) * max_velocity * dt,
+                });
+            }
+        }
+        commands
+    }
+
+    /// Forget moving axes (after disconnect)
+    pub fn reset(&mut self) {
+        self.moving.clear();
+    }
+}
+
+/// Results from the background tasks
+enum GamepadMessage {
+    Devices(Result<Vec<ControlDevice>, String>),
+    /// A jog or stop returned (clears the in-flight mark)
+    Finished {
+        device_id: String,
+        error: Option<String>,
+    },
+}
+
+/// Gamepad input, jogging and the Gamepad window.
+pub struct GamepadControl {
+    /// Settings per workspace (daemon address)
+    profiles: HashMap<String, GamepadSettings>,
+    workspace: String,
+    input: Option<GamepadInput>,
+    jogger: GamepadJogger,
+    /// Axes with a move request outstanding
+    in_flight: HashSet<String>,
+    last_tick: Option<Instant>,
+    last_state: StickState,
+    gamepad_name: Option<String>,
+    devices: Vec<ControlDevice>,
+    error: Option<String>,
+    /// Gamepad window visibility
+    pub window_open: bool,
+    tx: mpsc::Sender<GamepadMessage>,
+    rx: mpsc::Receiver<GamepadMessage>,
+}
+
+impl GamepadControl {
+    pub fn new(profiles: HashMap<String, GamepadSettings>) -> Self {
+        let (tx, rx) = mpsc::channel(64);
+        Self {
+            profiles,
+            workspace: String::new(),
+            input: None,
+            jogger: GamepadJogger::default(),
+            in_flight: HashSet::new(),
+            last_tick: None,
+            last_state: StickState::default(),
+            gamepad_name: None,
+            devices: Vec::new(),
+            error: None,
+            window_open: false,
+            tx,
+            rx,
+        }
+    }
+
+    /// All workspace profiles (for persistence)
+    pub fn profiles(&self) -> &HashMap<String, GamepadSettings> {
+        &self.profiles
+    }
+
+    /// Switch to the settings of another workspace
+    pub fn set_workspace(&mut self, workspace: &str) {
+        if self.workspace != workspace {
+            self.workspace = workspace.to_string();
+            self.jogger.reset();
+            self.in_flight.clear();
+        }
+    }
+
+    /// Settings of the current workspace
+    pub fn settings(&self) -> GamepadSettings {
+        self.profiles
+            .get(&self.workspace)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn settings_mut(&mut self) -> &mut GamepadSettings {
+        self.profiles.entry(self.workspace.clone()).or_default()
+    }
+
+    /// Forget moving axes; the daemon connection is gone
+    pub fn reset(&mut self) {
+        self.jogger.reset();
+        self.in_flight.clear();
+    }
+
+    /// Read the gamepad and send jog/stop commands when a tick is due
+    ///
+    /// Returns errors for the log. Keeps repainting while enabled so ticks
+    /// happen without other input events.
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        client: Option<&DaqClient>,
+        runtime: &tokio::runtime::Runtime,
+    ) -> Vec<String> {
+        let errors = self.poll();
+        let settings = self.settings();
+        if !settings.enabled {
+            self.last_tick = None;
+            return errors;
+        }
+        ctx.request_repaint_after(TICK);
+
+        if self.input.is_none() {
+            self.input = GamepadInput::open();
+        }
+        let state = self.input.as_mut().and_then(|input| {
+            let state = input.read(settings.enable_button);
+            self.gamepad_name = state.as_ref().map(|(name, _)| name.clone());
+            state.map(|(_, state)| state)
+        });
+        self.last_state = state.unwrap_or_default();
+
+        let now = Instant::now();
+        let dt = match self.last_tick {
+            Some(last) if now.duration_since(last) < TICK => return errors,
+            Some(last) => now.duration_since(last).min(TICK * 3).as_secs_f64(),
+            None => TICK.as_secs_f64(),
+        };
+        self.last_tick = Some(now);
+
+        let Some(client) = client else {
+            return errors;
+        };
+        for command in self.jogger.tick(&settings, self.last_state, dt) {
+            self.send(command, client.clone(), runtime);
+        }
+        errors
+    }
+
+    fn send(
+        &mut self,
+        command: GamepadCommand,
+        mut client: DaqClient,
+        runtime: &tokio::runtime::Runtime,
+    ) {
+        let device_id = match &command {
+            GamepadCommand::Jog { device_id, .. } => {
+                // Skip rather than queue behind a move still in flight
+                if !self.in_flight.insert(device_id.clone()) {
+                    return;
+                }
+                device_id.clone()
+            }
+            GamepadCommand::Stop { device_id } => device_id.clone(),
+        };
+        let tx = self.tx.clone();
+        runtime.spawn(async move {
+            let error = match command {
+                GamepadCommand::Jog { distance, .. } => {
+                    match client.move_relative(&device_id, distance).await {
+                        Ok(response) if response.success => None,
+                        Ok(response) => Some(response.error_message),
+                        Err(e) => Some(e.to_string()),
+                    }
+                }
+                GamepadCommand::Stop { .. } => client
+                    .stop_motion(&device_id)
+                    .await
+                    .err()
+                    .map(|e| e.to_string()),
+            };
+            let _ = tx.send(GamepadMessage::Finished { device_id, error }).await;
+        });
+    }
+
+    fn poll(&mut self) -> Vec<String> {
+        let mut errors = Vec::new();
+        while let Ok(message) = self.rx.try_recv() {
+            match message {
+                GamepadMessage::Devices(Ok(devices)) => {
+                    self.devices = devices;
+                    self.error = None;
+                }
+                GamepadMessage::Devices(Err(e)) => self.error = Some(e),
+                GamepadMessage::Finished { device_id, error } => {
+                    self.in_flight.remove(&device_id);
+                    if let Some(error) = error {
+                        errors.push(format!("Gamepad jog of {} failed: {}", device_id, error));
+                    }
+                }
+            }
+        }
+        errors
+    }
+
+    /// Fetch movable devices for the axis pickers
+    pub fn refresh_devices(&self, client: DaqClient, runtime: &tokio::runtime::Runtime) {
+        let tx = self.tx.clone();
+        runtime.spawn(async move {
+            let devices = fetch_control_devices(client).await;
+            let _ = tx.send(GamepadMessage::Devices(devices)).await;
+        });
+    }
+
+    /// Gamepad window; returns true when the device list should be refreshed
+    pub fn show(&mut self, ctx: &egui::Context) -> bool {
+        let mut refresh = false;
+        let mut open = self.window_open;
+        let workspace = self.workspace.clone();
+        let gamepad_name = self.gamepad_name.clone();
+        let state = self.last_state;
+        let movable: Vec<ControlDevice> = self
+            .devices
+            .iter()
+            .filter(|d| d.is_movable)
+            .cloned()
+            .collect();
+        let error = self.error.clone();
+        let settings = self.settings_mut();
+
+        egui::Window::new("🎮 Gamepad")
+            .open(&mut open)
+            .default_width(380.0)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if !GamepadInput::SUPPORTED {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        "Built without gamepad support (enable the `gamepad` feature).",
+                    );
+                    ui.separator();
+                }
+                ui.label(format!(
+                    "Workspace: {}",
+                    if workspace.is_empty() {
+                        "(not connected)"
+                    } else {
+                        &workspace
+                    }
+                ));
+                ui.checkbox(&mut settings.enabled, "Gamepad control");
+                match &gamepad_name {
+                    Some(name) => {
+                        ui.label(format!("Gamepad: {}", name));
+                        let (text, color) = if state.enable {
+                            (
+                                "ENABLED - sticks move hardware",
+                                egui::Color32::from_rgb(220, 60, 60),
+                            )
+                        } else {
+                            ("Hold the enable button to move", egui::Color32::GRAY)
+                        };
+                        ui.colored_label(color, text);
+                    }
+                    None if settings.enabled => {
+                        ui.label("No gamepad connected");
+                    }
+                    None => {}
+                }
+                ui.separator();
+
+                if ui.button("Refresh devices").clicked() {
+                    refresh = true;
+                }
+                if let Some(err) = &error {
+                    ui.colored_label(egui::Color32::RED, err);
+                }
+                let movable: Vec<&ControlDevice> = movable.iter().collect();
+
+                egui::Grid::new("gamepad_settings")
+                    .num_columns(2)
+                    .spacing([20.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("Enable button:");
+                        egui::ComboBox::from_id_salt("gamepad_enable_button")
+                            .selected_text(settings.enable_button.label())
+                            .show_ui(ui, |ui| {
+                                for button in [
+                                    EnableButton::LeftBumper,
+                                    EnableButton::RightBumper,
+                                    EnableButton::LeftTrigger,
+                                    EnableButton::RightTrigger,
+                                ] {
+                                    ui.selectable_value(
+                                        &mut settings.enable_button,
+                                        button,
+                                        button.label(),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label("X axis (left stick):");
+                        device_combo(ui, "gamepad_x", &mut settings.x_axis, &movable);
+                        ui.end_row();
+                        ui.label("Y axis (left stick):");
+                        device_combo(ui, "gamepad_y", &mut settings.y_axis, &movable);
+                        ui.end_row();
+                        ui.label("");
+                        ui.checkbox(&mut settings.invert_y, "Invert Y");
+                        ui.end_row();
+                        ui.label("Z axis (right stick):");
+                        device_combo(ui, "gamepad_z", &mut settings.z_axis, &movable);
+                        ui.end_row();
+
+                        ui.label("Deadzone:");
+                        ui.add(egui::Slider::new(&mut settings.deadzone, 0.0..=0.5));
+                        ui.end_row();
+                        ui.label("X/Y max speed (/s):");
+                        ui.add(
+                            egui::DragValue::new(&mut settings.max_velocity)
+                                .speed(0.01)
+                                .range(0.0..=1000.0),
+                        );
+                        ui.end_row();
+                        ui.label("Z max speed (/s):");
+                        ui.add(
+                            egui::DragValue::new(&mut settings.z_max_velocity)
+                                .speed(0.01)
+                                .range(0.0..=1000.0),
+                        );
+                        ui.end_row();
+                    });
+            });
+        self.window_open = open;
+        refresh
+    }
+}
+
+/// Connected gamepads, via gilrs
+#[cfg(feature = "gamepad")]
+struct GamepadInput {
+    gilrs: gilrs::Gilrs,
+}
+
+#[cfg(feature = "gamepad")]
+impl GamepadInput {
+    const SUPPORTED: bool = true;
+
+    fn open() -> Option<Self> {
+        match gilrs::Gilrs::new() {
+            Ok(gilrs) => Some(Self { gilrs }),
+            Err(e) => {
+                tracing::warn!("Gamepad input unavailable: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Name and stick state of the first connected gamepad
+    fn read(&mut self, enable_button: EnableButton) -> Option<(String, StickState)> {
+        use gilrs::{Axis, Button};
+
+        // Drain events so gilrs updates its cached gamepad state
+        while self.gilrs.next_event().is_some() {}
+
+        let (_, gamepad) = self.gilrs.gamepads().next()?;
+        let button = match enable_button {
+            EnableButton::LeftBumper => Button::LeftTrigger,
+            EnableButton::RightBumper => Button::RightTrigger,
+            EnableButton::LeftTrigger => Button::LeftTrigger2,
+            EnableButton::RightTrigger => Button::RightTrigger2,
+        };
+        Some((
+            gamepad.name().to_string(),
+            StickState {
+                enable: gamepad.is_pressed(button),
+                x: gamepad.value(Axis::LeftStickX),
+                y: gamepad.value(Axis::LeftStickY),
+                z: gamepad.value(Axis::RightStickY),
+            },
+        ))
+    }
+}
+
+/// Stand-in when built without the `gamepad` feature
+#[cfg(not(feature = "gamepad"))]
+struct GamepadInput;
+
+#[cfg(not(feature = "gamepad"))]
+impl GamepadInput {
+    const SUPPORTED: bool = false;
+
+    fn open() -> Option<Self> {
+        None
+    }
+
+    fn read(&mut self, _enable_button: EnableButton) -> Option<(String, StickState)> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> GamepadSettings {
+        GamepadSettings {
+            enabled: true,
+            x_axis: Some("stage_x".to_string()),
+            y_axis: Some("stage_y".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_deadzone_rescales_deflection() {
+        assert!(apply_deadzone(0.1, 0.2).abs() < f32::EPSILON);
+        assert!(apply_deadzone(-0.2, 0.2).abs() < f32::EPSILON);
+        assert!((apply_deadzone(0.6, 0.2) - 0.5).abs() < 1e-6);
+        assert!((apply_deadzone(-1.0, 0.2) + 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_jog_proportional_to_deflection() {
+        let mut jogger = GamepadJogger::default();
+        let state = StickState {
+            enable: true,
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        };
+        let commands = jogger.tick(&settings(), state, 0.1);
+        let [GamepadCommand::Jog {
+            device_id,
+            distance,
+        }] = commands.as_slice()
+        else {
+            panic!("expected one jog, got {:?}", commands);
+        };
+        assert_eq!(device_id, "stage_x");
+        assert!((distance - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_releasing_enable_stops_moving_axes() {
+        let mut jogger = GamepadJogger::default();
+        let moving = StickState {
+            enable: true,
+            x: 0.8,
+            y: -0.8,
+            z: 0.0,
+        };
+        assert_eq!(jogger.tick(&settings(), moving, 0.1).len(), 2);
+
+        let released = StickState {
+            enable: false,
+            ..moving
+        };
+        let commands = jogger.tick(&settings(), released, 0.1);
+        assert_eq!(
+            commands,
+            vec![
+                GamepadCommand::Stop {
+                    device_id: "stage_x".to_string()
+                },
+                GamepadCommand::Stop {
+                    device_id: "stage_y".to_string()
+                },
+            ]
+        );
+        // Stops are sent once
+        assert!(jogger.tick(&settings(), released, 0.1).is_empty());
+    }
+}
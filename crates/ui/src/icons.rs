@@ -24,6 +24,7 @@ pub mod nav {
     pub const PLAN_RUNNER: &str = PLAY_CIRCLE;
     pub const DOCUMENT_VIEWER: &str = FILE_TEXT;
     pub const INSTRUMENT_MANAGER: &str = SLIDERS_HORIZONTAL;
+    pub const CONSOLE: &str = TERMINAL_WINDOW;
 }
 
 pub mod action {
@@ -0,0 +1,484 @@
+//! Keyboard control of stages, shutter and runs.
+//!
+//! Arrow keys (and Page Up/Down) jog the stages chosen as X, Y and Z axes by
+//! the selected step size; other bindings toggle the shutter and start or
+//! pause the run engine. Bindings live in the [`ShortcutManager`] under
+//! [`ShortcutContext::StageControl`] and are customizable like every other
+//! shortcut.
+//!
+//! Keyboard control is disarmed at startup and on every disconnect, and
+//! must be armed explicitly (Ctrl+K or the Keyboard Control window) before
+//! any key touches hardware. Key auto-repeat is ignored, so holding an arrow
+//! key moves one step, not one step per repeat.
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use client::DaqClient;
+use eframe::egui;
+use protocol::daq::EngineState;
+
+use crate::shortcuts::{ShortcutAction, ShortcutContext, ShortcutManager};
+
+/// Selectable jog step sizes (device units)
+pub const JOG_STEP_SIZES: &[f64] = &[0.001, 0.01, 0.1, 1.0, 10.0, 100.0];
+
+/// Persisted keyboard control configuration (arming is never persisted)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyboardControlSettings {
+    /// Device jogged by left/right
+    pub x_axis: Option<String>,
+    /// Device jogged by up/down
+    pub y_axis: Option<String>,
+    /// Device jogged by Page Up/Page Down
+    pub z_axis: Option<String>,
+    /// Flip the Y direction (stage Y increasing downwards in the image)
+    pub invert_y: bool,
+    /// Shutter toggled by the shutter binding
+    pub shutter: Option<String>,
+    /// Index into [`JOG_STEP_SIZES`]
+    pub step_index: usize,
+}
+
+impl Default for KeyboardControlSettings {
+    fn default() -> Self {
+        Self {
+            x_axis: None,
+            y_axis: None,
+            z_axis: None,
+            invert_y: false,
+            shutter: None,
+            step_index: 2,
+        }
+    }
+}
+
+impl KeyboardControlSettings {
+    /// Current jog step
+    pub fn step(&self) -> f64 {
+        JOG_STEP_SIZES[self.step_index.min(JOG_STEP_SIZES.len() - 1)]
+    }
+}
+
+/// Hardware command triggered from the keyboard
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyboardCommand {
+    /// Relative move of one axis
+    Jog { device_id: String, distance: f64 },
+    /// Flip the shutter state
+    ToggleShutter { device_id: String },
+    /// Start the run engine on the queued plans
+    StartRun,
+    /// Pause a running engine, or resume a paused one
+    PauseResumeRun,
+}
+
+impl std::fmt::Display for KeyboardCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Jog {
+                device_id,
+                distance,
+            } => write!(f, "jog {} by {:+}", device_id, distance),
+            Self::ToggleShutter { device_id } => write!(f, "toggle shutter {}", device_id),
+            Self::StartRun => write!(f, "start run"),
+            Self::PauseResumeRun => write!(f, "pause/resume run"),
+        }
+    }
+}
+
+/// Outcome of a keyboard command, for the log
+pub enum KeyboardControlMessage {
+    /// Movable and shutter devices for the axis pickers
+    Devices(Result<Vec<ControlDevice>, String>),
+    /// A command completed, with a description of the result
+    Done(String),
+    /// A command failed
+    Failed(String),
+}
+
+/// Device offered in the axis/shutter pickers
+#[derive(Debug, Clone)]
+pub struct ControlDevice {
+    pub id: String,
+    pub name: String,
+    pub is_movable: bool,
+    pub is_shutter: bool,
+}
+
+/// Keyboard control state and the background command tasks.
+pub struct KeyboardControl {
+    pub settings: KeyboardControlSettings,
+    armed: bool,
+    /// Keyboard Control window visibility
+    pub window_open: bool,
+    devices: Vec<ControlDevice>,
+    error: Option<String>,
+    tx: mpsc::Sender<KeyboardControlMessage>,
+    rx: mpsc::Receiver<KeyboardControlMessage>,
+}
+
+impl KeyboardControl {
+    pub fn new(settings: KeyboardControlSettings) -> Self {
+        let (tx, rx) = mpsc::channel(64);
+        Self {
+            settings,
+            armed: false,
+            window_open: false,
+            devices: Vec::new(),
+            error: None,
+            tx,
+            rx,
+        }
+    }
+
+    pub fn is_armed(&self) -> bool {
+        self.armed
+    }
+
+    /// Stop reacting to keys (called on disconnect)
+    pub fn disarm(&mut self) {
+        self.armed = false;
+    }
+
+    /// Command for a stage control action with the current settings, if any
+    ///
+    /// Step changes are applied here and produce no command.
+    pub fn command_for(&mut self, action: ShortcutAction) -> Option<KeyboardCommand> {
+        let step = self.settings.step();
+        let y_step = if self.settings.invert_y { -step } else { step };
+        let jog = |axis: &Option<String>, distance: f64| {
+            axis.clone().map(|device_id| KeyboardCommand::Jog {
+                device_id,
+                distance,
+            })
+        };
+        match action {
+            ShortcutAction::JogLeft => jog(&self.settings.x_axis, -step),
+            ShortcutAction::JogRight => jog(&self.settings.x_axis, step),
+            ShortcutAction::JogUp => jog(&self.settings.y_axis, y_step),
+            ShortcutAction::JogDown => jog(&self.settings.y_axis, -y_step),
+            ShortcutAction::JogZUp => jog(&self.settings.z_axis, step),
+            ShortcutAction::JogZDown => jog(&self.settings.z_axis, -step),
+            ShortcutAction::JogStepIncrease => {
+                self.settings.step_index =
+                    (self.settings.step_index + 1).min(JOG_STEP_SIZES.len() - 1);
+                None
+            }
+            ShortcutAction::JogStepDecrease => {
+                self.settings.step_index = self.settings.step_index.saturating_sub(1);
+                None
+            }
+            ShortcutAction::ToggleShutter => self
+                .settings
+                .shutter
+                .clone()
+                .map(|device_id| KeyboardCommand::ToggleShutter { device_id }),
+            ShortcutAction::StartRun => Some(KeyboardCommand::StartRun),
+            ShortcutAction::PauseResumeRun => Some(KeyboardCommand::PauseResumeRun),
+            _ => None,
+        }
+    }
+
+    /// Handle the arm toggle and, while armed, collect commands for this frame
+    ///
+    /// Keys typed into text fields are never treated as commands.
+    pub fn handle_input(
+        &mut self,
+        ctx: &egui::Context,
+        shortcuts: &ShortcutManager,
+    ) -> Vec<KeyboardCommand> {
+        if ctx.wants_keyboard_input() {
+            return Vec::new();
+        }
+        if shortcuts.check_press(ctx, ShortcutAction::ToggleKeyboardControl) {
+            self.armed = !self.armed;
+        }
+        if !self.armed {
+            return Vec::new();
+        }
+
+        let actions: Vec<ShortcutAction> = shortcuts
+            .all_actions_by_context()
+            .remove(&ShortcutContext::StageControl)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|action| shortcuts.check_press(ctx, *action))
+            .collect();
+        actions
+            .into_iter()
+            .filter_map(|action| self.command_for(action))
+            .collect()
+    }
+
+    /// Run a command in the background; the result arrives via [`Self::poll`]
+    pub fn execute(
+        &self,
+        command: KeyboardCommand,
+        mut client: DaqClient,
+        runtime: &tokio::runtime::Runtime,
+    ) {
+        let tx = self.tx.clone();
+        runtime.spawn(async move {
+            let result = match &command {
+                KeyboardCommand::Jog {
+                    device_id,
+                    distance,
+                } => client
+                    .move_relative(device_id, *distance)
+                    .await
+                    .and_then(|response| {
+                        if response.success {
+                            Ok(format!("Jogged {} by {:+}", device_id, distance))
+                        } else {
+                            Err(anyhow::anyhow!(response.error_message))
+                        }
+                    }),
+                KeyboardCommand::ToggleShutter { device_id } => {
+                    match client.get_shutter(device_id).await {
+                        Ok(open) => client.set_shutter(device_id, !open).await.map(|open| {
+                            format!(
+                                "Shutter {} {}",
+                                device_id,
+                                if open { "open" } else { "closed" }
+                            )
+                        }),
+                        Err(e) => Err(e),
+                    }
+                }
+                KeyboardCommand::StartRun => client
+                    .start_engine()
+                    .await
+                    .map(|_| "Run engine started".to_string()),
+                KeyboardCommand::PauseResumeRun => pause_or_resume(&mut client).await,
+            };
+            let message = match result {
+                Ok(done) => KeyboardControlMessage::Done(done),
+                Err(e) => KeyboardControlMessage::Failed(format!("Failed to {}: {}", command, e)),
+            };
+            let _ = tx.send(message).await;
+        });
+    }
+
+    /// Fetch movable and shutter devices for the pickers
+    pub fn refresh_devices(&self, client: DaqClient, runtime: &tokio::runtime::Runtime) {
+        let tx = self.tx.clone();
+        runtime.spawn(async move {
+            let devices = fetch_control_devices(client).await;
+            let _ = tx.send(KeyboardControlMessage::Devices(devices)).await;
+        });
+    }
+
+    /// Drain command results; device lists are applied, the rest returned for logging
+    pub fn poll(&mut self) -> Vec<KeyboardControlMessage> {
+        let mut messages = Vec::new();
+        while let Ok(message) = self.rx.try_recv() {
+            match message {
+                KeyboardControlMessage::Devices(Ok(devices)) => {
+                    self.devices = devices;
+                    self.error = None;
+                }
+                KeyboardControlMessage::Devices(Err(e)) => {
+                    self.error = Some(e);
+                }
+                other => messages.push(other),
+            }
+        }
+        messages
+    }
+
+    /// Keyboard Control window: arming, axis/shutter selection, step size
+    ///
+    /// Returns true when the device list should be refreshed.
+    pub fn show(&mut self, ctx: &egui::Context, shortcuts: &ShortcutManager) -> bool {
+        let mut refresh = false;
+        let mut open = self.window_open;
+        egui::Window::new("⌨ Keyboard Control")
+            .open(&mut open)
+            .default_width(360.0)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let (label, color) = if self.armed {
+                    (
+                        "ARMED - keys move hardware",
+                        egui::Color32::from_rgb(220, 60, 60),
+                    )
+                } else {
+                    ("Disarmed", egui::Color32::GRAY)
+                };
+                ui.horizontal(|ui| {
+                    ui.toggle_value(&mut self.armed, "Keyboard control");
+                    ui.colored_label(color, label);
+                });
+                if let Some(binding) = shortcuts.get_binding(ShortcutAction::ToggleKeyboardControl)
+                {
+                    ui.small(format!("{} arms/disarms from anywhere", binding.label()));
+                }
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Jog step:");
+                    egui::ComboBox::from_id_salt("jog_step")
+                        .selected_text(format!("{}", self.settings.step()))
+                        .show_ui(ui, |ui| {
+                            for (index, step) in JOG_STEP_SIZES.iter().enumerate() {
+                                ui.selectable_value(
+                                    &mut self.settings.step_index,
+                                    index,
+                                    format!("{}", step),
+                                );
+                            }
+                        });
+                });
+
+                if ui.button("Refresh devices").clicked() {
+                    refresh = true;
+                }
+                if let Some(err) = &self.error {
+                    ui.colored_label(egui::Color32::RED, err);
+                }
+
+                egui::Grid::new("keyboard_control_axes")
+                    .num_columns(2)
+                    .spacing([20.0, 6.0])
+                    .show(ui, |ui| {
+                        let movable: Vec<&ControlDevice> =
+                            self.devices.iter().filter(|d| d.is_movable).collect();
+                        let shutters: Vec<&ControlDevice> =
+                            self.devices.iter().filter(|d| d.is_shutter).collect();
+
+                        ui.label("X axis (←/→):");
+                        device_combo(ui, "jog_x", &mut self.settings.x_axis, &movable);
+                        ui.end_row();
+                        ui.label("Y axis (↑/↓):");
+                        device_combo(ui, "jog_y", &mut self.settings.y_axis, &movable);
+                        ui.end_row();
+                        ui.label("");
+                        ui.checkbox(&mut self.settings.invert_y, "Invert Y");
+                        ui.end_row();
+                        ui.label("Z axis (PgUp/PgDn):");
+                        device_combo(ui, "jog_z", &mut self.settings.z_axis, &movable);
+                        ui.end_row();
+                        ui.label("Shutter:");
+                        device_combo(ui, "jog_shutter", &mut self.settings.shutter, &shutters);
+                        ui.end_row();
+                    });
+
+                ui.separator();
+                ui.small("Bindings are listed in the keyboard shortcuts cheat sheet (Shift+?).");
+            });
+        self.window_open = open;
+        refresh
+    }
+}
+
+/// Movable and shutter devices on the daemon
+pub async fn fetch_control_devices(mut client: DaqClient) -> Result<Vec<ControlDevice>, String> {
+    #[allow(deprecated)] // capability flags still used for GUI routing
+    client
+        .list_devices()
+        .await
+        .map(|devices| {
+            devices
+                .into_iter()
+                .filter(|d| d.is_movable || d.is_shutter_controllable)
+                .map(|d| ControlDevice {
+                    is_movable: d.is_movable,
+                    is_shutter: d.is_shutter_controllable,
+                    id: d.id,
+                    name: d.name,
+                })
+                .collect()
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// Picker for an optional device
+pub fn device_combo(
+    ui: &mut egui::Ui,
+    id: &str,
+    selected: &mut Option<String>,
+    devices: &[&ControlDevice],
+) {
+    let text = selected.clone().unwrap_or_else(|| "None".to_string());
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(text)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(selected, None, "None");
+            for device in devices {
+                ui.selectable_value(selected, Some(device.id.clone()), &device.name);
+            }
+        });
+}
+
+async fn pause_or_resume(client: &mut DaqClient) -> anyhow::Result<String> {
+    let status = client.get_engine_status().await?;
+    match status.state() {
+        EngineState::EngineRunning => {
+            client.pause_engine(true).await?;
+            Ok("Run pausing at next checkpoint".to_string())
+        }
+        EngineState::EnginePaused => {
+            client.resume_engine().await?;
+            Ok("Run resumed".to_string())
+        }
+        _ => anyhow::bail!("no run to pause or resume"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn control() -> KeyboardControl {
+        KeyboardControl::new(KeyboardControlSettings {
+            x_axis: Some("stage_x".to_string()),
+            y_axis: Some("stage_y".to_string()),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_jog_commands_use_step_and_direction() {
+        let mut control = control();
+        assert_eq!(
+            control.command_for(ShortcutAction::JogLeft),
+            Some(KeyboardCommand::Jog {
+                device_id: "stage_x".to_string(),
+                distance: -0.1
+            })
+        );
+        control.command_for(ShortcutAction::JogStepIncrease);
+        control.settings.invert_y = true;
+        assert_eq!(
+            control.command_for(ShortcutAction::JogUp),
+            Some(KeyboardCommand::Jog {
+                device_id: "stage_y".to_string(),
+                distance: -1.0
+            })
+        );
+        // No Z axis or shutter configured
+        assert_eq!(control.command_for(ShortcutAction::JogZUp), None);
+        assert_eq!(control.command_for(ShortcutAction::ToggleShutter), None);
+    }
+
+    #[test]
+    fn test_step_size_clamped() {
+        let mut control = control();
+        for _ in 0..10 {
+            control.command_for(ShortcutAction::JogStepDecrease);
+        }
+        assert_eq!(control.settings.step_index, 0);
+        for _ in 0..10 {
+            control.command_for(ShortcutAction::JogStepIncrease);
+        }
+        assert_eq!(control.settings.step_index, JOG_STEP_SIZES.len() - 1);
+    }
+
+    #[test]
+    fn test_starts_disarmed() {
+        let control = control();
+        assert!(!control.is_armed());
+    }
+}
@@ -13,16 +13,28 @@ pub use connection_state_ext::ConnectionStateExt;
 #[cfg(feature = "standalone")]
 pub mod app;
 #[cfg(feature = "standalone")]
+pub mod daemon_logs;
+#[cfg(feature = "standalone")]
 pub mod export;
 #[cfg(feature = "standalone")]
+pub mod gamepad;
+#[cfg(feature = "standalone")]
 pub mod graph;
 #[cfg(feature = "standalone")]
 pub mod icons;
 #[cfg(feature = "standalone")]
+pub mod keyboard_control;
+#[cfg(feature = "standalone")]
 pub mod layout;
 #[cfg(feature = "standalone")]
+pub mod notifications;
+#[cfg(feature = "standalone")]
+pub mod offline_cache;
+#[cfg(feature = "standalone")]
 pub mod panels;
 #[cfg(feature = "standalone")]
+pub mod presence;
+#[cfg(feature = "standalone")]
 pub mod settings;
 #[cfg(feature = "standalone")]
 pub mod shortcuts;
@@ -22,17 +22,29 @@ mod connection;
 mod connection_state_ext;
 mod daemon_launcher;
 #[cfg(feature = "standalone")]
+mod daemon_logs;
+#[cfg(feature = "standalone")]
 mod export;
 #[cfg(feature = "standalone")]
+mod gamepad;
+#[cfg(feature = "standalone")]
 mod graph;
 #[cfg(feature = "standalone")]
 mod gui_log_layer;
 #[cfg(feature = "standalone")]
 mod icons;
 #[cfg(feature = "standalone")]
+mod keyboard_control;
+#[cfg(feature = "standalone")]
 mod layout;
 #[cfg(feature = "standalone")]
+mod notifications;
+#[cfg(feature = "standalone")]
+mod offline_cache;
+#[cfg(feature = "standalone")]
 mod panels;
+#[cfg(feature = "standalone")]
+mod presence;
 mod reconnect;
 #[cfg(feature = "standalone")]
 mod settings;
@@ -64,6 +76,7 @@ struct Cli {
     /// Connect to a remote daemon at the specified URL (skips auto-start)
     ///
     /// Example: --daemon-url http://192.168.1.100:50051
+    /// (or unix:///run/rust-daq/daq.sock for a daemon on a local socket)
     #[arg(long, value_name = "URL")]
     daemon_url: Option<String>,
 
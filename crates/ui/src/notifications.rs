@@ -0,0 +1,414 @@
+//! Run-completion and alarm notifications.
+//!
+//! While connected the GUI follows the daemon's document stream (start and
+//! stop documents) and the event streams of its modules. Run completion, run
+//! failure and alarms (module conditions becoming active, or error events)
+//! become [`Notification`]s that the app shows in the status bar and, per the
+//! user's preferences, as desktop notifications with an optional sound, so a
+//! finished run isn't missed while working in another window.
+//!
+//! Desktop notifications need the `desktop_notifications` feature; without it
+//! the window only asks the window system for attention (taskbar flash).
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio::task::{JoinHandle, JoinSet};
+
+use client::DaqClient;
+use protocol::daq::{document::Payload, Document, DocumentType, ModuleEvent, ModuleEventSeverity};
+
+/// How often the module list is checked for modules to follow
+const MODULE_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Kinds of event that can notify
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NotificationKind {
+    RunCompleted,
+    RunFailed,
+    Alarm,
+}
+
+impl NotificationKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::RunCompleted => "Run completed",
+            Self::RunFailed => "Run failed or aborted",
+            Self::Alarm => "Alarm",
+        }
+    }
+}
+
+/// How one kind of event is announced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationPreference {
+    /// Show a desktop notification
+    pub desktop: bool,
+    /// Play the notification sound
+    pub sound: bool,
+}
+
+impl Default for NotificationPreference {
+    fn default() -> Self {
+        Self {
+            desktop: true,
+            sound: false,
+        }
+    }
+}
+
+/// Notification preferences (part of the app settings)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub run_completed: NotificationPreference,
+    pub run_failed: NotificationPreference,
+    pub alarm: NotificationPreference,
+    /// Only notify while the GUI window is not focused
+    pub only_when_unfocused: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            run_completed: NotificationPreference::default(),
+            run_failed: NotificationPreference {
+                desktop: true,
+                sound: true,
+            },
+            alarm: NotificationPreference {
+                desktop: true,
+                sound: true,
+            },
+            only_when_unfocused: true,
+        }
+    }
+}
+
+impl NotificationSettings {
+    /// Preference for one kind of event
+    pub fn preference(&self, kind: NotificationKind) -> NotificationPreference {
+        match kind {
+            NotificationKind::RunCompleted => self.run_completed,
+            NotificationKind::RunFailed => self.run_failed,
+            NotificationKind::Alarm => self.alarm,
+        }
+    }
+
+    /// Mutable preference for one kind of event (settings window)
+    pub fn preference_mut(&mut self, kind: NotificationKind) -> &mut NotificationPreference {
+        match kind {
+            NotificationKind::RunCompleted => &mut self.run_completed,
+            NotificationKind::RunFailed => &mut self.run_failed,
+            NotificationKind::Alarm => &mut self.alarm,
+        }
+    }
+}
+
+/// Something worth telling the user about
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+}
+
+/// Turns start/stop documents into run notifications.
+#[derive(Debug, Default)]
+pub struct RunTracker {
+    /// Run UID -> plan name, from start documents
+    names: HashMap<String, String>,
+}
+
+impl RunTracker {
+    /// Notification for a document, if it ends a run
+    pub fn on_document(&mut self, doc: &Document) -> Option<Notification> {
+        match doc.payload.as_ref()? {
+            Payload::Start(start) => {
+                let name = if start.plan_name.is_empty() {
+                    start.plan_type.clone()
+                } else {
+                    start.plan_name.clone()
+                };
+                self.names.insert(start.run_uid.clone(), name);
+                None
+            }
+            Payload::Stop(stop) => {
+                let name = self
+                    .names
+                    .remove(&stop.run_uid)
+                    .unwrap_or_else(|| short_uid(&stop.run_uid).to_string());
+                if stop.exit_status == "success" {
+                    Some(Notification {
+                        kind: NotificationKind::RunCompleted,
+                        title: format!("Run completed: {}", name),
+                        body: format!("{} events recorded", stop.num_events),
+                    })
+                } else {
+                    let mut body = format!("Exit status: {}", stop.exit_status);
+                    if !stop.reason.is_empty() {
+                        body = format!("{} ({})", body, stop.reason);
+                    }
+                    Some(Notification {
+                        kind: NotificationKind::RunFailed,
+                        title: format!("Run {}: {}", stop.exit_status, name),
+                        body,
+                    })
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// First 8 characters of a run UID, for display
+fn short_uid(uid: &str) -> &str {
+    uid.get(..8).unwrap_or(uid)
+}
+
+/// Notification for a module event, if it is an alarm
+pub fn alarm_notification(event: &ModuleEvent, module_name: &str) -> Option<Notification> {
+    let alarm = event.event_type == "condition_active"
+        || matches!(
+            event.severity(),
+            ModuleEventSeverity::Error | ModuleEventSeverity::Critical
+        );
+    alarm.then(|| Notification {
+        kind: NotificationKind::Alarm,
+        title: format!("Alarm: {}", module_name),
+        body: event.message.clone(),
+    })
+}
+
+/// Follows the document and module event streams in the background.
+pub struct RunNotifier {
+    tx: mpsc::Sender<Notification>,
+    rx: mpsc::Receiver<Notification>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Default for RunNotifier {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel(64);
+        Self {
+            tx,
+            rx,
+            tasks: Vec::new(),
+        }
+    }
+}
+
+impl RunNotifier {
+    /// Start following the daemon (restarts if running).
+    pub fn start(&mut self, client: DaqClient, runtime: &tokio::runtime::Runtime) {
+        self.stop();
+        self.tasks
+            .push(runtime.spawn(follow_documents(client.clone(), self.tx.clone())));
+        self.tasks
+            .push(runtime.spawn(follow_modules(client, self.tx.clone())));
+    }
+
+    /// Stop following the daemon, discarding undelivered notifications.
+    pub fn stop(&mut self) {
+        for task in self.tasks.drain(..) {
+            task.abort();
+        }
+        while self.rx.try_recv().is_ok() {}
+    }
+
+    /// Drain notifications from the background tasks.
+    pub fn poll(&mut self) -> Vec<Notification> {
+        let mut notifications = Vec::new();
+        while let Ok(notification) = self.rx.try_recv() {
+            notifications.push(notification);
+        }
+        notifications
+    }
+}
+
+async fn follow_documents(mut client: DaqClient, tx: mpsc::Sender<Notification>) {
+    let doc_types = vec![DocumentType::DocStart as i32, DocumentType::DocStop as i32];
+    let mut stream = match client.stream_documents(None, doc_types).await {
+        Ok(stream) => Box::pin(stream),
+        Err(e) => {
+            tracing::warn!("Run notifications unavailable: {}", e);
+            return;
+        }
+    };
+    let mut runs = RunTracker::default();
+    while let Some(Ok(doc)) = stream.next().await {
+        if let Some(notification) = runs.on_document(&doc) {
+            if tx.send(notification).await.is_err() {
+                return;
+            }
+        }
+    }
+    tracing::debug!("Document stream for notifications ended");
+}
+
+async fn follow_modules(mut client: DaqClient, tx: mpsc::Sender<Notification>) {
+    let mut followed = HashSet::new();
+    // Dropped (aborting the per-module streams) when this task is aborted
+    let mut streams = JoinSet::new();
+    loop {
+        match client.list_modules().await {
+            Ok(modules) => {
+                for module in modules {
+                    if followed.insert(module.module_id.clone()) {
+                        let name = if module.instance_name.is_empty() {
+                            module.module_id.clone()
+                        } else {
+                            module.instance_name.clone()
+                        };
+                        streams.spawn(follow_module(
+                            client.clone(),
+                            tx.clone(),
+                            module.module_id,
+                            name,
+                        ));
+                    }
+                }
+            }
+            Err(e) => tracing::debug!("Alarm notifications: failed to list modules: {}", e),
+        }
+        // Reap streams that ended (module removed)
+        while streams.try_join_next().is_some() {}
+        if tx.is_closed() {
+            return;
+        }
+        tokio::time::sleep(MODULE_POLL_INTERVAL).await;
+    }
+}
+
+async fn follow_module(
+    mut client: DaqClient,
+    tx: mpsc::Sender<Notification>,
+    module_id: String,
+    name: String,
+) {
+    let mut stream = match client.stream_module_events(&module_id, Vec::new()).await {
+        Ok(stream) => Box::pin(stream),
+        Err(e) => {
+            tracing::debug!("Alarm notifications unavailable for {}: {}", module_id, e);
+            return;
+        }
+    };
+    while let Some(Ok(event)) = stream.next().await {
+        if let Some(notification) = alarm_notification(&event, &name) {
+            if tx.send(notification).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Show a desktop notification (blocking; call off the UI thread)
+#[cfg(feature = "desktop_notifications")]
+pub fn show_desktop(notification: &Notification, sound: bool) {
+    let mut desktop = notify_rust::Notification::new();
+    desktop
+        .appname("rust-daq")
+        .summary(&notification.title)
+        .body(&notification.body);
+    if sound {
+        desktop.sound_name(match notification.kind {
+            NotificationKind::RunCompleted => "complete",
+            NotificationKind::RunFailed | NotificationKind::Alarm => "dialog-warning",
+        });
+    }
+    if let Err(e) = desktop.show() {
+        tracing::warn!("Failed to show desktop notification: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::daq::{StartDocument, StopDocument};
+
+    fn start(run_uid: &str, plan_name: &str) -> Document {
+        Document {
+            payload: Some(Payload::Start(StartDocument {
+                run_uid: run_uid.to_string(),
+                plan_type: "grid_scan".to_string(),
+                plan_name: plan_name.to_string(),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+    }
+
+    fn stop(run_uid: &str, exit_status: &str, reason: &str) -> Document {
+        Document {
+            payload: Some(Payload::Stop(StopDocument {
+                run_uid: run_uid.to_string(),
+                exit_status: exit_status.to_string(),
+                reason: reason.to_string(),
+                num_events: 121,
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_run_notifications_from_documents() {
+        let mut runs = RunTracker::default();
+        assert!(runs.on_document(&start("run-1", "Focus scan")).is_none());
+        let done = runs.on_document(&stop("run-1", "success", "")).unwrap();
+        assert_eq!(done.kind, NotificationKind::RunCompleted);
+        assert_eq!(done.title, "Run completed: Focus scan");
+        assert_eq!(done.body, "121 events recorded");
+
+        runs.on_document(&start("run-2", ""));
+        let failed = runs
+            .on_document(&stop("run-2", "fail", "stage timeout"))
+            .unwrap();
+        assert_eq!(failed.kind, NotificationKind::RunFailed);
+        assert_eq!(failed.title, "Run fail: grid_scan");
+        assert_eq!(failed.body, "Exit status: fail (stage timeout)");
+
+        // A stop without a start we saw (GUI connected mid-run)
+        let unknown = runs
+            .on_document(&stop("0123456789abcdef", "abort", ""))
+            .unwrap();
+        assert_eq!(unknown.title, "Run abort: 01234567");
+    }
+
+    #[test]
+    fn test_alarm_notifications_from_module_events() {
+        let event = |event_type: &str, severity: ModuleEventSeverity| ModuleEvent {
+            module_id: "pm-1".to_string(),
+            event_type: event_type.to_string(),
+            severity: severity as i32,
+            message: "Alarm condition met: power < 0.8".to_string(),
+            ..Default::default()
+        };
+        let alarm = alarm_notification(
+            &event("condition_active", ModuleEventSeverity::Warning),
+            "Laser power",
+        )
+        .unwrap();
+        assert_eq!(alarm.kind, NotificationKind::Alarm);
+        assert_eq!(alarm.title, "Alarm: Laser power");
+        assert!(alarm_notification(&event("error", ModuleEventSeverity::Error), "pm").is_some());
+        assert!(
+            alarm_notification(&event("condition_cleared", ModuleEventSeverity::Info), "pm")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_settings_defaults_fill_missing_fields() {
+        let settings: NotificationSettings =
+            serde_json::from_str(r#"{"run_completed": {"sound": true}}"#).unwrap();
+        assert!(settings.run_completed.desktop);
+        assert!(settings.run_completed.sound);
+        assert_eq!(settings.alarm, NotificationSettings::default().alarm);
+        assert!(settings.only_when_unfocused);
+    }
+}
@@ -0,0 +1,487 @@
+//! Offline mode: last-known daemon state for when the daemon is unreachable.
+//!
+//! While connected the GUI keeps a copy of the device list, each device's
+//! parameter schema and its latest readings. The copy is persisted with the
+//! rest of the app state, so when the daemon can't be reached (even right
+//! after a restart of the GUI) the Instruments tab shows it read-only instead
+//! of an empty "Not Connected" screen.
+//!
+//! Parameter changes and moves entered while offline are queued and replayed
+//! in order on the next connection to the same daemon. Dangerous parameters
+//! are never applied blindly: the daemon's confirmation request is reported
+//! back instead.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use client::DaqClient;
+use protocol::daq::{DeviceInfo, ParameterDescriptor};
+
+/// Milliseconds since the UNIX epoch
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Short human-readable age ("42 s", "5 min", "3 h", "2 d")
+pub fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..=59 => format!("{} s", secs),
+        60..=3599 => format!("{} min", secs / 60),
+        3600..=86_399 => format!("{} h", secs / 3600),
+        _ => format!("{} d", secs / 86_400),
+    }
+}
+
+/// Device as last listed by the daemon
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedDevice {
+    pub id: String,
+    pub name: String,
+    pub driver_type: String,
+    #[serde(default)]
+    pub is_movable: bool,
+    #[serde(default)]
+    pub is_readable: bool,
+    #[serde(default)]
+    pub is_frame_producer: bool,
+    #[serde(default)]
+    pub simulated: bool,
+}
+
+impl From<&DeviceInfo> for CachedDevice {
+    #[allow(deprecated)] // capability flags still used for GUI routing
+    fn from(info: &DeviceInfo) -> Self {
+        Self {
+            id: info.id.clone(),
+            name: info.name.clone(),
+            driver_type: info.driver_type.clone(),
+            is_movable: info.is_movable,
+            is_readable: info.is_readable,
+            is_frame_producer: info.is_frame_producer,
+            simulated: info.simulated,
+        }
+    }
+}
+
+/// Parameter schema with its last known value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedParameter {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub dtype: String,
+    #[serde(default)]
+    pub units: String,
+    #[serde(default)]
+    pub writable: bool,
+    #[serde(default)]
+    pub dangerous: bool,
+    #[serde(default)]
+    pub min_value: Option<f64>,
+    #[serde(default)]
+    pub max_value: Option<f64>,
+    #[serde(default)]
+    pub enum_values: Vec<String>,
+    /// Last value read, if any
+    #[serde(default)]
+    pub value: Option<String>,
+}
+
+impl From<&ParameterDescriptor> for CachedParameter {
+    fn from(desc: &ParameterDescriptor) -> Self {
+        Self {
+            name: desc.name.clone(),
+            description: desc.description.clone(),
+            dtype: desc.dtype.clone(),
+            units: desc.units.clone(),
+            writable: desc.writable,
+            dangerous: desc.dangerous,
+            min_value: desc.min_value,
+            max_value: desc.max_value,
+            enum_values: desc.enum_values.clone(),
+            value: None,
+        }
+    }
+}
+
+impl CachedParameter {
+    /// Descriptor form, for checking edits made offline
+    pub fn descriptor(&self) -> ParameterDescriptor {
+        ParameterDescriptor {
+            name: self.name.clone(),
+            dtype: self.dtype.clone(),
+            units: self.units.clone(),
+            writable: self.writable,
+            min_value: self.min_value,
+            max_value: self.max_value,
+            enum_values: self.enum_values.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Latest state read from a device
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CachedState {
+    pub online: bool,
+    pub position: Option<f64>,
+    pub reading: Option<f64>,
+    /// When the state was read (UNIX milliseconds)
+    pub updated_ms: u64,
+}
+
+/// An action entered while offline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum QueuedActionKind {
+    SetParameter { name: String, value: String },
+    MoveAbsolute { position: f64 },
+    MoveRelative { distance: f64 },
+}
+
+/// Queued action with its target device
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedAction {
+    pub device_id: String,
+    pub action: QueuedActionKind,
+    /// When the action was queued (UNIX milliseconds)
+    pub queued_ms: u64,
+}
+
+impl std::fmt::Display for QueuedAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.action {
+            QueuedActionKind::SetParameter { name, value } => {
+                write!(f, "{}: set {} = {}", self.device_id, name, value)
+            }
+            QueuedActionKind::MoveAbsolute { position } => {
+                write!(f, "{}: move to {}", self.device_id, position)
+            }
+            QueuedActionKind::MoveRelative { distance } => {
+                write!(f, "{}: move by {:+}", self.device_id, distance)
+            }
+        }
+    }
+}
+
+/// Last-known daemon state plus actions waiting for a connection
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OfflineCache {
+    /// Daemon address the state came from
+    pub daemon: String,
+    /// When the device list was last refreshed (UNIX milliseconds)
+    pub updated_ms: u64,
+    pub devices: Vec<CachedDevice>,
+    /// Device ID -> parameters
+    pub parameters: BTreeMap<String, Vec<CachedParameter>>,
+    /// Device ID -> latest state
+    pub states: BTreeMap<String, CachedState>,
+    pub queue: Vec<QueuedAction>,
+}
+
+impl OfflineCache {
+    /// Whether there is anything to show offline
+    pub fn has_state(&self) -> bool {
+        !self.devices.is_empty()
+    }
+
+    /// Age of the cached device list
+    pub fn age(&self) -> Duration {
+        Duration::from_millis(now_ms().saturating_sub(self.updated_ms))
+    }
+
+    /// Replace the device list, dropping data for devices that are gone.
+    ///
+    /// Switching to another daemon discards everything cached for the old one,
+    /// including queued actions.
+    pub fn set_devices(&mut self, daemon: &str, devices: Vec<CachedDevice>) {
+        if self.daemon != daemon {
+            *self = Self {
+                daemon: daemon.to_string(),
+                ..Self::default()
+            };
+        }
+        self.parameters
+            .retain(|id, _| devices.iter().any(|d| &d.id == id));
+        self.states
+            .retain(|id, _| devices.iter().any(|d| &d.id == id));
+        self.devices = devices;
+        self.updated_ms = now_ms();
+    }
+
+    /// Record a device's parameters, keeping known values the update lacks
+    pub fn set_parameters(&mut self, device_id: &str, mut parameters: Vec<CachedParameter>) {
+        if let Some(previous) = self.parameters.get(device_id) {
+            for param in parameters.iter_mut().filter(|p| p.value.is_none()) {
+                param.value = previous
+                    .iter()
+                    .find(|p| p.name == param.name)
+                    .and_then(|p| p.value.clone());
+            }
+        }
+        self.parameters.insert(device_id.to_string(), parameters);
+    }
+
+    /// Record a device's latest state
+    pub fn set_state(&mut self, device_id: &str, state: CachedState) {
+        self.states.insert(device_id.to_string(), state);
+    }
+
+    /// Queue an action for the next connection
+    pub fn enqueue(&mut self, device_id: &str, action: QueuedActionKind) {
+        self.queue.push(QueuedAction {
+            device_id: device_id.to_string(),
+            action,
+            queued_ms: now_ms(),
+        });
+    }
+
+    /// Take the queued actions if `daemon` is the daemon they were queued for
+    pub fn take_queue_for(&mut self, daemon: &str) -> Option<Vec<QueuedAction>> {
+        if self.queue.is_empty() || self.daemon != daemon {
+            return None;
+        }
+        Some(std::mem::take(&mut self.queue))
+    }
+}
+
+/// Result of replaying one queued action
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayResult {
+    Applied,
+    /// Dangerous parameter: the daemon wants confirmation, nothing was changed
+    NeedsConfirmation,
+    Failed(String),
+}
+
+/// Messages from the background sync task.
+pub enum OfflineSyncMessage {
+    /// Parameter schemas fetched for a device
+    Schemas {
+        device_id: String,
+        parameters: Vec<CachedParameter>,
+    },
+    /// One queued action was replayed
+    Replayed {
+        action: QueuedAction,
+        result: ReplayResult,
+    },
+}
+
+/// Background work keeping the offline cache in step with the daemon.
+pub struct OfflineSync {
+    tx: mpsc::Sender<OfflineSyncMessage>,
+    rx: mpsc::Receiver<OfflineSyncMessage>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Default for OfflineSync {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel(64);
+        Self {
+            tx,
+            rx,
+            tasks: Vec::new(),
+        }
+    }
+}
+
+impl OfflineSync {
+    /// Fetch parameter schemas for these devices
+    pub fn fetch_schemas(
+        &mut self,
+        client: DaqClient,
+        runtime: &tokio::runtime::Runtime,
+        device_ids: Vec<String>,
+    ) {
+        let tx = self.tx.clone();
+        self.tasks
+            .push(runtime.spawn(fetch_schemas(client, tx, device_ids)));
+    }
+
+    /// Replay queued actions in order
+    pub fn replay(
+        &mut self,
+        client: DaqClient,
+        runtime: &tokio::runtime::Runtime,
+        actions: Vec<QueuedAction>,
+    ) {
+        let tx = self.tx.clone();
+        self.tasks.push(runtime.spawn(replay(client, tx, actions)));
+    }
+
+    /// Drain messages from the background tasks.
+    pub fn poll(&mut self) -> Vec<OfflineSyncMessage> {
+        self.tasks.retain(|task| !task.is_finished());
+        let mut messages = Vec::new();
+        while let Ok(message) = self.rx.try_recv() {
+            messages.push(message);
+        }
+        messages
+    }
+}
+
+async fn fetch_schemas(
+    mut client: DaqClient,
+    tx: mpsc::Sender<OfflineSyncMessage>,
+    device_ids: Vec<String>,
+) {
+    for device_id in device_ids {
+        match client.list_parameters(&device_id).await {
+            Ok(descriptors) => {
+                let parameters = descriptors.iter().map(CachedParameter::from).collect();
+                let message = OfflineSyncMessage::Schemas {
+                    device_id,
+                    parameters,
+                };
+                if tx.send(message).await.is_err() {
+                    return;
+                }
+            }
+            Err(e) => {
+                tracing::debug!("Offline cache: no parameters for {}: {}", device_id, e);
+            }
+        }
+    }
+}
+
+async fn replay(
+    mut client: DaqClient,
+    tx: mpsc::Sender<OfflineSyncMessage>,
+    actions: Vec<QueuedAction>,
+) {
+    for action in actions {
+        let result = match &action.action {
+            QueuedActionKind::SetParameter { name, value } => {
+                match client.set_parameter(&action.device_id, name, value).await {
+                    Ok(resp) if resp.confirmation_required => ReplayResult::NeedsConfirmation,
+                    Ok(resp) if resp.success => ReplayResult::Applied,
+                    Ok(resp) => ReplayResult::Failed(resp.error_message),
+                    Err(e) => ReplayResult::Failed(e.to_string()),
+                }
+            }
+            QueuedActionKind::MoveAbsolute { position } => {
+                move_result(client.move_absolute(&action.device_id, *position).await)
+            }
+            QueuedActionKind::MoveRelative { distance } => {
+                move_result(client.move_relative(&action.device_id, *distance).await)
+            }
+        };
+        if tx
+            .send(OfflineSyncMessage::Replayed { action, result })
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+fn move_result(response: anyhow::Result<protocol::daq::MoveResponse>) -> ReplayResult {
+    match response {
+        Ok(resp) if resp.success => ReplayResult::Applied,
+        Ok(resp) => ReplayResult::Failed(resp.error_message),
+        Err(e) => ReplayResult::Failed(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(id: &str) -> CachedDevice {
+        CachedDevice {
+            id: id.to_string(),
+            name: id.to_string(),
+            driver_type: "mock_stage".to_string(),
+            is_movable: true,
+            is_readable: false,
+            is_frame_producer: false,
+            simulated: true,
+        }
+    }
+
+    fn param(name: &str, value: Option<&str>) -> CachedParameter {
+        CachedParameter {
+            name: name.to_string(),
+            description: String::new(),
+            dtype: "float".to_string(),
+            units: "mm".to_string(),
+            writable: true,
+            dangerous: false,
+            min_value: None,
+            max_value: None,
+            enum_values: Vec::new(),
+            value: value.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_device_refresh_keeps_known_parameters() {
+        let mut cache = OfflineCache::default();
+        cache.set_devices("http://lab:50051", vec![device("stage"), device("old")]);
+        cache.set_parameters("stage", vec![param("velocity", Some("2.0"))]);
+        cache.set_parameters("old", vec![param("velocity", None)]);
+
+        // A schema refresh without values keeps the last known value
+        cache.set_parameters("stage", vec![param("velocity", None), param("accel", None)]);
+        assert_eq!(cache.parameters["stage"][0].value.as_deref(), Some("2.0"));
+
+        cache.set_devices("http://lab:50051", vec![device("stage")]);
+        assert!(cache.parameters.contains_key("stage"));
+        assert!(!cache.parameters.contains_key("old"));
+    }
+
+    #[test]
+    fn test_queue_replays_only_on_same_daemon() {
+        let mut cache = OfflineCache::default();
+        cache.set_devices("http://lab:50051", vec![device("stage")]);
+        cache.enqueue("stage", QueuedActionKind::MoveAbsolute { position: 12.5 });
+        assert_eq!(cache.queue[0].to_string(), "stage: move to 12.5");
+
+        assert!(cache.take_queue_for("http://other:50051").is_none());
+        assert_eq!(cache.take_queue_for("http://lab:50051").unwrap().len(), 1);
+        assert!(cache.queue.is_empty());
+
+        // Connecting somewhere else replaces the cached state and queue
+        cache.enqueue("stage", QueuedActionKind::MoveRelative { distance: -1.0 });
+        cache.set_devices("http://other:50051", vec![device("camera")]);
+        assert!(cache.queue.is_empty());
+        assert_eq!(cache.devices[0].id, "camera");
+    }
+
+    #[test]
+    fn test_cache_round_trips_through_json() {
+        let mut cache = OfflineCache::default();
+        cache.set_devices("http://lab:50051", vec![device("stage")]);
+        cache.set_parameters("stage", vec![param("velocity", Some("2.0"))]);
+        cache.enqueue(
+            "stage",
+            QueuedActionKind::SetParameter {
+                name: "velocity".to_string(),
+                value: "3.0".to_string(),
+            },
+        );
+        let json = serde_json::to_string(&cache).unwrap();
+        assert_eq!(serde_json::from_str::<OfflineCache>(&json).unwrap(), cache);
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(Duration::from_secs(42)), "42 s");
+        assert_eq!(format_age(Duration::from_secs(300)), "5 min");
+        assert_eq!(format_age(Duration::from_secs(3 * 3600)), "3 h");
+        assert_eq!(format_age(Duration::from_secs(2 * 86_400)), "2 d");
+    }
+}
@@ -0,0 +1,310 @@
+//! Instrument console panel - send raw commands to a device and see the reply.
+//!
+//! For debugging wiring and firmware behaviour. The daemon refuses devices
+//! that the active run is using, and records every command in its audit log.
+
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use eframe::egui;
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+
+use crate::widgets::{offline_notice, OfflineContext};
+use client::DaqClient;
+use protocol::daq::{DeviceInfo, RawCommandResponse};
+
+/// Capability string of devices that accept raw commands
+const RAW_TERMINAL_CAPABILITY: &str = "raw_terminal";
+
+/// Maximum number of console lines kept
+const MAX_HISTORY: usize = 500;
+
+/// Result from an async action
+enum ActionResult {
+    Devices(Result<Vec<DeviceInfo>, String>),
+    Command {
+        device_id: String,
+        command: String,
+        result: Result<RawCommandResponse, String>,
+    },
+}
+
+/// One command and its outcome
+struct ConsoleLine {
+    device_id: String,
+    command: String,
+    /// Reply text, or the error if the command failed or was refused
+    output: String,
+    ok: bool,
+    elapsed_ms: u64,
+}
+
+/// Instrument console panel state
+pub struct InstrumentConsolePanel {
+    /// Devices supporting raw commands: (id, name)
+    devices: Vec<(String, String)>,
+    /// Device commands are sent to
+    selected_device: Option<String>,
+    /// Command being typed
+    command_input: String,
+    /// Wait for and show a reply
+    expect_response: bool,
+    /// Sent commands and their replies, oldest first
+    history: VecDeque<ConsoleLine>,
+    /// Last refresh timestamp
+    last_refresh: Option<Instant>,
+    /// Error message
+    error: Option<String>,
+    /// Async action result sender
+    action_tx: mpsc::Sender<ActionResult>,
+    /// Async action result receiver
+    action_rx: mpsc::Receiver<ActionResult>,
+    /// Number of in-flight async actions
+    action_in_flight: usize,
+}
+
+impl Default for InstrumentConsolePanel {
+    fn default() -> Self {
+        let (action_tx, action_rx) = mpsc::channel(16);
+        Self {
+            devices: Vec::new(),
+            selected_device: None,
+            command_input: String::new(),
+            expect_response: true,
+            history: VecDeque::new(),
+            last_refresh: None,
+            error: None,
+            action_tx,
+            action_rx,
+            action_in_flight: 0,
+        }
+    }
+}
+
+impl InstrumentConsolePanel {
+    /// Poll for async results and update state
+    fn poll_async_results(&mut self, ctx: &egui::Context) {
+        let mut updated = false;
+        while let Ok(result) = self.action_rx.try_recv() {
+            self.action_in_flight = self.action_in_flight.saturating_sub(1);
+            match result {
+                ActionResult::Devices(Ok(devices)) => {
+                    self.devices = devices
+                        .into_iter()
+                        .filter(|d| d.capabilities.iter().any(|c| c == RAW_TERMINAL_CAPABILITY))
+                        .map(|d| (d.id, d.name))
+                        .collect();
+                    if !self
+                        .devices
+                        .iter()
+                        .any(|(id, _)| Some(id) == self.selected_device.as_ref())
+                    {
+                        self.selected_device = self.devices.first().map(|(id, _)| id.clone());
+                    }
+                    self.last_refresh = Some(Instant::now());
+                    self.error = None;
+                }
+                ActionResult::Devices(Err(e)) => self.error = Some(e),
+                ActionResult::Command {
+                    device_id,
+                    command,
+                    result,
+                } => {
+                    let line = match result {
+                        Ok(response) if response.success => ConsoleLine {
+                            device_id,
+                            command,
+                            output: response.response,
+                            ok: true,
+                            elapsed_ms: response.elapsed_ms,
+                        },
+                        Ok(response) => ConsoleLine {
+                            device_id,
+                            command,
+                            output: response.error_message,
+                            ok: false,
+                            elapsed_ms: response.elapsed_ms,
+                        },
+                        Err(e) => ConsoleLine {
+                            device_id,
+                            command,
+                            output: e,
+                            ok: false,
+                            elapsed_ms: 0,
+                        },
+                    };
+                    if self.history.len() == MAX_HISTORY {
+                        self.history.pop_front();
+                    }
+                    self.history.push_back(line);
+                }
+            }
+            updated = true;
+        }
+
+        if self.action_in_flight > 0 || updated {
+            ctx.request_repaint();
+        }
+    }
+
+    /// Refresh the list of devices accepting raw commands
+    fn refresh(&mut self, client: &mut DaqClient, runtime: &Runtime) {
+        let mut client = client.clone();
+        let tx = self.action_tx.clone();
+        self.action_in_flight += 1;
+
+        runtime.spawn(async move {
+            let result = client.list_devices().await.map_err(|e| e.to_string());
+            let _ = tx.send(ActionResult::Devices(result)).await;
+        });
+    }
+
+    /// Send the typed command to the selected device
+    fn send(&mut self, client: &mut DaqClient, runtime: &Runtime, session_id: &str) {
+        let Some(device_id) = self.selected_device.clone() else {
+            return;
+        };
+        let command = self.command_input.trim().to_string();
+        if command.is_empty() {
+            return;
+        }
+        self.command_input.clear();
+
+        let mut client = client.clone();
+        let tx = self.action_tx.clone();
+        let expect_response = self.expect_response;
+        let session_id = session_id.to_string();
+        self.action_in_flight += 1;
+
+        runtime.spawn(async move {
+            let result = client
+                .send_raw_command(&device_id, &command, expect_response, &session_id)
+                .await
+                .map_err(|e| match e.downcast_ref::<tonic::Status>() {
+                    // Refusals (device busy, unsupported) carry a readable message
+                    Some(status) => status.message().to_string(),
+                    None => e.to_string(),
+                });
+            let _ = tx
+                .send(ActionResult::Command {
+                    device_id,
+                    command,
+                    result,
+                })
+                .await;
+        });
+    }
+
+    /// Render the instrument console panel
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        client: Option<&mut DaqClient>,
+        runtime: &Runtime,
+        session_id: Option<&str>,
+    ) {
+        self.poll_async_results(ui.ctx());
+
+        ui.heading("Instrument Console");
+
+        let Some(client) = client else {
+            offline_notice(ui, true, OfflineContext::Devices);
+            return;
+        };
+
+        if self.last_refresh.is_none() && self.action_in_flight == 0 && self.error.is_none() {
+            self.refresh(client, runtime);
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Device:");
+            let selected_text = self
+                .selected_device
+                .as_deref()
+                .unwrap_or("(none)")
+                .to_string();
+            egui::ComboBox::from_id_salt("console_device")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    for (id, name) in &self.devices {
+                        ui.selectable_value(
+                            &mut self.selected_device,
+                            Some(id.clone()),
+                            format!("{} ({})", name, id),
+                        );
+                    }
+                });
+            if ui.button("🔄 Refresh").clicked() {
+                self.refresh(client, runtime);
+            }
+            if ui.button("Clear").clicked() {
+                self.history.clear();
+            }
+        });
+
+        ui.colored_label(
+            egui::Color32::GRAY,
+            "Commands bypass the driver and are recorded in the daemon audit log. \
+             Devices in use by a run are refused.",
+        );
+
+        if let Some(err) = &self.error {
+            ui.colored_label(egui::Color32::RED, format!("Error: {}", err));
+        }
+
+        if self.devices.is_empty() && self.last_refresh.is_some() {
+            ui.label("No connected device accepts raw commands.");
+        }
+
+        ui.separator();
+
+        let input_height = ui.spacing().interact_size.y + 8.0;
+        egui::ScrollArea::vertical()
+            .max_height((ui.available_height() - input_height).max(0.0))
+            .auto_shrink([false, false])
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for line in &self.history {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.monospace(format!("{} > {}", line.device_id, line.command));
+                        if line.elapsed_ms > 0 {
+                            ui.weak(format!("{} ms", line.elapsed_ms));
+                        }
+                    });
+                    if line.ok {
+                        if !line.output.is_empty() {
+                            ui.monospace(&line.output);
+                        }
+                    } else {
+                        ui.colored_label(egui::Color32::RED, &line.output);
+                    }
+                }
+                if self.action_in_flight > 0 {
+                    ui.spinner();
+                }
+            });
+
+        ui.horizontal(|ui| {
+            let can_send = self.selected_device.is_some();
+            let response = ui.add_enabled(
+                can_send,
+                egui::TextEdit::singleline(&mut self.command_input)
+                    .font(egui::TextStyle::Monospace)
+                    .hint_text("Command (terminator added by the driver)")
+                    .desired_width(ui.available_width() - 180.0),
+            );
+            ui.checkbox(&mut self.expect_response, "Read reply");
+            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if (ui
+                .add_enabled(can_send, egui::Button::new("Send"))
+                .clicked()
+                || submitted)
+                && can_send
+            {
+                self.send(client, runtime, session_id.unwrap_or_default());
+                response.request_focus();
+            }
+        });
+    }
+}
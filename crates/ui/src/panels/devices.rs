@@ -17,6 +17,7 @@ use crate::icons;
 use crate::layout;
 use crate::widgets::{
     filter_parameters, group_parameters_by_prefix, offline_notice, OfflineContext, ParameterCache,
+    ParameterConfirmDialog, PendingProposal, ProposalDecision,
 };
 use client::DaqClient;
 
@@ -37,6 +38,8 @@ struct ParamSetResult {
     success: bool,
     actual_value: String,
     error: Option<String>,
+    /// Set when the parameter is dangerous and the change awaits confirmation
+    proposal: Option<PendingProposal>,
 }
 
 /// Cached device information
@@ -164,6 +167,8 @@ pub struct DevicesPanel {
     pp_editor: crate::widgets::PPEditor,
     /// Smart streaming editor (bd-cdh5.4)
     smart_stream_editor: crate::widgets::SmartStreamEditor,
+    /// Confirmation dialog for dangerous parameter changes
+    param_confirm: ParameterConfirmDialog,
 }
 
 impl Default for DevicesPanel {
@@ -195,6 +200,7 @@ impl Default for DevicesPanel {
             show_advanced: false,
             pp_editor: crate::widgets::PPEditor::new(),
             smart_stream_editor: crate::widgets::SmartStreamEditor::new(),
+            param_confirm: ParameterConfirmDialog::default(),
         }
     }
 }
@@ -351,7 +357,26 @@ impl DevicesPanel {
                     let key = (result.device_id.clone(), result.param_name.clone());
                     self.setting_params.remove(&key);
 
-                    if result.success {
+                    if let Some(proposal) = result.proposal {
+                        let param = self
+                            .devices
+                            .iter()
+                            .find(|d| d.info.id == result.device_id)
+                            .and_then(|d| {
+                                d.parameters
+                                    .iter()
+                                    .find(|p| p.descriptor.name == result.param_name)
+                            });
+                        let proposal = match param {
+                            Some(p) => proposal.with_details(
+                                &p.descriptor.description,
+                                &p.descriptor.units,
+                                Some(&p.current_value),
+                            ),
+                            None => proposal,
+                        };
+                        self.param_confirm.open(proposal);
+                    } else if result.success {
                         // Update cached value
                         if let Some(device) = self
                             .devices
@@ -399,7 +424,7 @@ impl DevicesPanel {
     }
 
     /// Render the devices panel
-    pub fn ui(&mut self, ui: &mut egui::Ui, client: Option<&mut DaqClient>, runtime: &Runtime) {
+    pub fn ui(&mut self, ui: &mut egui::Ui, mut client: Option<&mut DaqClient>, runtime: &Runtime) {
         // Poll for completed async operations (non-blocking)
         self.poll_async_results(ui.ctx());
 
@@ -573,6 +598,11 @@ impl DevicesPanel {
             }
         });
 
+        // Confirmation dialog for dangerous parameter changes
+        if let Some(decision) = self.param_confirm.show(ui.ctx()) {
+            self.resolve_proposal(client.as_deref_mut(), runtime, decision);
+        }
+
         // Execute pending action after UI is done borrowing self
         if let Some(action) = self.pending_action.take() {
             self.execute_action(action, client, runtime);
@@ -587,6 +617,9 @@ impl DevicesPanel {
             ui.heading(&info.name);
             ui.label(format!("ID: {}", info.id));
             ui.label(format!("Driver: {}", info.driver_type));
+            if !info.aliases.is_empty() {
+                ui.label(format!("Aliases: {}", info.aliases.join(", ")));
+            }
 
             ui.separator();
             ui.label("Capabilities:");
@@ -809,14 +842,19 @@ impl DevicesPanel {
             egui::CollapsingHeader::new("✨ PP Features")
                 .id_salt(egui::Id::new("pp_header").with(&info.id))
                 .show(ui, |ui| {
-                    if ui.button("🔄 Reset All to Defaults").clicked() {
+                    // Taken out so its parameters can be drawn by this panel
+                    let mut pp_editor = std::mem::take(&mut self.pp_editor);
+                    let reset = pp_editor.ui(ui, &info.id, &device.parameters, |ui, param| {
+                        self.render_single_parameter(ui, &info.id, param);
+                    });
+                    self.pp_editor = pp_editor;
+                    if reset {
                         self.pending_action = Some(PendingAction::ExecuteCommand {
                             device_id: info.id.clone(),
                             command: "reset_pp".into(),
                             args: "{}".into(),
                         });
                     }
-                    self.pp_editor.ui(ui, &info.id, &device.parameters);
                 });
 
             ui.add_space(8.0);
@@ -1446,27 +1484,98 @@ impl DevicesPanel {
                 .await;
 
             let set_result = match result {
-                Ok(response) => ParamSetResult {
-                    device_id: device_id_str,
-                    param_name: name_str,
-                    success: response.success,
-                    actual_value: response.actual_value,
-                    error: if response.success {
-                        None
-                    } else {
-                        Some(response.error_message)
-                    },
-                },
+                Ok(response) => {
+                    let proposal = PendingProposal::from_response(
+                        &device_id_str,
+                        &name_str,
+                        &value_str,
+                        &response,
+                    );
+                    ParamSetResult {
+                        device_id: device_id_str,
+                        param_name: name_str,
+                        success: response.success,
+                        actual_value: response.actual_value,
+                        error: if response.success || proposal.is_some() {
+                            None
+                        } else {
+                            Some(response.error_message)
+                        },
+                        proposal,
+                    }
+                }
                 Err(e) => ParamSetResult {
                     device_id: device_id_str,
                     param_name: name_str,
                     success: false,
                     actual_value: String::new(),
                     error: Some(e.to_string()),
+                    proposal: None,
                 },
             };
 
             let _ = tx.send(set_result).await;
         });
     }
+
+    /// Confirm, cancel or report an expired dangerous parameter change
+    fn resolve_proposal(
+        &mut self,
+        client: Option<&mut DaqClient>,
+        runtime: &Runtime,
+        decision: ProposalDecision,
+    ) {
+        match decision {
+            ProposalDecision::Confirm(proposal) => {
+                let Some(client) = client else {
+                    self.error = Some("Not connected to daemon".to_string());
+                    return;
+                };
+
+                let mut client = client.clone();
+                let tx = self.param_set_tx.clone();
+                self.setting_params
+                    .insert((proposal.device_id.clone(), proposal.parameter.clone()));
+
+                runtime.spawn(async move {
+                    let set_result = match client.confirm_parameter_change(&proposal.token).await {
+                        Ok(response) => ParamSetResult {
+                            device_id: proposal.device_id,
+                            param_name: proposal.parameter,
+                            success: response.success,
+                            actual_value: response.actual_value,
+                            error: (!response.success).then_some(response.error_message),
+                            proposal: None,
+                        },
+                        Err(e) => ParamSetResult {
+                            device_id: proposal.device_id,
+                            param_name: proposal.parameter,
+                            success: false,
+                            actual_value: String::new(),
+                            error: Some(e.to_string()),
+                            proposal: None,
+                        },
+                    };
+                    let _ = tx.send(set_result).await;
+                });
+            }
+            ProposalDecision::Cancel(proposal) => {
+                // Drop the edit so the field shows the unchanged value again
+                self.param_edit_buffers
+                    .remove(&(proposal.device_id.clone(), proposal.parameter.clone()));
+                if let Some(client) = client {
+                    let mut client = client.clone();
+                    runtime.spawn(async move {
+                        let _ = client.cancel_parameter_change(&proposal.token).await;
+                    });
+                }
+            }
+            ProposalDecision::Expired(proposal) => {
+                self.param_errors.insert(
+                    (proposal.device_id, proposal.parameter),
+                    "Change expired before it was confirmed".to_string(),
+                );
+            }
+        }
+    }
 }
@@ -6,6 +6,7 @@
 //! - Event: Measurement data points
 //! - Stop: Run completion status
 
+use client::reconnect::is_retriable_error;
 use eframe::egui;
 use futures::StreamExt;
 use protocol::daq::Document;
@@ -31,6 +32,10 @@ pub struct DocumentViewerPanel {
     is_subscribed: bool,
     rx: Option<mpsc::Receiver<Result<Document, String>>>,
     subscription_task: Option<JoinHandle<()>>,
+    /// Subscribe again once the connection is restored
+    resubscribe: bool,
+    /// The stream failed with a transport error (reported once)
+    stream_lost: bool,
 }
 
 impl DocumentViewerPanel {
@@ -46,9 +51,70 @@ impl DocumentViewerPanel {
             is_subscribed: false,
             rx: None,
             subscription_task: None,
+            resubscribe: false,
+            stream_lost: false,
         }
     }
 
+    /// Close the stream of a lost connection, to be reopened by [`Self::resume`]
+    pub fn suspend(&mut self) {
+        if !self.is_subscribed {
+            return;
+        }
+        if let Some(handle) = self.subscription_task.take() {
+            handle.abort();
+        }
+        self.is_subscribed = false;
+        self.rx = None;
+        self.resubscribe = true;
+    }
+
+    /// Reopen a stream closed by a lost connection
+    pub fn resume(&mut self, client: &crate::client::DaqClient, runtime: &tokio::runtime::Runtime) {
+        if std::mem::take(&mut self.resubscribe) && !self.is_subscribed {
+            self.document_log
+                .push("INFO: Connection restored, resubscribing".to_string());
+            self.subscribe(client, runtime);
+        }
+    }
+
+    /// Whether the stream failed with a transport error since the last call
+    pub fn take_stream_lost(&mut self) -> bool {
+        std::mem::take(&mut self.stream_lost)
+    }
+
+    fn subscribe(&mut self, client: &crate::client::DaqClient, runtime: &tokio::runtime::Runtime) {
+        let mut client = client.clone();
+        let (tx, rx) = mpsc::channel(100);
+        self.rx = Some(rx);
+        self.is_subscribed = true;
+
+        self.subscription_task = Some(runtime.spawn(async move {
+            match client.stream_documents(None, vec![]).await {
+                Ok(mut stream) => {
+                    while let Some(result) = stream.next().await {
+                        match result {
+                            Ok(doc) => {
+                                if tx.send(Ok(doc)).await.is_err() {
+                                    break;
+                                }
+                            }
+                            Err(status) => {
+                                let _ = tx.send(Err(format!("gRPC Error: {}", status))).await;
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    let _ = tx.send(Err(format!("Failed to subscribe: {}", e))).await;
+                }
+            }
+        }));
+        self.document_log
+            .push("INFO: Subscribing to stream...".to_string());
+    }
+
     /// Render the Document Viewer panel
     pub fn ui(
         &mut self,
@@ -70,6 +136,10 @@ impl DocumentViewerPanel {
                             .push(format!("ERROR: Stream disconnected: {}", err));
                         self.is_subscribed = false;
                         stream_disconnected = true;
+                        if is_retriable_error(&err) {
+                            self.resubscribe = true;
+                            self.stream_lost = true;
+                        }
                     }
                 }
             }
@@ -160,39 +230,8 @@ impl DocumentViewerPanel {
                 let connected = client.is_some();
                 let btn = ui.add_enabled(connected, egui::Button::new("Subscribe to Stream"));
                 if btn.clicked() {
-                    if let Some(client_ref) = client {
-                        let mut client = client_ref.clone();
-                        let (tx, rx) = mpsc::channel(100);
-                        self.rx = Some(rx);
-                        self.is_subscribed = true;
-
-                        self.subscription_task = Some(runtime.spawn(async move {
-                            match client.stream_documents(None, vec![]).await {
-                                Ok(mut stream) => {
-                                    while let Some(result) = stream.next().await {
-                                        match result {
-                                            Ok(doc) => {
-                                                if tx.send(Ok(doc)).await.is_err() {
-                                                    break;
-                                                }
-                                            }
-                                            Err(status) => {
-                                                let _ = tx
-                                                    .send(Err(format!("gRPC Error: {}", status)))
-                                                    .await;
-                                                break;
-                                            }
-                                        }
-                                    }
-                                }
-                                Err(e) => {
-                                    let _ =
-                                        tx.send(Err(format!("Failed to subscribe: {}", e))).await;
-                                }
-                            }
-                        }));
-                        self.document_log
-                            .push("INFO: Subscribing to stream...".to_string());
+                    if let Some(client) = client {
+                        self.subscribe(client, runtime);
                     }
                 }
                 if !connected {
@@ -254,6 +293,37 @@ fn format_document(doc: &Document) -> String {
                 stop.run_uid, stop.exit_status, stop.reason
             )
         }
+        Some(Payload::Progress(progress)) => {
+            format!(
+                "PROGRESS: {}/{} points, eta={}",
+                progress.points_completed,
+                progress.points_total,
+                progress
+                    .eta_s
+                    .map_or_else(|| "-".to_string(), |eta| format!("{eta:.1}s"))
+            )
+        }
+        Some(Payload::BatchSummary(summary)) => {
+            let succeeded = summary
+                .runs
+                .iter()
+                .filter(|run| run.exit_status == "success")
+                .count();
+            format!(
+                "BATCH: batch_uid={}, template={}, {}/{} runs succeeded",
+                summary.batch_uid,
+                summary.template_name,
+                succeeded,
+                summary.runs.len()
+            )
+        }
+        Some(Payload::DeviceEvent(doc)) => match &doc.event {
+            Some(event) => format!(
+                "DEVICE EVENT: device={}, kind={}, message={}",
+                event.device_id, event.kind, event.message
+            ),
+            None => "DEVICE EVENT: (empty)".to_string(),
+        },
         None => "UNKNOWN DOCUMENT".to_string(),
     }
 }
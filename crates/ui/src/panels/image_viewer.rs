@@ -18,12 +18,17 @@ use std::sync::mpsc;
 use std::time::{Duration, Instant};
 use tokio::runtime::Runtime;
 
+use crate::export::{
+    export_frame_csv, export_frame_png, fetch_export_context, CsvExportOptions, ExportContext,
+    FigureMetadata, RawFrame, ScreenCapture,
+};
 use crate::icons;
 use crate::layout::{self, colors};
 use crate::widgets::{Histogram, HistogramPosition, ParameterCache, RoiSelector};
 use client::DaqClient;
 use protocol::compression::decompress_frame;
-use protocol::daq::{FrameData, StreamQuality};
+use protocol::daq::{frame_annotation, FrameAnnotation, FrameData, FrameRegion, StreamQuality};
+use protocol::frame_tiles::{TileAssembler, TileSettings};
 
 /// Maximum frame queue depth (prevents memory buildup if GUI is slow)
 /// We only keep the latest frame anyway, so 4 frames is sufficient
@@ -33,6 +38,9 @@ const MAX_QUEUED_FRAMES: usize = 4;
 /// Debounce interval for live exposure updates (200ms)
 const EXPOSURE_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(200);
 
+/// Tile edge for tiled delivery (pixels of the streamed frame)
+const PREVIEW_TILE_SIZE: u32 = 64;
+
 /// Streaming metrics from server (bd-7rk0: gRPC improvements)
 ///
 /// Note: Some fields populated from proto but not yet displayed in UI.
@@ -63,6 +71,8 @@ pub struct FrameUpdate {
     pub timestamp_ns: u64,
     /// Streaming metrics from server (bd-7rk0)
     pub metrics: Option<StreamMetrics>,
+    /// Overlay annotations current for this frame
+    pub annotations: Vec<FrameAnnotation>,
 }
 
 impl From<FrameData> for FrameUpdate {
@@ -83,6 +93,7 @@ impl From<FrameData> for FrameUpdate {
             frame_number: frame.frame_number,
             timestamp_ns: frame.timestamp_ns,
             metrics,
+            annotations: frame.annotations,
         }
     }
 }
@@ -140,6 +151,102 @@ struct RgbaConversionResult {
     computed_max: f32,
 }
 
+/// Draw a camera's overlay annotations over the image at `image_rect`
+///
+/// Annotation coordinates are frame pixels; `zoom` is screen points per pixel.
+fn draw_frame_annotations(
+    painter: &egui::Painter,
+    image_rect: egui::Rect,
+    zoom: f32,
+    annotations: &[FrameAnnotation],
+) {
+    use frame_annotation::Shape;
+
+    let color = egui::Color32::from_rgb(0, 200, 255);
+    let stroke = egui::Stroke::new(1.5, color);
+    let font = egui::FontId::proportional(12.0);
+    let to_screen = |x: f64, y: f64| image_rect.min + egui::vec2(x as f32 * zoom, y as f32 * zoom);
+
+    for annotation in annotations {
+        let label = annotation.label.as_str();
+        match &annotation.shape {
+            Some(Shape::Roi(roi)) => {
+                let rect = egui::Rect::from_min_max(
+                    to_screen(roi.x, roi.y),
+                    to_screen(roi.x + roi.width, roi.y + roi.height),
+                );
+                painter.rect_stroke(rect, 0.0, stroke, egui::StrokeKind::Middle);
+                if !label.is_empty() {
+                    painter.text(
+                        rect.left_top() - egui::vec2(0.0, 2.0),
+                        egui::Align2::LEFT_BOTTOM,
+                        label,
+                        font.clone(),
+                        color,
+                    );
+                }
+            }
+            Some(Shape::Point(point)) => {
+                let pos = to_screen(point.x, point.y);
+                painter.circle_stroke(pos, 4.0, stroke);
+                if !label.is_empty() {
+                    painter.text(
+                        pos + egui::vec2(6.0, -6.0),
+                        egui::Align2::LEFT_BOTTOM,
+                        label,
+                        font.clone(),
+                        color,
+                    );
+                }
+            }
+            Some(Shape::Crosshair(point)) => {
+                let pos = to_screen(point.x, point.y);
+                painter.line_segment(
+                    [
+                        egui::pos2(image_rect.left(), pos.y),
+                        egui::pos2(image_rect.right(), pos.y),
+                    ],
+                    stroke,
+                );
+                painter.line_segment(
+                    [
+                        egui::pos2(pos.x, image_rect.top()),
+                        egui::pos2(pos.x, image_rect.bottom()),
+                    ],
+                    stroke,
+                );
+                if !label.is_empty() {
+                    painter.text(
+                        pos + egui::vec2(4.0, -4.0),
+                        egui::Align2::LEFT_BOTTOM,
+                        label,
+                        font.clone(),
+                        color,
+                    );
+                }
+            }
+            Some(Shape::ScaleBar(bar)) if bar.pixel_size > 0.0 => {
+                // Bottom-right corner, like a microscope scale bar
+                let width = (bar.length / bar.pixel_size) as f32 * zoom;
+                let right = image_rect.right() - 12.0;
+                let y = image_rect.bottom() - 12.0;
+                painter.line_segment(
+                    [egui::pos2(right - width, y), egui::pos2(right, y)],
+                    egui::Stroke::new(3.0, egui::Color32::WHITE),
+                );
+                painter.text(
+                    egui::pos2(right - width / 2.0, y - 4.0),
+                    egui::Align2::CENTER_BOTTOM,
+                    format!("{} {}", bar.length, bar.unit),
+                    font.clone(),
+                    egui::Color32::WHITE,
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
 /// Helper function to get pixel value from frame data (bd-pgcb)
 ///
 /// Used by crosshair feature. Free function to avoid borrow checker issues in closures.
@@ -893,6 +1000,34 @@ pub enum RecordingState {
     Stopping,
 }
 
+/// What an image export writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FrameExport {
+    /// Raw pixel values as 16-bit grayscale PNG
+    RawPng,
+    /// The view as displayed (colormap, contrast, overlays) as PNG
+    ViewPng,
+    /// Raw pixel values as a CSV matrix
+    Csv,
+}
+
+impl FrameExport {
+    fn label(self) -> &'static str {
+        match self {
+            FrameExport::RawPng => "PNG (raw values)",
+            FrameExport::ViewPng => "PNG (as displayed)",
+            FrameExport::Csv => "CSV (raw values)",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            FrameExport::RawPng | FrameExport::ViewPng => "png",
+            FrameExport::Csv => "csv",
+        }
+    }
+}
+
 /// Image Viewer Panel state
 pub struct ImageViewerPanel {
     /// Currently selected device ID
@@ -1024,6 +1159,8 @@ pub struct ImageViewerPanel {
     // -- Stream Quality Settings --
     /// Stream quality level for server-side downsampling
     stream_quality: StreamQuality,
+    /// Request keyframes plus changed tiles instead of whole frames
+    tiled_delivery: bool,
 
     // -- Background RGBA Conversion (bd-xifj: move CPU work off UI thread) --
     /// Receiver for completed RGBA conversions from background thread
@@ -1041,11 +1178,26 @@ pub struct ImageViewerPanel {
     /// Locked crosshair position (pixel coordinates)
     crosshair_locked_pos: Option<(i32, i32)>,
 
+    // -- Frame annotations --
+    /// Overlay annotations received with the latest frame
+    annotations: Vec<FrameAnnotation>,
+    /// Draw the annotations over the image
+    show_annotations: bool,
+
     // -- Interactive Colorbar (bd-07j1) --
     /// Interactive colorbar widget for midpoint adjustment
     colorbar: crate::widgets::Colorbar,
     /// Show colorbar in the image viewer
     show_colorbar: bool,
+
+    // -- Export --
+    /// Exports whose daemon context has arrived
+    export_tx: mpsc::Sender<(FrameExport, std::path::PathBuf, ExportContext)>,
+    export_rx: mpsc::Receiver<(FrameExport, std::path::PathBuf, ExportContext)>,
+    /// "As displayed" export waiting for its screenshot
+    export_capture: ScreenCapture,
+    /// Visible screen area of the image in the last frame
+    image_rect: Option<egui::Rect>,
 }
 
 impl Default for ImageViewerPanel {
@@ -1054,6 +1206,7 @@ impl Default for ImageViewerPanel {
         let (action_tx, action_rx) = std::sync::mpsc::channel();
         // Persistent channel for parameter set results - sender is cloned per request
         let (param_set_tx, param_set_rx) = mpsc::channel();
+        let (export_tx, export_rx) = mpsc::channel();
         Self {
             device_id: None,
             width: 0,
@@ -1120,6 +1273,7 @@ impl Default for ImageViewerPanel {
 
             // Stream quality for bandwidth control
             stream_quality: StreamQuality::Full,
+            tiled_delivery: false,
 
             // Physical coordinate calibration (bd-4088.6)
             pixel_scale_x: None,
@@ -1137,11 +1291,19 @@ impl Default for ImageViewerPanel {
             crosshair_enabled: false,
             crosshair_locked_pos: None,
 
+            annotations: Vec::new(),
+            show_annotations: true,
+
             // Interactive colorbar (bd-07j1)
             colorbar: crate::widgets::Colorbar::new()
                 .orientation(crate::widgets::ColorbarOrientation::Vertical)
                 .units("counts"),
             show_colorbar: true,
+
+            export_tx,
+            export_rx,
+            export_capture: ScreenCapture::default(),
+            image_rect: None,
         }
     }
 }
@@ -1812,6 +1974,109 @@ impl ImageViewerPanel {
         self.frame_tx.clone()
     }
 
+    /// Gather the daemon context for a frame export, then write it
+    fn begin_export(
+        &mut self,
+        kind: FrameExport,
+        path: std::path::PathBuf,
+        client: Option<&mut DaqClient>,
+        runtime: &Runtime,
+    ) {
+        match (client, &self.device_id) {
+            (Some(client), Some(device_id)) => {
+                let mut client = client.clone();
+                let device_ids = vec![device_id.clone()];
+                let tx = self.export_tx.clone();
+                self.status = Some("Exporting...".to_string());
+                runtime.spawn(async move {
+                    let context = fetch_export_context(&mut client, &device_ids).await;
+                    let _ = tx.send((kind, path, context));
+                });
+            }
+            // Offline: export with what the GUI knows
+            _ => {
+                let _ = self.export_tx.send((kind, path, ExportContext::default()));
+            }
+        }
+    }
+
+    /// Write exports whose context has arrived and finished view captures
+    fn poll_exports(&mut self, ctx: &egui::Context) {
+        while let Ok((kind, path, context)) = self.export_rx.try_recv() {
+            if let Err(e) = self.write_export(ctx, kind, &path, &context) {
+                self.error = Some(format!("Export failed: {}", e));
+            }
+        }
+        if let Some(result) = self.export_capture.poll(ctx) {
+            match result {
+                Ok(path) => self.status = Some(format!("Exported {}", path.display())),
+                Err(e) => self.error = Some(format!("Export failed: {}", e)),
+            }
+        }
+        if self.export_capture.is_pending() {
+            ctx.request_repaint();
+        }
+    }
+
+    /// Frame and display settings embedded in every export
+    fn export_metadata(&self, kind: FrameExport, context: &ExportContext) -> FigureMetadata {
+        let mut metadata = FigureMetadata::new(kind.label())
+            .with("Camera", self.device_id.clone().unwrap_or_default())
+            .with("Frame number", self.frame_count.to_string())
+            .with("Size (px)", format!("{}x{}", self.width, self.height))
+            .with("Bit depth", self.bit_depth.to_string());
+        if let (Some(sx), Some(sy)) = (self.pixel_scale_x, self.pixel_scale_y) {
+            metadata = metadata.with("Pixel size", format!("{} x {} {}", sx, sy, self.scale_unit));
+        }
+        if kind == FrameExport::ViewPng {
+            metadata = metadata
+                .with("Colormap", self.colormap.label())
+                .with("Scale", self.scale_mode.label())
+                .with(
+                    "Display range",
+                    format!("{:.3} – {:.3}", self.display_min, self.display_max),
+                );
+        }
+        context.apply(metadata)
+    }
+
+    fn write_export(
+        &mut self,
+        ctx: &egui::Context,
+        kind: FrameExport,
+        path: &std::path::Path,
+        context: &ExportContext,
+    ) -> Result<(), String> {
+        let metadata = self.export_metadata(kind, context);
+        if kind == FrameExport::ViewPng {
+            let rect = self.image_rect.ok_or("image not shown yet")?;
+            self.export_capture.request(
+                ctx,
+                egui::Id::new("image_viewer_export"),
+                rect,
+                path.to_path_buf(),
+                metadata,
+            );
+            return Ok(());
+        }
+
+        let data = self.last_frame_data.as_deref().ok_or("no frame received")?;
+        let frame = RawFrame {
+            width: self.width,
+            height: self.height,
+            bit_depth: self.bit_depth,
+            data,
+        };
+        let written = if kind == FrameExport::Csv {
+            export_frame_csv(path, &frame, &metadata, &CsvExportOptions::default())
+        } else {
+            export_frame_png(path, &frame, &metadata)
+        };
+        written.map_err(|e| e.to_string())?;
+        self.status = Some(format!("Exported {}", path.display()));
+        Ok(())
+    }
+
     /// Get pixel intensity value at given coordinates (bd-pgcb)
     pub(crate) fn get_pixel_value(&self, frame_data: &[u8], x: u32, y: u32) -> Option<u32> {
         if x >= self.width || y >= self.height {
@@ -1887,6 +2152,25 @@ impl ImageViewerPanel {
         let device_id_clone = device_id.to_string();
         let max_fps = self.max_fps;
         let stream_quality = self.stream_quality;
+        // Tiled delivery updates only tiles touching the ROIs, if any are drawn
+        let tile_settings = self.tiled_delivery.then(|| TileSettings {
+            tile_size: PREVIEW_TILE_SIZE,
+            regions: self
+                .roi_selector
+                .rois()
+                .iter()
+                .map(|roi| {
+                    let (x, y, width, height) = roi.shape.bounding_box();
+                    FrameRegion {
+                        x,
+                        y,
+                        width,
+                        height,
+                    }
+                })
+                .collect(),
+            ..Default::default()
+        });
 
         runtime.spawn(async move {
             use futures::StreamExt;
@@ -1916,7 +2200,17 @@ impl ImageViewerPanel {
             }
 
             // 2. Subscribe to the frame stream with quality setting
-            let stream = match client.stream_frames(&device_id_clone, max_fps, stream_quality).await {
+            let subscribed = match &tile_settings {
+                Some(tiles) => client
+                    .stream_frames_tiled(&device_id_clone, max_fps, stream_quality, tiles)
+                    .await
+                    .map(|s| s.boxed()),
+                None => client
+                    .stream_frames(&device_id_clone, max_fps, stream_quality)
+                    .await
+                    .map(|s| s.boxed()),
+            };
+            let stream = match subscribed {
                 Ok(s) => s,
                 Err(e) => {
                     // Clean up: stop stream if we started it successfully
@@ -1943,6 +2237,7 @@ impl ImageViewerPanel {
 
             let mut frames_received = 0u64;
             let mut frames_dropped = 0u64;
+            let mut assembler = TileAssembler::new();
 
             // Timeout for stream inactivity (30s) to prevent hanging on network faults (bd-7rk0)
             const STREAM_TIMEOUT: Duration = Duration::from_secs(30);
@@ -2004,6 +2299,17 @@ impl ImageViewerPanel {
                                     continue;
                                 }
 
+                                // Paste delta tiles over the last keyframe
+                                if let Err(e) = assembler.apply(&mut frame_data) {
+                                    tracing::debug!(
+                                        device_id = %device_id_clone,
+                                        frame = frames_received,
+                                        error = %e,
+                                        "Skipping tile update until next keyframe"
+                                    );
+                                    continue;
+                                }
+
                                 if frames_received > 10 && frames_received.is_multiple_of(30) {
                                     tracing::debug!(
                                         device_id = %device_id_clone,
@@ -2304,6 +2610,7 @@ impl ImageViewerPanel {
 
         // Store frame data for ROI statistics
         self.last_frame_data = Some(frame.data.clone());
+        self.annotations = frame.annotations.clone();
 
         // Update ROI statistics if we have an active ROI
         self.roi_selector.update_statistics(
@@ -2382,6 +2689,7 @@ impl ImageViewerPanel {
         let mut refresh_cameras = false;
         let mut start_recording = false;
         let mut stop_recording = false;
+        let mut frame_export: Option<FrameExport> = None;
 
         // Header with connection state indicator
         ui.horizontal(|ui| {
@@ -2545,6 +2853,23 @@ impl ImageViewerPanel {
                         ui.spinner();
                     }
                 }
+
+                // === Export ===
+                ui.separator();
+                let can_export =
+                    self.last_frame_data.is_some() && !self.export_capture.is_pending();
+                ui.add_enabled_ui(can_export, |ui| {
+                    ui.menu_button("📁 Export", |ui| {
+                        for kind in [FrameExport::RawPng, FrameExport::ViewPng, FrameExport::Csv] {
+                            if ui.button(kind.label()).clicked() {
+                                frame_export = Some(kind);
+                                ui.close();
+                            }
+                        }
+                    })
+                    .response
+                    .on_hover_text("Export the current frame with camera settings and run UID");
+                });
             });
         });
 
@@ -2571,6 +2896,11 @@ impl ImageViewerPanel {
                             "Fast (4x)",
                         );
                     });
+                ui.checkbox(&mut self.tiled_delivery, "Tiles")
+                    .on_hover_text(
+                        "Send only changed tiles (within ROIs, if any) between periodic \
+                         full frames. Saves bandwidth on slow links; applies on next start.",
+                    );
 
                 ui.separator();
 
@@ -2773,6 +3103,10 @@ impl ImageViewerPanel {
                 ui.separator();
 
                 ui.checkbox(&mut self.show_roi_panel, "Stats");
+                if !self.annotations.is_empty() {
+                    ui.checkbox(&mut self.show_annotations, "Annotations")
+                        .on_hover_text("Overlays marked on this camera (ROIs, points, scale bar)");
+                }
                 ui.checkbox(&mut self.show_controls, "Controls");
 
                 // === Histogram Position ===
@@ -2853,6 +3187,23 @@ impl ImageViewerPanel {
             None
         };
 
+        // Export the current frame (file dialog, then daemon context)
+        if let Some(kind) = frame_export {
+            if let Some(path) = rfd::FileDialog::new()
+                .set_file_name(format!(
+                    "{}_{}.{}",
+                    self.device_id.as_deref().unwrap_or("frame"),
+                    self.frame_count,
+                    kind.extension()
+                ))
+                .add_filter(kind.label(), &[kind.extension()])
+                .save_file()
+            {
+                self.begin_export(kind, path, client.as_deref_mut(), runtime);
+            }
+        }
+        self.poll_exports(ui.ctx());
+
         // Handle stop stream and recording actions
         if let Some(client) = client {
             if stop_stream {
@@ -3005,6 +3356,7 @@ impl ImageViewerPanel {
                             let offset = (image_available - image_size) / 2.0 + self.pan;
                             let image_rect =
                                 egui::Rect::from_min_size(rect.min + offset, image_size);
+                            self.image_rect = Some(image_rect.intersect(ui.clip_rect()));
 
                             // Handle ROI selection or pan depending on mode
                             if self.roi_selector.selection_mode {
@@ -3067,6 +3419,15 @@ impl ImageViewerPanel {
                                 self.pan,
                             );
 
+                            if self.show_annotations {
+                                draw_frame_annotations(
+                                    ui.painter(),
+                                    image_rect,
+                                    self.zoom,
+                                    &self.annotations,
+                                );
+                            }
+
                             // Draw histogram overlay if positioned on image
                             if self.histogram_position.is_overlay() {
                                 let hist_size = egui::vec2(180.0, 80.0);
@@ -10,6 +10,7 @@
 //! - Real-time state updates (position, readings, streaming status)
 //! - Pop-out support for device panels
 //! - PVCAM-specific features: PP Features reset, Smart Streaming configuration
+//! - Serial port picker with stable `serial:`/`usb:` specs for device configs
 //!
 //! ## Device Panel Routing
 //! Devices are routed to specialized panels based on driver type:
@@ -19,17 +20,22 @@
 //! - `pvcam`/`prime` → PVCAM panel with PP Features and Smart Streaming
 //! - Movable devices → StageControlPanel
 //! - Others → Generic control panel
+//!
+//! Devices whose driver ships a `ui_layout` get that panel instead of the
+//! routing above (after local TOML `[ui.control_panel]` configs).
 
 mod config_loader;
 mod config_renderer;
 #[cfg(test)]
 mod config_tests;
 mod dispatch;
+mod plugin_panel;
 mod types;
 
 // Note: dispatch module contains PanelType and determine_panel_type for future panel routing
 // Currently the panel selection logic is inline in render_device_control_panel
 use config_loader::DeviceConfigCache;
+use plugin_panel::{PanelCommand, PluginPanelState};
 pub use types::{DeviceCategory, DeviceGroup, ParameterInfo, PopOutRequest};
 
 use eframe::egui;
@@ -39,13 +45,15 @@ use std::sync::Arc;
 use tokio::runtime::Runtime;
 use tokio::sync::mpsc;
 
+use crate::offline_cache::{now_ms, CachedDevice, CachedParameter, CachedState, OfflineCache};
 use crate::panels::ComediPanel;
 use crate::widgets::{
-    offline_notice, DeviceControlWidget, MaiTaiControlPanel, OfflineContext,
-    PowerMeterControlPanel, RotatorControlPanel, SmartStreamEditor, StageControlPanel,
+    offline_notice, parse_typed_value, typed_value_text, DeviceControlWidget, MaiTaiControlPanel,
+    OfflineContext, ParameterConfirmDialog, PendingProposal, PortPicker, PowerMeterControlPanel,
+    ProposalDecision, RotatorControlPanel, SmartStreamEditor, StageControlPanel,
 };
 use client::DaqClient;
-use protocol::daq::DeviceInfo;
+use protocol::daq::{DeviceInfo, SerialPortInfo, TypedValue};
 
 /// Timeout for individual device state fetch (prevents stalls from hung devices)
 const DEVICE_STATE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
@@ -86,6 +94,8 @@ enum ActionResult {
         param_name: String,
         result: Result<String, String>,
     },
+    /// A dangerous parameter change awaits confirmation
+    ParameterProposed(PendingProposal),
     // Device control actions
     MoveDevice {
         device_id: String,
@@ -103,6 +113,13 @@ enum ActionResult {
         device_id: String,
         result: Result<(), String>,
     },
+    SerialPorts(Result<Vec<SerialPortInfo>, String>),
+    /// Value of a capability bound in a driver-shipped panel
+    PanelValue {
+        device_id: String,
+        name: String,
+        result: Result<serde_json::Value, String>,
+    },
 }
 
 /// Instrument Manager Panel state
@@ -134,6 +151,8 @@ pub struct InstrumentManagerPanel {
     params_viewer_error: Option<String>,
     /// Parameter edit state (for Configure dialog)
     param_edit_values: HashMap<String, String>,
+    /// Confirmation dialog for dangerous parameter changes
+    param_confirm: ParameterConfirmDialog,
     /// Pending context menu action (device_id, device_name, action_type)
     pending_action: Option<(String, String, ContextAction)>,
 
@@ -162,6 +181,8 @@ pub struct InstrumentManagerPanel {
     comedi_panels: HashMap<String, ComediPanel>,
     /// PVCAM Smart Stream editors (keyed by device_id)
     smart_stream_editors: HashMap<String, SmartStreamEditor>,
+    /// Driver-shipped panels (keyed by device_id)
+    plugin_panels: HashMap<String, PluginPanelState>,
 
     /// Pending pop-out request containing full device info
     /// Checked by DaqApp after each ui() call
@@ -169,6 +190,10 @@ pub struct InstrumentManagerPanel {
 
     /// Device configuration cache for UI config loading
     device_config_cache: DeviceConfigCache,
+
+    /// Serial port picker window
+    port_picker_open: bool,
+    port_picker: PortPicker,
 }
 
 /// Context menu actions
@@ -213,6 +238,7 @@ impl Default for InstrumentManagerPanel {
             params_viewer_loading: false,
             params_viewer_error: None,
             param_edit_values: HashMap::new(),
+            param_confirm: ParameterConfirmDialog::default(),
             pending_action: None,
             // Control panel state
             move_target: HashMap::new(),
@@ -227,8 +253,11 @@ impl Default for InstrumentManagerPanel {
             stage_panels: HashMap::new(),
             comedi_panels: HashMap::new(),
             smart_stream_editors: HashMap::new(),
+            plugin_panels: HashMap::new(),
             pending_pop_out: None,
             device_config_cache: DeviceConfigCache::new(),
+            port_picker_open: false,
+            port_picker: PortPicker::default(),
         }
     }
 }
@@ -254,6 +283,57 @@ impl InstrumentManagerPanel {
         self.status = None;
     }
 
+    /// Copy the device list, states and open parameters into the offline cache.
+    /// Does nothing until the first refresh has loaded devices.
+    pub fn record_offline_state(&self, cache: &mut OfflineCache, daemon: &str) {
+        if self.groups.is_empty() {
+            return;
+        }
+        let devices = self
+            .groups
+            .iter()
+            .flat_map(|g| g.devices.iter().map(CachedDevice::from))
+            .collect();
+        cache.set_devices(daemon, devices);
+
+        for (device_id, state) in &self.device_states {
+            let reading = self
+                .last_reading
+                .get(device_id)
+                .map(|(value, _)| *value)
+                .or(state.reading);
+            cache.set_state(
+                device_id,
+                CachedState {
+                    online: state.online,
+                    position: state.position,
+                    reading,
+                    updated_ms: now_ms(),
+                },
+            );
+        }
+
+        if let Some(device_id) = &self.params_viewer_device_id {
+            let parameters = self
+                .params_viewer_params
+                .iter()
+                .map(|p| CachedParameter {
+                    name: p.name.clone(),
+                    description: p.description.clone(),
+                    dtype: p.dtype.clone(),
+                    units: p.units.clone(),
+                    writable: p.writable,
+                    dangerous: p.dangerous,
+                    min_value: p.min_value,
+                    max_value: p.max_value,
+                    enum_values: p.enum_values.clone(),
+                    value: p.current_value.clone(),
+                })
+                .collect();
+            cache.set_parameters(device_id, parameters);
+        }
+    }
+
     /// Poll for async results
     fn poll_async_results(
         &mut self,
@@ -370,6 +450,25 @@ impl InstrumentManagerPanel {
                                 self.error = Some(format!("Failed to set {}: {}", param_name, e));
                             }
                         },
+                        ActionResult::ParameterProposed(proposal) => {
+                            let proposal = match self
+                                .params_viewer_params
+                                .iter()
+                                .find(|p| p.name == proposal.parameter)
+                                .filter(|_| {
+                                    self.params_viewer_device_id.as_deref()
+                                        == Some(proposal.device_id.as_str())
+                                }) {
+                                Some(param) => proposal.with_details(
+                                    &param.description,
+                                    &param.units,
+                                    param.current_value.as_deref(),
+                                ),
+                                None => proposal,
+                            };
+                            self.status = Some(format!("Confirm change to {}", proposal.parameter));
+                            self.param_confirm.open(proposal);
+                        }
                         // Device control action results
                         ActionResult::MoveDevice { device_id, result } => {
                             self.operation_pending.remove(&device_id);
@@ -397,6 +496,22 @@ impl InstrumentManagerPanel {
                                 }
                             }
                         }
+                        ActionResult::PanelValue {
+                            device_id,
+                            name,
+                            result,
+                        } => match result {
+                            Ok(value) => {
+                                self.plugin_panels
+                                    .entry(device_id)
+                                    .or_default()
+                                    .set_value(name, value);
+                            }
+                            // Polled every second, so only logged
+                            Err(e) => {
+                                tracing::debug!(device = %device_id, source = %name, "Panel read failed: {}", e);
+                            }
+                        },
                         ActionResult::StartStream { device_id, result } => {
                             self.operation_pending.remove(&device_id);
                             match result {
@@ -421,6 +536,7 @@ impl InstrumentManagerPanel {
                                 }
                             }
                         }
+                        ActionResult::SerialPorts(result) => self.port_picker.set_ports(result),
                     }
                     updated = true;
                 }
@@ -667,6 +783,15 @@ impl InstrumentManagerPanel {
             }
         }
 
+        // Confirmation dialog for dangerous parameter changes
+        if let Some(decision) = self.param_confirm.show(ui.ctx()) {
+            self.resolve_proposal(client.as_deref_mut(), runtime, decision);
+        }
+
+        if self.render_port_picker(ui.ctx()) {
+            self.list_serial_ports(client.as_deref_mut(), runtime);
+        }
+
         ui.heading("Instruments");
 
         // Show offline notice if not connected (bd-j3xz.4.4)
@@ -693,6 +818,16 @@ impl InstrumentManagerPanel {
             if ui.button("🔄 Refresh").clicked() {
                 self.refresh(client.as_deref_mut(), runtime);
             }
+            if ui
+                .selectable_label(self.port_picker_open, "🔌 Serial Ports")
+                .on_hover_text("Serial ports on the daemon host, for device configs")
+                .clicked()
+            {
+                self.port_picker_open = !self.port_picker_open;
+                if self.port_picker_open {
+                    self.list_serial_ports(client.as_deref_mut(), runtime);
+                }
+            }
 
             if let Some(last) = self.last_refresh {
                 let elapsed = last.elapsed();
@@ -960,6 +1095,38 @@ impl InstrumentManagerPanel {
         });
     }
 
+    /// Fetch the daemon host's serial ports for the port picker
+    fn list_serial_ports(&mut self, client: Option<&mut DaqClient>, runtime: &Runtime) {
+        let Some(client) = client else {
+            self.port_picker
+                .set_ports(Err("Not connected to daemon".to_string()));
+            return;
+        };
+
+        let mut client = client.clone();
+        let tx = self.action_tx.clone();
+        self.action_in_flight = self.action_in_flight.saturating_add(1);
+        self.port_picker.set_loading();
+
+        runtime.spawn(async move {
+            let result = client.list_serial_ports().await.map_err(|e| e.to_string());
+            let _ = tx.send(ActionResult::SerialPorts(result)).await;
+        });
+    }
+
+    /// Render the serial port picker window; returns `true` if a refresh was requested
+    fn render_port_picker(&mut self, ctx: &egui::Context) -> bool {
+        let mut refresh = false;
+        egui::Window::new("🔌 Serial Ports")
+            .open(&mut self.port_picker_open)
+            .resizable(true)
+            .default_width(460.0)
+            .show(ctx, |ui| {
+                refresh = self.port_picker.show(ui);
+            });
+        refresh
+    }
+
     /// Load parameters for a device (opens the parameter viewer)
     fn load_parameters(
         &mut self,
@@ -1002,7 +1169,8 @@ impl InstrumentManagerPanel {
             for desc in descriptors {
                 let current_value = if desc.readable {
                     match client.get_parameter(&device_id_clone, &desc.name).await {
-                        Ok(v) => Some(v.value),
+                        // Typed values show strings unquoted, matching enum choices
+                        Ok(v) => Some(v.typed.as_ref().map_or(v.value, typed_value_text)),
                         Err(_) => None,
                     }
                 } else {
@@ -1016,6 +1184,7 @@ impl InstrumentManagerPanel {
                     units: desc.units,
                     readable: desc.readable,
                     writable: desc.writable,
+                    dangerous: desc.dangerous,
                     min_value: desc.min_value,
                     max_value: desc.max_value,
                     enum_values: desc.enum_values,
@@ -1040,7 +1209,7 @@ impl InstrumentManagerPanel {
         runtime: &Runtime,
         device_id: String,
         param_name: String,
-        value: String,
+        value: TypedValue,
     ) {
         let Some(client) = client else {
             self.error = Some("Not connected to daemon".to_string());
@@ -1052,10 +1221,23 @@ impl InstrumentManagerPanel {
         self.action_in_flight = self.action_in_flight.saturating_add(1);
 
         runtime.spawn(async move {
-            let result = match client.set_parameter(&device_id, &param_name, &value).await {
+            let value_text = typed_value_text(&value);
+            let result = match client
+                .set_parameter_typed(&device_id, &param_name, value)
+                .await
+            {
                 Ok(resp) => {
+                    if let Some(proposal) =
+                        PendingProposal::from_response(&device_id, &param_name, &value_text, &resp)
+                    {
+                        let _ = tx.send(ActionResult::ParameterProposed(proposal)).await;
+                        return;
+                    }
                     if resp.success {
-                        Ok(resp.actual_value)
+                        Ok(resp
+                            .actual_typed
+                            .as_ref()
+                            .map_or(resp.actual_value, typed_value_text))
                     } else {
                         Err(resp.error_message)
                     }
@@ -1072,9 +1254,63 @@ impl InstrumentManagerPanel {
         });
     }
 
+    /// Confirm, cancel or report an expired dangerous parameter change
+    fn resolve_proposal(
+        &mut self,
+        client: Option<&mut DaqClient>,
+        runtime: &Runtime,
+        decision: ProposalDecision,
+    ) {
+        match decision {
+            ProposalDecision::Confirm(proposal) => {
+                let Some(client) = client else {
+                    self.error = Some("Not connected to daemon".to_string());
+                    return;
+                };
+
+                let mut client = client.clone();
+                let tx = self.action_tx.clone();
+                self.action_in_flight = self.action_in_flight.saturating_add(1);
+
+                runtime.spawn(async move {
+                    let result = match client.confirm_parameter_change(&proposal.token).await {
+                        Ok(resp) if resp.success => Ok(resp.actual_value),
+                        Ok(resp) => Err(resp.error_message),
+                        Err(e) => Err(e.to_string()),
+                    };
+                    let _ = tx
+                        .send(ActionResult::SetParameter {
+                            _device_id: proposal.device_id,
+                            param_name: proposal.parameter,
+                            result,
+                        })
+                        .await;
+                });
+            }
+            ProposalDecision::Cancel(proposal) => {
+                self.status = Some(format!("Cancelled change to {}", proposal.parameter));
+                if let Some(current) = proposal.current_value {
+                    self.param_edit_values.insert(proposal.parameter, current);
+                }
+                if let Some(client) = client {
+                    let mut client = client.clone();
+                    runtime.spawn(async move {
+                        let _ = client.cancel_parameter_change(&proposal.token).await;
+                    });
+                }
+            }
+            ProposalDecision::Expired(proposal) => {
+                self.error = Some(format!(
+                    "Change to {} expired before it was confirmed",
+                    proposal.parameter
+                ));
+            }
+        }
+    }
+
     /// Render the parameters viewer window
     /// Returns an optional (param_name, value) to set after rendering
-    fn render_params_viewer(&mut self, ctx: &egui::Context) -> Option<(String, String)> {
+    fn render_params_viewer(&mut self, ctx: &egui::Context) -> Option<(String, TypedValue)> {
         if !self.params_viewer_open {
             return None;
         }
@@ -1084,7 +1320,7 @@ impl InstrumentManagerPanel {
             .clone()
             .unwrap_or_else(|| "Device".to_string());
 
-        let mut action_to_perform: Option<(String, String)> = None;
+        let mut action_to_perform: Option<(String, TypedValue)> = None;
         let mut open = self.params_viewer_open;
 
         egui::Window::new(format!("Parameters: {}", device_name))
@@ -1128,7 +1364,13 @@ impl InstrumentManagerPanel {
                                 // Parameters - clone to avoid borrow issues
                                 let params = self.params_viewer_params.clone();
                                 for param in params {
-                                    ui.label(&param.name);
+                                    if param.dangerous {
+                                        ui.label(format!("⚠ {}", param.name)).on_hover_text(
+                                            "Dangerous setting: changes must be confirmed",
+                                        );
+                                    } else {
+                                        ui.label(&param.name);
+                                    }
 
                                     // Value display/edit
                                     if param.writable {
@@ -1203,9 +1445,24 @@ impl InstrumentManagerPanel {
                                                 .add_enabled(has_changes, egui::Button::new("Set"))
                                                 .clicked()
                                             {
-                                                if let Some(value) = current_edit.cloned() {
-                                                    action_to_perform =
-                                                        Some((param.name.clone(), value));
+                                                if let Some(text) = current_edit {
+                                                    // Typed check catches bad input before
+                                                    // the round trip to the daemon
+                                                    match parse_typed_value(
+                                                        &param.descriptor(),
+                                                        text,
+                                                    ) {
+                                                        Ok(value) => {
+                                                            action_to_perform =
+                                                                Some((param.name.clone(), value));
+                                                        }
+                                                        Err(e) => {
+                                                            self.error = Some(format!(
+                                                                "Invalid value for {}: {}",
+                                                                param.name, e
+                                                            ));
+                                                        }
+                                                    }
                                                 }
                                             }
                                         }
@@ -1451,6 +1708,21 @@ impl InstrumentManagerPanel {
             }
         }
 
+        // Then the panel shipped with the driver
+        if !device.ui_layout.is_empty() {
+            ui.heading(&device.name);
+            let panel = self.plugin_panels.entry(device_id.clone()).or_default();
+            let commands = ui
+                .push_id(("instr_mgr", &device_id), |ui| {
+                    panel.ui(ui, &device.ui_layout)
+                })
+                .inner;
+            for command in commands {
+                self.send_panel_command(client.as_deref_mut(), runtime, &device_id, command);
+            }
+            return;
+        }
+
         // Fallback: Determine which device-specific panel to use based on driver type and capabilities
         let driver_lower = device.driver_type.to_lowercase();
 
@@ -1951,6 +2223,49 @@ impl InstrumentManagerPanel {
         });
     }
 
+    /// Send a command from a driver-shipped panel
+    ///
+    /// Reads update the panel silently; writes and actions report like other
+    /// device commands.
+    fn send_panel_command(
+        &mut self,
+        client: Option<&mut DaqClient>,
+        runtime: &Runtime,
+        device_id: &str,
+        command: PanelCommand,
+    ) {
+        let (name, args) = command.to_device_command();
+        let PanelCommand::Get(source) = command else {
+            self.execute_device_command(client, runtime, device_id.to_string(), name, args);
+            return;
+        };
+        let Some(client) = client else {
+            return;
+        };
+
+        let mut client = client.clone();
+        let tx = self.action_tx.clone();
+        self.action_in_flight = self.action_in_flight.saturating_add(1);
+        let device_id = device_id.to_string();
+
+        runtime.spawn(async move {
+            let result = client
+                .execute_device_command(&device_id, &name, &args)
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|response| {
+                    serde_json::from_str(&response.results).map_err(|e| e.to_string())
+                });
+            let _ = tx
+                .send(ActionResult::PanelValue {
+                    device_id,
+                    name: source,
+                    result,
+                })
+                .await;
+        });
+    }
+
     /// Set exposure for a camera
     fn set_exposure(
         &mut self,
@@ -0,0 +1,247 @@
+//! Driver-shipped control panels
+//!
+//! Renders the `ui_layout` a driver sends with its `DeviceInfo` (groups,
+//! sliders, gauges, toggles, dropdowns and buttons bound to capability
+//! names), so a new instrument gets a usable panel without one being written
+//! here. Widgets become `ExecuteDeviceCommand` calls: `get`/`set` by
+//! capability name, and buttons by action name.
+
+use eframe::egui;
+use protocol::daq::PluginUiElement;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How often bound values are re-read while the panel is shown
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Command produced by a panel widget
+#[derive(Debug, Clone, PartialEq)]
+pub enum PanelCommand {
+    /// Read the capability named `source`
+    Get(String),
+    /// Write `value` to the capability named `target`
+    Set(String, Value),
+    /// Run the named action
+    Action(String),
+}
+
+impl PanelCommand {
+    /// `(command, args)` for `ExecuteDeviceCommand`
+    pub fn to_device_command(&self) -> (String, String) {
+        match self {
+            Self::Get(source) => ("get".to_string(), json!({ "source": source }).to_string()),
+            Self::Set(target, value) => (
+                "set".to_string(),
+                json!({ "target": target, "value": value }).to_string(),
+            ),
+            Self::Action(action) => (action.clone(), String::new()),
+        }
+    }
+}
+
+/// Values and edits of one device's panel
+#[derive(Default)]
+pub struct PluginPanelState {
+    /// Last value read per capability name
+    values: HashMap<String, Value>,
+    /// Slider positions being edited, applied on release
+    edits: HashMap<String, f64>,
+    last_poll: Option<Instant>,
+}
+
+impl PluginPanelState {
+    /// Record the result of a `get`
+    pub fn set_value(&mut self, name: String, value: Value) {
+        self.values.insert(name, value);
+    }
+
+    /// Render `elements`, returning the commands to send
+    pub fn ui(&mut self, ui: &mut egui::Ui, elements: &[PluginUiElement]) -> Vec<PanelCommand> {
+        let mut commands = Vec::new();
+        if self.last_poll.is_none_or(|t| t.elapsed() >= POLL_INTERVAL) {
+            self.last_poll = Some(Instant::now());
+            let mut names = Vec::new();
+            collect_bound_names(elements, &mut names);
+            commands.extend(names.into_iter().map(PanelCommand::Get));
+        }
+        for element in elements {
+            self.render_element(ui, element, &mut commands);
+        }
+        commands
+    }
+
+    fn render_element(
+        &mut self,
+        ui: &mut egui::Ui,
+        element: &PluginUiElement,
+        commands: &mut Vec<PanelCommand>,
+    ) {
+        let bound = element
+            .target
+            .as_deref()
+            .or(element.source.as_deref())
+            .unwrap_or_default();
+        let label = if element.label.is_empty() {
+            bound
+        } else {
+            element.label.as_str()
+        };
+        let unit = element.unit.as_deref().unwrap_or_default();
+        let current = self.values.get(bound);
+
+        match element.element_type.as_str() {
+            "group" => {
+                ui.group(|ui| {
+                    ui.strong(label);
+                    for child in &element.children {
+                        self.render_element(ui, child, commands);
+                    }
+                });
+            }
+            "slider" => {
+                let range = element.min.unwrap_or(0.0)..=element.max.unwrap_or(100.0);
+                let mut value = self
+                    .edits
+                    .get(bound)
+                    .copied()
+                    .or_else(|| current.and_then(Value::as_f64))
+                    .unwrap_or(*range.start());
+                ui.horizontal(|ui| {
+                    ui.label(label);
+                    let response =
+                        ui.add(egui::Slider::new(&mut value, range).suffix(format!(" {unit}")));
+                    // Applied once on release, not for every drag step
+                    if response.dragged() {
+                        self.edits.insert(bound.to_string(), value);
+                    } else if response.drag_stopped() || response.changed() {
+                        self.edits.remove(bound);
+                        self.values.insert(bound.to_string(), json!(value));
+                        commands.push(PanelCommand::Set(bound.to_string(), json!(value)));
+                    }
+                });
+            }
+            "readout" => {
+                ui.horizontal(|ui| {
+                    ui.label(label);
+                    ui.monospace(format!("{} {unit}", value_text(current)));
+                });
+            }
+            "gauge" => {
+                let (min, max) = (element.min.unwrap_or(0.0), element.max.unwrap_or(1.0));
+                let value = current.and_then(Value::as_f64);
+                let fraction = value.map_or(0.0, |v| ((v - min) / (max - min)).clamp(0.0, 1.0));
+                ui.horizontal(|ui| {
+                    ui.label(label);
+                    ui.add(
+                        egui::ProgressBar::new(fraction as f32)
+                            .text(format!("{} {unit}", value_text(current))),
+                    );
+                });
+            }
+            "toggle" => {
+                let mut on = current.and_then(Value::as_bool).unwrap_or(false);
+                if ui.checkbox(&mut on, label).changed() {
+                    self.values.insert(bound.to_string(), json!(on));
+                    commands.push(PanelCommand::Set(bound.to_string(), json!(on)));
+                }
+            }
+            "dropdown" => {
+                let selected = value_text(current);
+                ui.horizontal(|ui| {
+                    ui.label(label);
+                    egui::ComboBox::from_id_salt(("plugin_dropdown", bound))
+                        .selected_text(&selected)
+                        .show_ui(ui, |ui| {
+                            for option in &element.options {
+                                if ui.selectable_label(*option == selected, option).clicked() {
+                                    commands
+                                        .push(PanelCommand::Set(bound.to_string(), json!(option)));
+                                }
+                            }
+                        });
+                });
+            }
+            "button" => {
+                if let Some(action) = &element.action {
+                    if ui.button(label).clicked() {
+                        commands.push(PanelCommand::Action(action.clone()));
+                    }
+                }
+            }
+            other => {
+                ui.weak(format!("Unsupported panel element '{}'", other));
+            }
+        }
+    }
+}
+
+/// Capability names read by the panel (readouts, gauges and current
+/// values of sliders, toggles and dropdowns)
+fn collect_bound_names(elements: &[PluginUiElement], names: &mut Vec<String>) {
+    for element in elements {
+        if element.element_type == "group" {
+            collect_bound_names(&element.children, names);
+        } else if let Some(name) = element.source.as_ref().or(element.target.as_ref()) {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+    }
+}
+
+fn value_text(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Number(n)) => n
+            .as_f64()
+            .map_or_else(|| n.to_string(), |v| format!("{:.4}", v)),
+        Some(other) => other.to_string(),
+        None => "—".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(element_type: &str, target: Option<&str>, source: Option<&str>) -> PluginUiElement {
+        PluginUiElement {
+            element_type: element_type.to_string(),
+            target: target.map(str::to_string),
+            source: source.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_bound_names_are_collected_once_through_groups() {
+        let layout = vec![
+            element("readout", None, Some("power")),
+            PluginUiElement {
+                element_type: "group".to_string(),
+                children: vec![
+                    element("slider", Some("x"), None),
+                    element("gauge", None, Some("power")),
+                    element("button", None, None),
+                ],
+                ..Default::default()
+            },
+        ];
+        let mut names = Vec::new();
+        collect_bound_names(&layout, &mut names);
+        assert_eq!(names, vec!["power".to_string(), "x".to_string()]);
+    }
+
+    #[test]
+    fn test_commands_map_to_device_commands() {
+        let (command, args) = PanelCommand::Set("x".to_string(), json!(1.5)).to_device_command();
+        assert_eq!(command, "set");
+        let args: Value = serde_json::from_str(&args).unwrap();
+        assert_eq!(args, json!({ "target": "x", "value": 1.5 }));
+
+        let (command, args) = PanelCommand::Action("home_all".to_string()).to_device_command();
+        assert_eq!(command, "home_all");
+        assert!(args.is_empty());
+    }
+}
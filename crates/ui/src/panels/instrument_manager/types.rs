@@ -4,7 +4,7 @@
 //! and icon methods. It mirrors `common::capabilities::DeviceCategory` but adds
 //! GUI presentation logic that depends on `protocol::daq::DeviceInfo`.
 
-use protocol::daq::DeviceInfo;
+use protocol::daq::{DeviceInfo, ParameterDescriptor};
 
 /// Device category for grouping in the tree view.
 ///
@@ -88,12 +88,30 @@ pub struct ParameterInfo {
     #[allow(dead_code)]
     pub readable: bool,
     pub writable: bool,
+    /// Changes must be confirmed before the daemon applies them
+    pub dangerous: bool,
     pub min_value: Option<f64>,
     pub max_value: Option<f64>,
     pub enum_values: Vec<String>,
     pub current_value: Option<String>,
 }
 
+impl ParameterInfo {
+    /// Descriptor form, for parsing edits into typed values
+    pub fn descriptor(&self) -> ParameterDescriptor {
+        ParameterDescriptor {
+            name: self.name.clone(),
+            dtype: self.dtype.clone(),
+            units: self.units.clone(),
+            writable: self.writable,
+            min_value: self.min_value,
+            max_value: self.max_value,
+            enum_values: self.enum_values.clone(),
+            ..Default::default()
+        }
+    }
+}
+
 /// Request to pop out a device control panel into a dockable window
 #[derive(Debug, Clone)]
 pub struct PopOutRequest {
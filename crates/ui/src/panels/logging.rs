@@ -7,10 +7,11 @@
 //! - Log export to text file
 //! - Auto-scroll with pause capability
 //! - Text search filtering
+//! - Remote daemon logs with server-side level/target/device filters
 
 use std::collections::VecDeque;
 use std::sync::mpsc;
-use std::time::Instant;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::connection_state_ext::ConnectionStateExt;
 use eframe::egui;
@@ -18,6 +19,9 @@ use eframe::egui;
 /// Maximum number of log entries to keep in memory
 const MAX_LOG_ENTRIES: usize = 10_000;
 
+/// Daemon log records replayed when the stream (re)starts
+const DAEMON_LOG_HISTORY: u32 = 500;
+
 /// Case-insensitive ASCII substring search without allocation (bd-tjwm.4)
 ///
 /// Returns true if `haystack` contains `needle` (case-insensitive).
@@ -79,6 +83,30 @@ impl LogLevel {
         }
     }
 
+    /// Convert a daemon log level (`protocol::daq::LogLevel` as i32)
+    pub fn from_proto(level: i32) -> Self {
+        use protocol::daq::LogLevel as ProtoLevel;
+        match ProtoLevel::try_from(level).unwrap_or(ProtoLevel::Info) {
+            ProtoLevel::Error => Self::Error,
+            ProtoLevel::Warn => Self::Warn,
+            ProtoLevel::Info => Self::Info,
+            ProtoLevel::Debug => Self::Debug,
+            ProtoLevel::Trace => Self::Trace,
+        }
+    }
+
+    /// Convert to the daemon's log level
+    pub fn to_proto(self) -> protocol::daq::LogLevel {
+        use protocol::daq::LogLevel as ProtoLevel;
+        match self {
+            Self::Error => ProtoLevel::Error,
+            Self::Warn => ProtoLevel::Warn,
+            Self::Info => ProtoLevel::Info,
+            Self::Debug => ProtoLevel::Debug,
+            Self::Trace => ProtoLevel::Trace,
+        }
+    }
+
     /// Get color for the level
     pub fn color(&self) -> egui::Color32 {
         match self {
@@ -236,7 +264,8 @@ pub struct LogEntry {
     /// Entry ID for stable UI identification (for future row virtualization)
     #[allow(dead_code)]
     pub id: u64,
-    /// Timestamp relative to panel start
+    /// Wall-clock timestamp (seconds since the UNIX epoch), so GUI and
+    /// daemon entries line up
     pub timestamp_secs: f64,
     /// Severity level
     pub level: LogLevel,
@@ -246,6 +275,10 @@ pub struct LogEntry {
     pub source: String,
     /// Log message
     pub message: String,
+    /// Entry came from the daemon's log stream rather than this GUI
+    pub remote: bool,
+    /// Device the daemon tagged the record with (empty if none)
+    pub device_id: String,
 }
 
 impl LogEntry {
@@ -259,27 +292,53 @@ impl LogEntry {
             category,
             source: source.to_string(),
             message: message.to_string(),
+            remote: false,
+            device_id: String::new(),
         }
     }
 
-    /// Format timestamp as HH:MM:SS.mmm
+    /// Create an entry from a daemon log record
+    pub fn from_daemon(id: u64, record: &protocol::daq::LogRecord) -> Self {
+        let mut entry = Self::new(
+            id,
+            record.timestamp_ns as f64 / 1e9,
+            LogLevel::from_proto(record.level),
+            &record.target,
+            &record.message,
+        );
+        entry.remote = true;
+        entry.device_id.clone_from(&record.device_id);
+        entry
+    }
+
+    /// Format timestamp as local HH:MM:SS.mmm
     pub fn formatted_timestamp(&self) -> String {
-        let total_secs = self.timestamp_secs;
-        let hours = (total_secs / 3600.0) as u32;
-        let mins = ((total_secs % 3600.0) / 60.0) as u32;
-        let secs = (total_secs % 60.0) as u32;
-        let millis = ((total_secs % 1.0) * 1000.0) as u32;
-        format!("{:02}:{:02}:{:02}.{:03}", hours, mins, secs, millis)
+        let millis = (self.timestamp_secs * 1000.0) as i64;
+        chrono::DateTime::from_timestamp_millis(millis)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%H:%M:%S%.3f")
+                    .to_string()
+            })
+            .unwrap_or_default()
     }
 
     /// Format for export
     pub fn to_export_line(&self) -> String {
+        let origin = if self.remote { "daemon" } else { "gui" };
+        let device = if self.device_id.is_empty() {
+            String::new()
+        } else {
+            format!(" <{}>", self.device_id)
+        };
         format!(
-            "[{}] {} [{}] [{}] {}",
+            "[{}] {} [{}] [{}] [{}]{} {}",
             self.formatted_timestamp(),
             self.level.label(),
+            origin,
             self.category.label(),
             self.source,
+            device,
             self.message
         )
     }
@@ -402,14 +461,56 @@ impl ExperimentStatus {
     }
 }
 
+/// Server-side filters for the daemon log stream
+#[derive(Debug, Clone, PartialEq)]
+pub struct DaemonLogFilter {
+    /// Stream daemon logs into the panel
+    pub enabled: bool,
+    /// Minimum level sent by the daemon
+    pub min_level: LogLevel,
+    /// Comma-separated target prefixes (e.g. "hardware::drivers")
+    pub targets: String,
+    /// Comma-separated device IDs
+    pub device_ids: String,
+}
+
+impl Default for DaemonLogFilter {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_level: LogLevel::Info,
+            targets: String::new(),
+            device_ids: String::new(),
+        }
+    }
+}
+
+impl DaemonLogFilter {
+    /// Build the stream request, or `None` when daemon logs are disabled
+    pub fn to_request(&self) -> Option<protocol::daq::StreamLogsRequest> {
+        fn split(list: &str) -> Vec<String> {
+            list.split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect()
+        }
+
+        self.enabled.then(|| protocol::daq::StreamLogsRequest {
+            min_level: self.min_level.to_proto() as i32,
+            targets: split(&self.targets),
+            device_ids: split(&self.device_ids),
+            history: DAEMON_LOG_HISTORY,
+        })
+    }
+}
+
 /// Logging & Status Panel state
 pub struct LoggingPanel {
     /// Log entries (newest at end)
     entries: VecDeque<LogEntry>,
     /// Next entry ID
     next_id: u64,
-    /// Panel start time for timestamps
-    start_time: Instant,
 
     // Filter settings
     /// Minimum level to display
@@ -433,6 +534,16 @@ pub struct LoggingPanel {
     /// Show level column
     pub show_level: bool,
 
+    // Daemon log stream
+    /// Filters being edited
+    daemon_filter: DaemonLogFilter,
+    /// Filters the stream was last started with
+    applied_daemon_filter: DaemonLogFilter,
+    /// Set when applied filters changed and the stream must restart
+    daemon_filter_changed: bool,
+    /// Stream state shown next to the filters (None while streaming normally)
+    daemon_stream_status: Option<String>,
+
     // Export
     /// Export file path
     pub export_path: String,
@@ -462,7 +573,6 @@ impl Default for LoggingPanel {
         Self {
             entries: VecDeque::with_capacity(MAX_LOG_ENTRIES),
             next_id: 0,
-            start_time: Instant::now(),
             min_level: LogLevel::Debug, // Default to Debug to show streaming events
             selected_category: LogCategory::All,
             search_filter: String::new(),
@@ -472,6 +582,10 @@ impl Default for LoggingPanel {
             scroll_paused: false,
             show_source: true,
             show_level: true,
+            daemon_filter: DaemonLogFilter::default(),
+            applied_daemon_filter: DaemonLogFilter::default(),
+            daemon_filter_changed: false,
+            daemon_stream_status: None,
             export_path: String::from("logs/session.log"),
             export_status: None,
             export_in_progress: false,
@@ -495,10 +609,22 @@ impl LoggingPanel {
 
     /// Add a log entry
     pub fn log(&mut self, level: LogLevel, source: &str, message: &str) {
-        let timestamp = self.start_time.elapsed().as_secs_f64();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or(0.0);
         let entry = LogEntry::new(self.next_id, timestamp, level, source, message);
-        self.next_id += 1;
+        self.push_entry(entry);
+    }
+
+    /// Add a record received from the daemon's log stream
+    pub fn log_daemon_record(&mut self, record: &protocol::daq::LogRecord) {
+        let entry = LogEntry::from_daemon(self.next_id, record);
+        self.push_entry(entry);
+    }
 
+    fn push_entry(&mut self, entry: LogEntry) {
+        self.next_id += 1;
         self.entries.push_back(entry);
 
         // Trim if over capacity
@@ -536,6 +662,28 @@ impl LoggingPanel {
         self.entries.clear();
     }
 
+    /// Request for the daemon log stream, or `None` when disabled
+    pub fn daemon_log_request(&self) -> Option<protocol::daq::StreamLogsRequest> {
+        self.applied_daemon_filter.to_request()
+    }
+
+    /// Whether daemon log filters were applied since the last call
+    ///
+    /// Entries received under the old filters are dropped; the restarted
+    /// stream replays matching history.
+    pub fn take_daemon_filter_change(&mut self) -> bool {
+        if !std::mem::take(&mut self.daemon_filter_changed) {
+            return false;
+        }
+        self.entries.retain(|e| !e.remote);
+        true
+    }
+
+    /// Show the daemon log stream state (None when streaming normally)
+    pub fn set_daemon_stream_status(&mut self, status: Option<String>) {
+        self.daemon_stream_status = status;
+    }
+
     /// Get number of entries (for external queries)
     #[allow(dead_code)]
     pub fn entry_count(&self) -> usize {
@@ -572,7 +720,8 @@ impl LoggingPanel {
                 // Search filter (allocation-free case-insensitive)
                 if !search.is_empty() {
                     let matches = contains_ignore_ascii_case(&e.message, search)
-                        || contains_ignore_ascii_case(&e.source, search);
+                        || contains_ignore_ascii_case(&e.source, search)
+                        || contains_ignore_ascii_case(&e.device_id, search);
                     if !matches {
                         return false;
                     }
@@ -886,6 +1035,57 @@ impl LoggingPanel {
         });
     }
 
+    /// Render the daemon log stream filters (applied server-side)
+    fn show_daemon_controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.daemon_filter.enabled, "Daemon logs")
+                .on_hover_text("Stream logs from the daemon and its drivers");
+
+            ui.add_enabled_ui(self.daemon_filter.enabled, |ui| {
+                ui.label("Min:");
+                egui::ComboBox::from_id_salt("daemon_min_level")
+                    .selected_text(self.daemon_filter.min_level.label())
+                    .show_ui(ui, |ui| {
+                        for level in LogLevel::all() {
+                            ui.selectable_value(
+                                &mut self.daemon_filter.min_level,
+                                *level,
+                                level.label(),
+                            );
+                        }
+                    });
+
+                ui.label("Targets:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.daemon_filter.targets)
+                        .desired_width(150.0)
+                        .hint_text("hardware::drivers, ..."),
+                );
+
+                ui.label("Devices:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.daemon_filter.device_ids)
+                        .desired_width(120.0)
+                        .hint_text("all"),
+                );
+            });
+
+            let dirty = self.daemon_filter != self.applied_daemon_filter;
+            if ui.add_enabled(dirty, egui::Button::new("Apply")).clicked() {
+                self.applied_daemon_filter = self.daemon_filter.clone();
+                self.daemon_filter_changed = true;
+            }
+
+            if let Some(status) = &self.daemon_stream_status {
+                ui.label(
+                    egui::RichText::new(status)
+                        .small()
+                        .color(egui::Color32::from_gray(160)),
+                );
+            }
+        });
+    }
+
     /// Render the log entries table
     fn show_log_table(&mut self, ui: &mut egui::Ui) {
         let filtered = self.filtered_entries();
@@ -905,6 +1105,16 @@ impl LoggingPanel {
                                 // Timestamp
                                 ui.monospace(entry.formatted_timestamp());
 
+                                // Origin badge for daemon entries
+                                if entry.remote {
+                                    ui.label(
+                                        egui::RichText::new("daemon")
+                                            .small()
+                                            .color(egui::Color32::from_rgb(150, 170, 255)),
+                                    )
+                                    .on_hover_text("Received from the daemon log stream");
+                                }
+
                                 // Level (colored)
                                 if self.show_level {
                                     ui.colored_label(entry.level.color(), entry.level.label());
@@ -930,6 +1140,14 @@ impl LoggingPanel {
                                     );
                                 }
 
+                                // Device tag
+                                if !entry.device_id.is_empty() {
+                                    ui.label(
+                                        egui::RichText::new(format!("<{}>", entry.device_id))
+                                            .color(LogCategory::Devices.color()),
+                                    );
+                                }
+
                                 // Message
                                 ui.label(&entry.message);
                             });
@@ -1021,6 +1239,7 @@ impl LoggingPanel {
 
         // Filter controls
         self.show_filter_controls(ui);
+        self.show_daemon_controls(ui);
         ui.add_space(4.0);
         ui.separator();
 
@@ -2,6 +2,7 @@
 
 mod code_preview;
 pub mod comedi;
+mod console;
 mod devices;
 mod document_viewer;
 mod experiment_designer;
@@ -12,6 +13,7 @@ mod live_visualization;
 mod logging;
 mod modules;
 mod multi_detector_grid;
+mod offline;
 mod plan_runner;
 mod run_comparison;
 mod run_history;
@@ -21,6 +23,7 @@ mod script_editor;
 mod scripts;
 mod signal_plotter;
 mod signal_plotter_stream;
+mod state_machines;
 mod storage;
 
 // Comedi panels for NI DAQ control
@@ -29,6 +32,7 @@ pub use comedi::{
     AnalogInputPanel, AnalogOutputPanel, ComediPanel, CounterDisplayPanel, CounterPanel,
     DigitalIOPanel, DioMonitorPanel, OscilloscopePanel, VoltmeterPanel,
 };
+pub use console::InstrumentConsolePanel;
 pub use devices::DevicesPanel;
 pub use document_viewer::DocumentViewerPanel;
 pub use experiment_designer::ExperimentDesignerPanel;
@@ -42,6 +46,7 @@ pub use live_visualization::{
 pub use logging::{ConnectionDiagnostics, ConnectionStatus, LogLevel, LoggingPanel};
 pub use modules::ModulesPanel;
 pub use multi_detector_grid::{DetectorPanel, DetectorType, MultiDetectorGrid};
+pub use offline::OfflineInstrumentsView;
 pub use plan_runner::PlanRunnerPanel;
 pub use run_comparison::RunComparisonPanel;
 pub use run_history::RunHistoryPanel;
@@ -50,4 +55,5 @@ pub use scans::ScansPanel;
 pub use script_editor::ScriptEditorPanel;
 pub use scripts::ScriptsPanel;
 pub use signal_plotter::SignalPlotterPanel;
+pub use state_machines::StateMachinesPanel;
 pub use storage::StoragePanel;
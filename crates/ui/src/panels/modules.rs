@@ -4,15 +4,30 @@ use eframe::egui;
 use tokio::runtime::Runtime;
 use tokio::sync::mpsc;
 
-use crate::widgets::{offline_notice, OfflineContext};
+use crate::widgets::{offline_notice, OfflineContext, SchemaForm};
 use client::DaqClient;
 
 /// Pending action for modules panel
 enum PendingAction {
     Refresh,
-    CreateModule { type_id: String, name: String },
-    StartModule { module_id: String },
-    StopModule { module_id: String },
+    CreateModule {
+        type_id: String,
+        name: String,
+    },
+    StartModule {
+        module_id: String,
+    },
+    StopModule {
+        module_id: String,
+    },
+    LoadConfig {
+        module_id: String,
+        type_id: String,
+    },
+    ConfigureModule {
+        module_id: String,
+        parameters: std::collections::HashMap<String, String>,
+    },
 }
 
 enum ModuleActionResult {
@@ -37,6 +52,20 @@ enum ModuleActionResult {
         module_id: String,
         result: Result<(), String>,
     },
+    ConfigLoaded {
+        module_id: String,
+        result: Result<Box<(protocol::daq::ModuleTypeInfo, protocol::daq::ModuleConfig)>, String>,
+    },
+    Configured {
+        module_id: String,
+        result: Result<Vec<String>, String>,
+    },
+}
+
+/// Configuration form for the selected module
+struct ModuleConfigForm {
+    module_id: String,
+    form: SchemaForm,
 }
 
 /// Modules panel state
@@ -51,6 +80,8 @@ pub struct ModulesPanel {
     new_module_name: String,
     /// Selected module instance
     selected_module: Option<String>,
+    /// Configuration form for the selected module (once loaded)
+    config_form: Option<ModuleConfigForm>,
     /// Last refresh timestamp
     last_refresh: Option<std::time::Instant>,
     /// Error message
@@ -114,6 +145,34 @@ impl ModulesPanel {
                             }
                             Err(e) => self.error = Some(e),
                         },
+                        ModuleActionResult::ConfigLoaded { module_id, result } => {
+                            // Ignore stale loads for a previously selected module
+                            if self.selected_module.as_ref() == Some(&module_id) {
+                                match result {
+                                    Ok(loaded) => {
+                                        let (info, config) = *loaded;
+                                        self.config_form =
+                                            Some(self.build_config_form(module_id, &info, &config));
+                                    }
+                                    Err(e) => self.error = Some(e),
+                                }
+                            }
+                        }
+                        ModuleActionResult::Configured { module_id, result } => match result {
+                            Ok(warnings) if warnings.is_empty() => {
+                                self.status = Some(format!("Configured module: {}", module_id));
+                                self.error = None;
+                            }
+                            Ok(warnings) => {
+                                self.status = Some(format!(
+                                    "Configured module: {} ({})",
+                                    module_id,
+                                    warnings.join("; ")
+                                ));
+                                self.error = None;
+                            }
+                            Err(e) => self.error = Some(e),
+                        },
                     }
                     updated = true;
                 }
@@ -127,6 +186,29 @@ impl ModulesPanel {
         }
     }
 
+    /// Build the configuration form from the type's schema, falling back to
+    /// its flat parameter list when no schema is published.
+    fn build_config_form(
+        &mut self,
+        module_id: String,
+        info: &protocol::daq::ModuleTypeInfo,
+        config: &protocol::daq::ModuleConfig,
+    ) -> ModuleConfigForm {
+        let mut form = if info.config_schema.is_empty() {
+            SchemaForm::from_parameters(&info.parameters)
+        } else {
+            match SchemaForm::from_json(&info.config_schema) {
+                Ok(form) => form,
+                Err(e) => {
+                    self.error = Some(format!("Invalid config schema for {}: {}", info.type_id, e));
+                    SchemaForm::from_parameters(&info.parameters)
+                }
+            }
+        };
+        form.load_config(&config.parameters);
+        ModuleConfigForm { module_id, form }
+    }
+
     /// Render the modules panel
     pub fn ui(&mut self, ui: &mut egui::Ui, client: Option<&mut DaqClient>, runtime: &Runtime) {
         self.poll_async_results(ui.ctx());
@@ -237,6 +319,8 @@ impl ModulesPanel {
             }
         });
 
+        self.render_config_form(ui);
+
         // Execute pending action
         if let Some(action) = self.pending_action.take() {
             self.execute_action(action, client, runtime);
@@ -274,8 +358,14 @@ impl ModulesPanel {
                 if ui
                     .selectable_label(selected, &module.instance_name)
                     .clicked()
+                    && !selected
                 {
                     self.selected_module = Some(module.module_id.clone());
+                    self.config_form = None;
+                    self.pending_action = Some(PendingAction::LoadConfig {
+                        module_id: module.module_id.clone(),
+                        type_id: module.type_id.clone(),
+                    });
                 }
                 ui.label(format!("({})", module.type_id));
             });
@@ -329,6 +419,52 @@ impl ModulesPanel {
         });
     }
 
+    /// Render the configuration form of the selected module
+    fn render_config_form(&mut self, ui: &mut egui::Ui) {
+        let Some(config) = &mut self.config_form else {
+            if self.selected_module.is_some() && self.action_in_flight > 0 {
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Loading configuration...");
+                });
+            }
+            return;
+        };
+
+        ui.add_space(8.0);
+        ui.group(|ui| {
+            ui.heading("Configuration");
+            ui.label(egui::RichText::new(&config.module_id).small().weak());
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .id_salt("module_config")
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    config.form.show(ui, &config.module_id);
+                });
+
+            let errors = config.form.errors();
+            for error in &errors {
+                ui.colored_label(egui::Color32::YELLOW, format!("⚠ {}", error));
+            }
+
+            if ui
+                .add_enabled(
+                    errors.is_empty(),
+                    egui::Button::new("✔ Apply configuration"),
+                )
+                .clicked()
+            {
+                self.pending_action = Some(PendingAction::ConfigureModule {
+                    module_id: config.module_id.clone(),
+                    parameters: config.form.to_config(),
+                });
+            }
+        });
+    }
+
     /// Execute a pending action
     fn execute_action(
         &mut self,
@@ -347,6 +483,15 @@ impl ModulesPanel {
             PendingAction::StopModule { module_id } => {
                 self.stop_module(client, runtime, &module_id);
             }
+            PendingAction::LoadConfig { module_id, type_id } => {
+                self.load_config(client, runtime, module_id, type_id);
+            }
+            PendingAction::ConfigureModule {
+                module_id,
+                parameters,
+            } => {
+                self.configure_module(client, runtime, module_id, parameters);
+            }
         }
     }
 
@@ -493,6 +638,70 @@ impl ModulesPanel {
             let _ = tx.send(action).await;
         });
     }
+
+    /// Fetch type info and current config for the selected module
+    fn load_config(
+        &mut self,
+        client: Option<&mut DaqClient>,
+        runtime: &Runtime,
+        module_id: String,
+        type_id: String,
+    ) {
+        let Some(client) = client else {
+            self.error = Some("Not connected to daemon".to_string());
+            return;
+        };
+
+        let mut client = client.clone();
+        let tx = self.action_tx.clone();
+        self.action_in_flight = self.action_in_flight.saturating_add(1);
+
+        runtime.spawn(async move {
+            let result = async {
+                let info = client.get_module_type_info(&type_id).await?;
+                let config = client.get_module_config(&module_id).await?;
+                Ok::<_, anyhow::Error>(Box::new((info, config)))
+            }
+            .await
+            .map_err(|e| e.to_string());
+
+            let _ = tx
+                .send(ModuleActionResult::ConfigLoaded { module_id, result })
+                .await;
+        });
+    }
+
+    /// Apply a configuration to a module
+    fn configure_module(
+        &mut self,
+        client: Option<&mut DaqClient>,
+        runtime: &Runtime,
+        module_id: String,
+        parameters: std::collections::HashMap<String, String>,
+    ) {
+        self.error = None;
+        self.status = None;
+
+        let Some(client) = client else {
+            self.error = Some("Not connected to daemon".to_string());
+            return;
+        };
+
+        let mut client = client.clone();
+        let tx = self.action_tx.clone();
+        self.action_in_flight = self.action_in_flight.saturating_add(1);
+
+        runtime.spawn(async move {
+            let result = match client.configure_module(&module_id, parameters).await {
+                Ok(response) if response.success => Ok(response.warnings),
+                Ok(response) => Err(response.error_message),
+                Err(e) => Err(e.to_string()),
+            };
+            let _ = tx
+                .send(ModuleActionResult::Configured { module_id, result })
+                .await;
+        });
+    }
 }
 
 impl Default for ModulesPanel {
@@ -504,6 +713,7 @@ impl Default for ModulesPanel {
             selected_type: None,
             new_module_name: String::new(),
             selected_module: None,
+            config_form: None,
             last_refresh: None,
             error: None,
             status: None,
@@ -0,0 +1,210 @@
+//! Read-only Instruments view built from the offline cache.
+//!
+//! Shown in place of the Instruments panel while the daemon is unreachable.
+//! Devices, parameters and readings are the last values seen; edits are not
+//! applied but queued in the [`OfflineCache`] for the next connection.
+
+use std::collections::HashMap;
+
+use eframe::egui;
+
+use crate::offline_cache::{format_age, now_ms, OfflineCache, QueuedActionKind};
+use crate::widgets::{parse_typed_value, typed_value_text};
+
+/// Offline Instruments view state
+#[derive(Default)]
+pub struct OfflineInstrumentsView {
+    selected_device: Option<String>,
+    /// Pending edits keyed by parameter name (for the selected device)
+    edit_values: HashMap<String, String>,
+    /// Move target input
+    move_target: String,
+    error: Option<String>,
+}
+
+impl OfflineInstrumentsView {
+    /// Render the cached state; queued edits go into `cache`
+    pub fn ui(&mut self, ui: &mut egui::Ui, cache: &mut OfflineCache) {
+        ui.heading("Instruments");
+
+        ui.horizontal(|ui| {
+            ui.colored_label(egui::Color32::YELLOW, "⚠");
+            ui.label(format!(
+                "Offline - last-known state from {}, {} old. Changes are queued and applied when the daemon reconnects.",
+                cache.daemon,
+                format_age(cache.age())
+            ));
+        });
+        if let Some(err) = &self.error {
+            ui.colored_label(egui::Color32::RED, err);
+        }
+        ui.separator();
+
+        ui.columns(2, |columns| {
+            self.render_device_list(&mut columns[0], cache);
+            self.render_device(&mut columns[1], cache);
+        });
+
+        ui.separator();
+        self.render_queue(ui, cache);
+    }
+
+    fn render_device_list(&mut self, ui: &mut egui::Ui, cache: &OfflineCache) {
+        ui.label(egui::RichText::new("Devices").strong());
+        egui::ScrollArea::vertical()
+            .id_salt("offline_devices")
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                for device in &cache.devices {
+                    // Last position, or last reading for detectors
+                    let label = match cache
+                        .states
+                        .get(&device.id)
+                        .and_then(|state| state.position.or(state.reading))
+                    {
+                        Some(value) => format!("{} [{:.2}]", device.name, value),
+                        None => device.name.clone(),
+                    };
+                    let selected = self.selected_device.as_deref() == Some(device.id.as_str());
+                    let response = ui.selectable_label(selected, label).on_hover_text(format!(
+                        "ID: {}\nDriver: {}",
+                        device.id, device.driver_type
+                    ));
+                    if response.clicked() && !selected {
+                        self.selected_device = Some(device.id.clone());
+                        self.edit_values.clear();
+                        self.move_target.clear();
+                        self.error = None;
+                    }
+                }
+            });
+    }
+
+    fn render_device(&mut self, ui: &mut egui::Ui, cache: &mut OfflineCache) {
+        let Some(device) = self
+            .selected_device
+            .as_ref()
+            .and_then(|id| cache.devices.iter().find(|d| &d.id == id))
+            .cloned()
+        else {
+            ui.label("Select a device to see its last-known state.");
+            return;
+        };
+
+        ui.label(egui::RichText::new(&device.name).strong());
+        if let Some(state) = cache.states.get(&device.id) {
+            let age = format_age(std::time::Duration::from_millis(
+                now_ms().saturating_sub(state.updated_ms),
+            ));
+            if let Some(pos) = state.position {
+                ui.label(format!("Position: {:.3} ({} ago)", pos, age));
+            }
+            if let Some(reading) = state.reading {
+                ui.label(format!("Reading: {:.3} ({} ago)", reading, age));
+            }
+        }
+
+        if device.is_movable {
+            ui.horizontal(|ui| {
+                ui.label("Move:");
+                ui.add(egui::TextEdit::singleline(&mut self.move_target).desired_width(80.0));
+                let absolute = ui.button("Queue move to").clicked();
+                let relative = ui.button("Queue move by").clicked();
+                if absolute || relative {
+                    match self.move_target.trim().parse::<f64>() {
+                        Ok(value) => {
+                            let action = if absolute {
+                                QueuedActionKind::MoveAbsolute { position: value }
+                            } else {
+                                QueuedActionKind::MoveRelative { distance: value }
+                            };
+                            cache.enqueue(&device.id, action);
+                            self.move_target.clear();
+                            self.error = None;
+                        }
+                        Err(_) => {
+                            self.error = Some(format!("Invalid position: {}", self.move_target));
+                        }
+                    }
+                }
+            });
+        }
+
+        let Some(parameters) = cache.parameters.get(&device.id).cloned() else {
+            ui.label("No parameters cached for this device.");
+            return;
+        };
+
+        egui::Grid::new("offline_params_grid")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Parameter");
+                ui.strong("Last value");
+                ui.strong("Units");
+                ui.strong("Queue change");
+                ui.end_row();
+
+                for param in &parameters {
+                    if param.dangerous {
+                        ui.label(format!("⚠ {}", param.name))
+                            .on_hover_text("Dangerous setting: must be confirmed after reconnect");
+                    } else {
+                        ui.label(&param.name);
+                    }
+                    ui.label(param.value.as_deref().unwrap_or("-"));
+                    ui.label(&param.units);
+                    if param.writable {
+                        ui.horizontal(|ui| {
+                            let edit = self.edit_values.entry(param.name.clone()).or_default();
+                            ui.add(egui::TextEdit::singleline(edit).desired_width(80.0));
+                            if ui.button("Queue").clicked() {
+                                match parse_typed_value(&param.descriptor(), edit) {
+                                    Ok(value) => {
+                                        cache.enqueue(
+                                            &device.id,
+                                            QueuedActionKind::SetParameter {
+                                                name: param.name.clone(),
+                                                value: typed_value_text(&value),
+                                            },
+                                        );
+                                        edit.clear();
+                                        self.error = None;
+                                    }
+                                    Err(e) => {
+                                        self.error = Some(format!("{}: {}", param.name, e));
+                                    }
+                                }
+                            }
+                        });
+                    } else {
+                        ui.label("");
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn render_queue(&mut self, ui: &mut egui::Ui, cache: &mut OfflineCache) {
+        ui.horizontal(|ui| {
+            ui.label(
+                egui::RichText::new(format!("Queued actions ({})", cache.queue.len())).strong(),
+            );
+            if !cache.queue.is_empty() && ui.button("Clear").clicked() {
+                cache.queue.clear();
+            }
+        });
+        let mut remove = None;
+        for (index, action) in cache.queue.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                    remove = Some(index);
+                }
+                ui.label(format!("{}. {}", index + 1, action));
+            });
+        }
+        if let Some(index) = remove {
+            cache.queue.remove(index);
+        }
+    }
+}
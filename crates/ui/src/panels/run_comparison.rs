@@ -1,18 +1,38 @@
-//! Run comparison panel - overlay plots from multiple runs for visual analysis.
+//! Run comparison panel - overlay plots from multiple runs for visual analysis,
+//! and diff a "good" run against a "bad" one (configuration, device parameters,
+//! plan parameters and channel statistics).
+//!
+//! Markers users dropped on live plots are re-drawn on a per-run timeline in
+//! the diff view.
 
 use eframe::egui;
-use egui_plot::{Corner, Legend, Line, Plot, PlotPoints};
-use std::collections::{HashMap, HashSet};
+use egui_plot::{Corner, Legend, Line, LineStyle, Plot, PlotPoints, VLine};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use tokio::runtime::Runtime;
 use tokio::sync::mpsc;
 
 use crate::widgets::{offline_notice, OfflineContext};
 use client::DaqClient;
 
+/// Mean shift (in pooled standard deviations) highlighted as significant
+const SIGNIFICANT_SHIFT_SIGMA: f64 = 3.0;
+
+/// Which comparison is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum ComparisonView {
+    /// Overlaid plots of stored acquisitions
+    #[default]
+    Overlay,
+    /// Side-by-side diff of two runs
+    Diff,
+}
+
 /// Pending action for run comparison panel
 enum PendingAction {
     Refresh,
     LoadRunData { file_path: String, run_id: String },
+    RefreshRunHistory,
+    CompareRuns { baseline: String, candidate: String },
 }
 
 /// Result from an async action
@@ -22,6 +42,8 @@ enum ActionResult {
         run_id: String,
         result: Result<RunData, String>,
     },
+    RunHistory(Result<Vec<protocol::daq::RunSummary>, String>),
+    Comparison(Result<protocol::daq::RunComparison, String>),
 }
 
 /// Loaded run data for comparison
@@ -61,6 +83,16 @@ pub struct RunComparisonPanel {
     action_rx: mpsc::Receiver<ActionResult>,
     /// Number of in-flight async actions
     action_in_flight: usize,
+    /// Overlay or diff view
+    view: ComparisonView,
+    /// Completed runs known to the RunEngine (newest first)
+    run_history: Vec<protocol::daq::RunSummary>,
+    /// Run UID of the "good" run
+    baseline_uid: Option<String>,
+    /// Run UID of the run being investigated
+    candidate_uid: Option<String>,
+    /// Latest diff result
+    comparison: Option<protocol::daq::RunComparison>,
 }
 
 impl Default for RunComparisonPanel {
@@ -78,6 +110,11 @@ impl Default for RunComparisonPanel {
             action_tx,
             action_rx,
             action_in_flight: 0,
+            view: ComparisonView::default(),
+            run_history: Vec::new(),
+            baseline_uid: None,
+            candidate_uid: None,
+            comparison: None,
         }
     }
 }
@@ -107,6 +144,20 @@ impl RunComparisonPanel {
                             }
                             Err(e) => self.error = Some(e),
                         },
+                        ActionResult::RunHistory(result) => match result {
+                            Ok(runs) => {
+                                self.run_history = runs;
+                                self.error = None;
+                            }
+                            Err(e) => self.error = Some(e),
+                        },
+                        ActionResult::Comparison(result) => match result {
+                            Ok(comparison) => {
+                                self.comparison = Some(comparison);
+                                self.error = None;
+                            }
+                            Err(e) => self.error = Some(e),
+                        },
                     }
                     updated = true;
                 }
@@ -137,6 +188,49 @@ impl RunComparisonPanel {
         });
     }
 
+    /// Refresh the RunEngine's run history
+    fn refresh_run_history(&mut self, client: Option<&mut DaqClient>, runtime: &Runtime) {
+        let Some(client) = client else {
+            self.error = Some("Not connected".to_string());
+            return;
+        };
+
+        let mut client = client.clone();
+        let tx = self.action_tx.clone();
+        self.action_in_flight += 1;
+
+        runtime.spawn(async move {
+            let result = client.list_runs(0).await.map_err(|e| e.to_string());
+            let _ = tx.send(ActionResult::RunHistory(result)).await;
+        });
+    }
+
+    /// Request a diff of two runs
+    fn compare_runs(
+        &mut self,
+        baseline: String,
+        candidate: String,
+        client: Option<&mut DaqClient>,
+        runtime: &Runtime,
+    ) {
+        let Some(client) = client else {
+            self.error = Some("Not connected".to_string());
+            return;
+        };
+
+        let mut client = client.clone();
+        let tx = self.action_tx.clone();
+        self.action_in_flight += 1;
+
+        runtime.spawn(async move {
+            let result = client
+                .compare_runs(&baseline, &candidate)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(ActionResult::Comparison(result)).await;
+        });
+    }
+
     /// Load run data from HDF5 file
     fn load_run_data(&mut self, file_path: String, run_id: String, runtime: &Runtime) {
         let tx = self.action_tx.clone();
@@ -167,6 +261,11 @@ impl RunComparisonPanel {
             PendingAction::LoadRunData { file_path, run_id } => {
                 self.load_run_data(file_path, run_id, runtime);
             }
+            PendingAction::RefreshRunHistory => self.refresh_run_history(client, runtime),
+            PendingAction::CompareRuns {
+                baseline,
+                candidate,
+            } => self.compare_runs(baseline, candidate, client, runtime),
         }
     }
 
@@ -243,8 +342,21 @@ impl RunComparisonPanel {
         }
 
         ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.view, ComparisonView::Overlay, "Overlay");
+            if ui
+                .selectable_value(&mut self.view, ComparisonView::Diff, "Diff")
+                .clicked()
+                && self.run_history.is_empty()
+            {
+                self.pending_action = Some(PendingAction::RefreshRunHistory);
+            }
+            ui.separator();
+
             if ui.button("🔄 Refresh Runs").clicked() {
-                self.pending_action = Some(PendingAction::Refresh);
+                self.pending_action = Some(match self.view {
+                    ComparisonView::Overlay => PendingAction::Refresh,
+                    ComparisonView::Diff => PendingAction::RefreshRunHistory,
+                });
             }
 
             if let Some(last) = self.last_refresh {
@@ -260,6 +372,24 @@ impl RunComparisonPanel {
 
         ui.separator();
 
+        match self.view {
+            ComparisonView::Overlay => self.render_overlay_view(ui),
+            ComparisonView::Diff => self.render_diff_view(ui),
+        }
+
+        // Auto-refresh on first render
+        if self.last_refresh.is_none() {
+            self.pending_action = Some(PendingAction::Refresh);
+        }
+
+        // Execute pending action
+        if let Some(action) = self.pending_action.take() {
+            self.execute_action(action, client, runtime);
+        }
+    }
+
+    /// Render acquisition selection and the overlay plot
+    fn render_overlay_view(&mut self, ui: &mut egui::Ui) {
         // Split into two columns: run selection (left) and plot (right)
         ui.columns(2, |cols| {
             // Left column: run selection
@@ -323,79 +453,387 @@ impl RunComparisonPanel {
                 self.render_comparison_plot(&mut cols[1]);
             }
         });
+    }
 
-        // Auto-refresh on first render
-        if self.last_refresh.is_none() {
-            self.pending_action = Some(PendingAction::Refresh);
+    /// Render run selection and the diff of the selected runs
+    fn render_diff_view(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            run_selector(
+                ui,
+                "Good run:",
+                "baseline_run",
+                &self.run_history,
+                &mut self.baseline_uid,
+            );
+            run_selector(
+                ui,
+                "Bad run:",
+                "candidate_run",
+                &self.run_history,
+                &mut self.candidate_uid,
+            );
+
+            let selected = self.baseline_uid.clone().zip(self.candidate_uid.clone());
+            if ui
+                .add_enabled(selected.is_some(), egui::Button::new("Compare"))
+                .clicked()
+            {
+                if let Some((baseline, candidate)) = selected {
+                    self.pending_action = Some(PendingAction::CompareRuns {
+                        baseline,
+                        candidate,
+                    });
+                }
+            }
+        });
+
+        if self.run_history.is_empty() {
+            ui.label("No completed runs yet");
+            return;
         }
 
-        // Execute pending action
-        if let Some(action) = self.pending_action.take() {
-            self.execute_action(action, client, runtime);
+        ui.separator();
+
+        let Some(comparison) = &self.comparison else {
+            ui.label("Select two runs to compare");
+            return;
+        };
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            let sections = [
+                ("Run", "diff_run_info", &comparison.run_info),
+                ("Plan parameters", "diff_plan_args", &comparison.plan_args),
+                (
+                    "Device parameters",
+                    "diff_device_params",
+                    &comparison.device_parameters,
+                ),
+                (
+                    "Configuration",
+                    "diff_configuration",
+                    &comparison.configuration,
+                ),
+                ("Metadata", "diff_metadata", &comparison.metadata),
+            ];
+            for (title, id, diffs) in sections {
+                egui::CollapsingHeader::new(format!("{} ({} changed)", title, diffs.len()))
+                    .id_salt(id)
+                    .default_open(!diffs.is_empty())
+                    .show(ui, |ui| render_field_diffs(ui, id, diffs));
+            }
+
+            let (baseline_drops, candidate_drops) = (
+                comparison.baseline.as_ref().map_or(&[][..], |r| &r.dropped),
+                comparison
+                    .candidate
+                    .as_ref()
+                    .map_or(&[][..], |r| &r.dropped),
+            );
+            egui::CollapsingHeader::new("Data integrity")
+                .id_salt("diff_integrity")
+                .default_open(!baseline_drops.is_empty() || !candidate_drops.is_empty())
+                .show(ui, |ui| {
+                    render_drop_counts(ui, baseline_drops, candidate_drops)
+                });
+
+            let marker_count = [&comparison.baseline, &comparison.candidate]
+                .iter()
+                .filter_map(|run| run.as_ref())
+                .map(|run| run.markers.len())
+                .sum::<usize>();
+            egui::CollapsingHeader::new(format!("Markers ({})", marker_count))
+                .id_salt("diff_markers")
+                .default_open(marker_count > 0)
+                .show(ui, |ui| {
+                    render_markers(
+                        ui,
+                        comparison.baseline.as_ref(),
+                        comparison.candidate.as_ref(),
+                    )
+                });
+
+            egui::CollapsingHeader::new(format!("Channels ({})", comparison.channels.len()))
+                .id_salt("diff_channels")
+                .default_open(true)
+                .show(ui, |ui| {
+                    render_channel_comparisons(ui, &comparison.channels)
+                });
+        });
+    }
+}
+
+/// Combo box selecting one run from the history
+fn run_selector(
+    ui: &mut egui::Ui,
+    label: &str,
+    id: &str,
+    runs: &[protocol::daq::RunSummary],
+    selected: &mut Option<String>,
+) {
+    ui.label(label);
+    let text = selected
+        .as_deref()
+        .and_then(|uid| runs.iter().find(|r| r.run_uid == uid))
+        .map_or_else(|| "Select run".to_string(), run_label);
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(text)
+        .width(260.0)
+        .show_ui(ui, |ui| {
+            for run in runs {
+                ui.selectable_value(selected, Some(run.run_uid.clone()), run_label(run));
+            }
+        });
+}
+
+/// Short label for a run: plan name, UID prefix, exit status and lost data
+fn run_label(run: &protocol::daq::RunSummary) -> String {
+    let mut label = format!(
+        "{} ({}) {}",
+        run.plan_name,
+        &run.run_uid[..8.min(run.run_uid.len())],
+        run.exit_status
+    );
+    let dropped: u64 = run.dropped.iter().map(|d| d.count).sum();
+    if dropped > 0 {
+        label.push_str(&format!(", {} dropped", dropped));
+    }
+    label
+}
+
+/// Dropped frames and samples of both runs, by source and stage
+fn render_drop_counts(
+    ui: &mut egui::Ui,
+    baseline: &[protocol::daq::DropCount],
+    candidate: &[protocol::daq::DropCount],
+) {
+    let mut rows: BTreeMap<(&str, &str), (u64, u64)> = BTreeMap::new();
+    for drop in baseline {
+        rows.entry((&drop.source, &drop.stage)).or_default().0 += drop.count;
+    }
+    for drop in candidate {
+        rows.entry((&drop.source, &drop.stage)).or_default().1 += drop.count;
+    }
+    if rows.is_empty() {
+        ui.label("No data dropped in either run");
+        return;
+    }
+    egui::Grid::new("diff_integrity")
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Source");
+            ui.strong("Stage");
+            ui.strong("Good run");
+            ui.strong("Bad run");
+            ui.end_row();
+            for ((source, stage), (good, bad)) in rows {
+                ui.label(source);
+                ui.label(stage);
+                ui.label(good.to_string());
+                let text = egui::RichText::new(bad.to_string());
+                ui.label(if bad > good {
+                    text.color(egui::Color32::RED)
+                } else {
+                    text
+                });
+                ui.end_row();
+            }
+        });
+}
+
+/// Markers of both runs on a shared "time since run start" axis
+fn render_markers(
+    ui: &mut egui::Ui,
+    baseline: Option<&protocol::daq::RunSummary>,
+    candidate: Option<&protocol::daq::RunSummary>,
+) {
+    let runs = [
+        ("Good run", egui::Color32::LIGHT_BLUE, baseline),
+        ("Bad run", egui::Color32::LIGHT_RED, candidate),
+    ];
+    let mut rows: Vec<(f64, &str, egui::Color32, &protocol::daq::RunMarker)> = runs
+        .iter()
+        .filter_map(|&(label, color, run)| run.map(|run| (label, color, run)))
+        .flat_map(|(label, color, run)| {
+            run.markers.iter().map(move |marker| {
+                let offset_s = marker.time_ns.saturating_sub(run.start_ns) as f64 / 1e9;
+                (offset_s, label, color, marker)
+            })
+        })
+        .collect();
+    if rows.is_empty() {
+        ui.label("No markers in either run");
+        return;
+    }
+    rows.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let duration_s = runs
+        .iter()
+        .filter_map(|(_, _, run)| *run)
+        .map(|run| run.stop_ns.saturating_sub(run.start_ns) as f64 / 1e9)
+        .fold(0.0, f64::max);
+    Plot::new("diff_markers_plot")
+        .height(80.0)
+        .show_y(false)
+        .show_axes([true, false])
+        .include_x(0.0)
+        .include_x(duration_s)
+        .include_y(0.0)
+        .include_y(1.0)
+        .x_axis_label("Time since run start (s)")
+        .legend(Legend::default().position(Corner::RightTop))
+        .show(ui, |plot_ui| {
+            for (offset_s, label, color, _) in &rows {
+                plot_ui.vline(
+                    VLine::new(*label, *offset_s)
+                        .color(*color)
+                        .style(LineStyle::dashed_loose()),
+                );
+            }
+        });
+
+    egui::Grid::new("diff_markers")
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Time (s)");
+            ui.strong("Run");
+            ui.strong("Marker");
+            ui.strong("Author");
+            ui.end_row();
+            for (offset_s, label, color, marker) in rows {
+                ui.label(format!("{:.1}", offset_s));
+                ui.colored_label(color, label);
+                ui.label(&marker.text);
+                ui.label(&marker.author);
+                ui.end_row();
+            }
+        });
+}
+
+/// Table of changed keys: added in green, removed in red, changed in yellow
+fn render_field_diffs(ui: &mut egui::Ui, id: &str, diffs: &[protocol::daq::FieldDifference]) {
+    if diffs.is_empty() {
+        ui.label("No differences");
+        return;
+    }
+    egui::Grid::new(id).striped(true).show(ui, |ui| {
+        ui.strong("Key");
+        ui.strong("Good run");
+        ui.strong("Bad run");
+        ui.end_row();
+        for diff in diffs {
+            let color = match (&diff.baseline, &diff.candidate) {
+                (None, Some(_)) => egui::Color32::GREEN,
+                (Some(_), None) => egui::Color32::RED,
+                _ => egui::Color32::YELLOW,
+            };
+            ui.colored_label(color, &diff.key);
+            ui.label(diff.baseline.as_deref().unwrap_or("—"));
+            ui.label(diff.candidate.as_deref().unwrap_or("—"));
+            ui.end_row();
         }
+    });
+}
+
+/// Channel statistics for both runs, significant mean shifts highlighted
+fn render_channel_comparisons(ui: &mut egui::Ui, channels: &[protocol::daq::ChannelComparison]) {
+    fn stats_text(stats: Option<&protocol::daq::ChannelStatistics>) -> String {
+        stats.map_or_else(
+            || "—".to_string(),
+            |s| format!("{:.4} ± {:.4} (n={})", s.mean, s.std_dev, s.count),
+        )
     }
+
+    egui::Grid::new("diff_channel_grid")
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Channel");
+            ui.strong("Good run");
+            ui.strong("Bad run");
+            ui.strong("Shift (σ)");
+            ui.end_row();
+            for channel in channels {
+                ui.label(&channel.channel);
+                ui.label(stats_text(channel.baseline.as_ref()));
+                ui.label(stats_text(channel.candidate.as_ref()));
+                match channel.mean_shift_sigma {
+                    Some(shift) if shift.abs() >= SIGNIFICANT_SHIFT_SIGMA => {
+                        ui.colored_label(egui::Color32::YELLOW, format!("{:+.1}", shift));
+                    }
+                    Some(shift) => {
+                        ui.label(format!("{:+.1}", shift));
+                    }
+                    None => {
+                        ui.label("—");
+                    }
+                }
+                ui.end_row();
+            }
+        });
 }
 
 /// Load run data from HDF5 file (blocking I/O)
 #[cfg(feature = "storage_hdf5")]
 fn load_run_data_blocking(file_path: &str, run_id: &str) -> Result<RunData, String> {
-    use hdf5::File;
-    use std::path::Path;
+    use storage::DataFile;
 
-    let file =
-        File::open(Path::new(file_path)).map_err(|e| format!("Failed to open HDF5 file: {}", e))?;
+    let file = DataFile::open(file_path).map_err(|e| format!("Failed to open HDF5 file: {}", e))?;
 
     // Read start doc for metadata
-    let start_group = file
-        .group("start")
-        .map_err(|e| format!("Missing start group: {}", e))?;
+    let metadata = file
+        .metadata()
+        .map_err(|e| format!("Failed to read run metadata: {:#}", e))?;
+    if !metadata.keys().any(|key| key.starts_with("/start@")) {
+        return Err("Missing start group".to_string());
+    }
 
-    let run_name = start_group
-        .attr("plan_name")
-        .and_then(|a| a.read_scalar::<hdf5::types::VarLenUnicode>())
-        .map(|s| s.to_string())
-        .unwrap_or_else(|_| run_id[..8].to_string());
+    let run_name = metadata
+        .get("/start@plan_name")
+        .cloned()
+        .unwrap_or_else(|| run_id[..8].to_string());
+
+    // Whole 1D dataset of the primary stream
+    let read_1d = |name: &str| -> Option<Vec<f64>> {
+        let path = format!("/primary/{}", name);
+        let dataset = file.dataset(&path).ok()?;
+        if dataset.shape.len() != 1 {
+            return None;
+        }
+        file.read_slice(&path, 0..dataset.rows()).ok()
+    };
 
     // Read event data (assuming primary stream)
     let mut points = Vec::new();
     let mut x_label = "Point Index".to_string();
     let mut y_label = "Value".to_string();
 
-    // Try to read primary stream data
-    if let Ok(primary_group) = file.group("primary") {
-        // Try common detector names
-        let detector_names = ["detector", "photodiode", "intensity", "counts"];
-        for name in &detector_names {
-            if let Ok(dataset) = primary_group.dataset(name) {
-                if let Ok(data) = dataset.read_1d::<f64>() {
-                    y_label = name.to_string();
-                    points = data
-                        .iter()
-                        .enumerate()
-                        .map(|(i, &y)| (i as f64, y))
-                        .collect();
-                    break;
-                }
-            }
+    // Try common detector names
+    let detector_names = ["detector", "photodiode", "intensity", "counts"];
+    for name in &detector_names {
+        if let Some(data) = read_1d(name) {
+            y_label = name.to_string();
+            points = data
+                .iter()
+                .enumerate()
+                .map(|(i, &y)| (i as f64, y))
+                .collect();
+            break;
         }
+    }
 
-        // Try to read actuator data for x-axis
-        let actuator_names = ["motor", "actuator", "position", "wavelength"];
-        for name in &actuator_names {
-            if let Ok(dataset) = primary_group.dataset(name) {
-                if let Ok(x_data) = dataset.read_1d::<f64>() {
-                    x_label = name.to_string();
-                    // Re-map points with actual x values
-                    if x_data.len() == points.len() {
-                        points = x_data
-                            .iter()
-                            .zip(points.iter())
-                            .map(|(&x, &(_, y))| (x, y))
-                            .collect();
-                    }
-                    break;
-                }
+    // Try to read actuator data for x-axis
+    let actuator_names = ["motor", "actuator", "position", "wavelength"];
+    for name in &actuator_names {
+        if let Some(x_data) = read_1d(name) {
+            x_label = name.to_string();
+            // Re-map points with actual x values
+            if x_data.len() == points.len() {
+                points = x_data
+                    .iter()
+                    .zip(points.iter())
+                    .map(|(&x, &(_, y))| (x, y))
+                    .collect();
             }
+            break;
         }
     }
 
@@ -3,19 +3,22 @@
 //! This panel provides a simplified UI for scientists to configure parameter scans
 //! by selecting devices from the daemon and entering scan parameters through a form.
 
-use std::collections::HashMap;
-use std::time::Instant;
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
 
 use eframe::egui;
-use egui_plot::{Legend, Line, Plot, PlotPoints, Points};
+use egui_plot::{HLine, Legend, Line, Plot, PlotPoints, Points, Polygon, VLine};
 use futures::StreamExt;
 use tokio::runtime::Runtime;
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 
 use crate::widgets::{offline_notice, MetadataEditor, OfflineContext};
+use client::reconnect::is_retriable_error;
 use client::DaqClient;
-use protocol::daq::Document;
+use protocol::daq::{
+    Document, DryRunPlanRequest, DryRunPlanResponse, DryRunSeverity, ProgressDocument,
+};
 
 /// Scan mode selection (1D vs 2D)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -51,6 +54,11 @@ enum PendingAction {
         device_mapping: HashMap<String, String>,
         metadata: HashMap<String, String>,
     },
+    DryRun {
+        plan_type: String,
+        parameters: HashMap<String, String>,
+        device_mapping: HashMap<String, String>,
+    },
     AbortScan,
 }
 
@@ -65,6 +73,10 @@ enum ActionResult {
         success: bool,
         error: Option<String>,
     },
+    DryRunCompleted {
+        key: String,
+        result: Result<DryRunPlanResponse, String>,
+    },
 }
 
 /// Scan preview calculation result
@@ -74,6 +86,22 @@ struct ScanPreview {
     valid: bool,
 }
 
+/// Server-side dry run of the plan the form describes
+struct DryRunPreview {
+    /// [`plan_key`] of the form the dry run was made for
+    key: String,
+    result: Result<DryRunPlanResponse, String>,
+}
+
+/// Form must be unchanged this long before a dry run is requested
+const DRY_RUN_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Above this many points the preview draws row outlines instead of every point
+const MAX_PREVIEW_POINTS: usize = 5_000;
+
+/// Above this many points only the first and last camera footprint are drawn
+const MAX_FOOTPRINTS: usize = 100;
+
 /// Completion summary displayed after scan finishes
 #[derive(Debug, Clone)]
 struct CompletionSummary {
@@ -140,6 +168,10 @@ pub struct ScanBuilderPanel {
     // Document streaming
     document_rx: Option<mpsc::Receiver<Result<Document, String>>>,
     subscription_task: Option<JoinHandle<()>>,
+    /// The running scan lost its document stream and resumes after reconnecting
+    awaiting_reconnect: bool,
+    /// The document stream failed with a transport error (reported once)
+    stream_lost: bool,
 
     // Live plot data: detector_id -> Vec<(actuator_position, detector_value)>
     plot_data: HashMap<String, Vec<(f64, f64)>>,
@@ -155,6 +187,14 @@ pub struct ScanBuilderPanel {
 
     // Metadata editor
     metadata_editor: MetadataEditor,
+
+    // Dry-run preview (geometry, point count, duration, issues)
+    dry_run: Option<DryRunPreview>,
+    dry_run_requested: Option<String>,
+    preview_form_key: String,
+    preview_form_changed: Instant,
+    /// Camera pixel size in stage units, for the FOV footprint (empty = hidden)
+    fov_pixel_size: String,
 }
 
 impl Default for ScanBuilderPanel {
@@ -195,6 +235,8 @@ impl Default for ScanBuilderPanel {
             // Document streaming
             document_rx: None,
             subscription_task: None,
+            awaiting_reconnect: false,
+            stream_lost: false,
             // Live plot
             plot_data: HashMap::new(),
             plot_data_2d: Vec::new(),
@@ -205,6 +247,12 @@ impl Default for ScanBuilderPanel {
             completion_summary: None,
             // Metadata editor
             metadata_editor: MetadataEditor::new(),
+            // Dry-run preview
+            dry_run: None,
+            dry_run_requested: None,
+            preview_form_key: String::new(),
+            preview_form_changed: Instant::now(),
+            fov_pixel_size: String::new(),
         }
     }
 }
@@ -269,6 +317,15 @@ impl ScanBuilderPanel {
                                 self.execution_state = ExecutionState::Idle;
                             }
                         }
+                        ActionResult::DryRunCompleted { key, result } => {
+                            if self.dry_run_requested.as_ref() == Some(&key) {
+                                self.dry_run_requested = None;
+                            }
+                            // Results for an earlier form would only flicker
+                            if key == self.preview_form_key {
+                                self.dry_run = Some(DryRunPreview { key, result });
+                            }
+                        }
                     }
                     updated = true;
                 }
@@ -307,6 +364,13 @@ impl ScanBuilderPanel {
         for result in documents {
             match result {
                 Ok(doc) => self.handle_document(doc),
+                Err(err) if is_retriable_error(&err) => {
+                    // The daemon may be restarting; keep the run until we know
+                    tracing::warn!("Scan document stream interrupted: {}", err);
+                    self.stream_lost = true;
+                    self.suspend();
+                    return;
+                }
                 Err(err) => {
                     self.error = Some(err);
                     self.execution_complete(false);
@@ -432,6 +496,63 @@ impl ScanBuilderPanel {
         }
     }
 
+    /// Keep the running scan across a lost connection instead of failing it
+    pub fn suspend(&mut self) {
+        if self.execution_state != ExecutionState::Running {
+            return;
+        }
+        if let Some(handle) = self.subscription_task.take() {
+            handle.abort();
+        }
+        self.document_rx = None;
+        self.awaiting_reconnect = true;
+        self.status = Some("Connection lost - waiting to resume the run".to_string());
+    }
+
+    /// Whether the document stream failed with a transport error since the last call
+    pub fn take_stream_lost(&mut self) -> bool {
+        std::mem::take(&mut self.stream_lost)
+    }
+
+    /// Pick up a suspended scan after reconnecting
+    ///
+    /// `progress` is the daemon's active run, if known. Events published while
+    /// disconnected are not replayed into the live plot.
+    pub fn resume(
+        &mut self,
+        client: &mut DaqClient,
+        runtime: &Runtime,
+        progress: Option<&ProgressDocument>,
+    ) {
+        if !std::mem::take(&mut self.awaiting_reconnect) {
+            return;
+        }
+        let Some(run_uid) = self.current_run_uid.clone() else {
+            self.execution_complete(false);
+            return;
+        };
+        match progress.filter(|progress| progress.run_uid == run_uid) {
+            Some(progress) => {
+                self.current_point = progress.points_completed;
+                if progress.points_total > 0 {
+                    self.total_points = progress.points_total;
+                }
+                self.status = Some(format!(
+                    "Resumed run {} at point {}",
+                    run_uid, self.current_point
+                ));
+                self.start_document_subscription(client, runtime, &run_uid);
+            }
+            None => {
+                self.error = Some(format!(
+                    "Lost track of run {} while disconnected; see Run History for its result",
+                    run_uid
+                ));
+                self.execution_complete(false);
+            }
+        }
+    }
+
     /// Start document subscription for the current run
     fn start_document_subscription(
         &mut self,
@@ -858,49 +979,7 @@ impl ScanBuilderPanel {
                         .add_enabled(can_start, egui::Button::new("Start Scan"))
                         .clicked()
                     {
-                        // Build plan parameters based on scan mode
-                        let (plan_type, parameters, device_mapping) = match self.scan_mode {
-                            ScanMode::OneDimensional => {
-                                let mut params = HashMap::new();
-                                params.insert("start_position".to_string(), self.start_1d.clone());
-                                params.insert("stop_position".to_string(), self.stop_1d.clone());
-                                params.insert("num_points".to_string(), self.points_1d.clone());
-
-                                let mut devices = HashMap::new();
-                                if let Some(actuator) = &self.selected_actuator {
-                                    devices.insert("motor".to_string(), actuator.clone());
-                                }
-                                if let Some(detector) = self.selected_detectors.first() {
-                                    devices.insert("detector".to_string(), detector.clone());
-                                }
-
-                                ("line_scan".to_string(), params, devices)
-                            }
-                            ScanMode::TwoDimensional => {
-                                let mut params = HashMap::new();
-                                // X axis (fast/inner)
-                                params.insert("x_start".to_string(), self.x_start.clone());
-                                params.insert("x_stop".to_string(), self.x_stop.clone());
-                                params.insert("x_points".to_string(), self.x_points.clone());
-                                // Y axis (slow/outer)
-                                params.insert("y_start".to_string(), self.y_start.clone());
-                                params.insert("y_stop".to_string(), self.y_stop.clone());
-                                params.insert("y_points".to_string(), self.y_points.clone());
-
-                                let mut devices = HashMap::new();
-                                if let Some(actuator_x) = &self.selected_actuator_x {
-                                    devices.insert("x_motor".to_string(), actuator_x.clone());
-                                }
-                                if let Some(actuator_y) = &self.selected_actuator_y {
-                                    devices.insert("y_motor".to_string(), actuator_y.clone());
-                                }
-                                if let Some(detector) = self.selected_detectors.first() {
-                                    devices.insert("detector".to_string(), detector.clone());
-                                }
-
-                                ("grid_scan".to_string(), params, devices)
-                            }
-                        };
+                        let (plan_type, parameters, device_mapping) = self.build_plan();
 
                         // Build metadata from editor + auto-add scan provenance
                         let mut metadata = self.metadata_editor.to_metadata_map();
@@ -1433,23 +1512,278 @@ impl ScanBuilderPanel {
         }
     }
 
+    /// Plan type, parameters and device mapping for the current form
+    fn build_plan(&self) -> (String, HashMap<String, String>, HashMap<String, String>) {
+        match self.scan_mode {
+            ScanMode::OneDimensional => {
+                let mut params = HashMap::new();
+                params.insert("start".to_string(), self.start_1d.clone());
+                params.insert("end".to_string(), self.stop_1d.clone());
+                params.insert("num_points".to_string(), self.points_1d.clone());
+
+                let mut devices = HashMap::new();
+                if let Some(actuator) = &self.selected_actuator {
+                    devices.insert("motor".to_string(), actuator.clone());
+                }
+                if let Some(detector) = self.selected_detectors.first() {
+                    devices.insert("detector".to_string(), detector.clone());
+                }
+
+                ("line_scan".to_string(), params, devices)
+            }
+            ScanMode::TwoDimensional => {
+                let mut params = HashMap::new();
+                // X axis (fast/inner)
+                params.insert("x_start".to_string(), self.x_start.clone());
+                params.insert("x_end".to_string(), self.x_stop.clone());
+                params.insert("x_points".to_string(), self.x_points.clone());
+                // Y axis (slow/outer)
+                params.insert("y_start".to_string(), self.y_start.clone());
+                params.insert("y_end".to_string(), self.y_stop.clone());
+                params.insert("y_points".to_string(), self.y_points.clone());
+
+                let mut devices = HashMap::new();
+                if let Some(actuator_x) = &self.selected_actuator_x {
+                    devices.insert("x_motor".to_string(), actuator_x.clone());
+                }
+                if let Some(actuator_y) = &self.selected_actuator_y {
+                    devices.insert("y_motor".to_string(), actuator_y.clone());
+                }
+                if let Some(detector) = self.selected_detectors.first() {
+                    devices.insert("detector".to_string(), detector.clone());
+                }
+
+                ("grid_scan".to_string(), params, devices)
+            }
+        }
+    }
+
     /// Calculate and render scan preview
+    ///
+    /// Geometry (trajectory, travel limits, camera footprint) is drawn from the
+    /// form. Point count, duration and issues come from a dry run on the
+    /// daemon, requested once the form has settled, so a range outside the
+    /// stage travel is flagged before anything moves.
     fn render_scan_preview(&mut self, ui: &mut egui::Ui) {
         let preview = self.calculate_scan_preview();
+        let (plan_type, parameters, device_mapping) = self.build_plan();
+        let key = plan_key(&plan_type, &parameters, &device_mapping);
+        if key != self.preview_form_key {
+            self.preview_form_key = key.clone();
+            self.preview_form_changed = Instant::now();
+        }
+        let idle = self.execution_state == ExecutionState::Idle;
+        let stale = self.dry_run.as_ref().is_none_or(|d| d.key != key);
+        let requesting = self.dry_run_requested.as_ref() == Some(&key);
+        let mut request = false;
 
         ui.group(|ui| {
             ui.heading("Scan Preview");
 
-            if preview.valid {
-                ui.label(format!(
-                    "{} points, ~{}",
-                    preview.total_points,
-                    format_duration(preview.estimated_duration_secs)
-                ));
-            } else {
+            if !preview.valid {
                 ui.colored_label(egui::Color32::GRAY, "Complete form to see preview");
+                return;
+            }
+
+            ui.label(format!(
+                "{} points, ~{}",
+                preview.total_points,
+                format_duration(preview.estimated_duration_secs)
+            ));
+
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(idle && !requesting, egui::Button::new("🔍 Dry Run"))
+                    .on_hover_text("Simulate the plan on the daemon without moving hardware")
+                    .clicked()
+                {
+                    request = true;
+                }
+                if requesting {
+                    ui.spinner();
+                    ui.label("Simulating plan...");
+                } else if stale && self.dry_run.is_some() {
+                    ui.colored_label(egui::Color32::GRAY, "Form changed since last dry run");
+                }
+            });
+
+            if stale && !requesting && idle {
+                let settled = self.preview_form_changed.elapsed();
+                if settled >= DRY_RUN_DEBOUNCE {
+                    request = true;
+                } else {
+                    ui.ctx().request_repaint_after(DRY_RUN_DEBOUNCE - settled);
+                }
+            }
+
+            if let Some(dry_run) = self.dry_run.as_ref().filter(|d| d.key == key) {
+                render_dry_run_result(ui, &dry_run.result);
             }
+
+            ui.horizontal(|ui| {
+                ui.label("Camera pixel size:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.fov_pixel_size)
+                        .desired_width(60.0)
+                        .hint_text("units/px"),
+                )
+                .on_hover_text("Stage units per camera pixel, to draw the field of view");
+            });
+
+            self.render_preview_plot(ui);
         });
+
+        if request && self.pending_action.is_none() {
+            self.dry_run_requested = Some(key);
+            self.pending_action = Some(PendingAction::DryRun {
+                plan_type,
+                parameters,
+                device_mapping,
+            });
+        }
+    }
+
+    /// Draw the scan trajectory against stage travel limits and camera footprint
+    fn render_preview_plot(&self, ui: &mut egui::Ui) {
+        let (x_axis, y_axis, x_device, y_device) = match self.scan_mode {
+            ScanMode::OneDimensional => (
+                ScanAxis::parse(&self.start_1d, &self.stop_1d, &self.points_1d),
+                None,
+                self.selected_actuator.as_deref(),
+                None,
+            ),
+            ScanMode::TwoDimensional => (
+                ScanAxis::parse(&self.x_start, &self.x_stop, &self.x_points),
+                ScanAxis::parse(&self.y_start, &self.y_stop, &self.y_points),
+                self.selected_actuator_x.as_deref(),
+                self.selected_actuator_y.as_deref(),
+            ),
+        };
+        let Some(x_axis) = x_axis else {
+            return;
+        };
+        if self.scan_mode == ScanMode::TwoDimensional && y_axis.is_none() {
+            return;
+        }
+
+        let x_limits = self.travel_limits(x_device);
+        let y_limits = self.travel_limits(y_device);
+        let total_points = x_axis
+            .points
+            .saturating_mul(y_axis.map_or(1, |axis| axis.points));
+        let points = (total_points <= MAX_PREVIEW_POINTS).then(|| scan_points(x_axis, y_axis));
+        let footprint = self.camera_footprint();
+
+        let mut plot = Plot::new("scan_preview_plot")
+            .height(220.0)
+            .legend(Legend::default())
+            .show_grid(true)
+            .x_axis_label(x_device.unwrap_or("X"));
+        if let Some(y_device) = y_device {
+            plot = plot.data_aspect(1.0).y_axis_label(y_device);
+        }
+
+        plot.show(ui, |plot_ui| {
+            plot_ui.line(
+                Line::new("Trajectory", PlotPoints::new(scan_path(x_axis, y_axis)))
+                    .color(egui::Color32::LIGHT_BLUE),
+            );
+
+            if let Some(points) = &points {
+                let (inside, outside): (Vec<[f64; 2]>, Vec<[f64; 2]>) =
+                    points.iter().copied().partition(|[x, y]| {
+                        within_limits(*x, x_limits)
+                            && (y_axis.is_none() || within_limits(*y, y_limits))
+                    });
+                plot_ui.points(
+                    Points::new("Scan points", PlotPoints::new(inside))
+                        .color(egui::Color32::LIGHT_BLUE)
+                        .radius(2.5),
+                );
+                if !outside.is_empty() {
+                    plot_ui.points(
+                        Points::new("Outside travel", PlotPoints::new(outside))
+                            .color(egui::Color32::RED)
+                            .radius(3.5),
+                    );
+                }
+            }
+
+            for x in [x_limits.0, x_limits.1].into_iter().flatten() {
+                plot_ui.vline(VLine::new("Travel limit", x).color(egui::Color32::RED));
+            }
+            if y_axis.is_some() {
+                for y in [y_limits.0, y_limits.1].into_iter().flatten() {
+                    plot_ui.hline(HLine::new("Travel limit", y).color(egui::Color32::RED));
+                }
+            }
+
+            if let Some((width, height)) = footprint {
+                let path = scan_path(x_axis, y_axis);
+                let centers = match &points {
+                    Some(points) if points.len() <= MAX_FOOTPRINTS => points.clone(),
+                    _ => [path.first(), path.last()]
+                        .into_iter()
+                        .flatten()
+                        .copied()
+                        .collect(),
+                };
+                for [x, y] in centers {
+                    let (half_w, half_h) = (width / 2.0, height / 2.0);
+                    let corners = vec![
+                        [x - half_w, y - half_h],
+                        [x + half_w, y - half_h],
+                        [x + half_w, y + half_h],
+                        [x - half_w, y + half_h],
+                    ];
+                    plot_ui.polygon(
+                        Polygon::new("Camera FOV", PlotPoints::new(corners))
+                            .fill_color(egui::Color32::from_rgba_unmultiplied(255, 200, 0, 20))
+                            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(255, 200, 0))),
+                    );
+                }
+            }
+        });
+
+        if points.is_none() {
+            ui.colored_label(
+                egui::Color32::GRAY,
+                format!("{} points: showing scan outline only", total_points),
+            );
+        }
+    }
+
+    /// Travel limits (min, max) of a movable device, from its metadata
+    fn travel_limits(&self, device_id: Option<&str>) -> (Option<f64>, Option<f64>) {
+        device_id
+            .and_then(|id| self.devices.iter().find(|d| d.id == id))
+            .and_then(|device| device.metadata.as_ref())
+            .map_or((None, None), |metadata| {
+                (metadata.min_position, metadata.max_position)
+            })
+    }
+
+    /// Field of view (width, height) in stage units of the first camera detector
+    ///
+    /// Needs the sensor size from device metadata and a pixel size entered in
+    /// the preview, since the daemon does not know the optical magnification.
+    fn camera_footprint(&self) -> Option<(f64, f64)> {
+        let pixel_size: f64 = self.fov_pixel_size.trim().parse().ok()?;
+        if !pixel_size.is_finite() || pixel_size <= 0.0 {
+            return None;
+        }
+        self.selected_detectors.iter().find_map(|id| {
+            let metadata = self
+                .devices
+                .iter()
+                .find(|d| &d.id == id)?
+                .metadata
+                .as_ref()?;
+            Some((
+                f64::from(metadata.frame_width?) * pixel_size,
+                f64::from(metadata.frame_height?) * pixel_size,
+            ))
+        })
     }
 
     /// Calculate scan preview (total points, estimated duration)
@@ -1533,6 +1867,11 @@ impl ScanBuilderPanel {
                 device_mapping,
                 metadata,
             ),
+            PendingAction::DryRun {
+                plan_type,
+                parameters,
+                device_mapping,
+            } => self.dry_run_plan(client, runtime, plan_type, parameters, device_mapping),
             PendingAction::AbortScan => self.abort_scan(client, runtime),
         }
     }
@@ -1639,6 +1978,43 @@ impl ScanBuilderPanel {
         });
     }
 
+    /// Simulate the form's plan on the daemon for the preview
+    fn dry_run_plan(
+        &mut self,
+        client: Option<&mut DaqClient>,
+        runtime: &Runtime,
+        plan_type: String,
+        parameters: HashMap<String, String>,
+        device_mapping: HashMap<String, String>,
+    ) {
+        let Some(client) = client else {
+            self.dry_run_requested = None;
+            return;
+        };
+
+        let key = plan_key(&plan_type, &parameters, &device_mapping);
+        let request = DryRunPlanRequest {
+            plan_type,
+            parameters,
+            device_mapping,
+            // Only the summary is shown, not the command list
+            max_commands: 1,
+            ..Default::default()
+        };
+
+        let mut client = client.clone();
+        let tx = self.action_tx.clone();
+        self.action_in_flight = self.action_in_flight.saturating_add(1);
+
+        runtime.spawn(async move {
+            let result = client
+                .dry_run_plan(request)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(ActionResult::DryRunCompleted { key, result }).await;
+        });
+    }
+
     /// Abort the current scan
     fn abort_scan(&mut self, client: Option<&mut DaqClient>, runtime: &Runtime) {
         let Some(client) = client else {
@@ -1694,3 +2070,134 @@ fn format_duration(secs: f64) -> String {
         format!("{:.1}h", secs / 3600.0)
     }
 }
+
+/// One scan axis as entered in the form
+#[derive(Debug, Clone, Copy)]
+struct ScanAxis {
+    start: f64,
+    stop: f64,
+    points: usize,
+}
+
+impl ScanAxis {
+    fn parse(start: &str, stop: &str, points: &str) -> Option<Self> {
+        let axis = Self {
+            start: start.trim().parse().ok()?,
+            stop: stop.trim().parse().ok()?,
+            points: points.trim().parse().ok()?,
+        };
+        (axis.start.is_finite() && axis.stop.is_finite() && axis.points > 0).then_some(axis)
+    }
+
+    /// Position of point `idx`, spaced as the scan plans space them
+    fn position(&self, idx: usize) -> f64 {
+        if self.points <= 1 {
+            self.start
+        } else {
+            self.start + (self.stop - self.start) / (self.points - 1) as f64 * idx as f64
+        }
+    }
+}
+
+/// Every point visited, in order (grid scans: y outer, x inner, snake order)
+fn scan_points(x: ScanAxis, y: Option<ScanAxis>) -> Vec<[f64; 2]> {
+    let Some(y) = y else {
+        return (0..x.points).map(|i| [x.position(i), 0.0]).collect();
+    };
+    let mut points = Vec::with_capacity(x.points * y.points);
+    for row in 0..y.points {
+        let y_pos = y.position(row);
+        for col in 0..x.points {
+            let col = if row % 2 == 0 {
+                col
+            } else {
+                x.points - 1 - col
+            };
+            points.push([x.position(col), y_pos]);
+        }
+    }
+    points
+}
+
+/// Vertices of the stage path: row end points only, so huge grids stay cheap
+fn scan_path(x: ScanAxis, y: Option<ScanAxis>) -> Vec<[f64; 2]> {
+    let Some(y) = y else {
+        return vec![[x.start, 0.0], [x.position(x.points - 1), 0.0]];
+    };
+    let x_end = x.position(x.points - 1);
+    if y.points > MAX_PREVIEW_POINTS {
+        // Outline of the scanned area
+        return vec![
+            [x.start, y.start],
+            [x_end, y.start],
+            [x_end, y.stop],
+            [x.start, y.stop],
+            [x.start, y.start],
+        ];
+    }
+    (0..y.points)
+        .flat_map(|row| {
+            let y_pos = y.position(row);
+            if row % 2 == 0 {
+                [[x.start, y_pos], [x_end, y_pos]]
+            } else {
+                [[x_end, y_pos], [x.start, y_pos]]
+            }
+        })
+        .collect()
+}
+
+fn within_limits(value: f64, (min, max): (Option<f64>, Option<f64>)) -> bool {
+    min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max)
+}
+
+/// Identifies the plan a dry run was made for
+fn plan_key(
+    plan_type: &str,
+    parameters: &HashMap<String, String>,
+    device_mapping: &HashMap<String, String>,
+) -> String {
+    let parameters: BTreeMap<_, _> = parameters.iter().collect();
+    let device_mapping: BTreeMap<_, _> = device_mapping.iter().collect();
+    format!("{}|{:?}|{:?}", plan_type, parameters, device_mapping)
+}
+
+/// Dry-run summary: simulated point count, duration and any issues
+fn render_dry_run_result(ui: &mut egui::Ui, result: &Result<DryRunPlanResponse, String>) {
+    let response = match result {
+        Ok(response) if response.success => response,
+        Ok(response) => {
+            ui.colored_label(
+                egui::Color32::RED,
+                format!("Dry run failed: {}", response.error_message),
+            );
+            return;
+        }
+        Err(e) => {
+            ui.colored_label(egui::Color32::RED, format!("Dry run failed: {}", e));
+            return;
+        }
+    };
+
+    ui.label(format!(
+        "Dry run: {} points, ~{}, {} commands",
+        response.num_events,
+        format_duration(response.estimated_duration_s),
+        response.total_commands
+    ));
+    if response.issues.is_empty() {
+        ui.colored_label(egui::Color32::GREEN, "✓ No issues found");
+    }
+    for issue in &response.issues {
+        let (color, label) = match issue.severity() {
+            DryRunSeverity::DryRunError => (egui::Color32::RED, "Error"),
+            _ => (egui::Color32::YELLOW, "Warning"),
+        };
+        let text = if issue.device_id.is_empty() {
+            format!("{}: {}", label, issue.message)
+        } else {
+            format!("{} ({}): {}", label, issue.device_id, issue.message)
+        };
+        ui.colored_label(color, text);
+    }
+}
@@ -1,9 +1,15 @@
 //! Scripts panel - manage and execute Rhai scripts.
 //!
 //! Phase 6 (bd-r8uq): Enhanced with Run/Stop controls, progress bars, and execution status.
+//!
+//! The library section manages the daemon's versioned scripts, plan
+//! definitions and device configs, so files no longer have to be copied to
+//! the daemon host by hand.
 
 use eframe::egui;
+use protocol::daq::{LibraryFileInfo, LibraryFileKind};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use tokio::runtime::Runtime;
 use tokio::sync::mpsc;
 
@@ -27,6 +33,27 @@ enum ActionResult {
     Started(Result<String, String>),
     /// Script stopped
     Stopped(Result<String, String>),
+    /// Library listing for the selected kind
+    LibraryRefresh(Result<Vec<LibraryFileInfo>, String>),
+    /// Library upload, download, delete or load finished; refresh afterwards
+    LibraryChanged(Result<String, String>),
+}
+
+/// Library action requested from the UI
+#[derive(Debug)]
+enum LibraryAction {
+    Refresh,
+    /// Upload a local file as the next version
+    Upload(PathBuf),
+    /// Save a version (0 = latest) to a local file
+    Download {
+        name: String,
+        version: u32,
+        path: PathBuf,
+    },
+    Delete(String),
+    /// Upload the latest version to the daemon's script list
+    Load(String),
 }
 
 /// Scripts panel state
@@ -55,6 +82,12 @@ pub struct ScriptsPanel {
     auto_refresh_enabled: bool,
     /// Last auto-refresh time
     last_auto_refresh: Option<std::time::Instant>,
+    /// Kind of library file shown
+    library_kind: LibraryFileKind,
+    /// Cached library listing for `library_kind`
+    library_files: Vec<LibraryFileInfo>,
+    /// Selected library file name
+    selected_library_file: Option<String>,
 }
 
 impl ScriptsPanel {
@@ -98,6 +131,24 @@ impl ScriptsPanel {
                         ActionResult::Stopped(Err(e)) => {
                             self.error = Some(format!("Failed to stop: {}", e));
                         }
+                        ActionResult::LibraryRefresh(Ok(files)) => {
+                            if let Some(name) = &self.selected_library_file {
+                                if !files.iter().any(|f| &f.name == name) {
+                                    self.selected_library_file = None;
+                                }
+                            }
+                            self.library_files = files;
+                        }
+                        ActionResult::LibraryRefresh(Err(e)) => {
+                            self.error = Some(format!("Failed to list library: {}", e));
+                        }
+                        ActionResult::LibraryChanged(Ok(msg)) => {
+                            self.status = Some(msg);
+                            self.error = None;
+                        }
+                        ActionResult::LibraryChanged(Err(e)) => {
+                            self.error = Some(e);
+                        }
                     }
                     updated = true;
                 }
@@ -123,6 +174,7 @@ impl ScriptsPanel {
         // Track pending actions
         let mut pending_refresh = false;
         let mut pending_start: Option<String> = None;
+        let mut pending_library: Option<LibraryAction> = None;
 
         // Auto-refresh when executions are running
         if self.auto_refresh_enabled && self.has_running_executions() {
@@ -154,6 +206,7 @@ impl ScriptsPanel {
         ui.horizontal(|ui| {
             if ui.button("🔄 Refresh").clicked() {
                 pending_refresh = true;
+                pending_library = Some(LibraryAction::Refresh);
             }
 
             ui.separator();
@@ -201,8 +254,16 @@ impl ScriptsPanel {
         // Two-column layout - render without client reference
         let pending_stop = self.render_panels(ui);
 
+        ui.separator();
+        if let Some(action) = self.render_library(ui) {
+            pending_library = Some(action);
+        }
+
         // Execute pending actions with client
         if let Some(client) = client {
+            if let Some(action) = pending_library {
+                self.run_library_action(action, client, runtime);
+            }
             if pending_refresh {
                 self.refresh_internal(Some(client), runtime);
             } else if let Some(script_id) = pending_start {
@@ -210,11 +271,230 @@ impl ScriptsPanel {
             } else if let Some(exec_id) = pending_stop {
                 self.stop_script(exec_id, false, Some(client), runtime);
             }
-        } else if pending_refresh || pending_start.is_some() || pending_stop.is_some() {
+        } else if pending_refresh
+            || pending_start.is_some()
+            || pending_stop.is_some()
+            || pending_library.is_some()
+        {
             self.error = Some("Not connected to daemon".to_string());
         }
     }
 
+    /// Render the library section and return any requested action
+    fn render_library(&mut self, ui: &mut egui::Ui) -> Option<LibraryAction> {
+        let mut action = None;
+
+        ui.heading("📚 Library");
+        ui.horizontal(|ui| {
+            let previous_kind = self.library_kind;
+            for (kind, label) in [
+                (LibraryFileKind::Script, "Scripts"),
+                (LibraryFileKind::Plan, "Plans"),
+                (LibraryFileKind::DeviceConfig, "Device Configs"),
+            ] {
+                ui.selectable_value(&mut self.library_kind, kind, label);
+            }
+            if self.library_kind != previous_kind {
+                self.library_files.clear();
+                self.selected_library_file = None;
+                action = Some(LibraryAction::Refresh);
+            }
+
+            ui.separator();
+
+            let idle = self.action_in_flight == 0;
+            if ui
+                .add_enabled(idle, egui::Button::new("⬆ Upload…"))
+                .clicked()
+            {
+                let extensions: &[&str] = match self.library_kind {
+                    LibraryFileKind::Script => &["rhai"],
+                    LibraryFileKind::Plan => &["rhai", "toml", "json"],
+                    LibraryFileKind::DeviceConfig => &["toml"],
+                };
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Library file", extensions)
+                    .pick_file()
+                {
+                    action = Some(LibraryAction::Upload(path));
+                }
+            }
+
+            let Some(name) = self.selected_library_file.clone() else {
+                return;
+            };
+            if ui
+                .add_enabled(idle, egui::Button::new("⬇ Download…"))
+                .clicked()
+            {
+                if let Some(path) = rfd::FileDialog::new().set_file_name(&name).save_file() {
+                    action = Some(LibraryAction::Download {
+                        name: name.clone(),
+                        version: 0,
+                        path,
+                    });
+                }
+            }
+            if self.library_kind == LibraryFileKind::Script
+                && ui
+                    .add_enabled(idle, egui::Button::new("▶ Load"))
+                    .on_hover_text("Add the latest version to the script list")
+                    .clicked()
+            {
+                action = Some(LibraryAction::Load(name.clone()));
+            }
+            if ui
+                .add_enabled(idle, egui::Button::new("🗑 Delete"))
+                .on_hover_text("Delete the file and all its versions")
+                .clicked()
+            {
+                action = Some(LibraryAction::Delete(name));
+            }
+        });
+
+        if self.library_files.is_empty() {
+            ui.label(
+                egui::RichText::new("No files. Upload one or press Refresh.")
+                    .small()
+                    .weak(),
+            );
+            return action;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_salt("library_list")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for file in &self.library_files {
+                    let selected = self.selected_library_file.as_ref() == Some(&file.name);
+                    ui.horizontal(|ui| {
+                        if ui.selectable_label(selected, &file.name).clicked() {
+                            self.selected_library_file = Some(file.name.clone());
+                        }
+                        if let Some(latest) = &file.latest {
+                            ui.label(egui::RichText::new(version_summary(latest)).small().weak());
+                        }
+                    });
+
+                    // Older versions of the selected file can be downloaded individually
+                    if selected {
+                        for version in file.history.iter().skip(1) {
+                            ui.horizontal(|ui| {
+                                ui.add_space(16.0);
+                                ui.label(
+                                    egui::RichText::new(version_summary(version)).small().weak(),
+                                );
+                                if ui.small_button("⬇").clicked() {
+                                    if let Some(path) =
+                                        rfd::FileDialog::new().set_file_name(&file.name).save_file()
+                                    {
+                                        action = Some(LibraryAction::Download {
+                                            name: file.name.clone(),
+                                            version: version.version,
+                                            path,
+                                        });
+                                    }
+                                }
+                            });
+                        }
+                    }
+                }
+            });
+
+        action
+    }
+
+    /// Run a library action in the background
+    fn run_library_action(
+        &mut self,
+        action: LibraryAction,
+        client: &mut DaqClient,
+        runtime: &Runtime,
+    ) {
+        let kind = self.library_kind;
+        let mut client = client.clone();
+        let tx = self.action_tx.clone();
+        self.action_in_flight = self.action_in_flight.saturating_add(1);
+
+        if let LibraryAction::Refresh = action {
+            runtime.spawn(async move {
+                let result = client
+                    .list_library_files(Some(kind))
+                    .await
+                    .map_err(|e| e.to_string());
+                let _ = tx.send(ActionResult::LibraryRefresh(result)).await;
+            });
+            return;
+        }
+
+        // Uploads are based on the version last listed, so edits made by
+        // someone else in the meantime are refused instead of overwritten
+        let listed_versions: HashMap<String, u32> = self
+            .library_files
+            .iter()
+            .filter_map(|f| Some((f.name.clone(), f.latest.as_ref()?.version)))
+            .collect();
+
+        runtime.spawn(async move {
+            let result = match action {
+                LibraryAction::Refresh => unreachable!("handled above"),
+                LibraryAction::Upload(path) => {
+                    upload_file(&mut client, kind, &path, &listed_versions).await
+                }
+                LibraryAction::Download {
+                    name,
+                    version,
+                    path,
+                } => async {
+                    let (header, content) =
+                        client.download_library_file(kind, &name, version).await?;
+                    std::fs::write(&path, content)?;
+                    Ok(format!(
+                        "Saved {} v{} to {}",
+                        name,
+                        header.version,
+                        path.display()
+                    ))
+                }
+                .await
+                .map_err(|e: anyhow::Error| e.to_string()),
+                LibraryAction::Delete(name) => client
+                    .delete_library_file(kind, &name)
+                    .await
+                    .map(|_| format!("Deleted {}", name))
+                    .map_err(|e| e.to_string()),
+                LibraryAction::Load(name) => async {
+                    let (header, content) = client.download_library_file(kind, &name, 0).await?;
+                    let content = String::from_utf8(content)?;
+                    let response = client
+                        .upload_script(&name, &content, HashMap::new())
+                        .await?;
+                    if !response.success {
+                        anyhow::bail!("{}", response.error_message);
+                    }
+                    Ok(format!("Loaded {} v{} as a script", name, header.version))
+                }
+                .await
+                .map_err(|e: anyhow::Error| format!("Failed to load {}: {}", name, e)),
+            };
+            let _ = tx.send(ActionResult::LibraryChanged(result)).await;
+
+            // Show the effect of the change
+            let listing = client
+                .list_library_files(Some(kind))
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(ActionResult::LibraryRefresh(listing)).await;
+            if let Ok(scripts) = client.list_scripts().await {
+                if let Ok(executions) = client.list_executions().await {
+                    let _ = tx
+                        .send(ActionResult::Refresh(Ok((scripts, executions))))
+                        .await;
+                }
+            }
+        });
+    }
+
     /// Render the panels and return any pending stop action
     fn render_panels(&mut self, ui: &mut egui::Ui) -> Option<String> {
         let mut pending_stop: Option<String> = None;
@@ -237,7 +517,7 @@ impl ScriptsPanel {
         if self.scripts.is_empty() {
             ui.label("No scripts found.");
             ui.label(
-                egui::RichText::new("Upload via CLI: rust-daq-daemon upload <file.rhai>")
+                egui::RichText::new("Load one from the library below")
                     .small()
                     .weak(),
             );
@@ -491,6 +771,51 @@ impl Default for ScriptsPanel {
             action_in_flight: 0,
             auto_refresh_enabled: false,
             last_auto_refresh: None,
+            library_kind: LibraryFileKind::Script,
+            library_files: Vec::new(),
+            selected_library_file: None,
         }
     }
 }
+
+/// One-line description of a library file version
+fn version_summary(version: &protocol::daq::LibraryFileVersion) -> String {
+    let created = chrono::DateTime::from_timestamp_nanos(version.created_ns as i64)
+        .with_timezone(&chrono::Local)
+        .format("%Y-%m-%d %H:%M");
+    let mut summary = format!(
+        "v{} · {} bytes · {}",
+        version.version, version.size, created
+    );
+    if !version.comment.is_empty() {
+        summary.push_str(" · ");
+        summary.push_str(&version.comment);
+    }
+    summary
+}
+
+/// Upload a local file to the library
+async fn upload_file(
+    client: &mut DaqClient,
+    kind: LibraryFileKind,
+    path: &std::path::Path,
+    listed_versions: &HashMap<String, u32>,
+) -> Result<String, String> {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| "Not a file".to_string())?;
+    let content =
+        std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let base_version = listed_versions.get(&name).copied().unwrap_or(0);
+
+    let response = client
+        .upload_library_file(kind, &name, content, base_version, "Uploaded from GUI")
+        .await
+        .map_err(|e| format!("Failed to upload {}: {}", name, e))?;
+    if !response.success {
+        return Err(format!("{} rejected: {}", name, response.error_message));
+    }
+    let version = response.version.map(|v| v.version).unwrap_or_default();
+    Ok(format!("Uploaded {} as v{}", name, version))
+}
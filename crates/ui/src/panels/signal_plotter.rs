@@ -9,12 +9,33 @@
 //! - `ObservableUpdateSender` is passed to background Tokio tasks
 //! - `SignalPlotterPanel` stores a receiver and drains it each frame
 //! - No mutable borrows cross async boundaries
+//!
+//! ## Markers
+//!
+//! "📍 Mark" drops a timestamped note on the plot ("door opened", "beam
+//! dropped"). Markers are sent to the daemon, which files them with the
+//! active run so they show up again when the run is reviewed later.
+//!
+//! ## Display calibration
+//!
+//! Channels with a display transform in the daemon (e.g. photodiode volts as
+//! estimated mW) are plotted calibrated and with their unit, so every GUI
+//! shows them the same way. Only the view changes: CSV exports, like the
+//! daemon's files, keep the raw values. "Raw" switches calibration off.
 
 use eframe::egui;
-use egui_plot::{Line, Plot, PlotPoints};
-use std::collections::VecDeque;
+use egui_plot::{Line, LineStyle, Plot, PlotPoint, PlotPoints, Text, VLine};
+use std::collections::{HashMap, VecDeque};
 use std::sync::mpsc;
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::runtime::Runtime;
+
+use crate::export::{
+    fetch_export_context, ExportContext, FigureFormat, FigureMetadata, ScreenCapture, SvgPlot,
+    SvgSeries,
+};
+use client::DaqClient;
+use protocol::daq::ChannelDisplayTransform;
 
 /// Maximum history depth (points)
 const MAX_HISTORY: usize = 500;
@@ -31,6 +52,140 @@ const MAX_HISTORY_SECONDS: f64 = 120.0;
 /// Available time window presets
 const TIME_WINDOW_OPTIONS: &[f64] = &[1.0, 5.0, 10.0, 30.0, 60.0];
 
+/// How often display transforms are re-read from the daemon
+const DISPLAY_TRANSFORM_REFRESH: Duration = Duration::from_secs(30);
+
+/// Where a marker dropped on the plot ended up
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MarkerStatus {
+    /// Waiting for the daemon
+    Sending,
+    /// Filed with the run with this UID
+    Stored(String),
+    /// Not filed (offline, or no run was active)
+    LocalOnly(String),
+}
+
+/// Note dropped on the plot at a point in time
+#[derive(Debug, Clone)]
+struct PlotMarker {
+    /// Seconds since the panel baseline
+    time: f64,
+    /// Wall-clock time sent to the daemon (ns since UNIX epoch)
+    time_ns: u64,
+    text: String,
+    status: MarkerStatus,
+}
+
+/// Answer to a display calibration call
+#[derive(Debug)]
+enum CalibrationReply {
+    /// Every transform known to the daemon
+    Listed(Vec<ChannelDisplayTransform>),
+    /// Transform of this channel saved
+    Saved(String),
+    Failed(String),
+}
+
+/// Display calibration being edited in the trace manager
+#[derive(Debug, Clone)]
+struct CalibrationEdit {
+    /// Channel name sent to the daemon
+    channel: String,
+    scale: String,
+    offset: String,
+    unit: String,
+    log_scale: bool,
+}
+
+impl CalibrationEdit {
+    fn new(channel: String, current: Option<&ChannelDisplayTransform>) -> Self {
+        match current {
+            Some(t) => Self {
+                channel,
+                scale: t.scale.to_string(),
+                offset: t.offset.to_string(),
+                unit: t.unit.clone(),
+                log_scale: t.log_scale,
+            },
+            None => Self {
+                channel,
+                scale: "1".to_string(),
+                offset: "0".to_string(),
+                unit: String::new(),
+                log_scale: false,
+            },
+        }
+    }
+
+    fn to_transform(&self) -> Result<ChannelDisplayTransform, String> {
+        let scale = self
+            .scale
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid scale '{}'", self.scale))?;
+        let offset = self
+            .offset
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid offset '{}'", self.offset))?;
+        Ok(ChannelDisplayTransform {
+            channel: self.channel.clone(),
+            scale,
+            offset,
+            unit: self.unit.trim().to_string(),
+            log_scale: self.log_scale,
+            target: String::new(),
+        })
+    }
+}
+
+/// Calibrated value of a raw value
+fn calibrate(transform: Option<&ChannelDisplayTransform>, raw: f64) -> f64 {
+    match transform {
+        Some(t) => raw * t.scale + t.offset,
+        None => raw,
+    }
+}
+
+/// Plot coordinate of a raw value: calibrated, then log10 on log-scale
+/// channels (`None` for values a log axis cannot show)
+fn plot_value(transform: Option<&ChannelDisplayTransform>, raw: f64) -> Option<f64> {
+    let value = calibrate(transform, raw);
+    match transform {
+        Some(t) if t.log_scale => (value > 0.0).then(|| value.log10()),
+        _ => Some(value),
+    }
+}
+
+/// Transform of an observable: its own, else its device's
+fn lookup_transform<'a>(
+    transforms: &'a HashMap<String, ChannelDisplayTransform>,
+    device_id: &str,
+    observable_name: &str,
+) -> Option<&'a ChannelDisplayTransform> {
+    transforms
+        .get(&format!("{}:{}", device_id, observable_name))
+        .or_else(|| transforms.get(device_id))
+}
+
+/// ` unit` after a calibrated value (empty when there is no unit)
+fn unit_suffix(transform: Option<&ChannelDisplayTransform>) -> String {
+    match transform {
+        Some(t) if !t.unit.is_empty() => format!(" {}", t.unit),
+        _ => String::new(),
+    }
+}
+
+/// Legend entry of a trace, with its display unit
+fn trace_legend(trace: &SignalTrace, transform: Option<&ChannelDisplayTransform>) -> String {
+    match transform {
+        Some(t) if t.log_scale => format!("{} [log₁₀{}]", trace.label, unit_suffix(transform)),
+        Some(t) if !t.unit.is_empty() => format!("{} [{}]", trace.label, t.unit),
+        _ => trace.label.clone(),
+    }
+}
+
 /// Observable update message for async integration
 ///
 /// This struct is sent from background Tokio tasks to the UI thread
@@ -101,6 +256,11 @@ pub struct SignalTrace {
 }
 
 impl SignalTrace {
+    /// Channel name of this trace (`device_id:observable`)
+    pub fn channel(&self) -> String {
+        format!("{}:{}", self.device_id, self.observable_name)
+    }
+
     /// Create a new trace with current time as start (convenience constructor)
     #[allow(dead_code)]
     pub fn new(label: &str, device_id: &str, observable_name: &str, color: egui::Color32) -> Self {
@@ -207,6 +367,27 @@ pub struct TraceStatistics {
     pub std_dev: f64,
 }
 
+impl TraceStatistics {
+    /// Statistics of the calibrated values
+    fn calibrated(&self, transform: Option<&ChannelDisplayTransform>) -> Self {
+        let Some(t) = transform else {
+            return self.clone();
+        };
+        let (a, b) = (
+            calibrate(transform, self.min),
+            calibrate(transform, self.max),
+        );
+        Self {
+            count: self.count,
+            // A negative scale swaps the extremes
+            min: a.min(b),
+            max: a.max(b),
+            mean: calibrate(transform, self.mean),
+            std_dev: self.std_dev * t.scale.abs(),
+        }
+    }
+}
+
 /// Signal Plotter Panel state
 pub struct SignalPlotterPanel {
     /// Shared time baseline for all traces (ensures comparable timestamps)
@@ -251,11 +432,48 @@ pub struct SignalPlotterPanel {
     export_path: String,
     /// Last export status message
     export_status: Option<(String, bool)>, // (message, is_error)
+    /// Wall-clock time of `panel_start_time`
+    panel_start_wall: SystemTime,
+    /// Markers dropped on the plot
+    markers: Vec<PlotMarker>,
+    /// Text of the next marker
+    marker_text: String,
+    /// Author recorded with markers (the GUI's session name)
+    marker_author: String,
+    /// Results of AddRunMarker calls: (marker time_ns, run UID or error)
+    marker_tx: tokio::sync::mpsc::UnboundedSender<(u64, Result<String, String>)>,
+    marker_rx: tokio::sync::mpsc::UnboundedReceiver<(u64, Result<String, String>)>,
+    /// Export chosen in the UI this frame (format, destination)
+    pending_export: Option<(FigureFormat, std::path::PathBuf)>,
+    /// Exports whose daemon context has arrived
+    export_tx:
+        tokio::sync::mpsc::UnboundedSender<(FigureFormat, std::path::PathBuf, ExportContext)>,
+    export_rx:
+        tokio::sync::mpsc::UnboundedReceiver<(FigureFormat, std::path::PathBuf, ExportContext)>,
+    /// PNG export waiting for its screenshot
+    export_capture: ScreenCapture,
+    /// Screen area of the plot in the last frame (for PNG export)
+    plot_rect: Option<egui::Rect>,
+    /// Display transforms from the daemon, keyed by resolved channel
+    display_transforms: HashMap<String, ChannelDisplayTransform>,
+    /// Plot raw values, ignoring display transforms
+    show_raw: bool,
+    /// When display transforms were last requested
+    transforms_requested: Option<Instant>,
+    calibration_tx: tokio::sync::mpsc::UnboundedSender<CalibrationReply>,
+    calibration_rx: tokio::sync::mpsc::UnboundedReceiver<CalibrationReply>,
+    /// Calibration open in the trace manager
+    calibration_edit: Option<CalibrationEdit>,
+    /// Last calibration message (message, is_error)
+    calibration_status: Option<(String, bool)>,
 }
 
 impl Default for SignalPlotterPanel {
     fn default() -> Self {
         let (tx, rx) = observable_channel();
+        let (marker_tx, marker_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (export_tx, export_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (calibration_tx, calibration_rx) = tokio::sync::mpsc::unbounded_channel();
         Self {
             panel_start_time: Instant::now(),
             traces: Vec::new(),
@@ -278,6 +496,24 @@ impl Default for SignalPlotterPanel {
             new_trace_color_idx: 0,
             export_path: String::from("signal_data.csv"),
             export_status: None,
+            panel_start_wall: SystemTime::now(),
+            markers: Vec::new(),
+            marker_text: String::new(),
+            marker_author: String::new(),
+            marker_tx,
+            marker_rx,
+            pending_export: None,
+            export_tx,
+            export_rx,
+            export_capture: ScreenCapture::default(),
+            plot_rect: None,
+            display_transforms: HashMap::new(),
+            show_raw: false,
+            transforms_requested: None,
+            calibration_tx,
+            calibration_rx,
+            calibration_edit: None,
+            calibration_status: None,
         }
     }
 }
@@ -369,7 +605,7 @@ impl SignalPlotterPanel {
     }
 
     /// Export all visible traces to CSV
-    fn export_to_csv(&mut self, path: std::path::PathBuf) {
+    fn export_to_csv(&mut self, path: std::path::PathBuf, metadata: &FigureMetadata) {
         use crate::export::{SignalExportOptions, SignalTraceData};
 
         // Collect visible traces
@@ -392,8 +628,10 @@ impl SignalPlotterPanel {
             return;
         }
 
-        // Export with default options
-        let options = SignalExportOptions::default();
+        let options = SignalExportOptions {
+            extra_metadata: metadata.entries().to_vec(),
+            ..Default::default()
+        };
         match crate::export::export_signal_traces(&path, &traces, &options) {
             Ok(_) => {
                 let filename = path.file_name().unwrap_or_default().to_string_lossy();
@@ -409,9 +647,13 @@ impl SignalPlotterPanel {
     }
 
     /// Render the signal plotter
-    pub fn ui(&mut self, ui: &mut egui::Ui) {
+    pub fn ui(&mut self, ui: &mut egui::Ui, mut client: Option<&mut DaqClient>, runtime: &Runtime) {
         // Drain any pending async updates first
         self.drain_updates();
+        self.poll_markers(ui.ctx());
+        self.poll_exports(ui.ctx());
+        self.poll_calibration(ui.ctx());
+        self.refresh_display_transforms(client.as_deref_mut(), runtime);
 
         // Toolbar
         ui.horizontal(|ui| {
@@ -429,10 +671,14 @@ impl SignalPlotterPanel {
             ui.toggle_value(&mut self.show_legend, "Legend");
             ui.toggle_value(&mut self.show_statistics, "Stats");
             ui.toggle_value(&mut self.show_trace_manager, "Traces");
+            ui.toggle_value(&mut self.show_raw, "Raw")
+                .on_hover_text("Show raw values instead of the daemon's display calibration");
 
             if ui.button("Clear").clicked() {
                 // Reset panel baseline so new traces align with cleared traces
                 self.panel_start_time = Instant::now();
+                self.panel_start_wall = SystemTime::now();
+                self.markers.clear();
                 for trace in &mut self.traces {
                     trace.points.clear();
                     trace.start_time = self.panel_start_time;
@@ -440,6 +686,35 @@ impl SignalPlotterPanel {
             }
         });
 
+        // Marker row
+        let mut new_marker = None;
+        ui.horizontal(|ui| {
+            ui.label("Marker:");
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.marker_text)
+                    .desired_width(200.0)
+                    .hint_text("What happened?"),
+            );
+            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            let has_text = !self.marker_text.trim().is_empty();
+            if (ui
+                .add_enabled(has_text, egui::Button::new("📍 Mark"))
+                .on_hover_text("Drop a marker at the current time and file it with the active run")
+                .clicked()
+                || submitted)
+                && has_text
+            {
+                let text = std::mem::take(&mut self.marker_text);
+                new_marker = Some(text.trim().to_string());
+            }
+            if !self.markers.is_empty() {
+                ui.label(format!("{} markers", self.markers.len()));
+            }
+        });
+        if let Some(text) = new_marker {
+            self.drop_marker(text, client.as_deref_mut(), runtime);
+        }
+
         // Y-axis controls row
         ui.horizontal(|ui| {
             ui.label("Y-axis:");
@@ -583,16 +858,24 @@ impl SignalPlotterPanel {
         ui.horizontal(|ui| {
             ui.label("Export:");
 
-            // Export button
-            if ui.button("📁 Export to CSV").clicked() {
-                // Open file dialog
-                if let Some(path) = rfd::FileDialog::new()
-                    .set_file_name("signal_data.csv")
-                    .add_filter("CSV Files", &["csv"])
-                    .add_filter("All Files", &["*"])
-                    .save_file()
+            // Export buttons (run UID, channels, time range and device
+            // settings are embedded in every format)
+            for format in [FigureFormat::Png, FigureFormat::Svg, FigureFormat::Csv] {
+                let label = format!("📁 {}", format.extension().to_uppercase());
+                if ui
+                    .add_enabled(!self.export_capture.is_pending(), egui::Button::new(label))
+                    .on_hover_text(format!("Export the plot as {}", format.label()))
+                    .clicked()
                 {
-                    self.export_to_csv(path);
+                    // Open file dialog
+                    if let Some(path) = rfd::FileDialog::new()
+                        .set_file_name(format!("signal_data.{}", format.extension()))
+                        .add_filter(format.label(), &[format.extension()])
+                        .add_filter("All Files", &["*"])
+                        .save_file()
+                    {
+                        self.pending_export = Some((format, path));
+                    }
                 }
             }
 
@@ -623,7 +906,16 @@ impl SignalPlotterPanel {
             ui.horizontal(|ui| {
                 for trace in &visible_traces {
                     if let Some(value) = trace.last_value() {
-                        ui.colored_label(trace.color, format!("{}: {:.4}", trace.label, value));
+                        let transform = self.display_transform(trace);
+                        ui.colored_label(
+                            trace.color,
+                            format!(
+                                "{}: {:.4}{}",
+                                trace.label,
+                                calibrate(transform, value),
+                                unit_suffix(transform)
+                            ),
+                        );
                         ui.separator();
                     }
                 }
@@ -638,13 +930,13 @@ impl SignalPlotterPanel {
             .show_axes(true)
             .show_grid(true)
             .x_axis_label("Time (s)")
-            .y_axis_label("Value");
+            .y_axis_label(self.y_axis_label());
 
         if self.show_legend {
             plot = plot.legend(egui_plot::Legend::default());
         }
 
-        plot.show(ui, |plot_ui| {
+        let plot_response = plot.show(ui, |plot_ui| {
             // Calculate x-axis bounds based on time window and frozen state
             let (x_min, x_max) = if self.frozen {
                 // When frozen, show window at offset from current time
@@ -675,16 +967,58 @@ impl SignalPlotterPanel {
                     continue;
                 }
 
-                let points: PlotPoints = trace.points.iter().map(|(t, v)| [*t, *v]).collect();
+                let transform = self.display_transform(trace);
+                let points: PlotPoints = trace
+                    .points
+                    .iter()
+                    .filter_map(|(t, v)| plot_value(transform, *v).map(|y| [*t, y]))
+                    .collect();
 
-                let line = Line::new(&trace.label, points)
+                let line = Line::new(trace_legend(trace, transform), points)
                     .color(trace.color)
                     .width(2.0);
 
                 plot_ui.line(line);
             }
+
+            let label_y = plot_ui.plot_bounds().max()[1];
+            for marker in &self.markers {
+                let color = match marker.status {
+                    MarkerStatus::Sending => egui::Color32::GRAY,
+                    MarkerStatus::Stored(_) => egui::Color32::LIGHT_BLUE,
+                    MarkerStatus::LocalOnly(_) => egui::Color32::YELLOW,
+                };
+                plot_ui.vline(
+                    VLine::new(&marker.text, marker.time)
+                        .color(color)
+                        .style(LineStyle::dashed_loose()),
+                );
+                if label_y.is_finite() {
+                    plot_ui.text(
+                        Text::new(
+                            &marker.text,
+                            PlotPoint::new(marker.time, label_y),
+                            format!("📍 {}", marker.text),
+                        )
+                        .color(color)
+                        .anchor(egui::Align2::LEFT_TOP),
+                    );
+                }
+            }
         });
 
+        self.plot_rect = Some(plot_response.response.rect);
+
+        // Markers that could not be filed with a run
+        for marker in &self.markers {
+            if let MarkerStatus::LocalOnly(reason) = &marker.status {
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    format!("📍 '{}' kept locally only: {}", marker.text, reason),
+                );
+            }
+        }
+
         // Statistics panel
         if self.show_statistics && !self.traces.is_empty() {
             ui.separator();
@@ -717,7 +1051,9 @@ impl SignalPlotterPanel {
 
                     // Per-trace statistics (only visible traces)
                     for trace in self.traces.iter().filter(|t| t.visible) {
-                        let stats = trace.statistics_for_range(t_start, t_end);
+                        let stats = trace
+                            .statistics_for_range(t_start, t_end)
+                            .calibrated(self.display_transform(trace));
 
                         ui.colored_label(trace.color, &trace.label);
                         ui.label(format!("{}", stats.count));
@@ -747,6 +1083,7 @@ impl SignalPlotterPanel {
         }
 
         // Trace manager panel
+        let mut calibration_request = None;
         if self.show_trace_manager {
             ui.separator();
             ui.collapsing("Trace Manager", |ui| {
@@ -755,9 +1092,10 @@ impl SignalPlotterPanel {
                     ui.label("Active Traces:");
 
                     let mut trace_to_remove: Option<usize> = None;
+                    let mut calibrate_channel: Option<String> = None;
 
                     egui::Grid::new("trace_manager_grid")
-                        .num_columns(5)
+                        .num_columns(6)
                         .spacing([8.0, 4.0])
                         .show(ui, |ui| {
                             // Header
@@ -765,6 +1103,7 @@ impl SignalPlotterPanel {
                             ui.strong("Color");
                             ui.strong("Label");
                             ui.strong("Device/Observable");
+                            ui.strong("Display");
                             ui.strong("");
                             ui.end_row();
 
@@ -795,6 +1134,29 @@ impl SignalPlotterPanel {
                                 // Device/Observable info
                                 ui.label(format!("{}/{}", trace.device_id, trace.observable_name));
 
+                                // Display calibration
+                                let current = lookup_transform(
+                                    &self.display_transforms,
+                                    &trace.device_id,
+                                    &trace.observable_name,
+                                );
+                                let summary = current.map_or_else(
+                                    || "raw".to_string(),
+                                    |t| format!("×{} {:+} {}", t.scale, t.offset, t.unit),
+                                );
+                                if ui
+                                    .button(summary)
+                                    .on_hover_text(
+                                        "Edit display calibration (stored data stays raw)",
+                                    )
+                                    .clicked()
+                                {
+                                    calibrate_channel = Some(
+                                        current
+                                            .map_or_else(|| trace.channel(), |t| t.channel.clone()),
+                                    );
+                                }
+
                                 // Remove button
                                 if ui.button("✖").on_hover_text("Remove trace").clicked() {
                                     trace_to_remove = Some(idx);
@@ -809,6 +1171,16 @@ impl SignalPlotterPanel {
                         self.traces.remove(idx);
                     }
 
+                    if let Some(channel) = calibrate_channel {
+                        let current = self
+                            .display_transforms
+                            .values()
+                            .find(|t| t.channel == channel);
+                        self.calibration_edit = Some(CalibrationEdit::new(channel, current));
+                        self.calibration_status = None;
+                    }
+                    self.calibration_editor_ui(ui, &mut calibration_request);
+
                     ui.separator();
                 }
 
@@ -903,8 +1275,8 @@ impl SignalPlotterPanel {
                         .clicked()
                     {
                         let path = self.export_path.clone();
-                        // export_to_csv updates self.export_status internally
-                        self.export_to_csv(std::path::PathBuf::from(&path));
+                        self.pending_export =
+                            Some((FigureFormat::Csv, std::path::PathBuf::from(&path)));
                     }
                 });
 
@@ -921,6 +1293,426 @@ impl SignalPlotterPanel {
         } else if self.traces.is_empty() {
             ui.label("No traces. Click 'Traces' to add observables.");
         }
+
+        if let Some((transform, clear)) = calibration_request {
+            self.send_calibration(transform, clear, client.as_deref_mut(), runtime);
+        }
+        if let Some((format, path)) = self.pending_export.take() {
+            self.begin_export(format, path, client, runtime);
+        }
+    }
+
+    /// Display transform of a trace, unless raw values are shown
+    fn display_transform(&self, trace: &SignalTrace) -> Option<&ChannelDisplayTransform> {
+        if self.show_raw {
+            return None;
+        }
+        lookup_transform(
+            &self.display_transforms,
+            &trace.device_id,
+            &trace.observable_name,
+        )
+    }
+
+    /// Y-axis label: the unit shared by all visible traces, if any
+    fn y_axis_label(&self) -> String {
+        let mut labels: Vec<String> = self
+            .traces
+            .iter()
+            .filter(|t| t.visible)
+            .map(|t| match self.display_transform(t) {
+                Some(t) if t.log_scale && !t.unit.is_empty() => format!("log₁₀ {}", t.unit),
+                Some(t) if t.log_scale => "log₁₀ value".to_string(),
+                Some(t) if !t.unit.is_empty() => t.unit.clone(),
+                _ => "Value".to_string(),
+            })
+            .collect();
+        labels.sort();
+        labels.dedup();
+        match labels.as_slice() {
+            [label] => label.clone(),
+            _ => "Value".to_string(),
+        }
+    }
+
+    /// Re-read display transforms from the daemon when they are due
+    fn refresh_display_transforms(&mut self, client: Option<&mut DaqClient>, runtime: &Runtime) {
+        let Some(client) = client else {
+            return;
+        };
+        if self
+            .transforms_requested
+            .is_some_and(|t| t.elapsed() < DISPLAY_TRANSFORM_REFRESH)
+        {
+            return;
+        }
+        self.transforms_requested = Some(Instant::now());
+
+        let mut client = client.clone();
+        let tx = self.calibration_tx.clone();
+        runtime.spawn(async move {
+            let reply = match client.list_display_transforms().await {
+                Ok(transforms) => CalibrationReply::Listed(transforms),
+                Err(e) => CalibrationReply::Failed(e.to_string()),
+            };
+            let _ = tx.send(reply);
+        });
+    }
+
+    /// Save or clear a display transform in the daemon
+    fn send_calibration(
+        &mut self,
+        transform: ChannelDisplayTransform,
+        clear: bool,
+        client: Option<&mut DaqClient>,
+        runtime: &Runtime,
+    ) {
+        let Some(client) = client else {
+            self.calibration_status = Some(("Not connected".to_string(), true));
+            return;
+        };
+        let mut client = client.clone();
+        let tx = self.calibration_tx.clone();
+        self.calibration_status = Some(("Saving...".to_string(), false));
+        runtime.spawn(async move {
+            let channel = transform.channel.clone();
+            let reply = match client.set_display_transform(transform, clear).await {
+                Ok(()) => CalibrationReply::Saved(channel),
+                Err(e) => CalibrationReply::Failed(e.to_string()),
+            };
+            let _ = tx.send(reply);
+        });
+    }
+
+    /// Apply answers to display calibration calls
+    fn poll_calibration(&mut self, ctx: &egui::Context) {
+        let mut updated = false;
+        while let Ok(reply) = self.calibration_rx.try_recv() {
+            match reply {
+                CalibrationReply::Listed(transforms) => {
+                    self.display_transforms = transforms
+                        .into_iter()
+                        .map(|t| {
+                            let key = if t.target.is_empty() {
+                                t.channel.clone()
+                            } else {
+                                t.target.clone()
+                            };
+                            (key, t)
+                        })
+                        .collect();
+                }
+                CalibrationReply::Saved(channel) => {
+                    self.calibration_edit = None;
+                    self.calibration_status =
+                        Some((format!("✓ Display calibration of {} saved", channel), false));
+                    // Pick up the change (and its resolved target) right away
+                    self.transforms_requested = None;
+                }
+                CalibrationReply::Failed(e) => {
+                    self.calibration_status = Some((e, true));
+                }
+            }
+            updated = true;
+        }
+        if updated {
+            ctx.request_repaint();
+        }
+    }
+
+    /// Editor for the calibration opened in the trace manager
+    fn calibration_editor_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        request: &mut Option<(ChannelDisplayTransform, bool)>,
+    ) {
+        if let Some(edit) = &mut self.calibration_edit {
+            ui.separator();
+            ui.label(format!(
+                "Display calibration of {} (display = raw × scale + offset):",
+                edit.channel
+            ));
+            ui.horizontal(|ui| {
+                ui.label("Scale:");
+                ui.add(egui::TextEdit::singleline(&mut edit.scale).desired_width(60.0));
+                ui.label("Offset:");
+                ui.add(egui::TextEdit::singleline(&mut edit.offset).desired_width(60.0));
+                ui.label("Unit:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut edit.unit)
+                        .desired_width(40.0)
+                        .hint_text("raw"),
+                );
+                ui.checkbox(&mut edit.log_scale, "Log");
+            });
+            let mut close = false;
+            ui.horizontal(|ui| {
+                if ui.button("💾 Save").clicked() {
+                    match edit.to_transform() {
+                        Ok(transform) => *request = Some((transform, false)),
+                        Err(e) => self.calibration_status = Some((e, true)),
+                    }
+                }
+                if ui
+                    .button("Clear")
+                    .on_hover_text("Remove the calibration; the channel is shown raw")
+                    .clicked()
+                {
+                    let transform = ChannelDisplayTransform {
+                        channel: edit.channel.clone(),
+                        ..Default::default()
+                    };
+                    *request = Some((transform, true));
+                }
+                close = ui.button("Cancel").clicked();
+            });
+            if close {
+                self.calibration_edit = None;
+                self.calibration_status = None;
+            }
+        }
+        if let Some((message, is_error)) = &self.calibration_status {
+            let color = if *is_error {
+                egui::Color32::from_rgb(255, 100, 100)
+            } else {
+                egui::Color32::from_rgb(100, 200, 100)
+            };
+            ui.colored_label(color, message);
+        }
+    }
+
+    /// Visible time range (seconds since the panel baseline)
+    fn visible_range(&self) -> (f64, f64) {
+        let current_time = self.current_time();
+        let end = if self.frozen {
+            current_time - self.frozen_time_offset
+        } else {
+            current_time
+        };
+        ((end - self.time_window).max(0.0), end)
+    }
+
+    /// Gather the daemon context for an export, then write it
+    fn begin_export(
+        &mut self,
+        format: FigureFormat,
+        path: std::path::PathBuf,
+        client: Option<&mut DaqClient>,
+        runtime: &Runtime,
+    ) {
+        let Some(client) = client else {
+            // Offline: export with what the GUI knows
+            let _ = self
+                .export_tx
+                .send((format, path, ExportContext::default()));
+            return;
+        };
+
+        let mut device_ids: Vec<String> = self
+            .traces
+            .iter()
+            .filter(|t| t.visible)
+            .map(|t| t.device_id.clone())
+            .collect();
+        device_ids.sort();
+        device_ids.dedup();
+
+        let mut client = client.clone();
+        let tx = self.export_tx.clone();
+        self.export_status = Some(("Exporting...".to_string(), false));
+        runtime.spawn(async move {
+            let context = fetch_export_context(&mut client, &device_ids).await;
+            let _ = tx.send((format, path, context));
+        });
+    }
+
+    /// Write exports whose context has arrived and finished PNG captures
+    fn poll_exports(&mut self, ctx: &egui::Context) {
+        while let Ok((format, path, context)) = self.export_rx.try_recv() {
+            self.write_export(ctx, format, path, &context);
+        }
+
+        if let Some(result) = self.export_capture.poll(ctx) {
+            self.export_status = Some(match result {
+                Ok(path) => (
+                    format!(
+                        "✓ Exported plot to {}",
+                        path.file_name().unwrap_or_default().to_string_lossy()
+                    ),
+                    false,
+                ),
+                Err(e) => (format!("Export failed: {}", e), true),
+            });
+        }
+        if self.export_capture.is_pending() {
+            ctx.request_repaint();
+        }
+    }
+
+    /// Export metadata: channels, time range, markers and daemon context
+    fn export_metadata(&self, context: &ExportContext) -> FigureMetadata {
+        let (t_start, t_end) = self.visible_range();
+        let channels: Vec<String> = self
+            .traces
+            .iter()
+            .filter(|t| t.visible)
+            .map(|t| format!("{} ({}.{})", t.label, t.device_id, t.observable_name))
+            .collect();
+        let start = chrono::DateTime::<chrono::Local>::from(self.panel_start_wall);
+        let mut metadata = FigureMetadata::new("Signal plot")
+            .with("Channels", channels.join(", "))
+            .with("Time origin", start.to_rfc3339())
+            .with_time_range(t_start, t_end);
+        for marker in self
+            .markers
+            .iter()
+            .filter(|m| m.time >= t_start && m.time <= t_end)
+        {
+            metadata = metadata.with(format!("Marker @ {:.3} s", marker.time), &marker.text);
+        }
+        // CSV keeps raw values; figures show the calibrated ones
+        for trace in self.traces.iter().filter(|t| t.visible) {
+            if let Some(t) = self.display_transform(trace) {
+                metadata = metadata.with(
+                    format!("Display calibration of {}", trace.label),
+                    format!("raw × {} + {}{}", t.scale, t.offset, unit_suffix(Some(t))),
+                );
+            }
+        }
+        context.apply(metadata)
+    }
+
+    fn write_export(
+        &mut self,
+        ctx: &egui::Context,
+        format: FigureFormat,
+        path: std::path::PathBuf,
+        context: &ExportContext,
+    ) {
+        let metadata = self.export_metadata(context);
+        match format {
+            FigureFormat::Csv => self.export_to_csv(path, &metadata),
+            FigureFormat::Svg => {
+                let (t_start, t_end) = self.visible_range();
+                let mut plot = SvgPlot {
+                    title: "Signal Scope".to_string(),
+                    x_label: "Time (s)".to_string(),
+                    y_label: self.y_axis_label(),
+                    series: self
+                        .traces
+                        .iter()
+                        .filter(|t| t.visible)
+                        .map(|t| {
+                            let transform = self.display_transform(t);
+                            SvgSeries {
+                                label: trace_legend(t, transform),
+                                color: [t.color.r(), t.color.g(), t.color.b()],
+                                points: t
+                                    .points
+                                    .iter()
+                                    .filter(|(time, _)| *time >= t_start && *time <= t_end)
+                                    .filter_map(|(time, v)| {
+                                        plot_value(transform, *v).map(|y| (*time, y))
+                                    })
+                                    .collect(),
+                            }
+                        })
+                        .collect(),
+                    markers: self
+                        .markers
+                        .iter()
+                        .map(|m| (m.time, m.text.clone()))
+                        .collect(),
+                    ..Default::default()
+                };
+                plot.fit_ranges();
+                plot.x_range = (t_start, t_end);
+                if let Some(y_range) = self.y_range {
+                    plot.y_range = y_range;
+                }
+                self.export_status =
+                    Some(match crate::export::write_svg(&path, &plot, &metadata) {
+                        Ok(()) => (
+                            format!(
+                                "✓ Exported plot to {}",
+                                path.file_name().unwrap_or_default().to_string_lossy()
+                            ),
+                            false,
+                        ),
+                        Err(e) => (format!("Export failed: {}", e), true),
+                    });
+            }
+            FigureFormat::Png => match self.plot_rect {
+                Some(rect) => {
+                    self.export_capture.request(
+                        ctx,
+                        egui::Id::new("signal_scope_export"),
+                        rect,
+                        path,
+                        metadata,
+                    );
+                }
+                None => {
+                    self.export_status =
+                        Some(("Export failed: plot not shown yet".to_string(), true));
+                }
+            },
+        }
+    }
+
+    /// Set the author recorded with markers
+    pub fn set_marker_author(&mut self, author: &str) {
+        author.clone_into(&mut self.marker_author);
+    }
+
+    /// Place a marker at the current time and send it to the daemon
+    fn drop_marker(&mut self, text: String, client: Option<&mut DaqClient>, runtime: &Runtime) {
+        let time = self.current_time();
+        let time_ns = (self.panel_start_wall + std::time::Duration::from_secs_f64(time))
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+
+        let status = if let Some(client) = client {
+            let mut client = client.clone();
+            let tx = self.marker_tx.clone();
+            let marker_text = text.clone();
+            let author = self.marker_author.clone();
+            runtime.spawn(async move {
+                let result = client
+                    .add_run_marker("", time_ns, &marker_text, &author)
+                    .await
+                    .map_err(|e| e.to_string());
+                let _ = tx.send((time_ns, result));
+            });
+            MarkerStatus::Sending
+        } else {
+            MarkerStatus::LocalOnly("not connected".to_string())
+        };
+
+        self.markers.push(PlotMarker {
+            time,
+            time_ns,
+            text,
+            status,
+        });
+    }
+
+    /// Apply the daemon's answers to sent markers
+    fn poll_markers(&mut self, ctx: &egui::Context) {
+        let mut updated = false;
+        while let Ok((time_ns, result)) = self.marker_rx.try_recv() {
+            if let Some(marker) = self.markers.iter_mut().find(|m| m.time_ns == time_ns) {
+                marker.status = match result {
+                    Ok(run_uid) => MarkerStatus::Stored(run_uid),
+                    Err(e) => MarkerStatus::LocalOnly(e),
+                };
+            }
+            updated = true;
+        }
+        if updated {
+            ctx.request_repaint();
+        }
     }
 
     /// Get trace count (public API for external queries)
@@ -987,3 +1779,45 @@ impl SignalPlotterPanel {
         csv
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    fn transform(scale: f64, offset: f64, log_scale: bool) -> ChannelDisplayTransform {
+        ChannelDisplayTransform {
+            channel: "photodiode".to_string(),
+            scale,
+            offset,
+            unit: "mW".to_string(),
+            log_scale,
+            target: "photodiode".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_calibration_changes_view_only() {
+        let mut trace = SignalTrace::new("pd", "photodiode", "voltage", egui::Color32::RED);
+        trace.push(1.0);
+        trace.push(3.0);
+        let raw = trace.statistics_for_range(0.0, f64::INFINITY);
+
+        let inverted = transform(-2.0, 1.0, false);
+        let stats = raw.calibrated(Some(&inverted));
+        assert_eq!((stats.min, stats.max), (-5.0, -1.0));
+        assert_eq!(stats.mean, -3.0);
+        assert_eq!(stats.std_dev, 2.0);
+        // The trace itself keeps raw values
+        assert_eq!(trace.last_value(), Some(3.0));
+
+        let log = transform(10.0, 0.0, true);
+        assert_eq!(plot_value(Some(&log), 10.0), Some(2.0));
+        assert_eq!(plot_value(Some(&log), -1.0), None);
+        assert_eq!(plot_value(None, -1.0), Some(-1.0));
+
+        let transforms = HashMap::from([("photodiode".to_string(), inverted)]);
+        assert!(lookup_transform(&transforms, "photodiode", "voltage").is_some());
+        assert!(lookup_transform(&transforms, "stage", "position").is_none());
+    }
+}
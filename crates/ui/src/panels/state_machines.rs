@@ -0,0 +1,557 @@
+//! State machines panel - live lifecycle states of modules, the run engine
+//! and devices.
+//!
+//! Draws each kind's state graph with the selected instance's current state
+//! highlighted and its last transition marked, so a module stuck in
+//! `staged` or an engine that never leaves `aborting` is visible at a
+//! glance. Transitions arrive over `StreamStateTransitions`; the full state
+//! is re-read periodically and after the stream drops.
+
+use common::experiment::document::now_ns;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+use futures::StreamExt;
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::widgets::{offline_notice, OfflineContext};
+use client::DaqClient;
+use protocol::daq::{
+    GetStateMachinesResponse, StateMachineGraph, StateMachineInstance, StateTransitionEvent,
+    StreamStateTransitionsRequest,
+};
+
+/// How often the full state is re-read
+const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Wait before resubscribing after the transition stream failed
+const STREAM_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Transitions kept per instance (matches the daemon's history)
+const HISTORY_PER_INSTANCE: usize = 32;
+
+/// Radius of a state node
+const NODE_RADIUS: f32 = 28.0;
+
+/// Messages from background tasks
+enum StateMessage {
+    Refreshed(Result<GetStateMachinesResponse, String>),
+    Transition(StateTransitionEvent),
+    /// The daemon does not offer state machines (older version)
+    Unsupported,
+    StreamFailed(String),
+}
+
+/// State machines panel state
+pub struct StateMachinesPanel {
+    graphs: Vec<StateMachineGraph>,
+    /// Instances keyed by (kind, instance ID)
+    instances: BTreeMap<(String, String), StateMachineInstance>,
+    selected_kind: String,
+    selected_instance: Option<String>,
+    last_refresh: Option<Instant>,
+    error: Option<String>,
+    unsupported: bool,
+    tx: mpsc::Sender<StateMessage>,
+    rx: mpsc::Receiver<StateMessage>,
+    stream_task: Option<JoinHandle<()>>,
+    /// Earliest time to resubscribe after the stream failed
+    stream_retry_at: Option<Instant>,
+    refresh_in_flight: bool,
+}
+
+impl Default for StateMachinesPanel {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel(256);
+        Self {
+            graphs: Vec::new(),
+            instances: BTreeMap::new(),
+            selected_kind: "module".to_string(),
+            selected_instance: None,
+            last_refresh: None,
+            error: None,
+            unsupported: false,
+            tx,
+            rx,
+            stream_task: None,
+            stream_retry_at: None,
+            refresh_in_flight: false,
+        }
+    }
+}
+
+impl Drop for StateMachinesPanel {
+    fn drop(&mut self) {
+        if let Some(task) = self.stream_task.take() {
+            task.abort();
+        }
+    }
+}
+
+impl StateMachinesPanel {
+    fn poll_messages(&mut self, ctx: &egui::Context) {
+        let mut updated = false;
+        while let Ok(message) = self.rx.try_recv() {
+            updated = true;
+            match message {
+                StateMessage::Refreshed(result) => {
+                    self.refresh_in_flight = false;
+                    self.last_refresh = Some(Instant::now());
+                    match result {
+                        Ok(response) => {
+                            self.graphs = response.graphs;
+                            self.instances = response
+                                .instances
+                                .into_iter()
+                                .map(|i| ((i.kind.clone(), i.instance_id.clone()), i))
+                                .collect();
+                            self.error = None;
+                        }
+                        Err(e) => self.error = Some(e),
+                    }
+                }
+                StateMessage::Transition(event) => self.apply_transition(event),
+                StateMessage::Unsupported => {
+                    self.unsupported = true;
+                    self.stream_task = None;
+                }
+                StateMessage::StreamFailed(e) => {
+                    self.error = Some(e);
+                    self.stream_task = None;
+                    self.stream_retry_at = Some(Instant::now() + STREAM_RETRY_DELAY);
+                }
+            }
+        }
+        if updated || self.refresh_in_flight {
+            ctx.request_repaint();
+        }
+    }
+
+    fn apply_transition(&mut self, event: StateTransitionEvent) {
+        let instance = self
+            .instances
+            .entry((event.kind.clone(), event.instance_id.clone()))
+            .or_insert_with(|| StateMachineInstance {
+                kind: event.kind.clone(),
+                instance_id: event.instance_id.clone(),
+                ..Default::default()
+            });
+        instance.state.clone_from(&event.to_state);
+        instance.entered_ns = event.timestamp_ns;
+        if instance.recent_transitions.len() >= HISTORY_PER_INSTANCE {
+            instance.recent_transitions.remove(0);
+        }
+        instance.recent_transitions.push(event);
+    }
+
+    fn refresh(&mut self, client: &DaqClient, runtime: &Runtime) {
+        let mut client = client.clone();
+        let tx = self.tx.clone();
+        self.refresh_in_flight = true;
+        runtime.spawn(async move {
+            let result = match client.get_state_machines(Vec::new()).await {
+                Ok(response) => Ok(response),
+                Err(e) if is_unimplemented(&e) => {
+                    let _ = tx.send(StateMessage::Unsupported).await;
+                    Err("Daemon does not report state machines".to_string())
+                }
+                Err(e) => Err(e.to_string()),
+            };
+            let _ = tx.send(StateMessage::Refreshed(result)).await;
+        });
+    }
+
+    fn start_stream(&mut self, client: &DaqClient, runtime: &Runtime) {
+        let client = client.clone();
+        let tx = self.tx.clone();
+        self.stream_retry_at = None;
+        self.stream_task = Some(runtime.spawn(run_stream(client, tx)));
+    }
+
+    /// Render the panel
+    pub fn ui(&mut self, ui: &mut egui::Ui, client: Option<&mut DaqClient>, runtime: &Runtime) {
+        self.poll_messages(ui.ctx());
+
+        ui.heading("State Machines");
+
+        if offline_notice(ui, client.is_none(), OfflineContext::Generic) {
+            if let Some(task) = self.stream_task.take() {
+                task.abort();
+            }
+            return;
+        }
+        let Some(client) = client else {
+            return;
+        };
+
+        if self.unsupported {
+            ui.label("The connected daemon does not report state machines.");
+            return;
+        }
+
+        let mut refresh = !self.refresh_in_flight
+            && self
+                .last_refresh
+                .is_none_or(|t| t.elapsed() >= REFRESH_INTERVAL);
+        if self.stream_task.is_none() && self.stream_retry_at.is_none_or(|t| Instant::now() >= t) {
+            // (Re)subscribe and re-read what was missed meanwhile
+            self.start_stream(client, runtime);
+            refresh = !self.refresh_in_flight;
+        }
+
+        ui.horizontal(|ui| {
+            for graph in &self.graphs {
+                let count = self
+                    .instances
+                    .keys()
+                    .filter(|(k, _)| *k == graph.kind)
+                    .count();
+                let label = format!("{} ({})", kind_label(&graph.kind), count);
+                if ui
+                    .selectable_label(self.selected_kind == graph.kind, label)
+                    .clicked()
+                {
+                    self.selected_kind.clone_from(&graph.kind);
+                    self.selected_instance = None;
+                }
+            }
+            ui.separator();
+            if ui.button("🔄 Refresh").clicked() {
+                refresh = !self.refresh_in_flight;
+            }
+            if let Some(last) = self.last_refresh {
+                ui.label(format!("Updated {}s ago", last.elapsed().as_secs()));
+            }
+        });
+
+        if let Some(err) = &self.error {
+            ui.colored_label(egui::Color32::RED, format!("Error: {}", err));
+        }
+        ui.separator();
+
+        let instances: Vec<&StateMachineInstance> = self
+            .instances
+            .values()
+            .filter(|i| i.kind == self.selected_kind)
+            .collect();
+        let selected = self
+            .selected_instance
+            .as_ref()
+            .and_then(|id| instances.iter().find(|i| &i.instance_id == id).copied())
+            .or(instances.first().copied());
+        let mut clicked_instance = None;
+
+        ui.columns(2, |columns| {
+            egui::ScrollArea::vertical()
+                .id_salt("state_machine_instances")
+                .show(&mut columns[0], |ui| {
+                    if instances.is_empty() {
+                        ui.weak("No instances");
+                    }
+                    for instance in &instances {
+                        let is_selected =
+                            selected.is_some_and(|s| s.instance_id == instance.instance_id);
+                        let label = format!(
+                            "{}  {}  ({})",
+                            instance.instance_id,
+                            instance.state,
+                            format_duration(time_in_state(instance.entered_ns))
+                        );
+                        let text = if last_was_unexpected(instance) {
+                            egui::RichText::new(label).color(egui::Color32::RED)
+                        } else {
+                            egui::RichText::new(label)
+                        };
+                        if ui.selectable_label(is_selected, text).clicked() {
+                            clicked_instance = Some(instance.instance_id.clone());
+                        }
+                    }
+                });
+
+            let ui = &mut columns[1];
+            if let Some(graph) = self.graphs.iter().find(|g| g.kind == self.selected_kind) {
+                draw_graph(ui, graph, selected);
+            }
+            if let Some(instance) = selected {
+                ui.separator();
+                ui.strong(format!("Recent transitions of {}", instance.instance_id));
+                egui::ScrollArea::vertical()
+                    .id_salt("state_machine_transitions")
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        for event in instance.recent_transitions.iter().rev() {
+                            let from = if event.from_state.is_empty() {
+                                "∅"
+                            } else {
+                                event.from_state.as_str()
+                            };
+                            let line = format!(
+                                "{}  {} → {}  ({})",
+                                format_clock(event.timestamp_ns),
+                                from,
+                                event.to_state,
+                                event.trigger
+                            );
+                            if event.expected {
+                                ui.monospace(line);
+                            } else {
+                                ui.colored_label(
+                                    egui::Color32::RED,
+                                    format!("{}  unexpected", line),
+                                );
+                            }
+                        }
+                    });
+            }
+        });
+
+        if let Some(id) = clicked_instance {
+            self.selected_instance = Some(id);
+        }
+        if refresh {
+            self.refresh(client, runtime);
+        }
+        // Keep the time-in-state counters moving
+        ui.ctx().request_repaint_after(Duration::from_secs(1));
+    }
+}
+
+/// Draw the states on a circle with the allowed transitions between them
+fn draw_graph(
+    ui: &mut egui::Ui,
+    graph: &StateMachineGraph,
+    instance: Option<&StateMachineInstance>,
+) {
+    let size = egui::vec2(ui.available_width(), 320.0);
+    let (response, painter) = ui.allocate_painter(size, egui::Sense::hover());
+    let rect = response.rect;
+    let visuals = ui.visuals();
+
+    let positions = node_positions(
+        graph.states.len(),
+        rect.center(),
+        rect.size() / 2.0 - egui::vec2(NODE_RADIUS, NODE_RADIUS) * 1.5,
+    );
+    let position = |state: &str| {
+        graph
+            .states
+            .iter()
+            .position(|s| s == state)
+            .map(|i| positions[i])
+    };
+
+    let current = instance.map(|i| i.state.as_str());
+    let last = instance.and_then(|i| i.recent_transitions.last());
+    let is_last =
+        |from: &str, to: &str| last.is_some_and(|t| t.from_state == from && t.to_state == to);
+
+    let edge_stroke = egui::Stroke::new(1.0, visuals.weak_text_color());
+    let last_stroke = egui::Stroke::new(2.5, visuals.selection.bg_fill);
+    for edge in &graph.edges {
+        let (Some(from), Some(to)) = (position(&edge.from_state), position(&edge.to_state)) else {
+            continue;
+        };
+        // Separate the two directions of a pair of edges
+        let reverse = graph
+            .edges
+            .iter()
+            .any(|e| e.from_state == edge.to_state && e.to_state == edge.from_state);
+        let stroke = if is_last(&edge.from_state, &edge.to_state) {
+            last_stroke
+        } else {
+            edge_stroke
+        };
+        draw_edge(&painter, from, to, reverse, stroke);
+    }
+
+    // A transition that is not in the graph
+    if let Some(t) = last.filter(|t| !t.expected) {
+        if let (Some(from), Some(to)) = (position(&t.from_state), position(&t.to_state)) {
+            draw_edge(
+                &painter,
+                from,
+                to,
+                false,
+                egui::Stroke::new(2.5, egui::Color32::RED),
+            );
+        }
+    }
+
+    for (state, &center) in graph.states.iter().zip(&positions) {
+        let is_current = current == Some(state.as_str());
+        let (fill, text_color) = if is_current {
+            (visuals.selection.bg_fill, visuals.selection.stroke.color)
+        } else {
+            (visuals.extreme_bg_color, visuals.text_color())
+        };
+        painter.circle(
+            center,
+            NODE_RADIUS,
+            fill,
+            egui::Stroke::new(1.5, visuals.text_color()),
+        );
+        if *state == graph.initial_state {
+            painter.circle_stroke(
+                center,
+                NODE_RADIUS + 3.0,
+                egui::Stroke::new(1.0, visuals.weak_text_color()),
+            );
+        }
+        painter.text(
+            center,
+            egui::Align2::CENTER_CENTER,
+            state,
+            egui::FontId::proportional(11.0),
+            text_color,
+        );
+    }
+}
+
+fn draw_edge(
+    painter: &egui::Painter,
+    from: egui::Pos2,
+    to: egui::Pos2,
+    offset: bool,
+    stroke: egui::Stroke,
+) {
+    let direction = (to - from).normalized();
+    let normal = egui::vec2(-direction.y, direction.x) * if offset { 5.0 } else { 0.0 };
+    let start = from + direction * NODE_RADIUS + normal;
+    let end = to - direction * NODE_RADIUS + normal;
+    painter.arrow(start, end - start, stroke);
+}
+
+/// Evenly spaced points on an ellipse, starting at the top
+fn node_positions(count: usize, center: egui::Pos2, radii: egui::Vec2) -> Vec<egui::Pos2> {
+    (0..count)
+        .map(|i| {
+            let angle = std::f32::consts::TAU * i as f32 / count.max(1) as f32
+                - std::f32::consts::FRAC_PI_2;
+            center + egui::vec2(radii.x * angle.cos(), radii.y * angle.sin())
+        })
+        .collect()
+}
+
+async fn run_stream(mut client: DaqClient, tx: mpsc::Sender<StateMessage>) {
+    let mut stream = match client
+        .stream_state_transitions(StreamStateTransitionsRequest::default())
+        .await
+    {
+        Ok(stream) => Box::pin(stream),
+        Err(e) => {
+            let message = if is_unimplemented(&e) {
+                StateMessage::Unsupported
+            } else {
+                StateMessage::StreamFailed(format!("State transition stream failed: {}", e))
+            };
+            let _ = tx.send(message).await;
+            return;
+        }
+    };
+
+    while let Some(item) = stream.next().await {
+        let message = match item {
+            Ok(event) => StateMessage::Transition(event),
+            Err(status) => StateMessage::StreamFailed(format!(
+                "State transition stream failed: {}",
+                status.message()
+            )),
+        };
+        let failed = matches!(message, StateMessage::StreamFailed(_));
+        if tx.send(message).await.is_err() || failed {
+            return;
+        }
+    }
+    let _ = tx
+        .send(StateMessage::StreamFailed(
+            "State transition stream closed".to_string(),
+        ))
+        .await;
+}
+
+fn is_unimplemented(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<tonic::Status>()
+        .is_some_and(|status| status.code() == tonic::Code::Unimplemented)
+}
+
+fn kind_label(kind: &str) -> &str {
+    match kind {
+        "module" => "Modules",
+        "run_engine" => "Run Engine",
+        "device" => "Devices",
+        other => other,
+    }
+}
+
+fn last_was_unexpected(instance: &StateMachineInstance) -> bool {
+    instance
+        .recent_transitions
+        .last()
+        .is_some_and(|t| !t.expected)
+}
+
+fn time_in_state(entered_ns: u64) -> Duration {
+    Duration::from_nanos(now_ns().saturating_sub(entered_ns))
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, (secs % 3600) / 60),
+    }
+}
+
+fn format_clock(timestamp_ns: u64) -> String {
+    chrono::DateTime::from_timestamp_nanos(timestamp_ns as i64)
+        .with_timezone(&chrono::Local)
+        .format("%H:%M:%S%.3f")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transition(to: &str, expected: bool) -> StateTransitionEvent {
+        StateTransitionEvent {
+            kind: "module".to_string(),
+            instance_id: "m1".to_string(),
+            to_state: to.to_string(),
+            timestamp_ns: 42,
+            expected,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_streamed_transitions_update_instances() {
+        let mut panel = StateMachinesPanel::default();
+        panel.apply_transition(transition("created", true));
+        panel.apply_transition(transition("running", false));
+
+        let instance = &panel.instances[&("module".to_string(), "m1".to_string())];
+        assert_eq!(instance.state, "running");
+        assert_eq!(instance.entered_ns, 42);
+        assert_eq!(instance.recent_transitions.len(), 2);
+        assert!(last_was_unexpected(instance));
+
+        for _ in 0..HISTORY_PER_INSTANCE {
+            panel.apply_transition(transition("stopped", true));
+        }
+        let instance = &panel.instances[&("module".to_string(), "m1".to_string())];
+        assert_eq!(instance.recent_transitions.len(), HISTORY_PER_INSTANCE);
+    }
+
+    #[test]
+    fn test_duration_formatting() {
+        assert_eq!(format_duration(Duration::from_secs(5)), "5s");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2m 5s");
+        assert_eq!(format_duration(Duration::from_secs(7260)), "2h 1m");
+    }
+}
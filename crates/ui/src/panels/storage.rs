@@ -1,4 +1,9 @@
 //! Storage panel - HDF5 recording and acquisition management.
+//!
+//! Also switches individual channels in and out of run files; disabled
+//! channels keep streaming to live plots.
+
+use std::collections::BTreeSet;
 
 use eframe::egui;
 use tokio::runtime::Runtime;
@@ -12,6 +17,8 @@ enum PendingAction {
     Refresh,
     StartRecording { name: String },
     StopRecording,
+    SetChannelRecording { channel: String, enabled: bool },
+    SetDecimation { every_nth: u64 },
 }
 
 /// Result data from a Refresh action (boxed to reduce enum size variance).
@@ -19,6 +26,7 @@ type RefreshData = (
     Option<protocol::daq::StorageConfig>,
     Option<protocol::daq::RecordingStatus>,
     Vec<protocol::daq::AcquisitionSummary>,
+    Option<protocol::daq::ChannelRecordingStatus>,
 );
 
 enum StorageActionResult {
@@ -26,6 +34,7 @@ enum StorageActionResult {
     Refresh(Result<Box<RefreshData>, String>),
     Start(Result<String, String>),
     Stop(Result<(String, u64, u64), String>),
+    ChannelRecording(Result<protocol::daq::ChannelRecordingStatus, String>),
 }
 
 /// Storage panel state
@@ -36,6 +45,14 @@ pub struct StoragePanel {
     recording_status: Option<protocol::daq::RecordingStatus>,
     /// List of acquisitions
     acquisitions: Vec<protocol::daq::AcquisitionSummary>,
+    /// Channels excluded from run files
+    channel_recording: Option<protocol::daq::ChannelRecordingStatus>,
+    /// Channel name input for disabling a channel not seen yet
+    new_channel: String,
+    /// Name logged with channel recording changes
+    author: String,
+    /// Commissioning mode: persist every Nth event
+    decimation_every_nth: u64,
     /// Last refresh timestamp
     last_refresh: Option<std::time::Instant>,
     /// Recording name input
@@ -64,10 +81,11 @@ impl StoragePanel {
                     match result {
                         StorageActionResult::Refresh(result) => match result {
                             Ok(data) => {
-                                let (config, status, acquisitions) = *data;
+                                let (config, status, acquisitions, channel_recording) = *data;
                                 self.config = config;
                                 self.recording_status = status;
                                 self.acquisitions = acquisitions;
+                                self.channel_recording = channel_recording;
                                 self.last_refresh = Some(std::time::Instant::now());
                                 self.status = Some(format!(
                                     "Loaded {} acquisitions",
@@ -96,6 +114,17 @@ impl StoragePanel {
                             }
                             Err(e) => self.error = Some(e),
                         },
+                        StorageActionResult::ChannelRecording(result) => match result {
+                            Ok(status) => {
+                                self.status = Some(format!(
+                                    "{} channels excluded from recording",
+                                    status.disabled_channels.len()
+                                ));
+                                self.channel_recording = Some(status);
+                                self.error = None;
+                            }
+                            Err(e) => self.error = Some(e),
+                        },
                     }
                     updated = true;
                 }
@@ -109,6 +138,11 @@ impl StoragePanel {
         }
     }
 
+    /// Name logged with channel recording changes (the session name)
+    pub fn set_author(&mut self, author: &str) {
+        author.clone_into(&mut self.author);
+    }
+
     /// Render the storage panel
     pub fn ui(&mut self, ui: &mut egui::Ui, client: Option<&mut DaqClient>, runtime: &Runtime) {
         self.poll_async_results(ui.ctx());
@@ -248,6 +282,10 @@ impl StoragePanel {
 
         ui.add_space(8.0);
 
+        self.render_channel_recording(ui);
+
+        ui.add_space(8.0);
+
         // Acquisitions list
         ui.group(|ui| {
             ui.heading("Saved Acquisitions");
@@ -280,6 +318,113 @@ impl StoragePanel {
         }
     }
 
+    /// Per-channel recording toggles
+    fn render_channel_recording(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.heading("Channel Recording");
+            ui.weak("Disabled channels stay in live plots but are not written to run files.");
+
+            if let Some(status) = &self.channel_recording {
+                let disabled: BTreeSet<&str> = status
+                    .disabled_channels
+                    .iter()
+                    .map(String::as_str)
+                    .collect();
+                let channels: BTreeSet<&str> = status
+                    .known_channels
+                    .iter()
+                    .map(String::as_str)
+                    .chain(disabled.iter().copied())
+                    .collect();
+
+                if channels.is_empty() {
+                    ui.label("No channels seen yet. Disable one by name below.");
+                } else {
+                    egui::ScrollArea::vertical()
+                        .id_salt("recording_channels")
+                        .max_height(200.0)
+                        .show(ui, |ui| {
+                            for channel in channels {
+                                let mut recorded = !disabled.contains(channel);
+                                if ui.checkbox(&mut recorded, channel).changed() {
+                                    self.pending_action =
+                                        Some(PendingAction::SetChannelRecording {
+                                            channel: channel.to_string(),
+                                            enabled: recorded,
+                                        });
+                                }
+                            }
+                        });
+                }
+
+                if !status.run_uid.is_empty() && !status.changes.is_empty() {
+                    ui.label(format!(
+                        "{} changes logged in run {}",
+                        status.changes.len(),
+                        &status.run_uid[..status.run_uid.len().min(8)]
+                    ));
+                }
+            } else {
+                ui.label("Channel recording state not available");
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("Channel:");
+                ui.text_edit_singleline(&mut self.new_channel);
+                let channel = self.new_channel.trim();
+                if ui
+                    .add_enabled(!channel.is_empty(), egui::Button::new("Disable"))
+                    .on_hover_text("A data key, or a prefix before \".\" to cover a whole device")
+                    .clicked()
+                {
+                    self.pending_action = Some(PendingAction::SetChannelRecording {
+                        channel: channel.to_string(),
+                        enabled: false,
+                    });
+                    self.new_channel.clear();
+                }
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Commissioning mode: record every");
+                ui.add(egui::DragValue::new(&mut self.decimation_every_nth).range(2..=10_000));
+                ui.label("th event");
+                if ui
+                    .button("Apply")
+                    .on_hover_text("Live plots keep the full rate; only run files are decimated")
+                    .clicked()
+                {
+                    self.pending_action = Some(PendingAction::SetDecimation {
+                        every_nth: self.decimation_every_nth,
+                    });
+                }
+                if ui.button("Full rate").clicked() {
+                    self.pending_action = Some(PendingAction::SetDecimation { every_nth: 0 });
+                }
+            });
+            if let Some(status) = &self.channel_recording {
+                if status.decimation_every_nth > 0 {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        format!(
+                            "Recording every {}th event ({} left out of this run)",
+                            status.decimation_every_nth, status.events_decimated
+                        ),
+                    );
+                } else if status.decimation_max_rate_hz > 0.0 {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        format!(
+                            "Recording at most {} events/s ({} left out of this run)",
+                            status.decimation_max_rate_hz, status.events_decimated
+                        ),
+                    );
+                }
+            }
+        });
+    }
+
     /// Execute a pending action
     fn execute_action(
         &mut self,
@@ -291,6 +436,12 @@ impl StoragePanel {
             PendingAction::Refresh => self.refresh(client, runtime),
             PendingAction::StartRecording { name } => self.start_recording(client, runtime, &name),
             PendingAction::StopRecording => self.stop_recording(client, runtime),
+            PendingAction::SetChannelRecording { channel, enabled } => {
+                self.set_channel_recording(client, runtime, channel, enabled);
+            }
+            PendingAction::SetDecimation { every_nth } => {
+                self.set_decimation(client, runtime, every_nth);
+            }
         }
     }
 
@@ -313,7 +464,8 @@ impl StoragePanel {
                 let config = client.get_storage_config().await.ok();
                 let status = client.get_recording_status().await.ok();
                 let acquisitions = client.list_acquisitions().await.unwrap_or_default();
-                Ok::<_, anyhow::Error>((config, status, acquisitions))
+                let channel_recording = client.get_channel_recording().await.ok();
+                Ok::<_, anyhow::Error>((config, status, acquisitions, channel_recording))
             }
             .await
             .map_err(|e| e.to_string());
@@ -388,6 +540,65 @@ impl StoragePanel {
             let _ = tx.send(action).await;
         });
     }
+
+    /// Enable or disable recording of one channel
+    fn set_channel_recording(
+        &mut self,
+        client: Option<&mut DaqClient>,
+        runtime: &Runtime,
+        channel: String,
+        enabled: bool,
+    ) {
+        self.error = None;
+        self.status = None;
+
+        let Some(client) = client else {
+            self.error = Some("Not connected to daemon".to_string());
+            return;
+        };
+
+        let mut client = client.clone();
+        let author = self.author.clone();
+        let tx = self.action_tx.clone();
+        self.action_in_flight = self.action_in_flight.saturating_add(1);
+
+        runtime.spawn(async move {
+            let result = client
+                .set_channel_recording(&channel, enabled, &author)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(StorageActionResult::ChannelRecording(result)).await;
+        });
+    }
+
+    /// Switch storage decimation (0 records every event)
+    fn set_decimation(
+        &mut self,
+        client: Option<&mut DaqClient>,
+        runtime: &Runtime,
+        every_nth: u64,
+    ) {
+        self.error = None;
+        self.status = None;
+
+        let Some(client) = client else {
+            self.error = Some("Not connected to daemon".to_string());
+            return;
+        };
+
+        let mut client = client.clone();
+        let author = self.author.clone();
+        let tx = self.action_tx.clone();
+        self.action_in_flight = self.action_in_flight.saturating_add(1);
+
+        runtime.spawn(async move {
+            let result = client
+                .set_recording_decimation(every_nth, 0.0, &author)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(StorageActionResult::ChannelRecording(result)).await;
+        });
+    }
 }
 
 impl Default for StoragePanel {
@@ -397,6 +608,10 @@ impl Default for StoragePanel {
             config: None,
             recording_status: None,
             acquisitions: Vec::new(),
+            channel_recording: None,
+            new_channel: String::new(),
+            author: String::new(),
+            decimation_every_nth: 10,
             last_refresh: None,
             recording_name: String::new(),
             error: None,
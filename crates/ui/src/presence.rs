@@ -0,0 +1,208 @@
+//! Multi-user presence for the GUI.
+//!
+//! On connect the GUI opens a session with the daemon's SessionService,
+//! heartbeats it in the background and follows the presence stream, so the
+//! status bar can show who else is connected and which devices they hold.
+
+use std::time::Duration;
+
+use futures::StreamExt;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use client::DaqClient;
+use protocol::daq::{PresenceEventKind, SessionInfo, SessionRole};
+
+/// How often the session is heartbeated (daemon expires it after 30 s)
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Messages from the background presence task.
+enum PresenceMessage {
+    /// Our session was opened
+    Opened(SessionInfo),
+    /// Full session list after a presence change
+    Sessions(Vec<SessionInfo>),
+    /// An admin revoked our session
+    Revoked(String),
+    /// The session or presence stream failed
+    Failed(String),
+}
+
+/// Something the app should react to after [`PresenceTracker::poll`].
+pub enum PresenceNotice {
+    /// An admin ended this GUI's session
+    Revoked { reason: String },
+    /// Presence tracking stopped with an error
+    Failed(String),
+}
+
+/// Owns this GUI's session and the latest view of all sessions.
+pub struct PresenceTracker {
+    tx: mpsc::Sender<PresenceMessage>,
+    rx: mpsc::Receiver<PresenceMessage>,
+    task: Option<JoinHandle<()>>,
+    own: Option<SessionInfo>,
+    sessions: Vec<SessionInfo>,
+}
+
+impl Default for PresenceTracker {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel(32);
+        Self {
+            tx,
+            rx,
+            task: None,
+            own: None,
+            sessions: Vec::new(),
+        }
+    }
+}
+
+impl PresenceTracker {
+    /// Open a session and start following presence (restarts if running).
+    pub fn start(
+        &mut self,
+        client: DaqClient,
+        runtime: &tokio::runtime::Runtime,
+        name: String,
+        role: SessionRole,
+    ) {
+        self.stop(None, runtime);
+        let host = std::env::var("HOSTNAME")
+            .or_else(|_| std::env::var("COMPUTERNAME"))
+            .unwrap_or_default();
+        let tx = self.tx.clone();
+        self.task = Some(runtime.spawn(run_session(client, tx, name, host, role)));
+    }
+
+    /// Stop following presence, closing the session if `client` is given.
+    pub fn stop(&mut self, client: Option<DaqClient>, runtime: &tokio::runtime::Runtime) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        if let (Some(mut client), Some(own)) = (client, self.own.take()) {
+            runtime.spawn(async move {
+                let _ = client.close_session(&own.session_id).await;
+            });
+        }
+        self.sessions.clear();
+    }
+
+    /// Apply messages from the background task.
+    pub fn poll(&mut self) -> Option<PresenceNotice> {
+        let mut notice = None;
+        while let Ok(message) = self.rx.try_recv() {
+            match message {
+                PresenceMessage::Opened(info) => self.own = Some(info),
+                PresenceMessage::Sessions(sessions) => {
+                    if let Some(own) = self.own.as_mut() {
+                        if let Some(updated) =
+                            sessions.iter().find(|s| s.session_id == own.session_id)
+                        {
+                            own.clone_from(updated);
+                        }
+                    }
+                    self.sessions = sessions;
+                }
+                PresenceMessage::Revoked(reason) => {
+                    self.own = None;
+                    self.sessions.clear();
+                    self.task = None;
+                    notice = Some(PresenceNotice::Revoked { reason });
+                }
+                PresenceMessage::Failed(error) => {
+                    self.task = None;
+                    notice = Some(PresenceNotice::Failed(error));
+                }
+            }
+        }
+        notice
+    }
+
+    /// This GUI's session, once opened.
+    pub fn own_session(&self) -> Option<&SessionInfo> {
+        self.own.as_ref()
+    }
+
+    /// All sessions except this GUI's own.
+    pub fn others(&self) -> Vec<SessionInfo> {
+        let own_id = self.own.as_ref().map(|own| own.session_id.as_str());
+        self.sessions
+            .iter()
+            .filter(|s| Some(s.session_id.as_str()) != own_id)
+            .cloned()
+            .collect()
+    }
+}
+
+async fn run_session(
+    mut client: DaqClient,
+    tx: mpsc::Sender<PresenceMessage>,
+    name: String,
+    host: String,
+    role: SessionRole,
+) {
+    let session_id = match client.open_session(&name, &host, role).await {
+        Ok(info) => {
+            let session_id = info.session_id.clone();
+            let _ = tx.send(PresenceMessage::Opened(info)).await;
+            session_id
+        }
+        Err(e) => {
+            let _ = tx
+                .send(PresenceMessage::Failed(format!(
+                    "Failed to open session: {}",
+                    e
+                )))
+                .await;
+            return;
+        }
+    };
+
+    let mut updates = match client.stream_presence().await {
+        Ok(stream) => stream,
+        Err(e) => {
+            let _ = tx
+                .send(PresenceMessage::Failed(format!(
+                    "Presence stream failed: {}",
+                    e
+                )))
+                .await;
+            return;
+        }
+    };
+
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = heartbeat.tick() => {
+                if let Err(e) = client.session_heartbeat(&session_id).await {
+                    let _ = tx
+                        .send(PresenceMessage::Failed(format!("Session heartbeat failed: {}", e)))
+                        .await;
+                    return;
+                }
+            }
+            update = updates.next() => match update {
+                Some(Ok(update)) => {
+                    if update.kind() == PresenceEventKind::PresenceRevoked
+                        && update.session_id == session_id
+                    {
+                        let _ = tx.send(PresenceMessage::Revoked(update.reason)).await;
+                        return;
+                    }
+                    if tx.send(PresenceMessage::Sessions(update.sessions)).await.is_err() {
+                        return;
+                    }
+                }
+                Some(Err(e)) => {
+                    let _ = tx
+                        .send(PresenceMessage::Failed(format!("Presence stream failed: {}", e)))
+                        .await;
+                    return;
+                }
+                None => return,
+            }
+        }
+    }
+}
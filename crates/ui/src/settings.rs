@@ -1,8 +1,12 @@
 //! Centralized settings window for the GUI.
 
+use std::time::Duration;
+
+use client::reconnect::{HealthConfig, ReconnectConfig};
 use eframe::egui;
 use serde::{Deserialize, Serialize};
 
+use crate::notifications::{NotificationKind, NotificationSettings};
 use crate::theme::ThemePreference;
 
 /// Application settings that can be configured by the user.
@@ -16,6 +20,9 @@ pub struct AppSettings {
     pub logging: LoggingSettings,
     /// Storage settings
     pub storage: StorageSettings,
+    /// Run completion and alarm notifications
+    #[serde(default)]
+    pub notifications: NotificationSettings,
 }
 
 impl Default for AppSettings {
@@ -25,19 +32,35 @@ impl Default for AppSettings {
             appearance: AppearanceSettings::default(),
             logging: LoggingSettings::default(),
             storage: StorageSettings::default(),
+            notifications: NotificationSettings::default(),
         }
     }
 }
 
 /// Connection settings for daemon communication.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ConnectionSettings {
     /// Daemon address (hostname:port or URL)
     pub daemon_address: String,
     /// Enable automatic reconnection on disconnect
     pub auto_reconnect: bool,
+    /// Delay before the first reconnect attempt, in seconds
+    pub reconnect_initial_delay_secs: f64,
+    /// Longest delay between reconnect attempts, in seconds
+    pub reconnect_max_delay_secs: f64,
+    /// Factor applied to the delay after each failed attempt
+    pub reconnect_backoff_multiplier: f64,
+    /// Attempts before giving up (0 = unlimited)
+    pub reconnect_max_attempts: u32,
+    /// Interval between connection health checks, in seconds
+    pub health_check_interval_secs: u64,
     /// Connection timeout in seconds
     pub timeout_secs: u64,
+    /// Name shown to other users connected to the same daemon
+    pub session_name: String,
+    /// Role announced when opening a session
+    pub session_role: SessionRolePreference,
 }
 
 impl Default for ConnectionSettings {
@@ -45,7 +68,68 @@ impl Default for ConnectionSettings {
         Self {
             daemon_address: "localhost:50051".to_string(),
             auto_reconnect: true,
+            reconnect_initial_delay_secs: 1.0,
+            reconnect_max_delay_secs: 30.0,
+            reconnect_backoff_multiplier: 2.0,
+            reconnect_max_attempts: 0,
+            health_check_interval_secs: 30,
             timeout_secs: 10,
+            session_name: std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .unwrap_or_else(|_| "gui".to_string()),
+            session_role: SessionRolePreference::Operator,
+        }
+    }
+}
+
+impl ConnectionSettings {
+    /// Reconnect behavior for the connection manager
+    pub fn reconnect_config(&self) -> ReconnectConfig {
+        let initial_delay = self.reconnect_initial_delay_secs.max(0.1);
+        ReconnectConfig {
+            initial_delay: Duration::from_secs_f64(initial_delay),
+            max_delay: Duration::from_secs_f64(self.reconnect_max_delay_secs.max(initial_delay)),
+            backoff_multiplier: self.reconnect_backoff_multiplier.max(1.0),
+            max_attempts: self.reconnect_max_attempts,
+            jitter: true,
+            enabled: self.auto_reconnect,
+        }
+    }
+
+    /// Health checks for the connection manager
+    pub fn health_config(&self) -> HealthConfig {
+        HealthConfig {
+            interval: Duration::from_secs(self.health_check_interval_secs.max(1)),
+            ..HealthConfig::default()
+        }
+    }
+}
+
+/// Session role announced to the daemon.
+///
+/// The daemon refuses `Admin` unless the connection's token carries the
+/// `sessions:admin` scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SessionRolePreference {
+    Observer,
+    Operator,
+    Admin,
+}
+
+impl SessionRolePreference {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Observer => "Observer",
+            Self::Operator => "Operator",
+            Self::Admin => "Admin",
+        }
+    }
+
+    pub fn to_proto(self) -> protocol::daq::SessionRole {
+        match self {
+            Self::Observer => protocol::daq::SessionRole::Observer,
+            Self::Operator => protocol::daq::SessionRole::Operator,
+            Self::Admin => protocol::daq::SessionRole::Admin,
         }
     }
 }
@@ -180,6 +264,7 @@ enum SettingsSection {
     Appearance,
     Logging,
     Storage,
+    Notifications,
     Calibration,
     Shortcuts,
 }
@@ -191,6 +276,7 @@ impl SettingsSection {
             Self::Appearance => "Appearance",
             Self::Logging => "Logging",
             Self::Storage => "Storage",
+            Self::Notifications => "Notifications",
             Self::Calibration => "Calibration",
             Self::Shortcuts => "Shortcuts",
         }
@@ -202,6 +288,7 @@ impl SettingsSection {
             Self::Appearance => crate::icons::PALETTE,
             Self::Logging => crate::icons::LIST_BULLETS,
             Self::Storage => crate::icons::DATABASE,
+            Self::Notifications => crate::icons::BELL,
             Self::Calibration => crate::icons::RULER,
             Self::Shortcuts => crate::icons::KEYBOARD,
         }
@@ -250,6 +337,7 @@ impl SettingsWindow {
                                 SettingsSection::Appearance,
                                 SettingsSection::Logging,
                                 SettingsSection::Storage,
+                                SettingsSection::Notifications,
                                 SettingsSection::Calibration,
                                 SettingsSection::Shortcuts,
                             ] {
@@ -282,6 +370,9 @@ impl SettingsWindow {
                         SettingsSection::Storage => {
                             self.show_storage_settings(ui);
                         }
+                        SettingsSection::Notifications => {
+                            self.show_notification_settings(ui);
+                        }
                         SettingsSection::Calibration => {
                             self.show_calibration_settings(ui);
                         }
@@ -340,6 +431,53 @@ impl SettingsWindow {
                 ui.checkbox(&mut self.working_settings.connection.auto_reconnect, "");
                 ui.end_row();
 
+                let connection = &mut self.working_settings.connection;
+                let reconnect = connection.auto_reconnect;
+                ui.label("Reconnect Delay (seconds):");
+                ui.add_enabled_ui(reconnect, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut connection.reconnect_initial_delay_secs)
+                                .speed(0.1)
+                                .range(0.1..=60.0)
+                                .prefix("first "),
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut connection.reconnect_max_delay_secs)
+                                .speed(1.0)
+                                .range(1.0..=600.0)
+                                .prefix("max "),
+                        );
+                    });
+                });
+                ui.end_row();
+
+                ui.label("Backoff Multiplier:");
+                ui.add_enabled(
+                    reconnect,
+                    egui::DragValue::new(&mut connection.reconnect_backoff_multiplier)
+                        .speed(0.1)
+                        .range(1.0..=10.0),
+                );
+                ui.end_row();
+
+                ui.label("Max Attempts (0 = unlimited):");
+                ui.add_enabled(
+                    reconnect,
+                    egui::DragValue::new(&mut connection.reconnect_max_attempts)
+                        .speed(1.0)
+                        .range(0..=1000),
+                );
+                ui.end_row();
+
+                ui.label("Health Check Interval (seconds):");
+                ui.add(
+                    egui::DragValue::new(&mut connection.health_check_interval_secs)
+                        .speed(1.0)
+                        .range(1..=600),
+                );
+                ui.end_row();
+
                 ui.label("Timeout (seconds):");
                 ui.add(
                     egui::DragValue::new(&mut self.working_settings.connection.timeout_secs)
@@ -347,6 +485,28 @@ impl SettingsWindow {
                         .range(1..=60),
                 );
                 ui.end_row();
+
+                ui.label("Session Name:");
+                ui.text_edit_singleline(&mut self.working_settings.connection.session_name);
+                ui.end_row();
+
+                ui.label("Session Role:");
+                egui::ComboBox::from_id_salt("session_role_combo")
+                    .selected_text(self.working_settings.connection.session_role.as_str())
+                    .show_ui(ui, |ui| {
+                        for role in [
+                            SessionRolePreference::Observer,
+                            SessionRolePreference::Operator,
+                            SessionRolePreference::Admin,
+                        ] {
+                            ui.selectable_value(
+                                &mut self.working_settings.connection.session_role,
+                                role,
+                                role.as_str(),
+                            );
+                        }
+                    });
+                ui.end_row();
             });
 
         ui.add_space(10.0);
@@ -529,6 +689,48 @@ impl SettingsWindow {
         });
     }
 
+    fn show_notification_settings(&mut self, ui: &mut egui::Ui) {
+        let notifications = &mut self.working_settings.notifications;
+
+        egui::Grid::new("notifications_grid")
+            .num_columns(3)
+            .spacing([20.0, 8.0])
+            .show(ui, |ui| {
+                ui.strong("Event");
+                ui.strong("Desktop");
+                ui.strong("Sound");
+                ui.end_row();
+
+                for kind in [
+                    NotificationKind::RunCompleted,
+                    NotificationKind::RunFailed,
+                    NotificationKind::Alarm,
+                ] {
+                    let preference = notifications.preference_mut(kind);
+                    ui.label(kind.label());
+                    ui.checkbox(&mut preference.desktop, "");
+                    ui.checkbox(&mut preference.sound, "");
+                    ui.end_row();
+                }
+            });
+
+        ui.add_space(10.0);
+        ui.checkbox(
+            &mut notifications.only_when_unfocused,
+            "Only notify when the window is not focused",
+        );
+
+        ui.add_space(10.0);
+        ui.separator();
+        let note = if cfg!(feature = "desktop_notifications") {
+            "Events are also shown in the status bar and log."
+        } else {
+            "Desktop notifications need the `desktop_notifications` feature; \
+             without it the window requests attention instead."
+        };
+        ui.label(egui::RichText::new(note).small().weak());
+    }
+
     fn show_shortcuts_settings(&mut self, ui: &mut egui::Ui) {
         ui.label("Keyboard shortcuts:");
         ui.add_space(10.0);
@@ -583,6 +785,34 @@ mod tests {
         assert_eq!(settings.logging.level, LogLevel::Info);
     }
 
+    #[test]
+    fn test_reconnect_config_from_settings() {
+        let mut connection = ConnectionSettings {
+            reconnect_initial_delay_secs: 0.5,
+            reconnect_max_delay_secs: 0.2,
+            reconnect_backoff_multiplier: 0.5,
+            reconnect_max_attempts: 5,
+            health_check_interval_secs: 10,
+            ..Default::default()
+        };
+        let config = connection.reconnect_config();
+        assert!(config.enabled);
+        assert_eq!(config.initial_delay, Duration::from_millis(500));
+        // Max delay and multiplier can't shrink the delay
+        assert_eq!(config.max_delay, Duration::from_millis(500));
+        assert_eq!(config.backoff_multiplier, 1.0);
+        assert_eq!(config.max_attempts, 5);
+        assert_eq!(connection.health_config().interval, Duration::from_secs(10));
+
+        connection.auto_reconnect = false;
+        assert!(!connection.reconnect_config().enabled);
+
+        // Settings saved before the reconnect fields existed still load
+        let old: ConnectionSettings =
+            serde_json::from_str(r#"{"daemon_address": "lab:50051"}"#).unwrap();
+        assert_eq!(old.reconnect_max_delay_secs, 30.0);
+    }
+
     #[test]
     fn test_log_level_as_str() {
         assert_eq!(LogLevel::Trace.as_str(), "Trace");
@@ -9,6 +9,8 @@ pub enum ShortcutContext {
     ImageViewer,
     /// Signal plotter specific shortcuts
     SignalPlotter,
+    /// Keyboard stage/shutter/run control (only while armed)
+    StageControl,
 }
 
 impl ShortcutContext {
@@ -17,6 +19,7 @@ impl ShortcutContext {
             Self::Global => "Global",
             Self::ImageViewer => "Image Viewer",
             Self::SignalPlotter => "Signal Plotter",
+            Self::StageControl => "Stage Control (when armed)",
         }
     }
 }
@@ -31,6 +34,8 @@ pub enum ShortcutAction {
     ToggleCheatSheet,
     /// Save current frame/data (Ctrl+S)
     SaveCurrent,
+    /// Arm/disarm keyboard control of hardware (Ctrl+K)
+    ToggleKeyboardControl,
 
     // === Image Viewer Actions ===
     /// Start/stop acquisition (Space)
@@ -75,15 +80,40 @@ pub enum ShortcutAction {
     ToggleHistogram,
     /// Cycle through colormaps (M)
     CycleColormap,
+
+    // === Stage Control Actions ===
+    /// Jog X axis negative (←)
+    JogLeft,
+    /// Jog X axis positive (→)
+    JogRight,
+    /// Jog Y axis positive (↑)
+    JogUp,
+    /// Jog Y axis negative (↓)
+    JogDown,
+    /// Jog Z axis positive (Page Up)
+    JogZUp,
+    /// Jog Z axis negative (Page Down)
+    JogZDown,
+    /// Next larger jog step (])
+    JogStepIncrease,
+    /// Next smaller jog step ([)
+    JogStepDecrease,
+    /// Open/close the shutter (O)
+    ToggleShutter,
+    /// Start the run engine (Ctrl+Enter)
+    StartRun,
+    /// Pause or resume the run engine (P)
+    PauseResumeRun,
 }
 
 impl ShortcutAction {
     /// Get the context where this action is valid
     pub fn context(&self) -> ShortcutContext {
         match self {
-            Self::OpenSettings | Self::ToggleCheatSheet | Self::SaveCurrent => {
-                ShortcutContext::Global
-            }
+            Self::OpenSettings
+            | Self::ToggleCheatSheet
+            | Self::SaveCurrent
+            | Self::ToggleKeyboardControl => ShortcutContext::Global,
             Self::ToggleAcquisition
             | Self::ToggleRecording
             | Self::FitToView
@@ -105,6 +135,17 @@ impl ShortcutAction {
             | Self::ToggleCrosshair
             | Self::ToggleHistogram
             | Self::CycleColormap => ShortcutContext::ImageViewer,
+            Self::JogLeft
+            | Self::JogRight
+            | Self::JogUp
+            | Self::JogDown
+            | Self::JogZUp
+            | Self::JogZDown
+            | Self::JogStepIncrease
+            | Self::JogStepDecrease
+            | Self::ToggleShutter
+            | Self::StartRun
+            | Self::PauseResumeRun => ShortcutContext::StageControl,
         }
     }
 
@@ -114,6 +155,7 @@ impl ShortcutAction {
             Self::OpenSettings => "Open settings",
             Self::ToggleCheatSheet => "Show/hide keyboard shortcuts",
             Self::SaveCurrent => "Save current frame",
+            Self::ToggleKeyboardControl => "Arm/disarm keyboard control",
             Self::ToggleAcquisition => "Start/stop acquisition",
             Self::ToggleRecording => "Start/stop recording",
             Self::FitToView => "Fit image to view",
@@ -135,6 +177,17 @@ impl ShortcutAction {
             Self::ToggleCrosshair => "Toggle crosshair",
             Self::ToggleHistogram => "Toggle histogram overlay",
             Self::CycleColormap => "Cycle through colormaps",
+            Self::JogLeft => "Jog X axis -",
+            Self::JogRight => "Jog X axis +",
+            Self::JogUp => "Jog Y axis +",
+            Self::JogDown => "Jog Y axis -",
+            Self::JogZUp => "Jog Z axis +",
+            Self::JogZDown => "Jog Z axis -",
+            Self::JogStepIncrease => "Larger jog step",
+            Self::JogStepDecrease => "Smaller jog step",
+            Self::ToggleShutter => "Open/close shutter",
+            Self::StartRun => "Start queued runs",
+            Self::PauseResumeRun => "Pause/resume run",
         }
     }
 }
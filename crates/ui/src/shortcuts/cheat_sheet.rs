@@ -60,6 +60,15 @@ impl CheatSheetPanel {
             {
                 self.selected_context = Some(ShortcutContext::ImageViewer);
             }
+            if ui
+                .selectable_label(
+                    self.selected_context == Some(ShortcutContext::StageControl),
+                    "Stage Control",
+                )
+                .clicked()
+            {
+                self.selected_context = Some(ShortcutContext::StageControl);
+            }
         });
 
         ui.add_space(8.0);
@@ -75,6 +84,7 @@ impl CheatSheetPanel {
                 ShortcutContext::Global,
                 ShortcutContext::ImageViewer,
                 ShortcutContext::SignalPlotter,
+                ShortcutContext::StageControl,
             ]
         };
 
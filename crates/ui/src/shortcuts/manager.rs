@@ -45,6 +45,29 @@ impl KeyBinding {
         }
     }
 
+    /// Check for a fresh press of this binding, ignoring key auto-repeat
+    ///
+    /// Used for hardware actions, where holding a key must not queue moves.
+    pub fn matches_press(&self, ctx: &egui::Context) -> bool {
+        ctx.input(|i| {
+            i.events.iter().any(|event| {
+                matches!(
+                    event,
+                    egui::Event::Key {
+                        key,
+                        pressed: true,
+                        repeat: false,
+                        modifiers,
+                        ..
+                    } if *key == self.key
+                        && modifiers.ctrl == self.ctrl
+                        && modifiers.shift == self.shift
+                        && modifiers.alt == self.alt
+                )
+            })
+        })
+    }
+
     /// Check if this binding matches the current input state
     pub fn matches(&self, ctx: &egui::Context) -> bool {
         ctx.input(|i| {
@@ -104,6 +127,7 @@ impl ShortcutManager {
         manager.set_binding(OpenSettings, KeyBinding::ctrl(Key::Comma));
         manager.set_binding(ToggleCheatSheet, KeyBinding::shift(Key::Slash)); // ? key
         manager.set_binding(SaveCurrent, KeyBinding::ctrl(Key::S));
+        manager.set_binding(ToggleKeyboardControl, KeyBinding::ctrl(Key::K));
 
         // === Image Viewer shortcuts ===
         manager.set_binding(ToggleAcquisition, KeyBinding::new(Key::Space));
@@ -136,9 +160,35 @@ impl ShortcutManager {
         manager.set_binding(ToggleHistogram, KeyBinding::new(Key::H));
         manager.set_binding(CycleColormap, KeyBinding::new(Key::M));
 
+        // === Stage Control shortcuts (active only while armed) ===
+        manager.set_binding(JogLeft, KeyBinding::new(Key::ArrowLeft));
+        manager.set_binding(JogRight, KeyBinding::new(Key::ArrowRight));
+        manager.set_binding(JogUp, KeyBinding::new(Key::ArrowUp));
+        manager.set_binding(JogDown, KeyBinding::new(Key::ArrowDown));
+        manager.set_binding(JogZUp, KeyBinding::new(Key::PageUp));
+        manager.set_binding(JogZDown, KeyBinding::new(Key::PageDown));
+        manager.set_binding(JogStepIncrease, KeyBinding::new(Key::CloseBracket));
+        manager.set_binding(JogStepDecrease, KeyBinding::new(Key::OpenBracket));
+        manager.set_binding(ToggleShutter, KeyBinding::new(Key::O));
+        manager.set_binding(StartRun, KeyBinding::ctrl(Key::Enter));
+        manager.set_binding(PauseResumeRun, KeyBinding::new(Key::P));
+
         manager
     }
 
+    /// Add default bindings for actions missing from a saved manager
+    ///
+    /// Bindings saved by older versions don't know about newer actions.
+    pub fn with_missing_defaults(mut self) -> Self {
+        for (action, binding) in Self::with_defaults().bindings {
+            if !self.bindings.contains_key(&action) && self.find_conflict(action, binding).is_none()
+            {
+                self.set_binding(action, binding);
+            }
+        }
+        self
+    }
+
     /// Set a key binding for an action
     pub fn set_binding(&mut self, action: ShortcutAction, binding: KeyBinding) {
         self.bindings.insert(action, binding);
@@ -150,6 +200,13 @@ impl ShortcutManager {
         self.bindings.get(&action).copied()
     }
 
+    /// Check for a fresh (non-repeat) press of an action's binding
+    pub fn check_press(&self, ctx: &egui::Context, action: ShortcutAction) -> bool {
+        self.bindings
+            .get(&action)
+            .is_some_and(|binding| binding.matches_press(ctx))
+    }
+
     /// Check if an action should be triggered in the current context
     pub fn check_action(
         &self,
@@ -276,4 +333,34 @@ mod tests {
             .iter()
             .any(|(a, _)| *a == ShortcutAction::OpenSettings));
     }
+
+    #[test]
+    fn test_stage_control_shares_keys_with_other_contexts() {
+        let manager = ShortcutManager::with_defaults();
+
+        // Arrow keys jog stages and pan images: different contexts, no conflict
+        let arrow = KeyBinding::new(egui::Key::ArrowLeft);
+        assert_eq!(manager.get_binding(ShortcutAction::JogLeft), Some(arrow));
+        assert_eq!(manager.find_conflict(ShortcutAction::JogLeft, arrow), None);
+    }
+
+    #[test]
+    fn test_missing_defaults_added_without_conflicts() {
+        let mut saved = ShortcutManager::with_defaults();
+        saved.bindings.remove(&ShortcutAction::JogLeft);
+        saved.bindings.remove(&ShortcutAction::ToggleShutter);
+        // Ctrl+K was taken by a global action before keyboard control existed
+        saved
+            .bindings
+            .remove(&ShortcutAction::ToggleKeyboardControl);
+        saved.set_binding(ShortcutAction::SaveCurrent, KeyBinding::ctrl(egui::Key::K));
+
+        let manager = saved.with_missing_defaults();
+        assert!(manager.get_binding(ShortcutAction::JogLeft).is_some());
+        assert!(manager.get_binding(ShortcutAction::ToggleShutter).is_some());
+        assert_eq!(
+            manager.get_binding(ShortcutAction::ToggleKeyboardControl),
+            None
+        );
+    }
 }
@@ -50,6 +50,7 @@ impl ShortcutSettingsPanel {
             super::action::ShortcutContext::Global,
             super::action::ShortcutContext::ImageViewer,
             super::action::ShortcutContext::SignalPlotter,
+            super::action::ShortcutContext::StageControl,
         ];
 
         for context in contexts {
@@ -153,6 +154,10 @@ impl ShortcutSettingsPanel {
                 egui::Key::ArrowDown,
                 egui::Key::ArrowLeft,
                 egui::Key::ArrowRight,
+                egui::Key::PageUp,
+                egui::Key::PageDown,
+                egui::Key::OpenBracket,
+                egui::Key::CloseBracket,
                 egui::Key::Num1,
                 egui::Key::Num2,
                 egui::Key::Num3,
@@ -169,7 +174,10 @@ impl ShortcutSettingsPanel {
                 egui::Key::R,
                 egui::Key::C,
                 egui::Key::H,
+                egui::Key::K,
                 egui::Key::M,
+                egui::Key::O,
+                egui::Key::P,
                 egui::Key::S,
                 egui::Key::Comma,
                 egui::Key::Slash,
@@ -44,19 +44,21 @@ use tokio::runtime::Runtime;
 use crate::widgets::device_controls::{DeviceControlWidget, DevicePanelState};
 use crate::widgets::Gauge;
 use client::DaqClient;
-use protocol::daq::DeviceInfo;
+use protocol::daq::{DataQuality, DeviceInfo};
 
 /// Power meter state cached from the daemon
 #[derive(Debug, Clone, Default)]
 struct MeterState {
     power_mw: Option<f64>,
+    /// Quality flag of the last reading
+    quality: DataQuality,
     wavelength_nm: Option<f64>,
     loading: bool,
 }
 
 /// Async action results
 enum ActionResult {
-    ReadPower(Result<(f64, String), String>),
+    ReadPower(Result<(f64, String, DataQuality), String>),
     GetWavelength(Result<f64, String>),
     SetWavelength(Result<f64, String>),
 }
@@ -134,9 +136,10 @@ impl PowerMeterControlPanel {
 
             match result {
                 ActionResult::ReadPower(result) => match result {
-                    Ok((power, units)) => {
+                    Ok((power, units, quality)) => {
                         let power_mw = Self::normalize_power_to_mw(power, &units);
                         self.state.power_mw = Some(power_mw);
+                        self.state.quality = quality;
                         self.state.loading = false;
                         self.panel_state.error = None; // Clear any previous error on success
                     }
@@ -181,7 +184,10 @@ impl PowerMeterControlPanel {
             let result = client
                 .read_value(&device_id)
                 .await
-                .map(|r| (r.value, r.units))
+                .map(|r| {
+                    let quality = r.quality();
+                    (r.value, r.units, quality)
+                })
                 .map_err(|e| e.to_string());
             let _ = tx.send(ActionResult::ReadPower(result)).await;
         });
@@ -319,6 +325,9 @@ impl DeviceControlWidget for PowerMeterControlPanel {
                     .monospace()
                     .size(14.0),
             );
+            if let Some(warning) = quality_warning(self.state.quality) {
+                ui.colored_label(egui::Color32::YELLOW, warning);
+            }
         });
 
         ui.add_space(8.0);
@@ -379,6 +388,17 @@ impl DeviceControlWidget for PowerMeterControlPanel {
     }
 }
 
+/// Warning shown under a reading that is not good
+fn quality_warning(quality: DataQuality) -> Option<&'static str> {
+    match quality {
+        DataQuality::Good => None,
+        DataQuality::Suspect => Some("⚠ Suspect reading (retry or range change)"),
+        DataQuality::Stale => Some("⚠ Stale reading"),
+        DataQuality::OutOfRange => Some("⚠ Out of range"),
+        DataQuality::Interpolated => Some("⚠ Interpolated value"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
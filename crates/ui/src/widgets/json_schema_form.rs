@@ -0,0 +1,893 @@
+//! Form editor driven by a JSON Schema.
+//!
+//! Modules that need more than a flat list of parameters publish a JSON Schema
+//! for their configuration (`ModuleTypeInfo.config_schema`). [`SchemaForm`]
+//! renders that schema as an egui form and keeps the edited document as a
+//! `serde_json::Value`.
+//!
+//! Supported keywords: `type`, `properties`, `required`, `items`, `enum`,
+//! `const`, `default`, `title`, `description`, `minimum`/`maximum`,
+//! `minItems`/`maxItems`, `oneOf`/`anyOf` (variant selector), `allOf`,
+//! `if`/`then`/`else` and local `$ref`s (`#/$defs/...`). Properties are shown
+//! in `propertyOrder`, then `required` order, then by name.
+//!
+//! The daemon's `ConfigureModule` RPC takes a flat string map, so each
+//! top-level property becomes one entry: string values are passed as-is,
+//! everything else is compact JSON.
+
+use std::collections::HashMap;
+
+use eframe::egui;
+use protocol::daq::ModuleParameter;
+use serde_json::{Map, Value};
+
+/// Maximum `$ref` hops followed before giving up (guards against cycles)
+const MAX_REF_DEPTH: usize = 16;
+
+/// Editable form for a JSON Schema document
+#[derive(Debug, Clone)]
+pub struct SchemaForm {
+    schema: Value,
+    value: Value,
+}
+
+impl SchemaForm {
+    /// Parse a JSON Schema and start from its defaults
+    pub fn from_json(schema: &str) -> Result<Self, serde_json::Error> {
+        let schema: Value = serde_json::from_str(schema)?;
+        Ok(Self::from_schema(schema))
+    }
+
+    /// Start from an already parsed schema
+    pub fn from_schema(schema: Value) -> Self {
+        let value = default_value(&schema, &schema);
+        Self { schema, value }
+    }
+
+    /// Build an object schema from a module's flat parameter list
+    pub fn from_parameters(parameters: &[ModuleParameter]) -> Self {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+
+        for (order, param) in parameters.iter().enumerate() {
+            let mut prop = Map::new();
+            let title = if param.units.is_empty() {
+                param.display_name.clone()
+            } else {
+                format!("{} ({})", param.display_name, param.units)
+            };
+            prop.insert("title".into(), Value::String(title));
+            if !param.description.is_empty() {
+                prop.insert(
+                    "description".into(),
+                    Value::String(param.description.clone()),
+                );
+            }
+            prop.insert("propertyOrder".into(), Value::from(order));
+
+            let ty = match param.param_type.as_str() {
+                "float" | "double" | "number" => "number",
+                "int" | "integer" => "integer",
+                "bool" | "boolean" => "boolean",
+                _ => "string",
+            };
+            prop.insert("type".into(), Value::String(ty.into()));
+
+            if !param.enum_values.is_empty() {
+                let values = param
+                    .enum_values
+                    .iter()
+                    .cloned()
+                    .map(Value::String)
+                    .collect();
+                prop.insert("enum".into(), Value::Array(values));
+            }
+            for (key, bound) in [("minimum", &param.min_value), ("maximum", &param.max_value)] {
+                if let Some(n) = bound.as_deref().and_then(|b| b.parse::<f64>().ok()) {
+                    prop.insert(key.into(), Value::from(n));
+                }
+            }
+            if !param.default_value.is_empty() {
+                prop.insert(
+                    "default".into(),
+                    decode_config_value(&Value::Object(prop.clone()), &param.default_value),
+                );
+            }
+            if param.required {
+                required.push(Value::String(param.param_id.clone()));
+            }
+            properties.insert(param.param_id.clone(), Value::Object(prop));
+        }
+
+        let mut schema = Map::new();
+        schema.insert("type".into(), Value::String("object".into()));
+        schema.insert("properties".into(), Value::Object(properties));
+        schema.insert("required".into(), Value::Array(required));
+        Self::from_schema(Value::Object(schema))
+    }
+
+    /// Overlay values from a module's current configuration
+    pub fn load_config(&mut self, config: &HashMap<String, String>) {
+        let root = effective_schema(&self.schema, &self.schema, &self.value);
+        let properties = root.get("properties").and_then(Value::as_object);
+        let Value::Object(object) = &mut self.value else {
+            return;
+        };
+        for (key, raw) in config {
+            let prop = properties
+                .and_then(|p| p.get(key))
+                .map(|p| resolve(&self.schema, p))
+                .unwrap_or(&Value::Null);
+            object.insert(key.clone(), decode_config_value(prop, raw));
+        }
+    }
+
+    /// Flatten the document into `ConfigureModule` parameters.
+    ///
+    /// Properties from an inactive conditional branch are left out.
+    pub fn to_config(&self) -> HashMap<String, String> {
+        let Value::Object(object) = &self.value else {
+            return HashMap::new();
+        };
+        let root = effective_schema(&self.schema, &self.schema, &self.value);
+        let properties = root.get("properties").and_then(Value::as_object);
+        object
+            .iter()
+            .filter(|(key, _)| properties.is_none_or(|p| p.contains_key(*key)))
+            .map(|(key, value)| {
+                let encoded = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                (key.clone(), encoded)
+            })
+            .collect()
+    }
+
+    /// Problems that would make the daemon reject this configuration
+    pub fn errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        collect_errors(&self.schema, &self.schema, &self.value, "", &mut errors);
+        errors
+    }
+
+    /// Render the form; returns true if any value changed
+    pub fn show(&mut self, ui: &mut egui::Ui, id_salt: impl std::hash::Hash) -> bool {
+        let id = egui::Id::new("json_schema_form").with(id_salt);
+        show_node(ui, &self.schema, &self.schema, &mut self.value, id)
+    }
+}
+
+/// Decode one config entry using the property's schema
+fn decode_config_value(schema: &Value, raw: &str) -> Value {
+    if schema_type(schema) == Some("string") {
+        return Value::String(raw.to_string());
+    }
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+/// Follow local `$ref`s
+fn resolve<'a>(root: &'a Value, mut schema: &'a Value) -> &'a Value {
+    for _ in 0..MAX_REF_DEPTH {
+        let Some(target) = schema
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|r| r.strip_prefix('#'))
+            .and_then(|pointer| root.pointer(pointer))
+        else {
+            break;
+        };
+        schema = target;
+    }
+    schema
+}
+
+/// Fold `allOf` and `if`/`then`/`else` into a single schema for `value`
+fn effective_schema(root: &Value, schema: &Value, value: &Value) -> Value {
+    let schema = resolve(root, schema);
+    let Value::Object(map) = schema else {
+        return schema.clone();
+    };
+
+    let mut merged = map.clone();
+    merged.remove("allOf");
+    merged.remove("if");
+    merged.remove("then");
+    merged.remove("else");
+
+    if let Some(parts) = map.get("allOf").and_then(Value::as_array) {
+        for part in parts {
+            merge_into(&mut merged, &effective_schema(root, part, value));
+        }
+    }
+    if let Some(condition) = map.get("if") {
+        let branch = if matches(root, condition, value) {
+            map.get("then")
+        } else {
+            map.get("else")
+        };
+        if let Some(branch) = branch {
+            merge_into(&mut merged, &effective_schema(root, branch, value));
+        }
+    }
+    Value::Object(merged)
+}
+
+/// Merge `properties` and `required` of `other` into `target`
+fn merge_into(target: &mut Map<String, Value>, other: &Value) {
+    let Value::Object(other) = other else {
+        return;
+    };
+    for (key, value) in other {
+        match (key.as_str(), target.get_mut(key)) {
+            ("properties", Some(Value::Object(existing))) => {
+                if let Value::Object(props) = value {
+                    for (name, prop) in props {
+                        existing.insert(name.clone(), prop.clone());
+                    }
+                }
+            }
+            ("required", Some(Value::Array(existing))) => {
+                if let Value::Array(names) = value {
+                    for name in names {
+                        if !existing.contains(name) {
+                            existing.push(name.clone());
+                        }
+                    }
+                }
+            }
+            (_, None) => {
+                target.insert(key.clone(), value.clone());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The schema's primary type, inferred when `type` is absent
+fn schema_type(schema: &Value) -> Option<&str> {
+    match schema.get("type") {
+        Some(Value::String(t)) => return Some(t),
+        Some(Value::Array(types)) => {
+            return types
+                .iter()
+                .filter_map(Value::as_str)
+                .find(|t| *t != "null");
+        }
+        _ => {}
+    }
+    if schema.get("properties").is_some() {
+        Some("object")
+    } else if schema.get("items").is_some() {
+        Some("array")
+    } else if let Some(first) = schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .and_then(|e| e.first())
+        .or_else(|| schema.get("const"))
+    {
+        Some(json_type(first))
+    } else {
+        None
+    }
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Lightweight validation used for `if` conditions and variant detection
+fn matches(root: &Value, schema: &Value, value: &Value) -> bool {
+    let schema = resolve(root, schema);
+    match schema {
+        Value::Bool(b) => return *b,
+        Value::Object(_) => {}
+        _ => return true,
+    }
+
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            return false;
+        }
+    }
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            return false;
+        }
+    }
+    if let Some(ty) = schema.get("type").and_then(Value::as_str) {
+        let actual = json_type(value);
+        if actual != ty && !(ty == "number" && actual == "integer") {
+            return false;
+        }
+    }
+    if let Some(n) = value.as_f64() {
+        if schema
+            .get("minimum")
+            .and_then(Value::as_f64)
+            .is_some_and(|min| n < min)
+            || schema
+                .get("maximum")
+                .and_then(Value::as_f64)
+                .is_some_and(|max| n > max)
+        {
+            return false;
+        }
+    }
+    if let Value::Object(object) = value {
+        let required = schema.get("required").and_then(Value::as_array);
+        if required.is_some_and(|names| {
+            names
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|name| !object.contains_key(name))
+        }) {
+            return false;
+        }
+        if let Some(props) = schema.get("properties").and_then(Value::as_object) {
+            for (name, prop) in props {
+                if let Some(v) = object.get(name) {
+                    if !matches(root, prop, v) {
+                        return false;
+                    }
+                }
+            }
+        }
+    }
+    true
+}
+
+/// Initial value for a schema
+fn default_value(root: &Value, schema: &Value) -> Value {
+    let schema = resolve(root, schema);
+    if let Some(default) = schema.get("default") {
+        return default.clone();
+    }
+    if let Some(constant) = schema.get("const") {
+        return constant.clone();
+    }
+    if let Some(first) = variants(schema).and_then(|v| v.first()) {
+        return default_value(root, first);
+    }
+    if let Some(first) = schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .and_then(|e| e.first())
+    {
+        return first.clone();
+    }
+
+    match schema_type(schema) {
+        Some("object") => {
+            let mut object = Map::new();
+            let effective = effective_schema(root, schema, &Value::Object(Map::new()));
+            if let Some(props) = effective.get("properties").and_then(Value::as_object) {
+                for (name, prop) in props {
+                    object.insert(name.clone(), default_value(root, prop));
+                }
+            }
+            Value::Object(object)
+        }
+        Some("array") => {
+            let min = schema.get("minItems").and_then(Value::as_u64).unwrap_or(0);
+            let item = schema.get("items").cloned().unwrap_or(Value::Bool(true));
+            Value::Array((0..min).map(|_| default_value(root, &item)).collect())
+        }
+        Some("string") => Value::String(String::new()),
+        Some("integer") => {
+            let min = schema.get("minimum").and_then(Value::as_f64);
+            Value::from(min.map_or(0, |m| m.ceil() as i64))
+        }
+        Some("number") => Value::from(schema.get("minimum").and_then(Value::as_f64).unwrap_or(0.0)),
+        Some("boolean") => Value::Bool(false),
+        _ => Value::Null,
+    }
+}
+
+fn variants(schema: &Value) -> Option<&Vec<Value>> {
+    schema
+        .get("oneOf")
+        .or_else(|| schema.get("anyOf"))
+        .and_then(Value::as_array)
+}
+
+/// Index of the variant that best describes `value`
+fn selected_variant(root: &Value, variants: &[Value], value: &Value) -> usize {
+    variants
+        .iter()
+        .position(|v| matches(root, v, value))
+        .unwrap_or(0)
+}
+
+fn variant_label(root: &Value, schema: &Value, index: usize) -> String {
+    let schema = resolve(root, schema);
+    schema
+        .get("title")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .or_else(|| schema.get("const").map(|c| c.to_string()))
+        .unwrap_or_else(|| format!("Option {}", index + 1))
+}
+
+/// Property names in display order
+fn ordered_properties(schema: &Value) -> Vec<(&String, &Value)> {
+    let Some(props) = schema.get("properties").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let mut entries: Vec<_> = props.iter().collect();
+    entries.sort_by_key(|(name, prop)| {
+        (
+            prop.get("propertyOrder")
+                .and_then(Value::as_i64)
+                .unwrap_or(i64::MAX),
+            required
+                .iter()
+                .position(|r| r == name)
+                .unwrap_or(usize::MAX),
+            (*name).clone(),
+        )
+    });
+    entries
+}
+
+fn collect_errors(
+    root: &Value,
+    schema: &Value,
+    value: &Value,
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    let schema = effective_schema(root, schema, value);
+    let name = if path.is_empty() {
+        "configuration"
+    } else {
+        path
+    };
+
+    if let Some(variants) = variants(&schema) {
+        let index = selected_variant(root, variants, value);
+        if !matches(root, &variants[index], value) {
+            errors.push(format!("{} does not match any allowed form", name));
+        }
+        collect_errors(root, &variants[index], value, path, errors);
+        return;
+    }
+
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            errors.push(format!("{} must be one of the listed values", name));
+        }
+    }
+
+    match value {
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or_default();
+            if let Some(min) = schema
+                .get("minimum")
+                .and_then(Value::as_f64)
+                .filter(|m| n < *m)
+            {
+                errors.push(format!("{} must be at least {}", name, min));
+            }
+            if let Some(max) = schema
+                .get("maximum")
+                .and_then(Value::as_f64)
+                .filter(|m| n > *m)
+            {
+                errors.push(format!("{} must be at most {}", name, max));
+            }
+        }
+        Value::Object(object) => {
+            for required in schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+            {
+                let missing = match object.get(required) {
+                    None | Some(Value::Null) => true,
+                    Some(Value::String(s)) => s.is_empty(),
+                    Some(_) => false,
+                };
+                if missing {
+                    errors.push(format!("{} is required", join_path(path, required)));
+                }
+            }
+            for (prop_name, prop) in ordered_properties(&schema) {
+                if let Some(v) = object.get(prop_name) {
+                    collect_errors(root, prop, v, &join_path(path, prop_name), errors);
+                }
+            }
+        }
+        Value::Array(items) => {
+            let len = items.len() as u64;
+            if let Some(min) = schema
+                .get("minItems")
+                .and_then(Value::as_u64)
+                .filter(|m| len < *m)
+            {
+                errors.push(format!("{} needs at least {} entries", name, min));
+            }
+            if let Some(max) = schema
+                .get("maxItems")
+                .and_then(Value::as_u64)
+                .filter(|m| len > *m)
+            {
+                errors.push(format!("{} allows at most {} entries", name, max));
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    collect_errors(root, item_schema, item, &format!("{}[{}]", name, i), errors);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn join_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+/// Render one schema node; returns true if the value changed
+fn show_node(
+    ui: &mut egui::Ui,
+    root: &Value,
+    schema: &Value,
+    value: &mut Value,
+    id: egui::Id,
+) -> bool {
+    let schema = effective_schema(root, schema, value);
+
+    if let Some(variants) = variants(&schema) {
+        let current = selected_variant(root, variants, value);
+        let mut selected = current;
+        egui::ComboBox::from_id_salt(id.with("variant"))
+            .selected_text(variant_label(root, &variants[current], current))
+            .show_ui(ui, |ui| {
+                for (i, variant) in variants.iter().enumerate() {
+                    ui.selectable_value(&mut selected, i, variant_label(root, variant, i));
+                }
+            });
+        let mut changed = false;
+        if selected != current {
+            *value = default_value(root, &variants[selected]);
+            changed = true;
+        }
+        let variant = resolve(root, &variants[selected]);
+        // Pure `const` variants are fully described by the selector
+        if variant.get("const").is_none() {
+            changed |= show_node(ui, root, variant, value, id.with(selected));
+        }
+        return changed;
+    }
+
+    match schema_type(&schema) {
+        Some("object") => show_object(ui, root, &schema, value, id),
+        Some("array") => show_array(ui, root, &schema, value, id),
+        Some("string") => {
+            if !value.is_string() {
+                *value = Value::String(String::new());
+            }
+            if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+                show_enum(ui, options, value, id)
+            } else if let Value::String(s) = value {
+                ui.text_edit_singleline(s).changed()
+            } else {
+                false
+            }
+        }
+        Some("integer") => {
+            let mut n = value.as_i64().unwrap_or_default();
+            let mut drag = egui::DragValue::new(&mut n);
+            if let (Some(min), Some(max)) = (
+                schema.get("minimum").and_then(Value::as_f64),
+                schema.get("maximum").and_then(Value::as_f64),
+            ) {
+                drag = drag.range(min.ceil() as i64..=max.floor() as i64);
+            }
+            let changed = ui.add(drag).changed();
+            if changed || !value.is_i64() {
+                *value = Value::from(n);
+            }
+            changed
+        }
+        Some("number") => {
+            let mut n = value.as_f64().unwrap_or_default();
+            let mut drag = egui::DragValue::new(&mut n).speed(0.1);
+            if let (Some(min), Some(max)) = (
+                schema.get("minimum").and_then(Value::as_f64),
+                schema.get("maximum").and_then(Value::as_f64),
+            ) {
+                drag = drag.range(min..=max);
+            }
+            let changed = ui.add(drag).changed();
+            if changed || !value.is_number() {
+                *value = Value::from(n);
+            }
+            changed
+        }
+        Some("boolean") => {
+            let mut b = value.as_bool().unwrap_or_default();
+            let changed = ui.checkbox(&mut b, "").changed();
+            if changed || !value.is_boolean() {
+                *value = Value::Bool(b);
+            }
+            changed
+        }
+        _ => {
+            if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+                show_enum(ui, options, value, id)
+            } else {
+                ui.weak(value.to_string());
+                false
+            }
+        }
+    }
+}
+
+fn show_enum(ui: &mut egui::Ui, options: &[Value], value: &mut Value, id: egui::Id) -> bool {
+    let display = |v: &Value| match v {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let mut changed = false;
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(display(value))
+        .show_ui(ui, |ui| {
+            for option in options {
+                if ui
+                    .selectable_label(option == value, display(option))
+                    .clicked()
+                {
+                    *value = option.clone();
+                    changed = true;
+                }
+            }
+        });
+    changed
+}
+
+fn show_object(
+    ui: &mut egui::Ui,
+    root: &Value,
+    schema: &Value,
+    value: &mut Value,
+    id: egui::Id,
+) -> bool {
+    if !value.is_object() {
+        *value = default_value(root, schema);
+    }
+    let required: Vec<String> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| {
+            r.iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut changed = false;
+    for (name, prop) in ordered_properties(schema) {
+        let prop_schema = resolve(root, prop);
+        let Value::Object(object) = value else {
+            break;
+        };
+        let entry = object
+            .entry(name.clone())
+            .or_insert_with(|| default_value(root, prop_schema));
+
+        let mut label = prop_schema
+            .get("title")
+            .and_then(Value::as_str)
+            .unwrap_or(name)
+            .to_string();
+        if required.contains(name) {
+            label.push_str(" *");
+        }
+        let description = prop_schema.get("description").and_then(Value::as_str);
+        let child_id = id.with(name);
+
+        let nested = matches!(schema_type(prop_schema), Some("object") | Some("array"))
+            && variants(prop_schema).is_none();
+        if nested {
+            let header = egui::CollapsingHeader::new(label)
+                .id_salt(child_id)
+                .default_open(true)
+                .show(ui, |ui| show_node(ui, root, prop_schema, entry, child_id));
+            if let Some(description) = description {
+                header.header_response.on_hover_text(description);
+            }
+            changed |= header.body_returned.unwrap_or(false);
+        } else {
+            ui.horizontal(|ui| {
+                let response = ui.label(label);
+                if let Some(description) = description {
+                    response.on_hover_text(description);
+                }
+                changed |= show_node(ui, root, prop_schema, entry, child_id);
+            });
+        }
+    }
+    changed
+}
+
+fn show_array(
+    ui: &mut egui::Ui,
+    root: &Value,
+    schema: &Value,
+    value: &mut Value,
+    id: egui::Id,
+) -> bool {
+    if !value.is_array() {
+        *value = default_value(root, schema);
+    }
+    let Value::Array(items) = value else {
+        return false;
+    };
+    let item_schema = schema.get("items").cloned().unwrap_or(Value::Bool(true));
+    let min = schema.get("minItems").and_then(Value::as_u64).unwrap_or(0) as usize;
+    let max = schema
+        .get("maxItems")
+        .and_then(Value::as_u64)
+        .map_or(usize::MAX, |m| m as usize);
+
+    let mut changed = false;
+    let mut remove = None;
+    let can_remove = items.len() > min;
+    for (i, item) in items.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.label(format!("{}.", i + 1));
+            changed |= show_node(ui, root, &item_schema, item, id.with(i));
+            if ui
+                .add_enabled(can_remove, egui::Button::new("🗑"))
+                .on_hover_text("Remove entry")
+                .clicked()
+            {
+                remove = Some(i);
+            }
+        });
+    }
+    if let Some(i) = remove {
+        items.remove(i);
+        changed = true;
+    }
+    if ui
+        .add_enabled(items.len() < max, egui::Button::new("➕ Add"))
+        .clicked()
+    {
+        items.push(default_value(root, &item_schema));
+        changed = true;
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn stabilization_schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["mode", "setpoint"],
+            "properties": {
+                "mode": { "type": "string", "enum": ["pid", "feedforward"], "default": "pid" },
+                "setpoint": { "type": "number", "minimum": 0.0, "maximum": 10.0, "default": 1.0 },
+                "label": { "type": "string" },
+                "sensors": {
+                    "type": "array",
+                    "items": { "$ref": "#/$defs/sensor" }
+                }
+            },
+            "if": { "properties": { "mode": { "const": "pid" } } },
+            "then": {
+                "properties": {
+                    "gains": {
+                        "type": "object",
+                        "properties": {
+                            "kp": { "type": "number", "default": 0.5 },
+                            "ki": { "type": "number" }
+                        }
+                    }
+                }
+            },
+            "$defs": {
+                "sensor": {
+                    "type": "object",
+                    "required": ["device"],
+                    "properties": {
+                        "device": { "type": "string" },
+                        "weight": { "type": "number", "default": 1.0 }
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn defaults_follow_conditionals() {
+        let form = SchemaForm::from_schema(stabilization_schema());
+        let value = form.value;
+        assert_eq!(value["mode"], json!("pid"));
+        assert_eq!(value["setpoint"], json!(1.0));
+        assert_eq!(value["sensors"], json!([]));
+        assert_eq!(value["gains"]["kp"], json!(0.5));
+    }
+
+    #[test]
+    fn config_round_trip_keeps_strings_raw() {
+        let mut form = SchemaForm::from_schema(stabilization_schema());
+        let mut config = HashMap::new();
+        config.insert("mode".to_string(), "feedforward".to_string());
+        config.insert("label".to_string(), "42".to_string());
+        config.insert(
+            "sensors".to_string(),
+            r#"[{"device":"pd1","weight":2.0}]"#.to_string(),
+        );
+        form.load_config(&config);
+
+        assert_eq!(form.value["label"], json!("42"));
+        assert_eq!(form.value["sensors"][0]["device"], json!("pd1"));
+
+        let out = form.to_config();
+        assert_eq!(out["mode"], "feedforward");
+        assert_eq!(out["label"], "42");
+        assert_eq!(out["setpoint"], "1.0");
+        assert_eq!(out["sensors"], r#"[{"device":"pd1","weight":2.0}]"#);
+    }
+
+    #[test]
+    fn errors_report_required_and_bounds() {
+        let mut form = SchemaForm::from_schema(stabilization_schema());
+        let mut config = HashMap::new();
+        config.insert("setpoint".to_string(), "12".to_string());
+        config.insert("sensors".to_string(), r#"[{"weight":1}]"#.to_string());
+        form.load_config(&config);
+
+        let errors = form.errors();
+        assert!(errors.iter().any(|e| e == "setpoint must be at most 10"));
+        assert!(errors.iter().any(|e| e == "sensors[0].device is required"));
+    }
+
+    #[test]
+    fn flat_parameters_become_schema() {
+        let params = vec![ModuleParameter {
+            param_id: "threshold".to_string(),
+            display_name: "Threshold".to_string(),
+            description: String::new(),
+            param_type: "float".to_string(),
+            default_value: "2.5".to_string(),
+            min_value: Some("0".to_string()),
+            max_value: None,
+            enum_values: vec![],
+            units: "mW".to_string(),
+            required: true,
+        }];
+        let form = SchemaForm::from_parameters(&params);
+        assert_eq!(form.value["threshold"], json!(2.5));
+        assert_eq!(form.to_config()["threshold"], "2.5");
+        assert!(form.errors().is_empty());
+    }
+}
@@ -11,11 +11,14 @@ pub mod device_selector;
 pub mod double_slider;
 pub mod gauge;
 pub mod histogram;
+pub mod json_schema_form;
 pub mod line_profile;
 pub mod metadata_editor;
 pub mod node_palette;
 pub mod offline_notice;
+pub mod parameter_confirm;
 pub mod parameter_editor;
+pub mod port_picker;
 pub mod pp_editor;
 pub mod property_inspector;
 pub mod roi_selector;
@@ -37,12 +40,15 @@ pub use device_selector::DeviceSelector;
 pub use double_slider::{double_slider, DoubleSlider};
 pub use gauge::*;
 pub use histogram::*;
+pub use json_schema_form::SchemaForm;
 pub use line_profile::*;
 pub use metadata_editor::MetadataEditor;
 #[allow(unused_imports)]
 pub use node_palette::{NodePalette, NodeType};
 pub use offline_notice::*;
+pub use parameter_confirm::{ParameterConfirmDialog, PendingProposal, ProposalDecision};
 pub use parameter_editor::*;
+pub use port_picker::PortPicker;
 pub use pp_editor::*;
 #[allow(unused_imports)]
 pub use property_inspector::PropertyInspector;
@@ -0,0 +1,209 @@
+//! Confirmation dialog for changes to dangerous parameters.
+//!
+//! The daemon answers `SetParameter` on a parameter flagged `dangerous`
+//! (laser power, high-voltage setpoints) with a proposal token instead of
+//! applying the change. Panels hand the proposal to [`ParameterConfirmDialog`],
+//! which shows what is about to change and returns the operator's decision.
+
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use eframe::egui;
+use protocol::daq::SetParameterResponse;
+
+/// A parameter change waiting for the operator's confirmation
+#[derive(Debug, Clone)]
+pub struct PendingProposal {
+    pub device_id: String,
+    pub parameter: String,
+    pub value: String,
+    pub current_value: Option<String>,
+    pub units: String,
+    pub description: String,
+    pub token: String,
+    expires_at: Instant,
+}
+
+impl PendingProposal {
+    /// Build a proposal from a `SetParameter` response that asks for confirmation
+    pub fn from_response(
+        device_id: &str,
+        parameter: &str,
+        value: &str,
+        response: &SetParameterResponse,
+    ) -> Option<Self> {
+        if !response.confirmation_required {
+            return None;
+        }
+
+        let now_ns = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        let remaining = Duration::from_nanos(response.proposal_expires_ns.saturating_sub(now_ns));
+
+        Some(Self {
+            device_id: device_id.to_string(),
+            parameter: parameter.to_string(),
+            value: value.to_string(),
+            current_value: None,
+            units: String::new(),
+            description: String::new(),
+            token: response.proposal_token.clone(),
+            expires_at: Instant::now() + remaining,
+        })
+    }
+
+    /// Fill in schema details shown in the dialog
+    pub fn with_details(
+        mut self,
+        description: &str,
+        units: &str,
+        current_value: Option<&str>,
+    ) -> Self {
+        self.description = description.to_string();
+        self.units = units.to_string();
+        self.current_value = current_value.map(str::to_string);
+        self
+    }
+
+    /// Time left before the daemon discards the proposal
+    pub fn remaining(&self) -> Duration {
+        self.expires_at.saturating_duration_since(Instant::now())
+    }
+}
+
+/// What the operator decided about a proposal
+pub enum ProposalDecision {
+    /// Apply the change (send `ConfirmParameterChange`)
+    Confirm(PendingProposal),
+    /// Discard the change (send `CancelParameterChange`)
+    Cancel(PendingProposal),
+    /// The proposal timed out before a decision was made
+    Expired(PendingProposal),
+}
+
+/// Modal-style window asking the operator to confirm a dangerous change
+#[derive(Default)]
+pub struct ParameterConfirmDialog {
+    pending: Option<PendingProposal>,
+}
+
+impl ParameterConfirmDialog {
+    /// Show the dialog for a new proposal (replaces any open one)
+    pub fn open(&mut self, proposal: PendingProposal) {
+        self.pending = Some(proposal);
+    }
+
+    /// Render the dialog; returns the decision once one is made
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<ProposalDecision> {
+        let proposal = self.pending.as_ref()?;
+
+        let remaining = proposal.remaining();
+        if remaining.is_zero() {
+            return self.pending.take().map(ProposalDecision::Expired);
+        }
+
+        let mut confirmed = false;
+        let mut cancelled = false;
+
+        egui::Window::new("⚠ Confirm parameter change")
+            .id(egui::Id::new("parameter_confirm_dialog").with(&proposal.token))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(format!("{}.{}", proposal.device_id, proposal.parameter))
+                        .strong(),
+                );
+                if !proposal.description.is_empty() {
+                    ui.label(&proposal.description);
+                }
+                ui.add_space(4.0);
+
+                egui::Grid::new("parameter_confirm_grid")
+                    .num_columns(2)
+                    .spacing([12.0, 4.0])
+                    .show(ui, |ui| {
+                        if let Some(current) = &proposal.current_value {
+                            ui.label("Current:");
+                            ui.label(format!("{} {}", current, proposal.units));
+                            ui.end_row();
+                        }
+                        ui.label("New:");
+                        ui.colored_label(
+                            ui.visuals().warn_fg_color,
+                            format!("{} {}", proposal.value, proposal.units),
+                        );
+                        ui.end_row();
+                    });
+
+                ui.add_space(4.0);
+                ui.weak(format!(
+                    "This setting is marked dangerous. The request expires in {} s.",
+                    remaining.as_secs() + 1
+                ));
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    let confirm = egui::Button::new(
+                        egui::RichText::new("Apply change").color(egui::Color32::WHITE),
+                    )
+                    .fill(ui.visuals().error_fg_color);
+                    if ui.add(confirm).clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+
+        // Keep the countdown ticking
+        ctx.request_repaint_after(Duration::from_millis(250));
+
+        if confirmed {
+            self.pending.take().map(ProposalDecision::Confirm)
+        } else if cancelled {
+            self.pending.take().map(ProposalDecision::Cancel)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(confirmation_required: bool, expires_in: Duration) -> SetParameterResponse {
+        let expires_ns = (SystemTime::now() + expires_in)
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as u64;
+        SetParameterResponse {
+            success: !confirmation_required,
+            error_message: String::new(),
+            actual_value: String::new(),
+            confirmation_required,
+            proposal_token: "token".to_string(),
+            proposal_expires_ns: expires_ns,
+            actual_typed: None,
+        }
+    }
+
+    #[test]
+    fn proposal_only_for_confirmation_responses() {
+        let applied = response(false, Duration::ZERO);
+        assert!(PendingProposal::from_response("laser", "power", "5", &applied).is_none());
+
+        let proposed = response(true, Duration::from_secs(30));
+        let proposal = PendingProposal::from_response("laser", "power", "5", &proposed)
+            .unwrap()
+            .with_details("Output power", "W", Some("1"));
+        assert_eq!(proposal.token, "token");
+        assert_eq!(proposal.units, "W");
+        assert!(proposal.remaining() > Duration::from_secs(25));
+        assert!(proposal.remaining() <= Duration::from_secs(30));
+    }
+}
@@ -6,8 +6,13 @@
 //! - StringEditor: TextEdit for string values
 //! - EnumEditor: ComboBox for enum values
 //! - JsonFallback: read-only display for complex types
+//!
+//! Edits are reported as typed values ([`TypedValue`]) that the daemon checks
+//! against the parameter's dtype and limits, so a new driver parameter only
+//! needs an entry in the driver's parameter table to become editable here.
 
 use eframe::egui;
+use protocol::daq::{typed_value::Kind, ParameterDescriptor, TypedValue};
 
 /// Cached parameter value with editing state
 #[derive(Clone)]
@@ -52,7 +57,85 @@ pub enum ParameterEditResult {
     /// No change
     None,
     /// Value changed, request server update
-    Changed(String),
+    Changed(TypedValue),
+}
+
+/// Effective dtype of a parameter (enum values take precedence)
+fn effective_dtype(desc: &ParameterDescriptor) -> &str {
+    if desc.enum_values.is_empty() {
+        &desc.dtype
+    } else {
+        "enum"
+    }
+}
+
+/// Build a typed value from edit text, checked against the descriptor's dtype,
+/// limits and enum values so bad input is caught before the round trip.
+///
+/// Text of string parameters may be JSON-quoted, as in `ParameterValue.value`.
+pub fn parse_typed_value(desc: &ParameterDescriptor, text: &str) -> Result<TypedValue, String> {
+    let text = text.trim();
+    let in_range = |number: f64| {
+        if desc.min_value.is_some_and(|min| number < min)
+            || desc.max_value.is_some_and(|max| number > max)
+        {
+            Err(format!(
+                "{} is outside [{}, {}]",
+                number,
+                desc.min_value.map_or("-inf".to_string(), |v| v.to_string()),
+                desc.max_value.map_or("inf".to_string(), |v| v.to_string())
+            ))
+        } else {
+            Ok(())
+        }
+    };
+    let kind = match effective_dtype(desc) {
+        "float" => {
+            let value = text
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| format!("'{}' is not a number", text))?;
+            in_range(value)?;
+            Kind::FloatValue(value)
+        }
+        "int" => {
+            let value = text
+                .parse::<i64>()
+                .map_err(|_| format!("'{}' is not an integer", text))?;
+            in_range(value as f64)?;
+            Kind::IntValue(value)
+        }
+        "bool" => Kind::BoolValue(
+            text.parse::<bool>()
+                .map_err(|_| format!("'{}' is not true or false", text))?,
+        ),
+        "string" | "enum" => {
+            let value = serde_json::from_str::<String>(text).unwrap_or_else(|_| text.to_string());
+            if !desc.enum_values.is_empty() && !desc.enum_values.contains(&value) {
+                return Err(format!("'{}' is not one of {:?}", value, desc.enum_values));
+            }
+            Kind::StringValue(value)
+        }
+        other => return Err(format!("Parameters of type '{}' are not editable", other)),
+    };
+    Ok(TypedValue { kind: Some(kind) })
+}
+
+/// Display text for a typed value (strings unquoted)
+pub fn typed_value_text(value: &TypedValue) -> String {
+    match &value.kind {
+        Some(Kind::FloatValue(v)) => v.to_string(),
+        Some(Kind::IntValue(v)) => v.to_string(),
+        Some(Kind::BoolValue(v)) => v.to_string(),
+        Some(Kind::StringValue(v)) => v.clone(),
+        None => String::new(),
+    }
+}
+
+/// Typed value wrapping `kind`
+fn typed(kind: Kind) -> TypedValue {
+    TypedValue { kind: Some(kind) }
 }
 
 /// Render the appropriate editor widget for a parameter
@@ -106,7 +189,7 @@ fn render_bool_editor(ui: &mut egui::Ui, param: &mut ParameterCache) -> Paramete
     });
 
     if value != old_value {
-        ParameterEditResult::Changed(value.to_string())
+        ParameterEditResult::Changed(typed(Kind::BoolValue(value)))
     } else {
         ParameterEditResult::None
     }
@@ -153,7 +236,7 @@ fn render_int_editor(ui: &mut egui::Ui, param: &mut ParameterCache) -> Parameter
         // Commit on focus lost or change (slider changes immediately)
         if (response.lost_focus() || response.changed()) && value != original {
             param.edit_buffer = value.to_string();
-            result = ParameterEditResult::Changed(value.to_string());
+            result = ParameterEditResult::Changed(typed(Kind::IntValue(value)));
         }
     });
 
@@ -201,7 +284,7 @@ fn render_float_editor(ui: &mut egui::Ui, param: &mut ParameterCache) -> Paramet
         if (response.lost_focus() || response.changed()) && (value - original).abs() > f64::EPSILON
         {
             param.edit_buffer = value.to_string();
-            result = ParameterEditResult::Changed(value.to_string());
+            result = ParameterEditResult::Changed(typed(Kind::FloatValue(value)));
         }
     });
 
@@ -224,7 +307,8 @@ fn render_string_editor(ui: &mut egui::Ui, param: &mut ParameterCache) -> Parame
 
         // Commit on Enter or focus lost
         if response.lost_focus() && param.edit_buffer != original {
-            result = ParameterEditResult::Changed(format!("\"{}\"", param.edit_buffer));
+            result =
+                ParameterEditResult::Changed(typed(Kind::StringValue(param.edit_buffer.clone())));
         }
     });
 
@@ -261,7 +345,7 @@ fn render_enum_editor(ui: &mut egui::Ui, param: &mut ParameterCache) -> Paramete
     });
 
     if selected != original {
-        result = ParameterEditResult::Changed(format!("\"{}\"", selected));
+        result = ParameterEditResult::Changed(typed(Kind::StringValue(selected)));
     }
 
     result
@@ -323,3 +407,46 @@ pub fn filter_parameters<'a>(params: &'a [ParameterCache], query: &str) -> Vec<&
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn descriptor(dtype: &str) -> ParameterDescriptor {
+        ParameterDescriptor {
+            name: "p".to_string(),
+            dtype: dtype.to_string(),
+            writable: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_typed_value() {
+        let mut float = descriptor("float");
+        float.min_value = Some(0.0);
+        float.max_value = Some(10.0);
+        assert_eq!(
+            parse_typed_value(&float, "2.5").unwrap().kind,
+            Some(Kind::FloatValue(2.5))
+        );
+        assert!(parse_typed_value(&float, "11").is_err());
+        assert!(parse_typed_value(&float, "abc").is_err());
+
+        assert_eq!(
+            parse_typed_value(&descriptor("int"), "7").unwrap().kind,
+            Some(Kind::IntValue(7))
+        );
+        assert!(parse_typed_value(&descriptor("int"), "7.5").is_err());
+
+        let mut mode = descriptor("string");
+        mode.enum_values = vec!["auto".to_string(), "manual".to_string()];
+        assert_eq!(
+            parse_typed_value(&mode, "\"manual\"").unwrap().kind,
+            Some(Kind::StringValue("manual".to_string()))
+        );
+        assert!(parse_typed_value(&mode, "turbo").is_err());
+
+        assert!(parse_typed_value(&descriptor("object"), "{}").is_err());
+    }
+}
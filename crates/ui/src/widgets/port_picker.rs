@@ -0,0 +1,230 @@
+//! Serial port picker for device configs.
+//!
+//! Lists the daemon host's serial ports (from `ListSerialPorts`) with their
+//! USB identity and yields the value for a device config's `port`: the stable
+//! `serial:`/`usb:` spec when the port has one, so the config keeps working
+//! after `/dev/ttyUSB*` renumbering, otherwise the device path.
+
+use eframe::egui;
+use protocol::daq::SerialPortInfo;
+
+/// Port list with a selection and the config value it produces
+#[derive(Default)]
+pub struct PortPicker {
+    ports: Vec<SerialPortInfo>,
+    /// Path of the selected port
+    selected: Option<String>,
+    /// Use the device path even if the port has a stable spec
+    use_path: bool,
+    loading: bool,
+    error: Option<String>,
+}
+
+impl PortPicker {
+    /// Mark a port list request as in flight
+    pub fn set_loading(&mut self) {
+        self.loading = true;
+        self.error = None;
+    }
+
+    /// Apply a `ListSerialPorts` result, keeping the selection if the port is still there
+    pub fn set_ports(&mut self, result: Result<Vec<SerialPortInfo>, String>) {
+        self.loading = false;
+        match result {
+            Ok(ports) => {
+                if !ports
+                    .iter()
+                    .any(|p| Some(&p.path) == self.selected.as_ref())
+                {
+                    self.selected = ports.first().map(|p| p.path.clone());
+                }
+                self.ports = ports;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    pub fn selected_port(&self) -> Option<&SerialPortInfo> {
+        let selected = self.selected.as_ref()?;
+        self.ports.iter().find(|p| &p.path == selected)
+    }
+
+    /// Value for the selected port's `port` config key
+    pub fn config_value(&self) -> Option<String> {
+        self.selected_port()
+            .map(|port| config_value(port, !self.use_path))
+    }
+
+    /// Render the picker; returns `true` if the user asked for a refresh
+    pub fn show(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut refresh = false;
+        ui.horizontal(|ui| {
+            refresh = ui
+                .add_enabled(!self.loading, egui::Button::new("🔄 Refresh"))
+                .clicked();
+            if self.loading {
+                ui.spinner();
+            } else {
+                ui.weak(format!("{} ports on the daemon host", self.ports.len()));
+            }
+        });
+        if let Some(err) = &self.error {
+            ui.colored_label(egui::Color32::RED, err);
+        }
+        if self.ports.is_empty() {
+            if !self.loading && self.error.is_none() {
+                ui.label("No serial ports found.");
+            }
+            return refresh;
+        }
+
+        let selected_text = self
+            .selected_port()
+            .map_or_else(|| "Select a port".to_string(), port_label);
+        egui::ComboBox::from_id_salt("serial_port_picker")
+            .selected_text(selected_text)
+            .width(ui.available_width().min(420.0))
+            .show_ui(ui, |ui| {
+                for port in &self.ports {
+                    let is_selected = self.selected.as_ref() == Some(&port.path);
+                    if ui.selectable_label(is_selected, port_label(port)).clicked() {
+                        self.selected = Some(port.path.clone());
+                    }
+                }
+            });
+
+        let Some(port) = self.selected_port().cloned() else {
+            return refresh;
+        };
+        ui.add_space(4.0);
+        egui::Grid::new("serial_port_details")
+            .num_columns(2)
+            .spacing([12.0, 2.0])
+            .show(ui, |ui| {
+                let mut row = |name: &str, value: &str| {
+                    if !value.is_empty() {
+                        ui.label(name);
+                        ui.monospace(value);
+                        ui.end_row();
+                    }
+                };
+                row("Path", &port.path);
+                row("Type", &port.port_type);
+                row("VID:PID", &usb_ids(&port).unwrap_or_default());
+                row("Serial", &port.serial_number);
+                row("Manufacturer", &port.manufacturer);
+                row("Product", &port.product);
+                row("Driver", &port.driver);
+                row("By-id", &port.by_id_path);
+            });
+
+        ui.add_space(4.0);
+        if port.stable_spec.is_empty() {
+            ui.weak("No USB identity; the config must use the device path.");
+        } else {
+            let mut stable = !self.use_path;
+            ui.checkbox(&mut stable, "Match by USB identity (survives renumbering)");
+            self.use_path = !stable;
+        }
+
+        let value = config_value(&port, !self.use_path);
+        ui.horizontal(|ui| {
+            ui.label("Config:");
+            ui.monospace(format!("port = \"{}\"", value));
+            if ui.small_button("📋 Copy").clicked() {
+                ui.ctx().copy_text(value.clone());
+            }
+        });
+        refresh
+    }
+}
+
+/// `port` config value: the stable spec if wanted and available, else the path
+pub fn config_value(port: &SerialPortInfo, stable: bool) -> String {
+    if stable && !port.stable_spec.is_empty() {
+        port.stable_spec.clone()
+    } else {
+        port.path.clone()
+    }
+}
+
+/// "0403:6015" for USB ports
+fn usb_ids(port: &SerialPortInfo) -> Option<String> {
+    Some(format!("{:04x}:{:04x}", port.vid?, port.pid?))
+}
+
+/// One-line description, e.g. "/dev/ttyUSB0 — FT230X Basic UART (0403:6015, DJ00XXXX)"
+fn port_label(port: &SerialPortInfo) -> String {
+    let ids: Vec<String> = usb_ids(port)
+        .into_iter()
+        .chain(Some(port.serial_number.clone()).filter(|s| !s.is_empty()))
+        .collect();
+    let mut label = port.path.clone();
+    let name = if port.product.is_empty() {
+        &port.manufacturer
+    } else {
+        &port.product
+    };
+    if !name.is_empty() {
+        label.push_str(" — ");
+        label.push_str(name);
+    }
+    if !ids.is_empty() {
+        label = format!("{} ({})", label, ids.join(", "));
+    }
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ftdi() -> SerialPortInfo {
+        SerialPortInfo {
+            path: "/dev/ttyUSB0".to_string(),
+            port_type: "usb".to_string(),
+            vid: Some(0x0403),
+            pid: Some(0x6015),
+            serial_number: "DJ00XXXX".to_string(),
+            product: "FT230X Basic UART".to_string(),
+            stable_spec: "serial:DJ00XXXX".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_port_label() {
+        assert_eq!(
+            port_label(&ftdi()),
+            "/dev/ttyUSB0 — FT230X Basic UART (0403:6015, DJ00XXXX)"
+        );
+        let uart = SerialPortInfo {
+            path: "/dev/ttyS0".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(port_label(&uart), "/dev/ttyS0");
+    }
+
+    #[test]
+    fn test_config_value_and_selection() {
+        let mut picker = PortPicker::default();
+        assert_eq!(picker.config_value(), None);
+
+        let uart = SerialPortInfo {
+            path: "/dev/ttyS0".to_string(),
+            ..Default::default()
+        };
+        picker.set_ports(Ok(vec![ftdi(), uart.clone()]));
+        assert_eq!(picker.config_value().as_deref(), Some("serial:DJ00XXXX"));
+        picker.use_path = true;
+        assert_eq!(picker.config_value().as_deref(), Some("/dev/ttyUSB0"));
+
+        // Selection follows the port across refreshes and falls back if it's gone
+        picker.selected = Some("/dev/ttyS0".to_string());
+        picker.set_ports(Ok(vec![uart, ftdi()]));
+        assert_eq!(picker.config_value().as_deref(), Some("/dev/ttyS0"));
+        picker.set_ports(Ok(vec![ftdi()]));
+        assert_eq!(picker.selected_port().unwrap().path, "/dev/ttyUSB0");
+    }
+}
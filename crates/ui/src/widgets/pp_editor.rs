@@ -1,4 +1,8 @@
 //! Post-processing features editor for PVCAM cameras (bd-cdh5.4).
+//!
+//! The driver enumerates the features each camera supports and exposes their
+//! parameters as `processing.<Feature>.<Param>`. New values are applied when
+//! the camera is next staged or armed.
 
 use crate::widgets::ParameterCache;
 use eframe::egui;
@@ -14,17 +18,24 @@ impl PPEditor {
         Self::default()
     }
 
-    pub fn ui(&mut self, ui: &mut egui::Ui, device_id: &str, params: &[ParameterCache]) {
+    /// Show the features, drawing each parameter with `render`
+    ///
+    /// Returns `true` if "Reset All to Defaults" was clicked.
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        device_id: &str,
+        params: &[ParameterCache],
+        mut render: impl FnMut(&mut egui::Ui, &ParameterCache),
+    ) -> bool {
+        let mut reset = false;
         ui.horizontal(|ui| {
             ui.heading("Post-Processing Features");
             ui.add_space(8.0);
-            if ui
+            reset = ui
                 .button("🔄 Reset All to Defaults")
                 .on_hover_text("Resets all PP features via ExecuteDeviceCommand")
-                .clicked()
-            {
-                // This will be handled by PendingAction in DevicesPanel
-            }
+                .clicked();
         });
 
         ui.horizontal(|ui| {
@@ -34,20 +45,21 @@ impl PPEditor {
 
         ui.separator();
 
-        // PVCAM PP features are typically named "processing.PP_FEATURE_NAME.param"
-        // We can group them by feature name
+        // PVCAM PP features are named "processing.PP_FEATURE_NAME.param";
+        // host-side processing ("processing.host_flip") has no feature part
         let pp_params: Vec<_> = params
             .iter()
             .filter(|p| {
                 p.descriptor.name.starts_with("processing.")
-                    && !p.descriptor.name.ends_with(".metadata_enabled")
+                    && p.descriptor.name.split('.').count() >= 3
             })
             .collect();
 
         if pp_params.is_empty() {
-            ui.label("No post-processing parameters found.");
-            return;
+            ui.label("This camera has no post-processing features.");
+            return reset;
         }
+        ui.weak("Changes are applied when the camera is next staged or armed.");
 
         egui::ScrollArea::vertical()
             .id_salt(egui::Id::new("pp_scroll").with(device_id))
@@ -57,12 +69,8 @@ impl PPEditor {
                 let mut groups: std::collections::BTreeMap<String, Vec<&ParameterCache>> =
                     std::collections::BTreeMap::new();
                 for p in pp_params {
-                    let parts: Vec<&str> = p.descriptor.name.split('.').collect();
-                    if parts.len() >= 2 {
-                        groups.entry(parts[1].to_string()).or_default().push(p);
-                    } else {
-                        groups.entry("other".to_string()).or_default().push(p);
-                    }
+                    let feature = p.descriptor.name.split('.').nth(1).unwrap_or("other");
+                    groups.entry(feature.to_string()).or_default().push(p);
                 }
 
                 for (feature_name, feature_params) in groups {
@@ -78,13 +86,11 @@ impl PPEditor {
                         .default_open(false)
                         .show(ui, |ui| {
                             for param in feature_params {
-                                // We'll delegate to render_single_parameter in DevicesPanel for now
-                                // to avoid code duplication, but in a real implementation we might
-                                // want custom layout here.
-                                ui.label(&param.descriptor.name);
+                                render(ui, param);
                             }
                         });
                 }
             });
+        reset
     }
 }
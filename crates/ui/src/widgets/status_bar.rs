@@ -1,7 +1,8 @@
 //! Status bar widget for the DAQ GUI.
 //!
 //! Displays connection state, breadcrumb navigation, transient status messages,
-//! and version information in a fixed-height bottom panel.
+//! other connected users, the daemon's open streams, and version information in
+//! a fixed-height bottom panel.
 //!
 //! Some methods are defined for future use and may not currently be called.
 #![allow(dead_code)]
@@ -11,18 +12,27 @@ use eframe::egui;
 use crate::icons;
 use crate::layout::{self, colors};
 use client::reconnect::ConnectionState;
+use protocol::daq::{ListStreamStatsResponse, SessionInfo, SessionRole};
 
 /// Status bar widget displaying connection state and contextual information.
 ///
 /// The status bar has three sections:
 /// - **Left**: Breadcrumb/context path
 /// - **Center**: Transient status message (with automatic timeout)
-/// - **Right**: Connection indicator and version number
+/// - **Right**: Connection indicator, open streams, other users and version number
 pub struct StatusBar {
     /// Current breadcrumb/context path (e.g., "Devices > Motor Stage")
     breadcrumb: Option<String>,
     /// Transient status message
     status_message: Option<StatusMessage>,
+    /// Other sessions connected to the same daemon
+    other_sessions: Vec<SessionInfo>,
+    /// Whether this GUI's session may revoke others
+    is_admin: bool,
+    /// Session the user asked to revoke (taken by the app)
+    revoke_request: Option<String>,
+    /// Latest statistics of the daemon's server-streaming RPCs
+    stream_stats: Option<ListStreamStatsResponse>,
 }
 
 /// A transient status message with automatic timeout.
@@ -82,9 +92,36 @@ impl StatusBar {
         Self {
             breadcrumb: None,
             status_message: None,
+            other_sessions: Vec::new(),
+            is_admin: false,
+            revoke_request: None,
+            stream_stats: None,
         }
     }
 
+    /// Set the other sessions connected to the daemon.
+    pub fn set_presence(&mut self, other_sessions: Vec<SessionInfo>, is_admin: bool) {
+        self.other_sessions = other_sessions;
+        self.is_admin = is_admin;
+    }
+
+    /// Set the daemon's stream statistics (None hides them).
+    pub fn set_stream_stats(&mut self, stats: Option<ListStreamStatsResponse>) {
+        self.stream_stats = stats;
+    }
+
+    /// Take the session ID the user asked to revoke, if any.
+    pub fn take_revoke_request(&mut self) -> Option<String> {
+        self.revoke_request.take()
+    }
+
+    /// Other sessions that can drive hardware (operators and admins).
+    fn controllers(&self) -> impl Iterator<Item = &SessionInfo> {
+        self.other_sessions
+            .iter()
+            .filter(|s| s.role() != SessionRole::Observer)
+    }
+
     /// Set the breadcrumb/context path.
     pub fn set_breadcrumb(&mut self, breadcrumb: impl Into<String>) {
         self.breadcrumb = Some(breadcrumb.into());
@@ -204,7 +241,7 @@ impl StatusBar {
 
     /// Render the right section (connection indicator and version).
     fn render_right_section(
-        &self,
+        &mut self,
         ui: &mut egui::Ui,
         connection_state: &ConnectionState,
         error_count: Option<u32>,
@@ -219,6 +256,9 @@ impl StatusBar {
 
         ui.add_space(8.0);
 
+        self.render_presence(ui);
+        self.render_stream_stats(ui);
+
         // Error count (if any)
         if let Some(count) = error_count {
             if count > 0 {
@@ -277,6 +317,158 @@ impl StatusBar {
         };
         response.on_hover_text(tooltip_text);
     }
+
+    /// Render who else is connected, highlighting sessions holding locks.
+    fn render_presence(&mut self, ui: &mut egui::Ui) {
+        if self.other_sessions.is_empty() {
+            return;
+        }
+
+        let holding_locks: Vec<&SessionInfo> = self
+            .controllers()
+            .filter(|s| !s.held_locks.is_empty())
+            .collect();
+        let (text, color) = if let Some(first) = holding_locks.first() {
+            let text = if holding_locks.len() == 1 {
+                format!(
+                    "👥 {}@{} controls {}",
+                    first.client_name,
+                    first.host,
+                    first.held_locks.join(", ")
+                )
+            } else {
+                format!("👥 {} users control devices", holding_locks.len())
+            };
+            (text, colors::WARNING)
+        } else {
+            let text = format!("👥 {} other", self.other_sessions.len());
+            (text, colors::MUTED)
+        };
+
+        let mut revoke = None;
+        ui.menu_button(egui::RichText::new(text).small().color(color), |ui| {
+            ui.label(egui::RichText::new("Connected sessions").strong());
+            ui.separator();
+            egui::Grid::new("presence_grid")
+                .num_columns(if self.is_admin { 4 } else { 3 })
+                .spacing([12.0, 4.0])
+                .show(ui, |ui| {
+                    for session in &self.other_sessions {
+                        ui.label(format!("{}@{}", session.client_name, session.host));
+                        ui.label(role_label(session.role()));
+                        if session.held_locks.is_empty() {
+                            ui.label(egui::RichText::new("no locks").color(colors::MUTED));
+                        } else {
+                            ui.colored_label(colors::WARNING, session.held_locks.join(", "));
+                        }
+                        if self.is_admin && ui.small_button("Revoke").clicked() {
+                            revoke = Some(session.session_id.clone());
+                            ui.close();
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+        if revoke.is_some() {
+            self.revoke_request = revoke;
+        }
+
+        ui.add_space(8.0);
+    }
+
+    /// Render the number of open streams and their total rate, with a
+    /// per-stream breakdown (busiest first) on click.
+    fn render_stream_stats(&self, ui: &mut egui::Ui) {
+        let Some(stats) = &self.stream_stats else {
+            return;
+        };
+        if stats.streams.is_empty() {
+            return;
+        }
+
+        let dropped: u64 = stats.streams.iter().map(|s| s.dropped).sum();
+        let color = if dropped > 0 {
+            colors::WARNING
+        } else {
+            colors::MUTED
+        };
+        let text = format!(
+            "⇅ {} {} · {}",
+            stats.streams.len(),
+            if stats.streams.len() == 1 {
+                "stream"
+            } else {
+                "streams"
+            },
+            format_rate(stats.total_bytes_per_sec)
+        );
+
+        let response = ui.menu_button(egui::RichText::new(text).small().color(color), |ui| {
+            ui.label(egui::RichText::new("Open streams").strong());
+            ui.separator();
+            egui::Grid::new("stream_stats_grid")
+                .num_columns(5)
+                .spacing([12.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label(egui::RichText::new("Stream").color(colors::MUTED));
+                    ui.label(egui::RichText::new("Client").color(colors::MUTED));
+                    ui.label(egui::RichText::new("Msg/s").color(colors::MUTED));
+                    ui.label(egui::RichText::new("Rate").color(colors::MUTED));
+                    ui.label(egui::RichText::new("Dropped").color(colors::MUTED));
+                    ui.end_row();
+
+                    for stream in &stats.streams {
+                        if stream.detail.is_empty() {
+                            ui.label(&stream.method);
+                        } else {
+                            ui.label(format!("{} ({})", stream.method, stream.detail));
+                        }
+                        let client = if stream.peer.is_empty() {
+                            stream.user.clone()
+                        } else {
+                            format!("{}@{}", stream.user, stream.peer)
+                        };
+                        ui.label(client).on_hover_text(format!(
+                            "{}\nopen for {:.0} s",
+                            stream.user_agent, stream.uptime_s
+                        ));
+                        ui.label(format!("{:.1}", stream.messages_per_sec));
+                        ui.label(format_rate(stream.bytes_per_sec));
+                        if stream.dropped > 0 {
+                            ui.colored_label(colors::WARNING, stream.dropped.to_string());
+                        } else {
+                            ui.label("0");
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+        response.response.on_hover_text(format!(
+            "{:.0} msg/s to clients, {} dropped",
+            stats.total_messages_per_sec, dropped
+        ));
+
+        ui.add_space(8.0);
+    }
+}
+
+/// Byte rate with a unit, e.g. "1.2 MB/s".
+fn format_rate(bytes_per_sec: f64) -> String {
+    if bytes_per_sec >= 1e6 {
+        format!("{:.1} MB/s", bytes_per_sec / 1e6)
+    } else if bytes_per_sec >= 1e3 {
+        format!("{:.1} kB/s", bytes_per_sec / 1e3)
+    } else {
+        format!("{:.0} B/s", bytes_per_sec)
+    }
+}
+
+fn role_label(role: SessionRole) -> &'static str {
+    match role {
+        SessionRole::Observer => "observer",
+        SessionRole::Operator => "operator",
+        SessionRole::Admin => "admin",
+    }
 }
 
 impl Default for StatusBar {
@@ -310,6 +502,38 @@ mod tests {
         assert!(bar.breadcrumb.is_none());
     }
 
+    #[test]
+    fn test_controllers_exclude_observers() {
+        let session = |name: &str, role: SessionRole| SessionInfo {
+            session_id: name.to_string(),
+            client_name: name.to_string(),
+            host: "lab-pc".to_string(),
+            role: role as i32,
+            connected_at_ns: 0,
+            last_seen_ns: 0,
+            held_locks: Vec::new(),
+        };
+        let mut bar = StatusBar::new();
+        bar.set_presence(
+            vec![
+                session("alice", SessionRole::Operator),
+                session("viewer", SessionRole::Observer),
+                session("admin", SessionRole::Admin),
+            ],
+            false,
+        );
+        let names: Vec<&str> = bar.controllers().map(|s| s.client_name.as_str()).collect();
+        assert_eq!(names, vec!["alice", "admin"]);
+        assert!(bar.take_revoke_request().is_none());
+    }
+
+    #[test]
+    fn test_format_rate() {
+        assert_eq!(format_rate(512.0), "512 B/s");
+        assert_eq!(format_rate(12_300.0), "12.3 kB/s");
+        assert_eq!(format_rate(4_560_000.0), "4.6 MB/s");
+    }
+
     #[test]
     fn test_status_message_expiry() {
         let mut bar = StatusBar::new();
@@ -250,6 +250,7 @@ mod daemon_lifecycle_tests {
             let response = client
                 .list_devices(tonic::Request::new(ListDevicesRequest {
                     capability_filter: None,
+                    ..Default::default()
                 }))
                 .await;
 
@@ -0,0 +1,42 @@
+#!/bin/bash
+# Verify the minimal headless daemon profile stays light
+#
+# Usage:
+#   bash scripts/check-minimal.sh                              # host build
+#   bash scripts/check-minimal.sh aarch64-unknown-linux-gnu    # cross target
+#
+# Fails if the dependency tree of `bin --features minimal` pulls in a GUI,
+# HDF5, Arrow/Parquet, GPU, Rerun, Python or WASM runtime crate, then builds
+# the daemon for the given target. See docs/guides/minimal-build.md.
+
+set -euo pipefail
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(dirname "$SCRIPT_DIR")"
+cd "$PROJECT_ROOT"
+
+TARGET="${1:-}"
+TARGET_ARGS=()
+if [[ -n "$TARGET" ]]; then
+    TARGET_ARGS=(--target "$TARGET")
+fi
+
+FEATURE_ARGS=(-p bin --no-default-features --features minimal)
+
+# Crates that must never appear in the minimal build
+FORBIDDEN='^(egui|eframe|egui_plot|egui_extras|egui_dock|slint|arrow|parquet|hdf5|hdf5-metno|hdf5-metno-sys|wgpu|rerun|pyo3|wasmtime|zarrs|pvcam-sys|comedi-sys) '
+
+echo "🔍 Checking minimal dependency tree${TARGET:+ for $TARGET}..."
+TREE="$(cargo tree "${FEATURE_ARGS[@]}" "${TARGET_ARGS[@]}" -e normal --prefix none --format '{p}' | sort -u)"
+HEAVY="$(echo "$TREE" | grep -E "$FORBIDDEN" || true)"
+if [[ -n "$HEAVY" ]]; then
+    echo "❌ Heavy dependencies in the minimal profile:"
+    echo "$HEAVY" | sed 's/^/   /'
+    echo "   Inspect with: cargo tree ${FEATURE_ARGS[*]} -i <crate>"
+    exit 1
+fi
+echo "✅ No heavy dependencies ($(echo "$TREE" | wc -l) crates)"
+
+echo "🔧 Building minimal daemon..."
+cargo build --release "${FEATURE_ARGS[@]}" "${TARGET_ARGS[@]}"
+echo "✅ Minimal daemon built"